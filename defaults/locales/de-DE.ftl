@@ -0,0 +1,320 @@
+# Die deutschen Texte der Oberfläche
+
+## Title bar
+
+title-connect-ssh-host = Mit SSH-Host verbinden
+title-open-folder = Ordner öffnen
+title-open-recent-workspace = Zuletzt verwendeten Arbeitsbereich öffnen
+title-command-palette = Befehlspalette
+title-open-settings = Einstellungen öffnen
+title-update-in-progress = Aktualisierung läuft ({ $version })
+title-restart-to-update = Neu starten zum Aktualisieren ({ $version })
+title-no-update-available = Keine Aktualisierung verfügbar
+
+## Editor tabs and palette
+
+tab-new = Neuer Tab
+palette-no-results = Keine passenden Ergebnisse
+palette-not-in-macro = (nicht im Makro, da mit Argumenten)
+palette-dropped-folder-open = { $folder } als Arbeitsbereich öffnen
+palette-dropped-folder-new-tab = { $folder } in einem neuen Arbeitsbereich-Tab öffnen
+
+## Editor gutter
+
+gutter-add-breakpoint = Haltepunkt hinzufügen
+gutter-remove-breakpoint = Haltepunkt entfernen
+gutter-breakpoint-condition = Bedingung bearbeiten…
+gutter-breakpoint-hit-count = Trefferanzahl bearbeiten…
+gutter-breakpoint-log-message = Protokollnachricht bearbeiten…
+gutter-jump-to-line = Zur Zeile springen
+gutter-show-change = Entfernte Zeilen anzeigen
+gutter-revert-change = Änderung zurücksetzen
+
+## Hovers
+
+hover-pin = Anheften
+hover-pinned = Angeheftete Info
+
+## Vorschau der Änderungen
+
+edit-preview-title = Vorschau der Änderungen an { $count } Dateien
+edit-preview-apply = Anwenden
+edit-preview-discard = Verwerfen
+
+## Die Ansicht des Speicherverbrauchs
+
+memory-usage-title = Speicherverbrauch
+memory-usage-document = Dokument
+memory-usage-buffer = Puffer
+memory-usage-syntax = Syntaxbaum
+memory-usage-lsp = Sprachserver
+memory-usage-total = Puffer: { $buffers }, Syntaxbäume: { $syntax } und Sprachserver-Caches: { $lsp } in { $docs ->
+        [one] { $docs } Dokument
+       *[other] { $docs } Dokumenten
+    }. Terminals: { $terminals }
+memory-usage-refresh = Aktualisieren
+memory-usage-close = Schließen
+
+## Codeaktionen
+
+code-action-quick-fix = Schnelle Korrektur
+code-action-refactor = Refactoring
+code-action-source = Quellaktion
+code-action-other = Weitere Aktionen
+
+## Panels
+
+panel-open-editors = Geöffnete Editoren
+panel-file-explorer = Explorer
+panel-installed-plugins = Installiert
+panel-available-plugins = Verfügbar
+panel-processes = Prozesse
+panel-stack-frames = Stack-Frames
+panel-variables = Variablen
+panel-watch = Überwachen
+panel-watch-add = Ausdruck hinzufügen…
+panel-exception-breakpoints = Ausnahme-Haltepunkte
+panel-data-breakpoints = Daten-Haltepunkte
+debug-console-all = Alle
+debug-console-console = Debugger
+debug-console-stdout = Ausgabe
+debug-console-stderr = Fehler
+debug-console-telemetry = Telemetrie
+debug-break-on-value-change = Anhalten, wenn sich der Wert ändert
+debug-set-value = Wert setzen…
+debug-restart-frame = Frame neu starten
+debug-view-memory = Speicher anzeigen
+debug-memory-empty = Speicher anzeigen bei einer Variable wählen, um ihren Speicher zu sehen
+debug-thread-paused = Angehalten
+debug-thread-running = Läuft
+debug-task-title = Aufgabe: { $task }
+panel-errors = Fehler
+panel-warnings = Warnungen
+panel-changes = Änderungen
+panel-notifications = Benachrichtigungen
+panel-terminal = Terminal
+panel-source-control = Quellcodeverwaltung
+panel-plugins = Plugins
+panel-search = Suche
+panel-problems = Probleme
+panel-debug = Ausführen und Debuggen
+panel-log = Protokoll
+panel-test-explorer = Test-Explorer
+panel-http-response = HTTP-Antwort
+panel-debug-console = Debugkonsole
+panel-debug-memory = Speicher
+panel-collaboration = Zusammenarbeit
+panel-todo = TODO
+
+## Test explorer
+
+test-explorer-empty = Keine Tests in den geöffneten Dateien
+
+## TODO
+
+todo-scanning = Der Arbeitsbereich wird nach den Kommentaren durchsucht…
+todo-empty = Keine Kommentare mit den Tags der Einstellungen im Arbeitsbereich
+
+## HTTP response
+
+http-response-empty = Sende eine Anfrage einer .http-Datei, um ihre Antwort zu sehen
+http-response-sending = Wird gesendet…
+
+## Collaboration
+
+collab-not-shared = Der Arbeitsbereich wird nicht geteilt
+collab-connecting = Wird verbunden…
+collab-hosting = Der Arbeitsbereich wird geteilt
+collab-joined = Dem Arbeitsbereich von { $host } beigetreten
+collab-start = Arbeitsbereich teilen
+collab-join = Einer Sitzung beitreten
+collab-stop = Teilen beenden
+collab-leave = Sitzung verlassen
+collab-copy-invite = Einladung kopieren
+collab-participants = Teilnehmer
+collab-files = Geteilte Dateien
+collab-no-files = Es sind keine Dateien geöffnet
+collab-you = (du)
+collab-host = (Gastgeber)
+collab-can-edit = Bearbeiten
+collab-can-use-terminals = Terminals
+collab-follow = Folgen
+collab-unfollow = Nicht mehr folgen
+collab-go-to-ping = Anzeigen
+
+## SSH
+
+ssh-trust-host = Vertrauen
+ssh-continue = Fortfahren
+ssh-cancel = Abbrechen
+
+## Source control
+
+source-control-commit-message = Commit-Nachricht
+source-control-commit = Commit
+source-control-discard-changes = Änderungen verwerfen
+
+## Plugins
+
+plugin-reload = Plugin neu laden
+plugin-enable = Aktivieren
+plugin-disable = Deaktivieren
+plugin-enable-for-workspace = Für Arbeitsbereich aktivieren
+plugin-disable-for-workspace = Für Arbeitsbereich deaktivieren
+plugin-uninstall = Deinstallieren
+plugin-disabled = Deaktiviert
+plugin-installed = Installiert
+plugin-installing = Wird installiert
+plugin-install = Installieren
+
+## Status bar
+
+status-mode-normal = Normal
+status-mode-insert = Einfügen
+status-mode-visual = Visuell
+status-mode-terminal = Terminal
+status-no-document = Kein Dokument
+status-cursor-position = Z. { $line }, Sp. { $column }, Zeichen { $character }
+status-selected = { $count } ausgewählt
+status-selections = { $count } Auswahlen
+status-plain-text = Nur Text
+status-indent-tabs = Tabulatoren
+status-indent-spaces = Leerzeichen: { $count }
+status-recording-macro = Makro wird aufgezeichnet
+status-collab-hosting = { $count ->
+    [0] Wird geteilt
+   *[other] Mit { $count } geteilt
+}
+status-collab-joined = { $host } beigetreten
+
+## Settings
+
+settings-core = Allgemeine Einstellungen
+settings-editor = Editor-Einstellungen
+settings-ui = Oberflächen-Einstellungen
+settings-terminal = Terminal-Einstellungen
+settings-plugins = Plugin-Einstellungen
+settings-open-file = Einstellungsdatei öffnen
+settings-search = Einstellungen durchsuchen
+settings-user = Benutzer
+settings-workspace = Arbeitsbereich
+settings-all-languages = Alle Sprachen
+settings-source = Festgelegt in { $source }
+settings-source-language = Festgelegt in { $source } für { $language }
+settings-source-default = Standard
+settings-source-profile = Profil
+
+## Notifications
+
+notification-install-theme-failed = Das Design konnte nicht installiert werden
+notification-theme-renamed = Das Design wurde unter einem anderen Namen installiert
+notification-theme-renamed-message = Es gibt bereits ein Design namens { $name }, daher heißt dieses { $new-name }
+notification-create-profile-failed = Das Profil konnte nicht erstellt werden
+notification-import-profile-failed = Das Profil konnte nicht importiert werden
+notification-export-profile-failed = Das Profil konnte nicht exportiert werden
+notification-profile-exported = Das Profil wurde exportiert
+notification-update-failed = Die Aktualisierung ist fehlgeschlagen
+notification-install-plugin-failed = { $plugin } konnte nicht installiert werden
+notification-remove-plugin-failed = { $plugin } konnte nicht entfernt werden
+notification-server-stopped = { $plugin }: Der Sprachserver wurde beendet
+notification-server-exited = { $server } wurde beendet
+notification-restart-server = Server neu starten
+notification-open-settings = Einstellungen öffnen
+notification-link-failed = { $link } konnte nicht geöffnet werden
+notification-git-permalink-failed = Die Datei auf dem Remote wurde nicht gefunden
+notification-export-html-failed = Das HTML der Datei konnte nicht geschrieben werden
+notification-build-runnable-failed = { $runnable } konnte nicht gebaut werden
+notification-debug-task-failed = Die Aufgabe { $task } von { $config } ist fehlgeschlagen
+notification-coverage-failed = Der Abdeckungsbericht konnte nicht geladen werden
+notification-save-macro-failed = Das Makro konnte nicht gespeichert werden
+notification-collab-failed = Die Sitzung der Zusammenarbeit ist fehlgeschlagen
+notification-collab-invalid-invite = Das ist keine Einladung einer Sitzung
+notification-collab-rejected = Der Sitzung konnte nicht beigetreten werden
+notification-collab-ended = Der Gastgeber hat die Sitzung beendet
+notification-collab-invite-copied = Die Einladung der Sitzung wurde kopiert
+notification-collab-joined = { $name } ist der Sitzung beigetreten
+notification-collab-left = { $name } hat die Sitzung verlassen
+notification-collab-pinged = { $name } hat auf eine Stelle hingewiesen
+notification-ssh-host-key = { $host } ist kein bekannter Host
+notification-ssh-confirm = { $host } bittet um Bestätigung
+notification-ssh-info = { $host } wartet
+notification-workspace-edit-applied = { $count ->
+    [one] { $count } Datei bearbeitet
+   *[other] { $count } Dateien bearbeitet
+}
+notification-workspace-edit-undo = Rückgängig
+notification-workspace-edit-failed = Die Änderung konnte nicht angewendet werden
+notification-workspace-edit-undo-failed = Die Änderung konnte nicht rückgängig gemacht werden
+notification-file-conflict = { $file } wurde auf der Festplatte geändert
+notification-file-conflict-message = Die Datei hat ungespeicherte Änderungen, die beim Neuladen verloren gehen.
+notification-file-conflict-reload = Neu laden
+notification-file-conflict-overwrite = Überschreiben
+notification-file-conflict-compare = Vergleichen
+notification-save-failed = { $file } konnte nicht gespeichert werden
+notification-save-as-admin = Als Administrator wiederholen
+notification-save-as-admin-failed = Speichern als Administrator fehlgeschlagen
+notification-file-trashed = { $file } in den Papierkorb verschoben
+notification-file-trashed-undo = Rückgängig
+notification-delete-failed = { $file } konnte nicht gelöscht werden
+notification-restore-failed = { $file } konnte nicht wiederhergestellt werden
+notification-delete-permanently = { $file } endgültig löschen?
+notification-delete-permanently-message = Dies kann nicht rückgängig gemacht werden.
+notification-delete-permanently-confirm = Endgültig löschen
+notification-terminal-paste = { $lines } Zeilen in das Terminal einfügen?
+notification-terminal-paste-message = Jede Zeile kann als Befehl ausgeführt werden.
+notification-terminal-paste-confirm = Einfügen
+notification-link-outside-workspace = { $file } verweist auf einen Ordner außerhalb des Arbeitsbereichs
+notification-data-breakpoint-unavailable = Kann nicht anhalten, wenn sich { $name } ändert
+notification-set-variable-failed = { $name } konnte nicht gesetzt werden
+notification-restart-frame-failed = Der Frame konnte nicht neu gestartet werden
+notification-jump-failed = Zeile { $line } kann nicht angesprungen werden
+notification-write-memory-failed = Der Speicher konnte nicht geschrieben werden
+notification-embedded-code-lost = Der eingebettete Code wurde in seiner Datei nicht mehr gefunden
+notification-invalid-memory-bytes = Die Bytes müssen Paare von Hexziffern sein, z. B. 2a 0f ff
+notification-no-goto-targets = Der Debugger kann in dieser Zeile nirgendwohin springen
+notification-subsystem-crashed = { $source } ist abgestürzt
+notification-subsystem-restarted = { $message }
+    Es wurde neu gestartet.
+notification-subsystem-stopped = { $message }
+    Es ist zu oft hintereinander abgestürzt und wurde angehalten.
+notification-report-crash = Melden
+
+## Command descriptions
+
+command-palette = Gehe zu Datei
+command-palette-command = Befehlspalette
+command-open-settings = Einstellungen öffnen
+command-open-keyboard-shortcuts = Tastenkombinationen öffnen
+command-change-color-theme = Farbdesign ändern
+command-change-icon-theme = Symboldesign ändern
+command-open-folder = Ordner öffnen
+command-new-window = Neues Fenster
+command-close-window = Fenster schließen
+command-toggle-do-not-disturb = Nicht stören umschalten
+command-clear-notifications = Benachrichtigungen löschen
+command-palette-paste-from-history = Aus dem Verlauf einfügen...
+command-make-writable = Aktiven Editor beschreibbar machen
+command-file-explorer-delete = Ausgewählte Datei in den Papierkorb verschieben
+command-file-explorer-delete-permanently = Ausgewählte Datei endgültig löschen
+command-undo-file-delete = Löschen der Datei rückgängig machen
+command-toggle-macro-recording = Aufzeichnung eines Makros starten oder beenden
+command-play-macro = Letztes Makro abspielen
+command-play-macro-over-selections = Letztes Makro über jeder Auswahl abspielen
+command-save-macro = Letztes Makro als Befehl speichern
+command-macro-from-command-history = Makro aus dem Befehlsverlauf erstellen...
+command-palette-command-history = Befehlsverlauf...
+command-repeat-last-command = Letzten Befehl wiederholen
+
+## Setting descriptions
+
+setting-core-language = Die Sprache der Oberfläche. „system“ verwendet die Sprache des Systems
+setting-core-modal = Modale Bearbeitung aktivieren (wie Vim)
+setting-core-color-theme = Das Farbdesign von Lapce
+setting-core-icon-theme = Das Symboldesign von Lapce
+
+## Barrierefreiheit
+
+accessibility-editor = Editor
+accessibility-cursor-position = Zeile { $line }, Spalte { $column }
+accessibility-palette-input = Suche
+accessibility-palette-results = Ergebnisse
@@ -0,0 +1,288 @@
+# The English strings of the UI, which every other translation falls back to.
+# See docs/translations.md for how to add a translation.
+
+## Title bar
+
+title-connect-ssh-host = Connect to SSH Host
+title-open-folder = Open Folder
+title-open-recent-workspace = Open Recent Workspace
+title-command-palette = Command Palette
+title-open-settings = Open Settings
+title-update-in-progress = Update in progress ({ $version })
+title-restart-to-update = Restart to update ({ $version })
+title-no-update-available = No update available
+
+## Editor tabs and palette
+
+tab-new = New Tab
+palette-no-results = No matching results
+palette-not-in-macro = (left out of the macro, as it has arguments)
+palette-dropped-folder-open = Open { $folder } as the workspace
+palette-dropped-folder-new-tab = Open { $folder } in a new workspace tab
+
+## Editor gutter
+
+gutter-add-breakpoint = Add Breakpoint
+gutter-remove-breakpoint = Remove Breakpoint
+gutter-breakpoint-condition = Edit Condition…
+gutter-breakpoint-hit-count = Edit Hit Count…
+gutter-breakpoint-log-message = Edit Log Message…
+gutter-jump-to-line = Jump to Line
+gutter-show-change = Show Removed Lines
+gutter-revert-change = Revert Change
+
+## Hovers
+
+hover-pin = Pin
+hover-pinned = Pinned Hover
+
+## Edit preview
+
+edit-preview-title = Preview the edits of { $count } files
+edit-preview-apply = Apply
+edit-preview-discard = Discard
+
+## The memory usage view
+
+memory-usage-title = Memory Usage
+memory-usage-document = Document
+memory-usage-buffer = Buffer
+memory-usage-syntax = Syntax Tree
+memory-usage-lsp = Language Servers
+memory-usage-total = Buffers: { $buffers }, syntax trees: { $syntax } and language server caches: { $lsp } in { $docs ->
+        [one] { $docs } document
+       *[other] { $docs } documents
+    }. Terminals: { $terminals }
+memory-usage-refresh = Refresh
+memory-usage-close = Close
+
+## Code actions
+
+code-action-quick-fix = Quick Fix
+code-action-refactor = Refactor
+code-action-source = Source Action
+code-action-other = More Actions
+
+## Panels
+
+panel-open-editors = Open Editors
+panel-file-explorer = File Explorer
+panel-installed-plugins = Installed
+panel-available-plugins = Available
+panel-processes = Processes
+panel-stack-frames = Stack Frames
+panel-variables = Variables
+panel-watch = Watch
+panel-watch-add = Add Expression…
+panel-exception-breakpoints = Exception Breakpoints
+panel-data-breakpoints = Data Breakpoints
+debug-console-all = All
+debug-console-console = Debugger
+debug-console-stdout = Output
+debug-console-stderr = Errors
+debug-console-telemetry = Telemetry
+debug-break-on-value-change = Break When Value Changes
+debug-set-value = Set Value…
+debug-restart-frame = Restart Frame
+debug-view-memory = View Memory
+debug-memory-empty = Choose View Memory on a variable to see its memory
+debug-thread-paused = Paused
+debug-thread-running = Running
+debug-task-title = Task: { $task }
+panel-errors = Errors
+panel-warnings = Warnings
+panel-changes = Changes
+panel-notifications = Notifications
+panel-terminal = Terminal
+panel-source-control = Source Control
+panel-plugins = Plugins
+panel-search = Search
+panel-problems = Problems
+panel-debug = Run and Debug
+panel-log = Log
+panel-test-explorer = Test Explorer
+panel-http-response = HTTP Response
+panel-debug-console = Debug Console
+panel-debug-memory = Memory
+panel-collaboration = Collaboration
+panel-todo = TODO
+
+## Test explorer
+
+test-explorer-empty = No tests in the open files
+
+## TODO
+
+todo-scanning = Searching the workspace for the comments…
+todo-empty = No comments with the tags of the settings in the workspace
+
+## HTTP response
+
+http-response-empty = Send a request of a .http file to see its response
+http-response-sending = Sending…
+
+## Collaboration
+
+collab-not-shared = The workspace isn't shared
+collab-connecting = Connecting…
+collab-hosting = Sharing the workspace
+collab-joined = Joined the workspace of { $host }
+collab-start = Share the Workspace
+collab-join = Join a Session
+collab-stop = Stop Sharing
+collab-leave = Leave the Session
+collab-copy-invite = Copy Invite
+collab-participants = Participants
+collab-files = Shared Files
+collab-no-files = No files are open
+collab-you = (you)
+collab-host = (host)
+collab-can-edit = Edit
+collab-can-use-terminals = Terminals
+collab-follow = Follow
+collab-unfollow = Unfollow
+collab-go-to-ping = Go There
+
+## SSH
+
+ssh-trust-host = Trust
+ssh-continue = Continue
+ssh-cancel = Cancel
+
+## Source control
+
+source-control-commit-message = Commit Message
+source-control-commit = Commit
+source-control-discard-changes = Discard Changes
+
+## Plugins
+
+plugin-reload = Reload Plugin
+plugin-enable = Enable
+plugin-disable = Disable
+plugin-enable-for-workspace = Enable For Workspace
+plugin-disable-for-workspace = Disable For Workspace
+plugin-uninstall = Uninstall
+plugin-disabled = Disabled
+plugin-installed = Installed
+plugin-installing = Installing
+plugin-install = Install
+
+## Status bar
+
+status-mode-normal = Normal
+status-mode-insert = Insert
+status-mode-visual = Visual
+status-mode-terminal = Terminal
+status-no-document = No document
+status-cursor-position = Ln { $line }, Col { $column }, Char { $character }
+status-selected = { $count } selected
+status-selections = { $count } selections
+status-plain-text = Plain Text
+status-indent-tabs = Tabs
+status-indent-spaces = Spaces: { $count }
+status-recording-macro = Recording Macro
+status-collab-hosting = { $count ->
+    [0] Sharing
+   *[other] Sharing with { $count }
+}
+status-collab-joined = Joined { $host }
+
+## Settings
+
+settings-core = Core Settings
+settings-editor = Editor Settings
+settings-ui = UI Settings
+settings-terminal = Terminal Settings
+settings-plugins = Plugin Settings
+settings-open-file = Open Settings File
+settings-search = Search Settings
+settings-user = User
+settings-workspace = Workspace
+settings-all-languages = All Languages
+settings-source = Set in { $source }
+settings-source-language = Set in { $source } for { $language }
+settings-source-default = Default
+settings-source-profile = Profile
+
+## Notifications
+
+notification-install-theme-failed = Failed to install theme
+notification-theme-renamed = Installed the theme under another name
+notification-theme-renamed-message = There is a theme named { $name } already, so this one is named { $new-name }
+notification-create-profile-failed = Failed to create profile
+notification-import-profile-failed = Failed to import profile
+notification-export-profile-failed = Failed to export profile
+notification-profile-exported = Exported the profile
+notification-update-failed = Failed to update
+notification-install-plugin-failed = Failed to install { $plugin }
+notification-remove-plugin-failed = Failed to remove { $plugin }
+notification-server-stopped = { $plugin }: language server stopped
+notification-server-exited = { $server } exited
+notification-restart-server = Restart Server
+notification-open-settings = Open Settings
+notification-link-failed = Failed to open { $link }
+notification-git-permalink-failed = Failed to get the file on the remote
+notification-export-html-failed = Failed to write the HTML of the file
+notification-build-runnable-failed = Failed to build { $runnable }
+notification-debug-task-failed = The task { $task } of { $config } failed
+notification-coverage-failed = Failed to load the coverage report
+notification-save-macro-failed = Failed to save the macro
+notification-collab-failed = The collaboration session failed
+notification-collab-invalid-invite = That isn't the invite of a session
+notification-collab-rejected = Couldn't join the session
+notification-collab-ended = The host ended the session
+notification-collab-invite-copied = Copied the invite of the session
+notification-collab-joined = { $name } joined the session
+notification-collab-left = { $name } left the session
+notification-collab-pinged = { $name } pinged a range
+notification-ssh-host-key = { $host } isn't a known host
+notification-ssh-confirm = { $host } asks to confirm
+notification-ssh-info = { $host } is waiting
+notification-workspace-edit-applied = { $count ->
+    [one] Edited { $count } file
+   *[other] Edited { $count } files
+}
+notification-workspace-edit-undo = Undo
+notification-workspace-edit-failed = Couldn't apply the edit
+notification-workspace-edit-undo-failed = Couldn't undo the edit
+notification-file-conflict = { $file } has changed on disk
+notification-file-conflict-message = The file has unsaved changes, which would be lost by reloading it.
+notification-file-conflict-reload = Reload
+notification-file-conflict-overwrite = Overwrite
+notification-file-conflict-compare = Compare
+notification-save-failed = Couldn't save { $file }
+notification-save-as-admin = Retry as Admin
+notification-save-as-admin-failed = Couldn't save as administrator
+notification-file-trashed = Moved { $file } to the trash
+notification-file-trashed-undo = Undo
+notification-delete-failed = Couldn't delete { $file }
+notification-restore-failed = Couldn't restore { $file }
+notification-delete-permanently = Delete { $file } permanently?
+notification-delete-permanently-message = This can't be undone.
+notification-delete-permanently-confirm = Delete Permanently
+notification-terminal-paste = Paste { $lines } lines into the terminal?
+notification-terminal-paste-message = Each line can run as a command.
+notification-terminal-paste-confirm = Paste
+notification-link-outside-workspace = { $file } links to a folder outside of the workspace
+notification-data-breakpoint-unavailable = Can't break when { $name } changes
+notification-set-variable-failed = Couldn't set { $name }
+notification-restart-frame-failed = Couldn't restart the frame
+notification-jump-failed = Can't jump to line { $line }
+notification-write-memory-failed = Couldn't write the memory
+notification-embedded-code-lost = Couldn't find the embedded code in its file anymore
+notification-invalid-memory-bytes = The bytes have to be pairs of hex digits, like 2a 0f ff
+notification-no-goto-targets = The debugger has no place to jump to at the line
+notification-subsystem-crashed = The { $source } crashed
+notification-subsystem-restarted = { $message }
+    It was started again.
+notification-subsystem-stopped = { $message }
+    It crashed too many times in a row, and was left stopped.
+notification-report-crash = Report
+
+## Accessibility, which is read out by screen readers
+
+accessibility-editor = Editor
+accessibility-cursor-position = Line { $line }, Column { $column }
+accessibility-palette-input = Search
+accessibility-palette-results = Results
@@ -0,0 +1,554 @@
+//! The accessibility tree of a window, which is what screen readers see of it.
+//!
+//! floem doesn't build one from the views, so it's put together from the state of
+//! the active window tab instead: the editor, the palette, the panels which are
+//! shown and the notifications. The menus are native ones, which are accessible
+//! already. The tree is published with [AccessKit](https://accesskit.dev), which
+//! can only talk to AT-SPI on Linux for now, as the adapters of the other
+//! platforms have to hook into the native window.
+
+use std::{num::NonZeroU128, sync::Arc};
+
+use accesskit::{
+    Action, ActionRequest, Live, NodeBuilder, NodeClassSet, NodeId, Role, Tree,
+    TreeUpdate,
+};
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{
+        create_effect, RwSignal, Scope, SignalGet, SignalGetUntracked, SignalSet,
+        SignalWith, SignalWithUntracked,
+    },
+};
+use fluent_bundle::FluentValue;
+use lapce_core::buffer::rope_text::RopeText;
+use strum::IntoEnumIterator;
+
+use crate::{
+    doc::DocContent,
+    palette::PaletteStatus,
+    panel::kind::PanelKind,
+    window_tab::{Focus, WindowTabData},
+};
+
+/// How many items of a list are put in the tree, so that long lists don't make
+/// every update slow
+const MAX_ITEMS: usize = 500;
+
+const WINDOW_ID: u128 = 1;
+const EDITOR_ID: u128 = 2;
+const PALETTE_ID: u128 = 3;
+const PALETTE_INPUT_ID: u128 = 4;
+const PALETTE_RESULTS_ID: u128 = 5;
+const PANEL_ID: u128 = 1 << 16;
+const ROW_ID: u128 = 1 << 32;
+const PALETTE_ITEM_ID: u128 = 1 << 64;
+const ALERT_ID: u128 = 1 << 96;
+
+fn node_id(id: u128) -> NodeId {
+    // the ids above all start at 1
+    NodeId(NonZeroU128::new(id).unwrap())
+}
+
+fn panel_id(kind: PanelKind) -> u128 {
+    PANEL_ID + kind as u128
+}
+
+fn row_id(kind: PanelKind, row: usize) -> u128 {
+    ROW_ID * (kind as u128 + 1) + row as u128
+}
+
+/// What an action request of a screen reader is aimed at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    Editor,
+    PaletteItem(usize),
+    Panel(PanelKind),
+    Row(PanelKind, usize),
+}
+
+impl Target {
+    fn from_node_id(id: NodeId) -> Option<Self> {
+        let id = id.0.get();
+        let kind = |i: u128| PanelKind::iter().nth(i as usize);
+        if id == EDITOR_ID {
+            Some(Target::Editor)
+        } else if id >= ALERT_ID {
+            None
+        } else if id >= PALETTE_ITEM_ID {
+            Some(Target::PaletteItem((id - PALETTE_ITEM_ID) as usize))
+        } else if id >= ROW_ID {
+            let row = (id % ROW_ID) as usize;
+            Some(Target::Row(kind(id / ROW_ID - 1)?, row))
+        } else if id >= PANEL_ID {
+            Some(Target::Panel(kind(id - PANEL_ID)?))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibleEditor {
+    /// The file name of the document
+    pub name: String,
+    /// The text of the line the cursor is on
+    pub line: String,
+    pub position: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessiblePalette {
+    pub name: String,
+    pub input_name: String,
+    pub input: String,
+    pub results_name: String,
+    pub items: Vec<String>,
+    pub index: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessiblePanel {
+    pub kind: PanelKind,
+    pub name: String,
+    /// The rows of the panels which are lists, e.g. the file explorer
+    pub rows: Vec<String>,
+    pub selected: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessibleAlert {
+    pub id: u64,
+    pub title: String,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AccessibleFocus {
+    #[default]
+    Window,
+    Editor,
+    Palette,
+    Panel(PanelKind),
+}
+
+/// The parts of a window tab which are exposed to screen readers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessibleWindow {
+    pub title: String,
+    pub editor: Option<AccessibleEditor>,
+    pub palette: Option<AccessiblePalette>,
+    pub panels: Vec<AccessiblePanel>,
+    pub alerts: Vec<AccessibleAlert>,
+    pub focus: AccessibleFocus,
+}
+
+impl AccessibleWindow {
+    /// Collect the state of the window tab. The signals are tracked, so that this
+    /// can be run in an effect.
+    pub fn new(window_tab_data: &WindowTabData) -> Self {
+        let config = window_tab_data.common.config.get();
+
+        let title = window_tab_data
+            .workspace
+            .display()
+            .map(|name| format!("{name} - Lapce"))
+            .unwrap_or_else(|| "Lapce".to_string());
+
+        let editor = window_tab_data
+            .main_split
+            .active_editor
+            .get()
+            .map(|editor| {
+                let editor = editor.get();
+                let doc = editor.doc.get();
+                let cursor = editor.cursor.get();
+                let buffer = doc.buffer();
+                let (line, column) = buffer.offset_to_line_col(cursor.offset());
+                let name = match &doc.content {
+                    DocContent::File(path) => path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    DocContent::Local => config.tr("accessibility-editor"),
+                };
+                AccessibleEditor {
+                    name,
+                    line: buffer.line_content(line).trim_end().to_string(),
+                    position: config.tr_args(
+                        "accessibility-cursor-position",
+                        [
+                            ("line", FluentValue::from(line + 1)),
+                            ("column", FluentValue::from(column + 1)),
+                        ],
+                    ),
+                }
+            });
+
+        let palette = &window_tab_data.palette;
+        let palette = (palette.status.get() != PaletteStatus::Inactive).then(|| {
+            AccessiblePalette {
+                name: config.tr("title-command-palette"),
+                input_name: config.tr("accessibility-palette-input"),
+                input: palette.input.with(|input| input.input.clone()),
+                results_name: config.tr("accessibility-palette-results"),
+                items: palette.filtered_items.with(|items| {
+                    items
+                        .iter()
+                        .take(MAX_ITEMS)
+                        .map(|item| item.filter_text.clone())
+                        .collect()
+                }),
+                index: palette.index.get(),
+            }
+        });
+
+        let panels = window_tab_data
+            .panel
+            .visible_panels(true)
+            .into_iter()
+            .map(|kind| {
+                let (rows, selected) = if kind == PanelKind::FileExplorer {
+                    let file_explorer = &window_tab_data.file_explorer;
+                    let paths = file_explorer.visible_paths(true);
+                    let selected = file_explorer.selected.with(|selected| {
+                        selected
+                            .as_ref()
+                            .and_then(|selected| {
+                                paths.iter().position(|p| p == selected)
+                            })
+                            .filter(|i| *i < MAX_ITEMS)
+                    });
+                    let rows = paths
+                        .iter()
+                        .take(MAX_ITEMS)
+                        .map(|path| {
+                            path.file_name()
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_default()
+                        })
+                        .collect();
+                    (rows, selected)
+                } else {
+                    (Vec::new(), None)
+                };
+                AccessiblePanel {
+                    kind,
+                    name: config.tr(kind.message_id()),
+                    rows,
+                    selected,
+                }
+            })
+            .collect();
+
+        let alerts = window_tab_data.notification.toasts.with(|toasts| {
+            toasts
+                .iter()
+                .map(|notification| AccessibleAlert {
+                    id: notification.id.to_raw(),
+                    title: notification.title.clone(),
+                    message: notification.message.clone(),
+                })
+                .collect()
+        });
+
+        let focus = match window_tab_data.common.focus.get() {
+            Focus::Palette => AccessibleFocus::Palette,
+            Focus::Panel(kind) => AccessibleFocus::Panel(kind),
+            Focus::Workbench
+            | Focus::CodeAction
+            | Focus::Rename
+            | Focus::ColorPicker
+            | Focus::EditPreview => AccessibleFocus::Editor,
+        };
+
+        Self {
+            title,
+            editor,
+            palette,
+            panels,
+            alerts,
+            focus,
+        }
+    }
+
+    /// The whole tree, which replaces the one the screen reader has.
+    pub fn tree_update(&self) -> TreeUpdate {
+        let mut classes = NodeClassSet::new();
+        let mut nodes = Vec::new();
+        let mut children = Vec::new();
+
+        if let Some(editor) = &self.editor {
+            let mut builder = NodeBuilder::new(Role::TextField);
+            builder.set_name(editor.name.clone());
+            builder.set_value(editor.line.clone());
+            builder.set_description(editor.position.clone());
+            builder.add_action(Action::Focus);
+            nodes.push((node_id(EDITOR_ID), builder.build(&mut classes)));
+            children.push(node_id(EDITOR_ID));
+        }
+
+        for panel in &self.panels {
+            let id = node_id(panel_id(panel.kind));
+            let mut builder = NodeBuilder::new(Role::Pane);
+            builder.set_name(panel.name.clone());
+            builder.add_action(Action::Focus);
+            if !panel.rows.is_empty() {
+                let rows: Vec<NodeId> = (0..panel.rows.len())
+                    .map(|row| node_id(row_id(panel.kind, row)))
+                    .collect();
+                for (i, row) in panel.rows.iter().enumerate() {
+                    let mut builder = NodeBuilder::new(Role::TreeItem);
+                    builder.set_name(row.clone());
+                    builder.set_selected(panel.selected == Some(i));
+                    builder.add_action(Action::Focus);
+                    builder.add_action(Action::Default);
+                    nodes.push((rows[i], builder.build(&mut classes)));
+                }
+                builder.set_children(rows);
+            }
+            nodes.push((id, builder.build(&mut classes)));
+            children.push(id);
+        }
+
+        if let Some(palette) = &self.palette {
+            let items: Vec<NodeId> = (0..palette.items.len())
+                .map(|i| node_id(PALETTE_ITEM_ID + i as u128))
+                .collect();
+            for (i, item) in palette.items.iter().enumerate() {
+                let mut builder = NodeBuilder::new(Role::ListBoxOption);
+                builder.set_name(item.clone());
+                builder.set_selected(i == palette.index);
+                builder.add_action(Action::Default);
+                nodes.push((items[i], builder.build(&mut classes)));
+            }
+
+            let mut builder = NodeBuilder::new(Role::TextField);
+            builder.set_name(palette.input_name.clone());
+            builder.set_value(palette.input.clone());
+            if let Some(item) = items.get(palette.index) {
+                builder.set_active_descendant(*item);
+            }
+            let input = builder.build(&mut classes);
+
+            let mut builder = NodeBuilder::new(Role::ListBox);
+            builder.set_name(palette.results_name.clone());
+            builder.set_children(items);
+            let results = builder.build(&mut classes);
+
+            let mut builder = NodeBuilder::new(Role::Dialog);
+            builder.set_name(palette.name.clone());
+            builder.set_children(vec![
+                node_id(PALETTE_INPUT_ID),
+                node_id(PALETTE_RESULTS_ID),
+            ]);
+            nodes.push((node_id(PALETTE_INPUT_ID), input));
+            nodes.push((node_id(PALETTE_RESULTS_ID), results));
+            nodes.push((node_id(PALETTE_ID), builder.build(&mut classes)));
+            children.push(node_id(PALETTE_ID));
+        }
+
+        for alert in &self.alerts {
+            let id = node_id(ALERT_ID + alert.id as u128);
+            let mut builder = NodeBuilder::new(Role::Alert);
+            builder.set_name(alert.title.clone());
+            builder.set_description(alert.message.clone());
+            builder.set_live(Live::Polite);
+            nodes.push((id, builder.build(&mut classes)));
+            children.push(id);
+        }
+
+        let focus = match self.focus {
+            AccessibleFocus::Window => WINDOW_ID,
+            AccessibleFocus::Editor if self.editor.is_some() => EDITOR_ID,
+            AccessibleFocus::Editor => WINDOW_ID,
+            AccessibleFocus::Palette if self.palette.is_some() => PALETTE_INPUT_ID,
+            AccessibleFocus::Palette => WINDOW_ID,
+            AccessibleFocus::Panel(kind) => {
+                match self.panels.iter().find(|panel| panel.kind == kind) {
+                    Some(panel) => match panel.selected {
+                        Some(row) => row_id(kind, row),
+                        None => panel_id(kind),
+                    },
+                    None => WINDOW_ID,
+                }
+            }
+        };
+
+        let mut builder = NodeBuilder::new(Role::Window);
+        builder.set_name(self.title.clone());
+        builder.set_children(children);
+        nodes.push((node_id(WINDOW_ID), builder.build(&mut classes)));
+
+        TreeUpdate {
+            nodes,
+            tree: Some(Tree::new(node_id(WINDOW_ID))),
+            focus: Some(node_id(focus)),
+        }
+    }
+}
+
+/// Sends the action requests, which come in on the thread of the adapter, over
+/// to the UI thread.
+#[cfg(target_os = "linux")]
+struct ActionForwarder(crossbeam_channel::Sender<ActionRequest>);
+
+#[cfg(target_os = "linux")]
+impl accesskit::ActionHandler for ActionForwarder {
+    fn do_action(&self, request: ActionRequest) {
+        let _ = self.0.send(request);
+    }
+}
+
+/// Keeps the tree of the screen reader in sync with the active window tab.
+#[derive(Clone)]
+pub struct AccessibilityData {
+    #[cfg(target_os = "linux")]
+    adapter: Option<std::rc::Rc<accesskit_unix::Adapter>>,
+}
+
+impl AccessibilityData {
+    pub fn new(
+        cx: Scope,
+        window_tabs: RwSignal<im::Vector<(RwSignal<usize>, Arc<WindowTabData>)>>,
+        active: RwSignal<usize>,
+    ) -> Self {
+        let active_window_tab = move || {
+            let active = active.get();
+            window_tabs.with(|tabs| {
+                tabs.get(active.min(tabs.len().saturating_sub(1)))
+                    .map(|(_, tab)| tab.clone())
+            })
+        };
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let request = create_signal_from_channel(cx, rx);
+        create_effect(cx, move |_| {
+            if let Some(request) = request.get() {
+                let active = active.get_untracked();
+                let window_tab = window_tabs.with_untracked(|tabs| {
+                    tabs.get(active).map(|(_, tab)| tab.clone())
+                });
+                if let Some(window_tab) = window_tab {
+                    do_action(&window_tab, &request);
+                }
+            }
+        });
+
+        #[cfg(target_os = "linux")]
+        {
+            let adapter = accesskit_unix::Adapter::new(
+                "Lapce".to_string(),
+                "floem".to_string(),
+                lapce_core::meta::VERSION.to_string(),
+                || TreeUpdate {
+                    nodes: vec![(
+                        node_id(WINDOW_ID),
+                        NodeBuilder::new(Role::Window)
+                            .build(&mut NodeClassSet::new()),
+                    )],
+                    tree: Some(Tree::new(node_id(WINDOW_ID))),
+                    focus: None,
+                },
+                Box::new(ActionForwarder(tx)),
+            )
+            .map(std::rc::Rc::new);
+
+            if let Some(adapter) = adapter.clone() {
+                create_effect(cx, move |last: Option<AccessibleWindow>| {
+                    let window = active_window_tab()
+                        .map(|tab| AccessibleWindow::new(&tab))
+                        .unwrap_or_default();
+                    if last.as_ref() != Some(&window) {
+                        adapter.update(window.tree_update());
+                    }
+                    window
+                });
+            }
+
+            Self { adapter }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (tx, active_window_tab);
+            Self {}
+        }
+    }
+}
+
+fn do_action(window_tab: &WindowTabData, request: &ActionRequest) {
+    let Some(target) = Target::from_node_id(request.target) else {
+        return;
+    };
+    match (request.action, target) {
+        (Action::Focus, Target::Editor) => {
+            window_tab.common.focus.set(Focus::Workbench);
+        }
+        (Action::Focus, Target::Panel(kind)) => {
+            window_tab.show_panel(kind);
+        }
+        (Action::Focus | Action::Default, Target::Row(kind, row)) => {
+            window_tab.show_panel(kind);
+            if kind == PanelKind::FileExplorer {
+                let file_explorer = &window_tab.file_explorer;
+                let Some(path) =
+                    file_explorer.visible_paths(false).get(row).cloned()
+                else {
+                    return;
+                };
+                file_explorer.selected.set(Some(path.clone()));
+                if request.action == Action::Default {
+                    let node = file_explorer
+                        .all_files
+                        .with_untracked(|all_files| all_files.get(&path).cloned());
+                    if let Some(node) = node {
+                        node.click(&window_tab.common.proxy);
+                    }
+                }
+            }
+        }
+        (Action::Default, Target::PaletteItem(index)) => {
+            window_tab.palette.clicked_index.set(Some(index));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_update() {
+        let window = AccessibleWindow {
+            title: "Lapce".to_string(),
+            editor: None,
+            palette: None,
+            panels: vec![AccessiblePanel {
+                kind: PanelKind::FileExplorer,
+                name: "File Explorer".to_string(),
+                rows: vec!["src".to_string(), "Cargo.toml".to_string()],
+                selected: Some(1),
+            }],
+            alerts: Vec::new(),
+            focus: AccessibleFocus::Panel(PanelKind::FileExplorer),
+        };
+        let update = window.tree_update();
+        let row = node_id(row_id(PanelKind::FileExplorer, 1));
+        assert_eq!(update.focus, Some(row));
+        assert_eq!(update.nodes.len(), 4);
+        assert_eq!(
+            Target::from_node_id(row),
+            Some(Target::Row(PanelKind::FileExplorer, 1))
+        );
+        assert_eq!(
+            Target::from_node_id(node_id(panel_id(PanelKind::Search))),
+            Some(Target::Panel(PanelKind::Search))
+        );
+        assert_eq!(
+            Target::from_node_id(node_id(PALETTE_ITEM_ID + 3)),
+            Some(Target::PaletteItem(3))
+        );
+    }
+}
@@ -3,6 +3,7 @@
 use std::{
     io::{BufReader, Read, Write},
     ops::Range,
+    path::{Path, PathBuf},
     process::Stdio,
     sync::Arc,
 };
@@ -29,34 +30,46 @@
     },
     view::View,
     views::{
-        container, container_box, empty, label, list, scroll, stack, svg, tab,
-        virtual_list, Decorators, VirtualListDirection, VirtualListItemSize,
+        container, container_box, empty, label, list, rich_text, scroll, stack, svg,
+        tab, virtual_list, Decorators, VirtualListDirection, VirtualListItemSize,
         VirtualListVector,
     },
     window::WindowConfig,
     ViewContext,
 };
-use lapce_core::{directory::Directory, meta, mode::Mode};
+use fluent_bundle::FluentValue;
+use lapce_core::{directory::Directory, meta};
 use lapce_rpc::{
     core::{CoreMessage, CoreNotification},
     file::PathObject,
+    proxy::ProxyResponse,
     RpcMessage,
 };
-use lsp_types::{CompletionItemKind, DiagnosticSeverity};
+use lsp_types::{CompletionItem, CompletionItemKind};
 use notify::Watcher;
 use serde::{Deserialize, Serialize};
-use tracing::{error, metadata::LevelFilter, trace};
-use tracing_subscriber::{filter::FilterFn, reload::Handle};
+use tracing::{error, trace, warn};
+use tracing_subscriber::{
+    filter::{FilterFn, Targets},
+    reload::Handle,
+};
 
 use crate::{
     code_action::CodeActionStatus,
+    color_picker::{format_color, ColorChannel, ColorFormat, ColorPickerData},
     command::{InternalCommand, WindowCommand},
+    completion::CompletionStatus,
     config::{
         color::LapceColor, icon::LapceIcons, watcher::ConfigWatcher, LapceConfig,
     },
     db::LapceDb,
     debug::RunDebugMode,
-    doc::DocContent,
+    deep_link::{self, DeepLink},
+    doc::{
+        memory::{format_bytes, DocumentMemory},
+        DocContent,
+    },
+    edit_preview::{EditPreviewData, PreviewFile, PreviewHunk},
     editor::{
         location::{EditorLocation, EditorPosition},
         view::editor_container_view,
@@ -64,22 +77,31 @@
     },
     editor_tab::{EditorTabChild, EditorTabData},
     focus_text::focus_text,
+    hover::{open_link, HoverData, PinnedHover},
     id::{EditorId, EditorTabId, SplitId},
     keypress::keymap::KeyMap,
     listener::Listener,
+    log,
     main_split::{MainSplitData, SplitContent, SplitData, SplitDirection},
+    markdown::{parse_completion_documentation, MarkdownContent},
+    notification::Notification,
     palette::{
         item::{PaletteItem, PaletteItemContent},
         kind::PaletteKind,
         PaletteData, PaletteStatus,
     },
     panel::{
-        kind::PanelKind, position::PanelContainerPosition,
+        notification_view::notification_view, position::PanelContainerPosition,
         view::panel_container_view,
     },
-    settings::settings_view,
+    plugin::PluginData,
+    popup::PopupKind,
+    settings::{checkbox, settings_view},
+    signature::signature_label,
+    startup::{self, on_first_paint},
+    status::status,
     text_input::text_input,
-    title::title,
+    title::{distinguishing_dir, relative_path, render_template, title},
     update::ReleaseInfo,
     window::{TabsInfo, WindowData, WindowInfo},
     window_tab::{CommonData, Focus, WindowTabData},
@@ -94,14 +116,42 @@ struct Cli {
     /// Launch new window even if Lapce is already running
     #[clap(short, long, action)]
     new: bool,
-    /// Don't return instantly when opened in a terminal
+    /// Open the paths in a new window, apart from the windows of the Lapce which
+    /// is already running
+    #[clap(long, action, conflicts_with = "reuse_window")]
+    new_window: bool,
+    /// Open the paths in the active window tab of the Lapce which is already
+    /// running, rather than in the one whose workspace has them
+    #[clap(short, long, action)]
+    reuse_window: bool,
+    /// Don't return until the files are closed, such as for `GIT_EDITOR`, or
+    /// until Lapce is closed when there are no files
     #[clap(short, long, action)]
     wait: bool,
+    /// Open the two files side by side to compare them
+    #[clap(long, number_of_values = 2, value_names = &["LEFT", "RIGHT"])]
+    #[clap(value_parser = lapce_proxy::cli::parse_file_line_column)]
+    #[clap(value_hint = clap::ValueHint::FilePath)]
+    diff: Option<Vec<PathObject>>,
+    /// Set on the process which Lapce runs in once it's detached from the
+    /// terminal
+    #[clap(long, action, hide = true)]
+    detached: bool,
 
     /// Manually set log level
     #[clap(short, long)]
     log_level: Option<String>,
 
+    /// Print how long the phases of the startup took, once the first window is
+    /// painted
+    #[clap(long, action)]
+    timing: bool,
+
+    /// Relay the collaboration sessions of the hosts which can't be joined
+    /// directly, on the address, instead of opening a window
+    #[clap(long, value_name = "ADDRESS")]
+    collab_relay: Option<String>,
+
     /// Paths to file(s) and/or folder(s) to open.
     /// When path is a file (that exists or not),
     /// it accepts `path:line:column` syntax
@@ -130,7 +180,7 @@ pub struct AppData {
     /// The latest release information
     pub latest_release: RwSignal<Arc<Option<ReleaseInfo>>>,
     pub watcher: Arc<notify::RecommendedWatcher>,
-    pub tracing_handle: Handle<LevelFilter>,
+    pub tracing_handle: Handle<Targets>,
 }
 
 impl AppData {
@@ -149,6 +199,164 @@ pub fn active_window_tab(&self) -> Option<Arc<WindowTabData>> {
         None
     }
 
+    /// Open the paths which another `lapce` process sent. The files go to the
+    /// window tab whose workspace has them, unless `reuse_window` puts them in
+    /// the active one, and since they're local paths never to a remote one.
+    fn open_request(&self, request: OpenRequest) {
+        let (paths, reuse_window, diff) = match request.notification {
+            CoreNotification::OpenPaths {
+                paths,
+                reuse_window,
+                diff,
+                ..
+            } => (paths, reuse_window, diff),
+            CoreNotification::OpenLink { url } => {
+                self.open_link(&url);
+                return;
+            }
+            _ => return,
+        };
+        let Some(window) = self.windows.get_untracked().iter().next().cloned()
+        else {
+            return;
+        };
+        let (folders, files): (Vec<PathObject>, Vec<PathObject>) =
+            paths.into_iter().partition(|p| p.is_dir);
+        let mut folders = folders.into_iter();
+
+        if reuse_window {
+            if let Some(folder) = folders.next() {
+                window.run_window_command(WindowCommand::SetWorkspace {
+                    workspace: local_workspace(Some(folder.path)),
+                });
+            }
+        }
+
+        let target = if files.is_empty() {
+            None
+        } else if reuse_window {
+            Some((window.clone(), window.active.get_untracked()))
+        } else {
+            self.find_window_tab(&files[0])
+                .or_else(|| Some((window.clone(), window.active.get_untracked())))
+        }
+        .filter(|(window, index)| {
+            window.window_tabs.with_untracked(|window_tabs| {
+                window_tabs
+                    .get(*index)
+                    .map(|(_, window_tab)| !window_tab.workspace.kind.is_remote())
+                    .unwrap_or(false)
+            })
+        });
+        let target = target.or_else(|| {
+            if files.is_empty() {
+                return None;
+            }
+            // The files are local, so they get a window tab of their own when the
+            // active one is remote, with the first folder as its workspace
+            window.run_window_command(WindowCommand::NewWorkspaceTab {
+                workspace: local_workspace(folders.next().map(|f| f.path)),
+                end: true,
+            });
+            Some((window.clone(), window.active.get_untracked()))
+        });
+
+        // The folders which are open already are only shown
+        for folder in folders {
+            if let Some((window, index)) = self.find_window_tab(&folder) {
+                window.active.set(index);
+            } else {
+                window.run_window_command(WindowCommand::NewWorkspaceTab {
+                    workspace: local_workspace(Some(folder.path)),
+                    end: true,
+                });
+            }
+        }
+
+        let Some((window, index)) = target else {
+            return;
+        };
+        window.active.set(index);
+        let Some(window_tab) = window.active_window_tab() else {
+            return;
+        };
+        match files.as_slice() {
+            [left, right] if diff => {
+                window_tab
+                    .main_split
+                    .open_diff(left.path.clone(), right.path.clone());
+            }
+            _ => window_tab.open_paths(&files),
+        }
+        if let Some(closed) = request.closed {
+            let files = files.into_iter().map(|p| p.path).collect();
+            window_tab.main_split.notify_when_closed(files, closed);
+        }
+    }
+
+    /// Open a `lapce://` link, where a file goes where it would from the command
+    /// line, and the rest to the active window tab.
+    pub fn open_link(&self, url: &str) {
+        let Some(window_tab) = self.active_window_tab() else {
+            return;
+        };
+        match DeepLink::parse(url) {
+            Ok(DeepLink::Open(path)) => {
+                self.open_request(OpenRequest {
+                    notification: CoreNotification::OpenPaths {
+                        paths: vec![path],
+                        reuse_window: false,
+                        diff: false,
+                        wait: false,
+                    },
+                    closed: None,
+                });
+            }
+            Ok(DeepLink::Plugin(volt_id)) => window_tab.show_plugin(&volt_id),
+            Ok(DeepLink::Settings(key)) => window_tab.show_setting(key),
+            Err(e) => {
+                let config = window_tab.common.config.get_untracked();
+                window_tab.notification.notify(Notification::error(
+                    config.tr_args(
+                        "notification-link-failed",
+                        [("link", FluentValue::from(url))],
+                    ),
+                    e.to_string(),
+                ));
+            }
+        }
+    }
+
+    /// The window tab whose local workspace is the folder or has the file, the
+    /// one with the innermost workspace when there are more.
+    fn find_window_tab(&self, path: &PathObject) -> Option<(WindowData, usize)> {
+        let mut found: Option<(WindowData, usize, usize)> = None;
+        for window in self.windows.get_untracked() {
+            window.window_tabs.with_untracked(|window_tabs| {
+                for (index, (_, window_tab)) in window_tabs.iter().enumerate() {
+                    if window_tab.workspace.kind.is_remote() {
+                        continue;
+                    }
+                    let Some(workspace) = window_tab.workspace.path.as_ref() else {
+                        continue;
+                    };
+                    let has_path = if path.is_dir {
+                        &path.path == workspace
+                    } else {
+                        path.path.starts_with(workspace)
+                    };
+                    let depth = workspace.components().count();
+                    if has_path
+                        && found.as_ref().map(|(_, _, d)| depth > *d).unwrap_or(true)
+                    {
+                        found = Some((window.clone(), index, depth));
+                    }
+                }
+            });
+        }
+        found.map(|(window, index, _)| (window, index))
+    }
+
     pub fn run_app_command(&self, cmd: AppCommand) {
         match cmd {
             AppCommand::SaveApp => {
@@ -159,6 +367,63 @@ pub fn run_app_command(&self, cmd: AppCommand) {
     }
 }
 
+/// The paths of the files of the editors of the editor tab.
+fn editor_tab_paths(
+    editor_tab: RwSignal<EditorTabData>,
+    editors: ReadSignal<im::HashMap<EditorId, RwSignal<EditorData>>>,
+) -> Vec<PathBuf> {
+    editor_tab.with(|editor_tab| {
+        editor_tab
+            .children
+            .iter()
+            .filter_map(|(_, child)| match child {
+                EditorTabChild::Editor(editor_id) => {
+                    editors.with(|editors| editors.get(editor_id).copied())
+                }
+                EditorTabChild::Settings(_) => None,
+            })
+            .filter_map(|editor| {
+                editor.with(|editor| {
+                    editor.doc.with(|doc| match &doc.content {
+                        DocContent::File(path) => Some(path.clone()),
+                        DocContent::Local => None,
+                    })
+                })
+            })
+            .collect()
+    })
+}
+
+/// The label of the tab of the file from the template, which is told apart from
+/// the other files of the editor tab with the same name by the end of its
+/// folder.
+fn tab_label(
+    template: &str,
+    workspace: &LapceWorkspace,
+    path: &Path,
+    paths: &[PathBuf],
+    dirty: bool,
+) -> String {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let label = render_template(
+        template,
+        &[
+            ("file", &file_name),
+            ("dir", &distinguishing_dir(path, paths)),
+            ("path", &relative_path(workspace, path)),
+            ("dirty", if dirty { "\u{25cf}" } else { "" }),
+        ],
+    );
+    if label.is_empty() {
+        file_name
+    } else {
+        label
+    }
+}
+
 fn editor_tab_header(
     active_editor_tab: ReadSignal<Option<EditorTabId>>,
     editor_tab: RwSignal<EditorTabData>,
@@ -168,6 +433,7 @@ fn editor_tab_header(
     let focus = common.focus;
     let config = common.config;
     let internal_command = common.internal_command;
+    let workspace = common.workspace.clone();
 
     let items = move || {
         let editor_tab = editor_tab.get();
@@ -190,8 +456,10 @@ fn editor_tab_header(
     };
 
     let view_fn = move |(i, child): (RwSignal<usize>, EditorTabChild)| {
+        let workspace = workspace.clone();
         let local_child = child.clone();
         let child_for_close = child.clone();
+        let child_for_lock = child.clone();
         let child_view = move || {
             #[derive(PartialEq)]
             struct Info {
@@ -200,15 +468,27 @@ struct Info {
                 path: String,
                 confirmed: Option<RwSignal<bool>>,
                 is_pristine: bool,
+                read_only: bool,
             }
 
             let cx = ViewContext::get_current();
             let info = match child {
                 EditorTabChild::Editor(editor_id) => {
+                    let workspace = workspace.clone();
                     create_memo(cx.scope, move |_| {
                         let config = config.get();
                         let editor_data =
                             editors.with(|editors| editors.get(&editor_id).cloned());
+                        let read_only = editor_data
+                            .map(|editor_data| {
+                                editor_data.with(|editor_data| {
+                                    editor_data.read_only.get()
+                                        || editor_data
+                                            .doc
+                                            .with(|doc| doc.read_only())
+                                })
+                            })
+                            .unwrap_or(false);
                         let path = if let Some(editor_data) = editor_data {
                             let ((content, is_pristine), confirmed) = editor_data
                                 .with(|editor_data| {
@@ -235,17 +515,14 @@ struct Info {
                         {
                             Some((path, confirmed, is_pritine)) => {
                                 let (svg, color) = config.file_svg(&path);
-                                (
-                                    svg,
-                                    color.cloned(),
-                                    path.file_name()
-                                        .unwrap_or_default()
-                                        .to_str()
-                                        .unwrap_or_default()
-                                        .to_string(),
-                                    confirmed,
-                                    is_pritine,
-                                )
+                                let label = tab_label(
+                                    &config.ui.tab_label,
+                                    &workspace,
+                                    &path,
+                                    &editor_tab_paths(editor_tab, editors),
+                                    !is_pritine,
+                                );
+                                (svg, color.cloned(), label, confirmed, is_pritine)
                             }
                             None => (
                                 config.ui_svg(LapceIcons::FILE),
@@ -263,6 +540,7 @@ struct Info {
                             path,
                             confirmed: Some(confirmed),
                             is_pristine,
+                            read_only,
                         }
                     })
                 }
@@ -276,6 +554,7 @@ struct Info {
                         path: "Settings".to_string(),
                         confirmed: None,
                         is_pristine: true,
+                        read_only: false,
                     }
                 }),
             };
@@ -305,6 +584,35 @@ struct Info {
                             )
                         },
                     ),
+                    // The lock of a read-only editor, which makes it writable
+                    clickable_icon(
+                        || LapceIcons::LOCK,
+                        move || {
+                            if let EditorTabChild::Editor(editor_id) =
+                                &child_for_lock
+                            {
+                                let editor = editors.with_untracked(|editors| {
+                                    editors.get(editor_id).copied()
+                                });
+                                if let Some(editor) = editor {
+                                    editor.with_untracked(|editor| {
+                                        editor.make_writable()
+                                    });
+                                }
+                            }
+                        },
+                        || false,
+                        || false,
+                        config,
+                    )
+                    .on_event(EventListener::PointerDown, |_| true)
+                    .style(move || {
+                        Style::BASE
+                            .margin_left_px(6.0)
+                            .apply_if(!info.with(|info| info.read_only), |s| {
+                                s.hide()
+                            })
+                    }),
                     clickable_icon(
                         move || {
                             if info.with(|info| info.is_pristine) {
@@ -332,11 +640,25 @@ struct Info {
                 )
             })
             .style(move || {
+                let config = config.get();
+                let (background, foreground) = if active() == i.get() {
+                    (
+                        LapceColor::LAPCE_TAB_ACTIVE_BACKGROUND,
+                        LapceColor::LAPCE_TAB_ACTIVE_FOREGROUND,
+                    )
+                } else {
+                    (
+                        LapceColor::LAPCE_TAB_INACTIVE_BACKGROUND,
+                        LapceColor::LAPCE_TAB_INACTIVE_FOREGROUND,
+                    )
+                };
                 Style::BASE
                     .items_center()
                     .border_left(if i.get() == 0 { 1.0 } else { 0.0 })
                     .border_right(1.0)
-                    .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+                    .border_color(*config.get_color(LapceColor::LAPCE_TAB_SEPARATOR))
+                    .background(*config.get_color(background))
+                    .color(*config.get_color(foreground))
             })
         };
 
@@ -484,12 +806,13 @@ struct Info {
             .items_center()
             .border_bottom(1.0)
             .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
-            .background(*config.get_color(LapceColor::PANEL_BACKGROUND))
+            .background(*config.get_color(LapceColor::LAPCE_TAB_BAR_BACKGROUND))
     })
 }
 
 fn editor_tab_content(
     main_split: MainSplitData,
+    plugin: PluginData,
     workspace: Arc<LapceWorkspace>,
     active_editor_tab: ReadSignal<Option<EditorTabId>>,
     editor_tab: RwSignal<EditorTabData>,
@@ -536,7 +859,8 @@ fn editor_tab_content(
                 }
             }
             EditorTabChild::Settings(_) => {
-                container_box(move || Box::new(settings_view(common)))
+                let plugin = plugin.clone();
+                container_box(move || Box::new(settings_view(plugin, common)))
             }
         };
         child.style(|| Style::BASE.size_pct(100.0, 100.0))
@@ -548,6 +872,7 @@ fn editor_tab_content(
 
 fn editor_tab(
     main_split: MainSplitData,
+    plugin: PluginData,
     workspace: Arc<LapceWorkspace>,
     active_editor_tab: ReadSignal<Option<EditorTabId>>,
     editor_tab: RwSignal<EditorTabData>,
@@ -577,6 +902,7 @@ fn editor_tab(
             editor_tab_header(active_editor_tab, editor_tab, editors, common),
             editor_tab_content(
                 main_split.clone(),
+                plugin.clone(),
                 workspace.clone(),
                 active_editor_tab,
                 editor_tab,
@@ -680,6 +1006,7 @@ fn split_list(
     workspace: Arc<LapceWorkspace>,
     split: ReadSignal<SplitData>,
     main_split: MainSplitData,
+    plugin: PluginData,
 ) -> impl View {
     let editor_tabs = main_split.editor_tabs.read_only();
     let active_editor_tab = main_split.active_editor_tab.read_only();
@@ -712,6 +1039,7 @@ fn split_list(
                     container_box(|| {
                         Box::new(editor_tab(
                             main_split.clone(),
+                            plugin.clone(),
                             workspace.clone(),
                             active_editor_tab,
                             editor_tab_data,
@@ -732,6 +1060,7 @@ fn split_list(
                         workspace.clone(),
                         split.read_only(),
                         main_split.clone(),
+                        plugin.clone(),
                     )
                 } else {
                     container_box(|| Box::new(label(|| "emtpy split".to_string())))
@@ -799,18 +1128,22 @@ fn main_split(window_tab_data: Arc<WindowTabData>) -> impl View {
     let config = window_tab_data.main_split.common.config;
     let workspace = window_tab_data.workspace.clone();
     let panel = window_tab_data.panel.clone();
-    split_list(workspace, root_split, window_tab_data.main_split.clone()).style(
-        move || {
-            let config = config.get();
-            let is_hidden = panel.panel_bottom_maximized(true)
-                && panel.is_container_shown(&PanelContainerPosition::Bottom, true);
-            Style::BASE
-                .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
-                .background(*config.get_color(LapceColor::EDITOR_BACKGROUND))
-                .apply_if(is_hidden, |s| s.display(Display::None))
-                .flex_grow(1.0)
-        },
+    split_list(
+        workspace,
+        root_split,
+        window_tab_data.main_split.clone(),
+        window_tab_data.plugin.clone(),
     )
+    .style(move || {
+        let config = config.get();
+        let is_hidden = panel.panel_bottom_maximized(true)
+            && panel.is_container_shown(&PanelContainerPosition::Bottom, true);
+        Style::BASE
+            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+            .background(*config.get_color(LapceColor::EDITOR_BACKGROUND))
+            .apply_if(is_hidden, |s| s.display(Display::None))
+            .flex_grow(1.0)
+    })
 }
 
 pub fn clickable_icon(
@@ -895,408 +1228,92 @@ fn workbench(window_tab_data: Arc<WindowTabData>) -> impl View {
     .style(|| Style::BASE.size_pct(100.0, 100.0))
 }
 
-fn status(window_tab_data: Arc<WindowTabData>) -> impl View {
-    let config = window_tab_data.common.config;
-    let diagnostics = window_tab_data.main_split.diagnostics;
-    let editor = window_tab_data.main_split.active_editor;
-    let panel = window_tab_data.panel.clone();
-    let palette = window_tab_data.palette.clone();
-    let cx = ViewContext::get_current();
-    let diagnostic_count = create_memo(cx.scope, move |_| {
-        let mut errors = 0;
-        let mut warnings = 0;
-        for (_, diagnostics) in diagnostics.get().iter() {
-            for diagnostic in diagnostics.diagnostics.get().iter() {
-                if let Some(severity) = diagnostic.diagnostic.severity {
-                    match severity {
-                        DiagnosticSeverity::ERROR => errors += 1,
-                        DiagnosticSeverity::WARNING => warnings += 1,
-                        _ => (),
-                    }
-                }
-            }
-        }
-        (errors, warnings)
-    });
-
-    let mode = create_memo(cx.scope, move |_| window_tab_data.mode());
+fn palette_item(
+    workspace: Arc<LapceWorkspace>,
+    i: usize,
+    item: PaletteItem,
+    index: ReadSignal<usize>,
+    palette_item_height: f64,
+    config: ReadSignal<Arc<LapceConfig>>,
+    keymap: Option<&KeyMap>,
+) -> impl View {
+    match &item.content {
+        PaletteItemContent::File { path, .. }
+        | PaletteItemContent::Reference { path, .. } => {
+            let file_name = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_string();
+            // let (file_name, _) = create_signal(cx.scope, file_name);
+            let folder = path
+                .parent()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_string();
+            // let (folder, _) = create_signal(cx.scope, folder);
+            let folder_len = folder.len();
 
-    stack(|| {
-        (
-            stack(|| {
-                (
-                    label(move || match mode.get() {
-                        Mode::Normal => "Normal".to_string(),
-                        Mode::Insert => "Insert".to_string(),
-                        Mode::Visual => "Visual".to_string(),
-                        Mode::Terminal => "Terminal".to_string(),
-                    })
-                    .style(move || {
-                        let config = config.get();
-                        let display = if config.core.modal {
-                            Display::Flex
+            let file_name_indices = item
+                .indices
+                .iter()
+                .filter_map(|&i| {
+                    if folder_len > 0 {
+                        if i > folder_len {
+                            Some(i - folder_len - 1)
                         } else {
-                            Display::None
-                        };
-
-                        let (bg, fg) = match mode.get() {
-                            Mode::Normal => (
-                                LapceColor::STATUS_MODAL_NORMAL_BACKGROUND,
-                                LapceColor::STATUS_MODAL_NORMAL_FOREGROUND,
-                            ),
-                            Mode::Insert => (
-                                LapceColor::STATUS_MODAL_INSERT_BACKGROUND,
-                                LapceColor::STATUS_MODAL_INSERT_FOREGROUND,
-                            ),
-                            Mode::Visual => (
-                                LapceColor::STATUS_MODAL_VISUAL_BACKGROUND,
-                                LapceColor::STATUS_MODAL_VISUAL_FOREGROUND,
-                            ),
-                            Mode::Terminal => (
-                                LapceColor::STATUS_MODAL_TERMINAL_BACKGROUND,
-                                LapceColor::STATUS_MODAL_TERMINAL_FOREGROUND,
-                            ),
-                        };
-
-                        let bg = *config.get_color(bg);
-                        let fg = *config.get_color(fg);
+                            None
+                        }
+                    } else {
+                        Some(i)
+                    }
+                })
+                .collect::<Vec<_>>();
+            let folder_indices = item
+                .indices
+                .iter()
+                .filter_map(|&i| if i < folder_len { Some(i) } else { None })
+                .collect::<Vec<_>>();
 
-                        Style::BASE
-                            .display(display)
-                            .padding_horiz_px(10.0)
-                            .color(fg)
-                            .background(bg)
-                            .height_pct(100.0)
-                            .align_items(Some(AlignItems::Center))
-                    }),
-                    {
-                        let panel = panel.clone();
-                        stack(|| {
-                            (
-                                svg(move || config.get().ui_svg(LapceIcons::ERROR))
-                                    .style(move || {
-                                        let config = config.get();
-                                        let size = config.ui.icon_size() as f32;
-                                        Style::BASE.size_px(size, size).color(
-                                            *config.get_color(
-                                                LapceColor::LAPCE_ICON_ACTIVE,
-                                            ),
-                                        )
-                                    }),
-                                label(move || diagnostic_count.get().0.to_string())
-                                    .style(|| Style::BASE.margin_left_px(5.0)),
-                                svg(move || {
-                                    config.get().ui_svg(LapceIcons::WARNING)
-                                })
-                                .style(move || {
+            let path = path.to_path_buf();
+            let style_path = path.clone();
+            container_box(move || {
+                Box::new(
+                    stack(move || {
+                        (
+                            svg(move || config.get().file_svg(&path).0).style(
+                                move || {
                                     let config = config.get();
                                     let size = config.ui.icon_size() as f32;
+                                    let color =
+                                        config.file_svg(&style_path).1.copied();
                                     Style::BASE
+                                        .min_width_px(size)
                                         .size_px(size, size)
-                                        .margin_left_px(5.0)
-                                        .color(*config.get_color(
-                                            LapceColor::LAPCE_ICON_ACTIVE,
-                                        ))
-                                }),
-                                label(move || diagnostic_count.get().1.to_string())
-                                    .style(|| Style::BASE.margin_left_px(5.0)),
+                                        .margin_right_px(5.0)
+                                        .apply_opt(color, Style::color)
+                                },
+                            ),
+                            focus_text(
+                                move || file_name.clone(),
+                                move || file_name_indices.clone(),
+                                move || {
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
+                                },
                             )
-                        })
-                        .on_click(move |_| {
-                            panel.show_panel(&PanelKind::Problem);
-                            true
-                        })
-                        .style(|| {
-                            Style::BASE
-                                .height_pct(100.0)
-                                .padding_horiz_px(10.0)
-                                .items_center()
-                        })
-                        .hover_style(move || {
-                            Style::BASE.cursor(CursorStyle::Pointer).background(
-                                *config
-                                    .get()
-                                    .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                            )
-                        })
-                    },
-                )
-            })
-            .style(|| {
-                Style::BASE
-                    .height_pct(100.0)
-                    .flex_basis_px(0.0)
-                    .flex_grow(1.0)
-                    .items_center()
-            }),
-            stack(move || {
-                (
-                    {
-                        let panel = panel.clone();
-                        let icon = {
-                            let panel = panel.clone();
-                            move || {
-                                if panel.is_container_shown(
-                                    &PanelContainerPosition::Left,
-                                    true,
-                                ) {
-                                    LapceIcons::SIDEBAR_LEFT
-                                } else {
-                                    LapceIcons::SIDEBAR_LEFT_OFF
-                                }
-                            }
-                        };
-                        clickable_icon(
-                            icon,
-                            move || {
-                                panel.toggle_container_visual(
-                                    &PanelContainerPosition::Left,
-                                )
-                            },
-                            || false,
-                            || false,
-                            config,
-                        )
-                    },
-                    {
-                        let panel = panel.clone();
-                        let icon = {
-                            let panel = panel.clone();
-                            move || {
-                                if panel.is_container_shown(
-                                    &PanelContainerPosition::Bottom,
-                                    true,
-                                ) {
-                                    LapceIcons::LAYOUT_PANEL
-                                } else {
-                                    LapceIcons::LAYOUT_PANEL_OFF
-                                }
-                            }
-                        };
-                        clickable_icon(
-                            icon,
-                            move || {
-                                panel.toggle_container_visual(
-                                    &PanelContainerPosition::Bottom,
-                                )
-                            },
-                            || false,
-                            || false,
-                            config,
-                        )
-                    },
-                    {
-                        let panel = panel.clone();
-                        let icon = {
-                            let panel = panel.clone();
-                            move || {
-                                if panel.is_container_shown(
-                                    &PanelContainerPosition::Right,
-                                    true,
-                                ) {
-                                    LapceIcons::SIDEBAR_RIGHT
-                                } else {
-                                    LapceIcons::SIDEBAR_RIGHT_OFF
-                                }
-                            }
-                        };
-                        clickable_icon(
-                            icon,
-                            move || {
-                                panel.toggle_container_visual(
-                                    &PanelContainerPosition::Right,
-                                )
-                            },
-                            || false,
-                            || false,
-                            config,
-                        )
-                    },
-                )
-            })
-            .style(|| Style::BASE.height_pct(100.0).items_center()),
-            stack(|| {
-                let palette_clone = palette.clone();
-                let cursor_info = label(move || {
-                    if let Some(editor) = editor.get() {
-                        let mut status = String::new();
-                        let cursor = editor.get().cursor.get();
-                        if let Some((line, column, character)) =
-                            cursor.get_line_col_char(editor.get().doc.get().buffer())
-                        {
-                            status = format!(
-                                "Ln {}, Col {}, Char {}",
-                                line, column, character,
-                            );
-                        }
-                        if let Some(selection) = cursor.get_selection() {
-                            let selection_range = selection.0.abs_diff(selection.1);
-
-                            if selection.0 != selection.1 {
-                                status =
-                                    format!("{status} ({selection_range} selected)");
-                            }
-                        }
-                        let selection_count = cursor.get_selection_count();
-                        if selection_count > 1 {
-                            status =
-                                format!("{status} {selection_count} selections");
-                        }
-                        return status;
-                    }
-                    String::from("No document")
-                })
-                .on_click(move |_| {
-                    palette_clone.run(cx.scope, PaletteKind::Line);
-                    true
-                })
-                .style(|| {
-                    Style::BASE
-                        .height_pct(100.0)
-                        .padding_horiz_px(10.0)
-                        .items_center()
-                })
-                .hover_style(move || {
-                    Style::BASE.cursor(CursorStyle::Pointer).background(
-                        *config
-                            .get()
-                            .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                    )
-                });
-                let palette_clone = palette.clone();
-                let language_info = label(move || {
-                    if let Some(editor) = editor.get() {
-                        if let Some(syn) = editor.get().doc.get().syntax() {
-                            if let Some(lang) =
-                                strum::EnumMessage::get_message(&syn.language)
-                            {
-                                return lang.to_string();
-                            }
-                        }
-                    }
-                    "Plain Text".to_string() // FIXME: remove
-                })
-                .on_click(move |_| {
-                    palette_clone.run(cx.scope, PaletteKind::Language);
-                    true
-                })
-                .style(|| {
-                    Style::BASE
-                        .height_pct(100.0)
-                        .padding_horiz_px(10.0)
-                        .items_center()
-                })
-                .hover_style(move || {
-                    Style::BASE.cursor(CursorStyle::Pointer).background(
-                        *config
-                            .get()
-                            .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                    )
-                });
-                (cursor_info, language_info)
-            })
-            .style(|| {
-                Style::BASE
-                    .height_pct(100.0)
-                    .flex_basis_px(0.0)
-                    .flex_grow(1.0)
-                    .justify_end()
-            }),
-        )
-    })
-    .style(move || {
-        let config = config.get();
-        Style::BASE
-            .border_top(1.0)
-            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
-            .background(*config.get_color(LapceColor::STATUS_BACKGROUND))
-            .height_px(config.ui.status_height() as f32)
-            .align_items(Some(AlignItems::Center))
-    })
-}
-
-fn palette_item(
-    workspace: Arc<LapceWorkspace>,
-    i: usize,
-    item: PaletteItem,
-    index: ReadSignal<usize>,
-    palette_item_height: f64,
-    config: ReadSignal<Arc<LapceConfig>>,
-    keymap: Option<&KeyMap>,
-) -> impl View {
-    match &item.content {
-        PaletteItemContent::File { path, .. }
-        | PaletteItemContent::Reference { path, .. } => {
-            let file_name = path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_string();
-            // let (file_name, _) = create_signal(cx.scope, file_name);
-            let folder = path
-                .parent()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_string();
-            // let (folder, _) = create_signal(cx.scope, folder);
-            let folder_len = folder.len();
-
-            let file_name_indices = item
-                .indices
-                .iter()
-                .filter_map(|&i| {
-                    if folder_len > 0 {
-                        if i > folder_len {
-                            Some(i - folder_len - 1)
-                        } else {
-                            None
-                        }
-                    } else {
-                        Some(i)
-                    }
-                })
-                .collect::<Vec<_>>();
-            let folder_indices = item
-                .indices
-                .iter()
-                .filter_map(|&i| if i < folder_len { Some(i) } else { None })
-                .collect::<Vec<_>>();
-
-            let path = path.to_path_buf();
-            let style_path = path.clone();
-            container_box(move || {
-                Box::new(
-                    stack(move || {
-                        (
-                            svg(move || config.get().file_svg(&path).0).style(
-                                move || {
-                                    let config = config.get();
-                                    let size = config.ui.icon_size() as f32;
-                                    let color =
-                                        config.file_svg(&style_path).1.copied();
-                                    Style::BASE
-                                        .min_width_px(size)
-                                        .size_px(size, size)
-                                        .margin_right_px(5.0)
-                                        .apply_opt(color, Style::color)
-                                },
-                            ),
-                            focus_text(
-                                move || file_name.clone(),
-                                move || file_name_indices.clone(),
-                                move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
-                                },
-                            )
-                            .style(|| {
-                                Style::BASE.margin_right_px(6.0).max_width_pct(100.0)
-                            }),
-                            focus_text(
-                                move || folder.clone(),
-                                move || folder_indices.clone(),
-                                move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
-                                },
+                            .style(|| {
+                                Style::BASE.margin_right_px(6.0).max_width_pct(100.0)
+                            }),
+                            focus_text(
+                                move || folder.clone(),
+                                move || folder_indices.clone(),
+                                move || {
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
+                                },
                             )
                             .style(move || {
                                 Style::BASE
@@ -1379,7 +1396,9 @@ fn palette_item(
                                 move || text.clone(),
                                 move || text_indices.clone(),
                                 move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
                                 },
                             )
                             .style(|| {
@@ -1389,7 +1408,9 @@ fn palette_item(
                                 move || hint.clone(),
                                 move || hint_indices.clone(),
                                 move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
                                 },
                             )
                             .style(move || {
@@ -1484,7 +1505,9 @@ fn palette_item(
                                 move || text.clone(),
                                 move || text_indices.clone(),
                                 move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
                                 },
                             )
                             .style(|| {
@@ -1494,7 +1517,9 @@ fn palette_item(
                                 move || hint.clone(),
                                 move || hint_indices.clone(),
                                 move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
                                 },
                             )
                             .style(move || {
@@ -1582,7 +1607,9 @@ fn palette_item(
                                 move || text.clone(),
                                 move || text_indices.clone(),
                                 move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
                                 },
                             )
                             .style(|| {
@@ -1592,7 +1619,9 @@ fn palette_item(
                                 move || hint.clone(),
                                 move || hint_indices.clone(),
                                 move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
                                 },
                             )
                             .style(move || {
@@ -1637,7 +1666,9 @@ fn palette_item(
                                 move || text.clone(),
                                 move || indices.clone(),
                                 move || {
-                                    *config.get().get_color(LapceColor::EDITOR_FOCUS)
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::PALETTE_MATCH)
                                 },
                             )
                             .style(|| {
@@ -1679,6 +1710,10 @@ fn palette_item(
         | PaletteItemContent::Language { .. }
         | PaletteItemContent::ColorTheme { .. }
         | PaletteItemContent::SCMReference { .. }
+        | PaletteItemContent::Profile { .. }
+        | PaletteItemContent::ClipboardHistory { .. }
+        | PaletteItemContent::CommandHistory { .. }
+        | PaletteItemContent::DroppedFolder { .. }
         | PaletteItemContent::IconTheme { .. } => {
             let text = item.filter_text;
             let indices = item.indices;
@@ -1687,7 +1722,7 @@ fn palette_item(
                     focus_text(
                         move || text.clone(),
                         move || indices.clone(),
-                        move || *config.get().get_color(LapceColor::EDITOR_FOCUS),
+                        move || *config.get().get_color(LapceColor::PALETTE_MATCH),
                     )
                     .style(|| {
                         Style::BASE
@@ -1718,9 +1753,20 @@ fn palette_input(window_tab_data: Arc<WindowTabData>) -> impl View {
     let config = window_tab_data.common.config;
     let focus = window_tab_data.common.focus;
     let is_focused = move || focus.get() == Focus::Palette;
+    let ssh_prompt = window_tab_data.palette.ssh_prompt;
     container(move || {
         container(move || {
-            text_input(editor, is_focused).style(|| Style::BASE.width_pct(100.0))
+            text_input(editor, is_focused)
+                .placeholder(move || {
+                    ssh_prompt.with(|prompt| {
+                        prompt
+                            .as_ref()
+                            .map(|prompt| prompt.prompt.trim().to_string())
+                            .unwrap_or_default()
+                    })
+                })
+                .masked(move || ssh_prompt.with(|prompt| prompt.is_some()))
+                .style(|| Style::BASE.width_pct(100.0))
         })
         .style(move || {
             let config = config.get();
@@ -1834,7 +1880,7 @@ fn palette_content(
                 )
             })
             .style(|| Style::BASE.width_pct(100.0).min_height_px(0.0)),
-            label(|| "No matching results".to_string()).style(move || {
+            label(move || config.get().tr("palette-no-results")).style(move || {
                 Style::BASE
                     .display(if items.with(|items| items.is_empty()) {
                         Display::Flex
@@ -1922,7 +1968,7 @@ fn palette(window_tab_data: Arc<WindowTabData>) -> impl View {
                 .margin_top_px(5.0)
                 .border(1.0)
                 .border_radius(6.0)
-                .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+                .border_color(*config.get_color(LapceColor::PALETTE_BORDER))
                 .flex_col()
                 .background(*config.get_color(LapceColor::PALETTE_BACKGROUND))
         })
@@ -1941,6 +1987,66 @@ fn palette(window_tab_data: Arc<WindowTabData>) -> impl View {
     })
 }
 
+/// The toasts of the notifications, stacked in the bottom right corner above the
+/// status bar.
+fn notification_toasts(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let layout_rect = window_tab_data.layout_rect.read_only();
+    let config = window_tab_data.common.config;
+    let workbench_command = window_tab_data.common.workbench_command;
+    let internal_command = window_tab_data.common.internal_command;
+    let notifications = window_tab_data.notification;
+    let toasts = notifications.toasts;
+    let cx = ViewContext::get_current();
+    let toasts_rect = create_rw_signal(cx.scope, Rect::ZERO);
+    let width = 360.0;
+
+    list(
+        move || toasts.get(),
+        |notification| notification.id,
+        move |notification| {
+            container(|| {
+                notification_view(
+                    notification,
+                    notifications,
+                    true,
+                    workbench_command,
+                    internal_command,
+                    config,
+                )
+            })
+            .on_event(EventListener::PointerDown, move |_| true)
+            .style(move || {
+                let config = config.get();
+                Style::BASE
+                    .width_pct(100.0)
+                    .margin_top_px(6.0)
+                    .border(1.0)
+                    .border_radius(6.0)
+                    .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+                    .background(*config.get_color(LapceColor::PANEL_BACKGROUND))
+            })
+        },
+    )
+    .on_resize(move |_, rect| {
+        toasts_rect.set(rect);
+    })
+    .style(move || {
+        let rect = layout_rect.get();
+        let height = toasts_rect.get().height();
+        let status_height = config.get().ui.status_height() as f64;
+        Style::BASE
+            .position(Position::Absolute)
+            .flex_col()
+            .width_px(width as f32)
+            .max_width_pct(90.0)
+            .margin_left_px((rect.width() - width - 10.0).max(0.0) as f32)
+            .margin_top_px(
+                (rect.height() - status_height - height - 10.0).max(0.0) as f32
+            )
+            .apply_if(toasts.with(|toasts| toasts.is_empty()), |s| s.hide())
+    })
+}
+
 struct VectorItems<V>(im::Vector<V>);
 
 impl<V: Clone + 'static> VirtualListVector<(usize, V)> for VectorItems<V> {
@@ -2085,6 +2191,7 @@ fn completion(window_tab_data: Arc<WindowTabData>) -> impl View {
     .style(move || {
         let config = config.get();
         let origin = window_tab_data.completion_origin();
+        let front = window_tab_data.common.popups.front(true);
         Style::BASE
             .position(Position::Absolute)
             .width_px(400.0)
@@ -2095,119 +2202,1227 @@ fn completion(window_tab_data: Arc<WindowTabData>) -> impl View {
             .font_family(config.editor.font_family.clone())
             .font_size(config.editor.font_size() as f32)
             .border_radius(10.0)
+            .apply_if(front == Some(PopupKind::Completion), |s| s.z_index(1))
     })
 }
 
-fn code_action(window_tab_data: Arc<WindowTabData>) -> impl View {
+/// The signature of the call which the cursor is in, with its active parameter
+/// highlighted.
+fn signature_help(window_tab_data: Arc<WindowTabData>) -> impl View {
     let config = window_tab_data.common.config;
-    let code_action = window_tab_data.code_action;
-    let (status, active) = code_action
-        .with_untracked(|code_action| (code_action.status, code_action.active));
-    let request_id =
-        move || code_action.with_untracked(|code_action| code_action.request_id);
-    scroll(move || {
-        container(|| {
-            list(
+    let signature = window_tab_data.common.signature;
+    let popups = window_tab_data.common.popups;
+    let active_label = move || {
+        signature
+            .help
+            .with(|help| help.as_ref().and_then(signature_label))
+    };
+
+    stack(|| {
+        (
+            focus_text(
                 move || {
-                    code_action.with(|code_action| {
-                        code_action.filtered_items.clone().into_iter().enumerate()
-                    })
+                    active_label()
+                        .map(|(label, _, _)| label)
+                        .unwrap_or_default()
                 },
-                move |(i, _item)| (request_id(), *i),
-                move |(i, item)| {
-                    container(move || {
-                        label(move || item.title().replace('\n', " "))
-                            .style(|| Style::BASE.text_ellipsis().min_width_px(0.0))
-                    })
-                    .style(move || {
-                        let config = config.get();
-                        Style::BASE
-                            .padding_horiz_px(10.0)
-                            .align_items(Some(AlignItems::Center))
-                            .min_width_px(0.0)
-                            .width_pct(100.0)
-                            .line_height(1.6)
-                            .apply_if(active.get() == i, |s| {
-                                s.border_radius(6.0).background(
-                                    *config
-                                        .get_color(LapceColor::COMPLETION_CURRENT),
-                                )
-                            })
-                    })
+                move || {
+                    active_label()
+                        .map(|(_, indices, _)| indices)
+                        .unwrap_or_default()
                 },
-            )
-            .style(|| Style::BASE.width_pct(100.0).flex_col())
-        })
-        .style(|| Style::BASE.width_pct(100.0).padding_vert_px(4.0))
-    })
-    .scroll_bar_color(move || *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR))
-    .on_ensure_visible(move || {
-        let config = config.get();
-        let active = active.get();
-        Size::new(1.0, config.editor.line_height() as f64)
-            .to_rect()
-            .with_origin(Point::new(
-                0.0,
-                active as f64 * config.editor.line_height() as f64,
-            ))
+                move || *config.get().get_color(LapceColor::EDITOR_FOCUS),
+            ),
+            label(move || {
+                active_label()
+                    .and_then(|(_, _, count)| count)
+                    .map(|(index, count)| format!("{index}/{count}"))
+                    .unwrap_or_default()
+            })
+            .style(move || {
+                Style::BASE
+                    .margin_left_px(10.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    .apply_if(
+                        active_label().and_then(|(_, _, count)| count).is_none(),
+                        |s| s.hide(),
+                    )
+            }),
+        )
     })
     .on_resize(move |_, rect| {
-        code_action.update(|c| {
-            c.layout_rect = rect;
-        });
+        signature.layout_rect.set(rect);
     })
+    .on_event(EventListener::PointerDown, |_| true)
     .style(move || {
-        let origin = window_tab_data.code_action_origin();
+        let origin = window_tab_data.signature_help_origin();
+        let config = config.get();
         Style::BASE
-            .display(match status.get() {
-                CodeActionStatus::Inactive => Display::None,
+            .position(Position::Absolute)
+            .items_center()
+            .padding_horiz_px(10.0)
+            .padding_vert_px(5.0)
+            .margin_left_px(origin.x as f32)
+            .margin_top_px(origin.y as f32)
+            .font_family(config.editor.font_family.clone())
+            .font_size(config.editor.font_size() as f32)
+            .background(*config.get_color(LapceColor::COMPLETION_BACKGROUND))
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+            .apply_if(popups.front(true) == Some(PopupKind::SignatureHelp), |s| {
+                s.z_index(1)
+            })
+            .apply_if(!signature.active.get(), |s| s.hide())
+    })
+}
+
+/// The width which the documentation of the completion starts at
+const COMPLETION_DOCUMENTATION_WIDTH: f64 = 300.0;
+/// How narrow and wide the documentation of the completion can be dragged to
+const COMPLETION_DOCUMENTATION_MIN_WIDTH: f64 = 150.0;
+const COMPLETION_DOCUMENTATION_MAX_WIDTH: f64 = 800.0;
+
+/// The documentation of the active completion item, next to the completion. The
+/// item is resolved for it when it has none, and the edge away from the
+/// completion can be dragged to resize it.
+fn completion_documentation(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let cx = ViewContext::get_current();
+    let completion_data = window_tab_data.common.completion;
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let proxy = window_tab_data.common.proxy.clone();
+    let active = completion_data.with_untracked(|c| c.active);
+    let documentation = create_rw_signal(cx.scope, Vec::new());
+    // Bumped when the active item changes, so that the documentation which is
+    // resolved for an older one is dropped
+    let documentation_id = create_rw_signal(cx.scope, 0);
+    let width = create_rw_signal(cx.scope, COMPLETION_DOCUMENTATION_WIDTH);
+    let drag_start = create_rw_signal(cx.scope, None);
+
+    create_effect(cx.scope, move |_| {
+        let config = config.get();
+        let index = active.get();
+        let item = completion_data.with(|c| {
+            if c.status == CompletionStatus::Inactive {
+                None
+            } else {
+                c.filtered_items.get(index).cloned()
+            }
+        });
+        documentation_id.update(|id| *id += 1);
+        let id = documentation_id.get_untracked();
+        let Some(item) =
+            item.filter(|_| config.editor.completion_show_documentation)
+        else {
+            documentation.set(Vec::new());
+            return;
+        };
+        documentation.set(parse_completion_documentation(&item.item, &config));
+
+        if item.item.documentation.is_none() && item.item.data.is_some() {
+            let send = create_ext_action(cx.scope, move |item: CompletionItem| {
+                if documentation_id.get_untracked() == id {
+                    documentation
+                        .set(parse_completion_documentation(&item, &config));
+                }
+            });
+            proxy.completion_resolve(
+                item.plugin_id,
+                item.item.clone(),
+                move |result| {
+                    if let Ok(ProxyResponse::CompletionResolveResponse { item }) =
+                        result
+                    {
+                        send(*item);
+                    }
+                },
+            );
+        }
+    });
+
+    // Where the documentation is, which is to the left of the completion when
+    // there's no room for it on the right
+    let placement = move || {
+        let origin = window_tab_data.completion_origin();
+        let completion_width = completion_data.with(|c| c.layout_rect.width());
+        let tab_width = window_tab_data.layout_rect.get().width();
+        let width = width.get();
+        let right = origin.x + completion_width;
+        if right + width > tab_width && origin.x - width >= 0.0 {
+            (Point::new(origin.x - width, origin.y), true)
+        } else {
+            (Point::new(right, origin.y), false)
+        }
+    };
+    let flipped = {
+        let placement = placement.clone();
+        move || placement().1
+    };
+
+    stack(|| {
+        (
+            scroll(move || {
+                markdown_view(
+                    move || documentation.get(),
+                    move || documentation_id.get_untracked(),
+                    move || width.get() as f32,
+                    internal_command,
+                    config,
+                )
+                .style(|| Style::BASE.padding_px(HOVER_PADDING))
+            })
+            .scroll_bar_color(move || {
+                *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+            })
+            .style(|| Style::BASE.width_pct(100.0).max_height_px(400.0)),
+            {
+                let drag_flipped = flipped.clone();
+                let handle = empty();
+                let handle_id = handle.id();
+                handle
+                    .on_event(EventListener::PointerDown, move |event| {
+                        if let Event::PointerDown(pointer_event) = event {
+                            handle_id.request_active();
+                            drag_start.set(Some(pointer_event.pos));
+                        }
+                        true
+                    })
+                    .on_event(EventListener::PointerMove, move |event| {
+                        if let (Event::PointerMove(pointer_event), Some(start)) =
+                            (event, drag_start.get_untracked())
+                        {
+                            let delta = pointer_event.pos.x - start.x;
+                            let delta = if drag_flipped() { -delta } else { delta };
+                            width.update(|width| {
+                                *width = (*width + delta).clamp(
+                                    COMPLETION_DOCUMENTATION_MIN_WIDTH,
+                                    COMPLETION_DOCUMENTATION_MAX_WIDTH,
+                                );
+                            });
+                        }
+                        true
+                    })
+                    .on_event(EventListener::PointerUp, move |_| {
+                        drag_start.set(None);
+                        true
+                    })
+                    .style(move || {
+                        // The handle is on the edge away from the completion
+                        let margin = if flipped() {
+                            0.0
+                        } else {
+                            width.get() as f32 - 4.0
+                        };
+                        Style::BASE
+                            .position(Position::Absolute)
+                            .width_px(4.0)
+                            .height_pct(100.0)
+                            .margin_left_px(margin)
+                    })
+                    .hover_style(|| Style::BASE.cursor(CursorStyle::ColResize))
+            },
+        )
+    })
+    .on_event(EventListener::PointerDown, |_| true)
+    .style(move || {
+        let (origin, _) = placement();
+        let config = config.get();
+        Style::BASE
+            .position(Position::Absolute)
+            .width_px(width.get() as f32)
+            .margin_left_px(origin.x as f32)
+            .margin_top_px(origin.y as f32)
+            .background(*config.get_color(LapceColor::COMPLETION_BACKGROUND))
+            .border_radius(10.0)
+            .apply_if(documentation.with(|d| d.is_empty()), |s| s.hide())
+    })
+}
+
+fn code_action(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let code_action = window_tab_data.code_action;
+    let (status, active) = code_action
+        .with_untracked(|code_action| (code_action.status, code_action.active));
+    let request_id =
+        move || code_action.with_untracked(|code_action| code_action.request_id);
+    scroll(move || {
+        container(|| {
+            list(
+                move || {
+                    code_action.with(|code_action| {
+                        code_action.filtered_items.clone().into_iter().enumerate()
+                    })
+                },
+                move |(i, _item)| (request_id(), *i),
+                move |(i, item)| {
+                    let header = code_action
+                        .with_untracked(|code_action| code_action.header(i));
+                    stack(move || {
+                        (
+                            label(move || {
+                                header.map_or(String::new(), |group| {
+                                    config.get().tr(group.message_id())
+                                })
+                            })
+                            .style(move || {
+                                Style::BASE
+                                    .padding_horiz_px(10.0)
+                                    .line_height(1.6)
+                                    .color(
+                                        *config
+                                            .get()
+                                            .get_color(LapceColor::EDITOR_DIM),
+                                    )
+                                    .apply_if(header.is_none(), |s| s.hide())
+                            }),
+                            container(move || {
+                                label(move || item.title().replace('\n', " ")).style(
+                                    || Style::BASE.text_ellipsis().min_width_px(0.0),
+                                )
+                            })
+                            .style(move || {
+                                let config = config.get();
+                                Style::BASE
+                                    .padding_horiz_px(10.0)
+                                    .align_items(Some(AlignItems::Center))
+                                    .min_width_px(0.0)
+                                    .width_pct(100.0)
+                                    .line_height(1.6)
+                                    .apply_if(active.get() == i, |s| {
+                                        s.border_radius(6.0).background(
+                                            *config.get_color(
+                                                LapceColor::COMPLETION_CURRENT,
+                                            ),
+                                        )
+                                    })
+                            }),
+                        )
+                    })
+                    .style(|| {
+                        Style::BASE.flex_col().min_width_px(0.0).width_pct(100.0)
+                    })
+                },
+            )
+            .style(|| Style::BASE.width_pct(100.0).flex_col())
+        })
+        .style(|| Style::BASE.width_pct(100.0).padding_vert_px(4.0))
+    })
+    .scroll_bar_color(move || *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR))
+    .on_ensure_visible(move || {
+        let config = config.get();
+        let row = code_action.with_untracked(|c| c.row(active.get()));
+        Size::new(1.0, config.editor.line_height() as f64)
+            .to_rect()
+            .with_origin(Point::new(
+                0.0,
+                row as f64 * config.editor.line_height() as f64,
+            ))
+    })
+    .on_resize(move |_, rect| {
+        code_action.update(|c| {
+            c.layout_rect = rect;
+        });
+    })
+    .style(move || {
+        let origin = window_tab_data.code_action_origin();
+        Style::BASE
+            .display(match status.get() {
+                CodeActionStatus::Inactive => Display::None,
                 CodeActionStatus::Active => Display::Flex,
             })
             .position(Position::Absolute)
-            .width_px(400.0)
-            .max_height_px(400.0)
-            .margin_left_px(origin.x as f32)
-            .margin_top_px(origin.y as f32)
-            .background(*config.get().get_color(LapceColor::COMPLETION_BACKGROUND))
-            .border_radius(10.0)
+            .width_px(400.0)
+            .max_height_px(400.0)
+            .margin_left_px(origin.x as f32)
+            .margin_top_px(origin.y as f32)
+            .background(*config.get().get_color(LapceColor::COMPLETION_BACKGROUND))
+            .border_radius(10.0)
+    })
+}
+
+/// The width of the hovers, which their text is wrapped at
+const HOVER_WIDTH: f32 = 500.0;
+const HOVER_MAX_HEIGHT: f32 = 300.0;
+const HOVER_PADDING: f32 = 10.0;
+
+/// The blocks of markdown, whose links are opened when they're clicked, wrapped
+/// at the width. The key changes when the content is replaced, so that its views
+/// are made again.
+fn markdown_view(
+    content: impl Fn() -> Vec<MarkdownContent> + 'static,
+    key: impl Fn() -> usize + 'static,
+    width: impl Fn() -> f32 + 'static,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    list(
+        move || {
+            let width = width();
+            content()
+                .into_iter()
+                .enumerate()
+                .map(move |(i, content)| (i, width, content))
+        },
+        move |(i, width, _)| (key(), width.to_bits(), *i),
+        move |(_, width, content)| match content {
+            MarkdownContent::Text(mut text) => container_box(move || {
+                text.layout.set_size(width - 2.0 * HOVER_PADDING, f32::MAX);
+                let layout = text.layout.clone();
+                Box::new(rich_text(move || layout.clone()).on_event(
+                    EventListener::PointerDown,
+                    move |event| {
+                        if let Event::PointerDown(pointer_event) = event {
+                            let index =
+                                text.layout.hit_point(pointer_event.pos).index;
+                            if let Some(link) = text.link_at(index) {
+                                open_link(link, internal_command);
+                                return true;
+                            }
+                        }
+                        false
+                    },
+                ))
+            }),
+            MarkdownContent::Separator => container_box(move || {
+                Box::new(empty().style(move || {
+                    Style::BASE
+                        .width_pct(100.0)
+                        .height_px(1.0)
+                        .margin_vert_px(6.0)
+                        .background(
+                            *config.get().get_color(LapceColor::LAPCE_BORDER),
+                        )
+                }))
+            }),
+        },
+    )
+    .style(|| Style::BASE.flex_col().width_pct(100.0))
+}
+
+fn hover(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let hover = window_tab_data.common.hover;
+
+    stack(|| {
+        (
+            scroll(move || {
+                markdown_view(
+                    move || hover.content.get(),
+                    move || hover.request_id.get_untracked(),
+                    || HOVER_WIDTH,
+                    internal_command,
+                    config,
+                )
+                .style(|| Style::BASE.padding_px(HOVER_PADDING))
+            })
+            .scroll_bar_color(move || {
+                *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+            })
+            .style(|| Style::BASE.width_pct(100.0).max_height_px(HOVER_MAX_HEIGHT)),
+            label(move || config.get().tr("hover-pin"))
+                .on_click({
+                    let window_tab_data = window_tab_data.clone();
+                    move |_| {
+                        hover.pin(window_tab_data.hover_origin());
+                        true
+                    }
+                })
+                .style(move || {
+                    Style::BASE
+                        .padding_horiz_px(HOVER_PADDING)
+                        .padding_bottom_px(6.0)
+                        .color(*config.get().get_color(LapceColor::EDITOR_LINK))
+                })
+                .hover_style(|| Style::BASE.cursor(CursorStyle::Pointer)),
+        )
+    })
+    .on_resize(move |_, rect| {
+        hover.layout_rect.set(rect);
+    })
+    .on_event(EventListener::PointerDown, |_| true)
+    .style(move || {
+        let origin = window_tab_data.hover_origin();
+        let front = window_tab_data.common.popups.front(true);
+        let config = config.get();
+        Style::BASE
+            .position(Position::Absolute)
+            .flex_col()
+            .width_px(HOVER_WIDTH)
+            .margin_left_px(origin.x as f32)
+            .margin_top_px(origin.y as f32)
+            .background(*config.get_color(LapceColor::HOVER_BACKGROUND))
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+            .apply_if(front == Some(PopupKind::Hover), |s| s.z_index(1))
+            .apply_if(!hover.active.get(), |s| s.hide())
+    })
+}
+
+/// The hovers which were pinned, in windows which can be dragged by their title.
+fn pinned_hovers(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let hover = window_tab_data.common.hover;
+
+    list(
+        move || hover.pinned.get(),
+        |pinned| pinned.id,
+        move |pinned| pinned_hover(pinned, hover, internal_command, config),
+    )
+    .style(move || {
+        Style::BASE
+            .position(Position::Absolute)
+            .size_pct(100.0, 100.0)
+            .apply_if(hover.pinned.with(|pinned| pinned.is_empty()), |s| s.hide())
+    })
+}
+
+fn pinned_hover(
+    pinned: PinnedHover,
+    hover: HoverData,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let cx = ViewContext::get_current();
+    let id = pinned.id;
+    let content = pinned.content;
+    let drag_start = create_rw_signal(cx.scope, None);
+    let origin = move || {
+        hover.pinned.with(|hovers| {
+            hovers
+                .iter()
+                .find(|h| h.id == id)
+                .map(|h| h.origin)
+                .unwrap_or_default()
+        })
+    };
+
+    stack(|| {
+        (
+            stack(|| {
+                (
+                    {
+                        let title = label(move || config.get().tr("hover-pinned"));
+                        let title_id = title.id();
+                        title
+                            .on_event(EventListener::PointerDown, move |event| {
+                                if let Event::PointerDown(pointer_event) = event {
+                                    title_id.request_active();
+                                    drag_start.set(Some(pointer_event.pos));
+                                }
+                                true
+                            })
+                            .on_event(EventListener::PointerMove, move |event| {
+                                if let (
+                                    Event::PointerMove(pointer_event),
+                                    Some(start),
+                                ) = (event, drag_start.get_untracked())
+                                {
+                                    hover.move_pinned(id, pointer_event.pos - start);
+                                }
+                                true
+                            })
+                            .on_event(EventListener::PointerUp, move |_| {
+                                drag_start.set(None);
+                                true
+                            })
+                            .style(|| {
+                                Style::BASE
+                                    .flex_grow(1.0)
+                                    .min_width_px(0.0)
+                                    .font_bold()
+                                    .text_ellipsis()
+                            })
+                    },
+                    clickable_icon(
+                        || LapceIcons::CLOSE,
+                        move || hover.unpin(id),
+                        || false,
+                        || false,
+                        config,
+                    ),
+                )
+            })
+            .style(move || {
+                Style::BASE
+                    .items_center()
+                    .width_pct(100.0)
+                    .padding_left_px(HOVER_PADDING)
+                    .padding_right_px(4.0)
+                    .padding_vert_px(4.0)
+                    .border_bottom(1.0)
+                    .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+            }),
+            scroll(move || {
+                markdown_view(
+                    move || content.clone(),
+                    || 0,
+                    || HOVER_WIDTH,
+                    internal_command,
+                    config,
+                )
+                .style(|| Style::BASE.padding_px(HOVER_PADDING))
+            })
+            .scroll_bar_color(move || {
+                *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+            })
+            .style(|| Style::BASE.width_pct(100.0).max_height_px(HOVER_MAX_HEIGHT)),
+        )
+    })
+    .on_event(EventListener::PointerDown, |_| true)
+    .style(move || {
+        let origin = origin();
+        let config = config.get();
+        Style::BASE
+            .position(Position::Absolute)
+            .flex_col()
+            .width_px(HOVER_WIDTH)
+            .margin_left_px(origin.x as f32)
+            .margin_top_px(origin.y as f32)
+            .background(*config.get_color(LapceColor::HOVER_BACKGROUND))
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+    })
+}
+
+fn rename(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let editor = window_tab_data.rename.editor.clone();
+    let active = window_tab_data.rename.active;
+    let layout_rect = window_tab_data.rename.layout_rect;
+    let config = window_tab_data.common.config;
+
+    container(|| {
+        container(move || {
+            text_input(editor, move || active.get())
+                .style(|| Style::BASE.width_px(150.0))
+        })
+        .style(move || {
+            let config = config.get();
+            Style::BASE
+                .font_family(config.editor.font_family.clone())
+                .font_size(config.editor.font_size() as f32)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+                .background(*config.get_color(LapceColor::EDITOR_BACKGROUND))
+        })
+    })
+    .on_resize(move |_, rect| {
+        layout_rect.set(rect);
+    })
+    .style(move || {
+        let origin = window_tab_data.rename_origin();
+        Style::BASE
+            .position(Position::Absolute)
+            .apply_if(!active.get(), |s| s.hide())
+            .margin_left_px(origin.x as f32)
+            .margin_top_px(origin.y as f32)
+            .background(*config.get().get_color(LapceColor::PANEL_BACKGROUND))
+            .border_radius(6.0)
+            .padding_px(6.0)
+    })
+}
+
+/// The number of cells of a bar of the color picker
+const COLOR_PICKER_STEPS: usize = 24;
+
+/// The bar of a channel of the color picker, whose cells set the channel to
+/// their value when they're clicked.
+fn color_channel(
+    picker: ColorPickerData,
+    channel: ColorChannel,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let hsla = picker.hsla;
+    stack(move || {
+        (
+            label(move || channel.name().to_string()).style(move || {
+                Style::BASE
+                    .width_px(16.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            list(
+                move || 0..COLOR_PICKER_STEPS,
+                |i| *i,
+                move |i| {
+                    let picker = picker.clone();
+                    let value = i as f64 / (COLOR_PICKER_STEPS - 1) as f64;
+                    empty()
+                        .on_click(move |_| {
+                            let hsla = picker.hsla.get_untracked();
+                            picker.set_hsla(channel.set(hsla, value));
+                            true
+                        })
+                        .style(move || {
+                            let hsla = hsla.get();
+                            let step = (channel.get(hsla)
+                                * (COLOR_PICKER_STEPS - 1) as f64)
+                                .round()
+                                as usize;
+                            Style::BASE
+                                .size_px(8.0, 16.0)
+                                .background(channel.swatch(hsla, value))
+                                .apply_if(step == i, |s| {
+                                    s.border(1.0).border_color(
+                                        *config.get().get_color(
+                                            LapceColor::EDITOR_FOREGROUND,
+                                        ),
+                                    )
+                                })
+                        })
+                },
+            ),
+        )
+    })
+    .style(|| Style::BASE.items_center().margin_top_px(4.0))
+}
+
+/// The popup which picks the color of a literal by its hue, saturation,
+/// lightness and alpha, and the format it's written back in.
+fn color_picker(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let picker = window_tab_data.color_picker.clone();
+    let active = picker.active;
+    let hsla = picker.hsla;
+    let format = picker.format;
+    let layout_rect = picker.layout_rect;
+    let config = window_tab_data.common.config;
+
+    container(|| {
+        stack(|| {
+            (
+                stack(|| {
+                    (
+                        empty().style(move || {
+                            let config = config.get();
+                            Style::BASE
+                                .size_px(24.0, 24.0)
+                                .border(1.0)
+                                .border_radius(4.0)
+                                .border_color(
+                                    *config.get_color(LapceColor::LAPCE_BORDER),
+                                )
+                                .background(hsla.get().to_color())
+                        }),
+                        label(move || format_color(hsla.get(), format.get())).style(
+                            move || {
+                                let config = config.get();
+                                Style::BASE
+                                    .margin_left_px(8.0)
+                                    .font_family(config.editor.font_family.clone())
+                            },
+                        ),
+                    )
+                })
+                .style(|| Style::BASE.items_center()),
+                {
+                    let picker = picker.clone();
+                    list(
+                        move || ColorChannel::ALL,
+                        |channel| *channel,
+                        move |channel| {
+                            color_channel(picker.clone(), channel, config)
+                        },
+                    )
+                    .style(|| Style::BASE.flex_col())
+                },
+                {
+                    let picker = picker.clone();
+                    list(
+                        move || ColorFormat::ALL,
+                        |format| *format,
+                        move |item| {
+                            let picker = picker.clone();
+                            label(move || item.name().to_string())
+                                .on_click(move |_| {
+                                    picker.set_format(item);
+                                    true
+                                })
+                                .style(move || {
+                                    let config = config.get();
+                                    Style::BASE
+                                        .padding_horiz_px(6.0)
+                                        .margin_right_px(4.0)
+                                        .border_radius(4.0)
+                                        .apply_if(format.get() == item, |s| {
+                                            s.background(*config.get_color(
+                                                LapceColor::PANEL_CURRENT_BACKGROUND,
+                                            ))
+                                        })
+                                })
+                                .hover_style(move || {
+                                    Style::BASE
+                                        .cursor(CursorStyle::Pointer)
+                                        .background(*config.get().get_color(
+                                            LapceColor::PANEL_HOVERED_BACKGROUND,
+                                        ))
+                                })
+                        },
+                    )
+                    .style(|| Style::BASE.margin_top_px(6.0))
+                },
+            )
+        })
+        .style(|| Style::BASE.flex_col())
+    })
+    .on_resize(move |_, rect| {
+        layout_rect.set(rect);
+    })
+    .style(move || {
+        let origin = window_tab_data.color_picker_origin();
+        let config = config.get();
+        Style::BASE
+            .position(Position::Absolute)
+            .apply_if(!active.get(), |s| s.hide())
+            .margin_left_px(origin.x as f32)
+            .margin_top_px(origin.y as f32)
+            .background(*config.get_color(LapceColor::PANEL_BACKGROUND))
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+            .padding_px(8.0)
+    })
+}
+
+/// The edits of a file in their preview, with the lines which each changes
+/// before and after it.
+fn edit_preview_file(
+    edit_preview: EditPreviewData,
+    file: usize,
+    preview: PreviewFile,
+) -> impl View {
+    let config = edit_preview.common.config;
+    let path = preview.edit.path.clone();
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let folder = path
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let hunks: Vec<(usize, PreviewHunk)> =
+        preview.hunks.into_iter().enumerate().collect();
+    let line_style = move |color: &'static str| {
+        let config = config.get();
+        Style::BASE
+            .padding_horiz_px(6.0)
+            .min_width_px(0.0)
+            .text_ellipsis()
+            .font_family(config.editor.font_family.clone())
+            .font_size(config.editor.font_size() as f32)
+            .background(config.get_color(color).with_alpha_factor(0.15))
+    };
+    stack(|| {
+        (
+            stack(|| {
+                (
+                    {
+                        let edit_preview = edit_preview.clone();
+                        checkbox(
+                            move || {
+                                edit_preview.is_file_accepted(file) != Some(false)
+                            },
+                            config,
+                        )
+                    }
+                    .on_click({
+                        let edit_preview = edit_preview.clone();
+                        move |_| {
+                            edit_preview.toggle_file(file);
+                            true
+                        }
+                    })
+                    .hover_style(|| Style::BASE.cursor(CursorStyle::Pointer)),
+                    svg(move || config.get().file_svg(&path).0).style(move || {
+                        let size = config.get().ui.icon_size() as f32;
+                        Style::BASE.size_px(size, size).margin_horiz_px(6.0)
+                    }),
+                    label(move || file_name.clone()),
+                    label(move || folder.clone()).style(move || {
+                        Style::BASE
+                            .margin_left_px(6.0)
+                            .min_width_px(0.0)
+                            .text_ellipsis()
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    }),
+                )
+            })
+            .style(|| Style::BASE.items_center().padding_vert_px(4.0)),
+            list(
+                move || hunks.clone(),
+                |(hunk, _)| *hunk,
+                move |(hunk, preview)| {
+                    let edit_preview = edit_preview.clone();
+                    stack(|| {
+                        (
+                            {
+                                let edit_preview = edit_preview.clone();
+                                checkbox(
+                                    move || edit_preview.is_accepted(file, hunk),
+                                    config,
+                                )
+                            }
+                            .on_click(move |_| {
+                                edit_preview.toggle_hunk(file, hunk);
+                                true
+                            })
+                            .hover_style(|| {
+                                Style::BASE.cursor(CursorStyle::Pointer)
+                            }),
+                            label(move || format!("{}", preview.line + 1)).style(
+                                move || {
+                                    Style::BASE
+                                        .width_px(40.0)
+                                        .margin_left_px(6.0)
+                                        .justify_end()
+                                        .color(
+                                            *config
+                                                .get()
+                                                .get_color(LapceColor::EDITOR_DIM),
+                                        )
+                                },
+                            ),
+                            stack(|| {
+                                (
+                                    label(move || preview.old.clone()).style(
+                                        move || {
+                                            line_style(
+                                                LapceColor::SOURCE_CONTROL_REMOVED,
+                                            )
+                                        },
+                                    ),
+                                    label(move || preview.new.clone()).style(
+                                        move || {
+                                            line_style(
+                                                LapceColor::SOURCE_CONTROL_ADDED,
+                                            )
+                                        },
+                                    ),
+                                )
+                            })
+                            .style(|| {
+                                Style::BASE
+                                    .flex_col()
+                                    .flex_grow(1.0)
+                                    .min_width_px(0.0)
+                                    .margin_left_px(6.0)
+                            }),
+                        )
+                    })
+                    .style(|| {
+                        Style::BASE
+                            .align_items(Some(AlignItems::FlexStart))
+                            .padding_left_px(20.0)
+                            .padding_vert_px(2.0)
+                    })
+                },
+            )
+            .style(|| Style::BASE.flex_col()),
+        )
+    })
+    .style(|| Style::BASE.flex_col().padding_horiz_px(10.0))
+}
+
+/// The preview of the edits of many files, where each file and each of its
+/// edits can be unticked before the others are made.
+fn edit_preview(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let edit_preview = window_tab_data.edit_preview.clone();
+    let active = edit_preview.active;
+    let files = edit_preview.files;
+    let preview_id = edit_preview.preview_id;
+    let config = edit_preview.common.config;
+    let button = move |text: &'static str, primary: bool| {
+        label(move || config.get().tr(text)).style(move || {
+            let config = config.get();
+            Style::BASE
+                .margin_left_px(6.0)
+                .padding_horiz_px(8.0)
+                .padding_vert_px(2.0)
+                .border_radius(4.0)
+                .apply_if(primary, |s| {
+                    s.color(
+                        *config
+                            .get_color(LapceColor::LAPCE_BUTTON_PRIMARY_FOREGROUND),
+                    )
+                    .background(
+                        *config
+                            .get_color(LapceColor::LAPCE_BUTTON_PRIMARY_BACKGROUND),
+                    )
+                })
+                .apply_if(!primary, |s| {
+                    s.border(1.0)
+                        .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+                })
+        })
+    };
+    container(|| {
+        stack(|| {
+            (
+                label(move || {
+                    config.get().tr_args(
+                        "edit-preview-title",
+                        [("count", FluentValue::from(files.with(|f| f.len())))],
+                    )
+                })
+                .style(|| Style::BASE.padding_px(10.0)),
+                scroll({
+                    let edit_preview = edit_preview.clone();
+                    move || {
+                        list(
+                            move || files.get().into_iter().enumerate(),
+                            move |(file, _)| (preview_id.get_untracked(), *file),
+                            move |(file, preview)| {
+                                edit_preview_file(
+                                    edit_preview.clone(),
+                                    file,
+                                    preview,
+                                )
+                            },
+                        )
+                        .style(|| Style::BASE.flex_col().width_pct(100.0))
+                    }
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| {
+                    Style::BASE
+                        .flex_grow(1.0)
+                        .min_height(Dimension::Points(0.0))
+                }),
+                stack(|| {
+                    (
+                        button("edit-preview-discard", false)
+                            .on_click({
+                                let edit_preview = edit_preview.clone();
+                                move |_| {
+                                    edit_preview.close();
+                                    true
+                                }
+                            })
+                            .hover_style(|| {
+                                Style::BASE.cursor(CursorStyle::Pointer)
+                            }),
+                        button("edit-preview-apply", true)
+                            .on_click({
+                                let edit_preview = edit_preview.clone();
+                                move |_| {
+                                    edit_preview.apply();
+                                    true
+                                }
+                            })
+                            .hover_style(|| {
+                                Style::BASE.cursor(CursorStyle::Pointer)
+                            }),
+                    )
+                })
+                .style(|| Style::BASE.justify_end().padding_px(10.0)),
+            )
+        })
+        .on_event(EventListener::PointerDown, move |_| true)
+        .style(move || {
+            let config = config.get();
+            Style::BASE
+                .flex_col()
+                .width_px(800.0)
+                .max_width_pct(90.0)
+                .max_height(Dimension::Percent(0.8))
+                .margin_top_px(40.0)
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+                .background(*config.get_color(LapceColor::PANEL_BACKGROUND))
+        })
+    })
+    .style(move || {
+        Style::BASE
+            .position(Position::Absolute)
+            .size_pct(100.0, 100.0)
+            .flex_col()
+            .items_center()
+            .apply_if(!active.get(), |s| s.hide())
     })
 }
 
-fn rename(window_tab_data: Arc<WindowTabData>) -> impl View {
-    let editor = window_tab_data.rename.editor.clone();
-    let active = window_tab_data.rename.active;
-    let layout_rect = window_tab_data.rename.layout_rect;
+fn memory_usage(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let report = window_tab_data.memory_usage;
     let config = window_tab_data.common.config;
+    let workspace = window_tab_data.workspace.path.clone();
+    let row = move |[document, buffer, syntax, lsp]: [String; 4], bold: bool| {
+        stack(|| {
+            (
+                label(move || document.clone()).style(|| {
+                    Style::BASE
+                        .flex_grow(1.0)
+                        .flex_basis(Dimension::Points(0.0))
+                        .min_width_px(0.0)
+                        .text_ellipsis()
+                }),
+                label(move || buffer.clone()).style(|| Style::BASE.width_px(90.0)),
+                label(move || syntax.clone()).style(|| Style::BASE.width_px(90.0)),
+                label(move || lsp.clone()).style(|| Style::BASE.width_px(120.0)),
+            )
+        })
+        .style(move || {
+            Style::BASE
+                .width_pct(100.0)
+                .padding_horiz_px(10.0)
+                .padding_vert_px(2.0)
+                .apply_if(bold, |s| s.font_bold())
+        })
+    };
+    let sizes = |memory: DocumentMemory| {
+        [
+            format_bytes(memory.buffer),
+            format_bytes(memory.syntax),
+            format_bytes(memory.lsp),
+        ]
+    };
+    let button = move |text: &'static str| {
+        label(move || config.get().tr(text))
+            .style(move || {
+                Style::BASE
+                    .margin_left_px(6.0)
+                    .padding_horiz_px(8.0)
+                    .padding_vert_px(2.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+            })
+            .hover_style(|| Style::BASE.cursor(CursorStyle::Pointer))
+    };
 
     container(|| {
-        container(move || {
-            text_input(editor, move || active.get())
-                .style(|| Style::BASE.width_px(150.0))
+        stack(|| {
+            (
+                label(move || config.get().tr("memory-usage-title"))
+                    .style(|| Style::BASE.padding_px(10.0).font_bold()),
+                row(
+                    [
+                        config.get_untracked().tr("memory-usage-document"),
+                        config.get_untracked().tr("memory-usage-buffer"),
+                        config.get_untracked().tr("memory-usage-syntax"),
+                        config.get_untracked().tr("memory-usage-lsp"),
+                    ],
+                    true,
+                ),
+                scroll(move || {
+                    list(
+                        move || {
+                            report.with(|report| {
+                                report
+                                    .as_ref()
+                                    .map(|report| report.documents.clone())
+                                    .unwrap_or_default()
+                            })
+                        },
+                        |(path, memory)| {
+                            (path.clone(), memory.buffer, memory.syntax, memory.lsp)
+                        },
+                        move |(path, memory)| {
+                            let path = workspace
+                                .as_ref()
+                                .and_then(|workspace| {
+                                    path.strip_prefix(workspace).ok()
+                                })
+                                .unwrap_or(path.as_path())
+                                .display()
+                                .to_string();
+                            let [buffer, syntax, lsp] = sizes(memory);
+                            row([path, buffer, syntax, lsp], false)
+                        },
+                    )
+                    .style(|| Style::BASE.flex_col().width_pct(100.0))
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| {
+                    Style::BASE
+                        .flex_grow(1.0)
+                        .min_height(Dimension::Points(0.0))
+                }),
+                label(move || {
+                    let (count, total, terminals) = report.with(|report| {
+                        report
+                            .as_ref()
+                            .map(|report| {
+                                (
+                                    report.documents.len(),
+                                    report.documents_total(),
+                                    report.terminals,
+                                )
+                            })
+                            .unwrap_or_default()
+                    });
+                    let [buffer, syntax, lsp] = sizes(total);
+                    config.get().tr_args(
+                        "memory-usage-total",
+                        [
+                            ("docs", FluentValue::from(count)),
+                            ("buffers", FluentValue::from(buffer)),
+                            ("syntax", FluentValue::from(syntax)),
+                            ("lsp", FluentValue::from(lsp)),
+                            (
+                                "terminals",
+                                FluentValue::from(format_bytes(terminals)),
+                            ),
+                        ],
+                    )
+                })
+                .style(move || {
+                    Style::BASE.padding_px(10.0).border_top(1.0).border_color(
+                        *config.get().get_color(LapceColor::LAPCE_BORDER),
+                    )
+                }),
+                stack(|| {
+                    (
+                        button("memory-usage-refresh").on_click({
+                            let window_tab_data = window_tab_data.clone();
+                            move |_| {
+                                window_tab_data.show_memory_usage();
+                                true
+                            }
+                        }),
+                        button("memory-usage-close").on_click(move |_| {
+                            report.set(None);
+                            true
+                        }),
+                    )
+                })
+                .style(|| Style::BASE.justify_end().padding_px(10.0)),
+            )
         })
+        .on_event(EventListener::PointerDown, move |_| true)
         .style(move || {
             let config = config.get();
             Style::BASE
-                .font_family(config.editor.font_family.clone())
-                .font_size(config.editor.font_size() as f32)
+                .flex_col()
+                .width_px(700.0)
+                .max_width_pct(90.0)
+                .max_height(Dimension::Percent(0.8))
+                .margin_top_px(40.0)
                 .border(1.0)
                 .border_radius(6.0)
                 .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
-                .background(*config.get_color(LapceColor::EDITOR_BACKGROUND))
+                .background(*config.get_color(LapceColor::PANEL_BACKGROUND))
         })
     })
-    .on_resize(move |_, rect| {
-        layout_rect.set(rect);
+    .on_event(EventListener::PointerDown, move |_| {
+        report.set(None);
+        true
     })
     .style(move || {
-        let origin = window_tab_data.rename_origin();
         Style::BASE
             .position(Position::Absolute)
-            .apply_if(!active.get(), |s| s.hide())
-            .margin_left_px(origin.x as f32)
-            .margin_top_px(origin.y as f32)
-            .background(*config.get().get_color(LapceColor::PANEL_BACKGROUND))
-            .border_radius(6.0)
-            .padding_px(6.0)
+            .size_pct(100.0, 100.0)
+            .flex_col()
+            .items_center()
+            .apply_if(report.with(|report| report.is_none()), |s| s.hide())
     })
 }
 
@@ -2257,9 +3472,22 @@ fn window_tab(window_tab_data: Arc<WindowTabData>) -> impl View {
             })
             .style(|| Style::BASE.size_pct(100.0, 100.0).flex_col()),
             completion(window_tab_data.clone()),
+            completion_documentation(window_tab_data.clone()),
+            signature_help(window_tab_data.clone()),
             code_action(window_tab_data.clone()),
+            pinned_hovers(window_tab_data.clone()),
+            hover(window_tab_data.clone()),
             rename(window_tab_data.clone()),
+            color_picker(window_tab_data.clone()),
+            edit_preview(window_tab_data.clone()),
+            memory_usage(window_tab_data.clone()),
             palette(window_tab_data.clone()),
+            notification_toasts(window_tab_data.clone()),
+            {
+                let window_tab_data = window_tab_data.clone();
+                on_first_paint(move || window_tab_data.start_deferred())
+                    .style(|| Style::BASE.absolute().size_px(0.0, 0.0))
+            },
         )
     })
     .style(move || {
@@ -2331,7 +3559,7 @@ fn workspace_tab_header(window_data: WindowData) -> impl View {
                                         label(move || {
                                             workspace_title(&tab.workspace)
                                                 .unwrap_or_else(|| {
-                                                    String::from("New Tab")
+                                                    config.get().tr("tab-new")
                                                 })
                                         })
                                         .style(|| {
@@ -2505,7 +3733,13 @@ fn app_view(window_data: WindowData) -> impl View {
 }
 
 pub fn launch() {
+    // Lapce is run by SSH to ask for its prompts
+    if let Some(code) = crate::proxy::askpass::run() {
+        std::process::exit(code);
+    }
+
     use tracing_subscriber::{filter, fmt, prelude::*, reload};
+    let start = std::time::Instant::now();
     let file_appender = tracing_appender::rolling::Builder::new()
         .max_log_files(10)
         .rotation(tracing_appender::rolling::Rotation::DAILY)
@@ -2515,7 +3749,8 @@ pub fn launch() {
         .expect("Couldn't create rolling appender");
     let (log_file, _guard) = tracing_appender::non_blocking(file_appender);
     let (filter, reload_handle) =
-        reload::Subscriber::new(filter::LevelFilter::ERROR);
+        reload::Subscriber::new(log::log_levels("", filter::LevelFilter::ERROR));
+    log::init_log_levels(reload_handle.clone(), filter::LevelFilter::ERROR);
 
     let file_layer = tracing_subscriber::fmt::subscriber()
         .with_ansi(false)
@@ -2523,6 +3758,7 @@ pub fn launch() {
     tracing_subscriber::registry()
         .with(filter)
         .with(file_layer)
+        .with(log::CaptureLogs)
         .with(
             fmt::Subscriber::default().with_filter(FilterFn::new(|metadata| {
                 metadata.target().starts_with("lapce_app")
@@ -2536,52 +3772,103 @@ pub fn launch() {
         load_shell_env();
     }
 
-    let cli = Cli::parse();
-
-    if let Some(log_level) = cli.log_level {
-        if let Err(e) = reload_handle.modify(|filter| {
-            *filter = match log_level.to_lowercase().as_str() {
-                "off" => filter::LevelFilter::OFF,
-                "error" => filter::LevelFilter::ERROR,
-                "warn" => filter::LevelFilter::WARN,
-                "info" => filter::LevelFilter::INFO,
-                "debug" => filter::LevelFilter::DEBUG,
-                "trace" => filter::LevelFilter::TRACE,
-                val => {
-                    panic!("ignored unknown log level: '{val}'");
-                }
+    let (args, links) = deep_link::split_args(std::env::args());
+    let cli = Cli::parse_from(args);
+    if cli.timing {
+        startup::enable_timing(start);
+        startup::phase("logging and arguments");
+    }
+
+    if let Some(log_level) = cli.log_level.as_deref() {
+        log::set_default_log_level(match log_level.to_lowercase().as_str() {
+            "off" => filter::LevelFilter::OFF,
+            "error" => filter::LevelFilter::ERROR,
+            "warn" => filter::LevelFilter::WARN,
+            "info" => filter::LevelFilter::INFO,
+            "debug" => filter::LevelFilter::DEBUG,
+            "trace" => filter::LevelFilter::TRACE,
+            val => {
+                panic!("ignored unknown log level: '{val}'");
             }
-        }) {
-            error!("Failed to modify log level: {e}");
-        };
+        });
+    }
+
+    if let Some(address) = cli.collab_relay.as_deref() {
+        if let Err(e) = crate::collab::relay::run(address) {
+            eprintln!("Failed to relay collaboration sessions: {e}");
+        }
+        return;
+    }
+
+    let paths = cli.diff.clone().unwrap_or_else(|| cli.paths.clone());
+    // A new window is a new Lapce, as the one which is running can't open
+    // windows anymore, and it takes over the socket from it
+    let new = cli.new || cli.new_window;
+    // Waiting is for the files, and otherwise it keeps this process in the
+    // terminal until Lapce is closed, as it does with `--new`
+    let wait_for_files = cli.wait && !cli.new && paths.iter().any(|p| !p.is_dir);
+    let mut messages = links
+        .iter()
+        .map(|url| CoreNotification::OpenLink { url: url.clone() })
+        .collect::<Vec<_>>();
+    if !paths.is_empty() || links.is_empty() {
+        messages.push(CoreNotification::OpenPaths {
+            paths,
+            reuse_window: cli.reuse_window,
+            diff: cli.diff.is_some(),
+            wait: wait_for_files,
+        });
     }
 
     // small hack to unblock terminal if launched from it
-    // launch it as a separate process that waits
-    if !cli.wait {
-        let mut args = std::env::args().collect::<Vec<_>>();
-        args.push("--wait".to_string());
-        let mut cmd = std::process::Command::new(&args[0]);
+    // hand the paths to the running Lapce or launch it as a separate process,
+    // unless the timing is asked for, which is printed to the terminal
+    if !cli.detached && !cli.timing && (!cli.wait || wait_for_files) {
+        if !new && open_in_existing_process(&messages) {
+            return;
+        }
+
+        let args = detached_args(
+            std::env::args().skip(1),
+            cli.log_level.as_deref(),
+            wait_for_files,
+        );
+        let Some(program) = std::env::args().next() else {
+            return;
+        };
+        let socket_before = socket_modified();
+        let mut cmd = std::process::Command::new(program);
         #[cfg(target_os = "windows")]
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
         if let Err(why) = cmd
-            .args(&args[1..])
+            .args(&args)
             .stderr(Stdio::null())
             .stdout(Stdio::null())
             .spawn()
         {
             eprintln!("Failed to launch lapce: {why}");
+            return;
         };
+
+        if wait_for_files {
+            // A running Lapce listens on the socket until the new one replaces
+            // it
+            let started = (0..100).any(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                (socket_before.is_none() || socket_modified() != socket_before)
+                    && get_socket().is_ok()
+            });
+            if !started || !open_in_existing_process(&messages) {
+                eprintln!("Failed to open path(s) in the new lapce");
+            }
+        }
         return;
     }
 
-    if !cli.new {
-        if let Ok(socket) = get_socket() {
-            if let Err(e) = try_open_in_existing_process(socket, &cli.paths) {
-                error!("failed to open path(s): {e}");
-            };
-            return;
-        }
+    // The detached Lapce was started because the paths couldn't go to one which
+    // is running, or to have a window of its own
+    if !new && !cli.detached && open_in_existing_process(&messages) {
+        return;
     }
 
     #[cfg(feature = "updater")]
@@ -2589,6 +3876,7 @@ pub fn launch() {
 
     let _ = lapce_proxy::register_lapce_path();
     let db = Arc::new(LapceDb::new().unwrap());
+    startup::phase("database");
     let mut app = floem::Application::new();
     let scope = app.scope();
     provide_context(scope, db.clone());
@@ -2609,6 +3897,15 @@ pub fn launch() {
         latest_release.read_only(),
         app_command,
     );
+    if let Some([left, right]) = cli.diff.as_deref() {
+        let window_tab = windows.iter().next().and_then(|w| w.active_window_tab());
+        if let Some(window_tab) = window_tab {
+            window_tab
+                .main_split
+                .open_diff(left.path.clone(), right.path.clone());
+        }
+    }
+    startup::phase("windows");
 
     let (tx, rx) = crossbeam_channel::bounded(1);
     let mut watcher = notify::recommended_watcher(ConfigWatcher::new(tx)).unwrap();
@@ -2618,6 +3915,9 @@ pub fn launch() {
     if let Some(path) = Directory::themes_directory() {
         let _ = watcher.watch(&path, notify::RecursiveMode::Recursive);
     }
+    if let Some(path) = Directory::profiles_directory() {
+        let _ = watcher.watch(&path, notify::RecursiveMode::Recursive);
+    }
     if let Some(path) = LapceConfig::keymaps_file() {
         let _ = watcher.watch(&path, notify::RecursiveMode::Recursive);
     }
@@ -2635,6 +3935,9 @@ pub fn launch() {
         app_command,
         tracing_handle: reload_handle,
     };
+    for link in &links {
+        app_data.open_link(link);
+    }
 
     {
         let app_data = app_data.clone();
@@ -2669,10 +3972,8 @@ pub fn launch() {
         let notification = create_signal_from_channel(scope, rx);
         let app_data = app_data.clone();
         create_effect(scope, move |_| {
-            if let Some(CoreNotification::OpenPaths { paths }) = notification.get() {
-                if let Some(window_tab) = app_data.active_window_tab() {
-                    window_tab.open_paths(&paths);
-                }
+            if let Some(request) = notification.get() {
+                app_data.open_request(request);
             }
         });
         std::thread::spawn(move || {
@@ -2687,6 +3988,7 @@ pub fn launch() {
         });
     }
 
+    startup::phase("app");
     app.on_event(move |event| match event {
         floem::AppEvent::WillTerminate => {
             let _ = db.insert_app(app_data.clone());
@@ -2695,6 +3997,25 @@ pub fn launch() {
     .run();
 }
 
+/// The arguments of the Lapce which is started detached from the terminal. It
+/// gets the arguments of this one, but when this one waits for the files they're
+/// sent to it once it listens instead, so it only gets the flags which set it up.
+fn detached_args(
+    args: impl Iterator<Item = String>,
+    log_level: Option<&str>,
+    wait_for_files: bool,
+) -> Vec<String> {
+    let mut args = if wait_for_files {
+        log_level
+            .map(|level| vec!["--log-level".to_string(), level.to_string()])
+            .unwrap_or_default()
+    } else {
+        args.collect()
+    };
+    args.push("--detached".to_string());
+    args
+}
+
 #[allow(clippy::too_many_arguments)]
 fn create_windows(
     scope: floem::reactive::Scope,
@@ -2872,35 +4193,92 @@ pub fn get_socket() -> Result<interprocess::local_socket::LocalSocketStream> {
     Ok(socket)
 }
 
+/// When the socket which Lapce listens on was created, where there's a file for
+/// it.
+fn socket_modified() -> Option<std::time::SystemTime> {
+    let local_socket = Directory::local_socket()?;
+    std::fs::metadata(local_socket).ok()?.modified().ok()
+}
+
+/// Whether the paths and the links went to the Lapce which is already running.
+/// Only one which can't be connected to is taken as gone, and a new one is
+/// started instead, which takes over the socket, as once the paths are sent the
+/// running one may still open them.
+fn open_in_existing_process(messages: &[CoreNotification]) -> bool {
+    for open in messages {
+        let Ok(socket) = get_socket() else {
+            return false;
+        };
+        if let Err(e) = try_open_in_existing_process(socket, open) {
+            error!("failed to open path(s) in the running lapce: {e}");
+            return false;
+        }
+    }
+    true
+}
+
+/// Send the paths to the Lapce which is already running, and wait for it to
+/// close the files when the paths are opened with `wait`. It only fails when the
+/// paths can't be sent.
 pub fn try_open_in_existing_process(
     mut socket: interprocess::local_socket::LocalSocketStream,
-    paths: &[PathObject],
+    open: &CoreNotification,
 ) -> Result<()> {
-    let msg: CoreMessage = RpcMessage::Notification(CoreNotification::OpenPaths {
-        paths: paths.to_vec(),
-    });
+    let wait = matches!(open, CoreNotification::OpenPaths { wait: true, .. });
+    let msg: CoreMessage = RpcMessage::Notification(open.clone());
     lapce_rpc::stdio::write_msg(&mut socket, msg)?;
 
-    let (tx, rx) = crossbeam_channel::bounded(1);
+    let (tx, rx) = crossbeam_channel::bounded(2);
     std::thread::spawn(move || {
         let mut buf = [0; 100];
-        let received = if let Ok(n) = socket.read(&mut buf) {
-            &buf[..n] == b"received"
+        let (received, closed) = if let Ok(n) = socket.read(&mut buf) {
+            (
+                buf[..n].starts_with(b"received"),
+                buf[..n].ends_with(b"closed"),
+            )
         } else {
-            false
+            (false, false)
         };
-        tx.send(received)
+        let _ = tx.send(received);
+        if received && wait && !closed {
+            // The files are closed once Lapce writes again, or when it's
+            // closed itself
+            let _ = socket.read(&mut buf);
+        }
+        let _ = tx.send(true);
     });
 
-    let received = rx.recv_timeout(std::time::Duration::from_millis(500))?;
+    let received = rx
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .unwrap_or(false);
     if !received {
-        return Err(anyhow!("didn't receive response"));
+        warn!("the running lapce didn't answer after the path(s) were sent");
+        return Ok(());
+    }
+    if wait {
+        let _ = rx.recv();
     }
 
     Ok(())
 }
 
-fn listen_local_socket(tx: Sender<CoreNotification>) -> Result<()> {
+fn local_workspace(path: Option<PathBuf>) -> LapceWorkspace {
+    LapceWorkspace {
+        kind: LapceWorkspaceType::Local,
+        path,
+        last_open: 0,
+    }
+}
+
+/// The paths which another `lapce` process sent, with the channel which tells it
+/// that the files are closed when it waits for that.
+#[derive(Clone)]
+struct OpenRequest {
+    notification: CoreNotification,
+    closed: Option<Sender<()>>,
+}
+
+fn listen_local_socket(tx: Sender<OpenRequest>) -> Result<()> {
     let local_socket = Directory::local_socket()
         .ok_or_else(|| anyhow!("can't get local socket folder"))?;
     let _ = std::fs::remove_file(&local_socket);
@@ -2914,8 +4292,20 @@ fn listen_local_socket(tx: Sender<CoreNotification>) -> Result<()> {
             loop {
                 let msg: CoreMessage = lapce_rpc::stdio::read_msg(&mut reader)?;
 
-                if let RpcMessage::Notification(msg) = msg {
-                    tx.send(msg)?;
+                let mut closed = None;
+                if let RpcMessage::Notification(notification) = msg {
+                    let (closed_tx, closed_rx) = crossbeam_channel::bounded(1);
+                    let wait = matches!(
+                        notification,
+                        CoreNotification::OpenPaths { wait: true, .. }
+                    );
+                    if wait {
+                        closed = Some(closed_rx);
+                    }
+                    tx.send(OpenRequest {
+                        notification,
+                        closed: wait.then_some(closed_tx),
+                    })?;
                 } else {
                     trace!("Unhandled message: {msg:?}");
                 }
@@ -2923,8 +4313,40 @@ fn listen_local_socket(tx: Sender<CoreNotification>) -> Result<()> {
                 let stream_ref = reader.get_mut();
                 let _ = stream_ref.write_all(b"received");
                 let _ = stream_ref.flush();
+
+                if let Some(closed) = closed {
+                    // The sender goes away along with the window tab, which
+                    // closes the files as well
+                    let _ = closed.recv();
+                    let _ = stream_ref.write_all(b"closed");
+                    let _ = stream_ref.flush();
+                }
             }
         });
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detached_args() {
+        let args = || {
+            ["--log-level", "debug", "--new-window", "a.rs"]
+                .into_iter()
+                .map(String::from)
+        };
+        assert_eq!(
+            detached_args(args(), Some("debug"), false),
+            ["--log-level", "debug", "--new-window", "a.rs", "--detached"]
+        );
+        // The files are sent to the new Lapce instead
+        assert_eq!(
+            detached_args(args(), Some("debug"), true),
+            ["--log-level", "debug", "--detached"]
+        );
+        assert_eq!(detached_args(args(), None, true), ["--detached"]);
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use floem::reactive::{
+    create_rw_signal, use_context, RwSignal, Scope, SignalGetUntracked, SignalSet,
+    SignalUpdate,
+};
+
+use crate::{config::LapceConfig, db::LapceDb};
+
+/// The text which is copied or cut in the editors, newest first, which is shared
+/// by the editors of a window tab so that any of them can paste it again.
+#[derive(Clone, Copy)]
+pub struct ClipboardHistory {
+    pub items: RwSignal<im::Vector<String>>,
+    /// The text which is selected in an editor at the moment, which a middle
+    /// click pastes on Linux, like the primary selection of X11 and Wayland
+    pub primary_selection: RwSignal<Option<String>>,
+    scope: Scope,
+}
+
+impl ClipboardHistory {
+    pub fn new(cx: Scope, config: &LapceConfig) -> Self {
+        let items = if config.editor.persist_clipboard_history {
+            let db: Arc<LapceDb> = use_context(cx).unwrap();
+            db.get_clipboard_history().unwrap_or_default()
+        } else {
+            im::Vector::new()
+        };
+        Self {
+            items: create_rw_signal(cx, items),
+            primary_selection: create_rw_signal(cx, None),
+            scope: cx,
+        }
+    }
+
+    /// Add the text to the top of the history, and save the history if that's
+    /// turned on.
+    pub fn push(&self, content: String, config: &LapceConfig) {
+        let limit = config.editor.clipboard_history_size;
+        if limit == 0 {
+            return;
+        }
+        self.items
+            .update(|items| add_to_history(items, content, limit));
+        if config.editor.persist_clipboard_history {
+            let db: Arc<LapceDb> = use_context(self.scope).unwrap();
+            db.save_clipboard_history(self.items.get_untracked());
+        }
+    }
+
+    /// Set the primary selection, unless it's the same already.
+    pub fn set_primary_selection(&self, content: Option<String>) {
+        if self.primary_selection.get_untracked() != content {
+            self.primary_selection.set(content);
+        }
+    }
+}
+
+fn add_to_history(items: &mut im::Vector<String>, content: String, limit: usize) {
+    if content.trim().is_empty() {
+        return;
+    }
+    items.retain(|item| item != &content);
+    items.push_front(content);
+    items.truncate(limit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::add_to_history;
+
+    #[test]
+    fn test_add_to_history() {
+        let mut items = im::Vector::new();
+        add_to_history(&mut items, "a".to_string(), 2);
+        add_to_history(&mut items, "b".to_string(), 2);
+        add_to_history(&mut items, " \n".to_string(), 2);
+        assert_eq!(items, im::vector!["b".to_string(), "a".to_string()]);
+
+        // Copying the same text again moves it to the top
+        add_to_history(&mut items, "a".to_string(), 2);
+        assert_eq!(items, im::vector!["a".to_string(), "b".to_string()]);
+
+        add_to_history(&mut items, "c".to_string(), 2);
+        assert_eq!(items, im::vector!["c".to_string(), "a".to_string()]);
+    }
+}
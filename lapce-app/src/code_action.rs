@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use floem::{
     peniko::kurbo::Rect,
@@ -8,21 +8,176 @@
     },
 };
 use lapce_core::{command::FocusCommand, mode::Mode, movement::Movement};
-use lapce_rpc::plugin::PluginId;
-use lsp_types::CodeActionOrCommand;
+use lapce_rpc::{
+    plugin::PluginId,
+    proxy::{ProxyResponse, ProxyRpcHandler},
+};
+use lsp_types::{CodeActionKind, CodeActionOrCommand, Diagnostic, Range, TextEdit};
 
 use crate::{
     command::{CommandExecuted, CommandKind, InternalCommand},
     keypress::{condition::Condition, KeyPressFocus},
     window_tab::{CommonData, Focus},
+    workspace_edit::file_edits,
 };
 
+/// The kind of the source actions which remove the imports that aren't used,
+/// which isn't one of the kinds of the protocol yet
+pub const SOURCE_REMOVE_UNUSED_IMPORTS: &str = "source.removeUnusedImports";
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum CodeActionStatus {
     Inactive,
     Active,
 }
 
+/// The kinds of code actions which the menu puts together, in the order which
+/// it shows them in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CodeActionGroup {
+    QuickFix,
+    Refactor,
+    Source,
+    Other,
+}
+
+impl CodeActionGroup {
+    /// The group of the kind of an action, where `refactor.extract` is a
+    /// refactor, and the commands and the actions without a kind are others.
+    pub fn of(item: &CodeActionOrCommand) -> Self {
+        let kind = match item {
+            CodeActionOrCommand::CodeAction(action) => action.kind.as_ref(),
+            CodeActionOrCommand::Command(_) => None,
+        };
+        let Some(kind) = kind else {
+            return CodeActionGroup::Other;
+        };
+        let is = |group: CodeActionKind| is_kind_of(kind, &group);
+        if is(CodeActionKind::QUICKFIX) {
+            CodeActionGroup::QuickFix
+        } else if is(CodeActionKind::REFACTOR) {
+            CodeActionGroup::Refactor
+        } else if is(CodeActionKind::SOURCE) {
+            CodeActionGroup::Source
+        } else {
+            CodeActionGroup::Other
+        }
+    }
+
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            CodeActionGroup::QuickFix => "code-action-quick-fix",
+            CodeActionGroup::Refactor => "code-action-refactor",
+            CodeActionGroup::Source => "code-action-source",
+            CodeActionGroup::Other => "code-action-other",
+        }
+    }
+}
+
+/// Whether the kind is the other one or one under it, like `refactor.extract`
+/// is a `refactor`.
+pub fn is_kind_of(kind: &CodeActionKind, of: &CodeActionKind) -> bool {
+    kind.as_str()
+        .strip_prefix(of.as_str())
+        .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+fn is_preferred(item: &CodeActionOrCommand) -> bool {
+    match item {
+        CodeActionOrCommand::CodeAction(action) => {
+            action.is_preferred.unwrap_or(false)
+        }
+        CodeActionOrCommand::Command(_) => false,
+    }
+}
+
+/// The quick fix which is applied without a menu, which is the preferred one,
+/// or the only one there is.
+pub fn auto_fix(items: &[CodeActionOrCommand]) -> Option<&CodeActionOrCommand> {
+    let mut quick_fixes = items
+        .iter()
+        .filter(|item| CodeActionGroup::of(item) == CodeActionGroup::QuickFix);
+    if let Some(preferred) = quick_fixes.clone().find(|item| is_preferred(item)) {
+        return Some(preferred);
+    }
+    let first = quick_fixes.next()?;
+    quick_fixes.next().is_none().then_some(first)
+}
+
+/// The code action of the kind which is run without a menu, which is the
+/// preferred one, or else the first one. Language servers can answer with
+/// actions of other kinds than the ones which were asked for.
+pub fn source_action<'a>(
+    items: &'a [CodeActionOrCommand],
+    kind: &CodeActionKind,
+) -> Option<&'a CodeActionOrCommand> {
+    let mut actions = items.iter().filter(|item| match item {
+        CodeActionOrCommand::CodeAction(action) => action
+            .kind
+            .as_ref()
+            .map_or(false, |action_kind| is_kind_of(action_kind, kind)),
+        CodeActionOrCommand::Command(_) => false,
+    });
+    actions
+        .clone()
+        .find(|item| is_preferred(item))
+        .or_else(|| actions.next())
+}
+
+/// The edits of the file which the source action of the kind for the range of
+/// it makes, where the action is resolved when it comes without them. This
+/// blocks, and waits for each answer of the language server for a second.
+pub fn source_action_edits(
+    proxy: &ProxyRpcHandler,
+    path: PathBuf,
+    range: Range,
+    kind: CodeActionKind,
+    diagnostics: Vec<Diagnostic>,
+) -> Option<Vec<TextEdit>> {
+    let timeout = Duration::from_secs(1);
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    proxy.get_source_actions(
+        path.clone(),
+        range,
+        vec![kind.clone()],
+        diagnostics,
+        move |result| {
+            let _ = tx.send(result);
+        },
+    );
+    let Ok(ProxyResponse::GetCodeActionsResponse { plugin_id, resp }) =
+        rx.recv_timeout(timeout).ok()?
+    else {
+        return None;
+    };
+    let Some(CodeActionOrCommand::CodeAction(action)) =
+        source_action(&resp, &kind).cloned()
+    else {
+        return None;
+    };
+
+    let edit = match action.edit.clone() {
+        Some(edit) => edit,
+        None => {
+            let (tx, rx) = crossbeam_channel::bounded(1);
+            proxy.code_action_resolve(action, plugin_id, move |result| {
+                let _ = tx.send(result);
+            });
+            let Ok(ProxyResponse::CodeActionResolveResponse { item }) =
+                rx.recv_timeout(timeout).ok()?
+            else {
+                return None;
+            };
+            item.edit?
+        }
+    };
+    file_edits(&edit)
+        .ok()?
+        .into_iter()
+        .find(|file_edit| file_edit.path == path)
+        .map(|file_edit| file_edit.edits)
+}
+
 #[derive(Clone, PartialEq)]
 pub struct ScoredCodeActionItem {
     pub item: CodeActionOrCommand,
@@ -38,6 +193,10 @@ pub fn title(&self) -> &str {
             CodeActionOrCommand::CodeAction(c) => &c.title,
         }
     }
+
+    pub fn group(&self) -> CodeActionGroup {
+        CodeActionGroup::of(&self.item)
+    }
 }
 
 #[derive(Clone)]
@@ -165,20 +324,25 @@ pub fn previous_page(&self) {
         self.active.set(new);
     }
 
+    /// Show the actions of the group, or all of them when there's none, with
+    /// the preferred one selected.
     pub fn show(
         &mut self,
         code_actions: Arc<(PluginId, Vec<CodeActionOrCommand>)>,
         offset: usize,
         mouse_click: bool,
+        group: Option<CodeActionGroup>,
     ) {
-        self.active.set(0);
         self.status.set(CodeActionStatus::Active);
         self.offset = offset;
         self.mouse_click = mouse_click;
         self.request_id += 1;
-        self.items = code_actions
+        let mut items: Vec<ScoredCodeActionItem> = code_actions
             .1
             .iter()
+            .filter(|code_action| {
+                group.map_or(true, |group| CodeActionGroup::of(code_action) == group)
+            })
             .map(|code_action| ScoredCodeActionItem {
                 item: code_action.clone(),
                 plugin_id: code_actions.0,
@@ -186,10 +350,35 @@ pub fn show(
                 indices: Vec::new(),
             })
             .collect();
+        items.sort_by_key(|item| item.group());
+        self.items = items.into();
         self.filtered_items = self.items.clone();
+        self.active.set(
+            self.filtered_items
+                .iter()
+                .position(|item| is_preferred(&item.item))
+                .unwrap_or(0),
+        );
         self.common.focus.set(Focus::CodeAction);
     }
 
+    /// The group of the item when it's the first one of it, which has the name
+    /// of the group above it.
+    pub fn header(&self, index: usize) -> Option<CodeActionGroup> {
+        let group = self.filtered_items.get(index)?.group();
+        let previous = index
+            .checked_sub(1)
+            .and_then(|i| self.filtered_items.get(i))
+            .map(|item| item.group());
+        (previous != Some(group)).then_some(group)
+    }
+
+    /// The line of the menu which the item is on, after the names of the groups
+    /// above it.
+    pub fn row(&self, index: usize) -> usize {
+        index + (0..=index).filter(|i| self.header(*i).is_some()).count()
+    }
+
     fn cancel(&self) {
         self.status.set(CodeActionStatus::Inactive);
         self.common.focus.set(Focus::Workbench);
@@ -232,3 +421,70 @@ fn run_focus_command(&self, cmd: &FocusCommand) -> CommandExecuted {
         CommandExecuted::Yes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{CodeAction, Command};
+
+    use super::*;
+
+    fn action(kind: &str, is_preferred: bool) -> CodeActionOrCommand {
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: kind.to_string(),
+            kind: Some(CodeActionKind::from(kind.to_string())),
+            is_preferred: Some(is_preferred),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_code_action_group() {
+        let group = |kind| CodeActionGroup::of(&action(kind, false));
+        assert_eq!(group("quickfix"), CodeActionGroup::QuickFix);
+        assert_eq!(group("refactor.extract"), CodeActionGroup::Refactor);
+        assert_eq!(group("source.organizeImports"), CodeActionGroup::Source);
+        assert_eq!(group("sourcery"), CodeActionGroup::Other);
+        let command = CodeActionOrCommand::Command(Command {
+            title: String::new(),
+            command: String::new(),
+            arguments: None,
+        });
+        assert_eq!(CodeActionGroup::of(&command), CodeActionGroup::Other);
+    }
+
+    #[test]
+    fn test_auto_fix() {
+        let items = vec![
+            action("quickfix", false),
+            action("refactor", true),
+            action("quickfix", true),
+        ];
+        assert_eq!(auto_fix(&items), Some(&items[2]));
+
+        let items = vec![action("refactor", true), action("quickfix", false)];
+        assert_eq!(auto_fix(&items), Some(&items[1]));
+
+        let items = vec![action("quickfix", false), action("quickfix", false)];
+        assert_eq!(auto_fix(&items), None);
+    }
+
+    #[test]
+    fn test_source_action() {
+        let organize = CodeActionKind::SOURCE_ORGANIZE_IMPORTS;
+        let items = vec![
+            action("quickfix", true),
+            action("source.organizeImports.ts", false),
+            action("source.organizeImports", false),
+        ];
+        assert_eq!(source_action(&items, &organize), Some(&items[1]));
+
+        let items = vec![
+            action("source.organizeImports", false),
+            action("source.organizeImports", true),
+        ];
+        assert_eq!(source_action(&items, &organize), Some(&items[1]));
+
+        let items = vec![action("source.organizeImportsLater", true)];
+        assert_eq!(source_action(&items, &organize), None);
+    }
+}
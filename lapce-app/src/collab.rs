@@ -0,0 +1,635 @@
+pub mod connection;
+pub mod crdt;
+pub mod protocol;
+pub mod relay;
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use crossbeam_channel::Sender;
+use floem::{
+    ext_event::create_signal_from_channel,
+    peniko::Color,
+    reactive::{
+        create_rw_signal, ReadSignal, RwSignal, Scope, SignalGetUntracked,
+        SignalSet, SignalUpdate, SignalWithUntracked,
+    },
+};
+use lapce_rpc::terminal::TermId;
+use lapce_xi_rope::{DeltaElement, RopeDelta};
+
+use self::{
+    connection::{CollabEvent, Peers, SharedTerminals},
+    crdt::{CharId, CrdtOp, CrdtSnapshot, CrdtText},
+    protocol::{
+        new_token, CollabMessage, Invite, Participant, ParticipantId, Permissions,
+        SharedTerminal, HOST,
+    },
+};
+use crate::{config::LapceConfig, workspace::LapceWorkspace};
+
+/// The root of the paths of the files which a guest has open from the host, which
+/// keeps them apart from the files on the disk
+const GUEST_ROOT: &str = "@collab";
+
+/// The colors of the cursors of the participants
+const PARTICIPANT_COLORS: [Color; 6] = [
+    Color::rgb8(0xe0, 0x6c, 0x75),
+    Color::rgb8(0x98, 0xc3, 0x79),
+    Color::rgb8(0xe5, 0xc0, 0x7b),
+    Color::rgb8(0x61, 0xaf, 0xef),
+    Color::rgb8(0xc6, 0x78, 0xdd),
+    Color::rgb8(0x56, 0xb6, 0xc2),
+];
+
+/// Whether the path is of a file which a guest has open from the host.
+pub fn is_guest_path(path: &Path) -> bool {
+    path.starts_with(GUEST_ROOT)
+}
+
+pub fn participant_color(participant: ParticipantId) -> Color {
+    PARTICIPANT_COLORS[participant as usize % PARTICIPANT_COLORS.len()]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollabRole {
+    Host,
+    Guest,
+}
+
+#[derive(Clone)]
+pub struct CollabSession {
+    pub role: CollabRole,
+    /// The id of this participant, which a guest gets once it's welcomed
+    pub participant: Option<ParticipantId>,
+    /// The invite of the session, once it's shared
+    pub invite: Option<Invite>,
+    pub participants: im::Vector<Participant>,
+    /// The files which can be opened, relative to the workspace of the host
+    pub files: im::Vector<PathBuf>,
+    pub terminals: im::Vector<SharedTerminal>,
+    token: String,
+    peers: Peers,
+}
+
+impl CollabSession {
+    pub fn participant(&self, id: ParticipantId) -> Option<&Participant> {
+        self.participants.iter().find(|p| p.id == id)
+    }
+
+    /// The permissions of this participant.
+    pub fn permissions(&self) -> Option<Permissions> {
+        self.participant(self.participant?).map(|p| p.permissions)
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.role == CollabRole::Host
+    }
+}
+
+/// Where the cursor of a participant is, in a file relative to the workspace of
+/// the host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollabCursor {
+    pub path: PathBuf,
+    pub anchor: Option<CharId>,
+    pub selection: Vec<(Option<CharId>, Option<CharId>)>,
+    /// The start of the first line which the participant sees
+    pub top: Option<CharId>,
+}
+
+/// A range which a participant pinged, which is highlighted for a while.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollabPing {
+    pub id: u64,
+    pub participant: ParticipantId,
+    pub path: PathBuf,
+    pub start: Option<CharId>,
+    pub end: Option<CharId>,
+}
+
+/// The cursor of another participant in a document which is open here.
+pub struct RemoteCursor {
+    pub participant: ParticipantId,
+    pub offset: usize,
+    pub selection: Vec<Range<usize>>,
+}
+
+/// The collaboration session which the workspace is shared in, or which is
+/// joined from it.
+#[derive(Clone)]
+pub struct CollabData {
+    pub session: RwSignal<Option<CollabSession>>,
+    pub cursors: RwSignal<im::HashMap<ParticipantId, CollabCursor>>,
+    /// The participant whose cursor is followed
+    pub following: RwSignal<Option<ParticipantId>>,
+    pub pings: RwSignal<im::Vector<CollabPing>>,
+    pub event: ReadSignal<Option<CollabEvent>>,
+    events: Sender<CollabEvent>,
+    pub shared_terminals: SharedTerminals,
+    /// The texts of the shared files which are open, by their path relative to
+    /// the workspace of the host
+    texts: Rc<RefCell<HashMap<PathBuf, CrdtText>>>,
+    /// The terminals which a guest shows, by the id of the host's terminal
+    guest_terminals: Rc<RefCell<HashMap<TermId, TermId>>>,
+    /// Whether the edits which are made are another participant's, which aren't
+    /// sent back
+    applying_remote: Rc<Cell<bool>>,
+    last_cursor: Rc<RefCell<Option<CollabCursor>>>,
+    next_ping: Rc<Cell<u64>>,
+    workspace: Arc<LapceWorkspace>,
+}
+
+impl CollabData {
+    pub fn new(cx: Scope, workspace: Arc<LapceWorkspace>) -> Self {
+        let (events, rx) = crossbeam_channel::unbounded();
+        Self {
+            session: create_rw_signal(cx, None),
+            cursors: create_rw_signal(cx, im::HashMap::new()),
+            following: create_rw_signal(cx, None),
+            pings: create_rw_signal(cx, im::Vector::new()),
+            event: create_signal_from_channel(cx, rx),
+            events,
+            shared_terminals: SharedTerminals::default(),
+            texts: Rc::new(RefCell::new(HashMap::new())),
+            guest_terminals: Rc::new(RefCell::new(HashMap::new())),
+            applying_remote: Rc::new(Cell::new(false)),
+            last_cursor: Rc::new(RefCell::new(None)),
+            next_ping: Rc::new(Cell::new(0)),
+            workspace,
+        }
+    }
+
+    pub fn role(&self) -> Option<CollabRole> {
+        self.session
+            .with_untracked(|session| session.as_ref().map(|s| s.role))
+    }
+
+    /// The name which the others see, which is the user's unless it's set.
+    pub fn name(config: &LapceConfig) -> String {
+        if !config.core.collab_name.is_empty() {
+            return config.core.collab_name.clone();
+        }
+        std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "Lapce".to_string())
+    }
+
+    /// Share the workspace, on the address of the settings or through the relay.
+    pub fn host(&self, config: &LapceConfig) {
+        if self.role().is_some() {
+            return;
+        }
+        let peers = Peers::default();
+        let token = new_token();
+        self.shared_terminals.start(peers.clone());
+        self.session.set(Some(CollabSession {
+            role: CollabRole::Host,
+            participant: Some(HOST),
+            invite: None,
+            participants: im::vector![Participant {
+                id: HOST,
+                name: Self::name(config),
+                permissions: Permissions {
+                    edit: true,
+                    terminal: true,
+                },
+            }],
+            files: im::Vector::new(),
+            terminals: im::Vector::new(),
+            token: token.clone(),
+            peers: peers.clone(),
+        }));
+        let relay = (!config.core.collab_relay.is_empty())
+            .then(|| config.core.collab_relay.clone());
+        connection::host(
+            config.core.collab_address.clone(),
+            relay,
+            token,
+            peers,
+            self.events.clone(),
+        );
+    }
+
+    pub fn join(&self, invite: Invite, config: &LapceConfig) {
+        if self.role().is_some() {
+            return;
+        }
+        let peers = Peers::default();
+        self.session.set(Some(CollabSession {
+            role: CollabRole::Guest,
+            participant: None,
+            invite: Some(invite.clone()),
+            participants: im::Vector::new(),
+            files: im::Vector::new(),
+            terminals: im::Vector::new(),
+            token: invite.token.clone(),
+            peers: peers.clone(),
+        }));
+        connection::join(invite, Self::name(config), peers, self.events.clone());
+    }
+
+    /// Stop sharing the workspace, or leave the session.
+    pub fn stop(&self) {
+        let Some(session) = self.session.get_untracked() else {
+            return;
+        };
+        session.peers.stop();
+        self.shared_terminals.stop();
+        self.texts.borrow_mut().clear();
+        self.guest_terminals.borrow_mut().clear();
+        *self.last_cursor.borrow_mut() = None;
+        self.cursors.set(im::HashMap::new());
+        self.following.set(None);
+        self.pings.set(im::Vector::new());
+        self.session.set(None);
+    }
+
+    /// Whether the token is the one of the session which is shared.
+    pub fn is_token(&self, token: &str) -> bool {
+        self.session.with_untracked(|session| {
+            session
+                .as_ref()
+                .map_or(false, |session| session.token == token)
+        })
+    }
+
+    /// Send the message to the host, or to all the guests from the host.
+    pub fn send(&self, message: CollabMessage) {
+        self.session.with_untracked(|session| match session {
+            Some(session) if session.is_host() => {
+                session.peers.broadcast(message, None)
+            }
+            Some(session) => session.peers.send(HOST, message),
+            None => {}
+        });
+    }
+
+    /// Send the message to one of the guests.
+    pub fn send_to(&self, participant: ParticipantId, message: CollabMessage) {
+        self.session.with_untracked(|session| {
+            if let Some(session) = session {
+                session.peers.send(participant, message);
+            }
+        });
+    }
+
+    /// Send the message to the guests other than the one it's from.
+    pub fn forward(&self, from: ParticipantId, message: CollabMessage) {
+        self.session.with_untracked(|session| {
+            if let Some(session) = session {
+                session.peers.broadcast(message, Some(from));
+            }
+        });
+    }
+
+    /// Close the connection of the guest.
+    pub fn close(&self, participant: ParticipantId) {
+        self.session.with_untracked(|session| {
+            if let Some(session) = session {
+                session.peers.close(participant);
+            }
+        });
+    }
+
+    /// The path of the file relative to the workspace of the host, when it's a
+    /// file of the session.
+    pub fn shared_path(&self, path: &Path) -> Option<PathBuf> {
+        let root = match self.role()? {
+            CollabRole::Host => self.workspace.path.clone()?,
+            CollabRole::Guest => PathBuf::from(GUEST_ROOT),
+        };
+        path.strip_prefix(root).ok().map(|path| path.to_path_buf())
+    }
+
+    /// Where the file of the session is opened from here.
+    pub fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        match self.role()? {
+            CollabRole::Host => Some(self.workspace.path.as_ref()?.join(path)),
+            CollabRole::Guest => Some(PathBuf::from(GUEST_ROOT).join(path)),
+        }
+    }
+
+    /// Send the edit of the document to the others, when its file is shared.
+    pub fn local_edit(&self, path: &Path, delta: &RopeDelta) {
+        if self.applying_remote.get() {
+            return;
+        }
+        let Some(shared) = self.shared_path(path) else {
+            return;
+        };
+        let ops = {
+            let mut texts = self.texts.borrow_mut();
+            let Some(text) = texts.get_mut(&shared) else {
+                return;
+            };
+            let mut ops = Vec::new();
+            // From the last edit, so that the ranges of the others stay the same
+            for (range, inserted) in delta_edits(delta).into_iter().rev() {
+                ops.extend(text.delete(range.clone()));
+                ops.extend(text.insert(range.start, &inserted));
+            }
+            ops
+        };
+        if !ops.is_empty() {
+            self.send(CollabMessage::Ops { path: shared, ops });
+        }
+    }
+
+    /// Apply the ops of another participant to the text of the file, and return
+    /// the edits which each of them makes.
+    pub fn apply_ops(
+        &self,
+        path: &Path,
+        ops: &[CrdtOp],
+    ) -> Vec<Vec<(Range<usize>, String)>> {
+        let mut texts = self.texts.borrow_mut();
+        let Some(text) = texts.get_mut(path) else {
+            return Vec::new();
+        };
+        ops.iter().map(|op| text.apply(op)).collect()
+    }
+
+    /// Make the edits of another participant to the documents, which aren't sent
+    /// back to the others.
+    pub fn apply_remote(&self, f: impl FnOnce()) {
+        self.applying_remote.set(true);
+        f();
+        self.applying_remote.set(false);
+    }
+
+    pub fn is_shared(&self, path: &Path) -> bool {
+        self.texts.borrow().contains_key(path)
+    }
+
+    /// The snapshot of the file for a guest, which starts sharing its text when
+    /// it isn't yet.
+    pub fn share_file(
+        &self,
+        path: &Path,
+        content: impl FnOnce() -> String,
+    ) -> CrdtSnapshot {
+        self.texts
+            .borrow_mut()
+            .entry(path.to_path_buf())
+            .or_insert_with(|| CrdtText::new(HOST, &content()))
+            .snapshot()
+    }
+
+    /// Keep the text of the file from the host, and return the content of it.
+    pub fn open_file(
+        &self,
+        path: PathBuf,
+        participant: ParticipantId,
+        snapshot: CrdtSnapshot,
+    ) -> String {
+        let text = CrdtText::from_snapshot(participant, snapshot);
+        let content = text.text();
+        self.texts.borrow_mut().insert(path, text);
+        content
+    }
+
+    /// Stop sharing the texts of the files which aren't open anymore.
+    pub fn retain_files(&self, files: &HashSet<PathBuf>) {
+        self.texts
+            .borrow_mut()
+            .retain(|path, _| files.contains(path));
+    }
+
+    fn local_participant(&self) -> Option<ParticipantId> {
+        self.session
+            .with_untracked(|session| session.as_ref().and_then(|s| s.participant))
+    }
+
+    /// Send where the cursor of the editor is to the others, along with what's
+    /// selected and the offset of the first line which is seen.
+    pub fn local_cursor(
+        &self,
+        path: &Path,
+        offset: usize,
+        selection: &[Range<usize>],
+        top: usize,
+    ) {
+        let Some(participant) = self.local_participant() else {
+            return;
+        };
+        let Some(shared) = self.shared_path(path) else {
+            return;
+        };
+        let cursor = {
+            let texts = self.texts.borrow();
+            let Some(text) = texts.get(&shared) else {
+                return;
+            };
+            CollabCursor {
+                path: shared,
+                anchor: text.anchor(offset),
+                selection: selection
+                    .iter()
+                    .map(|range| (text.anchor(range.start), text.anchor(range.end)))
+                    .collect(),
+                top: text.anchor(top),
+            }
+        };
+        if self.last_cursor.borrow().as_ref() == Some(&cursor) {
+            return;
+        }
+        *self.last_cursor.borrow_mut() = Some(cursor.clone());
+        self.send(CollabMessage::Cursor {
+            participant,
+            path: cursor.path,
+            anchor: cursor.anchor,
+            selection: cursor.selection,
+            top: cursor.top,
+        });
+    }
+
+    /// Send the range of the document to the others to draw their attention to
+    /// it, and return the ping to show here too.
+    pub fn local_ping(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> Option<CollabPing> {
+        let participant = self.local_participant()?;
+        let shared = self.shared_path(path)?;
+        let (start, end) = {
+            let texts = self.texts.borrow();
+            let text = texts.get(&shared)?;
+            (text.anchor(range.start), text.anchor(range.end))
+        };
+        self.send(CollabMessage::Ping {
+            participant,
+            path: shared.clone(),
+            start,
+            end,
+        });
+        Some(self.new_ping(participant, shared, start, end))
+    }
+
+    pub fn new_ping(
+        &self,
+        participant: ParticipantId,
+        path: PathBuf,
+        start: Option<CharId>,
+        end: Option<CharId>,
+    ) -> CollabPing {
+        let id = self.next_ping.get();
+        self.next_ping.set(id + 1);
+        CollabPing {
+            id,
+            participant,
+            path,
+            start,
+            end,
+        }
+    }
+
+    pub fn set_cursor(&self, participant: ParticipantId, cursor: CollabCursor) {
+        self.cursors.update(|cursors| {
+            cursors.insert(participant, cursor);
+        });
+    }
+
+    /// The offset of the anchor in the file, relative to the workspace of the
+    /// host, when its text is shared.
+    pub fn anchor_offset(
+        &self,
+        path: &Path,
+        anchor: Option<CharId>,
+    ) -> Option<usize> {
+        self.texts
+            .borrow()
+            .get(path)
+            .map(|text| text.offset_of_anchor(anchor))
+    }
+
+    /// The cursors of the others in the file.
+    pub fn remote_cursors(&self, path: &Path) -> Vec<RemoteCursor> {
+        let Some(shared) = self.shared_path(path) else {
+            return Vec::new();
+        };
+        let texts = self.texts.borrow();
+        let Some(text) = texts.get(&shared) else {
+            return Vec::new();
+        };
+        self.cursors.with_untracked(|cursors| {
+            cursors
+                .iter()
+                .filter(|(_, cursor)| cursor.path == shared)
+                .map(|(id, cursor)| RemoteCursor {
+                    participant: *id,
+                    offset: text.offset_of_anchor(cursor.anchor),
+                    selection: cursor
+                        .selection
+                        .iter()
+                        .map(|(start, end)| {
+                            text.offset_of_anchor(*start)
+                                ..text.offset_of_anchor(*end)
+                        })
+                        .collect(),
+                })
+                .collect()
+        })
+    }
+
+    /// The ranges of the file which are pinged, by who pinged them.
+    pub fn ping_ranges(&self, path: &Path) -> Vec<(ParticipantId, Range<usize>)> {
+        let Some(shared) = self.shared_path(path) else {
+            return Vec::new();
+        };
+        let texts = self.texts.borrow();
+        let Some(text) = texts.get(&shared) else {
+            return Vec::new();
+        };
+        self.pings.with_untracked(|pings| {
+            pings
+                .iter()
+                .filter(|ping| ping.path == shared)
+                .map(|ping| {
+                    (
+                        ping.participant,
+                        text.offset_of_anchor(ping.start)
+                            ..text.offset_of_anchor(ping.end),
+                    )
+                })
+                .collect()
+        })
+    }
+
+    /// The terminal here which shows the terminal of the host.
+    pub fn guest_terminal(&self, host: TermId) -> Option<TermId> {
+        self.guest_terminals.borrow().get(&host).copied()
+    }
+
+    pub fn set_guest_terminal(&self, host: TermId, term_id: Option<TermId>) {
+        let mut terminals = self.guest_terminals.borrow_mut();
+        match term_id {
+            Some(term_id) => terminals.insert(host, term_id),
+            None => terminals.remove(&host),
+        };
+    }
+
+    /// Send what's typed in a terminal of the host, when it can be typed in.
+    pub fn terminal_input(&self, term_id: TermId, content: &str) {
+        let can_type = self.session.with_untracked(|session| {
+            session
+                .as_ref()
+                .and_then(|session| session.permissions())
+                .map_or(false, |permissions| permissions.terminal)
+        });
+        if can_type {
+            self.send(CollabMessage::TerminalInput {
+                term_id,
+                content: content.to_string(),
+            });
+        }
+    }
+}
+
+/// The edits of the delta, which are the byte ranges of the text before it that
+/// are replaced along with what they're replaced with, in order.
+fn delta_edits(delta: &RopeDelta) -> Vec<(Range<usize>, String)> {
+    let mut edits = Vec::new();
+    let mut offset = 0;
+    let mut inserted = String::new();
+    for el in &delta.els {
+        match el {
+            DeltaElement::Copy(start, end) => {
+                if *start > offset || !inserted.is_empty() {
+                    edits.push((offset..*start, std::mem::take(&mut inserted)));
+                }
+                offset = *end;
+            }
+            DeltaElement::Insert(rope) => inserted.push_str(&rope.slice_to_cow(..)),
+        }
+    }
+    if delta.base_len > offset || !inserted.is_empty() {
+        edits.push((offset..delta.base_len, inserted));
+    }
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::{buffer::Buffer, editor::EditType};
+
+    use super::*;
+
+    #[test]
+    fn test_delta_edits() {
+        let mut buffer = Buffer::new("hello world");
+        let (delta, _, _) =
+            buffer.edit_ranges([(0..5, "bye"), (11..11, "!")], EditType::Other);
+        assert_eq!(
+            delta_edits(&delta),
+            vec![(0..5, "bye".to_string()), (11..11, "!".to_string())]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! The connections of a collaboration session. Each connection has a thread which
+//! reads its messages into the events of the session, and one which writes the
+//! messages that are sent to it.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, BufReader, BufWriter},
+    net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use crossbeam_channel::Sender;
+use lapce_rpc::terminal::TermId;
+use parking_lot::Mutex;
+
+use super::protocol::{
+    read_message, write_message, CollabMessage, Invite, ParticipantId, RelayMessage,
+    HOST,
+};
+
+pub enum CollabEvent {
+    /// The session is shared, and can be joined with the invite
+    Hosted {
+        invite: Invite,
+    },
+    Message {
+        from: ParticipantId,
+        message: CollabMessage,
+    },
+    Disconnected {
+        participant: ParticipantId,
+    },
+    /// The session couldn't be shared or joined, or the connection to it was lost
+    Failed {
+        error: String,
+    },
+}
+
+struct Peer {
+    tx: Sender<CollabMessage>,
+    stream: TcpStream,
+}
+
+/// The connections of a session, which are the guests for the host and the host
+/// for a guest.
+#[derive(Clone, Default)]
+pub struct Peers {
+    peers: Arc<Mutex<HashMap<ParticipantId, Peer>>>,
+    /// The connections which aren't to a participant, like the control connection
+    /// of a relay, which are closed when the session stops
+    others: Arc<Mutex<Vec<TcpStream>>>,
+    next: Arc<AtomicU64>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Peers {
+    pub fn send(&self, to: ParticipantId, message: CollabMessage) {
+        if let Some(peer) = self.peers.lock().get(&to) {
+            let _ = peer.tx.send(message);
+        }
+    }
+
+    pub fn broadcast(&self, message: CollabMessage, except: Option<ParticipantId>) {
+        for (id, peer) in self.peers.lock().iter() {
+            if Some(*id) != except {
+                let _ = peer.tx.send(message.clone());
+            }
+        }
+    }
+
+    /// Close the connection once what was sent to it is written.
+    pub fn close(&self, participant: ParticipantId) {
+        self.peers.lock().remove(&participant);
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        for (_, peer) in self.peers.lock().drain() {
+            let _ = peer.stream.shutdown(Shutdown::Both);
+        }
+        for stream in self.others.lock().drain(..) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    fn next_guest(&self) -> ParticipantId {
+        HOST + 1 + self.next.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn connect(
+        &self,
+        stream: TcpStream,
+        participant: ParticipantId,
+        events: Sender<CollabEvent>,
+    ) -> io::Result<()> {
+        let (tx, rx) = crossbeam_channel::unbounded::<CollabMessage>();
+        let writer = stream.try_clone()?;
+        let reader = stream.try_clone()?;
+        self.peers.lock().insert(participant, Peer { tx, stream });
+
+        thread::spawn(move || {
+            let mut buffered = BufWriter::new(&writer);
+            for message in rx {
+                if write_message(&mut buffered, &message).is_err() {
+                    break;
+                }
+            }
+            drop(buffered);
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+
+        let peers = self.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            while let Ok(Some(message)) = read_message(&mut reader) {
+                let event = CollabEvent::Message {
+                    from: participant,
+                    message,
+                };
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+            peers.close(participant);
+            let _ = events.send(CollabEvent::Disconnected { participant });
+        });
+        Ok(())
+    }
+}
+
+/// The terminals of the host which are shared. Their output is sent to the
+/// guests from the thread which gets it from the proxy, rather than going through
+/// the UI.
+#[derive(Clone, Default)]
+pub struct SharedTerminals {
+    inner: Arc<Mutex<(HashSet<TermId>, Option<Peers>)>>,
+}
+
+impl SharedTerminals {
+    pub fn start(&self, peers: Peers) {
+        *self.inner.lock() = (HashSet::new(), Some(peers));
+    }
+
+    pub fn stop(&self) {
+        *self.inner.lock() = (HashSet::new(), None);
+    }
+
+    pub fn share(&self, term_id: TermId) {
+        self.inner.lock().0.insert(term_id);
+    }
+
+    /// Stop sharing the terminal, and return whether it was shared.
+    pub fn unshare(&self, term_id: TermId) -> bool {
+        self.inner.lock().0.remove(&term_id)
+    }
+
+    pub fn output(&self, term_id: TermId, content: &[u8]) {
+        let inner = self.inner.lock();
+        if let (true, Some(peers)) = (inner.0.contains(&term_id), inner.1.as_ref()) {
+            peers.broadcast(
+                CollabMessage::TerminalOutput {
+                    term_id,
+                    content: content.to_vec(),
+                },
+                None,
+            );
+        }
+    }
+}
+
+/// Share the session, on the address or through the relay.
+pub fn host(
+    address: String,
+    relay: Option<String>,
+    token: String,
+    peers: Peers,
+    events: Sender<CollabEvent>,
+) {
+    thread::spawn(move || {
+        let result = match relay {
+            Some(relay) => host_relay(&relay, token, &peers, &events),
+            None => host_direct(&address, token, &peers, &events),
+        };
+        if let Err(e) = result {
+            if !peers.is_stopped() {
+                let _ = events.send(CollabEvent::Failed {
+                    error: e.to_string(),
+                });
+            }
+        }
+    });
+}
+
+fn host_direct(
+    address: &str,
+    token: String,
+    peers: &Peers,
+    events: &Sender<CollabEvent>,
+) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    // Accepting doesn't block, so that it can stop along with the session
+    listener.set_nonblocking(true)?;
+    let address = reachable_address(listener.local_addr()?);
+    let _ = events.send(CollabEvent::Hosted {
+        invite: Invite {
+            address: address.to_string(),
+            session: None,
+            token,
+        },
+    });
+
+    while !peers.is_stopped() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                peers.connect(stream, peers.next_guest(), events.clone())?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+fn host_relay(
+    relay: &str,
+    token: String,
+    peers: &Peers,
+    events: &Sender<CollabEvent>,
+) -> Result<()> {
+    let session = super::protocol::new_token();
+    let mut control = TcpStream::connect(relay)?;
+    write_message(
+        &mut control,
+        &RelayMessage::Host {
+            session: session.clone(),
+        },
+    )?;
+    peers.others.lock().push(control.try_clone()?);
+    let _ = events.send(CollabEvent::Hosted {
+        invite: Invite {
+            address: relay.to_string(),
+            session: Some(session.clone()),
+            token,
+        },
+    });
+
+    let mut reader = BufReader::new(control);
+    while let Some(message) = read_message::<RelayMessage>(&mut reader)? {
+        if let RelayMessage::Incoming { connection, nonce } = message {
+            let mut stream = TcpStream::connect(relay)?;
+            write_message(
+                &mut stream,
+                &RelayMessage::Accept {
+                    session: session.clone(),
+                    connection,
+                    nonce,
+                },
+            )?;
+            peers.connect(stream, peers.next_guest(), events.clone())?;
+        }
+    }
+    Err(anyhow!("the relay closed the session"))
+}
+
+/// Join the session of the invite.
+pub fn join(
+    invite: Invite,
+    name: String,
+    peers: Peers,
+    events: Sender<CollabEvent>,
+) {
+    thread::spawn(move || {
+        let result = (|| -> Result<()> {
+            let mut stream = TcpStream::connect(&invite.address)?;
+            if let Some(session) = invite.session.clone() {
+                write_message(&mut stream, &RelayMessage::Join { session })?;
+            }
+            write_message(
+                &mut stream,
+                &CollabMessage::Join {
+                    name,
+                    token: invite.token.clone(),
+                },
+            )?;
+            peers.connect(stream, HOST, events.clone())?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            let _ = events.send(CollabEvent::Failed {
+                error: e.to_string(),
+            });
+        }
+    });
+}
+
+/// The address which the guests can connect to, which is the address of the
+/// network interface that's used for outgoing connections when the listener is on
+/// all of them.
+fn reachable_address(address: SocketAddr) -> SocketAddr {
+    if !address.ip().is_unspecified() {
+        return address;
+    }
+    // Connecting a UDP socket doesn't send anything, it only picks the interface
+    let ip = UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect("8.8.8.8:80")?;
+            socket.local_addr()
+        })
+        .map(|local| local.ip())
+        .unwrap_or(IpAddr::from([127, 0, 0, 1]));
+    SocketAddr::new(ip, address.port())
+}
@@ -0,0 +1,369 @@
+//! The text of a shared buffer as a replicated growable array. Every character has
+//! an id which it keeps however the text around it is edited, and the edits refer
+//! to the ids rather than to offsets, so that the participants end up with the same
+//! text whichever order they get each other's edits in.
+
+use std::{collections::HashSet, ops::Range};
+
+use serde::{Deserialize, Serialize};
+
+/// The id of a character, which is the Lamport clock of its insertion and the
+/// participant who inserted it. Ids are ordered by their clock first, which puts
+/// the later of two insertions at the same place first.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct CharId {
+    pub clock: u64,
+    pub site: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CrdtOp {
+    /// The text is inserted after the character, or at the start when there's
+    /// none. Its characters have consecutive clocks, starting from the id.
+    Insert {
+        id: CharId,
+        after: Option<CharId>,
+        text: String,
+    },
+    /// The characters are deleted, and kept as tombstones which the insertions
+    /// after them can still refer to
+    Delete { ids: Vec<CharId> },
+}
+
+/// A run of characters with consecutive clocks from the same participant, which
+/// is how the text is sent to a participant who opens the buffer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrdtRun {
+    pub id: CharId,
+    pub text: String,
+    pub deleted: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrdtSnapshot {
+    pub clock: u64,
+    pub runs: Vec<CrdtRun>,
+}
+
+#[derive(Clone, Debug)]
+struct Item {
+    id: CharId,
+    ch: char,
+    deleted: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CrdtText {
+    /// The participant whose edits are made to this copy
+    site: u64,
+    clock: u64,
+    items: Vec<Item>,
+}
+
+impl CrdtText {
+    /// The text as it is when it's shared, whose characters are the site's.
+    pub fn new(site: u64, text: &str) -> Self {
+        let items: Vec<Item> = text
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| Item {
+                id: CharId {
+                    clock: i as u64 + 1,
+                    site,
+                },
+                ch,
+                deleted: false,
+            })
+            .collect();
+        Self {
+            site,
+            clock: items.len() as u64,
+            items,
+        }
+    }
+
+    pub fn from_snapshot(site: u64, snapshot: CrdtSnapshot) -> Self {
+        let mut items = Vec::new();
+        for run in snapshot.runs {
+            for (i, ch) in run.text.chars().enumerate() {
+                items.push(Item {
+                    id: CharId {
+                        clock: run.id.clock + i as u64,
+                        site: run.id.site,
+                    },
+                    ch,
+                    deleted: run.deleted,
+                });
+            }
+        }
+        Self {
+            site,
+            clock: snapshot.clock,
+            items,
+        }
+    }
+
+    pub fn snapshot(&self) -> CrdtSnapshot {
+        let mut runs: Vec<CrdtRun> = Vec::new();
+        let mut next = None;
+        for item in &self.items {
+            match runs.last_mut() {
+                Some(run)
+                    if next == Some(item.id) && run.deleted == item.deleted =>
+                {
+                    run.text.push(item.ch);
+                }
+                _ => runs.push(CrdtRun {
+                    id: item.id,
+                    text: item.ch.to_string(),
+                    deleted: item.deleted,
+                }),
+            }
+            next = Some(CharId {
+                clock: item.id.clock + 1,
+                site: item.id.site,
+            });
+        }
+        CrdtSnapshot {
+            clock: self.clock,
+            runs,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.items
+            .iter()
+            .filter(|item| !item.deleted)
+            .map(|item| item.ch)
+            .collect()
+    }
+
+    /// Insert the text at the byte offset, and return the op for the others.
+    pub fn insert(&mut self, offset: usize, text: &str) -> Option<CrdtOp> {
+        if text.is_empty() {
+            return None;
+        }
+        let after = self.index_before(offset).map(|i| self.items[i].id);
+        let id = CharId {
+            clock: self.clock + 1,
+            site: self.site,
+        };
+        self.clock += text.chars().count() as u64;
+        self.integrate(id, after, text);
+        Some(CrdtOp::Insert {
+            id,
+            after,
+            text: text.to_string(),
+        })
+    }
+
+    /// Delete the byte range, and return the op for the others.
+    pub fn delete(&mut self, range: Range<usize>) -> Option<CrdtOp> {
+        let mut ids = Vec::new();
+        let mut offset = 0;
+        for item in self.items.iter_mut().filter(|item| !item.deleted) {
+            if offset >= range.end {
+                break;
+            }
+            if offset >= range.start {
+                item.deleted = true;
+                ids.push(item.id);
+            }
+            offset += item.ch.len_utf8();
+        }
+        (!ids.is_empty()).then_some(CrdtOp::Delete { ids })
+    }
+
+    /// Apply the op of another participant, and return the edits of the text it
+    /// makes. The ranges of the edits are byte ranges of the text before the op, in
+    /// order.
+    pub fn apply(&mut self, op: &CrdtOp) -> Vec<(Range<usize>, String)> {
+        match op {
+            CrdtOp::Insert { id, after, text } => {
+                if text.is_empty() {
+                    return Vec::new();
+                }
+                let count = text.chars().count() as u64;
+                self.clock = self.clock.max(id.clock + count - 1);
+                let index = self.integrate(*id, *after, text);
+                let offset = self.offset_of_index(index);
+                vec![(offset..offset, text.clone())]
+            }
+            CrdtOp::Delete { ids } => {
+                let ids: HashSet<CharId> = ids.iter().copied().collect();
+                let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+                let mut offset = 0;
+                for item in self.items.iter_mut().filter(|item| !item.deleted) {
+                    let len = item.ch.len_utf8();
+                    if ids.contains(&item.id) {
+                        item.deleted = true;
+                        match edits.last_mut() {
+                            Some((range, _)) if range.end == offset => {
+                                range.end += len;
+                            }
+                            _ => edits.push((offset..offset + len, String::new())),
+                        }
+                    }
+                    offset += len;
+                }
+                edits
+            }
+        }
+    }
+
+    /// The character which the cursor at the byte offset is after, which keeps
+    /// the cursor in its place through the edits of the others.
+    pub fn anchor(&self, offset: usize) -> Option<CharId> {
+        self.index_before(offset).map(|i| self.items[i].id)
+    }
+
+    /// The byte offset after the character, or after where it was when it has been
+    /// deleted.
+    pub fn offset_of_anchor(&self, anchor: Option<CharId>) -> usize {
+        let Some(anchor) = anchor else {
+            return 0;
+        };
+        let mut offset = 0;
+        for item in &self.items {
+            if !item.deleted {
+                offset += item.ch.len_utf8();
+            }
+            if item.id == anchor {
+                return offset;
+            }
+        }
+        offset
+    }
+
+    /// Put the characters of an insertion in their place, and return the index of
+    /// the first of them. Insertions after a character which isn't known go at the
+    /// start, which can't happen as long as the ops are sent in order.
+    fn integrate(&mut self, id: CharId, after: Option<CharId>, text: &str) -> usize {
+        let mut index = after
+            .and_then(|after| self.items.iter().position(|item| item.id == after))
+            .map_or(0, |i| i + 1);
+        // The insertions at the same place which came later go first, and so do
+        // the ones after them, as those are later still
+        while index < self.items.len() && self.items[index].id > id {
+            index += 1;
+        }
+        let items = text.chars().enumerate().map(|(i, ch)| Item {
+            id: CharId {
+                clock: id.clock + i as u64,
+                site: id.site,
+            },
+            ch,
+            deleted: false,
+        });
+        self.items.splice(index..index, items);
+        index
+    }
+
+    /// The index of the last character before the byte offset which isn't
+    /// deleted.
+    fn index_before(&self, offset: usize) -> Option<usize> {
+        if offset == 0 {
+            return None;
+        }
+        let mut end = 0;
+        let mut last = None;
+        for (i, item) in self.items.iter().enumerate() {
+            if item.deleted {
+                continue;
+            }
+            end += item.ch.len_utf8();
+            last = Some(i);
+            if end >= offset {
+                break;
+            }
+        }
+        last
+    }
+
+    fn offset_of_index(&self, index: usize) -> usize {
+        self.items[..index]
+            .iter()
+            .filter(|item| !item.deleted)
+            .map(|item| item.ch.len_utf8())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_edits(text: &mut String, edits: Vec<(Range<usize>, String)>) {
+        for (range, new) in edits.into_iter().rev() {
+            text.replace_range(range, &new);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_inserts_converge() {
+        let mut a = CrdtText::new(1, "ac");
+        let mut b = CrdtText::from_snapshot(2, a.snapshot());
+
+        let op_a = a.insert(1, "x").unwrap();
+        let op_b = b.insert(1, "y").unwrap();
+        let mut text_a = a.text();
+        let mut text_b = b.text();
+        apply_edits(&mut text_a, a.apply(&op_b));
+        apply_edits(&mut text_b, b.apply(&op_a));
+
+        assert_eq!(a.text(), b.text());
+        assert_eq!(text_a, a.text());
+        assert_eq!(text_b, b.text());
+        assert_eq!(a.text().len(), 4);
+    }
+
+    #[test]
+    fn test_delete_and_insert_converge() {
+        let mut a = CrdtText::new(1, "hello world");
+        let mut b = CrdtText::from_snapshot(2, a.snapshot());
+
+        let op_a = a.delete(0..6).unwrap();
+        let op_b = b.insert(5, ",").unwrap();
+        let mut text_a = a.text();
+        apply_edits(&mut text_a, a.apply(&op_b));
+        b.apply(&op_a);
+
+        // The comma was inserted before it got the deletion, so it's kept
+        assert_eq!(a.text(), ",world");
+        assert_eq!(text_a, ",world");
+        assert_eq!(b.text(), a.text());
+
+        let op_b = b.insert(0, "é ").unwrap();
+        a.apply(&op_b);
+        assert_eq!(a.text(), "é ,world");
+        assert_eq!(b.text(), a.text());
+    }
+
+    #[test]
+    fn test_snapshot_keeps_ids() {
+        let mut a = CrdtText::new(1, "abc");
+        a.delete(1..2);
+        a.insert(2, "de");
+        let b = CrdtText::from_snapshot(2, a.snapshot());
+        assert_eq!(b.text(), "acde");
+        assert_eq!(b.snapshot(), a.snapshot());
+    }
+
+    #[test]
+    fn test_anchor_follows_edits() {
+        let mut a = CrdtText::new(1, "abc");
+        let mut b = CrdtText::from_snapshot(2, a.snapshot());
+        let anchor = b.anchor(2);
+
+        let op = a.insert(0, "xy").unwrap();
+        b.apply(&op);
+        assert_eq!(b.offset_of_anchor(anchor), 4);
+
+        let op = a.delete(2..4).unwrap();
+        b.apply(&op);
+        assert_eq!(b.offset_of_anchor(anchor), 2);
+    }
+}
@@ -0,0 +1,319 @@
+//! The messages of a collaboration session, which are lines of JSON sent over TCP
+//! between the host and each guest.
+//!
+//! The connections aren't encrypted, so the files, the terminals and the token of
+//! the invite can be read by anyone on the network between the participants, or
+//! by the relay. Sessions are only for networks which are trusted, or over a
+//! tunnel such as SSH or a VPN.
+
+use std::{
+    fmt,
+    io::{self, BufRead, Read, Write},
+    path::PathBuf,
+};
+
+use lapce_rpc::terminal::TermId;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::crdt::{CharId, CrdtOp, CrdtSnapshot};
+
+pub type ParticipantId = u64;
+
+/// The host is the first participant, and the guests are numbered after it
+pub const HOST: ParticipantId = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permissions {
+    /// Whether the participant can edit the shared files
+    pub edit: bool,
+    /// Whether the participant can type in the shared terminals
+    pub terminal: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Participant {
+    pub id: ParticipantId,
+    pub name: String,
+    pub permissions: Permissions,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedTerminal {
+    pub term_id: TermId,
+    pub title: String,
+}
+
+/// The paths of the files are relative to the workspace of the host.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CollabMessage {
+    /// The first message of a guest, with the token of the invite
+    Join {
+        name: String,
+        token: String,
+    },
+    Welcome {
+        participant: ParticipantId,
+        participants: Vec<Participant>,
+        files: Vec<PathBuf>,
+        terminals: Vec<SharedTerminal>,
+    },
+    Rejected {
+        reason: String,
+    },
+    ParticipantJoined {
+        participant: Participant,
+    },
+    ParticipantLeft {
+        participant: ParticipantId,
+    },
+    PermissionsChanged {
+        participant: ParticipantId,
+        permissions: Permissions,
+    },
+    /// The files which the host has open, which can be opened by the guests
+    Files {
+        files: Vec<PathBuf>,
+    },
+    OpenFile {
+        path: PathBuf,
+    },
+    FileContent {
+        path: PathBuf,
+        snapshot: CrdtSnapshot,
+    },
+    Ops {
+        path: PathBuf,
+        ops: Vec<CrdtOp>,
+    },
+    Cursor {
+        participant: ParticipantId,
+        path: PathBuf,
+        anchor: Option<CharId>,
+        /// The selected ranges, by the anchors of their ends
+        selection: Vec<(Option<CharId>, Option<CharId>)>,
+        /// The start of the first line which the participant sees
+        top: Option<CharId>,
+    },
+    /// Draw the attention of the others to a range of the file
+    Ping {
+        participant: ParticipantId,
+        path: PathBuf,
+        start: Option<CharId>,
+        end: Option<CharId>,
+    },
+    Save {
+        path: PathBuf,
+    },
+    Saved {
+        path: PathBuf,
+    },
+    TerminalOpened {
+        terminal: SharedTerminal,
+    },
+    TerminalOutput {
+        term_id: TermId,
+        content: Vec<u8>,
+    },
+    TerminalInput {
+        term_id: TermId,
+        content: String,
+    },
+    TerminalClosed {
+        term_id: TermId,
+    },
+}
+
+/// The first message of each connection to a relay.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RelayMessage {
+    /// The control connection of a host, which the relay tells about the guests
+    Host { session: String },
+    /// A guest joins the session of the host
+    Join { session: String },
+    /// Sent to the host, which then accepts the guest with a new connection,
+    /// with a nonce that only the host is told, so that no one else can take the
+    /// guest
+    Incoming { connection: u64, nonce: String },
+    /// The new connection of the host, which is piped to the guest's
+    Accept {
+        session: String,
+        connection: u64,
+        nonce: String,
+    },
+}
+
+/// Where a session is joined, which is `<address>#<token>` when the host is
+/// listened on directly, or `<relay>/<session>#<token>` when it goes through a
+/// relay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invite {
+    pub address: String,
+    pub session: Option<String>,
+    pub token: String,
+}
+
+impl Invite {
+    pub fn parse(invite: &str) -> Option<Self> {
+        let (target, token) = invite.trim().rsplit_once('#')?;
+        let (address, session) = match target.split_once('/') {
+            Some((address, session)) => (address, Some(session.to_string())),
+            None => (target, None),
+        };
+        if address.is_empty() || token.is_empty() {
+            return None;
+        }
+        Some(Self {
+            address: address.to_string(),
+            session,
+            token: token.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Invite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.session {
+            Some(session) => {
+                write!(f, "{}/{}#{}", self.address, session, self.token)
+            }
+            None => write!(f, "{}#{}", self.address, self.token),
+        }
+    }
+}
+
+/// A random token for a session, from the random source of the system, which
+/// can't be guessed.
+pub fn new_token() -> String {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes).expect("the system has no random source");
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn write_message<T: Serialize>(
+    writer: &mut impl Write,
+    message: &T,
+) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// The most bytes of a message, which is well above the content of the files
+/// which are shared, so that a peer can't grow the line which is read without end
+pub const MAX_MESSAGE: u64 = 64 * 1024 * 1024;
+
+/// Read the next message, or `None` once the connection is closed. A message
+/// which is longer than [`MAX_MESSAGE`] is an error, which drops the connection.
+pub fn read_message<T: DeserializeOwned>(
+    reader: &mut impl BufRead,
+) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    let read = (&mut *reader).take(MAX_MESSAGE).read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 == MAX_MESSAGE && !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the message is too long",
+        ));
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_invite() {
+        let invite = Invite::parse("192.168.1.5:7420#abc").unwrap();
+        assert_eq!(invite.address, "192.168.1.5:7420");
+        assert_eq!(invite.session, None);
+        assert_eq!(invite.token, "abc");
+        assert_eq!(invite.to_string(), "192.168.1.5:7420#abc");
+
+        let invite = Invite::parse(" relay.example.com:7421/s1#abc\n").unwrap();
+        assert_eq!(invite.address, "relay.example.com:7421");
+        assert_eq!(invite.session.as_deref(), Some("s1"));
+        assert_eq!(invite.to_string(), "relay.example.com:7421/s1#abc");
+
+        assert_eq!(Invite::parse("192.168.1.5:7420"), None);
+        assert_eq!(Invite::parse("#abc"), None);
+    }
+
+    #[test]
+    fn test_read_messages() {
+        let mut buffer = Vec::new();
+        write_message(
+            &mut buffer,
+            &CollabMessage::Save {
+                path: PathBuf::from("src/main.rs"),
+            },
+        )
+        .unwrap();
+        write_message(
+            &mut buffer,
+            &RelayMessage::Incoming {
+                connection: 3,
+                nonce: "n".to_string(),
+            },
+        )
+        .unwrap();
+
+        let mut reader = buffer.as_slice();
+        let message: CollabMessage = read_message(&mut reader).unwrap().unwrap();
+        assert!(
+            matches!(message, CollabMessage::Save { path } if path == PathBuf::from("src/main.rs"))
+        );
+        let message: RelayMessage = read_message(&mut reader).unwrap().unwrap();
+        assert!(
+            matches!(message, RelayMessage::Incoming { connection: 3, nonce } if nonce == "n")
+        );
+        assert!(read_message::<RelayMessage>(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_new_token() {
+        let token = new_token();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, new_token());
+    }
+
+    #[test]
+    fn test_read_endless_message() {
+        let mut reader = io::BufReader::new(io::repeat(b'a'));
+        let err = read_message::<RelayMessage>(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_cursor_message() {
+        let anchor = Some(CharId { clock: 4, site: 2 });
+        let mut buffer = Vec::new();
+        write_message(
+            &mut buffer,
+            &CollabMessage::Cursor {
+                participant: 2,
+                path: PathBuf::from("src/main.rs"),
+                anchor,
+                selection: vec![(None, anchor)],
+                top: None,
+            },
+        )
+        .unwrap();
+
+        let message: CollabMessage =
+            read_message(&mut buffer.as_slice()).unwrap().unwrap();
+        let CollabMessage::Cursor { selection, top, .. } = message else {
+            panic!("not a cursor");
+        };
+        assert_eq!(selection, vec![(None, anchor)]);
+        assert_eq!(top, None);
+    }
+}
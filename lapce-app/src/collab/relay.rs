@@ -0,0 +1,249 @@
+//! A relay for the collaboration sessions of hosts which can't be connected to
+//! directly. The host keeps a control connection to the relay, which tells it when
+//! a guest joins, and the host then opens another connection that the relay pipes
+//! to the guest's.
+//!
+//! The relay sees what the participants send in plain text, so only a relay which
+//! is trusted should be used.
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+
+use super::protocol::{new_token, read_message, write_message, RelayMessage};
+
+/// A guest who waits for their host to accept them.
+struct Waiting {
+    session: String,
+    /// What the host has to accept the guest with, which only the host is told
+    nonce: String,
+    stream: TcpStream,
+    /// What the guest sent after joining
+    rest: Vec<u8>,
+}
+
+#[derive(Clone, Default)]
+struct Relay {
+    /// The control connections of the hosts by their session
+    hosts: Arc<Mutex<HashMap<String, TcpStream>>>,
+    waiting: Arc<Mutex<HashMap<u64, Waiting>>>,
+    next: Arc<AtomicU64>,
+}
+
+/// Relay the sessions on the address until the process is stopped.
+pub fn run(address: &str) -> Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!(
+        "Relaying collaboration sessions on {}",
+        listener.local_addr()?
+    );
+    let relay = Relay::default();
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let relay = relay.clone();
+        thread::spawn(move || {
+            if let Err(e) = relay.handle(stream) {
+                eprintln!("{e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+impl Relay {
+    fn handle(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let Some(message) = read_message::<RelayMessage>(&mut reader)? else {
+            return Ok(());
+        };
+        // What was read past the first message belongs to the other side
+        let rest = reader.buffer().to_vec();
+        match message {
+            RelayMessage::Host { session } => {
+                {
+                    let mut hosts = self.hosts.lock();
+                    if hosts.contains_key(&session) {
+                        return Err(anyhow!("the session {session} has a host"));
+                    }
+                    hosts.insert(session.clone(), stream);
+                }
+                // The control connection is only read to know when the host is gone
+                while reader.fill_buf().map_or(false, |buf| !buf.is_empty()) {
+                    let len = reader.buffer().len();
+                    reader.consume(len);
+                }
+                self.hosts.lock().remove(&session);
+            }
+            RelayMessage::Join { session } => {
+                let mut host = self
+                    .hosts
+                    .lock()
+                    .get(&session)
+                    .map(|host| host.try_clone())
+                    .transpose()?
+                    .ok_or_else(|| anyhow!("there's no session {session}"))?;
+                let connection = self.next.fetch_add(1, Ordering::Relaxed);
+                let nonce = new_token();
+                self.waiting.lock().insert(
+                    connection,
+                    Waiting {
+                        session,
+                        nonce: nonce.clone(),
+                        stream,
+                        rest,
+                    },
+                );
+                write_message(
+                    &mut host,
+                    &RelayMessage::Incoming { connection, nonce },
+                )?;
+            }
+            RelayMessage::Accept {
+                session,
+                connection,
+                nonce,
+            } => {
+                let guest = {
+                    let mut waiting = self.waiting.lock();
+                    // A guest is only taken by the host of its session, which
+                    // knows the nonce
+                    let accepted = waiting.get(&connection).map_or(false, |guest| {
+                        guest.session == session && guest.nonce == nonce
+                    });
+                    accepted.then(|| waiting.remove(&connection)).flatten()
+                };
+                let mut guest =
+                    guest.ok_or_else(|| anyhow!("there's no guest {connection}"))?;
+                let mut host = stream;
+                host.write_all(&guest.rest)?;
+                guest.stream.write_all(&rest)?;
+                pipe(guest.stream, host)?;
+            }
+            RelayMessage::Incoming { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+/// Copy what each side sends to the other, until either is closed.
+fn pipe(a: TcpStream, b: TcpStream) -> io::Result<()> {
+    let (mut a_reader, mut b_writer) = (a.try_clone()?, b.try_clone()?);
+    let forward = thread::spawn(move || {
+        let _ = io::copy(&mut a_reader, &mut b_writer);
+        let _ = b_writer.shutdown(Shutdown::Both);
+    });
+    let (mut b_reader, mut a_writer) = (b, a);
+    let _ = io::copy(&mut b_reader, &mut a_writer);
+    let _ = a_writer.shutdown(Shutdown::Both);
+    let _ = forward.join();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn connect(relay: &Relay, listener: &TcpListener) -> TcpStream {
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (accepted, _) = listener.accept().unwrap();
+        let relay = relay.clone();
+        thread::spawn(move || relay.handle(accepted));
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+    }
+
+    #[test]
+    fn test_accept_guest() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let relay = Relay::default();
+
+        let mut control = connect(&relay, &listener);
+        let session = "s1".to_string();
+        write_message(
+            &mut control,
+            &RelayMessage::Host {
+                session: session.clone(),
+            },
+        )
+        .unwrap();
+        while !relay.hosts.lock().contains_key(&session) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        // The session can't be taken over by another host
+        let mut other = connect(&relay, &listener);
+        write_message(
+            &mut other,
+            &RelayMessage::Host {
+                session: session.clone(),
+            },
+        )
+        .unwrap();
+        assert!(read_message::<RelayMessage>(&mut BufReader::new(other))
+            .unwrap()
+            .is_none());
+
+        let mut guest = connect(&relay, &listener);
+        write_message(
+            &mut guest,
+            &RelayMessage::Join {
+                session: session.clone(),
+            },
+        )
+        .unwrap();
+        let mut control = BufReader::new(control);
+        let Some(RelayMessage::Incoming { connection, nonce }) =
+            read_message(&mut control).unwrap()
+        else {
+            panic!("the host wasn't told about the guest");
+        };
+
+        // Without the nonce the guest isn't taken
+        let mut impostor = connect(&relay, &listener);
+        write_message(
+            &mut impostor,
+            &RelayMessage::Accept {
+                session: session.clone(),
+                connection,
+                nonce: "guess".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(read_message::<RelayMessage>(&mut BufReader::new(impostor))
+            .unwrap()
+            .is_none());
+
+        let mut host = connect(&relay, &listener);
+        write_message(
+            &mut host,
+            &RelayMessage::Accept {
+                session,
+                connection,
+                nonce,
+            },
+        )
+        .unwrap();
+        host.write_all(b"hello\n").unwrap();
+        let mut line = String::new();
+        BufReader::new(guest.try_clone().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        assert_eq!(line, "hello\n");
+        guest.shutdown(Shutdown::Both).unwrap();
+    }
+}
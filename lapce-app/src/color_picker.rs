@@ -0,0 +1,588 @@
+//! The colors which are written in a file as hex, `rgb()` or `hsl()` literals,
+//! which the editor shows a swatch for and which are picked in a popup. The
+//! picked color is written back in the format which is chosen there, right
+//! away, so that a theme in the themes directory changes as it's picked.
+
+use std::ops::Range;
+
+use floem::{
+    peniko::{kurbo::Rect, Color},
+    reactive::{
+        create_effect, create_rw_signal, RwSignal, Scope, SignalGet,
+        SignalGetUntracked, SignalSet, SignalUpdate, SignalWithUntracked,
+    },
+};
+use lapce_core::{
+    buffer::rope_text::RopeText, command::FocusCommand, directory::Directory,
+    editor::EditType, mode::Mode,
+};
+
+use crate::{
+    command::{CommandExecuted, CommandKind, LapceCommand},
+    editor::EditorData,
+    keypress::{condition::Condition, KeyPressFocus},
+    window_tab::{CommonData, Focus},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorFormat {
+    Hex,
+    Rgb,
+    Hsl,
+}
+
+impl ColorFormat {
+    pub const ALL: [ColorFormat; 3] =
+        [ColorFormat::Hex, ColorFormat::Rgb, ColorFormat::Hsl];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorFormat::Hex => "HEX",
+            ColorFormat::Rgb => "RGB",
+            ColorFormat::Hsl => "HSL",
+        }
+    }
+}
+
+/// A color which is written in the text, with the range of its literal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorLiteral {
+    pub range: Range<usize>,
+    pub color: Color,
+    pub format: ColorFormat,
+}
+
+/// The hue in degrees, and the saturation, the lightness and the alpha from 0
+/// to 1. The picker keeps the color as this, so that the hue isn't lost when
+/// the color is turned grey.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsla {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+    pub a: f64,
+}
+
+impl Hsla {
+    pub fn from_color(color: Color) -> Self {
+        let r = color.r as f64 / 255.0;
+        let g = color.g as f64 / 255.0;
+        let b = color.b as f64 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        let (h, s) = if d == 0.0 {
+            (0.0, 0.0)
+        } else {
+            let s = d / (1.0 - (2.0 * l - 1.0).abs());
+            let h = if max == r {
+                ((g - b) / d).rem_euclid(6.0)
+            } else if max == g {
+                (b - r) / d + 2.0
+            } else {
+                (r - g) / d + 4.0
+            };
+            (h * 60.0, s)
+        };
+        Self {
+            h,
+            s,
+            l,
+            a: color.a as f64 / 255.0,
+        }
+    }
+
+    pub fn to_color(self) -> Color {
+        let c = (1.0 - (2.0 * self.l - 1.0).abs()) * self.s;
+        let h = self.h.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = self.l - c / 2.0;
+        let channel = |v: f64| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color::rgba8(
+            channel(r),
+            channel(g),
+            channel(b),
+            (self.a * 255.0).round().clamp(0.0, 255.0) as u8,
+        )
+    }
+}
+
+/// A channel of the color which a bar of the picker sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorChannel {
+    Hue,
+    Saturation,
+    Lightness,
+    Alpha,
+}
+
+impl ColorChannel {
+    pub const ALL: [ColorChannel; 4] = [
+        ColorChannel::Hue,
+        ColorChannel::Saturation,
+        ColorChannel::Lightness,
+        ColorChannel::Alpha,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorChannel::Hue => "H",
+            ColorChannel::Saturation => "S",
+            ColorChannel::Lightness => "L",
+            ColorChannel::Alpha => "A",
+        }
+    }
+
+    /// The channel of the color, from 0 to 1.
+    pub fn get(&self, hsla: Hsla) -> f64 {
+        match self {
+            ColorChannel::Hue => hsla.h / 360.0,
+            ColorChannel::Saturation => hsla.s,
+            ColorChannel::Lightness => hsla.l,
+            ColorChannel::Alpha => hsla.a,
+        }
+    }
+
+    /// The color with the channel set to the value from 0 to 1.
+    pub fn set(&self, hsla: Hsla, value: f64) -> Hsla {
+        let value = value.clamp(0.0, 1.0);
+        match self {
+            ColorChannel::Hue => Hsla {
+                h: value * 360.0,
+                ..hsla
+            },
+            ColorChannel::Saturation => Hsla { s: value, ..hsla },
+            ColorChannel::Lightness => Hsla { l: value, ..hsla },
+            ColorChannel::Alpha => Hsla { a: value, ..hsla },
+        }
+    }
+
+    /// The color which the bar of the channel shows at the value. The hues are
+    /// shown in full, as they can't be told apart in a grey color.
+    pub fn swatch(&self, hsla: Hsla, value: f64) -> Color {
+        match self {
+            ColorChannel::Hue => Hsla {
+                h: value * 360.0,
+                s: 1.0,
+                l: 0.5,
+                a: 1.0,
+            }
+            .to_color(),
+            _ => self.set(hsla, value).to_color(),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// A channel of `rgb()`, from 0 to 255 or a percentage.
+fn parse_rgb_channel(arg: &str) -> Option<u8> {
+    let value = match arg.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().ok()? / 100.0 * 255.0,
+        None => arg.parse::<f64>().ok()?,
+    };
+    Some(value.round().clamp(0.0, 255.0) as u8)
+}
+
+/// The alpha of a color function, from 0 to 1 or a percentage.
+fn parse_alpha(arg: &str) -> Option<f64> {
+    let value = match arg.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().ok()? / 100.0,
+        None => arg.parse::<f64>().ok()?,
+    };
+    Some(value.clamp(0.0, 1.0))
+}
+
+fn parse_percent(arg: &str) -> Option<f64> {
+    let value = arg.strip_suffix('%').unwrap_or(arg).parse::<f64>().ok()?;
+    Some((value / 100.0).clamp(0.0, 1.0))
+}
+
+fn parse_hex(digits: &str) -> Option<Color> {
+    let digit = |i: usize| u8::from_str_radix(&digits[i..i + 1], 16).ok();
+    let byte = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    match digits.len() {
+        3 | 4 => {
+            let short = |i: usize| digit(i).map(|d| d * 17);
+            let a = if digits.len() == 4 { short(3)? } else { 255 };
+            Some(Color::rgba8(short(0)?, short(1)?, short(2)?, a))
+        }
+        6 | 8 => {
+            let a = if digits.len() == 8 { byte(6)? } else { 255 };
+            Some(Color::rgba8(byte(0)?, byte(2)?, byte(4)?, a))
+        }
+        _ => None,
+    }
+}
+
+/// The color of the arguments of `rgb()`, `rgba()`, `hsl()` or `hsla()`, which
+/// are split by commas, or by spaces with a slash before the alpha.
+fn parse_function(name: &str, args: &str) -> Option<(Color, ColorFormat)> {
+    let args: Vec<&str> = args
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    if args.len() != 3 && args.len() != 4 {
+        return None;
+    }
+    let a = match args.get(3) {
+        Some(arg) => parse_alpha(arg)?,
+        None => 1.0,
+    };
+    if name.starts_with("rgb") {
+        let color = Color::rgba8(
+            parse_rgb_channel(args[0])?,
+            parse_rgb_channel(args[1])?,
+            parse_rgb_channel(args[2])?,
+            (a * 255.0).round() as u8,
+        );
+        Some((color, ColorFormat::Rgb))
+    } else {
+        let h = args[0].strip_suffix("deg").unwrap_or(args[0]);
+        let hsla = Hsla {
+            h: h.parse::<f64>().ok()?.rem_euclid(360.0),
+            s: parse_percent(args[1])?,
+            l: parse_percent(args[2])?,
+            a,
+        };
+        Some((hsla.to_color(), ColorFormat::Hsl))
+    }
+}
+
+/// The color literals of the text, which are hex colors of 3, 4, 6 or 8 digits,
+/// and `rgb()`, `rgba()`, `hsl()` and `hsla()`.
+pub fn find_color_literals(text: &str) -> Vec<ColorLiteral> {
+    let mut literals = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let after_word = text[..i].chars().next_back().map_or(false, is_word_char);
+        let found = if rest.starts_with('#') && !text[..i].ends_with('&') {
+            let len = rest[1..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .count();
+            let digits = &rest[1..1 + len];
+            parse_hex(digits).map(|color| (1 + len, color, ColorFormat::Hex))
+        } else if !after_word
+            && ["rgba(", "rgb(", "hsla(", "hsl("]
+                .iter()
+                .any(|name| rest.starts_with(name))
+        {
+            let open = rest.find('(').unwrap();
+            rest.find(')').and_then(|close| {
+                let (color, format) =
+                    parse_function(&rest[..open], &rest[open + 1..close])?;
+                Some((close + 1, color, format))
+            })
+        } else {
+            None
+        };
+        match found {
+            Some((len, color, format)) => {
+                literals.push(ColorLiteral {
+                    range: i..i + len,
+                    color,
+                    format,
+                });
+                i += len;
+            }
+            None => {
+                i += rest.chars().next().map_or(1, |c| c.len_utf8());
+            }
+        }
+    }
+    literals
+}
+
+/// A number with at most two decimals, without the trailing zeros.
+fn format_number(value: f64) -> String {
+    let text = format!("{value:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// The literal of the color in the format, which has the alpha only when the
+/// color isn't opaque.
+pub fn format_color(hsla: Hsla, format: ColorFormat) -> String {
+    let color = hsla.to_color();
+    let opaque = color.a == 255;
+    match format {
+        ColorFormat::Hex if opaque => {
+            format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+        }
+        ColorFormat::Hex => format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r, color.g, color.b, color.a
+        ),
+        ColorFormat::Rgb if opaque => {
+            format!("rgb({}, {}, {})", color.r, color.g, color.b)
+        }
+        ColorFormat::Rgb => format!(
+            "rgba({}, {}, {}, {})",
+            color.r,
+            color.g,
+            color.b,
+            format_number(hsla.a)
+        ),
+        ColorFormat::Hsl => {
+            let h = hsla.h.round() as u32 % 360;
+            let s = (hsla.s * 100.0).round();
+            let l = (hsla.l * 100.0).round();
+            if opaque {
+                format!("hsl({h}, {s}%, {l}%)")
+            } else {
+                format!("hsla({h}, {s}%, {l}%, {})", format_number(hsla.a))
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ColorPickerData {
+    pub active: RwSignal<bool>,
+    /// The editor of the color, which it's written back to
+    editor: RwSignal<Option<EditorData>>,
+    /// The range of the literal in the document, which changes as the color is
+    /// written back in another length
+    pub range: RwSignal<Range<usize>>,
+    pub format: RwSignal<ColorFormat>,
+    pub hsla: RwSignal<Hsla>,
+    pub layout_rect: RwSignal<Rect>,
+    pub common: CommonData,
+}
+
+impl KeyPressFocus for ColorPickerData {
+    fn get_mode(&self) -> Mode {
+        Mode::Insert
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        matches!(condition, Condition::ModalFocus)
+    }
+
+    fn run_command(
+        &self,
+        command: &LapceCommand,
+        _count: Option<usize>,
+        _mods: floem::glazier::Modifiers,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Focus(FocusCommand::ModalClose) => {
+                self.close();
+                CommandExecuted::Yes
+            }
+            _ => CommandExecuted::No,
+        }
+    }
+
+    fn receive_char(&self, _c: &str) {}
+}
+
+impl ColorPickerData {
+    pub fn new(cx: Scope, common: CommonData) -> Self {
+        let picker = Self {
+            active: create_rw_signal(cx, false),
+            editor: create_rw_signal(cx, None),
+            range: create_rw_signal(cx, 0..0),
+            format: create_rw_signal(cx, ColorFormat::Hex),
+            hsla: create_rw_signal(
+                cx,
+                Hsla {
+                    h: 0.0,
+                    s: 0.0,
+                    l: 0.0,
+                    a: 1.0,
+                },
+            ),
+            layout_rect: create_rw_signal(cx, Rect::ZERO),
+            common,
+        };
+
+        {
+            let picker = picker.clone();
+            create_effect(cx, move |_| {
+                let focus = picker.common.focus.get();
+                if focus != Focus::ColorPicker && picker.active.get_untracked() {
+                    picker.active.set(false);
+                    picker.editor.set(None);
+                }
+            });
+        }
+
+        picker
+    }
+
+    /// Pick the color of the literal, whose range is in the document of the
+    /// editor.
+    pub fn start(&self, editor: EditorData, literal: ColorLiteral) {
+        self.editor.set(Some(editor));
+        self.range.set(literal.range);
+        self.format.set(literal.format);
+        self.hsla.set(Hsla::from_color(literal.color));
+        self.active.set(true);
+        self.common.focus.set(Focus::ColorPicker);
+    }
+
+    pub fn close(&self) {
+        self.active.set(false);
+        self.editor.set(None);
+        if let Focus::ColorPicker = self.common.focus.get_untracked() {
+            self.common.focus.set(Focus::Workbench);
+        }
+    }
+
+    pub fn set_hsla(&self, hsla: Hsla) {
+        self.hsla.set(hsla);
+        self.write();
+    }
+
+    pub fn set_format(&self, format: ColorFormat) {
+        self.format.set(format);
+        self.write();
+    }
+
+    /// Write the color in the document in place of its literal. A theme in the
+    /// themes directory is saved too, so that it's reloaded with the color.
+    fn write(&self) {
+        let Some(editor) = self.editor.get_untracked() else {
+            return;
+        };
+        let text =
+            format_color(self.hsla.get_untracked(), self.format.get_untracked());
+        let range = self.range.get_untracked();
+        // The literal is gone when the document was edited around it
+        let unchanged = editor.doc.with_untracked(|doc| {
+            range.end > doc.buffer().len()
+                || doc.buffer().text().slice_to_cow(range.clone()) == text
+        });
+        if unchanged {
+            return;
+        }
+
+        let start = range.start;
+        let delta = editor
+            .doc
+            .try_update(|doc| {
+                doc.do_raw_edit_ranges([(range, text.as_str())], EditType::Other)
+                    .0
+            })
+            .unwrap();
+        editor.cursor.update(|cursor| cursor.apply_delta(&delta));
+        self.range.set(start..start + text.len());
+
+        let is_theme = editor.doc.with_untracked(|doc| {
+            let themes = Directory::themes_directory();
+            doc.content
+                .path()
+                .zip(themes)
+                .map_or(false, |(path, themes)| path.starts_with(themes))
+        });
+        if is_theme {
+            editor.save(false, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(text: &str) -> Option<(String, Color, ColorFormat)> {
+        let literals = find_color_literals(text);
+        let literal = literals.first()?;
+        Some((
+            text[literal.range.clone()].to_string(),
+            literal.color,
+            literal.format,
+        ))
+    }
+
+    #[test]
+    fn test_find_color_literals() {
+        assert_eq!(
+            literal("color: #ff8000;"),
+            Some((
+                "#ff8000".to_string(),
+                Color::rgb8(0xff, 0x80, 0),
+                ColorFormat::Hex
+            ))
+        );
+        assert_eq!(
+            literal("\"#f80c\""),
+            Some((
+                "#f80c".to_string(),
+                Color::rgba8(0xff, 0x88, 0, 0xcc),
+                ColorFormat::Hex
+            ))
+        );
+        assert_eq!(
+            literal("background: rgba(255, 0, 0, 0.5)"),
+            Some((
+                "rgba(255, 0, 0, 0.5)".to_string(),
+                Color::rgba8(255, 0, 0, 128),
+                ColorFormat::Rgb
+            ))
+        );
+        assert_eq!(
+            literal("rgb(0 128 255 / 50%)"),
+            Some((
+                "rgb(0 128 255 / 50%)".to_string(),
+                Color::rgba8(0, 128, 255, 128),
+                ColorFormat::Rgb
+            ))
+        );
+        assert_eq!(
+            literal("hsl(120deg, 100%, 50%)"),
+            Some((
+                "hsl(120deg, 100%, 50%)".to_string(),
+                Color::rgb8(0, 255, 0),
+                ColorFormat::Hsl
+            ))
+        );
+        assert_eq!(literal("#[derive(Debug)]"), None);
+        assert_eq!(literal("issue #12345"), None);
+        assert_eq!(literal("&#123;"), None);
+        assert_eq!(literal("torgb(1, 2, 3)"), None);
+        assert_eq!(find_color_literals("#fff #000").len(), 2);
+    }
+
+    #[test]
+    fn test_hsla() {
+        let color = Color::rgba8(51, 102, 153, 255);
+        let hsla = Hsla::from_color(color);
+        assert_eq!(hsla.h.round(), 210.0);
+        assert_eq!((hsla.s * 100.0).round(), 50.0);
+        assert_eq!((hsla.l * 100.0).round(), 40.0);
+        assert_eq!(hsla.to_color(), color);
+    }
+
+    #[test]
+    fn test_format_color() {
+        let hsla = Hsla::from_color(Color::rgba8(255, 128, 0, 255));
+        assert_eq!(format_color(hsla, ColorFormat::Hex), "#ff8000");
+        assert_eq!(format_color(hsla, ColorFormat::Rgb), "rgb(255, 128, 0)");
+        assert_eq!(format_color(hsla, ColorFormat::Hsl), "hsl(30, 100%, 50%)");
+
+        let hsla = Hsla { a: 0.5, ..hsla };
+        assert_eq!(format_color(hsla, ColorFormat::Hex), "#ff800080");
+        assert_eq!(
+            format_color(hsla, ColorFormat::Rgb),
+            "rgba(255, 128, 0, 0.5)"
+        );
+        assert_eq!(
+            format_color(hsla, ColorFormat::Hsl),
+            "hsla(30, 100%, 50%, 0.5)"
+        );
+    }
+}
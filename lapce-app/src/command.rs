@@ -1,23 +1,37 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{ops::Range, path::PathBuf, sync::Arc};
 
 use floem::peniko::kurbo::Vec2;
 use indexmap::IndexMap;
 use lapce_core::command::{
     EditCommand, FocusCommand, MotionModeCommand, MoveCommand, MultiSelectionCommand,
 };
-use lapce_rpc::{dap_types::RunDebugConfig, plugin::PluginId, terminal::TermId};
-use lsp_types::{CodeActionOrCommand, Position, WorkspaceEdit};
+use lapce_rpc::{
+    crash::CrashReport,
+    dap_types::{DapId, RunDebugConfig},
+    plugin::{PluginId, VoltID},
+    terminal::TermId,
+};
+use lsp_types::{CodeActionKind, CodeActionOrCommand, Position, WorkspaceEdit};
 use serde_json::Value;
 use strum::{EnumMessage, IntoEnumIterator};
 use strum_macros::{Display, EnumIter, EnumMessage, EnumString, IntoStaticStr};
 
 use crate::{
-    debug::RunDebugMode,
+    code_action::CodeActionGroup,
+    collab::protocol::{ParticipantId, Permissions},
+    color_picker::ColorLiteral,
+    config::localization::{command_message_id, Localization},
+    debug::{BreakpointEdit, RunDebugMode, VariableEdit},
+    debug_memory::MemoryEdit,
+    edit_preview::PreviewFile,
     editor::location::EditorLocation,
     editor_tab::EditorTabChild,
     id::EditorTabId,
-    main_split::{SplitDirection, SplitMoveDirection},
+    main_split::{FileConflictResolution, SplitDirection, SplitMoveDirection},
+    notification::Notification,
+    proxy::askpass::SshPrompt,
     workspace::LapceWorkspace,
+    workspace_edit::FileEdit,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -48,6 +62,17 @@ pub fn desc(&self) -> Option<&'static str> {
         }
     }
 
+    /// The description in the language of the UI, which falls back to the English
+    /// one when there's no translation for it.
+    pub fn localized_desc(&self, localization: &Localization) -> Option<String> {
+        let desc = self.desc()?;
+        Some(
+            localization
+                .try_tr(&command_message_id(self.str()), None)
+                .unwrap_or_else(|| desc.to_string()),
+        )
+    }
+
     pub fn str(&self) -> &'static str {
         match &self {
             CommandKind::Workbench(cmd) => cmd.into(),
@@ -144,6 +169,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Open Folder")]
     OpenFolder,
 
+    #[strum(serialize = "open_archive")]
+    #[strum(message = "Open Archive as Workspace")]
+    OpenArchive,
+
     #[strum(serialize = "close_folder")]
     #[strum(message = "Close Folder")]
     CloseFolder,
@@ -156,6 +185,66 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Reveal Active File in File Explorer")]
     RevealActiveFileInFileExplorer,
 
+    #[strum(serialize = "copy_git_permalink")]
+    #[strum(message = "Copy Git Permalink for Selection")]
+    CopyGitPermalink,
+
+    #[strum(serialize = "open_file_on_remote")]
+    #[strum(message = "Open File on Remote")]
+    OpenFileOnRemote,
+
+    #[strum(serialize = "export_html")]
+    #[strum(message = "Export as HTML")]
+    ExportHtml,
+
+    #[strum(serialize = "print")]
+    #[strum(message = "Print / Export as PDF")]
+    Print,
+
+    #[strum(serialize = "run_all_tests")]
+    #[strum(message = "Run All Tests")]
+    RunAllTests,
+
+    #[strum(serialize = "rerun_failed_tests")]
+    #[strum(message = "Re-run Failed Tests")]
+    RerunFailedTests,
+
+    #[strum(serialize = "cancel_tests")]
+    #[strum(message = "Cancel Tests")]
+    CancelTests,
+
+    #[strum(serialize = "load_coverage")]
+    #[strum(message = "Load Coverage Report")]
+    LoadCoverage,
+
+    #[strum(serialize = "clear_coverage")]
+    #[strum(message = "Clear Coverage")]
+    ClearCoverage,
+
+    #[strum(serialize = "send_http_request")]
+    #[strum(message = "Send Request")]
+    SendHttpRequest,
+
+    #[strum(serialize = "file_explorer_delete")]
+    #[strum(message = "Move Selected File to Trash")]
+    FileExplorerDelete,
+
+    #[strum(serialize = "file_explorer_delete_permanently")]
+    #[strum(message = "Delete Selected File Permanently")]
+    FileExplorerDeletePermanently,
+
+    #[strum(serialize = "file_explorer_follow_link")]
+    #[strum(message = "Follow Link in File Explorer")]
+    FileExplorerFollowLink,
+
+    #[strum(serialize = "undo_file_delete")]
+    #[strum(message = "Undo File Delete")]
+    UndoFileDelete,
+
+    #[strum(serialize = "make_writable")]
+    #[strum(message = "Make Active Editor Writable")]
+    MakeWritable,
+
     #[strum(serialize = "change_color_theme")]
     #[strum(message = "Change Color Theme")]
     ChangeColorTheme,
@@ -164,6 +253,30 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Change Icon Theme")]
     ChangeIconTheme,
 
+    #[strum(serialize = "switch_profile")]
+    #[strum(message = "Switch Profile")]
+    SwitchProfile,
+
+    #[strum(serialize = "create_profile")]
+    #[strum(message = "Create Profile From Current Configuration")]
+    CreateProfile,
+
+    #[strum(serialize = "import_profile")]
+    #[strum(message = "Import Profile")]
+    ImportProfile,
+
+    #[strum(serialize = "export_profile")]
+    #[strum(message = "Export Profile")]
+    ExportProfile,
+
+    #[strum(serialize = "toggle_do_not_disturb")]
+    #[strum(message = "Toggle Do Not Disturb")]
+    ToggleDoNotDisturb,
+
+    #[strum(serialize = "clear_notifications")]
+    #[strum(message = "Clear Notifications")]
+    ClearNotifications,
+
     #[strum(serialize = "open_settings")]
     #[strum(message = "Open Settings")]
     OpenSettings,
@@ -172,6 +285,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Open Settings File")]
     OpenSettingsFile,
 
+    #[strum(serialize = "open_workspace_settings_file")]
+    #[strum(message = "Open Workspace Settings File")]
+    OpenWorkspaceSettingsFile,
+
     #[strum(serialize = "open_settings_directory")]
     #[strum(message = "Open Settings Directory")]
     OpenSettingsDirectory,
@@ -188,6 +305,10 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Open Log File")]
     OpenLogFile,
 
+    #[strum(serialize = "show_memory_usage")]
+    #[strum(message = "Show Memory Usage")]
+    ShowMemoryUsage,
+
     #[strum(serialize = "open_logs_directory")]
     #[strum(message = "Open Logs Directory")]
     OpenLogsDirectory,
@@ -305,6 +426,20 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "palette.scm_references")]
     PaletteSCMReferences,
 
+    #[strum(message = "Paste from History...")]
+    #[strum(serialize = "palette.paste_from_history")]
+    PalettePasteFromHistory,
+
+    /// Pick a command which was run from the palette to run it again
+    #[strum(message = "Command History...")]
+    #[strum(serialize = "palette.command_history")]
+    PaletteCommandHistory,
+
+    /// Run the command which was run last from the palette again
+    #[strum(message = "Repeat Last Command")]
+    #[strum(serialize = "repeat_last_command")]
+    RepeatLastCommand,
+
     #[strum(message = "Run and Debug Restart Current Running")]
     #[strum(serialize = "palette.run_and_debug_restart")]
     RunAndDebugRestart,
@@ -313,6 +448,44 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "palette.run_and_debug_stop")]
     RunAndDebugStop,
 
+    /// Run the config of the current process again without debugging it
+    #[strum(message = "Debug: Run Without Debugging")]
+    #[strum(serialize = "debug.run_without_debugging")]
+    RunWithoutDebugging,
+
+    #[strum(message = "Organize Imports in Open Files")]
+    #[strum(serialize = "organize_imports_in_open_files")]
+    OrganizeImportsInOpenFiles,
+
+    #[strum(message = "Remove Unused Imports in Open Files")]
+    #[strum(serialize = "remove_unused_imports_in_open_files")]
+    RemoveUnusedImportsInOpenFiles,
+
+    /// Move the execution of the stopped session to the line of the cursor
+    #[strum(message = "Debug: Jump to Cursor")]
+    #[strum(serialize = "debug.jump_to_cursor")]
+    DebugJumpToCursor,
+
+    /// Run the frame whose variables are shown again from its start
+    #[strum(message = "Debug: Restart Frame")]
+    #[strum(serialize = "debug.restart_frame")]
+    DebugRestartFrame,
+
+    /// Run the stopped session backwards until a breakpoint is hit, for a
+    /// debugger which supports stepping back
+    #[strum(message = "Debug: Reverse Continue")]
+    #[strum(serialize = "debug.reverse_continue")]
+    DebugReverseContinue,
+
+    #[strum(message = "Debug: Step Back")]
+    #[strum(serialize = "debug.step_back")]
+    DebugStepBack,
+
+    /// Ask for a watch expression of the debug panel in the palette
+    #[strum(message = "Debug: Add Watch Expression")]
+    #[strum(serialize = "debug.add_watch_expression")]
+    AddWatchExpression,
+
     #[strum(serialize = "source_control.checkout_reference")]
     CheckoutReference,
 
@@ -385,6 +558,30 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "toggle_problem_visual")]
     ToggleProblemVisual,
 
+    #[strum(serialize = "toggle_notification_visual")]
+    ToggleNotificationVisual,
+
+    #[strum(serialize = "toggle_log_visual")]
+    ToggleLogVisual,
+
+    #[strum(serialize = "toggle_test_explorer_visual")]
+    ToggleTestExplorerVisual,
+
+    #[strum(serialize = "toggle_http_response_visual")]
+    ToggleHttpResponseVisual,
+
+    #[strum(serialize = "toggle_debug_console_visual")]
+    ToggleDebugConsoleVisual,
+
+    #[strum(serialize = "toggle_debug_memory_visual")]
+    ToggleDebugMemoryVisual,
+
+    #[strum(serialize = "toggle_todo_visual")]
+    ToggleTodoVisual,
+
+    #[strum(serialize = "toggle_collaboration_visual")]
+    ToggleCollaborationVisual,
+
     #[strum(serialize = "toggle_debug_visual")]
     ToggleDebugVisual,
 
@@ -397,6 +594,15 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "focus_terminal")]
     FocusTerminal,
 
+    /// Moves the focus to the next of the editor and the shown panels
+    #[strum(message = "Focus Next Part")]
+    #[strum(serialize = "focus_next_part")]
+    FocusNextPart,
+
+    #[strum(message = "Focus Previous Part")]
+    #[strum(serialize = "focus_previous_part")]
+    FocusPreviousPart,
+
     #[strum(message = "Source Control: Init")]
     #[strum(serialize = "source_control_init")]
     SourceControlInit,
@@ -427,8 +633,12 @@ pub enum LapceWorkbenchCommand {
     #[strum(message = "Install current theme file")]
     InstallTheme,
 
+    #[strum(serialize = "install_theme_from_file")]
+    #[strum(message = "Install Theme From File...")]
+    InstallThemeFromFile,
+
     #[strum(serialize = "change_file_language")]
-    #[strum(message = "Change current file language")]
+    #[strum(message = "Change Language Mode")]
     ChangeFileLanguage,
 
     #[strum(serialize = "next_editor_tab")]
@@ -476,6 +686,61 @@ pub enum LapceWorkbenchCommand {
     #[strum(serialize = "jump_location_forward_local")]
     JumpLocationForwardLocal,
 
+    #[strum(message = "Start or Stop Recording a Macro")]
+    #[strum(serialize = "toggle_macro_recording")]
+    ToggleMacroRecording,
+
+    #[strum(message = "Play the Last Macro")]
+    #[strum(serialize = "play_macro")]
+    PlayMacro,
+
+    #[strum(message = "Play the Last Macro over Each Selection")]
+    #[strum(serialize = "play_macro_over_selections")]
+    PlayMacroOverSelections,
+
+    /// Ask for a name in the palette, which the macro is bound to as
+    /// `macro.<name>`
+    #[strum(message = "Save the Last Macro as a Command")]
+    #[strum(serialize = "save_macro")]
+    SaveMacro,
+
+    /// Pick a command of the command history, which is made the last macro
+    /// along with the commands which were run after it, and save it
+    #[strum(message = "Make a Macro from the Command History...")]
+    #[strum(serialize = "macro_from_command_history")]
+    MacroFromCommandHistory,
+
+    /// Share the workspace with the guests who join with the invite, which is
+    /// copied
+    #[strum(message = "Start a Collaboration Session")]
+    #[strum(serialize = "start_collaboration")]
+    StartCollaboration,
+
+    /// Ask for the invite of the session in the palette
+    #[strum(message = "Join a Collaboration Session")]
+    #[strum(serialize = "join_collaboration")]
+    JoinCollaboration,
+
+    #[strum(message = "Stop or Leave the Collaboration Session")]
+    #[strum(serialize = "stop_collaboration")]
+    StopCollaboration,
+
+    #[strum(message = "Copy the Invite of the Collaboration Session")]
+    #[strum(serialize = "copy_collaboration_invite")]
+    CopyCollaborationInvite,
+
+    /// Let the guests of the session see the active terminal, and type in it when
+    /// they're allowed to
+    #[strum(message = "Share the Terminal in the Collaboration Session")]
+    #[strum(serialize = "share_terminal_in_collaboration")]
+    ShareTerminalInCollaboration,
+
+    /// Highlight the selection, or the line of the cursor, for the others in the
+    /// collaboration session
+    #[strum(message = "Ping the Selection to the Collaborators")]
+    #[strum(serialize = "ping_collaborators")]
+    PingCollaborators,
+
     #[strum(message = "Next Error in Workspace")]
     #[strum(serialize = "next_error")]
     NextError,
@@ -495,12 +760,21 @@ pub enum InternalCommand {
     OpenFile {
         path: PathBuf,
     },
+    /// Open a settings file next to the settings editor
+    OpenSettingsFile {
+        path: PathBuf,
+    },
     GoToLocation {
         location: EditorLocation,
     },
     JumpToLocation {
         location: EditorLocation,
     },
+    /// Jump to the location in an editor which is read-only until it's made
+    /// writable, such as for a search result
+    PreviewLocation {
+        location: EditorLocation,
+    },
     PaletteReferences {
         references: Vec<EditorLocation>,
     },
@@ -532,6 +806,12 @@ pub enum InternalCommand {
     SplitTerminalExchange {
         term_id: TermId,
     },
+    /// Paste the text into the terminal, once pasting that many lines has been
+    /// confirmed
+    PasteInTerminal {
+        term_id: TermId,
+        content: String,
+    },
     EditorTabClose {
         editor_tab_id: EditorTabId,
     },
@@ -543,24 +823,150 @@ pub enum InternalCommand {
         offset: usize,
         mouse_click: bool,
         code_actions: Arc<(PluginId, Vec<CodeActionOrCommand>)>,
+        /// The kind of the actions which are shown, or all of them when it's
+        /// `None`
+        group: Option<CodeActionGroup>,
     },
     RunCodeAction {
         plugin_id: PluginId,
         action: CodeActionOrCommand,
     },
+    /// Run the code action of the kind for the whole of the file
+    RunSourceAction {
+        path: PathBuf,
+        kind: CodeActionKind,
+    },
     ApplyWorkspaceEdit {
         edit: WorkspaceEdit,
+        /// Whether the edit renames a symbol, which is previewed when it changes
+        /// more than one place
+        rename: bool,
+    },
+    /// Undo the last workspace edit in all of the files which it edited
+    UndoWorkspaceEdit,
+    /// Open the preview of the edits of many files, where they can be picked
+    /// before they're made
+    PreviewFileEdits {
+        files: Vec<PreviewFile>,
+    },
+    /// Make the edits of the files which were picked in their preview
+    ApplyFileEdits {
+        file_edits: Vec<FileEdit>,
+    },
+    ResolveFileConflict {
+        path: PathBuf,
+        resolution: FileConflictResolution,
+    },
+    SaveAsAdmin {
+        path: PathBuf,
+    },
+    /// Edit the code in the range of the host file in an editor of its own,
+    /// with the language of the file extension
+    EditEmbeddedCode {
+        host: PathBuf,
+        range: Range<usize>,
+        extension: String,
+    },
+    /// Write the code of the document of embedded code back to its host
+    SaveEmbeddedCode {
+        path: PathBuf,
+    },
+    /// Delete a path for good, once that has been confirmed
+    DeletePathPermanently {
+        path: PathBuf,
     },
     RunAndDebug {
         mode: RunDebugMode,
         config: RunDebugConfig,
     },
+    RunWithoutDebugging {
+        config: RunDebugConfig,
+    },
+    ToggleBreakpoint {
+        path: PathBuf,
+        line: usize,
+        offset: usize,
+    },
+    /// Move the execution of the stopped session to the line of the file
+    DapJumpToLine {
+        path: PathBuf,
+        line: usize,
+    },
+    /// Ask for the field of a breakpoint in the palette
+    EditBreakpoint {
+        edit: BreakpointEdit,
+    },
+    SetBreakpointField {
+        edit: BreakpointEdit,
+        value: String,
+    },
+    AddWatchExpression {
+        expression: String,
+    },
+    RemoveWatchExpression {
+        index: usize,
+    },
+    EditVariableValue {
+        edit: VariableEdit,
+    },
+    SetVariableValue {
+        edit: VariableEdit,
+        value: String,
+    },
+    /// Show the memory of the reference in the memory panel
+    ViewMemory {
+        dap_id: DapId,
+        memory_reference: String,
+    },
+    EditMemory {
+        edit: MemoryEdit,
+    },
+    /// Write the bytes of the value, which are in hex
+    WriteMemory {
+        edit: MemoryEdit,
+        value: String,
+    },
+    /// Answer the prompt of SSH, where `None` cancels it
+    AnswerSshPrompt {
+        prompt: SshPrompt,
+        answer: Option<String>,
+    },
+    /// Save the last recorded macro under the name
+    SaveMacro {
+        name: String,
+    },
+    JoinCollaboration {
+        invite: String,
+    },
+    /// Change what the participant of the collaboration session can do, which
+    /// only the host can
+    SetCollaborationPermissions {
+        participant: ParticipantId,
+        permissions: Permissions,
+    },
+    /// Follow the cursor of the participant, or stop following it
+    FollowParticipant {
+        participant: Option<ParticipantId>,
+    },
+    /// Open the file of the host of the collaboration session
+    OpenCollaborationFile {
+        path: PathBuf,
+    },
+    /// Send the request of the `.http` file whose block has the line
+    SendHttpRequest {
+        path: PathBuf,
+        line: usize,
+    },
     StartRename {
         path: PathBuf,
         placeholder: String,
         start: usize,
         position: Position,
     },
+    /// Pick the color of the literal in the active editor
+    StartColorPicker {
+        literal: ColorLiteral,
+    },
     Search {
         pattern: Option<String>,
     },
@@ -594,6 +1000,20 @@ pub enum InternalCommand {
         /// Whether to save the theme to the config file
         save: bool,
     },
+    ShowNotification {
+        notification: Notification,
+    },
+    RestartVolt {
+        volt_id: VoltID,
+    },
+    /// Open an issue for the crash of a subsystem of the proxy
+    ReportCrash {
+        report: CrashReport,
+    },
+    /// Switch to the profile with the given id, or to no profile if it's empty
+    SetProfile {
+        id: String,
+    },
 
     UpdateLogLevel {
         level: tracing_subscriber::filter::LevelFilter,
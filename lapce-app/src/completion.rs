@@ -16,7 +16,10 @@
 };
 
 use crate::{
-    config::LapceConfig, doc::Document, editor::view::EditorViewData, id::EditorId,
+    config::{editor::CompletionFilter, LapceConfig},
+    doc::Document,
+    editor::view::EditorViewData,
+    id::EditorId,
     snippet::Snippet,
 };
 
@@ -180,13 +183,19 @@ pub fn filter_items(&mut self) {
             return;
         }
 
-        // Filter the items by the fuzzy matching with the input text.
+        // Filter the items by the fuzzy matching with the input text, where the
+        // prefix filter only keeps the ones which start with it as well
+        let prefix = self.config.get_untracked().editor.completion_filter
+            == CompletionFilter::Prefix;
         let mut items: im::Vector<ScoredCompletionItem> = self
             .all_items()
             .iter()
             .filter_map(|i| {
                 let filter_text =
                     i.item.filter_text.as_ref().unwrap_or(&i.item.label);
+                if prefix && !starts_with_ignore_case(filter_text, &self.input) {
+                    return None;
+                }
                 let shift = i
                     .item
                     .label
@@ -282,6 +291,16 @@ pub fn current_item(&self) -> Option<&ScoredCompletionItem> {
         self.filtered_items.get(self.active.get_untracked())
     }
 
+    /// Whether typing the character accepts the current item, because it's one of
+    /// the commit characters of the item.
+    pub fn is_commit_character(&self, c: &str) -> bool {
+        self.status != CompletionStatus::Inactive
+            && self
+                .current_item()
+                .and_then(|item| item.item.commit_characters.as_ref())
+                .map_or(false, |characters| characters.iter().any(|s| s == c))
+    }
+
     /// Update the completion lens of the document with the active completion item.  
     pub fn update_document_completion(
         &self,
@@ -329,6 +348,14 @@ pub fn update_document_completion(
     }
 }
 
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    prefix
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| chars.next() == Some(c))
+}
+
 /// Clear the current completion lens. Only `update`s if there is a completion lens.
 pub fn clear_completion_lens(doc: RwSignal<Document>) {
     let has_completion = doc.with_untracked(|doc| doc.completion_lens().is_some());
@@ -1,18 +1,22 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
 };
 
-use floem::peniko::Color;
+use floem::{cosmic_text::FamilyOwned, peniko::Color};
+use fluent_bundle::FluentValue;
+use globset::GlobBuilder;
 use itertools::Itertools;
-use lapce_core::directory::Directory;
+use lapce_core::{directory::Directory, language::LapceLanguage};
 use lapce_proxy::plugin::wasi::find_all_volts;
-use lapce_rpc::plugin::VoltID;
+use lapce_rpc::{buffer::SaveMode, plugin::VoltID};
 use lsp_types::{CompletionItemKind, SymbolKind};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use serde::Deserialize;
+use strum::IntoEnumIterator;
 
 use self::{
     color::LapceColor,
@@ -21,11 +25,16 @@
     editor::EditorConfig,
     icon::LapceIcons,
     icon_theme::IconThemeConfig,
+    localization::{Localization, SYSTEM_LOCALE},
     svg::SvgStore,
     terminal::TerminalConfig,
-    ui::UIConfig,
+    ui::{UIConfig, ZoomMode},
+};
+use crate::{
+    editor::animation::Easing,
+    profile::Profile,
+    workspace::{LapceWorkspace, LapceWorkspaceType},
 };
-use crate::workspace::{LapceWorkspace, LapceWorkspaceType};
 
 pub mod color;
 pub mod color_theme;
@@ -33,8 +42,10 @@
 pub mod editor;
 pub mod icon;
 pub mod icon_theme;
+pub mod localization;
 pub mod svg;
 pub mod terminal;
+pub mod theme_import;
 pub mod ui;
 pub mod watcher;
 
@@ -49,13 +60,43 @@
     Lazy::new(LapceConfig::default_lapce_config);
 
 /// Used for creating a `DropdownData` for a setting
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DropdownInfo {
     /// The currently selected item.
     pub active_index: usize,
     pub items: im::Vector<String>,
 }
 
+/// The settings which are merged on top of the defaults, from the lowest to the
+/// highest priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsLayer {
+    Default,
+    User,
+    /// The profile picked in the user settings
+    Profile,
+    Workspace,
+}
+
+/// Where the effective value of a setting comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingSource {
+    pub layer: SettingsLayer,
+    /// Whether it's set for the language in that layer, like `[language.rust]`,
+    /// rather than for all of them
+    pub language: bool,
+}
+
+impl DropdownInfo {
+    /// The currently selected item, falling back to the last one.
+    pub fn active_item(&self) -> Option<String> {
+        self.items
+            .get(self.active_index)
+            .or_else(|| self.items.last())
+            .cloned()
+    }
+}
+
 #[derive(Clone, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct LapceConfig {
@@ -67,14 +108,32 @@ pub struct LapceConfig {
     pub terminal: TerminalConfig,
     pub color_theme: ColorThemeConfig,
     pub icon_theme: IconThemeConfig,
+    /// The tables of the editor settings of each language, which are resolved
+    /// into `language_editors` and kept out of `plugins`
+    #[serde(default)]
+    language: HashMap<String, HashMap<String, serde_json::Value>>,
     #[serde(flatten)]
     pub plugins: HashMap<String, HashMap<String, serde_json::Value>>,
+    /// The editor settings of the languages which override some of them
+    #[serde(skip)]
+    language_editors: HashMap<LapceLanguage, EditorConfig>,
+    /// The settings which are merged on top of the defaults, to tell where the
+    /// value of a setting comes from
+    #[serde(skip)]
+    layers: Vec<(SettingsLayer, config::Config)>,
+    /// The active profile, picked by `core.profile`.
+    #[serde(skip)]
+    pub profile: Option<Arc<Profile>>,
     #[serde(skip)]
     pub default_color_theme: ColorThemeConfig,
     #[serde(skip)]
     pub default_icon_theme: IconThemeConfig,
     #[serde(skip)]
     pub color: ThemeColor,
+    /// The translations of the UI strings in the language picked by
+    /// `core.language`
+    #[serde(skip)]
+    pub localization: Arc<Localization>,
     #[serde(skip)]
     pub available_color_themes: HashMap<String, (String, config::Config)>,
     #[serde(skip)]
@@ -98,10 +157,19 @@ pub fn load(workspace: &LapceWorkspace, disabled_volts: &[VoltID]) -> Self {
         let mut lapce_config: LapceConfig = config
             .try_deserialize()
             .unwrap_or_else(|_| DEFAULT_LAPCE_CONFIG.clone());
+        lapce_config.layers = Self::settings_layers(workspace);
+        lapce_config.resolve_language_editors();
+
+        lapce_config.profile =
+            Profile::load(&lapce_config.core.profile).map(Arc::new);
+        let mut disabled_volts = disabled_volts.to_vec();
+        if let Some(profile) = lapce_config.profile.as_ref() {
+            disabled_volts.extend(profile.info.disabled_volts.iter().cloned());
+        }
 
         lapce_config.available_color_themes =
-            Self::load_color_themes(disabled_volts);
-        lapce_config.available_icon_themes = Self::load_icon_themes(disabled_volts);
+            Self::load_color_themes(&disabled_volts);
+        lapce_config.available_icon_themes = Self::load_icon_themes(&disabled_volts);
         lapce_config.resolve_theme(workspace);
 
         lapce_config.color_theme_list = lapce_config
@@ -122,6 +190,9 @@ pub fn load(workspace: &LapceWorkspace, disabled_volts: &[VoltID]) -> Self {
 
         lapce_config.terminal.get_indexed_colors();
 
+        lapce_config.localization =
+            Arc::new(Localization::load(&lapce_config.core.language));
+
         lapce_config
     }
 
@@ -147,33 +218,158 @@ fn merge_config(
                 .unwrap_or_else(|_| config.clone());
         }
 
-        if let Some(path) = Self::settings_file() {
+        for (_, layer) in Self::settings_layers(workspace) {
             config = config::Config::builder()
                 .add_source(config.clone())
-                .add_source(config::File::from(path.as_path()).required(false))
+                .add_source(layer)
                 .build()
                 .unwrap_or_else(|_| config.clone());
         }
 
-        match workspace.kind {
-            LapceWorkspaceType::Local => {
-                if let Some(path) = workspace.path.as_ref() {
-                    let path = path.join("./.lapce/settings.toml");
-                    config = config::Config::builder()
-                        .add_source(config.clone())
-                        .add_source(
-                            config::File::from(path.as_path()).required(false),
-                        )
-                        .build()
-                        .unwrap_or_else(|_| config.clone());
+        config
+    }
+
+    /// The user settings, the profile picked in them and the settings of the
+    /// workspace, in the order they go on top of each other.
+    fn settings_layers(
+        workspace: &LapceWorkspace,
+    ) -> Vec<(SettingsLayer, config::Config)> {
+        fn load(
+            source: impl config::Source + Send + Sync + 'static,
+        ) -> Option<config::Config> {
+            config::Config::builder().add_source(source).build().ok()
+        }
+
+        let mut layers = Vec::new();
+        if let Some(user) = Self::settings_file().and_then(|path| {
+            load(config::File::from(path.as_path()).required(false))
+        }) {
+            let profile = user
+                .get_string("core.profile")
+                .ok()
+                .and_then(|id| Profile::load(&id));
+            layers.push((SettingsLayer::User, user));
+            if let Some(profile) = profile.and_then(|profile| {
+                load(config::File::from_str(
+                    &profile.settings,
+                    config::FileFormat::Toml,
+                ))
+            }) {
+                layers.push((SettingsLayer::Profile, profile));
+            }
+        }
+        if let Some(workspace) =
+            Self::workspace_settings_path(workspace).and_then(|path| {
+                load(config::File::from(path.as_path()).required(false))
+            })
+        {
+            layers.push((SettingsLayer::Workspace, workspace));
+        }
+        layers
+    }
+
+    /// Resolve the editor settings of each language which overrides some of them.
+    /// The settings of a language go on top of the rest of the layer they're in,
+    /// so they're merged as default → user → the language in the user settings →
+    /// profile → the language in the profile → workspace → the language in the
+    /// workspace.
+    fn resolve_language_editors(&mut self) {
+        let languages: BTreeSet<LapceLanguage> = self
+            .layers
+            .iter()
+            .flat_map(|(_, layer)| language_tables(layer))
+            .map(|(language, _)| language)
+            .collect();
+
+        self.language_editors.clear();
+        for language in languages {
+            let mut config = DEFAULT_CONFIG.clone();
+            for (_, layer) in &self.layers {
+                let mut builder = config::Config::builder()
+                    .add_source(config.clone())
+                    .add_source(layer.clone());
+                for (key, value) in language_tables(layer)
+                    .into_iter()
+                    .filter(|(l, _)| *l == language)
+                    .flat_map(|(_, table)| table)
+                {
+                    builder = builder
+                        .clone()
+                        .set_override(format!("editor.{key}"), value)
+                        .unwrap_or(builder);
                 }
+                config = builder.build().unwrap_or_else(|_| config.clone());
+            }
+            if let Ok(editor) = config.get::<EditorConfig>("editor") {
+                self.language_editors.insert(language, editor);
             }
-            LapceWorkspaceType::RemoteSSH(_) => {}
-            #[cfg(windows)]
-            LapceWorkspaceType::RemoteWSL => {}
         }
+    }
 
-        config
+    /// The editor settings for a document in the language, which are the ones
+    /// of the language where it overrides them.
+    pub fn editor_config(&self, language: Option<LapceLanguage>) -> &EditorConfig {
+        language
+            .and_then(|language| self.language_editors.get(&language))
+            .unwrap_or(&self.editor)
+    }
+
+    /// The language which the file associations give the file, where the
+    /// longest pattern which matches goes first. A pattern without a leading
+    /// `/` matches the end of the path, so `*.conf` matches the name of a file.
+    pub fn file_association(&self, path: &Path) -> Option<LapceLanguage> {
+        self.core
+            .file_associations
+            .iter()
+            .filter(|(pattern, _)| {
+                let pattern =
+                    if pattern.starts_with('/') || pattern.starts_with("**") {
+                        pattern.to_string()
+                    } else {
+                        format!("**/{pattern}")
+                    };
+                GlobBuilder::new(&pattern)
+                    .literal_separator(true)
+                    .build()
+                    .map(|glob| glob.compile_matcher().is_match(path))
+                    .unwrap_or(false)
+            })
+            .max_by_key(|(pattern, _)| pattern.len())
+            .and_then(|(_, name)| LapceLanguage::from_name(name))
+    }
+
+    /// Where the effective value of the setting comes from, for the language if
+    /// it's an editor setting.
+    pub fn setting_source(
+        &self,
+        kind: &str,
+        field: &str,
+        language: Option<LapceLanguage>,
+    ) -> SettingSource {
+        let key = format!("{kind}.{field}");
+        for (layer, config) in self.layers.iter().rev() {
+            if let Some(language) = language.filter(|_| kind == "editor") {
+                if language_tables(config)
+                    .iter()
+                    .any(|(l, table)| *l == language && table.contains_key(field))
+                {
+                    return SettingSource {
+                        layer: *layer,
+                        language: true,
+                    };
+                }
+            }
+            if config.get::<config::Value>(&key).is_ok() {
+                return SettingSource {
+                    layer: *layer,
+                    language: false,
+                };
+            }
+        }
+        SettingSource {
+            layer: SettingsLayer::Default,
+            language: false,
+        }
     }
 
     fn update_id(&mut self) {
@@ -277,9 +473,43 @@ fn load_color_themes(
             Self::load_color_theme_from_str(DEFAULT_DARK_THEME).unwrap();
         themes.insert(name.to_lowercase(), (name, theme));
 
+        let extended: Vec<_> = themes
+            .iter()
+            .filter_map(|(key, (name, _))| {
+                let theme = Self::extend_color_theme(&themes, key, 0)?;
+                Some((key.clone(), (name.clone(), theme)))
+            })
+            .collect();
+        themes.extend(extended);
+
         themes
     }
 
+    /// Layer a theme which `extends` another one over the theme it's derived from,
+    /// so that whatever it doesn't set is taken from the parent.
+    fn extend_color_theme(
+        themes: &HashMap<String, (String, config::Config)>,
+        key: &str,
+        depth: usize,
+    ) -> Option<config::Config> {
+        // guard against themes extending each other
+        if depth > 8 {
+            return None;
+        }
+        let (_, theme) = themes.get(key)?;
+        let parent = theme.get_string("color-theme.extends").ok()?.to_lowercase();
+        if parent == key {
+            return None;
+        }
+        let parent_theme = Self::extend_color_theme(themes, &parent, depth + 1)
+            .or_else(|| themes.get(&parent).map(|(_, theme)| theme.clone()))?;
+        config::Config::builder()
+            .add_source(parent_theme)
+            .add_source(theme.clone())
+            .build()
+            .ok()
+    }
+
     /// Set the active color theme.
     /// Note that this does not save the config.
     pub fn set_color_theme(&mut self, workspace: &LapceWorkspace, theme: &str) {
@@ -307,8 +537,21 @@ pub fn get_color(&self, name: &str) -> &Color {
     }
 
     /// Retrieve a color value whose key starts with "style."
+    /// Semantic tokens come as the token type followed by its modifiers, like
+    /// `variable.readonly.static`, so when there's no color for the full name,
+    /// the type with each single modifier is tried, and then the bare type.
     pub fn get_style_color(&self, name: &str) -> Option<&Color> {
-        self.color.syntax.get(name)
+        if let Some(color) = self.color.syntax.get(name) {
+            return Some(color);
+        }
+
+        let mut parts = name.split('.');
+        let kind = parts.next()?;
+        parts
+            .find_map(|modifier| {
+                self.color.syntax.get(&format!("{kind}.{modifier}"))
+            })
+            .or_else(|| self.color.syntax.get(kind))
     }
 
     pub fn completion_color(
@@ -502,6 +745,36 @@ pub fn settings_file() -> Option<PathBuf> {
         Some(path)
     }
 
+    /// The path of the settings file that overrides the user settings for the
+    /// given workspace. Only local workspaces can have one.
+    pub fn workspace_settings_path(workspace: &LapceWorkspace) -> Option<PathBuf> {
+        match workspace.kind {
+            LapceWorkspaceType::Local => workspace
+                .path
+                .as_ref()
+                .map(|path| path.join(".lapce").join("settings.toml")),
+            LapceWorkspaceType::RemoteSSH(_) => None,
+            #[cfg(windows)]
+            LapceWorkspaceType::RemoteWSL => None,
+        }
+    }
+
+    /// Like [`LapceConfig::workspace_settings_path`], but creates the file if it
+    /// doesn't exist yet.
+    pub fn workspace_settings_file(workspace: &LapceWorkspace) -> Option<PathBuf> {
+        let path = Self::workspace_settings_path(workspace)?;
+
+        if !path.exists() {
+            std::fs::create_dir_all(path.parent()?).ok()?;
+            let _ = std::fs::OpenOptions::new()
+                .create_new(true)
+                .write(true)
+                .open(&path);
+        }
+
+        Some(path)
+    }
+
     pub fn keymaps_file() -> Option<PathBuf> {
         let path = Directory::config_directory()?.join("keymaps.toml");
 
@@ -587,6 +860,38 @@ pub fn icon_theme_list(&self) -> im::Vector<String> {
         self.icon_theme_list.clone()
     }
 
+    /// The font families for the text of a document in the given language,
+    /// followed by the fallback fonts.
+    pub fn editor_font_family(
+        &self,
+        language: Option<LapceLanguage>,
+    ) -> Vec<FamilyOwned> {
+        let font_family = language
+            .and_then(|language| {
+                self.editor
+                    .language_font_family
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(language.as_ref()))
+                    .map(|(_, font_family)| font_family.as_str())
+            })
+            .filter(|font_family| !font_family.is_empty())
+            .unwrap_or(self.editor.font_family.as_str());
+        self.with_font_fallback(font_family)
+    }
+
+    /// Parse a list of font families, and add the fallback fonts after them, so
+    /// that they're only used for the glyphs none of the families have.
+    pub fn with_font_fallback(&self, font_family: &str) -> Vec<FamilyOwned> {
+        FamilyOwned::parse_list(font_family)
+            .chain(
+                self.editor
+                    .font_fallback
+                    .iter()
+                    .flat_map(|font| FamilyOwned::parse_list(font)),
+            )
+            .collect()
+    }
+
     pub fn terminal_font_family(&self) -> &str {
         if self.terminal.font_family.is_empty() {
             self.editor.font_family.as_str()
@@ -595,6 +900,24 @@ pub fn terminal_font_family(&self) -> &str {
         }
     }
 
+    /// Zoom the fonts of the editor, the terminal and the panels by `zoom`, which
+    /// is only done when `ui.zoom-mode` is `editor`, as the whole window is
+    /// scaled otherwise.
+    pub fn apply_zoom(&mut self, zoom: f64) {
+        if self.ui.zoom_mode != ZoomMode::Editor || zoom == 1.0 {
+            return;
+        }
+        self.editor.zoom(zoom);
+        for editor in self.language_editors.values_mut() {
+            editor.zoom(zoom);
+        }
+        // The ones which are 0 follow the editor
+        let scale = |size: usize| (size as f64 * zoom).round() as usize;
+        self.terminal.font_size = scale(self.terminal.font_size);
+        self.terminal.line_height = scale(self.terminal.line_height);
+        self.ui.zoom(zoom);
+    }
+
     pub fn terminal_font_size(&self) -> usize {
         if self.terminal.font_size > 0 {
             self.terminal.font_size
@@ -771,12 +1094,63 @@ pub fn get_dropdown_info(&self, kind: &str, key: &str) -> Option<DropdownInfo> {
                     .unwrap_or(0),
                 items: self.icon_theme_list.clone(),
             }),
+            ("core", "language") => {
+                let mut items = im::vector![SYSTEM_LOCALE.to_string()];
+                items.extend(Localization::available_locales());
+                Some(DropdownInfo {
+                    active_index: items
+                        .iter()
+                        .position(|s| s == &self.core.language)
+                        .unwrap_or(0),
+                    items,
+                })
+            }
+            ("editor", "smooth-scroll-easing") => {
+                let items: im::Vector<String> = Easing::iter()
+                    .map(|easing| easing.as_ref().to_string())
+                    .collect();
+                let active = self.editor.scroll_easing();
+                Some(DropdownInfo {
+                    active_index: items
+                        .iter()
+                        .position(|s| s == active.as_ref())
+                        .unwrap_or(0),
+                    items,
+                })
+            }
+            ("editor", "save-mode") => {
+                let items: im::Vector<String> = SaveMode::ALL
+                    .iter()
+                    .map(|mode| mode.name().to_string())
+                    .collect();
+                let active = SaveMode::from_name(&self.editor.save_mode);
+                Some(DropdownInfo {
+                    active_index: items
+                        .iter()
+                        .position(|s| s == active.name())
+                        .unwrap_or(0),
+                    items,
+                })
+            }
             _ => None,
         }
     }
 
-    fn get_file_table() -> Option<toml_edit::Document> {
-        let path = Self::settings_file()?;
+    /// The translation of a UI string, see [`Localization`].
+    pub fn tr(&self, id: &str) -> String {
+        self.localization.tr(id)
+    }
+
+    /// The translation of a UI string with arguments, see [`Localization`].
+    pub fn tr_args<'a>(
+        &self,
+        id: &str,
+        args: impl IntoIterator<Item = (&'a str, FluentValue<'a>)>,
+    ) -> String {
+        self.localization.tr_args(id, args)
+    }
+
+    fn get_file_table(path: &Path) -> Option<toml_edit::Document> {
         let content = std::fs::read_to_string(path).ok()?;
         let document: toml_edit::Document = content.parse().ok()?;
         Some(document)
@@ -789,7 +1163,29 @@ pub fn update_file(
         key: &str,
         value: toml_edit::Value,
     ) -> Option<()> {
-        let mut main_table = Self::get_file_table().unwrap_or_default();
+        let path = Self::settings_file()?;
+        Self::update_file_at(&path, parent, key, value)
+    }
+
+    /// Update the settings file of the workspace with the given edit, so that it
+    /// overrides the user settings for this workspace only.
+    pub fn update_workspace_file(
+        workspace: &LapceWorkspace,
+        parent: &str,
+        key: &str,
+        value: toml_edit::Value,
+    ) -> Option<()> {
+        let path = Self::workspace_settings_file(workspace)?;
+        Self::update_file_at(&path, parent, key, value)
+    }
+
+    fn update_file_at(
+        path: &Path,
+        parent: &str,
+        key: &str,
+        value: toml_edit::Value,
+    ) -> Option<()> {
+        let mut main_table = Self::get_file_table(path).unwrap_or_default();
 
         // Find the container table
         let mut table = main_table.as_table_mut();
@@ -807,9 +1203,99 @@ pub fn update_file(
         table.insert(key, toml_edit::Item::Value(value));
 
         // Store
-        let path = Self::settings_file()?;
         std::fs::write(path, main_table.to_string().as_bytes()).ok()?;
 
         Some(())
     }
 }
+
+/// The settings of each language in a layer, like `[language.rust]`, where the
+/// names which aren't languages are left out.
+fn language_tables(
+    layer: &config::Config,
+) -> Vec<(LapceLanguage, HashMap<String, config::Value>)> {
+    layer
+        .get_table("language")
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, table)| {
+            let language = LapceLanguage::from_str(&name).ok()?;
+            Some((language, table.into_table().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(settings: &str) -> config::Config {
+        config::Config::builder()
+            .add_source(config::File::from_str(settings, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_language_editors() {
+        let mut config = LapceConfig {
+            layers: vec![
+                (
+                    SettingsLayer::User,
+                    layer(
+                        "[editor]\ntab-width = 2\n\
+                         [language.rust]\ntab-width = 8\nformat-on-save = true\n\
+                         [language.not-a-language]\ntab-width = 3\n",
+                    ),
+                ),
+                (
+                    SettingsLayer::Workspace,
+                    layer(
+                        "[editor]\ntab-width = 6\n[language.Rust]\ntab-width = 5\n",
+                    ),
+                ),
+            ],
+            ..Default::default()
+        };
+        config.resolve_language_editors();
+
+        let rust = config.editor_config(Some(LapceLanguage::Rust));
+        assert_eq!(rust.tab_width, 5);
+        assert!(rust.format_on_save);
+        // Languages without settings of their own use the ones of all languages
+        let go = config.editor_config(Some(LapceLanguage::Go));
+        assert_eq!(go.tab_width, config.editor.tab_width);
+        assert_eq!(config.language_editors.len(), 1);
+
+        let source =
+            |field, language| config.setting_source("editor", field, language);
+        assert_eq!(
+            source("tab-width", Some(LapceLanguage::Rust)),
+            SettingSource {
+                layer: SettingsLayer::Workspace,
+                language: true,
+            }
+        );
+        assert_eq!(
+            source("format-on-save", Some(LapceLanguage::Rust)),
+            SettingSource {
+                layer: SettingsLayer::User,
+                language: true,
+            }
+        );
+        assert_eq!(
+            source("tab-width", None),
+            SettingSource {
+                layer: SettingsLayer::Workspace,
+                language: false,
+            }
+        );
+        assert_eq!(
+            source("show-tab", None),
+            SettingSource {
+                layer: SettingsLayer::Default,
+                language: false,
+            }
+        );
+    }
+}
@@ -38,6 +38,7 @@ impl LapceColor {
     pub const LAPCE_TAB_INACTIVE_UNDERLINE: &str = "lapce.tab.inactive.underline";
 
     pub const LAPCE_TAB_SEPARATOR: &str = "lapce.tab.separator";
+    pub const LAPCE_TAB_BAR_BACKGROUND: &str = "lapce.tab.bar.background";
 
     pub const LAPCE_ICON_ACTIVE: &str = "lapce.icon.active";
     pub const LAPCE_ICON_INACTIVE: &str = "lapce.icon.inactive";
@@ -62,6 +63,7 @@ impl LapceColor {
     pub const EDITOR_LINK: &str = "editor.link";
     pub const EDITOR_VISIBLE_WHITESPACE: &str = "editor.visible_whitespace";
     pub const EDITOR_INDENT_GUIDE: &str = "editor.indent_guide";
+    pub const EDITOR_RULER: &str = "editor.ruler";
     pub const EDITOR_DRAG_DROP_BACKGROUND: &str = "editor.drag_drop_background";
     pub const EDITOR_STICKY_HEADER_BACKGROUND: &str =
         "editor.sticky_header_background";
@@ -83,10 +85,23 @@ impl LapceColor {
     pub const SOURCE_CONTROL_ADDED: &str = "source_control.added";
     pub const SOURCE_CONTROL_REMOVED: &str = "source_control.removed";
     pub const SOURCE_CONTROL_MODIFIED: &str = "source_control.modified";
+    pub const SOURCE_CONTROL_UNSAVED_ADDED: &str = "source_control.unsaved.added";
+    pub const SOURCE_CONTROL_UNSAVED_REMOVED: &str =
+        "source_control.unsaved.removed";
+    pub const SOURCE_CONTROL_UNSAVED_MODIFIED: &str =
+        "source_control.unsaved.modified";
+
+    pub const TEST_PASSED: &str = "test.passed";
+    pub const TEST_FAILED: &str = "test.failed";
+
+    pub const COVERAGE_COVERED: &str = "coverage.covered";
+    pub const COVERAGE_PARTIAL: &str = "coverage.partial";
+    pub const COVERAGE_UNCOVERED: &str = "coverage.uncovered";
 
     pub const TERMINAL_CURSOR: &str = "terminal.cursor";
     pub const TERMINAL_BACKGROUND: &str = "terminal.background";
     pub const TERMINAL_FOREGROUND: &str = "terminal.foreground";
+    pub const TERMINAL_SELECTION: &str = "terminal.selection";
     pub const TERMINAL_RED: &str = "terminal.red";
     pub const TERMINAL_BLUE: &str = "terminal.blue";
     pub const TERMINAL_GREEN: &str = "terminal.green";
@@ -109,6 +124,8 @@ impl LapceColor {
     pub const PALETTE_FOREGROUND: &str = "palette.foreground";
     pub const PALETTE_CURRENT_BACKGROUND: &str = "palette.current.background";
     pub const PALETTE_CURRENT_FOREGROUND: &str = "palette.current.foreground";
+    pub const PALETTE_BORDER: &str = "palette.border";
+    pub const PALETTE_MATCH: &str = "palette.match";
 
     pub const COMPLETION_BACKGROUND: &str = "completion.background";
     pub const COMPLETION_CURRENT: &str = "completion.current";
@@ -120,6 +137,7 @@ impl LapceColor {
 
     pub const DEBUG_BREAKPOINT: &str = "debug.breakpoint";
     pub const DEBUG_BREAKPOINT_HOVER: &str = "debug.breakpoint.hover";
+    pub const DEBUG_CURRENT_LINE: &str = "debug.current_line";
 
     pub const PANEL_BACKGROUND: &str = "panel.background";
     pub const PANEL_FOREGROUND: &str = "panel.foreground";
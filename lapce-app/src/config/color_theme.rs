@@ -137,6 +137,8 @@ pub struct ColorThemeConfig {
     #[serde(skip)]
     pub path: PathBuf,
     pub name: String,
+    /// The name of the theme this one is derived from
+    pub extends: Option<String>,
     pub high_contrast: Option<bool>,
     pub base: ThemeBaseConfig,
     pub syntax: IndexMap<String, String>,
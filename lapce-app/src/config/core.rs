@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use lapce_rpc::proxy::ResourceLimits;
 use serde::{Deserialize, Serialize};
 use structdesc::FieldNames;
 
@@ -14,4 +17,102 @@ pub struct CoreConfig {
         desc = "Enable customised titlebar and disable OS native one (Linux, BSD, Windows)"
     )]
     pub custom_titlebar: bool,
+    #[field_names(
+        desc = "Set the configuration profile, which is applied on top of the user settings"
+    )]
+    pub profile: String,
+    #[field_names(
+        desc = "Set the language of the UI. \"system\" uses the language of the system"
+    )]
+    pub language: String,
+    #[field_names(
+        desc = "Set the log levels by module, like \"warn,lapce_proxy::plugin=debug\". A level without a module is the one of the rest"
+    )]
+    pub log_levels: String,
+    #[field_names(
+        desc = "Set the permalinks of self-hosted git remotes by host, e.g. `\"git.example.com\" = \"gitlab\"`. A permalink is `github`, `gitlab`, `bitbucket`, `gitea` or a url with {host}, {repo}, {commit}, {path}, {start} and {end}"
+    )]
+    pub git_permalink_templates: HashMap<String, String>,
+    #[field_names(
+        desc = "Set the coverage report of lcov or cobertura, relative to the workspace. When it's empty, the first of lcov.info, coverage/lcov.info, coverage.xml and coverage/cobertura.xml is loaded"
+    )]
+    pub coverage_report: String,
+    #[field_names(desc = "Load the coverage report again when it changes")]
+    pub coverage_watch: bool,
+    #[field_names(
+        desc = "Set the tags of the comments which the TODO panel collects"
+    )]
+    pub todo_tags: Vec<String>,
+    #[field_names(
+        desc = "Set the colors of the tags of the TODO panel, e.g. `TODO = \"#61AFEF\"`. A tag without a color is shown in the color of the editor"
+    )]
+    pub todo_tag_colors: HashMap<String, String>,
+    #[field_names(
+        desc = "Set how long a request to a debug adapter is waited for, in seconds. 0 waits for as long as it takes"
+    )]
+    pub dap_request_timeout: u64,
+    #[field_names(
+        desc = "Set the timeouts of the requests to a debug adapter by their command, e.g. `evaluate = 120`, in seconds"
+    )]
+    pub dap_request_timeouts: HashMap<String, u64>,
+    #[field_names(
+        desc = "Set the languages of the files which match the patterns, e.g. `\"*.conf\" = \"bash\"`. A pattern without a folder matches the name of the file, and the language goes before the one of the extension and the content of the file"
+    )]
+    pub file_associations: HashMap<String, String>,
+    #[field_names(
+        desc = "Set the environment of http-client.env.json whose variables the requests of .http files use"
+    )]
+    pub http_client_environment: String,
+    #[field_names(
+        desc = "Set the name which the others see in a collaboration session. When it's empty, the name of the user is used"
+    )]
+    pub collab_name: String,
+    #[field_names(
+        desc = "Set the address which a shared workspace is listened on for the guests to join"
+    )]
+    pub collab_address: String,
+    #[field_names(
+        desc = "Set the relay which a shared workspace goes through instead, like \"relay.example.com:7421\", for when the guests can't connect to it directly. A relay is run with `lapce --collab-relay <address>`"
+    )]
+    pub collab_relay: String,
+    #[field_names(
+        desc = "Let the guests of a shared workspace edit the files when they join"
+    )]
+    pub collab_guests_can_edit: bool,
+    #[field_names(
+        desc = "Forward the SSH agent to the host of a remote workspace, so that the keys of the agent can be used there"
+    )]
+    pub ssh_forward_agent: bool,
+    #[field_names(
+        desc = "Set the niceness which the proxy starts the language servers and the debug adapters with. 0 leaves it as it is"
+    )]
+    pub proxy_nice: i32,
+    #[field_names(
+        desc = "Set the memory of each language server and debug adapter which the proxy starts, in MB, with a cgroup of systemd-run on Linux. 0 is no limit"
+    )]
+    pub proxy_memory_max: u64,
+    #[field_names(
+        desc = "Set the cpu time of each language server and debug adapter which the proxy starts, in percent of a core, with a cgroup of systemd-run on Linux. 0 is no limit"
+    )]
+    pub proxy_cpu_quota: u32,
+    #[field_names(
+        desc = "Set the folders which the proxy watches for changes at most. A folder with more folders in it than are left is watched without them. 0 is no limit"
+    )]
+    pub proxy_max_watches: usize,
+    #[field_names(
+        desc = "Set the searches of the workspace which the proxy runs at the same time at most. 0 is no limit"
+    )]
+    pub proxy_search_threads: usize,
+}
+
+impl CoreConfig {
+    pub fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            nice: self.proxy_nice,
+            memory_max: self.proxy_memory_max,
+            cpu_quota: self.proxy_cpu_quota,
+            max_watches: self.proxy_max_watches,
+            search_threads: self.proxy_search_threads,
+        }
+    }
 }
@@ -1,6 +1,14 @@
+use std::{collections::HashMap, time::Duration};
+
+use lapce_rpc::buffer::{SaveMode, SaveOptions};
 use serde::{Deserialize, Serialize};
 use structdesc::FieldNames;
 
+use crate::editor::animation::Easing;
+
+/// A line height below this is a multiple of the font size
+const SCALE_OR_SIZE_LIMIT: f64 = 5.0;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub enum ClickMode {
     #[default]
@@ -12,11 +20,70 @@ pub enum ClickMode {
     DoubleClickAll,
 }
 
+/// When the messages of the diagnostics are shown at the end of their line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ErrorLensMessages {
+    #[default]
+    #[serde(rename = "always")]
+    Always,
+    /// Only on the line under the mouse
+    #[serde(rename = "hover")]
+    Hover,
+    #[serde(rename = "never")]
+    Never,
+}
+
+/// How the completion items are matched with what's typed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CompletionFilter {
+    /// The typed characters are found in order anywhere in the item
+    #[default]
+    #[serde(rename = "fuzzy")]
+    Fuzzy,
+    /// The item starts with what's typed
+    #[serde(rename = "prefix")]
+    Prefix,
+}
+
+/// The keys which accept the current completion item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CompletionAcceptKey {
+    #[default]
+    #[serde(rename = "enter-and-tab")]
+    EnterAndTab,
+    #[serde(rename = "enter")]
+    Enter,
+    #[serde(rename = "tab")]
+    Tab,
+}
+
+impl CompletionAcceptKey {
+    pub fn accepts_enter(&self) -> bool {
+        *self != CompletionAcceptKey::Tab
+    }
+
+    pub fn accepts_tab(&self) -> bool {
+        *self != CompletionAcceptKey::Enter
+    }
+}
+
 #[derive(FieldNames, Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct EditorConfig {
     #[field_names(desc = "Set the editor font family")]
     pub font_family: String,
+    #[field_names(
+        desc = "Set the fonts which are tried in order for the characters the font family has no glyphs for, e.g. CJK or emoji fonts. They're used by the terminal as well."
+    )]
+    pub font_fallback: Vec<String>,
+    #[field_names(
+        desc = "Set a font family for the documents of a language, e.g. `markdown = \"Inter\"`. The languages which aren't listed use the editor font family."
+    )]
+    pub language_font_family: HashMap<String, String>,
+    #[field_names(
+        desc = "Set whether the font may join characters like `->` or `!=` into ligatures. Set it in the settings of a language to turn the ligatures off only for its documents."
+    )]
+    pub font_ligatures: bool,
     #[field_names(desc = "Set the editor font size")]
     font_size: usize,
     #[field_names(desc = "Set the font size in the code lens")]
@@ -45,6 +112,22 @@ pub struct EditorConfig {
         desc = "If the editor should show the documentation of the current completion item"
     )]
     pub completion_show_documentation: bool,
+    #[field_names(
+        desc = "How the completion items are filtered by what's typed. `fuzzy` finds the typed characters in order anywhere in the item, and `prefix` only keeps the items which start with them."
+    )]
+    pub completion_filter: CompletionFilter,
+    #[field_names(
+        desc = "How long (in ms) to wait after typing before the completion is shown. If 0 it's shown right away."
+    )]
+    pub completion_delay: u64,
+    #[field_names(
+        desc = "Which keys accept the current completion item: `enter-and-tab`, `enter` or `tab`. The other key is typed as usual."
+    )]
+    pub completion_accept_key: CompletionAcceptKey,
+    #[field_names(
+        desc = "If typing a commit character of the current completion item, like `.` or `(`, accepts the item before the character is typed"
+    )]
+    pub completion_commit_characters: bool,
     #[field_names(
         desc = "If the editor should show the signature of the function as the parameters are being typed"
     )]
@@ -69,6 +152,20 @@ pub struct EditorConfig {
         desc = "Whether it should format the document on save (if there is an available formatter)"
     )]
     pub format_on_save: bool,
+    #[field_names(
+        desc = "The kinds of code actions which are run on save before the document is formatted, like `source.organizeImports`"
+    )]
+    pub code_actions_on_save: Vec<String>,
+    #[field_names(
+        desc = "Remove the whitespace at the ends of the lines on save, except on the lines of the cursors"
+    )]
+    pub trim_trailing_whitespace_on_save: bool,
+    #[field_names(desc = "End the file with a newline on save")]
+    pub insert_final_newline_on_save: bool,
+    #[field_names(
+        desc = "Remove the blank lines at the end of the file on save, which leaves a single newline"
+    )]
+    pub trim_final_newlines_on_save: bool,
 
     #[field_names(desc = "If matching brackets are highlighted")]
     pub highlight_matching_brackets: bool,
@@ -87,12 +184,24 @@ pub struct EditorConfig {
         desc = "Set the inlay hint font size. If less than 5 or greater than editor font size, it uses the editor font size."
     )]
     pub inlay_hint_font_size: usize,
+    #[field_names(
+        desc = "If the values of the variables should be displayed at the ends of the lines while debugging is paused"
+    )]
+    pub enable_inline_values: bool,
     #[field_names(desc = "If diagnostics should be displayed inline")]
     pub enable_error_lens: bool,
     #[field_names(
         desc = "Whether error lens should go to the end of view line, or only to the end of the diagnostic"
     )]
     pub error_lens_end_of_line: bool,
+    #[field_names(
+        desc = "When the messages of the diagnostics are shown at the end of their line: always, hover (only on the line under the mouse) or never"
+    )]
+    pub error_lens_messages: ErrorLensMessages,
+    #[field_names(
+        desc = "If the lines with diagnostics should be tinted by their worst severity"
+    )]
+    pub error_lens_line_background: bool,
     #[field_names(
         desc = "Set error lens font family. If empty, it uses the inlay hint font family."
     )]
@@ -131,6 +240,12 @@ pub struct EditorConfig {
     pub render_whitespace: String,
     #[field_names(desc = "Whether the editor show indent guide.")]
     pub show_indent_guide: bool,
+    #[field_names(
+        desc = "Set the columns where vertical rulers are drawn, e.g. [80, 100]"
+    )]
+    pub rulers: Vec<usize>,
+    #[field_names(desc = "Set the column at which Wrap Lines at Column wraps")]
+    pub wrap_column: usize,
     #[field_names(
         desc = "Set the auto save delay (in milliseconds), Set to 0 to completely disable"
     )]
@@ -153,6 +268,76 @@ pub struct EditorConfig {
         desc = "Set the default number of visible lines above and below the diff block (-1 for infinite)"
     )]
     pub diff_context_lines: i32,
+    #[field_names(
+        desc = "Set the multiplier of the mouse wheel scroll distance in the editor and the terminal"
+    )]
+    pub scroll_speed_modifier: f64,
+    #[field_names(
+        desc = "Set the multiplier of the mouse wheel scroll distance while Alt is held"
+    )]
+    pub fast_scroll_speed_modifier: f64,
+    #[field_names(
+        desc = "Set the multiplier of the touchpad scroll distance in the editor and the terminal"
+    )]
+    pub touchpad_scroll_sensitivity: f64,
+    #[field_names(
+        desc = "If a touchpad scroll keeps going and slows down once the fingers are lifted, which macOS does by itself"
+    )]
+    pub kinetic_scrolling: bool,
+    #[field_names(
+        desc = "If pinching the touchpad, or Ctrl with the mouse wheel, zooms in and out like Zoom In and Zoom Out, which zoom the editor font size when ui.zoom-mode is editor"
+    )]
+    pub pinch_to_zoom: bool,
+    #[field_names(desc = "If the editor scrolls smoothly instead of jumping")]
+    pub smooth_scroll: bool,
+    #[field_names(desc = "Set how long (in ms) a smooth scroll takes")]
+    pub smooth_scroll_duration: u64,
+    #[field_names(
+        desc = "Set how a smooth scroll speeds up and slows down.\nOptions: linear, ease-out, ease-in-out."
+    )]
+    pub smooth_scroll_easing: String,
+    #[field_names(
+        desc = "If the cursor caret glides to where it moves instead of jumping"
+    )]
+    pub smooth_caret: bool,
+    #[field_names(
+        desc = "Set how many copied texts are kept for Paste from History. Set to 0 to disable."
+    )]
+    pub clipboard_history_size: usize,
+    #[field_names(desc = "If the clipboard history is kept after Lapce is closed")]
+    pub persist_clipboard_history: bool,
+    #[field_names(
+        desc = "If pasted lines are moved to the indentation of where they're pasted. Paste Without Formatting always keeps their indentation."
+    )]
+    pub reindent_on_paste: bool,
+    #[field_names(
+        desc = "Set how files are written when they are saved. \"atomic\" writes a temporary file and renames it over the file, \"in-place\" writes over the file, which keeps it for tools watching it, and \"auto\" is atomic unless that would break the hard links or the owner of the file.\nOptions: auto, atomic, in-place."
+    )]
+    pub save_mode: String,
+    #[field_names(
+        desc = "If saved files are flushed to the disk before the save is done"
+    )]
+    pub save_fsync: bool,
+    #[field_names(
+        desc = "If the previous content of a saved file is kept in a backup file, which has a ~ at the end of its name"
+    )]
+    pub save_backup: bool,
+    #[field_names(
+        desc = "Set how long (in minutes) a document can go unshown before its syntax tree and semantic tokens are dropped, until it's shown again. Set to 0 to keep them."
+    )]
+    pub evict_unused_syntax_after: u64,
+    #[field_names(
+        desc = "If the edits of a code action which change more than one file, and the ones of a rename which change more than one place, are previewed before they're made"
+    )]
+    pub preview_workspace_edits: bool,
+    #[field_names(
+        desc = "If the line numbers are shown in a file which is exported as HTML or printed"
+    )]
+    pub export_line_numbers: bool,
+    #[field_names(
+        desc = "If a swatch of the color is shown in front of the hex, rgb() and hsl() colors of a file"
+    )]
+    pub color_decorators: bool,
 }
 
 impl EditorConfig {
@@ -161,8 +346,6 @@ pub fn font_size(&self) -> usize {
     }
 
     pub fn line_height(&self) -> usize {
-        const SCALE_OR_SIZE_LIMIT: f64 = 5.0;
-
         let line_height = if self.line_height < SCALE_OR_SIZE_LIMIT {
             self.line_height * self.font_size as f64
         } else {
@@ -199,6 +382,43 @@ pub fn completion_lens_font_size(&self) -> usize {
         }
     }
 
+    /// How long a scroll takes, which is zero when smooth scrolling is off.
+    pub fn scroll_duration(&self) -> Duration {
+        if self.smooth_scroll {
+            Duration::from_millis(self.smooth_scroll_duration)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    pub fn scroll_easing(&self) -> Easing {
+        Easing::from_name(&self.smooth_scroll_easing)
+    }
+
+    pub fn save_options(&self) -> SaveOptions {
+        SaveOptions {
+            mode: SaveMode::from_name(&self.save_mode),
+            fsync: self.save_fsync,
+            backup: self.save_backup,
+        }
+    }
+
+    /// Scale the font sizes by `zoom`.
+    pub fn zoom(&mut self, zoom: f64) {
+        let scale = |size: usize| (size as f64 * zoom).round() as usize;
+        self.font_size = scale(self.font_size);
+        self.code_lens_font_size = scale(self.code_lens_font_size);
+        if self.line_height >= SCALE_OR_SIZE_LIMIT {
+            self.line_height = (self.line_height * zoom).round();
+        }
+        // The ones which are 0 or too small fall back to the font size
+        if self.inlay_hint_font_size >= 5 {
+            self.inlay_hint_font_size = scale(self.inlay_hint_font_size);
+        }
+        self.error_lens_font_size = scale(self.error_lens_font_size);
+        self.completion_lens_font_size = scale(self.completion_lens_font_size);
+    }
+
     /// Returns the tab width if atomic soft tabs are enabled.
     pub fn atomic_soft_tab_width(&self) -> Option<usize> {
         if self.atomic_soft_tabs {
@@ -15,16 +15,21 @@ impl LapceIcons {
     pub const DEBUG: &str = "debug";
     pub const DEBUG_ALT: &str = "debug_alt";
     pub const DEBUG_BREAKPOINT: &str = "debug_breakpoint";
+    pub const DEBUG_BREAKPOINT_UNVERIFIED: &str = "debug_breakpoint_unverified";
     pub const DEBUG_SMALL: &str = "debug_small";
     pub const DEBUG_RESTART: &str = "debug_restart";
     pub const DEBUG_CONTINUE: &str = "debug_continue";
     pub const DEBUG_PAUSE: &str = "debug_pause";
+    pub const DEBUG_REVERSE_CONTINUE: &str = "debug_reverse_continue";
+    pub const DEBUG_STEP_BACK: &str = "debug_step_back";
     pub const DEBUG_STOP: &str = "debug_stop";
     pub const DEBUG_CONSOLE: &str = "debug_console";
     pub const DEBUG_DISCONNECT: &str = "debug_disconnect";
+    pub const DEBUG_MEMORY: &str = "debug_memory";
     pub const START: &str = "start";
     pub const RUN_ERRORS: &str = "run_errors";
     pub const UNSAVED: &str = "unsaved";
+    pub const LOCK: &str = "lock";
     pub const WARNING: &str = "warning";
     pub const TERMINAL: &str = "terminal";
     pub const SETTINGS: &str = "settings";
@@ -76,6 +81,23 @@ impl LapceIcons {
     pub const LAYOUT_PANEL: &str = "layout.panel.on";
     pub const LAYOUT_PANEL_OFF: &str = "layout.panel.off";
 
+    pub const NOTIFICATION: &str = "notification.on";
+    pub const NOTIFICATION_OFF: &str = "notification.off";
+    pub const NOTIFICATION_INFO: &str = "notification.info";
+
+    pub const LOG: &str = "log";
+
+    pub const TODO: &str = "todo";
+
+    pub const TEST_EXPLORER: &str = "test_explorer";
+    pub const TEST_STATUS: &str = "test.status";
+    pub const TEST_FAILED: &str = "test.failed";
+
+    pub const HTTP_RESPONSE: &str = "http_response";
+    pub const HTTP_SEND: &str = "http.send";
+
+    pub const COLLABORATION: &str = "collaboration";
+
     pub const SEARCH: &'static str = "search.icon";
     pub const SEARCH_CLEAR: &'static str = "search.clear";
     pub const SEARCH_FORWARD: &'static str = "search.forward";
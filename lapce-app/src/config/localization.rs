@@ -0,0 +1,209 @@
+use std::{collections::BTreeSet, path::Path};
+
+use fluent_bundle::{
+    concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue,
+};
+use include_dir::{include_dir, Dir};
+use lapce_core::directory::Directory;
+use unic_langid::LanguageIdentifier;
+
+const LOCALES_DIR: Dir = include_dir!("../defaults/locales");
+
+/// The locale which has all the strings. Strings which are missing from a
+/// translation are taken from it.
+pub const FALLBACK_LOCALE: &str = "en-US";
+/// The value of `core.language` which picks the language of the system
+pub const SYSTEM_LOCALE: &str = "system";
+
+/// The translations of the UI strings, which are written in
+/// [Fluent](https://projectfluent.org). The built-in translations are in
+/// `defaults/locales`, and the ones in the locales directory are layered on top of
+/// them, so that a user can add a language or override single strings.
+pub struct Localization {
+    /// The locale which is in use, e.g. `de-DE`
+    pub locale: String,
+    bundle: Option<FluentBundle<FluentResource>>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self::load(FALLBACK_LOCALE)
+    }
+}
+
+impl Localization {
+    /// Load the translation picked by `core.language`.
+    pub fn load(language: &str) -> Self {
+        let locale = if language.is_empty() || language == SYSTEM_LOCALE {
+            Self::system_locale()
+        } else {
+            language.to_string()
+        };
+
+        let fallback = Self::bundle(FALLBACK_LOCALE).unwrap_or_else(|| {
+            FluentBundle::new_concurrent(vec![FALLBACK_LOCALE
+                .parse()
+                .unwrap_or_default()])
+        });
+        let bundle = if locale == FALLBACK_LOCALE {
+            None
+        } else {
+            Self::bundle(&locale)
+        };
+
+        Self {
+            locale,
+            bundle,
+            fallback,
+        }
+    }
+
+    /// The available locale which matches the one of the system best, e.g. `de-DE`
+    /// for `de-AT`.
+    fn system_locale() -> String {
+        let available = Self::available_locales();
+        sys_locale::get_locale()
+            .and_then(|system| {
+                let system = system.replace('_', "-");
+                let language = system.split('-').next().unwrap_or_default();
+                available
+                    .iter()
+                    .find(|locale| locale.eq_ignore_ascii_case(&system))
+                    .or_else(|| {
+                        available.iter().find(|locale| {
+                            locale
+                                .split('-')
+                                .next()
+                                .map(|l| l.eq_ignore_ascii_case(language))
+                                .unwrap_or(false)
+                        })
+                    })
+                    .cloned()
+            })
+            .unwrap_or_else(|| FALLBACK_LOCALE.to_string())
+    }
+
+    /// All the locales there's a translation for, sorted by name.
+    pub fn available_locales() -> Vec<String> {
+        let mut locales: BTreeSet<String> = LOCALES_DIR
+            .files()
+            .iter()
+            .filter_map(|file| locale_of_file(file.path()))
+            .collect();
+        if let Some(entries) = Directory::locales_directory()
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+        {
+            locales.extend(
+                entries
+                    .flatten()
+                    .filter_map(|entry| locale_of_file(&entry.path())),
+            );
+        }
+        locales.into_iter().collect()
+    }
+
+    fn bundle(locale: &str) -> Option<FluentBundle<FluentResource>> {
+        let file_name = format!("{locale}.ftl");
+        let builtin = LOCALES_DIR
+            .get_file(&file_name)
+            .and_then(|file| file.contents_utf8())
+            .map(|s| s.to_string());
+        let user = Directory::locales_directory()
+            .and_then(|dir| std::fs::read_to_string(dir.join(&file_name)).ok());
+        if builtin.is_none() && user.is_none() {
+            return None;
+        }
+
+        let id: LanguageIdentifier = locale.parse().unwrap_or_default();
+        let mut bundle = FluentBundle::new_concurrent(vec![id]);
+        // The unicode isolation marks around the arguments would show up in the UI
+        bundle.set_use_isolating(false);
+        for source in [builtin, user].into_iter().flatten() {
+            // The messages which could be parsed are still used when a file has
+            // errors in it
+            let resource = FluentResource::try_new(source)
+                .unwrap_or_else(|(resource, _)| resource);
+            bundle.add_resource_overriding(resource);
+        }
+        Some(bundle)
+    }
+
+    /// The translation of the message with the given id, falling back to the id
+    /// when there's none.
+    pub fn tr(&self, id: &str) -> String {
+        self.try_tr(id, None).unwrap_or_else(|| id.to_string())
+    }
+
+    pub fn tr_args<'a>(
+        &self,
+        id: &str,
+        args: impl IntoIterator<Item = (&'a str, FluentValue<'a>)>,
+    ) -> String {
+        let args: FluentArgs = args.into_iter().collect();
+        self.try_tr(id, Some(&args))
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// The translation of the message with the given id, if there's one in either
+    /// the current locale or the fallback one.
+    pub fn try_tr(&self, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        self.bundle
+            .iter()
+            .chain(std::iter::once(&self.fallback))
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = Vec::new();
+                Some(
+                    bundle
+                        .format_pattern(pattern, args, &mut errors)
+                        .into_owned(),
+                )
+            })
+    }
+}
+
+fn locale_of_file(path: &Path) -> Option<String> {
+    if path.extension().and_then(|s| s.to_str()) != Some("ftl") {
+        return None;
+    }
+    Some(path.file_stem()?.to_str()?.to_string())
+}
+
+/// The id of the message which translates the description of a command, e.g.
+/// `command-palette-line` for `palette.line`.
+pub fn command_message_id(command: &str) -> String {
+    format!("command-{}", command.replace(['.', '_'], "-"))
+}
+
+/// The id of the message which translates the description of a setting, e.g.
+/// `setting-editor-font-size`.
+pub fn setting_message_id(kind: &str, field: &str) -> String {
+    format!("setting-{kind}-{field}")
+}
+
+#[cfg(test)]
+mod tests {
+    use fluent_bundle::FluentValue;
+
+    use super::{command_message_id, Localization, FALLBACK_LOCALE};
+
+    #[test]
+    fn test_fallback() {
+        let localization = Localization::load("de-DE");
+        assert_eq!(localization.locale, "de-DE");
+        assert_eq!(localization.tr("status-plain-text"), "Nur Text");
+        assert_eq!(localization.tr("no-such-message"), "no-such-message");
+        assert_eq!(
+            command_message_id("palette.line"),
+            "command-palette-line".to_string()
+        );
+
+        let localization = Localization::load(FALLBACK_LOCALE);
+        assert_eq!(
+            localization
+                .tr_args("status-indent-spaces", [("count", FluentValue::from(4))]),
+            "Spaces: 4"
+        );
+    }
+}
@@ -21,6 +21,10 @@ pub struct TerminalConfig {
     pub line_height: usize,
     #[field_names(desc = "Set the terminal Shell")]
     pub shell: String,
+    #[field_names(
+        desc = "Ask before pasting more lines than this into the terminal, where each line can run as a command. Set to 0 to never ask."
+    )]
+    pub paste_confirm_lines: usize,
 
     #[serde(skip)]
     #[field_names(skip)]
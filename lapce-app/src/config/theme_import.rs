@@ -0,0 +1,706 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use floem::peniko::Color;
+use indexmap::IndexMap;
+use lapce_core::directory::Directory;
+use serde_json::Value;
+
+/// VS Code workbench colors and the Lapce UI colors they're used for
+const UI_COLORS: &[(&str, &[&str])] = &[
+    (
+        "editor.background",
+        &["editor.background", "terminal.background"],
+    ),
+    (
+        "editor.foreground",
+        &[
+            "editor.foreground",
+            "terminal.foreground",
+            "palette.foreground",
+        ],
+    ),
+    ("editor.selectionBackground", &["editor.selection"]),
+    ("editor.lineHighlightBackground", &["editor.current_line"]),
+    (
+        "editorCursor.foreground",
+        &["editor.caret", "terminal.cursor"],
+    ),
+    ("editorLineNumber.foreground", &["editor.dim"]),
+    ("editorLineNumber.activeForeground", &["editor.focus"]),
+    ("editorIndentGuide.background", &["editor.indent_guide"]),
+    ("editorRuler.foreground", &["editor.ruler"]),
+    (
+        "editorWhitespace.foreground",
+        &["editor.visible_whitespace"],
+    ),
+    (
+        "editorStickyScroll.background",
+        &["editor.sticky_header_background"],
+    ),
+    ("textLink.foreground", &["editor.link"]),
+    (
+        "editorError.foreground",
+        &["lapce.error", "error_lens.error.foreground"],
+    ),
+    (
+        "editorWarning.foreground",
+        &["lapce.warn", "error_lens.warning.foreground"],
+    ),
+    ("editorInlayHint.foreground", &["inlay_hint.foreground"]),
+    ("editorInlayHint.background", &["inlay_hint.background"]),
+    (
+        "editorGhostText.foreground",
+        &["completion_lens.foreground"],
+    ),
+    ("editorGroup.border", &["lapce.border"]),
+    ("scrollbarSlider.background", &["lapce.scroll_bar"]),
+    ("button.background", &["lapce.button.primary.background"]),
+    ("button.foreground", &["lapce.button.primary.foreground"]),
+    ("tab.activeBackground", &["lapce.tab.active.background"]),
+    ("tab.activeForeground", &["lapce.tab.active.foreground"]),
+    ("tab.activeBorder", &["lapce.tab.active.underline"]),
+    ("tab.inactiveBackground", &["lapce.tab.inactive.background"]),
+    ("tab.inactiveForeground", &["lapce.tab.inactive.foreground"]),
+    ("tab.border", &["lapce.tab.separator"]),
+    (
+        "editorGroupHeader.tabsBackground",
+        &["lapce.tab.bar.background"],
+    ),
+    ("sideBar.background", &["panel.background"]),
+    ("sideBar.foreground", &["panel.foreground"]),
+    (
+        "list.activeSelectionBackground",
+        &["panel.current.background"],
+    ),
+    (
+        "list.activeSelectionForeground",
+        &["panel.current.foreground"],
+    ),
+    ("list.hoverBackground", &["panel.hovered.background"]),
+    ("list.hoverForeground", &["panel.hovered.foreground"]),
+    ("list.highlightForeground", &["palette.match"]),
+    ("activityBar.background", &["activity.background"]),
+    ("activityBar.foreground", &["lapce.icon.active"]),
+    ("activityBar.inactiveForeground", &["lapce.icon.inactive"]),
+    ("statusBar.background", &["status.background"]),
+    ("statusBar.foreground", &["status.foreground"]),
+    ("quickInput.background", &["palette.background"]),
+    ("quickInput.foreground", &["palette.foreground"]),
+    (
+        "quickInputList.focusBackground",
+        &["palette.current.background"],
+    ),
+    (
+        "quickInputList.focusForeground",
+        &["palette.current.foreground"],
+    ),
+    ("editorWidget.border", &["palette.border"]),
+    ("editorSuggestWidget.background", &["completion.background"]),
+    (
+        "editorSuggestWidget.selectedBackground",
+        &["completion.current"],
+    ),
+    ("editorHoverWidget.background", &["hover.background"]),
+    (
+        "gitDecoration.addedResourceForeground",
+        &["source_control.added"],
+    ),
+    (
+        "gitDecoration.deletedResourceForeground",
+        &["source_control.removed"],
+    ),
+    (
+        "gitDecoration.modifiedResourceForeground",
+        &["source_control.modified"],
+    ),
+    ("testing.iconPassed", &["test.passed"]),
+    ("testing.iconFailed", &["test.failed"]),
+    ("terminal.background", &["terminal.background"]),
+    ("terminal.foreground", &["terminal.foreground"]),
+    ("terminalCursor.foreground", &["terminal.cursor"]),
+    ("terminal.selectionBackground", &["terminal.selection"]),
+    ("terminal.ansiBlack", &["terminal.black"]),
+    ("terminal.ansiRed", &["terminal.red"]),
+    ("terminal.ansiGreen", &["terminal.green"]),
+    ("terminal.ansiYellow", &["terminal.yellow"]),
+    ("terminal.ansiBlue", &["terminal.blue"]),
+    ("terminal.ansiMagenta", &["terminal.magenta"]),
+    ("terminal.ansiCyan", &["terminal.cyan"]),
+    ("terminal.ansiWhite", &["terminal.white"]),
+    ("terminal.ansiBrightBlack", &["terminal.bright_black"]),
+    ("terminal.ansiBrightRed", &["terminal.bright_red"]),
+    ("terminal.ansiBrightGreen", &["terminal.bright_green"]),
+    ("terminal.ansiBrightYellow", &["terminal.bright_yellow"]),
+    ("terminal.ansiBrightBlue", &["terminal.bright_blue"]),
+    ("terminal.ansiBrightMagenta", &["terminal.bright_magenta"]),
+    ("terminal.ansiBrightCyan", &["terminal.bright_cyan"]),
+    ("terminal.ansiBrightWhite", &["terminal.bright_white"]),
+];
+
+/// The base colors taken from the terminal palette of a VS Code theme, so that
+/// the colors inherited from the Lapce theme it extends fit in
+const BASE_COLORS: &[(&str, &[&str])] = &[
+    ("terminal.ansiRed", &["red"]),
+    ("terminal.ansiGreen", &["green"]),
+    ("terminal.ansiYellow", &["yellow"]),
+    ("terminal.ansiBlue", &["blue"]),
+    ("terminal.ansiMagenta", &["magenta", "purple"]),
+    ("terminal.ansiCyan", &["cyan"]),
+];
+
+/// The Lapce syntax colors, which are tree-sitter highlight names and semantic
+/// token types, and the TextMate scope whose color they get
+const SYNTAX_SCOPES: &[(&str, &str)] = &[
+    ("comment", "comment.line"),
+    ("constant", "constant.language"),
+    ("number", "constant.numeric"),
+    ("string", "string.quoted"),
+    ("escape", "constant.character.escape"),
+    ("string.escape", "constant.character.escape"),
+    ("keyword", "keyword.control"),
+    ("operator", "keyword.operator"),
+    ("selfKeyword", "variable.language.self"),
+    ("type", "entity.name.type"),
+    ("typeAlias", "entity.name.type.alias"),
+    ("enum", "entity.name.type.enum"),
+    ("struct", "entity.name.type.struct"),
+    ("structure", "entity.name.type.struct"),
+    ("interface", "entity.name.type.interface"),
+    ("type.builtin", "support.type.primitive"),
+    ("builtinType", "support.type.primitive"),
+    ("namespace", "entity.name.namespace"),
+    ("function", "entity.name.function"),
+    ("method", "entity.name.function.member"),
+    ("function.method", "entity.name.function.member"),
+    ("macro", "entity.name.function.macro"),
+    ("constructor", "entity.name.function.constructor"),
+    ("attribute", "entity.other.attribute-name"),
+    ("tag", "entity.name.tag"),
+    ("variable", "variable.other"),
+    ("parameter", "variable.parameter"),
+    ("field", "variable.other.property"),
+    ("property", "variable.other.property"),
+    ("variable.other.member", "variable.other.member"),
+    ("enumMember", "variable.other.enummember"),
+    ("enum-member", "variable.other.enummember"),
+    ("punctuation.delimiter", "punctuation.separator"),
+    ("embedded", "punctuation.section.embedded"),
+    ("text.title", "markup.heading"),
+    ("text.uri", "markup.underline.link"),
+    ("text.reference", "string.other.link"),
+];
+
+/// A color theme which was converted from another editor's format.
+#[derive(Debug, Default)]
+pub struct ImportedTheme {
+    pub name: String,
+    pub dark: bool,
+    pub base: IndexMap<String, String>,
+    pub ui: IndexMap<String, String>,
+    pub syntax: IndexMap<String, String>,
+}
+
+impl ImportedTheme {
+    /// Convert a VS Code JSON color theme or a TextMate `.tmTheme` file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let mut theme = if extension == "tmtheme" {
+            let value = plist::Value::from_file(path)?;
+            Self::from_tm_theme(&value)?
+        } else {
+            let value = Self::read_vscode_theme(path, 0)?;
+            Self::from_vscode_theme(&value)?
+        };
+
+        if theme.name.is_empty() {
+            theme.name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Imported Theme")
+                .to_string();
+        }
+        Ok(theme)
+    }
+
+    /// Read a VS Code theme, with the themes it `include`s merged under it.
+    fn read_vscode_theme(path: &Path, depth: usize) -> Result<Value> {
+        let content = std::fs::read_to_string(path)?;
+        let mut value: Value = serde_json::from_str(&strip_jsonc(&content))?;
+
+        let include = value
+            .get("include")
+            .and_then(|v| v.as_str())
+            .filter(|_| depth < 8);
+        if let Some(include) = include {
+            let include = path.parent().unwrap_or(path).join(include);
+            let mut parent = Self::read_vscode_theme(&include, depth + 1)?;
+            merge_vscode_theme(&mut parent, value);
+            value = parent;
+        }
+
+        Ok(value)
+    }
+
+    pub fn from_vscode_theme(value: &Value) -> Result<Self> {
+        let colors = value
+            .get("colors")
+            .and_then(|v| v.as_object())
+            .ok_or_else(|| anyhow!("not a VS Code color theme"))?;
+        let color = |key: &str| {
+            colors
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|c| Color::parse(c).is_some())
+        };
+
+        let mut theme = Self {
+            name: value
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            ..Default::default()
+        };
+
+        theme.dark = match value.get("type").and_then(|v| v.as_str()) {
+            Some(kind) => !kind.contains("light"),
+            None => color("editor.background")
+                .and_then(Color::parse)
+                .map(is_dark)
+                .unwrap_or(true),
+        };
+
+        for (key, names) in BASE_COLORS {
+            if let Some(color) = color(key) {
+                for name in names.iter() {
+                    theme.base.insert(name.to_string(), color.to_string());
+                }
+            }
+        }
+        for (key, names) in UI_COLORS {
+            if let Some(color) = color(key) {
+                for name in names.iter() {
+                    theme.ui.insert(name.to_string(), color.to_string());
+                }
+            }
+        }
+
+        let rules: Vec<(String, String)> = value
+            .get("tokenColors")
+            .and_then(|v| v.as_array())
+            .map(|rules| {
+                rules
+                    .iter()
+                    .filter_map(|rule| {
+                        let scope = match rule.get("scope")? {
+                            Value::String(scope) => scope.clone(),
+                            Value::Array(scopes) => scopes
+                                .iter()
+                                .filter_map(|s| s.as_str())
+                                .collect::<Vec<_>>()
+                                .join(","),
+                            _ => return None,
+                        };
+                        let foreground = rule
+                            .get("settings")?
+                            .get("foreground")?
+                            .as_str()
+                            .filter(|c| Color::parse(c).is_some())?
+                            .to_string();
+                        Some((scope, foreground))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        theme.syntax = syntax_colors(&rules);
+
+        // semantic token colors are keyed the way Lapce keys them already,
+        // like `variable.readonly`
+        if let Some(semantic) =
+            value.get("semanticTokenColors").and_then(|v| v.as_object())
+        {
+            for (key, style) in semantic {
+                if key.contains(':') || key.starts_with('*') {
+                    continue;
+                }
+                let color = match style {
+                    Value::String(color) => Some(color.as_str()),
+                    Value::Object(style) => {
+                        style.get("foreground").and_then(|v| v.as_str())
+                    }
+                    _ => None,
+                };
+                if let Some(color) = color.filter(|c| Color::parse(c).is_some()) {
+                    theme.syntax.insert(key.to_string(), color.to_string());
+                }
+            }
+        }
+
+        Ok(theme)
+    }
+
+    pub fn from_tm_theme(value: &plist::Value) -> Result<Self> {
+        let dict = value
+            .as_dictionary()
+            .ok_or_else(|| anyhow!("not a TextMate theme"))?;
+        let settings = dict
+            .get("settings")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow!("not a TextMate theme"))?;
+
+        let mut theme = Self {
+            name: dict
+                .get("name")
+                .and_then(|v| v.as_string())
+                .unwrap_or_default()
+                .to_string(),
+            dark: true,
+            ..Default::default()
+        };
+
+        let mut rules = Vec::new();
+        for setting in settings.iter().filter_map(|v| v.as_dictionary()) {
+            let Some(colors) =
+                setting.get("settings").and_then(|v| v.as_dictionary())
+            else {
+                continue;
+            };
+            let color = |key: &str| {
+                colors
+                    .get(key)
+                    .and_then(|v| v.as_string())
+                    .filter(|c| Color::parse(c).is_some())
+            };
+
+            match setting.get("scope").and_then(|v| v.as_string()) {
+                Some(scope) => {
+                    if let Some(foreground) = color("foreground") {
+                        rules.push((scope.to_string(), foreground.to_string()));
+                    }
+                }
+                // the settings without a scope are the editor colors
+                None => {
+                    for (key, name) in [
+                        ("background", "editor.background"),
+                        ("foreground", "editor.foreground"),
+                        ("caret", "editor.caret"),
+                        ("selection", "editor.selection"),
+                        ("lineHighlight", "editor.current_line"),
+                        ("invisibles", "editor.visible_whitespace"),
+                        ("guide", "editor.indent_guide"),
+                    ] {
+                        if let Some(color) = color(key) {
+                            theme.ui.insert(name.to_string(), color.to_string());
+                        }
+                    }
+                    if let Some(background) = color("background") {
+                        theme.dark =
+                            Color::parse(background).map(is_dark).unwrap_or(true);
+                    }
+                }
+            }
+        }
+        theme.syntax = syntax_colors(&rules);
+
+        Ok(theme)
+    }
+
+    /// The content of the Lapce theme file. The colors which weren't converted
+    /// are taken from the default theme of the same kind.
+    pub fn to_toml(&self) -> String {
+        let mut document = toml_edit::Document::new();
+        let mut color_theme = toml_edit::Table::new();
+        color_theme.insert("name", toml_edit::value(self.name.as_str()));
+        color_theme.insert(
+            "extends",
+            toml_edit::value(if self.dark {
+                "Lapce Dark"
+            } else {
+                "Lapce Light"
+            }),
+        );
+        for (key, colors) in [
+            ("base", &self.base),
+            ("syntax", &self.syntax),
+            ("ui", &self.ui),
+        ] {
+            let mut table = toml_edit::Table::new();
+            for (name, color) in colors {
+                table.insert(name, toml_edit::value(color.as_str()));
+            }
+            color_theme.insert(key, toml_edit::Item::Table(table));
+        }
+        document.insert("color-theme", toml_edit::Item::Table(color_theme));
+        document.to_string()
+    }
+
+    /// Rename the theme to "<name> 2", "<name> 3" and so on while there's a
+    /// theme with its name, which `taken` tells by the lowercase name, so that
+    /// installing it doesn't replace that theme. Returns whether it was renamed.
+    pub fn rename_if_taken(&mut self, taken: impl Fn(&str) -> bool) -> bool {
+        if !taken(&self.name.to_lowercase()) {
+            return false;
+        }
+        let name = self.name.clone();
+        let mut i = 2;
+        loop {
+            let new_name = format!("{name} {i}");
+            if !taken(&new_name.to_lowercase()) {
+                self.name = new_name;
+                return true;
+            }
+            i += 1;
+        }
+    }
+
+    /// Write the theme into the themes directory, returning its path. A file
+    /// which is there already is kept, and the theme is written next to it with
+    /// a number added to its file name.
+    pub fn install(&self) -> Result<PathBuf> {
+        let dir = Directory::themes_directory()
+            .ok_or_else(|| anyhow!("can't get themes directory"))?;
+        let file_name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let mut i = 0;
+        loop {
+            let path = if i == 0 {
+                dir.join(format!("{file_name}.toml"))
+            } else {
+                dir.join(format!("{file_name}-{i}.toml"))
+            };
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    file.write_all(self.to_toml().as_bytes())?;
+                    return Ok(path);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    i += 1;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+fn is_dark(color: Color) -> bool {
+    (color.r as u32 + color.g as u32 + color.b as u32) < 128 * 3
+}
+
+/// Pick the color of each Lapce syntax name from TextMate scope rules. Like in
+/// TextMate, the most specific selector which matches the scope wins, and a
+/// later rule wins over an earlier one with the same selector.
+fn syntax_colors(rules: &[(String, String)]) -> IndexMap<String, String> {
+    let mut colors = IndexMap::new();
+    for (name, scope) in SYNTAX_SCOPES {
+        let mut best: Option<(usize, &str)> = None;
+        for (selectors, color) in rules {
+            for selector in selectors.split(',').map(|s| s.trim()) {
+                // selectors which depend on the parent scopes can't be mapped
+                if selector.is_empty() || selector.contains(char::is_whitespace) {
+                    continue;
+                }
+                let matches = *scope == selector
+                    || scope
+                        .strip_prefix(selector)
+                        .map(|rest| rest.starts_with('.'))
+                        .unwrap_or(false);
+                if !matches {
+                    continue;
+                }
+                let specificity = selector.split('.').count();
+                if best.map(|(s, _)| specificity >= s).unwrap_or(true) {
+                    best = Some((specificity, color));
+                }
+            }
+        }
+        if let Some((_, color)) = best {
+            colors.insert(name.to_string(), color.to_string());
+        }
+    }
+    colors
+}
+
+/// Layer an included VS Code theme under the theme which includes it.
+fn merge_vscode_theme(parent: &mut Value, child: Value) {
+    let (Value::Object(parent), Value::Object(child)) = (parent, child) else {
+        return;
+    };
+    for (key, value) in child {
+        match (parent.get_mut(&key), value) {
+            (Some(Value::Object(parent)), Value::Object(child)) => {
+                parent.extend(child);
+            }
+            (Some(Value::Array(parent)), Value::Array(child)) => {
+                parent.extend(child);
+            }
+            (_, value) => {
+                parent.insert(key, value);
+            }
+        }
+    }
+}
+
+/// VS Code themes are JSON with comments and trailing commas, which are
+/// removed here so that it can be parsed as plain JSON.
+fn strip_jsonc(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            result.push(c);
+            if c == '\\' {
+                if let Some(c) = chars.next() {
+                    result.push(c);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                result.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        result.push(c);
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '}' | ']' => {
+                let trimmed = result.trim_end().len();
+                if result[..trimmed].ends_with(',') {
+                    result.truncate(trimmed - 1);
+                }
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_jsonc() {
+        let content = r#"{
+            // a comment
+            "name": "a // b", /* block */
+            "list": [1, 2,],
+        }"#;
+        let value: Value = serde_json::from_str(&strip_jsonc(content)).unwrap();
+        assert_eq!(value["name"], "a // b");
+        assert_eq!(value["list"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_syntax_colors() {
+        let rules = vec![
+            ("comment".to_string(), "#111111".to_string()),
+            ("entity.name".to_string(), "#222222".to_string()),
+            (
+                "entity.name.function, support.function".to_string(),
+                "#333333".to_string(),
+            ),
+            (
+                "source.rust entity.name.type".to_string(),
+                "#444444".to_string(),
+            ),
+        ];
+        let colors = syntax_colors(&rules);
+        assert_eq!(colors.get("comment").unwrap(), "#111111");
+        assert_eq!(colors.get("function").unwrap(), "#333333");
+        assert_eq!(colors.get("type").unwrap(), "#222222");
+        assert!(colors.get("string").is_none());
+    }
+
+    #[test]
+    fn test_vscode_theme() {
+        let value: Value = serde_json::from_str(
+            r##"{
+                "name": "Test",
+                "type": "light",
+                "colors": {
+                    "editor.background": "#ffffff",
+                    "terminal.ansiRed": "#ff0000",
+                    "tab.activeBackground": "not a color"
+                },
+                "tokenColors": [
+                    { "scope": ["string"], "settings": { "foreground": "#00ff00" } }
+                ],
+                "semanticTokenColors": {
+                    "variable.readonly": { "foreground": "#0000ff" },
+                    "*.mutable": "#000000"
+                }
+            }"##,
+        )
+        .unwrap();
+        let theme = ImportedTheme::from_vscode_theme(&value).unwrap();
+        assert!(!theme.dark);
+        assert_eq!(theme.base.get("red").unwrap(), "#ff0000");
+        assert_eq!(theme.ui.get("editor.background").unwrap(), "#ffffff");
+        assert!(theme.ui.get("lapce.tab.active.background").is_none());
+        assert_eq!(theme.syntax.get("string").unwrap(), "#00ff00");
+        assert_eq!(theme.syntax.get("variable.readonly").unwrap(), "#0000ff");
+        assert!(theme.syntax.get("*.mutable").is_none());
+        assert!(theme.to_toml().contains(r#"extends = "Lapce Light""#));
+    }
+
+    #[test]
+    fn test_rename_if_taken() {
+        let taken = ["lapce dark", "monokai", "monokai 2"];
+        let mut theme = ImportedTheme {
+            name: "Solarized".to_string(),
+            ..Default::default()
+        };
+        assert!(!theme.rename_if_taken(|name| taken.contains(&name)));
+        assert_eq!(theme.name, "Solarized");
+
+        theme.name = "Monokai".to_string();
+        assert!(theme.rename_if_taken(|name| taken.contains(&name)));
+        assert_eq!(theme.name, "Monokai 3");
+
+        theme.name = "Lapce Dark".to_string();
+        assert!(theme.rename_if_taken(|name| taken.contains(&name)));
+        assert_eq!(theme.name, "Lapce Dark 2");
+    }
+}
@@ -2,6 +2,17 @@
 use serde::{Deserialize, Serialize};
 use structdesc::FieldNames;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ZoomMode {
+    /// The whole window is scaled
+    #[default]
+    #[serde(rename = "window")]
+    Window,
+    /// Only the fonts of the editor, the terminal and the panels are scaled
+    #[serde(rename = "editor")]
+    Editor,
+}
+
 #[derive(FieldNames, Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct UIConfig {
@@ -48,6 +59,36 @@ pub struct UIConfig {
 
     #[field_names(desc = "Set the line height for list items")]
     list_line_height: usize,
+
+    #[field_names(
+        desc = "What zooming in and out scales.\nOptions: window (the whole UI) or editor (the fonts of the editor, the terminal and the panels)."
+    )]
+    pub zoom_mode: ZoomMode,
+
+    #[field_names(
+        desc = "The items shown on the left of the status bar, in order. Plugin items are referred to as \"author.plugin.id\""
+    )]
+    pub status_bar_left: Vec<String>,
+
+    #[field_names(
+        desc = "The items shown on the right of the status bar, in order. Plugin items are referred to as \"author.plugin.id\""
+    )]
+    pub status_bar_right: Vec<String>,
+
+    #[field_names(
+        desc = "The status bar items which are hidden. Plugin items which aren't placed by the two settings above are shown unless they're listed here"
+    )]
+    pub status_bar_hidden: Vec<String>,
+
+    #[field_names(
+        desc = "Set the title of the window. The variables are ${workspace}, ${remote}, ${branch}, ${file}, ${path}, ${dirty} and ${separator}, which is only kept between parts that aren't empty"
+    )]
+    pub window_title: String,
+
+    #[field_names(
+        desc = "Set the labels of the editor tabs. The variables are ${file}, ${dir}, ${path}, ${dirty} and ${separator}, where ${dir} is the end of the folder which tells the file apart from the others of the same name in the editor group"
+    )]
+    pub tab_label: String,
 }
 
 impl UIConfig {
@@ -55,10 +96,35 @@ pub fn font_size(&self) -> usize {
         self.font_size.max(6).min(32)
     }
 
+    /// Scale the font sizes of the panels and the rest of the UI by `zoom`.
+    pub fn zoom(&mut self, zoom: f64) {
+        let scale = |size: usize| (size as f64 * zoom).round() as usize;
+        self.font_size = scale(self.font_size);
+        // The ones which are 0 follow the UI font size
+        self.hover_font_size = scale(self.hover_font_size);
+        self.icon_size = scale(self.icon_size);
+    }
+
     pub fn font_family(&self) -> Vec<FamilyOwned> {
         FamilyOwned::parse_list(&self.font_family).collect()
     }
 
+    pub fn hover_font_family(&self) -> Vec<FamilyOwned> {
+        if self.hover_font_family.is_empty() {
+            self.font_family()
+        } else {
+            FamilyOwned::parse_list(&self.hover_font_family).collect()
+        }
+    }
+
+    pub fn hover_font_size(&self) -> usize {
+        if self.hover_font_size == 0 {
+            self.font_size()
+        } else {
+            self.hover_font_size.max(6).min(32)
+        }
+    }
+
     pub fn header_height(&self) -> usize {
         let font_size = self.font_size();
         self.header_height.max(font_size)
@@ -2,6 +2,10 @@
 
 use crossbeam_channel::Sender;
 
+/// How long to wait for more changes before reloading, which is kept short so
+/// that edits to a theme show up right away
+const RELOAD_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+
 pub struct ConfigWatcher {
     tx: Sender<()>,
     delay_handler: Arc<AtomicBool>,
@@ -27,9 +31,7 @@ fn handle_event(&mut self, event: notify::Result<notify::Event>) {
                         let config_mutex = self.delay_handler.clone();
                         let tx = self.tx.clone();
                         std::thread::spawn(move || {
-                            std::thread::sleep(std::time::Duration::from_millis(
-                                500,
-                            ));
+                            std::thread::sleep(RELOAD_DELAY);
                             let _ = tx.send(());
                             config_mutex
                                 .store(false, std::sync::atomic::Ordering::Relaxed);
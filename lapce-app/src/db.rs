@@ -26,6 +26,7 @@ pub enum SaveEvent {
     DisabledVolts(Vec<VoltID>),
     WorkspaceDisabledVolts(Arc<LapceWorkspace>, Vec<VoltID>),
     PanelOrder(PanelOrder),
+    ClipboardHistory(im::Vector<String>),
 }
 
 #[derive(Clone)]
@@ -75,6 +76,9 @@ pub fn new() -> Result<Self> {
                     SaveEvent::PanelOrder(order) => {
                         let _ = local_db.insert_panel_orders(&order);
                     }
+                    SaveEvent::ClipboardHistory(items) => {
+                        let _ = local_db.insert_clipboard_history(&items);
+                    }
                 }
             }
         });
@@ -369,6 +373,28 @@ fn insert_panel_orders(&self, order: &PanelOrder) -> Result<()> {
         Ok(())
     }
 
+    pub fn get_clipboard_history(&self) -> Result<im::Vector<String>> {
+        let sled_db = self.get_db()?;
+        let items = sled_db
+            .get("clipboard_history")?
+            .ok_or_else(|| anyhow!("can't find clipboard history"))?;
+        let items = std::str::from_utf8(&items)?;
+        let items = serde_json::from_str(items)?;
+        Ok(items)
+    }
+
+    pub fn save_clipboard_history(&self, items: im::Vector<String>) {
+        let _ = self.save_tx.send(SaveEvent::ClipboardHistory(items));
+    }
+
+    fn insert_clipboard_history(&self, items: &im::Vector<String>) -> Result<()> {
+        let items = serde_json::to_string(items)?;
+        let sled_db = self.get_db()?;
+        sled_db.insert("clipboard_history", items.as_str())?;
+        sled_db.flush()?;
+        Ok(())
+    }
+
     pub fn save_doc_position(
         &self,
         workspace: &LapceWorkspace,
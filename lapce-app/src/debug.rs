@@ -5,19 +5,36 @@
     time::Instant,
 };
 
-use floem::reactive::{
-    create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalSet, SignalUpdate,
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{
+        create_rw_signal, RwSignal, Scope, SignalGet, SignalGetUntracked, SignalSet,
+        SignalUpdate, SignalWith, SignalWithUntracked,
+    },
 };
+use lapce_core::buffer::rope_text::RopeText;
 use lapce_rpc::{
     dap_types::{
-        DapId, RunDebugConfig, SourceBreakpoint, StackFrame, Stopped, ThreadId,
+        self, DapId, DataBreakpoint, DebuggerCapabilities, EvaluateResponse,
+        ExceptionBreakpointsFilter, Output, RunDebugConfig, SourceBreakpoint,
+        StackFrame, Stopped, Thread, ThreadId,
     },
+    inline_value::InlineValue,
+    proxy::{ProxyResponse, ProxyRpcHandler},
     terminal::TermId,
+    RpcError,
 };
 use serde::{Deserialize, Serialize};
+use tracing::error;
 
 const DEFAULT_RUN_TOML: &str = include_str!("../../defaults/run.toml");
 
+/// How many lines above the line where a frame stopped are looked through for
+/// the variables which it shows inline, when the language server doesn't say
+const INLINE_VALUES_LINES: usize = 30;
+/// The longest value which is shown inline, past which it's cut off
+const INLINE_VALUE_MAX_LEN: usize = 50;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RunDebugMode {
     Run,
@@ -40,6 +57,9 @@ pub struct RunDebugProcess {
     pub config: RunDebugConfig,
     pub stopped: bool,
     pub created: Instant,
+    /// Whether the process runs in the terminal, which it doesn't for a debug
+    /// session which attached to a process or the child session of another
+    pub in_terminal: bool,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -58,7 +78,16 @@ pub fn run_configs(workspace: Option<&Path>) -> Option<RunDebugConfigs> {
         return None;
     }
     let content = std::fs::read_to_string(run_toml).ok()?;
-    let configs: RunDebugConfigs = toml_edit::easy::from_str(&content).ok()?;
+    let mut configs: RunDebugConfigs = toml_edit::easy::from_str(&content).ok()?;
+    for config in configs.configs.iter_mut() {
+        let Some(env_file) = config.env_file.as_ref() else {
+            continue;
+        };
+        match std::fs::read_to_string(workspace.join(env_file)) {
+            Ok(content) => config.add_env_file(&content),
+            Err(e) => error!("failed to read the env file {env_file}: {e}"),
+        }
+    }
     Some(configs)
 }
 
@@ -67,17 +96,33 @@ pub struct RunDebugData {
     pub active_term: RwSignal<Option<TermId>>,
     pub daps: RwSignal<im::HashMap<DapId, DapData>>,
     pub breakpoints: RwSignal<BTreeMap<PathBuf, Vec<LapceBreakpoint>>>,
+    /// The watch expressions of the workspace
+    pub watches: RwSignal<im::Vector<String>>,
+    /// The exception filters which were toggled, by their ids, where the others
+    /// are as the debugger defaults them
+    pub exception_filters: RwSignal<BTreeMap<String, bool>>,
+    /// The category of the output which the debug console shows, or `None` for
+    /// all of it but the telemetry, with the evaluated expressions
+    pub console_category: RwSignal<Option<DapOutputCategory>>,
 }
 
 impl RunDebugData {
-    pub fn new(cx: Scope) -> Self {
+    pub fn new(
+        cx: Scope,
+        breakpoints: RwSignal<BTreeMap<PathBuf, Vec<LapceBreakpoint>>>,
+    ) -> Self {
         let active_term = create_rw_signal(cx, None);
         let daps = create_rw_signal(cx, im::HashMap::new());
-        let breakpoints = create_rw_signal(cx, BTreeMap::new());
+        let watches = create_rw_signal(cx, im::Vector::new());
+        let exception_filters = create_rw_signal(cx, BTreeMap::new());
+        let console_category = create_rw_signal(cx, None);
         Self {
             active_term,
             daps,
             breakpoints,
+            watches,
+            exception_filters,
+            console_category,
         }
     }
 
@@ -90,27 +135,253 @@ pub fn source_breakpoints(&self) -> HashMap<PathBuf, Vec<SourceBreakpoint>> {
                     path.to_path_buf(),
                     breakpoints
                         .iter()
-                        .map(|b| SourceBreakpoint {
-                            line: b.line + 1,
-                            column: None,
-                            condition: None,
-                            hit_condition: None,
-                            log_message: None,
-                        })
+                        .map(LapceBreakpoint::source_breakpoint)
                         .collect(),
                 )
             })
             .collect()
     }
+
+    /// Add a breakpoint on the line, or remove the one which is there.
+    pub fn toggle_breakpoint(
+        &self,
+        path: &Path,
+        line: usize,
+        offset: usize,
+        proxy: &ProxyRpcHandler,
+    ) {
+        self.breakpoints.update(|breakpoints| {
+            let breakpoints = breakpoints.entry(path.to_path_buf()).or_default();
+            if let Some(index) = breakpoints.iter().position(|b| b.is_on_line(line))
+            {
+                breakpoints.remove(index);
+            } else {
+                breakpoints.push(LapceBreakpoint::new(line, offset));
+                breakpoints.sort_by_key(|b| b.line);
+            }
+        });
+        self.send_breakpoints(path, proxy);
+    }
+
+    /// The value of the field of the breakpoint which is edited, or `None` when
+    /// the line has no breakpoint or the field isn't set.
+    pub fn breakpoint_field(&self, edit: &BreakpointEdit) -> Option<String> {
+        self.breakpoints.with_untracked(|breakpoints| {
+            let breakpoint = breakpoints
+                .get(&edit.path)?
+                .iter()
+                .find(|b| b.is_on_line(edit.line))?;
+            breakpoint.field(edit.field).clone()
+        })
+    }
+
+    /// Set the field of the breakpoint, which is added when the line has none,
+    /// where an empty value clears the field.
+    pub fn set_breakpoint_field(
+        &self,
+        edit: &BreakpointEdit,
+        value: String,
+        proxy: &ProxyRpcHandler,
+    ) {
+        self.breakpoints.update(|breakpoints| {
+            let breakpoints = breakpoints.entry(edit.path.clone()).or_default();
+            let breakpoint =
+                match breakpoints.iter().position(|b| b.is_on_line(edit.line)) {
+                    Some(index) => &mut breakpoints[index],
+                    None => {
+                        breakpoints
+                            .push(LapceBreakpoint::new(edit.line, edit.offset));
+                        breakpoints.last_mut().unwrap()
+                    }
+                };
+            let value = value.trim();
+            *breakpoint.field_mut(edit.field) =
+                (!value.is_empty()).then(|| value.to_string());
+            breakpoints.sort_by_key(|b| b.line);
+        });
+        self.send_breakpoints(&edit.path, proxy);
+    }
+
+    /// Take the answer of a session to the breakpoints of the file, which is in
+    /// the order they were sent in. An answer to breakpoints which were added
+    /// or removed since is dropped, as the change sends them again.
+    pub fn breakpoints_resp(&self, path: &Path, resp: &[dap_types::Breakpoint]) {
+        self.breakpoints.update(|breakpoints| {
+            if let Some(breakpoints) = breakpoints.get_mut(path) {
+                if breakpoints.len() == resp.len() {
+                    for (breakpoint, resp) in breakpoints.iter_mut().zip(resp) {
+                        breakpoint.set_dap_breakpoint(resp);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Take a breakpoint which the debugger changed, added or removed by itself.
+    pub fn breakpoint_changed(
+        &self,
+        reason: &str,
+        breakpoint: &dap_types::Breakpoint,
+    ) {
+        self.breakpoints.update(|breakpoints| {
+            apply_breakpoint_event(breakpoints, reason, breakpoint);
+        });
+    }
+
+    /// Forget what the debug sessions said about the breakpoints, for a session
+    /// which is started to answer anew.
+    pub fn reset_breakpoints(&self) {
+        self.breakpoints.update(|breakpoints| {
+            for breakpoint in breakpoints.values_mut().flatten() {
+                breakpoint.id = None;
+                breakpoint.verified = false;
+                breakpoint.message = None;
+                breakpoint.dap_line = None;
+            }
+        });
+    }
+
+    /// Send the breakpoints of the file to every debug session.
+    fn send_breakpoints(&self, path: &Path, proxy: &ProxyRpcHandler) {
+        let breakpoints = self.breakpoints.with_untracked(|breakpoints| {
+            breakpoints
+                .get(path)
+                .map(|breakpoints| {
+                    breakpoints
+                        .iter()
+                        .map(LapceBreakpoint::source_breakpoint)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        });
+        for dap_id in self.daps.get_untracked().keys() {
+            proxy.dap_set_breakpoints(
+                *dap_id,
+                path.to_path_buf(),
+                breakpoints.clone(),
+            );
+        }
+    }
+
+    pub fn add_watch(&self, expression: String, proxy: &ProxyRpcHandler) {
+        let expression = expression.trim();
+        if expression.is_empty() {
+            return;
+        }
+        self.watches.update(|watches| {
+            watches.push_back(expression.to_string());
+        });
+        self.send_watches(proxy);
+    }
+
+    pub fn remove_watch(&self, index: usize, proxy: &ProxyRpcHandler) {
+        if index >= self.watches.with_untracked(|watches| watches.len()) {
+            return;
+        }
+        self.watches.update(|watches| {
+            watches.remove(index);
+        });
+        self.send_watches(proxy);
+    }
+
+    /// Send the watch expressions to the proxy, and evaluate them again in the
+    /// sessions which are stopped.
+    fn send_watches(&self, proxy: &ProxyRpcHandler) {
+        let expressions: Vec<String> =
+            self.watches.get_untracked().into_iter().collect();
+        proxy.dap_set_watches(expressions.clone());
+        for dap in self.daps.get_untracked().values() {
+            dap.set_watch_expressions(&expressions);
+            if let (true, Some(frame_id)) =
+                (dap.stopped.get_untracked(), dap.frame_id.get_untracked())
+            {
+                proxy.dap_evaluate_watches(dap.dap_id, frame_id);
+            }
+        }
+    }
+
+    /// Whether the debuggers break on the exceptions of the filter.
+    pub fn is_exception_filter_enabled(
+        &self,
+        filter: &ExceptionBreakpointsFilter,
+    ) -> bool {
+        self.exception_filters
+            .with(|toggled| exception_filter_enabled(toggled, filter))
+    }
+
+    pub fn toggle_exception_filter(
+        &self,
+        filter: &ExceptionBreakpointsFilter,
+        proxy: &ProxyRpcHandler,
+    ) {
+        self.exception_filters.update(|toggled| {
+            let enabled = exception_filter_enabled(toggled, filter);
+            toggled.insert(filter.filter.clone(), !enabled);
+        });
+        proxy.dap_set_exception_filters(
+            self.exception_filters.get_untracked().into_iter().collect(),
+        );
+    }
+
+    /// The rows of the watch panel, which are only the expressions when there's
+    /// no session to evaluate them in.
+    pub fn watch_rows(&self, dap: Option<&DapData>) -> im::Vector<DapWatchRow> {
+        if let Some(dap) = dap.filter(|dap| dap.stopped.get()) {
+            return dap.watch_rows();
+        }
+        self.watches.with(|watches| {
+            watches
+                .iter()
+                .enumerate()
+                .map(|(i, expression)| DapWatchRow {
+                    error: false,
+                    row: DapVariableRow {
+                        path: vec![i],
+                        name: expression.clone(),
+                        value: None,
+                        ty: None,
+                        expandable: false,
+                        expanded: false,
+                    },
+                })
+                .collect()
+        })
+    }
+}
+
+fn exception_filter_enabled(
+    toggled: &BTreeMap<String, bool>,
+    filter: &ExceptionBreakpointsFilter,
+) -> bool {
+    toggled
+        .get(&filter.filter)
+        .copied()
+        .unwrap_or_else(|| filter.default.unwrap_or(false))
 }
 
 #[derive(Clone, PartialEq)]
 pub struct StackTraceData {
+    /// The name of the thread
+    pub name: RwSignal<String>,
+    /// Whether the thread is stopped, which it can be while the others run
+    pub stopped: RwSignal<bool>,
     pub expanded: RwSignal<bool>,
     pub frames: RwSignal<im::Vector<StackFrame>>,
     pub frames_shown: usize,
 }
 
+impl StackTraceData {
+    fn new(cx: Scope, name: String) -> Self {
+        Self {
+            name: create_rw_signal(cx, name),
+            stopped: create_rw_signal(cx, false),
+            expanded: create_rw_signal(cx, false),
+            frames: create_rw_signal(cx, im::Vector::new()),
+            frames_shown: 20,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct LapceBreakpoint {
     pub id: Option<usize>,
@@ -119,6 +390,408 @@ pub struct LapceBreakpoint {
     pub line: usize,
     pub offset: usize,
     pub dap_line: Option<usize>,
+    /// The expression which has to be true for the breakpoint to stop
+    pub condition: Option<String>,
+    /// How many hits it takes for the breakpoint to stop, like `>= 3`
+    pub hit_condition: Option<String>,
+    /// The message which is logged instead of stopping, which makes the
+    /// breakpoint a logpoint
+    pub log_message: Option<String>,
+}
+
+impl LapceBreakpoint {
+    pub fn new(line: usize, offset: usize) -> Self {
+        Self {
+            id: None,
+            verified: false,
+            message: None,
+            line,
+            offset,
+            dap_line: None,
+            condition: None,
+            hit_condition: None,
+            log_message: None,
+        }
+    }
+
+    fn field(&self, field: BreakpointField) -> &Option<String> {
+        match field {
+            BreakpointField::Condition => &self.condition,
+            BreakpointField::HitCondition => &self.hit_condition,
+            BreakpointField::LogMessage => &self.log_message,
+        }
+    }
+
+    fn field_mut(&mut self, field: BreakpointField) -> &mut Option<String> {
+        match field {
+            BreakpointField::Condition => &mut self.condition,
+            BreakpointField::HitCondition => &mut self.hit_condition,
+            BreakpointField::LogMessage => &mut self.log_message,
+        }
+    }
+
+    /// The line which the gutter shows the breakpoint on, which is the one that
+    /// the debugger moved it to when it did.
+    pub fn shown_line(&self) -> usize {
+        self.dap_line.unwrap_or(self.line)
+    }
+
+    fn is_on_line(&self, line: usize) -> bool {
+        self.line == line || self.dap_line == Some(line)
+    }
+
+    /// Take what the debugger said about the breakpoint, whose line counts
+    /// from one.
+    fn set_dap_breakpoint(&mut self, breakpoint: &dap_types::Breakpoint) {
+        self.id = breakpoint.id;
+        self.verified = breakpoint.verified;
+        self.message = breakpoint.message.clone();
+        self.dap_line = breakpoint
+            .line
+            .map(|line| line.saturating_sub(1))
+            .filter(|line| *line != self.line);
+    }
+
+    pub fn source_breakpoint(&self) -> SourceBreakpoint {
+        SourceBreakpoint {
+            line: self.line + 1,
+            column: None,
+            condition: self.condition.clone(),
+            hit_condition: self.hit_condition.clone(),
+            log_message: self.log_message.clone(),
+        }
+    }
+}
+
+/// The frame which the execution stopped in, which is the top one of the thread
+/// that stopped.
+pub fn stopped_frame<'a>(
+    stopped: &Stopped,
+    stack_traces: &'a HashMap<ThreadId, Vec<StackFrame>>,
+) -> Option<&'a StackFrame> {
+    stack_traces
+        .get(&stopped.thread_id.unwrap_or_default())?
+        .first()
+}
+
+/// The file of the frame and the position in it, counted from 0, when its
+/// source is a file rather than one which the debugger makes up.
+pub fn frame_position(frame: &StackFrame) -> Option<(PathBuf, lsp_types::Position)> {
+    let path = frame.source.as_ref()?.path.clone()?;
+    let position = lsp_types::Position {
+        line: frame.line.saturating_sub(1) as u32,
+        character: frame.column.saturating_sub(1) as u32,
+    };
+    Some((path, position))
+}
+
+/// Apply the change of the `breakpoint` event of a debugger to the breakpoints,
+/// which are found by the id that the debugger gave them. A new breakpoint is
+/// added to its line, unless there's one there already.
+fn apply_breakpoint_event(
+    breakpoints: &mut BTreeMap<PathBuf, Vec<LapceBreakpoint>>,
+    reason: &str,
+    breakpoint: &dap_types::Breakpoint,
+) {
+    let known = breakpoint.id.is_some()
+        && breakpoints
+            .values()
+            .flatten()
+            .any(|b| b.id.is_some() && b.id == breakpoint.id);
+    match reason {
+        "removed" => {
+            if known {
+                for breakpoints in breakpoints.values_mut() {
+                    breakpoints.retain(|b| b.id != breakpoint.id);
+                }
+            }
+        }
+        "new" if !known => {
+            let path = breakpoint
+                .source
+                .as_ref()
+                .and_then(|source| source.path.clone());
+            let (Some(path), Some(line)) = (path, breakpoint.line) else {
+                return;
+            };
+            let line = line.saturating_sub(1);
+            let breakpoints = breakpoints.entry(path).or_default();
+            if breakpoints.iter().any(|b| b.is_on_line(line)) {
+                return;
+            }
+            // The offset of the line isn't known without the document
+            let mut new = LapceBreakpoint::new(line, 0);
+            new.set_dap_breakpoint(breakpoint);
+            breakpoints.push(new);
+            breakpoints.sort_by_key(|b| b.line);
+        }
+        _ => {
+            if let Some(b) = breakpoints
+                .values_mut()
+                .flatten()
+                .find(|b| b.id.is_some() && b.id == breakpoint.id)
+            {
+                b.set_dap_breakpoint(breakpoint);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakpointField {
+    Condition,
+    HitCondition,
+    LogMessage,
+}
+
+/// The field of the breakpoint of a line which is edited in the palette.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BreakpointEdit {
+    pub path: PathBuf,
+    pub line: usize,
+    /// The offset of the start of the line, for when the breakpoint is added
+    pub offset: usize,
+    pub field: BreakpointField,
+}
+
+/// A scope or a variable of the variables tree, whose children are only
+/// requested the first time that it's expanded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DapVariable {
+    pub name: String,
+    /// The value of the variable, which the scopes don't have
+    pub value: Option<String>,
+    pub ty: Option<String>,
+    /// The expression which the debugger gives the value of the variable by
+    pub evaluate_name: Option<String>,
+    /// The reference of the children, which is 0 when there are none
+    pub reference: usize,
+    /// The reference of the memory which the variable is stored in
+    pub memory_reference: Option<String>,
+    pub expanded: bool,
+    pub children: Option<Vec<DapVariable>>,
+}
+
+impl DapVariable {
+    fn get<'a>(variables: &'a [DapVariable], path: &[usize]) -> Option<&'a Self> {
+        let (first, rest) = path.split_first()?;
+        let variable = variables.get(*first)?;
+        if rest.is_empty() {
+            return Some(variable);
+        }
+        Self::get(variable.children.as_ref()?, rest)
+    }
+
+    fn get_mut(variables: &mut [DapVariable], path: &[usize]) -> Option<&mut Self> {
+        let (first, rest) = path.split_first()?;
+        let variable = variables.get_mut(*first)?;
+        if rest.is_empty() {
+            return Some(variable);
+        }
+        Self::get_mut(variable.children.as_mut()?, rest)
+    }
+
+    fn line(text: &str, reference: usize) -> Self {
+        Self {
+            name: text.to_string(),
+            value: None,
+            ty: None,
+            evaluate_name: None,
+            reference,
+            memory_reference: None,
+            expanded: false,
+            children: None,
+        }
+    }
+}
+
+/// The trees of variables which can be expanded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariableTree {
+    /// The scopes of the frame which is shown
+    Frame,
+    /// The results and the output in the debug console
+    Console,
+    /// The values of the watch expressions
+    Watch,
+}
+
+fn push_variable_rows(
+    rows: &mut im::Vector<DapVariableRow>,
+    variables: &[DapVariable],
+    path: &mut Vec<usize>,
+) {
+    for (i, variable) in variables.iter().enumerate() {
+        path.push(i);
+        push_variable_row(rows, variable, path);
+        path.pop();
+    }
+}
+
+fn push_variable_row(
+    rows: &mut im::Vector<DapVariableRow>,
+    variable: &DapVariable,
+    path: &mut Vec<usize>,
+) {
+    rows.push_back(DapVariableRow {
+        path: path.clone(),
+        name: variable.name.clone(),
+        value: variable.value.clone(),
+        ty: variable.ty.clone(),
+        expandable: variable.reference > 0,
+        expanded: variable.expanded,
+    });
+    if variable.expanded {
+        if let Some(children) = variable.children.as_ref() {
+            push_variable_rows(rows, children, path);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DapConsoleKind {
+    /// An expression which was evaluated
+    Input,
+    /// The output of the program, or the result of an expression
+    Output,
+    /// The output of the program to stderr, or an expression which failed
+    Error,
+}
+
+/// The categories of the output of a debugger, which the debug console can be
+/// filtered by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DapOutputCategory {
+    /// The messages of the debugger itself
+    Console,
+    Stdout,
+    Stderr,
+    Telemetry,
+}
+
+impl DapOutputCategory {
+    pub const ALL: [DapOutputCategory; 4] = [
+        DapOutputCategory::Console,
+        DapOutputCategory::Stdout,
+        DapOutputCategory::Stderr,
+        DapOutputCategory::Telemetry,
+    ];
+
+    /// The category of an output event, where the ones which aren't known, like
+    /// `important`, are shown as the messages of the debugger.
+    pub fn from_category(category: Option<&str>) -> Self {
+        match category {
+            Some("stdout") => DapOutputCategory::Stdout,
+            Some("stderr") => DapOutputCategory::Stderr,
+            Some("telemetry") => DapOutputCategory::Telemetry,
+            _ => DapOutputCategory::Console,
+        }
+    }
+
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            DapOutputCategory::Console => "debug-console-console",
+            DapOutputCategory::Stdout => "debug-console-stdout",
+            DapOutputCategory::Stderr => "debug-console-stderr",
+            DapOutputCategory::Telemetry => "debug-console-telemetry",
+        }
+    }
+}
+
+/// The place in a source file which an output of the debugger comes from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DapOutputLocation {
+    pub path: PathBuf,
+    /// The line and the column, which start at 1
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A line of the debug console, whose variable is the text of the line and can
+/// be expanded when the line is a structured value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DapConsoleLine {
+    pub kind: DapConsoleKind,
+    pub variable: DapVariable,
+    /// The category of the output, which the evaluated expressions don't have
+    pub category: Option<DapOutputCategory>,
+    pub location: Option<DapOutputLocation>,
+}
+
+/// A row of the debug console which is shown, which is either a line or a
+/// child of a line which was expanded.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DapConsoleRow {
+    pub kind: DapConsoleKind,
+    pub row: DapVariableRow,
+    /// Where the output of the line comes from, which only its first row has
+    pub location: Option<DapOutputLocation>,
+}
+
+/// A watch expression, whose variable is named by the expression and has the
+/// value of its last evaluation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DapWatch {
+    pub variable: DapVariable,
+    /// Whether the value is the reason that the expression couldn't be evaluated
+    pub error: bool,
+}
+
+impl DapWatch {
+    fn new(expression: &str) -> Self {
+        Self {
+            variable: DapVariable::line(expression, 0),
+            error: false,
+        }
+    }
+}
+
+/// A row of the watch panel, where the path of a row starts with the index of
+/// its expression.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DapWatchRow {
+    pub error: bool,
+    pub row: DapVariableRow,
+}
+
+/// A row of the variables tree which is shown.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DapVariableRow {
+    /// The indices of the variable in the tree, starting from its scope
+    pub path: Vec<usize>,
+    pub name: String,
+    pub value: Option<String>,
+    pub ty: Option<String>,
+    pub expandable: bool,
+    pub expanded: bool,
+}
+
+/// What the value of a variable is assigned to when it's set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VariableTarget {
+    /// The variable of the name, in the container of the reference
+    Variable { reference: usize, name: String },
+    /// An expression, like the one of a watch
+    Expression(String),
+}
+
+/// The variable of a tree of a session whose value is edited in the palette.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariableEdit {
+    pub dap_id: DapId,
+    pub tree: VariableTree,
+    pub path: Vec<usize>,
+}
+
+/// A breakpoint on the value of a variable, which only lasts for the session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DapDataBreakpoint {
+    pub data_id: String,
+    /// What the debugger calls the data, like the name of the variable
+    pub description: String,
+    pub verified: bool,
+    /// Why the debugger couldn't set the breakpoint
+    pub message: Option<String>,
 }
 
 #[derive(Clone)]
@@ -128,27 +801,274 @@ pub struct DapData {
     pub stopped: RwSignal<bool>,
     pub thread_id: RwSignal<Option<ThreadId>>,
     pub stack_traces: RwSignal<BTreeMap<ThreadId, StackTraceData>>,
+    /// The frame whose variables are shown
+    pub frame_id: RwSignal<Option<usize>>,
+    /// The scopes of the frame, with their variables which were expanded
+    pub variables: RwSignal<Vec<DapVariable>>,
+    /// The lines of the debug console
+    pub console: RwSignal<Vec<DapConsoleLine>>,
+    /// The watch expressions, with their values in the frame
+    pub watches: RwSignal<Vec<DapWatch>>,
+    /// The exception filters which the debugger offers
+    pub exception_filters: RwSignal<Vec<ExceptionBreakpointsFilter>>,
+    /// Whether the debugger can break when the value of a variable changes
+    pub supports_data_breakpoints: RwSignal<bool>,
+    pub data_breakpoints: RwSignal<Vec<DapDataBreakpoint>>,
+    /// Whether the debugger can set the values of variables
+    pub supports_set_variable: RwSignal<bool>,
+    /// Whether the debugger can assign to expressions, like the ones of watches
+    pub supports_set_expression: RwSignal<bool>,
+    /// Whether the debugger can run a frame again from its start
+    pub supports_restart_frame: RwSignal<bool>,
+    /// Whether the debugger can move the execution to another line
+    pub supports_goto_targets: RwSignal<bool>,
+    pub supports_read_memory: RwSignal<bool>,
+    pub supports_write_memory: RwSignal<bool>,
+    /// Whether a thread can be paused and resumed while the others keep
+    /// running or stay stopped
+    pub supports_single_thread: RwSignal<bool>,
+    pub supports_terminate_threads: RwSignal<bool>,
+    /// Whether the debugger can run backwards, like the record and replay ones
+    pub supports_step_back: RwSignal<bool>,
 }
 
 impl DapData {
-    pub fn new(cx: Scope, dap_id: DapId, term_id: TermId) -> Self {
+    pub fn new(
+        cx: Scope,
+        dap_id: DapId,
+        term_id: TermId,
+        watches: &im::Vector<String>,
+    ) -> Self {
         let stopped = create_rw_signal(cx, false);
         let thread_id = create_rw_signal(cx, None);
         let stack_traces = create_rw_signal(cx, BTreeMap::new());
+        let frame_id = create_rw_signal(cx, None);
+        let variables = create_rw_signal(cx, Vec::new());
+        let console = create_rw_signal(cx, Vec::new());
+        let watches = create_rw_signal(
+            cx,
+            watches
+                .iter()
+                .map(|expression| DapWatch::new(expression))
+                .collect(),
+        );
+        let exception_filters = create_rw_signal(cx, Vec::new());
+        let supports_data_breakpoints = create_rw_signal(cx, false);
+        let data_breakpoints = create_rw_signal(cx, Vec::new());
+        let supports_set_variable = create_rw_signal(cx, false);
+        let supports_set_expression = create_rw_signal(cx, false);
+        let supports_restart_frame = create_rw_signal(cx, false);
+        let supports_goto_targets = create_rw_signal(cx, false);
+        let supports_read_memory = create_rw_signal(cx, false);
+        let supports_write_memory = create_rw_signal(cx, false);
+        let supports_single_thread = create_rw_signal(cx, false);
+        let supports_terminate_threads = create_rw_signal(cx, false);
+        let supports_step_back = create_rw_signal(cx, false);
         Self {
             term_id,
             dap_id,
             stopped,
             thread_id,
             stack_traces,
+            frame_id,
+            variables,
+            console,
+            watches,
+            exception_filters,
+            supports_data_breakpoints,
+            data_breakpoints,
+            supports_set_variable,
+            supports_set_expression,
+            supports_restart_frame,
+            supports_goto_targets,
+            supports_read_memory,
+            supports_write_memory,
+            supports_single_thread,
+            supports_terminate_threads,
+            supports_step_back,
         }
     }
 
+    pub fn set_capabilities(&self, capabilities: &DebuggerCapabilities) {
+        self.exception_filters.set(
+            capabilities
+                .exception_breakpoint_filters
+                .clone()
+                .unwrap_or_default(),
+        );
+        self.supports_data_breakpoints
+            .set(capabilities.supports_data_breakpoints.unwrap_or(false));
+        self.supports_set_variable
+            .set(capabilities.supports_set_variable.unwrap_or(false));
+        self.supports_set_expression
+            .set(capabilities.supports_set_expression.unwrap_or(false));
+        self.supports_restart_frame
+            .set(capabilities.supports_restart_frame.unwrap_or(false));
+        self.supports_goto_targets
+            .set(capabilities.supports_goto_targets_request.unwrap_or(false));
+        self.supports_read_memory
+            .set(capabilities.supports_read_memory_request.unwrap_or(false));
+        self.supports_write_memory
+            .set(capabilities.supports_write_memory_request.unwrap_or(false));
+        self.supports_single_thread.set(
+            capabilities
+                .supports_single_thread_execution_requests
+                .unwrap_or(false),
+        );
+        self.supports_terminate_threads.set(
+            capabilities
+                .supports_terminate_threads_request
+                .unwrap_or(false),
+        );
+        self.supports_step_back
+            .set(capabilities.supports_step_back.unwrap_or(false));
+    }
+
+    /// What setting the value of the variable at the path of the tree assigns
+    /// to. A watch is assigned to by its expression, and a child by its name
+    /// in its container, or by its expression when the debugger can only set
+    /// those. The scopes and the lines of the debug console can't be set.
+    pub fn set_value_target(
+        &self,
+        tree: VariableTree,
+        path: &[usize],
+    ) -> Option<VariableTarget> {
+        let set_variable = self.supports_set_variable.get_untracked();
+        let set_expression = self.supports_set_expression.get_untracked();
+        let (_, parent) = path.split_last()?;
+        if parent.is_empty() {
+            if tree != VariableTree::Watch || !set_expression {
+                return None;
+            }
+            return self
+                .variable(tree, path, |v| v.name.clone())
+                .map(VariableTarget::Expression);
+        }
+        let container = self.variable(tree, parent, |v| v.reference)?;
+        let (name, evaluate_name) = self
+            .variable(tree, path, |v| (v.name.clone(), v.evaluate_name.clone()))?;
+        if set_variable && container > 0 {
+            Some(VariableTarget::Variable {
+                reference: container,
+                name,
+            })
+        } else if set_expression {
+            evaluate_name.map(VariableTarget::Expression)
+        } else {
+            None
+        }
+    }
+
+    /// Show the value which the variable at the path of the tree was set to,
+    /// and get the variables which it could have changed again: the ones of
+    /// its scope, or of its watch or line, and the watches. The watches aren't
+    /// evaluated again for a child of one, which would collapse it.
+    pub fn value_set(
+        &self,
+        cx: Scope,
+        edit: &VariableEdit,
+        value: String,
+        proxy: &ProxyRpcHandler,
+    ) {
+        self.update_variable(edit.tree, &edit.path, |variable| {
+            variable.value = Some(value);
+        });
+        if edit.path.len() > 1 {
+            self.reload_children(cx, edit.tree, vec![edit.path[0]], proxy);
+            if edit.tree == VariableTree::Watch {
+                return;
+            }
+        }
+        if let (true, Some(frame_id)) =
+            (self.stopped.get_untracked(), self.frame_id.get_untracked())
+        {
+            if !self.watches.with_untracked(|watches| watches.is_empty()) {
+                proxy.dap_evaluate_watches(self.dap_id, frame_id);
+            }
+        }
+    }
+
+    /// The reference of the container and the name of the variable at the path
+    /// of the frame's tree, which the debugger is asked about a data breakpoint
+    /// with. The scopes themselves can't be broken on.
+    pub fn data_breakpoint_target(&self, path: &[usize]) -> Option<(usize, String)> {
+        let (_, parent) = path.split_last()?;
+        if parent.is_empty() {
+            return None;
+        }
+        self.variables.with_untracked(|variables| {
+            let container = DapVariable::get(variables, parent)?;
+            let variable = DapVariable::get(variables, path)?;
+            Some((container.reference, variable.name.clone()))
+        })
+    }
+
+    /// Add the breakpoint, unless there's one on the data already, and send all
+    /// of them to the debugger.
+    pub fn add_data_breakpoint(
+        &self,
+        data_id: String,
+        description: String,
+        proxy: &ProxyRpcHandler,
+    ) {
+        let exists = self.data_breakpoints.with_untracked(|breakpoints| {
+            breakpoints.iter().any(|b| b.data_id == data_id)
+        });
+        if exists {
+            return;
+        }
+        self.data_breakpoints.update(|breakpoints| {
+            breakpoints.push(DapDataBreakpoint {
+                data_id,
+                description,
+                verified: false,
+                message: None,
+            });
+        });
+        self.send_data_breakpoints(proxy);
+    }
+
+    pub fn remove_data_breakpoint(&self, index: usize, proxy: &ProxyRpcHandler) {
+        self.data_breakpoints.update(|breakpoints| {
+            if index < breakpoints.len() {
+                breakpoints.remove(index);
+            }
+        });
+        self.send_data_breakpoints(proxy);
+    }
+
+    fn send_data_breakpoints(&self, proxy: &ProxyRpcHandler) {
+        let breakpoints = self.data_breakpoints.with_untracked(|breakpoints| {
+            breakpoints
+                .iter()
+                .map(|breakpoint| DataBreakpoint {
+                    data_id: breakpoint.data_id.clone(),
+                    access_type: None,
+                    condition: None,
+                    hit_condition: None,
+                })
+                .collect()
+        });
+        proxy.dap_set_data_breakpoints(self.dap_id, breakpoints);
+    }
+
+    /// Whether the debugger could set the data breakpoints, which it answers in
+    /// the order that they were sent.
+    pub fn data_breakpoints_resp(&self, resp: &[dap_types::Breakpoint]) {
+        self.data_breakpoints.update(|breakpoints| {
+            for (breakpoint, resp) in breakpoints.iter_mut().zip(resp) {
+                breakpoint.verified = resp.verified;
+                breakpoint.message = resp.message.clone();
+            }
+        });
+    }
+
     pub fn stopped(
         &self,
         cx: Scope,
         stopped: &Stopped,
         stack_traces: &HashMap<ThreadId, Vec<StackFrame>>,
+        proxy: &ProxyRpcHandler,
     ) {
         self.stopped.set(true);
         self.thread_id.update(|thread_id| {
@@ -156,26 +1076,875 @@ pub fn stopped(
         });
 
         let main_thread_id = self.thread_id.get_untracked();
+        let all_threads_stopped = stopped.all_threads_stopped.unwrap_or(false);
         self.stack_traces.update(|current_stack_traces| {
-            current_stack_traces.retain(|t, _| stack_traces.contains_key(t));
+            // The frames are of all the threads when all of them stopped
+            if all_threads_stopped {
+                current_stack_traces.retain(|t, _| stack_traces.contains_key(t));
+            }
             for (thread_id, frames) in stack_traces {
-                let is_main_thread = main_thread_id.as_ref() == Some(thread_id);
-                if let Some(current) = current_stack_traces.get_mut(thread_id) {
-                    current.frames.set(frames.into());
-                    if is_main_thread {
-                        current.expanded.set(true);
+                let current =
+                    current_stack_traces.entry(*thread_id).or_insert_with(|| {
+                        StackTraceData::new(cx, thread_id.to_string())
+                    });
+                current.frames.set(frames.into());
+                current.stopped.set(true);
+                if main_thread_id.as_ref() == Some(thread_id) {
+                    current.expanded.set(true);
+                }
+            }
+        });
+
+        let top_frame = stopped_frame(stopped, stack_traces).map(|frame| frame.id);
+        match top_frame {
+            Some(frame_id) => self.load_scopes(cx, frame_id, proxy),
+            None => {
+                self.frame_id.set(None);
+                self.variables.set(Vec::new());
+            }
+        }
+    }
+
+    /// Keep the stack traces of the threads which the debuggee has now, with
+    /// their names.
+    pub fn set_threads(&self, cx: Scope, threads: &[Thread]) {
+        self.stack_traces.update(|stack_traces| {
+            stack_traces.retain(|id, _| threads.iter().any(|t| t.id == *id));
+            for thread in threads {
+                match stack_traces.get(&thread.id) {
+                    Some(current) => {
+                        if current.name.get_untracked() != thread.name {
+                            current.name.set(thread.name.clone());
+                        }
+                    }
+                    None => {
+                        stack_traces.insert(
+                            thread.id,
+                            StackTraceData::new(cx, thread.name.clone()),
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    /// The thread resumed, or all of them when it's `None`. The debugger stays
+    /// stopped while a thread is, and the variables move to another one when
+    /// the thread which they were of resumed.
+    pub fn continued(
+        &self,
+        cx: Scope,
+        thread_id: Option<ThreadId>,
+        proxy: &ProxyRpcHandler,
+    ) {
+        let Some(thread_id) = thread_id else {
+            self.stopped.set(false);
+            self.stack_traces.with_untracked(|stack_traces| {
+                for stack_trace in stack_traces.values() {
+                    stack_trace.stopped.set(false);
+                }
+            });
+            return;
+        };
+        let next = self.stack_traces.with_untracked(|stack_traces| {
+            if let Some(stack_trace) = stack_traces.get(&thread_id) {
+                stack_trace.stopped.set(false);
+                stack_trace.frames.set(im::Vector::new());
+            }
+            stack_traces
+                .iter()
+                .find(|(_, stack_trace)| stack_trace.stopped.get_untracked())
+                .map(|(id, stack_trace)| {
+                    (
+                        *id,
+                        stack_trace.frames.with_untracked(|f| f.head().cloned()),
+                    )
+                })
+        });
+        let Some((next_thread_id, top_frame)) = next else {
+            self.stopped.set(false);
+            return;
+        };
+        if self.thread_id.get_untracked() == Some(thread_id) {
+            self.thread_id.set(Some(next_thread_id));
+            match top_frame {
+                Some(frame) => self.load_scopes(cx, frame.id, proxy),
+                None => {
+                    self.frame_id.set(None);
+                    self.variables.set(Vec::new());
+                }
+            }
+        }
+    }
+
+    /// The file and the line of the frame whose variables are shown, while the
+    /// session is stopped.
+    pub fn frame_location(&self) -> Option<(PathBuf, usize)> {
+        if !self.stopped.get() {
+            return None;
+        }
+        let frame_id = self.frame_id.get()?;
+        self.stack_traces.with(|stack_traces| {
+            stack_traces.values().find_map(|stack_trace| {
+                stack_trace.frames.with(|frames| {
+                    let frame = frames.iter().find(|frame| frame.id == frame_id)?;
+                    let (path, position) = frame_position(frame)?;
+                    Some((path, position.line as usize))
+                })
+            })
+        })
+    }
+
+    /// The rows of the variables tree, which are the scopes and the children of
+    /// the variables which are expanded.
+    pub fn variable_rows(&self) -> im::Vector<DapVariableRow> {
+        let mut rows = im::Vector::new();
+        self.variables.with(|variables| {
+            push_variable_rows(&mut rows, variables, &mut Vec::new());
+        });
+        rows
+    }
+
+    /// The rows of the debug console with the output of the category, where the
+    /// path of a row starts with the index of its line.
+    pub fn console_rows(
+        &self,
+        category: Option<DapOutputCategory>,
+    ) -> im::Vector<DapConsoleRow> {
+        self.console.with(|console| {
+            let mut rows = im::Vector::new();
+            for (i, line) in console.iter().enumerate() {
+                let shown = match category {
+                    Some(category) => line.category == Some(category),
+                    None => line.category != Some(DapOutputCategory::Telemetry),
+                };
+                if !shown {
+                    continue;
+                }
+                let mut variable_rows = im::Vector::new();
+                push_variable_row(&mut variable_rows, &line.variable, &mut vec![i]);
+                rows.extend(variable_rows.into_iter().enumerate().map(
+                    |(j, row)| DapConsoleRow {
+                        kind: line.kind,
+                        row,
+                        location: if j == 0 { line.location.clone() } else { None },
+                    },
+                ));
+            }
+            rows
+        })
+    }
+
+    pub fn watch_rows(&self) -> im::Vector<DapWatchRow> {
+        self.watches.with(|watches| {
+            let mut rows = im::Vector::new();
+            for (i, watch) in watches.iter().enumerate() {
+                let mut variable_rows = im::Vector::new();
+                push_variable_row(&mut variable_rows, &watch.variable, &mut vec![i]);
+                rows.extend(variable_rows.into_iter().map(|row| DapWatchRow {
+                    error: watch.error,
+                    row,
+                }));
+            }
+            rows
+        })
+    }
+
+    /// Change the watch expressions, where the ones which are kept keep their
+    /// values until they're evaluated again.
+    fn set_watch_expressions(&self, expressions: &[String]) {
+        self.watches.update(|watches| {
+            let mut old = std::mem::take(watches);
+            for expression in expressions {
+                let watch = match old
+                    .iter()
+                    .position(|watch| &watch.variable.name == expression)
+                {
+                    Some(index) => old.remove(index),
+                    None => DapWatch::new(expression),
+                };
+                watches.push(watch);
+            }
+        });
+    }
+
+    /// Show the value of the watch expression, unless it's of a frame which
+    /// isn't shown anymore.
+    pub fn watch_evaluated(
+        &self,
+        frame_id: usize,
+        expression: &str,
+        result: &Result<EvaluateResponse, String>,
+    ) {
+        if self.frame_id.get_untracked() != Some(frame_id) {
+            return;
+        }
+        self.watches.update(|watches| {
+            for watch in watches
+                .iter_mut()
+                .filter(|watch| watch.variable.name == expression)
+            {
+                let (value, ty, reference, memory_reference, error) = match result {
+                    Ok(resp) => (
+                        resp.result.clone(),
+                        resp.ty.clone(),
+                        resp.variables_reference,
+                        resp.memory_reference.clone(),
+                        false,
+                    ),
+                    Err(err) => (err.clone(), None, 0, None, true),
+                };
+                watch.variable = DapVariable {
+                    value: Some(value),
+                    ty,
+                    memory_reference,
+                    ..DapVariable::line(expression, reference)
+                };
+                watch.error = error;
+            }
+        });
+    }
+
+    fn push_console_line(&self, kind: DapConsoleKind, text: &str, reference: usize) {
+        self.console.update(|console| {
+            console.push(DapConsoleLine {
+                kind,
+                variable: DapVariable::line(text, reference),
+                category: None,
+                location: None,
+            });
+        });
+    }
+
+    /// Show the output of the program in the debug console, where each of its
+    /// lines is a line of the console unless it's a structured value. The
+    /// location of the output goes with its first line.
+    pub fn output(&self, output: &Output) {
+        let category = DapOutputCategory::from_category(output.category.as_deref());
+        let kind = if category == DapOutputCategory::Stderr {
+            DapConsoleKind::Error
+        } else {
+            DapConsoleKind::Output
+        };
+        let mut location = output
+            .source
+            .as_ref()
+            .and_then(|source| source.path.clone())
+            .zip(output.line)
+            .map(|(path, line)| DapOutputLocation {
+                path,
+                line,
+                column: output.column.unwrap_or(1),
+            });
+        let reference = output.variables_reference.unwrap_or(0);
+        let lines = if reference > 0 {
+            vec![output.output.trim_end()]
+        } else {
+            output.output.lines().collect()
+        };
+        self.console.update(|console| {
+            for line in lines {
+                console.push(DapConsoleLine {
+                    kind,
+                    variable: DapVariable::line(line, reference),
+                    category: Some(category),
+                    location: location.take(),
+                });
+            }
+        });
+    }
+
+    /// Evaluate the expression of the debug console in the frame whose variables
+    /// are shown, and show its result in the console.
+    pub fn evaluate(&self, cx: Scope, expression: String, proxy: &ProxyRpcHandler) {
+        self.push_console_line(DapConsoleKind::Input, &expression, 0);
+        let frame_id = if self.stopped.get_untracked() {
+            self.frame_id.get_untracked()
+        } else {
+            None
+        };
+        let dap = self.clone();
+        let send =
+            create_ext_action(cx, move |result: Result<ProxyResponse, RpcError>| {
+                match result {
+                    Ok(ProxyResponse::DapEvaluate { result }) => {
+                        dap.console.update(|console| {
+                            console.push(DapConsoleLine {
+                                kind: DapConsoleKind::Output,
+                                variable: DapVariable {
+                                    ty: result.ty,
+                                    memory_reference: result.memory_reference,
+                                    ..DapVariable::line(
+                                        &result.result,
+                                        result.variables_reference,
+                                    )
+                                },
+                                category: None,
+                                location: None,
+                            });
+                        });
                     }
+                    Ok(_) => {}
+                    Err(err) => {
+                        dap.push_console_line(
+                            DapConsoleKind::Error,
+                            &err.message,
+                            0,
+                        );
+                    }
+                }
+            });
+        proxy.dap_evaluate(self.dap_id, expression, frame_id, move |result| {
+            send(result);
+        });
+    }
+
+    /// Call the function with the variable at the path of the tree, if it has
+    /// one there.
+    pub fn variable<R>(
+        &self,
+        tree: VariableTree,
+        path: &[usize],
+        f: impl FnOnce(&DapVariable) -> R,
+    ) -> Option<R> {
+        let (first, rest) = path.split_first()?;
+        let get = |variable: &DapVariable| {
+            if rest.is_empty() {
+                Some(f(variable))
+            } else {
+                DapVariable::get(variable.children.as_ref()?, rest).map(f)
+            }
+        };
+        match tree {
+            VariableTree::Frame => self
+                .variables
+                .with_untracked(|variables| get(variables.get(*first)?)),
+            VariableTree::Console => self
+                .console
+                .with_untracked(|console| get(&console.get(*first)?.variable)),
+            VariableTree::Watch => self
+                .watches
+                .with_untracked(|watches| get(&watches.get(*first)?.variable)),
+        }
+    }
+
+    fn update_variable(
+        &self,
+        tree: VariableTree,
+        path: &[usize],
+        f: impl FnOnce(&mut DapVariable),
+    ) {
+        match tree {
+            VariableTree::Frame => self.variables.update(|variables| {
+                if let Some(variable) = DapVariable::get_mut(variables, path) {
+                    f(variable);
+                }
+            }),
+            VariableTree::Console => self.console.update(|console| {
+                let Some((line, rest)) = path.split_first() else {
+                    return;
+                };
+                let Some(line) = console.get_mut(*line) else {
+                    return;
+                };
+                let variable = if rest.is_empty() {
+                    Some(&mut line.variable)
                 } else {
-                    current_stack_traces.insert(
-                        *thread_id,
-                        StackTraceData {
-                            expanded: create_rw_signal(cx, is_main_thread),
-                            frames: create_rw_signal(cx, frames.into()),
-                            frames_shown: 20,
-                        },
+                    line.variable
+                        .children
+                        .as_mut()
+                        .and_then(|children| DapVariable::get_mut(children, rest))
+                };
+                if let Some(variable) = variable {
+                    f(variable);
+                }
+            }),
+            VariableTree::Watch => self.watches.update(|watches| {
+                let Some((watch, rest)) = path.split_first() else {
+                    return;
+                };
+                let Some(watch) = watches.get_mut(*watch) else {
+                    return;
+                };
+                let variable =
+                    if rest.is_empty() {
+                        Some(&mut watch.variable)
+                    } else {
+                        watch.variable.children.as_mut().and_then(|children| {
+                            DapVariable::get_mut(children, rest)
+                        })
+                    };
+                if let Some(variable) = variable {
+                    f(variable);
+                }
+            }),
+        }
+    }
+
+    /// Show the variables of the frame, where the first scope which isn't
+    /// expensive to get is expanded.
+    pub fn load_scopes(&self, cx: Scope, frame_id: usize, proxy: &ProxyRpcHandler) {
+        self.frame_id.set(Some(frame_id));
+        let dap = self.clone();
+        let local_proxy = proxy.clone();
+        let send =
+            create_ext_action(cx, move |result: Result<ProxyResponse, RpcError>| {
+                if dap.frame_id.get_untracked() != Some(frame_id) {
+                    return;
+                }
+                let Ok(ProxyResponse::DapScopes { scopes }) = result else {
+                    return;
+                };
+                let first = scopes.iter().position(|scope| !scope.expensive);
+                let variables = scopes
+                    .into_iter()
+                    .map(|scope| DapVariable {
+                        name: scope.name,
+                        value: None,
+                        ty: None,
+                        evaluate_name: None,
+                        reference: scope.variables_reference,
+                        memory_reference: None,
+                        expanded: false,
+                        children: None,
+                    })
+                    .collect();
+                dap.variables.set(variables);
+                if let Some(first) = first {
+                    dap.toggle_variable(
+                        cx,
+                        VariableTree::Frame,
+                        vec![first],
+                        &local_proxy,
                     );
                 }
+            });
+        proxy.dap_scopes(self.dap_id, frame_id, move |result| {
+            send(result);
+        });
+    }
+
+    /// Expand or collapse the variable at the path of the tree, where its
+    /// children are requested the first time.
+    pub fn toggle_variable(
+        &self,
+        cx: Scope,
+        tree: VariableTree,
+        path: Vec<usize>,
+        proxy: &ProxyRpcHandler,
+    ) {
+        let mut load = None;
+        self.update_variable(tree, &path, |variable| {
+            if variable.reference == 0 {
+                return;
+            }
+            variable.expanded = !variable.expanded;
+            if variable.expanded && variable.children.is_none() {
+                load = Some(variable.reference);
             }
         });
+        if let Some(reference) = load {
+            self.load_children(cx, tree, path, reference, Vec::new(), proxy);
+        }
+    }
+
+    /// Get the children of the variable at the path of the tree again, where
+    /// the ones which were expanded stay expanded.
+    fn reload_children(
+        &self,
+        cx: Scope,
+        tree: VariableTree,
+        path: Vec<usize>,
+        proxy: &ProxyRpcHandler,
+    ) {
+        let reload = self
+            .variable(tree, &path, |variable| {
+                (variable.expanded && variable.reference > 0).then(|| {
+                    (
+                        variable.reference,
+                        variable.children.clone().unwrap_or_default(),
+                    )
+                })
+            })
+            .flatten();
+        match reload {
+            Some((reference, previous)) => {
+                self.load_children(cx, tree, path, reference, previous, proxy);
+            }
+            None => self.update_variable(tree, &path, |variable| {
+                variable.children = None;
+            }),
+        }
+    }
+
+    /// Request the children of the variable at the path of the tree, where the
+    /// ones which were expanded in the previous children are expanded again.
+    fn load_children(
+        &self,
+        cx: Scope,
+        tree: VariableTree,
+        path: Vec<usize>,
+        reference: usize,
+        previous: Vec<DapVariable>,
+        proxy: &ProxyRpcHandler,
+    ) {
+        let frame_id = self.frame_id.get_untracked();
+        let dap = self.clone();
+        let local_proxy = proxy.clone();
+        let send =
+            create_ext_action(cx, move |result: Result<ProxyResponse, RpcError>| {
+                if tree != VariableTree::Console
+                    && dap.frame_id.get_untracked() != frame_id
+                {
+                    return;
+                }
+                let Ok(ProxyResponse::DapVariables { variables }) = result else {
+                    return;
+                };
+                let mut reopened = Vec::new();
+                let children = variables
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, variable)| {
+                        let old = previous
+                            .iter()
+                            .find(|old| old.name == variable.name && old.expanded);
+                        let expanded =
+                            old.is_some() && variable.variables_reference > 0;
+                        if let (true, Some(old)) = (expanded, old) {
+                            reopened.push((
+                                i,
+                                variable.variables_reference,
+                                old.children.clone().unwrap_or_default(),
+                            ));
+                        }
+                        DapVariable {
+                            name: variable.name,
+                            value: Some(variable.value),
+                            ty: variable.ty,
+                            evaluate_name: variable.evaluate_name,
+                            reference: variable.variables_reference,
+                            memory_reference: variable.memory_reference,
+                            expanded,
+                            children: None,
+                        }
+                    })
+                    .collect();
+                let mut loaded = false;
+                dap.update_variable(tree, &path, |variable| {
+                    if variable.reference == reference {
+                        variable.children = Some(children);
+                        loaded = true;
+                    }
+                });
+                if !loaded {
+                    return;
+                }
+                for (i, reference, previous) in reopened {
+                    let mut path = path.clone();
+                    path.push(i);
+                    dap.load_children(
+                        cx,
+                        tree,
+                        path,
+                        reference,
+                        previous,
+                        &local_proxy,
+                    );
+                }
+            });
+        proxy.dap_variables(self.dap_id, reference, move |result| {
+            send(result);
+        });
+    }
+}
+
+/// The values which are shown at the ends of the lines of the source of a frame
+/// which stopped on the line, by line. The values of the language server are
+/// shown when it has any, where the variables which it looks up are the ones
+/// of the frame. Otherwise each variable is shown on the lines above the one
+/// which stopped that mention it.
+pub fn inline_values(
+    text: &impl RopeText,
+    line: usize,
+    variables: &[dap_types::Variable],
+    lsp_values: &[InlineValue],
+) -> BTreeMap<usize, String> {
+    let lookup = |name: &str, case_sensitive: bool| {
+        variables.iter().find(|v| {
+            if case_sensitive {
+                v.name == name || v.evaluate_name.as_deref() == Some(name)
+            } else {
+                v.name.eq_ignore_ascii_case(name)
+            }
+        })
+    };
+    let text_of = |range: &lsp_types::Range| {
+        let start = text.offset_of_position(&range.start);
+        let end = text.offset_of_position(&range.end);
+        text.slice_to_cow(start..end.max(start)).to_string()
+    };
+
+    let mut values: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    let mut push = |line: usize, value: String| {
+        let line_values = values.entry(line).or_default();
+        if !line_values.contains(&value) {
+            line_values.push(value);
+        }
+    };
+    if lsp_values.is_empty() {
+        for l in line.saturating_sub(INLINE_VALUES_LINES)..=line {
+            let content = text.line_content(l);
+            for word in content.split(|c: char| !c.is_alphanumeric() && c != '_') {
+                if let Some(variable) = lookup(word, true) {
+                    push(l, inline_value(&variable.name, &variable.value));
+                }
+            }
+        }
+    } else {
+        let mut lsp_values: Vec<&InlineValue> = lsp_values.iter().collect();
+        lsp_values.sort_by_key(|value| match value {
+            InlineValue::Text(v) => v.range.start,
+            InlineValue::VariableLookup(v) => v.range.start,
+            InlineValue::EvaluatableExpression(v) => v.range.start,
+        });
+        for value in lsp_values {
+            let (range, value) = match value {
+                InlineValue::Text(v) => (v.range, Some(v.text.clone())),
+                InlineValue::VariableLookup(v) => {
+                    let name =
+                        v.variable_name.clone().unwrap_or_else(|| text_of(&v.range));
+                    let value = lookup(&name, v.case_sensitive_lookup)
+                        .map(|variable| inline_value(&name, &variable.value));
+                    (v.range, value)
+                }
+                InlineValue::EvaluatableExpression(v) => {
+                    let expression =
+                        v.expression.clone().unwrap_or_else(|| text_of(&v.range));
+                    let value = lookup(&expression, true)
+                        .map(|variable| inline_value(&expression, &variable.value));
+                    (v.range, value)
+                }
+            };
+            let value_line = range.end.line as usize;
+            if let (true, Some(value)) = (value_line <= line, value) {
+                push(value_line, value);
+            }
+        }
+    }
+    values
+        .into_iter()
+        .map(|(line, values)| (line, values.join(", ")))
+        .collect()
+}
+
+/// The name and the first line of the value, cut off when it's long
+fn inline_value(name: &str, value: &str) -> String {
+    let value = value.lines().next().unwrap_or("");
+    if value.chars().count() > INLINE_VALUE_MAX_LEN {
+        let value: String = value.chars().take(INLINE_VALUE_MAX_LEN).collect();
+        format!("{name} = {value}\u{2026}")
+    } else {
+        format!("{name} = {value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::buffer::Buffer;
+    use lapce_rpc::{
+        dap_types::{Breakpoint, Source},
+        inline_value::{InlineValueText, InlineValueVariableLookup},
+    };
+
+    use super::*;
+
+    fn dap_breakpoint(id: usize, line: usize, verified: bool) -> Breakpoint {
+        Breakpoint {
+            id: Some(id),
+            verified,
+            message: None,
+            source: Some(Source {
+                path: Some(PathBuf::from("/main.rs")),
+                ..Default::default()
+            }),
+            line: Some(line),
+            column: None,
+            end_line: None,
+            end_column: None,
+            instruction_reference: None,
+            offset: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_breakpoint_event() {
+        let path = PathBuf::from("/main.rs");
+        let mut breakpoint = LapceBreakpoint::new(4, 0);
+        breakpoint.id = Some(1);
+        let mut breakpoints = BTreeMap::from([(path.clone(), vec![breakpoint])]);
+
+        // The debugger verifies the breakpoint and moves it two lines down
+        apply_breakpoint_event(
+            &mut breakpoints,
+            "changed",
+            &dap_breakpoint(1, 7, true),
+        );
+        let b = &breakpoints[&path][0];
+        assert!(b.verified);
+        assert_eq!(b.line, 4);
+        assert_eq!(b.shown_line(), 6);
+        assert!(b.is_on_line(6));
+
+        apply_breakpoint_event(
+            &mut breakpoints,
+            "new",
+            &dap_breakpoint(2, 2, false),
+        );
+        apply_breakpoint_event(&mut breakpoints, "new", &dap_breakpoint(3, 7, true));
+        let lines: Vec<usize> = breakpoints[&path].iter().map(|b| b.line).collect();
+        assert_eq!(lines, vec![1, 4]);
+
+        apply_breakpoint_event(
+            &mut breakpoints,
+            "removed",
+            &dap_breakpoint(1, 7, true),
+        );
+        let ids: Vec<_> = breakpoints[&path].iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![Some(2)]);
+    }
+
+    fn variable(name: &str, value: &str) -> dap_types::Variable {
+        dap_types::Variable {
+            name: name.to_string(),
+            value: value.to_string(),
+            ty: None,
+            evaluate_name: None,
+            variables_reference: 0,
+            named_variables: None,
+            indexed_variables: None,
+            memory_reference: None,
+        }
+    }
+
+    #[test]
+    fn test_inline_values() {
+        let buffer =
+            Buffer::new("let a = 1;\nlet b = a + 2;\nlet ab = b;\nprint(a, b);\n");
+        let variables = vec![variable("a", "1"), variable("b", "3")];
+
+        // The variables are shown on the lines which mention them, up to the
+        // one which stopped, and not on the ones which only have them in words
+        let values = inline_values(&buffer, 2, &variables, &[]);
+        assert_eq!(
+            values,
+            BTreeMap::from([
+                (0, "a = 1".to_string()),
+                (1, "b = 3, a = 1".to_string()),
+                (2, "b = 3".to_string()),
+            ])
+        );
+
+        let lsp_values = vec![
+            InlineValue::VariableLookup(InlineValueVariableLookup {
+                range: lsp_types::Range::new(
+                    lsp_types::Position::new(1, 4),
+                    lsp_types::Position::new(1, 5),
+                ),
+                variable_name: None,
+                case_sensitive_lookup: true,
+            }),
+            InlineValue::Text(InlineValueText {
+                range: lsp_types::Range::new(
+                    lsp_types::Position::new(0, 0),
+                    lsp_types::Position::new(0, 10),
+                ),
+                text: "a is one".to_string(),
+            }),
+            InlineValue::Text(InlineValueText {
+                range: lsp_types::Range::new(
+                    lsp_types::Position::new(3, 0),
+                    lsp_types::Position::new(3, 12),
+                ),
+                text: "past the line".to_string(),
+            }),
+        ];
+        let values = inline_values(&buffer, 2, &variables, &lsp_values);
+        assert_eq!(
+            values,
+            BTreeMap::from([(0, "a is one".to_string()), (1, "b = 3".to_string()),])
+        );
+
+        let long = "x".repeat(INLINE_VALUE_MAX_LEN + 10);
+        assert_eq!(
+            inline_value("a", &format!("{long}\nmore")),
+            format!("a = {}\u{2026}", &long[..INLINE_VALUE_MAX_LEN])
+        );
+    }
+
+    fn stack_frame(id: usize, path: Option<&str>, line: usize) -> StackFrame {
+        StackFrame {
+            id,
+            name: format!("frame {id}"),
+            source: Some(Source {
+                path: path.map(PathBuf::from),
+                source_reference: path.is_none().then_some(7),
+                ..Default::default()
+            }),
+            line,
+            column: 5,
+            end_line: None,
+            end_column: None,
+            can_restart: None,
+            instruction_pointer_reference: None,
+            module_id: None,
+            presentation_hint: None,
+        }
+    }
+
+    #[test]
+    fn test_frame_position() {
+        let frame = stack_frame(1, Some("/main.rs"), 10);
+        assert_eq!(
+            frame_position(&frame),
+            Some((PathBuf::from("/main.rs"), lsp_types::Position::new(9, 4)))
+        );
+
+        // A source which the debugger makes up can't be opened
+        assert_eq!(frame_position(&stack_frame(2, None, 10)), None);
+        let frame = StackFrame {
+            source: None,
+            ..stack_frame(3, Some("/main.rs"), 10)
+        };
+        assert_eq!(frame_position(&frame), None);
+    }
+
+    #[test]
+    fn test_stopped_frame() {
+        let stopped: Stopped =
+            serde_json::from_str(r#"{"reason":"breakpoint","threadId":2}"#).unwrap();
+        let stack_traces: HashMap<ThreadId, Vec<StackFrame>> =
+            serde_json::from_str::<Vec<(ThreadId, Vec<StackFrame>)>>(
+                r#"[
+                    [1, [{"id":1,"name":"main","line":1,"column":1}]],
+                    [2, [
+                        {"id":2,"name":"work","line":3,"column":1},
+                        {"id":3,"name":"spawn","line":8,"column":1}
+                    ]]
+                ]"#,
+            )
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            stopped_frame(&stopped, &stack_traces).map(|f| f.id),
+            Some(2)
+        );
+
+        let stopped = Stopped {
+            thread_id: Some(serde_json::from_str("3").unwrap()),
+            ..stopped
+        };
+        assert_eq!(stopped_frame(&stopped, &stack_traces), None);
     }
 }
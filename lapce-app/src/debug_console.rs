@@ -0,0 +1,151 @@
+use floem::reactive::{
+    create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalSet, SignalUpdate,
+    SignalWithUntracked,
+};
+use lapce_core::{
+    command::{EditCommand, MoveCommand},
+    mode::Mode,
+    selection::Selection,
+};
+use lapce_xi_rope::Rope;
+
+use crate::{
+    command::{CommandExecuted, CommandKind},
+    editor::EditorData,
+    id::EditorId,
+    keypress::{condition::Condition, KeyPressFocus},
+    terminal::panel::TerminalPanelData,
+    window_tab::CommonData,
+};
+
+/// The input of the debug console, whose expressions are evaluated in the active
+/// debug session.
+#[derive(Clone)]
+pub struct DebugConsoleData {
+    pub editor: EditorData,
+    /// The expressions which were evaluated, oldest first
+    history: RwSignal<Vec<String>>,
+    /// The entry of the history which is in the input, while going through it
+    history_index: RwSignal<Option<usize>>,
+    terminal: TerminalPanelData,
+}
+
+impl KeyPressFocus for DebugConsoleData {
+    fn get_mode(&self) -> Mode {
+        Mode::Insert
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        matches!(condition, Condition::PanelFocus)
+    }
+
+    fn run_command(
+        &self,
+        command: &crate::command::LapceCommand,
+        count: Option<usize>,
+        mods: floem::glazier::Modifiers,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Edit(EditCommand::InsertNewLine) => {
+                self.evaluate();
+            }
+            CommandKind::Move(MoveCommand::Up) => {
+                self.previous_history();
+            }
+            CommandKind::Move(MoveCommand::Down) => {
+                self.next_history();
+            }
+            CommandKind::Workbench(_) => {}
+            CommandKind::Focus(_) => {}
+            CommandKind::Edit(_)
+            | CommandKind::Move(_)
+            | CommandKind::MultiSelection(_) => {
+                self.editor.run_command(command, count, mods);
+            }
+            CommandKind::MotionMode(_) => {}
+        }
+        CommandExecuted::Yes
+    }
+
+    fn receive_char(&self, c: &str) {
+        self.editor.receive_char(c);
+    }
+}
+
+impl DebugConsoleData {
+    pub fn new(cx: Scope, terminal: TerminalPanelData, common: CommonData) -> Self {
+        Self {
+            editor: EditorData::new_local(cx, EditorId::next(), common),
+            history: create_rw_signal(cx, Vec::new()),
+            history_index: create_rw_signal(cx, None),
+            terminal,
+        }
+    }
+
+    fn set_input(&self, input: &str) {
+        self.editor
+            .doc
+            .update(|doc| doc.reload(Rope::from(input), true));
+        self.editor
+            .cursor
+            .update(|cursor| cursor.set_insert(Selection::caret(input.len())));
+    }
+
+    /// Evaluate the input in the active debug session.
+    fn evaluate(&self) {
+        let expression = self
+            .editor
+            .doc
+            .with_untracked(|doc| doc.buffer().to_string().trim().to_string());
+        if expression.is_empty() {
+            return;
+        }
+        let Some(dap) = self.terminal.get_active_dap(false) else {
+            return;
+        };
+
+        self.history.update(|history| {
+            if history.last() != Some(&expression) {
+                history.push(expression.clone());
+            }
+        });
+        self.history_index.set(None);
+        self.set_input("");
+        dap.evaluate(self.terminal.cx, expression, &self.terminal.common.proxy);
+    }
+
+    fn previous_history(&self) {
+        let len = self.history.with_untracked(|history| history.len());
+        if len == 0 {
+            return;
+        }
+        let index = match self.history_index.get_untracked() {
+            Some(index) => index.saturating_sub(1),
+            None => len - 1,
+        };
+        self.history_index.set(Some(index));
+        let expression = self
+            .history
+            .with_untracked(|history| history[index].clone());
+        self.set_input(&expression);
+    }
+
+    fn next_history(&self) {
+        let Some(index) = self.history_index.get_untracked() else {
+            return;
+        };
+        let expression = self
+            .history
+            .with_untracked(|history| history.get(index + 1).cloned());
+        match expression {
+            Some(expression) => {
+                self.history_index.set(Some(index + 1));
+                self.set_input(&expression);
+            }
+            None => {
+                self.history_index.set(None);
+                self.set_input("");
+            }
+        }
+    }
+}
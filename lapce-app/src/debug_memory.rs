@@ -0,0 +1,362 @@
+//! The memory of a debug session, which is read a page at a time from the
+//! reference which a variable gives, and whose bytes can be written when the
+//! debugger supports it.
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{
+        create_rw_signal, RwSignal, SignalGetUntracked, SignalSet, SignalUpdate,
+        SignalWith, SignalWithUntracked,
+    },
+};
+use lapce_rpc::{
+    dap_types::{DapId, Memory},
+    proxy::ProxyResponse,
+    RpcError,
+};
+
+use crate::{
+    command::InternalCommand,
+    notification::{Notification, NotificationSeverity},
+    terminal::panel::TerminalPanelData,
+};
+
+/// The number of bytes which are read and shown at a time
+pub const MEMORY_PAGE_SIZE: usize = 256;
+/// The number of bytes of a row of the memory view
+pub const MEMORY_ROW_SIZE: usize = 16;
+
+/// The bytes at the offset from the memory reference of a session, which are
+/// written in the palette.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryEdit {
+    pub dap_id: DapId,
+    pub memory_reference: String,
+    pub offset: i64,
+}
+
+/// A row of the memory view, with the bytes which couldn't be read as `None`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryRow {
+    /// The index of the first byte of the row in the page
+    pub start: usize,
+    pub address: String,
+    pub bytes: Vec<Option<u8>>,
+    /// The bytes which are printable ASCII, with dots for the rest
+    pub ascii: String,
+}
+
+#[derive(Clone)]
+pub struct MemoryViewData {
+    /// The session and the memory reference which are shown
+    pub target: RwSignal<Option<(DapId, String)>>,
+    /// The offset from the reference of the first byte of the page
+    pub offset: RwSignal<i64>,
+    /// The address of the first byte of the page, as the debugger gives it
+    pub address: RwSignal<Option<String>>,
+    pub bytes: RwSignal<Vec<Option<u8>>>,
+    /// Why the memory couldn't be read
+    pub error: RwSignal<Option<String>>,
+    /// Bumped for each read, so that the answers to the older ones are dropped
+    request_id: RwSignal<usize>,
+    terminal: TerminalPanelData,
+}
+
+impl MemoryViewData {
+    pub fn new(terminal: TerminalPanelData) -> Self {
+        let cx = terminal.cx;
+        Self {
+            target: create_rw_signal(cx, None),
+            offset: create_rw_signal(cx, 0),
+            address: create_rw_signal(cx, None),
+            bytes: create_rw_signal(cx, Vec::new()),
+            error: create_rw_signal(cx, None),
+            request_id: create_rw_signal(cx, 0),
+            terminal,
+        }
+    }
+
+    /// Show the memory from the reference of the session.
+    pub fn open(&self, dap_id: DapId, memory_reference: String) {
+        self.target.set(Some((dap_id, memory_reference)));
+        self.offset.set(0);
+        self.address.set(None);
+        self.bytes.set(Vec::new());
+        self.read();
+    }
+
+    /// Whether the debugger of the session which is shown can write its memory.
+    pub fn can_write(&self) -> bool {
+        let Some((dap_id, _)) = self.target.get_untracked() else {
+            return false;
+        };
+        self.terminal.debug.daps.with_untracked(|daps| {
+            daps.get(&dap_id)
+                .map_or(false, |dap| dap.supports_write_memory.get_untracked())
+        })
+    }
+
+    /// Read the page at the offset again.
+    pub fn read(&self) {
+        let Some((dap_id, memory_reference)) = self.target.get_untracked() else {
+            return;
+        };
+        let request_id = self.request_id.get_untracked() + 1;
+        self.request_id.set(request_id);
+
+        let view = self.clone();
+        let send = create_ext_action(
+            self.terminal.cx,
+            move |result: Result<ProxyResponse, RpcError>| {
+                if view.request_id.get_untracked() != request_id {
+                    return;
+                }
+                match result {
+                    Ok(ProxyResponse::DapReadMemory {
+                        address,
+                        data,
+                        unreadable_bytes,
+                    }) => {
+                        let unreadable = unreadable_bytes
+                            .min(MEMORY_PAGE_SIZE.saturating_sub(data.len()));
+                        let bytes = data
+                            .into_iter()
+                            .map(Some)
+                            .chain(std::iter::repeat(None).take(unreadable))
+                            .collect();
+                        view.address.set(Some(address));
+                        view.bytes.set(bytes);
+                        view.error.set(None);
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        view.bytes.set(Vec::new());
+                        view.error.set(Some(err.message));
+                    }
+                }
+            },
+        );
+        self.terminal.common.proxy.dap_read_memory(
+            dap_id,
+            memory_reference,
+            self.offset.get_untracked(),
+            MEMORY_PAGE_SIZE,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// Go to the next page, or the previous one.
+    pub fn page(&self, forward: bool) {
+        let step = MEMORY_PAGE_SIZE as i64;
+        self.offset.update(|offset| {
+            *offset = offset.saturating_add(if forward { step } else { -step });
+        });
+        self.read();
+    }
+
+    /// Read the page again when the memory which the debugger changed could be
+    /// on it. Memory of another reference could be anywhere.
+    pub fn memory_changed(&self, dap_id: DapId, memory: &Memory) {
+        let shown = self.target.with_untracked(|target| {
+            target.as_ref().and_then(|(id, reference)| {
+                (*id == dap_id).then(|| reference == &memory.memory_reference)
+            })
+        });
+        let Some(same_reference) = shown else {
+            return;
+        };
+        let offset = self.offset.get_untracked();
+        if !same_reference
+            || ranges_overlap(offset, MEMORY_PAGE_SIZE, memory.offset, memory.count)
+        {
+            self.read();
+        }
+    }
+
+    /// Read the page again once the session stopped, since the program could
+    /// have changed its memory.
+    pub fn dap_stopped(&self, dap_id: DapId) {
+        let shown = self
+            .target
+            .with_untracked(|target| target.as_ref().map(|(id, _)| *id));
+        if shown == Some(dap_id) {
+            self.read();
+        }
+    }
+
+    /// The byte which is shown at the offset from the reference, in hex.
+    pub fn byte_text(&self, offset: i64) -> Option<String> {
+        let index = usize::try_from(offset - self.offset.get_untracked()).ok()?;
+        self.bytes
+            .with_untracked(|bytes| bytes.get(index).copied().flatten())
+            .map(|byte| format!("{byte:02x}"))
+    }
+
+    /// The rows of the page which is shown.
+    pub fn rows(&self) -> Vec<MemoryRow> {
+        let address = self
+            .address
+            .with(|address| address.as_deref().and_then(parse_address));
+        self.bytes.with(|bytes| memory_rows(address, bytes))
+    }
+
+    /// Write the bytes at the offset from the reference, and read the page again
+    /// once they're written.
+    pub fn write(&self, edit: MemoryEdit, data: Vec<u8>) {
+        let internal_command = self.terminal.common.internal_command;
+        let config = self.terminal.common.config;
+        let view = self.clone();
+        let send = create_ext_action(
+            self.terminal.cx,
+            move |result: Result<ProxyResponse, RpcError>| match result {
+                Ok(_) => view.read(),
+                Err(err) => {
+                    internal_command.send(InternalCommand::ShowNotification {
+                        notification: Notification::new(
+                            NotificationSeverity::Warning,
+                            config
+                                .get_untracked()
+                                .tr("notification-write-memory-failed"),
+                            err.message,
+                        ),
+                    });
+                }
+            },
+        );
+        self.terminal.common.proxy.dap_write_memory(
+            edit.dap_id,
+            edit.memory_reference,
+            edit.offset,
+            data,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+}
+
+fn ranges_overlap(
+    start: i64,
+    len: usize,
+    other_start: i64,
+    other_len: usize,
+) -> bool {
+    start < other_start.saturating_add(other_len as i64)
+        && other_start < start.saturating_add(len as i64)
+}
+
+/// The address which the debugger gives, which is in hex when it starts with
+/// `0x` and in decimal otherwise.
+pub fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+/// The rows of the bytes from the address. Without an address, the rows are
+/// labelled by their offset from the first byte.
+pub fn memory_rows(address: Option<u64>, bytes: &[Option<u8>]) -> Vec<MemoryRow> {
+    bytes
+        .chunks(MEMORY_ROW_SIZE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let start = i * MEMORY_ROW_SIZE;
+            let address = match address {
+                Some(address) => {
+                    format!("0x{:016x}", address.wrapping_add(start as u64))
+                }
+                None => format!("+0x{start:04x}"),
+            };
+            let ascii = chunk
+                .iter()
+                .map(|byte| match byte {
+                    Some(byte) if byte.is_ascii_graphic() || *byte == b' ' => {
+                        *byte as char
+                    }
+                    Some(_) => '.',
+                    None => '?',
+                })
+                .collect();
+            MemoryRow {
+                start,
+                address,
+                bytes: chunk.to_vec(),
+                ascii,
+            }
+        })
+        .collect()
+}
+
+/// The bytes of the input, which are pairs of hex digits that can be split by
+/// whitespace, like `2a 0f ff` or `2a0fff`.
+pub fn parse_hex_bytes(input: &str) -> Option<Vec<u8>> {
+    let digits: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let high = pair[0].to_digit(16)?;
+            let low = pair[1].to_digit(16)?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("0x7ffe0010"), Some(0x7ffe0010));
+        assert_eq!(parse_address("4096"), Some(4096));
+        assert_eq!(parse_address("0xzz"), None);
+    }
+
+    #[test]
+    fn test_memory_rows() {
+        let mut bytes: Vec<Option<u8>> = b"Hello, world!\n\0\x7fab"
+            .iter()
+            .copied()
+            .map(Some)
+            .collect();
+        bytes.push(None);
+        let rows = memory_rows(Some(0x1000), &bytes);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].address, "0x0000000000001000");
+        assert_eq!(rows[0].ascii, "Hello, world!...");
+        assert_eq!(rows[1].start, 16);
+        assert_eq!(rows[1].address, "0x0000000000001010");
+        assert_eq!(rows[1].bytes, vec![Some(b'a'), Some(b'b'), None]);
+        assert_eq!(rows[1].ascii, "ab?");
+
+        let rows = memory_rows(None, &bytes);
+        assert_eq!(rows[1].address, "+0x0010");
+    }
+
+    #[test]
+    fn test_parse_hex_bytes() {
+        assert_eq!(parse_hex_bytes("2a 0f FF"), Some(vec![0x2a, 0x0f, 0xff]));
+        assert_eq!(parse_hex_bytes("2a0f"), Some(vec![0x2a, 0x0f]));
+        assert_eq!(parse_hex_bytes("2a 0"), None);
+        assert_eq!(parse_hex_bytes("zz"), None);
+        assert_eq!(parse_hex_bytes(" "), None);
+    }
+
+    #[test]
+    fn test_ranges_overlap() {
+        assert!(ranges_overlap(0, 256, 255, 1));
+        assert!(ranges_overlap(256, 256, 0, 300));
+        assert!(!ranges_overlap(0, 256, 256, 16));
+        assert!(!ranges_overlap(0, 256, -16, 16));
+    }
+}
@@ -0,0 +1,134 @@
+//! The `lapce://` links, which open a file, a plugin or a setting from a
+//! terminal, a browser or the documentation.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use lapce_rpc::{
+    file::{LineCol, PathObject},
+    plugin::VoltID,
+};
+use lsp_types::Url;
+
+pub const SCHEME: &str = "lapce";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeepLink {
+    /// `lapce://open?path=<path>&line=<line>&column=<column>`, where the line and
+    /// the column start at 1
+    Open(PathObject),
+    /// `lapce://plugin/<author>.<name>`
+    Plugin(VoltID),
+    /// `lapce://settings/<key>`, such as `editor.font-size`
+    Settings(String),
+}
+
+impl DeepLink {
+    pub fn parse(link: &str) -> Result<DeepLink> {
+        let url = Url::parse(link)?;
+        if url.scheme() != SCHEME {
+            return Err(anyhow!("not a {SCHEME}:// link"));
+        }
+        let rest = url.path().trim_start_matches('/');
+        match url.host_str().unwrap_or("") {
+            "open" => {
+                let mut path = None;
+                let mut line = None;
+                let mut column = None;
+                for (key, value) in url.query_pairs() {
+                    match key.as_ref() {
+                        "path" => path = Some(PathBuf::from(value.as_ref())),
+                        "line" => line = value.parse::<usize>().ok(),
+                        "column" => column = value.parse::<usize>().ok(),
+                        _ => {}
+                    }
+                }
+                let path = path.ok_or_else(|| anyhow!("the link has no path"))?;
+                // The link doesn't know which folder it's opened from
+                if !path.is_absolute() {
+                    return Err(anyhow!("the path of the link isn't absolute"));
+                }
+                Ok(DeepLink::Open(PathObject {
+                    is_dir: path.is_dir(),
+                    path,
+                    linecol: line.map(|line| LineCol {
+                        line,
+                        column: column.unwrap_or(1),
+                    }),
+                }))
+            }
+            "plugin" => {
+                let (author, name) = rest
+                    .split_once('.')
+                    .ok_or_else(|| anyhow!("the plugin isn't <author>.<name>"))?;
+                Ok(DeepLink::Plugin(VoltID {
+                    author: author.to_string(),
+                    name: name.to_string(),
+                }))
+            }
+            "settings" if !rest.is_empty() => {
+                Ok(DeepLink::Settings(rest.to_string()))
+            }
+            _ => Err(anyhow!("unknown link {link}")),
+        }
+    }
+}
+
+/// Take the `lapce://` links out of the arguments, and make the `file://` ones
+/// which a desktop passes for files into paths.
+pub fn split_args(args: impl Iterator<Item = String>) -> (Vec<String>, Vec<String>) {
+    let mut rest = Vec::new();
+    let mut links = Vec::new();
+    for arg in args {
+        if arg.starts_with(&format!("{SCHEME}://")) {
+            links.push(arg);
+        } else if let Some(path) = arg
+            .starts_with("file://")
+            .then(|| Url::parse(&arg).ok()?.to_file_path().ok())
+            .flatten()
+        {
+            rest.push(path.to_string_lossy().to_string());
+        } else {
+            rest.push(arg);
+        }
+    }
+    (rest, links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_parse_open() {
+        assert_eq!(
+            DeepLink::parse("lapce://open?path=/tmp/a%20b.rs&line=3&column=5")
+                .unwrap(),
+            DeepLink::Open(PathObject::new(
+                PathBuf::from("/tmp/a b.rs"),
+                false,
+                3,
+                5
+            )),
+        );
+        assert!(DeepLink::parse("lapce://open?path=a.rs").is_err());
+    }
+
+    #[test]
+    fn test_parse_plugin_and_settings() {
+        assert_eq!(
+            DeepLink::parse("lapce://plugin/dae.lapce-rust").unwrap(),
+            DeepLink::Plugin(VoltID {
+                author: "dae".to_string(),
+                name: "lapce-rust".to_string(),
+            }),
+        );
+        assert_eq!(
+            DeepLink::parse("lapce://settings/editor.font-size").unwrap(),
+            DeepLink::Settings("editor.font-size".to_string()),
+        );
+        assert!(DeepLink::parse("lapce://settings/").is_err());
+        assert!(DeepLink::parse("https://lapce.dev").is_err());
+    }
+}
@@ -1,9 +1,11 @@
 use std::{
-    cell::RefCell,
-    collections::HashMap,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    ops::Range,
     path::PathBuf,
     rc::Rc,
     sync::{atomic, Arc},
+    time::Instant,
 };
 
 use floem::{
@@ -20,15 +22,18 @@
     cursor::Cursor,
     editor::{EditType, Editor},
     language::LapceLanguage,
-    register::{Clipboard, Register},
+    register::{Clipboard, Register, RegisterData},
     selection::{SelRegion, Selection},
     style::line_styles,
     syntax::{edit::SyntaxEdit, Syntax},
 };
 use lapce_rpc::{
     buffer::BufferId,
+    dap_types::DapId,
+    http::{http_styles, is_http_file},
     plugin::PluginId,
     proxy::{ProxyResponse, ProxyRpcHandler},
+    runnable::Runnable,
     style::{LineStyle, LineStyles, Style},
 };
 use lapce_xi_rope::{
@@ -37,19 +42,34 @@
 };
 use lsp_types::{
     CodeActionResponse, Diagnostic, DiagnosticSeverity, InlayHint, InlayHintLabel,
+    Position,
 };
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
-use self::phantom_text::{PhantomText, PhantomTextKind, PhantomTextLine};
+use self::{
+    changes::{line_changes, LineChange},
+    embedded::{code_extension, fenced_code_block, EmbeddedCode},
+    html::HtmlLine,
+    phantom_text::{ligature_breaks, PhantomText, PhantomTextKind, PhantomTextLine},
+    syntax_parse::{SyntaxParse, SyntaxParseJob, SyntaxUpdate},
+};
 use crate::{
-    config::{color::LapceColor, LapceConfig},
+    collab::{is_guest_path, CollabData},
+    color_picker::find_color_literals,
+    config::{color::LapceColor, editor::ErrorLensMessages, LapceConfig},
     find::{Find, FindProgress, FindResult},
     history::DocumentHistory,
+    listener::Listener,
     workspace::LapceWorkspace,
 };
 
+pub mod changes;
+pub mod embedded;
+pub mod html;
+pub mod memory;
 pub mod phantom_text;
+pub mod syntax_parse;
 
 pub struct SystemClipboard {}
 
@@ -122,6 +142,14 @@ pub trait TextCacheListener {
 
 type TextCacheListeners = Rc<RefCell<SmallVec<[Rc<dyn TextCacheListener>; 2]>>>;
 
+/// The history which holds the content on disk, when it conflicts with the buffer
+const DISK_HISTORY: &str = "disk";
+/// The history which holds the content of the file the document is diffed with
+const DIFF_HISTORY: &str = "diff";
+/// How far into a line it gets syntax styles. The rest of a very long line is
+/// left plain, as laying out all of its styles would stall rendering.
+const LINE_STYLE_LIMIT: usize = 10_000;
+
 /// A single document that can be viewed by multiple [`EditorData`]'s
 /// [`EditorViewData`]s and [`EditorView]s.  
 #[derive(Clone)]
@@ -135,11 +163,23 @@ pub struct Document {
     text_cache_listeners: TextCacheListeners,
     buffer: Buffer,
     syntax: Option<Syntax>,
+    syntax_parse: SyntaxParse,
+    /// Whether the syntax tree and the semantic styles were dropped while the
+    /// document wasn't shown, until it's shown again
+    syntax_evicted: bool,
+    /// When an editor of the document was last painted
+    last_shown: Rc<Cell<Instant>>,
     line_styles: Rc<RefCell<LineStyles>>,
     /// Semantic highlighting information (which is provided by the LSP)
     semantic_styles: Option<Arc<Spans<Style>>>,
     /// Inlay hints for the document
     pub inlay_hints: Option<Spans<InlayHint>>,
+    /// The values of the variables which the debug session stopped with, at
+    /// the starts of the lines which they're shown at the ends of
+    inline_values: Option<(DapId, Spans<String>)>,
+    /// The runnables of the language server, such as the tests, which have a
+    /// run button in the gutter
+    pub runnables: im::Vector<Runnable>,
     /// The diagnostics for the document
     pub diagnostics: DiagnosticData,
     /// Current completion lens text, if any.  
@@ -147,17 +187,35 @@ pub struct Document {
     completion_lens: Option<String>,
     /// (line, col)
     completion_pos: (usize, usize),
+    /// The line under the mouse, whose diagnostic messages are shown when they're
+    /// only shown on hover
+    hovered_line: Option<usize>,
     /// (Offset -> (Plugin the code actions are from, Code Actions))
     pub code_actions: im::HashMap<usize, Arc<(PluginId, CodeActionResponse)>>,
     /// Whether the buffer's content has been loaded/initialized into the buffer.
     loaded: bool,
+    /// The content of the file on disk, when it was changed there while the buffer had
+    /// unsaved changes.
+    disk_conflict: Option<Rope>,
+    /// Whether the file can't be written for lack of permission, until the document is
+    /// made writable to be saved as administrator.
+    read_only: bool,
+    /// Where the code of the document is in its host file, when it's a document
+    /// of embedded code
+    embedded: Option<EmbeddedCode>,
     /// Stores information about different versions of the document from source control.
     histories: RwSignal<im::HashMap<String, DocumentHistory>>,
     pub head_changes: RwSignal<im::Vector<DiffLines>>,
+    /// Whether the changes in the gutter are the unsaved ones, against the
+    /// content which was last saved.
+    pub compare_saved: RwSignal<bool>,
 
     /// A cache for the sticky headers which maps a line to the lines it should show in the header.
     pub sticky_headers: Rc<RefCell<HashMap<usize, Option<Vec<usize>>>>>,
     proxy: ProxyRpcHandler,
+    /// The collaboration session which the edits of the file are sent to, when
+    /// it's shared
+    collab: Option<CollabData>,
     config: ReadSignal<Arc<LapceConfig>>,
     find: Find,
     pub find_result: FindResult,
@@ -170,9 +228,13 @@ pub fn new(
         diagnostics: DiagnosticData,
         find: Find,
         proxy: ProxyRpcHandler,
+        collab: CollabData,
         config: ReadSignal<Arc<LapceConfig>>,
     ) -> Self {
-        let syntax = Syntax::init(&path);
+        let syntax = match config.get_untracked().file_association(&path) {
+            Some(language) => Syntax::from_language(language),
+            None => Syntax::init(&path),
+        };
         Self {
             scope: cx,
             buffer_id: BufferId::next(),
@@ -180,19 +242,30 @@ pub fn new(
             style_rev: 0,
             text_cache_listeners: Rc::new(RefCell::new(SmallVec::new())),
             syntax: syntax.ok(),
+            syntax_parse: SyntaxParse::default(),
+            syntax_evicted: false,
+            last_shown: Rc::new(Cell::new(Instant::now())),
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             semantic_styles: None,
             inlay_hints: None,
+            inline_values: None,
+            runnables: im::Vector::new(),
             diagnostics,
             completion_lens: None,
             completion_pos: (0, 0),
+            hovered_line: None,
             content: DocContent::File(path),
             loaded: false,
+            disk_conflict: None,
+            read_only: false,
+            embedded: None,
             histories: create_rw_signal(cx, im::HashMap::new()),
             head_changes: create_rw_signal(cx, im::Vector::new()),
+            compare_saved: create_rw_signal(cx, false),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             code_actions: im::HashMap::new(),
             proxy,
+            collab: Some(collab),
             config,
             find,
             find_result: FindResult::new(cx),
@@ -213,21 +286,32 @@ pub fn new_local(
             text_cache_listeners: Rc::new(RefCell::new(SmallVec::new())),
             content: DocContent::Local,
             syntax: None,
+            syntax_parse: SyntaxParse::default(),
+            syntax_evicted: false,
+            last_shown: Rc::new(Cell::new(Instant::now())),
             line_styles: Rc::new(RefCell::new(HashMap::new())),
             sticky_headers: Rc::new(RefCell::new(HashMap::new())),
             semantic_styles: None,
             inlay_hints: None,
+            inline_values: None,
+            runnables: im::Vector::new(),
             diagnostics: DiagnosticData {
                 expanded: create_rw_signal(cx, true),
                 diagnostics: create_rw_signal(cx, im::Vector::new()),
             },
             completion_lens: None,
             completion_pos: (0, 0),
+            hovered_line: None,
             loaded: true,
+            disk_conflict: None,
+            read_only: false,
+            embedded: None,
             histories: create_rw_signal(cx, im::HashMap::new()),
             head_changes: create_rw_signal(cx, im::Vector::new()),
+            compare_saved: create_rw_signal(cx, false),
             code_actions: im::HashMap::new(),
             proxy,
+            collab: None,
             config,
             find,
             find_result: FindResult::new(cx),
@@ -252,6 +336,8 @@ pub fn syntax(&self) -> Option<&Syntax> {
 
     pub fn set_syntax(&mut self, syntax: Option<Syntax>) {
         self.syntax = syntax;
+        self.syntax_evicted = false;
+        self.reset_syntax_parse();
         if self.semantic_styles.is_none() {
             self.clear_style_cache();
         }
@@ -262,9 +348,44 @@ pub fn set_syntax(&mut self, syntax: Option<Syntax>) {
     pub fn set_language(&mut self, language: LapceLanguage) {
         if let Ok(syn) = Syntax::from_language(language) {
             self.syntax = Some(syn);
+            self.syntax_evicted = false;
+            self.reset_syntax_parse();
         }
     }
 
+    /// Choose the language again once the content is known, which the file
+    /// associations of the settings decide, and otherwise a modeline, the path,
+    /// a shebang and the start of the content.
+    fn detect_language(&mut self) {
+        let DocContent::File(path) = &self.content else {
+            return;
+        };
+        let language = self
+            .config
+            .get_untracked()
+            .file_association(path)
+            .or_else(|| LapceLanguage::detect(Some(path), &self.modeline_text()));
+        let Some(language) = language else {
+            return;
+        };
+        if self.syntax.as_ref().map(|syntax| syntax.language) != Some(language) {
+            self.syntax = Syntax::from_language(language).ok();
+            self.syntax_evicted = false;
+            self.reset_syntax_parse();
+        }
+    }
+
+    /// The first and the last lines, which a modeline is looked for in.
+    fn modeline_text(&self) -> String {
+        let lines = LapceLanguage::MODELINE_LINES;
+        let last_line = self.buffer.last_line();
+        let tail = last_line.saturating_sub(lines - 1).max(lines);
+        (0..lines.min(last_line + 1))
+            .chain(tail..=last_line)
+            .map(|line| self.buffer.line_content(line))
+            .collect()
+    }
+
     pub fn find(&self) -> &Find {
         &self.find
     }
@@ -274,9 +395,27 @@ pub fn loaded(&self) -> bool {
         self.loaded
     }
 
+    /// The range of the whole document and all of its diagnostics, which the
+    /// source actions like organizing the imports are asked for with.
+    pub fn source_action_context(&self) -> (lsp_types::Range, Vec<Diagnostic>) {
+        let range = lsp_types::Range {
+            start: Position::new(0, 0),
+            end: self.buffer.offset_to_position(self.buffer.len()),
+        };
+        let diagnostics = self
+            .diagnostics
+            .diagnostics
+            .get_untracked()
+            .iter()
+            .map(|d| d.diagnostic.clone())
+            .collect();
+        (range, diagnostics)
+    }
+
     //// Initialize the content with some text, this marks the document as loaded.
     pub fn init_content(&mut self, content: Rope) {
         self.buffer.init_content(content);
+        self.detect_language();
         self.buffer.detect_indent(self.syntax.as_ref());
         self.loaded = true;
         self.on_update(None);
@@ -291,14 +430,179 @@ pub fn reload(&mut self, content: Rope, set_pristine: bool) {
         // self.inlay_hints = None;
         let delta = self.buffer.reload(content, set_pristine);
         self.apply_deltas(&[delta]);
+        if set_pristine {
+            self.clear_disk_conflict();
+        }
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn embedded(&self) -> Option<&EmbeddedCode> {
+        self.embedded.as_ref()
+    }
+
+    pub fn set_embedded(&mut self, embedded: Option<EmbeddedCode>) {
+        self.embedded = embedded;
+    }
+
+    /// The range of the code which is embedded in the document at the offset,
+    /// and the file extension for its language. In markdown that's the fenced
+    /// code block, and otherwise the injection of the syntax.
+    pub fn embedded_code_at(&self, offset: usize) -> Option<(Range<usize>, String)> {
+        let is_markdown = self
+            .content
+            .path()
+            .and_then(|path| LapceLanguage::from_path(path))
+            == Some(LapceLanguage::Markdown);
+        if is_markdown {
+            let text = self.buffer.to_string();
+            let (range, info) = fenced_code_block(&text, offset)?;
+            return Some((range, code_extension(&info)));
+        }
+        let (language, range) = self.syntax.as_ref()?.layers.injection_at(offset)?;
+        Some((range, language.extension()?.to_string()))
+    }
+
+    /// The lines from `start_line` to `end_line` with the colors of their
+    /// highlighting, for a page of HTML.
+    pub fn html_lines(&self, start_line: usize, end_line: usize) -> Vec<HtmlLine> {
+        let config = self.config.get_untracked();
+        (start_line..=end_line.min(self.buffer.last_line()))
+            .map(|line| {
+                let content = self.buffer.line_content(line);
+                let text = content.trim_end_matches(['\n', '\r']).to_string();
+                let styles = self
+                    .line_style(line)
+                    .iter()
+                    .filter_map(|style| {
+                        let color = style
+                            .style
+                            .fg_color
+                            .as_ref()
+                            .and_then(|fg| config.get_style_color(fg))?;
+                        Some((style.start..style.end, *color))
+                    })
+                    .collect();
+                HtmlLine { line, text, styles }
+            })
+            .collect()
+    }
+
+    /// The content of the file on disk, if it has changed there since the buffer was
+    /// edited.
+    pub fn disk_conflict(&self) -> Option<&Rope> {
+        self.disk_conflict.as_ref()
+    }
+
+    /// Keep the content the file has on disk now, which conflicts with the unsaved
+    /// changes of the buffer. Returns whether there wasn't a conflict before.
+    pub fn set_disk_conflict(&mut self, content: Rope) -> bool {
+        let new = self.disk_conflict.is_none();
+        self.disk_conflict = Some(content);
+        if self
+            .histories
+            .with_untracked(|histories| histories.contains_key(DISK_HISTORY))
+        {
+            self.compare_with_disk();
+        }
+        new
+    }
+
+    /// The conflict is resolved, by the buffer being saved or reloaded.
+    pub fn clear_disk_conflict(&mut self) {
+        if self.disk_conflict.take().is_none() {
+            return;
+        }
+        if self
+            .histories
+            .with_untracked(|histories| histories.contains_key(DISK_HISTORY))
+        {
+            self.histories.update(|histories| {
+                histories.remove(DISK_HISTORY);
+            });
+            self.trigger_head_change();
+        }
+    }
+
+    /// Show the unsaved changes in the gutter, or go back to the ones they were
+    /// compared with before.
+    pub fn toggle_compare_saved(&self) {
+        self.compare_saved.update(|compare| *compare = !*compare);
+        self.trigger_head_change();
     }
 
-    pub fn handle_file_changed(&mut self, content: Rope) {
-        if self.buffer.is_pristine() {
-            self.reload(content, true);
+    /// Mark the buffer as saved.
+    pub fn set_pristine(&mut self) {
+        self.buffer.set_pristine();
+        if self.compare_saved.get_untracked() {
+            self.trigger_head_change();
         }
     }
 
+    /// Show the changes against the content on disk in the gutter, rather than the
+    /// changes against the source control head, until the conflict is resolved.
+    pub fn compare_with_disk(&self) {
+        let (Some(content), DocContent::File(path)) =
+            (self.disk_conflict.as_ref(), &self.content)
+        else {
+            return;
+        };
+        let history = DocumentHistory::new(
+            path.clone(),
+            DISK_HISTORY.to_string(),
+            &content.to_string(),
+        );
+        self.histories.update(|histories| {
+            histories.insert(DISK_HISTORY.to_string(), history);
+        });
+        self.trigger_head_change();
+    }
+
+    /// Show the changes against the content of another file in the gutter, for
+    /// the diff of the two files, until it's stopped.
+    pub fn compare_with(&self, path: PathBuf, content: &Rope) {
+        let history = DocumentHistory::new(
+            path,
+            DIFF_HISTORY.to_string(),
+            &content.to_string(),
+        );
+        self.histories.update(|histories| {
+            histories.insert(DIFF_HISTORY.to_string(), history);
+        });
+        self.trigger_head_change();
+    }
+
+    /// Go back to the changes the document was compared with before the diff.
+    pub fn stop_comparing(&self) {
+        let removed = self
+            .histories
+            .try_update(|histories| histories.remove(DIFF_HISTORY).is_some())
+            .unwrap_or(false);
+        if removed {
+            self.trigger_head_change();
+        }
+    }
+
+    pub fn do_paste(
+        &mut self,
+        cursor: &mut Cursor,
+        data: &RegisterData,
+    ) -> Vec<(RopeDelta, InvalLines, SyntaxEdit)> {
+        let old_cursor = cursor.mode.clone();
+        let deltas =
+            Editor::do_paste(cursor, &mut self.buffer, data, self.syntax.as_ref());
+        self.buffer.set_cursor_before(old_cursor);
+        self.buffer.set_cursor_after(cursor.mode.clone());
+        self.apply_deltas(&deltas);
+        deltas
+    }
+
     pub fn do_insert(
         &mut self,
         cursor: &mut Cursor,
@@ -311,7 +615,9 @@ pub fn do_insert(
             &mut self.buffer,
             s,
             self.syntax.as_ref(),
-            config.editor.auto_closing_matching_pairs,
+            config
+                .editor_config(self.syntax.as_ref().map(|syntax| syntax.language))
+                .auto_closing_matching_pairs,
         );
         // Keep track of the change in the cursor mode for undo/redo
         self.buffer.set_cursor_before(old_cursor);
@@ -330,6 +636,18 @@ pub fn do_raw_edit(
         (delta, inval_lines, edits)
     }
 
+    /// Replace the ranges of the text as one edit, which is quicker than a
+    /// selection of them when there are a lot.
+    pub fn do_raw_edit_ranges<'a>(
+        &mut self,
+        edits: impl IntoIterator<Item = (Range<usize>, &'a str)>,
+        edit_type: EditType,
+    ) -> (RopeDelta, InvalLines, SyntaxEdit) {
+        let (delta, inval_lines, edits) = self.buffer.edit_ranges(edits, edit_type);
+        self.apply_deltas(&[(delta.clone(), inval_lines.clone(), edits.clone())]);
+        (delta, inval_lines, edits)
+    }
+
     pub fn do_edit(
         &mut self,
         cursor: &mut Cursor,
@@ -358,16 +676,33 @@ pub fn do_edit(
         deltas
     }
 
+    /// Undo the last edit of the buffer, and return the delta which undid it
+    pub fn undo(&mut self) -> Option<RopeDelta> {
+        let (delta, inval_lines, edits, _) = self.buffer.do_undo()?;
+        self.apply_deltas(&[(delta.clone(), inval_lines, edits)]);
+        Some(delta)
+    }
+
     pub fn apply_deltas(&mut self, deltas: &[(RopeDelta, InvalLines, SyntaxEdit)]) {
         let rev = self.rev() - deltas.len() as u64;
         for (i, (delta, _, _)) in deltas.iter().enumerate() {
             self.update_styles(delta);
             self.update_inlay_hints(delta);
+            self.update_inline_values(delta);
             self.update_diagnostics(delta);
             self.update_completion_lens(delta);
             if let DocContent::File(path) = &self.content {
-                self.proxy
-                    .update(path.clone(), delta.clone(), rev + i as u64 + 1);
+                if let Some(collab) = self.collab.as_ref() {
+                    collab.local_edit(path, delta);
+                }
+                // The files of a guest only exist on the host
+                if !is_guest_path(path) {
+                    self.proxy.update(
+                        path.clone(),
+                        delta.clone(),
+                        rev + i as u64 + 1,
+                    );
+                }
             }
         }
 
@@ -421,16 +756,172 @@ fn update_inlay_hints(&mut self, delta: &RopeDelta) {
         }
     }
 
+    /// Update the inline values so they stay on their lines after an edit.
+    fn update_inline_values(&mut self, delta: &RopeDelta) {
+        if let Some((_, values)) = self.inline_values.as_mut() {
+            values.apply_shape(delta);
+        }
+    }
+
+    /// Show the values of the session at the ends of their lines.
+    pub fn set_inline_values(
+        &mut self,
+        dap_id: DapId,
+        values: BTreeMap<usize, String>,
+    ) {
+        let len = self.buffer.len();
+        let mut spans = SpansBuilder::new(len);
+        for (line, value) in values {
+            let offset = self.buffer.offset_of_line(line).min(len);
+            spans.add_span(Interval::new(offset, (offset + 1).min(len)), value);
+        }
+        self.inline_values = Some((dap_id, spans.build()));
+        self.clear_text_cache();
+    }
+
+    pub fn has_inline_values(&self, dap_id: DapId) -> bool {
+        self.inline_values.as_ref().map(|(id, _)| *id) == Some(dap_id)
+    }
+
+    /// Stop showing the values of the session, which continued or ended.
+    pub fn clear_inline_values(&mut self, dap_id: DapId) {
+        if self.has_inline_values(dap_id) {
+            self.inline_values = None;
+            self.clear_text_cache();
+        }
+    }
+
+    /// Parse the document again after it changed, which is done in the background
+    /// once the document listens for the results. One parse runs at a time, and
+    /// the changes which come in meanwhile are parsed when it's done.
     pub fn trigger_syntax_change(
         &mut self,
         edits: Option<SmallVec<[SyntaxEdit; 3]>>,
     ) {
-        let Some(syntax) = self.syntax.as_mut() else { return };
+        // An evicted document is parsed in full when it's shown again
+        if self.syntax.is_none() || self.syntax_evicted {
+            return;
+        }
+
+        match (self.syntax_parse.edits.as_mut(), edits) {
+            (Some(pending), Some(edits)) => pending.extend(edits),
+            _ => self.syntax_parse.edits = None,
+        }
+        self.syntax_parse.dirty = true;
+        if !self.syntax_parse.running {
+            self.start_syntax_parse();
+        }
+    }
+
+    fn start_syntax_parse(&mut self) {
+        let Some(syntax) = self.syntax.as_mut() else {
+            return;
+        };
 
         let rev = self.buffer.rev();
         let text = self.buffer.text().clone();
+        let edits = self.syntax_parse.edits.replace(Vec::new());
+        self.syntax_parse.dirty = false;
+
+        let Some(updates) = self.syntax_parse.updates else {
+            syntax.parse(rev, text, edits.as_deref());
+            return;
+        };
+
+        self.syntax_parse.running = true;
+        let job = SyntaxParseJob {
+            syntax: syntax.clone(),
+            generation: self.syntax_parse.generation,
+            rev,
+            text,
+            edits,
+            viewport: self.syntax_parse.viewport.get(),
+        };
+        let send_viewport =
+            create_ext_action(self.scope, move |update| updates.send(update));
+        let send_done =
+            create_ext_action(self.scope, move |update| updates.send(update));
+        job.spawn(send_viewport, send_done);
+    }
+
+    /// Apply the results of the background syntax parses to the document, which
+    /// starts once the document is in its signal.
+    pub fn listen_syntax_updates(cx: Scope, doc: RwSignal<Document>) {
+        let updates = Listener::new(cx, move |update| {
+            doc.update(|doc| doc.apply_syntax_update(update));
+        });
+        doc.update(|doc| doc.syntax_parse.updates = Some(updates));
+    }
+
+    fn apply_syntax_update(&mut self, update: SyntaxUpdate) {
+        match update {
+            SyntaxUpdate::Viewport {
+                generation,
+                rev,
+                range,
+                styles,
+            } => {
+                if generation != self.syntax_parse.generation
+                    || rev != self.buffer.rev()
+                {
+                    return;
+                }
+                let len = self.buffer.len();
+                let Some(syntax) = self.syntax.as_mut() else {
+                    return;
+                };
+                // The styles of the rest of the document are the ones from
+                // before, which were moved along with the edits
+                let mut all = syntax
+                    .styles
+                    .as_deref()
+                    .filter(|styles| styles.len() == len)
+                    .cloned()
+                    .unwrap_or_else(|| SpansBuilder::new(len).build());
+                all.edit(Interval::new(range.start, range.end), styles);
+                syntax.styles = Some(Arc::new(all));
+                if self.semantic_styles.is_none() {
+                    self.clear_style_cache();
+                }
+            }
+            SyntaxUpdate::Done { generation, syntax } => {
+                // The parse was for a syntax which has been replaced since
+                if generation != self.syntax_parse.generation {
+                    return;
+                }
+                self.syntax_parse.running = false;
+                if syntax.rev == self.buffer.rev() {
+                    self.syntax = Some(syntax);
+                    if self.semantic_styles.is_none() {
+                        self.clear_style_cache();
+                    }
+                    self.clear_sticky_headers_cache();
+                } else if let Some(current) = self.syntax.as_mut() {
+                    // The document was edited during the parse, so only the tree
+                    // is taken, which the next parse goes on from. The styles stay
+                    // the ones which were moved along with the edits until then.
+                    current.rev = syntax.rev;
+                    current.text = syntax.text;
+                    current.layers = syntax.layers;
+                }
+                if self.syntax_parse.dirty {
+                    self.start_syntax_parse();
+                }
+            }
+        }
+    }
 
-        syntax.parse(rev, text, edits.as_deref());
+    /// Set the lines which an editor of the document shows, which the next syntax
+    /// parse highlights before the rest of the document.
+    pub fn set_syntax_viewport(&self, start_line: usize, end_line: usize) {
+        self.syntax_parse.viewport.set(Some((start_line, end_line)));
+    }
+
+    /// Start over with the parses, for a syntax which replaced the one before.
+    fn reset_syntax_parse(&mut self) {
+        self.syntax_parse.generation += 1;
+        self.syntax_parse.running = false;
+        self.syntax_parse.edits = None;
     }
 
     fn clear_style_cache(&mut self) {
@@ -485,7 +976,15 @@ pub fn line_style(&self, line: usize) -> Arc<Vec<LineStyle>> {
             let styles = self.styles();
 
             let line_styles = styles
-                .map(|styles| line_styles(self.buffer.text(), line, styles))
+                .map(|styles| {
+                    let mut line_styles =
+                        line_styles(self.buffer.text(), line, styles);
+                    line_styles.retain(|style| style.start < LINE_STYLE_LIMIT);
+                    for style in line_styles.iter_mut() {
+                        style.end = style.end.min(LINE_STYLE_LIMIT);
+                    }
+                    line_styles
+                })
                 .unwrap_or_default();
             self.line_styles
                 .borrow_mut()
@@ -501,6 +1000,34 @@ pub fn tigger_proxy_update(
     ) {
         Self::get_inlay_hints(cx, doc, proxy);
         Self::get_semantic_styles(cx, doc, proxy);
+        Self::get_runnables(cx, doc, proxy);
+    }
+
+    /// Request the runnables of the buffer from the LSP through the proxy.
+    fn get_runnables(cx: Scope, doc: RwSignal<Document>, proxy: &ProxyRpcHandler) {
+        if !doc.with_untracked(|doc| doc.loaded) {
+            return;
+        }
+
+        let path = match doc.with_untracked(|doc| doc.content.clone()) {
+            DocContent::File(path) => path,
+            DocContent::Local => return,
+        };
+
+        let rev = doc.with_untracked(|doc| doc.buffer.rev());
+        let send = create_ext_action(cx, move |runnables| {
+            doc.update(|doc| {
+                if doc.buffer.rev() == rev {
+                    doc.runnables = runnables;
+                }
+            })
+        });
+
+        proxy.get_runnables(path, move |result| {
+            if let Ok(ProxyResponse::GetRunnables { runnables }) = result {
+                send(runnables.into_iter().collect::<im::Vector<_>>());
+            }
+        });
     }
 
     /// Request semantic styles for the buffer from the LSP through the proxy.
@@ -527,13 +1054,30 @@ fn get_semantic_styles(
 
         let send = create_ext_action(cx, move |styles| {
             doc.update(|doc| {
-                if doc.buffer.rev() == rev {
+                if doc.buffer.rev() == rev && !doc.syntax_evicted {
                     doc.semantic_styles = Some(styles);
                     doc.clear_style_cache();
                 }
             })
         });
 
+        // There's no grammar for `.http` files, so their styles are worked out
+        // here instead of by a language server
+        if is_http_file(&path) {
+            let text = doc.with_untracked(|doc| doc.buffer.text().to_string());
+            rayon::spawn(move || {
+                let mut styles_span = SpansBuilder::new(len);
+                for style in http_styles(&text) {
+                    styles_span.add_span(
+                        Interval::new(style.start, style.end),
+                        style.style,
+                    );
+                }
+                send(Arc::new(styles_span.build()));
+            });
+            return;
+        }
+
         proxy.get_semantic_tokens(path, move |result| {
             if let Ok(ProxyResponse::GetSemanticTokens { styles }) = result {
                 rayon::spawn(move || {
@@ -654,11 +1198,16 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
         // overall.
         let mut text: SmallVec<[PhantomText; 6]> = hints.collect();
 
+        let show_messages = match config.editor.error_lens_messages {
+            ErrorLensMessages::Always => true,
+            ErrorLensMessages::Hover => self.hovered_line == Some(line),
+            ErrorLensMessages::Never => false,
+        };
         // The max severity is used to determine the color given to the background of the line
         let mut max_severity = None;
         // If error lens is enabled, and the diagnostics field is filled, then get the diagnostics
         // that end on this line which have a severity worse than HINT and convert them into
-        // PhantomText instances
+        // PhantomText instances, unless their messages are hidden
         let diag_text = config
             .editor
             .enable_error_lens
@@ -670,7 +1219,7 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
                 diag.diagnostic.range.end.line as usize == line
                     && diag.diagnostic.severity < Some(DiagnosticSeverity::HINT)
             })
-            .map(|diag| {
+            .filter_map(|diag| {
                 match (diag.diagnostic.severity, max_severity) {
                     (Some(severity), Some(max)) => {
                         if severity < max {
@@ -682,6 +1231,9 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
                     }
                     _ => {}
                 }
+                if !show_messages {
+                    return None;
+                }
 
                 let col = self.buffer.offset_of_line(line + 1)
                     - self.buffer.offset_of_line(line);
@@ -703,7 +1255,7 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
                 };
                 let text =
                     format!("    {}", diag.diagnostic.message.lines().join(" "));
-                PhantomText {
+                Some(PhantomText {
                     kind: PhantomTextKind::Diagnostic,
                     col,
                     text,
@@ -712,12 +1264,66 @@ pub fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
                     // font_family: Some(config.editor.error_lens_font_family()),
                     bg: None,
                     under_line: None,
-                }
+                })
             });
         let mut diag_text: SmallVec<[PhantomText; 6]> = diag_text.collect();
+        if !config.editor.error_lens_line_background {
+            max_severity = None;
+        }
+
+        let inline_values = config
+            .editor
+            .enable_inline_values
+            .then_some(())
+            .and(self.inline_values.as_ref())
+            .map(|(_, values)| values.iter_chunks(start_offset..end_offset))
+            .into_iter()
+            .flatten()
+            .filter(|(interval, _)| {
+                interval.start >= start_offset && interval.start < end_offset
+            })
+            .map(|(_, value)| PhantomText {
+                kind: PhantomTextKind::InlineValue,
+                col: end_offset - start_offset,
+                text: format!("    {value}"),
+                fg: Some(*config.get_color(LapceColor::INLAY_HINT_FOREGROUND)),
+                font_size: Some(config.editor.inlay_hint_font_size()),
+                bg: None,
+                under_line: None,
+            });
+        text.extend(inline_values);
 
         text.append(&mut diag_text);
 
+        if config.editor.color_decorators {
+            let content = self.buffer.line_content(line);
+            text.extend(find_color_literals(&content).into_iter().map(|literal| {
+                PhantomText {
+                    kind: PhantomTextKind::Color,
+                    col: literal.range.start,
+                    text: "\u{25a0} ".to_string(),
+                    fg: Some(literal.color),
+                    font_size: None,
+                    bg: None,
+                    under_line: None,
+                }
+            }));
+        }
+
+        let language = self.syntax().map(|syntax| syntax.language);
+        if !config.editor_config(language).font_ligatures {
+            let content = self.buffer.line_content(line);
+            text.extend(ligature_breaks(&content).map(|col| PhantomText {
+                kind: PhantomTextKind::LigatureBreak,
+                col,
+                text: "\u{200c}".to_string(),
+                fg: None,
+                font_size: None,
+                bg: None,
+                under_line: None,
+            }));
+        }
+
         let (completion_line, completion_col) = self.completion_pos;
         let completion_text = config
             .editor
@@ -836,6 +1442,29 @@ pub fn set_completion_lens(
         self.completion_pos = (line, col);
     }
 
+    pub fn hovered_line(&self) -> Option<usize> {
+        self.hovered_line
+    }
+
+    /// Set the line under the mouse, which shows its diagnostic messages when
+    /// they're only shown on hover.
+    pub fn set_hovered_line(&mut self, line: Option<usize>) {
+        if self.hovered_line == line {
+            return;
+        }
+        let old_line = std::mem::replace(&mut self.hovered_line, line);
+        // Only the lines with diagnostics look different when they're hovered
+        let has_diagnostics = self.diagnostics.diagnostics.with_untracked(|diags| {
+            diags.iter().any(|d| {
+                let end = d.diagnostic.range.end.line as usize;
+                old_line == Some(end) || line == Some(end)
+            })
+        });
+        if has_diagnostics {
+            self.clear_text_cache();
+        }
+    }
+
     pub fn clear_completion_lens(&mut self) {
         // TODO: more granular invalidation
         self.clear_text_cache();
@@ -1064,13 +1693,28 @@ pub fn retrieve_head(&self) {
         }
     }
 
+    /// The content which the changes in the gutter are against, which is the one
+    /// which was last saved, the file it's diffed with or the one on disk while
+    /// they're compared with, or else the source control head.
+    pub fn diff_base(&self) -> Option<Rope> {
+        if self.compare_saved.get_untracked() {
+            return self.buffer.pristine_text();
+        }
+        self.histories.with_untracked(|histories| {
+            histories
+                .get(DIFF_HISTORY)
+                .or_else(|| histories.get(DISK_HISTORY))
+                .or_else(|| histories.get("head"))
+                .map(|history| history.buffer.text().clone())
+        })
+    }
+
+    pub fn line_changes(&self) -> Vec<LineChange> {
+        self.head_changes.with_untracked(|diff| line_changes(diff))
+    }
+
     pub fn trigger_head_change(&self) {
-        let history = if let Some(text) =
-            self.histories.with_untracked(|histories| {
-                histories
-                    .get("head")
-                    .map(|history| history.buffer.text().clone())
-            }) {
+        let history = if let Some(text) = self.diff_base() {
             text
         } else {
             return;
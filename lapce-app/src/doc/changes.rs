@@ -0,0 +1,139 @@
+use std::ops::Range;
+
+use lapce_core::buffer::DiffLines;
+
+/// A run of lines of a document which differ from the version it's compared
+/// with, which is the source control head or the content on disk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineChange {
+    /// The lines of the version which the document is compared with
+    pub old: Range<usize>,
+    /// The lines of the document, which are empty when lines were only removed
+    /// before the start of them
+    pub new: Range<usize>,
+}
+
+impl LineChange {
+    /// Whether the change is on the line, where the lines which were removed
+    /// are on the line after them.
+    pub fn is_on_line(&self, line: usize) -> bool {
+        self.new.contains(&line) || (self.new.is_empty() && self.new.start == line)
+    }
+}
+
+/// The changes of the diff, where the lines which were removed and the ones
+/// which were added next to them make up one change.
+pub fn line_changes<'a>(
+    diff: impl IntoIterator<Item = &'a DiffLines>,
+) -> Vec<LineChange> {
+    let mut changes = Vec::new();
+    let mut current: Option<LineChange> = None;
+    let mut old_line = 0;
+    let mut new_line = 0;
+    for lines in diff {
+        match lines {
+            DiffLines::Both(old, new) | DiffLines::Skip(old, new) => {
+                changes.extend(current.take());
+                old_line = old.end;
+                new_line = new.end;
+            }
+            DiffLines::Left(old) => {
+                let change = current.get_or_insert(LineChange {
+                    old: old.start..old.start,
+                    new: new_line..new_line,
+                });
+                change.old.end = old.end;
+                old_line = old.end;
+            }
+            DiffLines::Right(new) => {
+                let change = current.get_or_insert(LineChange {
+                    old: old_line..old_line,
+                    new: new.start..new.start,
+                });
+                change.new.end = new.end;
+                new_line = new.end;
+            }
+        }
+    }
+    changes.extend(current);
+    changes
+}
+
+/// The change which starts after the line, or before it when going backwards,
+/// which wraps around at the end of the document.
+pub fn next_change(
+    changes: &[LineChange],
+    line: usize,
+    forward: bool,
+) -> Option<&LineChange> {
+    if forward {
+        changes
+            .iter()
+            .find(|change| change.new.start > line)
+            .or_else(|| changes.first())
+    } else {
+        changes
+            .iter()
+            .rev()
+            .find(|change| change.new.start < line)
+            .or_else(|| changes.last())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_changes() {
+        let diff = [
+            DiffLines::Both(0..2, 0..2),
+            DiffLines::Left(2..3),
+            DiffLines::Right(2..4),
+            DiffLines::Skip(3..10, 4..11),
+            DiffLines::Right(11..12),
+            DiffLines::Both(10..12, 12..14),
+            DiffLines::Left(12..15),
+        ];
+        let changes = line_changes(&diff);
+        assert_eq!(
+            changes,
+            vec![
+                LineChange {
+                    old: 2..3,
+                    new: 2..4,
+                },
+                LineChange {
+                    old: 10..10,
+                    new: 11..12,
+                },
+                LineChange {
+                    old: 12..15,
+                    new: 14..14,
+                },
+            ]
+        );
+        assert!(changes[0].is_on_line(3));
+        assert!(!changes[0].is_on_line(4));
+        assert!(changes[2].is_on_line(14));
+    }
+
+    #[test]
+    fn test_next_change() {
+        let changes = line_changes(&[
+            DiffLines::Both(0..2, 0..2),
+            DiffLines::Right(2..4),
+            DiffLines::Both(2..5, 4..7),
+            DiffLines::Left(5..6),
+            DiffLines::Both(6..8, 7..9),
+        ]);
+        let start = |change: Option<&LineChange>| change.map(|c| c.new.start);
+        assert_eq!(start(next_change(&changes, 0, true)), Some(2));
+        assert_eq!(start(next_change(&changes, 2, true)), Some(7));
+        assert_eq!(start(next_change(&changes, 7, true)), Some(2));
+        assert_eq!(start(next_change(&changes, 8, false)), Some(7));
+        assert_eq!(start(next_change(&changes, 3, false)), Some(2));
+        assert_eq!(start(next_change(&changes, 2, false)), Some(7));
+        assert_eq!(next_change(&[], 0, true), None);
+    }
+}
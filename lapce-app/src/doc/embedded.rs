@@ -0,0 +1,177 @@
+//! Code which is embedded in another file, like a fenced code block of markdown
+//! or SQL in a string, and is edited in a document of its own with the language
+//! of the code. The edits are written back to the host file when the document
+//! is saved or closed.
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use lapce_core::language::LapceLanguage;
+
+/// Where the code of a document of embedded code is in its host file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmbeddedCode {
+    pub host: PathBuf,
+    /// The offset of the code in the host when it was last written there
+    pub start: usize,
+    /// The code as it was last written to the host, which is looked for there
+    /// again since the host could have been edited around it
+    pub text: String,
+}
+
+struct Fence<'a> {
+    char: char,
+    len: usize,
+    info: &'a str,
+}
+
+/// The fence of a line which opens or closes a fenced code block, which is
+/// indented by three spaces at most.
+fn fence(line: &str) -> Option<Fence> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == char).count();
+    let info = trimmed[len..].trim();
+    // The info string of a backtick fence can't have backticks, which makes it
+    // inline code instead
+    (len >= 3 && !(char == '`' && info.contains('`'))).then_some(Fence {
+        char,
+        len,
+        info,
+    })
+}
+
+/// The range of the content of the fenced code block of markdown which the
+/// offset is in, the fences included, and its info string. A block which isn't
+/// closed runs to the end of the text.
+pub fn fenced_code_block(
+    text: &str,
+    offset: usize,
+) -> Option<(Range<usize>, String)> {
+    // The fence of the block which is open, the start of its line and the
+    // start of its content
+    let mut open: Option<(Fence, usize, usize)> = None;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        match (&open, fence(line)) {
+            (None, Some(opening)) => {
+                open = Some((opening, line_start, line_end));
+            }
+            (Some((opening, start, content_start)), Some(closing))
+                if closing.char == opening.char
+                    && closing.len >= opening.len
+                    && closing.info.is_empty() =>
+            {
+                if (*start..line_end).contains(&offset) {
+                    return Some((
+                        *content_start..line_start,
+                        opening.info.to_string(),
+                    ));
+                }
+                open = None;
+            }
+            _ => {}
+        }
+        line_start = line_end;
+    }
+    let (opening, start, content_start) = open?;
+    (offset >= start).then(|| (content_start..text.len(), opening.info.to_string()))
+}
+
+/// The file extension for the code of the info string of a code block, which
+/// names the language or one of its extensions.
+pub fn code_extension(info: &str) -> String {
+    if let Some(extension) =
+        LapceLanguage::from_injection_name(info).and_then(|l| l.extension())
+    {
+        return extension.to_string();
+    }
+    let name: String = info
+        .trim_start_matches(['{', '.'])
+        .chars()
+        .take_while(|c| c.is_alphanumeric())
+        .collect();
+    if name.is_empty() {
+        "txt".to_string()
+    } else {
+        name.to_lowercase()
+    }
+}
+
+/// The path of the document of the code at the line of the host file. It's in
+/// the folder of the host, so that the language servers see the project around
+/// it, and it's never written to disk.
+pub fn embedded_path(host: &Path, line: usize, extension: &str) -> PathBuf {
+    let name = host
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    host.with_file_name(format!("{name}.{}.{extension}", line + 1))
+}
+
+/// The start of the code in the host, which is where it's closest to the start
+/// it last had.
+pub fn locate_code(host: &str, code: &str, start: usize) -> Option<usize> {
+    if code.is_empty() {
+        return host.is_char_boundary(start).then_some(start);
+    }
+    host.match_indices(code)
+        .map(|(index, _)| index)
+        .min_by_key(|index| index.abs_diff(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = concat!(
+        "# Title\n\n",
+        "```rust,ignore\nfn main() {}\n```\n\n",
+        "~~~\nplain\n~~~~\n\n",
+        "```py\nopen\n",
+    );
+
+    #[test]
+    fn test_fenced_code_block() {
+        let offset = MARKDOWN.find("main").unwrap();
+        let (range, info) = fenced_code_block(MARKDOWN, offset).unwrap();
+        assert_eq!(&MARKDOWN[range], "fn main() {}\n");
+        assert_eq!(info, "rust,ignore");
+
+        let offset = MARKDOWN.find("plain").unwrap();
+        let (range, info) = fenced_code_block(MARKDOWN, offset).unwrap();
+        assert_eq!(&MARKDOWN[range], "plain\n");
+        assert_eq!(info, "");
+
+        let offset = MARKDOWN.find("open").unwrap();
+        let (range, info) = fenced_code_block(MARKDOWN, offset).unwrap();
+        assert_eq!(&MARKDOWN[range], "open\n");
+        assert_eq!(info, "py");
+
+        assert_eq!(fenced_code_block(MARKDOWN, 2), None);
+        assert_eq!(fenced_code_block("``` `a` ```\n", 2), None);
+    }
+
+    #[test]
+    fn test_embedded_path() {
+        assert_eq!(
+            embedded_path(Path::new("/work/README.md"), 11, "rs"),
+            PathBuf::from("/work/README.md.12.rs")
+        );
+    }
+
+    #[test]
+    fn test_locate_code() {
+        let host = "select 1; -- select 1;";
+        assert_eq!(locate_code(host, "select 1;", 0), Some(0));
+        assert_eq!(locate_code(host, "select 1;", 14), Some(13));
+        assert_eq!(locate_code(host, "update", 0), None);
+        assert_eq!(locate_code(host, "", 3), Some(3));
+    }
+}
@@ -0,0 +1,222 @@
+//! The lines of a document as a page of HTML in the colors of its highlighting,
+//! which is saved to be shared, or opened in the browser to be printed or saved
+//! as a PDF from there.
+
+use std::ops::Range;
+
+use floem::peniko::Color;
+
+/// A line of the document, with the ranges of its text which are highlighted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HtmlLine {
+    /// The line in the document, counted from zero
+    pub line: usize,
+    pub text: String,
+    pub styles: Vec<(Range<usize>, Color)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HtmlOptions {
+    pub title: String,
+    pub font_family: String,
+    pub font_size: usize,
+    pub tab_width: usize,
+    pub background: Color,
+    pub foreground: Color,
+    /// The color of the line numbers
+    pub dim: Color,
+    pub line_numbers: bool,
+    /// Whether the page opens the print dialog of the browser once it's loaded
+    pub print: bool,
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn css_color(color: Color) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        color.r, color.g, color.b, color.a
+    )
+}
+
+/// The families of the font setting, quoted, with the generic monospace one
+/// for the ones the browser doesn't have.
+fn css_font_family(font_family: &str) -> String {
+    font_family
+        .split(',')
+        .map(|family| family.trim().trim_matches(['"', '\'']))
+        .filter(|family| !family.is_empty())
+        .map(|family| format!("\"{}\"", escape_html(family)))
+        .chain(std::iter::once("monospace".to_string()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The text of the line with its highlighted ranges in spans. A range which
+/// overlaps the one before it is cut to start after it.
+fn line_html(line: &HtmlLine) -> String {
+    let mut html = String::new();
+    let mut end = 0;
+    for (range, color) in &line.styles {
+        let start = range.start.max(end);
+        let (Some(before), Some(styled)) =
+            (line.text.get(end..start), line.text.get(start..range.end))
+        else {
+            continue;
+        };
+        if styled.is_empty() {
+            continue;
+        }
+        html.push_str(&escape_html(before));
+        html.push_str(&format!(
+            "<span style=\"color: {}\">{}</span>",
+            css_color(*color),
+            escape_html(styled)
+        ));
+        end = range.end;
+    }
+    html.push_str(&escape_html(line.text.get(end..).unwrap_or("")));
+    html
+}
+
+pub fn render_html(lines: &[HtmlLine], options: &HtmlOptions) -> String {
+    let number_width = lines
+        .last()
+        .map(|line| (line.line + 1).to_string().len())
+        .unwrap_or(1);
+    let mut body = String::new();
+    for line in lines {
+        body.push_str("<div class=\"line\">");
+        if options.line_numbers {
+            body.push_str(&format!(
+                "<span class=\"number\">{}</span>",
+                line.line + 1
+            ));
+        }
+        body.push_str(&line_html(line));
+        body.push_str("</div>\n");
+    }
+
+    let script = if options.print {
+        "<script>window.addEventListener(\"load\", () => window.print());</script>\n"
+    } else {
+        ""
+    };
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{
+  margin: 0;
+  padding: 16px;
+  background: {background};
+  color: {foreground};
+  -webkit-print-color-adjust: exact;
+  print-color-adjust: exact;
+}}
+pre {{
+  margin: 0;
+  font-family: {font_family};
+  font-size: {font_size}px;
+  tab-size: {tab_width};
+}}
+.line {{
+  white-space: pre-wrap;
+  min-height: 1.2em;
+}}
+.number {{
+  display: inline-block;
+  min-width: {number_width}ch;
+  margin-right: 2ch;
+  text-align: right;
+  color: {dim};
+  user-select: none;
+}}
+</style>
+{script}</head>
+<body>
+<pre>
+{body}</pre>
+</body>
+</html>
+"#,
+        title = escape_html(&options.title),
+        background = css_color(options.background),
+        foreground = css_color(options.foreground),
+        dim = css_color(options.dim),
+        font_family = css_font_family(&options.font_family),
+        font_size = options.font_size,
+        tab_width = options.tab_width,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_html() {
+        let red = Color::rgb8(0xff, 0, 0);
+        let line = HtmlLine {
+            line: 0,
+            text: "let a = \"<b>\";".to_string(),
+            styles: vec![(0..3, red), (8..13, red), (10..12, red)],
+        };
+        assert_eq!(
+            line_html(&line),
+            "<span style=\"color: #ff0000ff\">let</span> a = \
+             <span style=\"color: #ff0000ff\">&quot;&lt;b&gt;&quot;</span>;"
+        );
+    }
+
+    #[test]
+    fn test_css_font_family() {
+        assert_eq!(
+            css_font_family("Cascadia Code, 'Fira Code'"),
+            "\"Cascadia Code\", \"Fira Code\", monospace"
+        );
+        assert_eq!(css_font_family(""), "monospace");
+    }
+
+    #[test]
+    fn test_render_html_line_numbers() {
+        let options = HtmlOptions {
+            title: "main.rs".to_string(),
+            font_family: String::new(),
+            font_size: 13,
+            tab_width: 4,
+            background: Color::BLACK,
+            foreground: Color::WHITE,
+            dim: Color::GRAY,
+            line_numbers: true,
+            print: false,
+        };
+        let lines = vec![HtmlLine {
+            line: 9,
+            text: "fn main() {}".to_string(),
+            styles: Vec::new(),
+        }];
+        let html = render_html(&lines, &options);
+        assert!(html.contains(
+            "<div class=\"line\"><span class=\"number\">10</span>fn main() {}</div>"
+        ));
+        assert!(html.contains("min-width: 2ch"));
+        assert!(!html.contains("window.print"));
+    }
+}
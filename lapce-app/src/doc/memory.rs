@@ -0,0 +1,224 @@
+use std::{
+    mem,
+    ops::AddAssign,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{RwSignal, SignalUpdate, SignalWithUntracked},
+};
+use lapce_core::syntax::Syntax;
+use lapce_rpc::style::Style;
+use lapce_xi_rope::Interval;
+use lsp_types::{CodeActionOrCommand, InlayHint};
+
+use super::{Document, EditorDiagnostic};
+
+/// An estimate of the bytes which a document takes, by what they go to
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DocumentMemory {
+    /// The text in the buffer
+    pub buffer: usize,
+    /// The syntax tree and its styles
+    pub syntax: usize,
+    /// The semantic styles, inlay hints, diagnostics and code actions from the
+    /// language servers
+    pub lsp: usize,
+}
+
+impl DocumentMemory {
+    pub fn total(&self) -> usize {
+        self.buffer + self.syntax + self.lsp
+    }
+}
+
+impl AddAssign for DocumentMemory {
+    fn add_assign(&mut self, other: DocumentMemory) {
+        self.buffer += other.buffer;
+        self.syntax += other.syntax;
+        self.lsp += other.lsp;
+    }
+}
+
+/// How much memory the documents and the terminals of a window tab take, which
+/// the memory usage view shows
+#[derive(Clone, Debug, Default)]
+pub struct MemoryReport {
+    /// The documents by their path, the ones which take the most first
+    pub documents: Vec<(PathBuf, DocumentMemory)>,
+    /// The grids of the terminals
+    pub terminals: usize,
+}
+
+impl MemoryReport {
+    pub fn new(
+        mut documents: Vec<(PathBuf, DocumentMemory)>,
+        terminals: usize,
+    ) -> Self {
+        documents.sort_by(|(a_path, a), (b_path, b)| {
+            b.total().cmp(&a.total()).then_with(|| a_path.cmp(b_path))
+        });
+        Self {
+            documents,
+            terminals,
+        }
+    }
+
+    /// The memory of all the documents
+    pub fn documents_total(&self) -> DocumentMemory {
+        let mut total = DocumentMemory::default();
+        for (_, memory) in self.documents.iter() {
+            total += *memory;
+        }
+        total
+    }
+}
+
+/// The bytes in the largest unit which there's at least one of.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+impl Document {
+    pub fn memory_usage(&self) -> DocumentMemory {
+        let semantic_styles = self
+            .semantic_styles
+            .as_ref()
+            .map(|styles| styles.iter().count())
+            .unwrap_or(0);
+        let inlay_hints = self
+            .inlay_hints
+            .as_ref()
+            .map(|hints| hints.iter().count())
+            .unwrap_or(0);
+        let diagnostics = self
+            .diagnostics
+            .diagnostics
+            .with_untracked(|diagnostics| diagnostics.len());
+        let code_actions: usize = self
+            .code_actions
+            .values()
+            .map(|code_actions| code_actions.1.len())
+            .sum();
+
+        DocumentMemory {
+            buffer: self.buffer.len(),
+            syntax: self.syntax.as_ref().map(Syntax::memory_usage).unwrap_or(0),
+            lsp: semantic_styles * mem::size_of::<(Interval, Style)>()
+                + inlay_hints * mem::size_of::<(Interval, InlayHint)>()
+                + diagnostics * mem::size_of::<EditorDiagnostic>()
+                + code_actions * mem::size_of::<CodeActionOrCommand>(),
+        }
+    }
+
+    /// Whether the document has a syntax tree, and no editor of it was painted
+    /// for the duration.
+    pub fn can_evict_syntax(&self, unshown_for: Duration) -> bool {
+        self.loaded
+            && !self.syntax_evicted
+            && self.syntax.is_some()
+            && self.last_shown.get().elapsed() >= unshown_for
+    }
+
+    /// Drop the syntax tree and the semantic styles of a document which isn't
+    /// shown, which are made again once it is.
+    pub fn evict_syntax(&mut self) {
+        let Some(language) = self.syntax.as_ref().map(|syntax| syntax.language)
+        else {
+            return;
+        };
+        self.syntax = Syntax::from_language(language).ok();
+        self.syntax_evicted = true;
+        self.reset_syntax_parse();
+        self.semantic_styles = None;
+        self.clear_style_cache();
+        self.clear_sticky_headers_cache();
+    }
+
+    /// Note that an editor of the document is painted. A document whose syntax
+    /// was evicted is parsed again and asks for its semantic styles, which is
+    /// put off until after the paint, as the document can't be updated while
+    /// it's painted.
+    pub fn shown(doc: RwSignal<Document>) {
+        let (evicted, cx) = doc.with_untracked(|doc| {
+            doc.last_shown.set(Instant::now());
+            (doc.syntax_evicted, doc.scope)
+        });
+        if !evicted {
+            return;
+        }
+
+        let restore = create_ext_action(cx, move |_| {
+            // More than one paint can happen before this is run
+            if !doc.with_untracked(|doc| doc.syntax_evicted) {
+                return;
+            }
+            doc.update(|doc| {
+                doc.syntax_evicted = false;
+                doc.trigger_syntax_change(None);
+            });
+            let (cx, proxy) =
+                doc.with_untracked(|doc| (doc.scope, doc.proxy.clone()));
+            Document::tigger_proxy_update(cx, doc, &proxy);
+        });
+        restore(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KB");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+        // There's no unit above gigabytes
+        assert_eq!(format_bytes(2048 * 1024 * 1024 * 1024), "2048.0 GB");
+    }
+
+    #[test]
+    fn test_memory_report() {
+        let memory = |buffer, syntax, lsp| DocumentMemory {
+            buffer,
+            syntax,
+            lsp,
+        };
+        let report = MemoryReport::new(
+            vec![
+                (PathBuf::from("b"), memory(10, 0, 0)),
+                (PathBuf::from("c"), memory(1, 2, 30)),
+                (PathBuf::from("a"), memory(5, 5, 0)),
+            ],
+            100,
+        );
+        let paths: Vec<_> = report
+            .documents
+            .iter()
+            .map(|(path, _)| path.to_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["c", "a", "b"]);
+
+        let total = report.documents_total();
+        assert_eq!((total.buffer, total.syntax, total.lsp), (16, 7, 30));
+        assert_eq!(total.total(), 53);
+        assert_eq!(report.terminals, 100);
+    }
+}
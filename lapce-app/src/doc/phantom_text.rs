@@ -19,12 +19,18 @@ pub struct PhantomText {
 
 #[derive(Ord, Eq, PartialEq, PartialOrd)]
 pub enum PhantomTextKind {
+    /// The zero-width non-joiners which keep characters from forming ligatures
+    LigatureBreak,
     /// Input methods
     Ime,
     /// Completion lens
     Completion,
     /// Inlay hints supplied by an LSP/PSP (like type annotations)
     InlayHint,
+    /// The swatch in front of a color literal
+    Color,
+    /// The values of the variables while debugging is paused
+    InlineValue,
     /// Error lens
     Diagnostic,
 }
@@ -124,3 +130,54 @@ pub fn offset_size_iter(
         })
     }
 }
+
+/// The columns between two ASCII characters where a zero-width non-joiner keeps
+/// the font from joining them into a ligature, like the `->` of many coding
+/// fonts. Other scripts are left alone, since joining is part of how they're
+/// written.
+pub fn ligature_breaks(line: &str) -> impl Iterator<Item = usize> + '_ {
+    line.as_bytes()
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair.iter().all(|c| c.is_ascii_graphic()))
+        .map(|(col, _)| col + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ligature_breaks() {
+        assert_eq!(ligature_breaks("a -> b\n").collect::<Vec<_>>(), vec![3]);
+        assert_eq!(
+            ligature_breaks("x!==y").collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(ligature_breaks("é=>").collect::<Vec<_>>(), vec![3]);
+
+        let line = PhantomTextLine {
+            text: ligature_breaks("a->b")
+                .map(|col| PhantomText {
+                    kind: PhantomTextKind::LigatureBreak,
+                    col,
+                    text: "\u{200c}".to_string(),
+                    font_size: None,
+                    fg: None,
+                    bg: None,
+                    under_line: None,
+                })
+                .collect(),
+            max_severity: None,
+        };
+        let combined = line.combine_with_text("a->b".to_string());
+        assert_eq!(combined, "a\u{200c}-\u{200c}>\u{200c}b");
+        // A column of the line is right before its character in the combined
+        // text, and back
+        for (col, c) in "a->b".char_indices() {
+            let combined_col = line.col_at(col);
+            assert!(combined[combined_col..].starts_with(c));
+            assert_eq!(line.before_col(combined_col), col);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::{cell::Cell, ops::Range, rc::Rc, sync::Arc};
+
+use lapce_core::syntax::{edit::SyntaxEdit, Syntax};
+use lapce_rpc::style::Style;
+use lapce_xi_rope::{spans::Spans, Rope};
+
+use crate::listener::Listener;
+
+/// The most bytes of the visible lines which are highlighted before the rest of
+/// the document, so that a very long line doesn't hold up the first styles
+const VIEWPORT_HIGHLIGHT_LIMIT: usize = 64 * 1024;
+
+/// A result of a syntax parse which ran in the background
+#[derive(Clone)]
+pub enum SyntaxUpdate {
+    /// The styles of the visible lines, which are sent before the rest of the
+    /// document is highlighted. The offsets of the styles start at `range.start`.
+    Viewport {
+        generation: u64,
+        rev: u64,
+        range: Range<usize>,
+        styles: Spans<Style>,
+    },
+    /// The parse is done, and the whole document is highlighted
+    Done { generation: u64, syntax: Syntax },
+}
+
+/// The state of the syntax parses of a document, which run one at a time off the
+/// UI thread.
+#[derive(Clone, Default)]
+pub struct SyntaxParse {
+    /// Where the results of the parses go, which is set once the document is in
+    /// its signal. Until then the document is parsed right away.
+    pub updates: Option<Listener<SyntaxUpdate>>,
+    /// Bumped whenever the syntax of the document is replaced, so that results
+    /// for the syntax before are dropped
+    pub generation: u64,
+    /// Whether a parse is running
+    pub running: bool,
+    /// Whether the document has changed since the running parse was started
+    pub dirty: bool,
+    /// The edits since the revision of the syntax, one for each revision. This is
+    /// `None` when they aren't known, and the document is parsed in full then.
+    pub edits: Option<Vec<SyntaxEdit>>,
+    /// The first and last line which an editor shows, which are highlighted first
+    pub viewport: Rc<Cell<Option<(usize, usize)>>>,
+}
+
+/// A parse of the text of a document at a revision, which is done from the
+/// syntax of the revision which was parsed last.
+pub struct SyntaxParseJob {
+    pub syntax: Syntax,
+    pub generation: u64,
+    pub rev: u64,
+    pub text: Rope,
+    pub edits: Option<Vec<SyntaxEdit>>,
+    pub viewport: Option<(usize, usize)>,
+}
+
+impl SyntaxParseJob {
+    /// Parse the text and highlight it in the background, starting with the
+    /// visible lines, and send what's done back to the UI thread.
+    pub fn spawn(
+        self,
+        send_viewport: impl FnOnce(SyntaxUpdate) + Send + 'static,
+        send_done: impl FnOnce(SyntaxUpdate) + Send + 'static,
+    ) {
+        rayon::spawn(move || {
+            let SyntaxParseJob {
+                mut syntax,
+                generation,
+                rev,
+                text,
+                edits,
+                viewport,
+            } = self;
+            syntax.parse_tree(rev, text, edits.as_deref());
+
+            if let Some(range) =
+                viewport.and_then(|viewport| viewport_range(&syntax.text, viewport))
+            {
+                if let Some(styles) = syntax.highlight(range.clone()) {
+                    send_viewport(SyntaxUpdate::Viewport {
+                        generation,
+                        rev,
+                        range,
+                        styles,
+                    });
+                }
+            }
+
+            syntax.styles = syntax.highlight(0..syntax.text.len()).map(Arc::new);
+            send_done(SyntaxUpdate::Done { generation, syntax });
+        });
+    }
+}
+
+/// The byte range of the visible lines which is highlighted first, which is
+/// `None` when there's nothing to highlight in it. The viewport can be from
+/// before an edit, so lines past the end of the text are left out.
+fn viewport_range(
+    text: &Rope,
+    (start_line, end_line): (usize, usize),
+) -> Option<Range<usize>> {
+    let last_line = text.line_of_offset(text.len()) + 1;
+    let start = text.offset_of_line(start_line.min(last_line));
+    let end = text
+        .offset_of_line((end_line + 1).min(last_line))
+        .min(start + VIEWPORT_HIGHLIGHT_LIMIT);
+    (start < end).then_some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport_range() {
+        let text = Rope::from("a\nbc\n\ndef");
+        assert_eq!(viewport_range(&text, (0, 0)), Some(0..2));
+        assert_eq!(viewport_range(&text, (1, 2)), Some(2..6));
+        // The last line has no line break, and lines past it are empty
+        assert_eq!(viewport_range(&text, (3, 10)), Some(6..9));
+        assert_eq!(viewport_range(&text, (5, 10)), None);
+
+        let long = Rope::from("x".repeat(VIEWPORT_HIGHLIGHT_LIMIT * 2));
+        assert_eq!(
+            viewport_range(&long, (0, 0)),
+            Some(0..VIEWPORT_HIGHLIGHT_LIMIT)
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! The preview of the edits of many files, like the ones of a rename or a code
+//! action, where each file and each of its edits can be left out before the
+//! rest are made.
+
+use std::collections::HashSet;
+
+use floem::reactive::{
+    create_effect, create_rw_signal, RwSignal, Scope, SignalGet, SignalGetUntracked,
+    SignalSet, SignalUpdate, SignalWith, SignalWithUntracked,
+};
+use lapce_core::{
+    buffer::{rope_text::RopeText, Buffer},
+    command::FocusCommand,
+    mode::Mode,
+};
+use lsp_types::TextEdit;
+
+use crate::{
+    command::{CommandExecuted, CommandKind, InternalCommand, LapceCommand},
+    keypress::{condition::Condition, KeyPressFocus},
+    window_tab::{CommonData, Focus},
+    workspace_edit::FileEdit,
+};
+
+/// The lines of a file which an edit changes, before and after it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreviewHunk {
+    /// The first of the lines, counted from zero
+    pub line: usize,
+    pub old: String,
+    pub new: String,
+}
+
+impl PreviewHunk {
+    pub fn new(buffer: &Buffer, edit: &TextEdit) -> Self {
+        let start = buffer.offset_of_position(&edit.range.start);
+        let end = buffer.offset_of_position(&edit.range.end);
+        let line = buffer.line_of_offset(start);
+        let line_start = buffer.offset_of_line(line);
+        let line_end = buffer.line_end_offset(buffer.line_of_offset(end), true);
+        let new = format!(
+            "{}{}{}",
+            buffer.slice_to_cow(line_start..start),
+            edit.new_text,
+            buffer.slice_to_cow(end..line_end.max(end))
+        );
+        Self {
+            line,
+            old: buffer
+                .slice_to_cow(line_start..line_end.max(end))
+                .to_string(),
+            new,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreviewFile {
+    pub edit: FileEdit,
+    /// The hunks of the text edits of the file, in their order
+    pub hunks: Vec<PreviewHunk>,
+}
+
+/// The edits of the files which are left once the rejected ones are taken out,
+/// which are given by the index of the file and of the edit in it. The files
+/// without any edits left are dropped.
+pub fn accepted_file_edits(
+    files: &[PreviewFile],
+    rejected: &HashSet<(usize, usize)>,
+) -> Vec<FileEdit> {
+    files
+        .iter()
+        .enumerate()
+        .filter_map(|(file, preview)| {
+            let edits: Vec<TextEdit> = preview
+                .edit
+                .edits
+                .iter()
+                .enumerate()
+                .filter(|(hunk, _)| !rejected.contains(&(file, *hunk)))
+                .map(|(_, edit)| edit.clone())
+                .collect();
+            (!edits.is_empty()).then(|| FileEdit {
+                path: preview.edit.path.clone(),
+                version: preview.edit.version,
+                edits,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct EditPreviewData {
+    pub active: RwSignal<bool>,
+    pub files: RwSignal<im::Vector<PreviewFile>>,
+    /// The edits which were unticked, by the index of their file and of them
+    pub rejected: RwSignal<HashSet<(usize, usize)>>,
+    /// Bumped for each preview, so that the views of the files are made anew
+    pub preview_id: RwSignal<usize>,
+    pub common: CommonData,
+}
+
+impl KeyPressFocus for EditPreviewData {
+    fn get_mode(&self) -> Mode {
+        Mode::Insert
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        matches!(condition, Condition::ListFocus | Condition::ModalFocus)
+    }
+
+    fn run_command(
+        &self,
+        command: &LapceCommand,
+        _count: Option<usize>,
+        _mods: floem::glazier::Modifiers,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Focus(cmd) => self.run_focus_command(cmd),
+            CommandKind::Workbench(_)
+            | CommandKind::Edit(_)
+            | CommandKind::Move(_)
+            | CommandKind::MotionMode(_)
+            | CommandKind::MultiSelection(_) => CommandExecuted::No,
+        }
+    }
+
+    fn receive_char(&self, _c: &str) {}
+}
+
+impl EditPreviewData {
+    pub fn new(cx: Scope, common: CommonData) -> Self {
+        let preview = Self {
+            active: create_rw_signal(cx, false),
+            files: create_rw_signal(cx, im::Vector::new()),
+            rejected: create_rw_signal(cx, HashSet::new()),
+            preview_id: create_rw_signal(cx, 0),
+            common,
+        };
+
+        {
+            let preview = preview.clone();
+            create_effect(cx, move |_| {
+                let focus = preview.common.focus.get();
+                if focus != Focus::EditPreview && preview.active.get_untracked() {
+                    preview.active.set(false);
+                }
+            });
+        }
+
+        preview
+    }
+
+    pub fn show(&self, files: Vec<PreviewFile>) {
+        self.preview_id.update(|id| *id += 1);
+        self.files.set(files.into());
+        self.rejected.set(HashSet::new());
+        self.active.set(true);
+        self.common.focus.set(Focus::EditPreview);
+    }
+
+    pub fn is_accepted(&self, file: usize, hunk: usize) -> bool {
+        self.rejected
+            .with(|rejected| !rejected.contains(&(file, hunk)))
+    }
+
+    /// Whether all of the edits of the file are ticked, or `None` when only some
+    /// of them are.
+    pub fn is_file_accepted(&self, file: usize) -> Option<bool> {
+        let hunks = self
+            .files
+            .with(|files| files.get(file).map_or(0, |file| file.hunks.len()));
+        let rejected = self
+            .rejected
+            .with(|rejected| rejected.iter().filter(|(f, _)| *f == file).count());
+        if rejected == 0 {
+            Some(true)
+        } else if rejected == hunks {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn toggle_hunk(&self, file: usize, hunk: usize) {
+        self.rejected.update(|rejected| {
+            if !rejected.remove(&(file, hunk)) {
+                rejected.insert((file, hunk));
+            }
+        });
+    }
+
+    /// Untick all of the edits of the file when they're all ticked, and tick
+    /// them all otherwise.
+    pub fn toggle_file(&self, file: usize) {
+        let accept = self.is_file_accepted(file) != Some(true);
+        let hunks = self.files.with_untracked(|files| {
+            files.get(file).map_or(0, |file| file.hunks.len())
+        });
+        self.rejected.update(|rejected| {
+            for hunk in 0..hunks {
+                if accept {
+                    rejected.remove(&(file, hunk));
+                } else {
+                    rejected.insert((file, hunk));
+                }
+            }
+        });
+    }
+
+    /// Make the edits which are ticked.
+    pub fn apply(&self) {
+        let file_edits = self.files.with_untracked(|files| {
+            let files: Vec<PreviewFile> = files.iter().cloned().collect();
+            self.rejected
+                .with_untracked(|rejected| accepted_file_edits(&files, rejected))
+        });
+        self.close();
+        if !file_edits.is_empty() {
+            self.common
+                .internal_command
+                .send(InternalCommand::ApplyFileEdits { file_edits });
+        }
+    }
+
+    pub fn close(&self) {
+        self.active.set(false);
+        if self.common.focus.get_untracked() == Focus::EditPreview {
+            self.common.focus.set(Focus::Workbench);
+        }
+    }
+
+    fn run_focus_command(&self, cmd: &FocusCommand) -> CommandExecuted {
+        match cmd {
+            FocusCommand::ModalClose => {
+                self.close();
+            }
+            FocusCommand::ListSelect => {
+                self.apply();
+            }
+            _ => return CommandExecuted::No,
+        }
+        CommandExecuted::Yes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use lsp_types::{Position, Range};
+
+    use super::*;
+
+    fn text_edit(start: (u32, u32), end: (u32, u32), new_text: &str) -> TextEdit {
+        TextEdit {
+            range: Range {
+                start: Position::new(start.0, start.1),
+                end: Position::new(end.0, end.1),
+            },
+            new_text: new_text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_preview_hunk() {
+        let buffer = Buffer::new("let a = 1;\nlet b = a;\n");
+        assert_eq!(
+            PreviewHunk::new(&buffer, &text_edit((1, 8), (1, 9), "value")),
+            PreviewHunk {
+                line: 1,
+                old: "let b = a;".to_string(),
+                new: "let b = value;".to_string(),
+            }
+        );
+        assert_eq!(
+            PreviewHunk::new(&buffer, &text_edit((0, 4), (1, 5), "x")),
+            PreviewHunk {
+                line: 0,
+                old: "let a = 1;\nlet b = a;".to_string(),
+                new: "let x = a;".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_accepted_file_edits() {
+        let file = |path: &str, count: u32| PreviewFile {
+            edit: FileEdit {
+                path: PathBuf::from(path),
+                version: None,
+                edits: (0..count)
+                    .map(|line| text_edit((line, 0), (line, 1), "x"))
+                    .collect(),
+            },
+            hunks: Vec::new(),
+        };
+        let files = vec![file("/a.rs", 2), file("/b.rs", 1)];
+        let edits = accepted_file_edits(&files, &HashSet::from([(0, 0), (1, 0)]));
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].path, PathBuf::from("/a.rs"));
+        assert_eq!(edits[0].edits, vec![text_edit((1, 0), (1, 1), "x")]);
+    }
+}
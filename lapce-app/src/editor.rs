@@ -1,4 +1,6 @@
-use std::{cmp::Ordering, str::FromStr, sync::Arc};
+use std::{
+    cmp::Ordering, path::PathBuf, str::FromStr, sync::Arc, thread, time::Duration,
+};
 
 use anyhow::Result;
 use floem::{
@@ -6,43 +8,74 @@
     glazier::{Modifiers, PointerButton, PointerEvent},
     peniko::kurbo::{Point, Rect, Vec2},
     reactive::{
-        create_rw_signal, use_context, RwSignal, Scope, SignalGetUntracked,
-        SignalSet, SignalUpdate, SignalWithUntracked,
+        create_effect, create_rw_signal, use_context, RwSignal, Scope,
+        SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
+        SignalWithUntracked,
     },
 };
+use fluent_bundle::FluentValue;
 use lapce_core::{
-    buffer::{rope_text::RopeText, InvalLines},
+    buffer::{
+        rope_text::{RopeText, RopeTextRef},
+        InvalLines,
+    },
     command::{EditCommand, FocusCommand, MotionModeCommand, MultiSelectionCommand},
     cursor::{Cursor, CursorMode},
     editor::EditType,
-    mode::{Mode, MotionMode},
+    mode::{Mode, MotionMode, VisualMode},
     movement::Movement,
+    paragraph::hard_wrap,
+    register::{Register, RegisterData},
     selection::{InsertDrift, Selection},
     syntax::edit::SyntaxEdit,
+    whitespace::WhitespaceCleanup,
+    word::WordCursor,
 };
-use lapce_rpc::{plugin::PluginId, proxy::ProxyResponse};
+use lapce_rpc::{plugin::PluginId, proxy::ProxyResponse, RpcError};
 use lapce_xi_rope::{Rope, RopeDelta, Transformer};
 use lsp_types::{
-    CompletionItem, CompletionTextEdit, GotoDefinitionResponse, Location, TextEdit,
+    CodeActionKind, CompletionItem, CompletionTextEdit, GotoDefinitionResponse,
+    Location, TextEdit,
 };
 use serde::{Deserialize, Serialize};
 
-use self::view::EditorViewData;
+use self::{
+    animation::{EditorAnimation, ScrollDevice},
+    view::EditorViewData,
+};
 use crate::{
-    command::{CommandExecuted, CommandKind, InternalCommand},
+    code_action::{
+        auto_fix, source_action_edits, CodeActionGroup, SOURCE_REMOVE_UNUSED_IMPORTS,
+    },
+    collab::{is_guest_path, protocol::CollabMessage},
+    color_picker::{find_color_literals, ColorLiteral},
+    command::{
+        CommandExecuted, CommandKind, InternalCommand, LapceWorkbenchCommand,
+    },
     completion::{clear_completion_lens, CompletionStatus},
+    config::{editor::ErrorLensMessages, LapceConfig},
     db::LapceDb,
-    doc::{DocContent, Document},
+    doc::{
+        changes::{next_change, LineChange},
+        DocContent, Document,
+    },
     editor::location::{EditorLocation, EditorPosition},
     editor_tab::EditorTabChild,
     id::{EditorId, EditorTabId},
-    keypress::{condition::Condition, KeyPressFocus},
+    keypress::{
+        condition::{mode_matches, Condition, ContextKey},
+        KeyPressFocus,
+    },
     main_split::{MainSplitData, SplitDirection, SplitMoveDirection},
+    markdown::{code_block, parse_hover_contents},
+    notification::{Notification, NotificationCommand},
+    popup::PopupKind,
     proxy::path_from_url,
     snippet::Snippet,
     window_tab::{CommonData, Focus, WindowTabData},
 };
 
+pub mod animation;
 pub mod location;
 pub mod movement;
 pub mod view;
@@ -118,11 +151,15 @@ pub struct EditorData {
     pub doc: RwSignal<Document>,
     pub view: EditorViewData,
     pub confirmed: RwSignal<bool>,
+    /// Whether the editor was opened only to look at the file, such as from a
+    /// search result, so that it can't be edited until it's made writable
+    pub read_only: RwSignal<bool>,
     pub cursor: RwSignal<Cursor>,
     pub window_origin: RwSignal<Point>,
     pub viewport: RwSignal<Rect>,
     pub scroll_delta: RwSignal<Vec2>,
     pub scroll_to: RwSignal<Option<Vec2>>,
+    pub animation: EditorAnimation,
     pub snippet: RwSignal<Option<SnippetIndex>>,
     pub last_movement: RwSignal<Movement>,
     pub inline_find: RwSignal<Option<InlineFindDirection>>,
@@ -162,10 +199,12 @@ pub fn new(
         let cursor = create_rw_signal(cx, cursor);
         let scroll_delta = create_rw_signal(cx, Vec2::ZERO);
         let scroll_to = create_rw_signal(cx, None);
+        let animation = EditorAnimation::new(cx, scroll_delta);
         let snippet = create_rw_signal(cx, None);
         let window_origin = create_rw_signal(cx, Point::ZERO);
         let viewport = create_rw_signal(cx, Rect::ZERO);
         let confirmed = create_rw_signal(cx, false);
+        let read_only = create_rw_signal(cx, false);
         let last_movement = create_rw_signal(cx, Movement::Left);
         let inline_find = create_rw_signal(cx, None);
         let last_inline_find = create_rw_signal(cx, None);
@@ -181,11 +220,13 @@ pub fn new(
             view,
             cursor,
             confirmed,
+            read_only,
             snippet,
             window_origin,
             viewport,
             scroll_delta,
             scroll_to,
+            animation,
             last_movement,
             inline_find,
             last_inline_find,
@@ -205,6 +246,7 @@ pub fn new_local(cx: Scope, editor_id: EditorId, common: CommonData) -> Self {
             common.config,
         );
         let doc = create_rw_signal(cx, doc);
+        Document::listen_syntax_updates(cx, doc);
         Self::new(cx, None, editor_id, doc, common)
     }
 
@@ -244,12 +286,14 @@ pub fn copy(
         editor.cursor = create_rw_signal(cx, editor.cursor.get_untracked());
         editor.viewport = create_rw_signal(cx, editor.viewport.get_untracked());
         editor.scroll_delta = create_rw_signal(cx, Vec2::ZERO);
+        editor.animation = EditorAnimation::new(cx, editor.scroll_delta);
         editor.scroll_to = create_rw_signal(
             cx,
             Some(editor.viewport.get_untracked().origin().to_vec2()),
         );
         editor.window_origin = create_rw_signal(cx, Point::ZERO);
         editor.confirmed = create_rw_signal(cx, true);
+        editor.read_only = create_rw_signal(cx, editor.read_only.get_untracked());
         editor.snippet = create_rw_signal(cx, None);
         editor.last_movement =
             create_rw_signal(cx, editor.last_movement.get_untracked());
@@ -263,7 +307,39 @@ pub fn copy(
         editor
     }
 
+    /// Whether the editor can't be edited, because it was opened for a look at the
+    /// file or the file can't be written
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.get_untracked()
+            || self.doc.with_untracked(|doc| doc.read_only())
+    }
+
+    /// Let the editor be edited, even when the file can't be written, as it can be
+    /// saved as administrator then.
+    pub fn make_writable(&self) {
+        if self.read_only.get_untracked() {
+            self.read_only.set(false);
+        }
+        if self.doc.with_untracked(|doc| doc.read_only()) {
+            self.doc.update(|doc| doc.set_read_only(false));
+        }
+    }
+
     fn run_edit_command(&self, cmd: &EditCommand) -> CommandExecuted {
+        if self.is_read_only() && changes_buffer(cmd) {
+            return CommandExecuted::Yes;
+        }
+        let cmd = match cmd {
+            EditCommand::ClipboardPaste
+                if !self
+                    .common
+                    .config
+                    .with_untracked(|config| config.editor.reindent_on_paste) =>
+            {
+                &EditCommand::ClipboardPasteWithoutFormatting
+            }
+            cmd => cmd,
+        };
         let modal = self
             .common
             .config
@@ -281,6 +357,15 @@ fn run_edit_command(&self, cmd: &EditCommand) -> CommandExecuted {
                 None
             };
 
+        if let EditCommand::ClipboardCopy | EditCommand::ClipboardCut = cmd {
+            let content = self
+                .doc
+                .with_untracked(|doc| cursor.yank(doc.buffer()).content);
+            self.common
+                .clipboard_history
+                .push(content, &self.common.config.get_untracked());
+        }
+
         let deltas = self
             .doc
             .try_update(|doc| doc.do_edit(&mut cursor, cmd, modal, &mut register))
@@ -293,7 +378,7 @@ fn run_edit_command(&self, cmd: &EditCommand) -> CommandExecuted {
         }
 
         self.cursor.set(cursor);
-        self.common.register.set(register);
+        self.set_register(register);
 
         if show_completion(cmd, &doc_before_edit, &deltas) {
             self.update_completion(false);
@@ -304,6 +389,9 @@ fn run_edit_command(&self, cmd: &EditCommand) -> CommandExecuted {
         if let EditCommand::NormalMode = cmd {
             self.snippet.set(None);
         }
+        if self.common.signature.active.get_untracked() {
+            self.update_signature();
+        }
 
         CommandExecuted::Yes
     }
@@ -315,6 +403,9 @@ fn run_motion_mode_command(&self, cmd: &MotionModeCommand) -> CommandExecuted {
             MotionModeCommand::MotionModeOutdent => MotionMode::Outdent,
             MotionModeCommand::MotionModeYank => MotionMode::Yank,
         };
+        if self.is_read_only() && motion_mode != MotionMode::Yank {
+            return CommandExecuted::Yes;
+        }
         let mut cursor = self.cursor.get_untracked();
         let mut register = self.common.register.get_untracked();
 
@@ -323,11 +414,28 @@ fn run_motion_mode_command(&self, cmd: &MotionModeCommand) -> CommandExecuted {
         });
 
         self.cursor.set(cursor);
-        self.common.register.set(register);
+        self.set_register(register);
 
         CommandExecuted::Yes
     }
 
+    /// Keep the register after a command, with the text which the command
+    /// yanked, like `y` does in modal editing, put in the clipboard history as
+    /// well.
+    fn set_register(&self, register: Register) {
+        let yanked = self
+            .common
+            .register
+            .with_untracked(|old| old.yanks() != register.yanks());
+        if yanked {
+            self.common.clipboard_history.push(
+                register.last_yank().content.clone(),
+                &self.common.config.get_untracked(),
+            );
+        }
+        self.common.register.set(register);
+    }
+
     fn run_multi_selection_command(
         &self,
         cmd: &MultiSelectionCommand,
@@ -335,7 +443,7 @@ fn run_multi_selection_command(
         let mut cursor = self.cursor.get_untracked();
         movement::do_multi_selection(&self.view, &mut cursor, cmd);
         self.cursor.set(cursor);
-        // self.cancel_signature();
+        self.cancel_signature();
         self.cancel_completion();
         CommandExecuted::Yes
     }
@@ -363,16 +471,16 @@ fn run_move_command(
         self.last_movement.set(movement.clone());
 
         let mut cursor = self.cursor.get_untracked();
-        self.common.register.update(|register| {
-            movement::move_cursor(
-                &self.view,
-                &mut cursor,
-                movement,
-                count.unwrap_or(1),
-                mods.shift(),
-                register,
-            )
-        });
+        let mut register = self.common.register.get_untracked();
+        movement::move_cursor(
+            &self.view,
+            &mut cursor,
+            movement,
+            count.unwrap_or(1),
+            mods.shift(),
+            &mut register,
+        );
+        self.set_register(register);
 
         self.cursor.set(cursor);
 
@@ -392,6 +500,9 @@ fn run_move_command(
             })
         }
         self.cancel_completion();
+        if self.common.signature.active.get_untracked() {
+            self.update_signature();
+        }
         CommandExecuted::Yes
     }
 
@@ -548,7 +659,7 @@ fn run_focus_command(
                         if last_placeholder {
                             *snippet = None;
                         }
-                        // self.update_signature();
+                        self.update_signature();
                         self.cancel_completion();
                     }
                 });
@@ -580,7 +691,7 @@ fn run_focus_command(
                                     cursor.set_insert(selection);
                                 });
                             }
-                            // self.update_signature();
+                            self.update_signature();
                             self.cancel_completion();
                         }
                     }
@@ -589,8 +700,31 @@ fn run_focus_command(
             FocusCommand::GotoDefinition => {
                 self.go_to_definition();
             }
+            FocusCommand::FindReferences => {
+                self.find_references();
+            }
             FocusCommand::ShowCodeActions => {
-                self.show_code_actions(false);
+                self.show_code_actions(false, None);
+            }
+            FocusCommand::ShowQuickFixes => {
+                self.show_code_actions(false, Some(CodeActionGroup::QuickFix));
+            }
+            FocusCommand::ShowRefactorActions => {
+                self.show_code_actions(false, Some(CodeActionGroup::Refactor));
+            }
+            FocusCommand::ShowSourceActions => {
+                self.show_code_actions(false, Some(CodeActionGroup::Source));
+            }
+            FocusCommand::AutoFix => {
+                self.auto_fix();
+            }
+            FocusCommand::OrganizeImports => {
+                self.run_source_action(CodeActionKind::SOURCE_ORGANIZE_IMPORTS);
+            }
+            FocusCommand::RemoveUnusedImports => {
+                self.run_source_action(CodeActionKind::from(
+                    SOURCE_REMOVE_UNUSED_IMPORTS,
+                ));
             }
             FocusCommand::SearchWholeWordForward => {
                 self.search_whole_word_forward(mods);
@@ -618,6 +752,46 @@ fn run_focus_command(
             FocusCommand::Rename => {
                 self.rename();
             }
+            FocusCommand::WrapLines => {
+                self.wrap_lines();
+            }
+            FocusCommand::EditEmbeddedCode => {
+                self.edit_embedded_code();
+            }
+            FocusCommand::PickColor => {
+                self.pick_color();
+            }
+            FocusCommand::ShowHover => {
+                let offset = self.cursor.with_untracked(|c| c.offset());
+                self.show_hover(offset);
+            }
+            FocusCommand::NextDiff => {
+                self.go_to_change(true);
+            }
+            FocusCommand::PreviousDiff => {
+                self.go_to_change(false);
+            }
+            FocusCommand::ShowDiff => {
+                self.show_change(self.cursor_line());
+            }
+            FocusCommand::RevertDiff => {
+                self.revert_change(self.cursor_line());
+            }
+            FocusCommand::CompareWithSaved => {
+                self.doc.with_untracked(|doc| doc.toggle_compare_saved());
+            }
+            FocusCommand::GetSignature => {
+                self.update_signature();
+            }
+            FocusCommand::CyclePopups => {
+                self.common.popups.cycle();
+            }
+            FocusCommand::ClosePopup => match self.common.popups.front(false) {
+                Some(PopupKind::Completion) => self.cancel_completion(),
+                Some(PopupKind::SignatureHelp) => self.cancel_signature(),
+                Some(PopupKind::Hover) => self.common.hover.hide(),
+                None => {}
+            },
             FocusCommand::ClearSearch => {
                 self.clear_search();
             }
@@ -650,6 +824,184 @@ fn run_focus_command(
         CommandExecuted::Yes
     }
 
+    /// Ask the language server about the text at the offset, and show what it
+    /// answers in the hover.
+    fn show_hover(&self, offset: usize) {
+        let Some(path) = self.doc.with_untracked(|doc| {
+            if doc.loaded() {
+                doc.content.path().cloned()
+            } else {
+                None
+            }
+        }) else {
+            return;
+        };
+        let position = self
+            .doc
+            .with_untracked(|doc| doc.buffer().offset_to_position(offset));
+
+        let hover = self.common.hover;
+        hover.request_id.update(|id| *id += 1);
+        let request_id = hover.request_id.get_untracked();
+        let editor = self.clone();
+        let send = create_ext_action(
+            self.scope,
+            move |result: Result<ProxyResponse, RpcError>| {
+                if hover.request_id.get_untracked() != request_id {
+                    return;
+                }
+                let Ok(ProxyResponse::HoverResponse { hover: info, .. }) = result
+                else {
+                    return;
+                };
+                let config = editor.common.config.get_untracked();
+                let content = parse_hover_contents(&info.contents, &config);
+                if content.is_empty() {
+                    return;
+                }
+                let (start, end) = editor.doc.with_untracked(|doc| {
+                    let buffer = doc.buffer();
+                    match info.range {
+                        Some(range) => (
+                            buffer.offset_of_position(&range.start),
+                            buffer.offset_of_position(&range.end),
+                        ),
+                        None => WordCursor::new(buffer.text(), offset).select_word(),
+                    }
+                });
+                hover.show(editor.editor_id, start, (start, end), content);
+            },
+        );
+        self.common
+            .proxy
+            .get_hover(request_id, path, position, move |result| {
+                send(result);
+            });
+    }
+
+    /// Show the hover of the text under the mouse once the mouse rests on it for
+    /// the hover delay.
+    fn hover_at(&self, pos: Point) {
+        let hover = self.common.hover;
+        let mode = self.cursor.with_untracked(|c| c.get_mode());
+        let (offset, is_inside) = self.view.offset_of_point(mode, pos);
+        if hover.is_over(self.editor_id, offset)
+            || hover.pending.get_untracked() == Some((self.editor_id, offset))
+        {
+            return;
+        }
+        hover.hide();
+        if !is_inside || self.doc.with_untracked(|doc| doc.content.is_local()) {
+            return;
+        }
+
+        hover.pending.set(Some((self.editor_id, offset)));
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |_| {
+            let pending = editor.common.hover.pending.get_untracked();
+            if pending == Some((editor.editor_id, offset)) {
+                editor.show_hover(offset);
+            }
+        });
+        let delay = self.common.config.get_untracked().editor.hover_delay;
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(delay));
+            send(());
+        });
+    }
+
+    /// Edit the code which is embedded at the cursor, like a code block of
+    /// markdown, in an editor of its own.
+    fn edit_embedded_code(&self) {
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let code = self.doc.with_untracked(|doc| {
+            let host = doc.content.path()?.clone();
+            let (range, extension) = doc.embedded_code_at(offset)?;
+            Some((host, range, extension))
+        });
+        if let Some((host, range, extension)) = code {
+            self.common
+                .internal_command
+                .send(InternalCommand::EditEmbeddedCode {
+                    host,
+                    range,
+                    extension,
+                });
+        }
+    }
+
+    /// Pick the color of the literal which the cursor is in or right after.
+    fn pick_color(&self) {
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let literal = self.doc.with_untracked(|doc| {
+            let buffer = doc.buffer();
+            let line = buffer.line_of_offset(offset);
+            let line_start = buffer.offset_of_line(line);
+            let content = buffer.line_content(line);
+            let col = offset - line_start;
+            let literal =
+                find_color_literals(&content).into_iter().find(|literal| {
+                    literal.range.start <= col && col <= literal.range.end
+                })?;
+            Some(ColorLiteral {
+                range: line_start + literal.range.start
+                    ..line_start + literal.range.end,
+                ..literal
+            })
+        });
+        if let Some(literal) = literal {
+            self.common
+                .internal_command
+                .send(InternalCommand::StartColorPicker { literal });
+        }
+    }
+
+    /// Wrap the selected lines, or the paragraph around the cursor, at the wrap
+    /// column of the language.
+    fn wrap_lines(&self) {
+        let language = self.view.language();
+        let column = self
+            .common
+            .config
+            .get_untracked()
+            .editor_config(language)
+            .wrap_column;
+        let comment_token = language
+            .map(|language| language.comment_token().to_string())
+            .unwrap_or_default();
+        let cursor = self.cursor.get_untracked();
+        let Some((selection, range, wrapped)) = self.doc.with_untracked(|doc| {
+            let buffer = doc.buffer();
+            let selection = cursor.edit_selection(buffer);
+            let mut start_line = buffer.line_of_offset(selection.min_offset());
+            let mut end_line = buffer.line_of_offset(selection.max_offset());
+            if selection.is_caret() {
+                let is_blank =
+                    |line: usize| buffer.line_content(line).trim().is_empty();
+                if is_blank(start_line) {
+                    return None;
+                }
+                while start_line > 0 && !is_blank(start_line - 1) {
+                    start_line -= 1;
+                }
+                while end_line < buffer.last_line() && !is_blank(end_line + 1) {
+                    end_line += 1;
+                }
+            }
+            let start = buffer.offset_of_line(start_line);
+            let end = buffer.line_end_offset(end_line, true);
+            let text = buffer.slice_to_cow(start..end);
+            let wrapped = hard_wrap(&text, column, &comment_token);
+            (wrapped != text).then(|| (selection, start..end, wrapped))
+        }) else {
+            return;
+        };
+        self.do_edit(
+            &selection,
+            &[(Selection::region(range.start, range.end), wrapped.as_str())],
+        );
+    }
+
     /// Jump to the next/previous column on the line which matches the given text
     fn inline_find(&self, direction: InlineFindDirection, c: &str) {
         let offset = self.cursor.with_untracked(|c| c.offset());
@@ -712,10 +1064,14 @@ fn go_to_definition(&self) {
         enum DefinitionOrReferece {
             Location(EditorLocation),
             References(Vec<Location>),
+            /// The language server doesn't know the definition, so it's looked
+            /// up in the syntax tree
+            Syntax,
         }
 
         let internal_command = self.common.internal_command;
         let cursor = self.cursor.read_only();
+        let editor = self.clone();
         let send = create_ext_action(self.scope, move |d| {
             let current_offset = cursor.with_untracked(|c| c.offset());
             if current_offset != offset {
@@ -743,6 +1099,9 @@ enum DefinitionOrReferece {
                             .collect(),
                     });
                 }
+                DefinitionOrReferece::Syntax => {
+                    editor.syntax_go_to_definition(offset);
+                }
             }
         });
         let proxy = self.common.proxy.clone();
@@ -823,20 +1182,199 @@ enum DefinitionOrReferece {
                                 same_editor_tab: false,
                             }));
                         }
+                    } else {
+                        send(DefinitionOrReferece::Syntax);
                     }
+                } else {
+                    send(DefinitionOrReferece::Syntax);
                 }
             },
         );
     }
 
+    /// Go to the definition of the name at the offset which the syntax tree
+    /// finds, for when there is no language server which knows it. When the
+    /// offset is on the definition already, its references in the file are shown.
+    fn syntax_go_to_definition(&self, offset: usize) {
+        let (path, definition) = self.doc.with_untracked(|doc| {
+            (
+                doc.content.path().cloned(),
+                doc.syntax()
+                    .and_then(|syntax| syntax.find_definition(offset)),
+            )
+        });
+        let (Some(path), Some(definition)) = (path, definition) else {
+            return;
+        };
+
+        if definition.start <= offset && offset <= definition.end {
+            self.syntax_find_references(path, offset);
+        } else {
+            self.common
+                .internal_command
+                .send(InternalCommand::JumpToLocation {
+                    location: EditorLocation {
+                        path,
+                        position: Some(EditorPosition::Offset(definition.start)),
+                        scroll_offset: None,
+                        ignore_unconfirmed: false,
+                        same_editor_tab: false,
+                    },
+                });
+        }
+    }
+
+    /// Show the references to the name at the cursor which the language server
+    /// finds, or the ones in the file which the syntax tree finds when there is no
+    /// language server.
+    fn find_references(&self) {
+        let path = match self.doc.with_untracked(|doc| {
+            if doc.loaded() {
+                doc.content.path().cloned()
+            } else {
+                None
+            }
+        }) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let position = self
+            .doc
+            .with_untracked(|doc| doc.buffer().offset_to_position(offset));
+
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, {
+            let path = path.clone();
+            move |references: Option<Vec<Location>>| {
+                if editor.cursor.with_untracked(|c| c.offset()) != offset {
+                    return;
+                }
+                match references {
+                    Some(references) if !references.is_empty() => {
+                        editor.common.internal_command.send(
+                            InternalCommand::PaletteReferences {
+                                references: references
+                                    .into_iter()
+                                    .map(|l| EditorLocation {
+                                        path: path_from_url(&l.uri),
+                                        position: Some(EditorPosition::Position(
+                                            l.range.start,
+                                        )),
+                                        scroll_offset: None,
+                                        ignore_unconfirmed: false,
+                                        same_editor_tab: false,
+                                    })
+                                    .collect(),
+                            },
+                        );
+                    }
+                    _ => editor.syntax_find_references(path, offset),
+                }
+            }
+        });
+        self.common
+            .proxy
+            .get_references(path, position, move |result| {
+                if let Ok(ProxyResponse::GetReferencesResponse { references }) =
+                    result
+                {
+                    send(Some(references));
+                } else {
+                    send(None);
+                }
+            });
+    }
+
+    /// Show the references in the file to the name at the offset, which the
+    /// syntax tree finds.
+    fn syntax_find_references(&self, path: PathBuf, offset: usize) {
+        let references: Vec<EditorLocation> = self.doc.with_untracked(|doc| {
+            let Some(syntax) = doc.syntax() else {
+                return Vec::new();
+            };
+            syntax
+                .find_references(offset)
+                .into_iter()
+                .map(|range| EditorLocation {
+                    path: path.clone(),
+                    position: Some(EditorPosition::Position(
+                        doc.buffer().offset_to_position(range.start),
+                    )),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                })
+                .collect()
+        });
+        if !references.is_empty() {
+            self.common
+                .internal_command
+                .send(InternalCommand::PaletteReferences { references });
+        }
+    }
+
+    /// Scroll the editor by `delta`, smoothly if that's turned on.
+    pub fn scroll_by(&self, delta: Vec2) {
+        let (duration, easing) = self.common.config.with_untracked(|config| {
+            (
+                config.editor.scroll_duration(),
+                config.editor.scroll_easing(),
+            )
+        });
+        self.animation.scroll_by(delta, duration, easing);
+    }
+
+    /// Scroll by a wheel `delta`. Mouse wheels scroll by it times the scroll
+    /// speed modifier, or the fast one while Alt is held, and sideways while
+    /// Shift is. Touchpads scroll by the pixel, with their own sensitivity, and
+    /// zoom when pinched, which comes with Ctrl held.
+    pub fn wheel_scroll(&self, delta: Vec2, mods: Modifiers) {
+        let config = self.common.config.get_untracked();
+        let device = ScrollDevice::of(delta);
+        if mods.ctrl() && config.editor.pinch_to_zoom {
+            let steps = self.animation.pinch(delta.y, device);
+            let command = if steps > 0 {
+                LapceWorkbenchCommand::ZoomIn
+            } else {
+                LapceWorkbenchCommand::ZoomOut
+            };
+            for _ in 0..steps.unsigned_abs() {
+                self.common.workbench_command.send(command.clone());
+            }
+            return;
+        }
+        match device {
+            ScrollDevice::Wheel => {
+                let modifier = if mods.alt() {
+                    config.editor.fast_scroll_speed_modifier
+                } else {
+                    config.editor.scroll_speed_modifier
+                };
+                let delta = if mods.shift() && delta.x == 0.0 {
+                    Vec2::new(delta.y, 0.0)
+                } else {
+                    delta
+                };
+                self.scroll_by(delta * modifier);
+            }
+            ScrollDevice::Touchpad => {
+                self.animation.touchpad_scroll(
+                    delta * config.editor.touchpad_scroll_sensitivity,
+                    config.editor.kinetic_scrolling,
+                );
+            }
+        }
+    }
+
     fn page_move(&self, down: bool, mods: Modifiers) {
         let config = self.common.config.get_untracked();
         let viewport = self.viewport.get_untracked();
         let line_height = config.editor.line_height() as f64;
         let lines = (viewport.height() / line_height / 2.0).round() as usize;
         let distance = (lines as f64) * line_height;
-        self.scroll_delta
-            .set(Vec2::new(0.0, if down { distance } else { -distance }));
+        self.scroll_by(Vec2::new(0.0, if down { distance } else { -distance }));
         self.run_move_command(
             if down {
                 &lapce_core::movement::Movement::Down
@@ -876,7 +1414,7 @@ fn scroll(&self, down: bool, count: usize, mods: Modifiers) {
             line
         };
 
-        self.scroll_delta.set(Vec2::new(0.0, diff));
+        self.scroll_by(Vec2::new(0.0, diff));
 
         match new_line.cmp(&line) {
             Ordering::Greater => {
@@ -956,9 +1494,51 @@ pub fn cancel_completion(&self) {
         clear_completion_lens(self.doc);
     }
 
-    /// Update the displayed autocompletion box  
-    /// Sends a request to the LSP for completion information
+    /// Accept the current completion item right away, without resolving it, for
+    /// the commit character which is typed after it.
+    fn commit_completion(&self) {
+        let item = self
+            .common
+            .completion
+            .with_untracked(|c| c.current_item().cloned());
+        self.cancel_completion();
+        if let Some(item) = item {
+            let _ = self.apply_completion_item(&item.item);
+        }
+    }
+
+    /// Update the displayed autocompletion box, which is only started once the
+    /// typing stops for the completion delay
     fn update_completion(&self, display_if_empty_input: bool) {
+        let delay = self.common.config.get_untracked().editor.completion_delay;
+        if delay == 0
+            || self
+                .common
+                .completion
+                .with_untracked(|c| c.status != CompletionStatus::Inactive)
+        {
+            self.request_completion(display_if_empty_input);
+            return;
+        }
+
+        let rev = self.doc.with_untracked(|doc| doc.rev());
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let editor = self.clone();
+        let send = create_ext_action(self.scope, move |_| {
+            if editor.doc.with_untracked(|doc| doc.rev()) == rev
+                && editor.cursor.with_untracked(|c| c.offset()) == offset
+            {
+                editor.request_completion(display_if_empty_input);
+            }
+        });
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(delay));
+            send(());
+        });
+    }
+
+    /// Sends a request to the LSP for completion information
+    fn request_completion(&self, display_if_empty_input: bool) {
         if self.get_mode() != Mode::Insert {
             self.cancel_completion();
             return;
@@ -1072,6 +1652,34 @@ fn update_completion(&self, display_if_empty_input: bool) {
     }
 
     /// Check if there are completions that are being rendered
+    /// Ask for the signature of the call which the cursor is in, which updates
+    /// the one which is shown for where the cursor is now.
+    fn update_signature(&self) {
+        if self.get_mode() != Mode::Insert {
+            self.cancel_signature();
+            return;
+        }
+        let Some(path) = self.doc.with_untracked(|doc| {
+            if doc.loaded() {
+                doc.content.path().cloned()
+            } else {
+                None
+            }
+        }) else {
+            return;
+        };
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let position = self
+            .doc
+            .with_untracked(|doc| doc.buffer().offset_to_position(offset));
+        let request_id = self.common.signature.start_request(self.editor_id, offset);
+        self.common.proxy.signature_help(request_id, path, position);
+    }
+
+    pub fn cancel_signature(&self) {
+        self.common.signature.hide();
+    }
+
     fn has_completions(&self) -> bool {
         self.common.completion.with_untracked(|completion| {
             completion.status != CompletionStatus::Inactive
@@ -1253,6 +1861,9 @@ fn do_edit(
         selection: &Selection,
         edits: &[(impl AsRef<Selection>, &str)],
     ) {
+        if self.is_read_only() {
+            return;
+        }
         let mut cursor = self.cursor.get_untracked();
         let (delta, inval_lines, edits) = self
             .doc
@@ -1358,14 +1969,33 @@ pub fn go_to_location(
     ) {
         if !new_doc {
             self.do_go_to_location(location, edits);
+        } else if is_guest_path(&location.path) {
+            // The host sends the content of the file, which is put in the document
+            // when it comes
+            if let Some(path) = self.common.collab.shared_path(&location.path) {
+                self.common.collab.send(CollabMessage::OpenFile { path });
+            }
+            let doc = self.doc;
+            let editor = self.clone();
+            create_effect(self.scope, move |done| {
+                if done == Some(true) {
+                    return true;
+                }
+                let loaded = doc.with(|doc| doc.loaded());
+                if loaded {
+                    editor.do_go_to_location(location.clone(), edits.clone());
+                }
+                loaded
+            });
         } else {
             let buffer_id = self.doc.with_untracked(|doc| doc.buffer_id);
             let set_doc = self.doc.write_only();
             let editor = self.clone();
             let path = location.path.clone();
-            let send = create_ext_action(self.scope, move |content| {
+            let send = create_ext_action(self.scope, move |(content, read_only)| {
                 set_doc.update(move |doc| {
                     doc.init_content(content);
+                    doc.set_read_only(read_only);
                 });
 
                 editor.do_go_to_location(location.clone(), edits.clone());
@@ -1374,9 +2004,12 @@ pub fn go_to_location(
             self.common
                 .proxy
                 .new_buffer(buffer_id, path, move |result| {
-                    if let Ok(ProxyResponse::NewBufferResponse { content }) = result
+                    if let Ok(ProxyResponse::NewBufferResponse {
+                        content,
+                        read_only,
+                    }) = result
                     {
-                        send(Rope::from(content))
+                        send((Rope::from(content), read_only))
                     }
                 });
         }
@@ -1398,6 +2031,7 @@ pub fn go_to_position(
             Cursor::new(CursorMode::Insert(Selection::caret(offset)), None, None)
         });
         if let Some(scroll_offset) = scroll_offset {
+            self.animation.stop_scroll();
             self.scroll_to.set(Some(scroll_offset));
         }
         if let Some(edits) = edits.as_ref() {
@@ -1479,52 +2113,233 @@ pub fn get_code_actions(&self) {
         );
     }
 
-    pub fn show_code_actions(&self, mouse_click: bool) {
+    /// Show the menu of the code actions at the cursor of the group, or all of
+    /// them when there's none.
+    pub fn show_code_actions(
+        &self,
+        mouse_click: bool,
+        group: Option<CodeActionGroup>,
+    ) {
         let offset = self.cursor.with_untracked(|c| c.offset());
         let code_actions = self
             .doc
             .with_untracked(|doc| doc.code_actions.get(&offset).cloned());
         if let Some(code_actions) = code_actions {
-            if !code_actions.1.is_empty() {
+            let has_actions = code_actions.1.iter().any(|item| {
+                group.map_or(true, |group| CodeActionGroup::of(item) == group)
+            });
+            if has_actions {
                 self.common.internal_command.send(
                     InternalCommand::ShowCodeActions {
                         offset,
                         mouse_click,
                         code_actions,
+                        group,
                     },
                 );
             }
         }
     }
 
+    /// Apply the quick fix at the cursor which is preferred, or the only one,
+    /// and show the menu of them when there are more to pick from.
+    fn auto_fix(&self) {
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let Some(code_actions) = self
+            .doc
+            .with_untracked(|doc| doc.code_actions.get(&offset).cloned())
+        else {
+            return;
+        };
+        match auto_fix(&code_actions.1) {
+            Some(action) => {
+                self.common
+                    .internal_command
+                    .send(InternalCommand::RunCodeAction {
+                        plugin_id: code_actions.0,
+                        action: action.clone(),
+                    });
+            }
+            None => {
+                self.show_code_actions(false, Some(CodeActionGroup::QuickFix));
+            }
+        }
+    }
+
+    /// Move the cursor to the next or the previous of the changes in the gutter.
+    fn go_to_change(&self, forward: bool) {
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        let Some(line) = self.doc.with_untracked(|doc| {
+            let buffer = doc.buffer();
+            let line = buffer.line_of_offset(offset);
+            next_change(&doc.line_changes(), line, forward)
+                .map(|change| change.new.start.min(buffer.last_line()))
+        }) else {
+            return;
+        };
+        self.go_to_position(EditorPosition::Line(line), None, None);
+    }
+
+    fn cursor_line(&self) -> usize {
+        let offset = self.cursor.with_untracked(|c| c.offset());
+        self.doc
+            .with_untracked(|doc| doc.buffer().line_of_offset(offset))
+    }
+
+    /// The change in the gutter which is on the line, with the lines which it
+    /// replaced.
+    pub fn change_on_line(&self, line: usize) -> Option<(LineChange, String)> {
+        let (change, base) = self.doc.with_untracked(|doc| {
+            let change = doc
+                .line_changes()
+                .into_iter()
+                .find(|change| change.is_on_line(line))?;
+            Some((change, doc.diff_base()?))
+        })?;
+        let base = RopeTextRef::new(&base);
+        let old = base
+            .slice_to_cow(
+                base.offset_of_line(change.old.start)
+                    ..base.offset_of_line(change.old.end),
+            )
+            .to_string();
+        Some((change, old))
+    }
+
+    /// Show the lines which the change on the line removed in the hover,
+    /// highlighted as the language of the document.
+    pub fn show_change(&self, line: usize) {
+        let Some((change, old)) = self.change_on_line(line) else {
+            return;
+        };
+        let config = self.common.config.get_untracked();
+        let (language, start, end) = self.doc.with_untracked(|doc| {
+            let buffer = doc.buffer();
+            (
+                doc.syntax().map(|syntax| syntax.language),
+                buffer.offset_of_line(change.new.start),
+                buffer.offset_of_line(change.new.end),
+            )
+        });
+        let Some(content) = code_block(&old, language, &config) else {
+            return;
+        };
+        self.common
+            .hover
+            .show(self.editor_id, start, (start, end), vec![content]);
+    }
+
+    /// Put back the lines which the change on the line replaced.
+    pub fn revert_change(&self, line: usize) {
+        let Some((change, old)) = self.change_on_line(line) else {
+            return;
+        };
+        let (selection, start, end) = self.doc.with_untracked(|doc| {
+            let buffer = doc.buffer();
+            (
+                self.cursor.get_untracked().edit_selection(buffer),
+                buffer.offset_of_line(change.new.start),
+                buffer.offset_of_line(change.new.end),
+            )
+        });
+        self.do_edit(&selection, &[(Selection::region(start, end), old.as_str())]);
+    }
+
     fn do_save(&self) {
         let (rev, content) = self
             .doc
             .with_untracked(|doc| (doc.rev(), doc.content.clone()));
 
+        // The workspace settings file isn't watched like the user settings, so
+        // the config is reloaded explicitly when it's saved.
+        let is_workspace_settings = match &content {
+            DocContent::File(path) => {
+                LapceConfig::workspace_settings_path(&self.common.workspace).as_ref()
+                    == Some(path)
+            }
+            _ => false,
+        };
+        let internal_command = self.common.internal_command;
+        let config = self.common.config;
+
         let doc = self.doc;
-        let send = create_ext_action(self.scope, move |result| {
-            if let Ok(ProxyResponse::SaveResponse {}) = result {
+        let send = create_ext_action(self.scope, move |result| match result {
+            Ok(ProxyResponse::SaveResponse {}) => {
                 let current_rev = doc.with_untracked(|doc| doc.rev());
                 if current_rev == rev {
                     doc.update(|doc| {
-                        doc.buffer_mut().set_pristine();
+                        doc.set_pristine();
+                        doc.clear_disk_conflict();
                     });
                 }
+                if is_workspace_settings {
+                    internal_command.send(InternalCommand::ReloadConfig);
+                }
             }
+            Err(RpcError { code, message })
+                if code == RpcError::PERMISSION_DENIED =>
+            {
+                let Some(path) =
+                    doc.with_untracked(|doc| doc.content.path().cloned())
+                else {
+                    return;
+                };
+                let config = config.get_untracked();
+                let name = path
+                    .file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .to_string();
+                internal_command.send(InternalCommand::ShowNotification {
+                    notification: Notification::error(
+                        config.tr_args(
+                            "notification-save-failed",
+                            [("file", FluentValue::from(name))],
+                        ),
+                        message,
+                    )
+                    .with_action(
+                        config.tr("notification-save-as-admin"),
+                        NotificationCommand::Internal(
+                            InternalCommand::SaveAsAdmin { path },
+                        ),
+                    ),
+                });
+            }
+            _ => {}
         });
 
         if let DocContent::File(path) = content {
-            self.common.proxy.save(rev, path, move |result| {
+            let options = config.get_untracked().editor.save_options();
+            self.common.proxy.save(rev, path, options, move |result| {
                 send(result);
             })
         }
     }
 
-    pub fn save(&self, exit: bool, allow_formatting: bool) {
-        let (rev, is_pristine, content) = self.doc.with_untracked(|doc| {
-            (doc.rev(), doc.buffer().is_pristine(), doc.content.clone())
+    /// Run the source action of the kind for the whole of the document, when
+    /// it's a file.
+    fn run_source_action(&self, kind: CodeActionKind) {
+        let path = self.doc.with_untracked(|doc| {
+            doc.loaded().then(|| doc.content.path().cloned()).flatten()
         });
+        if let Some(path) = path {
+            self.common
+                .internal_command
+                .send(InternalCommand::RunSourceAction { path, kind });
+        }
+    }
+
+    pub fn save(&self, exit: bool, allow_formatting: bool) {
+        let (is_pristine, content, language, embedded) =
+            self.doc.with_untracked(|doc| {
+                (
+                    doc.buffer().is_pristine(),
+                    doc.content.clone(),
+                    doc.syntax().map(|syntax| syntax.language),
+                    doc.embedded().is_some(),
+                )
+            });
 
         if content.path().is_some() && is_pristine {
             if exit {}
@@ -1533,34 +2348,149 @@ pub fn save(&self, exit: bool, allow_formatting: bool) {
 
         let config = self.common.config.get_untracked();
         if let DocContent::File(path) = content {
-            let format_on_save = allow_formatting && config.editor.format_on_save;
-            if format_on_save {
-                let editor = self.clone();
-                let send = create_ext_action(self.scope, move |result| {
-                    if let Ok(Ok(ProxyResponse::GetDocumentFormatting { edits })) =
-                        result
-                    {
-                        let current_rev = editor.doc.with_untracked(|doc| doc.rev());
-                        if current_rev == rev {
-                            editor.do_text_edit(&edits);
-                        }
+            // Embedded code is saved to its host
+            if embedded {
+                self.common
+                    .internal_command
+                    .send(InternalCommand::SaveEmbeddedCode { path });
+                return;
+            }
+            // The host saves the files of a collaboration session
+            if is_guest_path(&path) {
+                if let Some(path) = self.common.collab.shared_path(&path) {
+                    self.common.collab.send(CollabMessage::Save { path });
+                }
+                return;
+            }
+            let editor_config = config.editor_config(language);
+            let format_on_save = allow_formatting && editor_config.format_on_save;
+            let actions_on_save = if allow_formatting {
+                editor_config
+                    .code_actions_on_save
+                    .iter()
+                    .map(|kind| CodeActionKind::from(kind.clone()))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let cleanup = if allow_formatting {
+                WhitespaceCleanup {
+                    trim_trailing_whitespace: editor_config
+                        .trim_trailing_whitespace_on_save,
+                    insert_final_newline: editor_config.insert_final_newline_on_save,
+                    trim_final_newlines: editor_config.trim_final_newlines_on_save,
+                }
+            } else {
+                WhitespaceCleanup::default()
+            };
+            self.save_with_actions(path, actions_on_save, format_on_save, cleanup);
+        }
+    }
+
+    /// Run the code actions of the kinds on the file one after the other, each
+    /// on the edits of the ones before it, and then format it, clean up its
+    /// whitespace and save it. Only the edits of an action to the file itself
+    /// are made.
+    fn save_with_actions(
+        &self,
+        path: PathBuf,
+        mut kinds: Vec<CodeActionKind>,
+        format_on_save: bool,
+        cleanup: WhitespaceCleanup,
+    ) {
+        if kinds.is_empty() {
+            self.format_and_save(path, format_on_save, cleanup);
+            return;
+        }
+        let kind = kinds.remove(0);
+        let (rev, (range, diagnostics)) = self
+            .doc
+            .with_untracked(|doc| (doc.rev(), doc.source_action_context()));
+
+        let editor = self.clone();
+        let action_path = path.clone();
+        let send =
+            create_ext_action(self.scope, move |edits: Option<Vec<TextEdit>>| {
+                if let Some(edits) = edits {
+                    if editor.doc.with_untracked(|doc| doc.rev()) == rev {
+                        editor.do_text_edit(&edits);
                     }
-                    editor.do_save();
-                });
+                }
+                editor.save_with_actions(path, kinds, format_on_save, cleanup);
+            });
+        let proxy = self.common.proxy.clone();
+        std::thread::spawn(move || {
+            send(source_action_edits(
+                &proxy,
+                action_path,
+                range,
+                kind,
+                diagnostics,
+            ));
+        });
+    }
 
-                let (tx, rx) = crossbeam_channel::bounded(1);
-                let proxy = self.common.proxy.clone();
-                std::thread::spawn(move || {
-                    proxy.get_document_formatting(path, move |result| {
-                        let _ = tx.send(result);
-                    });
-                    let result = rx.recv_timeout(std::time::Duration::from_secs(1));
-                    send(result);
+    fn format_and_save(
+        &self,
+        path: PathBuf,
+        format_on_save: bool,
+        cleanup: WhitespaceCleanup,
+    ) {
+        let rev = self.doc.with_untracked(|doc| doc.rev());
+        if format_on_save {
+            let editor = self.clone();
+            let send = create_ext_action(self.scope, move |result| {
+                if let Ok(Ok(ProxyResponse::GetDocumentFormatting { edits })) =
+                    result
+                {
+                    let current_rev = editor.doc.with_untracked(|doc| doc.rev());
+                    if current_rev == rev {
+                        editor.do_text_edit(&edits);
+                    }
+                }
+                editor.clean_up_and_save(cleanup);
+            });
+
+            let (tx, rx) = crossbeam_channel::bounded(1);
+            let proxy = self.common.proxy.clone();
+            std::thread::spawn(move || {
+                proxy.get_document_formatting(path, move |result| {
+                    let _ = tx.send(result);
                 });
-            } else {
-                self.do_save();
+                let result = rx.recv_timeout(std::time::Duration::from_secs(1));
+                send(result);
+            });
+        } else {
+            self.clean_up_and_save(cleanup);
+        }
+    }
+
+    /// Clean up the whitespace of the document with edits, which are undone
+    /// like the others, and save it.
+    fn clean_up_and_save(&self, cleanup: WhitespaceCleanup) {
+        if !cleanup.is_empty() {
+            let (selection, edits) = self.doc.with_untracked(|doc| {
+                let buffer = doc.buffer();
+                let selection = self.cursor.get_untracked().edit_selection(buffer);
+                let cursor_lines = selection
+                    .regions()
+                    .iter()
+                    .map(|region| buffer.line_of_offset(region.end))
+                    .collect::<Vec<_>>();
+                let edits = cleanup
+                    .edits(buffer.text(), &cursor_lines)
+                    .into_iter()
+                    .map(|(range, text)| {
+                        (Selection::region(range.start, range.end), text)
+                    })
+                    .collect::<Vec<_>>();
+                (selection, edits)
+            });
+            if !edits.is_empty() {
+                self.do_edit(&selection, &edits);
             }
         }
+        self.do_save();
     }
 
     fn search_whole_word_forward(&self, mods: Modifiers) {
@@ -1782,6 +2712,7 @@ fn search(&self) {
     }
 
     pub fn pointer_down(&self, pointer_event: &PointerEvent) {
+        self.common.hover.hide();
         if let Some(editor_tab_id) = self.editor_tab_id {
             self.common
                 .internal_command
@@ -1797,10 +2728,54 @@ pub fn pointer_down(&self, pointer_event: &PointerEvent) {
                 self.left_click(pointer_event);
             }
             PointerButton::Right => {}
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+            PointerButton::Middle => {
+                self.middle_click(pointer_event);
+            }
             _ => {}
         }
     }
 
+    /// Paste `content` at the cursor, without going through the clipboard.
+    pub fn paste(&self, content: String) {
+        if self.is_read_only() {
+            return;
+        }
+        let mode = if content.ends_with('\n') {
+            VisualMode::Linewise
+        } else {
+            VisualMode::Normal
+        };
+        let data = RegisterData { content, mode };
+        let mut cursor = self.cursor.get_untracked();
+        let deltas = self
+            .doc
+            .try_update(|doc| doc.do_paste(&mut cursor, &data))
+            .unwrap();
+        self.cursor.set(cursor);
+        self.apply_deltas(&deltas);
+    }
+
+    /// Paste the text which is selected in an editor where the middle click is,
+    /// which is how the primary selection works on X11 and Wayland.
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn middle_click(&self, pointer_event: &PointerEvent) {
+        let Some(content) = self
+            .common
+            .clipboard_history
+            .primary_selection
+            .get_untracked()
+        else {
+            return;
+        };
+        let mode = self.cursor.with_untracked(|c| c.get_mode());
+        let (offset, _) = self.view.offset_of_point(mode, pointer_event.pos);
+        self.cursor.update(|cursor| {
+            cursor.set_offset(offset, false, false);
+        });
+        self.paste(content);
+    }
+
     fn left_click(&self, pointer_event: &PointerEvent) {
         match pointer_event.count {
             1 => {
@@ -1861,7 +2836,9 @@ fn triple_click(&self, pointer_event: &PointerEvent) {
     }
 
     pub fn pointer_move(&self, pointer_event: &PointerEvent) {
+        self.hover_line(pointer_event.pos);
         if !self.active.get_untracked() {
+            self.hover_at(pointer_event.pos);
             return;
         }
 
@@ -1872,6 +2849,22 @@ pub fn pointer_move(&self, pointer_event: &PointerEvent) {
         });
     }
 
+    /// Tell the document which line is under the mouse, for the diagnostic
+    /// messages which are only shown on hover.
+    fn hover_line(&self, pos: Point) {
+        let config = self.common.config.get_untracked();
+        if !config.editor.enable_error_lens
+            || config.editor.error_lens_messages != ErrorLensMessages::Hover
+        {
+            return;
+        }
+        let line = (pos.y / config.editor.line_height() as f64).floor() as usize;
+        let line = (line <= self.view.last_line()).then_some(line);
+        if self.doc.with_untracked(|doc| doc.hovered_line() != line) {
+            self.doc.update(|doc| doc.set_hovered_line(line));
+        }
+    }
+
     pub fn pointer_up(&self, _pointer_event: &PointerEvent) {
         self.active.set(false);
     }
@@ -1902,6 +2895,26 @@ fn check_condition(&self, condition: Condition) -> bool {
             }
             Condition::ListFocus => self.has_completions(),
             Condition::CompletionFocus => self.has_completions(),
+            Condition::CompletionAcceptsEnter => {
+                self.has_completions()
+                    && self
+                        .common
+                        .config
+                        .get_untracked()
+                        .editor
+                        .completion_accept_key
+                        .accepts_enter()
+            }
+            Condition::CompletionAcceptsTab => {
+                self.has_completions()
+                    && self
+                        .common
+                        .config
+                        .get_untracked()
+                        .editor
+                        .completion_accept_key
+                        .accepts_tab()
+            }
             Condition::InSnippet => self.snippet.with_untracked(|s| s.is_some()),
             Condition::EditorFocus => {
                 self.doc.with_untracked(|doc| !doc.content.is_local())
@@ -1925,16 +2938,45 @@ fn check_condition(&self, condition: Condition) -> bool {
                     self.common.find.visual.get_untracked()
                 }
             }
+            Condition::HasSelection => {
+                self.cursor.with_untracked(|c| match &c.mode {
+                    CursorMode::Normal(_) => false,
+                    CursorMode::Visual { .. } => true,
+                    CursorMode::Insert(selection) => !selection.is_caret(),
+                })
+            }
+            Condition::ModalEditing => self.common.config.get_untracked().core.modal,
+            Condition::PopupVisible => !self.common.popups.visible(false).is_empty(),
             _ => false,
         }
     }
 
+    fn check_context(&self, key: ContextKey, value: &str) -> bool {
+        match key {
+            ContextKey::LanguageId => self.doc.with_untracked(|doc| {
+                let language = doc
+                    .syntax()
+                    .map(|syntax| syntax.language)
+                    .unwrap_or_default();
+                language.as_ref().eq_ignore_ascii_case(value)
+            }),
+            ContextKey::Mode => mode_matches(self.get_mode(), value),
+            ContextKey::PanelVisible => false,
+        }
+    }
+
     fn run_command(
         &self,
         command: &crate::command::LapceCommand,
         count: Option<usize>,
         mods: floem::glazier::Modifiers,
     ) -> crate::command::CommandExecuted {
+        if !matches!(
+            command.kind,
+            CommandKind::Focus(FocusCommand::ShowHover | FocusCommand::ShowDiff)
+        ) {
+            self.common.hover.hide();
+        }
         if self.common.find.visual.get_untracked() && self.find_focus.get_untracked()
         {
             match &command.kind {
@@ -1996,6 +3038,7 @@ fn expect_char(&self) -> bool {
     }
 
     fn receive_char(&self, c: &str) {
+        self.common.hover.hide();
         if self.common.find.visual.get_untracked() && self.find_focus.get_untracked()
         {
             // find/relace editor receive char
@@ -2010,9 +3053,16 @@ fn receive_char(&self, c: &str) {
             }
         } else {
             // normal editor receive char
-            if self.get_mode() == Mode::Insert {
-                let mut cursor = self.cursor.get_untracked();
+            if self.get_mode() == Mode::Insert && !self.is_read_only() {
                 let config = self.common.config.get_untracked();
+                if config.editor.completion_commit_characters
+                    && self.common.completion.with_untracked(|completion| {
+                        completion.is_commit_character(c)
+                    })
+                {
+                    self.commit_completion();
+                }
+                let mut cursor = self.cursor.get_untracked();
                 let deltas = self
                     .doc
                     .try_update(|doc| doc.do_insert(&mut cursor, c, &config))
@@ -2028,6 +3078,14 @@ fn receive_char(&self, c: &str) {
                     self.cancel_completion();
                 }
                 self.apply_deltas(&deltas);
+                // The signature is asked for once the arguments start, and then
+                // follows the typing until the server has none for the cursor
+                if c == "("
+                    || c == ","
+                    || self.common.signature.active.get_untracked()
+                {
+                    self.update_signature();
+                }
             } else if let Some(direction) = self.inline_find.get_untracked() {
                 self.inline_find(direction.clone(), c);
                 self.last_inline_find.set(Some((direction, c.to_string())));
@@ -2035,6 +3093,54 @@ fn receive_char(&self, c: &str) {
             }
         }
     }
+
+    fn for_each_selection(&self, f: &mut dyn FnMut()) {
+        let regions = self.cursor.with_untracked(|cursor| match &cursor.mode {
+            CursorMode::Insert(selection) => selection.regions().to_vec(),
+            _ => Vec::new(),
+        });
+        if regions.len() <= 1 {
+            f();
+            return;
+        }
+
+        // Go from the last selection to the first, so that the edits don't move
+        // the selections which are still to come
+        let mut offsets: Vec<usize> = Vec::new();
+        for region in regions.iter().rev() {
+            let len = self.doc.with_untracked(|doc| doc.buffer().len());
+            self.cursor.update(|cursor| {
+                cursor.set_insert(Selection::region(region.start, region.end))
+            });
+            f();
+            let new_len = self.doc.with_untracked(|doc| doc.buffer().len());
+            // The edits are before the offsets of the selections which are done
+            for offset in offsets.iter_mut() {
+                *offset = (*offset + new_len).saturating_sub(len);
+            }
+            offsets.push(self.cursor.with_untracked(|cursor| cursor.offset()));
+        }
+
+        let mut selection = Selection::new();
+        for offset in offsets {
+            selection.add_region(lapce_core::selection::SelRegion::caret(offset));
+        }
+        self.cursor.update(|cursor| cursor.set_insert(selection));
+    }
+}
+
+/// Checks if the command edits the text, or switches to a mode for editing it,
+/// which a read-only editor doesn't do
+fn changes_buffer(cmd: &EditCommand) -> bool {
+    !matches!(
+        cmd,
+        EditCommand::ClipboardCopy
+            | EditCommand::Yank
+            | EditCommand::NormalMode
+            | EditCommand::ToggleVisualMode
+            | EditCommand::ToggleLinewiseVisualMode
+            | EditCommand::ToggleBlockwiseVisualMode
+    )
 }
 
 /// Checks if completion should be triggered if the received command
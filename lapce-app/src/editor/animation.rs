@@ -0,0 +1,489 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use floem::{
+    ext_event::create_signal_from_channel,
+    peniko::kurbo::Vec2,
+    reactive::{
+        create_effect, create_rw_signal, RwSignal, Scope, SignalGet,
+        SignalGetUntracked, SignalSet, SignalWithUntracked,
+    },
+};
+use strum_macros::{AsRefStr, EnumIter, EnumString};
+
+/// How often the animations are advanced
+const FRAME: Duration = Duration::from_millis(16);
+/// How long the caret takes to move to where the cursor is
+const CARET_DURATION: Duration = Duration::from_millis(80);
+/// How long after the last touchpad scroll the fingers are taken to be lifted
+const GESTURE_END: Duration = Duration::from_millis(60);
+/// How far a touchpad scroll keeps going once the fingers are lifted, as the
+/// time it would take at the speed it was going
+const KINETIC_TIME: f64 = 0.325;
+/// The slowest touchpad scroll (in px/s) which keeps going
+const KINETIC_MIN_SPEED: f64 = 200.0;
+/// Mouse wheels scroll by at least this much (in px) a notch
+const WHEEL_NOTCH: f64 = 20.0;
+/// How far a touchpad pinch goes for each zoom step
+const PINCH_STEP: f64 = 20.0;
+/// Whether the system keeps a touchpad scroll going by itself once the fingers
+/// are lifted, with the scrolls of the momentum phase, which aren't flung again
+const SYSTEM_MOMENTUM: bool = cfg!(target_os = "macos");
+
+/// How an animated value gets from where it starts to where it ends.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, AsRefStr, EnumIter, EnumString,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Easing {
+    Linear,
+    #[default]
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// The easing named in the settings, which falls back to `ease-out`.
+    pub fn from_name(name: &str) -> Self {
+        name.parse().unwrap_or_default()
+    }
+
+    /// How much of the way is done at `t`, which goes from 0.0 to 1.0.
+    pub fn progress(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// What a scroll comes from, which tells how it's done and which sensitivity it's
+/// scaled by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollDevice {
+    /// A mouse wheel, which scrolls in notches
+    Wheel,
+    /// A touchpad, or anything else which scrolls by the pixel
+    Touchpad,
+}
+
+impl ScrollDevice {
+    /// Guess the device from the `delta` of a scroll, as the pointer events
+    /// don't say. Wheels scroll one axis by whole notches, while touchpads give
+    /// small or fractional deltas, often on both axes.
+    pub fn of(delta: Vec2) -> Self {
+        let precise = (delta.x != 0.0 && delta.y != 0.0)
+            || delta.x.fract() != 0.0
+            || delta.y.fract() != 0.0
+            || delta.x.abs().max(delta.y.abs()) < WHEEL_NOTCH;
+        if precise {
+            ScrollDevice::Touchpad
+        } else {
+            ScrollDevice::Wheel
+        }
+    }
+}
+
+/// A touchpad scroll in progress, whose speed is kept to go on with once the
+/// fingers are lifted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Gesture {
+    last: Instant,
+    /// In px/s, smoothed over the last few scrolls
+    velocity: Vec2,
+}
+
+impl Gesture {
+    fn new(delta: Vec2, now: Instant) -> Self {
+        Self {
+            last: now,
+            velocity: delta / FRAME.as_secs_f64(),
+        }
+    }
+
+    fn add(&mut self, delta: Vec2, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last)
+            .as_secs_f64()
+            .max(0.001);
+        self.velocity = self.velocity * 0.4 + delta / elapsed * 0.6;
+        self.last = now;
+    }
+
+    /// The scroll which goes on from the gesture, and how long it takes, unless
+    /// it was too slow for that.
+    fn fling(&self) -> Option<(Vec2, Duration)> {
+        if self.velocity.hypot() < KINETIC_MIN_SPEED {
+            return None;
+        }
+        // An ease-out starts at three times its average speed
+        Some((
+            self.velocity * KINETIC_TIME,
+            Duration::from_secs_f64(KINETIC_TIME * 3.0),
+        ))
+    }
+}
+
+/// The zoom steps which a touchpad pinch of `delta` makes after it went `pinch`
+/// since the last one, and how far it has gone since the last one then.
+fn pinch_steps(pinch: f64, delta: f64) -> (i32, f64) {
+    let pinch = pinch - delta;
+    let steps = (pinch / PINCH_STEP).trunc();
+    (steps as i32, pinch - steps * PINCH_STEP)
+}
+
+/// A value which moves from `from` to `to` over `duration`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    from: Vec2,
+    to: Vec2,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Animation {
+    pub fn new(from: Vec2, to: Vec2, duration: Duration, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            start: Instant::now(),
+            duration,
+            easing,
+        }
+    }
+
+    /// The value at the given time, and whether the animation is done by then
+    pub fn value_at(&self, now: Instant) -> (Vec2, bool) {
+        let elapsed = now.saturating_duration_since(self.start);
+        if self.duration.is_zero() || elapsed >= self.duration {
+            return (self.to, true);
+        }
+        let t = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        let progress = self.easing.progress(t);
+        (self.from + (self.to - self.from) * progress, false)
+    }
+}
+
+/// The smooth scrolling and the caret animation of an editor.
+///
+/// The scrolling is done in steps through `scroll_delta`, so that the scroll view
+/// keeps being the one which knows the scroll offset, and a scroll which comes
+/// in while one is still going adds to what's left of it.
+#[derive(Clone)]
+pub struct EditorAnimation {
+    scroll_delta: RwSignal<Vec2>,
+    /// The scroll which is in progress, and how much of it has been done
+    scroll: RwSignal<Option<(Animation, Vec2)>>,
+    /// How far away from the cursor the caret is drawn, which goes down to zero
+    caret: RwSignal<Option<Animation>>,
+    /// The touchpad scroll in progress, which is flung once it ends
+    gesture: RwSignal<Option<Gesture>>,
+    /// How far the pinch has gone since its last zoom step
+    pinch: RwSignal<f64>,
+    /// Bumped on every frame of the caret animation, so that the editor can be
+    /// painted again
+    pub caret_frame: RwSignal<u64>,
+    running: Arc<AtomicBool>,
+    tick: crossbeam_channel::Sender<()>,
+}
+
+impl EditorAnimation {
+    pub fn new(cx: Scope, scroll_delta: RwSignal<Vec2>) -> Self {
+        let (tick, rx) = crossbeam_channel::unbounded();
+        let animation = Self {
+            scroll_delta,
+            scroll: create_rw_signal(cx, None),
+            caret: create_rw_signal(cx, None),
+            gesture: create_rw_signal(cx, None),
+            pinch: create_rw_signal(cx, 0.0),
+            caret_frame: create_rw_signal(cx, 0),
+            running: Arc::new(AtomicBool::new(false)),
+            tick,
+        };
+
+        let frame = create_signal_from_channel(cx, rx);
+        {
+            let animation = animation.clone();
+            create_effect(cx, move |_| {
+                if frame.get().is_some() {
+                    animation.advance();
+                }
+            });
+        }
+
+        animation
+    }
+
+    /// Scroll by `delta`, either right away or over `duration`.
+    pub fn scroll_by(&self, delta: Vec2, duration: Duration, easing: Easing) {
+        if duration.is_zero() {
+            self.scroll_delta.set(delta);
+            return;
+        }
+        let left = self
+            .scroll
+            .with_untracked(|scroll| {
+                scroll
+                    .as_ref()
+                    .map(|(animation, done)| animation.to - *done)
+            })
+            .unwrap_or(Vec2::ZERO);
+        self.scroll.set(Some((
+            Animation::new(Vec2::ZERO, left + delta, duration, easing),
+            Vec2::ZERO,
+        )));
+        self.start();
+    }
+
+    /// Stop the scroll in progress, e.g. when the editor is scrolled to a place.
+    pub fn stop_scroll(&self) {
+        if self.scroll.with_untracked(|scroll| scroll.is_some()) {
+            self.scroll.set(None);
+        }
+        if self.gesture.with_untracked(|gesture| gesture.is_some()) {
+            self.gesture.set(None);
+        }
+    }
+
+    /// Scroll by a touchpad `delta` right away, as the fingers make it smooth
+    /// already, and keep the scroll going once they're lifted if `kinetic` and
+    /// the system doesn't do that.
+    pub fn touchpad_scroll(&self, delta: Vec2, kinetic: bool) {
+        let now = Instant::now();
+        let gesture = self.gesture.get_untracked().filter(|gesture| {
+            now.saturating_duration_since(gesture.last) < GESTURE_END
+        });
+        if self.scroll.with_untracked(|scroll| scroll.is_some()) {
+            self.scroll.set(None);
+        }
+        self.scroll_delta.set(delta);
+        if !kinetic || SYSTEM_MOMENTUM {
+            return;
+        }
+        let gesture = match gesture {
+            Some(mut gesture) => {
+                gesture.add(delta, now);
+                gesture
+            }
+            None => Gesture::new(delta, now),
+        };
+        self.gesture.set(Some(gesture));
+        self.start();
+    }
+
+    /// Add a pinch of `delta`, and give how many zoom steps it has made, which
+    /// are positive for zooming in. Pinches come as scrolls with Ctrl held, up
+    /// for zooming in, and so does Ctrl with the mouse wheel, whose every notch
+    /// is a step.
+    pub fn pinch(&self, delta: f64, device: ScrollDevice) -> i32 {
+        if device == ScrollDevice::Wheel {
+            self.pinch.set(0.0);
+            return if delta == 0.0 {
+                0
+            } else {
+                -delta.signum() as i32
+            };
+        }
+        let (steps, pinch) = pinch_steps(self.pinch.get_untracked(), delta);
+        self.pinch.set(pinch);
+        steps
+    }
+
+    /// Move the caret over from where it was drawn, which is `offset` away from
+    /// where the cursor is now.
+    pub fn move_caret(&self, offset: Vec2) {
+        let from = self.caret_offset() + offset;
+        self.caret.set(Some(Animation::new(
+            from,
+            Vec2::ZERO,
+            CARET_DURATION,
+            Easing::EaseOut,
+        )));
+        self.start();
+    }
+
+    /// How far away from the cursor the caret is drawn at the moment
+    pub fn caret_offset(&self) -> Vec2 {
+        self.caret
+            .with_untracked(|caret| {
+                caret
+                    .as_ref()
+                    .map(|animation| animation.value_at(Instant::now()).0)
+            })
+            .unwrap_or(Vec2::ZERO)
+    }
+
+    /// Start sending frames, unless that's being done already.
+    fn start(&self) {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let running = self.running.clone();
+        let tick = self.tick.clone();
+        std::thread::spawn(move || {
+            while running.load(Ordering::SeqCst) {
+                std::thread::sleep(FRAME);
+                if tick.send(()).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    fn advance(&self) {
+        let now = Instant::now();
+
+        let gesture = self.gesture.get_untracked();
+        if let Some(gesture) = gesture {
+            if now.saturating_duration_since(gesture.last) >= GESTURE_END {
+                self.gesture.set(None);
+                if let Some((distance, duration)) = gesture.fling() {
+                    self.scroll.set(Some((
+                        Animation::new(
+                            Vec2::ZERO,
+                            distance,
+                            duration,
+                            Easing::EaseOut,
+                        ),
+                        Vec2::ZERO,
+                    )));
+                }
+            }
+        }
+
+        let scroll = self.scroll.get_untracked();
+        if let Some((animation, done)) = scroll {
+            let (value, finished) = animation.value_at(now);
+            let step = value - done;
+            if step != Vec2::ZERO {
+                self.scroll_delta.set(step);
+            }
+            self.scroll.set((!finished).then_some((animation, value)));
+        }
+
+        let caret = self.caret.get_untracked();
+        if let Some(animation) = caret {
+            let (_, finished) = animation.value_at(now);
+            if finished {
+                self.caret.set(None);
+            }
+            self.caret_frame.set(self.caret_frame.get_untracked() + 1);
+        }
+
+        if self.scroll.with_untracked(|scroll| scroll.is_none())
+            && self.gesture.with_untracked(|gesture| gesture.is_none())
+            && self.caret.with_untracked(|caret| caret.is_none())
+        {
+            self.running.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use floem::peniko::kurbo::Vec2;
+
+    use super::{
+        pinch_steps, Animation, Easing, Gesture, ScrollDevice, FRAME, GESTURE_END,
+        KINETIC_TIME, PINCH_STEP,
+    };
+
+    #[test]
+    fn test_animation() {
+        let animation = Animation::new(
+            Vec2::ZERO,
+            Vec2::new(0.0, 100.0),
+            Duration::from_millis(100),
+            Easing::Linear,
+        );
+        let (value, finished) =
+            animation.value_at(animation.start + Duration::from_millis(50));
+        assert!((value.y - 50.0).abs() < 1e-9);
+        assert!(!finished);
+        let (value, finished) =
+            animation.value_at(animation.start + Duration::from_millis(150));
+        assert_eq!(value, Vec2::new(0.0, 100.0));
+        assert!(finished);
+
+        assert_eq!(Easing::from_name("ease-in-out"), Easing::EaseInOut);
+        assert_eq!(Easing::from_name("bounce"), Easing::EaseOut);
+        assert_eq!(Easing::EaseOut.progress(1.0), 1.0);
+        assert_eq!(Easing::EaseInOut.progress(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_scroll_device() {
+        assert_eq!(ScrollDevice::of(Vec2::new(0.0, 53.0)), ScrollDevice::Wheel);
+        assert_eq!(ScrollDevice::of(Vec2::new(-40.0, 0.0)), ScrollDevice::Wheel);
+        assert_eq!(
+            ScrollDevice::of(Vec2::new(0.0, 3.0)),
+            ScrollDevice::Touchpad
+        );
+        assert_eq!(
+            ScrollDevice::of(Vec2::new(0.0, 24.5)),
+            ScrollDevice::Touchpad
+        );
+        assert_eq!(
+            ScrollDevice::of(Vec2::new(2.0, 30.0)),
+            ScrollDevice::Touchpad
+        );
+    }
+
+    #[test]
+    fn test_gesture_fling() {
+        let gesture = Gesture::new(Vec2::new(0.0, 1.0), std::time::Instant::now());
+        assert_eq!(gesture.fling(), None);
+
+        let start = std::time::Instant::now();
+        let mut gesture = Gesture::new(Vec2::new(0.0, 20.0), start);
+        gesture.add(Vec2::new(0.0, 20.0), start + FRAME);
+        let (distance, duration) = gesture.fling().unwrap();
+        assert!(distance.y > 300.0);
+        assert_eq!(distance.x, 0.0);
+        assert!(duration > Duration::from_millis(500));
+
+        // The speed is of the last scrolls, so a gesture which slows down
+        // before the fingers are lifted doesn't go on
+        let mut gesture = Gesture::new(Vec2::new(0.0, 40.0), start);
+        for i in 1..10 {
+            gesture.add(Vec2::new(0.0, 0.5), start + FRAME * i);
+        }
+        assert_eq!(gesture.fling(), None);
+
+        // A sideways fling goes as far as the speed for the kinetic time
+        let mut gesture = Gesture::new(Vec2::new(-30.0, 0.0), start);
+        gesture.add(Vec2::new(-30.0, 0.0), start + GESTURE_END / 2);
+        let (distance, _) = gesture.fling().unwrap();
+        assert!((distance.x - gesture.velocity.x * KINETIC_TIME).abs() < 1e-9);
+        assert!(distance.x < 0.0);
+        assert_eq!(distance.y, 0.0);
+    }
+
+    #[test]
+    fn test_pinch_steps() {
+        // Pinching out scrolls up, which zooms in once it goes far enough
+        assert_eq!(pinch_steps(0.0, -PINCH_STEP / 2.0), (0, PINCH_STEP / 2.0));
+        assert_eq!(pinch_steps(PINCH_STEP / 2.0, -PINCH_STEP / 2.0), (1, 0.0));
+        // What's left over of a long pinch counts towards the next step
+        assert_eq!(pinch_steps(0.0, -PINCH_STEP * 2.5), (2, PINCH_STEP / 2.0));
+        assert_eq!(pinch_steps(0.0, PINCH_STEP * 3.0), (-3, 0.0));
+        // Going back undoes what the pinch went without a step
+        assert_eq!(pinch_steps(PINCH_STEP / 2.0, PINCH_STEP / 2.0), (0, 0.0));
+    }
+}
@@ -1,8 +1,8 @@
 //! Movement logic for the editor.
 
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range};
 
-use floem::reactive::{SignalGetUntracked, SignalUpdate};
+use floem::reactive::{SignalGetUntracked, SignalUpdate, SignalWithUntracked};
 use lapce_core::{
     buffer::rope_text::RopeText,
     command::MultiSelectionCommand,
@@ -101,7 +101,9 @@ pub fn move_offset(
                 offset,
                 mode,
                 count,
-                config.editor.atomic_soft_tab_width(),
+                config
+                    .editor_config(view.language())
+                    .atomic_soft_tab_width(),
             );
 
             (new_offset, None)
@@ -112,7 +114,9 @@ pub fn move_offset(
                 offset,
                 mode,
                 count,
-                config.editor.atomic_soft_tab_width(),
+                config
+                    .editor_config(view.language())
+                    .atomic_soft_tab_width(),
             );
 
             (new_offset, None)
@@ -700,9 +704,81 @@ pub fn do_multi_selection(
             let new_selection = Selection::region(0, rope_text.len());
             cursor.set_insert(new_selection);
         }
+        ExpandSelection => {
+            if let CursorMode::Insert(selection) = cursor.mode.clone() {
+                let new_selection = view.doc.with_untracked(|doc| {
+                    let syntax =
+                        doc.syntax().filter(|syntax| syntax.rev == doc.rev())?;
+                    Some(map_regions(&selection, |range| syntax.expand_range(range)))
+                });
+                if let Some(new_selection) =
+                    new_selection.filter(|new_selection| *new_selection != selection)
+                {
+                    // The history only goes back through selections which were
+                    // expanded one from another
+                    if !cursor
+                        .syntax_selections
+                        .last()
+                        .map(|last| selection_contains(&selection, last))
+                        .unwrap_or(true)
+                    {
+                        cursor.syntax_selections.clear();
+                    }
+                    cursor.syntax_selections.push(selection);
+                    cursor.set_insert(new_selection);
+                }
+            }
+        }
+        ShrinkSelection => {
+            if let CursorMode::Insert(selection) = cursor.mode.clone() {
+                let previous = cursor
+                    .syntax_selections
+                    .pop()
+                    .filter(|previous| selection_contains(&selection, previous));
+                let new_selection = previous.or_else(|| {
+                    cursor.syntax_selections.clear();
+                    view.doc.with_untracked(|doc| {
+                        let syntax =
+                            doc.syntax().filter(|syntax| syntax.rev == doc.rev())?;
+                        Some(map_regions(&selection, |range| {
+                            syntax.shrink_range(range)
+                        }))
+                    })
+                });
+                if let Some(new_selection) = new_selection {
+                    cursor.set_insert(new_selection);
+                }
+            }
+        }
     }
 }
 
+/// The selection with each of its regions replaced by the range `f` gives for
+/// it, or kept as it is when that's `None`.
+fn map_regions(
+    selection: &Selection,
+    f: impl Fn(Range<usize>) -> Option<Range<usize>>,
+) -> Selection {
+    let mut new_selection = Selection::new();
+    for region in selection.regions() {
+        let range = region.min()..region.max();
+        let range = f(range.clone()).unwrap_or(range);
+        new_selection.add_region(SelRegion::new(range.start, range.end, None));
+    }
+    new_selection
+}
+
+/// Whether each region of `inner` is within a region of `outer`, which regions
+/// that were expanded into the same one are.
+fn selection_contains(outer: &Selection, inner: &Selection) -> bool {
+    inner.regions().iter().all(|inner| {
+        outer
+            .regions()
+            .iter()
+            .any(|outer| outer.min() <= inner.min() && inner.max() <= outer.max())
+    })
+}
+
 pub fn do_motion_mode(
     doc: &mut Document,
     cursor: &mut Cursor,
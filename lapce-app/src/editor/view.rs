@@ -1,14 +1,21 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::Range,
+    rc::Rc,
+    sync::Arc,
+};
 
 use floem::{
     context::PaintCx,
-    cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout},
+    cosmic_text::{Attrs, AttrsList, TextLayout},
     event::{Event, EventListener},
     ext_event::create_ext_action,
     glazier::{Modifiers, PointerType},
     id::Id,
+    menu::{Menu, MenuItem},
     peniko::{
-        kurbo::{BezPath, Line, Point, Rect, Size},
+        kurbo::{BezPath, Line, Point, Rect, Size, Vec2},
         Color,
     },
     reactive::{
@@ -32,26 +39,35 @@
     },
     char_buffer::CharBuffer,
     cursor::{ColPosition, CursorMode},
+    language::LapceLanguage,
     mode::{Mode, VisualMode},
-    selection::Selection,
+    selection::{SelRegion, Selection},
     soft_tab::{snap_to_soft_tab_line_col, SnapDirection},
     word::WordCursor,
 };
-use lapce_rpc::style::LineStyle;
+use lapce_rpc::{
+    coverage::LineCoverage,
+    http::{is_http_file, parse_http_file},
+    style::LineStyle,
+};
 use lapce_xi_rope::{find::CaseMatching, Rope};
 use lsp_types::DiagnosticSeverity;
 
 use super::EditorData;
 use crate::{
     app::clickable_icon,
+    code_action::CodeActionGroup,
+    collab::participant_color,
     command::InternalCommand,
     config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    debug::{BreakpointEdit, BreakpointField, RunDebugMode},
     doc::{
         phantom_text::PhantomTextLine, DocContent, Document, EditorDiagnostic,
         TextCacheListener,
     },
     find::{Find, FindResult},
     main_split::MainSplitData,
+    test_explorer::{runnable_test_id, TestStatus},
     text_input::text_input,
     workspace::LapceWorkspace,
 };
@@ -85,6 +101,75 @@ pub fn editor_view(
         id.request_layout();
     });
 
+    create_effect(cx.scope, move |last_caret: Option<Option<Point>>| {
+        let (view, cursor, animation, config) = editor.with(|editor| {
+            (
+                editor.view.clone(),
+                editor.cursor,
+                editor.animation.clone(),
+                editor.common.config,
+            )
+        });
+        let offset = cursor.with(|cursor| cursor.offset());
+        let (smooth_caret, line_height) = config.with(|config| {
+            (
+                config.editor.smooth_caret,
+                config.editor.line_height() as f64,
+            )
+        });
+        let caret = match cursor_caret(&view, offset, false) {
+            CursorRender::Caret { x, line, .. } => {
+                Point::new(x, line as f64 * line_height)
+            }
+            _ => return None,
+        };
+        if let Some(Some(last_caret)) = last_caret {
+            if smooth_caret && last_caret != caret {
+                animation.move_caret(last_caret - caret);
+            }
+        }
+        Some(caret)
+    });
+
+    create_effect(cx.scope, move |_| {
+        editor.with(|editor| editor.animation.caret_frame.get());
+        id.request_paint();
+    });
+
+    create_effect(cx.scope, move |_| {
+        editor.with(|editor| {
+            editor.common.collab.cursors.with(|_| ());
+            editor.common.collab.pings.with(|_| ());
+        });
+        id.request_paint();
+    });
+
+    create_effect(cx.scope, move |_| {
+        editor.with(|editor| editor.common.debug_line.with(|_| ()));
+        id.request_paint();
+    });
+
+    // Whatever is selected becomes the primary selection, which stays when the
+    // selection goes away until something else is selected
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    create_effect(cx.scope, move |_| {
+        let (doc, cursor, clipboard_history) = editor.with(|editor| {
+            (editor.doc, editor.cursor, editor.common.clipboard_history)
+        });
+        let content = cursor.with(|cursor| {
+            let has_selection = match &cursor.mode {
+                CursorMode::Visual { .. } => true,
+                CursorMode::Insert(selection) => !selection.is_caret(),
+                CursorMode::Normal(_) => false,
+            };
+            has_selection
+                .then(|| doc.with_untracked(|doc| cursor.yank(doc.buffer()).content))
+        });
+        if let Some(content) = content.filter(|content| !content.is_empty()) {
+            clipboard_history.set_primary_selection(Some(content));
+        }
+    });
+
     create_effect(cx.scope, move |last_rev| {
         let doc = editor.with(|editor| editor.doc);
         let rev = doc.with(|doc| doc.rev());
@@ -198,6 +283,18 @@ fn paint_cursor(
             }
         });
 
+        // Only a single caret is animated
+        let carets = renders
+            .iter()
+            .filter(|render| matches!(render, CursorRender::Caret { .. }))
+            .count();
+        let caret_offset = if carets == 1 {
+            self.editor
+                .with_untracked(|editor| editor.animation.caret_offset())
+        } else {
+            Vec2::ZERO
+        };
+
         for render in renders {
             match render {
                 CursorRender::CurrentLine { line } => {
@@ -225,7 +322,10 @@ fn paint_cursor(
                     cx.fill(
                         &Rect::ZERO
                             .with_size(Size::new(width, line_height))
-                            .with_origin(Point::new(x, line_height * line as f64)),
+                            .with_origin(
+                                Point::new(x, line_height * line as f64)
+                                    + caret_offset,
+                            ),
                         config.get_color(LapceColor::EDITOR_CARET),
                     );
                 }
@@ -233,6 +333,192 @@ fn paint_cursor(
         }
     }
 
+    /// Paint the line of the frame which the debug session shows, when it's in
+    /// the file.
+    fn paint_debug_line(
+        &self,
+        cx: &mut PaintCx,
+        min_line: usize,
+        max_line: usize,
+        viewport: Rect,
+    ) {
+        let (doc, debug_line, config) = self.editor.with_untracked(|editor| {
+            (editor.doc, editor.common.debug_line, editor.common.config)
+        });
+        let Some((path, line)) = debug_line.get_untracked() else {
+            return;
+        };
+        if line < min_line || line > max_line {
+            return;
+        }
+        let in_file = doc.with_untracked(|doc| doc.content.path() == Some(&path));
+        if !in_file {
+            return;
+        }
+
+        let config = config.get_untracked();
+        let line_height = config.editor.line_height() as f64;
+        cx.fill(
+            &Rect::ZERO
+                .with_size(Size::new(viewport.width(), line_height))
+                .with_origin(Point::new(viewport.x0, line_height * line as f64)),
+            config.get_color(LapceColor::DEBUG_CURRENT_LINE),
+        );
+    }
+
+    /// Paint the selections and carets of the other participants of the
+    /// collaboration session who are in the file, in their colors, along with
+    /// the ranges which are pinged.
+    fn paint_collab_cursors(
+        &self,
+        cx: &mut PaintCx,
+        min_line: usize,
+        max_line: usize,
+    ) {
+        let (view, doc, collab, config) = self.editor.with_untracked(|editor| {
+            (
+                editor.view.clone(),
+                editor.doc,
+                editor.common.collab.clone(),
+                editor.common.config,
+            )
+        });
+        let Some(path) = doc.with_untracked(|doc| doc.content.path().cloned())
+        else {
+            return;
+        };
+        let cursors = collab.remote_cursors(&path);
+        let pings = collab.ping_ranges(&path);
+        if cursors.is_empty() && pings.is_empty() {
+            return;
+        }
+
+        let line_height = config.get_untracked().editor.line_height() as f64;
+        let len = doc.with_untracked(|doc| doc.buffer().len());
+        let fill_ranges = |cx: &mut PaintCx,
+                           ranges: &[Range<usize>],
+                           color: Color| {
+            let mut selection = Selection::new();
+            for range in ranges {
+                selection.add_region(SelRegion::new(
+                    range.start.min(len),
+                    range.end.min(len),
+                    None,
+                ));
+            }
+            for render in
+                insert_cursor(&view, &selection, min_line, max_line, 7.5, false)
+            {
+                if let CursorRender::Selection { x, width, line } = render {
+                    cx.fill(
+                        &Rect::ZERO
+                            .with_size(Size::new(width, line_height))
+                            .with_origin(Point::new(x, line_height * line as f64)),
+                        color,
+                    );
+                }
+            }
+        };
+
+        for (participant, range) in pings {
+            fill_ranges(
+                cx,
+                &[range],
+                participant_color(participant).with_alpha_factor(0.5),
+            );
+        }
+        for cursor in cursors {
+            let color = participant_color(cursor.participant);
+            fill_ranges(cx, &cursor.selection, color.with_alpha_factor(0.25));
+            if let CursorRender::Caret { x, width, line } =
+                cursor_caret(&view, cursor.offset.min(len), false)
+            {
+                if line < min_line || line > max_line {
+                    continue;
+                }
+                cx.fill(
+                    &Rect::ZERO
+                        .with_size(Size::new(width, line_height))
+                        .with_origin(Point::new(x, line_height * line as f64)),
+                    color,
+                );
+            }
+        }
+    }
+
+    /// Paint the names of the other participants above their carets.
+    fn paint_collab_names(
+        &self,
+        cx: &mut PaintCx,
+        min_line: usize,
+        max_line: usize,
+    ) {
+        let (view, doc, collab, config) = self.editor.with_untracked(|editor| {
+            (
+                editor.view.clone(),
+                editor.doc,
+                editor.common.collab.clone(),
+                editor.common.config,
+            )
+        });
+        let Some(path) = doc.with_untracked(|doc| doc.content.path().cloned())
+        else {
+            return;
+        };
+        let cursors = collab.remote_cursors(&path);
+        if cursors.is_empty() {
+            return;
+        }
+
+        let config = config.get_untracked();
+        let line_height = config.editor.line_height() as f64;
+        let family = config.ui.font_family();
+        let len = doc.with_untracked(|doc| doc.buffer().len());
+        for cursor in cursors {
+            let Some(name) = collab.session.with_untracked(|session| {
+                session
+                    .as_ref()?
+                    .participant(cursor.participant)
+                    .map(|p| p.name.clone())
+            }) else {
+                continue;
+            };
+            let CursorRender::Caret { x, line, .. } =
+                cursor_caret(&view, cursor.offset.min(len), false)
+            else {
+                continue;
+            };
+            if line < min_line || line > max_line {
+                continue;
+            }
+
+            let mut text_layout = TextLayout::new();
+            text_layout.set_text(
+                &name,
+                AttrsList::new(
+                    Attrs::new()
+                        .family(&family)
+                        .font_size(config.ui.font_size() as f32 - 2.0)
+                        .color(*config.get_color(LapceColor::EDITOR_BACKGROUND)),
+                ),
+            );
+            let size = text_layout.size();
+            // Above the line of the caret, unless it's the first line
+            let y = if line == 0 {
+                line_height
+            } else {
+                line_height * line as f64 - size.height
+            };
+            cx.fill(
+                &Rect::ZERO
+                    .with_size(Size::new(size.width + 6.0, size.height))
+                    .with_origin(Point::new(x, y)),
+                participant_color(cursor.participant),
+            );
+            cx.draw_text(&text_layout, Point::new(x + 3.0, y));
+        }
+    }
+
     fn paint_wave_line(
         &self,
         cx: &mut PaintCx,
@@ -315,8 +601,7 @@ fn paint_text(
         let font_size = config.editor.font_size();
 
         let indent_unit = view.indent_unit();
-        let family: Vec<FamilyOwned> =
-            FamilyOwned::parse_list(&config.editor.font_family).collect();
+        let family = config.editor_font_family(view.language());
         let attrs = Attrs::new()
             .family(&family)
             .font_size(config.editor.font_size() as f32);
@@ -346,7 +631,7 @@ fn paint_text(
                 viewport,
             );
 
-            if config.editor.show_indent_guide {
+            if config.editor_config(view.language()).show_indent_guide {
                 let mut x = 0.0;
                 while x + 1.0 < text_layout.indent {
                     cx.stroke(
@@ -365,6 +650,36 @@ fn paint_text(
         }
     }
 
+    /// Draw the rulers at the columns of the language, across the viewport.
+    fn paint_rulers(&self, cx: &mut PaintCx, viewport: Rect) {
+        let (view, config) = self
+            .editor
+            .with_untracked(|editor| (editor.view.clone(), editor.common.config));
+        let config = config.get_untracked();
+        let language = view.language();
+        let rulers = &config.editor_config(language).rulers;
+        if rulers.is_empty() {
+            return;
+        }
+
+        let family = config.editor_font_family(language);
+        let attrs = Attrs::new()
+            .family(&family)
+            .font_size(config.editor.font_size() as f32);
+        let mut text = TextLayout::new();
+        text.set_text("a", AttrsList::new(attrs));
+        let char_width = text.size().width;
+
+        for column in rulers {
+            let x = (*column as f64 * char_width).round() + 0.5;
+            cx.stroke(
+                &Line::new(Point::new(x, viewport.y0), Point::new(x, viewport.y1)),
+                config.get_color(LapceColor::EDITOR_RULER),
+                1.0,
+            );
+        }
+    }
+
     fn paint_find(&self, cx: &mut PaintCx, min_line: usize, max_line: usize) {
         let visual = self.editor.with_untracked(|e| e.common.find.visual);
         if !visual.get_untracked() {
@@ -548,8 +863,10 @@ fn paint_scroll_bar(
 
         let doc = self.editor.with_untracked(|e| e.doc);
         let total_len = doc.with_untracked(|doc| doc.buffer().last_line());
-        let changes = doc.with_untracked(|doc| doc.head_changes);
+        let (changes, unsaved) =
+            doc.with_untracked(|doc| (doc.head_changes, doc.compare_saved));
         let changes = changes.get_untracked();
+        let unsaved = unsaved.get_untracked();
         let total_height = viewport.height();
         let total_width = viewport.width();
         let line_height = config.editor.line_height();
@@ -559,7 +876,7 @@ fn paint_scroll_bar(
             (total_len * line_height) as f64
         };
 
-        let colors = changes_colors(changes, 0, total_len, &config);
+        let colors = changes_colors(changes, unsaved, 0, total_len, &config);
         for (y, height, _, color) in colors {
             let y = (y * line_height) as f64 / content_height * total_height;
             let height = ((height * line_height) as f64 / content_height
@@ -652,6 +969,18 @@ fn compute_layout(&mut self, cx: &mut floem::context::LayoutCx) -> Option<Rect>
         let viewport = cx.current_viewport().unwrap_or_default();
         if self.viewport.with_untracked(|v| v != &viewport) {
             self.viewport.set(viewport);
+
+            // The visible lines are highlighted first when the document is parsed
+            let (doc, config) =
+                self.editor.with_untracked(|e| (e.doc, e.common.config));
+            let line_height =
+                config.with_untracked(|config| config.editor.line_height()) as f64;
+            doc.with_untracked(|doc| {
+                doc.set_syntax_viewport(
+                    (viewport.y0 / line_height).floor() as usize,
+                    (viewport.y1 / line_height).ceil() as usize,
+                )
+            });
         }
         None
     }
@@ -676,10 +1005,15 @@ fn paint(&mut self, cx: &mut PaintCx) {
 
         let doc = self.editor.with_untracked(|e| e.doc);
         let is_local = doc.with_untracked(|doc| doc.content.is_local());
+        Document::shown(doc);
 
         self.paint_cursor(cx, min_line, max_line, is_local);
+        self.paint_debug_line(cx, min_line, max_line, viewport);
+        self.paint_collab_cursors(cx, min_line, max_line);
         self.paint_find(cx, min_line, max_line);
+        self.paint_rulers(cx, viewport);
         self.paint_text(cx, min_line, max_line, viewport);
+        self.paint_collab_names(cx, min_line, max_line);
         self.paint_sticky_headers(cx, min_line, viewport);
         self.paint_scroll_bar(cx, viewport, is_local, config);
     }
@@ -1077,13 +1411,19 @@ fn editor_gutter(
     is_active: impl Fn() -> bool + 'static + Copy,
     gutter_rect: RwSignal<Rect>,
 ) -> impl View {
-    let (cursor, viewport, scroll_delta, config) = editor.with(|editor| {
-        (
-            editor.cursor,
-            editor.viewport,
-            editor.scroll_delta,
-            editor.common.config,
-        )
+    let (cursor, viewport, config, test_results, coverage, breakpoints) = editor
+        .with(|editor| {
+            (
+                editor.cursor,
+                editor.viewport,
+                editor.common.config,
+                editor.common.test_results,
+                editor.common.coverage,
+                editor.common.breakpoints,
+            )
+        });
+    let (debug_can_jump, debugging) = editor.with_untracked(|editor| {
+        (editor.common.debug_can_jump, editor.common.debugging)
     });
 
     let padding_left = 10.0;
@@ -1111,6 +1451,188 @@ fn editor_gutter(
             None
         }
     });
+    // The lightbulb is lit up when there's a quick fix among the actions
+    let code_action_quick_fix = create_memo(cx.scope, move |_| {
+        code_action_line.get().is_some() && {
+            let doc = editor.with(|editor| editor.doc);
+            let offset = cursor.with(|cursor| cursor.offset());
+            doc.with(|doc| {
+                doc.code_actions.get(&offset).map_or(false, |c| {
+                    c.1.iter().any(|item| {
+                        CodeActionGroup::of(item) == CodeActionGroup::QuickFix
+                    })
+                })
+            })
+        }
+    });
+
+    // The first runnable of each line, which has a run button where there's no
+    // code action
+    let runnables = create_memo(cx.scope, move |_| {
+        let doc = editor.with(|editor| editor.doc);
+        doc.with(|doc| {
+            doc.runnables
+                .iter()
+                .rev()
+                .filter_map(|runnable| {
+                    Some((runnable.line()? as usize, runnable.clone()))
+                })
+                .collect::<im::HashMap<_, _>>()
+        })
+    });
+
+    // The result of the test of the runnable of a line in the test explorer,
+    // which colors its run button
+    let test_status = move |line: usize| {
+        let doc = editor.with(|editor| editor.doc);
+        let DocContent::File(path) = doc.with(|doc| doc.content.clone()) else {
+            return None;
+        };
+        let id = runnables.with(|r| {
+            r.get(&line)
+                .map(|runnable| runnable_test_id(&path, runnable))
+        })?;
+        test_results.with(|results| results.get(&id).cloned())
+    };
+
+    let file_coverage = create_memo(cx.scope, move |_| {
+        let doc = editor.with(|editor| editor.doc);
+        let DocContent::File(path) = doc.with(|doc| doc.content.clone()) else {
+            return None;
+        };
+        coverage.with(|coverage| coverage.files.get(&path).cloned())
+    });
+
+    // The request lines of a `.http` file, which have a send button
+    let http_request_lines = create_memo(cx.scope, move |_| {
+        let doc = editor.with(|editor| editor.doc);
+        doc.with(|doc| match &doc.content {
+            DocContent::File(path) if is_http_file(path) => {
+                parse_http_file(&doc.buffer().text().to_string())
+                    .requests
+                    .iter()
+                    .map(|request| request.line)
+                    .collect()
+            }
+            _ => HashSet::new(),
+        })
+    });
+
+    // The lines of the breakpoints, with whether the debug session which is
+    // running couldn't set them, which shows them as hollow
+    let breakpoint_lines = create_memo(cx.scope, move |_| {
+        let doc = editor.with(|editor| editor.doc);
+        let DocContent::File(path) = doc.with(|doc| doc.content.clone()) else {
+            return HashMap::new();
+        };
+        let debugging = debugging.get();
+        breakpoints.with(|breakpoints| {
+            breakpoints
+                .get(&path)
+                .map(|breakpoints| {
+                    breakpoints
+                        .iter()
+                        .map(|b| (b.shown_line(), debugging && !b.verified))
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    });
+
+    // The menu of the breakpoint of a line, which adds one when the line has
+    // none
+    let gutter_id = cx.id;
+    let show_breakpoint_menu = move |line: usize| {
+        let (doc, internal_command) = editor
+            .with_untracked(|editor| (editor.doc, editor.common.internal_command));
+        let (path, offset) = doc.with_untracked(|doc| {
+            (
+                doc.content.path().cloned(),
+                doc.buffer().offset_of_line(line),
+            )
+        });
+        let Some(path) = path else {
+            return;
+        };
+        let config = config.get_untracked();
+        let has_breakpoint =
+            breakpoint_lines.with_untracked(|b| b.contains_key(&line));
+        let can_jump = debug_can_jump.get_untracked();
+        let jump = {
+            let path = path.clone();
+            move || {
+                internal_command.send(InternalCommand::DapJumpToLine {
+                    path: path.clone(),
+                    line,
+                })
+            }
+        };
+        let toggle = {
+            let path = path.clone();
+            move || {
+                internal_command.send(InternalCommand::ToggleBreakpoint {
+                    path: path.clone(),
+                    line,
+                    offset,
+                })
+            }
+        };
+        let edit = move |field| {
+            let edit = BreakpointEdit {
+                path: path.clone(),
+                line,
+                offset,
+                field,
+            };
+            move || {
+                internal_command
+                    .send(InternalCommand::EditBreakpoint { edit: edit.clone() })
+            }
+        };
+        let mut menu = Menu::new("")
+            .entry(
+                MenuItem::new(config.tr(if has_breakpoint {
+                    "gutter-remove-breakpoint"
+                } else {
+                    "gutter-add-breakpoint"
+                }))
+                .action(toggle),
+            )
+            .separator()
+            .entry(
+                MenuItem::new(config.tr("gutter-breakpoint-condition"))
+                    .action(edit(BreakpointField::Condition)),
+            )
+            .entry(
+                MenuItem::new(config.tr("gutter-breakpoint-hit-count"))
+                    .action(edit(BreakpointField::HitCondition)),
+            )
+            .entry(
+                MenuItem::new(config.tr("gutter-breakpoint-log-message"))
+                    .action(edit(BreakpointField::LogMessage)),
+            );
+        if can_jump {
+            menu = menu
+                .separator()
+                .entry(MenuItem::new(config.tr("gutter-jump-to-line")).action(jump));
+        }
+        let editor = editor.get_untracked();
+        if let Some((change, _)) = editor.change_on_line(line) {
+            menu = menu.separator();
+            if !change.old.is_empty() {
+                let editor = editor.clone();
+                menu = menu.entry(
+                    MenuItem::new(config.tr("gutter-show-change"))
+                        .action(move || editor.show_change(line)),
+                );
+            }
+            menu = menu.entry(
+                MenuItem::new(config.tr("gutter-revert-change"))
+                    .action(move || editor.revert_change(line)),
+            );
+        }
+        gutter_id.show_context_menu(menu, Point::ZERO);
+    };
 
     let gutter_width = create_memo(cx.scope, move |_| gutter_rect.get().width());
 
@@ -1128,15 +1650,17 @@ fn editor_gutter(
     let head_changes = move || {
         let viewport = viewport.get();
         let doc = editor.with(|editor| editor.doc);
-        let changes = doc.with_untracked(|doc| doc.head_changes);
+        let (changes, unsaved) =
+            doc.with_untracked(|doc| (doc.head_changes, doc.compare_saved));
         let changes = changes.get();
+        let unsaved = unsaved.get();
         let config = config.get();
         let line_height = config.editor.line_height() as f64;
 
         let min_line = (viewport.y0 / line_height).floor() as usize;
         let max_line = (viewport.y1 / line_height).ceil() as usize;
 
-        changes_colors(changes, min_line, max_line, &config)
+        changes_colors(changes, unsaved, min_line, max_line, &config)
     };
 
     let gutter_view_fn = move |line: DocLine| {
@@ -1159,47 +1683,196 @@ fn editor_gutter(
 
         stack(move || {
             (
-                empty().style(move || Style::BASE.width_px(padding_left)),
                 container(|| {
-                    label(move || line_number.to_string()).style(move || {
+                    empty().style(move || {
                         let config = config.get();
-                        let (current_line, _) = current_line.get_untracked();
-                        Style::BASE.apply_if(current_line != line.line, move |s| {
-                            s.color(*config.get_color(LapceColor::EDITOR_DIM))
-                        })
+                        let color = file_coverage.with(|coverage| {
+                            let line_coverage =
+                                coverage.as_ref()?.lines.get(&(line.line as u32))?;
+                            Some(match line_coverage {
+                                LineCoverage::Covered => {
+                                    LapceColor::COVERAGE_COVERED
+                                }
+                                LineCoverage::Partial => {
+                                    LapceColor::COVERAGE_PARTIAL
+                                }
+                                LineCoverage::Uncovered => {
+                                    LapceColor::COVERAGE_UNCOVERED
+                                }
+                            })
+                        });
+                        Style::BASE
+                            .width_px(3.0)
+                            .height_pct(100.0)
+                            .apply_opt(color, |s, color| {
+                                s.background(*config.get_color(color))
+                            })
                     })
                 })
+                .style(move || Style::BASE.width_px(padding_left).height_pct(100.0)),
+                stack(|| {
+                    (
+                        svg(move || {
+                            let unverified = breakpoint_lines
+                                .with(|b| b.get(&line.line).copied())
+                                .unwrap_or(false);
+                            config.get().ui_svg(if unverified {
+                                LapceIcons::DEBUG_BREAKPOINT_UNVERIFIED
+                            } else {
+                                LapceIcons::DEBUG_BREAKPOINT
+                            })
+                        })
+                        .style(move || {
+                            let config = config.get();
+                            let size = config.ui.icon_size() as f32;
+                            Style::BASE
+                                .absolute()
+                                .size_px(size, size)
+                                .color(
+                                    *config.get_color(LapceColor::DEBUG_BREAKPOINT),
+                                )
+                                .apply_if(
+                                    !breakpoint_lines
+                                        .with(|b| b.contains_key(&line.line)),
+                                    |s| s.hide(),
+                                )
+                        }),
+                        label(move || line_number.to_string()).style(move || {
+                            let config = config.get();
+                            let (current_line, _) = current_line.get_untracked();
+                            Style::BASE.apply_if(
+                                current_line != line.line,
+                                move |s| {
+                                    s.color(
+                                        *config.get_color(LapceColor::EDITOR_DIM),
+                                    )
+                                },
+                            )
+                        }),
+                    )
+                })
                 .style(move || {
                     Style::BASE
                         .width_px(
                             gutter_width.get() as f32 - padding_left - padding_right,
                         )
+                        .items_center()
                         .justify_end()
                 }),
                 container(|| {
-                    container(|| {
-                        container(|| {
-                            svg(move || config.get().ui_svg(LapceIcons::LIGHTBULB))
+                    stack(|| {
+                        (
+                            container(|| {
+                                svg(move || {
+                                    config.get().ui_svg(LapceIcons::LIGHTBULB)
+                                })
+                                .style(move || {
+                                    let config = config.get();
+                                    let size = config.ui.icon_size() as f32;
+                                    let color = if code_action_quick_fix.get() {
+                                        LapceColor::LAPCE_WARN
+                                    } else {
+                                        LapceColor::LAPCE_ICON_ACTIVE
+                                    };
+                                    Style::BASE
+                                        .size_px(size, size)
+                                        .color(*config.get_color(color))
+                                })
+                            })
+                            .on_click(move |_| {
+                                editor.with_untracked(|editor| {
+                                    editor.show_code_actions(true, None);
+                                });
+                                true
+                            })
+                            .style(move || {
+                                Style::BASE.apply_if(
+                                    code_action_line.get() != Some(line.line),
+                                    |s| s.hide(),
+                                )
+                            }),
+                            container(|| {
+                                svg(move || config.get().ui_svg(LapceIcons::START))
+                                    .style(move || {
+                                        let config = config.get();
+                                        let size = config.ui.icon_size() as f32;
+                                        let color = match test_status(line.line) {
+                                            Some(TestStatus::Passed) => {
+                                                LapceColor::TEST_PASSED
+                                            }
+                                            Some(TestStatus::Failed(_)) => {
+                                                LapceColor::TEST_FAILED
+                                            }
+                                            _ => LapceColor::LAPCE_ICON_ACTIVE,
+                                        };
+                                        Style::BASE
+                                            .size_px(size, size)
+                                            .color(*config.get_color(color))
+                                    })
+                            })
+                            .on_click(move |_| {
+                                let runnable = runnables
+                                    .with_untracked(|r| r.get(&line.line).cloned());
+                                if let Some(runnable) = runnable {
+                                    editor.with_untracked(|editor| {
+                                        editor.common.internal_command.send(
+                                            InternalCommand::RunAndDebug {
+                                                mode: RunDebugMode::Run,
+                                                config: runnable.run_config(),
+                                            },
+                                        );
+                                    });
+                                }
+                                true
+                            })
+                            .style(move || {
+                                Style::BASE.apply_if(
+                                    code_action_line.get() == Some(line.line)
+                                        || !runnables
+                                            .with(|r| r.contains_key(&line.line)),
+                                    |s| s.hide(),
+                                )
+                            }),
+                            container(|| {
+                                svg(move || {
+                                    config.get().ui_svg(LapceIcons::HTTP_SEND)
+                                })
                                 .style(move || {
                                     let config = config.get();
                                     let size = config.ui.icon_size() as f32;
                                     Style::BASE.size_px(size, size).color(
-                                        *config.get_color(LapceColor::LAPCE_WARN),
+                                        *config.get_color(
+                                            LapceColor::LAPCE_ICON_ACTIVE,
+                                        ),
                                     )
                                 })
-                        })
-                        .on_click(move |_| {
-                            editor.with_untracked(|editor| {
-                                editor.show_code_actions(true);
-                            });
-                            true
-                        })
-                        .style(move || {
-                            Style::BASE.apply_if(
-                                code_action_line.get() != Some(line.line),
-                                |s| s.hide(),
-                            )
-                        })
+                            })
+                            .on_click(move |_| {
+                                editor.with_untracked(|editor| {
+                                    let DocContent::File(path) = editor
+                                        .doc
+                                        .with_untracked(|doc| doc.content.clone())
+                                    else {
+                                        return;
+                                    };
+                                    editor.common.internal_command.send(
+                                        InternalCommand::SendHttpRequest {
+                                            path,
+                                            line: line.line,
+                                        },
+                                    );
+                                });
+                                true
+                            })
+                            .style(move || {
+                                Style::BASE.apply_if(
+                                    code_action_line.get() == Some(line.line)
+                                        || !http_request_lines
+                                            .with(|l| l.contains(&line.line)),
+                                    |s| s.hide(),
+                                )
+                            }),
+                        )
                     })
                     .style(move || {
                         Style::BASE
@@ -1211,6 +1884,15 @@ fn editor_gutter(
                 .style(move || Style::BASE.justify_end().width_px(padding_right)),
             )
         })
+        .on_event(EventListener::PointerDown, move |event| {
+            if let Event::PointerDown(pointer_event) = event {
+                if pointer_event.button.is_right() {
+                    show_breakpoint_menu(line.line);
+                    return true;
+                }
+            }
+            false
+        })
         .style(move || {
             let config = config.get_untracked();
             let line_height = config.editor.line_height();
@@ -1304,7 +1986,9 @@ fn editor_gutter(
             .on_event(EventListener::PointerWheel, move |event| {
                 if let Event::PointerWheel(pointer_event) = event {
                     if let PointerType::Mouse(info) = &pointer_event.pointer_type {
-                        scroll_delta.set(info.wheel_delta);
+                        editor
+                            .get_untracked()
+                            .wheel_scroll(info.wheel_delta, pointer_event.modifiers);
                     }
                 }
                 true
@@ -1489,6 +2173,17 @@ fn editor_content(
     })
     .on_scroll_to(move || scroll_to.get().map(|s| s.to_point()))
     .on_scroll_delta(move || scroll_delta.get())
+    .on_event(EventListener::PointerWheel, move |event| {
+        if let Event::PointerWheel(pointer_event) = event {
+            if let PointerType::Mouse(info) = &pointer_event.pointer_type {
+                editor
+                    .get_untracked()
+                    .wheel_scroll(info.wheel_delta, pointer_event.modifiers);
+                return true;
+            }
+        }
+        false
+    })
     .on_ensure_visible(move || {
         let cursor = cursor.get();
         let offset = cursor.offset();
@@ -1997,6 +2692,12 @@ pub fn duplicate(&self) -> Self {
         self.clone()
     }
 
+    /// The language of the document, which can have its own font
+    pub fn language(&self) -> Option<LapceLanguage> {
+        self.doc
+            .with_untracked(|doc| doc.syntax().map(|syntax| syntax.language))
+    }
+
     fn line_phantom_text(&self, line: usize) -> PhantomTextLine {
         self.doc.with_untracked(|doc| doc.line_phantom_text(line))
     }
@@ -2048,8 +2749,7 @@ fn new_text_layout(&self, line: usize, _font_size: usize) -> TextLayoutLine {
         let line_content = phantom_text.combine_with_text(line_content);
 
         let color = config.get_color(LapceColor::EDITOR_FOREGROUND);
-        let family: Vec<FamilyOwned> =
-            FamilyOwned::parse_list(&config.editor.font_family).collect();
+        let family = config.editor_font_family(self.language());
         let attrs = Attrs::new()
             .color(*color)
             .family(&family)
@@ -2396,13 +3096,14 @@ pub fn line_col_of_point(
         let max_col = self.line_end_col(line, mode != Mode::Normal);
         let mut col = col.min(max_col);
 
-        if config.editor.atomic_soft_tabs && config.editor.tab_width > 1 {
+        let editor_config = config.editor_config(self.language());
+        if editor_config.atomic_soft_tabs && editor_config.tab_width > 1 {
             col = snap_to_soft_tab_line_col(
                 &self.text(),
                 line,
                 col,
                 SnapDirection::Nearest,
-                config.editor.tab_width,
+                editor_config.tab_width,
             );
         }
 
@@ -2494,12 +3195,28 @@ fn clear(&self) {
     }
 }
 
+/// The colors of the changes, which are the ones of the unsaved changes when
+/// they're against the content which was last saved.
 fn changes_colors(
     changes: im::Vector<DiffLines>,
+    unsaved: bool,
     min_line: usize,
     max_line: usize,
     config: &LapceConfig,
 ) -> Vec<(usize, usize, bool, Color)> {
+    let (added, removed, modified_color) = if unsaved {
+        (
+            LapceColor::SOURCE_CONTROL_UNSAVED_ADDED,
+            LapceColor::SOURCE_CONTROL_UNSAVED_REMOVED,
+            LapceColor::SOURCE_CONTROL_UNSAVED_MODIFIED,
+        )
+    } else {
+        (
+            LapceColor::SOURCE_CONTROL_ADDED,
+            LapceColor::SOURCE_CONTROL_REMOVED,
+            LapceColor::SOURCE_CONTROL_MODIFIED,
+        )
+    };
     let mut line = 0;
     let mut last_change = None;
     let mut colors = Vec::new();
@@ -2518,17 +3235,15 @@ fn changes_colors(
 
         let mut modified = false;
         let color = match change {
-            DiffLines::Left(_range) => {
-                Some(config.get_color(LapceColor::SOURCE_CONTROL_REMOVED))
-            }
+            DiffLines::Left(_range) => Some(config.get_color(removed)),
             DiffLines::Right(_range) => {
                 if let Some(DiffLines::Left(_)) = last_change.as_ref() {
                     modified = true;
                 }
                 if modified {
-                    Some(config.get_color(LapceColor::SOURCE_CONTROL_MODIFIED))
+                    Some(config.get_color(modified_color))
                 } else {
-                    Some(config.get_color(LapceColor::SOURCE_CONTROL_ADDED))
+                    Some(config.get_color(added))
                 }
             }
             _ => None,
@@ -1,16 +1,75 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
-use floem::reactive::{create_rw_signal, RwSignal, Scope, SignalUpdate};
+use floem::{
+    ext_event::create_ext_action,
+    glazier::Modifiers,
+    reactive::{
+        create_effect, create_rw_signal, RwSignal, Scope, SignalGet,
+        SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
+        SignalWithUntracked,
+    },
+};
+use fluent_bundle::FluentValue;
 use indexmap::IndexMap;
+use lapce_core::{command::FocusCommand, mode::Mode};
+use lapce_rpc::{file::TrashedPath, proxy::ProxyResponse};
 
 use super::node::FileNode;
-use crate::window_tab::CommonData;
+use crate::{
+    command::{
+        CommandExecuted, CommandKind, InternalCommand, LapceCommand,
+        LapceWorkbenchCommand,
+    },
+    keypress::{condition::Condition, KeyPressFocus},
+    notification::{Notification, NotificationCommand, NotificationSeverity},
+    window_tab::CommonData,
+};
+
+/// How many rows `list.next_page` and `list.previous_page` move the selection by
+const PAGE_SIZE: usize = 10;
+/// How many of the paths which were moved to the trash can be restored again
+const TRASHED_LIMIT: usize = 20;
 
 #[derive(Clone)]
 pub struct FileExplorerData {
     pub root: FileNode,
     pub common: CommonData,
     pub all_files: RwSignal<im::HashMap<PathBuf, FileNode>>,
+    /// The row which is selected with the keyboard
+    pub selected: RwSignal<Option<PathBuf>>,
+    /// The paths which were moved to the trash and can be restored, newest last
+    pub trashed: RwSignal<Vec<TrashedPath>>,
+    /// The path which is selected once the folders down to it are read
+    revealing: RwSignal<Option<PathBuf>>,
+}
+
+impl KeyPressFocus for FileExplorerData {
+    fn get_mode(&self) -> Mode {
+        Mode::Normal
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        matches!(
+            condition,
+            Condition::PanelFocus
+                | Condition::ListFocus
+                | Condition::FileExplorerFocus
+        )
+    }
+
+    fn run_command(
+        &self,
+        command: &LapceCommand,
+        _count: Option<usize>,
+        _mods: Modifiers,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Focus(cmd) => self.run_focus_command(cmd),
+            _ => CommandExecuted::No,
+        }
+    }
+
+    fn receive_char(&self, _c: &str) {}
 }
 
 impl FileExplorerData {
@@ -21,6 +80,7 @@ pub fn new(cx: Scope, common: CommonData) -> Self {
             scope: cx,
             path: path.clone(),
             is_dir: true,
+            link_target: None,
             read: create_rw_signal(cx, false),
             expanded: create_rw_signal(cx, false),
             children: create_rw_signal(cx, IndexMap::new()),
@@ -35,10 +95,378 @@ pub fn new(cx: Scope, common: CommonData) -> Self {
         if common.workspace.path.is_some() {
             root.toggle_expand(&common.proxy);
         }
-        Self {
+        let explorer = Self {
             root,
             common,
             all_files,
+            selected: create_rw_signal(cx, None),
+            trashed: create_rw_signal(cx, Vec::new()),
+            revealing: create_rw_signal(cx, None),
+        };
+        {
+            let explorer = explorer.clone();
+            // The reveal goes on each time a folder was read
+            create_effect(cx, move |_| {
+                let Some(path) = explorer.revealing.get() else {
+                    return;
+                };
+                explorer.all_files.with(|_| ());
+                explorer.continue_reveal(&path);
+            });
+        }
+        explorer
+    }
+
+    fn run_focus_command(&self, cmd: &FocusCommand) -> CommandExecuted {
+        match cmd {
+            FocusCommand::ListNext => self.move_selection(1),
+            FocusCommand::ListNextPage => self.move_selection(PAGE_SIZE as isize),
+            FocusCommand::ListPrevious => self.move_selection(-1),
+            FocusCommand::ListPreviousPage => {
+                self.move_selection(-(PAGE_SIZE as isize))
+            }
+            FocusCommand::ListSelect => {
+                if let Some(node) = self.selected_node() {
+                    node.click(&self.common.proxy);
+                }
+            }
+            FocusCommand::ListExpand => {
+                if let Some(node) = self.selected_node() {
+                    node.toggle_expand(&self.common.proxy);
+                }
+            }
+            _ => return CommandExecuted::No,
+        }
+        CommandExecuted::Yes
+    }
+
+    /// The paths of the rows which are shown, in the order they're shown in.
+    pub fn visible_paths(&self, tracked: bool) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        visible_children(&self.root, tracked, &mut paths);
+        paths
+    }
+
+    /// Move the selected file or directory to the trash, or with `permanently`
+    /// ask whether to delete it for good, as that can't be undone.
+    pub fn delete_selected(&self, permanently: bool) {
+        let Some(path) = self.selected.get_untracked() else {
+            return;
+        };
+        if path == self.root.path {
+            return;
+        }
+        let config = self.common.config.get_untracked();
+        let name = file_name(&path);
+
+        if permanently {
+            self.notify(
+                Notification::new(
+                    NotificationSeverity::Warning,
+                    config.tr_args(
+                        "notification-delete-permanently",
+                        [("file", FluentValue::from(name.as_str()))],
+                    ),
+                    config.tr("notification-delete-permanently-message"),
+                )
+                .with_action(
+                    config.tr("notification-delete-permanently-confirm"),
+                    NotificationCommand::Internal(
+                        InternalCommand::DeletePathPermanently { path },
+                    ),
+                ),
+            );
+            return;
+        }
+
+        let explorer = self.clone();
+        let send = create_ext_action(self.common.scope, {
+            let path = path.clone();
+            move |result| {
+                let config = explorer.common.config.get_untracked();
+                match result {
+                    Ok(ProxyResponse::TrashPathResponse { trashed }) => {
+                        explorer.path_removed(&path);
+                        // Only the trash of the freedesktop spec can restore it
+                        let Some(trashed) = trashed else {
+                            return;
+                        };
+                        explorer.trashed.update(|all| {
+                            all.push(trashed);
+                            if all.len() > TRASHED_LIMIT {
+                                all.remove(0);
+                            }
+                        });
+                        explorer.notify(
+                            Notification::new(
+                                NotificationSeverity::Info,
+                                config.tr_args(
+                                    "notification-file-trashed",
+                                    [("file", FluentValue::from(name.as_str()))],
+                                ),
+                                path.display().to_string(),
+                            )
+                            .with_action(
+                                config.tr("notification-file-trashed-undo"),
+                                NotificationCommand::Workbench(
+                                    LapceWorkbenchCommand::UndoFileDelete,
+                                ),
+                            ),
+                        );
+                    }
+                    Err(err) => {
+                        explorer.notify(Notification::error(
+                            config.tr_args(
+                                "notification-delete-failed",
+                                [("file", FluentValue::from(name.as_str()))],
+                            ),
+                            err.message,
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        });
+        self.common.proxy.trash_path(path, move |result| {
+            send(result);
+        });
+    }
+
+    /// Delete the path for good, which has been confirmed already.
+    pub fn delete_permanently(&self, path: PathBuf) {
+        let explorer = self.clone();
+        let send = create_ext_action(self.common.scope, {
+            let path = path.clone();
+            move |result| match result {
+                Ok(_) => explorer.path_removed(&path),
+                Err(err) => {
+                    let config = explorer.common.config.get_untracked();
+                    explorer.notify(Notification::error(
+                        config.tr_args(
+                            "notification-delete-failed",
+                            [("file", FluentValue::from(file_name(&path)))],
+                        ),
+                        err.message,
+                    ));
+                }
+            }
+        });
+        self.common.proxy.delete_path(path, move |result| {
+            send(result);
+        });
+    }
+
+    /// Restore the path which was moved to the trash last. It's only taken off
+    /// the paths which can be restored once it is, so that the undo can be
+    /// tried again when it fails.
+    pub fn undo_delete(&self) {
+        let Some(trashed) = self.trashed.with_untracked(|all| all.last().cloned())
+        else {
+            return;
+        };
+        let explorer = self.clone();
+        let send = create_ext_action(self.common.scope, {
+            let trashed = trashed.clone();
+            move |result| match result {
+                Ok(_) => {
+                    // More paths may have been trashed since
+                    explorer.trashed.update(|all| {
+                        if let Some(i) = all.iter().rposition(|t| t == &trashed) {
+                            all.remove(i);
+                        }
+                    });
+                    explorer.reload_parent(&trashed.path);
+                    explorer.selected.set(Some(trashed.path));
+                }
+                Err(err) => {
+                    let config = explorer.common.config.get_untracked();
+                    explorer.notify(Notification::error(
+                        config.tr_args(
+                            "notification-restore-failed",
+                            [("file", FluentValue::from(file_name(&trashed.path)))],
+                        ),
+                        err.message,
+                    ));
+                }
+            }
+        });
+        self.common.proxy.restore_path(trashed, move |result| {
+            send(result);
+        });
+    }
+
+    /// Select what the selected symlink points to, with the folders down to it
+    /// expanded. A file outside of the workspace is opened instead.
+    pub fn follow_selected_link(&self) {
+        let Some(node) = self.selected_node() else {
+            return;
+        };
+        let Some(target) = node.link_target.as_ref() else {
+            return;
+        };
+        let destination = link_destination(&node.path, target);
+        if destination.starts_with(&self.root.path) {
+            self.revealing.set(Some(destination));
+        } else if !node.is_dir {
+            self.common
+                .internal_command
+                .send(InternalCommand::OpenFile { path: destination });
+        } else {
+            let config = self.common.config.get_untracked();
+            self.notify(Notification::new(
+                NotificationSeverity::Info,
+                config.tr_args(
+                    "notification-link-outside-workspace",
+                    [("file", FluentValue::from(file_name(&node.path)))],
+                ),
+                destination.display().to_string(),
+            ));
+        }
+    }
+
+    /// Expand the folders down to the path which is revealed, where a folder
+    /// which isn't read yet is waited for, and select the path once it's there.
+    fn continue_reveal(&self, path: &Path) {
+        let folders: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|folder| folder.starts_with(&self.root.path))
+            .collect();
+        for folder in folders.into_iter().rev() {
+            let node = self
+                .all_files
+                .with_untracked(|all_files| all_files.get(folder).cloned());
+            let Some(node) = node else {
+                return;
+            };
+            if !node.expanded.get_untracked() {
+                let read = node.read.get_untracked();
+                node.toggle_expand(&self.common.proxy);
+                if !read {
+                    return;
+                }
+            }
+        }
+        if self
+            .all_files
+            .with_untracked(|all_files| all_files.contains_key(path))
+        {
+            self.revealing.set(None);
+            self.selected.set(Some(path.to_path_buf()));
+        }
+    }
+
+    fn path_removed(&self, path: &Path) {
+        if self
+            .selected
+            .with_untracked(|selected| selected.as_deref() == Some(path))
+        {
+            self.selected.set(None);
+        }
+        self.reload_parent(path);
+    }
+
+    fn reload_parent(&self, path: &Path) {
+        let parent = path.parent().and_then(|parent| {
+            self.all_files
+                .with_untracked(|all_files| all_files.get(parent).cloned())
+        });
+        if let Some(parent) = parent {
+            parent.reload(&self.common.proxy);
+        }
+    }
+
+    fn notify(&self, notification: Notification) {
+        self.common
+            .internal_command
+            .send(InternalCommand::ShowNotification { notification });
+    }
+
+    fn selected_node(&self) -> Option<FileNode> {
+        let selected = self.selected.get_untracked()?;
+        self.all_files
+            .with_untracked(|all_files| all_files.get(&selected).cloned())
+    }
+
+    fn move_selection(&self, delta: isize) {
+        let paths = self.visible_paths(false);
+        if paths.is_empty() {
+            return;
+        }
+        let index = self
+            .selected
+            .with_untracked(|selected| {
+                selected
+                    .as_ref()
+                    .and_then(|selected| paths.iter().position(|p| p == selected))
+            })
+            .map(|index| {
+                (index as isize + delta).clamp(0, paths.len() as isize - 1) as usize
+            })
+            .unwrap_or(0);
+        self.selected.set(Some(paths[index].clone()));
+    }
+}
+
+fn visible_children(node: &FileNode, tracked: bool, paths: &mut Vec<PathBuf>) {
+    let expanded = if tracked {
+        node.expanded.get()
+    } else {
+        node.expanded.get_untracked()
+    };
+    if !node.is_dir || !expanded {
+        return;
+    }
+    let mut push = |children: &IndexMap<PathBuf, FileNode>| {
+        for child in children.values() {
+            paths.push(child.path.clone());
+            visible_children(child, tracked, paths);
+        }
+    };
+    if tracked {
+        node.children.with(&mut push);
+    } else {
+        node.children.with_untracked(&mut push);
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// The path which the target of the symlink leads to, with its `.` and `..`
+/// taken out. It isn't canonicalized, as the files can be on the machine of a
+/// remote proxy.
+fn link_destination(link: &Path, target: &Path) -> PathBuf {
+    let mut destination = PathBuf::new();
+    for component in link.parent().unwrap_or(link).join(target).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                destination.pop();
+            }
+            component => destination.push(component),
         }
     }
+    destination
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_destination() {
+        let link = Path::new("/work/src/current");
+        assert_eq!(
+            link_destination(link, Path::new("../releases/./v2")),
+            PathBuf::from("/work/releases/v2")
+        );
+        assert_eq!(
+            link_destination(link, Path::new("/etc/hosts")),
+            PathBuf::from("/etc/hosts")
+        );
+    }
 }
@@ -18,6 +18,8 @@ pub struct FileNode {
     pub scope: Scope,
     pub path: PathBuf,
     pub is_dir: bool,
+    /// What the symlink points to, as it's written in the link
+    pub link_target: Option<PathBuf>,
     pub read: RwSignal<bool>,
     pub expanded: RwSignal<bool>,
     pub children: RwSignal<IndexMap<PathBuf, FileNode>>,
@@ -111,6 +113,16 @@ fn update_node_open_count(&self) {
         }
     }
 
+    /// Read the directory again, e.g. after a path in it was deleted or
+    /// restored. The nodes which are still there are kept as they are.
+    pub fn reload(&self, proxy: &ProxyRpcHandler) {
+        if !self.is_dir || !self.read.get_untracked() {
+            return;
+        }
+        self.read.set(false);
+        self.read_dir(proxy);
+    }
+
     fn read_dir(&self, proxy: &ProxyRpcHandler) {
         if self.read.get_untracked() {
             return;
@@ -120,15 +132,20 @@ fn read_dir(&self, proxy: &ProxyRpcHandler) {
         let file_node = self.clone();
         let send = create_ext_action(self.scope, move |result| {
             if let Ok(ProxyResponse::ReadDirResponse { items }) = result {
+                let existing = file_node.children.get_untracked();
                 let items = items
                     .into_iter()
                     .map(|item| {
+                        if let Some(node) = existing.get(&item.path_buf) {
+                            return (item.path_buf, node.clone());
+                        }
                         (
                             item.path_buf.clone(),
                             FileNode {
                                 scope: cx,
                                 path: item.path_buf,
                                 is_dir: item.is_dir,
+                                link_target: item.link_target,
                                 read: create_rw_signal(cx, false),
                                 expanded: create_rw_signal(cx, false),
                                 children: create_rw_signal(cx, IndexMap::new()),
@@ -141,6 +158,11 @@ fn read_dir(&self, proxy: &ProxyRpcHandler) {
                     })
                     .collect::<IndexMap<PathBuf, FileNode>>();
                 file_node.all_files.update(|all_files| {
+                    for path in existing.keys() {
+                        if !items.contains_key(path) {
+                            all_files.retain(|p, _| !p.starts_with(path));
+                        }
+                    }
                     for (_, item) in items.iter() {
                         all_files.insert(item.path.clone(), item.clone());
                     }
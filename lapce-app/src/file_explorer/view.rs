@@ -1,8 +1,12 @@
 use std::{path::PathBuf, sync::Arc};
 
 use floem::{
-    peniko::Color,
-    reactive::{ReadSignal, SignalGet},
+    event::EventListener,
+    peniko::{
+        kurbo::{Point, Size},
+        Color,
+    },
+    reactive::{ReadSignal, RwSignal, SignalGet, SignalSet, SignalWith},
     style::{CursorStyle, Style},
     view::View,
     views::{
@@ -10,13 +14,13 @@
         Decorators, VirtualListDirection, VirtualListItemSize, VirtualListVector,
     },
 };
-use lapce_rpc::proxy::ProxyRpcHandler;
+use lapce_rpc::{coverage::Coverage, proxy::ProxyRpcHandler};
 
 use super::node::FileNode;
 use crate::{
     config::{color::LapceColor, icon::LapceIcons, LapceConfig},
-    panel::{position::PanelPosition, view::panel_header},
-    window_tab::WindowTabData,
+    panel::{kind::PanelKind, position::PanelPosition, view::panel_header},
+    window_tab::{Focus, WindowTabData},
 };
 
 pub fn file_explorer_panel(
@@ -24,27 +28,53 @@ pub fn file_explorer_panel(
     position: PanelPosition,
 ) -> impl View {
     let config = window_tab_data.common.config;
-    let root_file_node = window_tab_data.file_explorer.root.clone();
+    let focus = window_tab_data.common.focus;
+    let file_explorer = window_tab_data.file_explorer.clone();
+    let root_file_node = file_explorer.root.clone();
+    let selected = file_explorer.selected;
+    let line_height = window_tab_data.common.ui_line_height;
     let proxy = window_tab_data.common.proxy.clone();
+    let coverage = window_tab_data.common.coverage;
     stack(|| {
         (
-            stack(move || (panel_header("Open Editors".to_string(), config),))
+            stack(move || (panel_header("panel-open-editors", config),))
                 .style(|| Style::BASE.width_pct(100.0).flex_col().height_px(150.0)),
             stack(|| {
                 (
-                    panel_header("File Explorer".to_string(), config),
+                    panel_header("panel-file-explorer", config),
                     container(|| {
                         scroll(move || {
                             file_node_view(
                                 root_file_node.clone(),
                                 proxy.clone(),
                                 0,
+                                selected,
+                                coverage,
                                 config,
                             )
                         })
                         .scroll_bar_color(move || {
                             *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
                         })
+                        .on_ensure_visible(move || {
+                            let line_height = line_height.get();
+                            let index = selected
+                                .get()
+                                .and_then(|selected| {
+                                    file_explorer
+                                        .visible_paths(true)
+                                        .iter()
+                                        .position(|p| p == &selected)
+                                })
+                                .unwrap_or(0);
+                            Size::new(1.0, line_height).to_rect().with_origin(
+                                Point::new(0.0, index as f64 * line_height),
+                            )
+                        })
+                        .on_event(EventListener::PointerDown, move |_| {
+                            focus.set(Focus::Panel(PanelKind::FileExplorer));
+                            false
+                        })
                         .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
                     })
                     .style(|| Style::BASE.size_pct(100.0, 100.0).line_height(1.6)),
@@ -64,6 +94,8 @@ fn file_node_view(
     file_node: FileNode,
     proxy: ProxyRpcHandler,
     level: usize,
+    selected: RwSignal<Option<PathBuf>>,
+    coverage: RwSignal<Coverage>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
     virtual_list(
@@ -82,6 +114,14 @@ fn file_node_view(
                         let proxy = proxy.clone();
                         let expanded = file_node.expanded;
                         let is_dir = file_node.is_dir;
+                        let is_selected = {
+                            let path = path.clone();
+                            move || {
+                                selected.with(|selected| {
+                                    selected.as_ref() == Some(&path)
+                                })
+                            }
+                        };
                         stack(|| {
                             (
                                 svg(move || {
@@ -153,14 +193,67 @@ fn file_node_view(
                                         },
                                     )
                                 },
-                                label(move || {
-                                    path.file_name()
-                                        .map(|f| f.to_string_lossy().to_string())
-                                        .unwrap_or_default()
-                                }),
+                                {
+                                    let path = path.clone();
+                                    label(move || {
+                                        path.file_name()
+                                            .map(|f| f.to_string_lossy().to_string())
+                                            .unwrap_or_default()
+                                    })
+                                },
+                                {
+                                    let link_target = file_node.link_target.clone();
+                                    let is_link = link_target.is_some();
+                                    label(move || {
+                                        link_target
+                                            .as_ref()
+                                            .map(|target| {
+                                                format!("→ {}", target.display())
+                                            })
+                                            .unwrap_or_default()
+                                    })
+                                    .style(
+                                        move || {
+                                            Style::BASE
+                                                .margin_left_px(6.0)
+                                                .color(*config.get().get_color(
+                                                    LapceColor::EDITOR_DIM,
+                                                ))
+                                                .apply_if(!is_link, |s| s.hide())
+                                        },
+                                    )
+                                },
+                                {
+                                    let percent = move || {
+                                        if is_dir {
+                                            return None;
+                                        }
+                                        coverage.with(|coverage| {
+                                            coverage.files.get(&path)?.percent()
+                                        })
+                                    };
+                                    label(move || {
+                                        percent()
+                                            .map(|percent| format!("{percent:.0}%"))
+                                            .unwrap_or_default()
+                                    })
+                                    .style(
+                                        move || {
+                                            Style::BASE
+                                                .margin_left_px(6.0)
+                                                .color(*config.get().get_color(
+                                                    LapceColor::EDITOR_DIM,
+                                                ))
+                                                .apply_if(percent().is_none(), |s| {
+                                                    s.hide()
+                                                })
+                                        },
+                                    )
+                                },
                             )
                         })
                         .on_click(move |_| {
+                            selected.set(Some(file_node.path.clone()));
                             file_node.click(&proxy);
                             true
                         })
@@ -170,6 +263,11 @@ fn file_node_view(
                                 .padding_right_px(10.0)
                                 .padding_left_px((level * 10) as f32)
                                 .min_width_pct(100.0)
+                                .apply_if(is_selected(), |s| {
+                                    s.background(*config.get().get_color(
+                                        LapceColor::PANEL_CURRENT_BACKGROUND,
+                                    ))
+                                })
                         })
                         .hover_style(move || {
                             Style::BASE
@@ -186,6 +284,8 @@ fn file_node_view(
                             file_node,
                             proxy.clone(),
                             level + 1,
+                            selected,
+                            coverage,
                             config,
                         ))
                     }),
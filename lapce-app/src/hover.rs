@@ -0,0 +1,182 @@
+use floem::{
+    peniko::kurbo::{Point, Rect, Vec2},
+    reactive::{
+        create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalSet,
+        SignalUpdate,
+    },
+};
+use lsp_types::{Position, Url};
+use tracing::error;
+
+use crate::{
+    command::InternalCommand,
+    editor::location::{EditorLocation, EditorPosition},
+    id::EditorId,
+    listener::Listener,
+    markdown::MarkdownContent,
+    proxy::path_from_url,
+};
+
+/// A hover which was pinned, and stays open in a window of its own until it's
+/// closed.
+#[derive(Clone)]
+pub struct PinnedHover {
+    pub id: usize,
+    /// Where the window is, from the top left of the window tab
+    pub origin: Point,
+    pub content: Vec<MarkdownContent>,
+}
+
+#[derive(Clone, Copy)]
+pub struct HoverData {
+    pub active: RwSignal<bool>,
+    pub editor_id: RwSignal<Option<EditorId>>,
+    /// The offset which the hover is shown under
+    pub offset: RwSignal<usize>,
+    /// The range of the text which the hover is for, which it's hidden once the
+    /// mouse leaves
+    pub range: RwSignal<(usize, usize)>,
+    pub content: RwSignal<Vec<MarkdownContent>>,
+    pub layout_rect: RwSignal<Rect>,
+    pub pinned: RwSignal<im::Vector<PinnedHover>>,
+    /// The editor and the offset which the mouse rests on, which are hovered once
+    /// the delay passes if it stays there
+    pub pending: RwSignal<Option<(EditorId, usize)>>,
+    /// Bumped for each request, so that the answers to the older ones are dropped
+    pub request_id: RwSignal<usize>,
+    next_pinned_id: RwSignal<usize>,
+}
+
+impl HoverData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            active: create_rw_signal(cx, false),
+            editor_id: create_rw_signal(cx, None),
+            offset: create_rw_signal(cx, 0),
+            range: create_rw_signal(cx, (0, 0)),
+            content: create_rw_signal(cx, Vec::new()),
+            layout_rect: create_rw_signal(cx, Rect::ZERO),
+            pinned: create_rw_signal(cx, im::Vector::new()),
+            pending: create_rw_signal(cx, None),
+            request_id: create_rw_signal(cx, 0),
+            next_pinned_id: create_rw_signal(cx, 0),
+        }
+    }
+
+    pub fn show(
+        &self,
+        editor_id: EditorId,
+        offset: usize,
+        range: (usize, usize),
+        content: Vec<MarkdownContent>,
+    ) {
+        self.editor_id.set(Some(editor_id));
+        self.offset.set(offset);
+        self.range.set(range);
+        self.content.set(content);
+        self.active.set(true);
+    }
+
+    pub fn hide(&self) {
+        self.pending.set(None);
+        self.request_id.update(|id| *id += 1);
+        if self.active.get_untracked() {
+            self.active.set(false);
+        }
+    }
+
+    /// Whether the hover is open for the offset of the editor.
+    pub fn is_over(&self, editor_id: EditorId, offset: usize) -> bool {
+        let (start, end) = self.range.get_untracked();
+        self.active.get_untracked()
+            && self.editor_id.get_untracked() == Some(editor_id)
+            && start <= offset
+            && offset <= end
+    }
+
+    /// Move the hover into a window of its own at the origin, which stays open
+    /// until it's closed.
+    pub fn pin(&self, origin: Point) {
+        let id = self.next_pinned_id.get_untracked();
+        self.next_pinned_id.set(id + 1);
+        let pinned = PinnedHover {
+            id,
+            origin,
+            content: self.content.get_untracked(),
+        };
+        self.pinned.update(|hovers| hovers.push_back(pinned));
+        self.hide();
+    }
+
+    /// Drag the window of a pinned hover by the distance.
+    pub fn move_pinned(&self, id: usize, delta: Vec2) {
+        self.pinned.update(|hovers| {
+            if let Some(hover) = hovers.iter_mut().find(|h| h.id == id) {
+                hover.origin += delta;
+            }
+        });
+    }
+
+    pub fn unpin(&self, id: usize) {
+        self.pinned.update(|hovers| hovers.retain(|h| h.id != id));
+    }
+}
+
+/// Open a link of a hover. A `file://` link is opened in an editor, at the line
+/// and column of its fragment if it has one, like `#L10` or `#L10,5`, and the
+/// other links are opened by the system.
+pub fn open_link(link: &str, internal_command: Listener<InternalCommand>) {
+    let url = match Url::parse(link) {
+        Ok(url) => url,
+        Err(e) => {
+            error!("failed to open the link {link}: {e}");
+            return;
+        }
+    };
+    if url.scheme() == "file" {
+        internal_command.send(InternalCommand::JumpToLocation {
+            location: EditorLocation {
+                path: path_from_url(&url),
+                position: url
+                    .fragment()
+                    .and_then(link_position)
+                    .map(EditorPosition::Position),
+                scroll_offset: None,
+                ignore_unconfirmed: false,
+                same_editor_tab: false,
+            },
+        });
+    } else if let Err(e) = open::that(url.as_str()) {
+        error!("failed to open the link {link}: {e}");
+    }
+}
+
+/// The position of the fragment of a file link, whose line and column count
+/// from one, like `L10`, `L10,5`, `10:5` or `L10C5`.
+fn link_position(fragment: &str) -> Option<Position> {
+    let fragment = fragment.strip_prefix('L').unwrap_or(fragment);
+    let mut parts = fragment.split([',', ':', 'C']);
+    let line: u32 = parts.next()?.parse().ok()?;
+    let character: u32 = parts
+        .next()
+        .and_then(|character| character.parse().ok())
+        .unwrap_or(1);
+    Some(Position {
+        line: line.saturating_sub(1),
+        character: character.saturating_sub(1),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_link_position() {
+        assert_eq!(link_position("L10"), Some(Position::new(9, 0)));
+        assert_eq!(link_position("L10,5"), Some(Position::new(9, 4)));
+        assert_eq!(link_position("10:5"), Some(Position::new(9, 4)));
+        assert_eq!(link_position("L10C5"), Some(Position::new(9, 4)));
+        assert_eq!(link_position("section"), None);
+    }
+}
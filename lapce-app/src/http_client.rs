@@ -0,0 +1,87 @@
+//! The requests of `.http` files which are sent from the editor, and the last
+//! response which the response panel shows.
+
+use std::path::PathBuf;
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{
+        create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalSet,
+        SignalWithUntracked,
+    },
+};
+use lapce_rpc::{
+    http::{parse_http_file, HttpResponse},
+    proxy::ProxyResponse,
+    RpcError,
+};
+
+use crate::window_tab::CommonData;
+
+#[derive(Clone)]
+pub struct HttpClientData {
+    /// The request which was sent last, like `GET https://example.com`
+    pub request: RwSignal<Option<String>>,
+    pub response: RwSignal<Option<HttpResponse>>,
+    /// Why the last request failed, when it didn't get a response
+    pub error: RwSignal<Option<String>>,
+    pub sending: RwSignal<bool>,
+    pub common: CommonData,
+}
+
+impl HttpClientData {
+    pub fn new(cx: Scope, common: CommonData) -> Self {
+        Self {
+            request: create_rw_signal(cx, None),
+            response: create_rw_signal(cx, None),
+            error: create_rw_signal(cx, None),
+            sending: create_rw_signal(cx, false),
+            common,
+        }
+    }
+
+    /// Send the request of the text of the file whose block has the line. The
+    /// response is dropped when another request was sent since.
+    pub fn send(&self, path: PathBuf, text: String, line: usize) {
+        let file = parse_http_file(&text);
+        let Some(request) = file.request_at(line) else {
+            return;
+        };
+        let label = format!("{} {}", request.method, request.url);
+        self.request.set(Some(label.clone()));
+        self.response.set(None);
+        self.error.set(None);
+        self.sending.set(true);
+
+        let environment = self.common.config.with_untracked(|config| {
+            let environment = &config.core.http_client_environment;
+            (!environment.is_empty()).then(|| environment.clone())
+        });
+        let data = self.clone();
+        let send = create_ext_action(
+            self.common.scope,
+            move |result: Result<ProxyResponse, RpcError>| {
+                if data.request.get_untracked().as_ref() != Some(&label) {
+                    return;
+                }
+                data.sending.set(false);
+                match result {
+                    Ok(ProxyResponse::SendHttpRequest { response }) => {
+                        data.response.set(Some(response));
+                    }
+                    Ok(_) => {}
+                    Err(e) => data.error.set(Some(e.message)),
+                }
+            },
+        );
+        self.common.proxy.send_http_request(
+            path,
+            text,
+            line,
+            environment,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+}
@@ -20,3 +20,4 @@ pub fn to_raw(self) -> u64 {
 pub type SettingsId = Id;
 pub type EditorId = Id;
 pub type TerminalTabId = Id;
+pub type NotificationId = Id;
@@ -2,17 +2,30 @@
 mod key;
 pub mod keymap;
 mod loader;
+pub mod macros;
 mod press;
 
-use std::{path::PathBuf, str::FromStr};
+use std::path::PathBuf;
 
 use anyhow::Result;
-use floem::glazier::{KbKey, KeyEvent, Modifiers, MouseEvent};
+use floem::{
+    glazier::{KbKey, KeyEvent, Modifiers, MouseEvent},
+    reactive::{
+        create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalUpdate,
+        SignalWithUntracked,
+    },
+};
 use indexmap::IndexMap;
 use lapce_core::mode::Mode;
 use tracing::{debug, error};
 
-use self::{key::Key, keymap::KeyMap, loader::KeyMapLoader, press::KeyPress};
+use self::{
+    key::Key,
+    keymap::KeyMap,
+    loader::KeyMapLoader,
+    macros::{MacroRecorder, MacroStep, MACRO_COMMAND_PREFIX},
+    press::KeyPress,
+};
 use crate::{
     command::{
         lapce_internal_commands, CommandExecuted, CommandKind, LapceCommand,
@@ -20,10 +33,11 @@
     },
     config::LapceConfig,
     keypress::{
-        condition::{CheckCondition, Condition},
+        condition::{mode_matches, Condition, ConditionExpr, ContextKey},
         keymap::KeymapMatch,
     },
     listener::Listener,
+    panel::kind::PanelKind,
 };
 
 const DEFAULT_KEYMAPS_COMMON: &str =
@@ -38,6 +52,16 @@ pub trait KeyPressFocus {
 
     fn check_condition(&self, condition: Condition) -> bool;
 
+    /// Check a valued context key from a `when` clause, such as
+    /// `language_id == rust`. `panel_visible` isn't asked, as it doesn't depend
+    /// on the focus.
+    fn check_context(&self, key: ContextKey, value: &str) -> bool {
+        match key {
+            ContextKey::Mode => mode_matches(self.get_mode(), value),
+            ContextKey::LanguageId | ContextKey::PanelVisible => false,
+        }
+    }
+
     fn run_command(
         &self,
         command: &LapceCommand,
@@ -54,6 +78,12 @@ fn focus_only(&self) -> bool {
     }
 
     fn receive_char(&self, c: &str);
+
+    /// Run `f` once for each selection, with only that selection active. Focuses
+    /// without selections run it once.
+    fn for_each_selection(&self, f: &mut dyn FnMut()) {
+        f();
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -84,10 +114,17 @@ pub struct KeyPressData {
     pub command_keymaps: IndexMap<String, Vec<KeyMap>>,
     commands_with_keymap: Vec<KeyMap>,
     commands_without_keymap: Vec<LapceCommand>,
+    /// The recorded macros, which are kept in a signal so that they're shared by
+    /// the copies of the data
+    pub macros: RwSignal<MacroRecorder>,
+    /// The panels which are shown, which `panel_visible` in a `when` clause is
+    /// checked against
+    pub visible_panels: RwSignal<im::HashSet<PanelKind>>,
 }
 
 impl KeyPressData {
     pub fn new(
+        cx: Scope,
         config: &LapceConfig,
         workbench_cmd: Listener<LapceWorkbenchCommand>,
     ) -> Self {
@@ -102,11 +139,22 @@ pub fn new(
             commands_with_keymap: Vec::new(),
             commands_without_keymap: Vec::new(),
             workbench_cmd,
+            macros: create_rw_signal(cx, MacroRecorder::new()),
+            visible_panels: create_rw_signal(cx, im::HashSet::new()),
         };
         keypress.load_commands();
         keypress
     }
 
+    /// Reload the keymaps, e.g. after the config or the active profile changed.
+    pub fn update_keymaps(&mut self, config: &LapceConfig) {
+        if let Ok((keymaps, command_keymaps)) = Self::get_keymaps(config) {
+            self.keymaps = keymaps;
+            self.command_keymaps = command_keymaps;
+            self.load_commands();
+        }
+    }
+
     fn load_commands(&mut self) {
         let mut commands_with_keymap = Vec::new();
         let mut commands_without_keymap = Vec::new();
@@ -183,6 +231,89 @@ fn run_command<T: KeyPressFocus>(
         }
     }
 
+    fn record(&self, step: MacroStep) {
+        self.macros.update(|macros| macros.record(step));
+    }
+
+    /// Play the steps of a macro on the focus, which are recorded again when
+    /// another macro is being recorded.
+    fn play_steps<T: KeyPressFocus>(&self, steps: &[MacroStep], focus: &T) {
+        for step in steps {
+            match step {
+                MacroStep::Command { command, count } => {
+                    self.run_command(command, *count, Modifiers::empty(), focus);
+                }
+                MacroStep::Text { text } => {
+                    let mut buf = [0; 4];
+                    for c in text.chars() {
+                        focus.receive_char(c.encode_utf8(&mut buf));
+                    }
+                }
+            }
+            self.record(step.clone());
+        }
+    }
+
+    /// Play the last recorded macro `times` times, or once over each selection.
+    pub fn play_last_macro<T: KeyPressFocus>(
+        &self,
+        focus: &T,
+        times: usize,
+        over_selections: bool,
+    ) {
+        let steps = self.macros.with_untracked(|macros| macros.last.clone());
+        if over_selections {
+            focus.for_each_selection(&mut || self.play_steps(&steps, focus));
+        } else {
+            for _ in 0..times {
+                self.play_steps(&steps, focus);
+            }
+        }
+    }
+
+    /// Run the commands which record and play macros, which aren't recorded
+    /// themselves. Returns false when the command isn't one of them.
+    fn run_macro_command<T: KeyPressFocus>(
+        &self,
+        command: &str,
+        count: Option<usize>,
+        focus: &T,
+    ) -> bool {
+        if let Some(name) = command.strip_prefix(MACRO_COMMAND_PREFIX) {
+            let steps = self
+                .macros
+                .with_untracked(|macros| macros.saved.get(name).cloned());
+            if let Some(steps) = steps {
+                for _ in 0..count.unwrap_or(1) {
+                    self.play_steps(&steps, focus);
+                }
+            }
+            return true;
+        }
+
+        let Some(CommandKind::Workbench(cmd)) =
+            self.commands.get(command).map(|cmd| &cmd.kind)
+        else {
+            return false;
+        };
+        match cmd {
+            LapceWorkbenchCommand::ToggleMacroRecording => {
+                self.macros.update(|macros| macros.toggle());
+            }
+            LapceWorkbenchCommand::PlayMacro => {
+                self.play_last_macro(focus, count.unwrap_or(1), false);
+            }
+            LapceWorkbenchCommand::PlayMacroOverSelections => {
+                self.play_last_macro(focus, 1, true);
+            }
+            LapceWorkbenchCommand::SaveMacro => {
+                self.workbench_cmd.send(cmd.clone());
+            }
+            _ => return false,
+        }
+        true
+    }
+
     pub fn key_down<'a, T: KeyPressFocus>(
         &mut self,
         event: impl Into<EventRef<'a>>,
@@ -221,16 +352,24 @@ pub fn key_down<'a, T: KeyPressFocus>(
             KeymapMatch::Full(command) => {
                 self.pending_keypress.clear();
                 let count = self.count.take();
+                if self.run_macro_command(&command, count, focus) {
+                    return true;
+                }
                 self.run_command(&command, count, mods, focus);
+                self.record(MacroStep::Command { command, count });
                 return true;
             }
             KeymapMatch::Multiple(commands) => {
                 self.pending_keypress.clear();
                 let count = self.count.take();
                 for command in commands {
+                    if self.run_macro_command(&command, count, focus) {
+                        return true;
+                    }
                     if self.run_command(&command, count, mods, focus)
                         == CommandExecuted::Yes
                     {
+                        self.record(MacroStep::Command { command, count });
                         return true;
                     }
                 }
@@ -253,6 +392,10 @@ pub fn key_down<'a, T: KeyPressFocus>(
                         if let Some(cmd) = self.commands.get(&command) {
                             if let CommandKind::Move(_) = cmd.kind {
                                 focus.run_command(cmd, None, mods);
+                                self.record(MacroStep::Command {
+                                    command,
+                                    count: None,
+                                });
                                 return true;
                             }
                         }
@@ -278,6 +421,9 @@ pub fn key_down<'a, T: KeyPressFocus>(
             if mods.is_empty() {
                 if let Key::Keyboard(KbKey::Character(c)) = &keypress.key {
                     focus.receive_char(c);
+                    self.record(MacroStep::Text {
+                        text: c.to_string(),
+                    });
                     return true;
                 }
             }
@@ -290,6 +436,9 @@ pub fn key_down<'a, T: KeyPressFocus>(
             if mods.is_empty() {
                 if let Key::Keyboard(KbKey::Character(c)) = &keypress.key {
                     focus.receive_char(c);
+                    self.record(MacroStep::Text {
+                        text: c.to_string(),
+                    });
                     return true;
                 }
             }
@@ -326,6 +475,7 @@ fn match_keymap<T: KeyPressFocus>(
     ) -> KeymapMatch {
         let keypresses: Vec<KeyPress> =
             keypresses.iter().map(KeyPress::to_lowercase).collect();
+        let visible_panels = self.visible_panels.get_untracked();
         let matches = self
             .keymaps
             .get(&keypresses)
@@ -345,7 +495,11 @@ fn match_keymap<T: KeyPressFocus>(
                             return false;
                         }
                         if let Some(condition) = &keymap.when {
-                            if !Self::check_condition(condition, check) {
+                            if !Self::check_condition(
+                                condition,
+                                check,
+                                &visible_panels,
+                            ) {
                                 return false;
                             }
                         }
@@ -370,42 +524,13 @@ fn match_keymap<T: KeyPressFocus>(
         }
     }
 
-    fn check_condition<T: KeyPressFocus>(condition: &str, check: &T) -> bool {
-        fn check_one_condition<T: KeyPressFocus>(
-            condition: &str,
-            check: &T,
-        ) -> bool {
-            let trimmed = condition.trim();
-            if let Some(stripped) = trimmed.strip_prefix('!') {
-                if let Ok(condition) = Condition::from_str(stripped) {
-                    !check.check_condition(condition)
-                } else {
-                    true
-                }
-            } else if let Ok(condition) = Condition::from_str(trimmed) {
-                check.check_condition(condition)
-            } else {
-                false
-            }
-        }
-
-        match CheckCondition::parse_first(condition) {
-            CheckCondition::Single(condition) => {
-                check_one_condition(condition, check)
-            }
-            CheckCondition::Or(left, right) => {
-                let left = check_one_condition(left, check);
-                let right = Self::check_condition(right, check);
-
-                left || right
-            }
-            CheckCondition::And(left, right) => {
-                let left = check_one_condition(left, check);
-                let right = Self::check_condition(right, check);
-
-                left && right
-            }
-        }
+    fn check_condition<T: KeyPressFocus>(
+        condition: &str,
+        check: &T,
+        visible_panels: &im::HashSet<PanelKind>,
+    ) -> bool {
+        ConditionExpr::parse(condition)
+            .map_or(false, |expr| expr.eval(check, visible_panels))
     }
 
     #[allow(clippy::type_complexity)]
@@ -441,6 +566,17 @@ fn get_keymaps(
             }
         }
 
+        if let Some(profile) = config.profile.as_ref() {
+            if let Some(keymaps) = profile.keymaps.as_ref() {
+                if let Err(err) = loader.load_from_str(keymaps, is_modal) {
+                    error!(
+                        "Failed to load keymaps of profile {}: {err}",
+                        profile.id
+                    );
+                }
+            }
+        }
+
         Ok(loader.finalize())
     }
 
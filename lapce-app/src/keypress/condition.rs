@@ -1,37 +1,245 @@
+use std::str::FromStr;
+
+use lapce_core::mode::Mode;
 use strum_macros::EnumString;
 
-#[derive(Debug, PartialEq, Eq)]
-pub(super) enum CheckCondition<'a> {
-    Single(&'a str),
-    Or(&'a str, &'a str),
-    And(&'a str, &'a str),
+use super::KeyPressFocus;
+use crate::panel::kind::PanelKind;
+
+/// A parsed `when` clause of a keymap.
+///
+/// The grammar is a small boolean expression language:
+///
+/// ```text
+/// expr    := or
+/// or      := and ("||" and)*
+/// and     := unary ("&&" unary)*
+/// unary   := "!" unary | primary
+/// primary := "(" expr ")" | key (("==" | "!=") value)?
+/// ```
+///
+/// A bare key is looked up as a [`Condition`], while a comparison is looked up
+/// as a [`ContextKey`], e.g. `editor_focus && language_id == rust`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(super) enum ConditionExpr<'a> {
+    Key(&'a str),
+    Equals(&'a str, &'a str),
+    NotEquals(&'a str, &'a str),
+    Not(Box<ConditionExpr<'a>>),
+    And(Box<ConditionExpr<'a>>, Box<ConditionExpr<'a>>),
+    Or(Box<ConditionExpr<'a>>, Box<ConditionExpr<'a>>),
 }
 
-impl<'a> CheckCondition<'a> {
-    pub(super) fn parse_first(condition: &'a str) -> Self {
-        let or = condition.match_indices("||").next();
-        let and = condition.match_indices("&&").next();
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Token<'a> {
+    Ident(&'a str),
+    Not,
+    And,
+    Or,
+    Eq,
+    NotEq,
+    LeftParen,
+    RightParen,
+}
 
-        match (or, and) {
-            (None, None) => CheckCondition::Single(condition),
-            (Some((pos, _)), None) => {
-                CheckCondition::Or(&condition[..pos], &condition[pos + 2..])
+fn tokenize(condition: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let bytes = condition.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
             }
-            (None, Some((pos, _))) => {
-                CheckCondition::And(&condition[..pos], &condition[pos + 2..])
+            b'(' => {
+                tokens.push(Token::LeftParen);
+                i += 1;
             }
-            (Some((or_pos, _)), Some((and_pos, _))) => {
-                if or_pos < and_pos {
-                    CheckCondition::Or(
-                        &condition[..or_pos],
-                        &condition[or_pos + 2..],
-                    )
-                } else {
-                    CheckCondition::And(
-                        &condition[..and_pos],
-                        &condition[and_pos + 2..],
+            b')' => {
+                tokens.push(Token::RightParen);
+                i += 1;
+            }
+            b'&' if next == Some(b'&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            b'|' if next == Some(b'|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            b'=' if next == Some(b'=') => {
+                tokens.push(Token::Eq);
+                i += 2;
+            }
+            b'!' if next == Some(b'=') => {
+                tokens.push(Token::NotEq);
+                i += 2;
+            }
+            b'!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            b'"' | b'\'' => {
+                let start = i + 1;
+                let end = condition[start..].find(c as char)? + start;
+                tokens.push(Token::Ident(&condition[start..end]));
+                i = end + 1;
+            }
+            _ => {
+                let start = i;
+                while i < bytes.len()
+                    && !matches!(
+                        bytes[i],
+                        b' ' | b'\t'
+                            | b'\n'
+                            | b'\r'
+                            | b'('
+                            | b')'
+                            | b'&'
+                            | b'|'
+                            | b'='
+                            | b'!'
+                            | b'"'
+                            | b'\''
                     )
+                {
+                    i += 1;
+                }
+                if start == i {
+                    // a lone `&`, `|` or `=`
+                    return None;
+                }
+                tokens.push(Token::Ident(&condition[start..i]));
+            }
+        }
+    }
+    Some(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Option<ConditionExpr<'a>> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(Token::Or) {
+            self.next();
+            let right = self.parse_and()?;
+            left = ConditionExpr::Or(Box::new(left), Box::new(right));
+        }
+        Some(left)
+    }
+
+    fn parse_and(&mut self) -> Option<ConditionExpr<'a>> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(Token::And) {
+            self.next();
+            let right = self.parse_unary()?;
+            left = ConditionExpr::And(Box::new(left), Box::new(right));
+        }
+        Some(left)
+    }
+
+    fn parse_unary(&mut self) -> Option<ConditionExpr<'a>> {
+        if self.peek() == Some(Token::Not) {
+            self.next();
+            let expr = self.parse_unary()?;
+            return Some(ConditionExpr::Not(Box::new(expr)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Option<ConditionExpr<'a>> {
+        match self.next()? {
+            Token::LeftParen => {
+                let expr = self.parse_or()?;
+                if self.next()? != Token::RightParen {
+                    return None;
                 }
+                Some(expr)
+            }
+            Token::Ident(key) => match self.peek() {
+                Some(Token::Eq) => {
+                    self.next();
+                    match self.next()? {
+                        Token::Ident(value) => {
+                            Some(ConditionExpr::Equals(key, value))
+                        }
+                        _ => None,
+                    }
+                }
+                Some(Token::NotEq) => {
+                    self.next();
+                    match self.next()? {
+                        Token::Ident(value) => {
+                            Some(ConditionExpr::NotEquals(key, value))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => Some(ConditionExpr::Key(key)),
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ConditionExpr<'a> {
+    /// Parse a `when` clause, returning `None` if it is malformed.
+    pub(super) fn parse(condition: &'a str) -> Option<Self> {
+        let tokens = tokenize(condition)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if parser.peek().is_some() {
+            return None;
+        }
+        Some(expr)
+    }
+
+    /// Evaluate the expression against the current focus, and `panel_visible`
+    /// against the panels which are shown, whatever has the focus.
+    /// Unknown keys evaluate to `false`.
+    pub(super) fn eval<T: KeyPressFocus>(
+        &self,
+        check: &T,
+        visible_panels: &im::HashSet<PanelKind>,
+    ) -> bool {
+        let context_matches = |key: ContextKey, value: &str| match key {
+            ContextKey::PanelVisible => PanelKind::from_str(value)
+                .map(|kind| visible_panels.contains(&kind))
+                .unwrap_or(false),
+            _ => check.check_context(key, value),
+        };
+        match self {
+            ConditionExpr::Key(key) => Condition::from_str(key)
+                .map(|condition| check.check_condition(condition))
+                .unwrap_or(false),
+            ConditionExpr::Equals(key, value) => ContextKey::from_str(key)
+                .map(|key| context_matches(key, value))
+                .unwrap_or(false),
+            ConditionExpr::NotEquals(key, value) => ContextKey::from_str(key)
+                .map(|key| !context_matches(key, value))
+                .unwrap_or(false),
+            ConditionExpr::Not(expr) => !expr.eval(check, visible_panels),
+            ConditionExpr::And(left, right) => {
+                left.eval(check, visible_panels) && right.eval(check, visible_panels)
+            }
+            ConditionExpr::Or(left, right) => {
+                left.eval(check, visible_panels) || right.eval(check, visible_panels)
             }
         }
     }
@@ -49,6 +257,12 @@ pub enum Condition {
     PaletteFocus,
     #[strum(serialize = "completion_focus")]
     CompletionFocus,
+    /// The completion is shown, and enter accepts its item
+    #[strum(serialize = "completion_accepts_enter")]
+    CompletionAcceptsEnter,
+    /// The completion is shown, and tab accepts its item
+    #[strum(serialize = "completion_accepts_tab")]
+    CompletionAcceptsTab,
     #[strum(serialize = "modal_focus")]
     ModalFocus,
     #[strum(serialize = "in_snippet")]
@@ -59,6 +273,8 @@ pub enum Condition {
     SourceControlFocus,
     #[strum(serialize = "panel_focus")]
     PanelFocus,
+    #[strum(serialize = "file_explorer_focus")]
+    FileExplorerFocus,
     #[strum(serialize = "rename_focus")]
     RenameFocus,
     #[strum(serialize = "search_active")]
@@ -67,6 +283,42 @@ pub enum Condition {
     SearchFocus,
     #[strum(serialize = "replace_focus")]
     ReplaceFocus,
+    /// The editor has at least one non-empty selection
+    #[strum(serialize = "has_selection")]
+    HasSelection,
+    /// Modal (vim-like) editing is enabled
+    #[strum(serialize = "modal_editing")]
+    ModalEditing,
+    /// The completion, the signature or the hover is shown
+    #[strum(serialize = "popup_visible")]
+    PopupVisible,
+}
+
+/// Context keys which carry a value and are compared with `==` or `!=`
+/// in a `when` clause, e.g. `language_id == rust` or `mode != insert`.
+#[derive(EnumString, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ContextKey {
+    /// The language of the focused document, e.g. `rust` or `markdown`
+    #[strum(serialize = "language_id")]
+    LanguageId,
+    /// The current editing mode: `normal`, `insert`, `visual` or `terminal`
+    #[strum(serialize = "mode")]
+    Mode,
+    /// A panel which is currently shown, e.g. `terminal` or `file_explorer`,
+    /// which is checked against the panels rather than the focus
+    #[strum(serialize = "panel_visible")]
+    PanelVisible,
+}
+
+/// Whether `mode` matches the value of a `mode == ...` comparison.
+pub fn mode_matches(mode: Mode, value: &str) -> bool {
+    let name = match mode {
+        Mode::Normal => "normal",
+        Mode::Insert => "insert",
+        Mode::Visual => "visual",
+        Mode::Terminal => "terminal",
+    };
+    name.eq_ignore_ascii_case(value)
 }
 
 #[cfg(test)]
@@ -74,11 +326,15 @@ mod test {
     use floem::glazier::Modifiers;
     use lapce_core::mode::Mode;
 
-    use super::Condition;
-    use crate::keypress::{condition::CheckCondition, KeyPressData, KeyPressFocus};
+    use super::{Condition, ContextKey};
+    use crate::{
+        keypress::{condition::ConditionExpr, KeyPressData, KeyPressFocus},
+        panel::kind::PanelKind,
+    };
 
     struct MockFocus {
         accepted_conditions: &'static [Condition],
+        language: &'static str,
     }
 
     impl KeyPressFocus for MockFocus {
@@ -86,6 +342,13 @@ fn check_condition(&self, condition: Condition) -> bool {
             self.accepted_conditions.contains(&condition)
         }
 
+        fn check_context(&self, key: ContextKey, value: &str) -> bool {
+            match key {
+                ContextKey::LanguageId => self.language == value,
+                _ => false,
+            }
+        }
+
         fn get_mode(&self) -> Mode {
             unimplemented!()
         }
@@ -104,30 +367,56 @@ fn receive_char(&self, _c: &str) {
         }
     }
 
+    fn key(key: &str) -> Box<ConditionExpr> {
+        Box::new(ConditionExpr::Key(key))
+    }
+
     #[test]
     fn test_parse() {
         assert_eq!(
-            CheckCondition::Or("foo", "bar"),
-            CheckCondition::parse_first("foo||bar")
+            Some(ConditionExpr::Or(key("foo"), key("bar"))),
+            ConditionExpr::parse("foo||bar")
+        );
+        assert_eq!(
+            Some(ConditionExpr::And(key("foo"), key("bar"))),
+            ConditionExpr::parse("foo&&bar")
+        );
+        assert_eq!(
+            Some(ConditionExpr::Or(
+                Box::new(ConditionExpr::And(key("foo"), key("bar"))),
+                key("baz")
+            )),
+            ConditionExpr::parse("foo && bar || baz")
         );
         assert_eq!(
-            CheckCondition::And("foo", "bar"),
-            CheckCondition::parse_first("foo&&bar")
+            Some(ConditionExpr::And(
+                key("foo"),
+                Box::new(ConditionExpr::Or(key("bar"), key("baz")))
+            )),
+            ConditionExpr::parse("foo && (bar || baz)")
         );
         assert_eq!(
-            CheckCondition::And("foo", "bar||baz"),
-            CheckCondition::parse_first("foo&&bar||baz")
+            Some(ConditionExpr::And(
+                Box::new(ConditionExpr::Not(key("foo"))),
+                Box::new(ConditionExpr::Equals("language_id", "rust"))
+            )),
+            ConditionExpr::parse("!foo && language_id == rust")
         );
         assert_eq!(
-            CheckCondition::And("foo ", " bar || baz"),
-            CheckCondition::parse_first("foo && bar || baz")
+            Some(ConditionExpr::NotEquals("language_id", "plain text")),
+            ConditionExpr::parse("language_id != \"plain text\"")
         );
+        assert_eq!(None, ConditionExpr::parse("foo &&"));
+        assert_eq!(None, ConditionExpr::parse("(foo || bar"));
+        assert_eq!(None, ConditionExpr::parse("foo & bar"));
+        assert_eq!(None, ConditionExpr::parse("foo bar"));
     }
 
     #[test]
     fn test_check_condition() {
         let focus = MockFocus {
             accepted_conditions: &[Condition::EditorFocus, Condition::ListFocus],
+            language: "rust",
         };
 
         let test_cases = [
@@ -144,12 +433,24 @@ fn test_check_condition() {
             ("editor_focus && list_focus || baz", true),
             ("editor_focus && list_focus && baz", false),
             ("editor_focus && list_focus && !baz", true),
+            ("!(editor_focus && list_focus)", false),
+            ("!editor_focus || (list_focus && !baz)", true),
+            ("language_id == rust", true),
+            ("language_id == markdown", false),
+            ("language_id != markdown && editor_focus", true),
+            ("baz == rust", false),
+            ("editor_focus &&", false),
+            ("panel_visible == terminal", true),
+            ("panel_visible == file_explorer", false),
+            ("panel_visible != file_explorer && editor_focus", true),
+            ("panel_visible == foo", false),
         ];
 
+        let visible_panels = im::hashset![PanelKind::Terminal, PanelKind::Plugin];
         for (condition, should_accept) in test_cases.into_iter() {
             assert_eq!(
                 should_accept,
-                KeyPressData::check_condition(condition, &focus),
+                KeyPressData::check_condition(condition, &focus, &visible_panels),
                 "Condition check failed. Condition: {condition}. Expected result: {should_accept}",
             );
         }
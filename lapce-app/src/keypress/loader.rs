@@ -3,7 +3,7 @@
 use lapce_core::mode::Modes;
 use tracing::{debug, error};
 
-use super::{keymap::KeyMap, press::KeyPress};
+use super::{condition::ConditionExpr, keymap::KeyMap, press::KeyPress};
 
 pub struct KeyMapLoader {
     keymaps: IndexMap<Vec<KeyPress>, Vec<KeyMap>>,
@@ -111,13 +111,20 @@ fn get_keymap(
             return Ok(None);
         }
 
+        let when = toml_keymap
+            .get("when")
+            .and_then(|w| w.as_str())
+            .map(|w| w.to_string());
+        if let Some(when) = when.as_deref() {
+            if ConditionExpr::parse(when).is_none() {
+                return Err(anyhow!("invalid when condition \"{when}\" for {key}"));
+            }
+        }
+
         Ok(Some(KeyMap {
             key: KeyPress::parse(key),
             modes,
-            when: toml_keymap
-                .get("when")
-                .and_then(|w| w.as_str())
-                .map(|w| w.to_string()),
+            when,
             command: toml_keymap
                 .get("command")
                 .and_then(|c| c.as_str())
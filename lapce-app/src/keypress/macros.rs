@@ -0,0 +1,274 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use lapce_core::directory::Directory;
+use serde::{Deserialize, Serialize};
+
+use crate::command::LapceCommand;
+
+/// The prefix of the commands which play a saved macro in the keymaps, like
+/// `command = "macro.wrap-in-quotes"`.
+pub const MACRO_COMMAND_PREFIX: &str = "macro.";
+
+/// A step of a recorded macro, which is either a command that was run by a key
+/// or the text that was typed.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MacroStep {
+    Command {
+        command: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        count: Option<usize>,
+    },
+    Text {
+        text: String,
+    },
+}
+
+impl MacroStep {
+    /// Append the step to the macro, where typed text is merged with the text
+    /// that was typed right before it.
+    pub fn push(steps: &mut Vec<MacroStep>, step: MacroStep) {
+        if let (Some(MacroStep::Text { text }), MacroStep::Text { text: new }) =
+            (steps.last_mut(), &step)
+        {
+            text.push_str(new);
+            return;
+        }
+        steps.push(step);
+    }
+}
+
+/// The macro which is being recorded, and the ones which can be played.
+#[derive(Clone, Default)]
+pub struct MacroRecorder {
+    /// The steps recorded so far, or `None` when nothing is being recorded
+    pub recording: Option<Vec<MacroStep>>,
+    /// The macro which was recorded last
+    pub last: Vec<MacroStep>,
+    /// The saved macros by their name
+    pub saved: IndexMap<String, Vec<MacroStep>>,
+}
+
+impl MacroRecorder {
+    pub fn new() -> Self {
+        Self {
+            recording: None,
+            last: Vec::new(),
+            saved: load_macros(),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Start recording, or stop and keep the recorded steps as the last macro.
+    pub fn toggle(&mut self) {
+        match self.recording.take() {
+            Some(steps) => {
+                if !steps.is_empty() {
+                    self.last = steps;
+                }
+            }
+            None => self.recording = Some(Vec::new()),
+        }
+    }
+
+    pub fn record(&mut self, step: MacroStep) {
+        if let Some(steps) = self.recording.as_mut() {
+            MacroStep::push(steps, step);
+        }
+    }
+
+    /// Make the commands the last macro, as if they were recorded, e.g. the ones
+    /// of the command history. The steps of a macro run commands by their name,
+    /// so the commands with arguments are left out rather than run without them.
+    /// Returns whether any command was left to make the macro of.
+    pub fn set_last_commands(&mut self, commands: &[LapceCommand]) -> bool {
+        let steps: Vec<MacroStep> = commands
+            .iter()
+            .filter(|cmd| cmd.data.is_none())
+            .map(|cmd| MacroStep::Command {
+                command: cmd.kind.str().to_string(),
+                count: None,
+            })
+            .collect();
+        if steps.is_empty() {
+            return false;
+        }
+        self.last = steps;
+        true
+    }
+
+    /// Save the last macro under the name, which replaces a macro with the same
+    /// name.
+    pub fn save(&mut self, name: &str) -> Result<()> {
+        if self.last.is_empty() {
+            return Err(anyhow::anyhow!("no macro was recorded"));
+        }
+        // Reload the file first, so that the macros edited in it aren't lost
+        let mut saved = load_macros();
+        saved.insert(name.to_string(), self.last.clone());
+        save_macros(&saved)?;
+        self.saved = saved;
+        Ok(())
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct MacrosFile {
+    #[serde(default)]
+    macros: IndexMap<String, Vec<MacroStep>>,
+}
+
+/// The file of the saved macros, which are kept next to the keymaps.
+fn macros_file() -> Option<PathBuf> {
+    Some(Directory::config_directory()?.join("macros.toml"))
+}
+
+/// The saved macros, by their name.
+fn load_macros() -> IndexMap<String, Vec<MacroStep>> {
+    macros_file()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| parse_macros(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Save the macros, which replaces the ones in the file.
+fn save_macros(macros: &IndexMap<String, Vec<MacroStep>>) -> Result<()> {
+    let path = macros_file()
+        .ok_or_else(|| anyhow::anyhow!("can't find the config directory"))?;
+    let content = toml_edit::easy::to_string(&MacrosFile {
+        macros: macros.clone(),
+    })?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn parse_macros(content: &str) -> Result<IndexMap<String, Vec<MacroStep>>> {
+    let file: MacrosFile = toml_edit::easy::from_str(content)?;
+    Ok(file.macros)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::command::{CommandKind, LapceWorkbenchCommand};
+
+    #[test]
+    fn test_push_merges_text() {
+        let mut steps = Vec::new();
+        MacroStep::push(
+            &mut steps,
+            MacroStep::Text {
+                text: "a".to_string(),
+            },
+        );
+        MacroStep::push(
+            &mut steps,
+            MacroStep::Text {
+                text: "b".to_string(),
+            },
+        );
+        MacroStep::push(
+            &mut steps,
+            MacroStep::Command {
+                command: "line_end".to_string(),
+                count: None,
+            },
+        );
+        MacroStep::push(
+            &mut steps,
+            MacroStep::Text {
+                text: "c".to_string(),
+            },
+        );
+        assert_eq!(
+            steps,
+            vec![
+                MacroStep::Text {
+                    text: "ab".to_string()
+                },
+                MacroStep::Command {
+                    command: "line_end".to_string(),
+                    count: None
+                },
+                MacroStep::Text {
+                    text: "c".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_last_commands() {
+        let command = |cmd: LapceWorkbenchCommand, data| LapceCommand {
+            kind: CommandKind::Workbench(cmd),
+            data,
+        };
+        let mut recorder = MacroRecorder::default();
+        assert!(recorder.set_last_commands(&[
+            command(LapceWorkbenchCommand::SaveAll, None),
+            command(
+                LapceWorkbenchCommand::OpenFile,
+                Some(json!({ "path": "/src/main.rs" })),
+            ),
+            command(LapceWorkbenchCommand::CloseFolder, None),
+        ]));
+        let steps = vec![
+            MacroStep::Command {
+                command: "save_all".to_string(),
+                count: None,
+            },
+            MacroStep::Command {
+                command: "close_folder".to_string(),
+                count: None,
+            },
+        ];
+        assert_eq!(recorder.last, steps);
+
+        // Nothing is left of only commands with arguments, which keeps the
+        // last macro
+        assert!(!recorder.set_last_commands(&[command(
+            LapceWorkbenchCommand::OpenFile,
+            Some(json!({ "path": "/src/lib.rs" })),
+        )]));
+        assert!(!recorder.set_last_commands(&[]));
+        assert_eq!(recorder.last, steps);
+    }
+
+    #[test]
+    fn test_parse_macros() {
+        let macros = parse_macros(
+            r#"
+[macros]
+quote = [
+    { command = "line_start" },
+    { text = "\"" },
+    { command = "down", count = 2 },
+]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            macros.get("quote").unwrap(),
+            &vec![
+                MacroStep::Command {
+                    command: "line_start".to_string(),
+                    count: None
+                },
+                MacroStep::Text {
+                    text: "\"".to_string()
+                },
+                MacroStep::Command {
+                    command: "down".to_string(),
+                    count: Some(2)
+                },
+            ]
+        );
+    }
+}
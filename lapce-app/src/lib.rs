@@ -1,11 +1,19 @@
+pub mod accessibility;
 pub mod app;
+pub mod clipboard;
 pub mod code_action;
+pub mod collab;
+pub mod color_picker;
 pub mod command;
 pub mod completion;
 pub mod config;
 pub mod db;
 pub mod debug;
+pub mod debug_console;
+pub mod debug_memory;
+pub mod deep_link;
 pub mod doc;
+pub mod edit_preview;
 pub mod editor;
 pub mod editor_tab;
 pub mod file_explorer;
@@ -13,24 +21,37 @@
 pub mod focus_text;
 pub mod global_search;
 pub mod history;
+pub mod hover;
+pub mod http_client;
 pub mod id;
 pub mod keypress;
 pub mod listener;
+pub mod log;
 pub mod main_split;
+pub mod markdown;
+pub mod notification;
 pub mod palette;
 pub mod panel;
 pub mod plugin;
+pub mod popup;
+pub mod profile;
 pub mod proxy;
 pub mod rename;
 pub mod settings;
+pub mod signature;
 pub mod snippet;
 pub mod source_control;
+pub mod startup;
+pub mod status;
 pub mod terminal;
+pub mod test_explorer;
 pub mod text_area;
 pub mod text_input;
 pub mod title;
+pub mod todo;
 pub mod update;
 pub mod wave;
 pub mod window;
 pub mod window_tab;
 pub mod workspace;
+pub mod workspace_edit;
@@ -0,0 +1,468 @@
+//! The log of the app and its proxy, which is kept in memory for the log panel
+//! along with being written to the log file, and the levels of its modules.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::Mutex,
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{
+        create_effect, create_memo, create_rw_signal, Memo, RwSignal, Scope,
+        SignalGet, SignalSet, SignalUpdate, SignalWith, SignalWithUntracked,
+    },
+};
+use lapce_core::mode::Mode;
+use lapce_proxy::logging::parse_log_levels;
+use once_cell::sync::Lazy;
+use tracing::{
+    field::{Field, Visit},
+    metadata::LevelFilter,
+    Collect, Event, Level,
+};
+use tracing_subscriber::{
+    filter::Targets,
+    reload::Handle,
+    subscribe::{Context, Subscribe},
+};
+
+use crate::{
+    command::{CommandExecuted, CommandKind},
+    config::color::LapceColor,
+    editor::EditorData,
+    id::{EditorId, WindowTabId},
+    keypress::{condition::Condition, KeyPressFocus},
+    window_tab::CommonData,
+};
+
+/// How many of the latest records are kept for the log panel
+const MAX_RECORDS: usize = 2000;
+
+/// The target of the log of a remote proxy, which left out what its levels
+/// don't enable before it was sent
+pub const REMOTE_PROXY_TARGET: &str = "lapce_proxy::remote";
+
+/// The levels which the log is at, and the handle which changes them
+static LEVELS: Lazy<Mutex<Option<LogLevels>>> = Lazy::new(|| Mutex::new(None));
+
+static LOG: Lazy<Mutex<LogBuffer>> = Lazy::new(|| Mutex::new(LogBuffer::default()));
+
+struct LogLevels {
+    handle: Handle<Targets>,
+    /// The level of the modules which the directives don't give, which is the
+    /// one of `--log-level`
+    default: LevelFilter,
+    /// The directives of the settings of each window tab, as the workspaces
+    /// can have settings of their own
+    directives: HashMap<WindowTabId, String>,
+}
+
+impl LogLevels {
+    fn reload(&self) {
+        let levels = merge_log_levels(
+            self.directives
+                .values()
+                .map(|directives| log_levels(directives, self.default)),
+            self.default,
+        )
+        .with_target(REMOTE_PROXY_TARGET, LevelFilter::TRACE);
+        if let Err(e) = self.handle.reload(levels) {
+            tracing::error!("Failed to change the log levels: {e}");
+        }
+    }
+}
+
+#[derive(Default)]
+struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    next_id: u64,
+    /// The log panels, which are told when there are new records
+    listeners: Vec<Sender<()>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    pub id: u64,
+    pub time: chrono::DateTime<chrono::Local>,
+    pub level: Level,
+    /// The module which the record is from
+    pub target: String,
+    pub message: String,
+}
+
+impl LogRecord {
+    pub fn color(&self) -> &'static str {
+        match self.level {
+            Level::ERROR => LapceColor::LAPCE_ERROR,
+            Level::WARN => LapceColor::LAPCE_WARN,
+            _ => LapceColor::EDITOR_DIM,
+        }
+    }
+}
+
+/// The levels of the log from the `core.log-levels` setting, where `default` is
+/// the level of the modules which it doesn't give.
+pub fn log_levels(directives: &str, default: LevelFilter) -> Targets {
+    parse_log_levels(directives, default)
+        .with_target(REMOTE_PROXY_TARGET, LevelFilter::TRACE)
+}
+
+/// The levels which log what any of the levels do, as the log is shared by the
+/// window tabs.
+fn merge_log_levels(
+    levels: impl Iterator<Item = Targets>,
+    default: LevelFilter,
+) -> Targets {
+    let levels: Vec<Targets> = levels.collect();
+    // The level of a module in the levels, from the module or the closest of
+    // the ones it's in which is given
+    let level_of = |targets: &Targets, target: &str| {
+        [
+            Level::TRACE,
+            Level::DEBUG,
+            Level::INFO,
+            Level::WARN,
+            Level::ERROR,
+        ]
+        .into_iter()
+        .find(|level| targets.would_enable(target, level))
+        .map(LevelFilter::from_level)
+        .unwrap_or(LevelFilter::OFF)
+    };
+
+    let mut merged = HashMap::new();
+    for targets in levels.iter() {
+        for (target, _) in targets.iter() {
+            let level = levels
+                .iter()
+                .map(|targets| level_of(targets, target))
+                .max()
+                .unwrap_or(default);
+            merged.insert(target.to_string(), level);
+        }
+    }
+    let default = levels
+        .iter()
+        .filter_map(|targets| targets.default_level())
+        .max()
+        .unwrap_or(default);
+    Targets::new().with_default(default).with_targets(merged)
+}
+
+/// Keep the handle which changes the levels of the log.
+pub fn init_log_levels(handle: Handle<Targets>, default: LevelFilter) {
+    *LEVELS.lock().unwrap() = Some(LogLevels {
+        handle,
+        default,
+        directives: HashMap::new(),
+    });
+}
+
+/// Set the levels of the modules which a window tab's settings have, from
+/// directives like `warn,lapce_proxy::plugin=debug`. A module is logged at the
+/// most verbose of the levels of the window tabs.
+pub fn set_log_levels(window_tab_id: WindowTabId, directives: &str) {
+    if let Some(levels) = LEVELS.lock().unwrap().as_mut() {
+        if levels.directives.get(&window_tab_id).map(|d| d.as_str())
+            != Some(directives)
+        {
+            levels
+                .directives
+                .insert(window_tab_id, directives.to_string());
+            levels.reload();
+        }
+    }
+}
+
+/// Stop using the levels of a window tab which was closed.
+pub fn remove_log_levels(window_tab_id: WindowTabId) {
+    if let Some(levels) = LEVELS.lock().unwrap().as_mut() {
+        if levels.directives.remove(&window_tab_id).is_some() {
+            levels.reload();
+        }
+    }
+}
+
+/// Set the level of the modules which the directives don't give.
+pub fn set_default_log_level(default: LevelFilter) {
+    if let Some(levels) = LEVELS.lock().unwrap().as_mut() {
+        levels.default = default;
+        levels.reload();
+    }
+}
+
+/// The records of the log after the one with the id, oldest first.
+fn records_after(id: Option<u64>) -> Vec<LogRecord> {
+    let log = LOG.lock().unwrap();
+    log.records
+        .iter()
+        .filter(|record| id.map(|id| record.id > id).unwrap_or(true))
+        .cloned()
+        .collect()
+}
+
+/// A channel which gets a message when there are new records.
+fn subscribe() -> Receiver<()> {
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    LOG.lock().unwrap().listeners.push(tx);
+    rx
+}
+
+/// Keep the events which the levels of the log enable for the log panel.
+pub struct CaptureLogs;
+
+impl<C: Collect> Subscribe<C> for CaptureLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let meta = event.metadata();
+        let mut visitor = RecordVisitor {
+            message: String::new(),
+            target: None,
+        };
+        event.record(&mut visitor);
+
+        let mut log = LOG.lock().unwrap();
+        let id = log.next_id;
+        log.next_id += 1;
+        if log.records.len() >= MAX_RECORDS {
+            log.records.pop_front();
+        }
+        log.records.push_back(LogRecord {
+            id,
+            time: chrono::Local::now(),
+            level: *meta.level(),
+            target: visitor.target.unwrap_or_else(|| meta.target().to_string()),
+            message: visitor.message,
+        });
+        // A full channel already has a message which the panel hasn't got to
+        log.listeners
+            .retain(|tx| !matches!(tx.try_send(()), Err(e) if e.is_disconnected()));
+    }
+}
+
+/// The message of an event, followed by its other fields, and the module of the
+/// proxy which an event forwarded from it is from.
+struct RecordVisitor {
+    message: String,
+    target: Option<String>,
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "proxy_target" {
+            self.target = Some(value.to_string());
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        use std::fmt::Write;
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.message, " {}={value:?}", field.name());
+        }
+    }
+}
+
+/// Log a message which a proxy sent, which a remote one only sends when its
+/// levels enable it.
+pub fn log_proxy_message(level: &str, target: &str, message: &str) {
+    let target = if target.is_empty() {
+        "lapce_proxy"
+    } else {
+        target
+    };
+    match level {
+        "ERROR" => tracing::error!(
+            target: REMOTE_PROXY_TARGET,
+            proxy_target = target,
+            "{message}"
+        ),
+        "WARN" => tracing::warn!(
+            target: REMOTE_PROXY_TARGET,
+            proxy_target = target,
+            "{message}"
+        ),
+        "INFO" => tracing::info!(
+            target: REMOTE_PROXY_TARGET,
+            proxy_target = target,
+            "{message}"
+        ),
+        "DEBUG" => tracing::debug!(
+            target: REMOTE_PROXY_TARGET,
+            proxy_target = target,
+            "{message}"
+        ),
+        _ => tracing::trace!(
+            target: REMOTE_PROXY_TARGET,
+            proxy_target = target,
+            "{message}"
+        ),
+    }
+}
+
+/// The log panel of a window tab.
+#[derive(Clone)]
+pub struct LogData {
+    /// The records since the panel was last cleared, oldest first
+    pub records: RwSignal<im::Vector<LogRecord>>,
+    /// The most verbose level which is shown
+    pub level: RwSignal<Level>,
+    pub filter_editor: EditorData,
+    /// The records which the level and the filter let through
+    pub filtered: Memo<im::Vector<LogRecord>>,
+    pub common: CommonData,
+}
+
+impl KeyPressFocus for LogData {
+    fn get_mode(&self) -> Mode {
+        Mode::Insert
+    }
+
+    fn check_condition(&self, condition: Condition) -> bool {
+        matches!(condition, Condition::PanelFocus)
+    }
+
+    fn run_command(
+        &self,
+        command: &crate::command::LapceCommand,
+        count: Option<usize>,
+        mods: floem::glazier::Modifiers,
+    ) -> CommandExecuted {
+        match &command.kind {
+            CommandKind::Workbench(_) => {}
+            CommandKind::Focus(_) => {}
+            CommandKind::Edit(_)
+            | CommandKind::Move(_)
+            | CommandKind::MultiSelection(_) => {
+                self.filter_editor.run_command(command, count, mods);
+            }
+            CommandKind::MotionMode(_) => {}
+        }
+        CommandExecuted::Yes
+    }
+
+    fn receive_char(&self, c: &str) {
+        self.filter_editor.receive_char(c);
+    }
+}
+
+impl LogData {
+    pub fn new(cx: Scope, common: CommonData) -> Self {
+        let records = create_rw_signal(cx, im::Vector::from(records_after(None)));
+        let level = create_rw_signal(cx, Level::TRACE);
+        let filter_editor =
+            EditorData::new_local(cx, EditorId::next(), common.clone());
+
+        let doc = filter_editor.doc;
+        let filtered = create_memo(cx, move |_| {
+            let filter = doc.with(|doc| doc.buffer().to_string()).to_lowercase();
+            let level = level.get();
+            records.with(|records| {
+                records
+                    .iter()
+                    .filter(|record| {
+                        record.level <= level
+                            && (filter.is_empty()
+                                || record.message.to_lowercase().contains(&filter)
+                                || record.target.to_lowercase().contains(&filter))
+                    })
+                    .cloned()
+                    .collect()
+            })
+        });
+
+        // The id of the last record which the panel got, which is kept apart
+        // from the records since they're cleared
+        let last = records.with_untracked(|records| records.last().map(|r| r.id));
+        let changed = create_signal_from_channel(cx, subscribe());
+        create_effect(cx, move |prev: Option<Option<u64>>| {
+            let last = prev.unwrap_or(last);
+            if changed.get().is_none() {
+                return last;
+            }
+            let new = records_after(last);
+            let latest = new.last().map(|record| record.id).or(last);
+            records.update(|records| {
+                records.extend(new);
+                while records.len() > MAX_RECORDS {
+                    records.pop_front();
+                }
+            });
+            latest
+        });
+
+        Self {
+            records,
+            level,
+            filter_editor,
+            filtered,
+            common,
+        }
+    }
+
+    /// Show the next more verbose level, or only the errors again after the
+    /// most verbose one.
+    pub fn cycle_level(&self) {
+        self.level.update(|level| {
+            *level = match *level {
+                Level::ERROR => Level::WARN,
+                Level::WARN => Level::INFO,
+                Level::INFO => Level::DEBUG,
+                Level::DEBUG => Level::TRACE,
+                _ => Level::ERROR,
+            };
+        });
+    }
+
+    pub fn clear(&self) {
+        self.records.set(im::Vector::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_log_levels() {
+        let levels = merge_log_levels(
+            [
+                parse_log_levels(
+                    "warn,lapce_proxy::plugin=debug",
+                    LevelFilter::ERROR,
+                ),
+                parse_log_levels("lapce_app=info", LevelFilter::ERROR),
+            ]
+            .into_iter(),
+            LevelFilter::ERROR,
+        );
+        assert_eq!(levels.default_level(), Some(LevelFilter::WARN));
+        assert!(levels.would_enable("lapce_proxy::plugin::lsp", &Level::DEBUG));
+        assert!(!levels.would_enable("lapce_proxy::plugin::lsp", &Level::TRACE));
+        assert!(levels.would_enable("lapce_app::log", &Level::INFO));
+        assert!(!levels.would_enable("lapce_app::log", &Level::DEBUG));
+        assert!(levels.would_enable("lapce_core", &Level::WARN));
+        assert!(!levels.would_enable("lapce_core", &Level::INFO));
+
+        // A module which a window tab turns off is still logged for the others
+        let levels = merge_log_levels(
+            [
+                parse_log_levels("info,lapce_app=off", LevelFilter::ERROR),
+                parse_log_levels("", LevelFilter::ERROR),
+            ]
+            .into_iter(),
+            LevelFilter::ERROR,
+        );
+        assert!(levels.would_enable("lapce_app", &Level::ERROR));
+        assert!(!levels.would_enable("lapce_app", &Level::WARN));
+        assert!(levels.would_enable("lapce_core", &Level::INFO));
+
+        let levels = merge_log_levels(std::iter::empty(), LevelFilter::ERROR);
+        assert_eq!(levels.default_level(), Some(LevelFilter::ERROR));
+    }
+}
@@ -1,10 +1,14 @@
 use std::{
-    collections::HashMap,
+    ops::Range,
     path::{Path, PathBuf},
+    thread,
+    time::Duration,
 };
 
+use anyhow::anyhow;
+use crossbeam_channel::Sender;
 use floem::{
-    ext_event::create_ext_action,
+    ext_event::{create_ext_action, create_signal_from_channel},
     glazier::KeyEvent,
     peniko::kurbo::{Point, Rect, Vec2},
     reactive::{
@@ -13,20 +17,32 @@
         SignalWithUntracked,
     },
 };
+use fluent_bundle::FluentValue;
 use itertools::Itertools;
 use lapce_core::{
-    buffer::rope_text::RopeText, cursor::Cursor, selection::Selection,
+    buffer::{rope_text::RopeText, Buffer},
+    cursor::{Cursor, CursorMode},
+    editor::EditType,
+    selection::{SelRegion, Selection},
 };
 use lapce_rpc::{plugin::PluginId, proxy::ProxyResponse};
-use lapce_xi_rope::Rope;
+use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
-    CodeAction, CodeActionOrCommand, DiagnosticSeverity, DocumentChangeOperation,
-    DocumentChanges, OneOf, Position, TextEdit, Url, WorkspaceEdit,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionResponse,
+    DiagnosticSeverity, Position, TextEdit, WorkspaceEdit,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    doc::{DiagnosticData, Document, EditorDiagnostic},
+    code_action::source_action,
+    collab::is_guest_path,
+    command::InternalCommand,
+    doc::{
+        embedded::{embedded_path, locate_code, EmbeddedCode},
+        memory::DocumentMemory,
+        DiagnosticData, DocContent, Document, EditorDiagnostic,
+    },
+    edit_preview::{PreviewFile, PreviewHunk},
     editor::{
         location::{EditorLocation, EditorPosition},
         EditorData,
@@ -34,9 +50,14 @@
     editor_tab::{EditorTabChild, EditorTabData, EditorTabInfo},
     id::{EditorId, EditorTabId, SettingsId, SplitId},
     keypress::KeyPressData,
+    notification::{Notification, NotificationCommand, NotificationSeverity},
     window_tab::{CommonData, Focus, WindowTabData},
+    workspace_edit::{check_file_edit, file_edits, AppliedWorkspaceEdit, FileEdit},
 };
 
+/// How often the documents which weren't shown for a while are looked for
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SplitDirection {
     Vertical,
@@ -51,6 +72,18 @@ pub enum SplitMoveDirection {
     Left,
 }
 
+/// How the conflict between a file changed on disk and the unsaved changes of its
+/// buffer is resolved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileConflictResolution {
+    /// Drop the unsaved changes and load the file from disk
+    Reload,
+    /// Save the buffer over the file on disk
+    Overwrite,
+    /// Show the changes against the file on disk
+    Compare,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SplitContent {
     EditorTab(EditorTabId),
@@ -198,6 +231,8 @@ pub struct MainSplitData {
     pub replace_editor: EditorData,
     pub locations: RwSignal<im::Vector<EditorLocation>>,
     pub current_location: RwSignal<usize>,
+    /// The last workspace edit which was made, so that it can be undone
+    pub last_workspace_edit: RwSignal<Option<AppliedWorkspaceEdit>>,
     pub common: CommonData,
 }
 
@@ -213,6 +248,7 @@ pub fn new(cx: Scope, common: CommonData) -> Self {
         let locations = create_rw_signal(cx, im::Vector::new());
         let current_location = create_rw_signal(cx, 0);
         let diagnostics = create_rw_signal(cx, im::HashMap::new());
+        let last_workspace_edit = create_rw_signal(cx, None);
         let find_editor =
             EditorData::new_local(cx, EditorId::next(), common.clone());
         let replace_editor =
@@ -247,6 +283,35 @@ pub fn new(cx: Scope, common: CommonData) -> Self {
             });
         }
 
+        {
+            let (tx, rx) = crossbeam_channel::bounded(1);
+            thread::spawn(move || loop {
+                thread::sleep(EVICTION_INTERVAL);
+                if tx.send(()).is_err() {
+                    return;
+                }
+            });
+            let tick = create_signal_from_channel(cx, rx);
+            let config = common.config;
+            create_effect(cx, move |_| {
+                if tick.get().is_none() {
+                    return;
+                }
+                let minutes = config.with_untracked(|config| {
+                    config.editor.evict_unused_syntax_after
+                });
+                if minutes == 0 {
+                    return;
+                }
+                let unshown_for = Duration::from_secs(minutes * 60);
+                for doc in docs.get_untracked().values() {
+                    if doc.with_untracked(|doc| doc.can_evict_syntax(unshown_for)) {
+                        doc.update(|doc| doc.evict_syntax());
+                    }
+                }
+            });
+        }
+
         Self {
             scope: cx,
             root_split: SplitId::next(),
@@ -261,10 +326,22 @@ pub fn new(cx: Scope, common: CommonData) -> Self {
             diagnostics,
             locations,
             current_location,
+            last_workspace_edit,
             common,
         }
     }
 
+    /// An estimate of the bytes which each of the documents takes.
+    pub fn memory_usage(&self) -> Vec<(PathBuf, DocumentMemory)> {
+        self.docs.with_untracked(|docs| {
+            docs.iter()
+                .map(|(path, doc)| {
+                    (path.clone(), doc.with_untracked(|doc| doc.memory_usage()))
+                })
+                .collect()
+        })
+    }
+
     pub fn key_down(
         &self,
         key_event: &KeyEvent,
@@ -378,9 +455,11 @@ pub fn get_doc(&self, path: PathBuf) -> (RwSignal<Document>, bool) {
                 diagnostic_data,
                 self.common.find.clone(),
                 self.common.proxy.clone(),
+                self.common.collab.clone(),
                 self.common.config,
             );
             let doc = create_rw_signal(cx, doc);
+            Document::listen_syntax_updates(cx, doc);
             self.docs.update(|docs| {
                 docs.insert(path.clone(), doc);
             });
@@ -395,7 +474,9 @@ pub fn get_doc(&self, path: PathBuf) -> (RwSignal<Document>, bool) {
                     let find_result =
                         doc.with_untracked(|doc| doc.find_result.clone());
                     find_result.reset();
-                    Document::tigger_proxy_update(cx, doc, &proxy);
+                    if !is_guest_path(&path) {
+                        Document::tigger_proxy_update(cx, doc, &proxy);
+                    }
                     rev
                 });
             }
@@ -404,6 +485,20 @@ pub fn get_doc(&self, path: PathBuf) -> (RwSignal<Document>, bool) {
         }
     }
 
+    /// Jump to the location for a look at the file, like from a search result. The
+    /// editor is read-only until it's made writable, unless the file is open for
+    /// editing already.
+    pub fn preview_location(&self, location: EditorLocation) {
+        self.jump_to_location(location, None);
+        if let Some(editor) = self.active_editor.get_untracked() {
+            editor.with_untracked(|editor| {
+                if !editor.confirmed.get_untracked() {
+                    editor.read_only.set(true);
+                }
+            });
+        }
+    }
+
     pub fn go_to_location(
         &self,
         location: EditorLocation,
@@ -520,6 +615,7 @@ fn get_editor_or_new(
                         editor.cursor.set(Cursor::origin(
                             self.common.config.with_untracked(|c| c.core.modal),
                         ));
+                        editor.read_only.set(false);
                     });
                 }
                 editor_tab.update(|editor_tab| {
@@ -724,7 +820,7 @@ pub fn split(
         &self,
         direction: SplitDirection,
         editor_tab_id: EditorTabId,
-    ) -> Option<()> {
+    ) -> Option<EditorTabId> {
         let editor_tabs = self.editor_tabs.get_untracked();
         let editor_tab = editor_tabs.get(&editor_tab_id).copied()?;
 
@@ -741,7 +837,7 @@ pub fn split(
                 .map(|index| (index, split.children.len()))
         })?;
 
-        if split_direction == direction {
+        let new_editor_tab_id = if split_direction == direction {
             let new_editor_tab = editor_tab.with_untracked(|editor_tab| {
                 self.split_editor_tab(self.scope, split_id, editor_tab)
             })?;
@@ -752,6 +848,7 @@ pub fn split(
                     .children
                     .insert(index + 1, SplitContent::EditorTab(new_editor_tab_id));
             });
+            new_editor_tab_id
         } else if children_len == 1 {
             let new_editor_tab = editor_tab.with_untracked(|editor_tab| {
                 self.split_editor_tab(self.scope, split_id, editor_tab)
@@ -764,6 +861,7 @@ pub fn split(
                     .children
                     .push(SplitContent::EditorTab(new_editor_tab_id));
             });
+            new_editor_tab_id
         } else {
             let new_split_id = SplitId::next();
 
@@ -798,9 +896,10 @@ pub fn split(
             split.update(|split| {
                 split.children[index] = SplitContent::Split(new_split_id);
             });
-        }
+            new_editor_tab_id
+        };
 
-        Some(())
+        Some(new_editor_tab_id)
     }
 
     fn split_editor_tab(
@@ -1138,6 +1237,7 @@ pub fn editor_tab_child_close(
                 if let Some(editor) = removed_editor {
                     let editor = editor.get_untracked();
                     editor.save_doc_position(cx);
+                    self.close_embedded_code(editor.doc);
                 }
             }
             EditorTabChild::Settings(_) => {}
@@ -1170,7 +1270,7 @@ pub fn run_code_action(&self, plugin_id: PluginId, action: CodeActionOrCommand)
             CodeActionOrCommand::Command(_) => {}
             CodeActionOrCommand::CodeAction(action) => {
                 if let Some(edit) = action.edit.as_ref() {
-                    self.apply_workspace_edit(edit);
+                    self.apply_workspace_edit(edit, false);
                 } else {
                     self.resolve_code_action(plugin_id, action);
                 }
@@ -1178,11 +1278,65 @@ pub fn run_code_action(&self, plugin_id: PluginId, action: CodeActionOrCommand)
         }
     }
 
+    /// Run the code action of the kind for the whole of the file, like the one
+    /// which organizes its imports, when the file is open and has one.
+    pub fn run_source_action(&self, path: PathBuf, kind: CodeActionKind) {
+        let Some(doc) = self.docs.with_untracked(|docs| docs.get(&path).copied())
+        else {
+            return;
+        };
+        let Some((range, diagnostics)) = doc
+            .with_untracked(|doc| doc.loaded().then(|| doc.source_action_context()))
+        else {
+            return;
+        };
+
+        let main_split = self.clone();
+        let action_kind = kind.clone();
+        let send = create_ext_action(
+            self.scope,
+            move |(plugin_id, resp): (PluginId, CodeActionResponse)| {
+                if let Some(action) = source_action(&resp, &action_kind) {
+                    main_split.run_code_action(plugin_id, action.clone());
+                }
+            },
+        );
+        self.common.proxy.get_source_actions(
+            path,
+            range,
+            vec![kind],
+            diagnostics,
+            move |result| {
+                if let Ok(ProxyResponse::GetCodeActionsResponse {
+                    plugin_id,
+                    resp,
+                }) = result
+                {
+                    send((plugin_id, resp));
+                }
+            },
+        );
+    }
+
+    /// Run the code action of the kind for each of the files which are open,
+    /// apart from the ones of a collaboration session.
+    pub fn run_source_action_in_open_files(&self, kind: CodeActionKind) {
+        let paths: Vec<PathBuf> = self.docs.with_untracked(|docs| {
+            docs.keys()
+                .filter(|path| !is_guest_path(path))
+                .cloned()
+                .collect()
+        });
+        for path in paths {
+            self.run_source_action(path, kind.clone());
+        }
+    }
+
     /// Resolve a code action and apply its held workspace edit
     fn resolve_code_action(&self, plugin_id: PluginId, action: CodeAction) {
         let main_split = self.clone();
         let send = create_ext_action(self.scope, move |edit| {
-            main_split.apply_workspace_edit(&edit);
+            main_split.apply_workspace_edit(&edit, false);
         });
         self.common
             .proxy
@@ -1197,40 +1351,334 @@ fn resolve_code_action(&self, plugin_id: PluginId, action: CodeAction) {
     }
 
     /// Perform a workspace edit, which are from the LSP (such as code actions, or symbol renaming)
-    pub fn apply_workspace_edit(&self, edit: &WorkspaceEdit) {
-        if let Some(DocumentChanges::Operations(_op)) =
-            edit.document_changes.as_ref()
-        {
-            // TODO
+    ///
+    /// The edit is made to all of its files or to none of them: the files which
+    /// aren't open are loaded first, and every file is checked before any of them
+    /// is edited. A `rename` is previewed when it changes more than one place,
+    /// rather than more than one file.
+    pub fn apply_workspace_edit(&self, edit: &WorkspaceEdit, rename: bool) {
+        let file_edits = match file_edits(edit) {
+            Ok(file_edits) => file_edits,
+            Err(err) => {
+                self.workspace_edit_failed(
+                    "notification-workspace-edit-failed",
+                    err,
+                );
+                return;
+            }
+        };
+        if file_edits.is_empty() {
+            return;
+        }
+
+        // A document which is still being loaded by an editor would be loaded
+        // twice, so the edit can't be made until it's done
+        if let Some(file_edit) = file_edits.iter().find(|file_edit| {
+            self.docs
+                .with_untracked(|docs| docs.get(&file_edit.path).copied())
+                .map(|doc| !doc.with_untracked(|doc| doc.loaded()))
+                .unwrap_or(false)
+        }) {
+            self.workspace_edit_failed(
+                "notification-workspace-edit-failed",
+                anyhow!("{} is still being loaded", file_edit.path.display()),
+            );
+            return;
+        }
+
+        let to_load = file_edits
+            .iter()
+            .filter_map(|file_edit| {
+                let (doc, new_doc) = self.get_doc(file_edit.path.clone());
+                new_doc.then(|| (doc, file_edit.path.clone()))
+            })
+            .collect::<Vec<_>>();
+        if to_load.is_empty() {
+            self.file_edits_loaded(&file_edits, rename);
+            return;
         }
 
-        if let Some(edits) = workspace_edits(edit) {
-            for (url, edits) in edits {
-                if let Ok(path) = url.to_file_path() {
-                    let active_path = self
-                        .active_editor
-                        .get_untracked()
-                        .map(|editor| editor.with_untracked(|editor| editor.doc))
-                        .map(|doc| doc.with_untracked(|doc| doc.content.clone()))
-                        .and_then(|content| content.path().cloned());
-                    let position = if active_path.as_ref() == Some(&path) {
-                        None
+        let pending = create_rw_signal(self.scope, to_load.len());
+        let failed = create_rw_signal(self.scope, None);
+        for (doc, path) in to_load {
+            let buffer_id = doc.with_untracked(|doc| doc.buffer_id);
+            let main_split = self.clone();
+            let file_edits = file_edits.clone();
+            let doc_path = path.clone();
+            let send = create_ext_action(self.scope, move |content| {
+                match content {
+                    Some((content, read_only)) => doc.update(move |doc| {
+                        doc.init_content(content);
+                        doc.set_read_only(read_only);
+                    }),
+                    None => {
+                        // Let the file be opened again later on
+                        main_split.docs.update(|docs| {
+                            docs.remove(&doc_path);
+                        });
+                        failed.set(Some(doc_path));
+                    }
+                }
+                pending.update(|pending| *pending -= 1);
+                if pending.get_untracked() > 0 {
+                    return;
+                }
+                if let Some(path) = failed.get_untracked() {
+                    main_split.workspace_edit_failed(
+                        "notification-workspace-edit-failed",
+                        anyhow!("{} couldn't be loaded", path.display()),
+                    );
+                } else {
+                    main_split.file_edits_loaded(&file_edits, rename);
+                }
+            });
+            self.common
+                .proxy
+                .new_buffer(buffer_id, path, move |result| {
+                    if let Ok(ProxyResponse::NewBufferResponse {
+                        content,
+                        read_only,
+                    }) = result
+                    {
+                        send(Some((Rope::from(content), read_only)))
                     } else {
-                        edits
-                            .get(0)
-                            .map(|edit| EditorPosition::Position(edit.range.start))
-                    };
-                    let location = EditorLocation {
-                        path,
-                        position,
-                        scroll_offset: None,
-                        ignore_unconfirmed: true,
-                        same_editor_tab: false,
-                    };
-                    self.jump_to_location(location, Some(edits));
+                        send(None)
+                    }
+                });
+        }
+    }
+
+    /// Open the preview of the edits once their documents are loaded when they
+    /// change more than one file, or more than one place for a rename, or else
+    /// make them.
+    fn file_edits_loaded(&self, file_edits: &[FileEdit], rename: bool) {
+        let changes = if rename {
+            file_edits
+                .iter()
+                .map(|file_edit| file_edit.edits.len())
+                .sum()
+        } else {
+            file_edits.len()
+        };
+        let preview = changes > 1
+            && self
+                .common
+                .config
+                .get_untracked()
+                .editor
+                .preview_workspace_edits;
+        if !preview {
+            self.apply_file_edits(file_edits);
+            return;
+        }
+
+        let mut files = Vec::new();
+        for file_edit in file_edits {
+            let hunks = self
+                .docs
+                .with_untracked(|docs| docs.get(&file_edit.path).copied())
+                .ok_or_else(|| {
+                    anyhow!("{} has been closed", file_edit.path.display())
+                })
+                .and_then(|doc| {
+                    doc.with_untracked(|doc| {
+                        check_file_edit(doc.buffer(), file_edit)?;
+                        Ok(file_edit
+                            .edits
+                            .iter()
+                            .map(|edit| PreviewHunk::new(doc.buffer(), edit))
+                            .collect())
+                    })
+                });
+            match hunks {
+                Ok(hunks) => files.push(PreviewFile {
+                    edit: file_edit.clone(),
+                    hunks,
+                }),
+                Err(err) => {
+                    self.workspace_edit_failed(
+                        "notification-workspace-edit-failed",
+                        err,
+                    );
+                    return;
                 }
             }
         }
+        self.common
+            .internal_command
+            .send(InternalCommand::PreviewFileEdits { files });
+    }
+
+    /// Check the edits against their documents, which are loaded by now, and make
+    /// them if they're all fine.
+    pub fn apply_file_edits(&self, file_edits: &[FileEdit]) {
+        let mut docs = Vec::new();
+        for file_edit in file_edits {
+            let doc = self
+                .docs
+                .with_untracked(|docs| docs.get(&file_edit.path).copied())
+                .ok_or_else(|| {
+                    anyhow!("{} has been closed", file_edit.path.display())
+                })
+                .and_then(|doc| {
+                    doc.with_untracked(|doc| {
+                        check_file_edit(doc.buffer(), file_edit)
+                    })?;
+                    Ok(doc)
+                });
+            match doc {
+                Ok(doc) => docs.push(doc),
+                Err(err) => {
+                    self.workspace_edit_failed(
+                        "notification-workspace-edit-failed",
+                        err,
+                    );
+                    return;
+                }
+            }
+        }
+
+        let active_path = self
+            .active_editor
+            .get_untracked()
+            .map(|editor| editor.with_untracked(|editor| editor.doc))
+            .map(|doc| doc.with_untracked(|doc| doc.content.clone()))
+            .and_then(|content| content.path().cloned());
+
+        let mut applied = Vec::new();
+        for (doc, file_edit) in docs.into_iter().zip(file_edits) {
+            let (delta, rev) = doc
+                .try_update(|doc| {
+                    let edits = file_edit
+                        .edits
+                        .iter()
+                        .map(|edit| {
+                            let start =
+                                doc.buffer().offset_of_position(&edit.range.start);
+                            let end =
+                                doc.buffer().offset_of_position(&edit.range.end);
+                            (start..end, edit.new_text.as_str())
+                        })
+                        .collect::<Vec<_>>();
+                    let (delta, _, _) =
+                        doc.do_raw_edit_ranges(edits, EditType::Other);
+                    (delta, doc.rev())
+                })
+                .unwrap();
+            self.apply_delta_to_cursors(doc, &delta);
+            applied.push((doc, rev));
+
+            if active_path.as_ref() != Some(&file_edit.path) {
+                // The file is opened, so that the edit can be seen and saved
+                let location = EditorLocation {
+                    path: file_edit.path.clone(),
+                    position: file_edit
+                        .edits
+                        .first()
+                        .map(|edit| EditorPosition::Position(edit.range.start)),
+                    scroll_offset: None,
+                    ignore_unconfirmed: true,
+                    same_editor_tab: false,
+                };
+                self.jump_to_location(location, None);
+            }
+        }
+
+        if file_edits.len() > 1 {
+            let config = self.common.config.get_untracked();
+            let message = file_edits
+                .iter()
+                .map(|file_edit| file_edit.path.to_string_lossy())
+                .join("\n");
+            self.common
+                .internal_command
+                .send(InternalCommand::ShowNotification {
+                    notification: Notification::new(
+                        NotificationSeverity::Info,
+                        config.tr_args(
+                            "notification-workspace-edit-applied",
+                            [("count", FluentValue::from(file_edits.len()))],
+                        ),
+                        message,
+                    )
+                    .with_action(
+                        config.tr("notification-workspace-edit-undo"),
+                        NotificationCommand::Internal(
+                            InternalCommand::UndoWorkspaceEdit,
+                        ),
+                    ),
+                });
+        }
+        self.last_workspace_edit
+            .set(Some(AppliedWorkspaceEdit { docs: applied }));
+    }
+
+    /// Undo the last workspace edit in all of the files which it edited, unless
+    /// any of them has been edited since.
+    pub fn undo_workspace_edit(&self) {
+        let Some(applied) = self.last_workspace_edit.get_untracked() else {
+            return;
+        };
+        if let Some((doc, _)) = applied
+            .docs
+            .iter()
+            .find(|(doc, rev)| doc.with_untracked(|doc| doc.rev()) != *rev)
+        {
+            let name = doc
+                .with_untracked(|doc| doc.content.path().cloned())
+                .unwrap_or_default();
+            self.workspace_edit_failed(
+                "notification-workspace-edit-undo-failed",
+                anyhow!("{} has been edited since", name.display()),
+            );
+            return;
+        }
+
+        self.last_workspace_edit.set(None);
+        for (doc, _) in applied.docs {
+            if let Some(delta) = doc.try_update(|doc| doc.undo()).flatten() {
+                self.apply_delta_to_cursors(doc, &delta);
+            }
+        }
+    }
+
+    /// Make the edits of another participant of the collaboration session to the
+    /// document. The ranges are of the text before the edits.
+    pub fn apply_remote_edit(
+        &self,
+        doc: RwSignal<Document>,
+        edits: Vec<(Range<usize>, String)>,
+    ) {
+        if edits.is_empty() {
+            return;
+        }
+        let delta = doc
+            .try_update(|doc| {
+                let edits = edits
+                    .iter()
+                    .map(|(range, content)| (range.clone(), content.as_str()));
+                doc.do_raw_edit_ranges(edits, EditType::Other).0
+            })
+            .unwrap();
+        self.apply_delta_to_cursors(doc, &delta);
+    }
+
+    /// Move the cursors of the editors which show the document along with an
+    /// edit which wasn't made through any of them.
+    fn apply_delta_to_cursors(&self, doc: RwSignal<Document>, delta: &RopeDelta) {
+        for editor in self.doc_editors(doc) {
+            let cursor = editor.with_untracked(|editor| editor.cursor);
+            cursor.update(|cursor| cursor.apply_delta(delta));
+        }
+    }
+
+    fn workspace_edit_failed(&self, title: &str, err: anyhow::Error) {
+        let config = self.common.config.get_untracked();
+        self.common
+            .internal_command
+            .send(InternalCommand::ShowNotification {
+                notification: Notification::error(config.tr(title), err.to_string()),
+            });
     }
 
     pub fn next_error(&self) {
@@ -1320,8 +1768,330 @@ pub fn open_file_changed(&self, path: &Path, content: &str) {
             None => return,
         };
 
+        let (is_pristine, is_same) = doc.with_untracked(|doc| {
+            (
+                doc.buffer().is_pristine(),
+                doc.buffer().text().to_string() == content,
+            )
+        });
+        if is_same {
+            if !is_pristine {
+                doc.update(|doc| {
+                    doc.set_pristine();
+                    doc.clear_disk_conflict();
+                });
+            }
+        } else if is_pristine {
+            self.reload_doc(doc, Rope::from(content));
+        } else {
+            let new_conflict = doc
+                .try_update(|doc| doc.set_disk_conflict(Rope::from(content)))
+                .unwrap();
+            if new_conflict {
+                self.notify_file_conflict(path, true);
+            }
+        }
+    }
+
+    pub fn resolve_file_conflict(
+        &self,
+        path: &Path,
+        resolution: FileConflictResolution,
+    ) {
+        let Some(doc) = self.docs.with_untracked(|docs| docs.get(path).copied())
+        else {
+            return;
+        };
+        let Some(content) = doc.with_untracked(|doc| doc.disk_conflict().cloned())
+        else {
+            return;
+        };
+        match resolution {
+            FileConflictResolution::Reload => {
+                self.reload_doc(doc, content);
+            }
+            FileConflictResolution::Overwrite => {
+                if let Some(editor) = self.doc_editors(doc).first() {
+                    editor.with_untracked(|editor| editor.save(false, true));
+                }
+            }
+            FileConflictResolution::Compare => {
+                doc.with_untracked(|doc| doc.compare_with_disk());
+                // The conflict still has to be resolved after looking at it
+                self.notify_file_conflict(path, false);
+            }
+        }
+    }
+
+    fn notify_file_conflict(&self, path: &Path, compare: bool) {
+        let config = self.common.config.get_untracked();
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        let action = |title, resolution| {
+            (
+                config.tr(title),
+                NotificationCommand::Internal(
+                    InternalCommand::ResolveFileConflict {
+                        path: path.to_path_buf(),
+                        resolution,
+                    },
+                ),
+            )
+        };
+        let mut notification = Notification::new(
+            NotificationSeverity::Warning,
+            config.tr_args(
+                "notification-file-conflict",
+                [("file", FluentValue::from(name))],
+            ),
+            config.tr("notification-file-conflict-message"),
+        );
+        let mut actions = vec![
+            action(
+                "notification-file-conflict-reload",
+                FileConflictResolution::Reload,
+            ),
+            action(
+                "notification-file-conflict-overwrite",
+                FileConflictResolution::Overwrite,
+            ),
+        ];
+        if compare {
+            actions.push(action(
+                "notification-file-conflict-compare",
+                FileConflictResolution::Compare,
+            ));
+        }
+        for (title, command) in actions {
+            notification = notification.with_action(title, command);
+        }
+        self.common
+            .internal_command
+            .send(InternalCommand::ShowNotification { notification });
+    }
+
+    /// Save the document with the privileges of an administrator, after saving it
+    /// normally was denied.
+    pub fn save_as_admin(&self, path: &Path) {
+        let Some(doc) = self.docs.with_untracked(|docs| docs.get(path).copied())
+        else {
+            return;
+        };
+        let rev = doc.with_untracked(|doc| doc.rev());
+        let main_split = self.clone();
+        let send = create_ext_action(self.scope, move |result| match result {
+            Ok(ProxyResponse::SaveResponse {}) => {
+                if doc.with_untracked(|doc| doc.rev()) == rev {
+                    doc.update(|doc| {
+                        doc.set_pristine();
+                        doc.clear_disk_conflict();
+                    });
+                }
+            }
+            Err(err) => {
+                let config = main_split.common.config.get_untracked();
+                main_split.common.internal_command.send(
+                    InternalCommand::ShowNotification {
+                        notification: Notification::error(
+                            config.tr("notification-save-as-admin-failed"),
+                            err.message,
+                        ),
+                    },
+                );
+            }
+            _ => {}
+        });
+        self.common
+            .proxy
+            .save_as_admin(rev, path.to_path_buf(), move |result| {
+                send(result);
+            });
+    }
+
+    /// Load the content from disk into the document, keeping the cursors of its
+    /// editors on the same lines and columns, and their scroll where it was.
+    fn reload_doc(&self, doc: RwSignal<Document>, content: Rope) {
+        let old_buffer = doc.with_untracked(|doc| doc.buffer().clone());
+        let editors = self
+            .doc_editors(doc)
+            .into_iter()
+            .map(|editor| {
+                let (cursor, viewport, scroll_to) =
+                    editor.with_untracked(|editor| {
+                        (editor.cursor, editor.viewport, editor.scroll_to)
+                    });
+                (
+                    cursor,
+                    scroll_to,
+                    viewport.get_untracked().origin().to_vec2(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        doc.update(|doc| doc.reload(content, true));
+
+        for (cursor, scroll_to, scroll_offset) in editors {
+            doc.with_untracked(|doc| {
+                cursor.update(|cursor| {
+                    cursor.mode = reloaded_cursor_mode(
+                        &cursor.mode,
+                        &old_buffer,
+                        doc.buffer(),
+                    );
+                    cursor.syntax_selections.clear();
+                });
+            });
+            // The same lines stay at the top, rather than the view following
+            // the cursor
+            scroll_to.set(Some(scroll_offset));
+        }
+    }
+
+    /// The editors which show the document
+    fn doc_editors(&self, doc: RwSignal<Document>) -> Vec<RwSignal<EditorData>> {
+        let buffer_id = doc.with_untracked(|doc| doc.buffer_id);
+        self.editors.with_untracked(|editors| {
+            editors
+                .values()
+                .filter(|editor| {
+                    editor.with_untracked(|editor| {
+                        editor.doc.with_untracked(|doc| doc.buffer_id) == buffer_id
+                    })
+                })
+                .copied()
+                .collect()
+        })
+    }
+
+    /// Open the code in the range of the host file in an editor of its own,
+    /// whose document has the language of the file extension.
+    pub fn edit_embedded_code(
+        &self,
+        host: PathBuf,
+        range: Range<usize>,
+        extension: String,
+    ) {
+        let Some(host_doc) =
+            self.docs.with_untracked(|docs| docs.get(&host).copied())
+        else {
+            return;
+        };
+        let (text, line) = host_doc.with_untracked(|doc| {
+            (
+                doc.buffer().slice_to_cow(range.clone()).to_string(),
+                doc.buffer().line_of_offset(range.start),
+            )
+        });
+        let path = embedded_path(&host, line, &extension);
+
+        let (doc, new_doc) = self.get_doc(path.clone());
+        if new_doc {
+            doc.update(|doc| {
+                doc.set_embedded(Some(EmbeddedCode {
+                    host,
+                    start: range.start,
+                    text: text.clone(),
+                }))
+            });
+            // The file doesn't exist, so the document is loaded empty and then
+            // gets the code, which the language servers see as a change
+            create_effect(self.scope, move |done| {
+                if done == Some(true) {
+                    return true;
+                }
+                let loaded = doc.with(|doc| doc.loaded());
+                if loaded {
+                    doc.update(|doc| doc.reload(Rope::from(text.as_str()), true));
+                }
+                loaded
+            });
+        }
+
+        self.jump_to_location(
+            EditorLocation {
+                path,
+                position: None,
+                scroll_offset: None,
+                ignore_unconfirmed: true,
+                same_editor_tab: false,
+            },
+            None,
+        );
+    }
+
+    /// Write the code of the document of embedded code back to its host file, in
+    /// place of the code which was last written there. The host is left unsaved.
+    pub fn save_embedded_code(&self, path: &Path) {
+        let Some(doc) = self.docs.with_untracked(|docs| docs.get(path).copied())
+        else {
+            return;
+        };
+        let Some((code, text)) = doc.with_untracked(|doc| {
+            Some((doc.embedded()?.clone(), doc.buffer().to_string()))
+        }) else {
+            return;
+        };
+
+        let host_doc = self
+            .docs
+            .with_untracked(|docs| docs.get(&code.host).copied());
+        let start = host_doc.and_then(|host_doc| {
+            host_doc.with_untracked(|doc| {
+                locate_code(&doc.buffer().to_string(), &code.text, code.start)
+            })
+        });
+        let (Some(host_doc), Some(start)) = (host_doc, start) else {
+            let config = self.common.config.get_untracked();
+            self.common
+                .internal_command
+                .send(InternalCommand::ShowNotification {
+                    notification: Notification::new(
+                        NotificationSeverity::Warning,
+                        config.tr("notification-embedded-code-lost"),
+                        code.host.to_string_lossy().to_string(),
+                    ),
+                });
+            return;
+        };
+
+        let delta = host_doc
+            .try_update(|doc| {
+                let range = start..start + code.text.len();
+                doc.do_raw_edit_ranges([(range, text.as_str())], EditType::Other)
+                    .0
+            })
+            .unwrap();
+        self.apply_delta_to_cursors(host_doc, &delta);
         doc.update(|doc| {
-            doc.handle_file_changed(Rope::from(content));
+            doc.set_embedded(Some(EmbeddedCode {
+                host: code.host,
+                start,
+                text,
+            }));
+            doc.set_pristine();
+        });
+    }
+
+    /// Write the code of a document of embedded code back to its host once the
+    /// last editor of it is closed, and drop the document.
+    fn close_embedded_code(&self, doc: RwSignal<Document>) {
+        let Some((path, is_pristine)) = doc.with_untracked(|doc| {
+            doc.embedded()?;
+            Some((doc.content.path()?.clone(), doc.buffer().is_pristine()))
+        }) else {
+            return;
+        };
+        if !self.doc_editors(doc).is_empty() {
+            return;
+        }
+        if !is_pristine {
+            self.save_embedded_code(&path);
+        }
+        self.docs.update(|docs| {
+            docs.remove(&path);
         });
     }
 
@@ -1392,6 +2162,184 @@ pub fn open_settings(&self) {
         });
     }
 
+    /// Open the settings file next to the settings editor, so that the file and
+    /// the settings UI can be edited side by side.
+    pub fn open_settings_file(&self, path: PathBuf) {
+        self.open_settings();
+
+        let Some(editor_tab_id) = self.active_editor_tab.get_untracked() else {
+            return;
+        };
+        let Some(new_editor_tab_id) =
+            self.split(SplitDirection::Vertical, editor_tab_id)
+        else {
+            return;
+        };
+
+        self.active_editor_tab.set(Some(new_editor_tab_id));
+        self.jump_to_location(
+            EditorLocation {
+                path,
+                position: None,
+                scroll_offset: None,
+                ignore_unconfirmed: true,
+                same_editor_tab: true,
+            },
+            None,
+        );
+
+        // splitting copies the settings into the new editor tab, which we only
+        // want to show the file
+        let settings = self.editor_tabs.with_untracked(|editor_tabs| {
+            editor_tabs.get(&new_editor_tab_id).and_then(|editor_tab| {
+                editor_tab.with_untracked(|editor_tab| {
+                    editor_tab
+                        .children
+                        .iter()
+                        .find(|(_, child)| child.is_settings())
+                        .map(|(_, child)| child.clone())
+                })
+            })
+        });
+        if let Some(settings) = settings {
+            self.editor_tab_child_close(self.scope, new_editor_tab_id, settings);
+        }
+    }
+
+    /// Open the diff of the two files side by side, the first one in the active
+    /// editor tab and the second one in a split next to it, whose gutter shows
+    /// its changes against the first one while both are open.
+    pub fn open_diff(&self, left: PathBuf, right: PathBuf) {
+        self.jump_to_location(
+            EditorLocation {
+                path: left.clone(),
+                position: None,
+                scroll_offset: None,
+                ignore_unconfirmed: true,
+                same_editor_tab: false,
+            },
+            None,
+        );
+
+        let Some(editor_tab_id) = self.active_editor_tab.get_untracked() else {
+            return;
+        };
+        let Some(new_editor_tab_id) =
+            self.split(SplitDirection::Vertical, editor_tab_id)
+        else {
+            return;
+        };
+
+        // splitting copies the first file into the new editor tab, which we
+        // only want to show the second one
+        let copy = self.editor_tabs.with_untracked(|editor_tabs| {
+            editor_tabs.get(&new_editor_tab_id).and_then(|editor_tab| {
+                editor_tab.with_untracked(|editor_tab| {
+                    editor_tab.children.first().map(|(_, child)| child.clone())
+                })
+            })
+        });
+        self.active_editor_tab.set(Some(new_editor_tab_id));
+        self.jump_to_location(
+            EditorLocation {
+                path: right.clone(),
+                position: None,
+                scroll_offset: None,
+                ignore_unconfirmed: true,
+                same_editor_tab: true,
+            },
+            None,
+        );
+        if let Some(copy) = copy {
+            self.editor_tab_child_close(self.scope, new_editor_tab_id, copy);
+        }
+        self.compare_while_open(left, right);
+    }
+
+    /// Compare the second file with the first one, again whenever the first one
+    /// is edited, until an editor of either of them is no longer open.
+    fn compare_while_open(&self, left: PathBuf, right: PathBuf) {
+        let (left_doc, _) = self.get_doc(left.clone());
+        let (right_doc, _) = self.get_doc(right.clone());
+        let editors = self.editors;
+        let (cx, _) = self.scope.run_child_scope(|cx| cx);
+        let left_rev = create_memo(cx, move |_| {
+            left_doc.with(|doc| doc.loaded().then(|| doc.buffer().rev()))
+        });
+        let right_loaded =
+            create_memo(cx, move |_| right_doc.with(|doc| doc.loaded()));
+        create_effect(cx, move |closed: Option<bool>| {
+            if closed == Some(true) {
+                return true;
+            }
+            let open = editors.with(|editors| {
+                [&left, &right].into_iter().all(|path| {
+                    editors.values().any(|editor| {
+                        editor.with_untracked(|editor| {
+                            editor.doc.with_untracked(|doc| {
+                                matches!(&doc.content, DocContent::File(p) if p == path)
+                            })
+                        })
+                    })
+                })
+            });
+            if !open {
+                right_doc.with_untracked(|doc| doc.stop_comparing());
+                let send = create_ext_action(cx, move |_| {
+                    cx.dispose();
+                });
+                std::thread::spawn(move || {
+                    send(());
+                });
+                return true;
+            }
+
+            if left_rev.get().is_some() && right_loaded.get() {
+                let text =
+                    left_doc.with_untracked(|doc| doc.buffer().text().clone());
+                right_doc
+                    .with_untracked(|doc| doc.compare_with(left.clone(), &text));
+            }
+            false
+        });
+    }
+
+    /// Send on `closed` once none of the files is open anymore,
+    /// for a `lapce --wait` which waits for that.
+    pub fn notify_when_closed(&self, paths: Vec<PathBuf>, closed: Sender<()>) {
+        let editors = self.editors;
+        let (cx, _) = self.scope.run_child_scope(|cx| cx);
+        create_effect(cx, move |was_open: Option<bool>| {
+            if was_open == Some(false) {
+                return false;
+            }
+            let open = editors.with(|editors| {
+                editors.values().any(|editor| {
+                    editor.with_untracked(|editor| {
+                        editor.doc.with_untracked(|doc| {
+                            matches!(
+                                &doc.content,
+                                DocContent::File(path) if paths.contains(path)
+                            )
+                        })
+                    })
+                })
+            });
+            // Files which didn't open aren't waited for either
+            if !open {
+                let _ = closed.send(());
+                // The effect is done, so it goes away, but not while it runs
+                let send = create_ext_action(cx, move |_| {
+                    cx.dispose();
+                });
+                std::thread::spawn(move || {
+                    send(());
+                });
+            }
+            open
+        });
+    }
+
     pub fn can_jump_location_backward(&self, tracked: bool) -> bool {
         if tracked {
             self.current_location.get() >= 1
@@ -1413,48 +2361,6 @@ pub fn can_jump_location_forward(&self, tracked: bool) -> bool {
     }
 }
 
-fn workspace_edits(edit: &WorkspaceEdit) -> Option<HashMap<Url, Vec<TextEdit>>> {
-    if let Some(changes) = edit.changes.as_ref() {
-        return Some(changes.clone());
-    }
-
-    let changes = edit.document_changes.as_ref()?;
-    let edits = match changes {
-        DocumentChanges::Edits(edits) => edits
-            .iter()
-            .map(|e| {
-                (
-                    e.text_document.uri.clone(),
-                    e.edits
-                        .iter()
-                        .map(|e| match e {
-                            OneOf::Left(e) => e.clone(),
-                            OneOf::Right(e) => e.text_edit.clone(),
-                        })
-                        .collect(),
-                )
-            })
-            .collect::<HashMap<Url, Vec<TextEdit>>>(),
-        DocumentChanges::Operations(ops) => ops
-            .iter()
-            .filter_map(|o| match o {
-                DocumentChangeOperation::Op(_op) => None,
-                DocumentChangeOperation::Edit(e) => Some((
-                    e.text_document.uri.clone(),
-                    e.edits
-                        .iter()
-                        .map(|e| match e {
-                            OneOf::Left(e) => e.clone(),
-                            OneOf::Right(e) => e.text_edit.clone(),
-                        })
-                        .collect(),
-                )),
-            })
-            .collect::<HashMap<Url, Vec<TextEdit>>>(),
-    };
-    Some(edits)
-}
-
 fn next_in_file_errors_offset(
     active_path: Option<(PathBuf, Position)>,
     file_diagnostics: &[(PathBuf, RwSignal<bool>, Vec<EditorDiagnostic>)],
@@ -1489,3 +2395,81 @@ fn next_in_file_errors_offset(
         file_diagnostics[0].2[0].diagnostic.range.start,
     )
 }
+
+/// The cursor mode with its offsets on the same lines and columns of the
+/// reloaded buffer, where they're clamped to the lines which are shorter now.
+fn reloaded_cursor_mode(
+    mode: &CursorMode,
+    old: &Buffer,
+    new: &Buffer,
+) -> CursorMode {
+    let reloaded = |offset: usize| {
+        let (line, col) = old.offset_to_line_col(offset);
+        new.offset_of_line_col(line, col)
+    };
+    match mode {
+        CursorMode::Normal(offset) => CursorMode::Normal(reloaded(*offset)),
+        CursorMode::Visual { start, end, mode } => CursorMode::Visual {
+            start: reloaded(*start),
+            end: reloaded(*end),
+            mode: *mode,
+        },
+        CursorMode::Insert(selection) => {
+            let mut reloaded_selection = Selection::new();
+            for region in selection.regions() {
+                reloaded_selection.add_region(SelRegion::new(
+                    reloaded(region.start),
+                    reloaded(region.end),
+                    None,
+                ));
+            }
+            CursorMode::Insert(reloaded_selection)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_core::mode::VisualMode;
+
+    use super::*;
+
+    #[test]
+    fn test_reloaded_cursor_mode() {
+        let old = Buffer::new("fn main() {\n    let a = 1;\n}\n");
+        let new = Buffer::new("// header\nfn main() {\n    let b = 22;\n");
+
+        // The same line and column, even though the text before them changed
+        let mode = reloaded_cursor_mode(&CursorMode::Normal(16), &old, &new);
+        assert_eq!(new.offset_to_line_col(mode.offset()), (1, 4));
+
+        // Clamped to the end of a line which is shorter now
+        let new_short = Buffer::new("a\nb\n");
+        let mode = CursorMode::Visual {
+            start: 3,
+            end: 20,
+            mode: VisualMode::Normal,
+        };
+        match reloaded_cursor_mode(&mode, &old, &new_short) {
+            CursorMode::Visual { start, end, .. } => {
+                assert_eq!((start, end), (1, 3));
+            }
+            _ => panic!("the visual mode should be kept"),
+        }
+
+        // Every region of the selection is moved
+        let mut selection = Selection::new();
+        selection.add_region(SelRegion::caret(3));
+        selection.add_region(SelRegion::new(16, 19, None));
+        let mode = reloaded_cursor_mode(&CursorMode::Insert(selection), &old, &new);
+        let CursorMode::Insert(selection) = mode else {
+            panic!("the insert mode should be kept");
+        };
+        let regions: Vec<(usize, usize)> = selection
+            .regions()
+            .iter()
+            .map(|region| (region.start, region.end))
+            .collect();
+        assert_eq!(regions, vec![(3, 3), (14, 17)]);
+    }
+}
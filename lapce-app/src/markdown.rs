@@ -0,0 +1,633 @@
+//! Markdown, like the documentation of hovers, laid out as text which can be
+//! drawn by a `rich_text` view.
+
+use std::ops::Range;
+
+use floem::{
+    cosmic_text::{
+        Attrs, AttrsList, LineHeightValue, Style as FontStyle, TextLayout, Weight,
+    },
+    peniko::Color,
+};
+use lapce_core::{language::LapceLanguage, syntax::Syntax};
+use lapce_xi_rope::Rope;
+use lsp_types::{
+    CompletionItem, Documentation, HoverContents, MarkedString, MarkupKind,
+};
+use pulldown_cmark::{
+    Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag,
+};
+use tracing::warn;
+
+use crate::config::{color::LapceColor, LapceConfig};
+
+#[derive(Clone)]
+pub enum MarkdownContent {
+    Text(MarkdownText),
+    Separator,
+}
+
+#[derive(Clone)]
+pub struct MarkdownText {
+    pub layout: TextLayout,
+    /// The ranges of the links in the text, with where they go
+    pub links: Vec<(Range<usize>, String)>,
+}
+
+impl MarkdownText {
+    /// Where the link at the index of the text goes, if there's one.
+    pub fn link_at(&self, index: usize) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|(range, _)| range.contains(&index))
+            .map(|(_, target)| target.as_str())
+    }
+}
+
+/// The style of a range of the text. The ranges are added when their tag ends,
+/// so an inner range comes before the ones around it, and what it sets wins.
+#[derive(Clone, Default)]
+struct SpanStyle {
+    color: Option<Color>,
+    weight: Option<Weight>,
+    italic: bool,
+    font_size: Option<f32>,
+    code: bool,
+}
+
+impl SpanStyle {
+    fn merge(&mut self, other: &SpanStyle) {
+        self.color = self.color.or(other.color);
+        self.weight = self.weight.or(other.weight);
+        self.italic |= other.italic;
+        self.font_size = self.font_size.or(other.font_size);
+        self.code |= other.code;
+    }
+}
+
+#[derive(Default)]
+struct TextBuilder {
+    text: String,
+    spans: Vec<(Range<usize>, SpanStyle)>,
+    links: Vec<(Range<usize>, String)>,
+}
+
+impl TextBuilder {
+    fn is_empty(&self) -> bool {
+        self.text.trim_end().is_empty()
+    }
+
+    fn build(
+        &mut self,
+        line_height: f32,
+        font_size: f32,
+        config: &LapceConfig,
+    ) -> MarkdownText {
+        let mut builder = std::mem::take(self);
+        let len = builder.text.trim_end().len();
+        builder.text.truncate(len);
+        let family = config.ui.hover_font_family();
+        let code_family = config.editor_font_family(None);
+        let attrs = Attrs::new()
+            .color(*config.get_color(LapceColor::EDITOR_FOREGROUND))
+            .family(&family)
+            .font_size(font_size)
+            .line_height(LineHeightValue::Normal(line_height));
+        let mut attrs_list = AttrsList::new(attrs);
+
+        let mut bounds: Vec<usize> = builder
+            .spans
+            .iter()
+            .flat_map(|(range, _)| [range.start, range.end])
+            .map(|bound| bound.min(len))
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+        for window in bounds.windows(2) {
+            let range = window[0]..window[1];
+            let mut style = SpanStyle::default();
+            for (span, span_style) in &builder.spans {
+                if span.start <= range.start && range.end <= span.end {
+                    style.merge(span_style);
+                }
+            }
+
+            let mut attrs = attrs;
+            if let Some(color) = style.color {
+                attrs = attrs.color(color);
+            }
+            if let Some(weight) = style.weight {
+                attrs = attrs.weight(weight);
+            }
+            if style.italic {
+                attrs = attrs.style(FontStyle::Italic);
+            }
+            if let Some(font_size) = style.font_size {
+                attrs = attrs.font_size(font_size);
+            }
+            if style.code {
+                attrs = attrs.family(&code_family);
+            }
+            attrs_list.add_span(range, attrs);
+        }
+
+        let mut layout = TextLayout::new();
+        layout.set_text(&builder.text, attrs_list);
+        MarkdownText {
+            layout,
+            links: builder.links,
+        }
+    }
+}
+
+/// A table, whose rows are laid out with the editor font once it ends so that
+/// the columns line up.
+#[derive(Default)]
+struct TableBuilder {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+    cell: String,
+}
+
+impl TableBuilder {
+    fn lay_out(&self) -> String {
+        let columns = self.rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let mut lines = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            let cells: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(column, width)| {
+                    let cell = row.get(column).map(|c| c.as_str()).unwrap_or("");
+                    match self.alignments.get(column) {
+                        Some(Alignment::Right) => format!("{cell:>width$}"),
+                        Some(Alignment::Center) => format!("{cell:^width$}"),
+                        _ => format!("{cell:<width$}"),
+                    }
+                })
+                .collect();
+            lines.push(cells.join(" │ ").trim_end().to_string());
+            // The first row is the head of the table
+            if i == 0 {
+                let rule: Vec<String> =
+                    widths.iter().map(|width| "─".repeat(*width)).collect();
+                lines.push(rule.join("─┼─"));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// The contents of a hover, where the parts of it are separated by lines.
+pub fn parse_hover_contents(
+    contents: &HoverContents,
+    config: &LapceConfig,
+) -> Vec<MarkdownContent> {
+    let marked_strings = match contents {
+        HoverContents::Scalar(text) => vec![text.clone()],
+        HoverContents::Array(texts) => texts.clone(),
+        HoverContents::Markup(content) => {
+            return match content.kind {
+                MarkupKind::PlainText => {
+                    plain_text(&content.value, config).into_iter().collect()
+                }
+                MarkupKind::Markdown => parse_markdown(&content.value, config),
+            };
+        }
+    };
+
+    let mut res = Vec::new();
+    for text in marked_strings {
+        let content = from_marked_string(&text, config);
+        if content.is_empty() {
+            continue;
+        }
+        if !res.is_empty() {
+            res.push(MarkdownContent::Separator);
+        }
+        res.extend(content);
+    }
+    res
+}
+
+/// The detail of a completion item, followed by its documentation.
+pub fn parse_completion_documentation(
+    item: &CompletionItem,
+    config: &LapceConfig,
+) -> Vec<MarkdownContent> {
+    let mut res: Vec<MarkdownContent> = item
+        .detail
+        .as_deref()
+        .and_then(|detail| plain_text(detail, config))
+        .into_iter()
+        .collect();
+    let documentation = match &item.documentation {
+        Some(Documentation::String(text)) => {
+            plain_text(text, config).into_iter().collect()
+        }
+        Some(Documentation::MarkupContent(content)) => match content.kind {
+            MarkupKind::PlainText => {
+                plain_text(&content.value, config).into_iter().collect()
+            }
+            MarkupKind::Markdown => parse_markdown(&content.value, config),
+        },
+        None => Vec::new(),
+    };
+    if !res.is_empty() && !documentation.is_empty() {
+        res.push(MarkdownContent::Separator);
+    }
+    res.extend(documentation);
+    res
+}
+
+fn from_marked_string(
+    text: &MarkedString,
+    config: &LapceConfig,
+) -> Vec<MarkdownContent> {
+    match text {
+        MarkedString::String(text) => parse_markdown(text, config),
+        // This is a short version of a code block
+        MarkedString::LanguageString(code) => parse_markdown(
+            &format!("```{}\n{}\n```", code.language, code.value),
+            config,
+        ),
+    }
+}
+
+fn plain_text(text: &str, config: &LapceConfig) -> Option<MarkdownContent> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let mut builder = TextBuilder::default();
+    builder.text.push_str(text);
+    Some(MarkdownContent::Text(builder.build(
+        1.5,
+        config.ui.hover_font_size() as f32,
+        config,
+    )))
+}
+
+/// The code highlighted as the language, which is kept as it is otherwise.
+pub fn code_block(
+    code: &str,
+    language: Option<LapceLanguage>,
+    config: &LapceConfig,
+) -> Option<MarkdownContent> {
+    if code.trim().is_empty() {
+        return None;
+    }
+    let mut builder = TextBuilder::default();
+    builder.text.push_str(code);
+    builder.spans.push((
+        0..code.len(),
+        SpanStyle {
+            code: true,
+            ..Default::default()
+        },
+    ));
+    highlight_as_code(&mut builder, config, language, code, 0);
+    Some(MarkdownContent::Text(builder.build(
+        1.5,
+        config.ui.hover_font_size() as f32,
+        config,
+    )))
+}
+
+pub fn parse_markdown(text: &str, config: &LapceConfig) -> Vec<MarkdownContent> {
+    let font_size = config.ui.hover_font_size() as f32;
+    let line_height = 1.5;
+
+    let mut res = Vec::new();
+    let mut builder = TextBuilder::default();
+    let mut tag_stack: Vec<(usize, Tag)> = Vec::new();
+    // The next numbers of the ordered lists which are open, or `None` for the
+    // lists with bullets
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut table: Option<TableBuilder> = None;
+
+    let parser = Parser::new_ext(
+        text,
+        Options::ENABLE_TABLES
+            | Options::ENABLE_FOOTNOTES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_HEADING_ATTRIBUTES,
+    );
+    // Whether we should add a newline on the next entry, so that there isn't
+    // one at the very end
+    let mut add_newline = false;
+    for event in parser {
+        if let Some(table) = table.as_mut() {
+            match &event {
+                Event::Text(text) | Event::Code(text) => {
+                    table.cell.push_str(text);
+                    continue;
+                }
+                Event::SoftBreak | Event::HardBreak => {
+                    table.cell.push(' ');
+                    continue;
+                }
+                Event::End(Tag::TableCell) => {
+                    let cell = std::mem::take(&mut table.cell);
+                    table.row.push(cell.trim().to_string());
+                    continue;
+                }
+                Event::End(Tag::TableHead | Tag::TableRow) => {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                    continue;
+                }
+                Event::End(Tag::Table(_)) => {}
+                _ => continue,
+            }
+        }
+
+        if add_newline {
+            builder.text.push('\n');
+            add_newline = false;
+        }
+
+        match event {
+            Event::Start(tag) => {
+                match &tag {
+                    Tag::Table(alignments) => {
+                        table = Some(TableBuilder {
+                            alignments: alignments.clone(),
+                            ..Default::default()
+                        });
+                    }
+                    Tag::List(start) => lists.push(*start),
+                    Tag::Item => {
+                        // The item of a nested list starts on its own line
+                        if !builder.is_empty() && !builder.text.ends_with('\n') {
+                            builder.text.push('\n');
+                        }
+                        let indent = "  ".repeat(lists.len().saturating_sub(1));
+                        let marker = match lists.last_mut() {
+                            Some(Some(number)) => {
+                                *number += 1;
+                                format!("{}. ", *number - 1)
+                            }
+                            _ => "• ".to_string(),
+                        };
+                        builder.text.push_str(&indent);
+                        builder.text.push_str(&marker);
+                    }
+                    _ => {}
+                }
+                tag_stack.push((builder.text.len(), tag));
+            }
+            Event::End(end_tag) => {
+                let Some((start, tag)) = tag_stack.pop() else {
+                    warn!("Unbalanced markdown tag");
+                    continue;
+                };
+                if end_tag != tag {
+                    warn!("Mismatched markdown tag");
+                    continue;
+                }
+                match &tag {
+                    Tag::Table(_) => {
+                        if let Some(table) = table.take() {
+                            let start = builder.text.len();
+                            builder.text.push_str(&table.lay_out());
+                            builder.spans.push((
+                                start..builder.text.len(),
+                                SpanStyle {
+                                    code: true,
+                                    ..Default::default()
+                                },
+                            ));
+                        }
+                    }
+                    Tag::CodeBlock(kind) => {
+                        let language = match kind {
+                            CodeBlockKind::Fenced(language) => {
+                                LapceLanguage::from_injection_name(language)
+                            }
+                            CodeBlockKind::Indented => None,
+                        };
+                        // The text of a code block ends with a newline, which is
+                        // added after it anyway
+                        if builder.text.ends_with('\n') {
+                            builder.text.pop();
+                        }
+                        let code = builder.text[start..].to_string();
+                        highlight_as_code(
+                            &mut builder,
+                            config,
+                            language,
+                            &code,
+                            start,
+                        );
+                    }
+                    Tag::List(_) => {
+                        lists.pop();
+                    }
+                    Tag::Link(_, target, _) | Tag::Image(_, target, _) => {
+                        let range = start..builder.text.len();
+                        builder.links.push((range, target.to_string()));
+                    }
+                    _ => {}
+                }
+
+                let range = start..builder.text.len();
+                if let Some(style) = style_for_tag(&tag, font_size, config) {
+                    builder.spans.push((range, style));
+                }
+
+                if should_add_newline_after_tag(&tag) {
+                    add_newline = true;
+                }
+            }
+            Event::Text(text) => {
+                builder.text.push_str(&text);
+            }
+            Event::Code(text) => {
+                let start = builder.text.len();
+                builder.text.push_str(&text);
+                builder.spans.push((
+                    start..builder.text.len(),
+                    SpanStyle {
+                        code: true,
+                        ..Default::default()
+                    },
+                ));
+            }
+            // TODO: Some minimal 'parsing' of html could be useful here, since some
+            // things use basic html like `<code>text</code>`.
+            Event::Html(text) => {
+                let start = builder.text.len();
+                builder.text.push_str(&text);
+                builder.spans.push((
+                    start..builder.text.len(),
+                    SpanStyle {
+                        color: Some(
+                            *config.get_color(LapceColor::MARKDOWN_BLOCKQUOTE),
+                        ),
+                        code: true,
+                        ..Default::default()
+                    },
+                ));
+            }
+            Event::HardBreak => {
+                builder.text.push('\n');
+            }
+            Event::SoftBreak => {
+                builder.text.push(' ');
+            }
+            Event::Rule => {
+                if !builder.is_empty() {
+                    let text = builder.build(line_height, font_size, config);
+                    res.push(MarkdownContent::Text(text));
+                }
+                res.push(MarkdownContent::Separator);
+            }
+            Event::TaskListMarker(checked) => {
+                builder.text.push_str(if checked { "☑ " } else { "☐ " });
+            }
+            Event::FootnoteReference(_) => {}
+        }
+    }
+
+    if !builder.is_empty() {
+        let text = builder.build(line_height, font_size, config);
+        res.push(MarkdownContent::Text(text));
+    }
+
+    res
+}
+
+/// Highlight the code of a code block, which starts at the offset of the text.
+fn highlight_as_code(
+    builder: &mut TextBuilder,
+    config: &LapceConfig,
+    language: Option<LapceLanguage>,
+    text: &str,
+    start_offset: usize,
+) {
+    let Some(mut syntax) = language.and_then(|l| Syntax::from_language(l).ok())
+    else {
+        return;
+    };
+    syntax.parse(0, Rope::from(text), None);
+    let Some(styles) = syntax.styles else {
+        return;
+    };
+
+    for (range, style) in styles.iter() {
+        if let Some(color) = style
+            .fg_color
+            .as_ref()
+            .and_then(|fg| config.get_style_color(fg))
+        {
+            builder.spans.push((
+                start_offset + range.start..start_offset + range.end,
+                SpanStyle {
+                    color: Some(*color),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+}
+
+fn style_for_tag(
+    tag: &Tag,
+    font_size: f32,
+    config: &LapceConfig,
+) -> Option<SpanStyle> {
+    let style = match tag {
+        Tag::Heading(level, _, _) => {
+            // The size calculations are based on the em values given at
+            // https://drafts.csswg.org/css2/#html-stylesheet
+            let font_scale = match level {
+                HeadingLevel::H1 => 2.0,
+                HeadingLevel::H2 => 1.5,
+                HeadingLevel::H3 => 1.17,
+                HeadingLevel::H4 => 1.0,
+                HeadingLevel::H5 => 0.83,
+                HeadingLevel::H6 => 0.75,
+            };
+            SpanStyle {
+                font_size: Some(font_scale * font_size),
+                weight: Some(Weight::BOLD),
+                ..Default::default()
+            }
+        }
+        Tag::BlockQuote => SpanStyle {
+            italic: true,
+            color: Some(*config.get_color(LapceColor::MARKDOWN_BLOCKQUOTE)),
+            ..Default::default()
+        },
+        Tag::CodeBlock(_) => SpanStyle {
+            code: true,
+            ..Default::default()
+        },
+        Tag::Emphasis => SpanStyle {
+            italic: true,
+            ..Default::default()
+        },
+        Tag::Strong => SpanStyle {
+            weight: Some(Weight::BOLD),
+            ..Default::default()
+        },
+        Tag::Link(..) | Tag::Image(..) => SpanStyle {
+            color: Some(*config.get_color(LapceColor::EDITOR_LINK)),
+            ..Default::default()
+        },
+        // All other tags are currently ignored
+        _ => return None,
+    };
+    Some(style)
+}
+
+/// Decides whether newlines should be added after a specific markdown tag
+fn should_add_newline_after_tag(tag: &Tag) -> bool {
+    !matches!(
+        tag,
+        Tag::Emphasis
+            | Tag::Strong
+            | Tag::Strikethrough
+            | Tag::Link(..)
+            | Tag::Image(..)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lay_out_table() {
+        let table = TableBuilder {
+            alignments: vec![Alignment::None, Alignment::Right],
+            rows: vec![
+                vec!["Name".to_string(), "Size".to_string()],
+                vec!["a".to_string(), "10".to_string()],
+                vec!["long name".to_string(), "2".to_string()],
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            table.lay_out(),
+            "Name      │ Size\n\
+             ──────────┼─────\n\
+             a         │   10\n\
+             long name │    2"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{
+        create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalSet,
+        SignalUpdate, SignalWithUntracked,
+    },
+};
+use lsp_types::MessageType;
+
+use crate::{
+    command::{InternalCommand, LapceWorkbenchCommand},
+    config::{color::LapceColor, icon::LapceIcons},
+    id::NotificationId,
+};
+
+/// How long a toast is shown before it's dismissed on its own.
+/// Errors stay until they're dismissed, so that they can't be missed.
+const TOAST_TIMEOUT: Duration = Duration::from_secs(8);
+/// How many notifications are kept in the history
+const HISTORY_LIMIT: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl NotificationSeverity {
+    pub fn from_message_type(typ: MessageType) -> Self {
+        match typ {
+            MessageType::ERROR => NotificationSeverity::Error,
+            MessageType::WARNING => NotificationSeverity::Warning,
+            _ => NotificationSeverity::Info,
+        }
+    }
+
+    pub fn svg_name(&self) -> &'static str {
+        match self {
+            NotificationSeverity::Info => LapceIcons::NOTIFICATION_INFO,
+            NotificationSeverity::Warning => LapceIcons::WARNING,
+            NotificationSeverity::Error => LapceIcons::ERROR,
+        }
+    }
+
+    pub fn color(&self) -> &'static str {
+        match self {
+            NotificationSeverity::Info => LapceColor::EDITOR_LINK,
+            NotificationSeverity::Warning => LapceColor::LAPCE_WARN,
+            NotificationSeverity::Error => LapceColor::LAPCE_ERROR,
+        }
+    }
+}
+
+/// What a notification button runs.
+#[derive(Clone, Debug)]
+pub enum NotificationCommand {
+    Workbench(LapceWorkbenchCommand),
+    Internal(InternalCommand),
+}
+
+#[derive(Clone, Debug)]
+pub struct NotificationAction {
+    pub title: String,
+    pub command: NotificationCommand,
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub id: NotificationId,
+    pub severity: NotificationSeverity,
+    pub title: String,
+    pub message: String,
+    pub actions: Vec<NotificationAction>,
+    pub time: chrono::DateTime<chrono::Local>,
+}
+
+impl Notification {
+    pub fn new(
+        severity: NotificationSeverity,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: NotificationId::next(),
+            severity,
+            title: title.into(),
+            message: message.into(),
+            actions: Vec::new(),
+            time: chrono::Local::now(),
+        }
+    }
+
+    pub fn error(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(NotificationSeverity::Error, title, message)
+    }
+
+    /// Add a button to the notification, which runs the command and dismisses it.
+    pub fn with_action(
+        mut self,
+        title: impl Into<String>,
+        command: NotificationCommand,
+    ) -> Self {
+        self.actions.push(NotificationAction {
+            title: title.into(),
+            command,
+        });
+        self
+    }
+}
+
+/// The notifications of a window tab, from both the app and the proxy.
+#[derive(Clone, Copy)]
+pub struct NotificationData {
+    /// The notifications which are shown as toasts at the moment
+    pub toasts: RwSignal<im::Vector<Notification>>,
+    /// All the past notifications, newest first
+    pub history: RwSignal<im::Vector<Notification>>,
+    /// Only record the notifications in the history, without showing toasts
+    pub do_not_disturb: RwSignal<bool>,
+    scope: Scope,
+}
+
+impl NotificationData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            toasts: create_rw_signal(cx, im::Vector::new()),
+            history: create_rw_signal(cx, im::Vector::new()),
+            do_not_disturb: create_rw_signal(cx, false),
+            scope: cx,
+        }
+    }
+
+    pub fn notify(&self, notification: Notification) {
+        self.history.update(|history| {
+            history.push_front(notification.clone());
+            history.truncate(HISTORY_LIMIT);
+        });
+
+        if self.do_not_disturb.get_untracked() {
+            return;
+        }
+
+        let id = notification.id;
+        let severity = notification.severity;
+        self.toasts.update(|toasts| toasts.push_back(notification));
+
+        if severity != NotificationSeverity::Error {
+            let data = *self;
+            let send = create_ext_action(self.scope, move |_| {
+                data.dismiss(id);
+            });
+            std::thread::spawn(move || {
+                std::thread::sleep(TOAST_TIMEOUT);
+                send(());
+            });
+        }
+    }
+
+    /// Hide the toast of the notification, which stays in the history.
+    pub fn dismiss(&self, id: NotificationId) {
+        if self
+            .toasts
+            .with_untracked(|toasts| toasts.iter().any(|n| n.id == id))
+        {
+            self.toasts.update(|toasts| toasts.retain(|n| n.id != id));
+        }
+    }
+
+    pub fn clear_history(&self) {
+        self.toasts.set(im::Vector::new());
+        self.history.set(im::Vector::new());
+    }
+
+    pub fn toggle_do_not_disturb(&self) {
+        let do_not_disturb = !self.do_not_disturb.get_untracked();
+        self.do_not_disturb.set(do_not_disturb);
+        if do_not_disturb {
+            self.toasts.set(im::Vector::new());
+        }
+    }
+}
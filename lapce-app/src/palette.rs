@@ -1,7 +1,7 @@
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
-    path::PathBuf,
+    path::{Path, PathBuf},
     rc::Rc,
     sync::{
         atomic::{AtomicU64, Ordering},
@@ -20,24 +20,32 @@
         SignalWith, SignalWithUntracked,
     },
 };
+use fluent_bundle::FluentValue;
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use itertools::Itertools;
 use lapce_core::{
     buffer::rope_text::RopeText, command::FocusCommand, language::LapceLanguage,
     mode::Mode, movement::Movement, selection::Selection,
 };
-use lapce_rpc::proxy::ProxyResponse;
+use lapce_rpc::{dap_types::RunDebugConfigMode, proxy::ProxyResponse};
 use lapce_xi_rope::Rope;
 use lsp_types::DocumentSymbolResponse;
+use tracing::error;
 
 use self::{
     item::{PaletteItem, PaletteItemContent},
     kind::PaletteKind,
 };
 use crate::{
-    command::{CommandExecuted, CommandKind, InternalCommand, WindowCommand},
+    command::{
+        CommandExecuted, CommandKind, InternalCommand, LapceCommand,
+        LapceWorkbenchCommand, WindowCommand,
+    },
+    config::LapceConfig,
     db::LapceDb,
-    debug::{run_configs, RunDebugMode},
+    debug::{run_configs, BreakpointEdit, RunDebugMode, VariableEdit},
+    debug_memory::MemoryEdit,
+    doc::Document,
     editor::{
         location::{EditorLocation, EditorPosition},
         EditorData,
@@ -45,7 +53,8 @@
     id::EditorId,
     keypress::{condition::Condition, KeyPressData, KeyPressFocus},
     main_split::MainSplitData,
-    proxy::path_from_url,
+    profile::Profile,
+    proxy::{askpass::SshPrompt, path_from_url},
     source_control::SourceControlData,
     window_tab::{CommonData, Focus},
     workspace::{LapceWorkspace, LapceWorkspaceType, SshHost},
@@ -54,6 +63,21 @@
 pub mod item;
 pub mod kind;
 
+/// The name of the palette item for not using any profile
+const NO_PROFILE: &str = "No Profile";
+
+/// How many of the commands which were run from the palette are kept
+const COMMAND_HISTORY_LIMIT: usize = 100;
+
+/// The commands which aren't kept in the command history, as repeating them
+/// would only open the history again or repeat another command
+const NOT_IN_HISTORY: &[&str] = &[
+    "palette.command",
+    "palette.command_history",
+    "repeat_last_command",
+    "macro_from_command_history",
+];
+
 #[derive(Clone, PartialEq, Eq)]
 pub enum PaletteStatus {
     Inactive,
@@ -93,9 +117,23 @@ pub struct PaletteData {
     /// Listened on for which entry in the palette has been clicked
     pub clicked_index: RwSignal<Option<usize>>,
     pub executed_commands: Rc<RefCell<HashMap<String, Instant>>>,
+    /// The commands which were run from the palette in this session, with
+    /// their arguments, oldest first
+    pub command_history: Rc<RefCell<Vec<LapceCommand>>>,
     pub executed_run_configs: Rc<RefCell<HashMap<(RunDebugMode, String), Instant>>>,
     pub main_split: MainSplitData,
     pub references: RwSignal<Vec<EditorLocation>>,
+    /// The field of the breakpoint which the palette's input is the value of
+    pub breakpoint_edit: RwSignal<Option<BreakpointEdit>>,
+    /// The variable which the palette's input is the new value of
+    pub variable_edit: RwSignal<Option<VariableEdit>>,
+    /// The memory which the palette's input is the bytes written to
+    pub memory_edit: RwSignal<Option<MemoryEdit>>,
+    /// The prompt of SSH which the palette's input is the answer to
+    pub ssh_prompt: RwSignal<Option<SshPrompt>>,
+    /// The folder which was dropped onto the window, which the palette asks how
+    /// to open
+    pub dropped_folder: RwSignal<Option<PathBuf>>,
     pub source_control: SourceControlData,
     pub common: CommonData,
 }
@@ -113,6 +151,11 @@ pub fn new(
         let items = create_rw_signal(cx, im::Vector::new());
         let index = create_rw_signal(cx, 0);
         let references = create_rw_signal(cx, Vec::new());
+        let breakpoint_edit = create_rw_signal(cx, None);
+        let variable_edit = create_rw_signal(cx, None);
+        let memory_edit = create_rw_signal(cx, None);
+        let ssh_prompt = create_rw_signal(cx, None);
+        let dropped_folder = create_rw_signal(cx, None);
         let input = create_rw_signal(
             cx,
             PaletteInput {
@@ -208,8 +251,14 @@ pub fn new(
             keypress,
             clicked_index,
             executed_commands: Rc::new(RefCell::new(HashMap::new())),
+            command_history: Rc::new(RefCell::new(Vec::new())),
             executed_run_configs: Rc::new(RefCell::new(HashMap::new())),
             references,
+            breakpoint_edit,
+            variable_edit,
+            memory_edit,
+            ssh_prompt,
+            dropped_folder,
             source_control,
             common,
         };
@@ -304,6 +353,7 @@ pub fn new(
 
     /// Start and focus the palette for the given kind.  
     pub fn run(&self, _cx: Scope, kind: PaletteKind) {
+        self.cancel_ssh_prompt();
         self.common.focus.set(Focus::Palette);
         self.status.set(PaletteStatus::Started);
         let symbol = kind.symbol();
@@ -317,6 +367,38 @@ pub fn run(&self, _cx: Scope, kind: PaletteKind) {
             .update(|cursor| cursor.set_insert(Selection::caret(symbol.len())));
     }
 
+    /// Ask for the answer to the prompt of SSH in the palette.
+    pub fn run_ssh_prompt(&self, cx: Scope, prompt: SshPrompt) {
+        self.run(cx, PaletteKind::SshPrompt);
+        self.ssh_prompt.set(Some(prompt));
+    }
+
+    /// Ask whether the folder which was dropped onto the window is opened as the
+    /// workspace of this window tab or of a new one.
+    pub fn run_dropped_folder(&self, cx: Scope, path: PathBuf) {
+        self.dropped_folder.set(Some(path));
+        self.run(cx, PaletteKind::DroppedFolder);
+    }
+
+    /// Cancel the prompt of SSH which the palette was run for, if any.
+    fn cancel_ssh_prompt(&self) {
+        if let Some(prompt) = self.ssh_prompt.get_untracked() {
+            self.ssh_prompt.set(None);
+            prompt.answer(None);
+        }
+    }
+
+    /// Replace the input of the palette which has been run, with the cursor at
+    /// its end.
+    pub fn set_input(&self, input: &str) {
+        self.input_editor
+            .doc
+            .update(|doc| doc.reload(Rope::from(input), true));
+        self.input_editor
+            .cursor
+            .update(|cursor| cursor.set_insert(Selection::caret(input.len())));
+    }
+
     /// Execute the internal behavior of the palette for the given kind. This ignores updating and
     /// focusing the palette input.
     fn run_inner(&self, cx: Scope, kind: PaletteKind) {
@@ -377,6 +459,34 @@ fn run_inner(&self, cx: Scope, kind: PaletteKind) {
             PaletteKind::SCMReferences => {
                 self.get_scm_references(cx);
             }
+            PaletteKind::Profile => {
+                self.get_profiles(cx);
+                let config = self.common.config.get_untracked();
+                let name = config
+                    .profile
+                    .as_ref()
+                    .map(|profile| profile.name().to_string())
+                    .unwrap_or_else(|| NO_PROFILE.to_string());
+                self.preselect_matching(&name);
+            }
+            PaletteKind::ClipboardHistory => {
+                self.get_clipboard_history(cx);
+            }
+            PaletteKind::CommandHistory | PaletteKind::CommandHistoryMacro => {
+                self.get_command_history(cx);
+            }
+            PaletteKind::DroppedFolder => {
+                self.get_dropped_folder_choices(cx);
+            }
+            PaletteKind::BreakpointField
+            | PaletteKind::MacroName
+            | PaletteKind::CollabInvite
+            | PaletteKind::WatchExpression
+            | PaletteKind::VariableValue
+            | PaletteKind::MemoryValue
+            | PaletteKind::SshPrompt => {
+                self.items.set(im::Vector::new());
+            }
         }
     }
 
@@ -460,6 +570,7 @@ fn get_lines(&self, _cx: Scope) {
     fn get_commands(&self, _cx: Scope) {
         const EXCLUDED_ITEMS: &[&str] = &["palette.command"];
 
+        let config = self.common.config.get_untracked();
         let items = self.keypress.with_untracked(|keypress| {
             // Get all the commands we've executed, and sort them by how recently they were
             // executed. Ignore commands without descriptions.
@@ -471,11 +582,15 @@ fn get_commands(&self, _cx: Scope) {
                 .rev()
                 .filter_map(|(key, _)| {
                     keypress.commands.get(key).and_then(|c| {
-                        c.kind.desc().as_ref().map(|m| PaletteItem {
-                            content: PaletteItemContent::Command { cmd: c.clone() },
-                            filter_text: m.to_string(),
-                            score: 0,
-                            indices: vec![],
+                        c.kind.localized_desc(&config.localization).map(|m| {
+                            PaletteItem {
+                                content: PaletteItemContent::Command {
+                                    cmd: c.clone(),
+                                },
+                                filter_text: m,
+                                score: 0,
+                                indices: vec![],
+                            }
                         })
                     })
                 })
@@ -491,12 +606,14 @@ fn get_commands(&self, _cx: Scope) {
                     return None;
                 }
 
-                c.kind.desc().as_ref().map(|m| PaletteItem {
-                    content: PaletteItemContent::Command { cmd: c.clone() },
-                    filter_text: m.to_string(),
-                    score: 0,
-                    indices: vec![],
-                })
+                c.kind
+                    .localized_desc(&config.localization)
+                    .map(|m| PaletteItem {
+                        content: PaletteItemContent::Command { cmd: c.clone() },
+                        filter_text: m,
+                        score: 0,
+                        indices: vec![],
+                    })
             }));
 
             items
@@ -623,7 +740,8 @@ fn get_document_symbols(&self, _cx: Scope) {
                 };
                 set_items.set(items);
             } else {
-                set_items.update(|items| items.clear());
+                // Without a language server the symbols come from the syntax tree
+                set_items.set(doc.with_untracked(syntax_document_symbols));
             }
         });
 
@@ -703,38 +821,79 @@ fn get_ssh_hosts(&self, cx: Scope) {
     }
 
     fn get_run_configs(&self, _cx: Scope) {
+        // The runnables of the language server for the active file, such as its
+        // tests, come along with the configs of the workspace
+        let active_doc = self
+            .main_split
+            .active_editor
+            .get_untracked()
+            .map(|editor| editor.with_untracked(|editor| editor.doc));
+        let runnables = active_doc
+            .map(|doc| {
+                doc.with_untracked(|doc| {
+                    doc.runnables
+                        .iter()
+                        .map(|runnable| runnable.run_config())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .unwrap_or_default();
+        let active_file = active_doc
+            .and_then(|doc| doc.with_untracked(|doc| doc.content.path().cloned()));
+
         let configs = run_configs(self.common.workspace.path.as_deref());
-        if configs.is_none() {
-            if let Some(path) = self.workspace.path.as_ref() {
-                let path = path.join(".lapce").join("run.toml");
-                self.common
-                    .internal_command
-                    .send(InternalCommand::OpenFile { path });
+        let workspace = self.workspace.path.clone();
+        let internal_command = self.common.internal_command;
+        let executed_run_configs = self.executed_run_configs.clone();
+        let set_items = self.items.write_only();
+        // The configs of `.lapce/launch.toml` are read by the proxy, which
+        // substitutes their variables
+        let send = create_ext_action(self.common.scope, move |result| {
+            let launch_configs = match result {
+                Ok(ProxyResponse::GetLaunchConfigs { configs }) => configs,
+                Err(e) => {
+                    error!("failed to get the launch configs: {}", e.message);
+                    Vec::new()
+                }
+                _ => Vec::new(),
+            };
+            if configs.is_none() && launch_configs.is_empty() && runnables.is_empty()
+            {
+                if let Some(path) = workspace.as_ref() {
+                    let path = path.join(".lapce").join("run.toml");
+                    internal_command.send(InternalCommand::OpenFile { path });
+                }
             }
-        }
-        let executed_run_configs = self.executed_run_configs.borrow();
+            let executed_run_configs = executed_run_configs.borrow();
 
-        let mut items = Vec::new();
-        if let Some(configs) = configs.as_ref() {
-            for config in &configs.configs {
-                items.push((
-                    executed_run_configs
-                        .get(&(RunDebugMode::Run, config.name.clone())),
-                    PaletteItem {
-                        content: PaletteItemContent::RunAndDebug {
-                            mode: RunDebugMode::Run,
-                            config: config.clone(),
+            let mut items = Vec::new();
+            let configs = configs
+                .iter()
+                .flat_map(|configs| configs.configs.iter())
+                .chain(launch_configs.iter())
+                .chain(runnables.iter());
+            for config in configs {
+                // A config which attaches to a process can only be debugged
+                if config.mode == RunDebugConfigMode::Launch {
+                    items.push((
+                        executed_run_configs
+                            .get(&(RunDebugMode::Run, config.name.clone())),
+                        PaletteItem {
+                            content: PaletteItemContent::RunAndDebug {
+                                mode: RunDebugMode::Run,
+                                config: config.clone(),
+                            },
+                            filter_text: format!(
+                                "Run {} {} {}",
+                                config.name,
+                                config.program,
+                                config.args.join(" ")
+                            ),
+                            score: 0,
+                            indices: vec![],
                         },
-                        filter_text: format!(
-                            "Run {} {} {}",
-                            config.name,
-                            config.program,
-                            config.args.join(" ")
-                        ),
-                        score: 0,
-                        indices: vec![],
-                    },
-                ));
+                    ));
+                }
                 items.push((
                     executed_run_configs
                         .get(&(RunDebugMode::Debug, config.name.clone())),
@@ -754,11 +913,18 @@ fn get_run_configs(&self, _cx: Scope) {
                     },
                 ));
             }
-        }
 
-        items.sort_by_key(|(executed, _item)| std::cmp::Reverse(executed.copied()));
-        self.items
-            .set(items.into_iter().map(|(_, item)| item).collect());
+            items.sort_by_key(|(executed, _item)| {
+                std::cmp::Reverse(executed.copied())
+            });
+            set_items.set(items.into_iter().map(|(_, item)| item).collect());
+        });
+
+        self.common
+            .proxy
+            .get_launch_configs(active_file, move |result| {
+                send(result);
+            });
     }
 
     fn get_color_themes(&self, _cx: Scope) {
@@ -776,6 +942,144 @@ fn get_color_themes(&self, _cx: Scope) {
         self.items.set(items);
     }
 
+    fn get_profiles(&self, _cx: Scope) {
+        let items = std::iter::once(PaletteItem {
+            content: PaletteItemContent::Profile {
+                id: String::new(),
+                name: NO_PROFILE.to_string(),
+            },
+            filter_text: NO_PROFILE.to_string(),
+            score: 0,
+            indices: Vec::new(),
+        })
+        .chain(Profile::all().into_iter().map(|profile| {
+            let name = profile.name().to_string();
+            PaletteItem {
+                content: PaletteItemContent::Profile {
+                    id: profile.id,
+                    name: name.clone(),
+                },
+                filter_text: name,
+                score: 0,
+                indices: Vec::new(),
+            }
+        }))
+        .collect();
+        self.items.set(items);
+    }
+
+    fn get_clipboard_history(&self, _cx: Scope) {
+        let items = self
+            .common
+            .clipboard_history
+            .items
+            .get_untracked()
+            .into_iter()
+            .map(|content| PaletteItem {
+                // Multiple lines are shown on one line
+                filter_text: content
+                    .trim()
+                    .lines()
+                    .map(|line| line.trim())
+                    .join(" ⏎ "),
+                content: PaletteItemContent::ClipboardHistory { content },
+                score: 0,
+                indices: Vec::new(),
+            })
+            .collect();
+        self.items.set(items);
+    }
+
+    /// Initialize the palette with the ways the dropped folder can be opened.
+    fn get_dropped_folder_choices(&self, _cx: Scope) {
+        let Some(path) = self.dropped_folder.get_untracked() else {
+            self.items.set(im::Vector::new());
+            return;
+        };
+        let config = self.common.config.get_untracked();
+        let folder = path.to_string_lossy().to_string();
+        let items = [
+            (false, "palette-dropped-folder-open"),
+            (true, "palette-dropped-folder-new-tab"),
+        ]
+        .into_iter()
+        .map(|(new_tab, id)| PaletteItem {
+            content: PaletteItemContent::DroppedFolder {
+                path: path.clone(),
+                new_tab,
+            },
+            filter_text: config
+                .tr_args(id, [("folder", FluentValue::from(folder.as_str()))]),
+            score: 0,
+            indices: Vec::new(),
+        })
+        .collect();
+        self.items.set(items);
+    }
+
+    /// Initialize the palette with the command history, the latest first.
+    fn get_command_history(&self, _cx: Scope) {
+        let config = self.common.config.get_untracked();
+        let for_macro =
+            self.kind.get_untracked() == PaletteKind::CommandHistoryMacro;
+        let items = self
+            .command_history
+            .borrow()
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, cmd)| {
+                let desc = cmd
+                    .kind
+                    .localized_desc(&config.localization)
+                    .unwrap_or_else(|| cmd.kind.str().to_string());
+                let filter_text = match cmd.data.as_ref() {
+                    // A macro can't run a command with arguments, which is left
+                    // out of it
+                    Some(data) if for_macro => {
+                        format!(
+                            "{desc} {data} {}",
+                            config.tr("palette-not-in-macro")
+                        )
+                    }
+                    Some(data) => format!("{desc} {data}"),
+                    None => desc,
+                };
+                PaletteItem {
+                    content: PaletteItemContent::CommandHistory {
+                        index,
+                        cmd: cmd.clone(),
+                    },
+                    filter_text,
+                    score: 0,
+                    indices: Vec::new(),
+                }
+            })
+            .collect();
+        self.items.set(items);
+    }
+
+    /// Run a command of the palette, which is kept in the command history.
+    pub fn run_command(&self, cmd: LapceCommand) {
+        let name = cmd.kind.str();
+        if !NOT_IN_HISTORY.contains(&name) {
+            self.executed_commands
+                .borrow_mut()
+                .insert(name.to_string(), Instant::now());
+            let mut history = self.command_history.borrow_mut();
+            history.push(cmd.clone());
+            if history.len() > COMMAND_HISTORY_LIMIT {
+                history.remove(0);
+            }
+        }
+        self.common.lapce_command.send(cmd);
+    }
+
+    /// The command which was run last from the palette.
+    pub fn last_command(&self) -> Option<LapceCommand> {
+        self.command_history.borrow().last().cloned()
+    }
+
     fn get_icon_themes(&self, _cx: Scope) {
         let config = self.common.config.get_untracked();
         let items = config
@@ -791,6 +1095,45 @@ fn get_icon_themes(&self, _cx: Scope) {
         self.items.set(items);
     }
 
+    /// Keep the language which was chosen for the file in the file associations
+    /// of the workspace, so that the file has it when it's opened again.
+    fn save_file_association(&self, path: &Path, language: LapceLanguage) {
+        let Some(relative) = self
+            .workspace
+            .path
+            .as_ref()
+            .and_then(|workspace| path.strip_prefix(workspace).ok())
+        else {
+            return;
+        };
+        // The characters of the name which a glob would take as a pattern match
+        // only themselves in brackets
+        let pattern = relative
+            .to_string_lossy()
+            .replace('\\', "/")
+            .chars()
+            .map(|c| {
+                if "*?[]{}".contains(c) {
+                    format!("[{c}]")
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect::<String>();
+        if LapceConfig::update_workspace_file(
+            &self.workspace,
+            "core.file-associations",
+            &pattern,
+            toml_edit::Value::from(language.as_ref().to_lowercase()),
+        )
+        .is_some()
+        {
+            self.common
+                .internal_command
+                .send(InternalCommand::ReloadConfig);
+        }
+    }
+
     fn get_languages(&self, _cx: Scope) {
         let langs = LapceLanguage::languages();
         let items = langs
@@ -879,7 +1222,29 @@ fn select(&self) {
                     );
                 }
                 PaletteItemContent::Command { cmd } => {
-                    self.common.lapce_command.send(cmd.clone());
+                    self.run_command(cmd.clone());
+                }
+                PaletteItemContent::CommandHistory { index, cmd } => {
+                    if self.kind.get_untracked() == PaletteKind::CommandHistoryMacro
+                    {
+                        let commands = self
+                            .command_history
+                            .borrow()
+                            .get(*index..)
+                            .unwrap_or_default()
+                            .to_vec();
+                        let macros = self.keypress.with_untracked(|k| k.macros);
+                        let made = macros
+                            .try_update(|macros| macros.set_last_commands(&commands))
+                            .unwrap_or(false);
+                        if made {
+                            self.common
+                                .workbench_command
+                                .send(LapceWorkbenchCommand::SaveMacro);
+                        }
+                    } else {
+                        self.run_command(cmd.clone());
+                    }
                 }
                 PaletteItemContent::Workspace { workspace } => {
                     self.common
@@ -962,6 +1327,32 @@ fn select(&self) {
                         name: name.clone(),
                         save: true,
                     }),
+                PaletteItemContent::Profile { id, .. } => self
+                    .common
+                    .internal_command
+                    .send(InternalCommand::SetProfile { id: id.clone() }),
+                PaletteItemContent::DroppedFolder { path, new_tab } => {
+                    let workspace = LapceWorkspace {
+                        kind: LapceWorkspaceType::Local,
+                        path: Some(path.clone()),
+                        last_open: 0,
+                    };
+                    self.common.window_command.send(if *new_tab {
+                        WindowCommand::NewWorkspaceTab {
+                            workspace,
+                            end: false,
+                        }
+                    } else {
+                        WindowCommand::SetWorkspace { workspace }
+                    });
+                }
+                PaletteItemContent::ClipboardHistory { content } => {
+                    if let Some(editor) =
+                        self.main_split.active_editor.get_untracked()
+                    {
+                        editor.get_untracked().paste(content.clone());
+                    }
+                }
                 PaletteItemContent::Language { name } => {
                     let editor = self.main_split.active_editor.get_untracked();
                     let doc = match editor {
@@ -970,18 +1361,28 @@ fn select(&self) {
                             return;
                         }
                     };
+                    let plain_text =
+                        name.is_empty() || name.to_lowercase().eq("plain text");
+                    let language = if plain_text {
+                        LapceLanguage::Plaintext
+                    } else {
+                        match LapceLanguage::from_name(name) {
+                            Some(v) => v,
+                            None => return,
+                        }
+                    };
                     doc.update(|doc| {
-                        if name.is_empty() || name.to_lowercase().eq("plain text") {
+                        if plain_text {
                             doc.set_syntax(None)
                         } else {
-                            let lang = match LapceLanguage::from_name(name) {
-                                Some(v) => v,
-                                None => return,
-                            };
-                            doc.set_language(lang);
+                            doc.set_language(language);
                         }
                         doc.trigger_syntax_change(None);
                     });
+                    let path = doc.with_untracked(|doc| doc.content.path().cloned());
+                    if let Some(path) = path {
+                        self.save_file_association(&path, language);
+                    }
                 }
                 PaletteItemContent::SCMReference { name } => {
                     self.common
@@ -1006,6 +1407,52 @@ fn select(&self) {
                         last_open: 0,
                     },
                 });
+        } else if self.kind.get_untracked() == PaletteKind::BreakpointField {
+            if let Some(edit) = self.breakpoint_edit.get_untracked() {
+                let value = self.input.with_untracked(|input| input.input.clone());
+                self.common
+                    .internal_command
+                    .send(InternalCommand::SetBreakpointField { edit, value });
+            }
+        } else if self.kind.get_untracked() == PaletteKind::MacroName {
+            let name = self
+                .input
+                .with_untracked(|input| input.input.trim().to_string());
+            if !name.is_empty() {
+                self.common
+                    .internal_command
+                    .send(InternalCommand::SaveMacro { name });
+            }
+        } else if self.kind.get_untracked() == PaletteKind::CollabInvite {
+            let invite = self.input.with_untracked(|input| input.input.clone());
+            self.common
+                .internal_command
+                .send(InternalCommand::JoinCollaboration { invite });
+        } else if self.kind.get_untracked() == PaletteKind::WatchExpression {
+            let expression = self.input.with_untracked(|input| input.input.clone());
+            self.common
+                .internal_command
+                .send(InternalCommand::AddWatchExpression { expression });
+        } else if self.kind.get_untracked() == PaletteKind::VariableValue {
+            if let Some(edit) = self.variable_edit.get_untracked() {
+                let value = self.input.with_untracked(|input| input.input.clone());
+                self.common
+                    .internal_command
+                    .send(InternalCommand::SetVariableValue { edit, value });
+            }
+        } else if self.kind.get_untracked() == PaletteKind::MemoryValue {
+            if let Some(edit) = self.memory_edit.get_untracked() {
+                let value = self.input.with_untracked(|input| input.input.clone());
+                self.common
+                    .internal_command
+                    .send(InternalCommand::WriteMemory { edit, value });
+            }
+        } else if self.kind.get_untracked() == PaletteKind::SshPrompt {
+            if let Some(prompt) = self.ssh_prompt.get_untracked() {
+                self.ssh_prompt.set(None);
+                let answer = self.input.with_untracked(|input| input.input.clone());
+                prompt.answer(Some(answer));
+            }
         }
     }
 
@@ -1050,6 +1497,7 @@ fn preview(&self, _cx: Scope) {
                     );
                 }
                 PaletteItemContent::Command { .. } => {}
+                PaletteItemContent::CommandHistory { .. } => {}
                 PaletteItemContent::Workspace { .. } => {}
                 PaletteItemContent::RunAndDebug { .. } => {}
                 PaletteItemContent::SshHost { .. } => {}
@@ -1120,7 +1568,10 @@ fn preview(&self, _cx: Scope) {
                         name: name.clone(),
                         save: false,
                     }),
-                PaletteItemContent::SCMReference { .. } => {}
+                PaletteItemContent::SCMReference { .. }
+                | PaletteItemContent::Profile { .. }
+                | PaletteItemContent::ClipboardHistory { .. }
+                | PaletteItemContent::DroppedFolder { .. } => {}
             }
         }
     }
@@ -1135,6 +1586,7 @@ fn cancel(&self) {
                 .internal_command
                 .send(InternalCommand::ReloadConfig);
         }
+        self.cancel_ssh_prompt();
 
         self.close();
     }
@@ -1331,3 +1783,29 @@ fn receive_char(&self, c: &str) {
         self.input_editor.receive_char(c);
     }
 }
+
+/// The document symbols which the syntax tree of the document finds.
+fn syntax_document_symbols(doc: &Document) -> im::Vector<PaletteItem> {
+    let Some(syntax) = doc.syntax() else {
+        return im::Vector::new();
+    };
+    let buffer = doc.buffer();
+    syntax
+        .symbols()
+        .into_iter()
+        .map(|symbol| PaletteItem {
+            content: PaletteItemContent::DocumentSymbol {
+                kind: symbol.kind,
+                name: symbol.name.clone(),
+                range: lsp_types::Range {
+                    start: buffer.offset_to_position(symbol.name_range.start),
+                    end: buffer.offset_to_position(symbol.name_range.end),
+                },
+                container_name: None,
+            },
+            filter_text: symbol.name,
+            score: 0,
+            indices: Vec::new(),
+        })
+        .collect()
+}
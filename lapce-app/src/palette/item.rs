@@ -31,6 +31,11 @@ pub enum PaletteItemContent {
     Command {
         cmd: LapceCommand,
     },
+    /// A command of the command history, which is at `index` in it
+    CommandHistory {
+        index: usize,
+        cmd: LapceCommand,
+    },
     Workspace {
         workspace: LapceWorkspace,
     },
@@ -69,4 +74,18 @@ pub enum PaletteItemContent {
     SCMReference {
         name: String,
     },
+    Profile {
+        /// The id of the profile, empty for using no profile
+        id: String,
+        name: String,
+    },
+    ClipboardHistory {
+        content: String,
+    },
+    /// Open the dropped folder as the workspace of this window tab, or of a new
+    /// one
+    DroppedFolder {
+        path: PathBuf,
+        new_tab: bool,
+    },
 }
@@ -13,6 +13,30 @@ pub enum PaletteKind {
     IconTheme,
     Language,
     SCMReferences,
+    Profile,
+    ClipboardHistory,
+    /// The commands which were run from the palette, to run one again
+    CommandHistory,
+    /// The commands which were run from the palette, to make a macro of the
+    /// picked one and the ones after it
+    CommandHistoryMacro,
+    /// The input is the value of the field of a breakpoint
+    BreakpointField,
+    /// The input is the name which the last macro is saved as
+    MacroName,
+    /// The input is the invite of the collaboration session which is joined
+    CollabInvite,
+    /// The input is the expression which is added to the watch panel
+    WatchExpression,
+    /// The input is the new value of a variable of the debugger
+    VariableValue,
+    /// The input is the bytes, in hex, which are written to the memory of the
+    /// debugger
+    MemoryValue,
+    /// The input is the answer to a prompt of SSH, like a password
+    SshPrompt,
+    /// How the folder which was dropped onto the window is opened
+    DroppedFolder,
 }
 
 impl PaletteKind {
@@ -32,7 +56,19 @@ pub fn symbol(&self) -> &'static str {
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
-            | PaletteKind::SCMReferences => "",
+            | PaletteKind::SCMReferences
+            | PaletteKind::Profile
+            | PaletteKind::ClipboardHistory
+            | PaletteKind::CommandHistory
+            | PaletteKind::CommandHistoryMacro
+            | PaletteKind::BreakpointField
+            | PaletteKind::MacroName
+            | PaletteKind::CollabInvite
+            | PaletteKind::WatchExpression
+            | PaletteKind::VariableValue
+            | PaletteKind::MemoryValue
+            | PaletteKind::SshPrompt
+            | PaletteKind::DroppedFolder => "",
         }
     }
 
@@ -68,7 +104,19 @@ pub fn get_input<'a>(&self, input: &'a str) -> &'a str {
             | PaletteKind::ColorTheme
             | PaletteKind::IconTheme
             | PaletteKind::Language
-            | PaletteKind::SCMReferences => input,
+            | PaletteKind::SCMReferences
+            | PaletteKind::Profile
+            | PaletteKind::ClipboardHistory
+            | PaletteKind::CommandHistory
+            | PaletteKind::CommandHistoryMacro
+            | PaletteKind::BreakpointField
+            | PaletteKind::MacroName
+            | PaletteKind::CollabInvite
+            | PaletteKind::WatchExpression
+            | PaletteKind::VariableValue
+            | PaletteKind::MemoryValue
+            | PaletteKind::SshPrompt
+            | PaletteKind::DroppedFolder => input,
             PaletteKind::Command
             | PaletteKind::Workspace
             | PaletteKind::DocumentSymbol
@@ -0,0 +1,428 @@
+use std::{path::PathBuf, sync::Arc};
+
+use floem::{
+    reactive::{create_memo, ReadSignal, SignalGet, SignalGetUntracked, SignalWith},
+    style::{CursorStyle, Style},
+    view::View,
+    views::{container, label, list, scroll, stack, Decorators},
+    ViewContext,
+};
+use fluent_bundle::FluentValue;
+
+use crate::{
+    collab::{
+        participant_color,
+        protocol::{Participant, Permissions, HOST},
+        CollabData,
+    },
+    command::{InternalCommand, LapceWorkbenchCommand},
+    config::{color::LapceColor, LapceConfig},
+    listener::Listener,
+    window_tab::WindowTabData,
+};
+
+pub fn collab_panel(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let cx = ViewContext::get_current();
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let workbench_command = window_tab_data.common.workbench_command;
+    let collab = window_tab_data.common.collab.clone();
+    let session = collab.session;
+
+    let is_active = move || session.with(|session| session.is_some());
+    let is_host = move || {
+        session.with(|session| session.as_ref().map_or(false, |s| s.is_host()))
+    };
+    let summary = move || {
+        let config = config.get();
+        session.with(|session| match session {
+            None => config.tr("collab-not-shared"),
+            Some(session) if session.is_host() => config.tr("collab-hosting"),
+            Some(session) if session.participant.is_none() => {
+                config.tr("collab-connecting")
+            }
+            Some(session) => config.tr_args(
+                "collab-joined",
+                [(
+                    "host",
+                    FluentValue::from(
+                        session
+                            .participant(HOST)
+                            .map(|p| p.name.clone())
+                            .unwrap_or_default(),
+                    ),
+                )],
+            ),
+        })
+    };
+    let invite = move || {
+        session.with(|session| {
+            session
+                .as_ref()
+                .and_then(|session| session.invite.as_ref())
+                .map(|invite| invite.to_string())
+        })
+    };
+    let participants = create_memo(cx.scope, move |_| {
+        session.with(|session| {
+            session
+                .as_ref()
+                .map(|session| session.participants.clone())
+                .unwrap_or_default()
+        })
+    });
+    let files = create_memo(cx.scope, move |_| {
+        session.with(|session| {
+            session
+                .as_ref()
+                .map(|session| session.files.clone())
+                .unwrap_or_default()
+        })
+    });
+
+    stack(move || {
+        (
+            label(move || config.get().tr("panel-collaboration")).style(move || {
+                Style::BASE
+                    .padding_horiz_px(10.0)
+                    .padding_vert_px(6.0)
+                    .width_pct(100.0)
+                    .background(
+                        *config.get().get_color(LapceColor::EDITOR_BACKGROUND),
+                    )
+            }),
+            container(|| {
+                scroll(move || {
+                    stack(move || {
+                        (
+                            label(summary).style(move || {
+                                Style::BASE.color(
+                                    *config.get().get_color(LapceColor::EDITOR_DIM),
+                                )
+                            }),
+                            label(move || invite().unwrap_or_default()).style(
+                                move || {
+                                    Style::BASE
+                                        .width_pct(100.0)
+                                        .text_ellipsis()
+                                        .apply_if(invite().is_none(), |s| s.hide())
+                                },
+                            ),
+                            button(
+                                move || config.get().tr("collab-copy-invite"),
+                                move || {
+                                    workbench_command.send(
+                                        LapceWorkbenchCommand::CopyCollaborationInvite,
+                                    )
+                                },
+                                move || invite().is_none(),
+                                config,
+                            ),
+                            button(
+                                move || config.get().tr("collab-start"),
+                                move || {
+                                    workbench_command.send(
+                                        LapceWorkbenchCommand::StartCollaboration,
+                                    )
+                                },
+                                is_active,
+                                config,
+                            ),
+                            button(
+                                move || config.get().tr("collab-join"),
+                                move || {
+                                    workbench_command.send(
+                                        LapceWorkbenchCommand::JoinCollaboration,
+                                    )
+                                },
+                                is_active,
+                                config,
+                            ),
+                            button(
+                                move || {
+                                    config.get().tr(if is_host() {
+                                        "collab-stop"
+                                    } else {
+                                        "collab-leave"
+                                    })
+                                },
+                                move || {
+                                    workbench_command.send(
+                                        LapceWorkbenchCommand::StopCollaboration,
+                                    )
+                                },
+                                move || !is_active(),
+                                config,
+                            ),
+                            section_header("collab-participants", is_active, config),
+                            list(
+                                move || participants.get(),
+                                |participant| participant.id,
+                                move |participant| {
+                                    participant_view(
+                                        participant,
+                                        collab.clone(),
+                                        internal_command,
+                                        config,
+                                    )
+                                },
+                            )
+                            .style(|| Style::BASE.flex_col().width_pct(100.0)),
+                            section_header("collab-files", is_active, config),
+                            label(move || config.get().tr("collab-no-files")).style(
+                                move || {
+                                    Style::BASE
+                                        .color(
+                                            *config
+                                                .get()
+                                                .get_color(LapceColor::EDITOR_DIM),
+                                        )
+                                        .apply_if(
+                                            !is_active()
+                                                || !files.with(|files| {
+                                                    files.is_empty()
+                                                }),
+                                            |s| s.hide(),
+                                        )
+                                },
+                            ),
+                            list(
+                                move || files.get(),
+                                |path| path.clone(),
+                                move |path| file_view(path, internal_command, config),
+                            )
+                            .style(|| Style::BASE.flex_col().width_pct(100.0)),
+                        )
+                    })
+                    .style(|| {
+                        Style::BASE
+                            .flex_col()
+                            .padding_horiz_px(10.0)
+                            .width_pct(100.0)
+                            .line_height(1.6)
+                    })
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|| Style::BASE.size_pct(100.0, 100.0)),
+        )
+    })
+    .style(|| Style::BASE.flex_col().size_pct(100.0, 100.0))
+}
+
+fn button(
+    text: impl Fn() -> String + 'static,
+    on_click: impl Fn() + 'static,
+    hidden: impl Fn() -> bool + 'static,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    label(text)
+        .on_click(move |_| {
+            on_click();
+            true
+        })
+        .style(move || {
+            Style::BASE
+                .margin_top_px(6.0)
+                .width_pct(100.0)
+                .justify_center()
+                .border(1.0)
+                .border_radius(6.0)
+                .border_color(*config.get().get_color(LapceColor::LAPCE_BORDER))
+                .apply_if(hidden(), |s| s.hide())
+        })
+        .hover_style(move || {
+            Style::BASE.cursor(CursorStyle::Pointer).background(
+                *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+            )
+        })
+        .active_style(move || {
+            Style::BASE.background(
+                *config
+                    .get()
+                    .get_color(LapceColor::PANEL_HOVERED_ACTIVE_BACKGROUND),
+            )
+        })
+}
+
+fn section_header(
+    message_id: &'static str,
+    is_active: impl Fn() -> bool + 'static,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    label(move || config.get().tr(message_id)).style(move || {
+        Style::BASE
+            .margin_top_px(10.0)
+            .font_bold()
+            .apply_if(!is_active(), |s| s.hide())
+    })
+}
+
+fn participant_view(
+    participant: Participant,
+    collab: CollabData,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let id = participant.id;
+    let session = collab.session;
+    let following = collab.following;
+    let is_me = move || {
+        session.with(|session| {
+            session.as_ref().and_then(|session| session.participant) == Some(id)
+        })
+    };
+    let can_change = move || {
+        session.with(|session| {
+            session
+                .as_ref()
+                .map_or(false, |session| session.is_host() && id != HOST)
+        })
+    };
+    let permissions = move || {
+        session.with(|session| {
+            session
+                .as_ref()
+                .and_then(|session| session.participant(id))
+                .map(|p| p.permissions)
+        })
+    };
+    let name = move || {
+        let config = config.get();
+        let mut name = participant.name.clone();
+        if is_me() {
+            name = format!("{name} {}", config.tr("collab-you"));
+        } else if id == HOST {
+            name = format!("{name} {}", config.tr("collab-host"));
+        }
+        name
+    };
+
+    stack(move || {
+        (
+            label(|| "●".to_string()).style(move || {
+                Style::BASE
+                    .margin_right_px(6.0)
+                    .color(participant_color(id))
+            }),
+            label(name).style(|| {
+                Style::BASE.flex_grow(1.0).min_width_px(0.0).text_ellipsis()
+            }),
+            permission_toggle(
+                "collab-can-edit",
+                move || permissions().map_or(false, |p| p.edit),
+                move || {
+                    if let Some(permissions) = permissions() {
+                        internal_command.send(
+                            InternalCommand::SetCollaborationPermissions {
+                                participant: id,
+                                permissions: Permissions {
+                                    edit: !permissions.edit,
+                                    ..permissions
+                                },
+                            },
+                        );
+                    }
+                },
+                can_change,
+                config,
+            ),
+            permission_toggle(
+                "collab-can-use-terminals",
+                move || permissions().map_or(false, |p| p.terminal),
+                move || {
+                    if let Some(permissions) = permissions() {
+                        internal_command.send(
+                            InternalCommand::SetCollaborationPermissions {
+                                participant: id,
+                                permissions: Permissions {
+                                    terminal: !permissions.terminal,
+                                    ..permissions
+                                },
+                            },
+                        );
+                    }
+                },
+                can_change,
+                config,
+            ),
+            label(move || {
+                config.get().tr(if following.get() == Some(id) {
+                    "collab-unfollow"
+                } else {
+                    "collab-follow"
+                })
+            })
+            .on_click(move |_| {
+                let participant =
+                    (following.get_untracked() != Some(id)).then_some(id);
+                internal_command
+                    .send(InternalCommand::FollowParticipant { participant });
+                true
+            })
+            .style(move || {
+                Style::BASE
+                    .margin_left_px(6.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_LINK))
+                    .apply_if(is_me(), |s| s.hide())
+            })
+            .hover_style(|| Style::BASE.cursor(CursorStyle::Pointer)),
+        )
+    })
+    .style(|| Style::BASE.items_center().width_pct(100.0))
+}
+
+/// A permission of a guest, which the host can click to change.
+fn permission_toggle(
+    message_id: &'static str,
+    allowed: impl Fn() -> bool + 'static + Copy,
+    on_click: impl Fn() + 'static,
+    can_change: impl Fn() -> bool + 'static + Copy,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    label(move || config.get().tr(message_id))
+        .on_click(move |_| {
+            if can_change() {
+                on_click();
+            }
+            true
+        })
+        .style(move || {
+            let config = config.get();
+            let color = if allowed() {
+                LapceColor::EDITOR_FOREGROUND
+            } else {
+                LapceColor::EDITOR_DIM
+            };
+            Style::BASE
+                .margin_left_px(6.0)
+                .color(*config.get_color(color))
+                .apply_if(allowed(), |s| s.font_bold())
+        })
+        .hover_style(move || {
+            Style::BASE.apply_if(can_change(), |s| s.cursor(CursorStyle::Pointer))
+        })
+}
+
+fn file_view(
+    path: PathBuf,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let name = path.to_string_lossy().to_string();
+    label(move || name.clone())
+        .on_click(move |_| {
+            internal_command
+                .send(InternalCommand::OpenCollaborationFile { path: path.clone() });
+            true
+        })
+        .style(|| Style::BASE.width_pct(100.0).text_ellipsis())
+        .hover_style(move || {
+            Style::BASE.cursor(CursorStyle::Pointer).background(
+                *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+            )
+        })
+}
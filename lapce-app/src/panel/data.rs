@@ -27,11 +27,23 @@ pub fn default_panel_order() -> PanelOrder {
             PanelKind::Plugin,
             PanelKind::SourceControl,
             PanelKind::Debug,
+            PanelKind::TestExplorer,
+            PanelKind::Collaboration,
         ],
     );
     order.insert(
         PanelPosition::BottomLeft,
-        im::vector![PanelKind::Terminal, PanelKind::Search, PanelKind::Problem,],
+        im::vector![
+            PanelKind::Terminal,
+            PanelKind::Search,
+            PanelKind::Problem,
+            PanelKind::Todo,
+            PanelKind::Notification,
+            PanelKind::Log,
+            PanelKind::HttpResponse,
+            PanelKind::DebugConsole,
+            PanelKind::DebugMemory,
+        ],
     );
 
     order
@@ -194,6 +206,13 @@ pub fn panel_position(
             .with_untracked(|panels| panel_position(panels, kind))
     }
 
+    /// The panels which are shown, which is the active one of each position
+    /// that's shown.
+    pub fn visible_panels(&self) -> im::HashSet<PanelKind> {
+        self.panels
+            .with(|panels| self.styles.with(|styles| visible_panels(panels, styles)))
+    }
+
     pub fn is_panel_visible(&self, kind: &PanelKind) -> bool {
         if let Some((index, position)) = self.panel_position(kind) {
             if let Some(style) = self
@@ -264,6 +283,23 @@ pub fn active_panel_at_position(
             .map(|p| (p, style.shown))
     }
 
+    /// The panels which are shown, from the top left around to the bottom right.
+    pub fn visible_panels(&self, tracked: bool) -> Vec<PanelKind> {
+        [
+            PanelPosition::LeftTop,
+            PanelPosition::LeftBottom,
+            PanelPosition::BottomLeft,
+            PanelPosition::BottomRight,
+            PanelPosition::RightTop,
+            PanelPosition::RightBottom,
+        ]
+        .iter()
+        .filter_map(|position| self.active_panel_at_position(position, tracked))
+        .filter(|(_, shown)| *shown)
+        .map(|(kind, _)| kind)
+        .collect()
+    }
+
     pub fn set_shown(&self, position: &PanelPosition, shown: bool) {
         self.styles.update(|styles| {
             if let Some(style) = styles.get_mut(position) {
@@ -391,6 +427,19 @@ pub fn move_panel_to_position(&self, kind: PanelKind, position: &PanelPosition)
     }
 }
 
+fn visible_panels(
+    order: &PanelOrder,
+    styles: &im::HashMap<PanelPosition, PanelStyle>,
+) -> im::HashSet<PanelKind> {
+    order
+        .iter()
+        .filter_map(|(position, panels)| {
+            let style = styles.get(position).filter(|style| style.shown)?;
+            panels.get(style.active).copied()
+        })
+        .collect()
+}
+
 pub fn panel_position(
     order: &PanelOrder,
     kind: &PanelKind,
@@ -403,3 +452,59 @@ pub fn panel_position(
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_panels() {
+        let order = default_panel_order();
+        let mut styles = im::HashMap::new();
+        styles.insert(
+            PanelPosition::LeftTop,
+            PanelStyle {
+                active: 0,
+                shown: true,
+                maximized: false,
+            },
+        );
+        styles.insert(
+            PanelPosition::BottomLeft,
+            PanelStyle {
+                active: 2,
+                shown: true,
+                maximized: false,
+            },
+        );
+        assert_eq!(
+            im::hashset![PanelKind::FileExplorer, PanelKind::Problem],
+            visible_panels(&order, &styles)
+        );
+
+        // A position which is hidden shows none of its panels
+        styles.insert(
+            PanelPosition::LeftTop,
+            PanelStyle {
+                active: 1,
+                shown: false,
+                maximized: false,
+            },
+        );
+        assert_eq!(
+            im::hashset![PanelKind::Problem],
+            visible_panels(&order, &styles)
+        );
+
+        // Nor does one whose active panel was moved away
+        styles.insert(
+            PanelPosition::BottomLeft,
+            PanelStyle {
+                active: 20,
+                shown: true,
+                maximized: false,
+            },
+        );
+        assert!(visible_panels(&order, &styles).is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+use std::sync::Arc;
+
+use floem::{
+    event::EventListener,
+    peniko::Color,
+    reactive::{ReadSignal, RwSignal, SignalGet, SignalSet},
+    style::{CursorStyle, Style},
+    view::View,
+    views::{container, label, list, scroll, stack, svg, Decorators},
+};
+
+use super::{kind::PanelKind, position::PanelPosition};
+use crate::{
+    command::InternalCommand,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    debug::{DapConsoleKind, DapOutputCategory, VariableTree},
+    editor::location::{EditorLocation, EditorPosition},
+    listener::Listener,
+    terminal::panel::TerminalPanelData,
+    text_input::text_input,
+    window_tab::{Focus, WindowTabData},
+};
+
+pub fn debug_console_panel(
+    window_tab_data: Arc<WindowTabData>,
+    _position: PanelPosition,
+) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let terminal = window_tab_data.terminal.clone();
+    let category = terminal.debug.console_category;
+    let editor = window_tab_data.debug_console.editor.clone();
+    let focus = window_tab_data.common.focus;
+    let is_focused = move || focus.get() == Focus::Panel(PanelKind::DebugConsole);
+
+    stack(|| {
+        (
+            console_categories(category, config),
+            console_lines(terminal, category, internal_command, config),
+            container(|| {
+                text_input(editor, is_focused)
+                    .on_event(EventListener::PointerDown, move |_| {
+                        focus.set(Focus::Panel(PanelKind::DebugConsole));
+                        false
+                    })
+                    .style(move || {
+                        Style::BASE
+                            .width_pct(100.0)
+                            .border(1.0)
+                            .border_radius(6.0)
+                            .border_color(
+                                *config.get().get_color(LapceColor::LAPCE_BORDER),
+                            )
+                    })
+            })
+            .style(|| Style::BASE.width_pct(100.0).padding_px(10.0)),
+        )
+    })
+    .style(|| Style::BASE.absolute().size_pct(100.0, 100.0).flex_col())
+}
+
+/// The categories of the output which the debug console can show.
+fn console_categories(
+    category: RwSignal<Option<DapOutputCategory>>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let categories: Vec<Option<DapOutputCategory>> = std::iter::once(None)
+        .chain(DapOutputCategory::ALL.into_iter().map(Some))
+        .collect();
+    list(
+        move || categories.clone(),
+        |c| *c,
+        move |c| {
+            label(move || {
+                config
+                    .get()
+                    .tr(c.map_or("debug-console-all", |c| c.message_id()))
+            })
+            .on_click(move |_| {
+                category.set(c);
+                true
+            })
+            .style(move || {
+                Style::BASE
+                    .padding_horiz_px(8.0)
+                    .border_radius(4.0)
+                    .cursor(CursorStyle::Pointer)
+                    .apply_if(category.get() == c, |s| {
+                        s.background(
+                            *config
+                                .get()
+                                .get_color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                        )
+                    })
+            })
+            .hover_style(move || {
+                Style::BASE.background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            })
+        },
+    )
+    .style(|| {
+        Style::BASE
+            .width_pct(100.0)
+            .padding_horiz_px(10.0)
+            .padding_vert_px(4.0)
+            .line_height(1.6)
+    })
+}
+
+fn console_lines(
+    terminal: TerminalPanelData,
+    category: RwSignal<Option<DapOutputCategory>>,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    container(move || {
+        scroll(move || {
+            let local_terminal = terminal.clone();
+            list(
+                move || {
+                    let category = category.get();
+                    local_terminal
+                        .get_active_dap(true)
+                        .map(|dap| dap.console_rows(category))
+                        .unwrap_or_default()
+                },
+                |row| row.clone(),
+                move |row| {
+                    let terminal = terminal.clone();
+                    let kind = row.kind;
+                    let has_location = row.location.is_some();
+                    let location_text = row
+                        .location
+                        .as_ref()
+                        .map(|location| {
+                            let name = location
+                                .path
+                                .file_name()
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            format!("{name}:{}", location.line)
+                        })
+                        .unwrap_or_default();
+                    let location = row.location.map(|location| EditorLocation {
+                        path: location.path,
+                        position: Some(EditorPosition::Position(
+                            lsp_types::Position {
+                                line: location.line.saturating_sub(1) as u32,
+                                character: location.column.saturating_sub(1) as u32,
+                            },
+                        )),
+                        scroll_offset: None,
+                        ignore_unconfirmed: false,
+                        same_editor_tab: false,
+                    });
+                    let row = row.row;
+                    let path = row.path.clone();
+                    let indent = (row.path.len() - 1) as f32 * 10.0;
+                    let expandable = row.expandable;
+                    let expanded = row.expanded;
+                    let text = match (kind, row.value) {
+                        (_, Some(value)) => format!("{} = {value}", row.name),
+                        (DapConsoleKind::Input, None) => format!("> {}", row.name),
+                        (_, None) => row.name,
+                    };
+
+                    stack(|| {
+                        (
+                            svg(move || {
+                                let config = config.get();
+                                let svg_str = if expanded {
+                                    LapceIcons::ITEM_OPENED
+                                } else {
+                                    LapceIcons::ITEM_CLOSED
+                                };
+                                config.ui_svg(svg_str)
+                            })
+                            .style(move || {
+                                let config = config.get();
+                                let size = config.ui.icon_size() as f32;
+                                Style::BASE
+                                    .margin_right_px(4.0)
+                                    .size_px(size, size)
+                                    .color(
+                                        *config.get_color(
+                                            LapceColor::LAPCE_ICON_ACTIVE,
+                                        ),
+                                    )
+                                    .apply_if(!expandable, |s| {
+                                        s.color(Color::TRANSPARENT)
+                                    })
+                            }),
+                            label(move || text.clone()).style(move || {
+                                let config = config.get();
+                                let color = match kind {
+                                    DapConsoleKind::Input => {
+                                        config.get_color(LapceColor::EDITOR_DIM)
+                                    }
+                                    DapConsoleKind::Output => config
+                                        .get_color(LapceColor::EDITOR_FOREGROUND),
+                                    DapConsoleKind::Error => {
+                                        config.get_color(LapceColor::LAPCE_ERROR)
+                                    }
+                                };
+                                Style::BASE.flex_grow(1.0).color(*color)
+                            }),
+                            label(move || location_text.clone())
+                                .on_click(move |_| {
+                                    if let Some(location) = location.clone() {
+                                        internal_command.send(
+                                            InternalCommand::JumpToLocation {
+                                                location,
+                                            },
+                                        );
+                                    }
+                                    true
+                                })
+                                .style(move || {
+                                    Style::BASE
+                                        .margin_left_px(10.0)
+                                        .cursor(CursorStyle::Pointer)
+                                        .color(
+                                            *config
+                                                .get()
+                                                .get_color(LapceColor::EDITOR_LINK),
+                                        )
+                                        .apply_if(!has_location, |s| s.hide())
+                                }),
+                        )
+                    })
+                    .on_click(move |_| {
+                        if !expandable {
+                            return false;
+                        }
+                        if let Some(dap) = terminal.get_active_dap(false) {
+                            dap.toggle_variable(
+                                terminal.cx,
+                                VariableTree::Console,
+                                path.clone(),
+                                &terminal.common.proxy,
+                            );
+                        }
+                        true
+                    })
+                    .style(move || {
+                        Style::BASE
+                            .items_center()
+                            .padding_left_px(10.0 + indent)
+                            .padding_right_px(10.0)
+                            .min_width_pct(100.0)
+                    })
+                    .hover_style(move || {
+                        Style::BASE
+                            .background(
+                                *config
+                                    .get()
+                                    .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                            )
+                            .apply_if(expandable, |s| s.cursor(CursorStyle::Pointer))
+                    })
+                },
+            )
+            .style(|| Style::BASE.flex_col().min_width_pct(100.0))
+        })
+        .scroll_bar_color(move || {
+            *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+        })
+        .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|| {
+        Style::BASE
+            .width_pct(100.0)
+            .flex_grow(1.0)
+            .flex_basis_px(0.0)
+            .line_height(1.6)
+    })
+}
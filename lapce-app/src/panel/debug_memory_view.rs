@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use floem::{
+    reactive::{ReadSignal, SignalGet, SignalGetUntracked, SignalWith},
+    style::{CursorStyle, Style},
+    view::View,
+    views::{container, label, list, scroll, stack, Decorators},
+};
+
+use crate::{
+    app::clickable_icon,
+    command::InternalCommand,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    debug_memory::{MemoryEdit, MemoryRow, MemoryViewData},
+    listener::Listener,
+    window_tab::WindowTabData,
+};
+
+pub fn debug_memory_panel(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let memory = window_tab_data.debug_memory.clone();
+    let target = memory.target;
+    let offset = memory.offset;
+    let error = memory.error;
+
+    let title = move || {
+        let config = config.get();
+        target.with(|target| match target {
+            Some((_, reference)) => {
+                let offset = offset.get();
+                if offset < 0 {
+                    format!("{reference} -0x{:x}", offset.unsigned_abs())
+                } else {
+                    format!("{reference} +0x{offset:x}")
+                }
+            }
+            None => config.tr("debug-memory-empty"),
+        })
+    };
+    let no_target = move || target.with(|target| target.is_none());
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    label(title).style(|| {
+                        Style::BASE
+                            .flex_grow(1.0)
+                            .min_width_px(0.0)
+                            .margin_right_px(10.0)
+                            .text_ellipsis()
+                    }),
+                    {
+                        let memory = memory.clone();
+                        clickable_icon(
+                            || LapceIcons::LOCATION_BACKWARD,
+                            move || memory.page(false),
+                            || false,
+                            no_target,
+                            config,
+                        )
+                    },
+                    {
+                        let memory = memory.clone();
+                        clickable_icon(
+                            || LapceIcons::LOCATION_FORWARD,
+                            move || memory.page(true),
+                            || false,
+                            no_target,
+                            config,
+                        )
+                    },
+                )
+            })
+            .style(move || {
+                Style::BASE
+                    .items_center()
+                    .padding_horiz_px(10.0)
+                    .padding_vert_px(4.0)
+                    .width_pct(100.0)
+                    .background(
+                        *config.get().get_color(LapceColor::EDITOR_BACKGROUND),
+                    )
+            }),
+            container(|| {
+                scroll(move || {
+                    stack(move || {
+                        (
+                            label(move || error.get().unwrap_or_default()).style(
+                                move || {
+                                    Style::BASE
+                                        .color(
+                                            *config
+                                                .get()
+                                                .get_color(LapceColor::LAPCE_ERROR),
+                                        )
+                                        .apply_if(error.with(|e| e.is_none()), |s| {
+                                            s.hide()
+                                        })
+                                },
+                            ),
+                            {
+                                let memory = memory.clone();
+                                let rows_memory = memory.clone();
+                                list(
+                                    move || rows_memory.rows(),
+                                    |row| row.clone(),
+                                    move |row| {
+                                        memory_row(
+                                            row,
+                                            memory.clone(),
+                                            internal_command,
+                                            config,
+                                        )
+                                    },
+                                )
+                                .style(|| Style::BASE.flex_col())
+                            },
+                        )
+                    })
+                    .style(move || {
+                        Style::BASE
+                            .flex_col()
+                            .padding_horiz_px(10.0)
+                            .min_width_pct(100.0)
+                            .line_height(1.6)
+                            .font_family(config.get().editor.font_family.clone())
+                    })
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|| Style::BASE.size_pct(100.0, 100.0)),
+        )
+    })
+    .style(|| Style::BASE.flex_col().size_pct(100.0, 100.0))
+}
+
+/// The address, the bytes in hex and the ASCII of a row. A byte is written by
+/// clicking it when the debugger can write the memory.
+fn memory_row(
+    row: MemoryRow,
+    memory: MemoryViewData,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let address = row.address.clone();
+    let ascii = row.ascii.clone();
+    let bytes: Vec<(usize, Option<u8>)> = row
+        .bytes
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| (row.start + i, *byte))
+        .collect();
+
+    stack(|| {
+        (
+            label(move || address.clone()).style(move || {
+                Style::BASE
+                    .margin_right_px(12.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            list(
+                move || bytes.clone(),
+                |(index, _)| *index,
+                move |(index, byte)| {
+                    let memory = memory.clone();
+                    let text = byte.map_or("??".to_string(), |b| format!("{b:02x}"));
+                    label(move || text.clone())
+                        .on_click(move |_| {
+                            if byte.is_none() || !memory.can_write() {
+                                return false;
+                            }
+                            let Some((dap_id, memory_reference)) =
+                                memory.target.get_untracked()
+                            else {
+                                return false;
+                            };
+                            let offset =
+                                memory.offset.get_untracked() + index as i64;
+                            internal_command.send(InternalCommand::EditMemory {
+                                edit: MemoryEdit {
+                                    dap_id,
+                                    memory_reference,
+                                    offset,
+                                },
+                            });
+                            true
+                        })
+                        .style(move || {
+                            let config = config.get();
+                            Style::BASE.margin_right_px(6.0).apply_if(
+                                byte.is_none(),
+                                |s| {
+                                    s.color(
+                                        *config.get_color(LapceColor::EDITOR_DIM),
+                                    )
+                                },
+                            )
+                        })
+                        .hover_style(move || {
+                            Style::BASE.cursor(CursorStyle::Pointer).background(
+                                *config
+                                    .get()
+                                    .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                            )
+                        })
+                },
+            ),
+            label(move || ascii.clone()).style(|| Style::BASE.margin_left_px(6.0)),
+        )
+    })
+    .style(|| Style::BASE.items_center())
+}
@@ -2,27 +2,33 @@
 
 use floem::{
     cosmic_text::Style as FontStyle,
+    event::{Event, EventListener},
+    menu::{Menu, MenuItem},
+    peniko::{kurbo::Point, Color},
     reactive::{
-        ReadSignal, RwSignal, SignalGet, SignalGetUntracked, SignalSet,
-        SignalUpdate, SignalWith, SignalWithUntracked,
+        ReadSignal, SignalGet, SignalGetUntracked, SignalSet, SignalUpdate,
+        SignalWith, SignalWithUntracked,
     },
     style::{CursorStyle, Style},
     view::View,
     views::{container, container_box, label, list, scroll, stack, svg, Decorators},
 };
 use lapce_rpc::{
-    dap_types::{DapId, ThreadId},
+    dap_types::{DapId, RunDebugConfig, RunDebugConfigMode, ThreadId},
     terminal::TermId,
 };
 
 use super::{position::PanelPosition, view::panel_header};
 use crate::{
     app::clickable_icon,
-    command::InternalCommand,
+    command::{InternalCommand, LapceWorkbenchCommand},
     config::{color::LapceColor, icon::LapceIcons, LapceConfig},
-    debug::{RunDebugMode, StackTraceData},
-    editor::location::{EditorLocation, EditorPosition},
+    debug::{
+        DapData, DapVariableRow, RunDebugMode, RunDebugProcess, StackTraceData,
+        VariableEdit, VariableTree,
+    },
     listener::Listener,
+    settings::checkbox,
     terminal::panel::TerminalPanelData,
     window_tab::WindowTabData,
 };
@@ -34,6 +40,7 @@ pub fn debug_panel(
     let config = window_tab_data.common.config;
     let terminal = window_tab_data.terminal.clone();
     let internal_command = window_tab_data.common.internal_command;
+    let workbench_command = window_tab_data.common.workbench_command;
 
     stack(move || {
         (
@@ -41,25 +48,98 @@ pub fn debug_panel(
                 let terminal = terminal.clone();
                 stack(move || {
                     (
-                        panel_header("Processes".to_string(), config),
+                        panel_header("panel-processes", config),
                         debug_processes(terminal, config),
                     )
                 })
                 .style(|| Style::BASE.width_pct(100.0).flex_col().height_px(150.0))
             },
-            stack(move || {
-                (
-                    panel_header("Stack Frames".to_string(), config),
-                    debug_stack_traces(terminal, internal_command, config),
-                )
-            })
-            .style(|| {
-                Style::BASE
-                    .width_pct(100.0)
-                    .flex_grow(1.0)
-                    .flex_basis_px(0.0)
-                    .flex_col()
-            }),
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("panel-stack-frames", config),
+                        debug_stack_traces(terminal, config),
+                    )
+                })
+                .style(|| {
+                    Style::BASE
+                        .width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("panel-variables", config),
+                        debug_variables(terminal, config),
+                    )
+                })
+                .style(|| {
+                    Style::BASE
+                        .width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("panel-watch", config),
+                        debug_watches(
+                            terminal,
+                            internal_command,
+                            workbench_command,
+                            config,
+                        ),
+                    )
+                })
+                .style(|| {
+                    Style::BASE
+                        .width_pct(100.0)
+                        .flex_grow(1.0)
+                        .flex_basis_px(0.0)
+                        .flex_col()
+                })
+            },
+            {
+                let terminal = terminal.clone();
+                stack(move || {
+                    (
+                        panel_header("panel-exception-breakpoints", config),
+                        debug_exception_filters(terminal, config),
+                    )
+                })
+                .style(|| Style::BASE.width_pct(100.0).flex_col().height_px(100.0))
+            },
+            {
+                let has_data_breakpoints = {
+                    let terminal = terminal.clone();
+                    move || {
+                        terminal.get_active_dap(true).map_or(false, |dap| {
+                            dap.data_breakpoints.with(|b| !b.is_empty())
+                        })
+                    }
+                };
+                stack(move || {
+                    (
+                        panel_header("panel-data-breakpoints", config),
+                        debug_data_breakpoints(terminal, config),
+                    )
+                })
+                .style(move || {
+                    Style::BASE
+                        .width_pct(100.0)
+                        .flex_col()
+                        .height_px(100.0)
+                        .apply_if(!has_data_breakpoints(), |s| s.hide())
+                })
+            },
         )
     })
     .style(move || {
@@ -69,14 +149,56 @@ pub fn debug_panel(
     })
 }
 
+/// The button which starts the config of the process the other way, which runs
+/// it without debugging when it's debugged and debugs it otherwise. A config
+/// which attaches to a process and a child session can only be debugged.
+fn switch_mode_icon(
+    terminal: TerminalPanelData,
+    mode: RunDebugMode,
+    run_config: RunDebugConfig,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let debugging = mode == RunDebugMode::Debug && !run_config.no_debug;
+    let disabled = debugging
+        && (run_config.mode == RunDebugConfigMode::Attach
+            || run_config.parent.is_some());
+    let internal_command = terminal.common.internal_command;
+    clickable_icon(
+        move || {
+            if debugging {
+                LapceIcons::START
+            } else {
+                LapceIcons::DEBUG
+            }
+        },
+        move || {
+            let mut run_config = run_config.clone();
+            run_config.dap_id = DapId::next();
+            run_config.no_debug = false;
+            internal_command.send(if debugging {
+                InternalCommand::RunWithoutDebugging { config: run_config }
+            } else {
+                InternalCommand::RunAndDebug {
+                    mode: RunDebugMode::Debug,
+                    config: run_config,
+                }
+            });
+        },
+        || false,
+        move || disabled,
+        config,
+    )
+}
+
 fn debug_process_icons(
     terminal: TerminalPanelData,
     term_id: TermId,
-    dap_id: DapId,
+    run_config: RunDebugConfig,
     mode: RunDebugMode,
     stopped: bool,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
+    let dap_id = run_config.dap_id;
     let paused = move || {
         let stopped = terminal
             .debug
@@ -84,10 +206,20 @@ fn debug_process_icons(
             .with_untracked(|daps| daps.get(&dap_id).map(|dap| dap.stopped));
         stopped.map(|stopped| stopped.get()).unwrap_or(false)
     };
+    let supports_step_back = move || {
+        let supports_step_back = terminal.debug.daps.with_untracked(|daps| {
+            daps.get(&dap_id).map(|dap| dap.supports_step_back)
+        });
+        supports_step_back
+            .map(|supports_step_back| supports_step_back.get())
+            .unwrap_or(false)
+    };
     match mode {
         RunDebugMode::Run => container_box(|| {
             Box::new(stack(|| {
                 (
+                    switch_mode_icon(terminal.clone(), mode, run_config, config)
+                        .style(|| Style::BASE.margin_horiz_px(6.0)),
                     {
                         let terminal = terminal.clone();
                         clickable_icon(
@@ -99,7 +231,7 @@ fn debug_process_icons(
                             || false,
                             config,
                         )
-                        .style(|| Style::BASE.margin_horiz_px(6.0))
+                        .style(|| Style::BASE.margin_right_px(6.0))
                     },
                     {
                         let terminal = terminal.clone();
@@ -133,6 +265,8 @@ fn debug_process_icons(
         RunDebugMode::Debug => container_box(|| {
             Box::new(stack(|| {
                 (
+                    switch_mode_icon(terminal.clone(), mode, run_config, config)
+                        .style(|| Style::BASE.margin_horiz_px(6.0)),
                     {
                         let terminal = terminal.clone();
                         clickable_icon(
@@ -144,7 +278,7 @@ fn debug_process_icons(
                             move || !paused() || stopped,
                             config,
                         )
-                        .style(|| Style::BASE.margin_horiz_px(6.0))
+                        .style(|| Style::BASE.margin_right_px(6.0))
                     },
                     {
                         let terminal = terminal.clone();
@@ -159,6 +293,40 @@ fn debug_process_icons(
                         )
                         .style(|| Style::BASE.margin_right_px(6.0))
                     },
+                    {
+                        let terminal = terminal.clone();
+                        clickable_icon(
+                            || LapceIcons::DEBUG_REVERSE_CONTINUE,
+                            move || {
+                                terminal.dap_reverse_continue(term_id);
+                            },
+                            || false,
+                            move || !paused() || stopped,
+                            config,
+                        )
+                        .style(move || {
+                            Style::BASE
+                                .margin_right_px(6.0)
+                                .apply_if(!supports_step_back(), |s| s.hide())
+                        })
+                    },
+                    {
+                        let terminal = terminal.clone();
+                        clickable_icon(
+                            || LapceIcons::DEBUG_STEP_BACK,
+                            move || {
+                                terminal.dap_step_back(term_id);
+                            },
+                            || false,
+                            move || !paused() || stopped,
+                            config,
+                        )
+                        .style(move || {
+                            Style::BASE
+                                .margin_right_px(6.0)
+                                .apply_if(!supports_step_back(), |s| s.hide())
+                        })
+                    },
                     {
                         let terminal = terminal.clone();
                         clickable_icon(
@@ -204,6 +372,39 @@ fn debug_process_icons(
     }
 }
 
+/// The processes with the child sessions of the debuggers right after their
+/// parents, and how deep each of them is nested.
+fn nest_debug_sessions(
+    processes: Vec<(TermId, RunDebugProcess)>,
+) -> Vec<(TermId, RunDebugProcess, usize)> {
+    fn push_children(
+        processes: &[(TermId, RunDebugProcess)],
+        parent: Option<DapId>,
+        depth: usize,
+        nested: &mut Vec<(TermId, RunDebugProcess, usize)>,
+    ) {
+        for (term_id, process) in processes {
+            // A child whose parent is gone is shown at the top
+            let process_parent = process.config.parent.filter(|parent| {
+                processes.iter().any(|(_, p)| p.config.dap_id == *parent)
+            });
+            if process_parent == parent {
+                nested.push((*term_id, process.clone(), depth));
+                push_children(
+                    processes,
+                    Some(process.config.dap_id),
+                    depth + 1,
+                    nested,
+                );
+            }
+        }
+    }
+
+    let mut nested = Vec::new();
+    push_children(&processes, None, 0, &mut nested);
+    nested
+}
+
 fn debug_processes(
     terminal: TerminalPanelData,
     config: ReadSignal<Arc<LapceConfig>>,
@@ -212,9 +413,9 @@ fn debug_processes(
         let terminal = terminal.clone();
         let local_terminal = terminal.clone();
         list(
-            move || local_terminal.run_debug_process(true),
-            |(term_id, p)| (*term_id, p.stopped),
-            move |(term_id, p)| {
+            move || nest_debug_sessions(local_terminal.run_debug_process(true)),
+            |(term_id, p, _)| (*term_id, p.stopped),
+            move |(term_id, p, depth)| {
                 let terminal = terminal.clone();
                 let is_active =
                     move || terminal.debug.active_term.get() == Some(term_id);
@@ -236,7 +437,8 @@ fn debug_processes(
                                     let size = config.ui.icon_size() as f32;
                                     Style::BASE
                                         .size_px(size, size)
-                                        .margin_horiz_px(10.0)
+                                        .margin_left_px(10.0 + depth as f32 * size)
+                                        .margin_right_px(10.0)
                                         .color(*config.get_color(
                                             LapceColor::LAPCE_ICON_ACTIVE,
                                         ))
@@ -253,7 +455,7 @@ fn debug_processes(
                         debug_process_icons(
                             terminal.clone(),
                             term_id,
-                            p.config.dap_id,
+                            p.config.clone(),
                             p.mode,
                             p.stopped,
                             config,
@@ -292,31 +494,126 @@ fn debug_processes(
     })
 }
 
-fn debug_stack_frames(
+/// The row of a thread, which can be paused and resumed on its own when the
+/// debugger supports single thread execution requests, and be terminated when it
+/// supports terminating threads.
+fn debug_thread_header(
+    terminal: TerminalPanelData,
+    dap: &DapData,
     thread_id: ThreadId,
-    stack_trace: StackTraceData,
-    stopped: RwSignal<bool>,
-    internal_command: Listener<InternalCommand>,
+    stack_trace: &StackTraceData,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
+    let dap_id = dap.dap_id;
+    let supports_single_thread = dap.supports_single_thread;
+    let supports_terminate_threads = dap.supports_terminate_threads;
+    let name = stack_trace.name;
+    let stopped = stack_trace.stopped;
     let expanded = stack_trace.expanded;
     stack(move || {
         (
-            container(|| label(move || thread_id.to_string()))
-                .on_click(move |_| {
-                    expanded.update(|expanded| {
-                        *expanded = !*expanded;
-                    });
-                    true
+            stack(move || {
+                (
+                    label(move || name.get()),
+                    label(move || {
+                        let key = if stopped.get() {
+                            "debug-thread-paused"
+                        } else {
+                            "debug-thread-running"
+                        };
+                        config.get().tr(key)
+                    })
+                    .style(move || {
+                        Style::BASE
+                            .margin_left_px(10.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                            .font_style(FontStyle::Italic)
+                    }),
+                )
+            })
+            .on_click(move |_| {
+                expanded.update(|expanded| {
+                    *expanded = !*expanded;
+                });
+                true
+            })
+            .style(|| Style::BASE.flex_grow(1.0)),
+            {
+                let terminal = terminal.clone();
+                clickable_icon(
+                    move || {
+                        if stopped.get() {
+                            LapceIcons::DEBUG_CONTINUE
+                        } else {
+                            LapceIcons::DEBUG_PAUSE
+                        }
+                    },
+                    move || {
+                        if stopped.get_untracked() {
+                            terminal.dap_continue_thread(dap_id, thread_id);
+                        } else {
+                            terminal.dap_pause_thread(dap_id, thread_id);
+                        }
+                    },
+                    || false,
+                    || false,
+                    config,
+                )
+                .style(move || {
+                    Style::BASE
+                        .margin_left_px(6.0)
+                        .apply_if(!supports_single_thread.get(), |s| s.hide())
                 })
-                .style(|| Style::BASE.padding_horiz_px(10.0).min_width_pct(100.0))
-                .hover_style(move || {
-                    Style::BASE.cursor(CursorStyle::Pointer).background(
-                        *config
-                            .get()
-                            .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
-                    )
-                }),
+            },
+            clickable_icon(
+                || LapceIcons::DEBUG_STOP,
+                move || {
+                    terminal.dap_terminate_thread(dap_id, thread_id);
+                },
+                || false,
+                || false,
+                config,
+            )
+            .style(move || {
+                Style::BASE
+                    .margin_left_px(6.0)
+                    .apply_if(!supports_terminate_threads.get(), |s| s.hide())
+            }),
+        )
+    })
+    .style(|| {
+        Style::BASE
+            .items_center()
+            .padding_horiz_px(10.0)
+            .min_width_pct(100.0)
+    })
+    .hover_style(move || {
+        Style::BASE.cursor(CursorStyle::Pointer).background(
+            *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+        )
+    })
+}
+
+fn debug_stack_frames(
+    terminal: TerminalPanelData,
+    dap: DapData,
+    thread_id: ThreadId,
+    stack_trace: StackTraceData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let dap_id = dap.dap_id;
+    // The frame whose variables are shown, and whose line the editor shows
+    let selected_frame_id = dap.frame_id;
+    let stopped = stack_trace.stopped;
+    stack(move || {
+        (
+            debug_thread_header(
+                terminal.clone(),
+                &dap,
+                thread_id,
+                &stack_trace,
+                config,
+            ),
             list(
                 move || {
                     let expanded = stack_trace.expanded.get() && stopped.get();
@@ -328,10 +625,11 @@ fn debug_stack_frames(
                 },
                 |frame| frame.id,
                 move |frame| {
-                    let full_path =
-                        frame.source.as_ref().and_then(|s| s.path.clone());
-                    let line = frame.line.saturating_sub(1);
-                    let col = frame.column.saturating_sub(1);
+                    let terminal = terminal.clone();
+                    let menu_terminal = terminal.clone();
+                    let frame_id = frame.id;
+                    let can_restart = frame.can_restart != Some(false);
+                    let selected_frame = frame.clone();
 
                     let source_path = frame
                         .source
@@ -373,24 +671,23 @@ fn debug_stack_frames(
                         })
                     })
                     .on_click(move |_| {
-                        if let Some(path) = full_path.clone() {
-                            internal_command.send(InternalCommand::JumpToLocation {
-                                location: EditorLocation {
-                                    path,
-                                    position: Some(EditorPosition::Position(
-                                        lsp_types::Position {
-                                            line: line as u32,
-                                            character: col as u32,
-                                        },
-                                    )),
-                                    scroll_offset: None,
-                                    ignore_unconfirmed: false,
-                                    same_editor_tab: false,
-                                },
-                            });
-                        }
+                        terminal.dap_select_frame(&dap_id, &selected_frame);
                         true
                     })
+                    .on_event(EventListener::PointerDown, move |event| {
+                        let Event::PointerDown(pointer_event) = event else {
+                            return false;
+                        };
+                        if pointer_event.button.is_right() && can_restart {
+                            show_frame_menu(
+                                &menu_terminal,
+                                dap_id,
+                                frame_id,
+                                &config.get_untracked(),
+                            );
+                        }
+                        false
+                    })
                     .style(move || {
                         Style::BASE
                             .padding_left_px(20.0)
@@ -401,6 +698,14 @@ fn debug_stack_frames(
                                     *config.get().get_color(LapceColor::EDITOR_DIM),
                                 )
                             })
+                            .apply_if(
+                                selected_frame_id.get() == Some(frame_id),
+                                |s| {
+                                    s.background(*config.get().get_color(
+                                        LapceColor::PANEL_CURRENT_BACKGROUND,
+                                    ))
+                                },
+                            )
                     })
                     .hover_style(move || {
                         Style::BASE
@@ -421,7 +726,6 @@ fn debug_stack_frames(
 
 fn debug_stack_traces(
     terminal: TerminalPanelData,
-    internal_command: Listener<InternalCommand>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
     container(move || {
@@ -445,24 +749,22 @@ fn debug_stack_traces(
                         let mut traces = stack_traces
                             .into_iter()
                             .map(|(thread_id, stack_trace)| {
-                                (dap.dap_id, dap.stopped, thread_id, stack_trace)
+                                (dap.clone(), thread_id, stack_trace)
                             })
                             .collect::<Vec<_>>();
-                        traces.sort_by_key(|(_, _, id, _)| main_thread != Some(*id));
+                        traces.sort_by_key(|(_, id, _)| main_thread != Some(*id));
                         traces
                     } else {
                         Vec::new()
                     }
                 },
-                |(dap_id, stopped, thread_id, _)| {
-                    (*dap_id, *thread_id, stopped.get_untracked())
-                },
-                move |(_, stopped, thread_id, stack_trace)| {
+                |(dap, thread_id, _)| (dap.dap_id, *thread_id),
+                move |(dap, thread_id, stack_trace)| {
                     debug_stack_frames(
+                        terminal.clone(),
+                        dap,
                         thread_id,
                         stack_trace,
-                        stopped,
-                        internal_command,
                         config,
                     )
                 },
@@ -482,3 +784,590 @@ fn debug_stack_traces(
             .flex_basis_px(0.0)
     })
 }
+
+fn debug_variables(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    container(move || {
+        scroll(move || {
+            let local_terminal = terminal.clone();
+            list(
+                move || {
+                    let dap = local_terminal.get_active_dap(true);
+                    match dap {
+                        Some(dap) if dap.stopped.get() => dap.variable_rows(),
+                        _ => im::Vector::new(),
+                    }
+                },
+                |row| row.clone(),
+                move |row| {
+                    let terminal = terminal.clone();
+                    let menu_terminal = terminal.clone();
+                    let path = row.path.clone();
+                    let menu_path = row.path.clone();
+                    let indent = (row.path.len() - 1) as f32 * 10.0;
+                    let expandable = row.expandable;
+
+                    variable_row(&row, false, config)
+                        .on_click(move |_| {
+                            if let Some(dap) = terminal.get_active_dap(false) {
+                                dap.toggle_variable(
+                                    terminal.cx,
+                                    VariableTree::Frame,
+                                    path.clone(),
+                                    &terminal.common.proxy,
+                                );
+                            }
+                            true
+                        })
+                        .on_event(EventListener::PointerDown, move |event| {
+                            let Event::PointerDown(pointer_event) = event else {
+                                return false;
+                            };
+                            if pointer_event.button.is_right() {
+                                show_variable_menu(
+                                    &menu_terminal,
+                                    VariableTree::Frame,
+                                    &menu_path,
+                                    &config.get_untracked(),
+                                );
+                            }
+                            false
+                        })
+                        .style(move || {
+                            Style::BASE
+                                .items_center()
+                                .padding_left_px(10.0 + indent)
+                                .padding_right_px(10.0)
+                                .min_width_pct(100.0)
+                        })
+                        .hover_style(move || {
+                            Style::BASE
+                                .background(
+                                    *config.get().get_color(
+                                        LapceColor::PANEL_HOVERED_BACKGROUND,
+                                    ),
+                                )
+                                .apply_if(expandable, |s| {
+                                    s.cursor(CursorStyle::Pointer)
+                                })
+                        })
+                },
+            )
+            .style(|| Style::BASE.flex_col().min_width_pct(100.0))
+        })
+        .scroll_bar_color(move || {
+            *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+        })
+        .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|| {
+        Style::BASE
+            .width_pct(100.0)
+            .line_height(1.6)
+            .flex_grow(1.0)
+            .flex_basis_px(0.0)
+    })
+}
+
+/// Show the menu of the variable at the path of the tree of the active session,
+/// unless the debugger can't do anything with it. Only the values of variables
+/// can be broken on, not the scopes.
+fn show_variable_menu(
+    terminal: &TerminalPanelData,
+    tree: VariableTree,
+    path: &[usize],
+    config: &LapceConfig,
+) {
+    let Some(dap) = terminal.get_active_dap(false) else {
+        return;
+    };
+    let can_set = dap.set_value_target(tree, path).is_some();
+    let can_break = tree == VariableTree::Frame
+        && dap.supports_data_breakpoints.get_untracked()
+        && path.len() > 1;
+    let memory_reference = if dap.supports_read_memory.get_untracked() {
+        dap.variable(tree, path, |variable| variable.memory_reference.clone())
+            .flatten()
+    } else {
+        None
+    };
+    if !can_set && !can_break && memory_reference.is_none() {
+        return;
+    }
+
+    let mut menu = Menu::new("");
+    if can_set {
+        let internal_command = terminal.common.internal_command;
+        let edit = VariableEdit {
+            dap_id: dap.dap_id,
+            tree,
+            path: path.to_vec(),
+        };
+        menu = menu.entry(MenuItem::new(config.tr("debug-set-value")).action(
+            move || {
+                internal_command
+                    .send(InternalCommand::EditVariableValue { edit: edit.clone() });
+            },
+        ));
+    }
+    if can_break {
+        let terminal = terminal.clone();
+        let path = path.to_vec();
+        menu = menu.entry(
+            MenuItem::new(config.tr("debug-break-on-value-change")).action(
+                move || {
+                    terminal.add_data_breakpoint(&path);
+                },
+            ),
+        );
+    }
+    if let Some(memory_reference) = memory_reference {
+        let internal_command = terminal.common.internal_command;
+        let dap_id = dap.dap_id;
+        menu = menu.entry(MenuItem::new(config.tr("debug-view-memory")).action(
+            move || {
+                internal_command.send(InternalCommand::ViewMemory {
+                    dap_id,
+                    memory_reference: memory_reference.clone(),
+                });
+            },
+        ));
+    }
+    terminal
+        .common
+        .view_id
+        .get_untracked()
+        .show_context_menu(menu, Point::ZERO);
+}
+
+fn show_frame_menu(
+    terminal: &TerminalPanelData,
+    dap_id: DapId,
+    frame_id: usize,
+    config: &LapceConfig,
+) {
+    let supported = terminal.debug.daps.with_untracked(|daps| {
+        daps.get(&dap_id)
+            .map_or(false, |dap| dap.supports_restart_frame.get_untracked())
+    });
+    if !supported {
+        return;
+    }
+
+    let terminal = terminal.clone();
+    let view_id = terminal.common.view_id.get_untracked();
+    let menu = Menu::new("").entry(
+        MenuItem::new(config.tr("debug-restart-frame")).action(move || {
+            terminal.dap_restart_frame(dap_id, frame_id);
+        }),
+    );
+    view_id.show_context_menu(menu, Point::ZERO);
+}
+
+/// The expand icon, the name, the value and the type of a row of a variables
+/// tree.
+fn variable_row(
+    row: &DapVariableRow,
+    error: bool,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let expandable = row.expandable;
+    let expanded = row.expanded;
+    let name = row.name.clone();
+    let value = row.value.clone();
+    let ty = row.ty.clone();
+    let has_value = value.is_some();
+    let has_ty = ty.is_some();
+
+    stack(|| {
+        (
+            svg(move || {
+                let config = config.get();
+                let svg_str = if expanded {
+                    LapceIcons::ITEM_OPENED
+                } else {
+                    LapceIcons::ITEM_CLOSED
+                };
+                config.ui_svg(svg_str)
+            })
+            .style(move || {
+                let config = config.get();
+                let size = config.ui.icon_size() as f32;
+                Style::BASE
+                    .margin_right_px(4.0)
+                    .size_px(size, size)
+                    .color(*config.get_color(LapceColor::LAPCE_ICON_ACTIVE))
+                    .apply_if(!expandable, |s| s.color(Color::TRANSPARENT))
+            }),
+            label(move || name.clone()),
+            label(move || format!(" = {}", value.clone().unwrap_or_default()))
+                .style(move || {
+                    Style::BASE
+                        .apply_if(error, |s| {
+                            s.color(*config.get().get_color(LapceColor::LAPCE_ERROR))
+                        })
+                        .apply_if(!has_value, |s| s.hide())
+                }),
+            label(move || ty.clone().unwrap_or_default()).style(move || {
+                Style::BASE
+                    .margin_left_px(10.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    .font_style(FontStyle::Italic)
+                    .apply_if(!has_ty, |s| s.hide())
+            }),
+        )
+    })
+}
+
+fn debug_watches(
+    terminal: TerminalPanelData,
+    internal_command: Listener<InternalCommand>,
+    workbench_command: Listener<LapceWorkbenchCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    container(move || {
+        scroll(move || {
+            let local_terminal = terminal.clone();
+            stack(move || {
+                (
+                    list(
+                        move || {
+                            let dap = local_terminal.get_active_dap(true);
+                            local_terminal.debug.watch_rows(dap.as_ref())
+                        },
+                        |row| row.clone(),
+                        move |row| {
+                            let terminal = terminal.clone();
+                            let menu_terminal = terminal.clone();
+                            let path = row.row.path.clone();
+                            let menu_path = row.row.path.clone();
+                            let index = path[0];
+                            let indent = (path.len() - 1) as f32 * 10.0;
+                            let expandable = row.row.expandable;
+                            let is_expression = path.len() == 1;
+
+                            stack(|| {
+                                (
+                                    variable_row(&row.row, row.error, config)
+                                        .style(|| {
+                                            Style::BASE
+                                                .items_center()
+                                                .flex_grow(1.0)
+                                        }),
+                                    clickable_icon(
+                                        || LapceIcons::CLOSE,
+                                        move || {
+                                            internal_command.send(
+                                                InternalCommand::RemoveWatchExpression {
+                                                    index,
+                                                },
+                                            )
+                                        },
+                                        || false,
+                                        || false,
+                                        config,
+                                    )
+                                    .style(move || {
+                                        Style::BASE
+                                            .apply_if(!is_expression, |s| s.hide())
+                                    }),
+                                )
+                            })
+                            .on_click(move |_| {
+                                if let Some(dap) = terminal.get_active_dap(false) {
+                                    dap.toggle_variable(
+                                        terminal.cx,
+                                        VariableTree::Watch,
+                                        path.clone(),
+                                        &terminal.common.proxy,
+                                    );
+                                }
+                                true
+                            })
+                            .on_event(EventListener::PointerDown, move |event| {
+                                let Event::PointerDown(pointer_event) = event else {
+                                    return false;
+                                };
+                                if pointer_event.button.is_right() {
+                                    show_variable_menu(
+                                        &menu_terminal,
+                                        VariableTree::Watch,
+                                        &menu_path,
+                                        &config.get_untracked(),
+                                    );
+                                }
+                                false
+                            })
+                            .style(move || {
+                                Style::BASE
+                                    .items_center()
+                                    .padding_left_px(10.0 + indent)
+                                    .padding_right_px(10.0)
+                                    .min_width_pct(100.0)
+                            })
+                            .hover_style(move || {
+                                Style::BASE
+                                    .background(*config.get().get_color(
+                                        LapceColor::PANEL_HOVERED_BACKGROUND,
+                                    ))
+                                    .apply_if(expandable, |s| {
+                                        s.cursor(CursorStyle::Pointer)
+                                    })
+                            })
+                        },
+                    )
+                    .style(|| Style::BASE.flex_col().min_width_pct(100.0)),
+                    label(move || config.get().tr("panel-watch-add"))
+                        .on_click(move |_| {
+                            workbench_command
+                                .send(LapceWorkbenchCommand::AddWatchExpression);
+                            true
+                        })
+                        .style(move || {
+                            Style::BASE
+                                .padding_horiz_px(10.0)
+                                .min_width_pct(100.0)
+                                .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                        })
+                        .hover_style(move || {
+                            Style::BASE.cursor(CursorStyle::Pointer).background(
+                                *config
+                                    .get()
+                                    .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                            )
+                        }),
+                )
+            })
+            .style(|| Style::BASE.flex_col().min_width_pct(100.0))
+        })
+        .scroll_bar_color(move || {
+            *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+        })
+        .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|| {
+        Style::BASE
+            .width_pct(100.0)
+            .line_height(1.6)
+            .flex_grow(1.0)
+            .flex_basis_px(0.0)
+    })
+}
+
+/// The exception filters of the debugger, which are toggled for every session.
+fn debug_exception_filters(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    container(move || {
+        scroll(move || {
+            let local_terminal = terminal.clone();
+            list(
+                move || {
+                    local_terminal
+                        .get_active_dap(true)
+                        .map(|dap| dap.exception_filters.get())
+                        .unwrap_or_default()
+                },
+                |filter| filter.filter.clone(),
+                move |filter| {
+                    let terminal = terminal.clone();
+                    let debug = terminal.debug.clone();
+                    let checked_filter = filter.clone();
+                    let text = filter.label.clone();
+
+                    stack(|| {
+                        (
+                            checkbox(
+                                move || {
+                                    debug
+                                        .is_exception_filter_enabled(&checked_filter)
+                                },
+                                config,
+                            ),
+                            label(move || text.clone())
+                                .style(|| Style::BASE.margin_left_px(6.0)),
+                        )
+                    })
+                    .on_click(move |_| {
+                        terminal.debug.toggle_exception_filter(
+                            &filter,
+                            &terminal.common.proxy,
+                        );
+                        true
+                    })
+                    .style(|| {
+                        Style::BASE
+                            .items_center()
+                            .padding_horiz_px(10.0)
+                            .min_width_pct(100.0)
+                    })
+                    .hover_style(move || {
+                        Style::BASE.cursor(CursorStyle::Pointer).background(
+                            *config
+                                .get()
+                                .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                },
+            )
+            .style(|| Style::BASE.flex_col().min_width_pct(100.0))
+        })
+        .scroll_bar_color(move || {
+            *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+        })
+        .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|| {
+        Style::BASE
+            .width_pct(100.0)
+            .line_height(1.6)
+            .flex_grow(1.0)
+            .flex_basis_px(0.0)
+    })
+}
+
+/// The data breakpoints of the active session, which are added from the
+/// variables.
+fn debug_data_breakpoints(
+    terminal: TerminalPanelData,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    container(move || {
+        scroll(move || {
+            let local_terminal = terminal.clone();
+            list(
+                move || {
+                    local_terminal
+                        .get_active_dap(true)
+                        .map(|dap| {
+                            dap.data_breakpoints
+                                .get()
+                                .into_iter()
+                                .enumerate()
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default()
+                },
+                |(i, breakpoint)| (*i, breakpoint.data_id.clone()),
+                move |(index, breakpoint)| {
+                    let terminal = terminal.clone();
+                    let verified = breakpoint.verified;
+                    let text = match breakpoint.message {
+                        Some(message) if !verified => {
+                            format!("{} ({message})", breakpoint.description)
+                        }
+                        _ => breakpoint.description,
+                    };
+
+                    stack(|| {
+                        (
+                            label(move || text.clone()).style(move || {
+                                Style::BASE
+                                    .flex_grow(1.0)
+                                    .min_width_px(0.0)
+                                    .text_ellipsis()
+                                    .apply_if(!verified, |s| {
+                                        s.color(
+                                            *config
+                                                .get()
+                                                .get_color(LapceColor::EDITOR_DIM),
+                                        )
+                                    })
+                            }),
+                            clickable_icon(
+                                || LapceIcons::CLOSE,
+                                move || {
+                                    if let Some(dap) = terminal.get_active_dap(false)
+                                    {
+                                        dap.remove_data_breakpoint(
+                                            index,
+                                            &terminal.common.proxy,
+                                        );
+                                    }
+                                },
+                                || false,
+                                || false,
+                                config,
+                            ),
+                        )
+                    })
+                    .style(|| {
+                        Style::BASE
+                            .items_center()
+                            .padding_horiz_px(10.0)
+                            .min_width_pct(100.0)
+                    })
+                    .hover_style(move || {
+                        Style::BASE.background(
+                            *config
+                                .get()
+                                .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                        )
+                    })
+                },
+            )
+            .style(|| Style::BASE.flex_col().min_width_pct(100.0))
+        })
+        .scroll_bar_color(move || {
+            *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+        })
+        .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|| {
+        Style::BASE
+            .width_pct(100.0)
+            .line_height(1.6)
+            .flex_grow(1.0)
+            .flex_basis_px(0.0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    fn process(name: &str, parent: Option<DapId>) -> (TermId, RunDebugProcess) {
+        let mut config: RunDebugConfig = serde_json::from_value(serde_json::json!({
+            "name": name,
+            "program": "app",
+        }))
+        .unwrap();
+        config.parent = parent;
+        (
+            TermId::next(),
+            RunDebugProcess {
+                mode: RunDebugMode::Debug,
+                config,
+                stopped: false,
+                created: Instant::now(),
+                in_terminal: parent.is_none(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_nest_debug_sessions() {
+        let a = process("a", None);
+        let b = process("b", None);
+        let c = process("c", Some(b.1.config.dap_id));
+        let e = process("e", Some(c.1.config.dap_id));
+        // The parent of the orphan was closed
+        let orphan = process("orphan", Some(DapId::next()));
+
+        // A child which comes before its parent is still shown after it
+        let nested = nest_debug_sessions(vec![e, c, a, orphan, b]);
+        let nested: Vec<(&str, usize)> = nested
+            .iter()
+            .map(|(_, process, depth)| (process.config.name.as_str(), *depth))
+            .collect();
+        assert_eq!(
+            nested,
+            vec![("a", 0), ("orphan", 0), ("b", 0), ("c", 1), ("e", 2)]
+        );
+    }
+}
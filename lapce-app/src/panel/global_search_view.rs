@@ -314,7 +314,7 @@ fn search_result(
                                     })
                                     .on_click(move |_| {
                                         internal_command.send(
-                                            InternalCommand::JumpToLocation {
+                                            InternalCommand::PreviewLocation {
                                                 location: EditorLocation {
                                                     path: path.clone(),
                                                     position: Some(
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use floem::{
+    reactive::{create_memo, ReadSignal, SignalGet, SignalWith},
+    style::Style,
+    view::View,
+    views::{container, label, list, scroll, stack, Decorators},
+    ViewContext,
+};
+
+use crate::{
+    config::{color::LapceColor, LapceConfig},
+    doc::memory::format_bytes,
+    window_tab::WindowTabData,
+};
+
+pub fn http_response_panel(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let cx = ViewContext::get_current();
+    let config = window_tab_data.common.config;
+    let http_client = window_tab_data.http_client.clone();
+    let request = http_client.request;
+    let response = http_client.response;
+    let error = http_client.error;
+    let sending = http_client.sending;
+
+    let headers = create_memo(cx.scope, move |_| {
+        response.with(|response| {
+            response
+                .as_ref()
+                .map(|response| {
+                    response.headers.iter().cloned().enumerate().collect()
+                })
+                .unwrap_or_else(im::Vector::new)
+        })
+    });
+    let body = create_memo(cx.scope, move |_| {
+        response.with(|response| {
+            response
+                .as_ref()
+                .map(|response| {
+                    response
+                        .body
+                        .lines()
+                        .map(|line| line.to_string())
+                        .enumerate()
+                        .collect()
+                })
+                .unwrap_or_else(im::Vector::new)
+        })
+    });
+    let summary = move || {
+        let config = config.get();
+        if sending.get() {
+            return config.tr("http-response-sending");
+        }
+        response.with(|response| {
+            response
+                .as_ref()
+                .map(|response| {
+                    format!(
+                        "{} {}  ·  {} ms  ·  {}",
+                        response.status,
+                        response.reason,
+                        response.duration_ms,
+                        format_bytes(response.body.len())
+                    )
+                })
+                .unwrap_or_default()
+        })
+    };
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    label(move || {
+                        request.get().unwrap_or_else(|| {
+                            config.get().tr("http-response-empty")
+                        })
+                    })
+                    .style(|| {
+                        Style::BASE
+                            .flex_grow(1.0)
+                            .min_width_px(0.0)
+                            .margin_right_px(10.0)
+                            .text_ellipsis()
+                    }),
+                    label(summary).style(move || {
+                        let config = config.get();
+                        let status = response
+                            .with(|response| response.as_ref().map(|r| r.status));
+                        let color = match status {
+                            Some(status) if status >= 400 => LapceColor::LAPCE_ERROR,
+                            Some(status) if status >= 300 => LapceColor::LAPCE_WARN,
+                            _ => LapceColor::EDITOR_DIM,
+                        };
+                        Style::BASE.color(*config.get_color(color))
+                    }),
+                )
+            })
+            .style(move || {
+                Style::BASE
+                    .items_center()
+                    .padding_horiz_px(10.0)
+                    .padding_vert_px(6.0)
+                    .width_pct(100.0)
+                    .background(
+                        *config.get().get_color(LapceColor::EDITOR_BACKGROUND),
+                    )
+            }),
+            container(|| {
+                scroll(move || {
+                    stack(move || {
+                        (
+                            label(move || error.get().unwrap_or_default()).style(
+                                move || {
+                                    Style::BASE
+                                        .color(
+                                            *config
+                                                .get()
+                                                .get_color(LapceColor::LAPCE_ERROR),
+                                        )
+                                        .apply_if(error.with(|e| e.is_none()), |s| {
+                                            s.hide()
+                                        })
+                                },
+                            ),
+                            list(
+                                move || headers.get(),
+                                |(i, _)| *i,
+                                move |(_, (name, value))| {
+                                    header_view(name, value, config)
+                                },
+                            )
+                            .style(|| Style::BASE.flex_col().margin_bottom_px(10.0)),
+                            list(
+                                move || body.get(),
+                                |(i, _)| *i,
+                                |(_, line)| label(move || line.clone()),
+                            )
+                            .style(|| Style::BASE.flex_col()),
+                        )
+                    })
+                    .style(|| {
+                        Style::BASE
+                            .flex_col()
+                            .padding_horiz_px(10.0)
+                            .min_width_pct(100.0)
+                            .line_height(1.6)
+                    })
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|| Style::BASE.size_pct(100.0, 100.0)),
+        )
+    })
+    .style(|| Style::BASE.flex_col().size_pct(100.0, 100.0))
+}
+
+fn header_view(
+    name: String,
+    value: String,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    stack(|| {
+        (
+            label(move || format!("{name}:")).style(move || {
+                Style::BASE
+                    .margin_right_px(6.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            label(move || value.clone()),
+        )
+    })
+}
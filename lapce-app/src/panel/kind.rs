@@ -1,12 +1,22 @@
 use serde::{Deserialize, Serialize};
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, EnumString};
 
 use super::{data::PanelOrder, position::PanelPosition};
 use crate::config::icon::LapceIcons;
 
 #[derive(
-    Clone, Copy, PartialEq, Serialize, Deserialize, Hash, Eq, Debug, EnumIter,
+    Clone,
+    Copy,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Hash,
+    Eq,
+    Debug,
+    EnumIter,
+    EnumString,
 )]
+#[strum(serialize_all = "snake_case")]
 pub enum PanelKind {
     Terminal,
     FileExplorer,
@@ -15,6 +25,14 @@ pub enum PanelKind {
     Search,
     Problem,
     Debug,
+    Notification,
+    Log,
+    TestExplorer,
+    HttpResponse,
+    DebugConsole,
+    Collaboration,
+    DebugMemory,
+    Todo,
 }
 
 impl PanelKind {
@@ -27,6 +45,35 @@ pub fn svg_name(&self) -> &'static str {
             PanelKind::Search => LapceIcons::SEARCH,
             PanelKind::Problem => LapceIcons::PROBLEM,
             PanelKind::Debug => LapceIcons::DEBUG,
+            PanelKind::Notification => LapceIcons::NOTIFICATION,
+            PanelKind::Log => LapceIcons::LOG,
+            PanelKind::TestExplorer => LapceIcons::TEST_EXPLORER,
+            PanelKind::HttpResponse => LapceIcons::HTTP_RESPONSE,
+            PanelKind::DebugConsole => LapceIcons::DEBUG_CONSOLE,
+            PanelKind::Collaboration => LapceIcons::COLLABORATION,
+            PanelKind::DebugMemory => LapceIcons::DEBUG_MEMORY,
+            PanelKind::Todo => LapceIcons::TODO,
+        }
+    }
+
+    /// The id of the message which translates the name of the panel
+    pub fn message_id(&self) -> &'static str {
+        match &self {
+            PanelKind::Terminal => "panel-terminal",
+            PanelKind::FileExplorer => "panel-file-explorer",
+            PanelKind::SourceControl => "panel-source-control",
+            PanelKind::Plugin => "panel-plugins",
+            PanelKind::Search => "panel-search",
+            PanelKind::Problem => "panel-problems",
+            PanelKind::Debug => "panel-debug",
+            PanelKind::Notification => "panel-notifications",
+            PanelKind::Log => "panel-log",
+            PanelKind::TestExplorer => "panel-test-explorer",
+            PanelKind::HttpResponse => "panel-http-response",
+            PanelKind::DebugConsole => "panel-debug-console",
+            PanelKind::Collaboration => "panel-collaboration",
+            PanelKind::DebugMemory => "panel-debug-memory",
+            PanelKind::Todo => "panel-todo",
         }
     }
 
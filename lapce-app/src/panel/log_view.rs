@@ -0,0 +1,205 @@
+use std::{ops::Range, sync::Arc};
+
+use floem::{
+    event::EventListener,
+    peniko::kurbo::{Point, Size},
+    reactive::{create_rw_signal, Memo, ReadSignal, SignalGet, SignalSet},
+    style::{CursorStyle, Style},
+    view::View,
+    views::{
+        container, label, scroll, stack, virtual_list, Decorators,
+        VirtualListDirection, VirtualListItemSize, VirtualListVector,
+    },
+    ViewContext,
+};
+
+use super::kind::PanelKind;
+use crate::{
+    app::clickable_icon,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    log::LogRecord,
+    text_input::text_input,
+    window_tab::{Focus, WindowTabData},
+};
+
+struct LogItems(im::Vector<LogRecord>);
+
+impl VirtualListVector<LogRecord> for LogItems {
+    type ItemIterator = Box<dyn Iterator<Item = LogRecord>>;
+
+    fn total_len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn slice(&mut self, range: Range<usize>) -> Self::ItemIterator {
+        Box::new(self.0.slice(range).into_iter())
+    }
+}
+
+pub fn log_panel(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let focus = window_tab_data.common.focus;
+    let ui_line_height = window_tab_data.common.ui_line_height;
+    let log = window_tab_data.log.clone();
+    let level = log.level;
+    let filtered = log.filtered;
+    let editor = log.filter_editor.clone();
+    let is_focused = move || focus.get() == Focus::Panel(PanelKind::Log);
+
+    let cx = ViewContext::get_current();
+    let cursor_x = create_rw_signal(cx.scope, 0.0);
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    label(move || config.get().tr("panel-log")).style(|| {
+                        Style::BASE.flex_grow(1.0).min_width_px(0.0).text_ellipsis()
+                    }),
+                    {
+                        let log = log.clone();
+                        label(move || level.get().as_str().to_string())
+                            .on_click(move |_| {
+                                log.cycle_level();
+                                true
+                            })
+                            .style(move || {
+                                Style::BASE
+                                    .padding_horiz_px(6.0)
+                                    .border_radius(4.0)
+                                    .color(
+                                        *config
+                                            .get()
+                                            .get_color(LapceColor::EDITOR_DIM),
+                                    )
+                            })
+                            .hover_style(move || {
+                                Style::BASE.cursor(CursorStyle::Pointer).background(
+                                    *config.get().get_color(
+                                        LapceColor::PANEL_HOVERED_BACKGROUND,
+                                    ),
+                                )
+                            })
+                    },
+                    clickable_icon(
+                        || LapceIcons::CLOSE,
+                        move || log.clear(),
+                        || false,
+                        || false,
+                        config,
+                    ),
+                )
+            })
+            .style(move || {
+                Style::BASE
+                    .items_center()
+                    .padding_left_px(10.0)
+                    .padding_right_px(6.0)
+                    .width_pct(100.0)
+                    .background(
+                        *config.get().get_color(LapceColor::EDITOR_BACKGROUND),
+                    )
+            }),
+            container(|| {
+                scroll(|| {
+                    text_input(editor, is_focused)
+                        .on_cursor_pos(move |point| {
+                            cursor_x.set(point.x);
+                        })
+                        .style(|| {
+                            Style::BASE
+                                .padding_vert_px(4.0)
+                                .padding_horiz_px(8.0)
+                                .min_width_pct(100.0)
+                        })
+                })
+                .hide_bar(|| true)
+                .on_ensure_visible(move || {
+                    Size::new(20.0, 0.0)
+                        .to_rect()
+                        .with_origin(Point::new(cursor_x.get(), 0.0))
+                })
+                .on_event(EventListener::PointerDown, move |_| {
+                    focus.set(Focus::Panel(PanelKind::Log));
+                    false
+                })
+                .style(move || {
+                    Style::BASE
+                        .width_pct(100.0)
+                        .cursor(CursorStyle::Text)
+                        .items_center()
+                        .border(1.0)
+                        .border_radius(6.0)
+                        .border_color(
+                            *config.get().get_color(LapceColor::LAPCE_BORDER),
+                        )
+                })
+            })
+            .style(|| Style::BASE.padding_px(6.0).width_pct(100.0)),
+            container(|| {
+                scroll(|| {
+                    virtual_list(
+                        VirtualListDirection::Vertical,
+                        VirtualListItemSize::Fixed(Box::new(move || {
+                            ui_line_height.get()
+                        })),
+                        move || LogItems(filtered.get()),
+                        |record| record.id,
+                        move |record| {
+                            log_record_view(record, config, ui_line_height)
+                        },
+                    )
+                    .style(|| Style::BASE.flex_col().min_width_pct(100.0))
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|| Style::BASE.size_pct(100.0, 100.0)),
+        )
+    })
+    .style(|| Style::BASE.flex_col().size_pct(100.0, 100.0))
+}
+
+/// A record on one line, where the lines of a message which has more are put
+/// together.
+fn log_record_view(
+    record: LogRecord,
+    config: ReadSignal<Arc<LapceConfig>>,
+    ui_line_height: Memo<f64>,
+) -> impl View {
+    let time = record.time.format("%H:%M:%S%.3f").to_string();
+    let level = record.level.as_str();
+    let color = record.color();
+    let target = record.target;
+    let message = record.message.lines().collect::<Vec<_>>().join(" ⏎ ");
+
+    stack(move || {
+        (
+            label(move || time.clone()).style(move || {
+                Style::BASE
+                    .margin_right_px(8.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            label(move || level.to_string()).style(move || {
+                Style::BASE
+                    .min_width_px(48.0)
+                    .margin_right_px(8.0)
+                    .color(*config.get().get_color(color))
+            }),
+            label(move || target.clone()).style(move || {
+                Style::BASE
+                    .margin_right_px(8.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            label(move || message.clone()),
+        )
+    })
+    .style(move || {
+        Style::BASE
+            .items_center()
+            .padding_horiz_px(10.0)
+            .height_px(ui_line_height.get() as f32)
+    })
+}
@@ -1,11 +1,19 @@
+pub mod collab_view;
 pub mod data;
+pub mod debug_console_view;
+pub mod debug_memory_view;
 pub mod debug_view;
 pub mod global_search_view;
+pub mod http_response_view;
 pub mod kind;
+pub mod log_view;
+pub mod notification_view;
 pub mod plugin_view;
 pub mod position;
 pub mod problem_view;
 pub mod source_control_view;
 pub mod style;
 pub mod terminal_view;
+pub mod test_explorer_view;
+pub mod todo_view;
 pub mod view;
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use floem::{
+    cosmic_text::Weight,
+    reactive::{ReadSignal, SignalGet},
+    style::{CursorStyle, Style},
+    view::View,
+    views::{container, label, list, scroll, stack, svg, Decorators},
+};
+
+use crate::{
+    app::clickable_icon,
+    command::{InternalCommand, LapceWorkbenchCommand},
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    listener::Listener,
+    notification::{Notification, NotificationCommand, NotificationData},
+    window_tab::WindowTabData,
+};
+
+pub fn notification_panel(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let workbench_command = window_tab_data.common.workbench_command;
+    let internal_command = window_tab_data.common.internal_command;
+    let notifications = window_tab_data.notification;
+    let do_not_disturb = notifications.do_not_disturb;
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    label(move || config.get().tr("panel-notifications")).style(
+                        || {
+                            Style::BASE
+                                .flex_grow(1.0)
+                                .min_width_px(0.0)
+                                .text_ellipsis()
+                        },
+                    ),
+                    clickable_icon(
+                        move || {
+                            if do_not_disturb.get() {
+                                LapceIcons::NOTIFICATION_OFF
+                            } else {
+                                LapceIcons::NOTIFICATION
+                            }
+                        },
+                        move || notifications.toggle_do_not_disturb(),
+                        move || do_not_disturb.get(),
+                        || false,
+                        config,
+                    ),
+                    clickable_icon(
+                        || LapceIcons::CLOSE,
+                        move || notifications.clear_history(),
+                        || false,
+                        || false,
+                        config,
+                    ),
+                )
+            })
+            .style(move || {
+                Style::BASE
+                    .items_center()
+                    .padding_left_px(10.0)
+                    .padding_right_px(6.0)
+                    .width_pct(100.0)
+                    .background(
+                        *config.get().get_color(LapceColor::EDITOR_BACKGROUND),
+                    )
+            }),
+            container(|| {
+                scroll(|| {
+                    list(
+                        move || notifications.history.get(),
+                        |notification| notification.id,
+                        move |notification| {
+                            notification_view(
+                                notification,
+                                notifications,
+                                false,
+                                workbench_command,
+                                internal_command,
+                                config,
+                            )
+                        },
+                    )
+                    .style(|| Style::BASE.flex_col().width_pct(100.0))
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|| Style::BASE.size_pct(100.0, 100.0)),
+        )
+    })
+    .style(|| Style::BASE.flex_col().size_pct(100.0, 100.0))
+}
+
+/// A notification with its buttons, which is used both for the toasts and in the
+/// history panel.
+pub fn notification_view(
+    notification: Notification,
+    notifications: NotificationData,
+    is_toast: bool,
+    workbench_command: Listener<LapceWorkbenchCommand>,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let id = notification.id;
+    let severity = notification.severity;
+    let title = notification.title;
+    let message = notification.message;
+    let time = notification.time.format("%H:%M:%S").to_string();
+    let actions = notification.actions;
+    let message_is_empty = message.is_empty();
+    let actions_is_empty = actions.is_empty();
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    svg(move || config.get().ui_svg(severity.svg_name())).style(
+                        move || {
+                            let config = config.get();
+                            let size = config.ui.icon_size() as f32;
+                            Style::BASE
+                                .size_px(size, size)
+                                .min_width_px(size)
+                                .margin_right_px(6.0)
+                                .color(*config.get_color(severity.color()))
+                        },
+                    ),
+                    label(move || title.clone()).style(|| {
+                        Style::BASE
+                            .font_weight(Weight::BOLD)
+                            .flex_grow(1.0)
+                            .min_width_px(0.0)
+                            .text_ellipsis()
+                    }),
+                    label(move || time.clone()).style(move || {
+                        Style::BASE
+                            .margin_left_px(6.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    }),
+                    container(|| {
+                        clickable_icon(
+                            || LapceIcons::CLOSE,
+                            move || notifications.dismiss(id),
+                            || false,
+                            || false,
+                            config,
+                        )
+                    })
+                    .style(move || {
+                        Style::BASE
+                            .margin_left_px(4.0)
+                            .apply_if(!is_toast, |s| s.hide())
+                    }),
+                )
+            })
+            .style(|| Style::BASE.items_center().width_pct(100.0)),
+            label(move || message.clone()).style(move || {
+                Style::BASE
+                    .margin_top_px(4.0)
+                    .min_width_px(0.0)
+                    .max_width_pct(100.0)
+                    .apply_if(message_is_empty, |s| s.hide())
+            }),
+            list(
+                move || actions.clone().into_iter().enumerate(),
+                |(i, _)| *i,
+                move |(_, action)| {
+                    let command = action.command;
+                    label(move || action.title.clone())
+                        .on_click(move |_| {
+                            match command.clone() {
+                                NotificationCommand::Workbench(cmd) => {
+                                    workbench_command.send(cmd)
+                                }
+                                NotificationCommand::Internal(cmd) => {
+                                    internal_command.send(cmd)
+                                }
+                            }
+                            notifications.dismiss(id);
+                            true
+                        })
+                        .style(move || {
+                            let config = config.get();
+                            Style::BASE
+                                .margin_right_px(6.0)
+                                .padding_horiz_px(8.0)
+                                .padding_vert_px(2.0)
+                                .border_radius(4.0)
+                                .color(*config.get_color(
+                                    LapceColor::LAPCE_BUTTON_PRIMARY_FOREGROUND,
+                                ))
+                                .background(*config.get_color(
+                                    LapceColor::LAPCE_BUTTON_PRIMARY_BACKGROUND,
+                                ))
+                        })
+                        .hover_style(|| Style::BASE.cursor(CursorStyle::Pointer))
+                },
+            )
+            .style(move || {
+                Style::BASE
+                    .margin_top_px(6.0)
+                    .apply_if(actions_is_empty, |s| s.hide())
+            }),
+        )
+    })
+    .style(move || {
+        let config = config.get();
+        Style::BASE
+            .flex_col()
+            .width_pct(100.0)
+            .padding_horiz_px(10.0)
+            .padding_vert_px(6.0)
+            .line_height(1.6)
+            .apply_if(!is_toast, |s| {
+                s.border_bottom(1.0)
+                    .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+            })
+    })
+}
@@ -75,7 +75,7 @@ pub fn plugin_panel(
                 let plugin = plugin.clone();
                 stack(move || {
                     (
-                        panel_header("Installed".to_string(), config),
+                        panel_header("panel-installed-plugins", config),
                         installed_view(plugin),
                     )
                 })
@@ -91,7 +91,7 @@ pub fn plugin_panel(
                 let plugin = plugin.clone();
                 stack(move || {
                     (
-                        panel_header("Available".to_string(), config),
+                        panel_header("panel-available-plugins", config),
                         available_view(plugin),
                     )
                 })
@@ -123,9 +123,10 @@ fn installed_view(plugin: PluginData) -> impl View {
     let plugin_controls = {
         move |plugin: PluginData, volt: VoltInfo, meta: VoltMetadata| {
             let volt_id = volt.id();
+            let config = config.get_untracked();
             let menu =
                 Menu::new("")
-                    .entry(MenuItem::new("Reload Plugin").action({
+                    .entry(MenuItem::new(config.tr("plugin-reload")).action({
                         let plugin = plugin.clone();
                         let meta = meta.clone();
                         move || {
@@ -134,7 +135,7 @@ fn installed_view(plugin: PluginData) -> impl View {
                     }))
                     .separator()
                     .entry(
-                        MenuItem::new("Enable")
+                        MenuItem::new(config.tr("plugin-enable"))
                             .enabled(disabled.with_untracked(|disabled| {
                                 disabled.contains(&volt_id)
                             }))
@@ -147,7 +148,7 @@ fn installed_view(plugin: PluginData) -> impl View {
                             }),
                     )
                     .entry(
-                        MenuItem::new("Disalbe")
+                        MenuItem::new(config.tr("plugin-disable"))
                             .enabled(disabled.with_untracked(|disabled| {
                                 !disabled.contains(&volt_id)
                             }))
@@ -161,7 +162,7 @@ fn installed_view(plugin: PluginData) -> impl View {
                     )
                     .separator()
                     .entry(
-                        MenuItem::new("Enable For Workspace")
+                        MenuItem::new(config.tr("plugin-enable-for-workspace"))
                             .enabled(workspace_disabled.with_untracked(|disabled| {
                                 disabled.contains(&volt_id)
                             }))
@@ -174,7 +175,7 @@ fn installed_view(plugin: PluginData) -> impl View {
                             }),
                     )
                     .entry(
-                        MenuItem::new("Disalbe For Workspace")
+                        MenuItem::new(config.tr("plugin-disable-for-workspace"))
                             .enabled(workspace_disabled.with_untracked(|disabled| {
                                 !disabled.contains(&volt_id)
                             }))
@@ -186,7 +187,7 @@ fn installed_view(plugin: PluginData) -> impl View {
                             }),
                     )
                     .separator()
-                    .entry(MenuItem::new("Uninstall").action({
+                    .entry(MenuItem::new(config.tr("plugin-uninstall")).action({
                         move || {
                             plugin.uninstall_volt(meta.clone());
                         }
@@ -232,7 +233,7 @@ fn installed_view(plugin: PluginData) -> impl View {
                                                 || workspace_disabled
                                                     .with(|d| d.contains(&volt_id))
                                             {
-                                                "Disabled".to_string()
+                                                config.get().tr("plugin-disabled")
                                             } else {
                                                 format!("v{}", meta.version.clone())
                                             }
@@ -326,12 +327,13 @@ fn available_view(plugin: PluginData) -> impl View {
             installed.with(|installed| installed.contains_key(&id))
         });
         label(move || {
+            let config = config.get();
             if installed.get() {
-                "Installed".to_string()
+                config.tr("plugin-installed")
             } else if installing.get() {
-                "Installing".to_string()
+                config.tr("plugin-installing")
             } else {
-                "Install".to_string()
+                config.tr("plugin-install")
             }
         })
         .disabled(move || installed.get() || installing.get())
@@ -35,7 +35,7 @@ pub fn problem_panel(
         (
             stack(|| {
                 (
-                    panel_header("Errors".to_string(), config),
+                    panel_header("panel-errors", config),
                     problem_section(
                         window_tab_data.clone(),
                         DiagnosticSeverity::ERROR,
@@ -54,7 +54,7 @@ pub fn problem_panel(
             }),
             stack(|| {
                 (
-                    panel_header("Warnings".to_string(), config),
+                    panel_header("panel-warnings", config),
                     problem_section(window_tab_data, DiagnosticSeverity::WARNING),
                 )
             })
@@ -49,13 +49,18 @@ pub fn source_control_panel(
                 (
                     container(|| {
                         scroll(|| {
-                            let view = stack(|| {
-                                (
-                                    editor_view(editor, is_active).style(|| {
-                                        Style::BASE.min_size_pct(100.0, 100.0)
-                                    }),
-                                    label(|| "Commit Message".to_string()).style(
-                                        move || {
+                            let view =
+                                stack(|| {
+                                    (
+                                        editor_view(editor, is_active).style(|| {
+                                            Style::BASE.min_size_pct(100.0, 100.0)
+                                        }),
+                                        label(move || {
+                                            config
+                                                .get()
+                                                .tr("source-control-commit-message")
+                                        })
+                                        .style(move || {
                                             let config = config.get();
                                             Style::BASE
                                                 .absolute()
@@ -70,16 +75,15 @@ pub fn source_control_panel(
                                                 .apply_if(!is_empty.get(), |s| {
                                                     s.hide()
                                                 })
-                                        },
-                                    ),
-                                )
-                            })
-                            .style(|| {
-                                Style::BASE
-                                    .min_size_pct(100.0, 100.0)
-                                    .padding_left_px(10.0)
-                                    .padding_vert_px(6.0)
-                            });
+                                        }),
+                                    )
+                                })
+                                .style(|| {
+                                    Style::BASE
+                                        .min_size_pct(100.0, 100.0)
+                                        .padding_left_px(10.0)
+                                        .padding_vert_px(6.0)
+                                });
                             let id = view.id();
                             view.on_event(EventListener::PointerDown, move |event| {
                                 let event = event.clone().offset((10.0, 6.0));
@@ -155,7 +159,7 @@ pub fn source_control_panel(
                     }),
                     {
                         let source_control = source_control.clone();
-                        label(|| "Commit".to_string())
+                        label(move || config.get().tr("source-control-commit"))
                             .style(move || {
                                 Style::BASE
                                     .margin_top_px(10.0)
@@ -192,7 +196,7 @@ pub fn source_control_panel(
             .style(|| Style::BASE.flex_col().width_pct(100.0).padding_px(10.0)),
             stack(|| {
                 (
-                    panel_header("Changes".to_string(), config),
+                    panel_header("panel-changes", config),
                     file_diffs_view(source_control),
                 )
             })
@@ -344,8 +348,14 @@ fn file_diffs_view(source_control: SourceControlData) -> impl View {
 
             if let Event::PointerDown(pointer_event) = event {
                 if pointer_event.button.is_right() {
-                    let menu = Menu::new("")
-                        .entry(MenuItem::new("Discard Changes").action(discard));
+                    let menu = Menu::new("").entry(
+                        MenuItem::new(
+                            config
+                                .get_untracked()
+                                .tr("source-control-discard-changes"),
+                        )
+                        .action(discard),
+                    );
                     cx.id.show_context_menu(menu, Point::ZERO);
                 }
             }
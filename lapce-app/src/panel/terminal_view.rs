@@ -240,7 +240,10 @@ fn terminal_tab_split(
                     if let Event::PointerWheel(pointer_event) = event {
                         if let PointerType::Mouse(info) = &pointer_event.pointer_type
                         {
-                            terminal.clone().wheel_scroll(info.wheel_delta.y);
+                            terminal.clone().wheel_scroll(
+                                info.wheel_delta,
+                                pointer_event.modifiers,
+                            );
                         }
                         true
                     } else {
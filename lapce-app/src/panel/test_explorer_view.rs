@@ -0,0 +1,384 @@
+use std::{path::PathBuf, sync::Arc};
+
+use floem::{
+    reactive::{
+        create_memo, create_rw_signal, ReadSignal, RwSignal, SignalGet,
+        SignalUpdate, SignalWith,
+    },
+    style::{CursorStyle, Style},
+    view::View,
+    views::{container, label, list, scroll, stack, svg, Decorators},
+    ViewContext,
+};
+
+use crate::{
+    app::clickable_icon,
+    command::InternalCommand,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    editor::location::{EditorLocation, EditorPosition},
+    listener::Listener,
+    test_explorer::{TestExplorerData, TestItem, TestStatus},
+    window_tab::WindowTabData,
+    workspace::LapceWorkspace,
+};
+
+pub fn test_explorer_panel(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let results = window_tab_data.common.test_results;
+    let workspace = window_tab_data.workspace.clone();
+    let test_explorer = window_tab_data.test_explorer.clone();
+    let tests = test_explorer.tests;
+    let no_failed = move || {
+        results.with(|results| {
+            !results
+                .values()
+                .any(|status| matches!(status, TestStatus::Failed(_)))
+        })
+    };
+    let no_running = move || {
+        results.with(|results| {
+            !results.values().any(|status| {
+                matches!(status, TestStatus::Queued | TestStatus::Running)
+            })
+        })
+    };
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    label(move || config.get().tr("panel-test-explorer")).style(
+                        || {
+                            Style::BASE
+                                .flex_grow(1.0)
+                                .min_width_px(0.0)
+                                .text_ellipsis()
+                        },
+                    ),
+                    {
+                        let test_explorer = test_explorer.clone();
+                        clickable_icon(
+                            || LapceIcons::START,
+                            move || test_explorer.run_all(),
+                            || false,
+                            move || tests.with(|tests| tests.is_empty()),
+                            config,
+                        )
+                    },
+                    {
+                        let test_explorer = test_explorer.clone();
+                        clickable_icon(
+                            || LapceIcons::DEBUG_RESTART,
+                            move || test_explorer.rerun_failed(),
+                            || false,
+                            no_failed,
+                            config,
+                        )
+                    },
+                    {
+                        let test_explorer = test_explorer.clone();
+                        clickable_icon(
+                            || LapceIcons::DEBUG_STOP,
+                            move || test_explorer.cancel(),
+                            || false,
+                            no_running,
+                            config,
+                        )
+                    },
+                )
+            })
+            .style(move || {
+                Style::BASE
+                    .items_center()
+                    .padding_left_px(10.0)
+                    .padding_right_px(6.0)
+                    .width_pct(100.0)
+                    .background(
+                        *config.get().get_color(LapceColor::EDITOR_BACKGROUND),
+                    )
+            }),
+            container(|| {
+                scroll(move || {
+                    stack(move || {
+                        (
+                            label(move || config.get().tr("test-explorer-empty"))
+                                .style(move || {
+                                    Style::BASE
+                                        .padding_horiz_px(10.0)
+                                        .color(
+                                            *config
+                                                .get()
+                                                .get_color(LapceColor::EDITOR_DIM),
+                                        )
+                                        .apply_if(
+                                            !tests.with(|tests| tests.is_empty()),
+                                            |s| s.hide(),
+                                        )
+                                }),
+                            list(
+                                move || tests.get(),
+                                |(path, _)| path.clone(),
+                                move |(path, tests)| {
+                                    file_view(
+                                        workspace.clone(),
+                                        path,
+                                        tests,
+                                        test_explorer.clone(),
+                                        results,
+                                        internal_command,
+                                        config,
+                                    )
+                                },
+                            )
+                            .style(|| Style::BASE.flex_col().width_pct(100.0)),
+                        )
+                    })
+                    .style(|| {
+                        Style::BASE.flex_col().width_pct(100.0).line_height(1.6)
+                    })
+                })
+                .scroll_bar_color(move || {
+                    *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+                })
+                .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+            })
+            .style(|| Style::BASE.size_pct(100.0, 100.0)),
+        )
+    })
+    .style(|| Style::BASE.flex_col().size_pct(100.0, 100.0))
+}
+
+fn file_view(
+    workspace: Arc<LapceWorkspace>,
+    path: PathBuf,
+    tests: im::Vector<TestItem>,
+    test_explorer: TestExplorerData,
+    results: RwSignal<im::HashMap<String, TestStatus>>,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let cx = ViewContext::get_current();
+    let collapsed = create_rw_signal(cx.scope, false);
+
+    let full_path = path.clone();
+    let path = if let Some(workspace_path) = workspace.path.as_ref() {
+        path.strip_prefix(workspace_path)
+            .unwrap_or(&full_path)
+            .to_path_buf()
+    } else {
+        path
+    };
+    let style_path = path.clone();
+
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let folder = path
+        .parent()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    svg(move || {
+                        config.get().ui_svg(if collapsed.get() {
+                            LapceIcons::ITEM_CLOSED
+                        } else {
+                            LapceIcons::ITEM_OPENED
+                        })
+                    })
+                    .style(move || {
+                        let config = config.get();
+                        let size = config.ui.icon_size() as f32;
+                        Style::BASE
+                            .margin_right_px(6.0)
+                            .size_px(size, size)
+                            .color(*config.get_color(LapceColor::LAPCE_ICON_ACTIVE))
+                    }),
+                    svg(move || config.get().file_svg(&path).0).style(move || {
+                        let config = config.get();
+                        let size = config.ui.icon_size() as f32;
+                        let color = config.file_svg(&style_path).1.copied();
+                        Style::BASE
+                            .min_width_px(size)
+                            .size_px(size, size)
+                            .margin_right_px(6.0)
+                            .apply_opt(color, Style::color)
+                    }),
+                    label(move || file_name.clone())
+                        .style(|| Style::BASE.margin_right_px(6.0).text_ellipsis()),
+                    label(move || folder.clone()).style(move || {
+                        Style::BASE
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                            .min_width_px(0.0)
+                            .text_ellipsis()
+                    }),
+                )
+            })
+            .on_click(move |_| {
+                collapsed.update(|collapsed| *collapsed = !*collapsed);
+                true
+            })
+            .style(|| {
+                Style::BASE
+                    .items_center()
+                    .width_pct(100.0)
+                    .min_width_px(0.0)
+                    .padding_horiz_px(10.0)
+            })
+            .hover_style(move || {
+                Style::BASE.cursor(CursorStyle::Pointer).background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            }),
+            list(
+                move || {
+                    if collapsed.get() {
+                        im::Vector::new()
+                    } else {
+                        tests.clone()
+                    }
+                },
+                |test| test.id.clone(),
+                move |test| {
+                    test_view(
+                        test,
+                        test_explorer.clone(),
+                        results,
+                        internal_command,
+                        config,
+                    )
+                },
+            )
+            .style(|| Style::BASE.flex_col().width_pct(100.0).min_width_px(0.0)),
+        )
+    })
+    .style(|| Style::BASE.flex_col().width_pct(100.0).items_start())
+}
+
+fn test_view(
+    test: TestItem,
+    test_explorer: TestExplorerData,
+    results: RwSignal<im::HashMap<String, TestStatus>>,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let cx = ViewContext::get_current();
+    let id = test.id.clone();
+    let status =
+        create_memo(cx.scope, move |_| results.with(|r| r.get(&id).cloned()));
+    let location = EditorLocation {
+        path: test.path.clone(),
+        position: test.line.map(|line| EditorPosition::Line(line as usize)),
+        scroll_offset: None,
+        ignore_unconfirmed: false,
+        same_editor_tab: false,
+    };
+    let text = test.label.clone();
+    let indent = move || 10.0 + (config.get().ui.icon_size() as f32 + 6.0) * 2.0;
+
+    stack(move || {
+        (
+            stack(|| {
+                (
+                    svg(move || {
+                        config.get().ui_svg(match status.get() {
+                            Some(TestStatus::Failed(_)) => LapceIcons::TEST_FAILED,
+                            _ => LapceIcons::TEST_STATUS,
+                        })
+                    })
+                    .style(move || {
+                        let config = config.get();
+                        let size = config.ui.icon_size() as f32;
+                        let color = match status.get() {
+                            Some(TestStatus::Passed) => LapceColor::TEST_PASSED,
+                            Some(TestStatus::Failed(_)) => LapceColor::TEST_FAILED,
+                            Some(TestStatus::Running) => LapceColor::LAPCE_WARN,
+                            Some(TestStatus::Queued) | None => {
+                                LapceColor::LAPCE_ICON_INACTIVE
+                            }
+                        };
+                        Style::BASE
+                            .min_width_px(size)
+                            .size_px(size, size)
+                            .margin_right_px(6.0)
+                            .color(*config.get_color(color))
+                    }),
+                    label(move || text.clone()).style(|| {
+                        Style::BASE.flex_grow(1.0).min_width_px(0.0).text_ellipsis()
+                    }),
+                    {
+                        let test_explorer = test_explorer.clone();
+                        let test = test.clone();
+                        clickable_icon(
+                            || LapceIcons::START,
+                            move || test_explorer.run_tests([test.clone()].into()),
+                            || false,
+                            move || {
+                                matches!(
+                                    status.get(),
+                                    Some(TestStatus::Queued | TestStatus::Running)
+                                )
+                            },
+                            config,
+                        )
+                    },
+                    clickable_icon(
+                        || LapceIcons::DEBUG,
+                        move || test_explorer.debug_test(&test),
+                        || false,
+                        || false,
+                        config,
+                    ),
+                )
+            })
+            .on_click(move |_| {
+                internal_command.send(InternalCommand::JumpToLocation {
+                    location: location.clone(),
+                });
+                true
+            })
+            .style(move || {
+                Style::BASE
+                    .items_center()
+                    .width_pct(100.0)
+                    .min_width_px(0.0)
+                    .padding_left_px(indent())
+                    .padding_right_px(6.0)
+            })
+            .hover_style(move || {
+                Style::BASE.cursor(CursorStyle::Pointer).background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            }),
+            label(move || match status.get() {
+                Some(TestStatus::Failed(message)) => {
+                    message.lines().collect::<Vec<_>>().join(" ⏎ ")
+                }
+                _ => String::new(),
+            })
+            .style(move || {
+                let config = config.get();
+                let failed = matches!(
+                    status.get(),
+                    Some(TestStatus::Failed(message)) if !message.is_empty()
+                );
+                Style::BASE
+                    .width_pct(100.0)
+                    .min_width_px(0.0)
+                    .text_ellipsis()
+                    .padding_left_px(indent() + config.ui.icon_size() as f32 + 6.0)
+                    .padding_right_px(10.0)
+                    .color(*config.get_color(LapceColor::TEST_FAILED))
+                    .apply_if(!failed, |s| s.hide())
+            }),
+        )
+    })
+    .style(|| Style::BASE.flex_col().width_pct(100.0).min_width_px(0.0))
+}
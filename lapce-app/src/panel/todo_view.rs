@@ -0,0 +1,251 @@
+use std::{path::PathBuf, sync::Arc};
+
+use floem::{
+    peniko::Color,
+    reactive::{ReadSignal, SignalGet, SignalWith},
+    style::{CursorStyle, Style},
+    view::View,
+    views::{container, container_box, label, list, scroll, stack, svg, Decorators},
+};
+
+use crate::{
+    command::InternalCommand,
+    config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    editor::location::{EditorLocation, EditorPosition},
+    listener::Listener,
+    todo::{TodoData, TodoNode},
+    window_tab::WindowTabData,
+    workspace::LapceWorkspace,
+};
+
+pub fn todo_panel(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let internal_command = window_tab_data.common.internal_command;
+    let workspace = window_tab_data.workspace.clone();
+    let todo = window_tab_data.todo.clone();
+    let files = todo.files;
+    let scanning = todo.scanning;
+
+    let empty_text = move || {
+        let config = config.get();
+        if scanning.get() {
+            config.tr("todo-scanning")
+        } else {
+            config.tr("todo-empty")
+        }
+    };
+
+    container(|| {
+        scroll(move || {
+            stack(move || {
+                (
+                    label(empty_text).style(move || {
+                        Style::BASE
+                            .padding_horiz_px(10.0)
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                            .apply_if(files.with(|files| !files.is_empty()), |s| {
+                                s.hide()
+                            })
+                    }),
+                    {
+                        let tree_todo = todo.clone();
+                        list(
+                            move || tree_todo.tree(),
+                            |node| node.clone(),
+                            move |node| {
+                                todo_node_view(
+                                    node,
+                                    todo.clone(),
+                                    workspace.clone(),
+                                    internal_command,
+                                    config,
+                                )
+                            },
+                        )
+                        .style(|| Style::BASE.flex_col().width_pct(100.0))
+                    },
+                )
+            })
+            .style(|| Style::BASE.flex_col().min_width_pct(100.0).line_height(1.6))
+        })
+        .scroll_bar_color(move || {
+            *config.get().get_color(LapceColor::LAPCE_SCROLL_BAR)
+        })
+        .style(|| Style::BASE.absolute().size_pct(100.0, 100.0))
+    })
+    .style(|| Style::BASE.size_pct(100.0, 100.0))
+}
+
+/// The color of the tag in the settings, or the one of the editor.
+fn tag_color(config: &LapceConfig, tag: &str) -> Color {
+    config
+        .core
+        .todo_tag_colors
+        .get(tag)
+        .and_then(|color| Color::parse(color))
+        .unwrap_or(*config.get_color(LapceColor::EDITOR_FOREGROUND))
+}
+
+fn todo_node_view(
+    node: TodoNode,
+    todo: TodoData,
+    workspace: Arc<LapceWorkspace>,
+    internal_command: Listener<InternalCommand>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let (level, view) = match node {
+        TodoNode::File { path, count } => (
+            0,
+            container_box(move || {
+                Box::new(file_view(path, count, todo, workspace, config))
+            }),
+        ),
+        TodoNode::Tag { tag, count, .. } => {
+            let style_tag = tag.clone();
+            let view = stack(|| {
+                (
+                    label(move || tag.clone()).style(move || {
+                        Style::BASE
+                            .margin_right_px(6.0)
+                            .color(tag_color(&config.get(), &style_tag))
+                    }),
+                    label(move || count.to_string()).style(move || {
+                        Style::BASE
+                            .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+                    }),
+                )
+            });
+            (1, container_box(move || Box::new(view)))
+        }
+        TodoNode::Item { path, item } => {
+            let location = EditorLocation {
+                path,
+                position: Some(EditorPosition::Line(item.line)),
+                scroll_offset: None,
+                ignore_unconfirmed: false,
+                same_editor_tab: false,
+            };
+            let text = format!("{}: {}", item.line + 1, item.text);
+            let view = label(move || text.clone())
+                .on_click(move |_| {
+                    internal_command.send(InternalCommand::JumpToLocation {
+                        location: location.clone(),
+                    });
+                    true
+                })
+                .style(|| {
+                    Style::BASE
+                        .width_pct(100.0)
+                        .min_width_px(0.0)
+                        .text_ellipsis()
+                });
+            (2, container_box(move || Box::new(view)))
+        }
+    };
+
+    view.style(move || {
+        let config = config.get();
+        Style::BASE
+            .width_pct(100.0)
+            .min_width_px(0.0)
+            .padding_left_px(
+                10.0 + (config.ui.icon_size() as f32 + 6.0) * level as f32,
+            )
+            .padding_right_px(10.0)
+    })
+    .hover_style(move || {
+        // The rows of the tags aren't clicked
+        Style::BASE.apply_if(level != 1, |s| {
+            s.cursor(CursorStyle::Pointer).background(
+                *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+            )
+        })
+    })
+}
+
+/// The row of a file, which folds its comments when it's clicked.
+fn file_view(
+    path: PathBuf,
+    count: usize,
+    todo: TodoData,
+    workspace: Arc<LapceWorkspace>,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let collapsed = todo.collapsed;
+    let full_path = path.clone();
+    let path = workspace
+        .path
+        .as_ref()
+        .and_then(|workspace| path.strip_prefix(workspace).ok())
+        .map(|path| path.to_path_buf())
+        .unwrap_or(path);
+    let style_path = path.clone();
+    let icon_path = path.clone();
+    let is_collapsed = {
+        let full_path = full_path.clone();
+        move || collapsed.with(|collapsed| collapsed.contains(&full_path))
+    };
+
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let folder = path
+        .parent()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    stack(move || {
+        (
+            svg(move || {
+                config.get().ui_svg(if is_collapsed() {
+                    LapceIcons::ITEM_CLOSED
+                } else {
+                    LapceIcons::ITEM_OPENED
+                })
+            })
+            .style(move || {
+                let config = config.get();
+                let size = config.ui.icon_size() as f32;
+                Style::BASE
+                    .margin_right_px(6.0)
+                    .size_px(size, size)
+                    .color(*config.get_color(LapceColor::LAPCE_ICON_ACTIVE))
+            }),
+            svg(move || config.get().file_svg(&icon_path).0).style(move || {
+                let config = config.get();
+                let size = config.ui.icon_size() as f32;
+                let color = config.file_svg(&style_path).1.copied();
+                Style::BASE
+                    .min_width_px(size)
+                    .size_px(size, size)
+                    .margin_right_px(6.0)
+                    .apply_opt(color, Style::color)
+            }),
+            label(move || file_name.clone())
+                .style(|| Style::BASE.margin_right_px(6.0).text_ellipsis()),
+            label(move || folder.clone()).style(move || {
+                Style::BASE
+                    .margin_right_px(6.0)
+                    .min_width_px(0.0)
+                    .text_ellipsis()
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+            label(move || count.to_string()).style(move || {
+                Style::BASE.color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
+        )
+    })
+    .on_click(move |_| {
+        todo.toggle_file(&full_path);
+        true
+    })
+    .style(|| {
+        Style::BASE
+            .items_center()
+            .width_pct(100.0)
+            .min_width_px(0.0)
+    })
+}
@@ -3,8 +3,8 @@
 use floem::{
     event::EventListener,
     reactive::{
-        create_rw_signal, ReadSignal, SignalGet, SignalGetUntracked, SignalSet,
-        SignalWith, SignalWithUntracked,
+        create_effect, create_rw_signal, ReadSignal, SignalGet, SignalGetUntracked,
+        SignalSet, SignalWith, SignalWithUntracked,
     },
     style::Style,
     view::View,
@@ -13,14 +13,22 @@
 };
 
 use super::{
+    collab_view::collab_panel,
+    debug_console_view::debug_console_panel,
+    debug_memory_view::debug_memory_panel,
     debug_view::debug_panel,
     global_search_view::global_search_panel,
+    http_response_view::http_response_panel,
     kind::PanelKind,
+    log_view::log_panel,
+    notification_view::notification_panel,
     plugin_view::plugin_panel,
     position::{PanelContainerPosition, PanelPosition},
     problem_view::problem_panel,
     source_control_view::source_control_panel,
     terminal_view::terminal_panel,
+    test_explorer_view::test_explorer_panel,
+    todo_view::todo_panel,
 };
 use crate::{
     app::clickable_icon,
@@ -162,30 +170,28 @@ fn panel_view(
         panels,
         |p| *p,
         move |kind| {
-            let view = match kind {
-                PanelKind::Terminal => container_box(|| {
-                    Box::new(terminal_panel(window_tab_data.clone()))
-                }),
-                PanelKind::FileExplorer => container_box(|| {
-                    Box::new(file_explorer_panel(window_tab_data.clone(), position))
-                }),
-                PanelKind::SourceControl => container_box(|| {
-                    Box::new(source_control_panel(window_tab_data.clone(), position))
-                }),
-                PanelKind::Plugin => container_box(|| {
-                    Box::new(plugin_panel(window_tab_data.clone(), position))
-                }),
-                PanelKind::Search => container_box(|| {
-                    Box::new(global_search_panel(window_tab_data.clone(), position))
-                }),
-                PanelKind::Problem => container_box(|| {
-                    Box::new(problem_panel(window_tab_data.clone(), position))
-                }),
-                PanelKind::Debug => container_box(|| {
-                    Box::new(debug_panel(window_tab_data.clone(), position))
-                }),
-            };
-            view.style(|| Style::BASE.size_pct(100.0, 100.0))
+            // The view of a panel is only built once the panel is first shown,
+            // so that the panels which aren't open don't slow down the startup
+            let cx = ViewContext::get_current();
+            let built = create_rw_signal(cx.scope, false);
+            {
+                let panel = window_tab_data.panel.clone();
+                create_effect(cx.scope, move |_| {
+                    if !built.get_untracked()
+                        && panel.active_panel_at_position(&position, true)
+                            == Some((kind, true))
+                    {
+                        built.set(true);
+                    }
+                });
+            }
+            let window_tab_data = window_tab_data.clone();
+            list(
+                move || if built.get() { vec![kind] } else { Vec::new() },
+                |kind| *kind,
+                move |kind| panel_kind_view(window_tab_data.clone(), kind, position),
+            )
+            .style(|| Style::BASE.size_pct(100.0, 100.0))
         },
     )
     .style(move || {
@@ -197,11 +203,68 @@ fn panel_view(
     })
 }
 
+fn panel_kind_view(
+    window_tab_data: Arc<WindowTabData>,
+    kind: PanelKind,
+    position: PanelPosition,
+) -> impl View {
+    let view = match kind {
+        PanelKind::Terminal => {
+            container_box(|| Box::new(terminal_panel(window_tab_data.clone())))
+        }
+        PanelKind::FileExplorer => container_box(|| {
+            Box::new(file_explorer_panel(window_tab_data.clone(), position))
+        }),
+        PanelKind::SourceControl => container_box(|| {
+            Box::new(source_control_panel(window_tab_data.clone(), position))
+        }),
+        PanelKind::Plugin => container_box(|| {
+            Box::new(plugin_panel(window_tab_data.clone(), position))
+        }),
+        PanelKind::Search => container_box(|| {
+            Box::new(global_search_panel(window_tab_data.clone(), position))
+        }),
+        PanelKind::Problem => container_box(|| {
+            Box::new(problem_panel(window_tab_data.clone(), position))
+        }),
+        PanelKind::Debug => container_box(|| {
+            Box::new(debug_panel(window_tab_data.clone(), position))
+        }),
+        PanelKind::Notification => {
+            container_box(|| Box::new(notification_panel(window_tab_data.clone())))
+        }
+        PanelKind::Log => {
+            container_box(|| Box::new(log_panel(window_tab_data.clone())))
+        }
+        PanelKind::TestExplorer => {
+            container_box(|| Box::new(test_explorer_panel(window_tab_data.clone())))
+        }
+        PanelKind::HttpResponse => {
+            container_box(|| Box::new(http_response_panel(window_tab_data.clone())))
+        }
+        PanelKind::DebugConsole => {
+            container_box(|| Box::new(debug_console_panel(window_tab_data.clone())))
+        }
+        PanelKind::Collaboration => {
+            container_box(|| Box::new(collab_panel(window_tab_data.clone())))
+        }
+        PanelKind::DebugMemory => {
+            container_box(|| Box::new(debug_memory_panel(window_tab_data.clone())))
+        }
+        PanelKind::Todo => {
+            container_box(|| Box::new(todo_panel(window_tab_data.clone())))
+        }
+    };
+    view.style(|| Style::BASE.size_pct(100.0, 100.0))
+}
+
+/// The header of a panel section, which shows the translation of the message with
+/// the given id.
 pub fn panel_header(
-    header: String,
+    message_id: &'static str,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
-    container(|| label(move || header.clone())).style(move || {
+    container(|| label(move || config.get().tr(message_id))).style(move || {
         Style::BASE
             .padding_horiz_px(10.0)
             .padding_vert_px(6.0)
@@ -237,6 +300,14 @@ fn panel_picker(
                 PanelKind::Search => LapceIcons::SEARCH,
                 PanelKind::Problem => LapceIcons::PROBLEM,
                 PanelKind::Debug => LapceIcons::DEBUG_ALT,
+                PanelKind::Notification => LapceIcons::NOTIFICATION,
+                PanelKind::Log => LapceIcons::LOG,
+                PanelKind::TestExplorer => LapceIcons::TEST_EXPLORER,
+                PanelKind::HttpResponse => LapceIcons::HTTP_RESPONSE,
+                PanelKind::DebugConsole => LapceIcons::DEBUG_CONSOLE,
+                PanelKind::Collaboration => LapceIcons::COLLABORATION,
+                PanelKind::DebugMemory => LapceIcons::DEBUG_MEMORY,
+                PanelKind::Todo => LapceIcons::TODO,
             };
             let is_active = {
                 let window_tab_data = window_tab_data.clone();
@@ -58,6 +58,8 @@ pub struct PluginData {
     pub all: AvailableVoltList,
     pub disabled: RwSignal<HashSet<VoltID>>,
     pub workspace_disabled: RwSignal<HashSet<VoltID>>,
+    /// The plugins disabled by the active profile
+    pub profile_disabled: RwSignal<HashSet<VoltID>>,
     pub common: CommonData,
 }
 
@@ -115,43 +117,28 @@ pub fn new(
         };
         let disabled = create_rw_signal(cx, disabled);
         let workspace_disabled = create_rw_signal(cx, workspace_disabled);
+        let profile_disabled = create_rw_signal(
+            cx,
+            common
+                .config
+                .get_untracked()
+                .profile
+                .as_ref()
+                .map(|profile| {
+                    HashSet::from_iter(profile.info.disabled_volts.clone())
+                })
+                .unwrap_or_default(),
+        );
 
         let plugin = Self {
             installed,
             all,
             disabled,
             workspace_disabled,
+            profile_disabled,
             common,
         };
 
-        plugin.load_available_volts("", 0);
-
-        {
-            let plugin = plugin.clone();
-            let send = create_ext_action(
-                cx,
-                move |volts: Vec<(Option<Vec<u8>>, VoltMetadata)>| {
-                    for (icon, meta) in volts {
-                        plugin.volt_installed(&meta, &icon);
-                    }
-                },
-            );
-            std::thread::spawn(move || {
-                let volts = find_all_volts();
-                let volts = volts
-                    .into_iter()
-                    .filter_map(|meta| {
-                        if meta.wasm.is_none() {
-                            Some((volt_icon(&meta), meta))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                send(volts);
-            });
-        }
-
         {
             let plugin = plugin.clone();
             create_effect(cx, move |s| {
@@ -160,7 +147,8 @@ pub fn new(
                     .query_editor
                     .doc
                     .with(|doc| doc.buffer().to_string());
-                if s.as_ref() == Some(&query) {
+                // The first query is made by `load`
+                if s.is_none() || s.as_ref() == Some(&query) {
                     return query;
                 }
                 plugin.all.query_id.update(|id| *id += 1);
@@ -174,6 +162,36 @@ pub fn new(
         plugin
     }
 
+    /// Find the installed plugins, and ask the registry for the available ones,
+    /// which is put off until the workspace is shown.
+    pub fn load(&self) {
+        self.load_available_volts("", 0);
+
+        let plugin = self.clone();
+        let send = create_ext_action(
+            self.common.scope,
+            move |volts: Vec<(Option<Vec<u8>>, VoltMetadata)>| {
+                for (icon, meta) in volts {
+                    plugin.volt_installed(&meta, &icon);
+                }
+            },
+        );
+        std::thread::spawn(move || {
+            let volts = find_all_volts();
+            let volts = volts
+                .into_iter()
+                .filter_map(|meta| {
+                    if meta.wasm.is_none() {
+                        Some((volt_icon(&meta), meta))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+            send(volts);
+        });
+    }
+
     pub fn volt_installed(&self, volt: &VoltMetadata, icon: &Option<Vec<u8>>) {
         let volt_id = volt.id();
         let (is_latest, latest) = self
@@ -354,6 +372,34 @@ pub fn install_volt(&self, info: VoltInfo) {
     pub fn plugin_disabled(&self, id: &VoltID) -> bool {
         self.disabled.with_untracked(|d| d.contains(id))
             || self.workspace_disabled.with_untracked(|d| d.contains(id))
+            || self.profile_disabled.with_untracked(|d| d.contains(id))
+    }
+
+    /// Update the plugins disabled by the active profile, and enable or disable
+    /// the installed plugins which are affected by the change.
+    pub fn set_profile_disabled(&self, profile_disabled: HashSet<VoltID>) {
+        let old = self.profile_disabled.get_untracked();
+        if old == profile_disabled {
+            return;
+        }
+        self.profile_disabled.set(profile_disabled.clone());
+
+        let installed = self.installed.get_untracked();
+        for (id, volt) in installed.iter() {
+            let disabled = profile_disabled.contains(id);
+            if old.contains(id) == disabled
+                || self.disabled.with_untracked(|d| d.contains(id))
+                || self.workspace_disabled.with_untracked(|d| d.contains(id))
+            {
+                continue;
+            }
+            let info = volt.meta.get_untracked().info();
+            if disabled {
+                self.common.proxy.disable_volt(info);
+            } else {
+                self.common.proxy.enable_volt(info);
+            }
+        }
     }
 
     pub fn enable_volt(&self, volt: VoltInfo) {
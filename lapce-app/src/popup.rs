@@ -0,0 +1,253 @@
+//! The popups which are shown at the cursor of an editor, which are laid out
+//! together so that they don't cover each other, and one of which is in front.
+
+use floem::reactive::{
+    create_effect, create_rw_signal, RwSignal, Scope, SignalGet, SignalGetUntracked,
+    SignalSet, SignalWith, SignalWithUntracked,
+};
+
+use crate::{
+    completion::{CompletionData, CompletionStatus},
+    hover::HoverData,
+    signature::SignatureHelpData,
+};
+
+/// The popups, in the order which they're stacked from the line in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PopupKind {
+    Completion,
+    SignatureHelp,
+    Hover,
+}
+
+impl PopupKind {
+    const ALL: [PopupKind; 3] = [
+        PopupKind::Completion,
+        PopupKind::SignatureHelp,
+        PopupKind::Hover,
+    ];
+
+    /// The side of the line which the popup goes on while there's room there
+    fn side(self) -> PopupSide {
+        match self {
+            PopupKind::SignatureHelp => PopupSide::Above,
+            PopupKind::Completion | PopupKind::Hover => PopupSide::Below,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PopupSide {
+    Above,
+    Below,
+}
+
+impl PopupSide {
+    pub fn other(self) -> Self {
+        match self {
+            PopupSide::Above => PopupSide::Below,
+            PopupSide::Below => PopupSide::Above,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PopupData {
+    /// The popup which was brought to the front, which is drawn over the others
+    /// and is closed first. The first one which is shown is in front otherwise.
+    front: RwSignal<Option<PopupKind>>,
+    completion: RwSignal<CompletionData>,
+    signature: SignatureHelpData,
+    hover: HoverData,
+}
+
+impl PopupData {
+    pub fn new(
+        cx: Scope,
+        completion: RwSignal<CompletionData>,
+        signature: SignatureHelpData,
+        hover: HoverData,
+    ) -> Self {
+        let popups = Self {
+            front: create_rw_signal(cx, None),
+            completion,
+            signature,
+            hover,
+        };
+        // A popup which was brought to the front doesn't stay there once it's
+        // closed, so that it isn't in front again when it's shown next
+        create_effect(cx, move |_| {
+            if let Some(front) = popups.front.get() {
+                if !popups.visible(true).contains(&front) {
+                    popups.front.set(None);
+                }
+            }
+        });
+        popups
+    }
+
+    fn is_visible(&self, kind: PopupKind, tracked: bool) -> bool {
+        match kind {
+            PopupKind::Completion => {
+                let visible = |c: &CompletionData| {
+                    c.status != CompletionStatus::Inactive
+                        && !c.filtered_items.is_empty()
+                };
+                if tracked {
+                    self.completion.with(visible)
+                } else {
+                    self.completion.with_untracked(visible)
+                }
+            }
+            PopupKind::SignatureHelp => {
+                if tracked {
+                    self.signature.active.get()
+                } else {
+                    self.signature.active.get_untracked()
+                }
+            }
+            PopupKind::Hover => {
+                if tracked {
+                    self.hover.active.get()
+                } else {
+                    self.hover.active.get_untracked()
+                }
+            }
+        }
+    }
+
+    /// The popups which are shown, in the order which they're stacked in.
+    pub fn visible(&self, tracked: bool) -> Vec<PopupKind> {
+        PopupKind::ALL
+            .into_iter()
+            .filter(|kind| self.is_visible(*kind, tracked))
+            .collect()
+    }
+
+    pub fn front(&self, tracked: bool) -> Option<PopupKind> {
+        let front = if tracked {
+            self.front.get()
+        } else {
+            self.front.get_untracked()
+        };
+        let visible = self.visible(tracked);
+        front
+            .filter(|front| visible.contains(front))
+            .or_else(|| visible.first().copied())
+    }
+
+    /// Bring the next of the popups which are shown to the front.
+    pub fn cycle(&self) {
+        let visible = self.visible(false);
+        let Some(front) = self.front(false) else {
+            return;
+        };
+        let index = visible.iter().position(|kind| *kind == front).unwrap_or(0);
+        self.front.set(Some(visible[(index + 1) % visible.len()]));
+    }
+}
+
+/// Where the popups of a line go, by the side of the line and how far the near
+/// edge of each is from it. They're stacked away from the line in the order
+/// which they're given in, and each goes on its own side while it fits there,
+/// or the other one if it fits there instead, or else the roomier one.
+pub fn stack_popups(
+    popups: &[(PopupKind, f64)],
+    room_above: f64,
+    room_below: f64,
+) -> Vec<(PopupKind, PopupSide, f64)> {
+    let mut used_above = 0.0;
+    let mut used_below = 0.0;
+    popups
+        .iter()
+        .map(|(kind, height)| {
+            let fits = |side| match side {
+                PopupSide::Above => used_above + height <= room_above,
+                PopupSide::Below => used_below + height <= room_below,
+            };
+            let side = kind.side();
+            let side = if fits(side) {
+                side
+            } else if fits(side.other()) {
+                side.other()
+            } else if room_above - used_above > room_below - used_below {
+                PopupSide::Above
+            } else {
+                PopupSide::Below
+            };
+            let used = match side {
+                PopupSide::Above => &mut used_above,
+                PopupSide::Below => &mut used_below,
+            };
+            let distance = *used;
+            *used += height;
+            (*kind, side, distance)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_popups() {
+        // The signature goes above the line, and the hover under the completion
+        assert_eq!(
+            stack_popups(
+                &[
+                    (PopupKind::Completion, 200.0),
+                    (PopupKind::SignatureHelp, 30.0),
+                    (PopupKind::Hover, 100.0),
+                ],
+                500.0,
+                500.0,
+            ),
+            vec![
+                (PopupKind::Completion, PopupSide::Below, 0.0),
+                (PopupKind::SignatureHelp, PopupSide::Above, 0.0),
+                (PopupKind::Hover, PopupSide::Below, 200.0),
+            ]
+        );
+
+        // Near the bottom the hover goes above the signature
+        assert_eq!(
+            stack_popups(
+                &[
+                    (PopupKind::Completion, 200.0),
+                    (PopupKind::SignatureHelp, 30.0),
+                    (PopupKind::Hover, 100.0),
+                ],
+                500.0,
+                250.0,
+            ),
+            vec![
+                (PopupKind::Completion, PopupSide::Below, 0.0),
+                (PopupKind::SignatureHelp, PopupSide::Above, 0.0),
+                (PopupKind::Hover, PopupSide::Above, 30.0),
+            ]
+        );
+
+        // Near the top the signature goes under the completion
+        assert_eq!(
+            stack_popups(
+                &[
+                    (PopupKind::Completion, 200.0),
+                    (PopupKind::SignatureHelp, 30.0),
+                ],
+                10.0,
+                500.0,
+            ),
+            vec![
+                (PopupKind::Completion, PopupSide::Below, 0.0),
+                (PopupKind::SignatureHelp, PopupSide::Below, 200.0),
+            ]
+        );
+
+        // Where neither side fits, the roomier one is used
+        assert_eq!(
+            stack_popups(&[(PopupKind::Hover, 400.0)], 300.0, 100.0),
+            vec![(PopupKind::Hover, PopupSide::Above, 0.0)]
+        );
+    }
+}
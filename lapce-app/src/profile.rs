@@ -0,0 +1,284 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use lapce_core::directory::Directory;
+use lapce_rpc::plugin::VoltID;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::LapceConfig, panel::data::PanelOrder};
+
+const PROFILE_TABLE: &str = "profile";
+const KEYMAPS_TABLE: &str = "keymaps";
+
+/// The metadata of a profile, stored in the `[profile]` table of the profile file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProfileInfo {
+    /// The name shown to the user, which defaults to the file name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Plugins which are disabled while the profile is active.
+    #[serde(default)]
+    pub disabled_volts: Vec<VoltID>,
+    /// The panel layout, which is applied when switching to the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel: Option<PanelOrder>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ProfileFile {
+    #[serde(default)]
+    profile: ProfileInfo,
+}
+
+/// A configuration profile, which bundles settings, keymaps, disabled plugins and
+/// the panel layout into one shareable file. The file is laid out like
+/// `settings.toml`, with the `[[keymaps]]` of `keymaps.toml` and a `[profile]`
+/// table added. The active profile is applied on top of the user configuration.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    /// The file stem of the profile, which is what `core.profile` refers to.
+    pub id: String,
+    pub info: ProfileInfo,
+    /// The settings part of the profile, without the keymaps and profile info.
+    pub settings: String,
+    /// The keymaps part of the profile, laid out like `keymaps.toml`.
+    pub keymaps: Option<String>,
+    /// The whole profile file.
+    pub content: String,
+}
+
+impl Profile {
+    pub fn file(id: &str) -> Option<PathBuf> {
+        Directory::profiles_directory().map(|dir| dir.join(format!("{id}.toml")))
+    }
+
+    pub fn parse(id: &str, content: &str) -> Result<Self> {
+        let mut document: toml_edit::Document = content.parse()?;
+        document.as_table_mut().remove(PROFILE_TABLE);
+        let keymaps = document.as_table_mut().remove(KEYMAPS_TABLE).map(|item| {
+            let mut keymaps = toml_edit::Document::new();
+            keymaps.insert(KEYMAPS_TABLE, item);
+            keymaps.to_string()
+        });
+
+        let file: ProfileFile = toml_edit::easy::from_str(content)?;
+
+        Ok(Self {
+            id: id.to_string(),
+            info: file.profile,
+            settings: document.to_string(),
+            keymaps,
+            content: content.to_string(),
+        })
+    }
+
+    /// Load the profile with the given id, if there's one.
+    pub fn load(id: &str) -> Option<Self> {
+        if id.is_empty() {
+            return None;
+        }
+        let content = std::fs::read_to_string(Self::file(id)?).ok()?;
+        Self::parse(id, &content).ok()
+    }
+
+    /// All the profiles in the profiles directory, sorted by id.
+    pub fn all() -> Vec<Self> {
+        let Some(dir) = Directory::profiles_directory() else {
+            return Vec::new();
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut profiles: Vec<Self> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("toml") {
+                    return None;
+                }
+                let id = path.file_stem()?.to_str()?.to_string();
+                let content = std::fs::read_to_string(&path).ok()?;
+                Self::parse(&id, &content).ok()
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.id.cmp(&b.id));
+        profiles
+    }
+
+    pub fn name(&self) -> &str {
+        self.info.name.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Build the content of a profile file from the current user settings and
+    /// keymaps.
+    pub fn snapshot(
+        name: &str,
+        disabled_volts: Vec<VoltID>,
+        panel: Option<PanelOrder>,
+    ) -> Result<String> {
+        let settings = LapceConfig::settings_file()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let mut document: toml_edit::Document = settings.parse()?;
+        // a profile can't pick another profile
+        if let Some(core) = document
+            .get_mut("core")
+            .and_then(|core| core.as_table_like_mut())
+        {
+            core.remove("profile");
+        }
+
+        let info = toml_edit::easy::to_string(&ProfileFile {
+            profile: ProfileInfo {
+                name: Some(name.to_string()),
+                disabled_volts,
+                panel,
+            },
+        })?;
+        let info: toml_edit::Document = info.parse()?;
+        if let Some(item) = info.get(PROFILE_TABLE) {
+            document.insert(PROFILE_TABLE, item.clone());
+        }
+
+        let keymaps = LapceConfig::keymaps_file()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let keymaps: toml_edit::Document = keymaps.parse()?;
+        if let Some(item) = keymaps.get(KEYMAPS_TABLE) {
+            document.insert(KEYMAPS_TABLE, item.clone());
+        }
+
+        Ok(document.to_string())
+    }
+
+    /// Write a new profile file with the given content, returning its path.
+    /// The id is made unique by adding a number to it.
+    pub fn create(id: &str, content: &str) -> Result<PathBuf> {
+        let dir = Directory::profiles_directory()
+            .ok_or_else(|| anyhow!("can't get profiles directory"))?;
+        write_new(&dir, id, content)
+    }
+
+    /// Copy a shared profile file into the profiles directory.
+    pub fn import(path: &Path) -> Result<PathBuf> {
+        let id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("invalid profile file name"))?;
+        let content = std::fs::read_to_string(path)?;
+        // make sure it's a valid profile before importing it
+        Self::parse(id, &content)?;
+        Self::create(id, &content)
+    }
+
+    /// Write the profile into the given directory so that it can be shared,
+    /// returning its path. A file which is there already is kept, and the
+    /// profile is written next to it with a number added to its name.
+    pub fn export(&self, dir: &Path) -> Result<PathBuf> {
+        write_new(dir, &self.id, &self.content)
+    }
+
+    /// Write the content of a profile, such as a [`Profile::snapshot`], into
+    /// the given directory like [`Profile::export`] does.
+    pub fn export_content(dir: &Path, id: &str, content: &str) -> Result<PathBuf> {
+        write_new(dir, id, content)
+    }
+}
+
+/// Write a new file named after the id in the directory, with a number added to
+/// the name when there's a file with it already, and return its path.
+fn write_new(dir: &Path, id: &str, content: &str) -> Result<PathBuf> {
+    let mut i = 0;
+    loop {
+        let path = if i == 0 {
+            dir.join(format!("{id}.toml"))
+        } else {
+            dir.join(format!("{id}-{i}.toml"))
+        };
+        // The file is only created if it's not there, so that one which was
+        // created since it was checked isn't replaced either
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(content.as_bytes())?;
+                return Ok(path);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                i += 1;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const CONTENT: &str = r#"[profile]
+name = "Writing"
+disabled-volts = [{ author = "author", name = "plugin" }]
+
+[editor]
+font-size = 16
+
+[[keymaps]]
+key = "ctrl+k"
+command = "palette"
+"#;
+
+    #[test]
+    fn test_parse() {
+        let profile = Profile::parse("writing", CONTENT).unwrap();
+        assert_eq!(profile.id, "writing");
+        assert_eq!(profile.name(), "Writing");
+        assert_eq!(profile.info.disabled_volts.len(), 1);
+        assert!(profile.settings.contains("font-size = 16"));
+        assert!(!profile.settings.contains("[profile]"));
+        assert!(!profile.settings.contains("keymaps"));
+        let keymaps = profile.keymaps.as_deref().unwrap();
+        assert!(keymaps.contains("[[keymaps]]"));
+        assert!(keymaps.contains("command = \"palette\""));
+        assert_eq!(profile.content, CONTENT);
+
+        // The name defaults to the id
+        let profile = Profile::parse("plain", "[editor]\nfont-size = 12\n").unwrap();
+        assert_eq!(profile.name(), "plain");
+        assert_eq!(profile.keymaps, None);
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = std::env::temp_dir()
+            .join(format!("lapce-profile-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let profile = Profile::parse("writing", CONTENT).unwrap();
+        let path = profile.export(&dir).unwrap();
+        assert_eq!(path, dir.join("writing.toml"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONTENT);
+
+        // A file which is there already isn't replaced
+        let path = Profile::export_content(&dir, "writing", "other").unwrap();
+        assert_eq!(path, dir.join("writing-1.toml"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "other");
+        assert_eq!(
+            fs::read_to_string(dir.join("writing.toml")).unwrap(),
+            CONTENT
+        );
+        let path = profile.export(&dir).unwrap();
+        assert_eq!(path, dir.join("writing-2.toml"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
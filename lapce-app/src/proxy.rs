@@ -1,4 +1,7 @@
-use std::{collections::HashMap, path::PathBuf, process::Command, sync::Arc};
+use std::{
+    cell::Cell, collections::HashMap, path::PathBuf, process::Command, rc::Rc,
+    sync::Arc,
+};
 
 use crossbeam_channel::Sender;
 use floem::{
@@ -9,18 +12,24 @@
 use lapce_rpc::{
     core::{CoreHandler, CoreNotification, CoreRpcHandler},
     plugin::VoltID,
-    proxy::ProxyRpcHandler,
+    proxy::{ProxyRpcHandler, ResourceLimits},
     terminal::TermId,
 };
-use lsp_types::Url;
+use lsp_types::{MessageType, ShowMessageParams, Url};
 use tracing::error;
 
-use self::{remote::start_remote, ssh::SshRemote};
+use self::{
+    askpass::{Askpass, SshPrompt},
+    remote::start_remote,
+    ssh::SshRemote,
+};
 use crate::{
+    collab::connection::SharedTerminals,
     terminal::event::TermEvent,
     workspace::{LapceWorkspace, LapceWorkspaceType},
 };
 
+pub mod askpass;
 mod remote;
 mod ssh;
 #[cfg(windows)]
@@ -29,6 +38,9 @@
 pub struct Proxy {
     pub tx: Sender<CoreNotification>,
     pub term_tx: Sender<(TermId, TermEvent)>,
+    /// The terminals whose output is sent to the guests of a collaboration
+    /// session
+    pub shared_terminals: SharedTerminals,
 }
 
 #[derive(Clone)]
@@ -36,9 +48,18 @@ pub struct ProxyData {
     pub proxy_rpc: ProxyRpcHandler,
     pub core_rpc: CoreRpcHandler,
     pub notification: ReadSignal<Option<CoreNotification>>,
+    /// Starts the proxy, which is put off until the workspace is shown. What's
+    /// sent to the proxy before then waits for it.
+    connect: Rc<Cell<Option<Box<dyn FnOnce()>>>>,
 }
 
 impl ProxyData {
+    pub fn connect(&self) {
+        if let Some(connect) = self.connect.take() {
+            connect();
+        }
+    }
+
     pub fn shutdown(&self) {
         self.proxy_rpc.shutdown();
         self.core_rpc.shutdown();
@@ -51,23 +72,30 @@ pub fn start_proxy(
     disabled_volts: Vec<VoltID>,
     plugin_configurations: HashMap<String, HashMap<String, serde_json::Value>>,
     term_tx: Sender<(TermId, TermEvent)>,
+    shared_terminals: SharedTerminals,
+    ssh_prompts: Sender<SshPrompt>,
+    ssh_forward_agent: bool,
+    resource_limits: ResourceLimits,
 ) -> ProxyData {
     let proxy_rpc = ProxyRpcHandler::new();
     let core_rpc = CoreRpcHandler::new();
 
-    {
+    // The initialize notification is sent first, so that the proxy gets it before
+    // anything which is sent until it's connected
+    proxy_rpc.initialize(
+        workspace.path.clone(),
+        disabled_volts,
+        plugin_configurations,
+        1,
+        1,
+        resource_limits,
+    );
+
+    let connect = {
         let core_rpc = core_rpc.clone();
         let proxy_rpc = proxy_rpc.clone();
-        std::thread::spawn(move || {
-            proxy_rpc.initialize(
-                workspace.path.clone(),
-                disabled_volts,
-                plugin_configurations,
-                1,
-                1,
-            );
-
-            match &workspace.kind {
+        move || {
+            std::thread::spawn(move || match &workspace.kind {
                 LapceWorkspaceType::Local => {
                     let core_rpc = core_rpc.clone();
                     let proxy_rpc = proxy_rpc.clone();
@@ -78,12 +106,26 @@ pub fn start_proxy(
                     });
                 }
                 LapceWorkspaceType::RemoteSSH(ssh) => {
+                    let askpass = Askpass::start(ssh.to_string(), ssh_prompts)
+                        .map_err(|e| error!("{e}"))
+                        .ok();
                     if let Err(e) = start_remote(
-                        SshRemote { ssh: ssh.clone() },
+                        SshRemote {
+                            ssh: ssh.clone(),
+                            forward_agent: ssh_forward_agent,
+                            askpass,
+                        },
                         core_rpc.clone(),
                         proxy_rpc.clone(),
                     ) {
                         error!("Failed to start SSH remote: {e}");
+                        core_rpc.show_message(
+                            ssh.to_string(),
+                            ShowMessageParams {
+                                typ: MessageType::ERROR,
+                                message: e.to_string(),
+                            },
+                        );
                     }
                 }
                 #[cfg(windows)]
@@ -103,15 +145,19 @@ pub fn start_proxy(
                         }
                     }
                 }
-            }
-        });
-    }
+            });
+        }
+    };
 
     let (tx, rx) = crossbeam_channel::unbounded();
     {
         let core_rpc = core_rpc.clone();
         std::thread::spawn(move || {
-            let mut proxy = Proxy { tx, term_tx };
+            let mut proxy = Proxy {
+                tx,
+                term_tx,
+                shared_terminals,
+            };
             core_rpc.mainloop(&mut proxy);
         })
     };
@@ -122,12 +168,14 @@ pub fn start_proxy(
         proxy_rpc,
         core_rpc,
         notification,
+        connect: Rc::new(Cell::new(Some(Box::new(connect)))),
     }
 }
 
 impl CoreHandler for Proxy {
     fn handle_notification(&mut self, rpc: lapce_rpc::core::CoreNotification) {
         if let CoreNotification::UpdateTerminal { term_id, content } = &rpc {
+            self.shared_terminals.output(*term_id, content);
             let _ = self
                 .term_tx
                 .send((*term_id, TermEvent::UpdateContent(content.to_vec())));
@@ -0,0 +1,168 @@
+//! The prompts of the SSH connections, like passwords, the codes of a second
+//! factor and the keys of new hosts. Lapce is the `SSH_ASKPASS` program of the
+//! SSH which it runs, and is run by it with the prompt, which it passes to the
+//! window over a local connection before printing the answer.
+
+use std::{
+    env,
+    io::{self, BufReader},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+
+use crate::collab::protocol::{new_token, read_message, write_message};
+
+/// The address which the askpass program connects to
+const ASKPASS_ADDRESS: &str = "LAPCE_ASKPASS_ADDRESS";
+/// The token which the askpass program sends with its prompt
+const ASKPASS_TOKEN: &str = "LAPCE_ASKPASS_TOKEN";
+
+/// How long SSH waits for a prompt which isn't answered, before it's cancelled
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SshPromptKind {
+    /// Something secret to type, like a password or a one-time code
+    Secret,
+    /// Whether to trust the key of a host which isn't in `known_hosts` yet
+    HostKey,
+    /// A question which is answered with yes or no
+    Confirm,
+    /// Something to do which isn't typed, like touching a security key
+    Info,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AskpassRequest {
+    token: String,
+    prompt: String,
+    kind: SshPromptKind,
+}
+
+#[derive(Clone, Debug)]
+pub struct SshPrompt {
+    pub host: String,
+    pub prompt: String,
+    pub kind: SshPromptKind,
+    reply: Sender<Option<String>>,
+}
+
+impl SshPrompt {
+    /// Answer the prompt, where `None` cancels it. Only the first answer counts.
+    pub fn answer(&self, answer: Option<String>) {
+        let _ = self.reply.try_send(answer);
+    }
+}
+
+/// Where the askpass program of the connections to a host asks for the prompts.
+pub struct Askpass {
+    address: String,
+    token: String,
+}
+
+impl Askpass {
+    /// Listen for the prompts of the SSH connections to the host, which are sent
+    /// to the window to be answered.
+    pub fn start(host: String, prompts: Sender<SshPrompt>) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?.to_string();
+        let token = new_token();
+        {
+            let token = token.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let host = host.clone();
+                    let token = token.clone();
+                    let prompts = prompts.clone();
+                    thread::spawn(move || {
+                        let _ = answer_prompt(stream, host, &token, &prompts);
+                    });
+                }
+            });
+        }
+        Ok(Self { address, token })
+    }
+
+    /// The environment which makes SSH ask Lapce for its prompts.
+    pub fn envs(&self) -> Vec<(&'static str, String)> {
+        let mut envs = vec![
+            ("SSH_ASKPASS_REQUIRE", "force".to_string()),
+            (ASKPASS_ADDRESS, self.address.clone()),
+            (ASKPASS_TOKEN, self.token.clone()),
+        ];
+        if let Ok(exe) = env::current_exe() {
+            envs.push(("SSH_ASKPASS", exe.to_string_lossy().to_string()));
+        }
+        // SSH before 8.4 only runs the askpass program when there's a display
+        if env::var_os("DISPLAY").is_none() {
+            envs.push(("DISPLAY", ":0".to_string()));
+        }
+        envs
+    }
+}
+
+fn answer_prompt(
+    mut stream: TcpStream,
+    host: String,
+    token: &str,
+    prompts: &Sender<SshPrompt>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(request) = read_message::<AskpassRequest>(&mut reader)? else {
+        return Ok(());
+    };
+    if request.token != token {
+        return Ok(());
+    }
+    let (reply, answer) = crossbeam_channel::bounded(1);
+    prompts.send(SshPrompt {
+        host,
+        prompt: request.prompt,
+        kind: request.kind,
+        reply,
+    })?;
+    let answer = answer.recv_timeout(ANSWER_TIMEOUT).unwrap_or(None);
+    write_message(&mut stream, &answer)?;
+    Ok(())
+}
+
+/// Ask the window for the answer to the prompt of SSH, when Lapce is run as its
+/// askpass program, and return the exit code. SSH reads the answer from the
+/// output, and takes an exit code which isn't 0 as a cancelled prompt.
+pub fn run() -> Option<i32> {
+    let address = env::var(ASKPASS_ADDRESS).ok()?;
+    let token = env::var(ASKPASS_TOKEN).ok()?;
+    let prompt = env::args().nth(1).unwrap_or_default();
+    let kind = match env::var("SSH_ASKPASS_PROMPT").as_deref() {
+        Ok("confirm") => SshPromptKind::Confirm,
+        Ok("none") => SshPromptKind::Info,
+        _ if prompt.contains("(yes/no") => SshPromptKind::HostKey,
+        _ => SshPromptKind::Secret,
+    };
+
+    let answer = (|| -> Result<Option<String>> {
+        let mut stream = TcpStream::connect(&address)?;
+        write_message(
+            &mut stream,
+            &AskpassRequest {
+                token,
+                prompt,
+                kind,
+            },
+        )?;
+        let mut reader = BufReader::new(stream);
+        Ok(read_message::<Option<String>>(&mut reader)?.flatten())
+    })();
+    match answer {
+        Ok(Some(answer)) => {
+            println!("{answer}");
+            Some(0)
+        }
+        _ => Some(1),
+    }
+}
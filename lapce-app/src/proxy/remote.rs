@@ -9,11 +9,12 @@
 use lapce_core::{directory::Directory, meta};
 use lapce_rpc::{
     core::CoreRpcHandler,
+    protocol::{ProtocolInfo, PROTOCOL_VERSION},
     proxy::{ProxyRpc, ProxyRpcHandler},
-    stdio_transport, RpcMessage,
+    stdio_transport, RpcError, RpcMessage,
 };
 use thiserror::Error;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 const UNIX_PROXY_SCRIPT: &[u8] = include_bytes!("../../../extra/proxy.sh");
 const WINDOWS_PROXY_SCRIPT: &[u8] = include_bytes!("../../../extra/proxy.ps1");
@@ -82,6 +83,17 @@ pub fn start_remote(
         Ok(cmd) => {
             debug!("{}", String::from_utf8_lossy(&cmd.stderr));
             debug!("{}", String::from_utf8_lossy(&cmd.stdout));
+            // SSH exits with 255 when it couldn't connect or log in, such as
+            // when a prompt was cancelled or the key of the host changed
+            if cmd.status.code() == Some(255) {
+                let stderr = String::from_utf8_lossy(&cmd.stderr);
+                let reason = stderr
+                    .lines()
+                    .rev()
+                    .find(|line| !line.trim().is_empty())
+                    .unwrap_or("the connection failed");
+                return Err(anyhow!(reason.trim().to_string()));
+            }
         }
         Err(err) => {
             error!("{err}");
@@ -257,6 +269,15 @@ pub fn start_remote(
         }
     }
 
+    let protocol = remote_protocol(&remote, platform, &remote_proxy_file);
+    if protocol.version < PROTOCOL_VERSION {
+        warn!(
+            "the remote proxy is at protocol version {} instead of {PROTOCOL_VERSION}, \
+             so the features which it doesn't know are left out",
+            protocol.version
+        );
+    }
+
     let mut child = match platform {
         // Force cmd.exe usage to resolve %envvar% variables
         Windows => remote
@@ -297,10 +318,23 @@ pub fn start_remote(
         for msg in local_proxy_rpc.rx() {
             match msg {
                 ProxyRpc::Request(id, rpc) => {
-                    let _ = local_writer_tx.send(RpcMessage::Request(id, rpc));
+                    if protocol.allows(rpc.capability()) {
+                        let _ = local_writer_tx.send(RpcMessage::Request(id, rpc));
+                    } else {
+                        local_proxy_rpc.handle_response(
+                            id,
+                            Err(RpcError {
+                                code: 0,
+                                message: "the remote proxy doesn't support this"
+                                    .to_string(),
+                            }),
+                        );
+                    }
                 }
                 ProxyRpc::Notification(rpc) => {
-                    let _ = local_writer_tx.send(RpcMessage::Notification(rpc));
+                    if protocol.allows(rpc.capability()) {
+                        let _ = local_writer_tx.send(RpcMessage::Notification(rpc));
+                    }
                 }
                 ProxyRpc::Shutdown => {
                     let _ = child.kill();
@@ -342,6 +376,40 @@ pub fn start_remote(
     Ok(())
 }
 
+/// What the proxy on the remote host knows of the protocol, which is nothing when
+/// it's from before the protocol was versioned, and so doesn't know `--protocol`.
+fn remote_protocol(
+    remote: &impl Remote,
+    platform: HostPlatform,
+    remote_proxy_file: &str,
+) -> ProtocolInfo {
+    let output = match platform {
+        HostPlatform::Windows => remote
+            .command_builder()
+            .args(["cmd", "/c"])
+            .arg(remote_proxy_file)
+            .arg("--protocol")
+            .output(),
+        _ => remote
+            .command_builder()
+            .arg(remote_proxy_file)
+            .arg("--protocol")
+            .output(),
+    };
+    let Ok(output) = output.map_err(|e| error!("{e}")) else {
+        return ProtocolInfo::legacy();
+    };
+    if !output.status.success() {
+        return ProtocolInfo::legacy();
+    }
+    // The output of the login of the shell can come before the one of the proxy
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str(line.trim()).ok())
+        .unwrap_or_else(ProtocolInfo::legacy)
+}
+
 fn host_specification(
     remote: &impl Remote,
 ) -> Result<(HostPlatform, HostArchitecture)> {
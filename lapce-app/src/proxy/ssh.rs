@@ -3,11 +3,14 @@
 use anyhow::Result;
 use tracing::debug;
 
-use super::remote::Remote;
+use super::{askpass::Askpass, remote::Remote};
 use crate::{proxy::new_command, workspace::SshHost};
 
 pub struct SshRemote {
     pub ssh: SshHost,
+    pub forward_agent: bool,
+    /// Where SSH asks for its prompts, like passwords and the keys of new hosts
+    pub askpass: Option<Askpass>,
 }
 
 impl SshRemote {
@@ -32,6 +35,9 @@ fn upload_file(&self, local: impl AsRef<Path>, remote: &str) -> Result<()> {
         let mut cmd = new_command("scp");
 
         cmd.args(Self::SSH_ARGS);
+        if let Some(askpass) = self.askpass.as_ref() {
+            cmd.envs(askpass.envs());
+        }
 
         if let Some(port) = self.ssh.port {
             cmd.arg("-P").arg(port.to_string());
@@ -51,6 +57,12 @@ fn upload_file(&self, local: impl AsRef<Path>, remote: &str) -> Result<()> {
     fn command_builder(&self) -> Command {
         let mut cmd = new_command("ssh");
         cmd.args(Self::SSH_ARGS);
+        if let Some(askpass) = self.askpass.as_ref() {
+            cmd.envs(askpass.envs());
+        }
+        if self.forward_agent {
+            cmd.arg("-A");
+        }
 
         if let Some(port) = self.ssh.port {
             cmd.arg("-p").arg(port.to_string());
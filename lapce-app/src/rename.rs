@@ -138,8 +138,10 @@ fn confirm(&self) {
             let internal_command = self.common.internal_command;
             let send = create_ext_action(self.common.scope, move |result| {
                 if let Ok(ProxyResponse::Rename { edit }) = result {
-                    internal_command
-                        .send(InternalCommand::ApplyWorkspaceEdit { edit });
+                    internal_command.send(InternalCommand::ApplyWorkspaceEdit {
+                        edit,
+                        rename: true,
+                    });
                 }
             });
             self.common.proxy.rename(
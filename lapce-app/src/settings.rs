@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, path::PathBuf, str::FromStr, sync::Arc};
 
 use floem::{
     event::EventListener,
@@ -16,25 +16,30 @@
     },
     ViewContext,
 };
+use fluent_bundle::FluentValue;
 use inflector::Inflector;
-use lapce_core::mode::Mode;
+use itertools::Itertools;
+use lapce_core::{language::LapceLanguage, mode::Mode};
+use lapce_rpc::plugin::VoltMetadata;
 use lapce_xi_rope::Rope;
 use serde::Serialize;
 
 use crate::{
-    command::CommandExecuted,
+    command::{CommandExecuted, InternalCommand},
     config::{
         color::LapceColor, core::CoreConfig, editor::EditorConfig, icon::LapceIcons,
-        terminal::TerminalConfig, ui::UIConfig, DropdownInfo, LapceConfig,
+        localization::setting_message_id, terminal::TerminalConfig, ui::UIConfig,
+        DropdownInfo, LapceConfig, SettingSource, SettingsLayer,
     },
     editor::EditorData,
     id::EditorId,
     keypress::KeyPressFocus,
+    plugin::PluginData,
     text_input::text_input,
     window_tab::CommonData,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SettingsValue {
     Float(f64),
     Integer(i64),
@@ -61,6 +66,27 @@ fn from(v: serde_json::Value) -> Self {
     }
 }
 
+impl SettingsValue {
+    /// The text shown in the input of a setting that is edited as text.
+    fn editor_string(&self) -> Option<String> {
+        match self {
+            SettingsValue::Float(n) => Some(n.to_string()),
+            SettingsValue::Integer(n) => Some(n.to_string()),
+            SettingsValue::String(s) => Some(s.to_string()),
+            SettingsValue::Bool(_) => None,
+            SettingsValue::Dropdown(_) => None,
+            SettingsValue::Empty => None,
+        }
+    }
+}
+
+/// Which settings file the changes made in the settings UI are written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsTarget {
+    User,
+    Workspace,
+}
+
 #[derive(Clone)]
 struct SettingsItem {
     kind: String,
@@ -68,14 +94,19 @@ struct SettingsItem {
     field: String,
     description: String,
     filter_text: String,
-    value: SettingsValue,
+    value: RwSignal<SettingsValue>,
+    source: RwSignal<SettingSource>,
     size: RwSignal<Size>,
 }
 
 #[derive(Clone)]
 struct SettingsData {
-    items: im::Vector<SettingsItem>,
+    items: RwSignal<im::Vector<SettingsItem>>,
     filtered_items: RwSignal<im::Vector<SettingsItem>>,
+    target: RwSignal<SettingsTarget>,
+    /// The language whose editor settings are shown and changed, instead of the
+    /// ones of all languages
+    language: RwSignal<Option<LapceLanguage>>,
     common: CommonData,
 }
 
@@ -115,135 +146,476 @@ fn slice(&mut self, _range: std::ops::Range<usize>) -> Self::ItemIterator {
     }
 }
 
-impl SettingsData {
-    pub fn new(cx: Scope, common: CommonData) -> Self {
-        fn into_settings_map(
-            data: &impl Serialize,
-        ) -> serde_json::Map<String, serde_json::Value> {
-            match serde_json::to_value(data).unwrap() {
-                serde_json::Value::Object(h) => h,
-                _ => serde_json::Map::default(),
-            }
+fn into_settings_map(
+    data: &impl Serialize,
+) -> serde_json::Map<String, serde_json::Value> {
+    match serde_json::to_value(data).unwrap() {
+        serde_json::Value::Object(h) => h,
+        _ => serde_json::Map::default(),
+    }
+}
+
+/// The current values of all the settings, keyed by the table they live in.
+/// Plugin settings which aren't set fall back to the default of the plugin.
+fn settings_maps(
+    config: &LapceConfig,
+    volts: &[VoltMetadata],
+    language: Option<LapceLanguage>,
+) -> HashMap<String, serde_json::Map<String, serde_json::Value>> {
+    let mut maps = HashMap::new();
+    maps.insert("core".to_string(), into_settings_map(&config.core));
+    maps.insert(
+        "editor".to_string(),
+        into_settings_map(config.editor_config(language)),
+    );
+    maps.insert("ui".to_string(), into_settings_map(&config.ui));
+    maps.insert("terminal".to_string(), into_settings_map(&config.terminal));
+
+    for meta in volts {
+        let Some(volt_config) = meta.config.as_ref() else {
+            continue;
+        };
+        let mut map: serde_json::Map<String, serde_json::Value> = volt_config
+            .iter()
+            .map(|(key, volt_config)| (key.clone(), volt_config.default.clone()))
+            .collect();
+        if let Some(values) = config.plugins.get(&meta.name) {
+            map.extend(values.iter().map(|(k, v)| (k.clone(), v.clone())));
         }
+        maps.insert(meta.name.clone(), map);
+    }
+
+    maps
+}
+
+fn settings_value(
+    config: &LapceConfig,
+    maps: &HashMap<String, serde_json::Map<String, serde_json::Value>>,
+    kind: &str,
+    field: &str,
+) -> SettingsValue {
+    if let Some(dropdown) = config.get_dropdown_info(kind, field) {
+        return SettingsValue::Dropdown(dropdown);
+    }
 
+    maps.get(kind)
+        .and_then(|map| map.get(field))
+        .cloned()
+        .map(SettingsValue::from)
+        .unwrap_or(SettingsValue::Empty)
+}
+
+/// The text which tells where the value of a setting comes from.
+fn source_text(
+    config: &LapceConfig,
+    source: SettingSource,
+    language: Option<LapceLanguage>,
+) -> String {
+    let layer = match source.layer {
+        SettingsLayer::Default => return config.tr("settings-source-default"),
+        SettingsLayer::User => config.tr("settings-user"),
+        SettingsLayer::Profile => config.tr("settings-source-profile"),
+        SettingsLayer::Workspace => config.tr("settings-workspace"),
+    };
+    match language.filter(|_| source.language) {
+        Some(language) => config.tr_args(
+            "settings-source-language",
+            [
+                ("source", FluentValue::from(layer)),
+                (
+                    "language",
+                    FluentValue::from(
+                        strum::EnumMessage::get_message(&language)
+                            .unwrap_or(language.as_ref()),
+                    ),
+                ),
+            ],
+        ),
+        None => {
+            config.tr_args("settings-source", [("source", FluentValue::from(layer))])
+        }
+    }
+}
+
+fn filter_text(kind: &str, name: &str, desc: &str) -> String {
+    let filter_text = format!("{kind} {name} {desc}").to_lowercase();
+    format!("{filter_text}{}", filter_text.replace(' ', ""))
+}
+
+impl SettingsData {
+    pub fn new(cx: Scope, plugin: PluginData, common: CommonData) -> Self {
         let config = common.config.get_untracked();
-        let mut items = im::Vector::new();
+        let maps = settings_maps(&config, &[], None);
+        let language = create_rw_signal(cx, None);
+        let mut core_items = im::Vector::new();
 
-        for (kind, fields, descs, mut settings_map) in [
-            (
-                "Core",
-                &CoreConfig::FIELDS[..],
-                &CoreConfig::DESCS[..],
-                into_settings_map(&config.core),
-            ),
+        for (kind, fields, descs) in [
+            ("Core", &CoreConfig::FIELDS[..], &CoreConfig::DESCS[..]),
             (
                 "Editor",
                 &EditorConfig::FIELDS[..],
                 &EditorConfig::DESCS[..],
-                into_settings_map(&config.editor),
-            ),
-            (
-                "UI",
-                &UIConfig::FIELDS[..],
-                &UIConfig::DESCS[..],
-                into_settings_map(&config.ui),
             ),
+            ("UI", &UIConfig::FIELDS[..], &UIConfig::DESCS[..]),
             (
                 "Terminal",
                 &TerminalConfig::FIELDS[..],
                 &TerminalConfig::DESCS[..],
-                into_settings_map(&config.terminal),
             ),
         ] {
             for (name, desc) in fields.iter().zip(descs.iter()) {
                 let field = name.replace('_', "-");
-
-                let value = if let Some(dropdown) =
-                    config.get_dropdown_info(&kind.to_lowercase(), &field)
-                {
-                    SettingsValue::Dropdown(dropdown)
-                } else {
-                    let value = settings_map.remove(&field).unwrap();
-                    SettingsValue::from(value)
-                };
-
                 let name =
                     format!("{kind}: {}", name.replace('_', " ").to_title_case());
                 let kind = kind.to_lowercase();
-                let filter_text = format!("{kind} {name} {desc}").to_lowercase();
-                let filter_text =
-                    format!("{filter_text}{}", filter_text.replace(' ', ""));
-                items.push_back(SettingsItem {
+                let value = settings_value(&config, &maps, &kind, &field);
+                let source = config.setting_source(&kind, &field, None);
+                let desc = config
+                    .localization
+                    .try_tr(&setting_message_id(&kind, &field), None)
+                    .unwrap_or_else(|| desc.to_string());
+                core_items.push_back(SettingsItem {
+                    filter_text: filter_text(&kind, &name, &desc),
                     kind,
                     name,
                     field,
-                    filter_text,
-                    description: desc.to_string(),
-                    value,
+                    description: desc,
+                    value: create_rw_signal(cx, value),
+                    source: create_rw_signal(cx, source),
                     size: create_rw_signal(cx, Size::ZERO),
                 });
             }
         }
 
+        let items = create_rw_signal(cx, core_items.clone());
+        let filtered_items = create_rw_signal(cx, core_items.clone());
+
+        // The plugin settings come from the installed plugins, which can change
+        // while the settings are open.
+        let installed = plugin.installed;
+        let config = common.config;
+        create_effect(cx, move |_| {
+            let volts: Vec<VoltMetadata> = installed.with(|installed| {
+                installed.values().map(|volt| volt.meta.get()).collect()
+            });
+            let config = config.get_untracked();
+            let language = language.get_untracked();
+            let maps = settings_maps(&config, &volts, language);
+
+            // The signals of the plugin settings which were already shown are
+            // kept, rather than created again each time the plugins change
+            let shown: HashMap<(String, String), SettingsItem> = items
+                .with_untracked(|items| {
+                    items
+                        .iter()
+                        .map(|item| {
+                            ((item.kind.clone(), item.field.clone()), item.clone())
+                        })
+                        .collect()
+                });
+
+            let mut new_items = core_items.clone();
+            for meta in &volts {
+                let Some(volt_config) = meta.config.as_ref() else {
+                    continue;
+                };
+                for (field, volt_config) in
+                    volt_config.iter().sorted_by_key(|(k, _)| *k)
+                {
+                    let name =
+                        format!("{}: {}", meta.display_name, field.to_title_case());
+                    let value = settings_value(&config, &maps, &meta.name, field);
+                    let source = config.setting_source(&meta.name, field, language);
+                    let (value, source, size) =
+                        match shown.get(&(meta.name.clone(), field.clone())) {
+                            Some(item) => {
+                                if item.value.with_untracked(|v| v != &value) {
+                                    item.value.set(value);
+                                }
+                                if item.source.get_untracked() != source {
+                                    item.source.set(source);
+                                }
+                                (item.value, item.source, item.size)
+                            }
+                            None => (
+                                create_rw_signal(cx, value),
+                                create_rw_signal(cx, source),
+                                create_rw_signal(cx, Size::ZERO),
+                            ),
+                        };
+                    new_items.push_back(SettingsItem {
+                        filter_text: filter_text(
+                            &meta.name,
+                            &name,
+                            &volt_config.description,
+                        ),
+                        kind: meta.name.clone(),
+                        name,
+                        field: field.clone(),
+                        description: volt_config.description.clone(),
+                        value,
+                        source,
+                        size,
+                    });
+                }
+            }
+            items.set(new_items);
+        });
+
+        // Keep the values in sync with the config, so that the changes made to
+        // the settings files directly show up here, and with the language whose
+        // settings are shown.
+        create_effect(cx, move |last: Option<()>| {
+            let config = config.get();
+            let language = language.get();
+            if last.is_none() {
+                return;
+            }
+            let volts: Vec<VoltMetadata> = installed.with_untracked(|installed| {
+                installed
+                    .values()
+                    .map(|volt| volt.meta.get_untracked())
+                    .collect()
+            });
+            let maps = settings_maps(&config, &volts, language);
+            items.with_untracked(|items| {
+                for item in items {
+                    let value =
+                        settings_value(&config, &maps, &item.kind, &item.field);
+                    if item.value.with_untracked(|v| v != &value) {
+                        item.value.set(value);
+                    }
+                    let source =
+                        config.setting_source(&item.kind, &item.field, language);
+                    if item.source.get_untracked() != source {
+                        item.source.set(source);
+                    }
+                }
+            });
+        });
+
         Self {
-            filtered_items: create_rw_signal(cx, items.clone()),
             items,
+            filtered_items,
+            target: create_rw_signal(cx, SettingsTarget::User),
+            language,
             common,
         }
     }
+
+    /// The settings file the changes are currently written to.
+    fn settings_file(&self) -> Option<PathBuf> {
+        match self.target.get_untracked() {
+            SettingsTarget::User => LapceConfig::settings_file(),
+            SettingsTarget::Workspace => {
+                LapceConfig::workspace_settings_file(&self.common.workspace)
+            }
+        }
+    }
+
+    fn update_file(&self, kind: &str, field: &str, value: toml_edit::Value) {
+        // The editor settings of a language go in its own table
+        let table = match self.language.get_untracked() {
+            Some(language) if kind == "editor" => {
+                format!("language.{}", language.as_ref().to_lowercase())
+            }
+            _ => kind.to_string(),
+        };
+        let kind = table.as_str();
+        match self.target.get_untracked() {
+            SettingsTarget::User => {
+                LapceConfig::update_file(kind, field, value);
+            }
+            SettingsTarget::Workspace => {
+                // the workspace settings file isn't watched, so the config
+                // needs to be reloaded here
+                if LapceConfig::update_workspace_file(
+                    &self.common.workspace,
+                    kind,
+                    field,
+                    value,
+                )
+                .is_some()
+                {
+                    self.common
+                        .internal_command
+                        .send(InternalCommand::ReloadConfig);
+                }
+            }
+        }
+    }
 }
 
-pub fn settings_view(common: CommonData) -> impl View {
+pub fn settings_view(plugin: PluginData, common: CommonData) -> impl View {
     let config = common.config;
+    let workspace = common.workspace.clone();
+    let internal_command = common.internal_command;
 
     let cx = ViewContext::get_current();
 
-    let settings_data = SettingsData::new(cx.scope, common.clone());
+    let settings_data = SettingsData::new(cx.scope, plugin, common.clone());
     let view_settings_data = settings_data.clone();
+    let file_settings_data = settings_data.clone();
+    let target = settings_data.target;
+    let language = settings_data.language;
+
+    let language_editor =
+        EditorData::new_local(cx.scope, EditorId::next(), common.clone());
+    {
+        let doc = language_editor.doc;
+        create_effect(cx.scope, move |_| {
+            let name = doc.with(|doc| doc.buffer().to_string());
+            let new_language = LapceLanguage::from_str(name.trim()).ok();
+            if language.get_untracked() != new_language {
+                language.set(new_language);
+            }
+        });
+    }
 
+    let settings_search = common.settings_search;
     let search_editor = EditorData::new_local(cx.scope, EditorId::next(), common);
     let doc = search_editor.doc;
+    {
+        let search_editor = search_editor.clone();
+        create_effect(cx.scope, move |_| {
+            if let Some(search) = settings_search.get() {
+                settings_search.set(None);
+                let len = search.len();
+                search_editor
+                    .doc
+                    .update(|doc| doc.reload(Rope::from(search), true));
+                search_editor
+                    .cursor
+                    .update(|cursor| cursor.set_offset(len, false, false));
+            }
+        });
+    }
 
-    let items = settings_data.items.clone();
+    let items = settings_data.items;
     let filtered_items_signal = settings_data.filtered_items;
-    create_effect(cx.scope, move |last| {
-        let rev = doc.with(|doc| doc.rev());
-
-        if last == Some(rev) {
-            return rev;
-        }
+    create_effect(cx.scope, move |_| {
+        doc.with(|doc| doc.rev());
+        let items = items.get();
+        // Only the editor settings can be set for a language
+        let items = if language.get().is_some() {
+            items
+                .into_iter()
+                .filter(|item| item.kind == "editor")
+                .collect()
+        } else {
+            items
+        };
 
         let pattern =
             doc.with_untracked(|doc| doc.buffer().to_string().to_lowercase());
+        let terms: Vec<&str> = pattern.split_whitespace().collect();
 
-        if pattern.is_empty() {
-            filtered_items_signal.set(items.clone());
-            return rev;
+        if terms.is_empty() {
+            filtered_items_signal.set(items);
+            return;
         }
 
         let mut filtered_items = im::Vector::new();
         for item in &items {
-            if item.filter_text.contains(&pattern) {
+            if terms.iter().all(|term| item.filter_text.contains(term)) {
                 filtered_items.push_back(item.clone());
             }
         }
         filtered_items_signal.set(filtered_items);
-
-        rev
     });
 
+    let target_view = move |name: &'static str, settings_target: SettingsTarget| {
+        label(move || config.get().tr(name))
+            .on_click(move |_| {
+                target.set(settings_target);
+                true
+            })
+            .style(move || {
+                Style::BASE
+                    .text_ellipsis()
+                    .padding_horiz_px(10.0)
+                    .border_radius(6.0)
+                    .cursor(CursorStyle::Pointer)
+                    .apply_if(target.get() == settings_target, |s| {
+                        s.background(
+                            *config
+                                .get()
+                                .get_color(LapceColor::PANEL_CURRENT_BACKGROUND),
+                        )
+                    })
+            })
+            .hover_style(move || {
+                Style::BASE.background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            })
+    };
+    let has_workspace_settings =
+        LapceConfig::workspace_settings_path(&workspace).is_some();
+
     stack(move || {
         (
             stack(move || {
                 (
-                    label(|| "Core Settings".to_string())
+                    stack(move || {
+                        (
+                            target_view("settings-user", SettingsTarget::User),
+                            target_view(
+                                "settings-workspace",
+                                SettingsTarget::Workspace,
+                            )
+                            .style(move || {
+                                Style::BASE
+                                    .apply_if(!has_workspace_settings, |s| s.hide())
+                            }),
+                        )
+                    })
+                    .style(|| Style::BASE.flex_col().margin_bottom_px(10.0)),
+                    text_input(language_editor, || false)
+                        .placeholder(move || {
+                            config.get().tr("settings-all-languages")
+                        })
+                        .keyboard_navigatable()
+                        .style(move || {
+                            Style::BASE
+                                .width_pct(100.0)
+                                .margin_bottom_px(10.0)
+                                .border(1.0)
+                                .border_radius(6.0)
+                                .border_color(
+                                    *config
+                                        .get()
+                                        .get_color(LapceColor::LAPCE_BORDER),
+                                )
+                        }),
+                    label(move || config.get().tr("settings-core"))
+                        .style(|| Style::BASE.text_ellipsis()),
+                    label(move || config.get().tr("settings-editor"))
                         .style(|| Style::BASE.text_ellipsis()),
-                    label(|| "Editor Settings".to_string())
+                    label(move || config.get().tr("settings-ui"))
                         .style(|| Style::BASE.text_ellipsis()),
-                    label(|| "UI Settings".to_string())
+                    label(move || config.get().tr("settings-terminal"))
                         .style(|| Style::BASE.text_ellipsis()),
-                    label(|| "Terminal Settings".to_string())
+                    label(move || config.get().tr("settings-plugins"))
                         .style(|| Style::BASE.text_ellipsis()),
+                    label(move || config.get().tr("settings-open-file"))
+                        .on_click(move |_| {
+                            if let Some(path) = file_settings_data.settings_file() {
+                                internal_command.send(
+                                    InternalCommand::OpenSettingsFile { path },
+                                );
+                            }
+                            true
+                        })
+                        .style(move || {
+                            Style::BASE
+                                .text_ellipsis()
+                                .margin_top_px(10.0)
+                                .cursor(CursorStyle::Pointer)
+                                .color(
+                                    *config.get().get_color(LapceColor::EDITOR_LINK),
+                                )
+                        }),
                 )
             })
             .style(move || {
@@ -261,7 +633,7 @@ pub fn settings_view(common: CommonData) -> impl View {
                 (
                     container(|| {
                         text_input(search_editor, || false)
-                            .placeholder(|| "Search Settings".to_string())
+                            .placeholder(move || config.get().tr("settings-search"))
                             .keyboard_navigatable()
                             .style(move || {
                                 Style::BASE
@@ -320,56 +692,69 @@ pub fn settings_view(common: CommonData) -> impl View {
 
 fn settings_item_view(settings_data: SettingsData, item: SettingsItem) -> impl View {
     let config = settings_data.common.config;
+    let language = settings_data.language;
 
-    let is_ticked = if let SettingsValue::Bool(is_ticked) = &item.value {
+    let value = item.value.get_untracked();
+    let is_ticked = if let SettingsValue::Bool(is_ticked) = &value {
         Some(*is_ticked)
     } else {
         None
     };
 
-    let editor_value = match &item.value {
-        SettingsValue::Float(n) => Some(n.to_string()),
-        SettingsValue::Integer(n) => Some(n.to_string()),
-        SettingsValue::String(s) => Some(s.to_string()),
-        SettingsValue::Bool(_) => None,
-        SettingsValue::Dropdown(_) => None,
-        SettingsValue::Empty => None,
-    };
+    let editor_value = value.editor_string();
 
     let view = {
         let item = item.clone();
+        let settings_data = settings_data.clone();
         move || {
             let cx = ViewContext::get_current();
             if let Some(editor_value) = editor_value {
                 let editor = EditorData::new_local(
                     cx.scope,
                     EditorId::next(),
-                    settings_data.common,
+                    settings_data.common.clone(),
                 );
                 let doc = editor.doc;
+                doc.update(|doc| doc.reload(Rope::from(editor_value), true));
 
+                let item_value = item.value;
                 let kind = item.kind.clone();
                 let field = item.field.clone();
                 create_effect(cx.scope, move |last| {
                     let rev = doc.with(|doc| doc.buffer().rev());
-                    if last == Some(rev) {
+                    if last.is_none() || last == Some(rev) {
                         return rev;
                     }
                     let value = doc.with_untracked(|doc| doc.buffer().to_string());
 
+                    // don't write back what was just loaded from the config
+                    if item_value.with_untracked(|v| {
+                        v.editor_string().as_ref() == Some(&value)
+                    }) {
+                        return rev;
+                    }
+
                     if let Some(value) = toml_edit::ser::to_item(&value)
                         .ok()
                         .and_then(|i| i.into_value().ok())
                     {
-                        LapceConfig::update_file(&kind, &field, value);
+                        settings_data.update_file(&kind, &field, value);
                     }
 
                     rev
                 });
 
-                editor
-                    .doc
-                    .update(|doc| doc.reload(Rope::from(editor_value), true));
+                create_effect(cx.scope, move |_| {
+                    let value = item_value.with(|v| v.editor_string());
+                    if let Some(value) = value {
+                        let current =
+                            doc.with_untracked(|doc| doc.buffer().to_string());
+                        if current != value {
+                            doc.update(|doc| doc.reload(Rope::from(value), true));
+                        }
+                    }
+                });
+
                 container_box(move || {
                     Box::new(
                         text_input(editor, || false).keyboard_navigatable().style(
@@ -387,20 +772,31 @@ fn settings_item_view(settings_data: SettingsData, item: SettingsItem) -> impl V
                         ),
                     )
                 })
-            } else if let SettingsValue::Dropdown(dropdown) = item.value {
+            } else if let SettingsValue::Dropdown(dropdown) = value {
                 let cx = ViewContext::get_current();
                 let expanded = create_rw_signal(cx.scope, false);
-                let current_value = dropdown
-                    .items
-                    .get(dropdown.active_index)
-                    .or_else(|| dropdown.items.last())
-                    .map(|s| s.to_string())
-                    .unwrap_or_default();
-                let current_value = create_rw_signal(cx.scope, current_value);
+                let current_value = create_rw_signal(
+                    cx.scope,
+                    dropdown.active_item().unwrap_or_default(),
+                );
+
+                let item_value = item.value;
+                create_effect(cx.scope, move |_| {
+                    let value = item_value.with(|v| match v {
+                        SettingsValue::Dropdown(dropdown) => dropdown.active_item(),
+                        _ => None,
+                    });
+                    if let Some(value) = value {
+                        if current_value.get_untracked() != value {
+                            current_value.set(value);
+                        }
+                    }
+                });
 
                 let kind = item.kind.clone();
                 let field = item.field.clone();
                 let view_fn = move |item_string: String| {
+                    let settings_data = settings_data.clone();
                     let kind = kind.clone();
                     let field = field.clone();
                     let local_item_string = item_string.clone();
@@ -412,7 +808,7 @@ fn settings_item_view(settings_data: SettingsData, item: SettingsItem) -> impl V
                                     .ok()
                                     .and_then(|i| i.into_value().ok())
                             {
-                                LapceConfig::update_file(&kind, &field, value);
+                                settings_data.update_file(&kind, &field, value);
                             }
                             expanded.set(false);
                             true
@@ -568,6 +964,16 @@ fn settings_item_view(settings_data: SettingsData, item: SettingsItem) -> impl V
                     .line_height(1.6)
                     .font_size(config.get().ui.font_size() as f32 + 1.0)
             }),
+            label(move || {
+                source_text(&config.get(), item.source.get(), language.get())
+            })
+            .style(move || {
+                Style::BASE
+                    .text_ellipsis()
+                    .min_width_px(0.0)
+                    .max_width_pct(100.0)
+                    .color(*config.get().get_color(LapceColor::EDITOR_DIM))
+            }),
             stack(move || {
                 (
                     label(move || item.description.clone()).style(move || {
@@ -585,15 +991,30 @@ fn settings_item_view(settings_data: SettingsData, item: SettingsItem) -> impl V
                         let cx = ViewContext::get_current();
                         let checked = create_rw_signal(cx.scope, is_ticked);
 
+                        let item_value = item.value;
                         let kind = item.kind.clone();
                         let field = item.field.clone();
                         create_effect(cx.scope, move |_| {
                             let checked = checked.get();
+                            // don't write back what was just loaded from the config
+                            if item_value.with_untracked(|v| {
+                                v == &SettingsValue::Bool(checked)
+                            }) {
+                                return;
+                            }
                             if let Some(value) = toml_edit::ser::to_item(&checked)
                                 .ok()
                                 .and_then(|i| i.into_value().ok())
                             {
-                                LapceConfig::update_file(&kind, &field, value);
+                                settings_data.update_file(&kind, &field, value);
+                            }
+                        });
+
+                        create_effect(cx.scope, move |_| {
+                            if let SettingsValue::Bool(value) = item_value.get() {
+                                if checked.get_untracked() != value {
+                                    checked.set(value);
+                                }
                             }
                         });
 
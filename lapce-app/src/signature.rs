@@ -0,0 +1,171 @@
+use floem::{
+    peniko::kurbo::Rect,
+    reactive::{create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalSet},
+};
+use lsp_types::{ParameterLabel, SignatureHelp};
+
+use crate::id::EditorId;
+
+/// The signature of the call which the cursor is in, which is asked for when
+/// its arguments are typed.
+#[derive(Clone, Copy)]
+pub struct SignatureHelpData {
+    pub active: RwSignal<bool>,
+    pub editor_id: RwSignal<Option<EditorId>>,
+    /// The offset which the signature was asked for at, which it's shown at
+    pub offset: RwSignal<usize>,
+    /// Bumped for each request, so that the answers to the older ones are dropped
+    pub request_id: RwSignal<usize>,
+    pub help: RwSignal<Option<SignatureHelp>>,
+    pub layout_rect: RwSignal<Rect>,
+}
+
+impl SignatureHelpData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            active: create_rw_signal(cx, false),
+            editor_id: create_rw_signal(cx, None),
+            offset: create_rw_signal(cx, 0),
+            request_id: create_rw_signal(cx, 0),
+            help: create_rw_signal(cx, None),
+            layout_rect: create_rw_signal(cx, Rect::ZERO),
+        }
+    }
+
+    /// Start a request for the offset of the editor, and return its id. The
+    /// signature stays where it's shown while it's updated.
+    pub fn start_request(&self, editor_id: EditorId, offset: usize) -> usize {
+        let request_id = self.request_id.get_untracked() + 1;
+        self.request_id.set(request_id);
+        if !self.active.get_untracked()
+            || self.editor_id.get_untracked() != Some(editor_id)
+        {
+            self.active.set(false);
+            self.editor_id.set(Some(editor_id));
+            self.offset.set(offset);
+        }
+        request_id
+    }
+
+    /// Show the answer of the request, unless a newer one was made since. An
+    /// answer without signatures hides it.
+    pub fn receive(&self, request_id: usize, help: SignatureHelp) {
+        if request_id != self.request_id.get_untracked() {
+            return;
+        }
+        if help.signatures.is_empty() {
+            self.hide();
+            return;
+        }
+        self.help.set(Some(help));
+        self.active.set(true);
+    }
+
+    pub fn hide(&self) {
+        self.request_id.set(self.request_id.get_untracked() + 1);
+        if self.active.get_untracked() {
+            self.active.set(false);
+        }
+    }
+}
+
+/// The label of the active signature, with the chars of its active parameter,
+/// and which of the signatures it is when there are more.
+pub fn signature_label(
+    help: &SignatureHelp,
+) -> Option<(String, Vec<usize>, Option<(usize, usize)>)> {
+    let index = help.active_signature.unwrap_or(0) as usize;
+    let index = index.min(help.signatures.len().checked_sub(1)?);
+    let signature = &help.signatures[index];
+    let label = signature.label.clone();
+
+    let indices = signature
+        .active_parameter
+        .or(help.active_parameter)
+        .and_then(|parameter| signature.parameters.as_ref()?.get(parameter as usize))
+        .and_then(|parameter| match &parameter.label {
+            ParameterLabel::Simple(name) => {
+                // The name is looked for in the parameters, not in the one of
+                // the function
+                let from = label.find('(').map_or(0, |i| i + 1);
+                let start = from + label[from..].find(name.as_str())?;
+                let start = label[..start].chars().count();
+                Some(start..start + name.chars().count())
+            }
+            ParameterLabel::LabelOffsets([start, end]) => {
+                let utf16_to_char = |offset: u32| {
+                    let mut utf16 = 0;
+                    label
+                        .chars()
+                        .take_while(|c| {
+                            utf16 += c.len_utf16() as u32;
+                            utf16 <= offset
+                        })
+                        .count()
+                };
+                Some(utf16_to_char(*start)..utf16_to_char(*end))
+            }
+        })
+        .map(|range| range.collect())
+        .unwrap_or_default();
+
+    let count =
+        (help.signatures.len() > 1).then_some((index + 1, help.signatures.len()));
+    Some((label, indices, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{ParameterInformation, SignatureInformation};
+
+    use super::*;
+
+    fn help(parameters: Vec<ParameterLabel>, active: u32) -> SignatureHelp {
+        SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label: "fn push(&mut self, värde: T)".to_string(),
+                documentation: None,
+                parameters: Some(
+                    parameters
+                        .into_iter()
+                        .map(|label| ParameterInformation {
+                            label,
+                            documentation: None,
+                        })
+                        .collect(),
+                ),
+                active_parameter: None,
+            }],
+            active_signature: None,
+            active_parameter: Some(active),
+        }
+    }
+
+    #[test]
+    fn test_signature_label() {
+        let (label, indices, count) = signature_label(&help(
+            vec![
+                ParameterLabel::Simple("&mut self".to_string()),
+                ParameterLabel::Simple("värde: T".to_string()),
+            ],
+            1,
+        ))
+        .unwrap();
+        assert_eq!(label, "fn push(&mut self, värde: T)");
+        assert_eq!(indices, (19..27).collect::<Vec<_>>());
+        assert_eq!(count, None);
+
+        let (_, indices, _) = signature_label(&help(
+            vec![
+                ParameterLabel::LabelOffsets([8, 17]),
+                ParameterLabel::LabelOffsets([19, 27]),
+            ],
+            1,
+        ))
+        .unwrap();
+        assert_eq!(indices, (19..27).collect::<Vec<_>>());
+
+        let (_, indices, _) = signature_label(&help(Vec::new(), 0)).unwrap();
+        assert!(indices.is_empty());
+    }
+}
@@ -1,9 +1,10 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use floem::reactive::{create_rw_signal, RwSignal, Scope, SignalWithUntracked};
 use indexmap::IndexMap;
 use lapce_core::mode::Mode;
-use lapce_rpc::source_control::FileDiff;
+use lapce_rpc::source_control::{FileDiff, GitRemoteFile};
+use lsp_types::Url;
 
 use crate::{
     command::{CommandExecuted, CommandKind},
@@ -13,6 +14,15 @@
     window_tab::CommonData,
 };
 
+const GITHUB_PERMALINK: &str =
+    "https://{host}/{repo}/blob/{commit}/{path}#L{start}-L{end}";
+const GITLAB_PERMALINK: &str =
+    "https://{host}/{repo}/-/blob/{commit}/{path}#L{start}-{end}";
+const BITBUCKET_PERMALINK: &str =
+    "https://{host}/{repo}/src/{commit}/{path}#lines-{start}:{end}";
+const GITEA_PERMALINK: &str =
+    "https://{host}/{repo}/src/commit/{commit}/{path}#L{start}-L{end}";
+
 #[derive(Clone)]
 pub struct SourceControlData {
     // VCS modified files & whether they should be included in the next commit
@@ -105,3 +115,105 @@ pub fn commit(&self) {
         self.common.proxy.git_commit(message.to_string(), diffs);
     }
 }
+
+/// The url of the file on the host of its repository, with the lines, which
+/// start at 1, when they're given.
+///
+/// The templates are by host, and are either `github`, `gitlab`, `bitbucket`
+/// or `gitea` for a self-hosted one of those, or a url with `{host}`, `{repo}`,
+/// `{commit}`, `{path}`, `{start}` and `{end}`, whose part from `#` is left out
+/// when there are no lines. A host without one is taken by its name.
+pub fn git_permalink(
+    file: &GitRemoteFile,
+    lines: Option<(usize, usize)>,
+    templates: &HashMap<String, String>,
+) -> String {
+    let kind = templates
+        .get(&file.host)
+        .map(|template| template.as_str())
+        .unwrap_or_else(|| {
+            if file.host.contains("gitlab") {
+                "gitlab"
+            } else if file.host.contains("bitbucket") {
+                "bitbucket"
+            } else if file.host == "codeberg.org" || file.host.contains("gitea") {
+                "gitea"
+            } else {
+                "github"
+            }
+        });
+    let template = match kind {
+        "github" => GITHUB_PERMALINK,
+        "gitlab" => GITLAB_PERMALINK,
+        "bitbucket" => BITBUCKET_PERMALINK,
+        "gitea" => GITEA_PERMALINK,
+        template => template,
+    };
+    let template = match lines {
+        Some(_) => template,
+        None => template.split('#').next().unwrap_or(template),
+    };
+    let (start, end) = lines.unwrap_or((1, 1));
+
+    template
+        .replace("{host}", &file.host)
+        .replace("{repo}", &file.repo)
+        .replace("{commit}", &file.commit)
+        .replace("{path}", &encode_path(&file.path))
+        .replace("{start}", &start.to_string())
+        .replace("{end}", &end.to_string())
+}
+
+/// The path with its parts percent-encoded for a url.
+fn encode_path(path: &str) -> String {
+    let Ok(mut url) = Url::parse("https://host") else {
+        return path.to_string();
+    };
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.clear().extend(path.split('/'));
+    }
+    url.path().trim_start_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(host: &str) -> GitRemoteFile {
+        GitRemoteFile {
+            host: host.to_string(),
+            repo: "lapce/lapce".to_string(),
+            commit: "abc".to_string(),
+            path: "lapce-app/src/a b.rs".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_git_permalink() {
+        let templates = HashMap::new();
+        assert_eq!(
+            git_permalink(&file("github.com"), Some((3, 5)), &templates),
+            "https://github.com/lapce/lapce/blob/abc/lapce-app/src/a%20b.rs#L3-L5"
+        );
+        assert_eq!(
+            git_permalink(&file("gitlab.com"), None, &templates),
+            "https://gitlab.com/lapce/lapce/-/blob/abc/lapce-app/src/a%20b.rs"
+        );
+
+        let templates = HashMap::from([
+            ("git.example.com".to_string(), "gitlab".to_string()),
+            (
+                "code.example.com".to_string(),
+                "https://{host}/r/{repo}?at={commit}&f={path}#{start}".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            git_permalink(&file("git.example.com"), Some((1, 1)), &templates),
+            "https://git.example.com/lapce/lapce/-/blob/abc/lapce-app/src/a%20b.rs#L1-1"
+        );
+        assert_eq!(
+            git_permalink(&file("code.example.com"), Some((7, 9)), &templates),
+            "https://code.example.com/r/lapce/lapce?at=abc&f=lapce-app/src/a%20b.rs#7"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! The startup of the app, which `--timing` times the phases of, and the view
+//! which a window tab starts what's put off until it's painted with.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use floem::{
+    id::Id,
+    view::{ChangeFlags, View},
+    ViewContext,
+};
+use once_cell::sync::Lazy;
+
+/// The phases of the startup so far, which is `None` unless `--timing` was given
+static TIMING: Lazy<Mutex<Option<StartupTiming>>> = Lazy::new(|| Mutex::new(None));
+
+struct StartupTiming {
+    start: Instant,
+    last: Instant,
+    phases: Vec<(String, Duration)>,
+    reported: bool,
+}
+
+/// Time the phases of the startup, which began at `start`.
+pub fn enable_timing(start: Instant) {
+    *TIMING.lock().unwrap() = Some(StartupTiming {
+        start,
+        last: start,
+        phases: Vec::new(),
+        reported: false,
+    });
+}
+
+/// The end of a phase of the startup, which took the time since the end of the
+/// phase before it.
+pub fn phase(name: impl Into<String>) {
+    if let Some(timing) = TIMING.lock().unwrap().as_mut() {
+        if timing.reported {
+            return;
+        }
+        let now = Instant::now();
+        timing.phases.push((name.into(), now - timing.last));
+        timing.last = now;
+    }
+}
+
+/// Print how long the phases of the startup took, which is done once, when the
+/// first window tab is painted.
+pub fn report() {
+    let mut timing = TIMING.lock().unwrap();
+    let Some(timing) = timing.as_mut().filter(|timing| !timing.reported) else {
+        return;
+    };
+    timing.reported = true;
+
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    eprintln!("Startup timing:");
+    for (name, duration) in timing.phases.iter() {
+        eprintln!("  {name:<32} {:>9.1} ms", ms(*duration));
+    }
+    eprintln!(
+        "  {:<32} {:>9.1} ms",
+        "total",
+        ms(timing.last - timing.start)
+    );
+}
+
+/// A view which takes no space, and calls `f` when it's painted for the first
+/// time.
+pub fn on_first_paint(f: impl FnOnce() + 'static) -> FirstPaint {
+    let cx = ViewContext::get_current();
+    let id = cx.new_id();
+    FirstPaint {
+        id,
+        f: Some(Box::new(f)),
+    }
+}
+
+pub struct FirstPaint {
+    id: Id,
+    f: Option<Box<dyn FnOnce()>>,
+}
+
+impl View for FirstPaint {
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn child(&mut self, _id: floem::id::Id) -> Option<&mut dyn View> {
+        None
+    }
+
+    fn children(&mut self) -> Vec<&mut dyn View> {
+        Vec::new()
+    }
+
+    fn update(
+        &mut self,
+        _cx: &mut floem::context::UpdateCx,
+        _state: Box<dyn std::any::Any>,
+    ) -> ChangeFlags {
+        ChangeFlags::empty()
+    }
+
+    fn layout(
+        &mut self,
+        cx: &mut floem::context::LayoutCx,
+    ) -> floem::taffy::prelude::Node {
+        cx.layout_node(self.id, false, |_cx| Vec::new())
+    }
+
+    fn event(
+        &mut self,
+        _cx: &mut floem::context::EventCx,
+        _id_path: Option<&[floem::id::Id]>,
+        _event: floem::event::Event,
+    ) -> bool {
+        false
+    }
+
+    fn paint(&mut self, _cx: &mut floem::context::PaintCx) {
+        if let Some(f) = self.f.take() {
+            f();
+        }
+    }
+}
@@ -0,0 +1,797 @@
+use std::{str::FromStr, sync::Arc};
+
+use floem::{
+    reactive::{
+        create_memo, create_rw_signal, ReadSignal, RwSignal, Scope, SignalGet,
+        SignalGetUntracked, SignalUpdate, SignalWith,
+    },
+    style::{AlignItems, CursorStyle, Display, Style},
+    view::View,
+    views::{container, container_box, label, list, stack, svg, Decorators},
+    ViewContext,
+};
+use fluent_bundle::FluentValue;
+use lapce_core::{buffer::rope_text::RopeText, mode::Mode};
+use lapce_rpc::plugin::{StatusItemAlignment, StatusItemParams, VoltID};
+use lsp_types::{
+    DiagnosticSeverity, ProgressParams, ProgressParamsValue, ProgressToken,
+    WorkDoneProgress,
+};
+use strum_macros::{EnumString, IntoStaticStr};
+
+use crate::{
+    app::clickable_icon,
+    command::LapceWorkbenchCommand,
+    config::{color::LapceColor, icon::LapceIcons, ui::UIConfig, LapceConfig},
+    palette::kind::PaletteKind,
+    panel::{kind::PanelKind, position::PanelContainerPosition},
+    window_tab::WindowTabData,
+};
+
+/// The items of the status bar which are built into Lapce. They're referred to by
+/// their kebab-case name in the `ui.status-bar-*` settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum BuiltinStatusItem {
+    Mode,
+    Diagnostics,
+    GitBranch,
+    LspStatus,
+    CursorPosition,
+    Language,
+    Encoding,
+    LineEnding,
+    Indent,
+    Notifications,
+}
+
+/// An item which is registered by a plugin or another part of Lapce.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatusItem {
+    pub id: String,
+    pub text: String,
+    pub alignment: StatusItemAlignment,
+    /// Items with a higher priority are placed further to the left
+    pub priority: i32,
+    /// The command which is run when the item is clicked
+    pub command: Option<LapceWorkbenchCommand>,
+}
+
+impl StatusItem {
+    pub fn from_plugin(volt_id: &VoltID, item: StatusItemParams) -> Self {
+        Self {
+            id: plugin_item_id(volt_id, &item.id),
+            text: item.text,
+            alignment: item.alignment,
+            priority: item.priority,
+            command: item
+                .command
+                .and_then(|command| LapceWorkbenchCommand::from_str(&command).ok()),
+        }
+    }
+}
+
+/// The id of a plugin item, which is prefixed with the plugin so that plugins
+/// can't clash with each other.
+pub fn plugin_item_id(volt_id: &VoltID, id: &str) -> String {
+    format!("{volt_id}.{id}")
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatusEntry {
+    Builtin(BuiltinStatusItem),
+    Item(String),
+}
+
+/// A piece of work a language server or a debugger reports progress for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkProgress {
+    pub token: ProgressToken,
+    pub title: String,
+    pub message: Option<String>,
+    pub percentage: Option<u32>,
+}
+
+impl WorkProgress {
+    fn text(&self) -> String {
+        let mut text = self.title.clone();
+        if let Some(message) = self.message.as_ref() {
+            text = format!("{text}: {message}");
+        }
+        if let Some(percentage) = self.percentage {
+            text = format!("{text} ({percentage}%)");
+        }
+        text
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct StatusData {
+    /// The items registered through [`StatusData::set_item`]
+    pub items: RwSignal<im::Vector<StatusItem>>,
+    /// The work in progress of the language servers and the debuggers, oldest
+    /// first
+    pub progresses: RwSignal<im::Vector<WorkProgress>>,
+}
+
+impl StatusData {
+    pub fn new(cx: Scope) -> Self {
+        Self {
+            items: create_rw_signal(cx, im::Vector::new()),
+            progresses: create_rw_signal(cx, im::Vector::new()),
+        }
+    }
+
+    /// Show an item in the status bar, which replaces the item with the same id.
+    pub fn set_item(&self, item: StatusItem) {
+        self.items.update(|items| {
+            if let Some(existing) = items.iter_mut().find(|i| i.id == item.id) {
+                *existing = item;
+            } else {
+                items.push_back(item);
+            }
+        });
+    }
+
+    pub fn remove_item(&self, id: &str) {
+        self.items
+            .update(|items| items.retain(|item| item.id != id));
+    }
+
+    /// Remove all the items of a plugin, e.g. when it's disabled or removed.
+    pub fn remove_plugin_items(&self, volt_id: &VoltID) {
+        let prefix = plugin_item_id(volt_id, "");
+        self.items
+            .update(|items| items.retain(|item| !item.id.starts_with(&prefix)));
+    }
+
+    pub fn update_progress(&self, progress: ProgressParams) {
+        let token = progress.token;
+        let ProgressParamsValue::WorkDone(value) = progress.value;
+        self.progresses.update(|progresses| match value {
+            WorkDoneProgress::Begin(begin) => {
+                progresses.retain(|p| p.token != token);
+                progresses.push_back(WorkProgress {
+                    token,
+                    title: begin.title,
+                    message: begin.message,
+                    percentage: begin.percentage,
+                });
+            }
+            WorkDoneProgress::Report(report) => {
+                if let Some(p) = progresses.iter_mut().find(|p| p.token == token) {
+                    if report.message.is_some() {
+                        p.message = report.message;
+                    }
+                    if report.percentage.is_some() {
+                        p.percentage = report.percentage;
+                    }
+                }
+            }
+            WorkDoneProgress::End(_) => {
+                progresses.retain(|p| p.token != token);
+            }
+        });
+    }
+
+    /// The entries of one side of the status bar, in the order they're shown.
+    /// Items which are placed by the settings come first, followed by the
+    /// registered items which aren't, sorted by their priority.
+    pub fn entries(
+        ui: &UIConfig,
+        items: &im::Vector<StatusItem>,
+        alignment: StatusItemAlignment,
+    ) -> Vec<StatusEntry> {
+        let placed = match alignment {
+            StatusItemAlignment::Left => &ui.status_bar_left,
+            StatusItemAlignment::Right => &ui.status_bar_right,
+        };
+        let is_placed = |id: &str| {
+            ui.status_bar_left
+                .iter()
+                .chain(ui.status_bar_right.iter())
+                .any(|i| i == id)
+        };
+        let is_hidden = |id: &str| ui.status_bar_hidden.iter().any(|i| i == id);
+
+        let mut entries: Vec<StatusEntry> = Vec::new();
+        for id in placed {
+            if is_hidden(id) {
+                continue;
+            }
+            let entry = if let Ok(builtin) = BuiltinStatusItem::from_str(id) {
+                StatusEntry::Builtin(builtin)
+            } else if items.iter().any(|item| &item.id == id) {
+                StatusEntry::Item(id.clone())
+            } else {
+                continue;
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+
+        let mut rest: Vec<&StatusItem> = items
+            .iter()
+            .filter(|item| {
+                item.alignment == alignment
+                    && !is_placed(&item.id)
+                    && !is_hidden(&item.id)
+            })
+            .collect();
+        rest.sort_by(|a, b| b.priority.cmp(&a.priority));
+        entries.extend(
+            rest.into_iter()
+                .map(|item| StatusEntry::Item(item.id.clone())),
+        );
+
+        entries
+    }
+}
+
+pub fn status(window_tab_data: Arc<WindowTabData>) -> impl View {
+    let config = window_tab_data.common.config;
+    let status_data = window_tab_data.status;
+    let panel = window_tab_data.panel.clone();
+    let cx = ViewContext::get_current();
+
+    let entries = move |alignment| {
+        create_memo(cx.scope, move |_| {
+            let config = config.get();
+            status_data
+                .items
+                .with(|items| StatusData::entries(&config.ui, items, alignment))
+        })
+    };
+    let left_entries = entries(StatusItemAlignment::Left);
+    let right_entries = entries(StatusItemAlignment::Right);
+
+    stack(|| {
+        (
+            {
+                let window_tab_data = window_tab_data.clone();
+                list(
+                    move || left_entries.get(),
+                    |entry| entry.clone(),
+                    move |entry| status_entry(window_tab_data.clone(), entry),
+                )
+                .style(|| {
+                    Style::BASE
+                        .height_pct(100.0)
+                        .flex_basis_px(0.0)
+                        .flex_grow(1.0)
+                        .items_center()
+                })
+            },
+            stack(move || {
+                (
+                    {
+                        let panel = panel.clone();
+                        let icon = {
+                            let panel = panel.clone();
+                            move || {
+                                if panel.is_container_shown(
+                                    &PanelContainerPosition::Left,
+                                    true,
+                                ) {
+                                    LapceIcons::SIDEBAR_LEFT
+                                } else {
+                                    LapceIcons::SIDEBAR_LEFT_OFF
+                                }
+                            }
+                        };
+                        clickable_icon(
+                            icon,
+                            move || {
+                                panel.toggle_container_visual(
+                                    &PanelContainerPosition::Left,
+                                )
+                            },
+                            || false,
+                            || false,
+                            config,
+                        )
+                    },
+                    {
+                        let panel = panel.clone();
+                        let icon = {
+                            let panel = panel.clone();
+                            move || {
+                                if panel.is_container_shown(
+                                    &PanelContainerPosition::Bottom,
+                                    true,
+                                ) {
+                                    LapceIcons::LAYOUT_PANEL
+                                } else {
+                                    LapceIcons::LAYOUT_PANEL_OFF
+                                }
+                            }
+                        };
+                        clickable_icon(
+                            icon,
+                            move || {
+                                panel.toggle_container_visual(
+                                    &PanelContainerPosition::Bottom,
+                                )
+                            },
+                            || false,
+                            || false,
+                            config,
+                        )
+                    },
+                    {
+                        let panel = panel.clone();
+                        let icon = {
+                            let panel = panel.clone();
+                            move || {
+                                if panel.is_container_shown(
+                                    &PanelContainerPosition::Right,
+                                    true,
+                                ) {
+                                    LapceIcons::SIDEBAR_RIGHT
+                                } else {
+                                    LapceIcons::SIDEBAR_RIGHT_OFF
+                                }
+                            }
+                        };
+                        clickable_icon(
+                            icon,
+                            move || {
+                                panel.toggle_container_visual(
+                                    &PanelContainerPosition::Right,
+                                )
+                            },
+                            || false,
+                            || false,
+                            config,
+                        )
+                    },
+                )
+            })
+            .style(|| Style::BASE.height_pct(100.0).items_center()),
+            list(
+                move || right_entries.get(),
+                |entry| entry.clone(),
+                move |entry| status_entry(window_tab_data.clone(), entry),
+            )
+            .style(|| {
+                Style::BASE
+                    .height_pct(100.0)
+                    .flex_basis_px(0.0)
+                    .flex_grow(1.0)
+                    .items_center()
+                    .justify_end()
+            }),
+        )
+    })
+    .style(move || {
+        let config = config.get();
+        Style::BASE
+            .border_top(1.0)
+            .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
+            .background(*config.get_color(LapceColor::STATUS_BACKGROUND))
+            .height_px(config.ui.status_height() as f32)
+            .align_items(Some(AlignItems::Center))
+    })
+}
+
+fn status_entry(
+    window_tab_data: Arc<WindowTabData>,
+    entry: StatusEntry,
+) -> impl View {
+    container_box(move || match entry {
+        StatusEntry::Builtin(builtin) => builtin_item(window_tab_data, builtin),
+        StatusEntry::Item(id) => {
+            let config = window_tab_data.common.config;
+            let workbench_command = window_tab_data.common.workbench_command;
+            let items = window_tab_data.status.items;
+            let item = create_memo(ViewContext::get_current().scope, move |_| {
+                items.with(|items| items.iter().find(|item| item.id == id).cloned())
+            });
+            Box::new(status_label(
+                move || item.get().map(|item| item.text).unwrap_or_default(),
+                move || {
+                    if let Some(command) =
+                        item.get_untracked().and_then(|item| item.command)
+                    {
+                        workbench_command.send(command);
+                    }
+                },
+                move || {
+                    item.with(|item| {
+                        item.as_ref().map(|item| item.command.is_some())
+                            == Some(true)
+                    })
+                },
+                config,
+            ))
+        }
+    })
+    .style(|| Style::BASE.height_pct(100.0))
+}
+
+fn builtin_item(
+    window_tab_data: Arc<WindowTabData>,
+    builtin: BuiltinStatusItem,
+) -> Box<dyn View> {
+    let config = window_tab_data.common.config;
+    let editor = window_tab_data.main_split.active_editor;
+    let panel = window_tab_data.panel.clone();
+    let palette = window_tab_data.palette.clone();
+    let scope = window_tab_data.scope;
+    match builtin {
+        BuiltinStatusItem::Mode => {
+            let mode = create_memo(scope, move |_| window_tab_data.mode());
+            Box::new(
+                label(move || {
+                    config.get().tr(match mode.get() {
+                        Mode::Normal => "status-mode-normal",
+                        Mode::Insert => "status-mode-insert",
+                        Mode::Visual => "status-mode-visual",
+                        Mode::Terminal => "status-mode-terminal",
+                    })
+                })
+                .style(move || {
+                    let config = config.get();
+                    let display = if config.core.modal {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    };
+
+                    let (bg, fg) = match mode.get() {
+                        Mode::Normal => (
+                            LapceColor::STATUS_MODAL_NORMAL_BACKGROUND,
+                            LapceColor::STATUS_MODAL_NORMAL_FOREGROUND,
+                        ),
+                        Mode::Insert => (
+                            LapceColor::STATUS_MODAL_INSERT_BACKGROUND,
+                            LapceColor::STATUS_MODAL_INSERT_FOREGROUND,
+                        ),
+                        Mode::Visual => (
+                            LapceColor::STATUS_MODAL_VISUAL_BACKGROUND,
+                            LapceColor::STATUS_MODAL_VISUAL_FOREGROUND,
+                        ),
+                        Mode::Terminal => (
+                            LapceColor::STATUS_MODAL_TERMINAL_BACKGROUND,
+                            LapceColor::STATUS_MODAL_TERMINAL_FOREGROUND,
+                        ),
+                    };
+
+                    let bg = *config.get_color(bg);
+                    let fg = *config.get_color(fg);
+
+                    Style::BASE
+                        .display(display)
+                        .padding_horiz_px(10.0)
+                        .color(fg)
+                        .background(bg)
+                        .height_pct(100.0)
+                        .align_items(Some(AlignItems::Center))
+                }),
+            )
+        }
+        BuiltinStatusItem::Diagnostics => {
+            let diagnostics = window_tab_data.main_split.diagnostics;
+            let diagnostic_count = create_memo(scope, move |_| {
+                let mut errors = 0;
+                let mut warnings = 0;
+                for (_, diagnostics) in diagnostics.get().iter() {
+                    for diagnostic in diagnostics.diagnostics.get().iter() {
+                        if let Some(severity) = diagnostic.diagnostic.severity {
+                            match severity {
+                                DiagnosticSeverity::ERROR => errors += 1,
+                                DiagnosticSeverity::WARNING => warnings += 1,
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+                (errors, warnings)
+            });
+            Box::new(
+                stack(|| {
+                    (
+                        svg(move || config.get().ui_svg(LapceIcons::ERROR)).style(
+                            move || {
+                                let config = config.get();
+                                let size = config.ui.icon_size() as f32;
+                                Style::BASE.size_px(size, size).color(
+                                    *config.get_color(LapceColor::LAPCE_ICON_ACTIVE),
+                                )
+                            },
+                        ),
+                        label(move || diagnostic_count.get().0.to_string())
+                            .style(|| Style::BASE.margin_left_px(5.0)),
+                        svg(move || config.get().ui_svg(LapceIcons::WARNING)).style(
+                            move || {
+                                let config = config.get();
+                                let size = config.ui.icon_size() as f32;
+                                Style::BASE
+                                    .size_px(size, size)
+                                    .margin_left_px(5.0)
+                                    .color(
+                                        *config.get_color(
+                                            LapceColor::LAPCE_ICON_ACTIVE,
+                                        ),
+                                    )
+                            },
+                        ),
+                        label(move || diagnostic_count.get().1.to_string())
+                            .style(|| Style::BASE.margin_left_px(5.0)),
+                    )
+                })
+                .on_click(move |_| {
+                    panel.show_panel(&PanelKind::Problem);
+                    true
+                })
+                .style(|| {
+                    Style::BASE
+                        .height_pct(100.0)
+                        .padding_horiz_px(10.0)
+                        .items_center()
+                })
+                .hover_style(move || {
+                    Style::BASE.cursor(CursorStyle::Pointer).background(
+                        *config
+                            .get()
+                            .get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                    )
+                }),
+            )
+        }
+        BuiltinStatusItem::GitBranch => {
+            let branch = window_tab_data.source_control.branch;
+            Box::new(status_label(
+                move || branch.get(),
+                move || panel.show_panel(&PanelKind::SourceControl),
+                || true,
+                config,
+            ))
+        }
+        BuiltinStatusItem::LspStatus => {
+            let progresses = window_tab_data.status.progresses;
+            Box::new(status_label(
+                move || {
+                    progresses.with(|progresses| {
+                        progresses.last().map(|p| p.text()).unwrap_or_default()
+                    })
+                },
+                || {},
+                || false,
+                config,
+            ))
+        }
+        BuiltinStatusItem::CursorPosition => Box::new(status_label(
+            move || {
+                let config = config.get();
+                if let Some(editor) = editor.get() {
+                    let mut status = String::new();
+                    let cursor = editor.get().cursor.get();
+                    if let Some((line, column, character)) =
+                        cursor.get_line_col_char(editor.get().doc.get().buffer())
+                    {
+                        status = config.tr_args(
+                            "status-cursor-position",
+                            [
+                                ("line", FluentValue::from(line)),
+                                ("column", FluentValue::from(column)),
+                                ("character", FluentValue::from(character)),
+                            ],
+                        );
+                    }
+                    if let Some(selection) = cursor.get_selection() {
+                        let selection_range = selection.0.abs_diff(selection.1);
+
+                        if selection.0 != selection.1 {
+                            let selected = config.tr_args(
+                                "status-selected",
+                                [("count", FluentValue::from(selection_range))],
+                            );
+                            status = format!("{status} ({selected})");
+                        }
+                    }
+                    let selection_count = cursor.get_selection_count();
+                    if selection_count > 1 {
+                        let selections = config.tr_args(
+                            "status-selections",
+                            [("count", FluentValue::from(selection_count))],
+                        );
+                        status = format!("{status} {selections}");
+                    }
+                    return status;
+                }
+                config.tr("status-no-document")
+            },
+            move || palette.run(scope, PaletteKind::Line),
+            || true,
+            config,
+        )),
+        BuiltinStatusItem::Language => Box::new(status_label(
+            move || {
+                if let Some(editor) = editor.get() {
+                    if let Some(syn) = editor.get().doc.get().syntax() {
+                        if let Some(lang) =
+                            strum::EnumMessage::get_message(&syn.language)
+                        {
+                            return lang.to_string();
+                        }
+                    }
+                    return config.get().tr("status-plain-text");
+                }
+                String::new()
+            },
+            move || palette.run(scope, PaletteKind::Language),
+            || true,
+            config,
+        )),
+        BuiltinStatusItem::Encoding => Box::new(status_label(
+            // documents are always read and written as UTF-8
+            move || {
+                if editor.get().is_some() {
+                    "UTF-8".to_string()
+                } else {
+                    String::new()
+                }
+            },
+            || {},
+            || false,
+            config,
+        )),
+        BuiltinStatusItem::LineEnding => Box::new(status_label(
+            move || {
+                let Some(editor) = editor.get() else {
+                    return String::new();
+                };
+                let doc = editor.get().doc.get();
+                let buffer = doc.buffer();
+                if buffer.line_content(0).ends_with("\r\n") {
+                    "CRLF".to_string()
+                } else {
+                    "LF".to_string()
+                }
+            },
+            || {},
+            || false,
+            config,
+        )),
+        BuiltinStatusItem::Indent => Box::new(status_label(
+            move || {
+                let Some(editor) = editor.get() else {
+                    return String::new();
+                };
+                let doc = editor.get().doc.get();
+                let config = config.get();
+                match doc.buffer().indent_unit() {
+                    "\t" => config.tr("status-indent-tabs"),
+                    unit => config.tr_args(
+                        "status-indent-spaces",
+                        [("count", FluentValue::from(unit.len()))],
+                    ),
+                }
+            },
+            || {},
+            || false,
+            config,
+        )),
+        BuiltinStatusItem::Notifications => {
+            let do_not_disturb = window_tab_data.notification.do_not_disturb;
+            let workbench_command = window_tab_data.common.workbench_command;
+            Box::new(
+                container(|| {
+                    clickable_icon(
+                        move || {
+                            if do_not_disturb.get() {
+                                LapceIcons::NOTIFICATION_OFF
+                            } else {
+                                LapceIcons::NOTIFICATION
+                            }
+                        },
+                        move || {
+                            workbench_command.send(
+                                LapceWorkbenchCommand::ToggleNotificationVisual,
+                            )
+                        },
+                        || false,
+                        || false,
+                        config,
+                    )
+                })
+                .style(|| {
+                    Style::BASE
+                        .height_pct(100.0)
+                        .padding_horiz_px(6.0)
+                        .items_center()
+                }),
+            )
+        }
+    }
+}
+
+/// A text item of the status bar, which is hidden while its text is empty.
+fn status_label(
+    text: impl Fn() -> String + 'static,
+    on_click: impl Fn() + 'static,
+    clickable: impl Fn() -> bool + 'static + Copy,
+    config: ReadSignal<Arc<LapceConfig>>,
+) -> impl View {
+    let text = create_memo(ViewContext::get_current().scope, move |_| text());
+    label(move || text.get())
+        .on_click(move |_| {
+            if clickable() {
+                on_click();
+            }
+            true
+        })
+        .style(move || {
+            Style::BASE
+                .height_pct(100.0)
+                .padding_horiz_px(10.0)
+                .items_center()
+                .apply_if(text.with(|text| text.is_empty()), |s| s.hide())
+        })
+        .hover_style(move || {
+            Style::BASE.apply_if(clickable(), |s| {
+                s.cursor(CursorStyle::Pointer).background(
+                    *config.get().get_color(LapceColor::PANEL_HOVERED_BACKGROUND),
+                )
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::plugin::StatusItemAlignment;
+
+    use super::{BuiltinStatusItem, StatusData, StatusEntry, StatusItem};
+    use crate::config::ui::UIConfig;
+
+    fn item(id: &str, alignment: StatusItemAlignment, priority: i32) -> StatusItem {
+        StatusItem {
+            id: id.to_string(),
+            text: id.to_string(),
+            alignment,
+            priority,
+            command: None,
+        }
+    }
+
+    #[test]
+    fn test_entries() {
+        let ui = UIConfig {
+            status_bar_left: vec![
+                "mode".to_string(),
+                "author.plugin.placed".to_string(),
+                "unknown".to_string(),
+            ],
+            status_bar_right: vec!["language".to_string(), "encoding".to_string()],
+            status_bar_hidden: vec![
+                "encoding".to_string(),
+                "author.plugin.hidden".to_string(),
+            ],
+            ..Default::default()
+        };
+        let items = im::vector![
+            item("author.plugin.low", StatusItemAlignment::Right, 0),
+            item("author.plugin.placed", StatusItemAlignment::Right, 0),
+            item("author.plugin.high", StatusItemAlignment::Right, 10),
+            item("author.plugin.hidden", StatusItemAlignment::Right, 0),
+        ];
+
+        assert_eq!(
+            StatusData::entries(&ui, &items, StatusItemAlignment::Left),
+            vec![
+                StatusEntry::Builtin(BuiltinStatusItem::Mode),
+                StatusEntry::Item("author.plugin.placed".to_string()),
+            ]
+        );
+        assert_eq!(
+            StatusData::entries(&ui, &items, StatusItemAlignment::Right),
+            vec![
+                StatusEntry::Builtin(BuiltinStatusItem::Language),
+                StatusEntry::Item("author.plugin.high".to_string()),
+                StatusEntry::Item("author.plugin.low".to_string()),
+            ]
+        );
+    }
+}
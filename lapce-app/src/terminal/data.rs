@@ -9,8 +9,10 @@
 };
 use floem::{
     glazier::{keyboard_types::Key, KeyEvent, Modifiers},
+    peniko::kurbo::Vec2,
     reactive::{create_rw_signal, RwSignal, Scope, SignalGetUntracked, SignalSet},
 };
+use fluent_bundle::FluentValue;
 use lapce_core::{
     command::{EditCommand, FocusCommand},
     mode::{Mode, VisualMode},
@@ -28,7 +30,9 @@
     command::{CommandExecuted, CommandKind, InternalCommand},
     debug::RunDebugProcess,
     doc::SystemClipboard,
+    editor::animation::ScrollDevice,
     keypress::{condition::Condition, KeyPressFocus},
+    notification::{Notification, NotificationCommand, NotificationSeverity},
     window_tab::CommonData,
     workspace::LapceWorkspace,
 };
@@ -43,6 +47,8 @@ pub struct TerminalData {
     pub visual_mode: RwSignal<VisualMode>,
     pub raw: RwSignal<Arc<RwLock<RawTerminal>>>,
     pub run_debug: RwSignal<Option<RunDebugProcess>>,
+    /// The terminal of the host of a collaboration session which this one shows
+    pub shared: Option<TermId>,
     pub common: CommonData,
 }
 
@@ -169,23 +175,13 @@ fn run_command(
                 }
                 EditCommand::ClipboardPaste => {
                     let clipboard = SystemClipboard {};
-                    let mut check_bracketed_paste: bool = false;
-                    if self.mode.get_untracked() == Mode::Terminal {
-                        let raw = self.raw.get_untracked();
-                        let mut raw = raw.write();
-                        let term = &mut raw.term;
-                        term.selection = None;
-                        if term.mode().contains(TermMode::BRACKETED_PASTE) {
-                            check_bracketed_paste = true;
-                        }
-                    }
                     if let Some(s) = clipboard.get_string() {
-                        if check_bracketed_paste {
-                            self.receive_char("\x1b[200~");
-                            self.receive_char(&s.replace('\x1b', ""));
-                            self.receive_char("\x1b[201~");
+                        let lines = s.lines().count();
+                        let limit = config.terminal.paste_confirm_lines;
+                        if limit > 0 && lines > limit {
+                            self.confirm_paste(s, lines);
                         } else {
-                            self.receive_char(&s);
+                            self.paste(&s);
                         }
                     }
                 }
@@ -282,9 +278,13 @@ fn run_command(
 
     fn receive_char(&self, c: &str) {
         if self.mode.get_untracked() == Mode::Terminal {
-            self.common
-                .proxy
-                .terminal_write(self.term_id, c.to_string());
+            if let Some(shared) = self.shared {
+                self.common.collab.terminal_input(shared, c);
+            } else {
+                self.common
+                    .proxy
+                    .terminal_write(self.term_id, c.to_string());
+            }
             self.raw
                 .get_untracked()
                 .write()
@@ -306,12 +306,17 @@ pub fn new(
 
         let title = create_rw_signal(cx, "title".to_string());
 
-        let raw = Self::new_raw_terminal(
-            workspace.clone(),
-            term_id,
-            run_debug.as_ref().map(|r| &r.config),
-            common.clone(),
-        );
+        let raw = match run_debug.as_ref() {
+            Some(run_debug) if !run_debug.in_terminal => {
+                Self::new_idle_raw_terminal(term_id, &common)
+            }
+            _ => Self::new_raw_terminal(
+                workspace.clone(),
+                term_id,
+                run_debug.as_ref().map(|r| &r.config),
+                common.clone(),
+            ),
+        };
 
         let run_debug = create_rw_signal(cx, run_debug);
         let mode = create_rw_signal(cx, Mode::Terminal);
@@ -327,10 +332,74 @@ pub fn new(
             run_debug,
             mode,
             visual_mode,
+            shared: None,
             common,
         }
     }
 
+    /// A terminal which shows the output of a terminal of the host of a
+    /// collaboration session, rather than running a process.
+    pub fn new_shared(
+        cx: Scope,
+        workspace: Arc<LapceWorkspace>,
+        shared: TermId,
+        title: String,
+        common: CommonData,
+    ) -> Self {
+        Self::new_idle(cx, workspace, Some(shared), title, common)
+    }
+
+    /// A terminal which shows the output of a task that the proxy runs.
+    pub fn new_task(
+        cx: Scope,
+        workspace: Arc<LapceWorkspace>,
+        title: String,
+        common: CommonData,
+    ) -> Self {
+        Self::new_idle(cx, workspace, None, title, common)
+    }
+
+    fn new_idle(
+        cx: Scope,
+        workspace: Arc<LapceWorkspace>,
+        shared: Option<TermId>,
+        title: String,
+        common: CommonData,
+    ) -> Self {
+        let (cx, _) = cx.run_child_scope(|cx| cx);
+        let term_id = TermId::next();
+        let raw = Self::new_idle_raw_terminal(term_id, &common);
+
+        Self {
+            scope: cx,
+            term_id,
+            workspace,
+            raw: create_rw_signal(cx, raw),
+            title: create_rw_signal(cx, title),
+            run_debug: create_rw_signal(cx, None),
+            mode: create_rw_signal(cx, Mode::Terminal),
+            visual_mode: create_rw_signal(cx, VisualMode::Normal),
+            shared,
+            common,
+        }
+    }
+
+    /// A raw terminal which doesn't run a process in the proxy.
+    fn new_idle_raw_terminal(
+        term_id: TermId,
+        common: &CommonData,
+    ) -> Arc<RwLock<RawTerminal>> {
+        let raw = Arc::new(RwLock::new(RawTerminal::new(
+            term_id,
+            common.proxy.clone(),
+            common.term_notification_tx.clone(),
+        )));
+        let _ = common
+            .term_tx
+            .send((term_id, TermEvent::NewTerminal(raw.clone())));
+        raw
+    }
+
     pub fn new_raw_terminal(
         workspace: Arc<LapceWorkspace>,
         term_id: TermId,
@@ -550,17 +619,100 @@ macro_rules! term_sequence {
         }
     }
 
-    pub fn wheel_scroll(&self, delta: f64) {
+    /// Send the text to the shell, which is bracketed when the program asked for
+    /// that, so that it isn't run as it's pasted.
+    pub fn paste(&self, content: &str) {
+        let mut check_bracketed_paste: bool = false;
+        if self.mode.get_untracked() == Mode::Terminal {
+            let raw = self.raw.get_untracked();
+            let mut raw = raw.write();
+            let term = &mut raw.term;
+            term.selection = None;
+            if term.mode().contains(TermMode::BRACKETED_PASTE) {
+                check_bracketed_paste = true;
+            }
+        }
+        if check_bracketed_paste {
+            self.receive_char("\x1b[200~");
+            self.receive_char(&content.replace('\x1b', ""));
+            self.receive_char("\x1b[201~");
+        } else {
+            self.receive_char(content);
+        }
+    }
+
+    /// Ask whether to paste the lines, as each of them can run as a command.
+    fn confirm_paste(&self, content: String, lines: usize) {
+        let config = self.common.config.get_untracked();
+        let notification = Notification::new(
+            NotificationSeverity::Warning,
+            config.tr_args(
+                "notification-terminal-paste",
+                [("lines", FluentValue::from(lines))],
+            ),
+            config.tr("notification-terminal-paste-message"),
+        )
+        .with_action(
+            config.tr("notification-terminal-paste-confirm"),
+            NotificationCommand::Internal(InternalCommand::PasteInTerminal {
+                term_id: self.term_id,
+                content,
+            }),
+        );
+        self.common
+            .internal_command
+            .send(InternalCommand::ShowNotification { notification });
+    }
+
+    /// Scroll by a wheel `delta`, times the sensitivity of the device it comes
+    /// from, a line or a column for every line height. Programs on the alternate
+    /// screen, which has no scrollback, are sent the arrow keys for it instead
+    /// when they ask for that, which is how they can be scrolled sideways.
+    pub fn wheel_scroll(&self, delta: Vec2, mods: Modifiers) {
         let config = self.common.config.get_untracked();
+        let delta = match ScrollDevice::of(delta) {
+            ScrollDevice::Wheel => {
+                let modifier = if mods.alt() {
+                    config.editor.fast_scroll_speed_modifier
+                } else {
+                    config.editor.scroll_speed_modifier
+                };
+                let delta = if mods.shift() && delta.x == 0.0 {
+                    Vec2::new(delta.y, 0.0)
+                } else {
+                    delta
+                };
+                delta * modifier
+            }
+            ScrollDevice::Touchpad => {
+                delta * config.editor.touchpad_scroll_sensitivity
+            }
+        };
         let step = config.terminal_line_height() as f64;
-        let raw = self.raw.get_untracked();
-        let mut raw = raw.write();
-        raw.scroll_delta -= delta;
-        let delta = (raw.scroll_delta / step) as i32;
-        raw.scroll_delta -= delta as f64 * step;
-        if delta != 0 {
-            let scroll = alacritty_terminal::grid::Scroll::Delta(delta);
-            raw.term.scroll_display(scroll);
+
+        let mut keys = String::new();
+        {
+            let raw = self.raw.get_untracked();
+            let mut raw = raw.write();
+            raw.scroll_delta -= delta;
+            let lines = (raw.scroll_delta.y / step) as i32;
+            let columns = (raw.scroll_delta.x / step) as i32;
+            raw.scroll_delta -= Vec2::new(columns as f64, lines as f64) * step;
+            if raw
+                .term
+                .mode()
+                .contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
+            {
+                let up = if lines > 0 { "\x1bOA" } else { "\x1bOB" };
+                let left = if columns > 0 { "\x1bOD" } else { "\x1bOC" };
+                keys.push_str(&up.repeat(lines.unsigned_abs() as usize));
+                keys.push_str(&left.repeat(columns.unsigned_abs() as usize));
+            } else if lines != 0 {
+                raw.term.scroll_display(Scroll::Delta(lines));
+            }
+        }
+        if !keys.is_empty() {
+            self.receive_char(&keys);
         }
     }
 
@@ -645,12 +797,17 @@ pub fn new_process(&self, run_debug: Option<RunDebugProcess>) {
             (width, height)
         };
 
-        let raw = Self::new_raw_terminal(
-            self.workspace.clone(),
-            self.term_id,
-            run_debug.as_ref().map(|r| &r.config),
-            self.common.clone(),
-        );
+        let raw = match run_debug.as_ref() {
+            Some(run_debug) if !run_debug.in_terminal => {
+                Self::new_idle_raw_terminal(self.term_id, &self.common)
+            }
+            _ => Self::new_raw_terminal(
+                self.workspace.clone(),
+                self.term_id,
+                run_debug.as_ref().map(|r| &r.config),
+                self.common.clone(),
+            ),
+        };
 
         self.raw.set(raw);
         self.run_debug.set(run_debug);
@@ -1,23 +1,37 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
+use alacritty_terminal::{grid::Dimensions, term::cell::Cell};
 use floem::{
+    ext_event::create_ext_action,
     glazier::KeyEvent,
     reactive::{
         create_rw_signal, RwSignal, Scope, SignalGet, SignalGetUntracked, SignalSet,
         SignalUpdate, SignalWith, SignalWithUntracked,
     },
 };
+use fluent_bundle::FluentValue;
 use lapce_core::mode::Mode;
 use lapce_rpc::{
-    dap_types::{DapId, RunDebugConfig, StackFrame, Stopped, ThreadId},
+    dap_types::{
+        Breakpoint, DapId, DebuggerCapabilities, EvaluateResponse, Output,
+        RunDebugConfig, StackFrame, Stopped, TaskId, Thread, ThreadId,
+    },
+    proxy::ProxyResponse,
     terminal::TermId,
+    RpcError,
 };
 
-use super::{data::TerminalData, tab::TerminalTabData};
+use super::{data::TerminalData, event::TermEvent, tab::TerminalTabData};
 use crate::{
-    debug::{DapData, RunDebugData, RunDebugMode, RunDebugProcess},
+    command::InternalCommand,
+    debug::{
+        frame_position, stopped_frame, DapData, RunDebugData, RunDebugMode,
+        RunDebugProcess, VariableEdit, VariableTarget,
+    },
+    editor::location::{EditorLocation, EditorPosition},
     id::TerminalTabId,
     keypress::{KeyPressData, KeyPressFocus},
+    notification::{Notification, NotificationSeverity},
     panel::kind::PanelKind,
     window_tab::{CommonData, Focus},
     workspace::LapceWorkspace,
@@ -34,6 +48,9 @@ pub struct TerminalPanelData {
     pub workspace: Arc<LapceWorkspace>,
     pub tab_info: RwSignal<TerminalTabInfo>,
     pub debug: RunDebugData,
+    /// The terminals which show the output of the tasks that are running, by
+    /// the ids of the tasks
+    pub tasks: RwSignal<HashMap<TaskId, TermId>>,
     pub common: CommonData,
 }
 
@@ -53,13 +70,15 @@ pub fn new(
         let tab_info = TerminalTabInfo { active: 0, tabs };
         let tab_info = create_rw_signal(cx, tab_info);
 
-        let debug = RunDebugData::new(cx);
+        let debug = RunDebugData::new(cx, common.breakpoints);
+        let tasks = create_rw_signal(cx, HashMap::new());
 
         Self {
             cx,
             workspace,
             tab_info,
             debug,
+            tasks,
             common,
         }
     }
@@ -106,7 +125,59 @@ pub fn new_tab(&self, run_debug: Option<RunDebugProcess>) -> TerminalTabData {
             run_debug,
             self.common.clone(),
         );
+        self.insert_tab(terminal_tab)
+    }
+
+    /// A new tab for a terminal of the host of a collaboration session.
+    pub fn new_shared_tab(&self, shared: TermId, title: String) -> TerminalTabData {
+        let terminal_tab = TerminalTabData::new_shared(
+            self.workspace.clone(),
+            shared,
+            title,
+            self.common.clone(),
+        );
+        self.insert_tab(terminal_tab)
+    }
+
+    /// A new tab for the output of a task which the proxy started.
+    pub fn task_started(&self, task_id: TaskId, name: &str) {
+        let title = self
+            .common
+            .config
+            .get_untracked()
+            .tr_args("debug-task-title", [("task", FluentValue::from(name))]);
+        let terminal_tab = self.insert_tab(TerminalTabData::new_task(
+            self.workspace.clone(),
+            title,
+            self.common.clone(),
+        ));
+        if let Some(terminal) = terminal_tab.active_terminal(false) {
+            self.tasks.update(|tasks| {
+                tasks.insert(task_id, terminal.term_id);
+            });
+        }
+    }
+
+    pub fn task_output(&self, task_id: TaskId, content: Vec<u8>) {
+        if let Some(term_id) = self
+            .tasks
+            .with_untracked(|tasks| tasks.get(&task_id).copied())
+        {
+            let _ = self
+                .common
+                .term_tx
+                .send((term_id, TermEvent::UpdateContent(content)));
+        }
+    }
+
+    /// The task exited, whose terminal is kept open for its output.
+    pub fn task_stopped(&self, task_id: TaskId) {
+        self.tasks.update(|tasks| {
+            tasks.remove(&task_id);
+        });
+    }
 
+    fn insert_tab(&self, terminal_tab: TerminalTabData) -> TerminalTabData {
         self.tab_info.update(|info| {
             info.tabs.insert(
                 if info.tabs.is_empty() {
@@ -188,6 +259,30 @@ pub fn get_terminal(&self, term_id: &TermId) -> Option<TerminalData> {
         })
     }
 
+    /// An estimate of the bytes which the grids of the terminals take, with
+    /// their scrollback.
+    pub fn memory_usage(&self) -> usize {
+        self.tab_info.with_untracked(|info| {
+            info.tabs
+                .iter()
+                .map(|(_, tab)| {
+                    tab.terminals.with_untracked(|terminals| {
+                        terminals
+                            .iter()
+                            .map(|(_, terminal)| {
+                                let raw = terminal.raw.get_untracked();
+                                let raw = raw.read();
+                                raw.term.grid().total_lines()
+                                    * raw.term.columns()
+                                    * std::mem::size_of::<Cell>()
+                            })
+                            .sum::<usize>()
+                    })
+                })
+                .sum()
+        })
+    }
+
     fn get_terminal_in_tab(
         &self,
         term_id: &TermId,
@@ -258,6 +353,17 @@ pub fn split_exchange(&self, term_id: TermId) {
     }
 
     pub fn close_terminal(&self, term_id: &TermId) {
+        // A task is killed when its terminal is closed while it runs
+        let task_id = self.tasks.with_untracked(|tasks| {
+            tasks
+                .iter()
+                .find(|(_, t)| *t == term_id)
+                .map(|(task_id, _)| *task_id)
+        });
+        if let Some(task_id) = task_id {
+            self.task_stopped(task_id);
+            self.common.proxy.cancel_task(task_id);
+        }
         if let Some((_, tab, index, _)) = self.get_terminal_in_tab(term_id) {
             let active = tab.active.get_untracked();
             let len = tab
@@ -293,6 +399,16 @@ pub fn terminal_stopped(&self, term_id: &TermId) {
         }
     }
 
+    /// The session ended, which stops the terminal which shows it when its
+    /// process isn't run in it, as no process there stops.
+    pub fn dap_terminated(&self, dap_id: &DapId) {
+        for (term_id, run_debug) in self.run_debug_process(false) {
+            if run_debug.config.dap_id == *dap_id && !run_debug.in_terminal {
+                self.terminal_stopped(&term_id);
+            }
+        }
+    }
+
     pub fn get_stopped_run_debug_terminal(
         &self,
         mode: &RunDebugMode,
@@ -360,9 +476,15 @@ pub fn restart_run_debug(&self, term_id: TermId) -> Option<()> {
                     terminal.run_debug.get_untracked().as_ref()?.config.dap_id;
                 let daps = self.debug.daps.get_untracked();
                 let dap = daps.get(&dap_id)?;
-                self.common
-                    .proxy
-                    .dap_restart(dap.dap_id, self.debug.source_breakpoints());
+                // The data of the variables doesn't outlive the debuggee
+                dap.data_breakpoints.set(Vec::new());
+                self.debug.reset_breakpoints();
+                let breakpoints = if run_debug.config.no_debug {
+                    HashMap::new()
+                } else {
+                    self.debug.source_breakpoints()
+                };
+                self.common.proxy.dap_restart(dap.dap_id, breakpoints);
                 term_id
             }
         };
@@ -471,13 +593,23 @@ pub fn set_process_id(&self, term_id: &TermId, process_id: Option<u32>) {
         }
     }
 
-    pub fn dap_continued(&self, dap_id: &DapId) {
+    pub fn dap_continued(&self, dap_id: &DapId, thread_id: Option<ThreadId>) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.continued(self.cx, thread_id, &self.common.proxy);
+        }
+    }
+
+    pub fn dap_threads(&self, dap_id: &DapId, threads: &[Thread]) {
         let dap = self
             .debug
             .daps
             .with_untracked(|daps| daps.get(dap_id).cloned());
         if let Some(dap) = dap {
-            dap.stopped.set(false);
+            dap.set_threads(self.cx, threads);
         }
     }
 
@@ -492,8 +624,347 @@ pub fn dap_stopped(
             .daps
             .with_untracked(|daps| daps.get(dap_id).cloned());
         if let Some(dap) = dap {
-            dap.stopped(self.cx, stopped, stack_frames);
+            dap.stopped(self.cx, stopped, stack_frames, &self.common.proxy);
+        }
+        // The execution is shown where the thread which stopped is
+        if let Some(frame) = stopped_frame(stopped, stack_frames) {
+            self.jump_to_frame(frame);
+        }
+    }
+
+    pub fn dap_output(&self, dap_id: &DapId, output: &Output) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.output(output);
+        }
+    }
+
+    /// Show the variables of the frame which was selected in the stack frames.
+    pub fn dap_watch_evaluated(
+        &self,
+        dap_id: &DapId,
+        frame_id: usize,
+        expression: &str,
+        result: &Result<EvaluateResponse, String>,
+    ) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.watch_evaluated(frame_id, expression, result);
+        }
+    }
+
+    pub fn dap_capabilities(
+        &self,
+        dap_id: &DapId,
+        capabilities: &DebuggerCapabilities,
+    ) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.set_capabilities(capabilities);
+        }
+    }
+
+    pub fn dap_data_breakpoints_resp(
+        &self,
+        dap_id: &DapId,
+        breakpoints: &[Breakpoint],
+    ) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.data_breakpoints_resp(breakpoints);
+        }
+    }
+
+    /// Ask the debugger of the active session which data the variable at the
+    /// path of the frame's tree has, and break when it changes.
+    pub fn add_data_breakpoint(&self, path: &[usize]) {
+        let Some(dap) = self.get_active_dap(false) else {
+            return;
+        };
+        let Some((reference, name)) = dap.data_breakpoint_target(path) else {
+            return;
+        };
+
+        let proxy = self.common.proxy.clone();
+        let internal_command = self.common.internal_command;
+        let config = self.common.config;
+        let dap_id = dap.dap_id;
+        let variable = name.clone();
+        let send = create_ext_action(
+            self.cx,
+            move |result: Result<ProxyResponse, RpcError>| {
+                let message = match result {
+                    Ok(ProxyResponse::DapDataBreakpointInfo { info }) => {
+                        if let Some(data_id) = info.data_id {
+                            dap.add_data_breakpoint(
+                                data_id,
+                                info.description,
+                                &proxy,
+                            );
+                            return;
+                        }
+                        info.description
+                    }
+                    Err(e) => e.message,
+                    Ok(_) => return,
+                };
+                let config = config.get_untracked();
+                internal_command.send(InternalCommand::ShowNotification {
+                    notification: Notification::new(
+                        NotificationSeverity::Warning,
+                        config.tr_args(
+                            "notification-data-breakpoint-unavailable",
+                            [("name", FluentValue::from(variable.as_str()))],
+                        ),
+                        message,
+                    ),
+                });
+            },
+        );
+        self.common.proxy.dap_data_breakpoint_info(
+            dap_id,
+            reference,
+            name,
+            move |result| {
+                send(result);
+            },
+        );
+    }
+
+    /// The value of the variable which is edited, which the palette starts with.
+    pub fn variable_value(&self, edit: &VariableEdit) -> Option<String> {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(&edit.dap_id).cloned())?;
+        dap.variable(edit.tree, &edit.path, |variable| variable.value.clone())
+            .flatten()
+    }
+
+    /// Set the value of the variable which is edited in its session, and get
+    /// the variables which that could have changed again once it's set.
+    pub fn set_variable_value(&self, edit: VariableEdit, value: String) {
+        let Some(dap) = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(&edit.dap_id).cloned())
+        else {
+            return;
+        };
+        let Some(target) = dap.set_value_target(edit.tree, &edit.path) else {
+            return;
+        };
+        let Some(variable) =
+            dap.variable(edit.tree, &edit.path, |variable| variable.name.clone())
+        else {
+            return;
+        };
+
+        let cx = self.cx;
+        let proxy = self.common.proxy.clone();
+        let internal_command = self.common.internal_command;
+        let config = self.common.config;
+        let dap_id = dap.dap_id;
+        let frame_id = dap.frame_id.get_untracked();
+        let send = create_ext_action(
+            self.cx,
+            move |result: Result<ProxyResponse, RpcError>| {
+                let message = match result {
+                    Ok(ProxyResponse::DapSetVariable { result }) => {
+                        dap.value_set(cx, &edit, result.value, &proxy);
+                        return;
+                    }
+                    Ok(ProxyResponse::DapSetExpression { result }) => {
+                        dap.value_set(cx, &edit, result.value, &proxy);
+                        return;
+                    }
+                    Err(e) => e.message,
+                    Ok(_) => return,
+                };
+                let config = config.get_untracked();
+                internal_command.send(InternalCommand::ShowNotification {
+                    notification: Notification::new(
+                        NotificationSeverity::Warning,
+                        config.tr_args(
+                            "notification-set-variable-failed",
+                            [("name", FluentValue::from(variable.as_str()))],
+                        ),
+                        message,
+                    ),
+                });
+            },
+        );
+        match target {
+            VariableTarget::Variable { reference, name } => {
+                self.common.proxy.dap_set_variable(
+                    dap_id,
+                    reference,
+                    name,
+                    value,
+                    move |result| {
+                        send(result);
+                    },
+                );
+            }
+            VariableTarget::Expression(expression) => {
+                self.common.proxy.dap_set_expression(
+                    dap_id,
+                    expression,
+                    value,
+                    frame_id,
+                    move |result| {
+                        send(result);
+                    },
+                );
+            }
+        }
+    }
+
+    /// Run the frame of the session again from its start, which the session
+    /// stops at.
+    pub fn dap_restart_frame(&self, dap_id: DapId, frame_id: usize) {
+        let internal_command = self.common.internal_command;
+        let config = self.common.config;
+        let send = create_ext_action(
+            self.cx,
+            move |result: Result<ProxyResponse, RpcError>| {
+                if let Err(e) = result {
+                    internal_command.send(InternalCommand::ShowNotification {
+                        notification: Notification::new(
+                            NotificationSeverity::Warning,
+                            config
+                                .get_untracked()
+                                .tr("notification-restart-frame-failed"),
+                            e.message,
+                        ),
+                    });
+                }
+            },
+        );
+        self.common
+            .proxy
+            .dap_restart_frame(dap_id, frame_id, move |result| {
+                send(result);
+            });
+    }
+
+    /// Move the execution of the stopped thread of the active session to the
+    /// line of the file, without running the code in between. It's moved to
+    /// the first of the targets which the debugger has at the line.
+    pub fn dap_jump_to_line(&self, path: PathBuf, line: usize) {
+        let Some(dap) = self.get_active_dap(false) else {
+            return;
+        };
+        if !dap.stopped.get_untracked() || !dap.supports_goto_targets.get_untracked()
+        {
+            return;
+        }
+        let Some(thread_id) = dap.thread_id.get_untracked() else {
+            return;
+        };
+
+        let cx = self.cx;
+        let proxy = self.common.proxy.clone();
+        let internal_command = self.common.internal_command;
+        let config = self.common.config;
+        let dap_id = dap.dap_id;
+        let notify = move |message: String| {
+            internal_command.send(InternalCommand::ShowNotification {
+                notification: Notification::new(
+                    NotificationSeverity::Warning,
+                    config.get_untracked().tr_args(
+                        "notification-jump-failed",
+                        [("line", FluentValue::from(line + 1))],
+                    ),
+                    message,
+                ),
+            });
+        };
+        let send = create_ext_action(
+            self.cx,
+            move |result: Result<ProxyResponse, RpcError>| {
+                let targets = match result {
+                    Ok(ProxyResponse::DapGotoTargets { targets }) => targets,
+                    Err(e) => {
+                        notify(e.message);
+                        return;
+                    }
+                    Ok(_) => return,
+                };
+                let Some(target) = targets.first() else {
+                    notify(
+                        config.get_untracked().tr("notification-no-goto-targets"),
+                    );
+                    return;
+                };
+                let send = create_ext_action(
+                    cx,
+                    move |result: Result<ProxyResponse, RpcError>| {
+                        if let Err(e) = result {
+                            notify(e.message);
+                        }
+                    },
+                );
+                proxy.dap_goto(dap_id, thread_id, target.id, move |result| {
+                    send(result);
+                });
+            },
+        );
+        self.common
+            .proxy
+            .dap_goto_targets(dap_id, path, line + 1, move |result| {
+                send(result);
+            });
+    }
+
+    /// Show the variables of the frame, evaluate the watches in it, and open
+    /// its source at its line.
+    pub fn dap_select_frame(&self, dap_id: &DapId, frame: &StackFrame) {
+        let dap = self
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(dap_id).cloned());
+        if let Some(dap) = dap {
+            dap.load_scopes(self.cx, frame.id, &self.common.proxy);
+            if !self
+                .debug
+                .watches
+                .with_untracked(|watches| watches.is_empty())
+            {
+                self.common.proxy.dap_evaluate_watches(dap.dap_id, frame.id);
+            }
         }
+        self.jump_to_frame(frame);
+    }
+
+    /// Open the source of the frame at its line and column, if it has one.
+    fn jump_to_frame(&self, frame: &StackFrame) {
+        let Some((path, position)) = frame_position(frame) else {
+            return;
+        };
+        self.common
+            .internal_command
+            .send(InternalCommand::JumpToLocation {
+                location: EditorLocation {
+                    path,
+                    position: Some(EditorPosition::Position(position)),
+                    scroll_offset: None,
+                    ignore_unconfirmed: false,
+                    same_editor_tab: false,
+                },
+            });
     }
 
     pub fn dap_continue(&self, term_id: TermId) -> Option<()> {
@@ -524,6 +995,48 @@ pub fn dap_pause(&self, term_id: TermId) -> Option<()> {
         Some(())
     }
 
+    /// Run the debuggee backwards until a breakpoint is hit, for a debugger
+    /// which supports stepping back.
+    pub fn dap_reverse_continue(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_step_back_thread(term_id)?;
+        self.common.proxy.dap_reverse_continue(dap_id, thread_id);
+        Some(())
+    }
+
+    pub fn dap_step_back(&self, term_id: TermId) -> Option<()> {
+        let (dap_id, thread_id) = self.dap_step_back_thread(term_id)?;
+        self.common.proxy.dap_step_back(dap_id, thread_id);
+        Some(())
+    }
+
+    /// The session and the thread which can be run backwards, which needs the
+    /// session to be stopped and its debugger to support stepping back.
+    fn dap_step_back_thread(&self, term_id: TermId) -> Option<(DapId, ThreadId)> {
+        let dap = self.get_dap(term_id, false)?;
+        if !dap.stopped.get_untracked() || !dap.supports_step_back.get_untracked() {
+            return None;
+        }
+        Some((
+            dap.dap_id,
+            dap.thread_id.get_untracked().unwrap_or_default(),
+        ))
+    }
+
+    /// Resume the thread while the others stay stopped.
+    pub fn dap_continue_thread(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.common.proxy.dap_continue_thread(dap_id, thread_id);
+    }
+
+    pub fn dap_pause_thread(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.common.proxy.dap_pause(dap_id, thread_id);
+    }
+
+    pub fn dap_terminate_thread(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.common
+            .proxy
+            .dap_terminate_threads(dap_id, vec![thread_id]);
+    }
+
     pub fn get_active_dap(&self, tracked: bool) -> Option<DapData> {
         let active_term = if tracked {
             self.debug.active_term.get()?
@@ -1,5 +1,6 @@
 use alacritty_terminal::{ansi, event::EventListener, term::test::TermSize, Term};
 use crossbeam_channel::Sender;
+use floem::peniko::kurbo::Vec2;
 use lapce_proxy::terminal::TermConfig;
 use lapce_rpc::{proxy::ProxyRpcHandler, terminal::TermId};
 
@@ -36,7 +37,7 @@ fn send_event(&self, event: alacritty_terminal::event::Event) {
 pub struct RawTerminal {
     pub parser: ansi::Processor,
     pub term: Term<EventProxy>,
-    pub scroll_delta: f64,
+    pub scroll_delta: Vec2,
 }
 
 impl RawTerminal {
@@ -59,7 +60,7 @@ pub fn new(
         Self {
             parser,
             term,
-            scroll_delta: 0.0,
+            scroll_delta: Vec2::ZERO,
         }
     }
 
@@ -5,6 +5,8 @@
     SignalWithUntracked,
 };
 
+use lapce_rpc::terminal::TermId;
+
 use super::data::TerminalData;
 use crate::{
     debug::RunDebugProcess, id::TerminalTabId, window_tab::CommonData,
@@ -27,6 +29,34 @@ pub fn new(
     ) -> Self {
         let (cx, _) = common.scope.run_child_scope(|cx| cx);
         let terminal_data = TerminalData::new(cx, workspace, run_debug, common);
+        Self::with_terminal(cx, terminal_data)
+    }
+
+    /// A tab with a terminal of the host of a collaboration session.
+    pub fn new_shared(
+        workspace: Arc<LapceWorkspace>,
+        shared: TermId,
+        title: String,
+        common: CommonData,
+    ) -> Self {
+        let (cx, _) = common.scope.run_child_scope(|cx| cx);
+        let terminal_data =
+            TerminalData::new_shared(cx, workspace, shared, title, common);
+        Self::with_terminal(cx, terminal_data)
+    }
+
+    /// A tab with the terminal of a task which the proxy runs.
+    pub fn new_task(
+        workspace: Arc<LapceWorkspace>,
+        title: String,
+        common: CommonData,
+    ) -> Self {
+        let (cx, _) = common.scope.run_child_scope(|cx| cx);
+        let terminal_data = TerminalData::new_task(cx, workspace, title, common);
+        Self::with_terminal(cx, terminal_data)
+    }
+
+    fn with_terminal(cx: Scope, terminal_data: TerminalData) -> Self {
         let terminals = im::vector![(create_rw_signal(cx, 0), terminal_data)];
         let terminals = create_rw_signal(cx, terminals);
         let active = create_rw_signal(cx, 0);
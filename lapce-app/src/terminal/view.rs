@@ -5,7 +5,7 @@
     term::{cell::Flags, test::TermSize},
 };
 use floem::{
-    cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout, Weight},
+    cosmic_text::{Attrs, AttrsList, TextLayout, Weight},
     id::Id,
     peniko::kurbo::{Point, Rect, Size},
     reactive::{
@@ -106,8 +106,7 @@ fn char_size(&self) -> Size {
         let config = self.config.get_untracked();
         let font_family = config.terminal_font_family();
         let font_size = config.terminal_font_size();
-        let family: Vec<FamilyOwned> =
-            FamilyOwned::parse_list(font_family).collect();
+        let family = config.with_font_fallback(font_family);
         let attrs = Attrs::new().family(&family).font_size(font_size as f32);
         let attrs_list = AttrsList::new(attrs);
         let mut text_layout = TextLayout::new();
@@ -208,8 +207,7 @@ fn paint(&mut self, cx: &mut floem::context::PaintCx) {
         let char_size = self.char_size();
         let char_width = char_size.width;
 
-        let family: Vec<FamilyOwned> =
-            FamilyOwned::parse_list(font_family).collect();
+        let family = config.with_font_fallback(font_family);
         let attrs = Attrs::new().family(&family).font_size(font_size as f32);
 
         let raw = self.raw.read();
@@ -246,7 +244,7 @@ fn paint(&mut self, cx: &mut floem::context::PaintCx) {
                 let y1 = y0 + line_height;
                 cx.fill(
                     &Rect::new(x0, y0, x1, y1),
-                    config.get_color(LapceColor::EDITOR_SELECTION),
+                    config.get_color(LapceColor::TERMINAL_SELECTION),
                 );
             }
         } else if mode != Mode::Terminal {
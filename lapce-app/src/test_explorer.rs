@@ -0,0 +1,327 @@
+//! The tests of the test explorer, which are the test runnables of the language
+//! servers in the open files along with the tests which plugins find, and the
+//! results of their last runs.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{
+        create_memo, create_rw_signal, Memo, RwSignal, Scope, SignalGet,
+        SignalGetUntracked, SignalUpdate, SignalWith,
+    },
+};
+use lapce_rpc::{
+    dap_types::RunDebugConfig,
+    plugin::{TestItemParams, VoltID},
+    proxy::ProxyResponse,
+    runnable::Runnable,
+    RpcError,
+};
+
+use crate::{
+    command::InternalCommand, debug::RunDebugMode, doc::Document,
+    window_tab::CommonData,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestItem {
+    /// The id of the test, which its result is kept by
+    pub id: String,
+    pub label: String,
+    pub path: PathBuf,
+    /// The line of the test, which starts at 0
+    pub line: Option<u32>,
+    pub config: RunDebugConfig,
+}
+
+impl TestItem {
+    fn from_runnable(path: &Path, runnable: &Runnable) -> Self {
+        Self {
+            id: runnable_test_id(path, runnable),
+            label: runnable.label.clone(),
+            path: path.to_path_buf(),
+            line: runnable.line(),
+            config: runnable.run_config(),
+        }
+    }
+
+    fn from_plugin(volt_id: &VoltID, test: TestItemParams) -> Self {
+        Self {
+            id: format!("{volt_id}/{}", test.id),
+            config: RunDebugConfig {
+                name: test.label.clone(),
+                program: test.program,
+                args: test.args,
+                cwd: test.cwd,
+                env: test.env,
+                env_file: None,
+                pre_launch_task: None,
+                post_debug_task: None,
+                mode: Default::default(),
+                pid: None,
+                port: None,
+                host: None,
+                adapter: None,
+                debug_command: None,
+                dap_id: Default::default(),
+                parent: None,
+                configuration: None,
+                no_debug: false,
+                runnable: None,
+                properties: Default::default(),
+            },
+            label: test.label,
+            path: test.path,
+            line: test.line,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestStatus {
+    /// The test waits for the ones before it in a run
+    Queued,
+    Running,
+    Passed,
+    /// The test failed with the message of its output
+    Failed(String),
+}
+
+/// Whether the runnable of the language server runs tests.
+pub fn is_test_runnable(runnable: &Runnable) -> bool {
+    runnable.args.cargo_args.first().map(|arg| arg.as_str()) == Some("test")
+        && runnable.line().is_some()
+}
+
+/// The id of the test of a runnable, which the gutter finds its result with.
+pub fn runnable_test_id(path: &Path, runnable: &Runnable) -> String {
+    format!("{}::{}", path.display(), runnable.label)
+}
+
+/// The message of the first panic in the output of a failed test, or its last
+/// line when it didn't panic.
+pub fn failure_message(output: &str) -> String {
+    let mut lines = output.lines();
+    while let Some(line) = lines.next() {
+        let Some((_, rest)) = line.split_once("panicked at ") else {
+            continue;
+        };
+        // Since Rust 1.73 the location ends the line, and the message is on the
+        // lines after it
+        if rest.ends_with(':') {
+            let message = lines
+                .by_ref()
+                .take_while(|l| !l.is_empty() && !l.starts_with("note:"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if !message.is_empty() {
+                return message;
+            }
+        }
+        return rest.to_string();
+    }
+    output
+        .lines()
+        .rev()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+        .to_string()
+}
+
+#[derive(Clone)]
+pub struct TestExplorerData {
+    /// The tests which each plugin found
+    pub plugin_tests: RwSignal<im::HashMap<VoltID, im::Vector<TestItem>>>,
+    /// The tests by file, in the order of the files and then of the lines
+    pub tests: Memo<im::Vector<(PathBuf, im::Vector<TestItem>)>>,
+    /// Goes up when the tests are cancelled, which stops the runs before
+    run: RwSignal<u64>,
+    pub common: CommonData,
+}
+
+impl TestExplorerData {
+    pub fn new(
+        cx: Scope,
+        docs: RwSignal<im::HashMap<PathBuf, RwSignal<Document>>>,
+        common: CommonData,
+    ) -> Self {
+        let plugin_tests = create_rw_signal(cx, im::HashMap::new());
+        let tests = create_memo(cx, move |_| {
+            let mut files: BTreeMap<PathBuf, im::Vector<TestItem>> = BTreeMap::new();
+            for (path, doc) in docs.get() {
+                doc.with(|doc| {
+                    for runnable in doc.runnables.iter() {
+                        if is_test_runnable(runnable) {
+                            files
+                                .entry(path.clone())
+                                .or_default()
+                                .push_back(TestItem::from_runnable(&path, runnable));
+                        }
+                    }
+                });
+            }
+            plugin_tests.with(|plugin_tests| {
+                for tests in plugin_tests.values() {
+                    for test in tests.iter() {
+                        files
+                            .entry(test.path.clone())
+                            .or_default()
+                            .push_back(test.clone());
+                    }
+                }
+            });
+            files
+                .into_iter()
+                .map(|(path, mut tests)| {
+                    tests.sort_by_key(|test| test.line);
+                    (path, tests)
+                })
+                .collect()
+        });
+        Self {
+            plugin_tests,
+            tests,
+            run: create_rw_signal(cx, 0),
+            common,
+        }
+    }
+
+    pub fn set_plugin_tests(&self, volt_id: &VoltID, tests: Vec<TestItemParams>) {
+        let tests = tests
+            .into_iter()
+            .map(|test| TestItem::from_plugin(volt_id, test))
+            .collect();
+        self.plugin_tests.update(|plugin_tests| {
+            plugin_tests.insert(volt_id.clone(), tests);
+        });
+    }
+
+    pub fn remove_plugin_tests(&self, volt_id: &VoltID) {
+        self.plugin_tests.update(|plugin_tests| {
+            plugin_tests.remove(volt_id);
+        });
+    }
+
+    pub fn run_all(&self) {
+        let tests = self
+            .tests
+            .get_untracked()
+            .into_iter()
+            .flat_map(|(_, tests)| tests)
+            .collect();
+        self.run_tests(tests);
+    }
+
+    pub fn rerun_failed(&self) {
+        let results = self.common.test_results.get_untracked();
+        let tests = self
+            .tests
+            .get_untracked()
+            .into_iter()
+            .flat_map(|(_, tests)| tests)
+            .filter(|test| {
+                matches!(results.get(&test.id), Some(TestStatus::Failed(_)))
+            })
+            .collect();
+        self.run_tests(tests);
+    }
+
+    /// Run the tests one after the other, as they would wait for each other's
+    /// builds anyway.
+    pub fn run_tests(&self, tests: VecDeque<TestItem>) {
+        self.common.test_results.update(|results| {
+            for test in tests.iter() {
+                results.insert(test.id.clone(), TestStatus::Queued);
+            }
+        });
+        self.run_next(tests, self.run.get_untracked());
+    }
+
+    /// Kill the tests which are running, and forget the queued ones.
+    pub fn cancel(&self) {
+        self.run.update(|run| *run += 1);
+        self.common.test_results.update(|results| {
+            results.retain(|_, status| *status != TestStatus::Queued);
+        });
+        self.common.proxy.cancel_tests();
+    }
+
+    fn run_next(&self, mut tests: VecDeque<TestItem>, run: u64) {
+        if self.run.get_untracked() != run {
+            return;
+        }
+        let Some(test) = tests.pop_front() else {
+            return;
+        };
+        let results = self.common.test_results;
+        results.update(|results| {
+            results.insert(test.id.clone(), TestStatus::Running);
+        });
+
+        let data = self.clone();
+        let id = test.id;
+        let send = create_ext_action(
+            self.common.scope,
+            move |result: Result<ProxyResponse, RpcError>| {
+                let status = match result {
+                    Ok(ProxyResponse::RunTest { success: true, .. }) => {
+                        TestStatus::Passed
+                    }
+                    Ok(ProxyResponse::RunTest { output, .. }) => {
+                        TestStatus::Failed(failure_message(&output))
+                    }
+                    Ok(_) => TestStatus::Failed(String::new()),
+                    Err(e) => TestStatus::Failed(e.message),
+                };
+                results.update(|results| {
+                    results.insert(id, status);
+                });
+                data.run_next(tests, run);
+            },
+        );
+        self.common.proxy.run_test(test.config, move |result| {
+            send(result);
+        });
+    }
+
+    pub fn debug_test(&self, test: &TestItem) {
+        self.common
+            .internal_command
+            .send(InternalCommand::RunAndDebug {
+                mode: RunDebugMode::Debug,
+                config: test.config.clone(),
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::failure_message;
+
+    #[test]
+    fn test_failure_message() {
+        let output = "running 1 test
+thread 'tests::foo' panicked at src/lib.rs:10:5:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+test tests::foo ... FAILED
+";
+        assert_eq!(
+            failure_message(output),
+            "assertion `left == right` failed\n  left: 1\n right: 2"
+        );
+
+        let output = "thread 'main' panicked at 'boom', src/main.rs:2:5\n";
+        assert_eq!(failure_message(output), "'boom', src/main.rs:2:5");
+
+        let output = "error: could not compile `foo`\n\n";
+        assert_eq!(failure_message(output), "error: could not compile `foo`");
+    }
+}
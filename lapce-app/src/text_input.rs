@@ -73,6 +73,7 @@ pub fn text_input(
         text_viewport: Rect::ZERO,
         placeholder: "".to_string(),
         placeholder_text_layout: None,
+        masked: false,
         cursor,
         doc,
         color: None,
@@ -106,6 +107,7 @@ enum TextInputState {
     Content(String),
     Focus(bool),
     Placeholder(String),
+    Masked(bool),
 }
 
 pub struct TextInput {
@@ -120,6 +122,8 @@ pub struct TextInput {
     text_viewport: Rect,
     placeholder: String,
     placeholder_text_layout: Option<TextLayout>,
+    /// Whether the content is drawn as `*`, for passwords
+    masked: bool,
     color: Option<Color>,
     font_size: Option<f32>,
     font_family: Option<String>,
@@ -142,6 +146,18 @@ pub fn placeholder(self, placeholder: impl Fn() -> String + 'static) -> Self {
         self
     }
 
+    /// Draw each byte of the content as `*`, so that the offsets of the cursor
+    /// still match the content.
+    pub fn masked(self, masked: impl Fn() -> bool + 'static) -> Self {
+        let cx = ViewContext::get_current();
+        let id = self.id;
+        create_effect(cx.scope, move |_| {
+            let masked = masked();
+            id.update_state(TextInputState::Masked(masked), false);
+        });
+        self
+    }
+
     pub fn on_cursor_pos(mut self, cursor_pos: impl Fn(Point) + 'static) -> Self {
         self.on_cursor_pos = Some(Box::new(cursor_pos));
         self
@@ -170,9 +186,13 @@ fn set_text_layout(&mut self) {
         if let Some(line_height) = self.line_height {
             attrs = attrs.line_height(line_height);
         }
+        let masked_content;
         text_layout.set_text(
             if self.content.is_empty() {
                 " "
+            } else if self.masked {
+                masked_content = "*".repeat(self.content.len());
+                masked_content.as_str()
             } else {
                 self.content.as_str()
             },
@@ -318,6 +338,10 @@ fn update(
                     self.placeholder = placeholder;
                     self.placeholder_text_layout = None;
                 }
+                TextInputState::Masked(masked) => {
+                    self.masked = masked;
+                    self.text_layout = None;
+                }
             }
             cx.request_layout(self.id);
             ChangeFlags::LAYOUT
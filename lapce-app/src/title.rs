@@ -1,4 +1,8 @@
-use std::sync::Arc;
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use floem::{
     menu::{Menu, MenuItem},
@@ -11,12 +15,14 @@
     views::{container, label, stack, svg, Decorators},
     ViewContext,
 };
+use fluent_bundle::FluentValue;
 use lapce_core::meta;
 
 use crate::{
     app::clickable_icon,
     command::LapceWorkbenchCommand,
     config::{color::LapceColor, icon::LapceIcons, LapceConfig},
+    doc::DocContent,
     listener::Listener,
     main_split::MainSplitData,
     source_control::SourceControlData,
@@ -57,7 +63,10 @@ fn left(
             .on_click(move |_| {
                 #[allow(unused_mut)]
                 let mut menu = Menu::new("").entry(
-                    MenuItem::new("Connect to SSH Host").action(move || {
+                    MenuItem::new(
+                        config.get_untracked().tr("title-connect-ssh-host"),
+                    )
+                    .action(move || {
                         workbench_command
                             .send(LapceWorkbenchCommand::ConnectSshHost);
                     }),
@@ -130,10 +139,12 @@ fn left(
 fn middle(
     workspace: Arc<LapceWorkspace>,
     main_split: MainSplitData,
+    branch: RwSignal<String>,
     workbench_command: Listener<LapceWorkbenchCommand>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
     let local_workspace = workspace.clone();
+    let active_editor = main_split.active_editor;
     let cx = ViewContext::get_current();
     let can_jump_backward = {
         let main_split = main_split.clone();
@@ -177,16 +188,26 @@ fn middle(
             move || {
                 id.show_context_menu(
                     Menu::new("")
-                        .entry(MenuItem::new("Open Folder").action(move || {
-                            workbench_command
-                                .send(LapceWorkbenchCommand::OpenFolder);
-                        }))
-                        .entry(MenuItem::new("Open Recent Workspace").action(
-                            move || {
+                        .entry(
+                            MenuItem::new(
+                                config.get_untracked().tr("title-open-folder"),
+                            )
+                            .action(move || {
+                                workbench_command
+                                    .send(LapceWorkbenchCommand::OpenFolder);
+                            }),
+                        )
+                        .entry(
+                            MenuItem::new(
+                                config
+                                    .get_untracked()
+                                    .tr("title-open-recent-workspace"),
+                            )
+                            .action(move || {
                                 workbench_command
                                     .send(LapceWorkbenchCommand::PaletteWorkspace);
-                            },
-                        )),
+                            }),
+                        ),
                     Point::ZERO,
                 );
             },
@@ -217,10 +238,31 @@ fn middle(
                             },
                         ),
                         label(move || {
-                            if let Some(s) = local_workspace.display() {
+                            let config = config.get();
+                            let file = active_editor.get().and_then(|editor| {
+                                editor.with(|editor| {
+                                    editor.doc.with(|doc| match &doc.content {
+                                        DocContent::File(path) => Some((
+                                            path.clone(),
+                                            !doc.buffer().is_pristine(),
+                                        )),
+                                        DocContent::Local => None,
+                                    })
+                                })
+                            });
+                            let title = window_title(
+                                &config.ui.window_title,
+                                &local_workspace,
+                                &branch.get(),
+                                file.as_ref()
+                                    .map(|(path, dirty)| (path.as_path(), *dirty)),
+                            );
+                            if !title.is_empty() {
+                                title
+                            } else if let Some(s) = local_workspace.display() {
                                 s
                             } else {
-                                "Open Folder".to_string()
+                                config.tr("title-open-folder")
                             }
                         })
                         .style(|| {
@@ -314,7 +356,7 @@ fn right(
                     move || {
                         cx.id.show_context_menu(
                             Menu::new("")
-                                .entry(MenuItem::new("Command Palette").action(
+                                .entry(MenuItem::new(config.get_untracked().tr("title-command-palette")).action(
                                     move || {
                                         workbench_command.send(
                                             LapceWorkbenchCommand::PaletteCommand,
@@ -322,7 +364,7 @@ fn right(
                                     },
                                 ))
                                 .separator()
-                                .entry(MenuItem::new("Open Settings").action(
+                                .entry(MenuItem::new(config.get_untracked().tr("title-open-settings")).action(
                                     move || {
                                         workbench_command.send(
                                             LapceWorkbenchCommand::OpenSettings,
@@ -332,21 +374,29 @@ fn right(
                                 .separator()
                                 .entry(
                                     if let Some(v) = latest_version.get_untracked() {
+                                        let config = config.get_untracked();
+                                        let args = [("version", FluentValue::from(v))];
                                         if update_in_progress.get_untracked() {
-                                            MenuItem::new(format!(
-                                                "Update in progress ({v})"
+                                            MenuItem::new(config.tr_args(
+                                                "title-update-in-progress",
+                                                args,
                                             ))
                                             .enabled(false)
                                         } else {
-                                            MenuItem::new(format!(
-                                                "Restart to update ({v})"
+                                            MenuItem::new(config.tr_args(
+                                                "title-restart-to-update",
+                                                args,
                                             ))
                                             .action(move || {
                                                 workbench_command.send(LapceWorkbenchCommand::RestartToUpdate)
                                             })
                                         }
                                     } else {
-                                        MenuItem::new("No update available")
+                                        MenuItem::new(
+                                            config
+                                                .get_untracked()
+                                                .tr("title-no-update-available"),
+                                        )
                                             .enabled(false)
                                     },
                                 ),
@@ -399,10 +449,11 @@ pub fn title(
     update_in_progress: RwSignal<bool>,
     config: ReadSignal<Arc<LapceConfig>>,
 ) -> impl View {
+    let branch = source_control.branch;
     stack(move || {
         (
             left(source_control, workbench_command, config),
-            middle(workspace, main_split, workbench_command, config),
+            middle(workspace, main_split, branch, workbench_command, config),
             right(
                 workbench_command,
                 latest_release,
@@ -422,3 +473,155 @@ pub fn title(
             .border_color(*config.get_color(LapceColor::LAPCE_BORDER))
     })
 }
+
+/// What the `${separator}` of a template of the window title or a tab label is
+/// filled in with
+const TEMPLATE_SEPARATOR: &str = " - ";
+
+/// Fill in the variables of a template of the window title or a tab label, like
+/// `${file}`. A `${separator}` is only kept between parts which aren't empty.
+pub fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    template
+        .split("${separator}")
+        .map(|part| {
+            vars.iter()
+                .fold(part.to_string(), |part, (name, value)| {
+                    part.replace(&format!("${{{name}}}"), value)
+                })
+                .trim()
+                .to_string()
+        })
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(TEMPLATE_SEPARATOR)
+}
+
+/// The title of the window from the template, with the file of the active
+/// editor and whether it has changes which aren't saved.
+fn window_title(
+    template: &str,
+    workspace: &LapceWorkspace,
+    branch: &str,
+    file: Option<(&Path, bool)>,
+) -> String {
+    let name = workspace.name().unwrap_or_default();
+    let remote = workspace.remote().unwrap_or_default();
+    let (file_name, path, dirty) = match file {
+        Some((path, dirty)) => (
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            relative_path(workspace, path),
+            if dirty { "\u{25cf}" } else { "" },
+        ),
+        None => (String::new(), String::new(), ""),
+    };
+    render_template(
+        template,
+        &[
+            ("workspace", &name),
+            ("remote", &remote),
+            ("branch", branch),
+            ("file", &file_name),
+            ("path", &path),
+            ("dirty", dirty),
+        ],
+    )
+}
+
+/// The path from the folder of the workspace, or the whole path when it's out
+/// of it.
+pub fn relative_path(workspace: &LapceWorkspace, path: &Path) -> String {
+    workspace
+        .path
+        .as_ref()
+        .and_then(|workspace| path.strip_prefix(workspace).ok())
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// The folders at the end of the one of the path which tell it apart from the
+/// other paths with the same file name, which is empty when there are none.
+pub fn distinguishing_dir(path: &Path, others: &[PathBuf]) -> String {
+    let folders = |path: &Path| -> Vec<&OsStr> {
+        path.parent()
+            .map(|parent| parent.iter().collect())
+            .unwrap_or_default()
+    };
+    let others: Vec<Vec<&OsStr>> = others
+        .iter()
+        .filter(|other| {
+            other.as_path() != path && other.file_name() == path.file_name()
+        })
+        .map(|other| folders(other))
+        .collect();
+    if others.is_empty() {
+        return String::new();
+    }
+
+    let dirs = folders(path);
+    // The whole folder is shown when its end doesn't tell it apart
+    for len in 1..dirs.len() {
+        let end = &dirs[dirs.len() - len..];
+        if others.iter().all(|other| !other.ends_with(end)) {
+            return end
+                .iter()
+                .map(|dir| dir.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+        }
+    }
+    path.parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let vars = [
+            ("file", "main.rs"),
+            ("workspace", "lapce"),
+            ("remote", ""),
+            ("dirty", ""),
+        ];
+        assert_eq!(
+            render_template("${dirty} ${file}${separator}${workspace}", &vars),
+            "main.rs - lapce"
+        );
+        assert_eq!(
+            render_template("${workspace}${separator}${remote}", &vars),
+            "lapce"
+        );
+        assert_eq!(
+            render_template("${file}${separator}${dir}${separator}", &[]),
+            "${file} - ${dir}"
+        );
+        assert_eq!(render_template("${dirty}${separator}${dirty}", &vars), "");
+    }
+
+    #[test]
+    fn test_distinguishing_dir() {
+        let paths = vec![
+            PathBuf::from("/work/lapce-app/src/panel/mod.rs"),
+            PathBuf::from("/work/lapce-app/src/mod.rs"),
+            PathBuf::from("/work/lapce-proxy/src/mod.rs"),
+            PathBuf::from("/work/lapce-app/src/main.rs"),
+        ];
+        assert_eq!(distinguishing_dir(&paths[0], &paths), "panel");
+        assert_eq!(distinguishing_dir(&paths[1], &paths), "lapce-app/src");
+        assert_eq!(distinguishing_dir(&paths[2], &paths), "lapce-proxy/src");
+        assert_eq!(distinguishing_dir(&paths[3], &paths), "");
+
+        let nested = vec![
+            PathBuf::from("/a/src/lib.rs"),
+            PathBuf::from("/b/a/src/lib.rs"),
+        ];
+        assert_eq!(distinguishing_dir(&nested[0], &nested), "/a/src");
+        assert_eq!(distinguishing_dir(&nested[1], &nested), "b/a/src");
+    }
+}
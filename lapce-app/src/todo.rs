@@ -0,0 +1,354 @@
+//! The comments of the workspace which start with one of the tags, like TODO or
+//! FIXME, which the proxy searches for in the background and again in the files
+//! which the file watcher sees change.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{
+        create_effect, create_memo, create_rw_signal, Memo, RwSignal, Scope,
+        SignalGet, SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
+    },
+};
+use indexmap::IndexMap;
+use lapce_rpc::{
+    proxy::{ProxyResponse, SearchMatch},
+    RpcError,
+};
+
+use crate::window_tab::CommonData;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TodoItem {
+    /// The line of the comment, counted from zero
+    pub line: usize,
+    pub tag: String,
+    /// The text of the comment after the tag
+    pub text: String,
+}
+
+/// A row of the tree of the TODO panel, which has the files, the tags of the
+/// comments of each file and then the comments.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TodoNode {
+    File {
+        path: PathBuf,
+        count: usize,
+    },
+    Tag {
+        path: PathBuf,
+        tag: String,
+        count: usize,
+    },
+    Item {
+        path: PathBuf,
+        item: TodoItem,
+    },
+}
+
+#[derive(Clone)]
+pub struct TodoData {
+    /// The comments of the files which have any, sorted by path
+    pub files: RwSignal<IndexMap<PathBuf, Vec<TodoItem>>>,
+    /// The files whose comments are folded
+    pub collapsed: RwSignal<HashSet<PathBuf>>,
+    /// Whether the whole workspace is being searched
+    pub scanning: RwSignal<bool>,
+    pub tags: Memo<Vec<String>>,
+    /// Bumped for each search of the whole workspace, so that the answers to the
+    /// older ones are dropped
+    scan_id: RwSignal<usize>,
+    common: CommonData,
+}
+
+impl TodoData {
+    pub fn new(cx: Scope, common: CommonData) -> Self {
+        let config = common.config;
+        let tags = create_memo(cx, move |_| {
+            config.with(|config| config.core.todo_tags.clone())
+        });
+        let todo = Self {
+            files: create_rw_signal(cx, IndexMap::new()),
+            collapsed: create_rw_signal(cx, HashSet::new()),
+            scanning: create_rw_signal(cx, false),
+            tags,
+            scan_id: create_rw_signal(cx, 0),
+            common,
+        };
+
+        {
+            let todo = todo.clone();
+            create_effect(cx, move |_| {
+                let tags = todo.tags.get();
+                todo.scan(tags);
+            });
+        }
+
+        todo
+    }
+
+    /// Search the whole workspace for the comments of the tags.
+    fn scan(&self, tags: Vec<String>) {
+        let scan_id = self.scan_id.get_untracked() + 1;
+        self.scan_id.set(scan_id);
+        self.scanning.set(true);
+
+        let todo = self.clone();
+        let search_tags = tags.clone();
+        let send = create_ext_action(
+            self.common.scope,
+            move |result: Result<ProxyResponse, RpcError>| {
+                if todo.scan_id.get_untracked() != scan_id {
+                    return;
+                }
+                todo.scanning.set(false);
+                if let Ok(ProxyResponse::GlobalSearchResponse { matches }) = result {
+                    let mut files: IndexMap<PathBuf, Vec<TodoItem>> = matches
+                        .into_iter()
+                        .map(|(path, matches)| (path, todo_items(&tags, &matches)))
+                        .filter(|(_, items)| !items.is_empty())
+                        .collect();
+                    files.sort_keys();
+                    todo.files.set(files);
+                }
+            },
+        );
+        self.common
+            .proxy
+            .todo_search(search_tags, None, move |result| {
+                send(result);
+            });
+    }
+
+    /// Search the files of the workspace which changed again, where a path can
+    /// be a folder, or one which was removed.
+    pub fn files_changed(&self, paths: Vec<PathBuf>) {
+        let tags = self.tags.get_untracked();
+        if tags.is_empty() {
+            return;
+        }
+
+        let todo = self.clone();
+        let search_tags = tags.clone();
+        let changed = paths.clone();
+        let send = create_ext_action(
+            self.common.scope,
+            move |result: Result<ProxyResponse, RpcError>| {
+                if let Ok(ProxyResponse::GlobalSearchResponse { matches }) = result {
+                    todo.files.update(|files| {
+                        files.retain(|file, _| {
+                            !changed.iter().any(|path| file.starts_with(path))
+                        });
+                        for (path, matches) in matches {
+                            let items = todo_items(&tags, &matches);
+                            if !items.is_empty() {
+                                files.insert(path, items);
+                            }
+                        }
+                        files.sort_keys();
+                    });
+                }
+            },
+        );
+        self.common
+            .proxy
+            .todo_search(search_tags, Some(paths), move |result| {
+                send(result);
+            });
+    }
+
+    pub fn toggle_file(&self, path: &Path) {
+        self.collapsed.update(|collapsed| {
+            if !collapsed.remove(path) {
+                collapsed.insert(path.to_path_buf());
+            }
+        });
+    }
+
+    /// The rows of the tree of the comments.
+    pub fn tree(&self) -> Vec<TodoNode> {
+        let tags = self.tags.get();
+        let collapsed = self.collapsed.get();
+        self.files.with(|files| todo_tree(files, &tags, &collapsed))
+    }
+}
+
+/// The comments of the matches of the search of the tags, without the ones
+/// which don't start with one of them.
+pub fn todo_items(tags: &[String], matches: &[SearchMatch]) -> Vec<TodoItem> {
+    matches
+        .iter()
+        .filter_map(|m| {
+            let matched = m.line_content.get(m.start..m.end)?;
+            let tag = tags.iter().find(|tag| matched.ends_with(tag.as_str()))?;
+            let text = m.line_content.get(m.end..).unwrap_or("").trim();
+            let text = text
+                .strip_suffix("*/")
+                .or_else(|| text.strip_suffix("-->"))
+                .unwrap_or(text)
+                .trim_start_matches(|c: char| c == ':' || c.is_whitespace())
+                .trim_end();
+            Some(TodoItem {
+                line: m.line.saturating_sub(1),
+                tag: tag.clone(),
+                text: text.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The rows of the files, with the tags in the order of the settings under the
+/// ones which aren't folded, and the comments of each tag by line.
+pub fn todo_tree(
+    files: &IndexMap<PathBuf, Vec<TodoItem>>,
+    tags: &[String],
+    collapsed: &HashSet<PathBuf>,
+) -> Vec<TodoNode> {
+    let mut nodes = Vec::new();
+    for (path, items) in files {
+        nodes.push(TodoNode::File {
+            path: path.clone(),
+            count: items.len(),
+        });
+        if collapsed.contains(path) {
+            continue;
+        }
+
+        // The tags which were taken out of the settings are left at the end
+        // until the workspace is searched again
+        let mut groups: IndexMap<&str, Vec<&TodoItem>> =
+            tags.iter().map(|tag| (tag.as_str(), Vec::new())).collect();
+        for item in items {
+            groups.entry(item.tag.as_str()).or_default().push(item);
+        }
+        for (tag, mut items) in groups {
+            if items.is_empty() {
+                continue;
+            }
+            items.sort_by_key(|item| item.line);
+            nodes.push(TodoNode::Tag {
+                path: path.clone(),
+                tag: tag.to_string(),
+                count: items.len(),
+            });
+            nodes.extend(items.into_iter().map(|item| TodoNode::Item {
+                path: path.clone(),
+                item: item.clone(),
+            }));
+        }
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_match(line: usize, content: &str, matched: &str) -> SearchMatch {
+        let start = content.find(matched).unwrap();
+        SearchMatch {
+            line,
+            start,
+            end: start + matched.len(),
+            line_content: content.to_string(),
+        }
+    }
+
+    fn item(line: usize, tag: &str, text: &str) -> TodoItem {
+        TodoItem {
+            line,
+            tag: tag.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_todo_items() {
+        let tags = vec!["TODO".to_string(), "FIXME".to_string()];
+        let matches = [
+            search_match(3, "    // TODO: remove this", "// TODO"),
+            search_match(10, "/* FIXME the rest */", "/* FIXME"),
+            search_match(12, "<!-- TODO -->", "<!-- TODO"),
+            search_match(20, "# HACK: gone from the tags", "# HACK"),
+        ];
+        assert_eq!(
+            todo_items(&tags, &matches),
+            vec![
+                item(2, "TODO", "remove this"),
+                item(9, "FIXME", "the rest"),
+                item(11, "TODO", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_todo_tree() {
+        let tags = vec!["TODO".to_string(), "FIXME".to_string()];
+        let a = PathBuf::from("/a.rs");
+        let b = PathBuf::from("/b.rs");
+        let mut files = IndexMap::new();
+        files.insert(
+            a.clone(),
+            vec![
+                item(8, "FIXME", "second"),
+                item(5, "TODO", "first"),
+                item(2, "FIXME", "first"),
+            ],
+        );
+        files.insert(b.clone(), vec![item(0, "HACK", "old")]);
+
+        let tree = todo_tree(&files, &tags, &HashSet::new());
+        assert_eq!(
+            tree,
+            vec![
+                TodoNode::File {
+                    path: a.clone(),
+                    count: 3,
+                },
+                TodoNode::Tag {
+                    path: a.clone(),
+                    tag: "TODO".to_string(),
+                    count: 1,
+                },
+                TodoNode::Item {
+                    path: a.clone(),
+                    item: item(5, "TODO", "first"),
+                },
+                TodoNode::Tag {
+                    path: a.clone(),
+                    tag: "FIXME".to_string(),
+                    count: 2,
+                },
+                TodoNode::Item {
+                    path: a.clone(),
+                    item: item(2, "FIXME", "first"),
+                },
+                TodoNode::Item {
+                    path: a.clone(),
+                    item: item(8, "FIXME", "second"),
+                },
+                TodoNode::File {
+                    path: b.clone(),
+                    count: 1,
+                },
+                TodoNode::Tag {
+                    path: b.clone(),
+                    tag: "HACK".to_string(),
+                    count: 1,
+                },
+                TodoNode::Item {
+                    path: b.clone(),
+                    item: item(0, "HACK", "old"),
+                },
+            ]
+        );
+
+        let tree = todo_tree(&files, &tags, &HashSet::from([a.clone()]));
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree[0], TodoNode::File { path: a, count: 3 });
+    }
+}
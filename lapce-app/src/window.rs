@@ -1,19 +1,27 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use floem::{
     glazier::KeyEvent,
     peniko::kurbo::{Point, Size},
     reactive::{
-        create_rw_signal, use_context, ReadSignal, RwSignal, Scope,
-        SignalGetUntracked, SignalSet, SignalUpdate, SignalWithUntracked,
+        create_effect, create_rw_signal, use_context, ReadSignal, RwSignal, Scope,
+        SignalGet, SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
+        SignalWithUntracked,
     },
 };
+use lapce_rpc::file::PathObject;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    app::AppCommand, command::WindowCommand, config::LapceConfig, db::LapceDb,
-    listener::Listener, update::ReleaseInfo, window_tab::WindowTabData,
-    workspace::LapceWorkspace,
+    accessibility::AccessibilityData,
+    app::AppCommand,
+    command::WindowCommand,
+    config::{ui::ZoomMode, LapceConfig},
+    db::LapceDb,
+    listener::Listener,
+    update::ReleaseInfo,
+    window_tab::WindowTabData,
+    workspace::{LapceWorkspace, LapceWorkspaceType},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +63,7 @@ pub struct WindowData {
     pub window_scale: RwSignal<f64>,
     pub latest_release: ReadSignal<Arc<Option<ReleaseInfo>>>,
     pub config: RwSignal<Arc<LapceConfig>>,
+    pub accessibility: AccessibilityData,
 }
 
 impl WindowData {
@@ -100,6 +109,7 @@ pub fn new(
         let active = create_rw_signal(cx, active);
         let size = create_rw_signal(cx, Size::ZERO);
         let position = create_rw_signal(cx, info.pos);
+        let accessibility = AccessibilityData::new(cx, window_tabs, active);
 
         let window_data = Self {
             scope: cx,
@@ -113,8 +123,30 @@ pub fn new(
             latest_release,
             app_command,
             config,
+            accessibility,
         };
 
+        // The whole window is scaled by the zoom of the active workspace tab,
+        // unless only the editor fonts are zoomed
+        create_effect(cx, move |_| {
+            let active = active.get();
+            let Some(window_tab) = window_tabs.with(|window_tabs| {
+                window_tabs
+                    .get(active.min(window_tabs.len().saturating_sub(1)))
+                    .map(|(_, window_tab)| window_tab.clone())
+            }) else {
+                return;
+            };
+            let zoom_mode =
+                window_tab.common.config.with(|config| config.ui.zoom_mode);
+            let zoom = window_tab.zoom.get();
+            window_scale.set(if zoom_mode == ZoomMode::Window {
+                zoom
+            } else {
+                1.0
+            });
+        });
+
         {
             let window_data = window_data.clone();
             window_data.window_command.listen(move |cmd| {
@@ -287,6 +319,61 @@ pub fn info(&self) -> WindowInfo {
         }
     }
 
+    /// Open the files and folders which were dropped onto the window. The files
+    /// open as editor tabs, and a single folder is asked about in the palette,
+    /// as it can be the workspace of the active window tab or of a new one.
+    /// Since they're on this machine, they get a new window tab of their own
+    /// when the workspace of the active one is remote.
+    pub fn drop_paths(&self, paths: Vec<PathBuf>) {
+        if paths.is_empty() {
+            return;
+        }
+        let Some(window_tab) = self.active_window_tab() else {
+            return;
+        };
+        let (folders, files): (Vec<PathObject>, Vec<PathObject>) = paths
+            .into_iter()
+            .map(|path| {
+                let is_dir = path.is_dir();
+                PathObject::from_path(path, is_dir)
+            })
+            .partition(|p| p.is_dir);
+        let new_tab = |path: Option<PathBuf>| WindowCommand::NewWorkspaceTab {
+            workspace: LapceWorkspace {
+                kind: LapceWorkspaceType::Local,
+                path,
+                last_open: 0,
+            },
+            end: false,
+        };
+
+        if window_tab.workspace.kind.is_remote() {
+            let mut folders = folders.into_iter();
+            self.run_window_command(new_tab(folders.next().map(|f| f.path)));
+            if let Some(window_tab) = self.active_window_tab() {
+                window_tab.open_paths(&files);
+            }
+            for folder in folders {
+                self.run_window_command(new_tab(Some(folder.path)));
+            }
+            return;
+        }
+
+        window_tab.open_paths(&files);
+        match <[PathObject; 1]>::try_from(folders) {
+            Ok([folder]) => {
+                window_tab
+                    .palette
+                    .run_dropped_folder(window_tab.scope, folder.path);
+            }
+            Err(folders) => {
+                for folder in folders {
+                    self.run_window_command(new_tab(Some(folder.path)));
+                }
+            }
+        }
+    }
+
     pub fn active_window_tab(&self) -> Option<Arc<WindowTabData>> {
         let window_tabs = self.window_tabs.get_untracked();
         let active = self
@@ -1,46 +1,107 @@
-use std::{collections::HashSet, env, path::Path, sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crossbeam_channel::Sender;
 use floem::{
     cosmic_text::{Attrs, AttrsList, FamilyOwned, LineHeightValue, TextLayout},
     ext_event::{create_ext_action, create_signal_from_channel},
-    glazier::{FileDialogOptions, KeyEvent, Modifiers},
-    peniko::kurbo::{Point, Rect, Vec2},
+    glazier::{FileDialogOptions, FileSpec, KeyEvent, Modifiers},
+    peniko::kurbo::{Point, Rect, Size, Vec2},
     reactive::{
-        create_effect, create_memo, create_rw_signal, create_signal, use_context,
-        Memo, ReadSignal, RwSignal, Scope, SignalGet, SignalGetUntracked, SignalSet,
-        SignalUpdate, SignalWith, SignalWithUntracked, WriteSignal,
+        create_effect, create_memo, create_rw_signal, create_signal, on_cleanup,
+        use_context, Memo, ReadSignal, RwSignal, Scope, SignalGet,
+        SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
+        SignalWithUntracked, WriteSignal,
     },
 };
+use fluent_bundle::FluentValue;
 use itertools::Itertools;
-use lapce_core::{directory::Directory, meta, mode::Mode, register::Register};
+use lapce_core::{
+    buffer::rope_text::RopeText,
+    directory::Directory,
+    meta,
+    mode::Mode,
+    register::{Clipboard, Register},
+};
 use lapce_rpc::{
-    core::CoreNotification, dap_types::RunDebugConfig, file::PathObject,
-    proxy::ProxyRpcHandler, source_control::FileDiff, terminal::TermId,
+    core::CoreNotification,
+    coverage::Coverage,
+    crash::CrashReport,
+    dap_types::{
+        DapId, DapRequestTimeouts, RunDebugConfig, RunDebugConfigMode, StackFrame,
+        Stopped, ThreadId,
+    },
+    file::PathObject,
+    http::is_http_file,
+    plugin::{StatusItemAlignment, VoltID},
+    proxy::{ProxyResponse, ProxyRpcHandler},
+    runnable::Runnable,
+    source_control::FileDiff,
+    terminal::TermId,
+    RpcError,
 };
+use lapce_xi_rope::Rope;
+use lsp_types::{CodeActionKind, Url};
 use serde_json::Value;
 use tracing::{debug, error};
 
 use crate::{
-    code_action::{CodeActionData, CodeActionStatus},
+    clipboard::ClipboardHistory,
+    code_action::{CodeActionData, CodeActionStatus, SOURCE_REMOVE_UNUSED_IMPORTS},
+    collab::{
+        connection::CollabEvent,
+        crdt::{CharId, CrdtOp},
+        is_guest_path,
+        protocol::{
+            CollabMessage, Invite, Participant, ParticipantId, Permissions,
+            SharedTerminal, HOST,
+        },
+        CollabCursor, CollabData, CollabPing, CollabRole,
+    },
+    color_picker::ColorPickerData,
     command::{
         CommandExecuted, CommandKind, InternalCommand, LapceCommand,
         LapceWorkbenchCommand, WindowCommand,
     },
-    completion::{CompletionData, CompletionStatus},
-    config::LapceConfig,
+    completion::CompletionData,
+    config::{
+        color::LapceColor, theme_import::ImportedTheme, ui::ZoomMode, LapceConfig,
+    },
     db::LapceDb,
-    debug::{DapData, RunDebugMode, RunDebugProcess},
-    doc::{DocContent, EditorDiagnostic},
+    debug::{
+        inline_values, DapData, LapceBreakpoint, RunDebugMode, RunDebugProcess,
+    },
+    debug_console::DebugConsoleData,
+    debug_memory::{parse_hex_bytes, MemoryViewData},
+    doc::{
+        html::{render_html, HtmlOptions},
+        memory::MemoryReport,
+        DocContent, Document, EditorDiagnostic, SystemClipboard,
+    },
+    edit_preview::EditPreviewData,
     editor::location::{EditorLocation, EditorPosition},
     editor_tab::EditorTabChild,
     file_explorer::data::FileExplorerData,
     find::Find,
     global_search::GlobalSearchData,
+    hover::HoverData,
+    http_client::HttpClientData,
     id::WindowTabId,
-    keypress::{condition::Condition, KeyPressData, KeyPressFocus},
+    keypress::{
+        condition::{mode_matches, Condition, ContextKey},
+        KeyPressData, KeyPressFocus,
+    },
     listener::Listener,
+    log::LogData,
     main_split::{MainSplitData, SplitData, SplitDirection},
+    notification::{
+        Notification, NotificationCommand, NotificationData, NotificationSeverity,
+    },
     palette::{kind::PaletteKind, PaletteData, PaletteStatus},
     panel::{
         data::{default_panel_order, PanelData},
@@ -48,23 +109,52 @@
         position::PanelContainerPosition,
     },
     plugin::PluginData,
-    proxy::{path_from_url, start_proxy, ProxyData},
+    popup::{stack_popups, PopupData, PopupKind, PopupSide},
+    profile::Profile,
+    proxy::{
+        askpass::{SshPrompt, SshPromptKind},
+        path_from_url, start_proxy, ProxyData,
+    },
     rename::RenameData,
-    source_control::SourceControlData,
+    signature::SignatureHelpData,
+    source_control::{git_permalink, SourceControlData},
+    startup,
+    status::{plugin_item_id, StatusData, StatusItem},
     terminal::{
         event::{terminal_update_process, TermEvent, TermNotification},
         panel::TerminalPanelData,
     },
+    test_explorer::{TestExplorerData, TestStatus},
+    todo::TodoData,
     update::ReleaseInfo,
     workspace::{LapceWorkspace, LapceWorkspaceType, WorkspaceInfo},
 };
 
+/// Where the crashes of the proxy are reported, when the report of one is sent
+const CRASH_REPORT_URL: &str = "https://github.com/lapce/lapce/issues/new";
+
+/// How many bytes of the backtrace of a crash go into the url of its report,
+/// which is cut short to keep the url in the length that browsers and GitHub
+/// take. The whole report is put into the clipboard.
+const CRASH_REPORT_BACKTRACE_LEN: usize = 2000;
+
+/// The id of the status item which is shown while a macro is recorded
+const MACRO_STATUS_ITEM: &str = "macro-recording";
+
+/// The id of the status item which is shown during a collaboration session
+const COLLAB_STATUS_ITEM: &str = "collaboration";
+
+/// How long a range which a participant pinged stays highlighted
+const COLLAB_PING_DURATION: Duration = Duration::from_secs(3);
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Focus {
     Workbench,
     Palette,
     CodeAction,
     Rename,
+    ColorPicker,
+    EditPreview,
     Panel(PanelKind),
 }
 
@@ -87,6 +177,11 @@ pub struct CommonData {
     pub focus: RwSignal<Focus>,
     pub keypress: RwSignal<KeyPressData>,
     pub completion: RwSignal<CompletionData>,
+    /// The hover of the editors, and the hovers which were pinned
+    pub hover: HoverData,
+    pub signature: SignatureHelpData,
+    /// Which of the completion, the signature and the hover is in front
+    pub popups: PopupData,
     pub register: RwSignal<Register>,
     pub find: Find,
     pub window_command: Listener<WindowCommand>,
@@ -99,6 +194,29 @@ pub struct CommonData {
     pub view_id: RwSignal<floem::id::Id>,
     pub ui_line_height: Memo<f64>,
     pub dragging: RwSignal<Option<DragContent>>,
+    pub clipboard_history: ClipboardHistory,
+    /// The search which the settings editor is given next, such as from a
+    /// `lapce://settings/` link
+    pub settings_search: RwSignal<Option<String>>,
+    /// The results of the test explorer by test, which the gutter shows too
+    pub test_results: RwSignal<im::HashMap<String, TestStatus>>,
+    /// The coverage which the gutter and the file explorer show
+    pub coverage: RwSignal<Coverage>,
+    /// The breakpoints by file, which the gutter shows and the debug sessions
+    /// are sent
+    pub breakpoints: RwSignal<BTreeMap<PathBuf, Vec<LapceBreakpoint>>>,
+    /// Whether the active debug session is stopped and can move its execution
+    /// to another line, which the gutter offers then
+    pub debug_can_jump: RwSignal<bool>,
+    /// Whether the active debug session is running, while which the gutter
+    /// shows the breakpoints that it couldn't set as hollow
+    pub debugging: RwSignal<bool>,
+    /// The file and the line of the frame which the active debug session shows,
+    /// which the editor highlights
+    pub debug_line: RwSignal<Option<(PathBuf, usize)>>,
+    /// The collaboration session which the workspace is shared in, or which is
+    /// joined from it
+    pub collab: CollabData,
     pub config: ReadSignal<Arc<LapceConfig>>,
 }
 
@@ -113,14 +231,29 @@ pub struct WindowTabData {
     pub panel: PanelData,
     pub terminal: TerminalPanelData,
     pub plugin: PluginData,
+    pub notification: NotificationData,
+    pub log: LogData,
+    pub test_explorer: TestExplorerData,
+    pub http_client: HttpClientData,
+    pub status: StatusData,
     pub code_action: RwSignal<CodeActionData>,
     pub source_control: SourceControlData,
     pub rename: RenameData,
+    pub color_picker: ColorPickerData,
+    pub edit_preview: EditPreviewData,
+    /// The memory usage which is shown, until the view of it is closed
+    pub memory_usage: RwSignal<Option<MemoryReport>>,
     pub global_search: GlobalSearchData,
+    pub debug_console: DebugConsoleData,
+    pub debug_memory: MemoryViewData,
+    pub todo: TodoData,
     pub window_origin: RwSignal<Point>,
     pub layout_rect: RwSignal<Rect>,
     pub proxy: ProxyData,
     pub window_scale: RwSignal<f64>,
+    /// The zoom level of the workspace, which scales either the whole window or
+    /// the editor fonts, depending on `ui.zoom-mode`
+    pub zoom: RwSignal<f64>,
     pub set_config: WriteSignal<Arc<LapceConfig>>,
     pub update_in_progress: RwSignal<bool>,
     pub latest_release: ReadSignal<Arc<Option<ReleaseInfo>>>,
@@ -133,12 +266,20 @@ fn get_mode(&self) -> Mode {
     }
 
     fn check_condition(&self, condition: Condition) -> bool {
-        if let Condition::PanelFocus = condition {
-            if let Focus::Panel(_) = self.common.focus.get_untracked() {
-                return true;
+        match condition {
+            Condition::PanelFocus => {
+                matches!(self.common.focus.get_untracked(), Focus::Panel(_))
             }
+            Condition::ModalEditing => self.common.config.get_untracked().core.modal,
+            _ => false,
+        }
+    }
+
+    fn check_context(&self, key: ContextKey, value: &str) -> bool {
+        match key {
+            ContextKey::Mode => mode_matches(self.get_mode(), value),
+            ContextKey::LanguageId | ContextKey::PanelVisible => false,
         }
-        false
     }
 
     fn run_command(
@@ -181,12 +322,18 @@ pub fn new(
             info
         };
 
-        let config = LapceConfig::load(&workspace, &all_disabled_volts);
+        let zoom = workspace_info.as_ref().map(|info| info.zoom).unwrap_or(1.0);
+        let mut config = LapceConfig::load(&workspace, &all_disabled_volts);
+        config.apply_zoom(zoom);
+        startup::phase("workspace config");
+        if let Some(profile) = config.profile.as_ref() {
+            all_disabled_volts.extend(profile.info.disabled_volts.iter().cloned());
+        }
         let lapce_command = Listener::new_empty(cx);
         let workbench_command = Listener::new_empty(cx);
         let internal_command = Listener::new_empty(cx);
         let keypress =
-            create_rw_signal(cx, KeyPressData::new(&config, workbench_command));
+            create_rw_signal(cx, KeyPressData::new(cx, &config, workbench_command));
 
         let (term_tx, term_rx) = crossbeam_channel::unbounded();
         let (term_notification_tx, term_notification_rx) =
@@ -198,17 +345,32 @@ pub fn new(
             });
         }
 
+        let (ssh_prompt_tx, ssh_prompt_rx) = crossbeam_channel::unbounded();
+
+        let collab = CollabData::new(cx, workspace.clone());
         let proxy = start_proxy(
             cx,
             workspace.clone(),
             all_disabled_volts,
             config.plugins.clone(),
             term_tx.clone(),
+            collab.shared_terminals.clone(),
+            ssh_prompt_tx,
+            config.core.ssh_forward_agent,
+            config.core.resource_limits(),
         );
+        let window_tab_id = WindowTabId::next();
+        crate::log::set_log_levels(window_tab_id, &config.core.log_levels);
+        on_cleanup(cx, move || crate::log::remove_log_levels(window_tab_id));
+        proxy
+            .proxy_rpc
+            .update_log_levels(config.core.log_levels.clone());
         let (config, set_config) = create_signal(cx, Arc::new(config));
 
         let focus = create_rw_signal(cx, Focus::Workbench);
         let completion = create_rw_signal(cx, CompletionData::new(cx, config));
+        let hover = HoverData::new(cx);
+        let signature = SignatureHelpData::new(cx);
 
         let register = create_rw_signal(cx, Register::default());
         let view_id = create_rw_signal(cx, floem::id::Id::next());
@@ -235,6 +397,9 @@ pub fn new(
             keypress,
             focus,
             completion,
+            hover,
+            signature,
+            popups: PopupData::new(cx, completion, signature, hover),
             register,
             find,
             window_command,
@@ -247,6 +412,15 @@ pub fn new(
             view_id,
             ui_line_height,
             dragging: create_rw_signal(cx, None),
+            clipboard_history: ClipboardHistory::new(cx, &config.get_untracked()),
+            settings_search: create_rw_signal(cx, None),
+            test_results: create_rw_signal(cx, im::HashMap::new()),
+            coverage: create_rw_signal(cx, Coverage::default()),
+            breakpoints: create_rw_signal(cx, BTreeMap::new()),
+            debug_can_jump: create_rw_signal(cx, false),
+            debugging: create_rw_signal(cx, false),
+            debug_line: create_rw_signal(cx, None),
+            collab,
             config,
         };
 
@@ -310,10 +484,34 @@ pub fn new(
 
         let terminal =
             TerminalPanelData::new(workspace.clone(), None, common.clone());
+        if let Some(info) = workspace_info.as_ref() {
+            if !info.watches.is_empty() {
+                terminal
+                    .debug
+                    .watches
+                    .set(info.watches.iter().cloned().collect());
+                common.proxy.dap_set_watches(info.watches.clone());
+            }
+            if !info.exception_filters.is_empty() {
+                terminal
+                    .debug
+                    .exception_filters
+                    .set(info.exception_filters.clone());
+                common.proxy.dap_set_exception_filters(
+                    info.exception_filters.clone().into_iter().collect(),
+                );
+            }
+        }
 
         let rename = RenameData::new(cx, common.clone());
+        let color_picker = ColorPickerData::new(cx, common.clone());
+        let edit_preview = EditPreviewData::new(cx, common.clone());
         let global_search =
             GlobalSearchData::new(cx, main_split.clone(), common.clone());
+        let debug_console =
+            DebugConsoleData::new(cx, terminal.clone(), common.clone());
+        let debug_memory = MemoryViewData::new(terminal.clone());
+        let todo = TodoData::new(cx, common.clone());
 
         let plugin = PluginData::new(
             cx,
@@ -322,6 +520,13 @@ pub fn new(
             common.clone(),
         );
 
+        let notification = NotificationData::new(cx);
+        let log = LogData::new(cx, common.clone());
+        let test_explorer =
+            TestExplorerData::new(cx, main_split.docs, common.clone());
+        let http_client = HttpClientData::new(cx, common.clone());
+        let status = StatusData::new(cx);
+
         {
             let notification = create_signal_from_channel(cx, term_notification_rx);
             let terminal = terminal.clone();
@@ -343,7 +548,7 @@ pub fn new(
 
         let window_tab_data = Self {
             scope: cx,
-            window_tab_id: WindowTabId::next(),
+            window_tab_id,
             workspace,
             palette,
             main_split,
@@ -353,12 +558,24 @@ pub fn new(
             code_action,
             source_control,
             plugin,
+            notification,
+            log,
+            test_explorer,
+            http_client,
+            status,
             rename,
+            color_picker,
+            edit_preview,
+            memory_usage: create_rw_signal(cx, None),
             global_search,
+            debug_console,
+            debug_memory,
+            todo,
             window_origin: create_rw_signal(cx, Point::ZERO),
             layout_rect: create_rw_signal(cx, Rect::ZERO),
             proxy,
             window_scale,
+            zoom: create_rw_signal(cx, zoom),
             set_config,
             update_in_progress: create_rw_signal(cx, false),
             latest_release,
@@ -399,9 +616,287 @@ pub fn new(
             });
         }
 
+        {
+            let status = window_tab_data.status;
+            let config = window_tab_data.common.config;
+            let macros =
+                window_tab_data.common.keypress.with_untracked(|k| k.macros);
+            create_effect(cx, move |_| {
+                if macros.with(|macros| macros.is_recording()) {
+                    status.set_item(StatusItem {
+                        id: MACRO_STATUS_ITEM.to_string(),
+                        text: config.get_untracked().tr("status-recording-macro"),
+                        alignment: StatusItemAlignment::Left,
+                        priority: 0,
+                        command: Some(LapceWorkbenchCommand::ToggleMacroRecording),
+                    });
+                } else {
+                    status.remove_item(MACRO_STATUS_ITEM);
+                }
+            });
+        }
+
+        {
+            let panel = window_tab_data.panel.clone();
+            let visible_panels = window_tab_data
+                .common
+                .keypress
+                .with_untracked(|k| k.visible_panels);
+            create_effect(cx, move |_| {
+                visible_panels.set(panel.visible_panels());
+            });
+        }
+
+        {
+            let terminal = window_tab_data.terminal.clone();
+            let debug_can_jump = window_tab_data.common.debug_can_jump;
+            create_effect(cx, move |_| {
+                let can_jump = terminal.get_active_dap(true).map_or(false, |dap| {
+                    dap.stopped.get() && dap.supports_goto_targets.get()
+                });
+                if can_jump != debug_can_jump.get_untracked() {
+                    debug_can_jump.set(can_jump);
+                }
+            });
+        }
+
+        {
+            let terminal = window_tab_data.terminal.clone();
+            let debugging = window_tab_data.common.debugging;
+            create_effect(cx, move |_| {
+                let running = terminal
+                    .debug
+                    .active_term
+                    .get()
+                    .and_then(|term_id| terminal.get_terminal(&term_id))
+                    .map_or(false, |terminal| {
+                        terminal.run_debug.with(|run_debug| {
+                            run_debug.as_ref().map_or(false, |run_debug| {
+                                run_debug.mode == RunDebugMode::Debug
+                                    && !run_debug.stopped
+                            })
+                        })
+                    });
+                if running != debugging.get_untracked() {
+                    debugging.set(running);
+                }
+            });
+        }
+
+        {
+            let terminal = window_tab_data.terminal.clone();
+            let debug_line = window_tab_data.common.debug_line;
+            create_effect(cx, move |_| {
+                let line = terminal
+                    .get_active_dap(true)
+                    .and_then(|dap| dap.frame_location());
+                if line != debug_line.get_untracked() {
+                    debug_line.set(line);
+                }
+            });
+        }
+
+        {
+            let config = window_tab_data.common.config;
+            let proxy = window_tab_data.common.proxy.clone();
+            let timeouts = create_memo(cx, move |_| {
+                config.with(|config| DapRequestTimeouts {
+                    default: config.core.dap_request_timeout,
+                    commands: config.core.dap_request_timeouts.clone(),
+                })
+            });
+            create_effect(cx, move |_| {
+                proxy.dap_set_request_timeouts(timeouts.get());
+            });
+        }
+
+        {
+            let config = window_tab_data.common.config;
+            let proxy = window_tab_data.common.proxy.clone();
+            let limits = create_memo(cx, move |_| {
+                config.with(|config| config.core.resource_limits())
+            });
+            // The proxy is started with the limits, which are only sent again
+            // when they change
+            create_effect(cx, move |started: Option<()>| {
+                let limits = limits.get();
+                if started.is_some() {
+                    proxy.update_resource_limits(limits);
+                }
+            });
+        }
+
+        {
+            let window_tab_data = window_tab_data.clone();
+            let prompt = create_signal_from_channel(cx, ssh_prompt_rx);
+            create_effect(cx, move |_| {
+                prompt.with(|prompt| {
+                    if let Some(prompt) = prompt.as_ref() {
+                        window_tab_data.handle_ssh_prompt(prompt);
+                    }
+                });
+            });
+        }
+
+        {
+            let window_tab_data = window_tab_data.clone();
+            let event = window_tab_data.common.collab.event;
+            create_effect(cx, move |_| {
+                event.with(|event| {
+                    if let Some(event) = event.as_ref() {
+                        window_tab_data.handle_collab_event(event);
+                    }
+                });
+            });
+        }
+
+        {
+            let status = window_tab_data.status;
+            let config = window_tab_data.common.config;
+            let session = window_tab_data.common.collab.session;
+            create_effect(cx, move |_| {
+                let text = session.with(|session| {
+                    let session = session.as_ref()?;
+                    let config = config.get_untracked();
+                    Some(if session.is_host() {
+                        config.tr_args(
+                            "status-collab-hosting",
+                            [(
+                                "count",
+                                FluentValue::from(session.participants.len() - 1),
+                            )],
+                        )
+                    } else {
+                        let host = session
+                            .participant(HOST)
+                            .map(|p| p.name.clone())
+                            .unwrap_or_default();
+                        config.tr_args(
+                            "status-collab-joined",
+                            [("host", FluentValue::from(host))],
+                        )
+                    })
+                });
+                match text {
+                    Some(text) => status.set_item(StatusItem {
+                        id: COLLAB_STATUS_ITEM.to_string(),
+                        text,
+                        alignment: StatusItemAlignment::Left,
+                        priority: 0,
+                        command: Some(
+                            LapceWorkbenchCommand::ToggleCollaborationVisual,
+                        ),
+                    }),
+                    None => status.remove_item(COLLAB_STATUS_ITEM),
+                }
+            });
+        }
+
+        // The cursor of the active editor, what it selects and which lines it
+        // shows are sent to the others in the collaboration session
+        {
+            let collab = window_tab_data.common.collab.clone();
+            let active_editor = window_tab_data.main_split.active_editor;
+            let config = window_tab_data.common.config;
+            create_effect(cx, move |_| {
+                let joined = collab.session.with(|session| {
+                    session.as_ref().map_or(false, |s| s.participant.is_some())
+                });
+                if !joined {
+                    return;
+                }
+                let Some(editor) = active_editor.get() else {
+                    return;
+                };
+                let (doc, cursor, viewport) = editor
+                    .with(|editor| (editor.doc, editor.cursor, editor.viewport));
+                let top_line = viewport.with(|viewport| viewport.y0)
+                    / config.get_untracked().editor.line_height() as f64;
+                let Some(path) =
+                    doc.with_untracked(|doc| doc.content.path().cloned())
+                else {
+                    return;
+                };
+                let (offset, selection) = cursor.with(|cursor| {
+                    let selection = if cursor.is_normal() {
+                        Vec::new()
+                    } else {
+                        doc.with_untracked(|doc| cursor.edit_selection(doc.buffer()))
+                            .regions()
+                            .iter()
+                            .filter(|region| !region.is_caret())
+                            .map(|region| region.min()..region.max())
+                            .collect()
+                    };
+                    (cursor.offset(), selection)
+                });
+                let (top, loaded) = doc.with_untracked(|doc| {
+                    let buffer = doc.buffer();
+                    let line = (top_line.max(0.0) as usize).min(buffer.last_line());
+                    (buffer.offset_of_line(line), doc.loaded())
+                });
+                // The host shares the text of the file once it's looked at, so
+                // that the guests who follow it can open it
+                if loaded && collab.role() == Some(CollabRole::Host) {
+                    if let Some(shared) = collab.shared_path(&path) {
+                        collab.share_file(&shared, || {
+                            doc.with_untracked(|doc| doc.buffer().text().to_string())
+                        });
+                    }
+                }
+                collab.local_cursor(&path, offset, &selection, top);
+            });
+        }
+
+        // The files which the host has open are the ones the guests can open
+        {
+            let collab = window_tab_data.common.collab.clone();
+            let docs = window_tab_data.main_split.docs;
+            create_effect(cx, move |_| {
+                let is_host = collab.session.with(|session| {
+                    session.as_ref().map_or(false, |session| session.is_host())
+                });
+                if !is_host {
+                    return;
+                }
+                let files: im::Vector<PathBuf> = docs.with(|docs| {
+                    docs.keys()
+                        .filter_map(|path| collab.shared_path(path))
+                        .sorted()
+                        .collect()
+                });
+                let changed = collab.session.with_untracked(|session| {
+                    session.as_ref().map_or(false, |s| s.files != files)
+                });
+                if !changed {
+                    return;
+                }
+                collab.retain_files(&files.iter().cloned().collect());
+                collab.session.update(|session| {
+                    if let Some(session) = session.as_mut() {
+                        session.files = files.clone();
+                    }
+                });
+                collab.send(CollabMessage::Files {
+                    files: files.into_iter().collect(),
+                });
+            });
+        }
+
+        startup::phase("workspace data");
         window_tab_data
     }
 
+    /// Start what's put off until the window tab is painted for the first time,
+    /// which is the proxy with the plugins and language servers of the
+    /// workspace, and the loading of the plugin panel.
+    pub fn start_deferred(&self) {
+        startup::phase("first paint");
+        startup::report();
+        self.proxy.connect();
+        self.plugin.load();
+    }
+
     pub fn reload_config(&self) {
         let db: Arc<LapceDb> = use_context(self.scope).unwrap();
 
@@ -412,7 +907,44 @@ pub fn reload_config(&self) {
         let mut all_disabled_volts = disabled_volts;
         all_disabled_volts.extend(workspace_disabled_volts);
 
-        let config = LapceConfig::load(&self.workspace, &all_disabled_volts);
+        let mut config = LapceConfig::load(&self.workspace, &all_disabled_volts);
+        config.apply_zoom(self.zoom.get_untracked());
+
+        let old_profile = self
+            .common
+            .config
+            .with_untracked(|config| config.core.profile.clone());
+        if old_profile != config.core.profile {
+            self.plugin.set_profile_disabled(
+                config
+                    .profile
+                    .as_ref()
+                    .map(|profile| {
+                        HashSet::from_iter(profile.info.disabled_volts.clone())
+                    })
+                    .unwrap_or_default(),
+            );
+            if let Some(panel) =
+                config.profile.as_ref().and_then(|p| p.info.panel.clone())
+            {
+                self.panel.panels.set(panel);
+            }
+        }
+
+        let old_log_levels = self
+            .common
+            .config
+            .with_untracked(|config| config.core.log_levels.clone());
+        if old_log_levels != config.core.log_levels {
+            crate::log::set_log_levels(self.window_tab_id, &config.core.log_levels);
+            self.proxy
+                .proxy_rpc
+                .update_log_levels(config.core.log_levels.clone());
+        }
+
+        self.common
+            .keypress
+            .update(|keypress| keypress.update_keymaps(&config));
         self.set_config.set(Arc::new(config));
     }
 
@@ -474,6 +1006,37 @@ pub fn run_workbench_command(
                     );
                 }
             }
+            OpenArchive => {
+                if !self.workspace.kind.is_remote() {
+                    let window_command = self.common.window_command;
+                    // The proxy reads the archive as a workspace of its own
+                    let options =
+                        FileDialogOptions::new().allowed_types(vec![FileSpec::new(
+                            "Archive",
+                            &[
+                                "zip", "jar", "whl", "vsix", "nupkg", "tar", "gz",
+                                "tgz", "crate", "zst", "tzst",
+                            ],
+                        )]);
+                    self.common.view_id.get_untracked().open_file(
+                        options,
+                        move |file| {
+                            if let Some(file) = file {
+                                let workspace = LapceWorkspace {
+                                    kind: LapceWorkspaceType::Local,
+                                    path: Some(file.path),
+                                    last_open: std::time::SystemTime::now()
+                                        .duration_since(std::time::UNIX_EPOCH)
+                                        .unwrap()
+                                        .as_secs(),
+                                };
+                                window_command
+                                    .send(WindowCommand::SetWorkspace { workspace });
+                            }
+                        },
+                    );
+                }
+            }
             CloseFolder => {
                 if !self.workspace.kind.is_remote() {
                     let window_command = self.common.window_command;
@@ -504,6 +1067,23 @@ pub fn run_workbench_command(
             NewFile => {
                 // TODO: needs scratch files
             }
+            FileExplorerDelete => {
+                self.file_explorer.delete_selected(false);
+            }
+            FileExplorerDeletePermanently => {
+                self.file_explorer.delete_selected(true);
+            }
+            FileExplorerFollowLink => {
+                self.file_explorer.follow_selected_link();
+            }
+            UndoFileDelete => {
+                self.file_explorer.undo_delete();
+            }
+            MakeWritable => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    editor.with_untracked(|editor| editor.make_writable());
+                }
+            }
             RevealActiveFileInFileExplorer => {
                 if let Some(editor_data) = self.main_split.active_editor.get() {
                     editor_data.with_untracked(|editor_data| {
@@ -521,6 +1101,52 @@ pub fn run_workbench_command(
                     });
                 }
             }
+            CopyGitPermalink => {
+                self.git_permalink(false);
+            }
+            OpenFileOnRemote => {
+                self.git_permalink(true);
+            }
+            ExportHtml => {
+                self.export_html(false);
+            }
+            Print => {
+                self.export_html(true);
+            }
+            RunAllTests => {
+                self.test_explorer.run_all();
+            }
+            RerunFailedTests => {
+                self.test_explorer.rerun_failed();
+            }
+            CancelTests => {
+                self.test_explorer.cancel();
+            }
+            LoadCoverage => {
+                self.load_coverage();
+            }
+            ClearCoverage => {
+                self.common.coverage.set(Coverage::default());
+                self.common.proxy.unwatch_coverage();
+            }
+            SendHttpRequest => {
+                let Some(editor) = self.main_split.active_editor.get_untracked()
+                else {
+                    return;
+                };
+                let (path, line) = editor.with_untracked(|editor| {
+                    let offset = editor.cursor.get_untracked().offset();
+                    editor.doc.with_untracked(|doc| match &doc.content {
+                        DocContent::File(path) => {
+                            (Some(path.clone()), doc.buffer().line_of_offset(offset))
+                        }
+                        DocContent::Local => (None, 0),
+                    })
+                });
+                if let Some(path) = path {
+                    self.send_http_request(path, line);
+                }
+            }
 
             SaveAll => {
                 self.main_split.editors.with_untracked(|editors| {
@@ -565,6 +1191,22 @@ pub fn run_workbench_command(
                     );
                 }
             }
+            OpenWorkspaceSettingsFile => {
+                if let Some(path) =
+                    LapceConfig::workspace_settings_file(&self.workspace)
+                {
+                    self.main_split.jump_to_location(
+                        EditorLocation {
+                            path,
+                            position: None,
+                            scroll_offset: None,
+                            ignore_unconfirmed: false,
+                            same_editor_tab: false,
+                        },
+                        None,
+                    );
+                }
+            }
             OpenSettingsDirectory => {
                 if let Some(dir) = Directory::config_directory() {
                     open_uri(&dir);
@@ -598,6 +1240,9 @@ pub fn run_workbench_command(
                     )])
                 }
             }
+            ShowMemoryUsage => {
+                self.show_memory_usage();
+            }
             OpenLogsDirectory => {
                 if let Some(dir) = Directory::logs_directory() {
                     open_uri(&dir);
@@ -620,9 +1265,108 @@ pub fn run_workbench_command(
             }
 
             InstallTheme => {}
+            InstallThemeFromFile => {
+                let internal_command = self.common.internal_command;
+                let notification = self.notification;
+                let config = self.common.config;
+                let options = FileDialogOptions::new();
+                self.common.view_id.get_untracked().open_file(
+                    options,
+                    move |file| {
+                        let Some(file) = file else { return };
+                        let config = config.get_untracked();
+                        match ImportedTheme::from_file(&file.path).and_then(
+                            |mut theme| {
+                                // A theme with the same name isn't replaced, the
+                                // imported one is named differently instead
+                                let original = theme.name.clone();
+                                let renamed = theme.rename_if_taken(|name| {
+                                    config.available_color_themes.contains_key(name)
+                                });
+                                theme.install()?;
+                                Ok((theme.name, renamed.then_some(original)))
+                            },
+                        ) {
+                            Ok((name, taken_name)) => {
+                                if let Some(taken_name) = taken_name {
+                                    let args = [
+                                        ("name", FluentValue::from(taken_name)),
+                                        (
+                                            "new-name",
+                                            FluentValue::from(name.clone()),
+                                        ),
+                                    ];
+                                    notification.notify(Notification::new(
+                                        NotificationSeverity::Info,
+                                        config.tr("notification-theme-renamed"),
+                                        config.tr_args(
+                                            "notification-theme-renamed-message",
+                                            args,
+                                        ),
+                                    ));
+                                }
+                                internal_command.send(
+                                    InternalCommand::SetColorTheme {
+                                        name,
+                                        save: true,
+                                    },
+                                );
+                            }
+                            Err(err) => notification.notify(Notification::error(
+                                config.tr("notification-install-theme-failed"),
+                                err.to_string(),
+                            )),
+                        }
+                    },
+                );
+            }
             ExportCurrentThemeSettings => {}
             ToggleInlayHints => {}
 
+            // ==== Macros ====
+            ToggleMacroRecording => {
+                let macros = self.common.keypress.with_untracked(|k| k.macros);
+                macros.update(|macros| macros.toggle());
+            }
+            PlayMacro => {
+                self.play_last_macro(false);
+            }
+            PlayMacroOverSelections => {
+                self.play_last_macro(true);
+            }
+            SaveMacro => {
+                self.palette.run(cx, PaletteKind::MacroName);
+            }
+            MacroFromCommandHistory => {
+                self.palette.run(cx, PaletteKind::CommandHistoryMacro);
+            }
+
+            // ==== Collaboration ====
+            StartCollaboration => {
+                self.common.collab.host(&self.common.config.get_untracked());
+                self.show_panel(PanelKind::Collaboration);
+            }
+            JoinCollaboration => {
+                self.palette.run(cx, PaletteKind::CollabInvite);
+            }
+            StopCollaboration => {
+                self.stop_collaboration();
+            }
+            CopyCollaborationInvite => {
+                let invite = self.common.collab.session.with_untracked(|session| {
+                    session.as_ref().and_then(|session| session.invite.clone())
+                });
+                if let Some(invite) = invite {
+                    SystemClipboard {}.put_string(invite.to_string());
+                }
+            }
+            ShareTerminalInCollaboration => {
+                self.share_terminal_in_collaboration();
+            }
+            PingCollaborators => {
+                self.ping_collab_selection();
+            }
+
             // ==== Window ====
             ReloadWindow => {
                 self.common
@@ -798,12 +1542,126 @@ pub fn run_workbench_command(
             PaletteSCMReferences => {
                 self.palette.run(cx, PaletteKind::SCMReferences);
             }
+            PalettePasteFromHistory => {
+                self.palette.run(cx, PaletteKind::ClipboardHistory);
+            }
+            PaletteCommandHistory => {
+                self.palette.run(cx, PaletteKind::CommandHistory);
+            }
+            RepeatLastCommand => {
+                if let Some(cmd) = self.palette.last_command() {
+                    self.palette.run_command(cmd);
+                }
+            }
             ChangeColorTheme => {
                 self.palette.run(cx, PaletteKind::ColorTheme);
             }
             ChangeIconTheme => {
                 self.palette.run(cx, PaletteKind::IconTheme);
             }
+            SwitchProfile => {
+                self.palette.run(cx, PaletteKind::Profile);
+            }
+            CreateProfile => {
+                let disabled_volts =
+                    self.plugin.disabled.get_untracked().into_iter().collect();
+                let panel = self.panel.panels.get_untracked();
+                match Profile::snapshot("New Profile", disabled_volts, Some(panel))
+                    .and_then(|content| Profile::create("new-profile", &content))
+                {
+                    Ok(path) => {
+                        self.main_split.jump_to_location(
+                            EditorLocation {
+                                path,
+                                position: None,
+                                scroll_offset: None,
+                                ignore_unconfirmed: false,
+                                same_editor_tab: false,
+                            },
+                            None,
+                        );
+                    }
+                    Err(err) => self.notification.notify(Notification::error(
+                        self.common
+                            .config
+                            .get_untracked()
+                            .tr("notification-create-profile-failed"),
+                        err.to_string(),
+                    )),
+                }
+            }
+            ImportProfile => {
+                let workbench_command = self.common.workbench_command;
+                let notification = self.notification;
+                let config = self.common.config;
+                let options = FileDialogOptions::new();
+                self.common.view_id.get_untracked().open_file(
+                    options,
+                    move |file| {
+                        if let Some(file) = file {
+                            match Profile::import(&file.path) {
+                                Ok(_) => workbench_command
+                                    .send(LapceWorkbenchCommand::SwitchProfile),
+                                Err(err) => {
+                                    notification.notify(Notification::error(
+                                        config.get_untracked().tr(
+                                            "notification-import-profile-failed",
+                                        ),
+                                        err.to_string(),
+                                    ))
+                                }
+                            }
+                        }
+                    },
+                );
+            }
+            ExportProfile => {
+                let profile = self.common.config.get_untracked().profile.clone();
+                let disabled_volts: Vec<_> =
+                    self.plugin.disabled.get_untracked().into_iter().collect();
+                let panel = self.panel.panels.get_untracked();
+                let notification = self.notification;
+                let config = self.common.config;
+                let options = FileDialogOptions::new().select_directories();
+                self.common.view_id.get_untracked().open_file(
+                    options,
+                    move |file| {
+                        let Some(file) = file else { return };
+                        // without an active profile, the current configuration
+                        // is exported as one
+                        let result = match profile.as_ref() {
+                            Some(profile) => profile.export(&file.path),
+                            None => Profile::snapshot(
+                                "Exported Profile",
+                                disabled_volts.clone(),
+                                Some(panel.clone()),
+                            )
+                            .and_then(|content| {
+                                Profile::export_content(
+                                    &file.path, "profile", &content,
+                                )
+                            }),
+                        };
+                        // The file is named differently when there's one with
+                        // its name already, so where it went is told
+                        match result {
+                            Ok(path) => notification.notify(Notification::new(
+                                NotificationSeverity::Info,
+                                config
+                                    .get_untracked()
+                                    .tr("notification-profile-exported"),
+                                path.to_string_lossy(),
+                            )),
+                            Err(err) => notification.notify(Notification::error(
+                                config
+                                    .get_untracked()
+                                    .tr("notification-export-profile-failed"),
+                                err.to_string(),
+                            )),
+                        }
+                    },
+                );
+            }
             ChangeFileLanguage => {
                 self.palette.run(cx, PaletteKind::Language);
             }
@@ -824,40 +1682,104 @@ pub fn run_workbench_command(
                     self.terminal.stop_run_debug(term_id);
                 }
             }
-
-            // ==== UI ====
-            ZoomIn => {
-                let mut scale = self.window_scale.get_untracked();
-                scale += 0.1;
-                if scale > 4.0 {
-                    scale = 4.0
+            RunWithoutDebugging => {
+                let config = self
+                    .terminal
+                    .debug
+                    .active_term
+                    .get_untracked()
+                    .and_then(|term_id| self.terminal.get_terminal(&term_id))
+                    .and_then(|terminal| terminal.run_debug.get_untracked())
+                    .map(|run_debug| run_debug.config)
+                    .filter(|config| config.mode == RunDebugConfigMode::Launch);
+                match config {
+                    Some(mut config) => {
+                        config.dap_id = DapId::next();
+                        config.parent = None;
+                        config.no_debug = false;
+                        self.run_without_debugging(cx, config);
+                    }
+                    None => {
+                        self.palette.run(cx, PaletteKind::RunAndDebug);
+                    }
                 }
-                self.window_scale.set(scale);
             }
-            ZoomOut => {
-                let mut scale = self.window_scale.get_untracked();
-                scale -= 0.1;
-                if scale < 0.1 {
-                    scale = 0.1
-                }
-                self.window_scale.set(scale);
+            AddWatchExpression => {
+                self.palette.run(cx, PaletteKind::WatchExpression);
             }
-            ZoomReset => {
-                self.window_scale.set(1.0);
+            DebugJumpToCursor => {
+                let Some(editor) = self.main_split.active_editor.get_untracked()
+                else {
+                    return;
+                };
+                let (doc, offset) = editor.with_untracked(|editor| {
+                    (editor.doc, editor.cursor.with_untracked(|c| c.offset()))
+                });
+                let location = doc.with_untracked(|doc| {
+                    let path = doc.content.path().cloned()?;
+                    Some((path, doc.buffer().line_of_offset(offset)))
+                });
+                if let Some((path, line)) = location {
+                    self.terminal.dap_jump_to_line(path, line);
+                }
             }
-
-            ToggleMaximizedPanel => {
-                if let Some(data) = data {
-                    if let Ok(kind) = serde_json::from_value::<PanelKind>(data) {
-                        self.panel.toggle_maximize(&kind);
+            DebugRestartFrame => {
+                if let Some(dap) = self.terminal.get_active_dap(false) {
+                    if let (true, Some(frame_id)) =
+                        (dap.stopped.get_untracked(), dap.frame_id.get_untracked())
+                    {
+                        self.terminal.dap_restart_frame(dap.dap_id, frame_id);
                     }
-                } else {
-                    self.panel.toggle_active_maximize();
                 }
             }
-            HidePanel => {
-                if let Some(data) = data {
-                    if let Ok(kind) = serde_json::from_value::<PanelKind>(data) {
+            DebugReverseContinue => {
+                if let Some(term_id) =
+                    self.terminal.debug.active_term.get_untracked()
+                {
+                    self.terminal.dap_reverse_continue(term_id);
+                }
+            }
+            DebugStepBack => {
+                if let Some(term_id) =
+                    self.terminal.debug.active_term.get_untracked()
+                {
+                    self.terminal.dap_step_back(term_id);
+                }
+            }
+            OrganizeImportsInOpenFiles => {
+                self.main_split.run_source_action_in_open_files(
+                    CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                );
+            }
+            RemoveUnusedImportsInOpenFiles => {
+                self.main_split.run_source_action_in_open_files(
+                    CodeActionKind::from(SOURCE_REMOVE_UNUSED_IMPORTS),
+                );
+            }
+
+            // ==== UI ====
+            ZoomIn => {
+                self.set_zoom(self.zoom.get_untracked() + 0.1);
+            }
+            ZoomOut => {
+                self.set_zoom(self.zoom.get_untracked() - 0.1);
+            }
+            ZoomReset => {
+                self.set_zoom(1.0);
+            }
+
+            ToggleMaximizedPanel => {
+                if let Some(data) = data {
+                    if let Ok(kind) = serde_json::from_value::<PanelKind>(data) {
+                        self.panel.toggle_maximize(&kind);
+                    }
+                } else {
+                    self.panel.toggle_active_maximize();
+                }
+            }
+            HidePanel => {
+                if let Some(data) = data {
+                    if let Ok(kind) = serde_json::from_value::<PanelKind>(data) {
                         self.hide_panel(kind);
                     }
                 }
@@ -928,6 +1850,36 @@ pub fn run_workbench_command(
             ToggleDebugVisual => {
                 self.toggle_panel_visual(PanelKind::Debug);
             }
+            ToggleNotificationVisual => {
+                self.toggle_panel_visual(PanelKind::Notification);
+            }
+            ToggleLogVisual => {
+                self.toggle_panel_visual(PanelKind::Log);
+            }
+            ToggleTestExplorerVisual => {
+                self.toggle_panel_visual(PanelKind::TestExplorer);
+            }
+            ToggleHttpResponseVisual => {
+                self.toggle_panel_visual(PanelKind::HttpResponse);
+            }
+            ToggleDebugConsoleVisual => {
+                self.toggle_panel_visual(PanelKind::DebugConsole);
+            }
+            ToggleDebugMemoryVisual => {
+                self.toggle_panel_visual(PanelKind::DebugMemory);
+            }
+            ToggleTodoVisual => {
+                self.toggle_panel_visual(PanelKind::Todo);
+            }
+            ToggleCollaborationVisual => {
+                self.toggle_panel_visual(PanelKind::Collaboration);
+            }
+            ToggleDoNotDisturb => {
+                self.notification.toggle_do_not_disturb();
+            }
+            ClearNotifications => {
+                self.notification.clear_history();
+            }
             ToggleSearchVisual => {
                 self.toggle_panel_visual(PanelKind::Search);
             }
@@ -937,6 +1889,12 @@ pub fn run_workbench_command(
             FocusTerminal => {
                 self.common.focus.set(Focus::Panel(PanelKind::Terminal));
             }
+            FocusNextPart => {
+                self.focus_next_part(true);
+            }
+            FocusPreviousPart => {
+                self.focus_next_part(false);
+            }
 
             // ==== Source Control ====
             SourceControlInit => {
@@ -991,13 +1949,25 @@ pub fn run_workbench_command(
                 if let Some(release) = self.latest_release.get_untracked().as_ref() {
                     let release = release.clone();
                     let update_in_progress = self.update_in_progress;
+                    let notification = self.notification;
+                    let config = self.common.config;
                     if release.version != *meta::VERSION {
                         if let Ok(process_path) = env::current_exe() {
                             update_in_progress.set(true);
-                            let send =
-                                create_ext_action(self.scope, move |_started| {
+                            let send = create_ext_action(
+                                self.scope,
+                                move |result: Result<(), String>| {
                                     update_in_progress.set(false);
-                                });
+                                    if let Err(err) = result {
+                                        notification.notify(Notification::error(
+                                            config
+                                                .get_untracked()
+                                                .tr("notification-update-failed"),
+                                            err,
+                                        ));
+                                    }
+                                },
+                            );
                             std::thread::spawn(move || {
                                 let do_update = || -> anyhow::Result<()> {
                                     let src =
@@ -1011,11 +1981,7 @@ pub fn run_workbench_command(
                                     Ok(())
                                 };
 
-                                if let Err(err) = do_update() {
-                                    error!("Failed to update: {err}");
-                                }
-
-                                send(false);
+                                send(do_update().map_err(|err| err.to_string()));
                             });
                         }
                     }
@@ -1054,8 +2020,10 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                 self.reload_config();
             }
             InternalCommand::UpdateLogLevel { level } => {
-                // TODO: implement logging panel, runtime log level change
-                debug!("{level}");
+                crate::log::set_default_log_level(level);
+            }
+            InternalCommand::OpenSettingsFile { path } => {
+                self.main_split.open_settings_file(path);
             }
             InternalCommand::OpenFile { path } => {
                 self.main_split.jump_to_location(
@@ -1075,6 +2043,9 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
             InternalCommand::JumpToLocation { location } => {
                 self.main_split.jump_to_location(location, None);
             }
+            InternalCommand::PreviewLocation { location } => {
+                self.main_split.preview_location(location);
+            }
             InternalCommand::PaletteReferences { references } => {
                 self.palette.references.set(references);
                 self.palette.run(cx, PaletteKind::Reference);
@@ -1108,16 +2079,48 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                 offset,
                 mouse_click,
                 code_actions,
+                group,
             } => {
                 let mut code_action = self.code_action.get_untracked();
-                code_action.show(code_actions, offset, mouse_click);
+                code_action.show(code_actions, offset, mouse_click, group);
                 self.code_action.set(code_action);
             }
             InternalCommand::RunCodeAction { plugin_id, action } => {
                 self.main_split.run_code_action(plugin_id, action);
             }
-            InternalCommand::ApplyWorkspaceEdit { edit } => {
-                self.main_split.apply_workspace_edit(&edit);
+            InternalCommand::RunSourceAction { path, kind } => {
+                self.main_split.run_source_action(path, kind);
+            }
+            InternalCommand::ApplyWorkspaceEdit { edit, rename } => {
+                self.main_split.apply_workspace_edit(&edit, rename);
+            }
+            InternalCommand::UndoWorkspaceEdit => {
+                self.main_split.undo_workspace_edit();
+            }
+            InternalCommand::PreviewFileEdits { files } => {
+                self.edit_preview.show(files);
+            }
+            InternalCommand::ApplyFileEdits { file_edits } => {
+                self.main_split.apply_file_edits(&file_edits);
+            }
+            InternalCommand::ResolveFileConflict { path, resolution } => {
+                self.main_split.resolve_file_conflict(&path, resolution);
+            }
+            InternalCommand::SaveAsAdmin { path } => {
+                self.main_split.save_as_admin(&path);
+            }
+            InternalCommand::EditEmbeddedCode {
+                host,
+                range,
+                extension,
+            } => {
+                self.main_split.edit_embedded_code(host, range, extension);
+            }
+            InternalCommand::SaveEmbeddedCode { path } => {
+                self.main_split.save_embedded_code(&path);
+            }
+            InternalCommand::DeletePathPermanently { path } => {
+                self.file_explorer.delete_permanently(path);
             }
             InternalCommand::SaveJumpLocation {
                 path,
@@ -1136,12 +2139,170 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
             InternalCommand::SplitTerminalPrevious { term_id } => {
                 self.terminal.split_previous(term_id);
             }
+            InternalCommand::PasteInTerminal { term_id, content } => {
+                if let Some(terminal) = self.terminal.get_terminal(&term_id) {
+                    terminal.paste(&content);
+                }
+            }
             InternalCommand::SplitTerminalExchange { term_id } => {
                 self.terminal.split_exchange(term_id);
             }
             InternalCommand::RunAndDebug { mode, config } => {
                 self.run_and_debug(cx, &mode, &config);
             }
+            InternalCommand::RunWithoutDebugging { config } => {
+                self.run_without_debugging(cx, config);
+            }
+            InternalCommand::ToggleBreakpoint { path, line, offset } => {
+                self.terminal.debug.toggle_breakpoint(
+                    &path,
+                    line,
+                    offset,
+                    &self.common.proxy,
+                );
+            }
+            InternalCommand::DapJumpToLine { path, line } => {
+                self.terminal.dap_jump_to_line(path, line);
+            }
+            InternalCommand::EditBreakpoint { edit } => {
+                let value = self.terminal.debug.breakpoint_field(&edit);
+                self.palette.breakpoint_edit.set(Some(edit));
+                self.palette.run(cx, PaletteKind::BreakpointField);
+                if let Some(value) = value {
+                    self.palette.set_input(&value);
+                }
+            }
+            InternalCommand::SetBreakpointField { edit, value } => {
+                self.terminal.debug.set_breakpoint_field(
+                    &edit,
+                    value,
+                    &self.common.proxy,
+                );
+            }
+            InternalCommand::AddWatchExpression { expression } => {
+                self.terminal
+                    .debug
+                    .add_watch(expression, &self.common.proxy);
+            }
+            InternalCommand::RemoveWatchExpression { index } => {
+                self.terminal.debug.remove_watch(index, &self.common.proxy);
+            }
+            InternalCommand::EditVariableValue { edit } => {
+                let value = self.terminal.variable_value(&edit);
+                self.palette.variable_edit.set(Some(edit));
+                self.palette.run(cx, PaletteKind::VariableValue);
+                if let Some(value) = value {
+                    self.palette.set_input(&value);
+                }
+            }
+            InternalCommand::SetVariableValue { edit, value } => {
+                self.terminal.set_variable_value(edit, value);
+            }
+            InternalCommand::ViewMemory {
+                dap_id,
+                memory_reference,
+            } => {
+                self.debug_memory.open(dap_id, memory_reference);
+                self.show_panel(PanelKind::DebugMemory);
+            }
+            InternalCommand::EditMemory { edit } => {
+                let value = self.debug_memory.byte_text(edit.offset);
+                self.palette.memory_edit.set(Some(edit));
+                self.palette.run(cx, PaletteKind::MemoryValue);
+                if let Some(value) = value {
+                    self.palette.set_input(&value);
+                }
+            }
+            InternalCommand::WriteMemory { edit, value } => {
+                match parse_hex_bytes(&value) {
+                    Some(data) => self.debug_memory.write(edit, data),
+                    None => {
+                        let config = self.common.config.get_untracked();
+                        self.notification.notify(Notification::error(
+                            config.tr("notification-invalid-memory-bytes"),
+                            value,
+                        ));
+                    }
+                }
+            }
+            InternalCommand::AnswerSshPrompt { prompt, answer } => {
+                prompt.answer(answer);
+            }
+            InternalCommand::SaveMacro { name } => {
+                let macros = self.common.keypress.with_untracked(|k| k.macros);
+                let mut result = Ok(());
+                macros.update(|macros| result = macros.save(&name));
+                if let Err(err) = result {
+                    self.notification.notify(Notification::error(
+                        self.common
+                            .config
+                            .get_untracked()
+                            .tr("notification-save-macro-failed"),
+                        err.to_string(),
+                    ));
+                }
+            }
+            InternalCommand::SendHttpRequest { path, line } => {
+                self.send_http_request(path, line);
+            }
+            InternalCommand::JoinCollaboration { invite } => {
+                let config = self.common.config.get_untracked();
+                match Invite::parse(&invite) {
+                    Some(invite) => {
+                        self.common.collab.join(invite, &config);
+                        self.show_panel(PanelKind::Collaboration);
+                    }
+                    None => {
+                        self.notification.notify(Notification::error(
+                            config.tr("notification-collab-invalid-invite"),
+                            invite,
+                        ));
+                    }
+                }
+            }
+            InternalCommand::SetCollaborationPermissions {
+                participant,
+                permissions,
+            } => {
+                let collab = &self.common.collab;
+                if collab.role() != Some(CollabRole::Host) || participant == HOST {
+                    return;
+                }
+                collab.session.update(|session| {
+                    if let Some(p) = session.as_mut().and_then(|session| {
+                        session
+                            .participants
+                            .iter_mut()
+                            .find(|p| p.id == participant)
+                    }) {
+                        p.permissions = permissions;
+                    }
+                });
+                collab.send(CollabMessage::PermissionsChanged {
+                    participant,
+                    permissions,
+                });
+            }
+            InternalCommand::FollowParticipant { participant } => {
+                self.common.collab.following.set(participant);
+                if let Some(participant) = participant {
+                    self.follow_collab_cursor(participant);
+                }
+            }
+            InternalCommand::OpenCollaborationFile { path } => {
+                if let Some(path) = self.common.collab.local_path(&path) {
+                    self.main_split.go_to_location(
+                        EditorLocation {
+                            path,
+                            position: None,
+                            scroll_offset: None,
+                            ignore_unconfirmed: false,
+                            same_editor_tab: false,
+                        },
+                        None,
+                    );
+                }
+            }
             InternalCommand::StartRename {
                 path,
                 placeholder,
@@ -1150,6 +2311,11 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
             } => {
                 self.rename.start(path, placeholder, start, position);
             }
+            InternalCommand::StartColorPicker { literal } => {
+                if let Some(editor) = self.main_split.active_editor.get_untracked() {
+                    self.color_picker.start(editor.get_untracked(), literal);
+                }
+            }
             InternalCommand::Search { pattern } => {
                 self.main_split.set_find_pattern(pattern);
             }
@@ -1194,6 +2360,28 @@ pub fn run_internal_command(&self, cmd: InternalCommand) {
                     self.set_config.set(Arc::new(new_config));
                 }
             }
+            InternalCommand::ShowNotification { notification } => {
+                self.notification.notify(notification);
+            }
+            InternalCommand::ReportCrash { report } => {
+                report_crash(&report);
+            }
+            InternalCommand::RestartVolt { volt_id } => {
+                let volt = self.plugin.installed.with_untracked(|installed| {
+                    installed.get(&volt_id).map(|v| v.meta.get_untracked())
+                });
+                if let Some(volt) = volt {
+                    self.plugin.reload_volt(volt);
+                }
+            }
+            InternalCommand::SetProfile { id } => {
+                // The config file is watched
+                LapceConfig::update_file(
+                    "core",
+                    "profile",
+                    toml_edit::Value::from(id),
+                );
+            }
             InternalCommand::SetIconTheme { name, save } => {
                 if save {
                     // The config file is watched
@@ -1268,6 +2456,11 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                     }
                 });
             }
+            CoreNotification::SignatureHelpResponse {
+                request_id, resp, ..
+            } => {
+                self.common.signature.receive(*request_id, resp.clone());
+            }
             CoreNotification::PublishDiagnostics { diagnostics } => {
                 let path = path_from_url(&diagnostics.uri);
                 let diagnostics: im::Vector<EditorDiagnostic> = diagnostics
@@ -1295,10 +2488,27 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 }
             }
             CoreNotification::TerminalProcessStopped { term_id } => {
+                let dap_id = self.terminal.get_terminal(term_id).and_then(|t| {
+                    t.run_debug
+                        .with_untracked(|r| r.as_ref().map(|r| r.config.dap_id))
+                });
+                if let Some(dap_id) = dap_id {
+                    self.clear_inline_values(dap_id);
+                }
                 let _ = self
                     .common
                     .term_tx
                     .send((*term_id, TermEvent::CloseTerminal));
+                if self.common.collab.shared_terminals.unshare(*term_id) {
+                    self.common.collab.session.update(|session| {
+                        if let Some(session) = session.as_mut() {
+                            session.terminals.retain(|t| t.term_id != *term_id);
+                        }
+                    });
+                    self.common
+                        .collab
+                        .send(CollabMessage::TerminalClosed { term_id: *term_id });
+                }
                 self.terminal.terminal_stopped(term_id);
                 if self
                     .terminal
@@ -1312,7 +2522,10 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 }
             }
             CoreNotification::RunInTerminal { config } => {
-                self.run_in_terminal(cx, &RunDebugMode::Debug, config);
+                self.run_in_terminal(cx, &RunDebugMode::Debug, config, true);
+            }
+            CoreNotification::DapSessionStarted { config } => {
+                self.run_in_terminal(cx, &RunDebugMode::Debug, config, false);
             }
             CoreNotification::TerminalProcessId {
                 term_id,
@@ -1326,12 +2539,88 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 stack_frames,
             } => {
                 self.terminal.dap_stopped(dap_id, stopped, stack_frames);
+                self.debug_memory.dap_stopped(*dap_id);
+                self.show_inline_values(*dap_id, stopped, stack_frames);
             }
-            CoreNotification::OpenPaths { paths } => {
+            CoreNotification::OpenPaths { paths, .. } => {
                 self.open_paths(paths);
             }
-            CoreNotification::DapContinued { dap_id } => {
-                self.terminal.dap_continued(dap_id);
+            CoreNotification::DapContinued { dap_id, thread_id } => {
+                self.terminal.dap_continued(dap_id, *thread_id);
+                // The values were of the thread which stopped last
+                self.clear_inline_values(*dap_id);
+            }
+            CoreNotification::DapTerminated { dap_id, .. } => {
+                self.terminal.dap_terminated(dap_id);
+                self.clear_inline_values(*dap_id);
+            }
+            CoreNotification::DapThreads { dap_id, threads } => {
+                self.terminal.dap_threads(dap_id, threads);
+            }
+            CoreNotification::DapBreakpointsResp {
+                path, breakpoints, ..
+            } => {
+                self.terminal.debug.breakpoints_resp(path, breakpoints);
+            }
+            CoreNotification::DapBreakpointChanged {
+                reason, breakpoint, ..
+            } => {
+                self.terminal.debug.breakpoint_changed(reason, breakpoint);
+            }
+            CoreNotification::DapOutput { dap_id, output } => {
+                self.terminal.dap_output(dap_id, output);
+            }
+            CoreNotification::DapMemory { dap_id, memory } => {
+                self.debug_memory.memory_changed(*dap_id, memory);
+            }
+            CoreNotification::DapWatchEvaluated {
+                dap_id,
+                frame_id,
+                expression,
+                result,
+            } => {
+                self.terminal
+                    .dap_watch_evaluated(dap_id, *frame_id, expression, result);
+            }
+            CoreNotification::DapCapabilities {
+                dap_id,
+                capabilities,
+            } => {
+                self.terminal.dap_capabilities(dap_id, capabilities);
+            }
+            CoreNotification::DapDataBreakpointsResp {
+                dap_id,
+                breakpoints,
+            } => {
+                self.terminal.dap_data_breakpoints_resp(dap_id, breakpoints);
+            }
+            CoreNotification::DapTaskFailed {
+                config,
+                task,
+                message,
+            } => {
+                self.notification.notify(Notification::error(
+                    self.common.config.get_untracked().tr_args(
+                        "notification-debug-task-failed",
+                        [
+                            ("task", FluentValue::from(task.clone())),
+                            ("config", FluentValue::from(config.clone())),
+                        ],
+                    ),
+                    message.clone(),
+                ));
+            }
+            CoreNotification::TaskStarted { task_id, name } => {
+                self.terminal.task_started(*task_id, name);
+                if !self.panel.is_panel_visible(&PanelKind::Terminal) {
+                    self.panel.show_panel(&PanelKind::Terminal);
+                }
+            }
+            CoreNotification::TaskOutput { task_id, content } => {
+                self.terminal.task_output(*task_id, content.clone());
+            }
+            CoreNotification::TaskStopped { task_id } => {
+                self.terminal.task_stopped(*task_id);
             }
             CoreNotification::OpenFileChanged { path, content } => {
                 self.main_split.open_file_changed(path, content);
@@ -1340,8 +2629,135 @@ fn handle_core_notification(&self, rpc: &CoreNotification) {
                 self.plugin.volt_installed(volt, icon);
             }
             CoreNotification::VoltRemoved { volt, .. } => {
+                self.status.remove_plugin_items(&volt.id());
+                self.test_explorer.remove_plugin_tests(&volt.id());
                 self.plugin.volt_removed(volt);
             }
+            CoreNotification::WorkDoneProgress { progress } => {
+                self.status.update_progress(progress.clone());
+            }
+            CoreNotification::SetStatusItem { volt_id, item } => {
+                self.status
+                    .set_item(StatusItem::from_plugin(volt_id, item.clone()));
+            }
+            CoreNotification::RemoveStatusItem { volt_id, id } => {
+                self.status.remove_item(&plugin_item_id(volt_id, id));
+            }
+            CoreNotification::SetTests { volt_id, tests } => {
+                self.test_explorer.set_plugin_tests(volt_id, tests.clone());
+            }
+            CoreNotification::CoverageChanged { coverage } => {
+                self.common.coverage.set(coverage.clone());
+            }
+            CoreNotification::WorkspaceFilesChanged { paths } => {
+                self.todo.files_changed(paths.clone());
+            }
+            CoreNotification::ShowMessage { title, message } => {
+                self.notification.notify(Notification::new(
+                    NotificationSeverity::from_message_type(message.typ),
+                    title,
+                    &message.message,
+                ));
+            }
+            CoreNotification::VoltInstalling { volt, error } => {
+                self.notification.notify(Notification::error(
+                    self.common.config.get_untracked().tr_args(
+                        "notification-install-plugin-failed",
+                        [("plugin", FluentValue::from(volt.display_name.clone()))],
+                    ),
+                    error,
+                ));
+            }
+            CoreNotification::VoltRemoving { volt, error } => {
+                self.notification.notify(Notification::error(
+                    self.common.config.get_untracked().tr_args(
+                        "notification-remove-plugin-failed",
+                        [("plugin", FluentValue::from(volt.display_name.clone()))],
+                    ),
+                    error,
+                ));
+            }
+            CoreNotification::PluginServerStopped { volt_id, server } => {
+                // the server is stopped on purpose when the plugin is disabled
+                if self.plugin.plugin_disabled(volt_id) {
+                    return;
+                }
+                let name = self
+                    .plugin
+                    .installed
+                    .with_untracked(|installed| {
+                        installed.get(volt_id).map(|v| {
+                            v.meta.with_untracked(|m| m.display_name.clone())
+                        })
+                    })
+                    .unwrap_or_else(|| volt_id.name.clone());
+                let config = self.common.config.get_untracked();
+                self.notification.notify(
+                    Notification::error(
+                        config.tr_args(
+                            "notification-server-stopped",
+                            [("plugin", FluentValue::from(name))],
+                        ),
+                        config.tr_args(
+                            "notification-server-exited",
+                            [("server", FluentValue::from(server.clone()))],
+                        ),
+                    )
+                    .with_action(
+                        config.tr("notification-restart-server"),
+                        NotificationCommand::Internal(
+                            InternalCommand::RestartVolt {
+                                volt_id: volt_id.clone(),
+                            },
+                        ),
+                    )
+                    .with_action(
+                        config.tr("notification-open-settings"),
+                        NotificationCommand::Workbench(
+                            LapceWorkbenchCommand::OpenSettings,
+                        ),
+                    ),
+                );
+            }
+            CoreNotification::SubsystemCrashed { report } => {
+                let config = self.common.config.get_untracked();
+                let message = FluentValue::from(report.message.clone());
+                let message = if report.restarted {
+                    config.tr_args(
+                        "notification-subsystem-restarted",
+                        [("message", message)],
+                    )
+                } else {
+                    config.tr_args(
+                        "notification-subsystem-stopped",
+                        [("message", message)],
+                    )
+                };
+                self.notification.notify(
+                    Notification::error(
+                        config.tr_args(
+                            "notification-subsystem-crashed",
+                            [("source", FluentValue::from(report.source()))],
+                        ),
+                        message,
+                    )
+                    .with_action(
+                        config.tr("notification-report-crash"),
+                        NotificationCommand::Internal(
+                            InternalCommand::ReportCrash {
+                                report: report.clone(),
+                            },
+                        ),
+                    ),
+                );
+            }
+            CoreNotification::Log {
+                level,
+                message,
+                target,
+            } => {
+                crate::log::log_proxy_message(level, target, message);
+            }
             _ => {}
         }
     }
@@ -1366,6 +2782,14 @@ pub fn key_down(&self, key_event: &KeyEvent) {
                 keypress.key_down(key_event, &self.rename);
                 true
             }
+            Focus::ColorPicker => {
+                keypress.key_down(key_event, &self.color_picker);
+                true
+            }
+            Focus::EditPreview => {
+                keypress.key_down(key_event, &self.edit_preview);
+                true
+            }
             Focus::Panel(PanelKind::Terminal) => {
                 self.terminal.key_down(key_event, &mut keypress);
                 true
@@ -1382,6 +2806,18 @@ pub fn key_down(&self, key_event: &KeyEvent) {
                 keypress.key_down(key_event, &self.source_control);
                 true
             }
+            Focus::Panel(PanelKind::FileExplorer) => {
+                keypress.key_down(key_event, &self.file_explorer);
+                true
+            }
+            Focus::Panel(PanelKind::Log) => {
+                keypress.key_down(key_event, &self.log);
+                true
+            }
+            Focus::Panel(PanelKind::DebugConsole) => {
+                keypress.key_down(key_event, &self.debug_console);
+                true
+            }
             _ => false,
         };
 
@@ -1403,53 +2839,35 @@ pub fn workspace_info(&self) -> WorkspaceInfo {
         WorkspaceInfo {
             split: main_split_data.get_untracked().split_info(self),
             panel: self.panel.panel_info(),
+            zoom: self.zoom.get_untracked(),
+            watches: self
+                .terminal
+                .debug
+                .watches
+                .get_untracked()
+                .into_iter()
+                .collect(),
+            exception_filters: self.terminal.debug.exception_filters.get_untracked(),
         }
     }
 
-    pub fn completion_origin(&self) -> Point {
-        let completion = self.common.completion.get();
-        let config = self.common.config.get();
-        if completion.status == CompletionStatus::Inactive {
-            return Point::ZERO;
-        }
-
-        let editor =
-            if let Some(editor) = self.main_split.active_editor.get_untracked() {
-                editor
-            } else {
-                return Point::ZERO;
-            };
-
-        let (window_origin, viewport, view) =
-            editor.with_untracked(|e| (e.window_origin, e.viewport, e.view.clone()));
-
-        let (point_above, point_below) = view.points_of_offset(completion.offset);
-
-        let window_origin = window_origin.get() - self.window_origin.get().to_vec2();
-        let viewport = viewport.get();
-        let completion_size = completion.layout_rect.size();
-        let tab_size = self.layout_rect.get().size();
-
-        let mut origin = window_origin
-            + Vec2::new(
-                point_below.x
-                    - viewport.x0
-                    - config.editor.line_height() as f64
-                    - 5.0,
-                point_below.y - viewport.y0,
-            );
-        if origin.y + completion_size.height > tab_size.height {
-            origin.y = window_origin.y + (point_above.y - viewport.y0)
-                - completion_size.height;
-        }
-        if origin.x + completion_size.width + 1.0 > tab_size.width {
-            origin.x = tab_size.width - completion_size.width - 1.0;
-        }
-        if origin.x <= 0.0 {
-            origin.x = 0.0;
+    /// Zoom the workspace to `zoom`. The window scale follows the zoom of the
+    /// active workspace tab, see [`crate::window::WindowData`], and the editor
+    /// fonts are zoomed when the config is loaded.
+    fn set_zoom(&self, zoom: f64) {
+        let zoom = ((zoom * 10.0).round() / 10.0).clamp(0.1, 4.0);
+        self.zoom.set(zoom);
+        if self
+            .common
+            .config
+            .with_untracked(|config| config.ui.zoom_mode == ZoomMode::Editor)
+        {
+            self.reload_config();
         }
+    }
 
-        origin
+    pub fn completion_origin(&self) -> Point {
+        self.popup_origin(PopupKind::Completion)
     }
 
     pub fn code_action_origin(&self) -> Point {
@@ -1502,14 +2920,187 @@ pub fn code_action_origin(&self) -> Point {
         origin
     }
 
-    pub fn rename_origin(&self) -> Point {
+    pub fn signature_help_origin(&self) -> Point {
+        self.popup_origin(PopupKind::SignatureHelp)
+    }
+
+    pub fn hover_origin(&self) -> Point {
+        self.popup_origin(PopupKind::Hover)
+    }
+
+    fn popup_origin(&self, kind: PopupKind) -> Point {
+        self.popup_layout()
+            .into_iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, rect)| rect.origin())
+            .unwrap_or(Point::ZERO)
+    }
+
+    /// Where the popups which are shown go. The ones at the line of the cursor
+    /// of the active editor are stacked around it, and a hover of another line
+    /// goes to the other side of its line when it would cover them.
+    fn popup_layout(&self) -> Vec<(PopupKind, Rect)> {
         let config = self.common.config.get();
+        let tab_size = self.layout_rect.get().size();
+        let visible = self.common.popups.visible(true);
+        let clamp_x =
+            |x: f64, width: f64| x.min(tab_size.width - width - 1.0).max(0.0);
+
+        let mut layout = Vec::new();
+        if let Some(editor) = self.main_split.active_editor.get_untracked() {
+            let (window_origin, viewport, view, cursor, editor_id) = editor
+                .with_untracked(|e| {
+                    (
+                        e.window_origin,
+                        e.viewport,
+                        e.view.clone(),
+                        e.cursor,
+                        e.editor_id,
+                    )
+                });
+            let window_origin =
+                window_origin.get() - self.window_origin.get().to_vec2();
+            let viewport = viewport.get();
+            let (point_above, point_below) =
+                view.points_of_offset(cursor.with(|c| c.offset()));
+            let top = window_origin.y + point_above.y - viewport.y0;
+            let bottom = window_origin.y + point_below.y - viewport.y0;
+            let x_of = |offset: usize| {
+                window_origin.x + view.points_of_offset(offset).1.x - viewport.x0
+            };
+
+            let hover = self.common.hover;
+            let signature = self.common.signature;
+            let mut stacked = Vec::new();
+            for kind in &visible {
+                let (x, size) = match kind {
+                    PopupKind::Completion => {
+                        let (offset, size) = self
+                            .common
+                            .completion
+                            .with(|c| (c.offset, c.layout_rect.size()));
+                        (
+                            x_of(offset) - config.editor.line_height() as f64 - 5.0,
+                            size,
+                        )
+                    }
+                    PopupKind::SignatureHelp => {
+                        if signature.editor_id.get() != Some(editor_id) {
+                            continue;
+                        }
+                        (
+                            x_of(signature.offset.get()),
+                            signature.layout_rect.get().size(),
+                        )
+                    }
+                    PopupKind::Hover => {
+                        let offset = hover.offset.get();
+                        if hover.editor_id.get() != Some(editor_id)
+                            || view.points_of_offset(offset).0.y != point_above.y
+                        {
+                            continue;
+                        }
+                        (x_of(offset), hover.layout_rect.get().size())
+                    }
+                };
+                stacked.push((*kind, x, size));
+            }
+
+            let heights: Vec<(PopupKind, f64)> = stacked
+                .iter()
+                .map(|(kind, _, size)| (*kind, size.height))
+                .collect();
+            let sides = stack_popups(&heights, top, tab_size.height - bottom);
+            for ((kind, x, size), (_, side, distance)) in stacked.iter().zip(sides) {
+                let y = match side {
+                    PopupSide::Below => bottom + distance,
+                    PopupSide::Above => top - distance - size.height,
+                };
+                let origin = Point::new(clamp_x(*x, size.width), y);
+                layout.push((*kind, Rect::from_origin_size(origin, *size)));
+            }
+        }
+
+        if visible.contains(&PopupKind::Hover)
+            && !layout.iter().any(|(kind, _)| *kind == PopupKind::Hover)
+        {
+            if let Some(rect) = self.hover_rect(&layout) {
+                layout.push((PopupKind::Hover, rect));
+            }
+        }
+        layout
+    }
+
+    /// Where the hover of a line which the other popups aren't at goes, which is
+    /// under the line, or above it when there's no room or it would cover them.
+    fn hover_rect(&self, popups: &[(PopupKind, Rect)]) -> Option<Rect> {
+        let hover = self.common.hover;
+        let editor = hover.editor_id.get().and_then(|editor_id| {
+            self.main_split
+                .editors
+                .with_untracked(|editors| editors.get(&editor_id).copied())
+        })?;
+
+        let tab_size = self.layout_rect.get().size();
+        let hover_size = hover.layout_rect.get().size();
+
+        let (window_origin, viewport, view) =
+            editor.with_untracked(|e| (e.window_origin, e.viewport, e.view.clone()));
+
+        let (point_above, point_below) = view.points_of_offset(hover.offset.get());
+
+        let window_origin = window_origin.get() - self.window_origin.get().to_vec2();
+        let viewport = viewport.get();
+
+        let x = (window_origin.x + point_below.x - viewport.x0)
+            .min(tab_size.width - hover_size.width - 1.0)
+            .max(0.0);
+        let below = window_origin.y + point_below.y - viewport.y0;
+        let above =
+            window_origin.y + point_above.y - viewport.y0 - hover_size.height;
+        let fits_below = below + hover_size.height <= tab_size.height;
+        let rect_at = |y| Rect::from_origin_size(Point::new(x, y), hover_size);
+        let covers = |rect: Rect| {
+            popups
+                .iter()
+                .any(|(_, popup)| popup.intersect(rect).area() > 0.0)
+        };
+
+        let rect = rect_at(if fits_below { below } else { above });
+        if covers(rect) {
+            let other = rect_at(if fits_below { above } else { below });
+            if !covers(other) {
+                return Some(other);
+            }
+        }
+        Some(rect)
+    }
+
+    pub fn rename_origin(&self) -> Point {
         if !self.rename.active.get() {
             return Point::ZERO;
         }
+        self.editor_popup_origin(
+            self.rename.start.get_untracked(),
+            self.rename.layout_rect.get().size(),
+        )
+    }
+
+    pub fn color_picker_origin(&self) -> Point {
+        if !self.color_picker.active.get() {
+            return Point::ZERO;
+        }
+        self.editor_popup_origin(
+            self.color_picker.range.get_untracked().start,
+            self.color_picker.layout_rect.get().size(),
+        )
+    }
 
+    /// Where a popup of the size goes below the offset of the active editor,
+    /// or above it when there's no room below.
+    fn editor_popup_origin(&self, offset: usize, popup_size: Size) -> Point {
+        let config = self.common.config.get();
         let tab_size = self.layout_rect.get().size();
-        let rename_size = self.rename.layout_rect.get().size();
 
         let editor =
             if let Some(editor) = self.main_split.active_editor.get_untracked() {
@@ -1521,8 +3112,7 @@ pub fn rename_origin(&self) -> Point {
         let (window_origin, viewport, view) =
             editor.with_untracked(|e| (e.window_origin, e.viewport, e.view.clone()));
 
-        let (_point_above, point_below) =
-            view.points_of_offset(self.rename.start.get_untracked());
+        let (_point_above, point_below) = view.points_of_offset(offset);
 
         let window_origin = window_origin.get() - self.window_origin.get().to_vec2();
         let viewport = viewport.get();
@@ -1530,12 +3120,12 @@ pub fn rename_origin(&self) -> Point {
         let mut origin = window_origin
             + Vec2::new(point_below.x - viewport.x0, point_below.y - viewport.y0);
 
-        if origin.y + rename_size.height > tab_size.height {
+        if origin.y + popup_size.height > tab_size.height {
             origin.y =
-                origin.y - config.editor.line_height() as f64 - rename_size.height;
+                origin.y - config.editor.line_height() as f64 - popup_size.height;
         }
-        if origin.x + rename_size.width + 1.0 > tab_size.width {
-            origin.x = tab_size.width - rename_size.width - 1.0;
+        if origin.x + popup_size.width + 1.0 > tab_size.width {
+            origin.x = tab_size.width - popup_size.width - 1.0;
         }
         if origin.x <= 0.0 {
             origin.x = 0.0;
@@ -1572,17 +3162,25 @@ pub fn toggle_panel_visual(&self, kind: PanelKind) {
     /// Toggle a specific kind of panel.
     fn toggle_panel_focus(&self, kind: PanelKind) {
         let should_hide = match kind {
-            PanelKind::FileExplorer
-            | PanelKind::Plugin
+            PanelKind::Plugin
             | PanelKind::Problem
-            | PanelKind::Debug => {
+            | PanelKind::Debug
+            | PanelKind::Notification
+            | PanelKind::TestExplorer
+            | PanelKind::HttpResponse
+            | PanelKind::Collaboration
+            | PanelKind::DebugMemory
+            | PanelKind::Todo => {
                 // Some panels don't accept focus (yet). Fall back to visibility check
                 // in those cases.
                 self.panel.is_panel_visible(&kind)
             }
-            PanelKind::Terminal | PanelKind::SourceControl | PanelKind::Search => {
-                self.is_panel_focused(kind)
-            }
+            PanelKind::Terminal
+            | PanelKind::SourceControl
+            | PanelKind::Search
+            | PanelKind::FileExplorer
+            | PanelKind::Log
+            | PanelKind::DebugConsole => self.is_panel_focused(kind),
         };
         if should_hide {
             self.hide_panel(kind);
@@ -1628,10 +3226,34 @@ fn toggle_container_visual(&self, position: &PanelContainerPosition) {
         }
     }
 
-    fn is_panel_focused(&self, kind: PanelKind) -> bool {
-        // Moving between e.g. Search and Problems doesn't affect focus, so we need to also check
-        // visibility.
-        self.common.focus.get_untracked() == Focus::Panel(kind)
+    /// Move the focus through the editor and the shown panels, so that all of
+    /// them can be reached without a mouse.
+    fn focus_next_part(&self, forward: bool) {
+        let parts: Vec<Focus> = std::iter::once(Focus::Workbench)
+            .chain(
+                self.panel
+                    .visible_panels(false)
+                    .into_iter()
+                    .map(Focus::Panel),
+            )
+            .collect();
+        let focus = self.common.focus.get_untracked();
+        let index = parts.iter().position(|f| f == &focus).unwrap_or(0);
+        let index = if forward {
+            (index + 1) % parts.len()
+        } else {
+            (index + parts.len() - 1) % parts.len()
+        };
+        match parts[index].clone() {
+            Focus::Panel(kind) => self.show_panel(kind),
+            focus => self.common.focus.set(focus),
+        }
+    }
+
+    fn is_panel_focused(&self, kind: PanelKind) -> bool {
+        // Moving between e.g. Search and Problems doesn't affect focus, so we need to also check
+        // visibility.
+        self.common.focus.get_untracked() == Focus::Panel(kind)
             && self.panel.is_panel_visible(&kind)
     }
 
@@ -1666,6 +3288,185 @@ pub fn show_panel(&self, kind: PanelKind) {
         self.common.focus.set(Focus::Panel(kind));
     }
 
+    /// Show the plugin panel searched for the plugin, such as for a
+    /// `lapce://plugin/` link.
+    pub fn show_plugin(&self, volt_id: &VoltID) {
+        self.show_panel(PanelKind::Plugin);
+        let query = volt_id.name.clone();
+        let len = query.len();
+        let query_editor = &self.plugin.all.query_editor;
+        query_editor
+            .doc
+            .update(|doc| doc.reload(Rope::from(query), true));
+        query_editor
+            .cursor
+            .update(|cursor| cursor.set_offset(len, false, false));
+    }
+
+    /// Open the settings searched for the setting, such as for a
+    /// `lapce://settings/` link.
+    pub fn show_setting(&self, key: String) {
+        self.common.settings_search.set(Some(key));
+        self.main_split.open_settings();
+    }
+
+    /// Copy the permalink of the lines of the selection of the active editor on
+    /// the remote of its repository, which are the line of the cursor without
+    /// a selection, or open the file there in the browser, at the lines of the
+    /// selection if there is one.
+    fn git_permalink(&self, open: bool) {
+        let Some(editor) = self.main_split.active_editor.get_untracked() else {
+            return;
+        };
+        let (path, lines) = editor.with_untracked(|editor| {
+            let cursor = editor.cursor.get_untracked();
+            editor.doc.with_untracked(|doc| {
+                let DocContent::File(path) = &doc.content else {
+                    return (None, None);
+                };
+                let buffer = doc.buffer();
+                let lines = match cursor.get_selection() {
+                    Some((start, end)) if start != end => {
+                        let (start, end) = (start.min(end), start.max(end));
+                        let start_line = buffer.line_of_offset(start);
+                        let mut end_line = buffer.line_of_offset(end);
+                        // A selection to the start of a line doesn't have it
+                        if cursor.is_insert()
+                            && end_line > start_line
+                            && buffer.offset_of_line(end_line) == end
+                        {
+                            end_line -= 1;
+                        }
+                        Some((start_line + 1, end_line + 1))
+                    }
+                    _ if open => None,
+                    _ => {
+                        let line = buffer.line_of_offset(cursor.offset()) + 1;
+                        Some((line, line))
+                    }
+                };
+                (Some(path.clone()), lines)
+            })
+        });
+        let Some(path) = path else {
+            return;
+        };
+
+        let config = self.common.config;
+        let notification = self.notification;
+        let send = create_ext_action(
+            self.scope,
+            move |result: Result<ProxyResponse, RpcError>| {
+                let config = config.get_untracked();
+                let file = match result {
+                    Ok(ProxyResponse::GitGetRemoteFile { file }) => file,
+                    Ok(_) => return,
+                    Err(e) => {
+                        notification.notify(Notification::error(
+                            config.tr("notification-git-permalink-failed"),
+                            e.message,
+                        ));
+                        return;
+                    }
+                };
+                let url = git_permalink(
+                    &file,
+                    lines,
+                    &config.core.git_permalink_templates,
+                );
+                if open {
+                    if let Err(e) = open::that(&url) {
+                        error!("failed to open {url}: {e}");
+                    }
+                } else {
+                    SystemClipboard {}.put_string(url);
+                }
+            },
+        );
+        self.common.proxy.git_get_remote_file(path, move |result| {
+            send(result);
+        });
+    }
+
+    /// Render the selection of the active editor, or its whole file, as HTML
+    /// with the colors of the theme. The page is saved where the dialog picks,
+    /// or opened in the browser to print it, which can print it to a PDF too.
+    fn export_html(&self, print: bool) {
+        let Some(editor) = self.main_split.active_editor.get_untracked() else {
+            return;
+        };
+        let config = self.common.config.get_untracked();
+        let (name, html) = editor.with_untracked(|editor| {
+            let cursor = editor.cursor.get_untracked();
+            editor.doc.with_untracked(|doc| {
+                let buffer = doc.buffer();
+                let (start_line, end_line) = match cursor.get_selection() {
+                    Some((start, end)) if start != end => {
+                        let (start, end) = (start.min(end), start.max(end));
+                        let start_line = buffer.line_of_offset(start);
+                        let mut end_line = buffer.line_of_offset(end);
+                        // A selection to the start of a line doesn't have it
+                        if end_line > start_line
+                            && buffer.offset_of_line(end_line) == end
+                        {
+                            end_line -= 1;
+                        }
+                        (start_line, end_line)
+                    }
+                    _ => (0, buffer.last_line()),
+                };
+                let name = doc
+                    .content
+                    .path()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "untitled".to_string());
+                let options = HtmlOptions {
+                    title: name.clone(),
+                    font_family: config.editor.font_family.clone(),
+                    font_size: config.editor.font_size(),
+                    tab_width: config.editor.tab_width,
+                    background: *config.get_color(LapceColor::EDITOR_BACKGROUND),
+                    foreground: *config.get_color(LapceColor::EDITOR_FOREGROUND),
+                    dim: *config.get_color(LapceColor::EDITOR_DIM),
+                    line_numbers: config.editor.export_line_numbers,
+                    print,
+                };
+                let lines = doc.html_lines(start_line, end_line);
+                (name, render_html(&lines, &options))
+            })
+        });
+
+        let notification = self.notification;
+        let write = move |path: &Path| {
+            if let Err(e) = std::fs::write(path, &html) {
+                notification.notify(Notification::error(
+                    config.tr("notification-export-html-failed"),
+                    e.to_string(),
+                ));
+                return false;
+            }
+            true
+        };
+        if print {
+            let path = std::env::temp_dir().join(format!("lapce-print-{name}.html"));
+            if write(&path) {
+                open_uri(&path);
+            }
+        } else {
+            let options =
+                FileDialogOptions::new().default_name(format!("{name}.html"));
+            self.common
+                .view_id
+                .get_untracked()
+                .save_as(options, move |file| {
+                    if let Some(file) = file {
+                        write(&file.path);
+                    }
+                });
+        }
+    }
+
     fn run_and_debug(
         &self,
         cx: Scope,
@@ -1674,22 +3475,947 @@ fn run_and_debug(
     ) {
         match mode {
             RunDebugMode::Run => {
-                self.run_in_terminal(cx, mode, config);
+                self.run_in_terminal(cx, mode, config, true);
             }
             RunDebugMode::Debug => {
-                self.common.proxy.dap_start(
-                    config.clone(),
-                    self.terminal.debug.source_breakpoints(),
+                if let Some(runnable) = config.runnable.clone() {
+                    self.debug_runnable(runnable);
+                    return;
+                }
+                self.terminal.debug.reset_breakpoints();
+                // A program which is only run doesn't stop at the breakpoints
+                let breakpoints = if config.no_debug {
+                    HashMap::new()
+                } else {
+                    self.terminal.debug.source_breakpoints()
+                };
+                self.common.proxy.dap_start(config.clone(), breakpoints);
+            }
+        }
+    }
+
+    /// Run the config without debugging it. A config with a debug adapter is
+    /// launched by the adapter with `noDebug`, since the adapter knows how to
+    /// start its program, and the others are run in the terminal.
+    fn run_without_debugging(&self, cx: Scope, mut config: RunDebugConfig) {
+        if config.mode == RunDebugConfigMode::Attach {
+            return;
+        }
+        if config.adapter.is_some() {
+            config.no_debug = true;
+            self.run_and_debug(cx, &RunDebugMode::Debug, &config);
+        } else {
+            self.run_and_debug(cx, &RunDebugMode::Run, &config);
+        }
+    }
+
+    /// Load the coverage report of the settings, which the proxy watches for
+    /// changes when it's set to.
+    fn load_coverage(&self) {
+        let (report, watch) = self.common.config.with_untracked(|config| {
+            let report = &config.core.coverage_report;
+            (
+                (!report.is_empty()).then(|| PathBuf::from(report)),
+                config.core.coverage_watch,
+            )
+        });
+        let config = self.common.config;
+        let notification = self.notification;
+        let coverage = self.common.coverage;
+        let send = create_ext_action(
+            self.scope,
+            move |result: Result<ProxyResponse, RpcError>| match result {
+                Ok(ProxyResponse::LoadCoverage {
+                    coverage: new_coverage,
+                    ..
+                }) => {
+                    coverage.set(new_coverage);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    notification.notify(Notification::error(
+                        config.get_untracked().tr("notification-coverage-failed"),
+                        e.message,
+                    ));
+                }
+            },
+        );
+        self.common
+            .proxy
+            .load_coverage(report, watch, move |result| {
+                send(result);
+            });
+    }
+
+    /// Play the last macro on the active editor, which is where the focus goes
+    /// back to from the palette.
+    fn play_last_macro(&self, over_selections: bool) {
+        let Some(editor) = self.main_split.active_editor.get_untracked() else {
+            return;
+        };
+        let keypress = self.common.keypress.get_untracked();
+        keypress.play_last_macro(&editor.get_untracked(), 1, over_selections);
+    }
+
+    /// Send the request of the open `.http` file whose block has the line, and
+    /// show the response panel for it.
+    fn send_http_request(&self, path: PathBuf, line: usize) {
+        if !is_http_file(&path) {
+            return;
+        }
+        let Some(doc) = self
+            .main_split
+            .docs
+            .with_untracked(|docs| docs.get(&path).cloned())
+        else {
+            return;
+        };
+        let text = doc.with_untracked(|doc| doc.buffer().text().to_string());
+        self.http_client.send(path, text, line);
+        self.show_panel(PanelKind::HttpResponse);
+    }
+
+    /// Ask for the answer to a prompt of SSH, in the palette when something is to
+    /// be typed and with a notification otherwise.
+    fn handle_ssh_prompt(&self, prompt: &SshPrompt) {
+        let config = self.common.config.get_untracked();
+        let host = || [("host", FluentValue::from(prompt.host.clone()))];
+        match prompt.kind {
+            SshPromptKind::Secret => {
+                self.palette.run_ssh_prompt(self.scope, prompt.clone());
+            }
+            SshPromptKind::HostKey | SshPromptKind::Confirm => {
+                let (title, accept, answer) =
+                    if prompt.kind == SshPromptKind::HostKey {
+                        ("notification-ssh-host-key", "ssh-trust-host", "yes")
+                    } else {
+                        ("notification-ssh-confirm", "ssh-continue", "")
+                    };
+                self.notification.notify(
+                    Notification::new(
+                        NotificationSeverity::Warning,
+                        config.tr_args(title, host()),
+                        prompt.prompt.trim(),
+                    )
+                    .with_action(
+                        config.tr(accept),
+                        NotificationCommand::Internal(
+                            InternalCommand::AnswerSshPrompt {
+                                prompt: prompt.clone(),
+                                answer: Some(answer.to_string()),
+                            },
+                        ),
+                    )
+                    .with_action(
+                        config.tr("ssh-cancel"),
+                        NotificationCommand::Internal(
+                            InternalCommand::AnswerSshPrompt {
+                                prompt: prompt.clone(),
+                                answer: None,
+                            },
+                        ),
+                    ),
+                );
+            }
+            SshPromptKind::Info => {
+                self.notification.notify(Notification::new(
+                    NotificationSeverity::Info,
+                    config.tr_args("notification-ssh-info", host()),
+                    prompt.prompt.trim(),
+                ));
+                prompt.answer(Some(String::new()));
+            }
+        }
+    }
+
+    fn handle_collab_event(&self, event: &CollabEvent) {
+        let collab = &self.common.collab;
+        let Some(role) = collab.role() else {
+            return;
+        };
+        let config = self.common.config.get_untracked();
+        match event {
+            CollabEvent::Hosted { invite } => {
+                collab.session.update(|session| {
+                    if let Some(session) = session.as_mut() {
+                        session.invite = Some(invite.clone());
+                    }
+                });
+                SystemClipboard {}.put_string(invite.to_string());
+                self.notification.notify(Notification::new(
+                    NotificationSeverity::Info,
+                    config.tr("notification-collab-invite-copied"),
+                    invite.to_string(),
+                ));
+            }
+            CollabEvent::Message { from, message } => match role {
+                CollabRole::Host => self.handle_collab_guest_message(*from, message),
+                CollabRole::Guest => self.handle_collab_host_message(message),
+            },
+            CollabEvent::Disconnected { participant } => match role {
+                CollabRole::Host => {
+                    let name = collab.session.with_untracked(|session| {
+                        session
+                            .as_ref()
+                            .and_then(|session| session.participant(*participant))
+                            .map(|p| p.name.clone())
+                    });
+                    // A guest which was rejected never joined
+                    let Some(name) = name else {
+                        return;
+                    };
+                    self.remove_collab_participant(*participant);
+                    collab.send(CollabMessage::ParticipantLeft {
+                        participant: *participant,
+                    });
+                    self.notification.notify(Notification::new(
+                        NotificationSeverity::Info,
+                        config.tr_args(
+                            "notification-collab-left",
+                            [("name", FluentValue::from(name))],
+                        ),
+                        "",
+                    ));
+                }
+                CollabRole::Guest => {
+                    self.stop_collaboration();
+                    self.notification.notify(Notification::new(
+                        NotificationSeverity::Info,
+                        config.tr("notification-collab-ended"),
+                        "",
+                    ));
+                }
+            },
+            CollabEvent::Failed { error } => {
+                self.stop_collaboration();
+                self.notification.notify(Notification::error(
+                    config.tr("notification-collab-failed"),
+                    error,
+                ));
+            }
+        }
+    }
+
+    /// Handle a message of a guest, on the host.
+    fn handle_collab_guest_message(
+        &self,
+        from: ParticipantId,
+        message: &CollabMessage,
+    ) {
+        let collab = &self.common.collab;
+        let config = self.common.config.get_untracked();
+
+        if let CollabMessage::Join { name, token } = message {
+            if !collab.is_token(token) {
+                collab.send_to(
+                    from,
+                    CollabMessage::Rejected {
+                        reason: "the invite isn't the one of the session"
+                            .to_string(),
+                    },
+                );
+                collab.close(from);
+                return;
+            }
+            let participant = Participant {
+                id: from,
+                name: name.clone(),
+                permissions: Permissions {
+                    edit: config.core.collab_guests_can_edit,
+                    terminal: false,
+                },
+            };
+            collab.session.update(|session| {
+                if let Some(session) = session.as_mut() {
+                    session.participants.push_back(participant.clone());
+                }
+            });
+            let (participants, files, terminals) =
+                collab.session.with_untracked(|session| {
+                    session
+                        .as_ref()
+                        .map(|session| {
+                            (
+                                session.participants.iter().cloned().collect(),
+                                session.files.iter().cloned().collect(),
+                                session.terminals.iter().cloned().collect(),
+                            )
+                        })
+                        .unwrap_or_default()
+                });
+            collab.send_to(
+                from,
+                CollabMessage::Welcome {
+                    participant: from,
+                    participants,
+                    files,
+                    terminals,
+                },
+            );
+            collab.forward(
+                from,
+                CollabMessage::ParticipantJoined {
+                    participant: participant.clone(),
+                },
+            );
+            self.notification.notify(Notification::new(
+                NotificationSeverity::Info,
+                config.tr_args(
+                    "notification-collab-joined",
+                    [("name", FluentValue::from(participant.name))],
+                ),
+                "",
+            ));
+            return;
+        }
+
+        // Only the guests who joined with the invite are listened to
+        let Some(permissions) = collab.session.with_untracked(|session| {
+            session
+                .as_ref()
+                .and_then(|session| session.participant(from))
+                .map(|p| p.permissions)
+        }) else {
+            return;
+        };
+        let is_shared_file = |path: &PathBuf| {
+            collab.session.with_untracked(|session| {
+                session
+                    .as_ref()
+                    .map_or(false, |session| session.files.contains(path))
+            })
+        };
+
+        match message {
+            CollabMessage::OpenFile { path } => {
+                if is_shared_file(path) {
+                    self.send_collab_file(from, path.clone());
+                }
+            }
+            CollabMessage::Ops { path, ops } => {
+                if !permissions.edit || !collab.is_shared(path) {
+                    // The guest's copy has the edits which weren't made here, so
+                    // it's replaced with this one
+                    if is_shared_file(path) {
+                        self.send_collab_file(from, path.clone());
+                    }
+                    return;
+                }
+                self.apply_collab_ops(path, ops);
+                collab.forward(
+                    from,
+                    CollabMessage::Ops {
+                        path: path.clone(),
+                        ops: ops.clone(),
+                    },
+                );
+            }
+            CollabMessage::Cursor {
+                participant,
+                path,
+                anchor,
+                selection,
+                top,
+            } if *participant == from => {
+                self.set_collab_cursor(
+                    from,
+                    CollabCursor {
+                        path: path.clone(),
+                        anchor: *anchor,
+                        selection: selection.clone(),
+                        top: *top,
+                    },
+                );
+                collab.forward(from, message.clone());
+            }
+            CollabMessage::Ping {
+                participant,
+                path,
+                start,
+                end,
+            } if *participant == from => {
+                self.show_collab_ping(from, path.clone(), *start, *end);
+                collab.forward(from, message.clone());
+            }
+            CollabMessage::Save { path } => {
+                if permissions.edit && is_shared_file(path) {
+                    self.save_collab_file(path.clone());
+                }
+            }
+            CollabMessage::TerminalInput { term_id, content } => {
+                let is_shared = collab.session.with_untracked(|session| {
+                    session.as_ref().map_or(false, |session| {
+                        session.terminals.iter().any(|t| t.term_id == *term_id)
+                    })
+                });
+                if permissions.terminal && is_shared {
+                    self.common.proxy.terminal_write(*term_id, content.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle a message of the host, on a guest.
+    fn handle_collab_host_message(&self, message: &CollabMessage) {
+        let collab = &self.common.collab;
+        let config = self.common.config.get_untracked();
+        match message {
+            CollabMessage::Welcome {
+                participant,
+                participants,
+                files,
+                terminals,
+            } => {
+                collab.session.update(|session| {
+                    if let Some(session) = session.as_mut() {
+                        session.participant = Some(*participant);
+                        session.participants =
+                            participants.iter().cloned().collect();
+                        session.files = files.iter().cloned().collect();
+                    }
+                });
+                for terminal in terminals {
+                    self.open_collab_terminal(terminal);
+                }
+            }
+            CollabMessage::Rejected { reason } => {
+                self.stop_collaboration();
+                self.notification.notify(Notification::error(
+                    config.tr("notification-collab-rejected"),
+                    reason,
+                ));
+            }
+            CollabMessage::ParticipantJoined { participant } => {
+                collab.session.update(|session| {
+                    if let Some(session) = session.as_mut() {
+                        session.participants.push_back(participant.clone());
+                    }
+                });
+            }
+            CollabMessage::ParticipantLeft { participant } => {
+                self.remove_collab_participant(*participant);
+            }
+            CollabMessage::PermissionsChanged {
+                participant,
+                permissions,
+            } => {
+                let mut is_me = false;
+                collab.session.update(|session| {
+                    if let Some(session) = session.as_mut() {
+                        if let Some(p) = session
+                            .participants
+                            .iter_mut()
+                            .find(|p| p.id == *participant)
+                        {
+                            p.permissions = *permissions;
+                        }
+                        is_me = session.participant == Some(*participant);
+                    }
+                });
+                if is_me {
+                    for doc in self.collab_guest_docs() {
+                        doc.update(|doc| doc.set_read_only(!permissions.edit));
+                    }
+                }
+            }
+            CollabMessage::Files { files } => {
+                let files: im::Vector<PathBuf> = files.iter().cloned().collect();
+                collab.retain_files(&files.iter().cloned().collect());
+                // The files which the host closed can't be edited anymore
+                for doc in self.collab_guest_docs() {
+                    let shared = doc
+                        .with_untracked(|doc| doc.content.path().cloned())
+                        .and_then(|path| collab.shared_path(&path));
+                    if shared.map_or(true, |path| !files.contains(&path)) {
+                        doc.update(|doc| doc.set_read_only(true));
+                    }
+                }
+                collab.session.update(|session| {
+                    if let Some(session) = session.as_mut() {
+                        session.files = files;
+                    }
+                });
+            }
+            CollabMessage::FileContent { path, snapshot } => {
+                let Some(participant) = collab
+                    .session
+                    .with_untracked(|session| session.as_ref()?.participant)
+                else {
+                    return;
+                };
+                let Some(local) = collab.local_path(path) else {
+                    return;
+                };
+                let can_edit = collab.session.with_untracked(|session| {
+                    session
+                        .as_ref()
+                        .and_then(|session| session.permissions())
+                        .map_or(false, |permissions| permissions.edit)
+                });
+                let content = Rope::from(collab.open_file(
+                    path.clone(),
+                    participant,
+                    snapshot.clone(),
+                ));
+                let (doc, _) = self.main_split.get_doc(local);
+                if doc.with_untracked(|doc| doc.loaded()) {
+                    collab.apply_remote(|| {
+                        doc.update(|doc| doc.reload(content, false));
+                    });
+                } else {
+                    doc.update(|doc| doc.init_content(content));
+                }
+                doc.update(|doc| doc.set_read_only(!can_edit));
+            }
+            CollabMessage::Ops { path, ops } => {
+                self.apply_collab_ops(path, ops);
+            }
+            CollabMessage::Cursor {
+                participant,
+                path,
+                anchor,
+                selection,
+                top,
+            } => {
+                self.set_collab_cursor(
+                    *participant,
+                    CollabCursor {
+                        path: path.clone(),
+                        anchor: *anchor,
+                        selection: selection.clone(),
+                        top: *top,
+                    },
+                );
+            }
+            CollabMessage::Ping {
+                participant,
+                path,
+                start,
+                end,
+            } => {
+                self.show_collab_ping(*participant, path.clone(), *start, *end);
+            }
+            CollabMessage::Saved { path } => {
+                if let Some(doc) = collab.local_path(path).and_then(|path| {
+                    self.main_split
+                        .docs
+                        .with_untracked(|docs| docs.get(&path).cloned())
+                }) {
+                    doc.update(|doc| doc.set_pristine());
+                }
+            }
+            CollabMessage::TerminalOpened { terminal } => {
+                self.open_collab_terminal(terminal);
+            }
+            CollabMessage::TerminalOutput { term_id, content } => {
+                if let Some(term_id) = collab.guest_terminal(*term_id) {
+                    let _ = self
+                        .common
+                        .term_tx
+                        .send((term_id, TermEvent::UpdateContent(content.clone())));
+                }
+            }
+            CollabMessage::TerminalClosed { term_id } => {
+                self.close_collab_terminal(*term_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply the ops which another participant made to the file, to its document
+    /// here when it's open.
+    fn apply_collab_ops(&self, path: &Path, ops: &[CrdtOp]) {
+        let collab = &self.common.collab;
+        let edits = collab.apply_ops(path, ops);
+        let Some(doc) = collab.local_path(path).and_then(|path| {
+            self.main_split
+                .docs
+                .with_untracked(|docs| docs.get(&path).cloned())
+        }) else {
+            return;
+        };
+        collab.apply_remote(|| {
+            for edits in edits {
+                self.main_split.apply_remote_edit(doc, edits);
+            }
+        });
+    }
+
+    fn set_collab_cursor(&self, participant: ParticipantId, cursor: CollabCursor) {
+        let collab = &self.common.collab;
+        collab.set_cursor(participant, cursor);
+        if collab.following.get_untracked() == Some(participant) {
+            self.follow_collab_cursor(participant);
+        }
+    }
+
+    /// Go to the file and the cursor of the participant, scrolled to the lines
+    /// which they see.
+    fn follow_collab_cursor(&self, participant: ParticipantId) {
+        let collab = &self.common.collab;
+        let Some(cursor) = collab
+            .cursors
+            .with_untracked(|cursors| cursors.get(&participant).cloned())
+        else {
+            return;
+        };
+        let Some(path) = collab.local_path(&cursor.path) else {
+            return;
+        };
+        let offset = collab.anchor_offset(&cursor.path, cursor.anchor);
+        let scroll_offset =
+            collab
+                .anchor_offset(&cursor.path, cursor.top)
+                .and_then(|top| {
+                    let doc = self
+                        .main_split
+                        .docs
+                        .with_untracked(|docs| docs.get(&path).cloned())?;
+                    let line = doc.with_untracked(|doc| {
+                        doc.loaded().then(|| doc.buffer().line_of_offset(top))
+                    })?;
+                    let line_height =
+                        self.common.config.get_untracked().editor.line_height();
+                    Some(Vec2::new(0.0, (line * line_height) as f64))
+                });
+        self.main_split.go_to_location(
+            EditorLocation {
+                path,
+                position: offset.map(EditorPosition::Offset),
+                scroll_offset,
+                ignore_unconfirmed: false,
+                same_editor_tab: false,
+            },
+            None,
+        );
+    }
+
+    /// Ping what's selected in the active editor, or the line of its cursor, to
+    /// the others in the collaboration session.
+    fn ping_collab_selection(&self) {
+        let Some(editor) = self.main_split.active_editor.get_untracked() else {
+            return;
+        };
+        let (doc, cursor) =
+            editor.with_untracked(|editor| (editor.doc, editor.cursor));
+        let Some(path) = doc.with_untracked(|doc| doc.content.path().cloned())
+        else {
+            return;
+        };
+        let range = doc.with_untracked(|doc| {
+            let buffer = doc.buffer();
+            let selection =
+                cursor.with_untracked(|cursor| cursor.edit_selection(buffer));
+            let (start, end) = (selection.min_offset(), selection.max_offset());
+            if start < end && !cursor.with_untracked(|cursor| cursor.is_normal()) {
+                start..end
+            } else {
+                let line = buffer.line_of_offset(start);
+                buffer.offset_of_line(line)..buffer.line_end_offset(line, true)
+            }
+        });
+        if let Some(ping) = self.common.collab.local_ping(&path, range) {
+            self.add_collab_ping(ping);
+        }
+    }
+
+    /// Highlight the range which another participant pinged, and tell about it.
+    fn show_collab_ping(
+        &self,
+        participant: ParticipantId,
+        path: PathBuf,
+        start: Option<CharId>,
+        end: Option<CharId>,
+    ) {
+        let collab = &self.common.collab;
+        let Some(local) = collab.local_path(&path) else {
+            return;
+        };
+        let name = collab
+            .session
+            .with_untracked(|session| {
+                session
+                    .as_ref()?
+                    .participant(participant)
+                    .map(|p| p.name.clone())
+            })
+            .unwrap_or_default();
+        let config = self.common.config.get_untracked();
+        self.notification.notify(
+            Notification::new(
+                NotificationSeverity::Info,
+                config.tr_args(
+                    "notification-collab-pinged",
+                    [("name", FluentValue::from(name))],
+                ),
+                path.to_string_lossy().to_string(),
+            )
+            .with_action(
+                config.tr("collab-go-to-ping"),
+                NotificationCommand::Internal(InternalCommand::JumpToLocation {
+                    location: EditorLocation {
+                        path: local,
+                        position: collab
+                            .anchor_offset(&path, start)
+                            .map(EditorPosition::Offset),
+                        scroll_offset: None,
+                        ignore_unconfirmed: false,
+                        same_editor_tab: false,
+                    },
+                }),
+            ),
+        );
+        let ping = collab.new_ping(participant, path, start, end);
+        self.add_collab_ping(ping);
+    }
+
+    /// Show the ping until it's been highlighted for a while.
+    fn add_collab_ping(&self, ping: CollabPing) {
+        let pings = self.common.collab.pings;
+        let id = ping.id;
+        pings.update(|pings| pings.push_back(ping));
+        let send = create_ext_action(self.scope, move |_| {
+            pings.update(|pings| pings.retain(|ping| ping.id != id));
+        });
+        std::thread::spawn(move || {
+            std::thread::sleep(COLLAB_PING_DURATION);
+            send(());
+        });
+    }
+
+    fn remove_collab_participant(&self, participant: ParticipantId) {
+        let collab = &self.common.collab;
+        collab.session.update(|session| {
+            if let Some(session) = session.as_mut() {
+                session.participants.retain(|p| p.id != participant);
+            }
+        });
+        collab.cursors.update(|cursors| {
+            cursors.remove(&participant);
+        });
+        if collab.following.get_untracked() == Some(participant) {
+            collab.following.set(None);
+        }
+    }
+
+    /// The documents of the files which a guest has open from the host.
+    fn collab_guest_docs(&self) -> Vec<RwSignal<Document>> {
+        self.main_split.docs.with_untracked(|docs| {
+            docs.iter()
+                .filter(|(path, _)| is_guest_path(path))
+                .map(|(_, doc)| *doc)
+                .collect()
+        })
+    }
+
+    /// Send the content of the file to the guest, once it's loaded when it isn't
+    /// open here.
+    fn send_collab_file(&self, to: ParticipantId, path: PathBuf) {
+        let Some(local) = self.common.collab.local_path(&path) else {
+            return;
+        };
+        let (doc, new_doc) = self.main_split.get_doc(local.clone());
+        if new_doc {
+            let buffer_id = doc.with_untracked(|doc| doc.buffer_id);
+            let send = create_ext_action(self.scope, move |(content, read_only)| {
+                doc.update(|doc| {
+                    doc.init_content(content);
+                    doc.set_read_only(read_only);
+                });
+            });
+            self.common
+                .proxy
+                .new_buffer(buffer_id, local, move |result| {
+                    if let Ok(ProxyResponse::NewBufferResponse {
+                        content,
+                        read_only,
+                    }) = result
+                    {
+                        send((Rope::from(content), read_only))
+                    }
+                });
+        }
+
+        let collab = self.common.collab.clone();
+        create_effect(self.scope, move |done| {
+            if done == Some(true) {
+                return true;
+            }
+            let loaded = doc.with(|doc| doc.loaded());
+            if loaded {
+                let snapshot = collab.share_file(&path, || {
+                    doc.with_untracked(|doc| doc.buffer().text().to_string())
+                });
+                collab.send_to(
+                    to,
+                    CollabMessage::FileContent {
+                        path: path.clone(),
+                        snapshot,
+                    },
                 );
             }
+            loaded
+        });
+    }
+
+    /// Save the file for a guest, and tell the guests once it's saved.
+    fn save_collab_file(&self, path: PathBuf) {
+        let collab = self.common.collab.clone();
+        let Some(local) = collab.local_path(&path) else {
+            return;
+        };
+        let Some(doc) = self
+            .main_split
+            .docs
+            .with_untracked(|docs| docs.get(&local).cloned())
+        else {
+            return;
+        };
+        let rev = doc.with_untracked(|doc| doc.rev());
+        let send = create_ext_action(self.scope, move |result| {
+            if let Ok(ProxyResponse::SaveResponse {}) = result {
+                if doc.with_untracked(|doc| doc.rev()) == rev {
+                    doc.update(|doc| doc.set_pristine());
+                }
+                collab.send(CollabMessage::Saved { path });
+            }
+        });
+        let options = self.common.config.get_untracked().editor.save_options();
+        self.common.proxy.save(rev, local, options, move |result| {
+            send(result);
+        });
+    }
+
+    /// Let the guests see the active terminal.
+    fn share_terminal_in_collaboration(&self) {
+        let collab = &self.common.collab;
+        if collab.role() != Some(CollabRole::Host) {
+            return;
+        }
+        let Some(terminal) = self
+            .terminal
+            .active_tab(false)
+            .and_then(|tab| tab.active_terminal(false))
+        else {
+            return;
+        };
+        let term_id = terminal.term_id;
+        let is_shared = collab.session.with_untracked(|session| {
+            session.as_ref().map_or(false, |session| {
+                session.terminals.iter().any(|t| t.term_id == term_id)
+            })
+        });
+        if is_shared {
+            return;
+        }
+        let terminal = SharedTerminal {
+            term_id,
+            title: terminal.title.get_untracked(),
+        };
+        collab.shared_terminals.share(term_id);
+        collab.session.update(|session| {
+            if let Some(session) = session.as_mut() {
+                session.terminals.push_back(terminal.clone());
+            }
+        });
+        collab.send(CollabMessage::TerminalOpened { terminal });
+    }
+
+    /// Open a terminal which shows the terminal of the host.
+    fn open_collab_terminal(&self, terminal: &SharedTerminal) {
+        let collab = &self.common.collab;
+        if collab.guest_terminal(terminal.term_id).is_some() {
+            return;
+        }
+        let tab = self
+            .terminal
+            .new_shared_tab(terminal.term_id, terminal.title.clone());
+        if let Some(local) = tab.active_terminal(false) {
+            collab.set_guest_terminal(terminal.term_id, Some(local.term_id));
+        }
+        collab.session.update(|session| {
+            if let Some(session) = session.as_mut() {
+                session.terminals.push_back(terminal.clone());
+            }
+        });
+    }
+
+    fn close_collab_terminal(&self, term_id: TermId) {
+        let collab = &self.common.collab;
+        if let Some(local) = collab.guest_terminal(term_id) {
+            let _ = self.common.term_tx.send((local, TermEvent::CloseTerminal));
+            self.terminal.close_terminal(&local);
+            collab.set_guest_terminal(term_id, None);
+        }
+        collab.session.update(|session| {
+            if let Some(session) = session.as_mut() {
+                session.terminals.retain(|t| t.term_id != term_id);
+            }
+        });
+    }
+
+    /// Stop sharing the workspace, or leave the session, which closes the
+    /// terminals that were shown from the host.
+    fn stop_collaboration(&self) {
+        let collab = &self.common.collab;
+        if collab.role() == Some(CollabRole::Guest) {
+            let terminals = collab.session.with_untracked(|session| {
+                session
+                    .as_ref()
+                    .map(|session| session.terminals.clone())
+                    .unwrap_or_default()
+            });
+            for terminal in terminals {
+                self.close_collab_terminal(terminal.term_id);
+            }
+            for doc in self.collab_guest_docs() {
+                doc.update(|doc| doc.set_read_only(true));
+            }
         }
+        collab.stop();
+    }
+
+    /// Build the runnable of the language server, and debug the binary which it
+    /// runs once it's built.
+    fn debug_runnable(&self, runnable: Runnable) {
+        let config = self.common.config;
+        let notification = self.notification;
+        let proxy = self.common.proxy.clone();
+        let debug = self.terminal.debug.clone();
+        let label = runnable.label.clone();
+        let send = create_ext_action(
+            self.scope,
+            move |result: Result<ProxyResponse, RpcError>| match result {
+                Ok(ProxyResponse::BuildRunnable { config }) => {
+                    debug.reset_breakpoints();
+                    proxy.dap_start(config, debug.source_breakpoints());
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    notification.notify(Notification::error(
+                        config.get_untracked().tr_args(
+                            "notification-build-runnable-failed",
+                            [("runnable", FluentValue::from(label))],
+                        ),
+                        e.message,
+                    ));
+                }
+            },
+        );
+        self.common.proxy.build_runnable(runnable, move |result| {
+            send(result);
+        });
     }
 
+    /// Show the run or the debug session of the config in a terminal, which runs
+    /// its process unless the process isn't run `in_terminal`.
     fn run_in_terminal(
         &self,
         cx: Scope,
         mode: &RunDebugMode,
         config: &RunDebugConfig,
+        in_terminal: bool,
     ) {
         let term_id = if let Some(terminal) =
             self.terminal.get_stopped_run_debug_terminal(mode, config)
@@ -1699,6 +4425,7 @@ fn run_in_terminal(
                 config: config.clone(),
                 stopped: false,
                 created: Instant::now(),
+                in_terminal,
             }));
 
             terminal.term_id
@@ -1708,6 +4435,7 @@ fn run_in_terminal(
                 config: config.clone(),
                 stopped: false,
                 created: Instant::now(),
+                in_terminal,
             }));
             new_terminal_tab.active_terminal(false).unwrap().term_id
         };
@@ -1715,8 +4443,12 @@ fn run_in_terminal(
         self.terminal.focus_terminal(term_id);
 
         self.terminal.debug.active_term.set(Some(term_id));
+        let watches = self.terminal.debug.watches.get_untracked();
         self.terminal.debug.daps.update(|daps| {
-            daps.insert(config.dap_id, DapData::new(cx, config.dap_id, term_id));
+            daps.insert(
+                config.dap_id,
+                DapData::new(cx, config.dap_id, term_id, &watches),
+            );
         });
 
         if !self.panel.is_panel_visible(&PanelKind::Terminal) {
@@ -1724,6 +4456,146 @@ fn run_in_terminal(
         }
     }
 
+    /// Show the values of the variables of the frame which the thread stopped
+    /// in at the ends of the lines of its file, when it's open. They're the
+    /// values which the language server points out up to the line, or the
+    /// variables of the first scope of the frame when it has none.
+    fn show_inline_values(
+        &self,
+        dap_id: DapId,
+        stopped: &Stopped,
+        stack_frames: &HashMap<ThreadId, Vec<StackFrame>>,
+    ) {
+        self.clear_inline_values(dap_id);
+        if !self
+            .common
+            .config
+            .get_untracked()
+            .editor
+            .enable_inline_values
+        {
+            return;
+        }
+        let Some(frame) = stack_frames
+            .get(&stopped.thread_id.unwrap_or_default())
+            .and_then(|frames| frames.first())
+        else {
+            return;
+        };
+        let Some(path) = frame.source.as_ref().and_then(|s| s.path.clone()) else {
+            return;
+        };
+        let Some(doc) = self
+            .main_split
+            .docs
+            .with_untracked(|docs| docs.get(&path).cloned())
+        else {
+            return;
+        };
+        let Some(dap) = self
+            .terminal
+            .debug
+            .daps
+            .with_untracked(|daps| daps.get(&dap_id).cloned())
+        else {
+            return;
+        };
+
+        let frame_id = frame.id;
+        let line = frame.line.saturating_sub(1);
+        let stopped_at = lsp_types::Position::new(
+            line as u32,
+            frame.column.saturating_sub(1) as u32,
+        );
+        let stopped_location = lsp_types::Range::new(stopped_at, stopped_at);
+        let range = lsp_types::Range::new(
+            lsp_types::Position::new(0, 0),
+            lsp_types::Position::new(line as u32 + 1, 0),
+        );
+        let rev = doc.with_untracked(|doc| doc.rev());
+        let send = create_ext_action(
+            self.scope,
+            move |(variables, lsp_values): (
+                Vec<lapce_rpc::dap_types::Variable>,
+                Vec<lapce_rpc::inline_value::InlineValue>,
+            )| {
+                if !dap.stopped.get_untracked() {
+                    return;
+                }
+                doc.update(|doc| {
+                    if doc.rev() == rev {
+                        let values = inline_values(
+                            doc.buffer(),
+                            line,
+                            &variables,
+                            &lsp_values,
+                        );
+                        doc.set_inline_values(dap_id, values);
+                    }
+                });
+            },
+        );
+
+        let proxy = self.common.proxy.clone();
+        self.common
+            .proxy
+            .dap_scopes(dap_id, frame_id, move |result| {
+                let Ok(ProxyResponse::DapScopes { scopes }) = result else {
+                    return;
+                };
+                let Some(scope) = scopes.into_iter().find(|scope| !scope.expensive)
+                else {
+                    return;
+                };
+                let local_proxy = proxy.clone();
+                proxy.dap_variables(
+                    dap_id,
+                    scope.variables_reference,
+                    move |result| {
+                        let Ok(ProxyResponse::DapVariables { variables }) = result
+                        else {
+                            return;
+                        };
+                        local_proxy.get_inline_values(
+                            path,
+                            range,
+                            frame_id,
+                            stopped_location,
+                            move |result| {
+                                // The variables are looked for in the text without a
+                                // language server which has inline values
+                                let lsp_values = match result {
+                                    Ok(ProxyResponse::GetInlineValues {
+                                        values,
+                                    }) => values,
+                                    _ => Vec::new(),
+                                };
+                                send((variables, lsp_values));
+                            },
+                        );
+                    },
+                );
+            });
+    }
+
+    /// Stop showing the values of the session in the editors.
+    fn clear_inline_values(&self, dap_id: DapId) {
+        let docs = self.main_split.docs.get_untracked();
+        for doc in docs.values() {
+            if doc.with_untracked(|doc| doc.has_inline_values(dap_id)) {
+                doc.update(|doc| doc.clear_inline_values(dap_id));
+            }
+        }
+    }
+
+    /// Show how much memory the documents and the terminals take.
+    pub fn show_memory_usage(&self) {
+        self.memory_usage.set(Some(MemoryReport::new(
+            self.main_split.memory_usage(),
+            self.terminal.memory_usage(),
+        )));
+    }
+
     pub fn open_paths(&self, paths: &[PathObject]) {
         let (folders, files): (Vec<&PathObject>, Vec<&PathObject>) =
             paths.iter().partition(|p| p.is_dir);
@@ -1766,6 +4638,60 @@ pub fn open_paths(&self, paths: &[PathObject]) {
     }
 }
 
+/// Open a new issue for the crash in the browser, with the report filled in,
+/// which is only sent once it's submitted there. The whole report is put into
+/// the clipboard too, as the backtrace in the url is cut short.
+fn report_crash(report: &CrashReport) {
+    SystemClipboard {}.put_string(crash_report_body(report, usize::MAX));
+    let url = Url::parse_with_params(
+        CRASH_REPORT_URL,
+        [
+            ("title", format!("Crash in the {}", report.subsystem)),
+            (
+                "body",
+                crash_report_body(report, CRASH_REPORT_BACKTRACE_LEN),
+            ),
+        ],
+    );
+    match url {
+        Ok(url) => {
+            if let Err(e) = open::that(url.as_str()) {
+                error!("failed to open the crash report: {e}");
+            }
+        }
+        Err(e) => error!("failed to make the crash report: {e}"),
+    }
+}
+
+/// The body of the issue of a crash, with the lines of the backtrace which fit
+/// in the given number of bytes.
+fn crash_report_body(report: &CrashReport, backtrace_len: usize) -> String {
+    let mut backtrace = String::new();
+    let mut cut = false;
+    for line in report.backtrace.lines() {
+        if backtrace.len() + line.len() + 1 > backtrace_len {
+            cut = true;
+            break;
+        }
+        backtrace.push_str(line);
+        backtrace.push('\n');
+    }
+    let mut body = format!(
+        "The {} panicked in Lapce {}:\n\n```\n{}\n\n{}```\n",
+        report.source(),
+        meta::VERSION,
+        report.message,
+        backtrace
+    );
+    if cut {
+        body.push_str(
+            "\nThe backtrace is cut short, paste the whole report from the \
+             clipboard here.\n",
+        );
+    }
+    body
+}
+
 /// Open path with the default application without blocking.
 fn open_uri(path: &Path) {
     match open::that(path) {
@@ -1777,3 +4703,36 @@ fn open_uri(path: &Path) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::crash::Subsystem;
+
+    use super::*;
+
+    #[test]
+    fn test_crash_report_body() {
+        let report = CrashReport {
+            subsystem: Subsystem::LanguageServer,
+            name: "rust-analyzer".to_string(),
+            message: "oops".to_string(),
+            backtrace: "0: a\n1: b\n2: c\n".to_string(),
+            restarted: true,
+        };
+        let start = format!(
+            "The language server rust-analyzer panicked in Lapce {}:\n\n```\noops\n\n",
+            meta::VERSION
+        );
+
+        let body = crash_report_body(&report, usize::MAX);
+        assert_eq!(body, format!("{start}0: a\n1: b\n2: c\n```\n"));
+        assert_eq!(body, crash_report_body(&report, 15));
+
+        // The backtrace is cut at the end of a line
+        let body = crash_report_body(&report, 14);
+        assert!(body.starts_with(&format!("{start}0: a\n1: b\n```\n")));
+        assert!(body.ends_with("from the clipboard here.\n"));
+        let body = crash_report_body(&report, 0);
+        assert!(body.starts_with(&format!("{start}```\n")));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -93,21 +93,32 @@ pub struct LapceWorkspace {
 
 impl LapceWorkspace {
     pub fn display(&self) -> Option<String> {
+        let path = self.name()?;
+        Some(match self.remote() {
+            Some(remote) => format!("{path} [{remote}]"),
+            None => path,
+        })
+    }
+
+    /// The name of the folder of the workspace
+    pub fn name(&self) -> Option<String> {
         let path = self.path.as_ref()?;
-        let path = path
-            .file_name()
-            .unwrap_or(path.as_os_str())
-            .to_string_lossy()
-            .to_string();
-        let remote = match &self.kind {
-            LapceWorkspaceType::Local => "".to_string(),
-            LapceWorkspaceType::RemoteSSH(ssh) => {
-                format!(" [SSH: {}]", ssh.host)
-            }
+        Some(
+            path.file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .to_string(),
+        )
+    }
+
+    /// Where a remote workspace is, like `SSH: host`
+    pub fn remote(&self) -> Option<String> {
+        match &self.kind {
+            LapceWorkspaceType::Local => None,
+            LapceWorkspaceType::RemoteSSH(ssh) => Some(format!("SSH: {}", ssh.host)),
             #[cfg(windows)]
-            LapceWorkspaceType::RemoteWSL => " [WSL]".to_string(),
-        };
-        Some(format!("{path}{remote}"))
+            LapceWorkspaceType::RemoteWSL => Some("WSL".to_string()),
+        }
     }
 }
 
@@ -136,4 +147,16 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 pub struct WorkspaceInfo {
     pub split: SplitInfo,
     pub panel: PanelInfo,
+    #[serde(default = "default_zoom")]
+    pub zoom: f64,
+    /// The watch expressions of the debug panel
+    #[serde(default)]
+    pub watches: Vec<String>,
+    /// The exception filters of the debuggers which were toggled, by their ids
+    #[serde(default)]
+    pub exception_filters: BTreeMap<String, bool>,
+}
+
+fn default_zoom() -> f64 {
+    1.0
 }
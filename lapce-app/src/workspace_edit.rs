@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use floem::reactive::RwSignal;
+use lapce_core::buffer::{rope_text::RopeText, Buffer};
+use lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf,
+    OptionalVersionedTextDocumentIdentifier, TextDocumentEdit, TextEdit, Url,
+    WorkspaceEdit,
+};
+
+use crate::doc::Document;
+
+/// The text edits of a workspace edit for one file
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileEdit {
+    pub path: PathBuf,
+    /// The version of the document which the edits were made for, when the
+    /// language server says which one it was
+    pub version: Option<i32>,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A workspace edit which was applied, which can be undone as a whole
+#[derive(Clone)]
+pub struct AppliedWorkspaceEdit {
+    /// The documents which were edited, with their revision after the edit
+    pub docs: Vec<(RwSignal<Document>, u64)>,
+}
+
+/// Split a workspace edit into the edits of each file. This fails when the edit
+/// is for something that isn't a file, as the edit can't be applied in full then.
+pub fn file_edits(edit: &WorkspaceEdit) -> Result<Vec<FileEdit>> {
+    if let Some(changes) = edit.changes.as_ref() {
+        return changes
+            .iter()
+            .map(|(url, edits)| {
+                Ok(FileEdit {
+                    path: url_path(url)?,
+                    version: None,
+                    edits: edits.clone(),
+                })
+            })
+            .collect();
+    }
+
+    let Some(changes) = edit.document_changes.as_ref() else {
+        return Ok(Vec::new());
+    };
+    let edits: Vec<&TextDocumentEdit> = match changes {
+        DocumentChanges::Edits(edits) => edits.iter().collect(),
+        DocumentChanges::Operations(ops) => ops
+            .iter()
+            .map(|op| match op {
+                DocumentChangeOperation::Edit(edit) => Ok(edit),
+                DocumentChangeOperation::Op(_) => Err(anyhow!(
+                    "creating, renaming and deleting files isn't supported"
+                )),
+            })
+            .collect::<Result<_>>()?,
+    };
+    edits
+        .into_iter()
+        .map(|edit| {
+            let OptionalVersionedTextDocumentIdentifier { uri, version } =
+                &edit.text_document;
+            Ok(FileEdit {
+                path: url_path(uri)?,
+                version: *version,
+                edits: edit
+                    .edits
+                    .iter()
+                    .map(|edit| match edit {
+                        OneOf::Left(edit) => edit.clone(),
+                        OneOf::Right(edit) => edit.text_edit.clone(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn url_path(url: &Url) -> Result<PathBuf> {
+    url.to_file_path()
+        .map_err(|_| anyhow!("{url} isn't a file"))
+}
+
+/// Check that the edits can be applied to the buffer as they are: the buffer is
+/// still the version the edits were made for, and the edits are within the
+/// buffer and don't overlap.
+pub fn check_file_edit(buffer: &Buffer, edit: &FileEdit) -> Result<()> {
+    let name = edit.path.display();
+    if let Some(version) = edit.version {
+        if version != buffer.rev() as i32 {
+            return Err(anyhow!("{name} has changed since the edit was made"));
+        }
+    }
+
+    // A position can be at the start of the line after the last one, which is
+    // the end of the buffer
+    let lines = buffer.last_line() as u32 + 1;
+    let mut ranges = Vec::new();
+    for text_edit in &edit.edits {
+        let range = text_edit.range;
+        if range.start.line > lines || range.end.line > lines {
+            return Err(anyhow!("the edit is outside of {name}"));
+        }
+        let start = buffer.offset_of_position(&range.start);
+        let end = buffer.offset_of_position(&range.end);
+        if start > end {
+            return Err(anyhow!("the edit of {name} has an invalid range"));
+        }
+        ranges.push((start, end));
+    }
+    ranges.sort();
+    if ranges.windows(2).any(|ranges| ranges[1].0 < ranges[0].1) {
+        return Err(anyhow!("the edits of {name} overlap"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use lapce_core::buffer::Buffer;
+    use lsp_types::{Position, Range, TextEdit, Url, WorkspaceEdit};
+
+    use super::{check_file_edit, file_edits, FileEdit};
+
+    fn text_edit(start: (u32, u32), end: (u32, u32)) -> TextEdit {
+        TextEdit {
+            range: Range {
+                start: Position::new(start.0, start.1),
+                end: Position::new(end.0, end.1),
+            },
+            new_text: "x".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_check_file_edit() {
+        let buffer = Buffer::new("let a = 1;\nlet b = a;\n");
+        let path = PathBuf::from("/a.rs");
+        let edit = |version, edits| FileEdit {
+            path: path.clone(),
+            version,
+            edits,
+        };
+
+        let edits = vec![text_edit((0, 4), (0, 5)), text_edit((1, 8), (1, 9))];
+        assert!(check_file_edit(&buffer, &edit(None, edits.clone())).is_ok());
+        assert!(check_file_edit(
+            &buffer,
+            &edit(Some(buffer.rev() as i32), edits.clone())
+        )
+        .is_ok());
+        assert!(check_file_edit(
+            &buffer,
+            &edit(Some(buffer.rev() as i32 + 1), edits)
+        )
+        .is_err());
+
+        let overlapping = vec![text_edit((0, 0), (0, 5)), text_edit((0, 4), (0, 6))];
+        assert!(check_file_edit(&buffer, &edit(None, overlapping)).is_err());
+
+        let outside = vec![text_edit((5, 0), (5, 1))];
+        assert!(check_file_edit(&buffer, &edit(None, outside)).is_err());
+    }
+
+    #[test]
+    fn test_file_edits() {
+        let url = Url::from_file_path("/a.rs").unwrap();
+        let edit = WorkspaceEdit {
+            changes: Some(HashMap::from([(url, vec![text_edit((0, 0), (0, 1))])])),
+            ..Default::default()
+        };
+        let edits = file_edits(&edit).unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].path, PathBuf::from("/a.rs"));
+        assert_eq!(edits[0].version, None);
+
+        let edit = WorkspaceEdit {
+            changes: Some(HashMap::from([(
+                Url::parse("untitled:Untitled-1").unwrap(),
+                vec![text_edit((0, 0), (0, 1))],
+            )])),
+            ..Default::default()
+        };
+        assert!(file_edits(&edit).is_err());
+    }
+}
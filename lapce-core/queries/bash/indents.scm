@@ -0,0 +1,29 @@
+; The lines in these nodes are indented, except their first line
+[
+  (array)
+  (case_item)
+  (case_statement)
+  (compound_statement)
+  (do_group)
+  (function_definition)
+  (if_statement)
+  (subshell)
+] @indent
+
+[
+  "}"
+  ")"
+  "done"
+  "elif"
+  "else"
+  "esac"
+  "fi"
+  "then"
+] @outdent
+
+[
+  (comment)
+  (heredoc_body)
+  (raw_string)
+  (string)
+] @ignore
@@ -0,0 +1,22 @@
+; Definitions
+
+(function_definition
+  declarator: (function_declarator declarator: (identifier) @name)) @definition.function
+(struct_specifier name: (type_identifier) @name body: (_)) @definition.struct
+(enum_specifier name: (type_identifier) @name body: (_)) @definition.enum
+(type_definition declarator: (type_identifier) @name) @definition.type
+(preproc_def name: (identifier) @name) @definition.constant
+(preproc_function_def name: (identifier) @name) @definition.macro
+
+; Local variables
+
+(function_definition) @local.scope
+(compound_statement) @local.scope
+(for_statement) @local.scope
+
+(parameter_declaration declarator: (identifier) @local.definition)
+(parameter_declaration
+  declarator: (pointer_declarator declarator: (identifier) @local.definition))
+(declaration declarator: (identifier) @local.definition)
+(declaration
+  declarator: (init_declarator declarator: (identifier) @local.definition))
@@ -0,0 +1,35 @@
+; The lines in these nodes are indented, except their first line
+[
+  (argument_list)
+  (compound_statement)
+  (enumerator_list)
+  (field_declaration_list)
+  (initializer_list)
+  (parameter_list)
+  (parenthesized_expression)
+  (case_statement)
+  (condition_clause)
+  (field_initializer_list)
+  (template_argument_list)
+  (template_parameter_list)
+  (lambda_expression)
+  (init_declarator)
+  (binary_expression)
+  (if_statement)
+  (for_statement)
+  (while_statement)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+  "else"
+] @outdent
+
+[
+  (comment)
+  (string_literal)
+  (preproc_arg)
+  (raw_string_literal)
+] @ignore
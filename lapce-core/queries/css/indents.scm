@@ -0,0 +1,13 @@
+; The lines in these nodes are indented, except their first line
+[
+  (block)
+  (declaration)
+  (arguments)
+] @indent
+
+[
+  "}"
+  ")"
+] @outdent
+
+(comment) @ignore
@@ -0,0 +1,35 @@
+; The lines in these nodes are indented, except their first line
+[
+  (argument_list)
+  (block)
+  (communication_case)
+  (const_declaration)
+  (default_case)
+  (expression_case)
+  (expression_switch_statement)
+  (field_declaration_list)
+  (import_spec_list)
+  (interface_type)
+  (literal_value)
+  (parameter_list)
+  (select_statement)
+  (type_case)
+  (type_declaration)
+  (type_switch_statement)
+  (var_declaration)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+  (default_case)
+  (expression_case)
+  (type_case)
+  (communication_case)
+] @outdent
+
+[
+  (comment)
+  (raw_string_literal)
+] @ignore
@@ -0,0 +1,21 @@
+; Definitions
+
+(function_declaration name: (identifier) @name) @definition.function
+(method_declaration name: (field_identifier) @name) @definition.method
+(type_spec name: (type_identifier) @name) @definition.type
+(const_spec name: (identifier) @name) @definition.constant
+(source_file
+  (var_declaration
+    (var_spec name: (identifier) @name) @definition.variable))
+
+; Local variables
+
+(function_declaration) @local.scope
+(method_declaration) @local.scope
+(func_literal) @local.scope
+(block) @local.scope
+
+(parameter_declaration name: (identifier) @local.definition)
+(short_var_declaration left: (expression_list (identifier) @local.definition))
+(var_spec name: (identifier) @local.definition)
+(range_clause left: (expression_list (identifier) @local.definition))
@@ -0,0 +1,19 @@
+; The lines in these nodes are indented, except their first line
+[
+  (element)
+  (script_element)
+  (style_element)
+  (start_tag)
+  (self_closing_tag)
+] @indent
+
+[
+  (end_tag)
+  ">"
+  "/>"
+] @outdent
+
+[
+  (comment)
+  (raw_text)
+] @ignore
@@ -0,0 +1,27 @@
+; The lines in these nodes are indented, except their first line
+[
+  (annotation_argument_list)
+  (argument_list)
+  (array_initializer)
+  (block)
+  (class_body)
+  (constructor_body)
+  (enum_body)
+  (formal_parameters)
+  (interface_body)
+  (switch_block)
+  (switch_block_statement_group)
+  (binary_expression)
+  (method_invocation)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+] @outdent
+
+[
+  (block_comment)
+  (line_comment)
+] @ignore
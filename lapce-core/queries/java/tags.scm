@@ -0,0 +1,19 @@
+; Definitions
+
+(class_declaration name: (identifier) @name) @definition.class
+(interface_declaration name: (identifier) @name) @definition.interface
+(enum_declaration name: (identifier) @name) @definition.enum
+(method_declaration name: (identifier) @name) @definition.method
+(constructor_declaration name: (identifier) @name) @definition.constructor
+(field_declaration
+  declarator: (variable_declarator name: (identifier) @name)) @definition.field
+
+; Local variables
+
+(method_declaration) @local.scope
+(constructor_declaration) @local.scope
+(block) @local.scope
+
+(formal_parameter name: (identifier) @local.definition)
+(local_variable_declaration
+  declarator: (variable_declarator name: (identifier) @local.definition))
@@ -0,0 +1,43 @@
+; The lines in these nodes are indented, except their first line
+[
+  (arguments)
+  (array)
+  (arrow_function)
+  (assignment_expression)
+  (binary_expression)
+  (class_body)
+  (formal_parameters)
+  (jsx_element)
+  (jsx_opening_element)
+  (jsx_self_closing_element)
+  (lexical_declaration)
+  (member_expression)
+  (named_imports)
+  (export_clause)
+  (object)
+  (object_pattern)
+  (array_pattern)
+  (parenthesized_expression)
+  (statement_block)
+  (switch_body)
+  (switch_case)
+  (switch_default)
+  (template_substitution)
+  (ternary_expression)
+  (variable_declaration)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+  (jsx_closing_element)
+] @outdent
+
+(jsx_opening_element ">" @outdent)
+(jsx_self_closing_element "/>" @outdent)
+
+[
+  (comment)
+  (template_string)
+] @ignore
@@ -0,0 +1,24 @@
+; Definitions
+
+(function_declaration name: (identifier) @name) @definition.function
+(generator_function_declaration name: (identifier) @name) @definition.function
+(class_declaration name: (identifier) @name) @definition.class
+(method_definition name: (property_identifier) @name) @definition.method
+(variable_declarator
+  name: (identifier) @name
+  value: (arrow_function)) @definition.function
+(program
+  (lexical_declaration
+    (variable_declarator name: (identifier) @name) @definition.variable))
+
+; Local variables
+
+(statement_block) @local.scope
+(function_declaration) @local.scope
+(arrow_function) @local.scope
+(method_definition) @local.scope
+(for_statement) @local.scope
+
+(variable_declarator name: (identifier) @local.definition)
+(formal_parameters (identifier) @local.definition)
+(arrow_function parameter: (identifier) @local.definition)
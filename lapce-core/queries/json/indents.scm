@@ -0,0 +1,10 @@
+; The lines in these nodes are indented, except their first line
+[
+  (array)
+  (object)
+] @indent
+
+[
+  "}"
+  "]"
+] @outdent
@@ -0,0 +1,44 @@
+; The lines in these nodes are indented, except their first line
+[
+  (argument_list)
+  (class_definition)
+  (dictionary)
+  (for_statement)
+  (function_definition)
+  (if_statement)
+  (list)
+  (match_statement)
+  (case_clause)
+  (parameters)
+  (parenthesized_expression)
+  (set)
+  (try_statement)
+  (tuple)
+  (while_statement)
+  (with_statement)
+] @indent
+
+; A block goes on after the end of its last line, until the line is outdented
+[
+  (class_definition)
+  (for_statement)
+  (function_definition)
+  (if_statement)
+  (match_statement)
+  (case_clause)
+  (try_statement)
+  (while_statement)
+  (with_statement)
+] @extend
+
+[
+  "}"
+  "]"
+  ")"
+  (elif_clause)
+  (else_clause)
+  (except_clause)
+  (finally_clause)
+] @outdent
+
+(string) @ignore
@@ -0,0 +1,5 @@
+; SQL in strings which start with a SQL keyword
+
+((string) @injection.content
+  (#match? @injection.content "^[a-zA-Z]*(\"|')+\\s*(?i:select|insert|update|delete|create|alter|drop|with)\\b")
+  (#set! injection.language "sql"))
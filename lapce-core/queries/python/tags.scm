@@ -0,0 +1,19 @@
+; Definitions
+
+(class_definition name: (identifier) @name) @definition.class
+(function_definition name: (identifier) @name) @definition.function
+(module
+  (expression_statement
+    (assignment left: (identifier) @name) @definition.variable))
+
+; Local variables
+
+(function_definition) @local.scope
+(lambda) @local.scope
+
+(parameters (identifier) @local.definition)
+(default_parameter name: (identifier) @local.definition)
+(typed_parameter (identifier) @local.definition)
+(typed_default_parameter name: (identifier) @local.definition)
+(assignment left: (identifier) @local.definition)
+(for_statement left: (identifier) @local.definition)
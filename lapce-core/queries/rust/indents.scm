@@ -0,0 +1,40 @@
+; The lines in these nodes are indented, except their first line
+[
+  (arguments)
+  (array_expression)
+  (assignment_expression)
+  (binary_expression)
+  (block)
+  (declaration_list)
+  (enum_variant_list)
+  (field_declaration_list)
+  (field_expression)
+  (field_initializer_list)
+  (let_declaration)
+  (match_arm)
+  (match_block)
+  (ordered_field_declaration_list)
+  (parameters)
+  (struct_pattern)
+  (token_tree)
+  (tuple_expression)
+  (tuple_pattern)
+  (tuple_type)
+  (type_arguments)
+  (type_parameters)
+  (use_list)
+  (where_clause)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+] @outdent
+
+[
+  (string_literal)
+  (raw_string_literal)
+  (line_comment)
+  (block_comment)
+] @ignore
@@ -0,0 +1,16 @@
+; SQL in strings which start with a SQL keyword
+
+((string_literal) @injection.content
+  (#match? @injection.content "^\"\\s*(?i:select|insert|update|delete|create|alter|drop|with)\\b")
+  (#set! injection.language "sql"))
+
+; Raw strings have no child nodes for their quotes, which the offset leaves out
+((raw_string_literal) @injection.content
+  (#match? @injection.content "^r\"\\s*(?i:select|insert|update|delete|create|alter|drop|with)\\b")
+  (#offset! @injection.content 0 2 0 -1)
+  (#set! injection.language "sql"))
+
+((raw_string_literal) @injection.content
+  (#match? @injection.content "^r#\"\\s*(?i:select|insert|update|delete|create|alter|drop|with)\\b")
+  (#offset! @injection.content 0 3 0 -2)
+  (#set! injection.language "sql"))
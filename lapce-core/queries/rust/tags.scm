@@ -0,0 +1,28 @@
+; Definitions
+
+(struct_item name: (type_identifier) @name) @definition.struct
+(enum_item name: (type_identifier) @name) @definition.enum
+(union_item name: (type_identifier) @name) @definition.struct
+(type_item name: (type_identifier) @name) @definition.type
+(trait_item name: (type_identifier) @name) @definition.interface
+(function_item name: (identifier) @name) @definition.function
+(function_signature_item name: (identifier) @name) @definition.function
+(mod_item name: (identifier) @name) @definition.module
+(macro_definition name: (identifier) @name) @definition.macro
+(const_item name: (identifier) @name) @definition.constant
+(static_item name: (identifier) @name) @definition.constant
+(enum_variant name: (identifier) @name) @definition.enum_member
+(field_declaration name: (field_identifier) @name) @definition.field
+
+; Local variables
+
+(block) @local.scope
+(function_item) @local.scope
+(closure_expression) @local.scope
+(for_expression) @local.scope
+(match_arm) @local.scope
+
+(let_declaration pattern: (identifier) @local.definition)
+(parameter pattern: (identifier) @local.definition)
+(closure_parameters (identifier) @local.definition)
+(for_expression pattern: (identifier) @local.definition)
@@ -0,0 +1,12 @@
+; The lines in these nodes are indented, except their first line
+[
+  (array)
+  (inline_table)
+] @indent
+
+[
+  "}"
+  "]"
+] @outdent
+
+(string) @ignore
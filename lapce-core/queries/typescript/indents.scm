@@ -0,0 +1,40 @@
+; The lines in these nodes are indented, except their first line
+[
+  (arguments)
+  (array)
+  (arrow_function)
+  (assignment_expression)
+  (binary_expression)
+  (class_body)
+  (enum_body)
+  (formal_parameters)
+  (lexical_declaration)
+  (member_expression)
+  (named_imports)
+  (export_clause)
+  (object)
+  (object_pattern)
+  (object_type)
+  (array_pattern)
+  (parenthesized_expression)
+  (statement_block)
+  (switch_body)
+  (switch_case)
+  (switch_default)
+  (template_substitution)
+  (ternary_expression)
+  (type_arguments)
+  (type_parameters)
+  (variable_declaration)
+] @indent
+
+[
+  "}"
+  "]"
+  ")"
+] @outdent
+
+[
+  (comment)
+  (template_string)
+] @ignore
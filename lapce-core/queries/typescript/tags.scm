@@ -0,0 +1,28 @@
+; Definitions
+
+(function_declaration name: (identifier) @name) @definition.function
+(class_declaration name: (type_identifier) @name) @definition.class
+(abstract_class_declaration name: (type_identifier) @name) @definition.class
+(interface_declaration name: (type_identifier) @name) @definition.interface
+(type_alias_declaration name: (type_identifier) @name) @definition.type
+(enum_declaration name: (identifier) @name) @definition.enum
+(method_definition name: (property_identifier) @name) @definition.method
+(variable_declarator
+  name: (identifier) @name
+  value: (arrow_function)) @definition.function
+(program
+  (lexical_declaration
+    (variable_declarator name: (identifier) @name) @definition.variable))
+
+; Local variables
+
+(statement_block) @local.scope
+(function_declaration) @local.scope
+(arrow_function) @local.scope
+(method_definition) @local.scope
+(for_statement) @local.scope
+
+(variable_declarator name: (identifier) @local.definition)
+(required_parameter pattern: (identifier) @local.definition)
+(optional_parameter pattern: (identifier) @local.definition)
+(arrow_function parameter: (identifier) @local.definition)
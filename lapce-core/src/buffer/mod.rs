@@ -151,6 +151,18 @@ pub fn is_pristine(&self) -> bool {
         self.is_equivalent_revision(self.pristine_rev_id, self.rev())
     }
 
+    /// The text of the buffer when it was last marked as pristine.
+    pub fn pristine_text(&self) -> Option<Rope> {
+        let rev_index = self.find_rev(self.pristine_rev_id)?;
+        let deletes_from_union = self.deletes_from_cur_union_for_index(rev_index);
+        let delta = Delta::synthesize(
+            &self.tombstones,
+            &self.deletes_from_union,
+            &deletes_from_union,
+        );
+        Some(delta.apply(&self.text))
+    }
+
     pub fn set_cursor_before(&mut self, cursor: CursorMode) {
         if let Some(rev) = self.revs.last_mut() {
             rev.cursor_before = Some(cursor);
@@ -188,15 +200,11 @@ pub fn atomic_rev(&self) -> Arc<AtomicU64> {
     }
 
     fn get_max_line_len(&self) -> (usize, usize) {
-        let mut pre_offset = 0;
         let mut max_len = 0;
         let mut max_len_line = 0;
-        for line in 0..=self.num_lines() {
-            let offset = self.offset_of_line(line);
-            let line_len = offset - pre_offset;
-            pre_offset = offset;
-            if line_len > max_len {
-                max_len = line_len;
+        for (line, content) in self.text.lines_raw(..).enumerate() {
+            if content.len() > max_len {
+                max_len = content.len();
                 max_len_line = line;
             }
         }
@@ -204,31 +212,32 @@ fn get_max_line_len(&self) -> (usize, usize) {
     }
 
     fn update_size(&mut self, inval_lines: &InvalLines) {
-        if self.max_len_line >= inval_lines.start_line
+        let mut max_len = 0;
+        let mut max_len_line = 0;
+        for line in
+            inval_lines.start_line..inval_lines.start_line + inval_lines.new_count
+        {
+            let line_len = self.line_len(line);
+            if line_len > max_len {
+                max_len = line_len;
+                max_len_line = line;
+            }
+        }
+
+        if max_len >= self.max_len {
+            // None of the other lines are longer than the longest one was
+            self.max_len = max_len;
+            self.max_len_line = max_len_line;
+        } else if self.max_len_line >= inval_lines.start_line
             && self.max_len_line <= inval_lines.start_line + inval_lines.inval_count
         {
+            // The longest line got shorter, and any line can be the longest now
             let (max_len, max_len_line) = self.get_max_line_len();
             self.max_len = max_len;
             self.max_len_line = max_len_line;
-        } else {
-            let mut max_len = 0;
-            let mut max_len_line = 0;
-            for line in inval_lines.start_line
-                ..inval_lines.start_line + inval_lines.new_count
-            {
-                let line_len = self.line_len(line);
-                if line_len > max_len {
-                    max_len = line_len;
-                    max_len_line = line;
-                }
-            }
-            if max_len > self.max_len {
-                self.max_len = max_len;
-                self.max_len_line = max_len_line;
-            } else if self.max_len_line >= inval_lines.start_line {
-                self.max_len_line = self.max_len_line + inval_lines.new_count
-                    - inval_lines.inval_count;
-            }
+        } else if self.max_len_line >= inval_lines.start_line {
+            self.max_len_line =
+                self.max_len_line + inval_lines.new_count - inval_lines.inval_count;
         }
     }
 
@@ -289,7 +298,6 @@ pub fn edit(
         edits: &[(impl AsRef<Selection>, &str)],
         edit_type: EditType,
     ) -> (RopeDelta, InvalLines, SyntaxEdit) {
-        let mut builder = DeltaBuilder::new(self.len());
         let mut interval_rope = Vec::new();
         for (selection, content) in edits {
             let rope = Rope::from(content);
@@ -297,6 +305,31 @@ pub fn edit(
                 interval_rope.push((region.min(), region.max(), rope.clone()));
             }
         }
+        self.edit_intervals(interval_rope, edit_type)
+    }
+
+    /// Replace the ranges of the text with their content, as one revision and one
+    /// delta however many there are. The ranges are of the text before the edit
+    /// and must not overlap, but they don't need to be in order, so a lot of
+    /// edits, like those of a rename, don't need a selection of them.
+    pub fn edit_ranges<'a>(
+        &mut self,
+        edits: impl IntoIterator<Item = (Range<usize>, &'a str)>,
+        edit_type: EditType,
+    ) -> (RopeDelta, InvalLines, SyntaxEdit) {
+        let interval_rope = edits
+            .into_iter()
+            .map(|(range, content)| (range.start, range.end, Rope::from(content)))
+            .collect();
+        self.edit_intervals(interval_rope, edit_type)
+    }
+
+    fn edit_intervals(
+        &mut self,
+        mut interval_rope: Vec<(usize, usize, Rope)>,
+        edit_type: EditType,
+    ) -> (RopeDelta, InvalLines, SyntaxEdit) {
+        let mut builder = DeltaBuilder::new(self.len());
         interval_rope.sort_by(|a, b| {
             if a.0 == b.0 && a.1 == b.1 {
                 Ordering::Equal
@@ -1,12 +1,14 @@
 use std::{borrow::Cow, ops::Range};
 
-use lapce_xi_rope::{interval::IntervalBounds, rope::ChunkIter, Cursor, Rope};
+use lapce_xi_rope::{
+    interval::IntervalBounds,
+    rope::{ChunkIter, Utf16CodeUnitsMetric},
+    Cursor, Rope,
+};
 use lsp_types::Position;
 
 use crate::{
-    encoding::{offset_utf16_to_utf8, offset_utf8_to_utf16},
-    mode::Mode,
-    paragraph::ParagraphCursor,
+    encoding::PositionEncoding, mode::Mode, paragraph::ParagraphCursor,
     word::WordCursor,
 };
 
@@ -49,14 +51,32 @@ fn line_of_offset(&self, offset: usize) -> usize {
         self.text().line_of_offset(offset)
     }
 
-    /// Converts a UTF8 offset to a UTF16 LSP position  
-    /// Returns None if it is not a valid UTF16 offset
+    /// The UTF16 offset of a UTF8 offset, from the UTF16 lengths which the nodes
+    /// of the rope keep, so this doesn't go through the text before it.  
+    /// An offset inside of a character is taken as the end of it.
+    fn offset_to_utf16(&self, offset: usize) -> usize {
+        let text = self.text();
+        let offset = offset.min(text.len());
+        let offset = text.at_or_next_codepoint_boundary(offset).unwrap_or(offset);
+        text.count::<Utf16CodeUnitsMetric>(offset)
+    }
+
+    /// The UTF8 offset of a UTF16 offset.  
+    /// An offset inside of a surrogate pair is taken as the end of the character,
+    /// and one past the end as the end of the text.
+    fn utf16_to_offset(&self, utf16_offset: usize) -> usize {
+        let text = self.text();
+        let utf16_offset = utf16_offset.min(text.measure::<Utf16CodeUnitsMetric>());
+        text.count_base_units::<Utf16CodeUnitsMetric>(utf16_offset)
+    }
+
+    /// Converts a UTF8 offset to a UTF16 LSP position
     fn offset_to_position(&self, offset: usize) -> Position {
         let (line, col) = self.offset_to_line_col(offset);
         let line_offset = self.offset_of_line(line);
 
-        let utf16_col =
-            offset_utf8_to_utf16(self.char_indices_iter(line_offset..), col);
+        let utf16_col = self.offset_to_utf16(line_offset + col)
+            - self.offset_to_utf16(line_offset);
 
         Position {
             line: line as u32,
@@ -70,16 +90,85 @@ fn offset_of_position(&self, pos: &Position) -> usize {
         self.offset_of_line_col(line, column)
     }
 
+    /// Converts a UTF8 offset to an LSP position whose character is counted in
+    /// the encoding.
+    fn offset_to_encoded_position(
+        &self,
+        offset: usize,
+        encoding: PositionEncoding,
+    ) -> Position {
+        match encoding {
+            PositionEncoding::Utf16 => self.offset_to_position(offset),
+            PositionEncoding::Utf8 => {
+                let text = self.text();
+                let offset = offset.min(text.len());
+                let offset =
+                    text.at_or_next_codepoint_boundary(offset).unwrap_or(offset);
+                let (line, col) = self.offset_to_line_col(offset);
+                Position {
+                    line: line as u32,
+                    character: col as u32,
+                }
+            }
+        }
+    }
+
+    fn offset_of_encoded_position(
+        &self,
+        pos: &Position,
+        encoding: PositionEncoding,
+    ) -> usize {
+        match encoding {
+            PositionEncoding::Utf16 => self.offset_of_position(pos),
+            PositionEncoding::Utf8 => {
+                self.offset_of_line_col(pos.line as usize, pos.character as usize)
+            }
+        }
+    }
+
+    /// The same position with its character counted in another encoding.
+    fn convert_position(
+        &self,
+        pos: &Position,
+        from: PositionEncoding,
+        to: PositionEncoding,
+    ) -> Position {
+        if from == to {
+            return *pos;
+        }
+        let offset = self.offset_of_encoded_position(pos, from);
+        self.offset_to_encoded_position(offset, to)
+    }
+
+    /// The offset which is the number of code units of the encoding after the
+    /// offset, like the end of a semantic token from its start and length.
+    fn offset_after_encoded(
+        &self,
+        offset: usize,
+        units: usize,
+        encoding: PositionEncoding,
+    ) -> usize {
+        match encoding {
+            PositionEncoding::Utf16 => {
+                self.utf16_to_offset(self.offset_to_utf16(offset) + units)
+            }
+            PositionEncoding::Utf8 => {
+                let text = self.text();
+                let offset = (offset + units).min(text.len());
+                text.at_or_next_codepoint_boundary(offset).unwrap_or(offset)
+            }
+        }
+    }
+
     fn position_to_line_col(&self, pos: &Position) -> (usize, usize) {
         let line = pos.line as usize;
         let line_offset = self.offset_of_line(line);
 
-        let column = offset_utf16_to_utf8(
-            self.char_indices_iter(line_offset..),
-            pos.character as usize,
+        let offset = self.utf16_to_offset(
+            self.offset_to_utf16(line_offset) + pos.character as usize,
         );
 
-        (line, column)
+        (line, offset - line_offset)
     }
 
     fn offset_to_line_col(&self, offset: usize) -> (usize, usize) {
@@ -94,25 +183,18 @@ fn offset_to_line_col(&self, offset: usize) -> (usize, usize) {
         (line, col)
     }
 
+    /// The offset of the column of the line, which is at most the end of the line
+    /// before the line ending, and at the start of the character that the column
+    /// is in.
     fn offset_of_line_col(&self, line: usize, col: usize) -> usize {
-        let mut pos = 0;
-        let mut offset = self.offset_of_line(line);
-        for c in self
-            .slice_to_cow(offset..self.offset_of_line(line + 1))
-            .chars()
-        {
-            if c == '\n' {
-                return offset;
-            }
-
-            let char_len = c.len_utf8();
-            if pos + char_len > col {
-                return offset;
-            }
-            pos += char_len;
-            offset += char_len;
+        let text = self.text();
+        let line_start = self.offset_of_line(line);
+        let mut line_end = self.offset_of_line(line + 1);
+        if line_end > line_start && text.byte_at(line_end - 1) == b'\n' {
+            line_end -= 1;
         }
-        offset
+        let offset = (line_start + col).min(line_end);
+        text.at_or_prev_codepoint_boundary(offset).unwrap_or(offset)
     }
 
     fn line_end_col(&self, line: usize, caret: bool) -> usize {
@@ -505,9 +587,10 @@ fn next(&mut self) -> Option<Self::Item> {
 #[cfg(test)]
 mod tests {
     use lapce_xi_rope::Rope;
+    use lsp_types::Position;
 
     use super::RopeText;
-    use crate::buffer::rope_text::RopeTextVal;
+    use crate::{buffer::rope_text::RopeTextVal, encoding::PositionEncoding};
 
     #[test]
     fn test_line_content() {
@@ -657,4 +740,66 @@ fn test_first_non_blank_character_on_line() {
         assert_eq!(text.first_non_blank_character_on_line(4), 10);
         assert_eq!(text.first_non_blank_character_on_line(5), 10);
     }
+
+    #[test]
+    fn test_offset_to_position() {
+        let text = Rope::from("a×b\n😀c\r\nd");
+        let text = RopeTextVal::new(text);
+
+        assert_eq!(text.offset_to_position(0), Position::new(0, 0));
+        assert_eq!(text.offset_to_position(3), Position::new(0, 2));
+        // In the middle of a character
+        assert_eq!(text.offset_to_position(2), Position::new(0, 2));
+        assert_eq!(text.offset_to_position(9), Position::new(1, 2));
+        assert_eq!(text.offset_to_position(10), Position::new(1, 3));
+        assert_eq!(text.offset_to_position(13), Position::new(2, 1));
+
+        assert_eq!(text.offset_of_position(&Position::new(0, 2)), 3);
+        assert_eq!(text.offset_of_position(&Position::new(1, 2)), 9);
+        // In the middle of a surrogate pair
+        assert_eq!(text.offset_of_position(&Position::new(1, 1)), 9);
+        // Past the end of the line
+        assert_eq!(text.offset_of_position(&Position::new(0, 10)), 4);
+        assert_eq!(text.offset_of_position(&Position::new(1, 10)), 11);
+        assert_eq!(text.offset_of_position(&Position::new(2, 10)), 13);
+    }
+
+    #[test]
+    fn test_encoded_position() {
+        let text = Rope::from("a×b\n😀c\r\nd");
+        let text = RopeTextVal::new(text);
+        let utf8 = PositionEncoding::Utf8;
+        let utf16 = PositionEncoding::Utf16;
+
+        assert_eq!(
+            text.offset_to_encoded_position(9, utf8),
+            Position::new(1, 4)
+        );
+        // In the middle of a character
+        assert_eq!(
+            text.offset_to_encoded_position(6, utf8),
+            Position::new(1, 4)
+        );
+        assert_eq!(
+            text.offset_of_encoded_position(&Position::new(0, 3), utf8),
+            3
+        );
+        assert_eq!(
+            text.offset_of_encoded_position(&Position::new(0, 10), utf8),
+            4
+        );
+
+        assert_eq!(
+            text.convert_position(&Position::new(1, 3), utf16, utf8),
+            Position::new(1, 5)
+        );
+        assert_eq!(
+            text.convert_position(&Position::new(0, 3), utf8, utf16),
+            Position::new(0, 2)
+        );
+
+        assert_eq!(text.offset_after_encoded(5, 2, utf16), 9);
+        assert_eq!(text.offset_after_encoded(5, 4, utf8), 9);
+        assert_eq!(text.offset_after_encoded(12, 4, utf8), 13);
+    }
 }
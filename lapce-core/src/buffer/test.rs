@@ -16,6 +16,42 @@ fn is_pristine() {
         buffer.do_undo();
         assert!(buffer.is_pristine());
     }
+
+    #[test]
+    fn pristine_text() {
+        let mut buffer = Buffer::new("");
+        buffer.init_content(Rope::from("abc\n"));
+        buffer.edit(&[(Selection::caret(0), "d")], EditType::InsertChars);
+        buffer.edit(&[(Selection::region(2, 4), "")], EditType::Delete);
+        assert_eq!(buffer.to_string(), "da\n");
+        assert_eq!(buffer.pristine_text().unwrap().to_string(), "abc\n");
+
+        buffer.set_pristine();
+        buffer.edit(&[(Selection::caret(3), "e")], EditType::InsertChars);
+        assert_eq!(buffer.pristine_text().unwrap().to_string(), "da\n");
+    }
+
+    #[test]
+    fn edit_ranges() {
+        let mut buffer = Buffer::new("");
+        buffer.init_content(Rope::from("let a = a + a;\nlongest line\n"));
+        let rev = buffer.rev();
+        buffer
+            .edit_ranges([(12..13, "b"), (4..5, "b"), (8..9, "b")], EditType::Other);
+        assert_eq!(buffer.to_string(), "let b = b + b;\nlongest line\n");
+        assert_eq!(buffer.rev(), rev + 1);
+        assert_eq!(buffer.max_len(), 15);
+
+        buffer.edit_ranges([(15..22, "")], EditType::Other);
+        assert_eq!(buffer.to_string(), "let b = b + b;\n line\n");
+        assert_eq!(buffer.max_len(), 15);
+
+        buffer.edit_ranges([(0..15, "")], EditType::Other);
+        assert_eq!(buffer.max_len(), 6);
+
+        buffer.do_undo();
+        assert_eq!(buffer.to_string(), "let b = b + b;\n line\n");
+    }
 }
 
 mod motion {
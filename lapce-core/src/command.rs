@@ -58,6 +58,9 @@ pub enum EditCommand {
     #[strum(message = "Outdent Line")]
     #[strum(serialize = "outdent_line")]
     OutdentLine,
+    #[strum(message = "Reindent Lines")]
+    #[strum(serialize = "reindent_lines")]
+    ReindentLines,
     #[strum(message = "Toggle Line Comment")]
     #[strum(serialize = "toggle_line_comment")]
     ToggleLineComment,
@@ -74,6 +77,12 @@ pub enum EditCommand {
     #[strum(message = "Paste")]
     #[strum(serialize = "clipboard_paste")]
     ClipboardPaste,
+    #[strum(message = "Paste Without Formatting")]
+    #[strum(serialize = "clipboard_paste_without_formatting")]
+    ClipboardPasteWithoutFormatting,
+    #[strum(message = "Paste as One Line")]
+    #[strum(serialize = "clipboard_paste_as_one_line")]
+    ClipboardPasteAsOneLine,
     #[strum(serialize = "yank")]
     Yank,
     #[strum(serialize = "paste")]
@@ -101,6 +110,18 @@ pub enum EditCommand {
     DuplicateLineUp,
     #[strum(serialize = "duplicate_line_down")]
     DuplicateLineDown,
+    #[strum(message = "Move Statement Up")]
+    #[strum(serialize = "move_statement_up")]
+    MoveStatementUp,
+    #[strum(message = "Move Statement Down")]
+    #[strum(serialize = "move_statement_down")]
+    MoveStatementDown,
+    #[strum(message = "Swap Argument with Previous")]
+    #[strum(serialize = "swap_argument_previous")]
+    SwapArgumentPrevious,
+    #[strum(message = "Swap Argument with Next")]
+    #[strum(serialize = "swap_argument_next")]
+    SwapArgumentNext,
 }
 
 #[derive(
@@ -272,6 +293,25 @@ pub enum FocusCommand {
     BottomOfWindow,
     #[strum(serialize = "show_code_actions")]
     ShowCodeActions,
+    #[strum(message = "Show Quick Fixes")]
+    #[strum(serialize = "show_quick_fixes")]
+    ShowQuickFixes,
+    #[strum(message = "Show Refactor Actions")]
+    #[strum(serialize = "show_refactor_actions")]
+    ShowRefactorActions,
+    #[strum(message = "Show Source Actions")]
+    #[strum(serialize = "show_source_actions")]
+    ShowSourceActions,
+    /// Apply the preferred quick fix at the cursor, or the only one there is
+    #[strum(message = "Auto Fix")]
+    #[strum(serialize = "auto_fix")]
+    AutoFix,
+    #[strum(message = "Organize Imports")]
+    #[strum(serialize = "organize_imports")]
+    OrganizeImports,
+    #[strum(message = "Remove Unused Imports")]
+    #[strum(serialize = "remove_unused_imports")]
+    RemoveUnusedImports,
     #[strum(serialize = "get_completion")]
     GetCompletion,
     #[strum(serialize = "get_signature")]
@@ -286,15 +326,39 @@ pub enum FocusCommand {
     #[strum(message = "Go to Type Definition")]
     #[strum(serialize = "goto_type_definition")]
     GotoTypeDefinition,
+    #[strum(message = "Find References")]
+    #[strum(serialize = "find_references")]
+    FindReferences,
     #[strum(message = "Show Hover")]
     #[strum(serialize = "show_hover")]
     ShowHover,
+    /// Bring the next of the completion, the signature and the hover to the
+    /// front
+    #[strum(message = "Cycle Popups")]
+    #[strum(serialize = "cycle_popups")]
+    CyclePopups,
+    /// Close the popup which is in front, before the others
+    #[strum(message = "Close Popup")]
+    #[strum(serialize = "close_popup")]
+    ClosePopup,
     #[strum(message = "Go to Next Difference")]
     #[strum(serialize = "next_diff")]
     NextDiff,
     #[strum(message = "Go to Previous Difference")]
     #[strum(serialize = "previous_diff")]
     PreviousDiff,
+    /// Show the lines which the change on the line of the cursor removed
+    #[strum(message = "Show Difference")]
+    #[strum(serialize = "show_diff")]
+    ShowDiff,
+    #[strum(message = "Revert Difference")]
+    #[strum(serialize = "revert_diff")]
+    RevertDiff,
+    /// Show the unsaved changes in the gutter rather than the source control
+    /// ones, or go back to those
+    #[strum(message = "Compare with Saved")]
+    #[strum(serialize = "compare_with_saved")]
+    CompareWithSaved,
     #[strum(message = "Toggle Code Lens")]
     #[strum(serialize = "toggle_code_lens")]
     ToggleCodeLens,
@@ -339,6 +403,15 @@ pub enum FocusCommand {
     SelectNextSyntaxItem,
     #[strum(serialize = "select_previous_syntax_item")]
     SelectPreviousSyntaxItem,
+    #[strum(message = "Wrap Lines at Column")]
+    #[strum(serialize = "wrap_lines")]
+    WrapLines,
+    #[strum(message = "Edit Embedded Code")]
+    #[strum(serialize = "edit_embedded_code")]
+    EditEmbeddedCode,
+    #[strum(message = "Pick Color")]
+    #[strum(serialize = "pick_color")]
+    PickColor,
     #[strum(serialize = "open_source_file")]
     OpenSourceFile,
 }
@@ -395,4 +468,10 @@ pub enum MultiSelectionCommand {
     SelectSkipCurrent,
     #[strum(serialize = "select_all")]
     SelectAll,
+    #[strum(message = "Expand Selection")]
+    #[strum(serialize = "expand_selection")]
+    ExpandSelection,
+    #[strum(message = "Shrink Selection")]
+    #[strum(serialize = "shrink_selection")]
+    ShrinkSelection,
 }
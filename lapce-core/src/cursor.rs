@@ -22,6 +22,9 @@ pub struct Cursor {
     pub horiz: Option<ColPosition>,
     pub motion_mode: Option<MotionMode>,
     pub history_selections: Vec<Selection>,
+    /// The selections before each time the selection was expanded to a syntax
+    /// node, newest last, which shrinking it goes back through
+    pub syntax_selections: Vec<Selection>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -66,6 +69,7 @@ pub fn new(
             horiz,
             motion_mode,
             history_selections: Vec::new(),
+            syntax_selections: Vec::new(),
         }
     }
 
@@ -128,6 +128,35 @@ pub fn config_directory() -> Option<PathBuf> {
         }
     }
 
+    /// Get the path to the profiles directory
+    /// Each profile is stored within as an individual toml file
+    pub fn profiles_directory() -> Option<PathBuf> {
+        if let Some(dir) = Self::config_directory() {
+            let dir = dir.join("profiles");
+            if !dir.exists() {
+                let _ = std::fs::create_dir(&dir);
+            }
+            Some(dir)
+        } else {
+            None
+        }
+    }
+
+    /// Get the path to the locales directory
+    /// Translations are stored within as individual ftl files, named after their
+    /// locale, e.g. `de-DE.ftl`
+    pub fn locales_directory() -> Option<PathBuf> {
+        if let Some(dir) = Self::config_directory() {
+            let dir = dir.join("locales");
+            if !dir.exists() {
+                let _ = std::fs::create_dir(&dir);
+            }
+            Some(dir)
+        } else {
+            None
+        }
+    }
+
     pub fn local_socket() -> Option<PathBuf> {
         Self::data_local_directory().map(|dir| dir.join("local.sock"))
     }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range};
 
 use itertools::Itertools;
 use lapce_xi_rope::RopeDelta;
@@ -341,7 +341,9 @@ fn insert_new_line(
         buffer: &mut Buffer,
         cursor: &mut Cursor,
         selection: Selection,
+        syntax: Option<&Syntax>,
     ) -> Vec<(RopeDelta, InvalLines, SyntaxEdit)> {
+        let syntax = syntax.filter(|syntax| syntax.rev == buffer.rev());
         let mut edits = Vec::with_capacity(selection.regions().len());
         let mut extra_edits = Vec::new();
         let mut shift = 0i32;
@@ -353,11 +355,45 @@ fn insert_new_line(
             let line_indent = buffer.indent_on_line(line);
             let first_half = buffer.slice_to_cow(line_start..offset);
             let second_half = buffer.slice_to_cow(offset..line_end);
+            // Where the text which goes on the new line starts
+            let text_start =
+                line_end - second_half.trim_start_matches([' ', '\t']).len();
             let second_half = second_half.trim();
 
+            // The new line is between a pair of brackets, and the closing one goes
+            // on a line of its own after it
+            let closing_pair = first_half
+                .chars()
+                .rev()
+                .find(|&c| c != ' ')
+                .filter(|&c| matching_pair_direction(c) == Some(true))
+                .and_then(matching_char)
+                .map(|c| second_half.starts_with(c))
+                .unwrap_or(false);
+
+            // The indentation which the indents query of the language gives, and
+            // the one of the closing bracket
+            let syntax_indent = syntax.and_then(|syntax| {
+                let level = syntax.line_indent_level(text_start)?;
+                let new_line_level = if closing_pair {
+                    syntax.indent_level(text_start)?
+                } else {
+                    level
+                };
+                let indent_unit = buffer.indent_unit();
+                Some((
+                    indent_unit.repeat(new_line_level),
+                    indent_unit.repeat(level),
+                ))
+            });
+
             let new_line_content = {
                 let indent_storage;
-                let indent = if has_unmatched_pair(&first_half) {
+                let indent = if let Some((indent, _)) = syntax_indent.as_ref() {
+                    indent
+                } else if has_unmatched_pair(&first_half)
+                    || first_half.trim_end().ends_with(':')
+                {
                     indent_storage =
                         format!("{}{}", line_indent, buffer.indent_unit());
                     &indent_storage
@@ -381,18 +417,15 @@ fn insert_new_line(
 
             edits.push((selection, new_line_content));
 
-            if let Some(c) = first_half.chars().rev().find(|&c| c != ' ') {
-                if let Some(true) = matching_pair_direction(c) {
-                    if let Some(c) = matching_char(c) {
-                        if second_half.starts_with(c) {
-                            let selection = Selection::caret(
-                                (region.max() as i32 + shift) as usize,
-                            );
-                            let content = format!("\n{line_indent}");
-                            extra_edits.push((selection, content));
-                        }
-                    }
-                }
+            if closing_pair {
+                let selection =
+                    Selection::caret((region.max() as i32 + shift) as usize);
+                let indent = syntax_indent
+                    .as_ref()
+                    .map(|(_, indent)| indent)
+                    .unwrap_or(&line_indent);
+                let content = format!("\n{indent}");
+                extra_edits.push((selection, content));
             }
         }
 
@@ -488,7 +521,22 @@ pub fn do_paste(
         cursor: &mut Cursor,
         buffer: &mut Buffer,
         data: &RegisterData,
+        syntax: Option<&Syntax>,
+    ) -> Vec<(RopeDelta, InvalLines, SyntaxEdit)> {
+        Self::paste(cursor, buffer, data, syntax, true)
+    }
+
+    /// Paste the data at the cursor, where the pasted lines are moved to the
+    /// indentation of where they're pasted when `reindent` is set.
+    fn paste(
+        cursor: &mut Cursor,
+        buffer: &mut Buffer,
+        data: &RegisterData,
+        syntax: Option<&Syntax>,
+        reindent: bool,
     ) -> Vec<(RopeDelta, InvalLines, SyntaxEdit)> {
+        // A linewise paste is only reindented with the syntax
+        let syntax = syntax.filter(|_| reindent);
         let mut deltas = Vec::new();
         match data.mode {
             VisualMode::Normal => {
@@ -502,9 +550,18 @@ pub fn do_paste(
                         cursor.edit_selection(buffer)
                     }
                 };
+                let content = if reindent && cursor.is_insert() {
+                    Self::reindent_inline_paste(
+                        buffer,
+                        selection.min_offset(),
+                        &data.content,
+                    )
+                } else {
+                    data.content.clone()
+                };
                 let after = cursor.is_insert() || !data.content.contains('\n');
                 let (delta, inval_lines, edits) =
-                    buffer.edit(&[(&selection, &data.content)], EditType::Paste);
+                    buffer.edit(&[(&selection, &content)], EditType::Paste);
                 let selection =
                     selection.apply_delta(&delta, after, InsertDrift::Default);
                 deltas.push((delta, inval_lines, edits));
@@ -531,7 +588,9 @@ pub fn do_paste(
                     CursorMode::Normal(offset) => {
                         let line = buffer.line_of_offset(*offset);
                         let offset = buffer.offset_of_line(line + 1);
-                        (Selection::caret(offset), data.content.clone())
+                        let content =
+                            Self::reindent_paste(buffer, syntax, offset, data);
+                        (Selection::caret(offset), content)
                     }
                     CursorMode::Insert(selection) => {
                         let mut selection = selection.clone();
@@ -543,12 +602,23 @@ pub fn do_paste(
                                 region.end = start;
                             }
                         }
-                        (selection, data.content.clone())
+                        let content = Self::reindent_paste(
+                            buffer,
+                            syntax,
+                            selection.min_offset(),
+                            data,
+                        );
+                        (selection, content)
                     }
                     CursorMode::Visual { mode, .. } => {
                         let selection = cursor.edit_selection(buffer);
                         let data = match mode {
-                            VisualMode::Linewise => data.content.clone(),
+                            VisualMode::Linewise => Self::reindent_paste(
+                                buffer,
+                                syntax,
+                                selection.min_offset(),
+                                data,
+                            ),
                             _ => "\n".to_string() + &data.content,
                         };
                         (selection, data)
@@ -652,6 +722,54 @@ fn do_outdent(
         buffer.edit(&edits, EditType::Outdent)
     }
 
+    /// Indent the lines of the selection by the indents query of the language,
+    /// which is `None` when they already are.
+    fn do_reindent(
+        buffer: &mut Buffer,
+        syntax: &Syntax,
+        selection: Selection,
+    ) -> Option<(RopeDelta, InvalLines, SyntaxEdit)> {
+        let indent = buffer.indent_unit();
+        let mut edits = Vec::new();
+
+        let mut lines = HashSet::new();
+        for region in selection.regions() {
+            let start_line = buffer.line_of_offset(region.min());
+            let mut end_line = buffer.line_of_offset(region.max());
+            if end_line > start_line {
+                let end_line_start = buffer.offset_of_line(end_line);
+                if end_line_start == region.max() {
+                    end_line -= 1;
+                }
+            }
+            for line in start_line..=end_line {
+                if lines.insert(line) {
+                    if buffer.line_content(line).trim().is_empty() {
+                        continue;
+                    }
+                    let start = buffer.offset_of_line(line);
+                    let nonblank = buffer.first_non_blank_character_on_line(line);
+                    let Some(level) = syntax.line_indent_level(nonblank) else {
+                        continue;
+                    };
+                    let new_indent = indent.repeat(level);
+                    if buffer.slice_to_cow(start..nonblank) != new_indent {
+                        edits.push((Selection::region(start, nonblank), new_indent));
+                    }
+                }
+            }
+        }
+        if edits.is_empty() {
+            return None;
+        }
+
+        let edits = edits
+            .iter()
+            .map(|(selection, s)| (selection, s.as_str()))
+            .collect::<Vec<_>>();
+        Some(buffer.edit(&edits, EditType::Indent))
+    }
+
     fn duplicate_line(
         cursor: &mut Cursor,
         buffer: &mut Buffer,
@@ -781,11 +899,14 @@ pub fn do_edit<T: Clipboard>(
                 deltas
             }
             InsertNewLine => match cursor.mode.clone() {
-                CursorMode::Normal(offset) => {
-                    Self::insert_new_line(buffer, cursor, Selection::caret(offset))
-                }
+                CursorMode::Normal(offset) => Self::insert_new_line(
+                    buffer,
+                    cursor,
+                    Selection::caret(offset),
+                    syntax,
+                ),
                 CursorMode::Insert(selection) => {
-                    Self::insert_new_line(buffer, cursor, selection)
+                    Self::insert_new_line(buffer, cursor, selection, syntax)
                 }
                 CursorMode::Visual {
                     start: _,
@@ -840,12 +961,11 @@ pub fn do_edit<T: Clipboard>(
                 let offset = cursor.offset();
                 let (line, _col) = buffer.offset_to_line_col(offset);
                 if line < buffer.last_line() {
-                    let start = buffer.line_end_offset(line, true);
-                    let end = buffer.first_non_blank_character_on_line(line + 1);
-                    vec![buffer.edit(
-                        &[(&Selection::region(start, end), " ")],
-                        EditType::Other,
-                    )]
+                    let syntax = syntax.filter(|syntax| syntax.rev == buffer.rev());
+                    let (delta, inval_lines, edits) =
+                        Self::join_lines(buffer, syntax, line);
+                    cursor.apply_delta(&delta);
+                    vec![(delta, inval_lines, edits)]
                 } else {
                     vec![]
                 }
@@ -857,6 +977,21 @@ pub fn do_edit<T: Clipboard>(
                 cursor.apply_delta(&delta);
                 vec![(delta, inval_lines, edits)]
             }
+            ReindentLines => {
+                let Some(syntax) =
+                    syntax.filter(|syntax| syntax.rev == buffer.rev())
+                else {
+                    return vec![];
+                };
+                let selection = cursor.edit_selection(buffer);
+                match Self::do_reindent(buffer, syntax, selection) {
+                    Some((delta, inval_lines, edits)) => {
+                        cursor.apply_delta(&delta);
+                        vec![(delta, inval_lines, edits)]
+                    }
+                    None => vec![],
+                }
+            }
             ToggleLineComment => {
                 let mut lines = HashSet::new();
                 let selection = cursor.edit_selection(buffer);
@@ -1025,7 +1160,7 @@ pub fn do_edit<T: Clipboard>(
                 cursor.update_selection(buffer, selection);
                 vec![(delta, inval_lines, edits)]
             }
-            ClipboardPaste => {
+            ClipboardPaste | ClipboardPasteWithoutFormatting => {
                 if let Some(s) = clipboard.get_string() {
                     let mode = if s.ends_with('\n') {
                         VisualMode::Linewise
@@ -1033,7 +1168,19 @@ pub fn do_edit<T: Clipboard>(
                         VisualMode::Normal
                     };
                     let data = RegisterData { content: s, mode };
-                    Self::do_paste(cursor, buffer, &data)
+                    let reindent = *cmd == ClipboardPaste;
+                    Self::paste(cursor, buffer, &data, syntax, reindent)
+                } else {
+                    vec![]
+                }
+            }
+            ClipboardPasteAsOneLine => {
+                if let Some(s) = clipboard.get_string() {
+                    let data = RegisterData {
+                        content: paste_as_one_line(&s),
+                        mode: VisualMode::Normal,
+                    };
+                    Self::paste(cursor, buffer, &data, syntax, false)
                 } else {
                     vec![]
                 }
@@ -1056,7 +1203,7 @@ pub fn do_edit<T: Clipboard>(
             }
             Paste => {
                 let data = register.unnamed.clone();
-                Self::do_paste(cursor, buffer, &data)
+                Self::do_paste(cursor, buffer, &data, syntax)
             }
             PasteBefore => {
                 let offset = cursor.offset();
@@ -1064,7 +1211,7 @@ pub fn do_edit<T: Clipboard>(
                 let mut local_cursor =
                     Cursor::new(CursorMode::Insert(Selection::new()), None, None);
                 local_cursor.set_offset(offset, false, false);
-                Self::do_paste(&mut local_cursor, buffer, &data)
+                Self::do_paste(&mut local_cursor, buffer, &data, syntax)
             }
             NewLineAbove => {
                 let offset = cursor.offset();
@@ -1074,8 +1221,12 @@ pub fn do_edit<T: Clipboard>(
                 } else {
                     buffer.first_non_blank_character_on_line(line)
                 };
-                let delta =
-                    Self::insert_new_line(buffer, cursor, Selection::caret(offset));
+                let delta = Self::insert_new_line(
+                    buffer,
+                    cursor,
+                    Selection::caret(offset),
+                    syntax,
+                );
                 if line == 0 {
                     cursor.mode = CursorMode::Insert(Selection::caret(offset));
                 }
@@ -1084,7 +1235,12 @@ pub fn do_edit<T: Clipboard>(
             NewLineBelow => {
                 let offset = cursor.offset();
                 let offset = buffer.offset_line_end(offset, true);
-                Self::insert_new_line(buffer, cursor, Selection::caret(offset))
+                Self::insert_new_line(
+                    buffer,
+                    cursor,
+                    Selection::caret(offset),
+                    syntax,
+                )
             }
             DeleteBackward => {
                 let (selection, edit_type) = match cursor.mode {
@@ -1513,7 +1669,239 @@ pub fn do_edit<T: Clipboard>(
             DuplicateLineDown => {
                 Self::duplicate_line(cursor, buffer, DuplicateDirection::Down)
             }
+            MoveStatementUp | MoveStatementDown => {
+                // The syntax tree is only used when it's up to date with the
+                // buffer, as the ranges would be off otherwise
+                let Some(syntax) =
+                    syntax.filter(|syntax| syntax.rev == buffer.rev())
+                else {
+                    return vec![];
+                };
+                let line = buffer.line_of_offset(cursor.offset());
+                let start = buffer.first_non_blank_character_on_line(line);
+                match syntax.statement_swap(start, *cmd == MoveStatementUp) {
+                    Some((first, second)) => {
+                        Self::swap_ranges(cursor, buffer, first, second)
+                    }
+                    None => vec![],
+                }
+            }
+            SwapArgumentPrevious | SwapArgumentNext => {
+                let Some(syntax) =
+                    syntax.filter(|syntax| syntax.rev == buffer.rev())
+                else {
+                    return vec![];
+                };
+                match syntax
+                    .argument_swap(cursor.offset(), *cmd == SwapArgumentPrevious)
+                {
+                    Some((first, second)) => {
+                        Self::swap_ranges(cursor, buffer, first, second)
+                    }
+                    None => vec![],
+                }
+            }
+        }
+    }
+
+    /// The lines of a linewise paste at the start of the line at the offset, with
+    /// their indentation moved to the one which the indents query of the language
+    /// gives there. The indentation of the lines relative to the first one is
+    /// kept.
+    fn reindent_paste(
+        buffer: &Buffer,
+        syntax: Option<&Syntax>,
+        offset: usize,
+        data: &RegisterData,
+    ) -> String {
+        let content = &data.content;
+        let Some(first_line) = content.lines().find(|line| !line.trim().is_empty())
+        else {
+            return content.clone();
+        };
+        let level = match syntax
+            .filter(|syntax| syntax.rev == buffer.rev())
+            .filter(|_| data.mode == VisualMode::Linewise)
+            .and_then(|syntax| syntax.indent_level(offset))
+        {
+            Some(level) => level,
+            None => return content.clone(),
+        };
+        let text = first_line.trim_start_matches([' ', '\t']);
+        let base = &first_line[..first_line.len() - text.len()];
+        // The pasted text isn't in the syntax tree, so a closing bracket is
+        // outdented by what it starts with
+        let level = if text.starts_with(['}', ']', ')']) {
+            level.saturating_sub(1)
+        } else {
+            level
+        };
+        let indent = buffer.indent_unit().repeat(level);
+
+        content
+            .split_inclusive('\n')
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.to_string()
+                } else if let Some(rest) = line.strip_prefix(base) {
+                    format!("{indent}{rest}")
+                } else {
+                    format!("{indent}{}", line.trim_start_matches([' ', '\t']))
+                }
+            })
+            .collect()
+    }
+
+    /// The text of a paste into the line at the offset, where the lines after
+    /// the first are moved to the indentation of that line. Their indentation
+    /// relative to the least indented of them is kept, as the first line is
+    /// usually copied without its own indentation.
+    fn reindent_inline_paste(
+        buffer: &Buffer,
+        offset: usize,
+        content: &str,
+    ) -> String {
+        let Some((first, rest)) = content.split_once('\n') else {
+            return content.to_string();
+        };
+        let line_content = buffer.line_content(buffer.line_of_offset(offset));
+        let indent = &line_content[..line_content.len()
+            - line_content.trim_start_matches([' ', '\t']).len()];
+        let indentation =
+            |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+        let base = rest
+            .split_inclusive('\n')
+            .filter(|line| !line.trim().is_empty())
+            .min_by_key(|line| indentation(line))
+            .map(|line| &line[..indentation(line)])
+            .unwrap_or("");
+
+        let rest: String = rest
+            .split_inclusive('\n')
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.to_string()
+                } else if let Some(rest) = line.strip_prefix(base) {
+                    format!("{indent}{rest}")
+                } else {
+                    format!("{indent}{}", line.trim_start_matches([' ', '\t']))
+                }
+            })
+            .collect();
+        format!("{first}\n{rest}")
+    }
+
+    /// Join the line with the next one. A trailing comma before a closing
+    /// bracket is left out, brackets are joined without a space, and a comment at
+    /// the end of the line stays at the end of the joined line, merged with the
+    /// comment of the next line if that's one.
+    fn join_lines(
+        buffer: &mut Buffer,
+        syntax: Option<&Syntax>,
+        line: usize,
+    ) -> (RopeDelta, InvalLines, SyntaxEdit) {
+        let line_start = buffer.offset_of_line(line);
+        let line_end = buffer.line_end_offset(line, true);
+        let next_start = buffer.first_non_blank_character_on_line(line + 1);
+        let next_end = buffer.line_end_offset(line + 1, true).max(next_start);
+
+        let current = buffer.slice_to_cow(line_start..line_end).trim_end().len();
+        let comment = syntax
+            .filter(|_| current > 0)
+            .and_then(|syntax| syntax.comment_at(line_start + current - 1))
+            .filter(|comment| comment.start >= line_start);
+        let code_end = match &comment {
+            Some(comment) => {
+                let code = buffer.slice_to_cow(line_start..comment.start);
+                if code.trim().is_empty() {
+                    // The indentation of a line which is only a comment is kept
+                    comment.start
+                } else {
+                    line_start + code.trim_end().len()
+                }
+            }
+            None => line_start + current,
+        };
+        let code = buffer.slice_to_cow(line_start..code_end).to_string();
+        let mut next = buffer.slice_to_cow(next_start..next_end).to_string();
+
+        let mut comment =
+            comment.map(|comment| buffer.slice_to_cow(comment).to_string());
+        if let (Some(comment), Some(syntax)) = (comment.as_mut(), syntax) {
+            let token = syntax.language.comment_token();
+            if syntax.comment_at(next_start).is_some() && next.starts_with(token) {
+                let rest = next[token.len()..].trim_start();
+                if !rest.is_empty() {
+                    comment.push(' ');
+                    comment.push_str(rest);
+                }
+                next.clear();
+            }
         }
+
+        let mut start = code_end;
+        let separator = if next.is_empty() || code.trim().is_empty() {
+            ""
+        } else if next.starts_with([')', ']', '}']) {
+            if code.ends_with(',') {
+                start -= 1;
+            }
+            if next.starts_with('}') {
+                " "
+            } else {
+                ""
+            }
+        } else if code.ends_with(['(', '[']) {
+            ""
+        } else {
+            " "
+        };
+
+        let (end, text) = match comment {
+            Some(comment) => {
+                let mut text = format!("{separator}{next}");
+                if !text.is_empty() || !code.trim().is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&comment);
+                (next_end, text)
+            }
+            None => (next_start, separator.to_string()),
+        };
+
+        buffer.edit(&[(&Selection::region(start, end), &text)], EditType::Other)
+    }
+
+    /// Swap the text of the two ranges, where `first` comes before `second`, and
+    /// move the cursor along with the text it was in.
+    fn swap_ranges(
+        cursor: &mut Cursor,
+        buffer: &mut Buffer,
+        first: Range<usize>,
+        second: Range<usize>,
+    ) -> Vec<(RopeDelta, InvalLines, SyntaxEdit)> {
+        let first_text = buffer.slice_to_cow(first.clone()).to_string();
+        let between = buffer.slice_to_cow(first.end..second.start).to_string();
+        let second_text = buffer.slice_to_cow(second.clone()).to_string();
+
+        let offset = cursor.offset();
+        let new_offset = if first.contains(&offset) {
+            second.end - first_text.len() + (offset - first.start)
+        } else if second.contains(&offset) {
+            first.start + (offset - second.start)
+        } else {
+            offset
+        };
+
+        let (delta, inval_lines, edits) = buffer.edit(
+            &[(
+                &Selection::region(first.start, second.end),
+                &format!("{second_text}{between}{first_text}"),
+            )],
+            EditType::Other,
+        );
+        cursor.set_offset(new_offset, false, false);
+        vec![(delta, inval_lines, edits)]
     }
 }
 
@@ -1522,12 +1910,23 @@ enum DuplicateDirection {
     Down,
 }
 
+/// The lines of the text joined by spaces, without their indentation and the
+/// empty ones.
+fn paste_as_one_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .join(" ")
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
         buffer::{rope_text::RopeText, Buffer},
         cursor::{Cursor, CursorMode},
-        editor::{DuplicateDirection, Editor},
+        editor::{paste_as_one_line, DuplicateDirection, Editor},
+        mode::VisualMode,
+        register::RegisterData,
         selection::{SelRegion, Selection},
     };
 
@@ -1763,5 +2162,106 @@ fn check_multiple_cursor_match_insertion() {
         assert_eq!(cursor.mode, CursorMode::Insert(end_selection));
     }
 
+    #[test]
+    fn test_paste() {
+        let paste = |text: &str, offset: usize, content: &str, reindent: bool| {
+            let mut buffer = Buffer::new(text);
+            let mut cursor = Cursor::new(
+                CursorMode::Insert(Selection::caret(offset)),
+                None,
+                None,
+            );
+            let data = RegisterData {
+                content: content.to_string(),
+                mode: VisualMode::Normal,
+            };
+            Editor::paste(&mut cursor, &mut buffer, &data, None, reindent);
+            buffer.slice_to_cow(0..buffer.len()).to_string()
+        };
+
+        // The lines after the first move under the line which they're pasted in
+        assert_eq!(
+            "    a = if b {\n        c\n    }\n",
+            paste("    a = \n", 8, "if b {\n    c\n}", true)
+        );
+        assert_eq!(
+            "    f(\n        x,\n    )",
+            paste("    ", 4, "f(\n            x,\n        )", true)
+        );
+        assert_eq!(
+            "    a = if b {\n    c\n}\n",
+            paste("    a = \n", 8, "if b {\n    c\n}", false)
+        );
+        assert_eq!("a b c", paste_as_one_line("a\n    b\n\n  c\n"));
+    }
+
+    #[test]
+    fn test_join_lines() {
+        let join = |text: &str, line: usize| {
+            let mut buffer = Buffer::new(text);
+            Editor::join_lines(&mut buffer, None, line);
+            buffer.slice_to_cow(0..buffer.len()).to_string()
+        };
+
+        assert_eq!("a b\n", join("a\n    b\n", 0));
+        assert_eq!("foo(\n    a)\n", join("foo(\n    a,\n)\n", 1));
+        assert_eq!("call(a\n", join("call(\n    a\n", 0));
+        assert_eq!("if a { b\n}", join("if a {\n    b\n}", 0));
+        assert_eq!("S { a }", join("S { a,\n}", 0));
+        assert_eq!("b\n", join("\n    b\n", 0));
+    }
+
+    #[test]
+    fn test_insert_new_line() {
+        let insert_new_line = |text: &str, offset: usize| {
+            let mut buffer = Buffer::new(text);
+            let mut cursor = Cursor::new(
+                CursorMode::Insert(Selection::caret(offset)),
+                None,
+                None,
+            );
+            Editor::insert_new_line(
+                &mut buffer,
+                &mut cursor,
+                Selection::caret(offset),
+                None,
+            );
+            (
+                buffer.slice_to_cow(0..buffer.len()).to_string(),
+                cursor.offset(),
+            )
+        };
+
+        assert_eq!(
+            (
+                "a
+b"
+                .to_string(),
+                2
+            ),
+            insert_new_line("ab", 1)
+        );
+        assert_eq!(
+            ("if a {\n    \n}".to_string(), 11),
+            insert_new_line("if a {}", 6)
+        );
+        assert_eq!(
+            ("    if a:\n        b".to_string(), 18),
+            insert_new_line("    if a:b", 9)
+        );
+    }
+
+    #[test]
+    fn test_swap_ranges() {
+        let mut buffer = Buffer::new("f(a, bb)");
+        let mut cursor =
+            Cursor::new(CursorMode::Insert(Selection::caret(2)), None, None);
+
+        Editor::swap_ranges(&mut cursor, &mut buffer, 2..3, 5..7);
+
+        assert_eq!("f(bb, a)", buffer.slice_to_cow(0..buffer.len()));
+        assert_eq!(cursor.offset(), 6);
+    }
+
     // TODO(dbuga): add tests duplicating selections (multiple line blocks)
 }
@@ -1,3 +1,39 @@
+use lsp_types::PositionEncodingKind;
+
+/// What the characters of the positions of a language server are counted in,
+/// which is agreed on when the server is initialized
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    /// What the servers which don't say count in
+    #[default]
+    Utf16,
+}
+
+impl PositionEncoding {
+    /// The encoding which a server picked, out of the ones which
+    /// [`PositionEncoding::supported`] offers it.
+    pub fn from_lsp(kind: Option<&PositionEncodingKind>) -> Self {
+        match kind {
+            Some(kind) if *kind == PositionEncodingKind::UTF8 => Self::Utf8,
+            _ => Self::Utf16,
+        }
+    }
+
+    pub fn to_lsp(self) -> PositionEncodingKind {
+        match self {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            PositionEncoding::Utf16 => PositionEncodingKind::UTF16,
+        }
+    }
+
+    /// The encodings which a server can pick, the one which is cheapest for the
+    /// text of a rope first.
+    pub fn supported() -> Vec<PositionEncodingKind> {
+        vec![PositionEncodingKind::UTF8, PositionEncodingKind::UTF16]
+    }
+}
+
 /// Convert a utf8 offset into a utf16 offset, if possible  
 /// `text` is what the offsets are into
 pub fn offset_utf8_to_utf16(
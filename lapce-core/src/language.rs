@@ -74,6 +74,8 @@ macro_rules! comment_properties {
         language: tree_sitter_plaintext::language,
         highlight: Some(tree_sitter_plaintext::HIGHLIGHTS_QUERY),
         injection: Some(tree_sitter_plaintext::INJECTIONS_QUERY),
+        tags: None,
+        indents: None,
         code_lens: (&[], &[]),
         sticky_headers: &[],
     }),
@@ -110,6 +112,16 @@ struct TreeSitterProperties {
     /// For most languages, it is `tree_sitter_$crate::INJECTION_QUERY`.  
     /// Though, not all languages have injections.
     injection: Option<&'static str>,
+    /// The query which finds the definitions in a document for the navigation
+    /// without a language server. Definitions are captured as `@definition.kind`
+    /// with their name as `@name`, and local variables as `@local.definition`
+    /// within a `@local.scope`.
+    tags: Option<&'static str>,
+    /// The query which finds the nodes that the lines in them are indented by,
+    /// captured as `@indent`, and the nodes such as closing brackets which a line
+    /// is outdented by when it starts with them, captured as `@outdent`.
+    /// `@extend` and `@ignore` are described in `syntax::indent`.
+    indents: Option<&'static str>,
     /// Lists of tree-sitter node types that control how code lenses are built.
     /// The first is a list of nodes that should be traversed and included in
     /// the lens, along with thier children. The second is a list of nodes that
@@ -302,6 +314,8 @@ pub enum LapceLanguage {
             language: tree_sitter_bash::language,
             highlight: Some(tree_sitter_bash::HIGHLIGHT_QUERY),
             injection: None,
+            tags: None,
+            indents: Some(include_str!("../queries/bash/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -322,6 +336,8 @@ pub enum LapceLanguage {
             language: tree_sitter_c::language,
             highlight: Some(include_str!("../queries/c/highlights.scm")),
             injection: None,
+            tags: Some(include_str!("../queries/c/tags.scm")),
+            indents: Some(include_str!("../queries/c/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &["function_definition", "struct_specifier"],
         }),
@@ -351,6 +367,8 @@ pub enum LapceLanguage {
             language: tree_sitter_clojure::language,
             highlight: Some(include_str!("../queries/clojure/highlights.scm")),
             injection: Some(include_str!("../queries/clojure/injections.scm")),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -371,6 +389,8 @@ pub enum LapceLanguage {
             language: tree_sitter_cmake::language,
             highlight: Some(include_str!("../queries/cmake/highlights.scm")),
             injection: Some(include_str!("../queries/cmake/injections.scm")),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &["function_definition"],
         }),
@@ -391,6 +411,8 @@ pub enum LapceLanguage {
             language: tree_sitter_cpp::language,
             highlight: Some(include_str!("../queries/cpp/highlights.scm")),
             injection: None,
+            tags: Some(include_str!("../queries/cpp/tags.scm")),
+            indents: Some(include_str!("../queries/cpp/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[
                 "function_definition",
@@ -415,6 +437,8 @@ pub enum LapceLanguage {
             language: tree_sitter_c_sharp::language,
             highlight: Some(tree_sitter_c_sharp::HIGHLIGHT_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[
                 "interface_declaration",
@@ -446,6 +470,8 @@ pub enum LapceLanguage {
             language: tree_sitter_css::language,
             highlight: Some(include_str!("../queries/css/highlights.scm")),
             injection: None,
+            tags: None,
+            indents: Some(include_str!("../queries/css/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -473,6 +499,8 @@ pub enum LapceLanguage {
             language: tree_sitter_d::language,
             highlight: Some(tree_sitter_d::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -500,6 +528,8 @@ pub enum LapceLanguage {
             language: tree_sitter_dart::language,
             highlight: Some(tree_sitter_dart::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (
                 &["program", "class_definition"],
                 &[
@@ -528,6 +558,8 @@ pub enum LapceLanguage {
             language: tree_sitter_dockerfile::language,
             highlight: Some(tree_sitter_dockerfile::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -548,6 +580,8 @@ pub enum LapceLanguage {
             language: tree_sitter_elixir::language,
             highlight: Some(tree_sitter_elixir::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &["do_block"],
         }),
@@ -568,6 +602,8 @@ pub enum LapceLanguage {
             language: tree_sitter_elm::language,
             highlight: Some(include_str!("../queries/elm/highlights.scm")),
             injection: Some(tree_sitter_elm::INJECTIONS_QUERY),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -588,6 +624,8 @@ pub enum LapceLanguage {
             language: tree_sitter_erlang::language,
             highlight: Some(include_str!("../queries/erlang/highlights.scm")),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -608,6 +646,8 @@ pub enum LapceLanguage {
             language: tree_sitter_glimmer::language,
             highlight: Some(tree_sitter_glimmer::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -633,6 +673,8 @@ pub enum LapceLanguage {
             language: tree_sitter_glsl::language,
             highlight: Some(tree_sitter_glsl::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -653,6 +695,8 @@ pub enum LapceLanguage {
             language: tree_sitter_go::language,
             highlight: Some(tree_sitter_go::HIGHLIGHT_QUERY),
             injection: None,
+            tags: Some(include_str!("../queries/go/tags.scm")),
+            indents: Some(include_str!("../queries/go/indents.scm")),
             code_lens: (
                 &[
                     "source_file",
@@ -682,6 +726,8 @@ pub enum LapceLanguage {
             language: tree_sitter_hare::language,
             highlight: Some(tree_sitter_hare::HIGHLIGHT_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -702,6 +748,8 @@ pub enum LapceLanguage {
             language: tree_sitter_haskell::language,
             highlight: Some(tree_sitter_haskell::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -722,6 +770,8 @@ pub enum LapceLanguage {
             language: tree_sitter_haxe::language,
             highlight: Some(tree_sitter_haxe::HIGHLIGHTS_QUERY),
             injection: Some(tree_sitter_haxe::INJECTIONS_QUERY),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -742,6 +792,8 @@ pub enum LapceLanguage {
             language: tree_sitter_hcl::language,
             highlight: Some(tree_sitter_hcl::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -762,6 +814,8 @@ pub enum LapceLanguage {
             language: tree_sitter_html::language,
             highlight: Some(tree_sitter_html::HIGHLIGHT_QUERY),
             injection: Some(tree_sitter_html::INJECTION_QUERY),
+            tags: None,
+            indents: Some(include_str!("../queries/html/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -782,6 +836,8 @@ pub enum LapceLanguage {
             language: tree_sitter_java::language,
             highlight: Some(tree_sitter_java::HIGHLIGHT_QUERY),
             injection: None,
+            tags: Some(include_str!("../queries/java/tags.scm")),
+            indents: Some(include_str!("../queries/java/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -802,6 +858,8 @@ pub enum LapceLanguage {
             language: tree_sitter_javascript::language,
             highlight: Some(include_str!("../queries/javascript/highlights.scm")),
             injection: Some(tree_sitter_javascript::INJECTION_QUERY),
+            tags: Some(include_str!("../queries/javascript/tags.scm")),
+            indents: Some(include_str!("../queries/javascript/indents.scm")),
             code_lens: (&["source_file", "program"], &["source_file"]),
             sticky_headers: &[],
         }),
@@ -822,6 +880,8 @@ pub enum LapceLanguage {
             language: tree_sitter_json::language,
             highlight: None,
             injection: None,
+            tags: None,
+            indents: Some(include_str!("../queries/json/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -843,6 +903,8 @@ pub enum LapceLanguage {
             highlight: Some(include_str!("../queries/jsx/highlights.scm")),
             // TODO: Does jsx use the javascript injection query too?
             injection: Some(tree_sitter_javascript::INJECTION_QUERY),
+            tags: Some(include_str!("../queries/javascript/tags.scm")),
+            indents: Some(include_str!("../queries/javascript/indents.scm")),
             code_lens: (&["source_file", "program"], &["source_file"]),
             sticky_headers: &[],
         }),
@@ -870,6 +932,8 @@ pub enum LapceLanguage {
             language: tree_sitter_julia::language,
             highlight: Some(include_str!("../queries/julia/highlights.scm")),
             injection: Some(include_str!("../queries/julia/injections.scm")),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -897,6 +961,8 @@ pub enum LapceLanguage {
             language: tree_sitter_kotlin::language,
             highlight: Some(include_str!("../queries/kotlin/highlights.scm")),
             injection: Some(include_str!("../queries/kotlin/injections.scm")),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -917,6 +983,8 @@ pub enum LapceLanguage {
             language: tree_sitter_latex::language,
             highlight: Some(include_str!("../queries/latex/highlights.scm")),
             injection: Some(include_str!("../queries/latex/injections.scm")),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -938,6 +1006,8 @@ pub enum LapceLanguage {
             highlight: Some(include_str!("../queries/lua/highlights.scm")),
             injection: None,
             sticky_headers: &[],
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
         }),
         #[cfg(not(feature = "lang-lua"))]
@@ -957,6 +1027,8 @@ pub enum LapceLanguage {
             language: tree_sitter_md::language,
             highlight: Some(include_str!("../queries/markdown/highlights.scm")),
             injection: Some(include_str!("../queries/markdown/injections.scm")),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -982,6 +1054,8 @@ pub enum LapceLanguage {
             injection: Some(include_str!(
                 "../queries/markdown.inline/injections.scm"
             )),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1009,6 +1083,8 @@ pub enum LapceLanguage {
             language: tree_sitter_nix::language,
             highlight: Some(tree_sitter_nix::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1036,6 +1112,8 @@ pub enum LapceLanguage {
             language: tree_sitter_ocaml::language_ocaml,
             highlight: Some(tree_sitter_ocaml::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1056,6 +1134,8 @@ pub enum LapceLanguage {
             language: tree_sitter_ocaml::language_ocaml_interface,
             highlight: Some(tree_sitter_ocaml::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1076,6 +1156,8 @@ pub enum LapceLanguage {
             language: tree_sitter_php::language,
             highlight: Some(tree_sitter_php::HIGHLIGHT_QUERY),
             injection: Some(tree_sitter_php::INJECTIONS_QUERY),
+            tags: None,
+            indents: None,
             code_lens: (
                 &[
                     "program",
@@ -1117,6 +1199,8 @@ pub enum LapceLanguage {
             language: tree_sitter_prisma_io::language,
             highlight: Some(include_str!("../queries/prisma/highlights.scm")),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1137,6 +1221,8 @@ pub enum LapceLanguage {
             language: tree_sitter_protobuf::language,
             highlight: Some(include_str!("../queries/protobuf/highlights.scm")),
             injection: Some(include_str!("../queries/protobuf/injections.scm")),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1156,7 +1242,9 @@ pub enum LapceLanguage {
         tree_sitter: Some(TreeSitterProperties {
             language: tree_sitter_python::language,
             highlight: Some(tree_sitter_python::HIGHLIGHT_QUERY),
-            injection: None,
+            injection: Some(include_str!("../queries/python/injections.scm")),
+            tags: Some(include_str!("../queries/python/tags.scm")),
+            indents: Some(include_str!("../queries/python/indents.scm")),
             code_lens: (
                 &[
                     "source_file",
@@ -1188,6 +1276,8 @@ pub enum LapceLanguage {
             language: tree_sitter_ql::language,
             highlight: Some(tree_sitter_ql::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1208,6 +1298,8 @@ pub enum LapceLanguage {
             language: tree_sitter_r::language,
             highlight: Some(include_str!("../queries/r/highlights.scm")),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1228,6 +1320,8 @@ pub enum LapceLanguage {
             language: tree_sitter_ruby::language,
             highlight: Some(tree_sitter_ruby::HIGHLIGHT_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &["module", "class", "method", "do_block"],
         }),
@@ -1247,7 +1341,9 @@ pub enum LapceLanguage {
         tree_sitter: Some(TreeSitterProperties {
             language: tree_sitter_rust::language,
             highlight: Some(tree_sitter_rust::HIGHLIGHT_QUERY),
-            injection: None,
+            injection: Some(include_str!("../queries/rust/injections.scm")),
+            tags: Some(include_str!("../queries/rust/tags.scm")),
+            indents: Some(include_str!("../queries/rust/indents.scm")),
             code_lens: (
                 &["source_file", "impl_item", "trait_item", "declaration_list"],
                 &["source_file", "use_declaration", "line_comment"],
@@ -1276,6 +1372,8 @@ pub enum LapceLanguage {
             language: tree_sitter_scheme::language,
             highlight: Some(tree_sitter_scheme::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1296,6 +1394,8 @@ pub enum LapceLanguage {
             language: tree_sitter_scss::language,
             highlight: Some(tree_sitter_scss::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1316,6 +1416,8 @@ pub enum LapceLanguage {
             language: tree_sitter_bash::language,
             highlight: Some(tree_sitter_bash::HIGHLIGHT_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1336,6 +1438,8 @@ pub enum LapceLanguage {
             language: tree_sitter_sql::language,
             highlight: Some(tree_sitter_sql::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1356,6 +1460,8 @@ pub enum LapceLanguage {
             language: tree_sitter_svelte::language,
             highlight: Some(include_str!("../queries/svelte/highlights.scm")),
             injection: Some(include_str!("../queries/svelte/injections.scm")),
+            tags: None,
+            indents: Some(include_str!("../queries/svelte/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1376,6 +1482,8 @@ pub enum LapceLanguage {
             language: tree_sitter_swift::language,
             highlight: Some(tree_sitter_swift::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1396,6 +1504,8 @@ pub enum LapceLanguage {
             language: tree_sitter_toml::language,
             highlight: Some(tree_sitter_toml::HIGHLIGHT_QUERY),
             injection: None,
+            tags: None,
+            indents: Some(include_str!("../queries/toml/indents.scm")),
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1416,6 +1526,8 @@ pub enum LapceLanguage {
             language: tree_sitter_typescript::language_tsx,
             highlight: Some(include_str!("../queries/typescript/highlights.scm")),
             injection: None,
+            tags: Some(include_str!("../queries/typescript/tags.scm")),
+            indents: Some(include_str!("../queries/typescript/indents.scm")),
             code_lens: (&["source_file", "program"], &["source_file"]),
             sticky_headers: &[],
         }),
@@ -1436,6 +1548,8 @@ pub enum LapceLanguage {
             language: tree_sitter_typescript::language_typescript,
             highlight: Some(include_str!("../queries/typescript/highlights.scm")),
             injection: None,
+            tags: Some(include_str!("../queries/typescript/tags.scm")),
+            indents: Some(include_str!("../queries/typescript/indents.scm")),
             code_lens: (&["source_file", "program"], &["source_file"]),
             sticky_headers: &[],
         }),
@@ -1456,6 +1570,8 @@ pub enum LapceLanguage {
             language: tree_sitter_vue::language,
             highlight: Some(tree_sitter_vue::HIGHLIGHTS_QUERY),
             injection: Some(tree_sitter_vue::INJECTIONS_QUERY),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1476,6 +1592,8 @@ pub enum LapceLanguage {
             language: tree_sitter_wgsl::language,
             highlight: Some(tree_sitter_wgsl::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1496,6 +1614,8 @@ pub enum LapceLanguage {
             language: tree_sitter_xml::language,
             highlight: Some(tree_sitter_xml::HIGHLIGHTS_QUERY),
             injection: None,
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1516,6 +1636,8 @@ pub enum LapceLanguage {
             language: tree_sitter_yaml::language,
             highlight: Some(tree_sitter_yaml::HIGHLIGHTS_QUERY),
             injection: Some(tree_sitter_yaml::INJECTIONS_QUERY),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1536,6 +1658,8 @@ pub enum LapceLanguage {
             language: tree_sitter_zig::language,
             highlight: Some(include_str!("../queries/zig/highlights.scm")),
             injection: Some(tree_sitter_zig::INJECTIONS_QUERY),
+            tags: None,
+            indents: None,
             code_lens: (DEFAULT_CODE_LENS_LIST, DEFAULT_CODE_LENS_IGNORE_LIST),
             sticky_headers: &[],
         }),
@@ -1545,6 +1669,9 @@ pub enum LapceLanguage {
 ];
 
 impl LapceLanguage {
+    /// The lines at the start and at the end of a file which a modeline is
+    /// looked for in
+    pub const MODELINE_LINES: usize = 5;
     const HIGHLIGHTS_QUERIES_FILE_NAME: &str = "highlights.scm";
     #[cfg(unix)]
     const SYSTEM_GRAMMARS_DIRECTORY: &str = "/usr/lib";
@@ -1553,12 +1680,18 @@ impl LapceLanguage {
 
     pub fn from_path(path: &Path) -> Option<LapceLanguage> {
         let filename = path.file_stem()?.to_str()?.to_lowercase();
-        let extension = path.extension()?.to_str()?.to_lowercase();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
         // NOTE: This is a linear search.  It is assumed that this function
         // isn't called in any tight loop.
         for properties in LANGUAGES {
             if properties.files.contains(&filename.as_str())
-                || properties.extensions.contains(&extension.as_str())
+                || extension
+                    .as_deref()
+                    .map(|extension| properties.extensions.contains(&extension))
+                    .unwrap_or(false)
             {
                 return Some(properties.id);
             }
@@ -1566,16 +1699,141 @@ pub fn from_path(path: &Path) -> Option<LapceLanguage> {
         None
     }
 
+    /// The language by its name, like `rust`, or by the name which the language
+    /// palette shows, like `JavaScript React`.
     pub fn from_name(name: &str) -> Option<LapceLanguage> {
         match LapceLanguage::from_str(name.to_lowercase().as_str()) {
             Ok(v) => Some(v),
             Err(e) => {
-                eprintln!("failed parsing {name} LapceLanguage: {e}");
-                None
+                let language = LANGUAGES
+                    .iter()
+                    .map(|properties| properties.id)
+                    .find(|id| strum::EnumMessage::get_message(id) == Some(name));
+                if language.is_none() {
+                    error!("failed parsing {name} LapceLanguage: {e}");
+                }
+                language
             }
         }
     }
 
+    /// The language of a file, where a modeline of vim or emacs in the file goes
+    /// before its path, since it was put there on purpose, and the shebang and
+    /// the start of the content are for a path which doesn't tell. `text` only
+    /// needs the first and the last [`LapceLanguage::MODELINE_LINES`] lines.
+    pub fn detect(path: Option<&Path>, text: &str) -> Option<LapceLanguage> {
+        LapceLanguage::from_modeline(text)
+            .or_else(|| path.and_then(LapceLanguage::from_path))
+            .or_else(|| LapceLanguage::from_shebang(text))
+            .or_else(|| LapceLanguage::from_content(text))
+    }
+
+    /// The language which a modeline in the first or the last lines sets, like
+    /// `# vim: set ft=python:` or `// -*- mode: rust -*-`.
+    pub fn from_modeline(text: &str) -> Option<LapceLanguage> {
+        let lines = text.lines().collect::<Vec<_>>();
+        let tail = lines.len().saturating_sub(Self::MODELINE_LINES);
+        lines
+            .iter()
+            .take(Self::MODELINE_LINES)
+            .chain(lines.iter().skip(tail.max(Self::MODELINE_LINES)))
+            .find_map(|line| emacs_mode(line).or_else(|| vim_filetype(line)))
+            .and_then(|name| {
+                let name = match name.to_lowercase().as_str() {
+                    "shell-script" => "bash".to_string(),
+                    "js" | "js2" => "javascript".to_string(),
+                    name => name.trim_end_matches("-ts").to_string(),
+                };
+                LapceLanguage::from_injection_name(&name)
+            })
+    }
+
+    /// The language of the interpreter of a shebang, like `#!/bin/sh` or
+    /// `#!/usr/bin/env python3`.
+    pub fn from_shebang(text: &str) -> Option<LapceLanguage> {
+        let line = text.trim_start_matches('\u{feff}').lines().next()?;
+        let mut words = line.strip_prefix("#!")?.split_whitespace();
+        let mut program = Path::new(words.next()?).file_name()?.to_str()?;
+        if program == "env" {
+            program = words.find(|word| !word.starts_with('-'))?;
+        }
+        // The version of the interpreter, like in `python3.11`, is left out
+        let program = program
+            .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+            .to_lowercase();
+        let language = match program.as_str() {
+            "sh" | "dash" | "ash" | "ksh" | "mksh" => LapceLanguage::Sh,
+            "bash" | "zsh" => LapceLanguage::Bash,
+            "python" | "pypy" => LapceLanguage::Python,
+            "node" | "nodejs" | "deno" | "bun" | "qjs" => LapceLanguage::Javascript,
+            "ts-node" | "tsx" => LapceLanguage::Typescript,
+            "rscript" => LapceLanguage::R,
+            "runghc" | "runhaskell" | "stack" => LapceLanguage::Haskell,
+            "escript" => LapceLanguage::Erlang,
+            "guile" | "racket" | "chibi-scheme" => LapceLanguage::Scheme,
+            "bb" | "clojure" => LapceLanguage::Clojure,
+            "cargo" | "rust-script" => LapceLanguage::Rust,
+            "luajit" => LapceLanguage::Lua,
+            program => LapceLanguage::from_str(program).ok()?,
+        };
+        Some(language)
+    }
+
+    /// The language which the start of the content tells, like the prolog of
+    /// xml.
+    pub fn from_content(text: &str) -> Option<LapceLanguage> {
+        let start = text.trim_start_matches('\u{feff}').trim_start();
+        let head = start
+            .get(..start.len().min(64))
+            .unwrap_or(start)
+            .to_lowercase();
+        if head.starts_with("<?xml") || head.starts_with("<svg") {
+            Some(LapceLanguage::Xml)
+        } else if head.starts_with("<!doctype html") || head.starts_with("<html") {
+            Some(LapceLanguage::Html)
+        } else if head.starts_with("<?php") {
+            Some(LapceLanguage::Php)
+        } else {
+            None
+        }
+    }
+
+    /// The language which an injection names, such as the info string of a
+    /// markdown code block. Besides the name of the language this can be one of
+    /// its file extensions or a common alias, and whatever follows the first word
+    /// of the info string, like `rust,ignore`, is left out.
+    pub fn from_injection_name(name: &str) -> Option<LapceLanguage> {
+        let name = name.trim().to_lowercase();
+        if let Ok(language) = LapceLanguage::from_str(&name) {
+            return Some(language);
+        }
+
+        let name = name
+            .trim_start_matches(['{', '.'])
+            .split(|c: char| {
+                !(c.is_alphanumeric() || matches!(c, '+' | '#' | '-' | '_'))
+            })
+            .next()
+            .unwrap_or_default();
+        let name = match name {
+            "c#" => "csharp",
+            "shell" | "zsh" | "console" | "shellsession" => "bash",
+            "golang" => "go",
+            "node" => "javascript",
+            "python3" => "python",
+            "docker" => "dockerfile",
+            "handlebars" => "glimmer",
+            name => name,
+        };
+        if let Ok(language) = LapceLanguage::from_str(name) {
+            return Some(language);
+        }
+        LANGUAGES
+            .iter()
+            .find(|properties| properties.extensions.contains(&name))
+            .map(|properties| properties.id)
+    }
+
     pub fn languages() -> Vec<&'static str> {
         let mut langs = vec![];
         for l in LANGUAGES {
@@ -1609,6 +1867,11 @@ fn tree_sitter(&self) -> TreeSitterProperties {
         }
     }
 
+    /// The first of the file extensions of the language.
+    pub fn extension(&self) -> Option<&'static str> {
+        self.properties().extensions.first().copied()
+    }
+
     pub fn sticky_header_tags(&self) -> &[&'static str] {
         if let Some(ts) = self.properties().tree_sitter {
             ts.sticky_headers
@@ -1617,6 +1880,17 @@ pub fn sticky_header_tags(&self) -> &[&'static str] {
         }
     }
 
+    /// The query which finds the definitions in a document, for the navigation
+    /// without a language server
+    pub fn tags_query(&self) -> Option<&'static str> {
+        self.properties().tree_sitter?.tags
+    }
+
+    /// The query which the indentation of the lines in a document comes from
+    pub fn indents_query(&self) -> Option<&'static str> {
+        self.properties().tree_sitter?.indents
+    }
+
     pub fn comment_token(&self) -> &str {
         self.properties()
             .comment
@@ -1796,3 +2070,114 @@ fn walk_tree(
         cursor.goto_parent();
     }
 }
+
+/// The mode of an emacs modeline, which is `-*- mode: rust -*-`, or only the
+/// mode as in `-*- rust -*-`.
+fn emacs_mode(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (variables, _) = rest.split_once("-*-")?;
+    let variables = variables.trim();
+    if !variables.contains(':') {
+        return (!variables.is_empty()).then_some(variables);
+    }
+    variables.split(';').find_map(|variable| {
+        let (name, value) = variable.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("mode")
+            .then(|| value.trim())
+    })
+}
+
+/// The file type of a vim modeline, like `vim: set ft=rust:` or
+/// `vi: filetype=rust`, whose marker starts the line or follows a blank.
+fn vim_filetype(line: &str) -> Option<&str> {
+    let options = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        let i = line.find(marker)?;
+        let starts_word = line[..i]
+            .chars()
+            .last()
+            .map(|c| c.is_whitespace())
+            .unwrap_or(true);
+        starts_word.then(|| &line[i + marker.len()..])
+    })?;
+    options
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .find_map(|option| {
+            let (name, value) = option.split_once('=')?;
+            matches!(name, "ft" | "filetype" | "syntax" | "syn").then_some(value)
+        })
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let detect = |path: &str, text: &str| {
+            LapceLanguage::detect(Some(Path::new(path)), text)
+        };
+        assert_eq!(
+            detect("run", "#!/usr/bin/env python3.11\n"),
+            Some(LapceLanguage::Python)
+        );
+        assert_eq!(detect("build", "#!/bin/sh -e\n"), Some(LapceLanguage::Sh));
+        assert_eq!(
+            detect("script", "#!/usr/bin/env -S deno run\n"),
+            Some(LapceLanguage::Javascript)
+        );
+        assert_eq!(
+            detect("notes.txt", "a\nb\n# vim: set ft=rust:\n"),
+            Some(LapceLanguage::Rust)
+        );
+        assert_eq!(
+            detect("x.h", "/* -*- mode: c++; indent-tabs-mode: nil -*- */\n"),
+            Some(LapceLanguage::Cpp)
+        );
+        assert_eq!(
+            detect("feed", "\u{feff}<?xml version=\"1.0\"?>\n"),
+            Some(LapceLanguage::Xml)
+        );
+        assert_eq!(
+            detect("main.rs", "#!/usr/bin/env python\n"),
+            Some(LapceLanguage::Rust)
+        );
+        assert_eq!(detect("Dockerfile", ""), Some(LapceLanguage::Dockerfile));
+        assert_eq!(detect("README", "navi: ft=go\n"), None);
+    }
+
+    #[test]
+    fn test_tags_queries() {
+        for properties in LANGUAGES {
+            let Some(ts) = properties.tree_sitter else {
+                continue;
+            };
+            if let Some(tags) = ts.tags {
+                if let Err(e) = tree_sitter::Query::new((ts.language)(), tags) {
+                    panic!(
+                        "The tags query of {:?} doesn't compile: {e:?}",
+                        properties.id
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_indents_queries() {
+        for properties in LANGUAGES {
+            let Some(ts) = properties.tree_sitter else {
+                continue;
+            };
+            if let Some(indents) = ts.indents {
+                if let Err(e) = tree_sitter::Query::new((ts.language)(), indents) {
+                    panic!(
+                        "The indents query of {:?} doesn't compile: {e:?}",
+                        properties.id
+                    );
+                }
+            }
+        }
+    }
+}
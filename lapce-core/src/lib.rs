@@ -20,4 +20,5 @@
 pub mod soft_tab;
 pub mod style;
 pub mod syntax;
+pub mod whitespace;
 pub mod word;
@@ -133,3 +133,102 @@ fn classify_boundary(
         _ => Interior,
     }
 }
+
+/// Wrap the paragraphs of the lines so that none is longer than the column,
+/// unless it's a single word. Each paragraph keeps the indentation of its first
+/// line, and the comment token in front of it when the lines are comments.
+/// Blank lines separate the paragraphs and are kept as they are.
+pub fn hard_wrap(text: &str, column: usize, comment_token: &str) -> String {
+    let mut wrapped = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        let content = line.trim();
+        if content.is_empty()
+            || (!comment_token.is_empty() && content == comment_token)
+        {
+            wrap_paragraph(&paragraph, column, comment_token, &mut wrapped);
+            paragraph.clear();
+            wrapped.push(line.trim_end().to_string());
+        } else {
+            paragraph.push(line);
+        }
+    }
+    wrap_paragraph(&paragraph, column, comment_token, &mut wrapped);
+
+    let mut wrapped = wrapped.join("\n");
+    if text.ends_with('\n') {
+        wrapped.push('\n');
+    }
+    wrapped
+}
+
+fn wrap_paragraph(
+    lines: &[&str],
+    column: usize,
+    comment_token: &str,
+    wrapped: &mut Vec<String>,
+) {
+    let Some(first) = lines.first() else {
+        return;
+    };
+    let indent = &first[..first.len() - first.trim_start().len()];
+    let is_comment = !comment_token.is_empty()
+        && lines
+            .iter()
+            .all(|line| line.trim_start().starts_with(comment_token));
+    let prefix = if is_comment {
+        format!("{indent}{comment_token} ")
+    } else {
+        indent.to_string()
+    };
+
+    let words = lines.iter().flat_map(|line| {
+        let line = line.trim_start();
+        let line = if is_comment {
+            &line[comment_token.len()..]
+        } else {
+            line
+        };
+        line.split_whitespace()
+    });
+    let mut current = prefix.clone();
+    let mut width = prefix.chars().count();
+    for word in words {
+        let word_width = word.chars().count();
+        if current.len() > prefix.len() {
+            if width + 1 + word_width > column {
+                wrapped.push(current);
+                current = prefix.clone();
+                width = prefix.chars().count();
+            } else {
+                current.push(' ');
+                width += 1;
+            }
+        }
+        current.push_str(word);
+        width += word_width;
+    }
+    wrapped.push(current);
+}
+
+#[cfg(test)]
+mod test {
+    use super::hard_wrap;
+
+    #[test]
+    fn test_hard_wrap() {
+        assert_eq!(
+            hard_wrap("one two three four five\n", 10, ""),
+            "one two\nthree four\nfive\n"
+        );
+        assert_eq!(
+            hard_wrap("  one two\n  three\n\n  four", 9, ""),
+            "  one two\n  three\n\n  four"
+        );
+        assert_eq!(
+            hard_wrap("    // one two three\n    // four", 17, "//"),
+            "    // one two\n    // three four"
+        );
+        assert_eq!(hard_wrap("a verylongword b", 4, ""), "a\nverylongword\nb");
+    }
+}
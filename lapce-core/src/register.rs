@@ -15,6 +15,8 @@ pub struct RegisterData {
 pub struct Register {
     pub unnamed: RegisterData,
     last_yank: RegisterData,
+    /// How many times something was yanked, which tells whether a command did
+    yanks: usize,
 }
 
 pub enum RegisterKind {
@@ -37,5 +39,14 @@ pub fn add_delete(&mut self, data: RegisterData) {
     pub fn add_yank(&mut self, data: RegisterData) {
         self.unnamed = data.clone();
         self.last_yank = data;
+        self.yanks += 1;
+    }
+
+    pub fn last_yank(&self) -> &RegisterData {
+        &self.last_yank
+    }
+
+    pub fn yanks(&self) -> usize {
+        self.yanks
     }
 }
@@ -17,7 +17,8 @@
 use arc_swap::ArcSwap;
 use lapce_xi_rope::Rope;
 use tree_sitter::{
-    Language, Point, Query, QueryCaptures, QueryCursor, QueryMatch, Tree,
+    Language, Point, Query, QueryCaptures, QueryCursor, QueryMatch,
+    QueryPredicateArg, Tree,
 };
 
 use super::{util::RopeProvider, PARSER};
@@ -816,6 +817,7 @@ pub(crate) fn injection_for_match<'a>(
     Option<Cow<'a, str>>,
     Option<tree_sitter::Node<'a>>,
     IncludedChildren,
+    Option<InjectionOffset>,
 ) {
     let content_capture_index = config.injection_content_capture_index;
     let language_capture_index = config.injection_language_capture_index;
@@ -862,5 +864,65 @@ pub(crate) fn injection_for_match<'a>(
         }
     }
 
-    (language_name, content_node, included_children)
+    // The start and the end of the content can be moved with an `#offset!`
+    // predicate, e.g. to leave out the quotes of a string which has no child
+    // nodes for them. Only offsets which keep to the same rows are supported.
+    let mut offset = None;
+    for predicate in query.general_predicates(query_match.pattern_index) {
+        if predicate.operator.as_ref() != "offset!" {
+            continue;
+        }
+        if let [QueryPredicateArg::Capture(index), args @ ..] =
+            predicate.args.as_slice()
+        {
+            let args: Vec<isize> = args
+                .iter()
+                .filter_map(|arg| match arg {
+                    QueryPredicateArg::String(arg) => arg.parse().ok(),
+                    QueryPredicateArg::Capture(_) => None,
+                })
+                .collect();
+            if Some(*index) == content_capture_index {
+                if let [0, start, 0, end] = args.as_slice() {
+                    offset = Some(InjectionOffset {
+                        start: *start,
+                        end: *end,
+                    });
+                }
+            }
+        }
+    }
+
+    (language_name, content_node, included_children, offset)
+}
+
+/// How many bytes the start and the end of an injection are moved by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct InjectionOffset {
+    pub start: isize,
+    pub end: isize,
+}
+
+impl InjectionOffset {
+    /// Move the start of the first range and the end of the last one, and drop the
+    /// ranges which are empty after that.
+    pub(crate) fn apply(&self, ranges: &mut Vec<tree_sitter::Range>) {
+        fn add(value: usize, offset: isize) -> usize {
+            if offset >= 0 {
+                value.saturating_add(offset as usize)
+            } else {
+                value.saturating_sub(offset.unsigned_abs())
+            }
+        }
+
+        if let Some(first) = ranges.first_mut() {
+            first.start_byte = add(first.start_byte, self.start);
+            first.start_point.column = add(first.start_point.column, self.start);
+        }
+        if let Some(last) = ranges.last_mut() {
+            last.end_byte = add(last.end_byte, self.end);
+            last.end_point.column = add(last.end_point.column, self.end);
+        }
+        ranges.retain(|range| range.start_byte < range.end_byte);
+    }
 }
@@ -0,0 +1,213 @@
+//! Indentation from the indents query of a language. A line is indented one level
+//! for each `@indent` node around it, where nodes which start on the same line
+//! count once, and one level less when it starts with an `@outdent` node, like a
+//! closing bracket. `@branch` is the same as `@outdent`.
+//!
+//! A node ends where its last token does, so a line after the end of an
+//! `@extend` node is still in it, which is how a python block carries on after
+//! the line before. The lines in an `@ignore` node, like a string, keep their
+//! indentation.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+use tracing::error;
+use tree_sitter::{Node, Query, QueryCursor};
+
+use super::{util::RopeProvider, Syntax};
+use crate::language::LapceLanguage;
+
+/// The indents queries which were compiled, which are `None` for the languages
+/// that have no query or whose query doesn't compile
+static INDENTS_QUERIES: Lazy<Mutex<HashMap<LapceLanguage, Option<Arc<Query>>>>> =
+    Lazy::new(Default::default);
+
+impl Syntax {
+    fn indents_query(&self) -> Option<Arc<Query>> {
+        let tree = self.layers.try_tree()?;
+        INDENTS_QUERIES
+            .lock()
+            .unwrap()
+            .entry(self.language)
+            .or_insert_with(|| {
+                let source = self.language.indents_query()?;
+                match Query::new(tree.language(), source) {
+                    Ok(query) => Some(Arc::new(query)),
+                    Err(e) => {
+                        error!("Failed to compile the indents query: {e:?}");
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+
+    /// The indent level of a new line at the offset, with the text before the
+    /// offset on the lines above it. This is `None` when the language has no
+    /// indents query, or the offset is in a node which the query can't tell the
+    /// indentation in, like a string or a syntax error.
+    pub fn indent_level(&self, offset: usize) -> Option<usize> {
+        self.indent_levels(offset).map(|(level, _)| level)
+    }
+
+    /// The indent level of the line whose text starts at the offset, which is one
+    /// less than [`Syntax::indent_level`] when the text starts with an
+    /// `@outdent` node.
+    pub fn line_indent_level(&self, offset: usize) -> Option<usize> {
+        self.indent_levels(offset)
+            .map(|(level, outdent)| level.saturating_sub(outdent as usize))
+    }
+
+    fn indent_levels(&self, offset: usize) -> Option<(usize, bool)> {
+        let tree = self.layers.try_tree()?;
+        let query = self.indents_query()?;
+        let capture_names = query.capture_names();
+
+        // The node before the offset is looked at too, which is the end of the
+        // `@extend` nodes that the offset is after
+        let line_start = self.text.offset_of_line(self.text.line_of_offset(offset));
+        let before = offset
+            - self
+                .text
+                .slice_to_cow(line_start..offset)
+                .chars()
+                .rev()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .count();
+
+        // Only the nodes around the offset can be the ones it is in
+        let mut captures: HashMap<usize, HashSet<&str>> = HashMap::new();
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(before.saturating_sub(1)..offset + 1);
+        for query_match in
+            cursor.matches(&query, tree.root_node(), RopeProvider(&self.text))
+        {
+            for capture in query_match.captures {
+                let name = capture_names[capture.index as usize].as_str();
+                captures.entry(capture.node.id()).or_default().insert(name);
+            }
+        }
+        let has_capture = |node: Node, name: &str| {
+            captures
+                .get(&node.id())
+                .map(|names| names.contains(name))
+                .unwrap_or(false)
+        };
+
+        let node = tree.root_node().descendant_for_byte_range(offset, offset)?;
+
+        // The nodes which the text starts with
+        let mut outdent = false;
+        let mut starts = Some(node).filter(|node| node.start_byte() == offset);
+        while let Some(node) = starts {
+            if has_capture(node, "outdent") || has_capture(node, "branch") {
+                outdent = true;
+            }
+            starts = node.parent().filter(|node| node.start_byte() == offset);
+        }
+
+        let mut nodes = vec![node];
+        if before > 0 {
+            nodes.extend(
+                tree.root_node()
+                    .descendant_for_byte_range(before - 1, before),
+            );
+        }
+        let mut visited = HashSet::new();
+        let mut lines = HashSet::new();
+        for node in nodes {
+            let mut ancestor = Some(node);
+            while let Some(node) = ancestor {
+                ancestor = node.parent();
+                if !visited.insert(node.id()) {
+                    break;
+                }
+                if node.is_error() {
+                    return None;
+                }
+                if node.start_byte() >= offset {
+                    continue;
+                }
+                let is_in = offset < node.end_byte()
+                    || (has_capture(node, "extend")
+                        && self.extends_to(node, offset));
+                if !is_in {
+                    continue;
+                }
+                if has_capture(node, "ignore") {
+                    return None;
+                }
+                if has_capture(node, "indent") {
+                    lines.insert(self.text.line_of_offset(node.start_byte()));
+                }
+            }
+        }
+
+        Some((lines.len(), outdent))
+    }
+
+    /// Whether there's only blank space on the same line between the end of the
+    /// node and the offset.
+    fn extends_to(&self, node: Node, offset: usize) -> bool {
+        node.end_byte() <= offset
+            && self
+                .text
+                .slice_to_cow(node.end_byte()..offset)
+                .chars()
+                .all(|c| c == ' ' || c == '\t')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_xi_rope::Rope;
+
+    use super::*;
+
+    fn parse(language: LapceLanguage, text: &str) -> Syntax {
+        let mut syntax = Syntax::from_language(language).unwrap();
+        syntax.parse(1, Rope::from(text), None);
+        syntax
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_indent_level() {
+        let text = "fn main() {
+    let x = foo(
+        1,
+    );
+    let s = \"a
+b\";
+}
+";
+        let syntax = parse(LapceLanguage::Rust, text);
+        let at = |s: &str| text.find(s).unwrap();
+
+        // A new line in a block, and in the arguments on the line of a statement
+        assert_eq!(syntax.indent_level(at("{") + 1), Some(1));
+        assert_eq!(syntax.indent_level(at("(\n") + 1), Some(2));
+        assert_eq!(syntax.indent_level(at("1,") + 2), Some(2));
+        assert_eq!(syntax.indent_level(at("main")), Some(0));
+
+        assert_eq!(syntax.line_indent_level(at("let x")), Some(1));
+        assert_eq!(syntax.line_indent_level(at("1,")), Some(2));
+        // A closing bracket is outdented
+        assert_eq!(syntax.line_indent_level(at(");")), Some(1));
+        assert_eq!(syntax.line_indent_level(at("}\n")), Some(0));
+
+        // The lines in a string keep their indentation
+        assert_eq!(syntax.indent_level(at("a\n") + 2), None);
+        assert_eq!(syntax.line_indent_level(at("b\"")), None);
+    }
+
+    #[test]
+    fn test_indent_level_without_query() {
+        let syntax = parse(LapceLanguage::Plaintext, "a\n  b\n");
+        assert_eq!(syntax.indent_level(2), None);
+        assert_eq!(syntax.line_indent_level(4), None);
+    }
+}
@@ -10,6 +10,7 @@
     cell::RefCell,
     collections::{HashSet, VecDeque},
     mem,
+    ops::Range,
     path::Path,
     sync::{atomic::AtomicUsize, Arc},
 };
@@ -41,10 +42,15 @@
 
 pub mod edit;
 pub mod highlight;
+pub mod indent;
+pub mod navigation;
 pub mod util;
 
 // Uses significant portions Helix's implementation, and on tree-sitter's highlighter implementation
 
+/// About how many bytes a node of a tree-sitter tree takes
+const TREE_NODE_SIZE: usize = 32;
+
 pub struct TsParser {
     parser: tree_sitter::Parser,
     pub cursors: Vec<QueryCursor>,
@@ -73,6 +79,9 @@ pub struct LanguageLayer {
     // mode
     // grammar
     pub config: Arc<HighlightConfiguration>,
+    /// The language of an injected layer, which the root one leaves to its
+    /// syntax
+    pub language: Option<LapceLanguage>,
     pub(crate) tree: Option<Tree>,
     pub ranges: Vec<tree_sitter::Range>,
     pub depth: usize,
@@ -140,6 +149,7 @@ pub fn new(
         let root_layer = LanguageLayer {
             tree: None,
             config,
+            language: None,
             depth: 0,
             ranges: vec![tree_sitter::Range {
                 start_byte: 0,
@@ -173,9 +183,11 @@ pub fn update(
         queue.push_back(self.root);
 
         let injection_callback = |language: &str| {
-            LapceLanguage::from_name(language)
-                .map(get_highlight_config)
-                .unwrap_or(Err(highlight::HighlightIssue::NotAvailable))
+            LapceLanguage::from_injection_name(language)
+                .ok_or(highlight::HighlightIssue::NotAvailable)
+                .and_then(|language| {
+                    get_highlight_config(language).map(|config| (language, config))
+                })
         };
 
         let mut edits = Vec::new();
@@ -314,7 +326,7 @@ fn point_sub(a: Point, b: Point) -> Point {
                 );
                 let mut injections = Vec::new();
                 for mat in matches {
-                    let (language_name, content_node, included_children) = injection_for_match(
+                    let (language_name, content_node, included_children, offset) = injection_for_match(
                         &layer.config,
                         &layer.config.injections_query,
                         &mat,
@@ -329,12 +341,15 @@ fn point_sub(a: Point, b: Point) -> Point {
                     // to the highlighted document.
                     if let (Some(language_name), Some(content_node)) = (language_name, content_node)
                     {
-                        if let Ok(config) = (injection_callback)(&language_name) {
-                            let ranges =
+                        if let Ok((language, config)) = (injection_callback)(&language_name) {
+                            let mut ranges =
                                 intersect_ranges(&layer.ranges, &[content_node], included_children);
+                            if let Some(offset) = offset {
+                                offset.apply(&mut ranges);
+                            }
 
                             if !ranges.is_empty() {
-                                injections.push((config, ranges));
+                                injections.push((language, config, ranges));
                             }
                         }
                     }
@@ -354,7 +369,7 @@ fn point_sub(a: Point, b: Point) -> Point {
                     );
                     for mat in matches {
                         let entry = &mut injections_by_pattern_index[mat.pattern_index];
-                        let (language_name, content_node, included_children) = injection_for_match(
+                        let (language_name, content_node, included_children, _) = injection_for_match(
                             &layer.config,
                             combined_injections_query,
                             &mat,
@@ -371,14 +386,14 @@ fn point_sub(a: Point, b: Point) -> Point {
                     for (lang_name, content_nodes, included_children) in injections_by_pattern_index
                     {
                         if let (Some(lang_name), false) = (lang_name, content_nodes.is_empty()) {
-                            if let Ok(config) = (injection_callback)(&lang_name) {
+                            if let Ok((language, config)) = (injection_callback)(&lang_name) {
                                 let ranges = intersect_ranges(
                                     &layer.ranges,
                                     &content_nodes,
                                     included_children,
                                 );
                                 if !ranges.is_empty() {
-                                    injections.push((config, ranges));
+                                    injections.push((language, config, ranges));
                                 }
                             }
                         }
@@ -387,7 +402,7 @@ fn point_sub(a: Point, b: Point) -> Point {
 
                 let depth = layer.depth + 1;
                 // TODO: can't inline this since matches borrows self.layers
-                for (config, ranges) in injections {
+                for (language, config, ranges) in injections {
                     // Find an existing layer
                     let layer = self
                         .layers
@@ -403,6 +418,7 @@ fn point_sub(a: Point, b: Point) -> Point {
                         self.layers.insert(LanguageLayer {
                             tree: None,
                             config,
+                            language: Some(language),
                             depth,
                             ranges,
                             rev: 0,
@@ -434,6 +450,49 @@ pub fn try_tree(&self) -> Option<&Tree> {
         self.layers[self.root].try_tree()
     }
 
+    /// The language and the range of the innermost injection at the offset, like
+    /// a code block of markdown or SQL in a string.
+    pub fn injection_at(
+        &self,
+        offset: usize,
+    ) -> Option<(LapceLanguage, Range<usize>)> {
+        self.layers
+            .values()
+            .filter(|layer| layer.depth > 0)
+            .filter_map(|layer| {
+                let range = layer.ranges.iter().find(|range| {
+                    range.start_byte <= offset && offset <= range.end_byte
+                })?;
+                Some((layer.depth, layer.language?, range))
+            })
+            .max_by_key(|(depth, _, _)| *depth)
+            .map(|(_, language, range)| (language, range.start_byte..range.end_byte))
+    }
+
+    /// How many nodes the trees of the layers have, which is what most of the
+    /// memory of a syntax tree goes to.
+    pub fn node_count(&self) -> usize {
+        self.layers
+            .values()
+            .filter_map(|layer| layer.try_tree())
+            .map(|tree| {
+                let mut count = 0;
+                let mut cursor = tree.walk();
+                loop {
+                    count += 1;
+                    if cursor.goto_first_child() {
+                        continue;
+                    }
+                    while !cursor.goto_next_sibling() {
+                        if !cursor.goto_parent() {
+                            return count;
+                        }
+                    }
+                }
+            })
+            .sum()
+    }
+
     /// Iterate over the highlighted regions for a given slice of source code.
     pub fn highlight_iter<'a>(
         &'a self,
@@ -572,52 +631,43 @@ pub fn from_language(language: LapceLanguage) -> Result<Syntax, HighlightIssue>
         })
     }
 
+    /// An estimate of the bytes which the trees and the styles take, leaving out
+    /// the text, which is shared with the buffer.
+    pub fn memory_usage(&self) -> usize {
+        let styles = self
+            .styles
+            .as_ref()
+            .map(|styles| styles.iter().count())
+            .unwrap_or(0);
+        self.layers.node_count() * TREE_NODE_SIZE
+            + styles * mem::size_of::<(Interval, Style)>()
+    }
+
+    /// Parse the new text and highlight all of it.
     pub fn parse(
         &mut self,
         new_rev: u64,
         new_text: Rope,
         edits: Option<&[SyntaxEdit]>,
+    ) {
+        self.parse_tree(new_rev, new_text, edits);
+        self.styles = self.highlight(0..self.text.len()).map(Arc::new);
+    }
+
+    /// Parse the new text without highlighting it, which only reparses what the
+    /// edits changed when there is one edit for each revision since the last
+    /// parse. The styles are left as they were, until [`Syntax::highlight`] is
+    /// done for the new text.
+    pub fn parse_tree(
+        &mut self,
+        new_rev: u64,
+        new_text: Rope,
+        edits: Option<&[SyntaxEdit]>,
     ) {
         let edits = edits.filter(|edits| new_rev == self.rev + edits.len() as u64);
         let _ = self.layers.update(self.rev, new_rev, &new_text, edits);
         let tree = self.layers.try_tree();
 
-        let styles = if tree.is_some() {
-            let mut current_hl: Option<Highlight> = None;
-            let mut highlights: SpansBuilder<Style> =
-                SpansBuilder::new(new_text.len());
-
-            // TODO: Should we be ignoring highlight errors via flattening them?
-            for highlight in self
-                .layers
-                .highlight_iter(&new_text, Some(0..new_text.len()), None)
-                .flatten()
-            {
-                match highlight {
-                    HighlightEvent::Source { start, end } => {
-                        if let Some(hl) = current_hl {
-                            if let Some(hl) = SCOPES.get(hl.0) {
-                                highlights.add_span(
-                                    Interval::new(start, end),
-                                    Style {
-                                        fg_color: Some(hl.to_string()),
-                                    },
-                                );
-                            }
-                        }
-                    }
-                    HighlightEvent::HighlightStart(hl) => {
-                        current_hl = Some(hl);
-                    }
-                    HighlightEvent::HighlightEnd => current_hl = None,
-                }
-            }
-
-            Some(Arc::new(highlights.build()))
-        } else {
-            None
-        };
-
         let normal_lines = if let Some(tree) = tree {
             let mut cursor = tree.walk();
             let mut normal_lines = HashSet::new();
@@ -637,10 +687,56 @@ pub fn parse(
         self.rev = new_rev;
         self.lens = lens;
         self.normal_lines = normal_lines;
-        self.styles = styles;
         self.text = new_text
     }
 
+    /// The styles of the byte range of the text which was parsed last, with the
+    /// offsets starting from the start of the range. This is `None` when there
+    /// is no tree to highlight from.
+    pub fn highlight(&self, range: Range<usize>) -> Option<Spans<Style>> {
+        self.layers.try_tree()?;
+
+        let mut current_hl: Option<Highlight> = None;
+        let mut highlights: SpansBuilder<Style> = SpansBuilder::new(range.len());
+
+        // TODO: Should we be ignoring highlight errors via flattening them?
+        for highlight in self
+            .layers
+            .highlight_iter(&self.text, Some(range.clone()), None)
+            .flatten()
+        {
+            match highlight {
+                HighlightEvent::Source { start, end } => {
+                    // The events can go past the range, at its ends
+                    let start = start.max(range.start);
+                    let end = end.min(range.end);
+                    if start >= end {
+                        continue;
+                    }
+                    if let Some(hl) = current_hl {
+                        if let Some(hl) = SCOPES.get(hl.0) {
+                            highlights.add_span(
+                                Interval::new(
+                                    start - range.start,
+                                    end - range.start,
+                                ),
+                                Style {
+                                    fg_color: Some(hl.to_string()),
+                                },
+                            );
+                        }
+                    }
+                }
+                HighlightEvent::HighlightStart(hl) => {
+                    current_hl = Some(hl);
+                }
+                HighlightEvent::HighlightEnd => current_hl = None,
+            }
+        }
+
+        Some(highlights.build())
+    }
+
     pub fn update_lens_height(&mut self, line_height: usize, lens_height: usize) {
         self.lens = Self::lens_from_normal_lines(
             self.text.line_of_offset(self.text.len()) + 1,
@@ -850,6 +946,151 @@ pub fn find_enclosing_pair(&self, offset: usize) -> Option<(usize, usize)> {
             }
         }
     }
+
+    /// The range of the smallest node which contains the range and is larger than
+    /// it, which a selection is expanded to.
+    pub fn expand_range(&self, range: Range<usize>) -> Option<Range<usize>> {
+        let tree = self.layers.try_tree()?;
+        let mut node = tree
+            .root_node()
+            .descendant_for_byte_range(range.start, range.end)?;
+        while node.start_byte() >= range.start && node.end_byte() <= range.end {
+            node = node.parent()?;
+        }
+        Some(node.byte_range())
+    }
+
+    /// The range of the first named node within the range which is smaller than
+    /// it, for shrinking a selection which wasn't expanded from a smaller one.
+    pub fn shrink_range(&self, range: Range<usize>) -> Option<Range<usize>> {
+        let tree = self.layers.try_tree()?;
+        let mut node = tree
+            .root_node()
+            .descendant_for_byte_range(range.start, range.end)?;
+        loop {
+            let child = (0..node.named_child_count())
+                .filter_map(|i| node.named_child(i))
+                .find(|child| {
+                    child.start_byte() >= range.start
+                        && child.end_byte() <= range.end
+                })?;
+            if child.byte_range() != range {
+                return Some(child.byte_range());
+            }
+            node = child;
+        }
+    }
+
+    /// The range of the comment at the offset.
+    pub fn comment_at(&self, offset: usize) -> Option<Range<usize>> {
+        let tree = self.layers.try_tree()?;
+        let node = tree.root_node().descendant_for_byte_range(offset, offset)?;
+        is_comment(node).then(|| node.byte_range())
+    }
+
+    /// The range of the statement or item which starts at `offset`, the first
+    /// character of a line, and the range of the statement before or after it,
+    /// in the order they are in. A comment at the end of the line of a statement
+    /// goes along with it.
+    pub fn statement_swap(
+        &self,
+        offset: usize,
+        previous: bool,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let tree = self.layers.try_tree()?;
+        let mut node = tree.root_node().descendant_for_byte_range(offset, offset)?;
+        // The largest node which starts there, but not the whole document
+        while let Some(parent) = node.parent() {
+            if parent.parent().is_none() || parent.start_byte() != node.start_byte()
+            {
+                break;
+            }
+            node = parent;
+        }
+        if node.parent().is_none() || node.start_byte() != offset {
+            return None;
+        }
+
+        let (range, last) = with_trailing_comment(node);
+        if previous {
+            let mut sibling = node.prev_named_sibling()?;
+            if is_comment(sibling) {
+                if let Some(statement) = sibling.prev_named_sibling() {
+                    if statement.end_position().row == sibling.start_position().row {
+                        sibling = statement;
+                    }
+                }
+            }
+            Some((with_trailing_comment(sibling).0, range))
+        } else {
+            let sibling = last.next_named_sibling()?;
+            Some((range, with_trailing_comment(sibling).0))
+        }
+    }
+
+    /// The range of the argument or parameter at the offset, and the range of the
+    /// one before or after it, in the order they are in.
+    pub fn argument_swap(
+        &self,
+        offset: usize,
+        previous: bool,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let tree = self.layers.try_tree()?;
+        let mut node = tree.root_node().descendant_for_byte_range(offset, offset)?;
+        loop {
+            let parent = node.parent()?;
+            if is_argument_list(parent) && node.is_named() && !is_comment(node) {
+                break;
+            }
+            node = parent;
+        }
+
+        let mut sibling = named_sibling(node, previous)?;
+        while is_comment(sibling) {
+            sibling = named_sibling(sibling, previous)?;
+        }
+
+        if previous {
+            Some((sibling.byte_range(), node.byte_range()))
+        } else {
+            Some((node.byte_range(), sibling.byte_range()))
+        }
+    }
+}
+
+fn named_sibling(node: Node, previous: bool) -> Option<Node> {
+    if previous {
+        node.prev_named_sibling()
+    } else {
+        node.next_named_sibling()
+    }
+}
+
+fn is_comment(node: Node) -> bool {
+    node.kind().contains("comment")
+}
+
+/// Whether the node is a list of arguments or parameters, which the grammars
+/// name in a few different ways
+fn is_argument_list(node: Node) -> bool {
+    let kind = node.kind();
+    ["arguments", "argument_list", "parameters", "parameter_list"]
+        .iter()
+        .any(|suffix| kind.ends_with(suffix))
+}
+
+/// The range of the node and a comment after it on the same line, and the last
+/// one of them.
+fn with_trailing_comment(node: Node) -> (Range<usize>, Node) {
+    match node.next_sibling() {
+        Some(comment)
+            if is_comment(comment)
+                && comment.start_position().row == node.end_position().row =>
+        {
+            (node.start_byte()..comment.end_byte(), comment)
+        }
+        _ => (node.byte_range(), node),
+    }
 }
 
 #[cfg(test)]
@@ -888,4 +1129,116 @@ fn test_lens_iter() {
         assert_eq!(Some((90, 25)), iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_expand_shrink_range() {
+        let text = "fn main() { foo(bar + 1); }\n";
+        let mut syntax = Syntax::from_language(LapceLanguage::Rust).unwrap();
+        syntax.parse(1, Rope::from(text), None);
+        let range = |s: &str| {
+            let start = text.find(s).unwrap();
+            start..start + s.len()
+        };
+
+        let nested = [
+            "bar",
+            "bar + 1",
+            "(bar + 1)",
+            "foo(bar + 1)",
+            "foo(bar + 1);",
+            "{ foo(bar + 1); }",
+            "fn main() { foo(bar + 1); }",
+            text,
+        ];
+        let bar = range("bar").start;
+        assert_eq!(syntax.expand_range(bar..bar), Some(range("bar")));
+        for ranges in nested.windows(2) {
+            assert_eq!(
+                syntax.expand_range(range(ranges[0])),
+                Some(range(ranges[1]))
+            );
+        }
+        assert_eq!(syntax.expand_range(0..text.len()), None);
+        // A range which isn't a node expands to the node around it
+        assert_eq!(syntax.expand_range(range("(bar")), Some(range("(bar + 1)")));
+
+        assert_eq!(
+            syntax.shrink_range(0..text.len()),
+            Some(range("fn main() { foo(bar + 1); }"))
+        );
+        assert_eq!(
+            syntax.shrink_range(range("fn main() { foo(bar + 1); }")),
+            Some(range("main"))
+        );
+        assert_eq!(
+            syntax.shrink_range(range("{ foo(bar + 1); }")),
+            Some(range("foo(bar + 1);"))
+        );
+        assert_eq!(
+            syntax.shrink_range(range("foo(bar + 1);")),
+            Some(range("foo(bar + 1)"))
+        );
+        assert_eq!(
+            syntax.shrink_range(range("foo(bar + 1)")),
+            Some(range("foo"))
+        );
+        assert_eq!(
+            syntax.shrink_range(range("(bar + 1)")),
+            Some(range("bar + 1"))
+        );
+        assert_eq!(syntax.shrink_range(range("bar + 1")), Some(range("bar")));
+        assert_eq!(syntax.shrink_range(range("bar")), None);
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_parse_tree_with_edits() {
+        use crate::{
+            buffer::{rope_text::RopeText, Buffer},
+            editor::EditType,
+            selection::Selection,
+        };
+
+        let styles = |syntax: &Syntax, range: Range<usize>| {
+            syntax
+                .highlight(range)
+                .unwrap()
+                .iter()
+                .map(|(iv, style)| (iv.start, iv.end, style.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut buffer = Buffer::new("fn main() {}\n");
+        let mut syntax = Syntax::from_language(LapceLanguage::Rust).unwrap();
+        syntax.parse(buffer.rev(), buffer.text().clone(), None);
+
+        let (_, _, edit) = buffer.edit(
+            &[(Selection::caret(11), "let s = \"a\"; ")],
+            EditType::InsertChars,
+        );
+        // The tree is only reparsed, and the styles wait for the highlight
+        let old_styles = syntax.styles.clone();
+        syntax.parse_tree(buffer.rev(), buffer.text().clone(), Some(&[edit]));
+        assert_eq!(syntax.rev, buffer.rev());
+        assert!(Arc::ptr_eq(
+            old_styles.as_ref().unwrap(),
+            syntax.styles.as_ref().unwrap()
+        ));
+
+        let mut full = Syntax::from_language(LapceLanguage::Rust).unwrap();
+        full.parse(buffer.rev(), buffer.text().clone(), None);
+        let len = buffer.text().len();
+        assert_eq!(styles(&syntax, 0..len), styles(&full, 0..len));
+
+        // The styles of a range start at its start
+        let all = styles(&full, 0..len);
+        let part = styles(&full, 11..len);
+        assert!(!part.is_empty());
+        assert!(part.iter().all(|(start, end, style)| all.contains(&(
+            start + 11,
+            end + 11,
+            style.clone()
+        ))));
+    }
 }
@@ -0,0 +1,346 @@
+//! Navigation from the syntax tree, for documents which have no language server
+//! to ask. The definitions come from the tags query of the language, the
+//! references are the names in the document which are the same as the one asked
+//! for.
+
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use lsp_types::SymbolKind;
+use once_cell::sync::Lazy;
+use tracing::error;
+use tree_sitter::{Node, Query, QueryCursor};
+
+use super::{util::RopeProvider, Syntax};
+use crate::language::LapceLanguage;
+
+/// The tags queries which were compiled, which are `None` for the languages that
+/// have no query or whose query doesn't compile
+static TAGS_QUERIES: Lazy<Mutex<HashMap<LapceLanguage, Option<Arc<Query>>>>> =
+    Lazy::new(Default::default);
+
+/// A definition which the tags query of a language finds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The range of the whole definition
+    pub range: Range<usize>,
+    /// The range of the name of the definition
+    pub name_range: Range<usize>,
+}
+
+impl Syntax {
+    fn tags_query(&self) -> Option<Arc<Query>> {
+        let tree = self.layers.try_tree()?;
+        TAGS_QUERIES
+            .lock()
+            .unwrap()
+            .entry(self.language)
+            .or_insert_with(|| {
+                let source = self.language.tags_query()?;
+                match Query::new(tree.language(), source) {
+                    Ok(query) => Some(Arc::new(query)),
+                    Err(e) => {
+                        error!("Failed to compile the tags query: {e:?}");
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+
+    /// The definitions in the document, in the order they are in.
+    pub fn symbols(&self) -> Vec<SyntaxSymbol> {
+        let (Some(tree), Some(query)) = (self.layers.try_tree(), self.tags_query())
+        else {
+            return Vec::new();
+        };
+        let capture_names = query.capture_names();
+
+        let mut symbols = Vec::new();
+        let mut cursor = QueryCursor::new();
+        for query_match in
+            cursor.matches(&query, tree.root_node(), RopeProvider(&self.text))
+        {
+            let mut definition = None;
+            let mut name = None;
+            for capture in query_match.captures {
+                let capture_name = capture_names[capture.index as usize].as_str();
+                if let Some(kind) = capture_name.strip_prefix("definition.") {
+                    definition = Some((symbol_kind(kind), capture.node));
+                } else if capture_name == "name" {
+                    name = Some(capture.node);
+                }
+            }
+            if let (Some((kind, node)), Some(name)) = (definition, name) {
+                symbols.push(SyntaxSymbol {
+                    name: self.node_text(name).into_owned(),
+                    kind,
+                    range: node.byte_range(),
+                    name_range: name.byte_range(),
+                });
+            }
+        }
+
+        // A name can be found by more than one pattern, the first one wins
+        symbols.sort_by_key(|symbol| symbol.name_range.start);
+        symbols.dedup_by(|a, b| a.name_range == b.name_range);
+        symbols
+    }
+
+    /// The range of the name which the name at the offset is defined by. A local
+    /// variable is looked up in the scopes around the offset first, and then the
+    /// definitions of the document are.
+    pub fn find_definition(&self, offset: usize) -> Option<Range<usize>> {
+        let tree = self.layers.try_tree()?;
+        let query = self.tags_query()?;
+        let node = self.name_at(offset)?;
+        let name = self.node_text(node);
+        let capture_names = query.capture_names();
+
+        let mut scopes = Vec::new();
+        let mut locals = Vec::new();
+        let mut definitions = Vec::new();
+        let mut cursor = QueryCursor::new();
+        for query_match in
+            cursor.matches(&query, tree.root_node(), RopeProvider(&self.text))
+        {
+            let is_definition = query_match.captures.iter().any(|capture| {
+                capture_names[capture.index as usize].starts_with("definition.")
+            });
+            for capture in query_match.captures {
+                let range = capture.node.byte_range();
+                match capture_names[capture.index as usize].as_str() {
+                    "local.scope" => scopes.push(range),
+                    "local.definition" if self.node_text(capture.node) == name => {
+                        locals.push(range)
+                    }
+                    "name"
+                        if is_definition && self.node_text(capture.node) == name =>
+                    {
+                        definitions.push(range)
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // The local definition before the name in the innermost scope around it
+        let document = 0..self.text.len();
+        let local = locals
+            .into_iter()
+            .filter_map(|local| {
+                let scope = scopes
+                    .iter()
+                    .filter(|scope| {
+                        scope.start <= local.start && local.end <= scope.end
+                    })
+                    .min_by_key(|scope| scope.len())
+                    .unwrap_or(&document);
+                (scope.start <= node.start_byte()
+                    && node.end_byte() <= scope.end
+                    && local.start <= node.start_byte())
+                .then_some((scope.len(), local))
+            })
+            .min_by_key(|(scope_len, local)| (*scope_len, Reverse(local.start)))
+            .map(|(_, local)| local);
+        if local.is_some() {
+            return local;
+        }
+
+        let range = node.byte_range();
+        definitions
+            .iter()
+            .find(|definition| **definition == range)
+            .or_else(|| definitions.first())
+            .cloned()
+    }
+
+    /// The ranges of the names in the document which are the same as the name at
+    /// the offset, and of the same kind of node.
+    pub fn find_references(&self, offset: usize) -> Vec<Range<usize>> {
+        let (Some(tree), Some(node)) =
+            (self.layers.try_tree(), self.name_at(offset))
+        else {
+            return Vec::new();
+        };
+        let name = self.node_text(node);
+        let kind = node.kind_id();
+
+        let mut references = Vec::new();
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            if node.child_count() > 0 {
+                if cursor.goto_first_child() {
+                    continue;
+                }
+            } else if node.kind_id() == kind
+                && node.byte_range().len() == name.len()
+                && self.node_text(node) == name
+            {
+                references.push(node.byte_range());
+            }
+
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return references;
+                }
+            }
+        }
+    }
+
+    /// The node which is a name, like an identifier, at the offset or just
+    /// before it.
+    fn name_at(&self, offset: usize) -> Option<Node> {
+        let root = self.layers.try_tree()?.root_node();
+        [offset, offset.saturating_sub(1)]
+            .into_iter()
+            .filter_map(|offset| {
+                root.named_descendant_for_byte_range(offset, offset)
+            })
+            .find(|node| {
+                let text = self.node_text(*node);
+                node.child_count() == 0
+                    && !text.is_empty()
+                    && !text.starts_with(|c: char| c.is_ascii_digit())
+                    && text
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+            })
+    }
+
+    fn node_text(&self, node: Node) -> Cow<str> {
+        self.text.slice_to_cow(node.byte_range())
+    }
+}
+
+/// The kind of symbol of a `@definition.kind` capture
+fn symbol_kind(kind: &str) -> SymbolKind {
+    match kind {
+        "function" | "macro" => SymbolKind::FUNCTION,
+        "method" => SymbolKind::METHOD,
+        "constructor" => SymbolKind::CONSTRUCTOR,
+        "class" => SymbolKind::CLASS,
+        "struct" => SymbolKind::STRUCT,
+        "enum" => SymbolKind::ENUM,
+        "enum_member" => SymbolKind::ENUM_MEMBER,
+        "interface" => SymbolKind::INTERFACE,
+        "module" => SymbolKind::MODULE,
+        "constant" => SymbolKind::CONSTANT,
+        "field" => SymbolKind::FIELD,
+        "type" => SymbolKind::TYPE_PARAMETER,
+        _ => SymbolKind::VARIABLE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_xi_rope::Rope;
+
+    use super::*;
+
+    fn parse(language: LapceLanguage, text: &str) -> Syntax {
+        let mut syntax = Syntax::from_language(language).unwrap();
+        syntax.parse(1, Rope::from(text), None);
+        syntax
+    }
+
+    /// The ranges of the whole words in the text which are the name
+    fn words(text: &str, name: &str) -> Vec<Range<usize>> {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        text.match_indices(name)
+            .map(|(i, _)| i..i + name.len())
+            .filter(|range| {
+                !text[..range.start].ends_with(is_word)
+                    && !text[range.end..].starts_with(is_word)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_find_definition() {
+        let text = "fn add(a: i32, b: i32) -> i32 {
+    let sum = a + b;
+    sum
+}
+
+fn main() {
+    let a = 1;
+    let c = add(a, 2);
+    let a = c;
+    a
+}
+";
+        let syntax = parse(LapceLanguage::Rust, text);
+        let word = |name: &str, nth: usize| words(text, name)[nth].clone();
+        let definition =
+            |name: &str, nth: usize| syntax.find_definition(word(name, nth).start);
+
+        // A local variable is defined in the scope around it
+        assert_eq!(definition("sum", 1), Some(word("sum", 0)));
+        assert_eq!(definition("a", 1), Some(word("a", 0)));
+        assert_eq!(definition("b", 1), Some(word("b", 0)));
+        // The parameter of another function doesn't define the name
+        assert_eq!(definition("a", 3), Some(word("a", 2)));
+        // The last definition before the name wins over a shadowed one
+        assert_eq!(definition("a", 5), Some(word("a", 4)));
+        // A definition of the document is found from anywhere
+        assert_eq!(definition("add", 1), Some(word("add", 0)));
+        assert_eq!(definition("add", 0), Some(word("add", 0)));
+        // The offset can be just after the name
+        assert_eq!(
+            syntax.find_definition(word("sum", 1).end),
+            Some(word("sum", 0))
+        );
+        assert_eq!(definition("i32", 0), None);
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_find_references() {
+        let text = "struct Point { x: i32 }
+
+fn point(x: i32) -> Point {
+    Point { x }
+}
+";
+        let syntax = parse(LapceLanguage::Rust, text);
+
+        let point = words(text, "Point");
+        assert_eq!(syntax.find_references(point[2].start), point);
+        // The names of another kind of node are left out
+        let x = words(text, "x");
+        assert_eq!(syntax.find_references(x[1].start), x[1..].to_vec());
+        assert_eq!(syntax.find_references(x[0].start), x[..1].to_vec());
+        assert!(syntax.find_references(text.find('{').unwrap()).is_empty());
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn test_symbols() {
+        let text = "mod a {\n    const B: u8 = 1;\n}\nenum C { D }\n";
+        let syntax = parse(LapceLanguage::Rust, text);
+        let symbols = syntax
+            .symbols()
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            vec![
+                ("a".to_string(), SymbolKind::MODULE),
+                ("B".to_string(), SymbolKind::CONSTANT),
+                ("C".to_string(), SymbolKind::ENUM),
+                ("D".to_string(), SymbolKind::ENUM_MEMBER),
+            ]
+        );
+    }
+}
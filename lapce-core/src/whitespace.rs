@@ -0,0 +1,179 @@
+//! The clean ups of the whitespace of a file which are made when it's saved.
+//! They come as edits to the text, so that they're undone like any other edit.
+
+use std::ops::Range;
+
+use lapce_xi_rope::Rope;
+
+use crate::buffer::rope_text::{RopeText, RopeTextRef};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WhitespaceCleanup {
+    /// Remove the spaces and the tabs at the ends of the lines
+    pub trim_trailing_whitespace: bool,
+    /// End the file with a line ending
+    pub insert_final_newline: bool,
+    /// Remove the blank lines at the end of the file, which leaves one line
+    /// ending when there was one
+    pub trim_final_newlines: bool,
+}
+
+impl WhitespaceCleanup {
+    pub fn is_empty(&self) -> bool {
+        *self == WhitespaceCleanup::default()
+    }
+
+    /// The ranges of the text to replace, in order and apart from each other.
+    /// The lines in `kept_lines`, like the ones of the cursors, keep the
+    /// whitespace at their ends, and the blank lines at the end of the file
+    /// are only removed after them.
+    pub fn edits(
+        &self,
+        text: &Rope,
+        kept_lines: &[usize],
+    ) -> Vec<(Range<usize>, &'static str)> {
+        let text = RopeTextRef::new(text);
+        let len = text.len();
+        let last_line = text.last_line();
+        // Where the content of the line ends, without its line ending and the
+        // whitespace which is trimmed, and how much whitespace that is
+        let content_end = |line: usize| {
+            let content = text.line_content(line);
+            let body = content.trim_end_matches(['\n', '\r']);
+            let trimmed =
+                if self.trim_trailing_whitespace && !kept_lines.contains(&line) {
+                    body.trim_end_matches([' ', '\t'])
+                } else {
+                    body
+                };
+            (
+                text.offset_of_line(line) + trimmed.len(),
+                body.len() - trimmed.len(),
+            )
+        };
+
+        // The last line which is kept when the blank lines at the end are
+        // removed, which is `None` when the file is blank
+        let last_kept_line = self.trim_final_newlines.then(|| {
+            let last_content_line = (0..=last_line)
+                .rev()
+                .find(|line| !text.line_content(*line).trim().is_empty());
+            let last_cursor_line = kept_lines
+                .iter()
+                .copied()
+                .filter(|line| *line <= last_line)
+                .max();
+            last_content_line.max(last_cursor_line)
+        });
+
+        let mut edits = Vec::new();
+        // The whitespace at the end of the last line which is kept goes with
+        // the blank lines after it
+        let trimmed_lines = match last_kept_line {
+            Some(Some(line)) => line,
+            Some(None) => 0,
+            None => last_line + 1,
+        };
+        if self.trim_trailing_whitespace {
+            for line in 0..trimmed_lines {
+                let (end, trailing) = content_end(line);
+                if trailing > 0 {
+                    edits.push((end..end + trailing, ""));
+                }
+            }
+        }
+
+        let line_ending = if text
+            .line_content(last_line.saturating_sub(1))
+            .ends_with("\r\n")
+        {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let ends_with_newline = len > 0 && text.line_content(last_line).is_empty();
+        match last_kept_line {
+            Some(last_kept_line) => {
+                let start = last_kept_line.map(|line| content_end(line).0);
+                let ending = match start {
+                    Some(_) if ends_with_newline || self.insert_final_newline => {
+                        line_ending
+                    }
+                    _ => "",
+                };
+                let start = start.unwrap_or(0);
+                if text.slice_to_cow(start..len) != ending {
+                    edits.push((start..len, ending));
+                }
+            }
+            None if self.insert_final_newline && len > 0 && !ends_with_newline => {
+                // The edit of the whitespace at the end of the last line
+                // reaches the end of the file
+                match edits.last_mut() {
+                    Some((range, ending)) if range.end == len => {
+                        *ending = line_ending;
+                    }
+                    _ => edits.push((len..len, line_ending)),
+                }
+            }
+            None => {}
+        }
+        edits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clean_up(
+        cleanup: WhitespaceCleanup,
+        text: &str,
+        kept_lines: &[usize],
+    ) -> String {
+        let mut text = text.to_string();
+        let edits = cleanup.edits(&Rope::from(text.as_str()), kept_lines);
+        for (range, new_text) in edits.into_iter().rev() {
+            text.replace_range(range, new_text);
+        }
+        text
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace() {
+        let cleanup = WhitespaceCleanup {
+            trim_trailing_whitespace: true,
+            ..Default::default()
+        };
+        assert_eq!(clean_up(cleanup, "a  \nb\t\r\nc ", &[]), "a\nb\r\nc");
+        assert_eq!(clean_up(cleanup, "a  \nb  \n", &[1]), "a\nb  \n");
+    }
+
+    #[test]
+    fn test_final_newlines() {
+        let insert = WhitespaceCleanup {
+            insert_final_newline: true,
+            ..Default::default()
+        };
+        assert_eq!(clean_up(insert, "a", &[]), "a\n");
+        assert_eq!(clean_up(insert, "a\r\nb", &[]), "a\r\nb\r\n");
+        assert_eq!(clean_up(insert, "", &[]), "");
+
+        let trim = WhitespaceCleanup {
+            trim_final_newlines: true,
+            ..Default::default()
+        };
+        assert_eq!(clean_up(trim, "a\n\n  \n", &[]), "a\n");
+        assert_eq!(clean_up(trim, "a", &[]), "a");
+        assert_eq!(clean_up(trim, "a\n\n\n", &[1]), "a\n\n");
+
+        let all = WhitespaceCleanup {
+            trim_trailing_whitespace: true,
+            insert_final_newline: true,
+            trim_final_newlines: true,
+        };
+        assert!(all.edits(&Rope::from("a\nb\n"), &[]).is_empty());
+        assert_eq!(clean_up(all, "a \nb  \n\n", &[]), "a\nb\n");
+        assert_eq!(clean_up(all, "a\nb  ", &[]), "a\nb\n");
+    }
+}
@@ -34,7 +34,7 @@
     selection::Selection,
 };
 use lapce_rpc::{
-    buffer::BufferId,
+    buffer::{BufferId, SaveOptions},
     core::{CoreMessage, CoreNotification},
     dap_types::RunDebugConfig,
     file::PathObject,
@@ -406,6 +406,9 @@ pub fn try_open_in_existing_process(
         let msg: CoreMessage =
             RpcMessage::Notification(CoreNotification::OpenPaths {
                 paths: paths.to_vec(),
+                reuse_window: false,
+                diff: false,
+                wait: false,
             });
         lapce_rpc::stdio::write_msg(&mut socket, msg)?;
 
@@ -2751,6 +2754,7 @@ pub fn document_save(
         self.proxy.proxy_rpc.save(
             rev,
             path.clone(),
+            SaveOptions::default(),
             Box::new(move |result| {
                 if let Ok(ProxyResponse::SaveResponse {}) = result {
                     let _ = event_sink.submit_command(
@@ -1,6 +1,7 @@
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
+    ops::Range,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
@@ -706,7 +707,8 @@ pub fn retrieve_file<P: EditorPosition + Send + 'static>(
             let proxy = self.proxy.clone();
             std::thread::spawn(move || {
                 proxy.proxy_rpc.new_buffer(id, path.clone(), move |result| {
-                    if let Ok(ProxyResponse::NewBufferResponse { content }) = result
+                    if let Ok(ProxyResponse::NewBufferResponse { content, .. }) =
+                        result
                     {
                         let _ = event_sink.submit_command(
                             LAPCE_UI_COMMAND,
@@ -1668,6 +1670,55 @@ pub fn do_multi_selection(
                 let new_selection = Selection::region(0, self.buffer.len());
                 cursor.set_insert(new_selection);
             }
+            ExpandSelection => {
+                if let CursorMode::Insert(selection) = cursor.mode.clone() {
+                    let new_selection = self
+                        .syntax()
+                        .filter(|syntax| syntax.rev == self.rev())
+                        .map(|syntax| {
+                            map_regions(&selection, |range| {
+                                syntax.expand_range(range)
+                            })
+                        });
+                    if let Some(new_selection) = new_selection
+                        .filter(|new_selection| *new_selection != selection)
+                    {
+                        // The history only goes back through selections which were
+                        // expanded one from another
+                        if !cursor
+                            .syntax_selections
+                            .last()
+                            .map(|last| selection_contains(&selection, last))
+                            .unwrap_or(true)
+                        {
+                            cursor.syntax_selections.clear();
+                        }
+                        cursor.syntax_selections.push(selection);
+                        cursor.set_insert(new_selection);
+                    }
+                }
+            }
+            ShrinkSelection => {
+                if let CursorMode::Insert(selection) = cursor.mode.clone() {
+                    let previous = cursor
+                        .syntax_selections
+                        .pop()
+                        .filter(|previous| selection_contains(&selection, previous));
+                    let new_selection = previous.or_else(|| {
+                        cursor.syntax_selections.clear();
+                        self.syntax().filter(|syntax| syntax.rev == self.rev()).map(
+                            |syntax| {
+                                map_regions(&selection, |range| {
+                                    syntax.shrink_range(range)
+                                })
+                            },
+                        )
+                    });
+                    if let Some(new_selection) = new_selection {
+                        cursor.set_insert(new_selection);
+                    }
+                }
+            }
         }
     }
 
@@ -1698,7 +1749,8 @@ pub fn do_motion_mode(
     }
 
     pub fn do_paste(&mut self, cursor: &mut Cursor, data: &RegisterData) {
-        let deltas = Editor::do_paste(cursor, &mut self.buffer, data);
+        let deltas =
+            Editor::do_paste(cursor, &mut self.buffer, data, self.syntax.as_ref());
         self.apply_deltas(&deltas)
     }
 
@@ -3231,3 +3283,29 @@ pub fn find_enclosing_brackets(&self, offset: usize) -> Option<(usize, usize)> {
         }
     }
 }
+
+/// The selection with each of its regions replaced by the range `f` gives for
+/// it, or kept as it is when that's `None`.
+fn map_regions(
+    selection: &Selection,
+    f: impl Fn(Range<usize>) -> Option<Range<usize>>,
+) -> Selection {
+    let mut new_selection = Selection::new();
+    for region in selection.regions() {
+        let range = region.min()..region.max();
+        let range = f(range.clone()).unwrap_or(range);
+        new_selection.add_region(SelRegion::new(range.start, range.end, None));
+    }
+    new_selection
+}
+
+/// Whether each region of `inner` is within a region of `outer`, which regions
+/// that were expanded into the same one are.
+fn selection_contains(outer: &Selection, inner: &Selection) -> bool {
+    inner.regions().iter().all(|inner| {
+        outer
+            .regions()
+            .iter()
+            .any(|outer| outer.min() <= inner.min() && inner.max() <= outer.max())
+    })
+}
@@ -90,6 +90,7 @@ pub fn new(
             items.push(FileNodeItem {
                 path_buf: path.clone(),
                 is_dir: true,
+                link_target: None,
                 read: false,
                 open: false,
                 children: HashMap::new(),
@@ -104,6 +105,7 @@ pub fn new(
             workspace: workspace.path.as_ref().map(|p| FileNodeItem {
                 path_buf: p.clone(),
                 is_dir: true,
+                link_target: None,
                 read: false,
                 open: false,
                 children: HashMap::new(),
@@ -24,6 +24,7 @@ pub fn new() -> Self {
         let root = FileNodeItem {
             path_buf: PathBuf::from("/"),
             is_dir: true,
+            link_target: None,
             read: false,
             open: false,
             children: HashMap::new(),
@@ -47,6 +48,7 @@ pub fn init_home(&mut self, home: &Path) {
         let mut current_file_node = FileNodeItem {
             path_buf: home.to_path_buf(),
             is_dir: true,
+            link_target: None,
             read: false,
             open: false,
             children: HashMap::new(),
@@ -61,6 +63,7 @@ pub fn init_home(&mut self, home: &Path) {
             let mut file_node = FileNodeItem {
                 path_buf: PathBuf::from(p),
                 is_dir: true,
+                link_target: None,
                 read: false,
                 open: true,
                 children: HashMap::new(),
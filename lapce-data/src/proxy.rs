@@ -19,7 +19,7 @@
 use lapce_rpc::{
     core::{CoreHandler, CoreNotification, CoreRequest, CoreRpcHandler},
     plugin::VoltID,
-    proxy::{ProxyRpc, ProxyRpcHandler},
+    proxy::{ProxyRpc, ProxyRpcHandler, ResourceLimits},
     stdio::stdio_transport,
     terminal::TermId,
     RequestId, RpcMessage,
@@ -283,7 +283,7 @@ fn handle_notification(&mut self, rpc: CoreNotification) {
                     Target::Widget(self.tab_id),
                 );
             }
-            Log { level, message } => {
+            Log { level, message, .. } => {
                 if let Ok(level) = log::Level::from_str(&level) {
                     log::log!(level, "{}", message);
                 }
@@ -303,7 +303,7 @@ fn handle_notification(&mut self, rpc: CoreNotification) {
                     Target::Widget(self.tab_id),
                 );
             }
-            DapContinued { dap_id } => {
+            DapContinued { dap_id, .. } => {
                 let _ = self.event_sink.submit_command(
                     LAPCE_UI_COMMAND,
                     LapceUICommand::DapContinued { dap_id },
@@ -325,6 +325,25 @@ fn handle_notification(&mut self, rpc: CoreNotification) {
                     Target::Widget(self.tab_id),
                 );
             }
+            PluginServerStopped { .. } => {}
+            SetStatusItem { .. } | RemoveStatusItem { .. } => {}
+            SubsystemCrashed { .. } => {}
+            OpenLink { .. } => {}
+            SetTests { .. } => {}
+            CoverageChanged { .. } => {}
+            DapOutput { .. } => {}
+            DapWatchEvaluated { .. } => {}
+            DapCapabilities { .. } | DapDataBreakpointsResp { .. } => {}
+            DapMemory { .. } => {}
+            DapBreakpointChanged { .. } => {}
+            WorkspaceFilesChanged { .. } => {}
+            DapTaskFailed { .. } => {}
+            DapThreads { .. } => {}
+            DapTerminated { .. } => {}
+            DapSessionStarted { .. } => {}
+            TaskStarted { .. } => {}
+            TaskOutput { .. } => {}
+            TaskStopped { .. } => {}
         }
     }
 
@@ -390,6 +409,7 @@ fn start(
             plugin_configurations,
             window_id,
             tab_id,
+            ResourceLimits::default(),
         );
         match workspace.kind {
             LapceWorkspaceType::Local => {
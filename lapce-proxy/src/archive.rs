@@ -0,0 +1,314 @@
+//! The archives which are opened as a read only workspace, like a zip or the
+//! tarball of the sources of a dependency, which are browsed and searched
+//! without extracting them. The files of an archive are the paths under the
+//! path of the archive, as in `serde-1.0.0.crate/serde-1.0.0/src/lib.rs`.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use tar::Archive;
+use zip::ZipArchive;
+
+/// The files which are bigger than this are left out of the searches, since a
+/// file is read into memory to be searched
+const MAX_SEARCH_SIZE: u64 = 10 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+impl ArchiveKind {
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        let ends_with = |extensions: &[&str]| {
+            extensions.iter().any(|extension| name.ends_with(extension))
+        };
+        if ends_with(&[".zip", ".jar", ".whl", ".vsix", ".nupkg"]) {
+            Some(ArchiveKind::Zip)
+        } else if ends_with(&[".tar"]) {
+            Some(ArchiveKind::Tar)
+        } else if ends_with(&[".tar.gz", ".tgz", ".crate"]) {
+            Some(ArchiveKind::TarGz)
+        } else if ends_with(&[".tar.zst", ".tzst"]) {
+            Some(ArchiveKind::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the path is a file which is opened as an archive.
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file() && ArchiveKind::from_path(path).is_some()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Entry {
+    Dir,
+    /// A file, with the name which the archive has for it
+    File {
+        name: String,
+    },
+}
+
+pub struct ArchiveFs {
+    path: PathBuf,
+    kind: ArchiveKind,
+    /// The folders and files of the archive by their paths in it. The folders
+    /// which an archive leaves out are the parents of its files.
+    entries: BTreeMap<PathBuf, Entry>,
+}
+
+impl ArchiveFs {
+    /// Read the list of the entries of the archive, without their contents.
+    pub fn open(path: &Path) -> Result<Self> {
+        let kind = ArchiveKind::from_path(path)
+            .ok_or_else(|| anyhow!("{} isn't an archive", path.display()))?;
+        let mut fs = ArchiveFs {
+            path: path.to_path_buf(),
+            kind,
+            entries: BTreeMap::new(),
+        };
+        if kind == ArchiveKind::Zip {
+            let mut zip = ZipArchive::new(File::open(path)?)?;
+            for i in 0..zip.len() {
+                let file = zip.by_index(i)?;
+                let entry = if file.is_dir() {
+                    Entry::Dir
+                } else {
+                    Entry::File {
+                        name: file.name().to_string(),
+                    }
+                };
+                if let Some(path) = file.enclosed_name().and_then(entry_path) {
+                    fs.insert(path, entry);
+                }
+            }
+        } else {
+            let mut tar = fs.tar()?;
+            for entry in tar.entries()? {
+                let entry = entry?;
+                let name = entry.path()?.to_string_lossy().to_string();
+                let entry_type = entry.header().entry_type();
+                let entry = if entry_type.is_dir() {
+                    Entry::Dir
+                } else if entry_type.is_file() {
+                    Entry::File { name: name.clone() }
+                } else {
+                    continue;
+                };
+                if let Some(path) = entry_path(Path::new(&name)) {
+                    fs.insert(path, entry);
+                }
+            }
+        }
+        Ok(fs)
+    }
+
+    fn insert(&mut self, path: PathBuf, entry: Entry) {
+        for parent in path.ancestors().skip(1) {
+            if parent.as_os_str().is_empty() {
+                break;
+            }
+            self.entries.insert(parent.to_path_buf(), Entry::Dir);
+        }
+        self.entries.insert(path, entry);
+    }
+
+    fn tar(&self) -> Result<Archive<Box<dyn Read>>> {
+        let file = File::open(&self.path)?;
+        let reader: Box<dyn Read> = match self.kind {
+            ArchiveKind::TarGz => Box::new(GzDecoder::new(file)),
+            ArchiveKind::TarZst => Box::new(zstd::Decoder::new(file)?),
+            ArchiveKind::Tar | ArchiveKind::Zip => Box::new(file),
+        };
+        Ok(Archive::new(reader))
+    }
+
+    /// The path of the path in the archive, which is empty for the archive.
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        path.strip_prefix(&self.path).ok()
+    }
+
+    /// Whether the path is the archive or a path in it.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.relative(path).is_some()
+    }
+
+    /// The paths in the folder of the archive, with whether they're folders,
+    /// which is `None` when there's no such folder.
+    pub fn read_dir(&self, path: &Path) -> Option<Vec<(PathBuf, bool)>> {
+        let folder = self.relative(path)?;
+        if !folder.as_os_str().is_empty()
+            && self.entries.get(folder) != Some(&Entry::Dir)
+        {
+            return None;
+        }
+        let children = self
+            .entries
+            .range(folder.to_path_buf()..)
+            .take_while(|(entry, _)| entry.starts_with(folder))
+            .filter(|(entry, _)| entry.parent() == Some(folder))
+            .map(|(entry, kind)| (self.path.join(entry), *kind == Entry::Dir))
+            .collect();
+        Some(children)
+    }
+
+    /// The paths of the files of the archive.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| matches!(entry, Entry::File { .. }))
+            .map(|(path, _)| self.path.join(path))
+            .collect()
+    }
+
+    /// The content of the file of the archive. A file of a tarball is looked
+    /// for from its start, as the tarball can't be seeked in.
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>> {
+        let Some(Entry::File { name, .. }) =
+            self.relative(path).and_then(|path| self.entries.get(path))
+        else {
+            return Err(anyhow!("{} isn't in the archive", path.display()));
+        };
+        let mut content = Vec::new();
+        if self.kind == ArchiveKind::Zip {
+            ZipArchive::new(File::open(&self.path)?)?
+                .by_name(name)?
+                .read_to_end(&mut content)?;
+            return Ok(content);
+        }
+        let mut tar = self.tar()?;
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            if entry.path()?.to_string_lossy() == name.as_str() {
+                entry.read_to_end(&mut content)?;
+                return Ok(content);
+            }
+        }
+        Err(anyhow!("{} isn't in the archive", path.display()))
+    }
+
+    /// Call `f` with the path and the content of each file of the archive which
+    /// isn't too big to be searched, in one pass over the archive, until `f`
+    /// returns false.
+    pub fn for_each_file(
+        &self,
+        mut f: impl FnMut(PathBuf, &[u8]) -> bool,
+    ) -> Result<()> {
+        let mut content = Vec::new();
+        if self.kind == ArchiveKind::Zip {
+            let mut zip = ZipArchive::new(File::open(&self.path)?)?;
+            for i in 0..zip.len() {
+                let mut file = zip.by_index(i)?;
+                if file.is_dir() || file.size() > MAX_SEARCH_SIZE {
+                    continue;
+                }
+                let Some(path) = file.enclosed_name().and_then(entry_path) else {
+                    continue;
+                };
+                content.clear();
+                file.read_to_end(&mut content)?;
+                if !f(self.path.join(path), &content) {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+        let mut tar = self.tar()?;
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file()
+                || entry.size() > MAX_SEARCH_SIZE
+            {
+                continue;
+            }
+            let Some(path) = entry_path(&entry.path()?) else {
+                continue;
+            };
+            content.clear();
+            entry.read_to_end(&mut content)?;
+            if !f(self.path.join(path), &content) {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The path of an entry in the archive, which is `None` for one which would be
+/// outside of the archive.
+fn entry_path(name: &Path) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(name) => path.push(name),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_kind() {
+        let kind = |name: &str| ArchiveKind::from_path(Path::new(name));
+        assert_eq!(kind("serde-1.0.0.crate"), Some(ArchiveKind::TarGz));
+        assert_eq!(kind("dist.TAR.ZST"), Some(ArchiveKind::TarZst));
+        assert_eq!(kind("app.jar"), Some(ArchiveKind::Zip));
+        assert_eq!(kind("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_read_dir() {
+        let mut fs = ArchiveFs {
+            path: PathBuf::from("/deps/serde.crate"),
+            kind: ArchiveKind::TarGz,
+            entries: BTreeMap::new(),
+        };
+        for name in [
+            "./serde/src/lib.rs",
+            "serde/Cargo.toml",
+            "../escape",
+            "/abs",
+        ] {
+            if let Some(path) = entry_path(Path::new(name)) {
+                let entry = Entry::File {
+                    name: name.to_string(),
+                };
+                fs.insert(path, entry);
+            }
+        }
+
+        assert_eq!(
+            fs.read_dir(Path::new("/deps/serde.crate")),
+            Some(vec![(PathBuf::from("/deps/serde.crate/serde"), true)])
+        );
+        assert_eq!(
+            fs.read_dir(Path::new("/deps/serde.crate/serde")),
+            Some(vec![
+                (PathBuf::from("/deps/serde.crate/serde/Cargo.toml"), false),
+                (PathBuf::from("/deps/serde.crate/serde/src"), true),
+            ])
+        );
+        assert_eq!(
+            fs.read_dir(Path::new("/deps/serde.crate/serde/Cargo.toml")),
+            None
+        );
+        assert_eq!(fs.files().len(), 2);
+    }
+}
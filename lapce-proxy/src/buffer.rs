@@ -5,14 +5,13 @@
     fs::File,
     io::{Read, Write},
     path::{Path, PathBuf},
+    process::Command,
     time::SystemTime,
 };
 
 use anyhow::{anyhow, Result};
-use lapce_core::{
-    buffer::rope_text::CharIndicesJoin, encoding::offset_utf8_to_utf16,
-};
-use lapce_rpc::buffer::BufferId;
+use lapce_core::buffer::rope_text::{CharIndicesJoin, RopeText, RopeTextRef};
+use lapce_rpc::buffer::{BufferId, SaveMode, SaveOptions};
 use lapce_xi_rope::{interval::IntervalBounds, rope::Rope, RopeDelta};
 use lsp_types::*;
 
@@ -28,7 +27,14 @@ pub struct Buffer {
 
 impl Buffer {
     pub fn new(id: BufferId, path: PathBuf) -> Buffer {
-        let rope = Rope::from(load_file(&path).unwrap_or_default());
+        let content = load_file(&path).unwrap_or_default();
+        Self::with_content(id, path, content)
+    }
+
+    /// The buffer of a file whose content isn't on the disk, like a file of an
+    /// archive.
+    pub fn with_content(id: BufferId, path: PathBuf, content: String) -> Buffer {
+        let rope = Rope::from(content);
         let rev = u64::from(!rope.is_empty());
         let language_id = language_id_from_path(&path).unwrap_or("");
         let mod_time = get_mod_time(&path);
@@ -42,11 +48,40 @@ pub fn new(id: BufferId, path: PathBuf) -> Buffer {
         }
     }
 
-    pub fn save(&mut self, rev: u64) -> Result<()> {
+    pub fn save(&mut self, rev: u64, options: SaveOptions) -> Result<()> {
         if self.rev != rev {
             return Err(anyhow!("not the right rev"));
         }
-        let tmp_extension = self.path.extension().map_or_else(
+        let path = save_path(&self.path)?;
+        let metadata = fs::metadata(&path).ok();
+
+        if options.backup && metadata.is_some() {
+            fs::copy(&path, backup_path(&path))?;
+        }
+
+        let saved = options.mode != SaveMode::InPlace
+            && self.save_atomic(&path, metadata.as_ref(), options)?;
+        if !saved {
+            // Truncating the file keeps it, with its links, owner and permissions
+            self.write_to(File::create(&path)?, options.fsync)?;
+        }
+        self.mod_time = get_mod_time(&path);
+
+        Ok(())
+    }
+
+    /// Write the buffer to a temporary file next to the file, and rename that
+    /// over the file. In the `auto` mode this doesn't write anything and returns
+    /// false when the rename would break the hard links of the file or change
+    /// its owner, or when the temporary file can't be created, like in a folder
+    /// which only the file in it is writable in.
+    fn save_atomic(
+        &self,
+        path: &Path,
+        metadata: Option<&fs::Metadata>,
+        options: SaveOptions,
+    ) -> Result<bool> {
+        let tmp_extension = path.extension().map_or_else(
             || OsString::from("swp"),
             |ext| {
                 let mut ext = ext.to_os_string();
@@ -54,24 +89,74 @@ pub fn save(&mut self, rev: u64) -> Result<()> {
                 ext
             },
         );
-        let path = if self.path.is_symlink() {
-            self.path.canonicalize()?
-        } else {
-            self.path.clone()
-        };
         let tmp_path = &path.with_extension(tmp_extension);
 
-        let mut f = File::create(tmp_path)?;
+        let f = match File::create(tmp_path) {
+            Ok(f) => f,
+            Err(_) if options.mode == SaveMode::Auto => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        if let (Some(metadata), SaveMode::Auto) = (metadata, options.mode) {
+            let replaceable = f
+                .metadata()
+                .map(|tmp_metadata| is_replaceable(metadata, &tmp_metadata));
+            if !matches!(replaceable, Ok(true)) {
+                drop(f);
+                fs::remove_file(tmp_path)?;
+                return Ok(false);
+            }
+        }
+
+        let result = self.write_to(f, options.fsync).and_then(|_| {
+            if let Some(metadata) = metadata {
+                fs::set_permissions(tmp_path, metadata.permissions())?;
+            }
+            fs::rename(tmp_path, path)?;
+            Ok(())
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(tmp_path);
+        }
+        result?;
+
+        if options.fsync {
+            sync_parent_dir(path)?;
+        }
+
+        Ok(true)
+    }
+
+    fn write_to(&self, mut f: File, fsync: bool) -> Result<()> {
         for chunk in self.rope.iter_chunks(..self.rope.len()) {
             f.write_all(chunk.as_bytes())?;
         }
+        if fsync {
+            f.sync_all()?;
+        }
+        Ok(())
+    }
 
-        if let Ok(metadata) = fs::metadata(&path) {
-            let perm = metadata.permissions();
-            fs::set_permissions(tmp_path, perm)?;
+    /// Save the buffer with the privileges of an administrator, which the system
+    /// asks the user to authenticate for: polkit on Linux and FreeBSD, an
+    /// administrator prompt on macOS, and UAC on Windows. The content is written
+    /// to a temporary file first, which is then copied over the file.
+    pub fn save_as_admin(&mut self, rev: u64) -> Result<()> {
+        if self.rev != rev {
+            return Err(anyhow!("not the right rev"));
         }
+        let path = save_path(&self.path)?;
+        let tmp_dir = private_temp_dir(&std::env::temp_dir(), "lapce-save")?;
+        let tmp_path = tmp_dir.join(
+            path.file_name()
+                .map(|name| name.to_os_string())
+                .unwrap_or_else(|| OsString::from("buffer")),
+        );
 
-        fs::rename(tmp_path, &path)?;
+        let result = create_private_file(&tmp_path)
+            .and_then(|f| self.write_to(f, false))
+            .and_then(|_| copy_as_admin(&tmp_path, &path));
+        let _ = fs::remove_dir_all(&tmp_dir);
+        result?;
         self.mod_time = get_mod_time(&path);
 
         Ok(())
@@ -116,17 +201,7 @@ pub fn offset_to_line_col(&self, offset: usize) -> (usize, usize) {
 
     /// Converts a UTF8 offset to a UTF16 LSP position  
     pub fn offset_to_position(&self, offset: usize) -> Position {
-        let (line, col) = self.offset_to_line_col(offset);
-        // Get the offset of line to make the conversion cheaper, rather than working
-        // from the very start of the document to `offset`
-        let line_offset = self.offset_of_line(line);
-        let utf16_col =
-            offset_utf8_to_utf16(self.char_indices_iter(line_offset..), col);
-
-        Position {
-            line: line as u32,
-            character: utf16_col as u32,
-        }
+        RopeTextRef::new(&self.rope).offset_to_position(offset)
     }
 
     pub fn slice_to_cow<T: IntervalBounds>(&self, range: T) -> Cow<str> {
@@ -151,6 +226,166 @@ pub fn is_empty(&self) -> bool {
     }
 }
 
+/// The file which is written when the buffer of the path is saved, which is the
+/// one that a symlink points to, so that the link stays a link. The target of a
+/// link to a file which is gone is created again.
+fn save_path(path: &Path) -> Result<PathBuf> {
+    if !path.is_symlink() {
+        return Ok(path.to_path_buf());
+    }
+    if let Ok(target) = path.canonicalize() {
+        return Ok(target);
+    }
+    let target = fs::read_link(path)?;
+    Ok(match path.parent() {
+        Some(parent) => parent.join(target),
+        None => target,
+    })
+}
+
+/// The path of the backup of a file, which is the file name with a `~` at the end
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_os_string();
+    backup.push("~");
+    PathBuf::from(backup)
+}
+
+/// Whether a file can be replaced by a new file without losing anything about
+/// it, which it can't when it has other hard links or when the new file would
+/// have another owner.
+#[cfg(unix)]
+fn is_replaceable(file: &fs::Metadata, new_file: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    file.nlink() <= 1 && file.uid() == new_file.uid() && file.gid() == new_file.gid()
+}
+
+#[cfg(not(unix))]
+fn is_replaceable(_file: &fs::Metadata, _new_file: &fs::Metadata) -> bool {
+    true
+}
+
+/// Flush the directory of the file, which a rename in it is only on the disk
+/// after.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Create a new directory in `parent` which only the user can get into, so that
+/// no one else can have put a file or a link in the place of a file which is
+/// written in it.
+fn private_temp_dir(parent: &Path, prefix: &str) -> Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    for attempt in 0..16u32 {
+        let dir = parent.join(format!(
+            "{prefix}-{}-{:x}",
+            std::process::id(),
+            nanos.wrapping_add(attempt)
+        ));
+        let mut builder = fs::DirBuilder::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(anyhow!("couldn't create a temporary directory"))
+}
+
+/// Create a file which doesn't exist yet and which only the user can read
+fn create_private_file(path: &Path) -> Result<File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    Ok(options.open(path)?)
+}
+
+/// Copy the file over the target with the privileges of an administrator
+fn copy_as_admin(from: &Path, to: &Path) -> Result<()> {
+    let status = copy_as_admin_command(from, to)?.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(anyhow!("the file couldn't be saved as administrator"))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+fn copy_as_admin_command(from: &Path, to: &Path) -> Result<Command> {
+    let mut command = Command::new("pkexec");
+    command.arg("cp").arg(from).arg(to);
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+fn copy_as_admin_command(from: &Path, to: &Path) -> Result<Command> {
+    let quote = |path: &Path| {
+        format!(
+            "quoted form of \"{}\"",
+            path.to_string_lossy()
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+        )
+    };
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "do shell script \"cp \" & {} & \" \" & {} with administrator privileges",
+        quote(from),
+        quote(to)
+    ));
+    Ok(command)
+}
+
+/// Windows asks for the elevation through UAC, for a `copy` which is started with
+/// the `RunAs` verb. Its exit code is passed on, so that a failed copy is noticed.
+#[cfg(target_os = "windows")]
+fn copy_as_admin_command(from: &Path, to: &Path) -> Result<Command> {
+    let quote =
+        |path: &Path| format!("'{}'", path.to_string_lossy().replace('\'', "''"));
+    let mut command = Command::new("powershell");
+    command.arg("-NoProfile").arg("-Command").arg(format!(
+        "$p = Start-Process -FilePath cmd -Verb RunAs -Wait -PassThru -WindowStyle Hidden \
+         -ArgumentList '/c', 'copy', '/y', ('\"' + {} + '\"'), ('\"' + {} + '\"'); \
+         exit $p.ExitCode",
+        quote(from),
+        quote(to)
+    ));
+    Ok(command)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "macos",
+    target_os = "windows"
+)))]
+fn copy_as_admin_command(_from: &Path, _to: &Path) -> Result<Command> {
+    Err(anyhow!(
+        "saving as administrator isn't supported on this platform"
+    ))
+}
+
 pub fn load_file(path: &Path) -> Result<String> {
     Ok(read_path_to_string_lossy(path)?)
 }
@@ -308,3 +543,123 @@ pub fn get_mod_time<P: AsRef<Path>>(path: P) -> Option<SystemTime> {
         .and_then(|meta| meta.modified())
         .ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_temp_file() {
+        let parent = std::env::temp_dir();
+        let dir = private_temp_dir(&parent, "lapce-test").unwrap();
+        let other = private_temp_dir(&parent, "lapce-test").unwrap();
+        assert_ne!(dir, other);
+
+        let path = dir.join("file");
+        create_private_file(&path).unwrap();
+        // A file which is there already isn't written through
+        assert!(create_private_file(&path).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| {
+                fs::metadata(path).unwrap().permissions().mode() & 0o777
+            };
+            assert_eq!(mode(&dir), 0o700);
+            assert_eq!(mode(&path), 0o600);
+
+            let link = other.join("link");
+            std::os::unix::fs::symlink(&path, &link).unwrap();
+            assert!(create_private_file(&link).is_err());
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(other).unwrap();
+    }
+
+    #[test]
+    fn test_save_modes() {
+        let dir = private_temp_dir(&std::env::temp_dir(), "lapce-test").unwrap();
+        let path = dir.join("file.txt");
+        assert_eq!(backup_path(&path), dir.join("file.txt~"));
+
+        for mode in SaveMode::ALL {
+            fs::write(&path, "old").unwrap();
+            let mut buffer = Buffer::new(BufferId(0), path.clone());
+            buffer.rope = Rope::from("new");
+            let options = SaveOptions {
+                mode,
+                fsync: true,
+                backup: true,
+            };
+            buffer.save(buffer.rev, options).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+            assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "old");
+            assert!(!dir.join("file.txt.swp").exists());
+        }
+
+        // A file with another hard link is written in place in the auto mode,
+        // so that the link sees the new content
+        #[cfg(unix)]
+        {
+            let link = dir.join("link.txt");
+            fs::hard_link(&path, &link).unwrap();
+            let mut buffer = Buffer::new(BufferId(0), path.clone());
+            buffer.rope = Rope::from("linked");
+            buffer.save(buffer.rev, SaveOptions::default()).unwrap();
+            assert_eq!(fs::read_to_string(&link).unwrap(), "linked");
+        }
+
+        // A writable file in a folder which isn't is written in place in the auto
+        // mode, since the temporary file can't be created next to it, which root
+        // can do all the same
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        if unsafe { libc::geteuid() } != 0 {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+            let mut buffer = Buffer::new(BufferId(0), path.clone());
+            buffer.rope = Rope::from("in place");
+            let atomic = SaveOptions {
+                mode: SaveMode::Atomic,
+                ..SaveOptions::default()
+            };
+            assert!(buffer.save(buffer.rev, atomic).is_err());
+            buffer.save(buffer.rev, SaveOptions::default()).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), "in place");
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    #[test]
+    fn test_copy_as_admin_command() {
+        let command =
+            copy_as_admin_command(Path::new("/tmp/a b"), Path::new("/etc/c"))
+                .unwrap();
+        assert_eq!(command.get_program(), "pkexec");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["cp", "/tmp/a b", "/etc/c"]);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_copy_as_admin_command() {
+        let command =
+            copy_as_admin_command(Path::new("/tmp/a\"b"), Path::new("/etc/c"))
+                .unwrap();
+        assert_eq!(command.get_program(), "osascript");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "-e",
+                "do shell script \"cp \" & quoted form of \"/tmp/a\\\"b\" & \" \" & \
+                 quoted form of \"/etc/c\" with administrator privileges"
+            ]
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Loading the coverage reports of lcov and cobertura.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use lapce_rpc::coverage::{Coverage, FileCoverage, LineCoverage};
+
+/// The reports which are looked for in the workspace when none is set
+const DEFAULT_REPORTS: [&str; 4] = [
+    "lcov.info",
+    "coverage/lcov.info",
+    "coverage.xml",
+    "coverage/cobertura.xml",
+];
+
+/// The report which is loaded, where a relative one is in the workspace.
+pub fn find_report(
+    workspace: Option<&Path>,
+    report: Option<PathBuf>,
+) -> Result<PathBuf> {
+    match report {
+        Some(report) if report.is_absolute() => Ok(report),
+        Some(report) => workspace
+            .map(|workspace| workspace.join(report))
+            .ok_or_else(|| anyhow!("a relative report needs a workspace")),
+        None => {
+            let workspace =
+                workspace.ok_or_else(|| anyhow!("there's no workspace"))?;
+            DEFAULT_REPORTS
+                .iter()
+                .map(|report| workspace.join(report))
+                .find(|report| report.is_file())
+                .ok_or_else(|| anyhow!("no coverage report in the workspace"))
+        }
+    }
+}
+
+/// Load the report, which is cobertura when it's xml and lcov otherwise. The
+/// relative paths of lcov are in the workspace.
+pub fn load_report(report: &Path, workspace: Option<&Path>) -> Result<Coverage> {
+    let text = fs::read_to_string(report)?;
+    let base = workspace
+        .or_else(|| report.parent())
+        .unwrap_or_else(|| Path::new(""));
+    if text.trim_start().starts_with('<') {
+        parse_cobertura(&text, base)
+    } else {
+        parse_lcov(&text, base)
+    }
+}
+
+fn resolve(base: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    }
+}
+
+/// Parse the `SF`, `DA` and `BRDA` records of lcov, where a line is partial
+/// when it ran but one of its branches didn't.
+fn parse_lcov(text: &str, base: &Path) -> Result<Coverage> {
+    let mut coverage = Coverage::default();
+    let mut path = None;
+    let mut file = FileCoverage::default();
+    // The lines which have a branch that wasn't taken
+    let mut untaken = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (kind, value) = line.split_once(':').unwrap_or((line, ""));
+        match kind {
+            "SF" => path = Some(resolve(base, value)),
+            "DA" => {
+                let mut fields = value.split(',');
+                let (Some(number), Some(hits)) = (fields.next(), fields.next())
+                else {
+                    continue;
+                };
+                let (Ok(number), Ok(hits)) =
+                    (number.parse::<u32>(), hits.parse::<u64>())
+                else {
+                    continue;
+                };
+                let line = if hits > 0 {
+                    LineCoverage::Covered
+                } else {
+                    LineCoverage::Uncovered
+                };
+                file.add_line(number.saturating_sub(1), line);
+            }
+            "BRDA" => {
+                let fields = value.split(',').collect::<Vec<_>>();
+                if let [number, _, _, taken] = fields[..] {
+                    let taken = taken.parse::<u64>().unwrap_or(0);
+                    if let (Ok(number), 0) = (number.parse::<u32>(), taken) {
+                        untaken.push(number.saturating_sub(1));
+                    }
+                }
+            }
+            "end_of_record" => {
+                for line in untaken.drain(..) {
+                    if let Some(coverage) = file.lines.get_mut(&line) {
+                        if *coverage == LineCoverage::Covered {
+                            *coverage = LineCoverage::Partial;
+                        }
+                    }
+                }
+                let file = std::mem::take(&mut file);
+                if let Some(path) = path.take() {
+                    let entry = coverage.files.entry(path).or_default();
+                    for (line, line_coverage) in file.lines {
+                        entry.add_line(line, line_coverage);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if coverage.files.is_empty() {
+        return Err(anyhow!("the report has no files"));
+    }
+    Ok(coverage)
+}
+
+/// The tags of the xml by name and attributes, along with the text which
+/// follows the tag.
+fn xml_tags(text: &str) -> impl Iterator<Item = (&str, &str, &str)> {
+    text.split('<').skip(1).filter_map(|tag| {
+        let (tag, rest) = tag.split_once('>')?;
+        if tag.starts_with('/') || tag.starts_with('?') || tag.starts_with('!') {
+            return None;
+        }
+        let tag = tag.trim_end_matches('/');
+        let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        Some((name, attrs, rest))
+    })
+}
+
+/// The value of an attribute of a tag, which the entities of xml are taken out
+/// of.
+fn xml_attr(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    while let Some(index) = rest.find(name) {
+        let before = rest[..index].chars().last();
+        let after = rest[index + name.len()..].trim_start();
+        rest = &rest[index + name.len()..];
+        if before.map(|c| !c.is_whitespace()).unwrap_or(false) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &value[1..];
+        let end = value.find(quote)?;
+        return Some(xml_unescape(&value[..end]));
+    }
+    None
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parse the `line`s of the `class`es of cobertura, whose file names are in
+/// one of its `source`s.
+fn parse_cobertura(text: &str, base: &Path) -> Result<Coverage> {
+    let mut sources = Vec::new();
+    let mut coverage = Coverage::default();
+    let mut path: Option<PathBuf> = None;
+
+    for (name, attrs, rest) in xml_tags(text) {
+        match name {
+            "source" => {
+                let source = xml_unescape(rest.trim());
+                if !source.is_empty() {
+                    sources.push(resolve(base, &source));
+                }
+            }
+            "class" => {
+                path = xml_attr(attrs, "filename").map(|filename| {
+                    sources
+                        .iter()
+                        .map(|source| source.join(&filename))
+                        .find(|path| path.is_file())
+                        .or_else(|| {
+                            sources.first().map(|source| source.join(&filename))
+                        })
+                        .unwrap_or_else(|| resolve(base, &filename))
+                });
+            }
+            "line" => {
+                let Some(path) = path.as_ref() else {
+                    continue;
+                };
+                let Some(number) = xml_attr(attrs, "number")
+                    .and_then(|number| number.parse::<u32>().ok())
+                else {
+                    continue;
+                };
+                let hits = xml_attr(attrs, "hits")
+                    .and_then(|hits| hits.parse::<u64>().ok())
+                    .unwrap_or(0);
+                // Like `50% (1/2)`
+                let all_branches = xml_attr(attrs, "condition-coverage")
+                    .map(|c| c.starts_with("100%"))
+                    .unwrap_or(true);
+                let line = match (hits > 0, all_branches) {
+                    (false, _) => LineCoverage::Uncovered,
+                    (true, false) => LineCoverage::Partial,
+                    (true, true) => LineCoverage::Covered,
+                };
+                coverage
+                    .files
+                    .entry(path.clone())
+                    .or_default()
+                    .add_line(number.saturating_sub(1), line);
+            }
+            _ => {}
+        }
+    }
+
+    if coverage.files.is_empty() {
+        return Err(anyhow!("the report has no files"));
+    }
+    Ok(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lcov() {
+        let text = "TN:
+SF:src/lib.rs
+DA:1,3
+DA:2,0
+DA:4,1
+BRDA:4,0,0,1
+BRDA:4,0,1,-
+end_of_record
+SF:/abs/main.rs
+DA:1,0
+end_of_record
+";
+        let coverage = parse_lcov(text, Path::new("/ws")).unwrap();
+        let lib = &coverage.files[Path::new("/ws/src/lib.rs")];
+        assert_eq!(lib.lines[&0], LineCoverage::Covered);
+        assert_eq!(lib.lines[&1], LineCoverage::Uncovered);
+        assert_eq!(lib.lines[&3], LineCoverage::Partial);
+        assert_eq!(
+            coverage.files[Path::new("/abs/main.rs")].lines[&0],
+            LineCoverage::Uncovered
+        );
+    }
+
+    #[test]
+    fn test_parse_cobertura() {
+        let text = r#"<?xml version="1.0" ?>
+<coverage line-rate="0.5">
+  <sources>
+    <source>/ws/src</source>
+  </sources>
+  <packages>
+    <package name="foo">
+      <classes>
+        <class name="lib" filename="lib.rs">
+          <lines>
+            <line number="1" hits="2"/>
+            <line number="2" hits="0"/>
+            <line number="3" hits="1" branch="true" condition-coverage="50% (1/2)"/>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>"#;
+        let coverage = parse_cobertura(text, Path::new("/ws")).unwrap();
+        let lib = &coverage.files[Path::new("/ws/src/lib.rs")];
+        assert_eq!(lib.lines[&0], LineCoverage::Covered);
+        assert_eq!(lib.lines[&1], LineCoverage::Uncovered);
+        assert_eq!(lib.lines[&2], LineCoverage::Partial);
+    }
+
+    #[test]
+    fn test_xml_attr() {
+        assert_eq!(
+            xml_attr(r#"name="a" filename="b &amp; c.rs""#, "name"),
+            Some("a".to_string())
+        );
+        assert_eq!(
+            xml_attr(r#"name="a" filename="b &amp; c.rs""#, "filename"),
+            Some("b & c.rs".to_string())
+        );
+        assert_eq!(xml_attr(r#"filename="a""#, "name"), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! The panics on the threads which the subsystems of the proxy run on, which are
+//! reported to the app with their backtrace, and the subsystem is started again
+//! instead of staying gone until the proxy is.
+
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+    thread,
+    time::{Duration, Instant},
+};
+
+use lapce_rpc::{
+    core::CoreRpcHandler,
+    crash::{CrashReport, Subsystem},
+};
+use tracing::{error, info_span};
+
+/// How many times in a row a subsystem is started again after it panics
+const MAX_RESTARTS: usize = 3;
+
+/// How long a subsystem has to run without panicking for the count of its
+/// restarts to start over
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+static PANIC_HOOK: Once = Once::new();
+
+thread_local! {
+    /// The backtrace of the last panic on this thread
+    static BACKTRACE: RefCell<Option<Backtrace>> = RefCell::new(None);
+}
+
+/// Run `f` on this thread, and again each time it panics, until it returns or it
+/// panicked too many times in a row. Each panic is reported to the app.
+pub fn supervise(
+    subsystem: Subsystem,
+    name: impl Into<String>,
+    core_rpc: &CoreRpcHandler,
+    mut f: impl FnMut(),
+) {
+    install_panic_hook();
+    let name = name.into();
+    let _span = info_span!("subsystem", %subsystem, name = %name).entered();
+    let mut restarts = 0;
+    loop {
+        let started = Instant::now();
+        let Err(payload) = panic::catch_unwind(AssertUnwindSafe(&mut f)) else {
+            return;
+        };
+
+        if started.elapsed() >= RESTART_WINDOW {
+            restarts = 0;
+        }
+        let restarted = restarts < MAX_RESTARTS;
+        restarts += 1;
+
+        let report = CrashReport {
+            subsystem,
+            name: name.clone(),
+            message: panic_message(payload.as_ref()),
+            backtrace: BACKTRACE
+                .with(|backtrace| backtrace.borrow_mut().take())
+                .map(|backtrace| backtrace.to_string())
+                .unwrap_or_default(),
+            restarted,
+        };
+        error!(
+            "the {} panicked: {}\n{}",
+            report.source(),
+            report.message,
+            report.backtrace
+        );
+        core_rpc.subsystem_crashed(report);
+
+        if !restarted {
+            return;
+        }
+    }
+}
+
+/// [`supervise`] on a new thread.
+pub fn spawn_supervised(
+    subsystem: Subsystem,
+    name: impl Into<String>,
+    core_rpc: CoreRpcHandler,
+    f: impl FnMut() + Send + 'static,
+) {
+    let name = name.into();
+    thread::spawn(move || supervise(subsystem, name, &core_rpc, f));
+}
+
+/// Keep the backtrace of a panic for the report of it, along with printing it
+/// as before.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            BACKTRACE.with(|backtrace| {
+                *backtrace.borrow_mut() = Some(Backtrace::force_capture());
+            });
+            default_hook(info);
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::core::{CoreNotification, CoreRpc};
+
+    use super::*;
+
+    fn crashes(core_rpc: &CoreRpcHandler) -> Vec<CrashReport> {
+        core_rpc
+            .rx()
+            .try_iter()
+            .filter_map(|rpc| match rpc {
+                CoreRpc::Notification(notification) => match *notification {
+                    CoreNotification::SubsystemCrashed { report } => Some(report),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_supervise_restarts() {
+        let core_rpc = CoreRpcHandler::new();
+        let mut runs = 0;
+        supervise(Subsystem::Terminal, "", &core_rpc, || {
+            runs += 1;
+            if runs < 3 {
+                panic!("run {runs}");
+            }
+        });
+        assert_eq!(runs, 3);
+
+        let crashes = crashes(&core_rpc);
+        assert_eq!(crashes.len(), 2);
+        assert_eq!(crashes[0].message, "run 1");
+        assert!(crashes.iter().all(|crash| crash.restarted));
+        assert!(!crashes[0].backtrace.is_empty());
+    }
+
+    #[test]
+    fn test_supervise_gives_up() {
+        let core_rpc = CoreRpcHandler::new();
+        let mut runs = 0;
+        supervise(Subsystem::FileWatcher, "", &core_rpc, || {
+            runs += 1;
+            panic!("always");
+        });
+        assert_eq!(runs, MAX_RESTARTS + 1);
+
+        let crashes = crashes(&core_rpc);
+        assert_eq!(crashes.len(), MAX_RESTARTS + 1);
+        assert!(!crashes.last().unwrap().restarted);
+    }
+}
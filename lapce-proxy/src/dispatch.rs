@@ -3,7 +3,7 @@
     fs,
     path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
     thread,
@@ -12,37 +12,58 @@
 
 use alacritty_terminal::{event::WindowSize, event_loop::Msg};
 use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use crossbeam_channel::Sender;
 use git2::{build::CheckoutBuilder, DiffOptions, Repository};
 use grep_matcher::Matcher;
-use grep_regex::RegexMatcherBuilder;
-use grep_searcher::{sinks::UTF8, SearcherBuilder};
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{sinks::UTF8, Searcher, SearcherBuilder};
 use indexmap::IndexMap;
 use lapce_rpc::{
+    buffer::SaveOptions,
     core::{CoreNotification, CoreRpcHandler},
+    dap_types::{
+        DataBreakpointInfoResponse, EvaluateResponse, GotoTargetsResponse,
+        ReadMemoryResponse, Scope, SetExpressionResponse, SetVariableResponse,
+        Variable, WriteMemoryResponse,
+    },
     file::FileNodeItem,
+    inline_value::InlineValueContext,
     proxy::{
         ProxyHandler, ProxyNotification, ProxyRequest, ProxyResponse,
         ProxyRpcHandler, SearchMatch,
     },
-    source_control::{DiffInfo, FileDiff},
+    runnable::CARGO_RUNNABLE,
+    source_control::{DiffInfo, FileDiff, GitRemoteFile},
     style::{LineStyle, SemanticStyles},
     terminal::TermId,
     RequestId, RpcError,
 };
 use lapce_xi_rope::Rope;
-use lsp_types::{Position, Range, TextDocumentItem, Url};
+use lsp_types::{
+    MessageType, Position, Range, ShowMessageParams, TextDocumentItem, Url,
+};
 use parking_lot::Mutex;
+use tracing::{debug, error};
 
 use crate::{
+    archive::{is_archive, ArchiveFs},
     buffer::{get_mod_time, load_file, Buffer},
+    coverage::{find_report, load_report},
+    file_index::{is_ignore_file, FileIndex},
+    http::send_http_request,
+    launch::launch_configs,
     plugin::{catalog::PluginCatalog, remove_volt, PluginCatalogRpcHandler},
+    runnable::{build_runnable, run_test, TEST_TIMEOUT},
+    task::cancel_task,
     terminal::Terminal,
+    trash_bin,
     watcher::{FileWatcher, Notify, WatchToken},
 };
 
 const OPEN_FILE_EVENT_TOKEN: WatchToken = WatchToken(1);
 const WORKSPACE_EVENT_TOKEN: WatchToken = WatchToken(2);
+const COVERAGE_EVENT_TOKEN: WatchToken = WatchToken(3);
 
 pub struct Dispatcher {
     workspace: Option<PathBuf>,
@@ -53,6 +74,17 @@ pub struct Dispatcher {
     #[allow(deprecated)]
     terminals: HashMap<TermId, mio::channel::Sender<Msg>>,
     file_watcher: FileWatcher,
+    file_index: FileIndex,
+    /// The archive which is opened as the workspace, which is only read
+    archive: Option<Arc<ArchiveFs>>,
+    /// The coverage report which is watched
+    coverage_report: Arc<Mutex<Option<PathBuf>>>,
+    /// The folder of the report when it's out of the workspace, which is
+    /// watched for it
+    coverage_folder: Option<PathBuf>,
+    /// Goes up when the tests are cancelled, which kills the ones which were
+    /// started before
+    test_run: Arc<AtomicU64>,
     window_id: usize,
     tab_id: usize,
 }
@@ -67,22 +99,42 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 plugin_configurations,
                 window_id,
                 tab_id,
+                resource_limits,
+                ..
             } => {
+                // The limits go first, for the watch of the workspace and the
+                // servers of the plugins
+                crate::limits::set_limits(resource_limits);
                 self.window_id = window_id;
                 self.tab_id = tab_id;
                 self.workspace = workspace;
-                self.file_watcher.notify(FileWatchNotifier::new(
-                    self.workspace.clone(),
+                self.archive = self.open_archive();
+                self.file_index = match self.archive.as_ref() {
+                    Some(archive) => FileIndex::with_files(archive.files()),
+                    None => FileIndex::new(self.workspace.clone()),
+                };
+                self.file_watcher.notify(
+                    FileWatchNotifier::new(
+                        self.workspace.clone(),
+                        self.core_rpc.clone(),
+                        self.proxy_rpc.clone(),
+                        self.file_index.clone(),
+                        self.coverage_report.clone(),
+                    ),
                     self.core_rpc.clone(),
-                    self.proxy_rpc.clone(),
-                ));
-                if let Some(workspace) = self.workspace.as_ref() {
+                );
+                if let Some(workspace) =
+                    self.workspace.as_ref().filter(|_| self.archive.is_none())
+                {
                     self.file_watcher
                         .watch(workspace, true, WORKSPACE_EVENT_TOKEN);
                 }
 
                 let plugin_rpc = self.catalog_rpc.clone();
-                let workspace = self.workspace.clone();
+                // The servers of the plugins get no workspace for an archive,
+                // as its folders aren't on the disk
+                let workspace =
+                    self.workspace.clone().filter(|_| self.archive.is_none());
                 thread::spawn(move || {
                     let mut plugin = PluginCatalog::new(
                         workspace,
@@ -102,8 +154,15 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 }
             }
             OpenPaths { paths } => {
-                self.core_rpc
-                    .notification(CoreNotification::OpenPaths { paths });
+                self.core_rpc.notification(CoreNotification::OpenPaths {
+                    paths,
+                    reuse_window: false,
+                    diff: false,
+                    wait: false,
+                });
+            }
+            RequeryFileIndex {} => {
+                self.file_index.requery();
             }
             OpenFileChanged { path } => {
                 if let Some(buffer) = self.buffers.get(&path) {
@@ -154,6 +213,21 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             UpdatePluginConfigs { configs } => {
                 let _ = self.catalog_rpc.update_plugin_configs(configs);
             }
+            UpdateLogLevels { directives } => {
+                crate::logging::update_log_levels(&directives);
+            }
+            UpdateResourceLimits { limits } => {
+                crate::limits::set_limits(limits);
+            }
+            UnwatchCoverage {} => {
+                self.watch_coverage(None);
+            }
+            CancelTests {} => {
+                self.test_run.fetch_add(1, Ordering::SeqCst);
+            }
+            CancelTask { task_id } => {
+                cancel_task(task_id);
+            }
             NewTerminal {
                 term_id,
                 cwd,
@@ -206,7 +280,7 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 }
             }
             TerminalClose { term_id } => {
-                println!("close terminal {term_id:?}");
+                debug!("close terminal {term_id:?}");
                 if let Some(tx) = self.terminals.remove(&term_id) {
                     #[allow(deprecated)]
                     let _ = tx.send(Msg::Shutdown);
@@ -228,9 +302,21 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
             DapContinue { dap_id, thread_id } => {
                 let _ = self.catalog_rpc.dap_continue(dap_id, thread_id);
             }
+            DapContinueThread { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_continue_thread(dap_id, thread_id);
+            }
             DapPause { dap_id, thread_id } => {
                 let _ = self.catalog_rpc.dap_pause(dap_id, thread_id);
             }
+            DapReverseContinue { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_reverse_continue(dap_id, thread_id);
+            }
+            DapStepBack { dap_id, thread_id } => {
+                let _ = self.catalog_rpc.dap_step_back(dap_id, thread_id);
+            }
+            DapTerminateThreads { dap_id, thread_ids } => {
+                let _ = self.catalog_rpc.dap_terminate_threads(dap_id, thread_ids);
+            }
             DapStop { dap_id } => {
                 let _ = self.catalog_rpc.dap_stop(dap_id);
             }
@@ -252,6 +338,26 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                     self.catalog_rpc
                         .dap_set_breakpoints(dap_id, path, breakpoints);
             }
+            DapSetWatches { expressions } => {
+                let _ = self.catalog_rpc.dap_set_watches(expressions);
+            }
+            DapEvaluateWatches { dap_id, frame_id } => {
+                let _ = self.catalog_rpc.dap_evaluate_watches(dap_id, frame_id);
+            }
+            DapSetExceptionFilters { filters } => {
+                let _ = self.catalog_rpc.dap_set_exception_filters(filters);
+            }
+            DapSetRequestTimeouts { timeouts } => {
+                let _ = self.catalog_rpc.dap_set_request_timeouts(timeouts);
+            }
+            DapSetDataBreakpoints {
+                dap_id,
+                breakpoints,
+            } => {
+                let _ = self
+                    .catalog_rpc
+                    .dap_set_data_breakpoints(dap_id, breakpoints);
+            }
             InstallVolt { volt } => {
                 let catalog_rpc = self.catalog_rpc.clone();
                 let _ = catalog_rpc.install_volt(volt);
@@ -276,7 +382,7 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 if let Some(workspace) = self.workspace.as_ref() {
                     match git_commit(workspace, &message, diffs) {
                         Ok(()) => (),
-                        Err(e) => eprintln!("{e:?}"),
+                        Err(e) => error!("{e:?}"),
                     }
                 }
             }
@@ -284,7 +390,7 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 if let Some(workspace) = self.workspace.as_ref() {
                     match git_checkout(workspace, &reference) {
                         Ok(()) => (),
-                        Err(e) => eprintln!("{e:?}"),
+                        Err(e) => error!("{e:?}"),
                     }
                 }
             }
@@ -295,7 +401,7 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                         files.iter().map(AsRef::as_ref),
                     ) {
                         Ok(()) => (),
-                        Err(e) => eprintln!("{e:?}"),
+                        Err(e) => error!("{e:?}"),
                     }
                 }
             }
@@ -303,7 +409,7 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 if let Some(workspace) = self.workspace.as_ref() {
                     match git_discard_workspace_changes(workspace) {
                         Ok(()) => (),
-                        Err(e) => eprintln!("{e:?}"),
+                        Err(e) => error!("{e:?}"),
                     }
                 }
             }
@@ -311,7 +417,7 @@ fn handle_notification(&mut self, rpc: ProxyNotification) {
                 if let Some(workspace) = self.workspace.as_ref() {
                     match git_init(workspace) {
                         Ok(()) => (),
-                        Err(e) => eprintln!("{e:?}"),
+                        Err(e) => error!("{e:?}"),
                     }
                 }
             }
@@ -322,19 +428,35 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
         use ProxyRequest::*;
         match rpc {
             NewBuffer { buffer_id, path } => {
-                let buffer = Buffer::new(buffer_id, path.clone());
+                // The files of an archive are read out of it, and can't be saved
+                let archive = self.archive.clone().filter(|a| a.contains(&path));
+                let buffer = match archive.as_ref() {
+                    Some(archive) => {
+                        let content = archive
+                            .read_file(&path)
+                            .map(|content| {
+                                String::from_utf8_lossy(&content).to_string()
+                            })
+                            .unwrap_or_default();
+                        Buffer::with_content(buffer_id, path.clone(), content)
+                    }
+                    None => Buffer::new(buffer_id, path.clone()),
+                };
                 let content = buffer.rope.to_string();
+                let read_only = archive.is_some() || is_read_only(&path);
                 self.catalog_rpc.did_open_document(
                     &path,
                     buffer.language_id.to_string(),
                     buffer.rev as i32,
                     content.clone(),
                 );
-                self.file_watcher.watch(&path, false, OPEN_FILE_EVENT_TOKEN);
+                if archive.is_none() {
+                    self.file_watcher.watch(&path, false, OPEN_FILE_EVENT_TOKEN);
+                }
                 self.buffers.insert(path, buffer);
                 self.respond_rpc(
                     id,
-                    Ok(ProxyResponse::NewBufferResponse { content }),
+                    Ok(ProxyResponse::NewBufferResponse { content, read_only }),
                 );
             }
             BufferHead { path } => {
@@ -377,16 +499,43 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     .collect::<Vec<PathBuf>>();
                 let proxy_rpc = self.proxy_rpc.clone();
 
+                if let Some(archive) = self.archive.clone() {
+                    thread::spawn(move || {
+                        let _permit = crate::limits::search_permit();
+                        proxy_rpc.handle_response(
+                            id,
+                            search_in_archive(
+                                our_id,
+                                &WORKER_ID,
+                                &archive,
+                                &pattern,
+                                case_sensitive,
+                                whole_word,
+                                is_regex,
+                            ),
+                        );
+                    });
+                    return;
+                }
+
                 // Perform the search on another thread to avoid blocking the proxy thread
                 thread::spawn(move || {
+                    let _permit = crate::limits::search_permit();
                     proxy_rpc.handle_response(
                         id,
                         search_in_path(
                             our_id,
                             &WORKER_ID,
+                            // The walk stops at the links which lead back to a
+                            // folder it's in
                             workspace
                                 .iter()
-                                .flat_map(|w| ignore::Walk::new(w).flatten())
+                                .flat_map(|w| {
+                                    ignore::WalkBuilder::new(w)
+                                        .follow_links(true)
+                                        .build()
+                                        .flatten()
+                                })
                                 .chain(
                                     buffers.iter().flat_map(|p| {
                                         ignore::Walk::new(p).flatten()
@@ -401,6 +550,76 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     );
                 });
             }
+            TodoSearch { tags, paths } => {
+                static WORKER_ID: AtomicU64 = AtomicU64::new(0);
+                // A search of the whole workspace stops the one before it, while
+                // the ones of the files which changed are left to finish
+                let our_id = if paths.is_none() {
+                    WORKER_ID.fetch_add(1, Ordering::SeqCst) + 1
+                } else {
+                    0
+                };
+
+                let proxy_rpc = self.proxy_rpc.clone();
+                if tags.is_empty() {
+                    proxy_rpc.handle_response(
+                        id,
+                        Ok(ProxyResponse::GlobalSearchResponse {
+                            matches: IndexMap::new(),
+                        }),
+                    );
+                    return;
+                }
+                if let Some(archive) = self.archive.clone() {
+                    thread::spawn(move || {
+                        let _permit = crate::limits::search_permit();
+                        let pattern = todo_pattern(&tags);
+                        let result = search_in_archive(
+                            our_id, &WORKER_ID, &archive, &pattern, true, false,
+                            true,
+                        );
+                        proxy_rpc.handle_response(id, result);
+                    });
+                    return;
+                }
+                // The files of the index are the ones which the ignore rules
+                // leave in
+                self.file_index.files(move |files| {
+                    thread::spawn(move || {
+                        let _permit = crate::limits::search_permit();
+                        let pattern = todo_pattern(&tags);
+                        let result = match paths {
+                            Some(paths) => {
+                                // The paths can be folders which were created
+                                // or renamed
+                                search_in_path(
+                                    0,
+                                    &AtomicU64::new(0),
+                                    files.into_iter().filter(|file| {
+                                        paths
+                                            .iter()
+                                            .any(|path| file.starts_with(path))
+                                    }),
+                                    &pattern,
+                                    true,
+                                    false,
+                                    true,
+                                )
+                            }
+                            None => search_in_path(
+                                our_id,
+                                &WORKER_ID,
+                                files.into_iter(),
+                                &pattern,
+                                true,
+                                false,
+                                true,
+                            ),
+                        };
+                        proxy_rpc.handle_response(id, result);
+                    });
+                });
+            }
             CompletionResolve {
                 plugin_id,
                 completion_item,
@@ -454,10 +673,315 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                             id,
                             Ok(ProxyResponse::GitGetRemoteFileUrl { file_url: s }),
                         ),
-                        Err(e) => eprintln!("{e:?}"),
+                        Err(e) => error!("{e:?}"),
                     }
                 }
             }
+            GetRunnables { path } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_runnables(&path, move |_, result| {
+                    let result = result.map(|runnables| {
+                        let runnables = runnables
+                            .into_iter()
+                            .filter(|runnable| runnable.kind == CARGO_RUNNABLE)
+                            .collect();
+                        ProxyResponse::GetRunnables { runnables }
+                    });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            BuildRunnable { runnable } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                thread::spawn(move || {
+                    let result = build_runnable(&runnable)
+                        .map(|config| ProxyResponse::BuildRunnable { config })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: e.to_string(),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            GetLaunchConfigs { active_file } => {
+                let result = launch_configs(
+                    self.workspace.as_deref(),
+                    active_file.as_deref(),
+                )
+                .map(|configs| ProxyResponse::GetLaunchConfigs { configs })
+                .map_err(|e| RpcError {
+                    code: 0,
+                    message: e.to_string(),
+                });
+                self.respond_rpc(id, result);
+            }
+            LoadCoverage { report, watch } => {
+                let workspace = self.workspace.clone();
+                let result =
+                    find_report(workspace.as_deref(), report).and_then(|report| {
+                        let coverage = load_report(&report, workspace.as_deref())?;
+                        Ok((report, coverage))
+                    });
+                let result = match result {
+                    Ok((report, coverage)) => {
+                        self.watch_coverage(watch.then(|| report.clone()));
+                        Ok(ProxyResponse::LoadCoverage { report, coverage })
+                    }
+                    Err(e) => Err(RpcError {
+                        code: 0,
+                        message: e.to_string(),
+                    }),
+                };
+                self.respond_rpc(id, result);
+            }
+            RunTest { config } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                let test_run = self.test_run.clone();
+                let run = test_run.load(Ordering::SeqCst);
+                thread::spawn(move || {
+                    let cancelled = || test_run.load(Ordering::SeqCst) != run;
+                    let result = run_test(&config, TEST_TIMEOUT, cancelled)
+                        .map(|(success, output)| ProxyResponse::RunTest {
+                            success,
+                            output,
+                        })
+                        .map_err(|e| RpcError {
+                            code: 0,
+                            message: e.to_string(),
+                        });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            SendHttpRequest {
+                path,
+                text,
+                line,
+                environment,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                let workspace = self.workspace.clone();
+                thread::spawn(move || {
+                    let result = send_http_request(
+                        &path,
+                        &text,
+                        line,
+                        environment.as_deref(),
+                        workspace.as_deref(),
+                    )
+                    .map(|response| ProxyResponse::SendHttpRequest { response })
+                    .map_err(|e| RpcError {
+                        code: 0,
+                        message: e.to_string(),
+                    });
+                    proxy_rpc.handle_response(id, result);
+                });
+            }
+            DapScopes { dap_id, frame_id } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_scopes(
+                    dap_id,
+                    frame_id,
+                    move |result: Result<Vec<Scope>, RpcError>| {
+                        let result =
+                            result.map(|scopes| ProxyResponse::DapScopes { scopes });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapVariables { dap_id, reference } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_variables(
+                    dap_id,
+                    reference,
+                    move |result: Result<Vec<Variable>, RpcError>| {
+                        let result = result.map(|variables| {
+                            ProxyResponse::DapVariables { variables }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapEvaluate {
+                dap_id,
+                expression,
+                frame_id,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_evaluate(
+                    dap_id,
+                    expression,
+                    frame_id,
+                    move |result: Result<EvaluateResponse, RpcError>| {
+                        let result = result
+                            .map(|result| ProxyResponse::DapEvaluate { result });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapDataBreakpointInfo {
+                dap_id,
+                reference,
+                name,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_data_breakpoint_info(
+                    dap_id,
+                    reference,
+                    name,
+                    move |result: Result<DataBreakpointInfoResponse, RpcError>| {
+                        let result = result.map(|info| {
+                            ProxyResponse::DapDataBreakpointInfo { info }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapSetVariable {
+                dap_id,
+                reference,
+                name,
+                value,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_set_variable(
+                    dap_id,
+                    reference,
+                    name,
+                    value,
+                    move |result: Result<SetVariableResponse, RpcError>| {
+                        let result = result
+                            .map(|result| ProxyResponse::DapSetVariable { result });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapSetExpression {
+                dap_id,
+                expression,
+                value,
+                frame_id,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_set_expression(
+                    dap_id,
+                    expression,
+                    value,
+                    frame_id,
+                    move |result: Result<SetExpressionResponse, RpcError>| {
+                        let result = result.map(|result| {
+                            ProxyResponse::DapSetExpression { result }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapRestartFrame { dap_id, frame_id } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_restart_frame(
+                    dap_id,
+                    frame_id,
+                    move |result: Result<(), RpcError>| {
+                        let result =
+                            result.map(|_| ProxyResponse::DapRestartFrame {});
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapGotoTargets { dap_id, path, line } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_goto_targets(
+                    dap_id,
+                    path,
+                    line,
+                    move |result: Result<GotoTargetsResponse, RpcError>| {
+                        let result =
+                            result.map(|resp| ProxyResponse::DapGotoTargets {
+                                targets: resp.targets,
+                            });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapGoto {
+                dap_id,
+                thread_id,
+                target_id,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_goto(
+                    dap_id,
+                    thread_id,
+                    target_id,
+                    move |result: Result<(), RpcError>| {
+                        let result = result.map(|_| ProxyResponse::DapGoto {});
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_read_memory(
+                    dap_id,
+                    memory_reference,
+                    offset,
+                    count,
+                    move |result: Result<ReadMemoryResponse, RpcError>| {
+                        let result = result.and_then(|resp| {
+                            let data = match resp.data {
+                                Some(data) => {
+                                    BASE64.decode(data).map_err(|e| RpcError {
+                                        code: 0,
+                                        message: e.to_string(),
+                                    })?
+                                }
+                                None => Vec::new(),
+                            };
+                            Ok(ProxyResponse::DapReadMemory {
+                                address: resp.address,
+                                data,
+                                unreadable_bytes: resp
+                                    .unreadable_bytes
+                                    .unwrap_or_default(),
+                            })
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            DapWriteMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                data,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.dap_write_memory(
+                    dap_id,
+                    memory_reference,
+                    offset,
+                    data,
+                    move |result: Result<WriteMemoryResponse, RpcError>| {
+                        let result =
+                            result.map(|resp| ProxyResponse::DapWriteMemory {
+                                bytes_written: resp.bytes_written,
+                            });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            GitGetRemoteFile { path } => {
+                let result = git_get_remote_file(&path)
+                    .map(|file| ProxyResponse::GitGetRemoteFile { file })
+                    .map_err(|e| RpcError {
+                        code: 0,
+                        message: e.to_string(),
+                    });
+                self.proxy_rpc.handle_response(id, result);
+            }
             GetDefinition {
                 request_id,
                 path,
@@ -512,6 +1036,30 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                         proxy_rpc.handle_response(id, result);
                     });
             }
+            GetInlineValues {
+                path,
+                range,
+                frame_id,
+                stopped_location,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                let context = InlineValueContext {
+                    frame_id: frame_id as i32,
+                    stopped_location,
+                };
+                self.catalog_rpc.get_inline_values(
+                    &path,
+                    range,
+                    context,
+                    move |_, result| {
+                        let result =
+                            result.map(|values| ProxyResponse::GetInlineValues {
+                                values: values.unwrap_or_default(),
+                            });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
             GetSemanticTokens { path } => {
                 let buffer = self.buffers.get(&path).unwrap();
                 let text = buffer.rope.clone();
@@ -567,7 +1115,31 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 let proxy_rpc = self.proxy_rpc.clone();
                 self.catalog_rpc.get_code_actions(
                     &path,
-                    position,
+                    Range {
+                        start: position,
+                        end: position,
+                    },
+                    None,
+                    diagnostics,
+                    move |plugin_id, result| {
+                        let result = result.map(|resp| {
+                            ProxyResponse::GetCodeActionsResponse { plugin_id, resp }
+                        });
+                        proxy_rpc.handle_response(id, result);
+                    },
+                );
+            }
+            GetSourceActions {
+                path,
+                range,
+                kinds,
+                diagnostics,
+            } => {
+                let proxy_rpc = self.proxy_rpc.clone();
+                self.catalog_rpc.get_code_actions(
+                    &path,
+                    range,
+                    Some(kinds),
                     diagnostics,
                     move |plugin_id, result| {
                         let result = result.map(|resp| {
@@ -636,45 +1208,21 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 );
             }
             GetFiles { .. } => {
-                let workspace = self.workspace.clone();
                 let proxy_rpc = self.proxy_rpc.clone();
-                thread::spawn(move || {
-                    let result = if let Some(workspace) = workspace {
-                        let git_folder =
-                            ignore::overrides::OverrideBuilder::new(&workspace)
-                                .add("!.git/")
-                                .map(|git_folder| git_folder.build());
-
-                        let walker = match git_folder {
-                            Ok(Ok(git_folder)) => {
-                                ignore::WalkBuilder::new(&workspace)
-                                    .hidden(false)
-                                    .parents(false)
-                                    .require_git(false)
-                                    .overrides(git_folder)
-                                    .build()
-                            }
-                            _ => ignore::WalkBuilder::new(&workspace)
-                                .parents(false)
-                                .require_git(false)
-                                .build(),
-                        };
-
-                        let mut items = Vec::new();
-                        for path in walker.flatten() {
-                            if let Some(file_type) = path.file_type() {
-                                if file_type.is_file() {
-                                    items.push(path.into_path());
-                                }
-                            }
-                        }
-                        Ok(ProxyResponse::GetFilesResponse { items })
-                    } else {
-                        Ok(ProxyResponse::GetFilesResponse { items: Vec::new() })
-                    };
-                    proxy_rpc.handle_response(id, result);
+                self.file_index.files(move |items| {
+                    proxy_rpc.handle_response(
+                        id,
+                        Ok(ProxyResponse::GetFilesResponse { items }),
+                    );
                 });
             }
+            GetFileIndexStatus {} => {
+                let status = self.file_index.status();
+                self.respond_rpc(
+                    id,
+                    Ok(ProxyResponse::GetFileIndexStatusResponse { status }),
+                );
+            }
             GetOpenFilesContent {} => {
                 let items = self
                     .buffers
@@ -690,6 +1238,34 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 self.proxy_rpc.handle_response(id, Ok(resp));
             }
             ReadDir { path } => {
+                if let Some(archive) =
+                    self.archive.as_ref().filter(|a| a.contains(&path))
+                {
+                    let result = archive
+                        .read_dir(&path)
+                        .map(|entries| {
+                            let mut items = entries
+                                .into_iter()
+                                .map(|(path_buf, is_dir)| FileNodeItem {
+                                    path_buf,
+                                    is_dir,
+                                    link_target: None,
+                                    open: false,
+                                    read: false,
+                                    children: HashMap::new(),
+                                    children_open_count: 0,
+                                })
+                                .collect::<Vec<FileNodeItem>>();
+                            items.sort();
+                            ProxyResponse::ReadDirResponse { items }
+                        })
+                        .ok_or_else(|| RpcError {
+                            code: 0,
+                            message: format!("{} isn't a folder", path.display()),
+                        });
+                    self.proxy_rpc.handle_response(id, result);
+                    return;
+                }
                 let proxy_rpc = self.proxy_rpc.clone();
                 thread::spawn(move || {
                     let result = fs::read_dir(path)
@@ -701,6 +1277,8 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                                         .map(|e| FileNodeItem {
                                             path_buf: e.path(),
                                             is_dir: e.path().is_dir(),
+                                            link_target: fs::read_link(e.path())
+                                                .ok(),
                                             open: false,
                                             read: false,
                                             children: HashMap::new(),
@@ -721,10 +1299,29 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                     proxy_rpc.handle_response(id, result);
                 });
             }
-            Save { rev, path } => {
+            Save { rev, path, options } => {
+                let buffer = self.buffers.get_mut(&path).unwrap();
+                let result = buffer
+                    .save(rev, options)
+                    .map(|_r| {
+                        self.catalog_rpc
+                            .did_save_text_document(&path, buffer.rope.clone());
+                        ProxyResponse::SaveResponse {}
+                    })
+                    .map_err(|e| RpcError {
+                        code: if is_permission_denied(&e) {
+                            RpcError::PERMISSION_DENIED
+                        } else {
+                            0
+                        },
+                        message: e.to_string(),
+                    });
+                self.respond_rpc(id, result);
+            }
+            SaveAsAdmin { rev, path } => {
                 let buffer = self.buffers.get_mut(&path).unwrap();
                 let result = buffer
-                    .save(rev)
+                    .save_as_admin(rev)
                     .map(|_r| {
                         self.catalog_rpc
                             .did_save_text_document(&path, buffer.rope.clone());
@@ -746,7 +1343,7 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 buffer.rope = Rope::from(content);
                 buffer.rev = rev;
                 let result = buffer
-                    .save(rev)
+                    .save(rev, SaveOptions::default())
                     .map(|_| ProxyResponse::Success {})
                     .map_err(|e| RpcError {
                         code: 0,
@@ -782,7 +1379,25 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
                 self.respond_rpc(id, result);
             }
             TrashPath { path } => {
-                let result = trash::delete(path)
+                let result = trash_bin::trash_path(&path)
+                    .map(|trashed| ProxyResponse::TrashPathResponse { trashed })
+                    .map_err(|e| RpcError {
+                        code: 0,
+                        message: e.to_string(),
+                    });
+                self.respond_rpc(id, result);
+            }
+            RestorePath { trashed } => {
+                let result = trash_bin::restore_path(&trashed)
+                    .map(|_| ProxyResponse::Success {})
+                    .map_err(|e| RpcError {
+                        code: 0,
+                        message: e.to_string(),
+                    });
+                self.respond_rpc(id, result);
+            }
+            DeletePath { path } => {
+                let result = trash_bin::delete_path(&path)
                     .map(|_| ProxyResponse::Success {})
                     .map_err(|e| RpcError {
                         code: 0,
@@ -875,6 +1490,28 @@ fn handle_request(&mut self, id: RequestId, rpc: ProxyRequest) {
 }
 
 impl Dispatcher {
+    /// The archive of the workspace when it's one, where an archive which can't
+    /// be read is told about and left as it is.
+    fn open_archive(&self) -> Option<Arc<ArchiveFs>> {
+        let workspace = self.workspace.as_deref().filter(|w| is_archive(w))?;
+        match ArchiveFs::open(workspace) {
+            Ok(archive) => Some(Arc::new(archive)),
+            Err(e) => {
+                self.core_rpc.show_message(
+                    "Archive".to_string(),
+                    ShowMessageParams {
+                        typ: MessageType::ERROR,
+                        message: format!(
+                            "Failed to open {}: {e}",
+                            workspace.display()
+                        ),
+                    },
+                );
+                None
+            }
+        }
+    }
+
     pub fn new(core_rpc: CoreRpcHandler, proxy_rpc: ProxyRpcHandler) -> Self {
         let plugin_rpc =
             PluginCatalogRpcHandler::new(core_rpc.clone(), proxy_rpc.clone());
@@ -889,6 +1526,11 @@ pub fn new(core_rpc: CoreRpcHandler, proxy_rpc: ProxyRpcHandler) -> Self {
             buffers: HashMap::new(),
             terminals: HashMap::new(),
             file_watcher,
+            file_index: FileIndex::new(None),
+            archive: None,
+            coverage_report: Arc::new(Mutex::new(None)),
+            coverage_folder: None,
+            test_run: Arc::new(AtomicU64::new(0)),
             window_id: 1,
             tab_id: 1,
         }
@@ -897,14 +1539,52 @@ pub fn new(core_rpc: CoreRpcHandler, proxy_rpc: ProxyRpcHandler) -> Self {
     fn respond_rpc(&self, id: RequestId, result: Result<ProxyResponse, RpcError>) {
         self.proxy_rpc.handle_response(id, result);
     }
+
+    /// Watch the coverage report instead of the one before, or none. The
+    /// folder of the report is watched, as tools write a new file for it.
+    fn watch_coverage(&mut self, report: Option<PathBuf>) {
+        let report = report.and_then(|report| {
+            let folder = report.parent()?.canonicalize().ok()?;
+            Some(folder.join(report.file_name()?))
+        });
+        if let Some(folder) = self.coverage_folder.take() {
+            self.file_watcher.unwatch(&folder, COVERAGE_EVENT_TOKEN);
+        }
+
+        // The events of a report in the workspace come with the ones of the
+        // workspace
+        let workspace = self.workspace.as_ref().and_then(|w| w.canonicalize().ok());
+        if let Some(report) = report.as_ref() {
+            let in_workspace = workspace
+                .map(|workspace| report.starts_with(workspace))
+                .unwrap_or(false);
+            if let (false, Some(folder)) = (in_workspace, report.parent()) {
+                let report = report.clone();
+                self.file_watcher.watch_filtered(
+                    folder,
+                    false,
+                    COVERAGE_EVENT_TOKEN,
+                    move |path| path == report,
+                );
+                self.coverage_folder = Some(folder.to_path_buf());
+            }
+        }
+        *self.coverage_report.lock() = report;
+    }
 }
 
 struct FileWatchNotifier {
     core_rpc: CoreRpcHandler,
     proxy_rpc: ProxyRpcHandler,
     workspace: Option<PathBuf>,
-    workspace_fs_change_handler: Arc<Mutex<Option<Sender<bool>>>>,
+    file_index: FileIndex,
+    /// Whether the explorer has to be updated for an event, along with the paths
+    /// of it, which are sent together once the events stop for a while
+    workspace_fs_change_handler: Arc<Mutex<Option<Sender<(bool, Vec<PathBuf>)>>>>,
     last_diff: Arc<Mutex<DiffInfo>>,
+    coverage_report: Arc<Mutex<Option<PathBuf>>>,
+    /// Whether the report is about to be loaded again
+    coverage_pending: Arc<AtomicBool>,
 }
 
 impl Notify for FileWatchNotifier {
@@ -918,13 +1598,18 @@ fn new(
         workspace: Option<PathBuf>,
         core_rpc: CoreRpcHandler,
         proxy_rpc: ProxyRpcHandler,
+        file_index: FileIndex,
+        coverage_report: Arc<Mutex<Option<PathBuf>>>,
     ) -> Self {
         let notifier = Self {
             workspace,
             core_rpc,
             proxy_rpc,
+            file_index,
             workspace_fs_change_handler: Arc::new(Mutex::new(None)),
             last_diff: Arc::new(Mutex::new(DiffInfo::default())),
+            coverage_report,
+            coverage_pending: Arc::new(AtomicBool::new(false)),
         };
 
         if let Some(workspace) = notifier.workspace.clone() {
@@ -946,6 +1631,7 @@ fn handle_fs_events(&self, events: Vec<(WatchToken, notify::Event)>) {
             match token {
                 OPEN_FILE_EVENT_TOKEN => self.handle_open_file_fs_event(event),
                 WORKSPACE_EVENT_TOKEN => self.handle_workspace_fs_event(event),
+                COVERAGE_EVENT_TOKEN => self.handle_coverage_fs_event(&event),
                 _ => {}
             }
         }
@@ -960,7 +1646,43 @@ fn handle_open_file_fs_event(&self, event: notify::Event) {
         }
     }
 
+    /// Load the coverage report again once the tool is done writing it, when
+    /// the event is of the report.
+    fn handle_coverage_fs_event(&self, event: &notify::Event) {
+        if !(event.kind.is_create() || event.kind.is_modify()) {
+            return;
+        }
+        let is_report = self
+            .coverage_report
+            .lock()
+            .as_ref()
+            .map(|report| event.paths.iter().any(|path| path == report))
+            .unwrap_or(false);
+        if !is_report || self.coverage_pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let pending = self.coverage_pending.clone();
+        let coverage_report = self.coverage_report.clone();
+        let workspace = self.workspace.clone();
+        let core_rpc = self.core_rpc.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            pending.store(false, Ordering::SeqCst);
+
+            let Some(report) = coverage_report.lock().clone() else {
+                return;
+            };
+            match load_report(&report, workspace.as_deref()) {
+                Ok(coverage) => core_rpc.coverage_changed(coverage),
+                Err(e) => debug!("coverage report {}: {e}", report.display()),
+            }
+        });
+    }
+
     fn handle_workspace_fs_event(&self, event: notify::Event) {
+        self.handle_coverage_fs_event(&event);
+
         let explorer_change = match &event.kind {
             notify::EventKind::Create(_)
             | notify::EventKind::Remove(_)
@@ -968,20 +1690,18 @@ fn handle_workspace_fs_event(&self, event: notify::Event) {
             notify::EventKind::Modify(_) => false,
             _ => return,
         };
+        // The contents of an ignore file change which files are indexed too
+        if explorer_change || event.paths.iter().any(|path| is_ignore_file(path)) {
+            self.file_index.paths_changed(event.paths.clone());
+        }
 
         let mut handler = self.workspace_fs_change_handler.lock();
         if let Some(sender) = handler.as_mut() {
-            if explorer_change {
-                // only send the value if we need to update file explorer as well
-                let _ = sender.send(explorer_change);
-            }
+            let _ = sender.send((explorer_change, event.paths));
             return;
         }
         let (sender, receiver) = crossbeam_channel::unbounded();
-        if explorer_change {
-            // only send the value if we need to update file explorer as well
-            let _ = sender.send(explorer_change);
-        }
+        let _ = sender.send((explorer_change, event.paths));
 
         let local_handler = self.workspace_fs_change_handler.clone();
         let core_rpc = self.core_rpc.clone();
@@ -995,15 +1715,17 @@ fn handle_workspace_fs_event(&self, event: notify::Event) {
             }
 
             let mut explorer_change = false;
-            for e in receiver {
-                if e {
-                    explorer_change = true;
-                    break;
-                }
+            let mut paths = HashSet::new();
+            for (e, event_paths) in receiver {
+                explorer_change |= e;
+                paths.extend(event_paths);
             }
             if explorer_change {
                 core_rpc.workspace_file_change();
             }
+            if !paths.is_empty() {
+                core_rpc.workspace_files_changed(paths.into_iter().collect());
+            }
             if let Some(diff) = git_diff_new(&workspace) {
                 let mut last_diff = last_diff.lock();
                 if diff != *last_diff {
@@ -1025,6 +1747,38 @@ pub struct DiffHunk {
     pub header: String,
 }
 
+/// Whether this process can't write to the file. The permission bits don't tell
+/// on their own, since the file can belong to another user or be on a read-only
+/// mount, so the access the process has is checked.
+fn is_read_only(path: &Path) -> bool {
+    if !path.exists() {
+        return false;
+    }
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+            return false;
+        };
+        unsafe { libc::access(path.as_ptr(), libc::W_OK) != 0 }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        // Opening it to write doesn't change it, without truncating it
+        matches!(
+            fs::OpenOptions::new().write(true).open(path),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied
+        )
+    }
+}
+
+fn is_permission_denied(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<std::io::Error>()
+        .map(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+        .unwrap_or(false)
+}
+
 fn git_init(workspace_path: &Path) -> Result<()> {
     if Repository::discover(workspace_path).is_err() {
         Repository::init(workspace_path)?;
@@ -1265,25 +2019,7 @@ fn git_get_remote_file_url(workspace_path: &Path, file: &Path) -> Result<String>
     let remote = target_remote
         .url()
         .ok_or(anyhow!("Failed to convert remote to str"))?;
-
-    let remote_url = match Url::parse(remote) {
-        Ok(url) => url,
-        Err(_) => {
-            // Parse URL as ssh
-            Url::parse(&format!("ssh://{}", remote.replacen(':', "/", 1)))?
-        }
-    };
-
-    // Get host part
-    let host = remote_url
-        .host_str()
-        .ok_or(anyhow!("Couldn't find remote host"))?;
-    // Get namespace (e.g. organisation/project in case of GitHub, org/team/team/team/../project on GitLab)
-    let namespace = if let Some(stripped) = remote_url.path().strip_suffix(".git") {
-        stripped
-    } else {
-        remote_url.path()
-    };
+    let (host, namespace) = parse_git_remote(remote)?;
 
     let commit = head.peel_to_commit()?.id();
 
@@ -1292,21 +2028,90 @@ fn git_get_remote_file_url(workspace_path: &Path, file: &Path) -> Result<String>
         .to_str()
         .ok_or(anyhow!("Couldn't convert file path to str"))?;
 
-    let url = format!("https://{host}{namespace}/blob/{commit}/{file_path}",);
+    let url = format!("https://{host}/{namespace}/blob/{commit}/{file_path}",);
 
     Ok(url)
 }
 
-fn search_in_path(
-    id: u64,
-    current_id: &AtomicU64,
-    paths: impl Iterator<Item = PathBuf>,
+/// The file in the repository which has it, on the remote of the branch, or on
+/// `origin` when the branch has none or nothing is checked out.
+fn git_get_remote_file(path: &Path) -> Result<GitRemoteFile> {
+    let repo = Repository::discover(path.parent().unwrap_or(path))?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("the repository has no working directory"))?;
+    let head = repo.head()?;
+    let remote_name = head
+        .name()
+        .and_then(|name| repo.branch_upstream_remote(name).ok())
+        .and_then(|name| name.as_str().map(|name| name.to_string()))
+        .unwrap_or_else(|| "origin".to_string());
+    let remote = repo.find_remote(&remote_name)?;
+    let url = remote
+        .url()
+        .ok_or_else(|| anyhow!("the url of {remote_name} isn't utf-8"))?;
+    let (host, repo_path) = parse_git_remote(url)?;
+    let commit = head.peel_to_commit()?.id().to_string();
+
+    // The work directory of git2 is canonical, which the path of the editor
+    // might not be
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let workdir = workdir
+        .canonicalize()
+        .unwrap_or_else(|_| workdir.to_path_buf());
+    let path = path
+        .strip_prefix(&workdir)?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Ok(GitRemoteFile {
+        host,
+        repo: repo_path,
+        commit,
+        path,
+    })
+}
+
+/// The host and the path of the repository of a remote, which is a url like
+/// `https://github.com/lapce/lapce.git` or `ssh://git@host:22/lapce/lapce`, or
+/// the scp form of ssh, `git@github.com:lapce/lapce.git`.
+fn parse_git_remote(remote: &str) -> Result<(String, String)> {
+    // The scp form either isn't a url, or is one with the host as its scheme
+    let url = match Url::parse(remote) {
+        Ok(url) if url.has_host() => url,
+        _ => Url::parse(&format!("ssh://{}", remote.replacen(':', "/", 1)))?,
+    };
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("the remote {remote} has no host"))?
+        .to_string();
+    let path = url.path().trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path).to_string();
+    if path.is_empty() {
+        return Err(anyhow!("the remote {remote} has no repository"));
+    }
+    Ok((host, path))
+}
+
+/// The pattern of the comments which start with one of the tags, like
+/// `// TODO: ...`, `# FIXME ...` or `<!-- HACK -->`.
+fn todo_pattern(tags: &[String]) -> String {
+    let tags = tags
+        .iter()
+        .map(|tag| regex::escape(tag))
+        .collect::<Vec<_>>()
+        .join("|");
+    format!(r"(?://+!?|#+|--|;+|%+|/\*+!?|\*|<!--|\{{-)\s*(?:{tags})\b")
+}
+
+fn search_matcher(
     pattern: &str,
     case_sensitive: bool,
     whole_word: bool,
     is_regex: bool,
-) -> Result<ProxyResponse, RpcError> {
-    let mut matches = IndexMap::new();
+) -> Result<RegexMatcher, RpcError> {
     let mut matcher = RegexMatcherBuilder::new();
     let matcher = matcher.case_insensitive(!case_sensitive).word(whole_word);
     let matcher = if is_regex {
@@ -1314,10 +2119,77 @@ fn search_in_path(
     } else {
         matcher.build_literals(&[&regex::escape(pattern)])
     };
-    let matcher = matcher.map_err(|_| RpcError {
+    matcher.map_err(|_| RpcError {
         code: 0,
         message: "can't build matcher".to_string(),
-    })?;
+    })
+}
+
+/// The matches of the file, whose content is read from its path unless it's
+/// given, like the one of a file of an archive.
+fn search_file(
+    searcher: &mut Searcher,
+    matcher: &RegexMatcher,
+    id: u64,
+    current_id: &AtomicU64,
+    path: &Path,
+    content: Option<&[u8]>,
+) -> Vec<SearchMatch> {
+    let mut line_matches = Vec::new();
+    let sink = UTF8(|lnum, line| {
+        if current_id.load(Ordering::SeqCst) != id {
+            return Ok(false);
+        }
+
+        let mymatch = matcher.find(line.as_bytes())?.unwrap();
+        let line = if line.len() > 200 {
+            // Shorten the line to avoid sending over absurdly long-lines
+            // (such as in minified javascript)
+            // Note that the start/end are column based, not absolute from the
+            // start of the file.
+            let left_keep = line[..mymatch.start()]
+                .chars()
+                .rev()
+                .take(100)
+                .map(|c| c.len_utf8())
+                .sum::<usize>();
+            let right_keep = line[mymatch.end()..]
+                .chars()
+                .take(100)
+                .map(|c| c.len_utf8())
+                .sum::<usize>();
+            let display_range =
+                mymatch.start() - left_keep..mymatch.end() + right_keep;
+            line[display_range].to_string()
+        } else {
+            line.to_string()
+        };
+        line_matches.push(SearchMatch {
+            line: lnum as usize,
+            start: mymatch.start(),
+            end: mymatch.end(),
+            line_content: line,
+        });
+        Ok(true)
+    });
+    let _ = match content {
+        Some(content) => searcher.search_slice(matcher, content, sink),
+        None => searcher.search_path(matcher, path, sink),
+    };
+    line_matches
+}
+
+fn search_in_path(
+    id: u64,
+    current_id: &AtomicU64,
+    paths: impl Iterator<Item = PathBuf>,
+    pattern: &str,
+    case_sensitive: bool,
+    whole_word: bool,
+    is_regex: bool,
+) -> Result<ProxyResponse, RpcError> {
+    let mut matches = IndexMap::new();
+    let matcher = search_matcher(pattern, case_sensitive, whole_word, is_regex)?;
     let mut searcher = SearcherBuilder::new().build();
 
     for path in paths {
@@ -1329,47 +2201,8 @@ fn search_in_path(
         }
 
         if path.is_file() {
-            let mut line_matches = Vec::new();
-            let _ = searcher.search_path(
-                &matcher,
-                path.clone(),
-                UTF8(|lnum, line| {
-                    if current_id.load(Ordering::SeqCst) != id {
-                        return Ok(false);
-                    }
-
-                    let mymatch = matcher.find(line.as_bytes())?.unwrap();
-                    let line = if line.len() > 200 {
-                        // Shorten the line to avoid sending over absurdly long-lines
-                        // (such as in minified javascript)
-                        // Note that the start/end are column based, not absolute from the
-                        // start of the file.
-                        let left_keep = line[..mymatch.start()]
-                            .chars()
-                            .rev()
-                            .take(100)
-                            .map(|c| c.len_utf8())
-                            .sum::<usize>();
-                        let right_keep = line[mymatch.end()..]
-                            .chars()
-                            .take(100)
-                            .map(|c| c.len_utf8())
-                            .sum::<usize>();
-                        let display_range =
-                            mymatch.start() - left_keep..mymatch.end() + right_keep;
-                        line[display_range].to_string()
-                    } else {
-                        line.to_string()
-                    };
-                    line_matches.push(SearchMatch {
-                        line: lnum as usize,
-                        start: mymatch.start(),
-                        end: mymatch.end(),
-                        line_content: line,
-                    });
-                    Ok(true)
-                }),
-            );
+            let line_matches =
+                search_file(&mut searcher, &matcher, id, current_id, &path, None);
             if !line_matches.is_empty() {
                 matches.insert(path.clone(), line_matches);
             }
@@ -1378,3 +2211,126 @@ fn search_in_path(
 
     Ok(ProxyResponse::GlobalSearchResponse { matches })
 }
+
+/// Search the files of the archive, which are read in one pass over it.
+fn search_in_archive(
+    id: u64,
+    current_id: &AtomicU64,
+    archive: &ArchiveFs,
+    pattern: &str,
+    case_sensitive: bool,
+    whole_word: bool,
+    is_regex: bool,
+) -> Result<ProxyResponse, RpcError> {
+    let mut matches = IndexMap::new();
+    let matcher = search_matcher(pattern, case_sensitive, whole_word, is_regex)?;
+    let mut searcher = SearcherBuilder::new().build();
+
+    let mut expired = false;
+    archive
+        .for_each_file(|path, content| {
+            if current_id.load(Ordering::SeqCst) != id {
+                expired = true;
+                return false;
+            }
+            let line_matches = search_file(
+                &mut searcher,
+                &matcher,
+                id,
+                current_id,
+                &path,
+                Some(content),
+            );
+            if !line_matches.is_empty() {
+                matches.insert(path, line_matches);
+            }
+            true
+        })
+        .map_err(|e| RpcError {
+            code: 0,
+            message: e.to_string(),
+        })?;
+    if expired {
+        return Err(RpcError {
+            code: 0,
+            message: "expired search job".to_string(),
+        });
+    }
+
+    Ok(ProxyResponse::GlobalSearchResponse { matches })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_read_only() {
+        let dir = std::env::temp_dir()
+            .join(format!("lapce-read-only-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("file.txt");
+        fs::write(&file, "text").unwrap();
+
+        assert!(!is_read_only(&file));
+        assert!(!is_read_only(&dir.join("missing.txt")));
+
+        let mut permissions = fs::metadata(&file).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&file, permissions.clone()).unwrap();
+        // Root can write to it all the same
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        assert_eq!(is_read_only(&file), unsafe { libc::geteuid() } != 0);
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        assert!(is_read_only(&file));
+
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&file, permissions).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_git_remote() {
+        let lapce = ("github.com".to_string(), "lapce/lapce".to_string());
+        for remote in [
+            "https://github.com/lapce/lapce.git",
+            "https://github.com/lapce/lapce/",
+            "git@github.com:lapce/lapce.git",
+            "github.com:lapce/lapce",
+            "ssh://git@github.com:22/lapce/lapce.git",
+        ] {
+            assert_eq!(parse_git_remote(remote).unwrap(), lapce, "{remote}");
+        }
+        assert_eq!(
+            parse_git_remote("git@gitlab.example.com:group/sub/project.git")
+                .unwrap(),
+            (
+                "gitlab.example.com".to_string(),
+                "group/sub/project".to_string()
+            ),
+        );
+        assert!(parse_git_remote("https://github.com/").is_err());
+    }
+
+    #[test]
+    fn test_todo_pattern() {
+        let pattern = todo_pattern(&["TODO".to_string(), "FIXME".to_string()]);
+        let regex = regex::Regex::new(&pattern).unwrap();
+        for line in [
+            "    // TODO: remove this",
+            "# FIXME",
+            "/* TODO(someone) later */",
+            " * FIXME: the rest",
+            "-- TODO",
+            "<!-- TODO: docs -->",
+            "let a = 1; //TODO",
+            "/// TODO: document it",
+        ] {
+            assert!(regex.is_match(line), "{line}");
+        }
+        for line in ["let todo = \"TODO\";", "// TODOS", "// HACK: not a tag"] {
+            assert!(!regex.is_match(line), "{line}");
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! The files of the workspace, which are walked once in the background and then
+//! kept up to date from the events of the file watcher, so that the file palette
+//! doesn't have to walk the workspace each time it's opened.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use ignore::WalkBuilder;
+use lapce_rpc::file::FileIndexStatus;
+use parking_lot::Mutex;
+
+/// How long the index waits for more changes after one, so that a checkout or a
+/// build which touches many files is handled at once
+const CHANGE_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// How many files are walked between the updates of the status
+const STATUS_INTERVAL: usize = 1000;
+
+type FilesCallback = Box<dyn FnOnce(Vec<PathBuf>) + Send>;
+
+#[derive(Clone)]
+pub struct FileIndex {
+    state: Arc<Mutex<FileIndexState>>,
+    tx: Sender<FileIndexEvent>,
+}
+
+#[derive(Default)]
+struct FileIndexState {
+    files: BTreeSet<PathBuf>,
+    /// How many files the walk of the workspace is at, which is `None` when
+    /// there's no walk running
+    walked: Option<usize>,
+    /// The ones which asked for the files while the workspace was walked
+    waiting: Vec<FilesCallback>,
+}
+
+enum FileIndexEvent {
+    Changed(Vec<PathBuf>),
+    Requery,
+}
+
+impl FileIndex {
+    pub fn new(workspace: Option<PathBuf>) -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let index = Self {
+            state: Arc::new(Mutex::new(FileIndexState::default())),
+            tx,
+        };
+        if let Some(workspace) = workspace {
+            index.state.lock().walked = Some(0);
+            let state = index.state.clone();
+            thread::spawn(move || {
+                index_mainloop(&workspace, &state, rx);
+            });
+            index.requery();
+        }
+        index
+    }
+
+    /// The index of files which don't change, like the ones of an archive.
+    pub fn with_files(files: Vec<PathBuf>) -> Self {
+        let (tx, _) = crossbeam_channel::unbounded();
+        let state = FileIndexState {
+            files: files.into_iter().collect(),
+            ..Default::default()
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            tx,
+        }
+    }
+
+    pub fn status(&self) -> FileIndexStatus {
+        let state = self.state.lock();
+        match state.walked {
+            Some(files) => FileIndexStatus::Indexing { files },
+            None => FileIndexStatus::Ready {
+                files: state.files.len(),
+            },
+        }
+    }
+
+    /// Call `f` with the files of the workspace, right away when the index is
+    /// ready, and else once the walk of the workspace is done.
+    pub fn files(&self, f: impl FnOnce(Vec<PathBuf>) + Send + 'static) {
+        let mut state = self.state.lock();
+        if state.walked.is_some() {
+            state.waiting.push(Box::new(f));
+            return;
+        }
+        let files = state.files.iter().cloned().collect();
+        drop(state);
+        f(files);
+    }
+
+    /// The paths which were created, removed or renamed, which are looked at
+    /// again along with everything under them.
+    pub fn paths_changed(&self, paths: Vec<PathBuf>) {
+        let _ = self.tx.send(FileIndexEvent::Changed(paths));
+    }
+
+    /// Walk the whole workspace again.
+    pub fn requery(&self) {
+        let _ = self.tx.send(FileIndexEvent::Requery);
+    }
+}
+
+fn index_mainloop(
+    workspace: &Path,
+    state: &Mutex<FileIndexState>,
+    rx: Receiver<FileIndexEvent>,
+) {
+    while let Ok(event) = rx.recv() {
+        if !matches!(event, FileIndexEvent::Requery) {
+            thread::sleep(CHANGE_DEBOUNCE);
+        }
+        let mut requery = false;
+        let mut changed = HashSet::new();
+        for event in std::iter::once(event).chain(rx.try_iter()) {
+            match event {
+                FileIndexEvent::Changed(paths) => changed.extend(paths),
+                FileIndexEvent::Requery => requery = true,
+            }
+        }
+
+        // The files which are left out can't be told apart from the ones which
+        // were before when the ignore rules change
+        if requery || changed.iter().any(|path| is_ignore_file(path)) {
+            walk_workspace(workspace, state);
+        } else {
+            update_paths(workspace, state, changed);
+        }
+    }
+}
+
+fn walk_workspace(workspace: &Path, state: &Mutex<FileIndexState>) {
+    state.lock().walked.get_or_insert(0);
+
+    let mut files = BTreeSet::new();
+    for entry in walk_builder(workspace).build().flatten() {
+        if entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            files.insert(entry.into_path());
+            if files.len() % STATUS_INTERVAL == 0 {
+                state.lock().walked = Some(files.len());
+            }
+        }
+    }
+
+    let (files, waiting) = {
+        let mut state = state.lock();
+        state.files = files;
+        state.walked = None;
+        (
+            state.files.iter().cloned().collect::<Vec<_>>(),
+            std::mem::take(&mut state.waiting),
+        )
+    };
+    for f in waiting {
+        f(files.clone());
+    }
+}
+
+/// Look at the changed paths again, where a path is left out when the walk of
+/// the workspace leaves it out, and the files under a path which is still there
+/// are walked again.
+fn update_paths(
+    workspace: &Path,
+    state: &Mutex<FileIndexState>,
+    paths: HashSet<PathBuf>,
+) {
+    let mut added = Vec::new();
+    for path in paths.iter() {
+        if !path.starts_with(workspace) || path == workspace {
+            continue;
+        }
+        // The walk goes down to the path through the folders above it, so that
+        // the same ignore rules apply to it as in the walk of the workspace
+        let target = path.clone();
+        let walk = walk_builder(workspace)
+            .filter_entry(move |entry| {
+                target.starts_with(entry.path()) || entry.path().starts_with(&target)
+            })
+            .build();
+        for entry in walk.flatten() {
+            if entry.file_type().map(|t| t.is_file()).unwrap_or(false)
+                && entry.path().starts_with(path)
+            {
+                added.push(entry.into_path());
+            }
+        }
+    }
+
+    let mut state = state.lock();
+    for path in paths.iter() {
+        let removed: Vec<PathBuf> = state
+            .files
+            .range(path.clone()..)
+            .take_while(|file| file.starts_with(path))
+            .cloned()
+            .collect();
+        for file in removed {
+            state.files.remove(&file);
+        }
+    }
+    state.files.extend(added);
+}
+
+/// A walk of the files of the workspace which leaves out the ones that its
+/// ignore files do, and the `.git` folder. The symlinks are followed, except for
+/// the ones to a folder which the walk is in already, which would never end.
+fn walk_builder(workspace: &Path) -> WalkBuilder {
+    let git_folder = ignore::overrides::OverrideBuilder::new(workspace)
+        .add("!.git/")
+        .map(|git_folder| git_folder.build());
+
+    let mut builder = WalkBuilder::new(workspace);
+    // The ignore files above the workspace are left out
+    builder.parents(false).require_git(false).follow_links(true);
+    if let Ok(Ok(git_folder)) = git_folder {
+        builder.hidden(false).overrides(git_folder);
+    }
+    builder
+}
+
+/// Whether the path is a file with ignore rules, which change what's indexed
+pub fn is_ignore_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name == ".gitignore" || name == ".ignore")
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(state: &Mutex<FileIndexState>, workspace: &Path) -> Vec<String> {
+        state
+            .lock()
+            .files
+            .iter()
+            .map(|file| {
+                file.strip_prefix(workspace)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_update_paths() {
+        let root = std::env::temp_dir()
+            .join(format!("lapce-file-index-test-{}", std::process::id()));
+        let workspace = root.join("workspace");
+        let _ = std::fs::remove_dir_all(&root);
+        for dir in ["src", "target", ".git"] {
+            std::fs::create_dir_all(workspace.join(dir)).unwrap();
+        }
+        // The ignore file above the workspace isn't used by either walk
+        std::fs::write(root.join(".gitignore"), "*.rs\n").unwrap();
+        std::fs::write(workspace.join(".gitignore"), "target/\n*.log\n").unwrap();
+        for file in ["a.txt", "b.log", "src/main.rs", "target/c", ".git/config"] {
+            std::fs::write(workspace.join(file), "").unwrap();
+        }
+
+        let state = Mutex::new(FileIndexState::default());
+        walk_workspace(&workspace, &state);
+        let walked = files(&state, &workspace);
+        assert_eq!(walked, [".gitignore", "a.txt", "src/main.rs"]);
+        assert_eq!(state.lock().walked, None);
+
+        // The changed paths are left out or not as the walk does
+        for file in ["src/lib.rs", "d.log", "target/e"] {
+            std::fs::write(workspace.join(file), "").unwrap();
+        }
+        std::fs::remove_file(workspace.join("a.txt")).unwrap();
+        let changed = ["src/lib.rs", "d.log", "target/e", "a.txt"]
+            .into_iter()
+            .map(|file| workspace.join(file))
+            .collect();
+        update_paths(&workspace, &state, changed);
+        assert_eq!(
+            files(&state, &workspace),
+            [".gitignore", "src/lib.rs", "src/main.rs"]
+        );
+
+        // A renamed folder is walked again, and the files under its old path go
+        std::fs::rename(workspace.join("src"), workspace.join("lib")).unwrap();
+        let changed = [workspace.join("src"), workspace.join("lib")]
+            .into_iter()
+            .collect();
+        update_paths(&workspace, &state, changed);
+        assert_eq!(
+            files(&state, &workspace),
+            [".gitignore", "lib/lib.rs", "lib/main.rs"]
+        );
+
+        walk_workspace(&workspace, &state);
+        assert_eq!(
+            files(&state, &workspace),
+            [".gitignore", "lib/lib.rs", "lib/main.rs"]
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,649 @@
+//! The proxy without the app, which opens files in a workspace and prints their
+//! diagnostics or formats them with their language servers, or runs a debug
+//! session, for scripts and the tests of the features of the editor.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use crossbeam_channel::RecvTimeoutError;
+use lapce_core::{
+    buffer::{rope_text::RopeText, Buffer},
+    editor::EditType,
+    selection::Selection,
+};
+use lapce_rpc::{
+    buffer::{BufferId, SaveOptions},
+    core::{CoreNotification, CoreRpc, CoreRpcHandler},
+    dap_types::{DapId, RunDebugConfig, SourceBreakpoint},
+    file::PathObject,
+    proxy::{ProxyRequest, ProxyResponse, ProxyRpcHandler},
+    terminal::TermId,
+};
+use lapce_xi_rope::Rope;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use crate::{dispatch::Dispatcher, logging};
+
+/// How long the diagnostics have to stay the same, once each file has them, for
+/// the language servers to be taken as done with the files
+const SETTLE: Duration = Duration::from_secs(2);
+
+/// What a headless proxy does with the files which it's given before it exits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeadlessTask {
+    /// Print the diagnostics of the files
+    Diagnostics,
+    /// Format the files and save them
+    Format,
+    /// Run the launch config with the name in a debug session, with breakpoints
+    /// at the lines of the files which are given as `path:line`
+    Debug { config: String },
+}
+
+/// Open the files in the workspace and do the task with them, waiting for the
+/// language servers or the debug session for up to `timeout`. Returns the exit
+/// code, which isn't 0 when a file has errors or couldn't be formatted, and is
+/// the one of the debuggee for a debug session.
+pub fn run(
+    task: HeadlessTask,
+    workspace: PathBuf,
+    paths: Vec<PathObject>,
+    timeout: Duration,
+) -> i32 {
+    let core_rpc = CoreRpcHandler::new();
+    logging::init(core_rpc.clone());
+    let proxy_rpc = ProxyRpcHandler::new();
+    let dispatcher = {
+        let core_rpc = core_rpc.clone();
+        let proxy_rpc = proxy_rpc.clone();
+        thread::spawn(move || {
+            let mut dispatcher = Dispatcher::new(core_rpc, proxy_rpc);
+            let proxy_rpc = dispatcher.proxy_rpc.clone();
+            proxy_rpc.mainloop(&mut dispatcher);
+        })
+    };
+    proxy_rpc.initialize(
+        Some(workspace),
+        Vec::new(),
+        HashMap::new(),
+        1,
+        1,
+        Default::default(),
+    );
+
+    if let HeadlessTask::Debug { config } = &task {
+        let code =
+            match debug(&core_rpc, &proxy_rpc, config, breakpoints(&paths), timeout)
+            {
+                Ok(code) => code,
+                Err(e) => {
+                    eprintln!("{config}: {e}");
+                    1
+                }
+            };
+        proxy_rpc.shutdown();
+        let _ = dispatcher.join();
+        return code;
+    }
+
+    let paths = paths.into_iter().map(|p| p.path).collect::<Vec<_>>();
+    let mut code = 0;
+    let mut files = Vec::new();
+    for path in paths {
+        match open_file(&proxy_rpc, &path, timeout) {
+            Ok(content) => files.push((path, content)),
+            Err(e) => {
+                eprintln!("{}: {e}", path.display());
+                code = 1;
+            }
+        }
+    }
+
+    // The servers are started along with the proxy, and the diagnostics of a
+    // file are how it's known that its server got to it
+    let paths = files
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    let diagnostics = wait_for_diagnostics(&core_rpc, &paths, timeout);
+
+    let failed = match task {
+        HeadlessTask::Diagnostics => print_diagnostics(&paths, &diagnostics),
+        HeadlessTask::Debug { .. } => false,
+        HeadlessTask::Format => {
+            let mut failed = false;
+            for (path, content) in &files {
+                if let Err(e) = format_file(&proxy_rpc, path, content, timeout) {
+                    eprintln!("{}: {e}", path.display());
+                    failed = true;
+                }
+            }
+            failed
+        }
+    };
+    if failed {
+        code = 1;
+    }
+
+    proxy_rpc.shutdown();
+    let _ = dispatcher.join();
+    code
+}
+
+/// Send a request to the dispatcher and wait for its response.
+fn request(
+    proxy_rpc: &ProxyRpcHandler,
+    request: ProxyRequest,
+    timeout: Duration,
+) -> Result<ProxyResponse> {
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    proxy_rpc.request_async(request, move |result| {
+        let _ = tx.send(result);
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result.map_err(|e| anyhow!(e.message)),
+        Err(_) => Err(anyhow!("no response in time")),
+    }
+}
+
+/// Open the file as the app does, which opens it in its language servers, and
+/// return its content.
+fn open_file(
+    proxy_rpc: &ProxyRpcHandler,
+    path: &Path,
+    timeout: Duration,
+) -> Result<String> {
+    let resp = request(
+        proxy_rpc,
+        ProxyRequest::NewBuffer {
+            buffer_id: BufferId::next(),
+            path: path.to_path_buf(),
+        },
+        timeout,
+    )?;
+    match resp {
+        ProxyResponse::NewBufferResponse { content, .. } => Ok(content),
+        _ => Err(anyhow!("unexpected response")),
+    }
+}
+
+/// The diagnostics of the files, once all of them have some and they stopped
+/// changing, or what there is of them at the timeout. The log of the proxy and
+/// the messages of the servers are printed to stderr in the meantime.
+fn wait_for_diagnostics(
+    core_rpc: &CoreRpcHandler,
+    paths: &[PathBuf],
+    timeout: Duration,
+) -> HashMap<PathBuf, Vec<Diagnostic>> {
+    let deadline = Instant::now() + timeout;
+    let mut diagnostics = HashMap::new();
+    let mut changed = Instant::now();
+    loop {
+        let now = Instant::now();
+        let until = if paths.iter().all(|path| diagnostics.contains_key(path)) {
+            (changed + SETTLE).min(deadline)
+        } else {
+            deadline
+        };
+        if now >= until {
+            break;
+        }
+
+        match core_rpc.rx().recv_timeout(until - now) {
+            Ok(CoreRpc::Notification(notification)) => match *notification {
+                CoreNotification::PublishDiagnostics {
+                    diagnostics: params,
+                } => {
+                    if let Ok(path) = params.uri.to_file_path() {
+                        diagnostics.insert(path, params.diagnostics);
+                        changed = Instant::now();
+                    }
+                }
+                notification => print_message(&notification),
+            },
+            Ok(CoreRpc::Request(..)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(CoreRpc::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    diagnostics
+}
+
+/// Print the log of the proxy and the messages of the servers to stderr.
+fn print_message(notification: &CoreNotification) {
+    match notification {
+        CoreNotification::Log {
+            level,
+            message,
+            target,
+        } => {
+            eprintln!("{level} {target}: {message}");
+        }
+        CoreNotification::ShowMessage { title, message } => {
+            eprintln!("{title}: {}", message.message);
+        }
+        _ => {}
+    }
+}
+
+/// Print the diagnostics as `path:line:column: severity: message`, and return
+/// whether there are errors.
+fn print_diagnostics(
+    paths: &[PathBuf],
+    diagnostics: &HashMap<PathBuf, Vec<Diagnostic>>,
+) -> bool {
+    let mut errors = false;
+    for path in paths {
+        let Some(diagnostics) = diagnostics.get(path) else {
+            eprintln!("{}: no diagnostics in time", path.display());
+            continue;
+        };
+        for diagnostic in diagnostics {
+            let severity = match diagnostic.severity {
+                Some(DiagnosticSeverity::WARNING) => "warning",
+                Some(DiagnosticSeverity::INFORMATION) => "info",
+                Some(DiagnosticSeverity::HINT) => "hint",
+                _ => {
+                    errors = true;
+                    "error"
+                }
+            };
+            let start = diagnostic.range.start;
+            println!(
+                "{}:{}:{}: {severity}: {}",
+                path.display(),
+                start.line + 1,
+                start.character + 1,
+                diagnostic.message
+            );
+        }
+    }
+    errors
+}
+
+/// Format the file with its language server and save it through the proxy, as
+/// the app does when the document is formatted.
+fn format_file(
+    proxy_rpc: &ProxyRpcHandler,
+    path: &Path,
+    content: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let resp = request(
+        proxy_rpc,
+        ProxyRequest::GetDocumentFormatting {
+            path: path.to_path_buf(),
+        },
+        timeout,
+    )?;
+    let ProxyResponse::GetDocumentFormatting { edits } = resp else {
+        return Err(anyhow!("unexpected response"));
+    };
+    if edits.is_empty() {
+        return Ok(());
+    }
+
+    let mut buffer = Buffer::new("");
+    buffer.init_content(Rope::from(content));
+    let edits = edits
+        .iter()
+        .map(|edit| {
+            let selection = Selection::region(
+                buffer.offset_of_position(&edit.range.start),
+                buffer.offset_of_position(&edit.range.end),
+            );
+            (selection, edit.new_text.as_str())
+        })
+        .collect::<Vec<_>>();
+    let (delta, _, _) = buffer.edit(&edits, EditType::Other);
+    proxy_rpc.update(path.to_path_buf(), delta, buffer.rev());
+
+    request(
+        proxy_rpc,
+        ProxyRequest::Save {
+            rev: buffer.rev(),
+            path: path.to_path_buf(),
+            options: SaveOptions::default(),
+        },
+        timeout,
+    )?;
+    println!("formatted {}", path.display());
+    Ok(())
+}
+
+/// The breakpoints at the lines of the paths which are given as `path:line`.
+fn breakpoints(paths: &[PathObject]) -> HashMap<PathBuf, Vec<SourceBreakpoint>> {
+    let mut breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>> = HashMap::new();
+    for path in paths {
+        if let Some(linecol) = path.linecol {
+            breakpoints.entry(path.path.clone()).or_default().push(
+                SourceBreakpoint {
+                    line: linecol.line,
+                    column: None,
+                    condition: None,
+                    hit_condition: None,
+                    log_message: None,
+                },
+            );
+        }
+    }
+    breakpoints
+}
+
+/// Start a debug session with the launch config, as the app does, and follow it
+/// until the debuggee ends.
+fn debug(
+    core_rpc: &CoreRpcHandler,
+    proxy_rpc: &ProxyRpcHandler,
+    name: &str,
+    breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+    timeout: Duration,
+) -> Result<i32> {
+    let deadline = Instant::now() + timeout;
+    let resp = request(
+        proxy_rpc,
+        ProxyRequest::GetLaunchConfigs { active_file: None },
+        timeout,
+    )?;
+    let ProxyResponse::GetLaunchConfigs { configs } = resp else {
+        return Err(anyhow!("unexpected response"));
+    };
+    let config = configs
+        .into_iter()
+        .find(|config| config.name == name)
+        .ok_or_else(|| anyhow!("there's no launch config with the name"))?;
+    let dap_id = config.dap_id;
+    let pre_launch_task = config.pre_launch_task.clone();
+    proxy_rpc.dap_start(config, breakpoints);
+    follow_session(
+        core_rpc,
+        proxy_rpc,
+        dap_id,
+        pre_launch_task.as_deref(),
+        deadline,
+    )
+}
+
+/// Print the output of the debug session, and where it stops, which it's then
+/// continued from as there's no one to look at it. The program which the
+/// debugger asks to be run in a terminal is run by the proxy. Returns the exit
+/// code of the debuggee once it ends, and stops the session when it doesn't end
+/// by the deadline.
+fn follow_session(
+    core_rpc: &CoreRpcHandler,
+    proxy_rpc: &ProxyRpcHandler,
+    dap_id: DapId,
+    pre_launch_task: Option<&str>,
+    deadline: Instant,
+) -> Result<i32> {
+    let mut debuggees: Vec<Child> = Vec::new();
+    let result = loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break Err(anyhow!("the debug session didn't end in time"));
+        }
+        match core_rpc.rx().recv_timeout(deadline - now) {
+            Ok(CoreRpc::Notification(notification)) => match *notification {
+                CoreNotification::RunInTerminal { config } => {
+                    let process_id = match run_debuggee(&config) {
+                        Ok(child) => {
+                            let process_id = child.id();
+                            debuggees.push(child);
+                            Some(process_id)
+                        }
+                        Err(e) => {
+                            eprintln!("failed to run the debuggee: {e}");
+                            None
+                        }
+                    };
+                    proxy_rpc.dap_process_id(
+                        config.dap_id,
+                        process_id,
+                        TermId::next(),
+                    );
+                }
+                CoreNotification::DapStopped {
+                    dap_id,
+                    stopped,
+                    stack_frames,
+                } => {
+                    let thread_id = stopped.thread_id.unwrap_or_default();
+                    let location = stack_frames
+                        .get(&thread_id)
+                        .and_then(|frames| frames.first())
+                        .and_then(|frame| {
+                            let path = frame.source.as_ref()?.path.as_ref()?;
+                            Some(format!(
+                                "{}:{}:{}",
+                                path.display(),
+                                frame.line,
+                                frame.column
+                            ))
+                        })
+                        .unwrap_or_else(|| "an unknown place".to_string());
+                    println!("stopped ({}) at {location}", stopped.reason);
+                    proxy_rpc.dap_continue(dap_id, thread_id);
+                }
+                CoreNotification::DapOutput { output, .. } => {
+                    if output.category.as_deref() == Some("stderr") {
+                        eprint!("{}", output.output);
+                    } else {
+                        print!("{}", output.output);
+                    }
+                }
+                CoreNotification::DapTerminated {
+                    dap_id: terminated,
+                    exit_code,
+                } if terminated == dap_id => {
+                    break Ok(exit_code.map(|code| code as i32).unwrap_or(0));
+                }
+                CoreNotification::DapTaskFailed { task, message, .. } => {
+                    eprintln!("the task {task} failed: {message}");
+                    // The session isn't started when the task before it fails
+                    if pre_launch_task == Some(task.as_str()) {
+                        break Err(anyhow!("the debug session wasn't started"));
+                    }
+                }
+                notification => print_message(&notification),
+            },
+            Ok(CoreRpc::Request(..)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(CoreRpc::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                break Err(anyhow!("the proxy stopped"));
+            }
+        }
+    };
+    if result.is_err() {
+        proxy_rpc.dap_stop(dap_id);
+    }
+    // The debuggees ended along with the session, unless it didn't end
+    for mut debuggee in debuggees {
+        if result.is_err() {
+            let _ = debuggee.kill();
+        }
+        let _ = debuggee.wait();
+    }
+    result
+}
+
+/// Run the command which the debugger asked to be run in a terminal with the
+/// shell, as the terminal of the app does.
+fn run_debuggee(config: &RunDebugConfig) -> Result<Child> {
+    let command = config
+        .debug_command
+        .as_deref()
+        .ok_or_else(|| anyhow!("there's no command"))?;
+    #[cfg(target_os = "windows")]
+    let mut process = {
+        let mut process = Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut process = {
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    };
+    if let Some(cwd) = config.cwd.as_ref() {
+        process.current_dir(cwd);
+    }
+    if let Some(env) = config.env.as_ref() {
+        process.envs(env);
+    }
+    Ok(process.spawn()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::{
+        dap_types::ThreadId,
+        file::LineCol,
+        proxy::{ProxyNotification, ProxyRpc},
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_breakpoints() {
+        let paths = vec![
+            PathObject::new(PathBuf::from("/w/src/main.rs"), false, 3, 1),
+            PathObject::from_path(PathBuf::from("/w/src/lib.rs"), false),
+            PathObject {
+                path: PathBuf::from("/w/src/main.rs"),
+                linecol: Some(LineCol { line: 7, column: 5 }),
+                is_dir: false,
+            },
+        ];
+        let breakpoints = breakpoints(&paths);
+        assert_eq!(breakpoints.len(), 1);
+        let lines: Vec<usize> = breakpoints[&PathBuf::from("/w/src/main.rs")]
+            .iter()
+            .map(|breakpoint| breakpoint.line)
+            .collect();
+        assert_eq!(lines, vec![3, 7]);
+    }
+
+    #[test]
+    fn test_follow_session() {
+        let core_rpc = CoreRpcHandler::new();
+        let proxy_rpc = ProxyRpcHandler::new();
+        let dap_id = DapId::next();
+        let child = DapId::next();
+        let thread: ThreadId = serde_json::from_value(json!(2)).unwrap();
+
+        core_rpc.dap_output(
+            dap_id,
+            serde_json::from_value(json!({ "output": "hello\n" })).unwrap(),
+        );
+        core_rpc.dap_stopped(
+            dap_id,
+            serde_json::from_value(json!({ "reason": "breakpoint", "threadId": 2 }))
+                .unwrap(),
+            [(
+                thread,
+                vec![serde_json::from_value(json!({
+                    "id": 1,
+                    "name": "main",
+                    "source": { "path": "/w/src/main.rs" },
+                    "line": 3,
+                    "column": 1,
+                }))
+                .unwrap()],
+            )]
+            .into_iter()
+            .collect(),
+        );
+        // The end of a child session isn't the end of the session
+        core_rpc.dap_terminated(child, Some(1));
+        core_rpc.dap_terminated(dap_id, Some(3));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let code =
+            follow_session(&core_rpc, &proxy_rpc, dap_id, None, deadline).unwrap();
+        assert_eq!(code, 3);
+
+        // It was continued from where it stopped
+        match proxy_rpc.rx().try_recv().unwrap() {
+            ProxyRpc::Notification(ProxyNotification::DapContinue {
+                dap_id: continued,
+                thread_id,
+            }) => {
+                assert_eq!(continued, dap_id);
+                assert_eq!(thread_id, thread);
+            }
+            _ => panic!("the session wasn't continued"),
+        }
+        assert!(proxy_rpc.rx().try_recv().is_err());
+    }
+
+    #[test]
+    fn test_follow_session_failed() {
+        let core_rpc = CoreRpcHandler::new();
+        let proxy_rpc = ProxyRpcHandler::new();
+        let dap_id = DapId::next();
+
+        // A session which doesn't end in time is stopped
+        let deadline = Instant::now() + Duration::from_millis(50);
+        assert!(
+            follow_session(&core_rpc, &proxy_rpc, dap_id, None, deadline).is_err()
+        );
+        match proxy_rpc.rx().try_recv().unwrap() {
+            ProxyRpc::Notification(ProxyNotification::DapStop {
+                dap_id: stopped,
+            }) => {
+                assert_eq!(stopped, dap_id);
+            }
+            _ => panic!("the session wasn't stopped"),
+        }
+
+        // and one whose task before the launch failed isn't waited for
+        core_rpc.notification(CoreNotification::DapTaskFailed {
+            config: "test".to_string(),
+            task: "build".to_string(),
+            message: "exit code 1".to_string(),
+        });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let start = Instant::now();
+        assert!(follow_session(
+            &core_rpc,
+            &proxy_rpc,
+            dap_id,
+            Some("build"),
+            deadline
+        )
+        .is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_debuggee() {
+        let dir = std::env::temp_dir()
+            .join(format!("lapce-headless-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut config: RunDebugConfig = serde_json::from_value(json!({
+            "name": "test",
+            "program": "test",
+            "cwd": dir.to_str().unwrap(),
+            "env": { "LAPCE_HEADLESS_TEST": "debuggee" },
+        }))
+        .unwrap();
+        assert!(run_debuggee(&config).is_err());
+
+        config.debug_command =
+            Some("echo $LAPCE_HEADLESS_TEST > output.txt".to_string());
+        let status = run_debuggee(&config).unwrap().wait().unwrap();
+        assert!(status.success());
+        let output = std::fs::read_to_string(dir.join("output.txt")).unwrap();
+        assert_eq!(output.trim(), "debuggee");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
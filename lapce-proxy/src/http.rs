@@ -0,0 +1,133 @@
+//! Sending the requests of `.http` files.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{anyhow, Result};
+use lapce_rpc::http::{
+    format_body, parse_http_file, resolve_variables, HttpRequest, HttpResponse,
+};
+use reqwest::{
+    blocking::Client,
+    header::{HeaderName, HeaderValue, CONTENT_TYPE},
+    Method,
+};
+use serde_json::Value;
+
+/// The environment files, where the private one has the secrets which aren't
+/// committed and wins over the shared one.
+const ENVIRONMENT_FILES: [&str; 2] =
+    ["http-client.env.json", "http-client.private.env.json"];
+
+/// The variables of the environment with the name, from the files next to the
+/// `.http` file or else in the workspace.
+fn load_environment(
+    path: &Path,
+    workspace: Option<&Path>,
+    environment: &str,
+) -> HashMap<String, String> {
+    let dirs = path
+        .parent()
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(workspace.map(PathBuf::from));
+    let mut variables = HashMap::new();
+    for dir in dirs {
+        let files = ENVIRONMENT_FILES
+            .iter()
+            .map(|file| dir.join(file))
+            .filter(|file| file.is_file())
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            continue;
+        }
+        for file in files {
+            let Some(values) = fs::read_to_string(&file)
+                .ok()
+                .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+            else {
+                continue;
+            };
+            let Some(values) = values.get(environment).and_then(|v| v.as_object())
+            else {
+                continue;
+            };
+            for (name, value) in values {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                variables.insert(name.clone(), value);
+            }
+        }
+        break;
+    }
+    variables
+}
+
+fn send(request: &HttpRequest) -> Result<HttpResponse> {
+    let method = Method::from_bytes(request.method.as_bytes())?;
+    let mut builder = Client::new().request(method, &request.url);
+    for (name, value) in request.headers.iter() {
+        builder = builder.header(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+    }
+    if let Some(body) = request.body.clone() {
+        builder = builder.body(body);
+    }
+
+    let start = Instant::now();
+    let response = builder.send()?;
+    let status = response.status();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|c| c.to_str().ok())
+        .map(|c| c.to_string());
+    let body = response.text()?;
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    Ok(HttpResponse {
+        status: status.as_u16(),
+        reason: status.canonical_reason().unwrap_or("").to_string(),
+        headers,
+        body: format_body(content_type.as_deref(), body),
+        duration_ms,
+    })
+}
+
+/// Send the request of the text of the file whose block has the line, with its
+/// variables and the ones of the environment.
+pub fn send_http_request(
+    path: &Path,
+    text: &str,
+    line: usize,
+    environment: Option<&str>,
+    workspace: Option<&Path>,
+) -> Result<HttpResponse> {
+    let file = parse_http_file(text);
+    let request = file
+        .request_at(line)
+        .ok_or_else(|| anyhow!("there's no request at line {}", line + 1))?;
+    let environment = environment
+        .map(|environment| load_environment(path, workspace, environment))
+        .unwrap_or_default();
+    let variables = resolve_variables(&file.variables, environment);
+    send(&request.substitute(&variables))
+}
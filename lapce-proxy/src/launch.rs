@@ -0,0 +1,204 @@
+//! The debug configs of a workspace in `.lapce/launch.toml` or
+//! `.lapce/launch.json`, whose values can have variables like
+//! `${workspaceFolder}` and `${file}`.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use lapce_rpc::dap_types::RunDebugConfig;
+use serde::Deserialize;
+use serde_json::Value;
+use toml_edit::easy as toml;
+
+#[derive(Deserialize)]
+struct LaunchFile {
+    #[serde(default)]
+    configurations: Vec<RunDebugConfig>,
+}
+
+/// The values of the variables which the configs and the tasks can have.
+pub(crate) struct Variables {
+    values: HashMap<&'static str, String>,
+}
+
+impl Variables {
+    pub(crate) fn new(workspace: &Path, active_file: Option<&Path>) -> Self {
+        let mut values = HashMap::new();
+        values.insert("workspaceFolder", workspace.to_string_lossy().to_string());
+        values.insert(
+            "workspaceFolderBasename",
+            file_name(workspace).unwrap_or_default(),
+        );
+        if let Some(file) = active_file {
+            values.insert("file", file.to_string_lossy().to_string());
+            values.insert("fileBasename", file_name(file).unwrap_or_default());
+            if let Some(dir) = file.parent() {
+                values.insert("fileDirname", dir.to_string_lossy().to_string());
+            }
+            if let Ok(relative) = file.strip_prefix(workspace) {
+                values
+                    .insert("relativeFile", relative.to_string_lossy().to_string());
+            }
+        }
+        Self { values }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        match name.strip_prefix("env:") {
+            Some(name) => Some(env::var(name).unwrap_or_default()),
+            None => self.values.get(name).cloned(),
+        }
+    }
+
+    /// The value with its variables substituted, where the ones which aren't
+    /// known are left as they are.
+    pub(crate) fn substitute(&self, value: &str) -> String {
+        let mut result = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            match self.get(&after[..end]) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..start + end + 3]),
+            }
+            rest = &after[end + 1..];
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Substitute the strings of a property of a config, in its arrays and
+    /// objects too.
+    fn substitute_value(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.substitute(s),
+            Value::Array(values) => {
+                for value in values.iter_mut() {
+                    self.substitute_value(value);
+                }
+            }
+            Value::Object(values) => {
+                for value in values.values_mut() {
+                    self.substitute_value(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn substitute_config(&self, config: &mut RunDebugConfig) {
+        config.program = self.substitute(&config.program);
+        for arg in config.args.iter_mut() {
+            *arg = self.substitute(arg);
+        }
+        if let Some(cwd) = config.cwd.as_mut() {
+            *cwd = self.substitute(cwd);
+        }
+        if let Some(env) = config.env.as_mut() {
+            for value in env.values_mut() {
+                *value = self.substitute(value);
+            }
+        }
+        if let Some(env_file) = config.env_file.as_mut() {
+            *env_file = self.substitute(env_file);
+        }
+        for value in config.properties.values_mut() {
+            self.substitute_value(value);
+        }
+        if let Some(adapter) = config.adapter.as_mut() {
+            adapter.program = self.substitute(&adapter.program);
+            for arg in adapter.args.iter_mut() {
+                *arg = self.substitute(arg);
+            }
+        }
+    }
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// The file of the configs in the workspace, where the toml one comes first.
+fn launch_file(workspace: &Path) -> Option<PathBuf> {
+    let dir = workspace.join(".lapce");
+    ["launch.toml", "launch.json"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The configs of the workspace with their variables substituted, which are
+/// empty when there's no workspace or it has no file of them.
+pub fn launch_configs(
+    workspace: Option<&Path>,
+    active_file: Option<&Path>,
+) -> Result<Vec<RunDebugConfig>> {
+    let Some(workspace) = workspace else {
+        return Ok(Vec::new());
+    };
+    let Some(path) = launch_file(workspace) else {
+        return Ok(Vec::new());
+    };
+    let content = fs::read_to_string(&path)?;
+    let file: LaunchFile =
+        if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            serde_json::from_str(&content)
+                .with_context(|| format!("failed to parse {}", path.display()))?
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("failed to parse {}", path.display()))?
+        };
+
+    let variables = Variables::new(workspace, active_file);
+    let mut configs = file.configurations;
+    for config in configs.iter_mut() {
+        variables.substitute_config(config);
+        if let Some(env_file) = config.env_file.clone() {
+            let path = workspace.join(env_file);
+            let content = fs::read_to_string(&path).with_context(|| {
+                format!("failed to read the env file {}", path.display())
+            })?;
+            config.add_env_file(&content);
+        }
+    }
+    Ok(configs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        let variables = Variables::new(
+            Path::new("/home/user/project"),
+            Some(Path::new("/home/user/project/src/main.rs")),
+        );
+        assert_eq!(
+            variables.substitute("${workspaceFolder}/target/debug/app"),
+            "/home/user/project/target/debug/app"
+        );
+        assert_eq!(
+            variables.substitute("${relativeFile} in ${fileDirname}"),
+            "src/main.rs in /home/user/project/src"
+        );
+        assert_eq!(
+            variables.substitute("${fileBasename}:${workspaceFolderBasename}"),
+            "main.rs:project"
+        );
+        assert_eq!(
+            variables.substitute("${unknown} ${file"),
+            "${unknown} ${file"
+        );
+    }
+}
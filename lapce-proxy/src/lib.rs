@@ -1,31 +1,48 @@
 #![allow(clippy::manual_clamp)]
 
+pub mod archive;
 pub mod buffer;
 pub mod cli;
+pub mod coverage;
+pub mod crash;
 pub mod dispatch;
+pub mod file_index;
+pub mod headless;
+pub mod http;
+pub mod launch;
+pub mod limits;
+pub mod logging;
 pub mod plugin;
+pub mod runnable;
+pub mod task;
 pub mod terminal;
+pub mod trash_bin;
 pub mod watcher;
 
 use std::{
+    collections::HashMap,
     io::{stdin, stdout, BufReader},
     path::PathBuf,
     process::exit,
     sync::Arc,
     thread,
+    time::Duration,
 };
 
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use dispatch::Dispatcher;
+use headless::HeadlessTask;
 use lapce_core::{directory::Directory, meta};
 use lapce_rpc::{
     core::{CoreRpc, CoreRpcHandler},
     file::PathObject,
+    protocol::ProtocolInfo,
     proxy::{ProxyMessage, ProxyNotification, ProxyRpcHandler},
     stdio::stdio_transport,
     RpcMessage,
 };
+use parking_lot::Mutex;
 use tracing::error;
 
 #[derive(Parser)]
@@ -35,6 +52,44 @@ struct Cli {
     #[clap(short, long, action, hide = true)]
     proxy: bool,
 
+    /// Print the version of the protocol and the capabilities of the proxy,
+    /// which the app asks a remote proxy for before it starts it
+    #[clap(long, action, hide = true)]
+    protocol: bool,
+
+    /// Run without the app. With `--diagnostics`, `--format` or `--debug` it
+    /// does that with the given files and exits, and otherwise it takes the
+    /// messages of the app on stdin and writes its own to stdout
+    #[clap(long, action)]
+    headless: bool,
+
+    /// The workspace which the headless proxy opens. It's the current folder
+    /// with `--diagnostics`, `--format` or `--debug` if it isn't given, and the
+    /// client opens it with the initialize notification otherwise
+    #[clap(long, requires = "headless")]
+    #[clap(value_hint = clap::ValueHint::DirPath)]
+    workspace: Option<PathBuf>,
+
+    /// Print the diagnostics of the files from their language servers, and exit
+    /// with 1 when there are errors
+    #[clap(long, action, requires = "headless", conflicts_with = "format")]
+    diagnostics: bool,
+
+    /// Format the files with their language servers and save them
+    #[clap(long, action, requires = "headless", conflicts_with = "debug")]
+    format: bool,
+
+    /// Run the launch config with the name in a debug session, which stops at
+    /// the files given as `path:line` to print where it is, and exit with the
+    /// exit code of the debuggee
+    #[clap(long, requires = "headless", conflicts_with = "diagnostics")]
+    debug: Option<String>,
+
+    /// How many seconds to wait for the language servers or the debug session,
+    /// which is 30 if it isn't given
+    #[clap(long, requires = "headless")]
+    timeout: Option<u64>,
+
     /// Paths to file(s) and/or folder(s) to open.
     /// When path is a file (that exists or not),
     /// it accepts `path:line:column` syntax
@@ -46,22 +101,80 @@ struct Cli {
 
 pub fn mainloop() {
     let cli = Cli::parse();
-    if !cli.proxy {
+    if cli.protocol {
+        if let Ok(info) = serde_json::to_string(&ProtocolInfo::current()) {
+            println!("{info}");
+        }
+        return;
+    }
+    if cli.headless && (cli.diagnostics || cli.format || cli.debug.is_some()) {
+        let task = if let Some(config) = cli.debug {
+            HeadlessTask::Debug { config }
+        } else if cli.diagnostics {
+            HeadlessTask::Diagnostics
+        } else {
+            HeadlessTask::Format
+        };
+        let timeout = Duration::from_secs(cli.timeout.unwrap_or(30));
+        let workspace = cli
+            .workspace
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        exit(headless::run(task, workspace, cli.paths, timeout));
+    }
+    if !cli.proxy && !cli.headless {
         if let Err(e) = cli::try_open_in_existing_process(&cli.paths) {
             error!("failed to open path(s): {e}");
         };
         exit(1);
     }
     let core_rpc = CoreRpcHandler::new();
+    logging::init(core_rpc.clone());
     let proxy_rpc = ProxyRpcHandler::new();
     let mut dispatcher = Dispatcher::new(core_rpc.clone(), proxy_rpc.clone());
 
+    if cli.headless {
+        // A client which drives the proxy knows the protocol of this build
+        serve_stdio(core_rpc, proxy_rpc.clone(), ProtocolInfo::current());
+        if let Some(workspace) = cli.workspace {
+            proxy_rpc.initialize(
+                Some(workspace),
+                Vec::new(),
+                HashMap::new(),
+                1,
+                1,
+                Default::default(),
+            );
+        }
+    } else {
+        serve_stdio(core_rpc, proxy_rpc.clone(), ProtocolInfo::legacy());
+        let local_proxy_rpc = proxy_rpc.clone();
+        std::thread::spawn(move || {
+            let _ = listen_local_socket(local_proxy_rpc);
+        });
+        let _ = register_lapce_path();
+    }
+
+    proxy_rpc.mainloop(&mut dispatcher);
+}
+
+/// Read the messages of the app from stdin and write the ones of the proxy to
+/// stdout, where `app_protocol` is what the app is taken to know until it sends
+/// the initialize notification.
+fn serve_stdio(
+    core_rpc: CoreRpcHandler,
+    proxy_rpc: ProxyRpcHandler,
+    app_protocol: ProtocolInfo,
+) {
     let (writer_tx, writer_rx) = crossbeam_channel::unbounded();
     let (reader_tx, reader_rx) = crossbeam_channel::unbounded();
     stdio_transport(stdout(), writer_rx, BufReader::new(stdin()), reader_tx);
 
+    let app_protocol = Arc::new(Mutex::new(app_protocol));
+
     let local_core_rpc = core_rpc.clone();
     let local_writer_tx = writer_tx.clone();
+    let local_app_protocol = app_protocol.clone();
     thread::spawn(move || {
         for msg in local_core_rpc.rx() {
             match msg {
@@ -69,7 +182,9 @@ pub fn mainloop() {
                     let _ = local_writer_tx.send(RpcMessage::Request(id, rpc));
                 }
                 CoreRpc::Notification(rpc) => {
-                    let _ = local_writer_tx.send(RpcMessage::Notification(rpc));
+                    if local_app_protocol.lock().allows(rpc.capability()) {
+                        let _ = local_writer_tx.send(RpcMessage::Notification(rpc));
+                    }
                 }
                 CoreRpc::Shutdown => {
                     return;
@@ -95,6 +210,9 @@ pub fn mainloop() {
                     });
                 }
                 RpcMessage::Notification(n) => {
+                    if let ProxyNotification::Initialize { protocol, .. } = &n {
+                        *app_protocol.lock() = protocol.clone();
+                    }
                     local_proxy_rpc.notification(n);
                 }
                 RpcMessage::Response(id, resp) => {
@@ -107,14 +225,6 @@ pub fn mainloop() {
         }
         local_proxy_rpc.shutdown();
     });
-
-    let local_proxy_rpc = proxy_rpc.clone();
-    std::thread::spawn(move || {
-        let _ = listen_local_socket(local_proxy_rpc);
-    });
-    let _ = register_lapce_path();
-
-    proxy_rpc.mainloop(&mut dispatcher);
 }
 
 pub fn register_lapce_path() -> Result<()> {
@@ -0,0 +1,218 @@
+//! The limits which the app sets for the resources of the proxy, which the
+//! language servers and the debug adapters are started within, and which the
+//! searches of the workspace and the file watcher keep to.
+
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use lapce_rpc::proxy::ResourceLimits;
+use once_cell::sync::Lazy;
+use parking_lot::{Condvar, Mutex, RwLock};
+
+static LIMITS: Lazy<RwLock<ResourceLimits>> = Lazy::new(Default::default);
+
+/// The searches which run now, which wait on `SEARCH_DONE` when there are as
+/// many as the limit
+static SEARCHES: Lazy<Mutex<usize>> = Lazy::new(|| Mutex::new(0));
+static SEARCH_DONE: Condvar = Condvar::new();
+
+/// `systemd-run`, when it can start a scope of the user, which the cgroup
+/// limits are set on
+static SYSTEMD_RUN: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let path = which::which("systemd-run").ok()?;
+    let works = Command::new(&path)
+        .args(["--user", "--scope", "--quiet", "true"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    works.then_some(path)
+});
+
+static NICE: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    if !cfg!(unix) {
+        return None;
+    }
+    which::which("nice").ok()
+});
+
+pub fn set_limits(limits: ResourceLimits) {
+    *LIMITS.write() = limits;
+    // A search which waits can run now if the limit went up
+    SEARCH_DONE.notify_all();
+}
+
+pub fn limits() -> ResourceLimits {
+    LIMITS.read().clone()
+}
+
+/// The command of the program of a server, which runs it within the limits.
+pub fn server_command(program: impl AsRef<OsStr>) -> Command {
+    let limits = limits();
+    // The probe of `systemd-run` spawns a process, and can stall without a bus
+    // of the user, so it's only done once there's a limit which takes it
+    let systemd_run = if has_cgroup_limits(&limits) {
+        SYSTEMD_RUN.as_deref()
+    } else {
+        None
+    };
+    let mut args = wrapper_args(&limits, systemd_run, NICE.as_deref());
+    args.push(program.as_ref().to_os_string());
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]);
+    command
+}
+
+/// Whether a limit is set which the server is put in a cgroup for.
+fn has_cgroup_limits(limits: &ResourceLimits) -> bool {
+    limits.memory_max > 0 || limits.cpu_quota > 0
+}
+
+/// The programs and their arguments which a server is run under for the
+/// limits, which are `systemd-run` for the cgroup and `nice` for the niceness.
+/// Both exec the program, so that its process is the one which was spawned.
+fn wrapper_args(
+    limits: &ResourceLimits,
+    systemd_run: Option<&Path>,
+    nice: Option<&Path>,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = Vec::new();
+    if let Some(systemd_run) = systemd_run.filter(|_| has_cgroup_limits(limits)) {
+        let mut properties = Vec::new();
+        if limits.memory_max > 0 {
+            properties.push(format!("MemoryMax={}M", limits.memory_max));
+        }
+        if limits.cpu_quota > 0 {
+            properties.push(format!("CPUQuota={}%", limits.cpu_quota));
+        }
+        args.push(systemd_run.into());
+        args.extend(
+            ["--user", "--scope", "--quiet", "--collect"].map(OsString::from),
+        );
+        for property in properties {
+            args.push("-p".into());
+            args.push(property.into());
+        }
+        args.push("--".into());
+    }
+    if let Some(nice) = nice.filter(|_| limits.nice != 0) {
+        args.push(nice.into());
+        args.push("-n".into());
+        args.push(limits.nice.to_string().into());
+    }
+    args
+}
+
+/// A search which runs, which lets the next one run when it's dropped.
+pub struct SearchPermit(());
+
+impl Drop for SearchPermit {
+    fn drop(&mut self) {
+        *SEARCHES.lock() -= 1;
+        SEARCH_DONE.notify_one();
+    }
+}
+
+/// Wait until a search can run within the limit of the searches which run at
+/// the same time.
+pub fn search_permit() -> SearchPermit {
+    let mut searches = SEARCHES.lock();
+    loop {
+        let max = LIMITS.read().search_threads;
+        if max == 0 || *searches < max {
+            break;
+        }
+        SEARCH_DONE.wait(&mut searches);
+    }
+    *searches += 1;
+    SearchPermit(())
+}
+
+/// The folder and the ones under it, counted up to `max`, which is what a
+/// recursive watch of it takes with inotify.
+pub fn count_folders(path: &Path, max: usize) -> usize {
+    let mut count = 0;
+    let mut folders = vec![path.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        count += 1;
+        if count >= max {
+            break;
+        }
+        let Ok(entries) = fs::read_dir(&folder) else {
+            continue;
+        };
+        // The file type of an entry isn't the one of what a symlink points to,
+        // which the watch doesn't follow
+        folders.extend(
+            entries
+                .flatten()
+                .filter(|entry| {
+                    entry.file_type().map(|t| t.is_dir()).unwrap_or(false)
+                })
+                .map(|entry| entry.path()),
+        );
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapper_args() {
+        let systemd_run = Path::new("/bin/systemd-run");
+        let nice = Path::new("/bin/nice");
+        let args = |limits: &ResourceLimits| {
+            wrapper_args(limits, Some(systemd_run), Some(nice))
+                .into_iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        assert_eq!(args(&ResourceLimits::default()), "");
+        let limits = ResourceLimits {
+            nice: 10,
+            memory_max: 2048,
+            ..Default::default()
+        };
+        assert_eq!(
+            args(&limits),
+            "/bin/systemd-run --user --scope --quiet --collect -p MemoryMax=2048M \
+             -- /bin/nice -n 10"
+        );
+        assert!(wrapper_args(&limits, None, None).is_empty());
+
+        let limits = ResourceLimits {
+            memory_max: 512,
+            cpu_quota: 150,
+            ..Default::default()
+        };
+        assert_eq!(
+            args(&limits),
+            "/bin/systemd-run --user --scope --quiet --collect -p MemoryMax=512M \
+             -p CPUQuota=150% --"
+        );
+        let limits = ResourceLimits {
+            nice: -5,
+            ..Default::default()
+        };
+        assert_eq!(args(&limits), "/bin/nice -n -5");
+    }
+
+    #[test]
+    fn test_server_command_without_limits() {
+        let command = server_command("rust-analyzer");
+        assert_eq!(command.get_program(), "rust-analyzer");
+        assert_eq!(command.get_args().count(), 0);
+        // Nothing is spawned to find out whether `systemd-run` works
+        assert!(Lazy::get(&SYSTEMD_RUN).is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! The log of a remote proxy, which is sent to the app to be written along with
+//! its own log, at the levels which the app sets for the modules of the proxy.
+
+use std::fmt::{self, Write};
+
+use lapce_rpc::core::CoreRpcHandler;
+use once_cell::sync::OnceCell;
+use tracing::{
+    field::{Field, Visit},
+    metadata::LevelFilter,
+    Collect, Event,
+};
+use tracing_subscriber::{
+    filter::Targets,
+    prelude::*,
+    registry::LookupSpan,
+    reload::{self, Handle},
+    subscribe::{Context, Subscribe},
+};
+
+/// The levels of the log, which is only installed in a remote proxy, since the
+/// one which runs in the app logs to the log of the app
+static LOG_LEVELS: OnceCell<Handle<Targets>> = OnceCell::new();
+
+/// Parse log level directives like `warn,lapce_proxy::plugin=debug`, where the
+/// modules which aren't given are at `default` unless a level without a module
+/// is given.
+pub fn parse_log_levels(directives: &str, default: LevelFilter) -> Targets {
+    let targets = directives
+        .trim()
+        .parse::<Targets>()
+        .unwrap_or_else(|_| Targets::new());
+    if targets.default_level().is_some() {
+        targets
+    } else {
+        targets.with_default(default)
+    }
+}
+
+/// Send the log of the proxy to the app.
+pub fn init(core_rpc: CoreRpcHandler) {
+    let (levels, handle) =
+        reload::Subscriber::new(parse_log_levels("", LevelFilter::ERROR));
+    let installed = tracing_subscriber::registry()
+        .with(levels)
+        .with(ForwardLogs { core_rpc })
+        .try_init()
+        .is_ok();
+    if installed {
+        let _ = LOG_LEVELS.set(handle);
+    }
+}
+
+/// Set the log levels from the directives which the app has in its settings.
+pub fn update_log_levels(directives: &str) {
+    if let Some(handle) = LOG_LEVELS.get() {
+        let _ = handle.reload(parse_log_levels(directives, LevelFilter::ERROR));
+    }
+}
+
+struct ForwardLogs {
+    core_rpc: CoreRpcHandler,
+}
+
+impl<C> Subscribe<C> for ForwardLogs
+where
+    C: Collect + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, C>) {
+        let mut message = String::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let _ = write!(message, "{}: ", span.name());
+            }
+        }
+        event.record(&mut MessageVisitor(&mut message));
+
+        let meta = event.metadata();
+        self.core_rpc
+            .forward_log(*meta.level(), meta.target().to_string(), message);
+    }
+}
+
+/// The message of an event, followed by its other fields
+struct MessageVisitor<'a>(&'a mut String);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_levels() {
+        let levels =
+            parse_log_levels("lapce_proxy::plugin=debug", LevelFilter::WARN);
+        assert_eq!(levels.default_level(), Some(LevelFilter::WARN));
+        assert!(
+            levels.would_enable("lapce_proxy::plugin::lsp", &tracing::Level::DEBUG)
+        );
+        assert!(!levels.would_enable("lapce_proxy::dispatch", &tracing::Level::INFO));
+
+        let levels = parse_log_levels("info", LevelFilter::WARN);
+        assert_eq!(levels.default_level(), Some(LevelFilter::INFO));
+
+        let levels = parse_log_levels("", LevelFilter::ERROR);
+        assert_eq!(levels.default_level(), Some(LevelFilter::ERROR));
+    }
+}
@@ -9,7 +9,14 @@
 };
 
 use lapce_rpc::{
-    dap_types::{DapId, DapServer, SetBreakpointsResponse},
+    dap_types::{
+        DapId, DapRequestTimeouts, DapServer, DataBreakpointInfoResponse,
+        EvaluateResponse, GotoTargetsResponse, ReadMemoryResponse, RunDebugConfig,
+        RunDebugConfigMode, Scope, ScopesResponse, SetBreakpointsResponse,
+        SetDataBreakpointsResponse, SetExpressionResponse, SetVariableResponse,
+        SourceBreakpoint, ThreadId, Variable, VariablesResponse,
+        WriteMemoryResponse,
+    },
     plugin::{PluginId, VoltID, VoltMetadata},
     proxy::ProxyResponse,
     style::LineStyle,
@@ -17,26 +24,38 @@
 };
 use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
-    notification::DidOpenTextDocument, DidOpenTextDocumentParams, SemanticTokens,
-    TextDocumentIdentifier, TextDocumentItem, VersionedTextDocumentIdentifier,
+    notification::DidOpenTextDocument, DidOpenTextDocumentParams, MessageType,
+    SemanticTokens, ShowMessageParams, TextDocumentIdentifier, TextDocumentItem,
+    VersionedTextDocumentIdentifier,
 };
 use parking_lot::Mutex;
 use psp_types::Notification;
 use serde_json::Value;
+use tracing::debug;
 
 use super::{
     dap::{DapClient, DapRpcHandler},
+    dap_registry::{resolve_adapter, RegistryAdapter},
     psp::{ClonableCallback, PluginServerRpc, PluginServerRpcHandler, RpcCallback},
+    schema::start_schema_server,
     wasi::{load_all_volts, start_volt},
     PluginCatalogNotification, PluginCatalogRpcHandler,
 };
-use crate::plugin::{install_volt, wasi::enable_volt};
+use crate::{
+    plugin::{install_volt, wasi::enable_volt},
+    task::{find_task, run_task, Task},
+};
 
 pub struct PluginCatalog {
     workspace: Option<PathBuf>,
     plugin_rpc: PluginCatalogRpcHandler,
     plugins: HashMap<PluginId, PluginServerRpcHandler>,
     daps: HashMap<DapId, DapRpcHandler>,
+    /// The watch expressions of the workspace, which every debugger evaluates
+    watches: Vec<String>,
+    /// The exception filters which were toggled, by their ids
+    exception_filters: HashMap<String, bool>,
+    request_timeouts: DapRequestTimeouts,
     plugin_configurations: HashMap<String, HashMap<String, serde_json::Value>>,
     unactivated_volts: HashMap<VoltID, VoltMetadata>,
     open_files: HashMap<PathBuf, String>,
@@ -78,12 +97,21 @@ pub fn new(
             });
         }
 
+        start_schema_server(
+            plugin_rpc.clone(),
+            workspace.clone(),
+            plugin_configurations.get("schemas").cloned(),
+        );
+
         let plugin = Self {
             workspace,
             plugin_rpc: plugin_rpc.clone(),
             plugin_configurations,
             plugins: HashMap::new(),
             daps: HashMap::new(),
+            watches: Vec::new(),
+            exception_filters: HashMap::new(),
+            request_timeouts: DapRequestTimeouts::default(),
             unactivated_volts: HashMap::new(),
             open_files: HashMap::new(),
         };
@@ -346,6 +374,274 @@ pub fn format_semantic_tokens(
         }
     }
 
+    pub fn dap_scopes(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: Box<dyn RpcCallback<Vec<Scope>, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.scopes_async(
+                frame_id,
+                move |result: Result<ScopesResponse, RpcError>| {
+                    f.call(result.map(|resp| resp.scopes));
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_variables(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        f: Box<dyn RpcCallback<Vec<Variable>, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.variables_async(
+                reference,
+                move |result: Result<VariablesResponse, RpcError>| {
+                    f.call(result.map(|resp| resp.variables));
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        f: Box<dyn RpcCallback<EvaluateResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.evaluate_async(
+                expression,
+                frame_id,
+                move |result: Result<EvaluateResponse, RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: Box<dyn RpcCallback<SetVariableResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.set_variable_async(
+                reference,
+                name,
+                value,
+                move |result: Result<SetVariableResponse, RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_restart_frame(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: Box<dyn RpcCallback<(), RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.restart_frame_async(
+                frame_id,
+                move |result: Result<(), RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_goto_targets(
+        &self,
+        dap_id: DapId,
+        path: PathBuf,
+        line: usize,
+        f: Box<dyn RpcCallback<GotoTargetsResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.goto_targets_async(
+                path,
+                line,
+                move |result: Result<GotoTargetsResponse, RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_goto(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+        target_id: usize,
+        f: Box<dyn RpcCallback<(), RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.goto_async(
+                thread_id,
+                target_id,
+                move |result: Result<(), RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: Box<dyn RpcCallback<ReadMemoryResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.read_memory_async(
+                memory_reference,
+                offset,
+                count,
+                move |result: Result<ReadMemoryResponse, RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_write_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        data: Vec<u8>,
+        f: Box<dyn RpcCallback<WriteMemoryResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.write_memory_async(
+                memory_reference,
+                offset,
+                &data,
+                move |result: Result<WriteMemoryResponse, RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_set_expression(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        value: String,
+        frame_id: Option<usize>,
+        f: Box<dyn RpcCallback<SetExpressionResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.set_expression_async(
+                expression,
+                value,
+                frame_id,
+                move |result: Result<SetExpressionResponse, RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    pub fn dap_data_breakpoint_info(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: Box<dyn RpcCallback<DataBreakpointInfoResponse, RpcError>>,
+    ) {
+        if let Some(dap) = self.daps.get(&dap_id) {
+            dap.data_breakpoint_info_async(
+                reference,
+                name,
+                move |result: Result<DataBreakpointInfoResponse, RpcError>| {
+                    f.call(result);
+                },
+            );
+        } else {
+            f.call(Err(RpcError {
+                code: 0,
+                message: "debug session doesn't exist".to_string(),
+            }));
+        }
+    }
+
+    /// The debug session and the child sessions which were started from it, the
+    /// children before their parents, which are stopped together.
+    fn dap_sessions(&self, dap_id: DapId) -> Vec<DapId> {
+        let mut sessions = Vec::new();
+        for dap in self.daps.values() {
+            if dap.parent == Some(dap_id) {
+                sessions.extend(self.dap_sessions(dap.dap_id));
+            }
+        }
+        sessions.push(dap_id);
+        sessions
+    }
+
     pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
         use PluginCatalogNotification::*;
         match notification {
@@ -435,23 +731,115 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                 breakpoints,
             } => {
                 let workspace = self.workspace.clone();
+                let watches = self.watches.clone();
+                let exception_filters = self.exception_filters.clone();
+                let request_timeouts = self.request_timeouts.clone();
                 let plugin_rpc = self.plugin_rpc.clone();
                 thread::spawn(move || {
-                    if let Ok(dap_rpc) = DapClient::start(
-                        DapServer {
-                            program: "/opt/homebrew/opt/llvm@14/bin/lldb-vscode"
-                                .to_string(),
-                            args: Vec::new(),
+                    let core_rpc = &plugin_rpc.core_rpc;
+                    let task_failed = |task: &str, e: anyhow::Error| {
+                        core_rpc.dap_task_failed(
+                            config.name.clone(),
+                            task.to_string(),
+                            e.to_string(),
+                        );
+                    };
+                    if let Some(name) = config.pre_launch_task.as_deref() {
+                        let result = find_task(workspace.as_deref(), name)
+                            .and_then(|task| run_task(&task, core_rpc));
+                        if let Err(e) = result {
+                            task_failed(name, e);
+                            return;
+                        }
+                    }
+                    // The post debug task is looked for now, so that a task
+                    // which isn't there is known before the session
+                    let post_debug_task =
+                        config.post_debug_task.as_deref().and_then(|name| {
+                            find_task(workspace.as_deref(), name)
+                                .map_err(|e| task_failed(name, e))
+                                .ok()
+                        });
+
+                    // The adapter of a config of `.lapce/launch.toml` is used
+                    // when it has one, and else the one of the registry which
+                    // it names, or which debugs the type of the config
+                    let server = match config.adapter.clone() {
+                        Some(adapter) if adapter.name.is_none() => DapServer {
+                            program: adapter.program,
+                            args: adapter.args,
                             cwd: workspace,
+                            transport: adapter.transport,
                         },
-                        config.clone(),
+                        adapter => {
+                            let name = adapter
+                                .and_then(|adapter| adapter.name)
+                                .or_else(|| {
+                                    config
+                                        .properties
+                                        .get("type")
+                                        .and_then(|v| v.as_str())
+                                        .map(|v| v.to_string())
+                                })
+                                .unwrap_or_else(|| {
+                                    RegistryAdapter::CodeLldb.name().to_string()
+                                });
+                            let server = RegistryAdapter::from_name(&name)
+                                .ok_or_else(|| {
+                                    anyhow::anyhow!(
+                                        "there's no debug adapter for {name}"
+                                    )
+                                })
+                                .and_then(|adapter| {
+                                    resolve_adapter(adapter, workspace, core_rpc)
+                                });
+                            match server {
+                                Ok(server) => server,
+                                Err(e) => {
+                                    core_rpc.show_message(
+                                        "Debug Adapter".to_string(),
+                                        ShowMessageParams {
+                                            typ: MessageType::ERROR,
+                                            message: format!("{e:#}"),
+                                        },
+                                    );
+                                    return;
+                                }
+                            }
+                        }
+                    };
+                    start_dap(
+                        server,
+                        config,
                         breakpoints,
-                        plugin_rpc.clone(),
-                    ) {
-                        let _ = plugin_rpc.dap_loaded(dap_rpc.clone());
-
-                        let _ = dap_rpc.launch(&config);
-                    }
+                        watches,
+                        exception_filters,
+                        request_timeouts,
+                        post_debug_task,
+                        plugin_rpc,
+                    );
+                });
+            }
+            DapStartChild {
+                server,
+                config,
+                breakpoints,
+            } => {
+                let watches = self.watches.clone();
+                let exception_filters = self.exception_filters.clone();
+                let request_timeouts = self.request_timeouts.clone();
+                let plugin_rpc = self.plugin_rpc.clone();
+                thread::spawn(move || {
+                    start_dap(
+                        server,
+                        config,
+                        breakpoints,
+                        watches,
+                        exception_filters,
+                        request_timeouts,
+                        None,
+                        plugin_rpc,
+                    );
                 });
             }
             DapProcessId {
@@ -459,7 +847,7 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                 process_id,
                 term_id,
             } => {
-                println!("dap process id {process_id:?}");
+                debug!("dap process id {process_id:?}");
                 if let Some(dap) = self.daps.get(&dap_id) {
                     let _ = dap.termain_process_tx.send((term_id, process_id));
                 }
@@ -468,8 +856,21 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                 if let Some(dap) = self.daps.get(&dap_id).cloned() {
                     let plugin_rpc = self.plugin_rpc.clone();
                     thread::spawn(move || {
-                        if dap.continue_thread(thread_id).is_ok() {
-                            plugin_rpc.core_rpc.dap_continued(dap_id);
+                        if dap.continue_thread(thread_id, false).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id, None);
+                        }
+                    });
+                }
+            }
+            DapContinueThread { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if let Ok(resp) = dap.continue_thread(thread_id, true) {
+                            let all = resp.all_threads_continued == Some(true);
+                            plugin_rpc
+                                .core_rpc
+                                .dap_continued(dap_id, (!all).then_some(thread_id));
                         }
                     });
                 }
@@ -481,18 +882,60 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                     });
                 }
             }
-            DapStop { dap_id } => {
-                if let Some(dap) = self.daps.get(&dap_id) {
-                    dap.stop();
+            DapReverseContinue { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if dap.reverse_continue(thread_id).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id, None);
+                        }
+                    });
                 }
             }
-            DapDisconnect { dap_id } => {
+            DapStepBack { dap_id, thread_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
+                    thread::spawn(move || {
+                        if dap.step_back(thread_id).is_ok() {
+                            plugin_rpc.core_rpc.dap_continued(dap_id, None);
+                        }
+                    });
+                }
+            }
+            DapTerminateThreads { dap_id, thread_ids } => {
                 if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let plugin_rpc = self.plugin_rpc.clone();
                     thread::spawn(move || {
-                        let _ = dap.disconnect();
+                        if let Err(err) = dap.terminate_threads(thread_ids) {
+                            plugin_rpc.core_rpc.show_message(
+                                "Debug".to_string(),
+                                ShowMessageParams {
+                                    typ: MessageType::ERROR,
+                                    message: format!(
+                                        "Failed to terminate the threads: {err}"
+                                    ),
+                                },
+                            );
+                        }
                     });
                 }
             }
+            DapStop { dap_id } => {
+                for dap_id in self.dap_sessions(dap_id) {
+                    if let Some(dap) = self.daps.get(&dap_id) {
+                        dap.stop();
+                    }
+                }
+            }
+            DapDisconnect { dap_id } => {
+                for dap_id in self.dap_sessions(dap_id) {
+                    if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                        thread::spawn(move || {
+                            let _ = dap.disconnect();
+                        });
+                    }
+                }
+            }
             DapRestart {
                 dap_id,
                 breakpoints,
@@ -523,6 +966,55 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
                     );
                 }
             }
+            DapSetWatches { expressions } => {
+                for dap in self.daps.values() {
+                    dap.set_watches(expressions.clone());
+                }
+                self.watches = expressions;
+            }
+            DapEvaluateWatches { dap_id, frame_id } => {
+                if let Some(dap) = self.daps.get(&dap_id).cloned() {
+                    let watches = self.watches.clone();
+                    let core_rpc = self.plugin_rpc.core_rpc.clone();
+                    thread::spawn(move || {
+                        dap.evaluate_watches(&core_rpc, &watches, frame_id);
+                    });
+                }
+            }
+            DapSetExceptionFilters { filters } => {
+                for dap in self.daps.values() {
+                    dap.set_exception_filters(filters.clone());
+                }
+                self.exception_filters = filters;
+            }
+            DapSetRequestTimeouts { timeouts } => {
+                for dap in self.daps.values() {
+                    dap.set_request_timeouts(timeouts.clone());
+                }
+                self.request_timeouts = timeouts;
+            }
+            DapSetDataBreakpoints {
+                dap_id,
+                breakpoints,
+            } => {
+                if let Some(dap) = self.daps.get(&dap_id) {
+                    let core_rpc = self.plugin_rpc.core_rpc.clone();
+                    dap.set_data_breakpoints_async(
+                        breakpoints,
+                        move |result: Result<
+                            SetDataBreakpointsResponse,
+                            RpcError,
+                        >| {
+                            if let Ok(resp) = result {
+                                core_rpc.dap_data_breakpoints_resp(
+                                    dap_id,
+                                    resp.breakpoints,
+                                );
+                            }
+                        },
+                    );
+                }
+            }
             Shutdown => {
                 for (_, plugin) in self.plugins.iter() {
                     plugin.shutdown();
@@ -531,3 +1023,41 @@ pub fn handle_notification(&mut self, notification: PluginCatalogNotification) {
         }
     }
 }
+
+/// Start the debug session of the config with the adapter, and launch or attach
+/// to its debuggee.
+fn start_dap(
+    server: DapServer,
+    config: RunDebugConfig,
+    breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+    watches: Vec<String>,
+    exception_filters: HashMap<String, bool>,
+    request_timeouts: DapRequestTimeouts,
+    post_debug_task: Option<Task>,
+    plugin_rpc: PluginCatalogRpcHandler,
+) {
+    let Ok(dap_rpc) = DapClient::start(
+        server,
+        config.clone(),
+        breakpoints,
+        watches,
+        exception_filters,
+        request_timeouts,
+        plugin_rpc.clone(),
+    ) else {
+        return;
+    };
+    let _ = plugin_rpc.dap_loaded(dap_rpc.clone());
+    if let Some(task) = post_debug_task {
+        dap_rpc.set_post_debug_task(task);
+    }
+
+    let started = dap_rpc.start_debuggee(&config);
+    if started.is_ok()
+        && (config.mode == RunDebugConfigMode::Attach || config.parent.is_some())
+    {
+        // A process which was attached to, or the child session of one, isn't
+        // run in a terminal which would show the session
+        plugin_rpc.core_rpc.dap_session_started(config);
+    }
+}
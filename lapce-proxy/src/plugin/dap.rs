@@ -1,50 +1,105 @@
 use std::{
-    collections::HashMap,
-    io::{BufReader, BufWriter, Write},
+    collections::{HashMap, HashSet},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    net::{TcpListener, TcpStream},
     path::PathBuf,
-    process::{Child, Command, Stdio},
+    process::{Child, Stdio},
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
     thread,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use crossbeam_channel::{Receiver, Sender};
 use lapce_rpc::{
+    core::CoreRpcHandler,
+    crash::Subsystem,
     dap_types::{
-        self, ConfigurationDone, Continue, ContinueArguments, ContinueResponse,
-        DapEvent, DapId, DapPayload, DapRequest, DapResponse, DapServer,
-        DebuggerCapabilities, Disconnect, Initialize, Launch, Pause, PauseArguments,
-        Request, RunDebugConfig, RunInTerminal, RunInTerminalArguments,
-        RunInTerminalResponse, SetBreakpoints, SetBreakpointsArguments,
-        SetBreakpointsResponse, Source, SourceBreakpoint, StackTrace,
-        StackTraceArguments, StackTraceResponse, Terminate, ThreadId, Threads,
-        ThreadsResponse,
+        self, Attach, Cancel, CancelArguments, ConfigurationDone, Continue,
+        ContinueArguments, ContinueResponse, DapEvent, DapId, DapPayload,
+        DapRequest, DapRequestTimeouts, DapResponse, DapServer, DapTransport,
+        DataBreakpoint, DataBreakpointInfo, DataBreakpointInfoArguments,
+        DataBreakpointInfoResponse, DebuggerCapabilities, Disconnect, Evaluate,
+        EvaluateArguments, EvaluateResponse, Goto, GotoArguments, GotoTargets,
+        GotoTargetsArguments, GotoTargetsResponse, Initialize, Launch, Pause,
+        PauseArguments, ReadMemory, ReadMemoryArguments, ReadMemoryResponse,
+        Request, Restart, RestartArguments, RestartFrame, RestartFrameArguments,
+        ReverseContinue, ReverseContinueArguments, RunDebugConfig,
+        RunDebugConfigMode, RunInTerminal, RunInTerminalArguments,
+        RunInTerminalResponse, Scopes, ScopesArguments, ScopesResponse,
+        SetBreakpoints, SetBreakpointsArguments, SetBreakpointsResponse,
+        SetDataBreakpoints, SetDataBreakpointsArguments, SetDataBreakpointsResponse,
+        SetExceptionBreakpoints, SetExceptionBreakpointsArguments, SetExpression,
+        SetExpressionArguments, SetExpressionResponse, SetVariable,
+        SetVariableArguments, SetVariableResponse, Source, SourceBreakpoint,
+        StackTrace, StackTraceArguments, StackTraceResponse, StartDebugging,
+        StartDebuggingRequestArguments, StartDebuggingRequestKind, StepBack,
+        StepBackArguments, Terminate, TerminateThreads, TerminateThreadsArguments,
+        ThreadId, Threads, ThreadsResponse, Variables, VariablesArguments,
+        VariablesResponse, WriteMemory, WriteMemoryArguments, WriteMemoryResponse,
     },
     terminal::TermId,
     RpcError,
 };
+use lsp_types::{
+    MessageType, ProgressParams, ProgressParamsValue, ProgressToken,
+    ShowMessageParams, WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressEnd,
+    WorkDoneProgressReport,
+};
 use parking_lot::Mutex;
 use serde_json::Value;
+use tracing::{debug, error};
 
 use super::{
     psp::{ResponseHandler, RpcCallback},
     PluginCatalogRpcHandler,
 };
+use crate::{
+    crash::spawn_supervised,
+    task::{run_task, Task},
+};
+
+/// The target of the log which the stderr of the adapters goes to, which the
+/// log panel can be filtered by
+const STDERR_TARGET: &str = "Debug Adapter";
+
+/// How long the connection to an adapter which was just started is retried, as
+/// it may not listen yet
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The ends of the connection to an adapter, which its messages are read from
+/// and written to
+type Connection = (Box<dyn BufRead + Send>, Box<dyn Write + Send>);
 
 pub struct DapClient {
     plugin_rpc: PluginCatalogRpcHandler,
     pub(crate) dap_rpc: DapRpcHandler,
     dap_server: DapServer,
+    /// The process of the adapter, which is killed when it disconnects or the
+    /// session ends
+    server: Option<Child>,
     config: RunDebugConfig,
     breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+    watches: Vec<String>,
+    /// The exception filters which were toggled, by their ids
+    exception_filters: HashMap<String, bool>,
     term_id: Option<TermId>,
     capabilities: Option<DebuggerCapabilities>,
+    /// The ids of the progress which the debugger started and didn't end yet
+    progresses: HashSet<String>,
     terminated: bool,
+    /// The exit code of the debuggee, which the debugger gives before the
+    /// session is terminated
+    exit_code: Option<usize>,
     disconnected: bool,
     restarted: bool,
+    /// The task which is run when the session ends
+    post_debug_task: Option<Task>,
 }
 
 impl DapClient {
@@ -52,20 +107,30 @@ pub fn new(
         dap_server: DapServer,
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        watches: Vec<String>,
+        exception_filters: HashMap<String, bool>,
+        request_timeouts: DapRequestTimeouts,
         plugin_rpc: PluginCatalogRpcHandler,
     ) -> Result<Self> {
-        let dap_rpc = DapRpcHandler::new(config.dap_id);
+        let dap_rpc =
+            DapRpcHandler::new(config.dap_id, config.parent, request_timeouts);
 
         Ok(Self {
             plugin_rpc,
             dap_server,
+            server: None,
             config,
             dap_rpc,
             breakpoints,
+            watches,
+            exception_filters,
             term_id: None,
             capabilities: None,
+            progresses: HashSet::new(),
             terminated: false,
+            exit_code: None,
             disconnected: false,
+            post_debug_task: None,
             restarted: false,
         })
     }
@@ -74,9 +139,20 @@ pub fn start(
         dap_server: DapServer,
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+        watches: Vec<String>,
+        exception_filters: HashMap<String, bool>,
+        request_timeouts: DapRequestTimeouts,
         plugin_rpc: PluginCatalogRpcHandler,
     ) -> Result<DapRpcHandler> {
-        let mut dap = Self::new(dap_server, config, breakpoints, plugin_rpc)?;
+        let mut dap = Self::new(
+            dap_server,
+            config,
+            breakpoints,
+            watches,
+            exception_filters,
+            request_timeouts,
+            plugin_rpc,
+        )?;
         dap.start_process()?;
 
         let dap_rpc = dap.dap_rpc.clone();
@@ -84,7 +160,9 @@ pub fn start(
 
         {
             let dap_rpc = dap_rpc.clone();
-            thread::spawn(move || {
+            let name = dap.config.name.clone();
+            let core_rpc = dap.plugin_rpc.core_rpc.clone();
+            spawn_supervised(Subsystem::DebugAdapter, name, core_rpc, move || {
                 dap_rpc.mainloop(&mut dap);
             });
         }
@@ -92,21 +170,13 @@ pub fn start(
         Ok(dap_rpc)
     }
 
-    fn start_process(&self) -> Result<()> {
+    fn start_process(&mut self) -> Result<()> {
         let program = self.dap_server.program.clone();
-        let mut process = Self::process(
-            &program,
-            &self.dap_server.args,
-            self.dap_server.cwd.as_ref(),
-        )?;
-        let stdin = process.stdin.take().unwrap();
-        let stdout = process.stdout.take().unwrap();
-        // let stderr = process.stderr.take().unwrap();
+        let (mut reader, mut writer) = self.connect()?;
 
         let dap_rpc = self.dap_rpc.clone();
         let io_rx = self.dap_rpc.io_rx.clone();
         let io_tx = self.dap_rpc.io_tx.clone();
-        let mut writer = Box::new(BufWriter::new(stdin));
         thread::spawn(move || -> Result<()> {
             for msg in io_rx {
                 if let Ok(msg) = serde_json::to_string(&msg) {
@@ -120,10 +190,11 @@ fn start_process(&self) -> Result<()> {
         });
 
         {
-            let plugin_rpc = self.plugin_rpc.clone();
-            thread::spawn(move || {
-                let mut reader = Box::new(BufReader::new(stdout));
-                loop {
+            spawn_supervised(
+                Subsystem::DebugAdapter,
+                self.config.name.clone(),
+                self.plugin_rpc.core_rpc.clone(),
+                move || loop {
                     match crate::plugin::lsp::read_message(&mut reader) {
                         Ok(message_str) => {
                             dap_rpc.handle_server_message(&message_str);
@@ -132,28 +203,131 @@ fn start_process(&self) -> Result<()> {
                             let _ = io_tx.send(DapPayload::Event(
                                 DapEvent::Initialized(None),
                             ));
-                            plugin_rpc.core_rpc.log(
-                                tracing::Level::ERROR,
-                                format!("dap server {program} stopped!"),
-                            );
+                            error!("dap server {program} stopped!");
 
                             dap_rpc.disconnected();
                             return;
                         }
                     };
-                }
-            });
+                },
+            );
         }
 
         Ok(())
     }
 
+    /// Start the adapter, and connect to it over its transport.
+    fn connect(&mut self) -> Result<Connection> {
+        let server = &self.dap_server;
+        match server.transport.clone() {
+            DapTransport::Stdio => {
+                let mut process = Self::process(
+                    &server.program,
+                    &server.args,
+                    server.cwd.as_ref(),
+                )?;
+                let stdin = process.stdin.take().unwrap();
+                let stdout = process.stdout.take().unwrap();
+                if let Some(stderr) = process.stderr.take() {
+                    self.forward_stderr(stderr);
+                }
+                self.server = Some(process);
+                Ok((
+                    Box::new(BufReader::new(stdout)),
+                    Box::new(BufWriter::new(stdin)),
+                ))
+            }
+            DapTransport::Tcp { host, port } => {
+                let port = if port == 0 { free_port()? } else { port };
+                let args = server_args(&server.args, port);
+                self.server = self.start_server(&args)?;
+                let stream = connect_with_retry(CONNECT_TIMEOUT, || {
+                    TcpStream::connect((
+                        host.as_deref().unwrap_or("127.0.0.1"),
+                        port,
+                    ))
+                })?;
+                // The port which was picked is the one which the child sessions
+                // connect to
+                self.dap_server.transport = DapTransport::Tcp { host, port };
+                Ok((
+                    Box::new(BufReader::new(stream.try_clone()?)),
+                    Box::new(BufWriter::new(stream)),
+                ))
+            }
+            DapTransport::Pipe { path } => {
+                if cfg!(windows) {
+                    return Err(anyhow!(
+                        "the debug adapter can't be connected to over a named pipe on Windows, use a port instead"
+                    ));
+                }
+                self.server = self.start_server(&server.args)?;
+                connect_pipe(&path)
+            }
+        }
+    }
+
+    /// Start the program of an adapter which is connected to after, whose stdout
+    /// isn't read.
+    fn start_server(&self, args: &[String]) -> Result<Option<Child>> {
+        let server = &self.dap_server;
+        if server.program.is_empty() {
+            return Ok(None);
+        }
+        let mut process = crate::limits::server_command(&server.program);
+        if let Some(cwd) = server.cwd.as_ref() {
+            process.current_dir(cwd);
+        }
+        process.args(args);
+        #[cfg(target_os = "windows")]
+        std::os::windows::process::CommandExt::creation_flags(
+            &mut process,
+            0x08000000,
+        );
+        let mut child = process
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(stderr) = child.stderr.take() {
+            self.forward_stderr(stderr);
+        }
+        Ok(Some(child))
+    }
+
+    /// Kill the adapter, which may keep listening after it disconnected.
+    fn kill_server(&mut self) {
+        if let Some(mut server) = self.server.take() {
+            let _ = server.kill();
+            let _ = server.wait();
+        }
+    }
+
+    /// Log the lines of the stderr of the adapter until it's closed, so that
+    /// why it failed to start can be seen.
+    fn forward_stderr(&self, stderr: impl Read + Send + 'static) {
+        let name = self.config.name.clone();
+        let core_rpc = self.plugin_rpc.core_rpc.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                let Ok(line) = line else {
+                    return;
+                };
+                core_rpc.forward_log(
+                    tracing::Level::INFO,
+                    STDERR_TARGET.to_string(),
+                    format!("{name}: {line}"),
+                );
+            }
+        });
+    }
+
     fn process(
         server: &str,
         args: &[String],
         cwd: Option<&PathBuf>,
     ) -> Result<Child> {
-        let mut process = Command::new(server);
+        let mut process = crate::limits::server_command(server);
         if let Some(cwd) = cwd {
             process.current_dir(cwd);
         }
@@ -176,6 +350,16 @@ fn process(
         Ok(child)
     }
 
+    /// The adapter of the child sessions, which connect to the same one when it
+    /// listens on a port or a pipe, and start one more when it's on stdio.
+    fn child_server(&self) -> DapServer {
+        let mut server = self.dap_server.clone();
+        if server.transport != DapTransport::Stdio {
+            server.program = String::new();
+        }
+        server
+    }
+
     fn handle_host_request(&mut self, req: &DapRequest) -> Result<Value> {
         match req.command.as_str() {
             RunInTerminal::COMMAND => {
@@ -199,10 +383,46 @@ fn handle_host_request(&mut self, req: &DapRequest) -> Result<Value> {
                 let resp = serde_json::to_value(resp)?;
                 Ok(resp)
             }
+            StartDebugging::COMMAND => {
+                let value = req
+                    .arguments
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("no arguments"))?;
+                let args: StartDebuggingRequestArguments =
+                    serde_json::from_value(value.clone())?;
+                self.plugin_rpc.dap_start_child(
+                    self.child_server(),
+                    Self::child_config(&self.config, args),
+                    self.breakpoints.clone(),
+                )?;
+                Ok(Value::Null)
+            }
             _ => Err(anyhow!("not implemented")),
         }
     }
 
+    /// The config of a child session which the adapter asked to start, which is
+    /// the one of its parent with the arguments that the adapter gave.
+    fn child_config(
+        parent: &RunDebugConfig,
+        args: StartDebuggingRequestArguments,
+    ) -> RunDebugConfig {
+        let mut config = parent.clone();
+        if let Some(name) = args.configuration.get("name").and_then(Value::as_str) {
+            config.name = name.to_string();
+        }
+        config.mode = match args.request {
+            StartDebuggingRequestKind::Launch => RunDebugConfigMode::Launch,
+            StartDebuggingRequestKind::Attach => RunDebugConfigMode::Attach,
+        };
+        config.dap_id = DapId::next();
+        config.parent = Some(parent.dap_id);
+        config.configuration = Some(args.configuration);
+        config.debug_command = None;
+        config.runnable = None;
+        config
+    }
+
     fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
         match event {
             DapEvent::Initialized(_) => {
@@ -217,6 +437,7 @@ fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
                         );
                     }
                 }
+                let _ = self.set_exception_breakpoints();
                 // send dap configurations here
                 let _ = self.dap_rpc.request::<ConfigurationDone>(());
             }
@@ -230,22 +451,46 @@ fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
                 let all_threads_stopped =
                     stopped.all_threads_stopped.unwrap_or_default();
                 let mut stack_frames = HashMap::new();
-                if all_threads_stopped {
-                    if let Ok(response) = self.dap_rpc.threads() {
-                        for thread in response.threads {
-                            if let Ok(frames) = self.dap_rpc.stack_trace(thread.id) {
-                                stack_frames.insert(thread.id, frames.stack_frames);
-                            }
+                // The threads which keep running when only one stopped are listed
+                // without their frames
+                if let Ok(response) = self.dap_rpc.threads() {
+                    for thread in &response.threads {
+                        if !all_threads_stopped
+                            && stopped.thread_id != Some(thread.id)
+                        {
+                            continue;
+                        }
+                        if let Ok(frames) = self.dap_rpc.stack_trace(thread.id) {
+                            stack_frames.insert(thread.id, frames.stack_frames);
                         }
                     }
+                    self.plugin_rpc
+                        .core_rpc
+                        .dap_threads(self.config.dap_id, response.threads);
                 }
 
+                // The watches are evaluated in the top frame of the thread which
+                // stopped, which is the frame that gets selected
+                let frame_id = stopped
+                    .thread_id
+                    .and_then(|thread_id| stack_frames.get(&thread_id))
+                    .and_then(|frames| frames.first())
+                    .map(|frame| frame.id);
+
                 self.plugin_rpc.core_rpc.dap_stopped(
                     self.config.dap_id,
                     stopped.clone(),
                     stack_frames,
                 );
 
+                if let Some(frame_id) = frame_id {
+                    self.dap_rpc.evaluate_watches(
+                        &self.plugin_rpc.core_rpc,
+                        &self.watches,
+                        frame_id,
+                    );
+                }
+
                 // if all_threads_stopped {
                 //     if let Ok(response) = self.dap_rpc.threads() {
                 //         for thread in response.threads {
@@ -260,29 +505,101 @@ fn handle_host_event(&mut self, event: &DapEvent) -> Result<()> {
                 //     self.select_thread_id(thread_id, false);
                 // }
             }
-            DapEvent::Continued(_) => {
-                self.plugin_rpc.core_rpc.dap_continued(self.dap_rpc.dap_id);
+            DapEvent::Continued(continued) => {
+                // Most debuggers resume all the threads without saying so
+                let thread_id = (continued.all_threads_continued == Some(false))
+                    .then_some(continued.thread_id);
+                self.plugin_rpc
+                    .core_rpc
+                    .dap_continued(self.dap_rpc.dap_id, thread_id);
+            }
+            DapEvent::Exited(exited) => {
+                self.exit_code = Some(exited.exit_code);
             }
-            DapEvent::Exited(_exited) => {}
             DapEvent::Terminated(_) => {
-                println!("dap process terminated");
+                debug!("dap process terminated");
+                self.session_ended();
+                self.end_progresses();
                 self.terminated = true;
-                // self.plugin_rpc.core_rpc.dap_terminated(self.dap_rpc.dap_id);
+                self.plugin_rpc
+                    .core_rpc
+                    .dap_terminated(self.dap_rpc.dap_id, self.exit_code.take());
                 if let Some(term_id) = self.term_id {
                     self.plugin_rpc.proxy_rpc.terminal_close(term_id);
                 }
                 let _ = self.check_restart();
             }
-            DapEvent::Thread { .. } => {}
-            DapEvent::Output(_) => todo!(),
+            DapEvent::Thread { .. } => {
+                // A thread started or exited
+                if let Ok(response) = self.dap_rpc.threads() {
+                    self.plugin_rpc
+                        .core_rpc
+                        .dap_threads(self.config.dap_id, response.threads);
+                }
+            }
+            DapEvent::Output(output) => {
+                self.plugin_rpc
+                    .core_rpc
+                    .dap_output(self.config.dap_id, output.clone());
+            }
             DapEvent::Breakpoint { reason, breakpoint } => {
-                println!("breakpoint  {reason} {breakpoint:?}");
+                self.plugin_rpc.core_rpc.dap_breakpoint_changed(
+                    self.config.dap_id,
+                    reason.clone(),
+                    breakpoint.clone(),
+                );
             }
             DapEvent::Module { .. } => todo!(),
             DapEvent::LoadedSource { .. } => todo!(),
             DapEvent::Process(_) => {}
-            DapEvent::Capabilities(_) => todo!(),
-            DapEvent::Memory(_) => todo!(),
+            DapEvent::Capabilities(changed) => {
+                let capabilities =
+                    self.capabilities.get_or_insert_with(Default::default);
+                capabilities.merge(&changed.capabilities);
+                self.dap_rpc.store_capabilities(capabilities);
+                self.plugin_rpc
+                    .core_rpc
+                    .dap_capabilities(self.config.dap_id, capabilities.clone());
+            }
+            DapEvent::ProgressStart(start) => {
+                self.progresses.insert(start.progress_id.clone());
+                self.progress(
+                    &start.progress_id,
+                    WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                        title: start.title.clone(),
+                        cancellable: None,
+                        message: start.message.clone(),
+                        percentage: start.percentage.map(|p| p as u32),
+                    }),
+                );
+            }
+            DapEvent::ProgressUpdate(update) => {
+                if self.progresses.contains(&update.progress_id) {
+                    self.progress(
+                        &update.progress_id,
+                        WorkDoneProgress::Report(WorkDoneProgressReport {
+                            cancellable: None,
+                            message: update.message.clone(),
+                            percentage: update.percentage.map(|p| p as u32),
+                        }),
+                    );
+                }
+            }
+            DapEvent::ProgressEnd(end) => {
+                if self.progresses.remove(&end.progress_id) {
+                    self.progress(
+                        &end.progress_id,
+                        WorkDoneProgress::End(WorkDoneProgressEnd {
+                            message: end.message.clone(),
+                        }),
+                    );
+                }
+            }
+            DapEvent::Memory(memory) => {
+                self.plugin_rpc
+                    .core_rpc
+                    .dap_memory(self.config.dap_id, memory.clone());
+            }
         }
         Ok(())
     }
@@ -303,40 +620,111 @@ pub(crate) fn initialize(&mut self) -> Result<()> {
             supports_run_in_terminal_request: Some(false),
             #[cfg(not(target_os = "windows"))]
             supports_run_in_terminal_request: Some(true),
-            supports_memory_references: Some(false),
-            supports_progress_reporting: Some(false),
+            supports_memory_references: Some(true),
+            supports_progress_reporting: Some(true),
             supports_invalidated_event: Some(false),
+            supports_start_debugging_request: Some(true),
         };
 
         let resp = self
             .dap_rpc
             .request::<Initialize>(params)
             .map_err(|e| anyhow!(e.message))?;
+        self.plugin_rpc
+            .core_rpc
+            .dap_capabilities(self.config.dap_id, resp.clone());
+        self.dap_rpc.store_capabilities(&resp);
         self.capabilities = Some(resp);
 
         Ok(())
     }
 
-    fn stop(&self) {
-        let dap_rpc = self.dap_rpc.clone();
-        if self
+    /// Tell the debugger which of its exception filters to break on, which are
+    /// the ones it enables by default unless they were toggled.
+    fn set_exception_breakpoints(&self) -> Result<()> {
+        let Some(filters) = self
             .capabilities
             .as_ref()
-            .and_then(|c| c.supports_terminate_request)
-            .unwrap_or(false)
+            .and_then(|c| c.exception_breakpoint_filters.as_ref())
+        else {
+            return Ok(());
+        };
+        let filters = filters
+            .iter()
+            .filter(|filter| {
+                self.exception_filters
+                    .get(&filter.filter)
+                    .copied()
+                    .unwrap_or_else(|| filter.default.unwrap_or(false))
+            })
+            .map(|filter| filter.filter.clone())
+            .collect();
+        self.dap_rpc.set_exception_breakpoints(filters)
+    }
+
+    fn stop(&self) {
+        let dap_rpc = self.dap_rpc.clone();
+        // A process which was attached to is left running
+        if self.config.mode == RunDebugConfigMode::Launch
+            && self
+                .capabilities
+                .as_ref()
+                .and_then(|c| c.supports_terminate_request)
+                .unwrap_or(false)
         {
-            println!("terminate");
+            debug!("terminate");
             thread::spawn(move || {
                 let _ = dap_rpc.terminate();
             });
         } else {
-            println!("discoonnect");
+            debug!("disconnect");
             thread::spawn(move || {
                 let _ = dap_rpc.disconnect();
             });
         }
     }
 
+    /// Run the post debug task once the debuggee terminated or the adapter
+    /// disconnected, whichever is first, unless it's for a restart.
+    fn session_ended(&self) {
+        if self.restarted || self.terminated || self.disconnected {
+            return;
+        }
+        let Some(task) = self.post_debug_task.clone() else {
+            return;
+        };
+        let name = self.config.name.clone();
+        let core_rpc = self.plugin_rpc.core_rpc.clone();
+        thread::spawn(move || {
+            if let Err(e) = run_task(&task, &core_rpc) {
+                core_rpc.dap_task_failed(name, task.name, e.to_string());
+            }
+        });
+    }
+
+    /// Show the progress of the debugger in the status bar, as the work done
+    /// progress of a language server is.
+    fn progress(&self, progress_id: &str, value: WorkDoneProgress) {
+        let token = ProgressToken::String(format!(
+            "dap.{}.{progress_id}",
+            self.config.dap_id.0
+        ));
+        self.plugin_rpc.core_rpc.work_done_progress(ProgressParams {
+            token,
+            value: ProgressParamsValue::WorkDone(value),
+        });
+    }
+
+    /// End the progress which the debugger didn't, as its session is over.
+    fn end_progresses(&mut self) {
+        for progress_id in std::mem::take(&mut self.progresses) {
+            self.progress(
+                &progress_id,
+                WorkDoneProgress::End(WorkDoneProgressEnd { message: None }),
+            );
+        }
+    }
+
     // check if the DAP was restared when we received terminated or disconnected
     // if the DAP doesn't suports terminate request, then we also need to wait for
     // disconnected
@@ -344,22 +732,22 @@ fn check_restart(&mut self) -> Result<()> {
         if !self.restarted {
             return Ok(());
         }
-        if !self
-            .capabilities
-            .as_ref()
-            .and_then(|c| c.supports_terminate_request)
-            .unwrap_or(false)
-            && !self.disconnected
-        {
+        let terminates = self.config.mode == RunDebugConfigMode::Launch
+            && self
+                .capabilities
+                .as_ref()
+                .and_then(|c| c.supports_terminate_request)
+                .unwrap_or(false);
+        if !terminates && !self.disconnected {
             return Ok(());
         }
 
         self.restarted = false;
 
         if self.disconnected {
-            println!("now start process");
+            debug!("start the process again");
             self.start_process()?;
-            println!("now initialize");
+            debug!("initialize again");
             self.initialize()?;
         }
         self.terminated = false;
@@ -368,15 +756,73 @@ fn check_restart(&mut self) -> Result<()> {
         let dap_rpc = self.dap_rpc.clone();
         let config = self.config.clone();
         thread::spawn(move || {
-            println!("now luanch");
-            let _ = dap_rpc.launch(&config);
-            println!("launched");
+            debug!("start the debuggee again");
+            let _ = dap_rpc.start_debuggee(&config);
+            debug!("started the debuggee");
         });
 
         Ok(())
     }
 
+    /// Restart the debuggee with the restart request of the debugger when it has
+    /// one, which keeps the debugger running, and else stop it and start it
+    /// again.
     fn restart(&mut self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
+        let supports_restart = self
+            .capabilities
+            .as_ref()
+            .and_then(|c| c.supports_restart_request)
+            .unwrap_or(false);
+        if !supports_restart || self.terminated || self.disconnected {
+            self.relaunch(breakpoints);
+            return;
+        }
+
+        // A restart without the arguments would run another debuggee than the
+        // one of the config, so there's none when they can't be made
+        let arguments = match DapRpcHandler::debuggee_arguments(&self.config) {
+            Ok(arguments) => arguments,
+            Err(e) => {
+                self.plugin_rpc.core_rpc.show_message(
+                    "Debug".to_string(),
+                    ShowMessageParams {
+                        typ: MessageType::ERROR,
+                        message: format!("Failed to restart the debuggee: {e}"),
+                    },
+                );
+                return;
+            }
+        };
+
+        self.breakpoints = breakpoints.clone();
+        let dap_rpc = self.dap_rpc.clone();
+        let config = self.config.clone();
+        let core_rpc = self.plugin_rpc.core_rpc.clone();
+        thread::spawn(move || {
+            debug!("restart request");
+            if let Err(e) = dap_rpc.restart_debuggee(arguments) {
+                debug!("restart request failed: {e}");
+                dap_rpc.relaunch(breakpoints);
+                return;
+            }
+            // The breakpoints are set again, since the ones in the editors
+            // were reset for the restart
+            for (path, breakpoints) in breakpoints {
+                if let Ok(response) =
+                    dap_rpc.set_breakpoints(path.clone(), breakpoints)
+                {
+                    core_rpc.dap_breakpoints_resp(
+                        config.dap_id,
+                        path,
+                        response.breakpoints.unwrap_or_default(),
+                    );
+                }
+            }
+        });
+    }
+
+    /// Stop the debuggee, and start it again once it's stopped.
+    fn relaunch(&mut self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
         self.restarted = true;
         self.breakpoints = breakpoints;
         if !self.terminated {
@@ -387,18 +833,82 @@ fn restart(&mut self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
     }
 }
 
+impl Drop for DapClient {
+    fn drop(&mut self) {
+        self.kill_server();
+    }
+}
+
 pub enum DapRpc {
     HostRequest(DapRequest),
     HostEvent(DapEvent),
     Stop,
     Restart(HashMap<PathBuf, Vec<SourceBreakpoint>>),
+    /// Stop and start the debuggee again, for when the restart request failed
+    Relaunch(HashMap<PathBuf, Vec<SourceBreakpoint>>),
+    SetWatches(Vec<String>),
+    SetExceptionFilters(HashMap<String, bool>),
+    SetPostDebugTask(Task),
     Shutdown,
     Disconnected,
 }
 
+/// A port on this machine which nothing listens on, for an adapter which is
+/// told the port to listen on.
+fn free_port() -> Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+/// The args of the program of an adapter, with the port which it listens on
+/// in place of `${port}`.
+fn server_args(args: &[String], port: u16) -> Vec<String> {
+    args.iter()
+        .map(|arg| arg.replace("${port}", &port.to_string()))
+        .collect()
+}
+
+/// Connect to an adapter, retrying while it may not listen yet.
+fn connect_with_retry<T>(
+    timeout: Duration,
+    connect: impl Fn() -> io::Result<T>,
+) -> Result<T> {
+    let start = Instant::now();
+    loop {
+        match connect() {
+            Ok(stream) => return Ok(stream),
+            Err(e) if start.elapsed() >= timeout => {
+                return Err(anyhow!("failed to connect to the debug adapter: {e}"));
+            }
+            Err(_) => thread::sleep(CONNECT_RETRY_INTERVAL),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn connect_pipe(path: &str) -> Result<Connection> {
+    let stream = connect_with_retry(CONNECT_TIMEOUT, || {
+        std::os::unix::net::UnixStream::connect(path)
+    })?;
+    Ok((
+        Box::new(BufReader::new(stream.try_clone()?)),
+        Box::new(BufWriter::new(stream)),
+    ))
+}
+
+/// A named pipe which is opened as a file blocks its writes while a read waits
+/// on it, so it can't carry the messages both ways at once.
+#[cfg(windows)]
+fn connect_pipe(path: &str) -> Result<Connection> {
+    Err(anyhow!(
+        "the named pipe {path} of the debug adapter can't be connected to on Windows"
+    ))
+}
+
 #[derive(Clone)]
 pub struct DapRpcHandler {
     pub dap_id: DapId,
+    /// The session which started this one, when it's a child session
+    pub parent: Option<DapId>,
     rpc_tx: Sender<DapRpc>,
     rpc_rx: Receiver<DapRpc>,
     io_tx: Sender<DapPayload>,
@@ -407,16 +917,28 @@ pub struct DapRpcHandler {
     termain_process_rx: Receiver<(TermId, Option<u32>)>,
     seq_counter: Arc<AtomicU64>,
     server_pending: Arc<Mutex<HashMap<u64, ResponseHandler<DapResponse, RpcError>>>>,
+    request_timeouts: Arc<Mutex<DapRequestTimeouts>>,
+    /// Whether the debugger takes the cancel request, for the requests which
+    /// timed out
+    supports_cancel: Arc<AtomicBool>,
+    /// Whether the debugger breaks when data changes, which it's asked for only
+    /// when it does
+    supports_data_breakpoints: Arc<AtomicBool>,
 }
 
 impl DapRpcHandler {
-    fn new(dap_id: DapId) -> Self {
+    fn new(
+        dap_id: DapId,
+        parent: Option<DapId>,
+        request_timeouts: DapRequestTimeouts,
+    ) -> Self {
         let (rpc_tx, rpc_rx) = crossbeam_channel::unbounded();
         let (io_tx, io_rx) = crossbeam_channel::unbounded();
         let (termain_process_tx, termain_process_rx) =
             crossbeam_channel::unbounded();
         Self {
             dap_id,
+            parent,
             io_tx,
             io_rx,
             rpc_rx,
@@ -425,7 +947,33 @@ fn new(dap_id: DapId) -> Self {
             termain_process_rx,
             seq_counter: Arc::new(AtomicU64::new(0)),
             server_pending: Arc::new(Mutex::new(HashMap::new())),
+            request_timeouts: Arc::new(Mutex::new(request_timeouts)),
+            supports_cancel: Arc::new(AtomicBool::new(false)),
+            supports_data_breakpoints: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn store_capabilities(&self, capabilities: &DebuggerCapabilities) {
+        self.supports_cancel.store(
+            capabilities.supports_cancel_request.unwrap_or(false),
+            Ordering::Relaxed,
+        );
+        self.supports_data_breakpoints.store(
+            capabilities.supports_data_breakpoints.unwrap_or(false),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// The error for a data breakpoint request to a debugger which doesn't
+    /// take them.
+    fn data_breakpoints_unsupported(&self) -> Option<RpcError> {
+        if self.supports_data_breakpoints.load(Ordering::Relaxed) {
+            return None;
         }
+        Some(RpcError {
+            code: 0,
+            message: "the debugger doesn't support data breakpoints".to_string(),
+        })
     }
 
     pub fn mainloop(&self, dap_client: &mut DapClient) {
@@ -455,19 +1003,38 @@ pub fn mainloop(&self, dap_client: &mut DapClient) {
                 DapRpc::Restart(breakpoints) => {
                     dap_client.restart(breakpoints);
                 }
+                DapRpc::Relaunch(breakpoints) => {
+                    dap_client.relaunch(breakpoints);
+                }
+                DapRpc::SetWatches(watches) => {
+                    dap_client.watches = watches;
+                }
+                DapRpc::SetExceptionFilters(filters) => {
+                    dap_client.exception_filters = filters;
+                    let _ = dap_client.set_exception_breakpoints();
+                }
+                DapRpc::SetPostDebugTask(task) => {
+                    dap_client.post_debug_task = Some(task);
+                }
                 DapRpc::Shutdown => {
+                    self.fail_pending("the debug session was shut down");
+                    dap_client.end_progresses();
                     if let Some(term_id) = dap_client.term_id {
                         dap_client.plugin_rpc.proxy_rpc.terminal_close(term_id);
                     }
-                    println!("dap shutdown");
+                    debug!("dap shutdown");
                     return;
                 }
                 DapRpc::Disconnected => {
+                    dap_client.session_ended();
+                    dap_client.end_progresses();
                     dap_client.disconnected = true;
+                    self.fail_pending("the debug adapter disconnected");
                     if let Some(term_id) = dap_client.term_id {
                         dap_client.plugin_rpc.proxy_rpc.terminal_close(term_id);
                     }
-                    println!("disconnected");
+                    debug!("disconnected");
+                    dap_client.kill_server();
                     let _ = dap_client.check_restart();
                 }
             }
@@ -513,13 +1080,22 @@ fn request<R: Request>(
         params: R::Arguments,
     ) -> Result<R::Result, RpcError> {
         let (tx, rx) = crossbeam_channel::bounded(1);
-        self.request_common::<R>(R::COMMAND, params, ResponseHandler::Chan(tx));
-        let resp = rx
-            .recv_timeout(std::time::Duration::from_secs(30))
-            .map_err(|_| RpcError {
+        let seq =
+            self.request_common::<R>(R::COMMAND, params, ResponseHandler::Chan(tx));
+        let timeout = self.request_timeouts.lock().timeout(R::COMMAND);
+        let resp = match timeout {
+            Some(timeout) => rx.recv_timeout(timeout).map_err(|_| {
+                self.cancel_request(seq);
+                RpcError {
+                    code: 0,
+                    message: format!("the {} request timed out", R::COMMAND),
+                }
+            }),
+            None => rx.recv().map_err(|_| RpcError {
                 code: 0,
                 message: "io error".to_string(),
-            })??;
+            }),
+        }??;
         if resp.success {
             let resp: R::Result =
                 serde_json::from_value(resp.body.into()).map_err(|e| RpcError {
@@ -540,7 +1116,7 @@ fn request_common<R: Request>(
         command: &'static str,
         arguments: R::Arguments,
         rh: ResponseHandler<DapResponse, RpcError>,
-    ) {
+    ) -> u64 {
         let seq = self.seq_counter.fetch_add(1, Ordering::Relaxed);
         let arguments: Value = serde_json::to_value(arguments).unwrap();
 
@@ -553,6 +1129,43 @@ fn request_common<R: Request>(
             command: command.to_string(),
             arguments: Some(arguments),
         }));
+        seq
+    }
+
+    /// Drop the handler of a request which timed out, and tell the debugger to
+    /// stop working on it when it can.
+    fn cancel_request(&self, seq: u64) {
+        self.server_pending.lock().remove(&seq);
+        if self.supports_cancel.load(Ordering::Relaxed) {
+            self.request_async::<Cancel>(
+                CancelArguments {
+                    request_id: Some(seq),
+                    progress_id: None,
+                },
+                |_: Result<(), RpcError>| {},
+            );
+        }
+    }
+
+    /// Fail the requests which are waiting for a response, which won't come once
+    /// the debugger is gone.
+    fn fail_pending(&self, message: &str) {
+        let pending: Vec<_> = self
+            .server_pending
+            .lock()
+            .drain()
+            .map(|(_, rh)| rh)
+            .collect();
+        for rh in pending {
+            rh.invoke(Err(RpcError {
+                code: 0,
+                message: message.to_string(),
+            }));
+        }
+    }
+
+    pub fn set_request_timeouts(&self, timeouts: DapRequestTimeouts) {
+        *self.request_timeouts.lock() = timeouts;
     }
 
     fn handle_server_response(&self, resp: DapResponse) {
@@ -578,16 +1191,89 @@ pub fn handle_server_message(&self, message_str: &str) {
         }
     }
 
-    pub fn launch(&self, config: &RunDebugConfig) -> Result<()> {
-        let params = serde_json::json!({
+    /// The arguments which launch the program of the config, where the other
+    /// properties of the config replace the ones which are built from it.
+    fn launch_arguments(config: &RunDebugConfig) -> Value {
+        let mut arguments = serde_json::json!({
             "program": config.program,
             "args": config.args,
             "cwd": config.cwd,
             "runInTerminal": true,
         });
-        let _resp = self
-            .request::<Launch>(params)
-            .map_err(|e| anyhow!(e.message))?;
+        if let Some(env) = config.env.as_ref().filter(|env| !env.is_empty()) {
+            arguments["env"] = serde_json::json!(env);
+        }
+        if let Value::Object(arguments) = &mut arguments {
+            arguments.extend(config.properties.clone());
+        }
+        arguments
+    }
+
+    /// The arguments which attach to the process of the config, by its pid or
+    /// its debug port. They're the ones of lldb-vscode, where the adapters which
+    /// attach otherwise, like by `processId`, are given the properties instead.
+    fn attach_arguments(config: &RunDebugConfig) -> Result<Value> {
+        let mut params = serde_json::Map::new();
+        if !config.program.is_empty() {
+            params.insert("program".to_string(), config.program.clone().into());
+        }
+        match (config.pid, config.port) {
+            (Some(pid), _) => {
+                params.insert("pid".to_string(), pid.into());
+            }
+            (None, Some(port)) => {
+                params.insert("gdb-remote-port".to_string(), port.into());
+                if let Some(host) = config.host.as_ref() {
+                    params.insert(
+                        "gdb-remote-hostname".to_string(),
+                        host.clone().into(),
+                    );
+                }
+            }
+            (None, None) if config.properties.is_empty() => {
+                return Err(anyhow!("attaching needs the pid or the port"));
+            }
+            (None, None) => {}
+        }
+        params.extend(config.properties.clone());
+        Ok(Value::Object(params))
+    }
+
+    /// The arguments of the launch or the attach of the config, which are the
+    /// configuration of the launch file when the config has one, with `noDebug`
+    /// when the program is only run.
+    fn debuggee_arguments(config: &RunDebugConfig) -> Result<Value> {
+        let mut arguments = match (&config.configuration, config.mode) {
+            (Some(configuration), _) => configuration.clone(),
+            (None, RunDebugConfigMode::Launch) => Self::launch_arguments(config),
+            (None, RunDebugConfigMode::Attach) => Self::attach_arguments(config)?,
+        };
+        if config.no_debug {
+            if let Value::Object(arguments) = &mut arguments {
+                arguments.insert("noDebug".to_string(), true.into());
+            }
+        }
+        Ok(arguments)
+    }
+
+    /// Launch or attach to the debuggee, by the mode of the config.
+    pub fn start_debuggee(&self, config: &RunDebugConfig) -> Result<()> {
+        let arguments = Self::debuggee_arguments(config)?;
+        let result = match config.mode {
+            RunDebugConfigMode::Launch => self.request::<Launch>(arguments),
+            RunDebugConfigMode::Attach => self.request::<Attach>(arguments),
+        };
+        result.map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    /// Restart the debuggee with the restart request, which is given the
+    /// arguments that it was started with.
+    fn restart_debuggee(&self, arguments: Value) -> Result<()> {
+        self.request::<Restart>(RestartArguments {
+            arguments: Some(arguments),
+        })
+        .map_err(|e| anyhow!(e.message))?;
         Ok(())
     }
 
@@ -599,6 +1285,22 @@ pub fn restart(&self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
         let _ = self.rpc_tx.send(DapRpc::Restart(breakpoints));
     }
 
+    fn relaunch(&self, breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>) {
+        let _ = self.rpc_tx.send(DapRpc::Relaunch(breakpoints));
+    }
+
+    pub fn set_watches(&self, watches: Vec<String>) {
+        let _ = self.rpc_tx.send(DapRpc::SetWatches(watches));
+    }
+
+    pub fn set_exception_filters(&self, filters: HashMap<String, bool>) {
+        let _ = self.rpc_tx.send(DapRpc::SetExceptionFilters(filters));
+    }
+
+    pub fn set_post_debug_task(&self, task: Task) {
+        let _ = self.rpc_tx.send(DapRpc::SetPostDebugTask(task));
+    }
+
     fn disconnected(&self) {
         let _ = self.rpc_tx.send(DapRpc::Disconnected);
     }
@@ -621,7 +1323,7 @@ pub fn set_breakpoints_async(
         breakpoints: Vec<SourceBreakpoint>,
         f: impl RpcCallback<SetBreakpointsResponse, RpcError> + 'static,
     ) {
-        println!("set breakpoints async");
+        debug!("set breakpoints async");
         let params = SetBreakpointsArguments {
             source: Source {
                 path: Some(file),
@@ -664,14 +1366,46 @@ pub fn set_breakpoints(
         Ok(resp)
     }
 
-    pub fn continue_thread(&self, thread_id: ThreadId) -> Result<ContinueResponse> {
-        let params = ContinueArguments { thread_id };
+    /// Resume the threads, or only the thread with `single_thread` when the
+    /// debugger supports single thread execution requests.
+    pub fn continue_thread(
+        &self,
+        thread_id: ThreadId,
+        single_thread: bool,
+    ) -> Result<ContinueResponse> {
+        let params = ContinueArguments {
+            thread_id,
+            single_thread,
+        };
         let resp = self
             .request::<Continue>(params)
             .map_err(|e| anyhow!(e.message))?;
         Ok(resp)
     }
 
+    /// Run the threads backwards until a breakpoint is hit, for a debugger
+    /// which supports stepping back.
+    pub fn reverse_continue(&self, thread_id: ThreadId) -> Result<()> {
+        let params = ReverseContinueArguments {
+            thread_id,
+            single_thread: false,
+        };
+        self.request::<ReverseContinue>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    /// Run the thread backwards by a step, for a debugger which supports that.
+    pub fn step_back(&self, thread_id: ThreadId) -> Result<()> {
+        let params = StepBackArguments {
+            thread_id,
+            single_thread: false,
+        };
+        self.request::<StepBack>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
     pub fn pause_thread(&self, thread_id: ThreadId) -> Result<()> {
         let params = PauseArguments { thread_id };
         self.request::<Pause>(params)
@@ -679,6 +1413,154 @@ pub fn pause_thread(&self, thread_id: ThreadId) -> Result<()> {
         Ok(())
     }
 
+    pub fn terminate_threads(&self, thread_ids: Vec<ThreadId>) -> Result<()> {
+        let params = TerminateThreadsArguments {
+            thread_ids: Some(thread_ids),
+        };
+        self.request::<TerminateThreads>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    pub fn set_exception_breakpoints(&self, filters: Vec<String>) -> Result<()> {
+        let params = SetExceptionBreakpointsArguments { filters };
+        self.request::<SetExceptionBreakpoints>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(())
+    }
+
+    /// Ask which data of a variable in the container of the reference can be
+    /// broken on when it changes.
+    pub fn data_breakpoint_info_async(
+        &self,
+        variables_reference: usize,
+        name: String,
+        f: impl RpcCallback<DataBreakpointInfoResponse, RpcError> + 'static,
+    ) {
+        if let Some(err) = self.data_breakpoints_unsupported() {
+            Box::new(f).call(Err(err));
+            return;
+        }
+        let params = DataBreakpointInfoArguments {
+            variables_reference: Some(variables_reference),
+            name,
+        };
+        self.request_async::<DataBreakpointInfo>(params, f);
+    }
+
+    pub fn set_variable_async(
+        &self,
+        variables_reference: usize,
+        name: String,
+        value: String,
+        f: impl RpcCallback<SetVariableResponse, RpcError> + 'static,
+    ) {
+        let params = SetVariableArguments {
+            variables_reference,
+            name,
+            value,
+        };
+        self.request_async::<SetVariable>(params, f);
+    }
+
+    pub fn restart_frame_async(
+        &self,
+        frame_id: usize,
+        f: impl RpcCallback<(), RpcError> + 'static,
+    ) {
+        let params = RestartFrameArguments { frame_id };
+        self.request_async::<RestartFrame>(params, f);
+    }
+
+    pub fn goto_targets_async(
+        &self,
+        path: PathBuf,
+        line: usize,
+        f: impl RpcCallback<GotoTargetsResponse, RpcError> + 'static,
+    ) {
+        let params = GotoTargetsArguments {
+            source: Source {
+                path: Some(path),
+                ..Default::default()
+            },
+            line,
+            column: None,
+        };
+        self.request_async::<GotoTargets>(params, f);
+    }
+
+    pub fn goto_async(
+        &self,
+        thread_id: ThreadId,
+        target_id: usize,
+        f: impl RpcCallback<(), RpcError> + 'static,
+    ) {
+        let params = GotoArguments {
+            thread_id,
+            target_id,
+        };
+        self.request_async::<Goto>(params, f);
+    }
+
+    pub fn read_memory_async(
+        &self,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl RpcCallback<ReadMemoryResponse, RpcError> + 'static,
+    ) {
+        let params = ReadMemoryArguments {
+            memory_reference,
+            offset: Some(offset),
+            count,
+        };
+        self.request_async::<ReadMemory>(params, f);
+    }
+
+    pub fn write_memory_async(
+        &self,
+        memory_reference: String,
+        offset: i64,
+        data: &[u8],
+        f: impl RpcCallback<WriteMemoryResponse, RpcError> + 'static,
+    ) {
+        let params = WriteMemoryArguments {
+            memory_reference,
+            offset: Some(offset),
+            allow_partial: None,
+            data: BASE64.encode(data),
+        };
+        self.request_async::<WriteMemory>(params, f);
+    }
+
+    pub fn set_expression_async(
+        &self,
+        expression: String,
+        value: String,
+        frame_id: Option<usize>,
+        f: impl RpcCallback<SetExpressionResponse, RpcError> + 'static,
+    ) {
+        let params = SetExpressionArguments {
+            expression,
+            value,
+            frame_id,
+        };
+        self.request_async::<SetExpression>(params, f);
+    }
+
+    pub fn set_data_breakpoints_async(
+        &self,
+        breakpoints: Vec<DataBreakpoint>,
+        f: impl RpcCallback<SetDataBreakpointsResponse, RpcError> + 'static,
+    ) {
+        if let Some(err) = self.data_breakpoints_unsupported() {
+            Box::new(f).call(Err(err));
+            return;
+        }
+        let params = SetDataBreakpointsArguments { breakpoints };
+        self.request_async::<SetDataBreakpoints>(params, f);
+    }
+
     pub fn threads(&self) -> Result<ThreadsResponse> {
         let resp = self
             .request::<Threads>(())
@@ -696,4 +1578,404 @@ pub fn stack_trace(&self, thread_id: ThreadId) -> Result<StackTraceResponse> {
             .map_err(|e| anyhow!(e.message))?;
         Ok(resp)
     }
+
+    pub fn scopes_async(
+        &self,
+        frame_id: usize,
+        f: impl RpcCallback<ScopesResponse, RpcError> + 'static,
+    ) {
+        let params = ScopesArguments { frame_id };
+        self.request_async::<Scopes>(params, f);
+    }
+
+    /// Get the children of the structured variable or the scope of the reference.
+    pub fn variables_async(
+        &self,
+        variables_reference: usize,
+        f: impl RpcCallback<VariablesResponse, RpcError> + 'static,
+    ) {
+        let params = VariablesArguments {
+            variables_reference,
+            ..Default::default()
+        };
+        self.request_async::<Variables>(params, f);
+    }
+
+    /// Evaluate the expression of the debug console in the frame.
+    pub fn evaluate_async(
+        &self,
+        expression: String,
+        frame_id: Option<usize>,
+        f: impl RpcCallback<EvaluateResponse, RpcError> + 'static,
+    ) {
+        let params = EvaluateArguments {
+            expression,
+            frame_id,
+            context: Some("repl".to_string()),
+        };
+        self.request_async::<Evaluate>(params, f);
+    }
+
+    pub fn evaluate(
+        &self,
+        expression: String,
+        frame_id: Option<usize>,
+        context: &str,
+    ) -> Result<EvaluateResponse> {
+        let params = EvaluateArguments {
+            expression,
+            frame_id,
+            context: Some(context.to_string()),
+        };
+        let resp = self
+            .request::<Evaluate>(params)
+            .map_err(|e| anyhow!(e.message))?;
+        Ok(resp)
+    }
+
+    /// Evaluate each watch expression in the frame, and send its result to the
+    /// editor as soon as it's known.
+    pub fn evaluate_watches(
+        &self,
+        core_rpc: &CoreRpcHandler,
+        watches: &[String],
+        frame_id: usize,
+    ) {
+        for expression in watches {
+            let result = self
+                .evaluate(expression.clone(), Some(frame_id), "watch")
+                .map_err(|e| e.to_string());
+            core_rpc.dap_watch_evaluated(
+                self.dap_id,
+                frame_id,
+                expression.clone(),
+                result,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::dap_types::DataBreakpointAccessType;
+    use serde_json::json;
+
+    use super::*;
+
+    fn config(value: Value) -> RunDebugConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_debuggee_arguments() {
+        let mut launch = config(json!({
+            "name": "run",
+            "program": "target/debug/app",
+            "args": ["-v"],
+            "env": { "RUST_LOG": "debug" },
+            "justMyCode": false,
+        }));
+        let arguments = DapRpcHandler::debuggee_arguments(&launch).unwrap();
+        assert_eq!(arguments["program"], "target/debug/app");
+        assert_eq!(arguments["args"], json!(["-v"]));
+        assert_eq!(arguments["env"], json!({ "RUST_LOG": "debug" }));
+        assert_eq!(arguments["justMyCode"], false);
+        assert_eq!(arguments["runInTerminal"], true);
+        assert_eq!(arguments.get("noDebug"), None);
+
+        launch.no_debug = true;
+        let arguments = DapRpcHandler::debuggee_arguments(&launch).unwrap();
+        assert_eq!(arguments["noDebug"], true);
+
+        // A child session is started with the arguments which the adapter gave
+        launch.configuration = Some(json!({ "request": "launch", "port": 9229 }));
+        let arguments = DapRpcHandler::debuggee_arguments(&launch).unwrap();
+        assert_eq!(
+            arguments,
+            json!({ "request": "launch", "port": 9229, "noDebug": true })
+        );
+    }
+
+    #[test]
+    fn test_attach_arguments() {
+        let attach = config(json!({
+            "name": "attach",
+            "program": "",
+            "mode": "attach",
+            "port": 1234,
+            "host": "10.0.0.2",
+        }));
+        let arguments = DapRpcHandler::debuggee_arguments(&attach).unwrap();
+        assert_eq!(
+            arguments,
+            json!({ "gdb-remote-port": 1234, "gdb-remote-hostname": "10.0.0.2" })
+        );
+
+        let attach = config(json!({
+            "name": "attach",
+            "program": "app",
+            "mode": "attach",
+            "pid": 42,
+        }));
+        let arguments = DapRpcHandler::debuggee_arguments(&attach).unwrap();
+        assert_eq!(arguments, json!({ "program": "app", "pid": 42 }));
+
+        // The pid goes before the port
+        let attach = config(json!({
+            "name": "attach",
+            "program": "",
+            "mode": "attach",
+            "pid": 42,
+            "port": 1234,
+        }));
+        let arguments = DapRpcHandler::debuggee_arguments(&attach).unwrap();
+        assert_eq!(arguments, json!({ "pid": 42 }));
+
+        // Adapters which attach otherwise are given the properties
+        let attach = config(json!({
+            "name": "attach",
+            "program": "",
+            "mode": "attach",
+            "processId": 42,
+        }));
+        let arguments = DapRpcHandler::debuggee_arguments(&attach).unwrap();
+        assert_eq!(arguments, json!({ "processId": 42 }));
+
+        // Without a pid or a port there's nothing to attach to, which is an error
+        // rather than a restart with no arguments
+        let attach = config(json!({
+            "name": "attach",
+            "program": "app",
+            "mode": "attach",
+        }));
+        assert!(DapRpcHandler::debuggee_arguments(&attach).is_err());
+    }
+
+    fn sent_request(handler: &DapRpcHandler) -> DapRequest {
+        match handler.io_rx.try_recv() {
+            Ok(DapPayload::Request(req)) => req,
+            _ => panic!("no request was sent"),
+        }
+    }
+
+    #[test]
+    fn test_request_timeout() {
+        let timeouts = DapRequestTimeouts {
+            default: 0,
+            commands: HashMap::from([("threads".to_string(), 1)]),
+        };
+        let handler = DapRpcHandler::new(DapId::next(), None, timeouts);
+        handler.supports_cancel.store(true, Ordering::Relaxed);
+
+        let err = handler.request::<Threads>(()).unwrap_err();
+        assert_eq!(err.message, "the threads request timed out");
+        let req = sent_request(&handler);
+        assert_eq!(req.command, "threads");
+
+        // The debugger is told to drop the request, whose handler is gone
+        let cancel = sent_request(&handler);
+        assert_eq!(cancel.command, "cancel");
+        assert_eq!(cancel.arguments.unwrap()["requestId"], req.seq);
+        let pending = handler.server_pending.lock();
+        assert!(!pending.contains_key(&req.seq));
+        assert!(pending.contains_key(&cancel.seq));
+    }
+
+    #[test]
+    fn test_fail_pending() {
+        let handler =
+            DapRpcHandler::new(DapId::next(), None, DapRequestTimeouts::default());
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        let seq = handler.request_common::<Threads>(
+            Threads::COMMAND,
+            (),
+            ResponseHandler::Chan(tx),
+        );
+        assert_eq!(sent_request(&handler).seq, seq);
+
+        handler.fail_pending("the debugger exited");
+        assert!(handler.server_pending.lock().is_empty());
+        let err = rx.try_recv().unwrap().unwrap_err();
+        assert_eq!(err.message, "the debugger exited");
+    }
+
+    #[test]
+    fn test_data_breakpoints() {
+        let handler =
+            DapRpcHandler::new(DapId::next(), None, DapRequestTimeouts::default());
+        handler.store_capabilities(&DebuggerCapabilities {
+            supports_data_breakpoints: Some(true),
+            ..Default::default()
+        });
+
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        handler.data_breakpoint_info_async(
+            7,
+            "count".to_string(),
+            move |result: Result<DataBreakpointInfoResponse, RpcError>| {
+                let _ = tx.send(result);
+            },
+        );
+        let req = sent_request(&handler);
+        assert_eq!(req.command, "dataBreakpointInfo");
+        assert_eq!(
+            req.arguments,
+            Some(json!({ "variablesReference": 7, "name": "count" }))
+        );
+
+        handler.handle_server_message(
+            &json!({
+                "type": "response",
+                "request_seq": req.seq,
+                "success": true,
+                "command": "dataBreakpointInfo",
+                "body": {
+                    "dataId": "0x10",
+                    "description": "count",
+                    "accessTypes": ["write"],
+                },
+            })
+            .to_string(),
+        );
+        let info = rx.try_recv().unwrap().unwrap();
+        assert_eq!(info.data_id.as_deref(), Some("0x10"));
+
+        // The data id which the debugger gave is what the breakpoint is set on
+        handler.set_data_breakpoints_async(
+            vec![DataBreakpoint {
+                data_id: info.data_id.unwrap(),
+                access_type: Some(DataBreakpointAccessType::Write),
+                condition: None,
+                hit_condition: None,
+            }],
+            |_: Result<SetDataBreakpointsResponse, RpcError>| {},
+        );
+        let req = sent_request(&handler);
+        assert_eq!(req.command, "setDataBreakpoints");
+        assert_eq!(
+            req.arguments,
+            Some(json!({
+                "breakpoints": [{ "dataId": "0x10", "accessType": "write" }],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_data_breakpoints_unsupported() {
+        let handler =
+            DapRpcHandler::new(DapId::next(), None, DapRequestTimeouts::default());
+
+        let (tx, rx) = crossbeam_channel::bounded(2);
+        let info_tx = tx.clone();
+        handler.data_breakpoint_info_async(
+            7,
+            "count".to_string(),
+            move |result: Result<DataBreakpointInfoResponse, RpcError>| {
+                let _ = info_tx.send(result.map(|_| ()));
+            },
+        );
+        handler.set_data_breakpoints_async(
+            Vec::new(),
+            move |result: Result<SetDataBreakpointsResponse, RpcError>| {
+                let _ = tx.send(result.map(|_| ()));
+            },
+        );
+
+        // The debugger isn't asked for what it said it can't do
+        assert!(handler.io_rx.try_recv().is_err());
+        for _ in 0..2 {
+            let err = rx.try_recv().unwrap().unwrap_err();
+            assert_eq!(err.message, "the debugger doesn't support data breakpoints");
+        }
+
+        // Until it says that it can
+        handler.store_capabilities(&DebuggerCapabilities {
+            supports_data_breakpoints: Some(true),
+            ..Default::default()
+        });
+        handler.set_data_breakpoints_async(
+            Vec::new(),
+            |_: Result<SetDataBreakpointsResponse, RpcError>| {},
+        );
+        assert_eq!(sent_request(&handler).command, "setDataBreakpoints");
+    }
+
+    #[test]
+    fn test_server_args() {
+        let args = vec![
+            "--port".to_string(),
+            "${port}".to_string(),
+            "--listen=127.0.0.1:${port}".to_string(),
+        ];
+        assert_eq!(
+            server_args(&args, 4711),
+            vec!["--port", "4711", "--listen=127.0.0.1:4711"]
+        );
+        assert_ne!(free_port().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_connect_with_retry() {
+        // The adapter listens a while after it's started
+        let port = free_port().unwrap();
+        let listener = thread::spawn(move || {
+            thread::sleep(CONNECT_RETRY_INTERVAL * 3);
+            let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+            listener.accept().unwrap();
+        });
+        connect_with_retry(CONNECT_TIMEOUT, || {
+            TcpStream::connect(("127.0.0.1", port))
+        })
+        .unwrap();
+        listener.join().unwrap();
+
+        // And is given up on when it doesn't
+        let attempts = AtomicU64::new(0);
+        let err = connect_with_retry(CONNECT_RETRY_INTERVAL, || {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>(io::Error::from(io::ErrorKind::ConnectionRefused))
+        })
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("failed to connect to the debug adapter"));
+        assert!(attempts.load(Ordering::Relaxed) > 1);
+    }
+
+    #[test]
+    fn test_child_config() {
+        let mut parent = config(json!({
+            "name": "run",
+            "program": "node",
+            "args": ["app.js"],
+        }));
+        parent.debug_command = Some("node --inspect app.js".to_string());
+        let args: StartDebuggingRequestArguments = serde_json::from_value(json!({
+            "configuration": { "name": "worker", "type": "pwa-node", "port": 9230 },
+            "request": "attach",
+        }))
+        .unwrap();
+        let child = DapClient::child_config(&parent, args);
+        assert_eq!(child.name, "worker");
+        assert_eq!(child.mode, RunDebugConfigMode::Attach);
+        assert_eq!(child.parent, Some(parent.dap_id));
+        assert_ne!(child.dap_id, parent.dap_id);
+        assert_eq!(
+            child.configuration,
+            Some(json!({ "name": "worker", "type": "pwa-node", "port": 9230 }))
+        );
+        // The child is started by the adapter rather than by a command
+        assert_eq!(child.debug_command, None);
+        assert_eq!(child.program, parent.program);
+
+        // Without a name the child is shown with the one of its parent
+        let args: StartDebuggingRequestArguments = serde_json::from_value(json!({
+            "configuration": {},
+            "request": "launch",
+        }))
+        .unwrap();
+        let child = DapClient::child_config(&parent, args);
+        assert_eq!(child.name, "run");
+        assert_eq!(child.mode, RunDebugConfigMode::Launch);
+    }
 }
@@ -0,0 +1,477 @@
+//! The debug adapters which are downloaded into the plugins directory, so that a
+//! config can be debugged without an adapter on the PATH. An adapter is looked
+//! up by its name, or by the `type` of the config, and it's installed from its
+//! latest release when it isn't there yet. A download is checked against the
+//! sha256 which its release publishes, and an adapter which is installed is
+//! checked for a newer release once a day.
+
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use lapce_core::directory::Directory;
+use lapce_rpc::{
+    core::CoreRpcHandler,
+    dap_types::{DapServer, DapTransport},
+};
+use lsp_types::{MessageType, ShowMessageParams};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tar::Archive;
+
+const ADAPTERS_DIR: &str = "debug-adapters";
+const MANIFEST_FILE: &str = "adapter.json";
+const UPDATE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Held while an adapter is installed, so that two sessions which start at the
+/// same time don't download it twice
+static INSTALLING: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegistryAdapter {
+    CodeLldb,
+    Debugpy,
+    JsDebug,
+}
+
+impl RegistryAdapter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegistryAdapter::CodeLldb => "codelldb",
+            RegistryAdapter::Debugpy => "debugpy",
+            RegistryAdapter::JsDebug => "js-debug",
+        }
+    }
+
+    /// The adapter by its name, or by the `type` of a config which it debugs.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "codelldb" | "lldb" => Some(RegistryAdapter::CodeLldb),
+            "debugpy" | "python" => Some(RegistryAdapter::Debugpy),
+            "js-debug" | "node" | "pwa-node" | "node-terminal" | "chrome"
+            | "pwa-chrome" | "msedge" | "pwa-msedge" => {
+                Some(RegistryAdapter::JsDebug)
+            }
+            _ => None,
+        }
+    }
+
+    /// The server of the adapter in the folder which it's installed in.
+    fn server(&self, dir: &Path, cwd: Option<PathBuf>) -> DapServer {
+        let path = |path: PathBuf| path.to_string_lossy().to_string();
+        // The adapters which listen on a port get a free one
+        let tcp = DapTransport::Tcp {
+            host: None,
+            port: 0,
+        };
+        match self {
+            RegistryAdapter::CodeLldb => DapServer {
+                program: path(codelldb_program(dir)),
+                args: vec!["--port".to_string(), "${port}".to_string()],
+                cwd,
+                transport: tcp,
+            },
+            // The adapter folder of the package is run as a script, which puts
+            // the package on the path of python itself
+            RegistryAdapter::Debugpy => DapServer {
+                program: if cfg!(windows) { "python" } else { "python3" }
+                    .to_string(),
+                args: vec![path(dir.join("debugpy").join("adapter"))],
+                cwd,
+                transport: DapTransport::Stdio,
+            },
+            RegistryAdapter::JsDebug => DapServer {
+                program: "node".to_string(),
+                args: vec![
+                    path(dir.join("js-debug").join("src").join("dapDebugServer.js")),
+                    "${port}".to_string(),
+                    "127.0.0.1".to_string(),
+                ],
+                cwd,
+                transport: tcp,
+            },
+        }
+    }
+
+    fn latest_release(&self) -> Result<Release> {
+        match self {
+            RegistryAdapter::CodeLldb => {
+                let names = codelldb_assets();
+                let release = fetch_json(
+                    "https://api.github.com/repos/vadimcn/codelldb/releases/latest",
+                )?;
+                github_release(&release, |name| names.iter().any(|n| n == name))
+            }
+            RegistryAdapter::Debugpy => {
+                let package = fetch_json("https://pypi.org/pypi/debugpy/json")?;
+                pypi_release(&package)
+            }
+            RegistryAdapter::JsDebug => {
+                let release = fetch_json(
+                    "https://api.github.com/repos/microsoft/vscode-js-debug/releases/latest",
+                )?;
+                github_release(&release, |name| {
+                    name.starts_with("js-debug-dap-") && name.ends_with(".tar.gz")
+                })
+            }
+        }
+    }
+}
+
+/// The names of the vsix of codelldb for this platform, which is named like a
+/// platform of vscode since 1.11, and by the target before that.
+fn codelldb_assets() -> Vec<String> {
+    let arch = std::env::consts::ARCH;
+    let (os, vscode_os) = match std::env::consts::OS {
+        "macos" => ("darwin", "darwin"),
+        "windows" => ("windows", "win32"),
+        os => (os, os),
+    };
+    let vscode_arch = match arch {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        arch => arch,
+    };
+    vec![
+        format!("codelldb-{vscode_os}-{vscode_arch}.vsix"),
+        format!("codelldb-{arch}-{os}.vsix"),
+    ]
+}
+
+fn codelldb_program(dir: &Path) -> PathBuf {
+    let program = if cfg!(windows) {
+        "codelldb.exe"
+    } else {
+        "codelldb"
+    };
+    dir.join("extension").join("adapter").join(program)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArchiveKind {
+    /// A vsix or a wheel
+    Zip,
+    TarGz,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Release {
+    version: String,
+    url: String,
+    sha256: String,
+    archive: ArchiveKind,
+}
+
+/// What's installed in the folder of an adapter.
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: String,
+    sha256: String,
+    /// When the latest release was last looked for, in seconds since the epoch
+    checked: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn fetch(url: &str) -> Result<reqwest::blocking::Response> {
+    // The api of GitHub turns down the requests without a user agent
+    let resp = reqwest::blocking::Client::builder()
+        .user_agent("lapce")
+        .timeout(Duration::from_secs(300))
+        .build()?
+        .get(url)
+        .send()?
+        .error_for_status()?;
+    Ok(resp)
+}
+
+fn fetch_json(url: &str) -> Result<Value> {
+    Ok(fetch(url)?.json()?)
+}
+
+/// The asset of the release of GitHub whose name matches, with the sha256 of
+/// its digest.
+fn github_release(
+    release: &Value,
+    matches: impl Fn(&str) -> bool,
+) -> Result<Release> {
+    let version = release
+        .get("tag_name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("the release has no tag"))?;
+    let asset = release
+        .get("assets")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .find(|asset| {
+            asset
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map(&matches)
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| {
+            anyhow!("the release {version} has nothing for this platform")
+        })?;
+    let name = asset
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    let url = asset
+        .get("browser_download_url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("{name} has no download url"))?;
+    let sha256 = asset
+        .get("digest")
+        .and_then(|v| v.as_str())
+        .and_then(|digest| digest.strip_prefix("sha256:"))
+        .ok_or_else(|| anyhow!("the release {version} has no sha256 of {name}"))?;
+    Ok(Release {
+        version: version.trim_start_matches('v').to_string(),
+        url: url.to_string(),
+        sha256: sha256.to_lowercase(),
+        archive: if name.ends_with(".tar.gz") {
+            ArchiveKind::TarGz
+        } else {
+            ArchiveKind::Zip
+        },
+    })
+}
+
+/// The wheel of the package of PyPI which runs on any platform, which the
+/// adapter of debugpy is in.
+fn pypi_release(package: &Value) -> Result<Release> {
+    let version = package
+        .get("info")
+        .and_then(|info| info.get("version"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("the package has no version"))?;
+    let wheel = package
+        .get("urls")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .find(|file| {
+            file.get("filename")
+                .and_then(|v| v.as_str())
+                .map(|name| name.ends_with("-none-any.whl"))
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| anyhow!("debugpy {version} has no wheel for any platform"))?;
+    let url = wheel
+        .get("url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("the wheel of debugpy {version} has no url"))?;
+    let sha256 = wheel
+        .get("digests")
+        .and_then(|digests| digests.get("sha256"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("the wheel of debugpy {version} has no sha256"))?;
+    Ok(Release {
+        version: version.to_string(),
+        url: url.to_string(),
+        sha256: sha256.to_lowercase(),
+        archive: ArchiveKind::Zip,
+    })
+}
+
+fn adapter_dir(adapter: RegistryAdapter) -> Result<PathBuf> {
+    let dir = Directory::plugins_directory()
+        .ok_or_else(|| anyhow!("can't get plugin directory"))?
+        .join(ADAPTERS_DIR)
+        .join(adapter.name());
+    Ok(dir)
+}
+
+fn read_manifest(dir: &Path) -> Option<Manifest> {
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<()> {
+    fs::write(dir.join(MANIFEST_FILE), serde_json::to_string(manifest)?)?;
+    Ok(())
+}
+
+/// Download the release, check it against its sha256, and unpack it into the
+/// folder of the adapter. The adapter which was there is only replaced once the
+/// new one is unpacked.
+fn install(adapter: RegistryAdapter, release: &Release) -> Result<()> {
+    let dir = adapter_dir(adapter)?;
+    let download = dir.with_extension("download");
+    let _ = fs::remove_dir_all(&download);
+    fs::create_dir_all(&download)?;
+
+    let bytes = fetch(&release.url)?.bytes()?;
+    let sha256 = format!("{:x}", Sha256::digest(&bytes));
+    if sha256 != release.sha256 {
+        return Err(anyhow!(
+            "the download of {} {} has the sha256 {sha256} instead of {}",
+            adapter.name(),
+            release.version,
+            release.sha256
+        ));
+    }
+    match release.archive {
+        ArchiveKind::Zip => {
+            zip::ZipArchive::new(Cursor::new(&bytes[..]))?.extract(&download)?
+        }
+        ArchiveKind::TarGz => {
+            Archive::new(GzDecoder::new(&bytes[..])).unpack(&download)?
+        }
+    }
+    #[cfg(unix)]
+    if adapter == RegistryAdapter::CodeLldb {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(
+            codelldb_program(&download),
+            fs::Permissions::from_mode(0o755),
+        )?;
+    }
+    write_manifest(
+        &download,
+        &Manifest {
+            version: release.version.clone(),
+            sha256,
+            checked: now(),
+        },
+    )?;
+
+    // The old adapter is moved away first, which fails on Windows while it
+    // runs, and then the one which was installed is kept
+    let old = dir.with_extension("old");
+    let _ = fs::remove_dir_all(&old);
+    if dir.exists() {
+        fs::rename(&dir, &old)?;
+    }
+    fs::rename(&download, &dir)?;
+    let _ = fs::remove_dir_all(&old);
+    Ok(())
+}
+
+/// Install the latest release of the adapter when it's newer than the one which
+/// is installed. The release is looked for again a day later at the earliest.
+fn update(adapter: RegistryAdapter) -> Result<()> {
+    let _installing = INSTALLING.lock();
+    let dir = adapter_dir(adapter)?;
+    let Some(mut manifest) = read_manifest(&dir) else {
+        return Ok(());
+    };
+    if now().saturating_sub(manifest.checked) < UPDATE_INTERVAL.as_secs() {
+        return Ok(());
+    }
+    let release = adapter.latest_release()?;
+    if release.version == manifest.version {
+        manifest.checked = now();
+        return write_manifest(&dir, &manifest);
+    }
+    install(adapter, &release)?;
+    tracing::info!("updated {} to {}", adapter.name(), release.version);
+    Ok(())
+}
+
+/// The server of the adapter of the registry, which is downloaded first when
+/// it isn't installed. An adapter which is installed already is updated in the
+/// background, for the sessions after this one.
+pub fn resolve_adapter(
+    adapter: RegistryAdapter,
+    cwd: Option<PathBuf>,
+    core_rpc: &CoreRpcHandler,
+) -> Result<DapServer> {
+    let dir = adapter_dir(adapter)?;
+    let installing = INSTALLING.lock();
+    if read_manifest(&dir).is_some() {
+        drop(installing);
+        thread::spawn(move || {
+            if let Err(e) = update(adapter) {
+                tracing::error!("failed to update {}: {e}", adapter.name());
+            }
+        });
+    } else {
+        let release = adapter
+            .latest_release()
+            .with_context(|| format!("failed to find {}", adapter.name()))?;
+        core_rpc.show_message(
+            "Debug Adapter".to_string(),
+            ShowMessageParams {
+                typ: MessageType::INFO,
+                message: format!(
+                    "Downloading {} {} into {}",
+                    adapter.name(),
+                    release.version,
+                    dir.display()
+                ),
+            },
+        );
+        install(adapter, &release).with_context(|| {
+            format!("failed to install {} {}", adapter.name(), release.version)
+        })?;
+    }
+    Ok(adapter.server(&dir, cwd))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(
+            RegistryAdapter::from_name("lldb"),
+            Some(RegistryAdapter::CodeLldb)
+        );
+        assert_eq!(
+            RegistryAdapter::from_name("pwa-node"),
+            Some(RegistryAdapter::JsDebug)
+        );
+        assert_eq!(RegistryAdapter::from_name("go"), None);
+    }
+
+    #[test]
+    fn test_github_release() {
+        let release = json!({
+            "tag_name": "v1.11.0",
+            "assets": [
+                {
+                    "name": "codelldb-linux-x64.vsix",
+                    "browser_download_url": "https://example.com/linux.vsix",
+                    "digest": "sha256:ABC123",
+                },
+                {
+                    "name": "codelldb-darwin-arm64.vsix",
+                    "browser_download_url": "https://example.com/darwin.vsix",
+                },
+            ],
+        });
+        assert_eq!(
+            github_release(&release, |name| name == "codelldb-linux-x64.vsix")
+                .unwrap(),
+            Release {
+                version: "1.11.0".to_string(),
+                url: "https://example.com/linux.vsix".to_string(),
+                sha256: "abc123".to_string(),
+                archive: ArchiveKind::Zip,
+            }
+        );
+        // A download without a digest can't be checked, so it isn't used
+        assert!(github_release(&release, |name| name.contains("darwin")).is_err());
+        assert!(github_release(&release, |name| name.contains("win32")).is_err());
+    }
+}
@@ -3,16 +3,16 @@
 use std::{
     io::{BufRead, BufReader, BufWriter, Write},
     path::{Path, PathBuf},
-    process::{self, Child, Command, Stdio},
+    process::{self, Child, Stdio},
     sync::Arc,
     thread,
 };
 
 use anyhow::{anyhow, Result};
-use crossbeam_channel::Sender;
-use jsonrpc_lite::{Id, Params};
+use crossbeam_channel::{Receiver, Sender};
+use jsonrpc_lite::{Id, JsonRpc, Params};
 use lapce_core::meta;
-use lapce_rpc::{plugin::VoltID, style::LineStyle, RpcError};
+use lapce_rpc::{crash::Subsystem, plugin::VoltID, style::LineStyle, RpcError};
 use lapce_xi_rope::Rope;
 use lsp_types::{
     notification::{Initialized, Notification},
@@ -21,15 +21,18 @@
 };
 use parking_lot::Mutex;
 use serde_json::Value;
+use tracing::error;
 
 use super::{
     client_capabilities,
     psp::{
         handle_plugin_server_message, PluginHandlerNotification, PluginHostHandler,
-        PluginServerHandler, PluginServerRpcHandler, RpcCallback,
+        PluginServerHandler, PluginServerRpcHandler, ResponseHandler, RpcCallback,
     },
 };
-use crate::{buffer::Buffer, plugin::PluginCatalogRpcHandler};
+use crate::{
+    buffer::Buffer, crash::spawn_supervised, plugin::PluginCatalogRpcHandler,
+};
 
 const HEADER_CONTENT_LENGTH: &str = "content-length";
 const HEADER_CONTENT_TYPE: &str = "content-type";
@@ -57,7 +60,9 @@ pub enum LspRpc {
 pub struct LspClient {
     plugin_rpc: PluginCatalogRpcHandler,
     server_rpc: PluginServerRpcHandler,
-    process: Child,
+    /// The process of the server, which the servers that run in the proxy
+    /// don't have
+    process: Option<Child>,
     workspace: Option<PathBuf>,
     host: PluginHostHandler,
     options: Option<Value>,
@@ -68,6 +73,20 @@ fn method_registered(&mut self, method: &'static str) -> bool {
         self.host.method_registered(method)
     }
 
+    fn encode_server_request(
+        &mut self,
+        method: &'static str,
+        params: Params,
+        path: Option<&Path>,
+        rh: ResponseHandler<Value, RpcError>,
+    ) -> (Params, ResponseHandler<Value, RpcError>) {
+        self.host.encode_server_request(method, params, path, rh)
+    }
+
+    fn handle_server_notification(&mut self, method: &'static str, params: &Params) {
+        self.host.handle_server_notification(method, params);
+    }
+
     fn document_supported(
         &mut self,
         lanaguage_id: Option<&str>,
@@ -86,7 +105,9 @@ fn handle_handler_notification(
                 self.initialize();
             }
             InitializeResult(result) => {
-                self.host.server_capabilities = result.capabilities;
+                if let Err(e) = self.host.set_server_capabilities(result) {
+                    error!("invalid initialize result: {e}");
+                }
             }
             Shutdown => {
                 self.shutdown();
@@ -205,9 +226,13 @@ fn new(
 
         let local_server_rpc = server_rpc.clone();
         let core_rpc = plugin_rpc.core_rpc.clone();
-        thread::spawn(move || {
-            let mut reader = Box::new(BufReader::new(stdout));
-            loop {
+        let local_volt_id = volt_id.clone();
+        let mut reader = Box::new(BufReader::new(stdout));
+        spawn_supervised(
+            Subsystem::LanguageServer,
+            volt_display_name.clone(),
+            plugin_rpc.core_rpc.clone(),
+            move || loop {
                 match read_message(&mut reader) {
                     Ok(message_str) => {
                         if let Some(resp) = handle_plugin_server_message(
@@ -218,17 +243,17 @@ fn new(
                         }
                     }
                     Err(_err) => {
-                        core_rpc.log(
-                            tracing::Level::ERROR,
-                            format!("lsp server {server} stopped!"),
+                        error!("lsp server {server} stopped!");
+                        core_rpc.plugin_server_stopped(
+                            local_volt_id.clone(),
+                            server.clone(),
                         );
                         return;
                     }
                 };
-            }
-        });
+            },
+        );
 
-        let core_rpc = plugin_rpc.core_rpc.clone();
         thread::spawn(move || {
             let mut reader = Box::new(BufReader::new(stderr));
             loop {
@@ -238,10 +263,7 @@ fn new(
                         if n == 0 {
                             return;
                         }
-                        core_rpc.log(
-                            tracing::Level::ERROR,
-                            format!("lsp server stderr: {}", line.trim_end()),
-                        );
+                        error!("lsp server stderr: {}", line.trim_end());
                     }
                     Err(_) => {
                         return;
@@ -263,7 +285,7 @@ fn new(
         Ok(Self {
             plugin_rpc,
             server_rpc,
-            process,
+            process: Some(process),
             workspace,
             host,
             options,
@@ -282,6 +304,8 @@ pub fn start(
         args: Vec<String>,
         options: Option<Value>,
     ) -> Result<()> {
+        let core_rpc = plugin_rpc.core_rpc.clone();
+        let name = volt_display_name.clone();
         let mut lsp = Self::new(
             plugin_rpc,
             document_selector,
@@ -294,12 +318,69 @@ pub fn start(
             options,
         )?;
         let rpc = lsp.server_rpc.clone();
-        thread::spawn(move || {
+        spawn_supervised(Subsystem::LanguageServer, name, core_rpc, move || {
             rpc.mainloop(&mut lsp);
         });
         Ok(())
     }
 
+    /// Start a language server which runs on a thread of the proxy rather than
+    /// as a process. The server gets the messages of the client from the
+    /// receiver and sends its own ones with the sender.
+    #[allow(clippy::too_many_arguments)]
+    pub fn start_in_process(
+        plugin_rpc: PluginCatalogRpcHandler,
+        document_selector: DocumentSelector,
+        workspace: Option<PathBuf>,
+        volt_id: VoltID,
+        volt_display_name: String,
+        options: Option<Value>,
+        server: impl FnOnce(Receiver<JsonRpc>, Sender<JsonRpc>) + Send + 'static,
+    ) {
+        let (io_tx, io_rx) = crossbeam_channel::unbounded();
+        let (server_tx, server_rx) = crossbeam_channel::unbounded();
+        let server_rpc = PluginServerRpcHandler::new(volt_id.clone(), io_tx.clone());
+        thread::spawn(move || server(io_rx, server_tx));
+
+        let local_server_rpc = server_rpc.clone();
+        thread::spawn(move || {
+            for msg in server_rx {
+                let Ok(msg) = serde_json::to_string(&msg) else {
+                    continue;
+                };
+                if let Some(resp) =
+                    handle_plugin_server_message(&local_server_rpc, &msg)
+                {
+                    let _ = io_tx.send(resp);
+                }
+            }
+        });
+
+        let core_rpc = plugin_rpc.core_rpc.clone();
+        let name = volt_display_name.clone();
+        let host = PluginHostHandler::new(
+            workspace.clone(),
+            None,
+            volt_id,
+            volt_display_name,
+            document_selector,
+            server_rpc.clone(),
+            plugin_rpc.clone(),
+        );
+        let mut lsp = Self {
+            plugin_rpc,
+            server_rpc,
+            process: None,
+            workspace,
+            host,
+            options,
+        };
+        let rpc = lsp.server_rpc.clone();
+        spawn_supervised(Subsystem::LanguageServer, name, core_rpc, move || {
+            rpc.mainloop(&mut lsp);
+        });
+    }
+
     fn initialize(&mut self) {
         let root_uri = self
             .workspace
@@ -332,8 +413,9 @@ fn initialize(&mut self) {
             None,
             false,
         ) {
-            let result: InitializeResult = serde_json::from_value(value).unwrap();
-            self.host.server_capabilities = result.capabilities;
+            if let Err(e) = self.host.set_server_capabilities(value) {
+                error!("invalid initialize result: {e}");
+            }
             self.server_rpc.server_notification(
                 Initialized::METHOD,
                 InitializedParams {},
@@ -363,8 +445,10 @@ fn initialize(&mut self) {
     }
 
     fn shutdown(&mut self) {
-        let _ = self.process.kill();
-        let _ = self.process.wait();
+        if let Some(process) = self.process.as_mut() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
     }
 
     fn process(
@@ -372,7 +456,7 @@ fn process(
         server: &str,
         args: &[String],
     ) -> Result<Child> {
-        let mut process = Command::new(server);
+        let mut process = crate::limits::server_command(server);
         if let Some(workspace) = workspace {
             process.current_dir(workspace);
         }
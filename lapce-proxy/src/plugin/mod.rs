@@ -1,7 +1,9 @@
 pub mod catalog;
 pub mod dap;
+pub mod dap_registry;
 pub mod lsp;
 pub mod psp;
+pub mod schema;
 pub mod wasi;
 
 use std::{
@@ -19,12 +21,22 @@
 use crossbeam_channel::{Receiver, Sender};
 use dyn_clone::DynClone;
 use flate2::read::GzDecoder;
-use lapce_core::directory::Directory;
+use lapce_core::{directory::Directory, encoding::PositionEncoding};
 use lapce_rpc::{
     core::CoreRpcHandler,
-    dap_types::{DapId, RunDebugConfig, SourceBreakpoint, ThreadId},
+    dap_types::{
+        DapId, DapRequestTimeouts, DapServer, DataBreakpoint,
+        DataBreakpointInfoResponse, EvaluateResponse, GotoTargetsResponse,
+        ReadMemoryResponse, RunDebugConfig, Scope, SetExpressionResponse,
+        SetVariableResponse, SourceBreakpoint, ThreadId, Variable,
+        WriteMemoryResponse,
+    },
+    inline_value::{
+        InlineValue, InlineValueContext, InlineValueParams, InlineValueRequest,
+    },
     plugin::{PluginId, VoltInfo, VoltMetadata},
     proxy::ProxyRpcHandler,
+    runnable::{Runnable, Runnables, RunnablesParams},
     style::LineStyle,
     terminal::TermId,
     RequestId, RpcError,
@@ -45,10 +57,11 @@
     CodeActionResponse, CompletionClientCapabilities, CompletionItem,
     CompletionItemCapability, CompletionItemCapabilityResolveSupport,
     CompletionParams, CompletionResponse, Diagnostic, DocumentFormattingParams,
-    DocumentSymbolParams, DocumentSymbolResponse, FormattingOptions, GotoCapability,
-    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverClientCapabilities,
-    HoverParams, InlayHint, InlayHintClientCapabilities, InlayHintParams, Location,
-    MarkupKind, MessageActionItemCapabilities, ParameterInformationSettings,
+    DocumentSymbolParams, DocumentSymbolResponse, FormattingOptions,
+    GeneralClientCapabilities, GotoCapability, GotoDefinitionParams,
+    GotoDefinitionResponse, Hover, HoverClientCapabilities, HoverParams, InlayHint,
+    InlayHintClientCapabilities, InlayHintParams, Location, MarkupKind,
+    MessageActionItemCapabilities, ParameterInformationSettings,
     PartialResultParams, Position, PrepareRenameResponse, Range, ReferenceContext,
     ReferenceParams, RenameParams, SelectionRange, SelectionRangeParams,
     SemanticTokens, SemanticTokensClientCapabilities, SemanticTokensParams,
@@ -65,6 +78,7 @@
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 use tar::Archive;
+use tracing::error;
 
 use self::{
     catalog::PluginCatalog,
@@ -102,6 +116,73 @@ pub enum PluginCatalogRpc {
     DidOpenTextDocument {
         document: TextDocumentItem,
     },
+    DapScopes {
+        dap_id: DapId,
+        frame_id: usize,
+        f: Box<dyn RpcCallback<Vec<Scope>, RpcError>>,
+    },
+    DapVariables {
+        dap_id: DapId,
+        reference: usize,
+        f: Box<dyn RpcCallback<Vec<Variable>, RpcError>>,
+    },
+    DapEvaluate {
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        f: Box<dyn RpcCallback<EvaluateResponse, RpcError>>,
+    },
+    DapDataBreakpointInfo {
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: Box<dyn RpcCallback<DataBreakpointInfoResponse, RpcError>>,
+    },
+    DapSetVariable {
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: Box<dyn RpcCallback<SetVariableResponse, RpcError>>,
+    },
+    DapSetExpression {
+        dap_id: DapId,
+        expression: String,
+        value: String,
+        frame_id: Option<usize>,
+        f: Box<dyn RpcCallback<SetExpressionResponse, RpcError>>,
+    },
+    DapRestartFrame {
+        dap_id: DapId,
+        frame_id: usize,
+        f: Box<dyn RpcCallback<(), RpcError>>,
+    },
+    DapGotoTargets {
+        dap_id: DapId,
+        path: PathBuf,
+        line: usize,
+        f: Box<dyn RpcCallback<GotoTargetsResponse, RpcError>>,
+    },
+    DapGoto {
+        dap_id: DapId,
+        thread_id: ThreadId,
+        target_id: usize,
+        f: Box<dyn RpcCallback<(), RpcError>>,
+    },
+    DapReadMemory {
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: Box<dyn RpcCallback<ReadMemoryResponse, RpcError>>,
+    },
+    DapWriteMemory {
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        data: Vec<u8>,
+        f: Box<dyn RpcCallback<WriteMemoryResponse, RpcError>>,
+    },
     DidChangeTextDocument {
         language_id: String,
         document: VersionedTextDocumentIdentifier,
@@ -134,6 +215,13 @@ pub enum PluginCatalogNotification {
         config: RunDebugConfig,
         breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
     },
+    /// A child session which an adapter asked for, started with the adapter of
+    /// its parent
+    DapStartChild {
+        server: DapServer,
+        config: RunDebugConfig,
+        breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+    },
     DapProcessId {
         dap_id: DapId,
         process_id: Option<u32>,
@@ -143,10 +231,26 @@ pub enum PluginCatalogNotification {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    DapContinueThread {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
     DapPause {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    DapReverseContinue {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepBack {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapTerminateThreads {
+        dap_id: DapId,
+        thread_ids: Vec<ThreadId>,
+    },
     DapStop {
         dap_id: DapId,
     },
@@ -162,6 +266,23 @@ pub enum PluginCatalogNotification {
         path: PathBuf,
         breakpoints: Vec<SourceBreakpoint>,
     },
+    DapSetWatches {
+        expressions: Vec<String>,
+    },
+    DapEvaluateWatches {
+        dap_id: DapId,
+        frame_id: usize,
+    },
+    DapSetExceptionFilters {
+        filters: HashMap<String, bool>,
+    },
+    DapSetRequestTimeouts {
+        timeouts: DapRequestTimeouts,
+    },
+    DapSetDataBreakpoints {
+        dap_id: DapId,
+        breakpoints: Vec<DataBreakpoint>,
+    },
     Shutdown,
 }
 
@@ -247,6 +368,108 @@ pub fn mainloop(&self, plugin: &mut PluginCatalog) {
                 PluginCatalogRpc::DidOpenTextDocument { document } => {
                     plugin.handle_did_open_text_document(document);
                 }
+                PluginCatalogRpc::DapScopes {
+                    dap_id,
+                    frame_id,
+                    f,
+                } => {
+                    plugin.dap_scopes(dap_id, frame_id, f);
+                }
+                PluginCatalogRpc::DapVariables {
+                    dap_id,
+                    reference,
+                    f,
+                } => {
+                    plugin.dap_variables(dap_id, reference, f);
+                }
+                PluginCatalogRpc::DapEvaluate {
+                    dap_id,
+                    expression,
+                    frame_id,
+                    f,
+                } => {
+                    plugin.dap_evaluate(dap_id, expression, frame_id, f);
+                }
+                PluginCatalogRpc::DapDataBreakpointInfo {
+                    dap_id,
+                    reference,
+                    name,
+                    f,
+                } => {
+                    plugin.dap_data_breakpoint_info(dap_id, reference, name, f);
+                }
+                PluginCatalogRpc::DapSetVariable {
+                    dap_id,
+                    reference,
+                    name,
+                    value,
+                    f,
+                } => {
+                    plugin.dap_set_variable(dap_id, reference, name, value, f);
+                }
+                PluginCatalogRpc::DapSetExpression {
+                    dap_id,
+                    expression,
+                    value,
+                    frame_id,
+                    f,
+                } => {
+                    plugin
+                        .dap_set_expression(dap_id, expression, value, frame_id, f);
+                }
+                PluginCatalogRpc::DapRestartFrame {
+                    dap_id,
+                    frame_id,
+                    f,
+                } => {
+                    plugin.dap_restart_frame(dap_id, frame_id, f);
+                }
+                PluginCatalogRpc::DapGotoTargets {
+                    dap_id,
+                    path,
+                    line,
+                    f,
+                } => {
+                    plugin.dap_goto_targets(dap_id, path, line, f);
+                }
+                PluginCatalogRpc::DapGoto {
+                    dap_id,
+                    thread_id,
+                    target_id,
+                    f,
+                } => {
+                    plugin.dap_goto(dap_id, thread_id, target_id, f);
+                }
+                PluginCatalogRpc::DapReadMemory {
+                    dap_id,
+                    memory_reference,
+                    offset,
+                    count,
+                    f,
+                } => {
+                    plugin.dap_read_memory(
+                        dap_id,
+                        memory_reference,
+                        offset,
+                        count,
+                        f,
+                    );
+                }
+                PluginCatalogRpc::DapWriteMemory {
+                    dap_id,
+                    memory_reference,
+                    offset,
+                    data,
+                    f,
+                } => {
+                    plugin.dap_write_memory(
+                        dap_id,
+                        memory_reference,
+                        offset,
+                        data,
+                        f,
+                    );
+                }
                 PluginCatalogRpc::DidSaveTextDocument {
                     language_id,
                     path,
@@ -387,6 +610,175 @@ pub fn format_semantic_tokens(
         });
     }
 
+    pub fn dap_scopes(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: impl RpcCallback<Vec<Scope>, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapScopes {
+            dap_id,
+            frame_id,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_variables(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        f: impl RpcCallback<Vec<Variable>, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapVariables {
+            dap_id,
+            reference,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        f: impl RpcCallback<EvaluateResponse, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapEvaluate {
+            dap_id,
+            expression,
+            frame_id,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_data_breakpoint_info(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: impl RpcCallback<DataBreakpointInfoResponse, RpcError> + 'static,
+    ) {
+        let _ = self
+            .plugin_tx
+            .send(PluginCatalogRpc::DapDataBreakpointInfo {
+                dap_id,
+                reference,
+                name,
+                f: Box::new(f),
+            });
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: impl RpcCallback<SetVariableResponse, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapSetVariable {
+            dap_id,
+            reference,
+            name,
+            value,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_set_expression(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        value: String,
+        frame_id: Option<usize>,
+        f: impl RpcCallback<SetExpressionResponse, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapSetExpression {
+            dap_id,
+            expression,
+            value,
+            frame_id,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_restart_frame(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: impl RpcCallback<(), RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapRestartFrame {
+            dap_id,
+            frame_id,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_goto_targets(
+        &self,
+        dap_id: DapId,
+        path: PathBuf,
+        line: usize,
+        f: impl RpcCallback<GotoTargetsResponse, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapGotoTargets {
+            dap_id,
+            path,
+            line,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_goto(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+        target_id: usize,
+        f: impl RpcCallback<(), RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapGoto {
+            dap_id,
+            thread_id,
+            target_id,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl RpcCallback<ReadMemoryResponse, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapReadMemory {
+            dap_id,
+            memory_reference,
+            offset,
+            count,
+            f: Box::new(f),
+        });
+    }
+
+    pub fn dap_write_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        data: Vec<u8>,
+        f: impl RpcCallback<WriteMemoryResponse, RpcError> + 'static,
+    ) {
+        let _ = self.plugin_tx.send(PluginCatalogRpc::DapWriteMemory {
+            dap_id,
+            memory_reference,
+            offset,
+            data,
+            f: Box::new(f),
+        });
+    }
+
     pub fn did_save_text_document(&self, path: &Path, text: Rope) {
         let text_document =
             TextDocumentIdentifier::new(Url::from_file_path(path).unwrap());
@@ -519,10 +911,13 @@ pub fn get_references(
         );
     }
 
+    /// Get the code actions for the range of the file, which are only the ones
+    /// of the kinds when they're given.
     pub fn get_code_actions(
         &self,
         path: &Path,
-        position: Position,
+        range: Range,
+        only: Option<Vec<CodeActionKind>>,
         diagnostics: Vec<Diagnostic>,
         cb: impl FnOnce(PluginId, Result<CodeActionResponse, RpcError>)
             + Clone
@@ -533,14 +928,8 @@ pub fn get_code_actions(
         let method = CodeActionRequest::METHOD;
         let params = CodeActionParams {
             text_document: TextDocumentIdentifier { uri },
-            range: Range {
-                start: position,
-                end: position,
-            },
-            context: CodeActionContext {
-                diagnostics,
-                only: None,
-            },
+            range,
+            context: CodeActionContext { diagnostics, only },
             work_done_progress_params: WorkDoneProgressParams::default(),
             partial_result_params: PartialResultParams::default(),
         };
@@ -582,6 +971,35 @@ pub fn get_inlay_hints(
         );
     }
 
+    pub fn get_inline_values(
+        &self,
+        path: &Path,
+        range: Range,
+        context: InlineValueContext,
+        cb: impl FnOnce(PluginId, Result<Option<Vec<InlineValue>>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = InlineValueRequest::METHOD;
+        let params = InlineValueParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            range,
+            context,
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
     pub fn get_document_symbols(
         &self,
         path: &Path,
@@ -766,6 +1184,31 @@ pub fn get_selection_range(
         );
     }
 
+    pub fn get_runnables(
+        &self,
+        path: &Path,
+        cb: impl FnOnce(PluginId, Result<Vec<Runnable>, RpcError>)
+            + Clone
+            + Send
+            + 'static,
+    ) {
+        let uri = Url::from_file_path(path).unwrap();
+        let method = Runnables::METHOD;
+        let params = RunnablesParams {
+            text_document: TextDocumentIdentifier { uri },
+            position: None,
+        };
+        let language_id =
+            Some(language_id_from_path(path).unwrap_or("").to_string());
+        self.send_request_to_all_plugins(
+            method,
+            params,
+            language_id,
+            Some(path.to_path_buf()),
+            cb,
+        );
+    }
+
     pub fn hover(
         &self,
         path: &Path,
@@ -900,8 +1343,16 @@ pub fn signature_help(
             Some(path.to_path_buf()),
             move |plugin_id, result| {
                 if let Ok(value) = result {
-                    if let Ok(resp) = serde_json::from_value::<SignatureHelp>(value)
+                    // No signature is sent as an empty one, for the editor to
+                    // hide the one which it shows
+                    if let Ok(resp) =
+                        serde_json::from_value::<Option<SignatureHelp>>(value)
                     {
+                        let resp = resp.unwrap_or(SignatureHelp {
+                            signatures: Vec::new(),
+                            active_signature: None,
+                            active_parameter: None,
+                        });
                         core_rpc
                             .signature_help_response(request_id, resp, plugin_id);
                     }
@@ -1019,6 +1470,19 @@ pub fn dap_start(
         })
     }
 
+    pub fn dap_start_child(
+        &self,
+        server: DapServer,
+        config: RunDebugConfig,
+        breakpoints: HashMap<PathBuf, Vec<SourceBreakpoint>>,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapStartChild {
+            server,
+            config,
+            breakpoints,
+        })
+    }
+
     pub fn dap_process_id(
         &self,
         dap_id: DapId,
@@ -1039,6 +1503,17 @@ pub fn dap_continue(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
         })
     }
 
+    pub fn dap_continue_thread(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapContinueThread {
+            dap_id,
+            thread_id,
+        })
+    }
+
     pub fn dap_pause(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
         self.catalog_notification(PluginCatalogNotification::DapPause {
             dap_id,
@@ -1046,6 +1521,35 @@ pub fn dap_pause(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
         })
     }
 
+    pub fn dap_reverse_continue(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapReverseContinue {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_step_back(&self, dap_id: DapId, thread_id: ThreadId) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapStepBack {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_terminate_threads(
+        &self,
+        dap_id: DapId,
+        thread_ids: Vec<ThreadId>,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapTerminateThreads {
+            dap_id,
+            thread_ids,
+        })
+    }
+
     pub fn dap_stop(&self, dap_id: DapId) -> Result<()> {
         self.catalog_notification(PluginCatalogNotification::DapStop { dap_id })
     }
@@ -1079,6 +1583,52 @@ pub fn dap_set_breakpoints(
             breakpoints,
         })
     }
+
+    pub fn dap_set_watches(&self, expressions: Vec<String>) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapSetWatches {
+            expressions,
+        })
+    }
+
+    pub fn dap_evaluate_watches(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapEvaluateWatches {
+            dap_id,
+            frame_id,
+        })
+    }
+
+    pub fn dap_set_exception_filters(
+        &self,
+        filters: HashMap<String, bool>,
+    ) -> Result<()> {
+        self.catalog_notification(
+            PluginCatalogNotification::DapSetExceptionFilters { filters },
+        )
+    }
+
+    pub fn dap_set_request_timeouts(
+        &self,
+        timeouts: DapRequestTimeouts,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapSetRequestTimeouts {
+            timeouts,
+        })
+    }
+
+    pub fn dap_set_data_breakpoints(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<DataBreakpoint>,
+    ) -> Result<()> {
+        self.catalog_notification(PluginCatalogNotification::DapSetDataBreakpoints {
+            dap_id,
+            breakpoints,
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1189,7 +1739,7 @@ pub fn remove_volt(
             anyhow::anyhow!("don't have dir")
         })?;
         if let Err(e) = std::fs::remove_dir_all(path) {
-            eprintln!("Could not delete plugin folder: {e}");
+            error!("Could not delete plugin folder: {e}");
             catalog_rpc.core_rpc.volt_removing(
                 volt.clone(),
                 "Could not remove Plugin Directory".to_string(),
@@ -1213,8 +1763,17 @@ fn client_capabilities() -> ClientCapabilities {
             completion: Some(CompletionClientCapabilities {
                 completion_item: Some(CompletionItemCapability {
                     snippet_support: Some(true),
+                    commit_characters_support: Some(true),
+                    documentation_format: Some(vec![
+                        MarkupKind::Markdown,
+                        MarkupKind::PlainText,
+                    ]),
                     resolve_support: Some(CompletionItemCapabilityResolveSupport {
-                        properties: vec!["additionalTextEdits".to_string()],
+                        properties: vec![
+                            "additionalTextEdits".to_string(),
+                            "documentation".to_string(),
+                            "detail".to_string(),
+                        ],
                     }),
                     ..Default::default()
                 }),
@@ -1263,6 +1822,7 @@ fn client_capabilities() -> ClientCapabilities {
                                 .to_string(),
                             "quickassist".to_string(),
                             "source.fixAll".to_string(),
+                            "source.removeUnusedImports".to_string(),
                         ],
                     },
                 }),
@@ -1298,6 +1858,10 @@ fn client_capabilities() -> ClientCapabilities {
             configuration: Some(false),
             ..Default::default()
         }),
+        general: Some(GeneralClientCapabilities {
+            position_encodings: Some(PositionEncoding::supported()),
+            ..Default::default()
+        }),
         ..Default::default()
     }
 }
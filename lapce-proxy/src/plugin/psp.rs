@@ -14,19 +14,24 @@
 use jsonrpc_lite::{Id, JsonRpc, Params};
 use lapce_core::{
     buffer::rope_text::{RopeText, RopeTextRef},
-    encoding::offset_utf16_to_utf8,
+    encoding::PositionEncoding,
 };
 use lapce_rpc::{
-    plugin::{PluginId, VoltID},
+    coverage::Coverage,
+    inline_value::InlineValueRequest,
+    plugin::{
+        PluginId, RemoveStatusItemParams, SetTestsParams, StatusItemParams, VoltID,
+    },
+    runnable::Runnables,
     style::{LineStyle, Style},
     RpcError,
 };
 use lapce_xi_rope::{Rope, RopeDelta};
 use lsp_types::{
     notification::{
-        DidChangeTextDocument, DidOpenTextDocument, DidSaveTextDocument,
-        Initialized, LogMessage, Notification, Progress, PublishDiagnostics,
-        ShowMessage,
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        DidSaveTextDocument, Initialized, LogMessage, Notification, Progress,
+        PublishDiagnostics, ShowMessage,
     },
     request::{
         CodeActionRequest, CodeActionResolveRequest, Completion,
@@ -36,15 +41,16 @@
         SelectionRangeRequest, SemanticTokensFullRequest, SignatureHelpRequest,
         WorkDoneProgressCreate, WorkspaceSymbol,
     },
-    CodeActionProviderCapability, DidChangeTextDocumentParams,
+    CodeActionProviderCapability, CompletionOptions, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams,
     DidSaveTextDocumentParams, DocumentSelector, HoverProviderCapability,
-    InitializeResult, LogMessageParams, OneOf, ProgressParams,
+    InitializeResult, LogMessageParams, OneOf, Position, ProgressParams,
     PublishDiagnosticsParams, Range, Registration, RegistrationParams,
     SemanticTokens, SemanticTokensLegend, SemanticTokensServerCapabilities,
     ServerCapabilities, ShowMessageParams, TextDocumentContentChangeEvent,
     TextDocumentIdentifier, TextDocumentSaveRegistrationOptions,
     TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncSaveOptions,
-    VersionedTextDocumentIdentifier,
+    Url, VersionedTextDocumentIdentifier,
 };
 use parking_lot::Mutex;
 use psp_types::{
@@ -53,12 +59,22 @@
 };
 use serde::Serialize;
 use serde_json::Value;
+use tracing::{debug_span, error, warn};
 
 use super::{
     lsp::{DocumentFilter, LspClient},
     PluginCatalogRpcHandler,
 };
 
+/// Sent by a plugin to show or update an item in the status bar
+const SET_STATUS_ITEM_METHOD: &str = "lapce/setStatusItem";
+/// Sent by a plugin to remove one of its status bar items
+const REMOVE_STATUS_ITEM_METHOD: &str = "lapce/removeStatusItem";
+/// Sent by a plugin to replace the tests which it found for the test explorer
+const SET_TESTS_METHOD: &str = "lapce/setTests";
+/// Sent by a plugin to replace the coverage which is shown
+const SET_COVERAGE_METHOD: &str = "lapce/setCoverage";
+
 pub enum ResponseHandler<Resp, Error> {
     Chan(Sender<Result<Resp, Error>>),
     Callback(Box<dyn RpcCallback<Resp, Error>>),
@@ -99,7 +115,8 @@ fn call(self: Box<F>, result: Result<Resp, Error>) {
 
 pub enum PluginHandlerNotification {
     Initialize,
-    InitializeResult(InitializeResult),
+    /// The result of the initialize request of the server
+    InitializeResult(Value),
     Shutdown,
 }
 
@@ -174,6 +191,14 @@ fn document_supported(
         path: Option<&Path>,
     ) -> bool;
     fn method_registered(&mut self, method: &'static str) -> bool;
+    fn encode_server_request(
+        &mut self,
+        method: &'static str,
+        params: Params,
+        path: Option<&Path>,
+        rh: ResponseHandler<Value, RpcError>,
+    ) -> (Params, ResponseHandler<Value, RpcError>);
+    fn handle_server_notification(&mut self, method: &'static str, params: &Params);
     fn handle_host_notification(&mut self, method: String, params: Params);
     fn handle_host_request(
         &mut self,
@@ -391,10 +416,17 @@ pub fn mainloop<H>(&self, handler: &mut H)
                     path,
                     rh,
                 } => {
+                    let _span = debug_span!("server_request", method).entered();
                     if handler
                         .document_supported(language_id.as_deref(), path.as_deref())
                         && handler.method_registered(method)
                     {
+                        let (params, rh) = handler.encode_server_request(
+                            method,
+                            params,
+                            path.as_deref(),
+                            rh,
+                        );
                         self.send_server_request(id, method, params, rh);
                     } else {
                         rh.invoke(Err(RpcError {
@@ -409,10 +441,12 @@ pub fn mainloop<H>(&self, handler: &mut H)
                     language_id,
                     path,
                 } => {
+                    let _span = debug_span!("server_notification", method).entered();
                     if handler
                         .document_supported(language_id.as_deref(), path.as_deref())
                         && handler.method_registered(method)
                     {
+                        handler.handle_server_notification(method, &params);
                         self.send_server_notification(method, params);
                     }
                 }
@@ -422,9 +456,11 @@ pub fn mainloop<H>(&self, handler: &mut H)
                     params,
                     chan,
                 } => {
+                    let _span = debug_span!("host_request", %method).entered();
                     handler.handle_host_request(id, method, params, chan);
                 }
                 PluginServerRpc::HostNotification { method, params } => {
+                    let _span = debug_span!("host_notification", %method).entered();
                     handler.handle_host_notification(method, params);
                 }
                 PluginServerRpc::DidSaveTextDocument {
@@ -525,7 +561,7 @@ pub fn handle_plugin_server_message(
             None
         }
         Err(err) => {
-            eprintln!("parse error {err} message {message}");
+            error!("parse error {err} message {message}");
             None
         }
     }
@@ -550,7 +586,16 @@ pub struct PluginHostHandler {
     catalog_rpc: PluginCatalogRpcHandler,
     pub server_rpc: PluginServerRpcHandler,
     pub server_capabilities: ServerCapabilities,
+    /// Whether the server gives the inline values of the debugger, which the
+    /// server capabilities of the lsp types don't have yet
+    inline_value_provider: bool,
     server_registrations: ServerRegistrations,
+    /// The texts of the open documents, which the positions of a server that
+    /// doesn't count in UTF-16 are converted with
+    documents: Arc<Mutex<HashMap<Url, Rope>>>,
+    /// The document of the last completion, which is where the completion items
+    /// which are resolved are from
+    completion_uri: Option<String>,
 }
 
 impl PluginHostHandler {
@@ -576,7 +621,128 @@ pub fn new(
             catalog_rpc,
             server_rpc,
             server_capabilities: ServerCapabilities::default(),
+            inline_value_provider: false,
             server_registrations: ServerRegistrations::default(),
+            documents: Arc::new(Mutex::new(HashMap::new())),
+            completion_uri: None,
+        }
+    }
+
+    /// Keep the capabilities of the server from the result of its initialize
+    /// request.
+    pub fn set_server_capabilities(&mut self, result: Value) -> Result<()> {
+        self.inline_value_provider = inline_value_provider(&result);
+        let result: InitializeResult = serde_json::from_value(result)?;
+        self.server_capabilities = result.capabilities;
+        Ok(())
+    }
+
+    /// What the server counts the characters of positions in
+    pub fn position_encoding(&self) -> PositionEncoding {
+        PositionEncoding::from_lsp(
+            self.server_capabilities.position_encoding.as_ref(),
+        )
+    }
+
+    /// The editor counts the characters of positions in UTF-16, so when the server
+    /// doesn't, the positions of a request to it are converted to its encoding,
+    /// and the ones of the response back. The items of a completion get the
+    /// commit characters which the server has for all of them as well.
+    pub fn encode_server_request(
+        &mut self,
+        method: &'static str,
+        params: Params,
+        path: Option<&Path>,
+        rh: ResponseHandler<Value, RpcError>,
+    ) -> (Params, ResponseHandler<Value, RpcError>) {
+        let rh = match &self.server_capabilities.completion_provider {
+            Some(CompletionOptions {
+                all_commit_characters: Some(characters),
+                ..
+            }) if method == Completion::METHOD => {
+                with_commit_characters(rh, characters.clone())
+            }
+            _ => rh,
+        };
+        let encoding = self.position_encoding();
+        if encoding == PositionEncoding::Utf16 {
+            return (params, rh);
+        }
+        let mut value = match serde_json::to_value(&params) {
+            Ok(value) => value,
+            Err(_) => return (params, rh),
+        };
+
+        let uri = value
+            .get("textDocument")
+            .and_then(|document| document.get("uri"))
+            .and_then(Value::as_str)
+            .map(|uri| uri.to_string())
+            .or_else(|| {
+                path.and_then(|path| Url::from_file_path(path).ok())
+                    .map(|uri| uri.to_string())
+            })
+            .or_else(|| {
+                (method == ResolveCompletionItem::METHOD)
+                    .then(|| self.completion_uri.clone())
+                    .flatten()
+            });
+        if method == Completion::METHOD {
+            self.completion_uri = uri.clone();
+        }
+
+        convert_positions(
+            &mut value,
+            uri.as_deref(),
+            &self.documents.lock(),
+            PositionEncoding::Utf16,
+            encoding,
+        );
+        let documents = self.documents.clone();
+        let rh = ResponseHandler::Callback(Box::new(
+            move |result: Result<Value, RpcError>| {
+                let result = result.map(|mut value| {
+                    convert_positions(
+                        &mut value,
+                        uri.as_deref(),
+                        &documents.lock(),
+                        encoding,
+                        PositionEncoding::Utf16,
+                    );
+                    value
+                });
+                rh.invoke(result);
+            },
+        ));
+        (Params::from(value), rh)
+    }
+
+    pub fn handle_server_notification(&mut self, method: &str, params: &Params) {
+        if self.position_encoding() == PositionEncoding::Utf16 {
+            return;
+        }
+        let Ok(params) = serde_json::to_value(params) else {
+            return;
+        };
+        match method {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) =
+                    serde_json::from_value::<DidOpenTextDocumentParams>(params)
+                {
+                    self.documents.lock().insert(
+                        params.text_document.uri,
+                        Rope::from(params.text_document.text),
+                    );
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) =
+                    serde_json::from_value::<DidCloseTextDocumentParams>(params)
+                {
+                    self.documents.lock().remove(&params.text_document.uri);
+                }
+            }
+            _ => {}
         }
     }
 
@@ -705,6 +871,7 @@ pub fn method_registered(&mut self, method: &'static str) -> bool {
             InlayHintRequest::METHOD => {
                 self.server_capabilities.inlay_hint_provider.is_some()
             }
+            InlineValueRequest::METHOD => self.inline_value_provider,
             DocumentSymbolRequest::METHOD => {
                 self.server_capabilities.document_symbol_provider.is_some()
             }
@@ -721,6 +888,13 @@ pub fn method_registered(&mut self, method: &'static str) -> bool {
             CodeActionResolveRequest::METHOD => {
                 self.server_capabilities.code_action_provider.is_some()
             }
+            // rust-analyzer has the runnables in its experimental capabilities
+            Runnables::METHOD => self
+                .server_capabilities
+                .experimental
+                .as_ref()
+                .and_then(|experimental| experimental.get("runnables"))
+                .is_some(),
             _ => false,
         }
     }
@@ -791,7 +965,7 @@ fn register_capability(&mut self, registration: Registration) -> Result<()> {
                 });
             }
             _ => {
-                eprintln!(
+                warn!(
                     "don't handle register capability for {}",
                     registration.method
                 );
@@ -872,8 +1046,19 @@ pub fn handle_notification(
                 });
             }
             PublishDiagnostics::METHOD => {
+                let mut value = serde_json::to_value(params)?;
+                let encoding = self.position_encoding();
+                if encoding != PositionEncoding::Utf16 {
+                    convert_positions(
+                        &mut value,
+                        None,
+                        &self.documents.lock(),
+                        encoding,
+                        PositionEncoding::Utf16,
+                    );
+                }
                 let diagnostics: PublishDiagnosticsParams =
-                    serde_json::from_value(serde_json::to_value(params)?)?;
+                    serde_json::from_value(value)?;
                 self.catalog_rpc.core_rpc.publish_diagnostics(diagnostics);
             }
             Progress::METHOD => {
@@ -892,8 +1077,34 @@ pub fn handle_notification(
                     serde_json::from_value(serde_json::to_value(params)?)?;
                 self.catalog_rpc.core_rpc.log_message(message);
             }
+            SET_STATUS_ITEM_METHOD => {
+                let item: StatusItemParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                self.catalog_rpc
+                    .core_rpc
+                    .set_status_item(self.volt_id.clone(), item);
+            }
+            REMOVE_STATUS_ITEM_METHOD => {
+                let params: RemoveStatusItemParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                self.catalog_rpc
+                    .core_rpc
+                    .remove_status_item(self.volt_id.clone(), params.id);
+            }
+            SET_TESTS_METHOD => {
+                let params: SetTestsParams =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                self.catalog_rpc
+                    .core_rpc
+                    .set_tests(self.volt_id.clone(), params.tests);
+            }
+            SET_COVERAGE_METHOD => {
+                let coverage: Coverage =
+                    serde_json::from_value(serde_json::to_value(params)?)?;
+                self.catalog_rpc.core_rpc.coverage_changed(coverage);
+            }
             _ => {
-                eprintln!("host notificaton {method} not handled");
+                warn!("host notification {method} not handled");
             }
         }
         Ok(())
@@ -949,6 +1160,12 @@ pub fn handle_did_change_text_document(
             }
             None => TextDocumentSyncKind::NONE,
         };
+        let encoding = self.position_encoding();
+        if encoding != PositionEncoding::Utf16 {
+            self.documents
+                .lock()
+                .insert(document.uri.clone(), new_text.clone());
+        }
 
         let mut existing = change.lock();
         let change = match kind {
@@ -966,16 +1183,21 @@ pub fn handle_did_change_text_document(
                 }
             }
             TextDocumentSyncKind::INCREMENTAL => {
-                if let Some(c) = existing.1.as_ref() {
+                // The change which is kept for the other servers is in UTF-16
+                let utf16 = encoding == PositionEncoding::Utf16;
+                if let Some(c) = existing.1.as_ref().filter(|_| utf16) {
                     c.clone()
                 } else {
-                    let change = get_document_content_change(&text, &delta)
-                        .unwrap_or_else(|| TextDocumentContentChangeEvent {
-                            range: None,
-                            range_length: None,
-                            text: new_text.to_string(),
-                        });
-                    existing.1 = Some(change.clone());
+                    let change =
+                        get_document_content_change(&text, &delta, encoding)
+                            .unwrap_or_else(|| TextDocumentContentChangeEvent {
+                                range: None,
+                                range_length: None,
+                                text: new_text.to_string(),
+                            });
+                    if utf16 {
+                        existing.1 = Some(change.clone());
+                    }
                     change
                 }
             }
@@ -1009,6 +1231,7 @@ pub fn format_semantic_tokens(
             &text,
             self.server_capabilities.semantic_tokens_provider.as_ref(),
             &tokens,
+            self.position_encoding(),
         )
         .ok_or_else(|| RpcError {
             code: 0,
@@ -1021,6 +1244,7 @@ pub fn format_semantic_tokens(
 fn get_document_content_change(
     text: &Rope,
     delta: &RopeDelta,
+    encoding: PositionEncoding,
 ) -> Option<TextDocumentContentChangeEvent> {
     let (interval, _) = delta.summary();
     let (start, end) = interval.start_end();
@@ -1030,9 +1254,9 @@ fn get_document_content_change(
     // TODO: Handle more trivial cases like typing when there's a selection or transpose
     if let Some(node) = delta.as_simple_insert() {
         let (start, end) = interval.start_end();
-        let start = text.offset_to_position(start);
+        let start = text.offset_to_encoded_position(start, encoding);
 
-        let end = text.offset_to_position(end);
+        let end = text.offset_to_encoded_position(end, encoding);
 
         let text = String::from(node);
         let text_document_content_change_event = TextDocumentContentChangeEvent {
@@ -1045,9 +1269,9 @@ fn get_document_content_change(
     }
     // Or a simple delete
     else if delta.is_simple_delete() {
-        let end_position = text.offset_to_position(end);
+        let end_position = text.offset_to_encoded_position(end, encoding);
 
-        let start = text.offset_to_position(start);
+        let start = text.offset_to_encoded_position(start, encoding);
 
         let text_document_content_change_event = TextDocumentContentChangeEvent {
             range: Some(Range {
@@ -1068,6 +1292,7 @@ fn format_semantic_styles(
     text: &Rope,
     semantic_tokens_provider: Option<&SemanticTokensServerCapabilities>,
     tokens: &SemanticTokens,
+    encoding: PositionEncoding,
 ) -> Option<Vec<LineStyle>> {
     let semantic_tokens_provider = semantic_tokens_provider?;
     let semantic_legends = semantic_tokens_legend(semantic_tokens_provider);
@@ -1083,16 +1308,29 @@ fn format_semantic_styles(
             start = text.offset_of_line(line);
         }
 
-        let sub_text = text.char_indices_iter(start..);
-        start += offset_utf16_to_utf8(sub_text, semantic_token.delta_start as usize);
-
-        let sub_text = text.char_indices_iter(start..);
-        let end =
-            start + offset_utf16_to_utf8(sub_text, semantic_token.length as usize);
+        start = text.offset_after_encoded(
+            start,
+            semantic_token.delta_start as usize,
+            encoding,
+        );
+        let end = text.offset_after_encoded(
+            start,
+            semantic_token.length as usize,
+            encoding,
+        );
 
-        let kind = semantic_legends.token_types[semantic_token.token_type as usize]
+        let mut kind = semantic_legends.token_types
+            [semantic_token.token_type as usize]
             .as_str()
             .to_string();
+        for (i, modifier) in
+            semantic_legends.token_modifiers.iter().enumerate().take(32)
+        {
+            if semantic_token.token_modifiers_bitset & (1 << i) != 0 {
+                kind.push('.');
+                kind.push_str(modifier.as_str());
+            }
+        }
         if start < last_start {
             continue;
         }
@@ -1109,6 +1347,132 @@ fn format_semantic_styles(
     Some(highlights)
 }
 
+/// Gives the items of the response to a completion the commit characters, when
+/// they have none of their own.
+fn with_commit_characters(
+    rh: ResponseHandler<Value, RpcError>,
+    characters: Vec<String>,
+) -> ResponseHandler<Value, RpcError> {
+    ResponseHandler::Callback(Box::new(move |result: Result<Value, RpcError>| {
+        let result = result.map(|mut value| {
+            let items = match &mut value {
+                Value::Array(items) => Some(items),
+                Value::Object(list) => {
+                    list.get_mut("items").and_then(Value::as_array_mut)
+                }
+                _ => None,
+            };
+            for item in items.into_iter().flatten() {
+                if let Value::Object(item) = item {
+                    item.entry("commitCharacters")
+                        .or_insert_with(|| characters.clone().into());
+                }
+            }
+            value
+        });
+        rh.invoke(result);
+    }))
+}
+
+/// Converts the `{ line, character }` positions in the JSON of a message to or
+/// from a server which doesn't count in UTF-16. A position is in the document of
+/// the nearest `uri` around it, or of the uri which is its key in a map like the
+/// `changes` of a workspace edit, and else in the document of the request.
+fn convert_positions(
+    value: &mut Value,
+    uri: Option<&str>,
+    documents: &HashMap<Url, Rope>,
+    from: PositionEncoding,
+    to: PositionEncoding,
+) {
+    PositionConverter {
+        documents,
+        texts: HashMap::new(),
+        from,
+        to,
+    }
+    .convert(value, uri);
+}
+
+struct PositionConverter<'a> {
+    documents: &'a HashMap<Url, Rope>,
+    /// The texts which were looked up, where the documents which aren't open are
+    /// read from disk
+    texts: HashMap<String, Option<Rope>>,
+    from: PositionEncoding,
+    to: PositionEncoding,
+}
+
+impl PositionConverter<'_> {
+    fn text(&mut self, uri: &str) -> Option<&Rope> {
+        let documents = self.documents;
+        self.texts
+            .entry(uri.to_string())
+            .or_insert_with(|| {
+                let uri = Url::parse(uri).ok()?;
+                documents.get(&uri).cloned().or_else(|| {
+                    let path = uri.to_file_path().ok()?;
+                    std::fs::read_to_string(path).ok().map(Rope::from)
+                })
+            })
+            .as_ref()
+    }
+
+    fn convert(&mut self, value: &mut Value, uri: Option<&str>) {
+        match value {
+            Value::Array(values) => {
+                for value in values {
+                    self.convert(value, uri);
+                }
+            }
+            Value::Object(map) => {
+                let line = map.get("line").and_then(Value::as_u64);
+                let character = map.get("character").and_then(Value::as_u64);
+                if let (2, Some(line), Some(character)) =
+                    (map.len(), line, character)
+                {
+                    let (from, to) = (self.from, self.to);
+                    if let Some(text) = uri.and_then(|uri| self.text(uri)) {
+                        let position = RopeTextRef::new(text).convert_position(
+                            &Position::new(line as u32, character as u32),
+                            from,
+                            to,
+                        );
+                        map.insert(
+                            "character".to_string(),
+                            position.character.into(),
+                        );
+                    }
+                    return;
+                }
+
+                let own_uri = ["uri", "targetUri"]
+                    .iter()
+                    .find_map(|key| map.get(*key))
+                    .or_else(|| {
+                        map.get("textDocument")
+                            .and_then(|document| document.get("uri"))
+                    })
+                    .and_then(Value::as_str)
+                    .map(|uri| uri.to_string());
+                for (key, value) in map.iter_mut() {
+                    let uri = if key == "originSelectionRange" {
+                        // The range of a location link which is in the document
+                        // of the request
+                        uri
+                    } else if key.starts_with("file:") {
+                        Some(key.as_str())
+                    } else {
+                        own_uri.as_deref().or(uri)
+                    };
+                    self.convert(value, uri);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 fn semantic_tokens_legend(
     semantic_tokens_provider: &SemanticTokensServerCapabilities,
 ) -> &SemanticTokensLegend {
@@ -1121,3 +1485,129 @@ fn semantic_tokens_legend(
         ) => &options.semantic_tokens_options.legend,
     }
 }
+
+/// Whether the server gives the inline values of the debugger, from the result
+/// of its initialize request, where the provider is `true` or its options.
+fn inline_value_provider(result: &Value) -> bool {
+    result
+        .pointer("/capabilities/inlineValueProvider")
+        .map(|provider| provider.as_bool().unwrap_or(provider.is_object()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn convert(
+        mut value: Value,
+        uri: Option<&str>,
+        from: PositionEncoding,
+        to: PositionEncoding,
+    ) -> Value {
+        let documents = HashMap::from([
+            (
+                Url::parse("file:///a.rs").unwrap(),
+                Rope::from("a\u{e9}\u{1f600}b\n"),
+            ),
+            (
+                Url::parse("file:///b.rs").unwrap(),
+                Rope::from("\u{1f600}\u{1f600}\n"),
+            ),
+        ]);
+        convert_positions(&mut value, uri, &documents, from, to);
+        value
+    }
+
+    fn position(character: u32) -> Value {
+        json!({ "line": 0, "character": character })
+    }
+
+    #[test]
+    fn test_convert_positions() {
+        let (utf8, utf16) = (PositionEncoding::Utf8, PositionEncoding::Utf16);
+
+        // The position is in the document of the request
+        let params = json!({
+            "textDocument": { "uri": "file:///a.rs" },
+            "position": position(4),
+        });
+        let converted = convert(params.clone(), None, utf16, utf8);
+        assert_eq!(converted["position"], position(7));
+        assert_eq!(convert(converted, None, utf8, utf16), params);
+
+        // The uri of the request is used when the message has none
+        assert_eq!(
+            convert(position(3), Some("file:///a.rs"), utf8, utf16),
+            position(2)
+        );
+        assert_eq!(
+            convert(position(4), Some("file:///b.rs"), utf8, utf16),
+            position(2)
+        );
+
+        // A location link has its origin in the document of the request
+        let link = json!([{
+            "originSelectionRange": { "start": position(1), "end": position(4) },
+            "targetUri": "file:///b.rs",
+            "targetRange": { "start": position(0), "end": position(2) },
+        }]);
+        assert_eq!(
+            convert(link, Some("file:///a.rs"), utf16, utf8),
+            json!([{
+                "originSelectionRange": { "start": position(1), "end": position(7) },
+                "targetUri": "file:///b.rs",
+                "targetRange": { "start": position(0), "end": position(4) },
+            }])
+        );
+
+        // The changes of a workspace edit are in the documents of their keys
+        let edit = json!({
+            "changes": {
+                "file:///a.rs": [{
+                    "range": { "start": position(4), "end": position(5) },
+                    "newText": "c",
+                }],
+                "file:///b.rs": [{
+                    "range": { "start": position(2), "end": position(4) },
+                    "newText": "c",
+                }],
+            },
+        });
+        let converted = convert(edit, Some("file:///b.rs"), utf16, utf8);
+        assert_eq!(
+            converted["changes"]["file:///a.rs"][0]["range"],
+            json!({ "start": position(7), "end": position(8) })
+        );
+        assert_eq!(
+            converted["changes"]["file:///b.rs"][0]["range"],
+            json!({ "start": position(4), "end": position(8) })
+        );
+
+        // An object which has more than a line and a character isn't a position,
+        // and a document which can't be read is left as it is
+        let value = json!({ "line": 0, "character": 4, "text": "x" });
+        assert_eq!(
+            convert(value.clone(), Some("file:///a.rs"), utf16, utf8),
+            value
+        );
+        assert_eq!(
+            convert(position(4), Some("file:///missing.rs"), utf16, utf8),
+            position(4)
+        );
+        assert_eq!(convert(position(4), None, utf16, utf8), position(4));
+    }
+
+    #[test]
+    fn test_inline_value_provider() {
+        let result = |provider: Value| json!({ "capabilities": { "inlineValueProvider": provider } });
+        assert!(inline_value_provider(&result(json!(true))));
+        assert!(inline_value_provider(&result(
+            json!({ "workDoneProgress": true })
+        )));
+        assert!(!inline_value_provider(&result(json!(false))));
+        assert!(!inline_value_provider(&json!({ "capabilities": {} })));
+    }
+}
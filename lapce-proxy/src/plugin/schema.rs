@@ -0,0 +1,776 @@
+//! The built in language server of json, toml and yaml files which checks them
+//! against their JSON Schemas, and completes and describes their keys and values.
+//!
+//! The schema of a file is the one which the file declares, or else the one of
+//! the user's mappings which matches the file, or else the one of the
+//! [SchemaStore](https://www.schemastore.org) catalog which matches it.
+
+pub mod document;
+pub mod validate;
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use globset::{Glob, GlobMatcher};
+use jsonrpc_lite::{JsonRpc, Params};
+use lapce_rpc::plugin::VoltID;
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification, PublishDiagnostics,
+    },
+    request::{Completion, HoverRequest, Initialize, Request, Shutdown},
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams,
+    CompletionResponse, CompletionTextEdit, Diagnostic, DiagnosticSeverity,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DocumentFilter, Documentation, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, InitializeResult, MarkupContent,
+    MarkupKind, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+use self::{
+    document::{locate, parse, Document, Format, Location, Node, NodeValue},
+    validate::{validate, Schema},
+};
+use super::{lsp::LspClient, PluginCatalogRpcHandler};
+
+const CATALOG_URL: &str = "https://www.schemastore.org/api/json/catalog.json";
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SchemaOptions {
+    /// Whether the schemas of the SchemaStore catalog are used for the files
+    /// which they match
+    pub catalog: bool,
+    /// The schemas of the files which match the globs, which are urls or paths
+    /// in the workspace
+    pub mappings: HashMap<String, String>,
+}
+
+impl Default for SchemaOptions {
+    fn default() -> Self {
+        Self {
+            catalog: true,
+            mappings: HashMap::new(),
+        }
+    }
+}
+
+/// Start the schema server with the options of the `schemas` section of the
+/// settings.
+pub fn start_schema_server(
+    plugin_rpc: PluginCatalogRpcHandler,
+    workspace: Option<PathBuf>,
+    options: Option<HashMap<String, Value>>,
+) {
+    let options: SchemaOptions = options
+        .and_then(|options| {
+            serde_json::from_value(serde_json::to_value(options).ok()?).ok()
+        })
+        .unwrap_or_default();
+    let document_selector = ["json", "jsonc", "toml", "yaml"]
+        .iter()
+        .map(|language| DocumentFilter {
+            language: Some(language.to_string()),
+            scheme: None,
+            pattern: None,
+        })
+        .collect();
+    let server_workspace = workspace.clone();
+    LspClient::start_in_process(
+        plugin_rpc,
+        document_selector,
+        workspace,
+        VoltID {
+            author: "lapce".to_string(),
+            name: "schemas".to_string(),
+        },
+        "Schemas".to_string(),
+        None,
+        move |rx, tx| {
+            SchemaServer::new(server_workspace, options, tx).mainloop(rx);
+        },
+    );
+}
+
+/// The byte offsets of the starts of the lines of a text, to convert the offsets
+/// to the positions of lsp, which count utf-16 code units.
+struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self.text[start..offset]
+            .chars()
+            .map(|c| c.len_utf16())
+            .sum::<usize>();
+        Position::new(line as u32, character as u32)
+    }
+
+    fn range(&self, range: &std::ops::Range<usize>) -> Range {
+        Range::new(self.position(range.start), self.position(range.end))
+    }
+
+    fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut units = 0;
+        for (i, c) in self.text[start..].char_indices() {
+            if units >= position.character as usize || c == '\n' {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+}
+
+struct OpenDocument {
+    format: Format,
+    text: String,
+    version: i32,
+}
+
+/// A schema of the catalog, with the globs of the files which it's for.
+struct CatalogEntry {
+    url: String,
+    file_match: Vec<GlobMatcher>,
+}
+
+struct SchemaServer {
+    workspace: Option<PathBuf>,
+    options: SchemaOptions,
+    mappings: Vec<(GlobMatcher, String)>,
+    tx: Sender<JsonRpc>,
+    documents: HashMap<Url, OpenDocument>,
+    /// The catalog, which is fetched the first time that it's needed
+    catalog: Option<Vec<CatalogEntry>>,
+    /// The remote schemas which were fetched, or failed to be
+    schemas: HashMap<String, Option<Arc<Value>>>,
+}
+
+/// The glob of a pattern of a file, where the ones without a `/` match the file
+/// name in any directory.
+fn file_glob(pattern: &str) -> Option<GlobMatcher> {
+    let pattern = pattern.trim_start_matches("./");
+    let pattern = if pattern.contains('/') || pattern.starts_with("**") {
+        pattern.to_string()
+    } else {
+        format!("**/{pattern}")
+    };
+    Glob::new(&pattern).ok().map(|glob| glob.compile_matcher())
+}
+
+fn from_params<T: DeserializeOwned>(params: Option<Params>) -> Option<T> {
+    serde_json::from_value(serde_json::to_value(params?).ok()?).ok()
+}
+
+impl SchemaServer {
+    fn new(
+        workspace: Option<PathBuf>,
+        options: SchemaOptions,
+        tx: Sender<JsonRpc>,
+    ) -> Self {
+        let mappings = options
+            .mappings
+            .iter()
+            .filter_map(|(pattern, schema)| {
+                Some((file_glob(pattern)?, schema.clone()))
+            })
+            .collect();
+        Self {
+            workspace,
+            options,
+            mappings,
+            tx,
+            documents: HashMap::new(),
+            catalog: None,
+            schemas: HashMap::new(),
+        }
+    }
+
+    fn mainloop(&mut self, rx: Receiver<JsonRpc>) {
+        for msg in rx {
+            match msg {
+                JsonRpc::Request(_) => {
+                    let Some(id) = msg.get_id() else {
+                        continue;
+                    };
+                    let method = msg.get_method().unwrap_or_default().to_string();
+                    let result = self.handle_request(&method, msg.get_params());
+                    let resp = match result {
+                        Some(result) => JsonRpc::success(id, &result),
+                        None => JsonRpc::error(
+                            id,
+                            jsonrpc_lite::Error::method_not_found(),
+                        ),
+                    };
+                    let _ = self.tx.send(resp);
+                }
+                JsonRpc::Notification(_) => {
+                    let method = msg.get_method().unwrap_or_default().to_string();
+                    if method == "exit" {
+                        return;
+                    }
+                    self.handle_notification(&method, msg.get_params());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_request(
+        &mut self,
+        method: &str,
+        params: Option<Params>,
+    ) -> Option<Value> {
+        match method {
+            Initialize::METHOD => serde_json::to_value(InitializeResult {
+                capabilities: ServerCapabilities {
+                    text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                        TextDocumentSyncKind::FULL,
+                    )),
+                    completion_provider: Some(CompletionOptions {
+                        trigger_characters: Some(vec![
+                            "\"".to_string(),
+                            ":".to_string(),
+                        ]),
+                        ..Default::default()
+                    }),
+                    hover_provider: Some(HoverProviderCapability::Simple(true)),
+                    ..Default::default()
+                },
+                server_info: Some(ServerInfo {
+                    name: "schemas".to_string(),
+                    version: None,
+                }),
+            })
+            .ok(),
+            Completion::METHOD => {
+                let params: CompletionParams = from_params(params)?;
+                let position = params.text_document_position;
+                let items = self
+                    .completion(&position.text_document.uri, position.position)
+                    .unwrap_or_default();
+                serde_json::to_value(CompletionResponse::Array(items)).ok()
+            }
+            HoverRequest::METHOD => {
+                let params: HoverParams = from_params(params)?;
+                let position = params.text_document_position_params;
+                let hover =
+                    self.hover(&position.text_document.uri, position.position);
+                serde_json::to_value(hover).ok()
+            }
+            Shutdown::METHOD => Some(Value::Null),
+            _ => None,
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: Option<Params>) {
+        match method {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = from_params::<DidOpenTextDocumentParams>(params)
+                else {
+                    return;
+                };
+                let item = params.text_document;
+                let Some(format) = Format::from_language_id(&item.language_id)
+                else {
+                    return;
+                };
+                self.documents.insert(
+                    item.uri.clone(),
+                    OpenDocument {
+                        format,
+                        text: item.text,
+                        version: item.version,
+                    },
+                );
+                self.publish_diagnostics(&item.uri);
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) =
+                    from_params::<DidChangeTextDocumentParams>(params)
+                else {
+                    return;
+                };
+                let uri = params.text_document.uri;
+                let Some(document) = self.documents.get_mut(&uri) else {
+                    return;
+                };
+                // The changes are of the full text, as that's how the document
+                // is synced
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    document.text = change.text;
+                }
+                document.version = params.text_document.version;
+                self.publish_diagnostics(&uri);
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Some(params) =
+                    from_params::<DidCloseTextDocumentParams>(params)
+                {
+                    self.documents.remove(&params.text_document.uri);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn notify(&self, method: &str, params: impl serde::Serialize) {
+        if let Ok(params) = serde_json::to_value(params) {
+            let _ = self.tx.send(JsonRpc::notification_with_params(
+                method,
+                Params::from(params),
+            ));
+        }
+    }
+
+    fn publish_diagnostics(&mut self, uri: &Url) {
+        let Some(document) = self.documents.get(uri) else {
+            return;
+        };
+        let (format, version) = (document.format, document.version);
+        let text = document.text.clone();
+        let index = LineIndex::new(&text);
+        let parsed = parse(format, &text);
+
+        let mut diagnostics = Vec::new();
+        // Only the syntax errors of json are shown, as the toml and yaml parsers
+        // don't have all of their syntax and their language servers do it
+        if format == Format::Json {
+            diagnostics.extend(parsed.errors.iter().map(|error| Diagnostic {
+                range: index.range(&error.range),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("schemas".to_string()),
+                message: error.message.clone(),
+                ..Default::default()
+            }));
+        }
+        if parsed.errors.is_empty() {
+            if let (Some(root), Some(schema)) = (
+                parsed.root.as_ref(),
+                self.schema_of(uri, format, &text, &parsed),
+            ) {
+                diagnostics.extend(
+                    validate(root, Schema::new(&schema)).into_iter().map(
+                        |problem| Diagnostic {
+                            range: index.range(&problem.range),
+                            severity: Some(DiagnosticSeverity::WARNING),
+                            source: Some("schemas".to_string()),
+                            message: problem.message,
+                            ..Default::default()
+                        },
+                    ),
+                );
+            }
+        }
+
+        self.notify(
+            PublishDiagnostics::METHOD,
+            PublishDiagnosticsParams {
+                uri: uri.clone(),
+                diagnostics,
+                version: Some(version),
+            },
+        );
+    }
+
+    /// The url of the schema which the document declares, with `$schema` in json,
+    /// `# yaml-language-server: $schema=` in yaml and `#:schema` in toml.
+    fn declared_schema(
+        format: Format,
+        text: &str,
+        parsed: &Document,
+    ) -> Option<String> {
+        match format {
+            Format::Json => {
+                let NodeValue::Object(properties) = &parsed.root.as_ref()?.value
+                else {
+                    return None;
+                };
+                match &properties.iter().find(|p| p.key == "$schema")?.value.value {
+                    NodeValue::String(url) => Some(url.clone()),
+                    _ => None,
+                }
+            }
+            Format::Yaml => text.lines().find_map(|line| {
+                let comment = line.trim_start().strip_prefix('#')?.trim_start();
+                let url = comment
+                    .strip_prefix("yaml-language-server:")?
+                    .trim_start()
+                    .strip_prefix("$schema=")?;
+                Some(url.trim().to_string())
+            }),
+            Format::Toml => text
+                .lines()
+                .take_while(|line| line.trim_start().starts_with('#'))
+                .find_map(|line| {
+                    let url = line.trim_start().strip_prefix("#:schema")?;
+                    Some(url.trim().to_string())
+                }),
+        }
+    }
+
+    /// The url of the schema of the document.
+    fn schema_url(
+        &mut self,
+        uri: &Url,
+        format: Format,
+        text: &str,
+        parsed: &Document,
+    ) -> Option<Url> {
+        if let Some(url) = Self::declared_schema(format, text, parsed) {
+            return uri.join(&url).ok();
+        }
+
+        let path = uri.to_file_path().ok()?;
+        let relative = self
+            .workspace
+            .as_ref()
+            .and_then(|workspace| path.strip_prefix(workspace).ok())
+            .unwrap_or(&path)
+            .to_path_buf();
+        if let Some((_, schema)) = self
+            .mappings
+            .iter()
+            .find(|(glob, _)| glob.is_match(&relative) || glob.is_match(&path))
+        {
+            return match Url::parse(schema) {
+                Ok(url) => Some(url),
+                Err(_) => {
+                    let schema = Path::new(schema);
+                    let schema = match self.workspace.as_ref() {
+                        Some(workspace) if schema.is_relative() => {
+                            workspace.join(schema)
+                        }
+                        _ => schema.to_path_buf(),
+                    };
+                    Url::from_file_path(schema).ok()
+                }
+            };
+        }
+
+        if !self.options.catalog {
+            return None;
+        }
+        let catalog = self.catalog.get_or_insert_with(fetch_catalog);
+        catalog
+            .iter()
+            .find(|entry| entry.file_match.iter().any(|glob| glob.is_match(&path)))
+            .and_then(|entry| Url::parse(&entry.url).ok())
+    }
+
+    /// The schema of the document, which is loaded the first time it's needed
+    /// when it's remote.
+    fn schema_of(
+        &mut self,
+        uri: &Url,
+        format: Format,
+        text: &str,
+        parsed: &Document,
+    ) -> Option<Arc<Value>> {
+        let url = self.schema_url(uri, format, text, parsed)?;
+        if url.scheme() == "file" {
+            let text = fs::read_to_string(url.to_file_path().ok()?).ok()?;
+            return serde_json::from_str(&text).ok().map(Arc::new);
+        }
+        self.schemas
+            .entry(url.to_string())
+            .or_insert_with(|| fetch_json(url.as_str()).map(Arc::new))
+            .clone()
+    }
+
+    /// The document, its schema and its index at the uri, to answer the requests
+    /// with.
+    fn with_document<T>(
+        &mut self,
+        uri: &Url,
+        f: impl FnOnce(&OpenDocument, &Document, Schema, &LineIndex) -> Option<T>,
+    ) -> Option<T> {
+        let document = self.documents.get(uri)?;
+        let (format, text) = (document.format, document.text.clone());
+        let parsed = parse(format, &text);
+        let schema = self.schema_of(uri, format, &text, &parsed)?;
+        let document = self.documents.get(uri)?;
+        let index = LineIndex::new(&text);
+        f(document, &parsed, Schema::new(&schema), &index)
+    }
+
+    fn completion(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Option<Vec<CompletionItem>> {
+        self.with_document(uri, |document, parsed, schema, index| {
+            let offset = index.offset(position);
+            let format = document.format;
+            let root = parsed.root.as_ref()?;
+            let mut items = Vec::new();
+            match locate(root, offset)? {
+                Location::Key {
+                    path,
+                    object,
+                    property,
+                } => {
+                    let range = index.range(&property.key_range);
+                    for schema in schema.at_path(&path) {
+                        for (key, child) in schema.properties() {
+                            if key != property.key
+                                && object.iter().any(|p| p.key == key)
+                            {
+                                continue;
+                            }
+                            let new_text =
+                                key_text(format, key, !property.separated);
+                            items.push(key_item(key, child, range, new_text));
+                        }
+                    }
+                }
+                Location::Object { path, object } => {
+                    let range = index.range(&(offset..offset));
+                    for schema in schema.at_path(&path) {
+                        for (key, child) in schema.properties() {
+                            if object.iter().any(|p| p.key == key) {
+                                continue;
+                            }
+                            let new_text = key_text(format, key, true);
+                            items.push(key_item(key, child, range, new_text));
+                        }
+                    }
+                }
+                Location::Value { path, node } => {
+                    let range = value_range(node, offset);
+                    let range = index.range(&range);
+                    for schema in schema.at_path(&path) {
+                        for value in schema.suggested_values() {
+                            let new_text = value_text(format, value);
+                            items.push(CompletionItem {
+                                label: new_text.clone(),
+                                kind: Some(CompletionItemKind::VALUE),
+                                documentation: schema.description().map(markdown),
+                                text_edit: Some(CompletionTextEdit::Edit(
+                                    TextEdit { range, new_text },
+                                )),
+                                ..Default::default()
+                            });
+                        }
+                    }
+                }
+            }
+            items.dedup_by(|a, b| a.label == b.label);
+            Some(items)
+        })
+    }
+
+    fn hover(&mut self, uri: &Url, position: Position) -> Option<Hover> {
+        self.with_document(uri, |_, parsed, schema, index| {
+            let offset = index.offset(position);
+            let root = parsed.root.as_ref()?;
+            let (path, range) = match locate(root, offset)? {
+                Location::Key {
+                    mut path, property, ..
+                } => {
+                    path.push(document::Segment::Key(property.key.clone()));
+                    (path, property.key_range.clone())
+                }
+                Location::Value { path, node } => (path, node.range.clone()),
+                Location::Object { .. } => return None,
+            };
+            let description = schema
+                .at_path(&path)
+                .iter()
+                .find_map(|schema| schema.description())?;
+            Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: description.to_string(),
+                }),
+                range: Some(index.range(&range)),
+            })
+        })
+    }
+}
+
+fn markdown(value: &str) -> Documentation {
+    Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: value.to_string(),
+    })
+}
+
+fn key_item(
+    key: &str,
+    schema: Schema,
+    range: Range,
+    new_text: String,
+) -> CompletionItem {
+    CompletionItem {
+        label: key.to_string(),
+        kind: Some(CompletionItemKind::PROPERTY),
+        detail: Some(schema.resolve().types().join(" | ")).filter(|d| !d.is_empty()),
+        documentation: schema.description().map(markdown),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text })),
+        ..Default::default()
+    }
+}
+
+/// The text of a key, with its separator when it doesn't have it yet.
+fn key_text(format: Format, key: &str, separator: bool) -> String {
+    let key = match format {
+        Format::Json => format!("\"{key}\""),
+        Format::Toml
+            if !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') =>
+        {
+            format!("\"{key}\"")
+        }
+        _ => key.to_string(),
+    };
+    if !separator {
+        return key;
+    }
+    match format {
+        Format::Json | Format::Yaml => format!("{key}: "),
+        Format::Toml => format!("{key} = "),
+    }
+}
+
+/// The text of a value which is suggested, in the syntax of the format.
+fn value_text(format: Format, value: &Value) -> String {
+    match (format, value) {
+        (Format::Yaml, Value::String(s))
+            if !s.is_empty()
+                && !s.contains([':', '#', '"', '\'', '[', ']', '{', '}', ','])
+                && !matches!(s.as_str(), "true" | "false" | "null" | "~")
+                && s.parse::<f64>().is_err() =>
+        {
+            s.clone()
+        }
+        (Format::Toml, Value::Object(object)) => {
+            let properties = object
+                .iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{} = {}",
+                        key_text(format, k, false),
+                        value_text(format, v)
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("{{ {} }}", properties.join(", "))
+        }
+        (Format::Toml, Value::Array(items)) => {
+            let items = items
+                .iter()
+                .map(|v| value_text(format, v))
+                .collect::<Vec<_>>();
+            format!("[{}]", items.join(", "))
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// What a value which is completed replaces, which is nothing when there's no
+/// value yet or when it's inside of an object or array.
+fn value_range(node: &Node, offset: usize) -> std::ops::Range<usize> {
+    match node.value {
+        NodeValue::Missing | NodeValue::Object(_) | NodeValue::Array(_) => {
+            offset..offset
+        }
+        _ if node.range.is_empty() => offset..offset,
+        _ => node.range.clone(),
+    }
+}
+
+fn fetch_json(url: &str) -> Option<Value> {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .ok()?
+        .get(url)
+        .send()
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .ok()
+}
+
+fn fetch_catalog() -> Vec<CatalogEntry> {
+    let Some(catalog) = fetch_json(CATALOG_URL) else {
+        return Vec::new();
+    };
+    catalog
+        .get("schemas")
+        .and_then(|schemas| schemas.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|schema| {
+            let url = schema.get("url")?.as_str()?.to_string();
+            let file_match = schema
+                .get("fileMatch")?
+                .as_array()?
+                .iter()
+                .filter_map(|pattern| pattern.as_str())
+                .filter(|pattern| !pattern.starts_with('!'))
+                .filter_map(file_glob)
+                .collect::<Vec<_>>();
+            Some(CatalogEntry { url, file_match })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let text = "a\n\u{1F600}b\nc";
+        let index = LineIndex::new(text);
+        assert_eq!(index.position(0), Position::new(0, 0));
+        assert_eq!(index.position(7), Position::new(1, 3));
+        assert_eq!(index.offset(Position::new(1, 2)), 6);
+        assert_eq!(index.offset(Position::new(2, 1)), text.len());
+    }
+
+    #[test]
+    fn test_declared_schema() {
+        let text = "#:schema https://example.com/a.json\nname = 1\n";
+        let parsed = parse(Format::Toml, text);
+        assert_eq!(
+            SchemaServer::declared_schema(Format::Toml, text, &parsed),
+            Some("https://example.com/a.json".to_string())
+        );
+        let text = "# yaml-language-server: $schema=./b.json\na: 1\n";
+        let parsed = parse(Format::Yaml, text);
+        assert_eq!(
+            SchemaServer::declared_schema(Format::Yaml, text, &parsed),
+            Some("./b.json".to_string())
+        );
+    }
+}
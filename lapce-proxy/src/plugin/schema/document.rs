@@ -0,0 +1,1558 @@
+//! The values of json, toml and yaml documents along with where they are in the
+//! text, which is what the schema of a document is checked against.
+//!
+//! The parsers keep what they got up to the first error, so that a document
+//! which is being typed still has the keys and values before the cursor.
+
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    pub fn from_language_id(language_id: &str) -> Option<Self> {
+        match language_id {
+            "json" | "jsonc" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Node>),
+    Object(Vec<Property>),
+    /// Where a value is expected but there's none yet, like after `"key":`, or
+    /// a value which isn't known like an alias of yaml
+    Missing,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub value: NodeValue,
+    pub range: Range<usize>,
+}
+
+impl Node {
+    fn new(value: NodeValue, range: Range<usize>) -> Self {
+        Self { value, range }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Property {
+    pub key: String,
+    /// Where the key is, along with its quotes
+    pub key_range: Range<usize>,
+    pub value: Node,
+    /// Whether the key is followed by its `:` or `=`
+    pub separated: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub range: Range<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Document {
+    /// The value of the document, which an empty one doesn't have
+    pub root: Option<Node>,
+    pub errors: Vec<SyntaxError>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// What is at an offset of a document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Location<'a> {
+    /// On the key of a property of the object at the path
+    Key {
+        path: Vec<Segment>,
+        object: &'a [Property],
+        property: &'a Property,
+    },
+    /// In the object at the path, where a property can be added
+    Object {
+        path: Vec<Segment>,
+        object: &'a [Property],
+    },
+    /// On the value at the path
+    Value { path: Vec<Segment>, node: &'a Node },
+}
+
+pub fn parse(format: Format, text: &str) -> Document {
+    match format {
+        Format::Json => parse_json(text),
+        Format::Toml => parse_toml(text),
+        Format::Yaml => parse_yaml(text),
+    }
+}
+
+fn contains(range: &Range<usize>, offset: usize) -> bool {
+    range.start <= offset && offset <= range.end
+}
+
+/// What is at the offset, which is the innermost value whose range has it.
+pub fn locate(root: &Node, offset: usize) -> Option<Location<'_>> {
+    if !contains(&root.range, offset) {
+        return None;
+    }
+    let mut path = Vec::new();
+    let mut node = root;
+    loop {
+        match &node.value {
+            NodeValue::Object(properties) => {
+                if let Some(property) = properties
+                    .iter()
+                    .find(|property| contains(&property.key_range, offset))
+                {
+                    return Some(Location::Key {
+                        path,
+                        object: properties,
+                        property,
+                    });
+                }
+                // The tables of toml can be spread out, so the smallest one
+                // which has the offset is the innermost
+                match properties
+                    .iter()
+                    .filter(|property| contains(&property.value.range, offset))
+                    .min_by_key(|property| property.value.range.len())
+                {
+                    Some(property) => {
+                        path.push(Segment::Key(property.key.clone()));
+                        node = &property.value;
+                    }
+                    None => {
+                        return Some(Location::Object {
+                            path,
+                            object: properties,
+                        })
+                    }
+                }
+            }
+            NodeValue::Array(items) => {
+                match items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| contains(&item.range, offset))
+                    .min_by_key(|(_, item)| item.range.len())
+                {
+                    Some((index, item)) => {
+                        path.push(Segment::Index(index));
+                        node = item;
+                    }
+                    None => return Some(Location::Value { path, node }),
+                }
+            }
+            _ => return Some(Location::Value { path, node }),
+        }
+    }
+}
+
+fn parse_json(text: &str) -> Document {
+    let mut parser = FlowParser::new(text, 0, false);
+    parser.skip_whitespace();
+    if parser.peek().is_none() {
+        return Document::default();
+    }
+    let root = parser.parse_value();
+    if !parser.failed {
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            parser.error("unexpected content after the value", parser.pos);
+        }
+    }
+    Document {
+        root: Some(root),
+        errors: parser.errors,
+    }
+}
+
+/// The parser of json, and of the flow collections of yaml like `[a, b]` whose
+/// scalars don't need quotes.
+struct FlowParser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    yaml: bool,
+    /// Whether there was an error, which stops the parsing
+    failed: bool,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> FlowParser<'a> {
+    fn new(text: &'a str, pos: usize, yaml: bool) -> Self {
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            pos,
+            yaml,
+            failed: false,
+            errors: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn error(&mut self, message: &str, pos: usize) {
+        let end = self.text[pos..]
+            .chars()
+            .next()
+            .map(|c| pos + c.len_utf8())
+            .unwrap_or(pos);
+        self.errors.push(SyntaxError {
+            message: message.to_string(),
+            range: pos..end,
+        });
+        self.failed = true;
+    }
+
+    fn skip_line(&mut self) {
+        while !matches!(self.peek(), None | Some(b'\n')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+                Some(b'#') if self.yaml => self.skip_line(),
+                Some(b'/') if !self.yaml => match self.bytes.get(self.pos + 1) {
+                    Some(b'/') => self.skip_line(),
+                    Some(b'*') => {
+                        self.pos = self.text[self.pos + 2..]
+                            .find("*/")
+                            .map(|end| self.pos + 2 + end + 2)
+                            .unwrap_or(self.text.len());
+                    }
+                    _ => return,
+                },
+                _ => return,
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Node {
+        let before = self.pos;
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            Some(b'"') => {
+                let value = self.parse_string();
+                Node::new(NodeValue::String(value), start..self.pos)
+            }
+            Some(b'\'') if self.yaml => {
+                let value = self.parse_single_quoted();
+                Node::new(NodeValue::String(value), start..self.pos)
+            }
+            Some(b',' | b']' | b'}') | None => {
+                self.error("expected a value", start);
+                Node::new(NodeValue::Missing, before..start)
+            }
+            Some(_) if self.yaml => {
+                let end = self.plain_end(false);
+                self.pos = end;
+                let scalar = self.text[start..end].trim_end();
+                Node::new(yaml_scalar(scalar), start..start + scalar.len())
+            }
+            Some(_) => {
+                let end = self.text[start..]
+                    .find(|c: char| {
+                        !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                    })
+                    .map(|end| start + end)
+                    .unwrap_or(self.text.len());
+                let token = &self.text[start..end];
+                let value = match token {
+                    "true" => Some(NodeValue::Bool(true)),
+                    "false" => Some(NodeValue::Bool(false)),
+                    "null" => Some(NodeValue::Null),
+                    _ => token.parse::<f64>().ok().map(NodeValue::Number),
+                };
+                match value {
+                    Some(value) => {
+                        self.pos = end;
+                        Node::new(value, start..end)
+                    }
+                    None => {
+                        self.error("expected a value", start);
+                        Node::new(NodeValue::Missing, before..start)
+                    }
+                }
+            }
+        }
+    }
+
+    /// The end of a plain scalar of a yaml flow collection, which is a key when
+    /// it ends at a `:`.
+    fn plain_end(&self, key: bool) -> usize {
+        let mut end = self.pos;
+        while let Some(c) = self.bytes.get(end) {
+            match c {
+                b',' | b']' | b'}' | b'\n' => break,
+                b':' if key
+                    || matches!(
+                        self.bytes.get(end + 1),
+                        None | Some(b' ' | b',' | b']' | b'}' | b'\n')
+                    ) =>
+                {
+                    break
+                }
+                b'#' if end > self.pos && self.bytes[end - 1] == b' ' => break,
+                _ => end += 1,
+            }
+        }
+        end
+    }
+
+    fn parse_string(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1;
+        let mut value = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return value;
+                }
+                '\n' => break,
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('u') => {
+                        let hex = chars
+                            .by_ref()
+                            .take(4)
+                            .map(|(_, c)| c)
+                            .collect::<String>();
+                        if let Some(c) = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                        {
+                            value.push(c);
+                        }
+                    }
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        self.pos = self.text[start..]
+            .find('\n')
+            .map(|end| start + end)
+            .unwrap_or(self.text.len());
+        self.error("the string isn't closed", start);
+        value
+    }
+
+    fn parse_single_quoted(&mut self) -> String {
+        let start = self.pos;
+        let (value, end) = single_quoted(self.text, start);
+        self.pos = end;
+        if !self.text[..end].ends_with('\'') || end == start + 1 {
+            self.error("the string isn't closed", start);
+        }
+        value
+    }
+
+    fn parse_key(&mut self) -> (String, Range<usize>) {
+        let start = self.pos;
+        let key = match self.peek() {
+            Some(b'"') => self.parse_string(),
+            Some(b'\'') if self.yaml => self.parse_single_quoted(),
+            _ => {
+                let end = self.plain_end(true);
+                self.pos = end;
+                self.text[start..end].trim_end().to_string()
+            }
+        };
+        let end = if self.yaml {
+            start + self.text[start..self.pos].trim_end().len()
+        } else {
+            self.pos
+        };
+        (key, start..end)
+    }
+
+    fn parse_object(&mut self) -> Node {
+        let start = self.pos;
+        self.pos += 1;
+        let mut properties = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                None => {
+                    self.error("expected `}`", self.pos);
+                    break;
+                }
+                Some(b'"') => {}
+                Some(b',' | b']') => {
+                    self.error("expected a property name", self.pos);
+                    break;
+                }
+                Some(_) if self.yaml => {}
+                Some(_) => {
+                    // A name which is being typed without its quotes yet
+                    let key_start = self.pos;
+                    let end = self.plain_end(true);
+                    self.pos = end;
+                    let key = self.text[key_start..end].trim_end();
+                    properties.push(Property {
+                        key: key.to_string(),
+                        key_range: key_start..key_start + key.len(),
+                        value: Node::new(NodeValue::Missing, end..end),
+                        separated: false,
+                    });
+                    self.error("expected a property name", key_start);
+                    break;
+                }
+            }
+
+            let (key, key_range) = self.parse_key();
+            if self.failed {
+                properties.push(Property {
+                    key,
+                    value: Node::new(NodeValue::Missing, self.pos..self.pos),
+                    key_range,
+                    separated: false,
+                });
+                break;
+            }
+            self.skip_whitespace();
+            if self.peek() != Some(b':') {
+                properties.push(Property {
+                    key,
+                    value: Node::new(NodeValue::Missing, self.pos..self.pos),
+                    key_range,
+                    separated: false,
+                });
+                self.error("expected `:`", self.pos);
+                break;
+            }
+            self.pos += 1;
+            let value = self.parse_value();
+            properties.push(Property {
+                key,
+                key_range,
+                value,
+                separated: true,
+            });
+            if self.failed {
+                break;
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {
+                    self.error("expected `,` or `}`", self.pos);
+                    break;
+                }
+            }
+        }
+        let end = if self.failed {
+            self.text.len()
+        } else {
+            self.pos
+        };
+        Node::new(NodeValue::Object(properties), start..end)
+    }
+
+    fn parse_array(&mut self) -> Node {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+                break;
+            }
+            let item = self.parse_value();
+            items.push(item);
+            if self.failed {
+                break;
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {
+                    self.error("expected `,` or `]`", self.pos);
+                    break;
+                }
+            }
+        }
+        let end = if self.failed {
+            self.text.len()
+        } else {
+            self.pos
+        };
+        Node::new(NodeValue::Array(items), start..end)
+    }
+}
+
+/// The value of a single quoted string of yaml or toml which starts at the
+/// offset, and where it ends.
+fn single_quoted(text: &str, start: usize) -> (String, usize) {
+    let mut value = String::new();
+    let mut chars = text[start + 1..].char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' if chars.peek().map(|(_, c)| *c) == Some('\'') => {
+                chars.next();
+                value.push('\'');
+            }
+            '\'' => return (value, start + 1 + i + 1),
+            '\n' => return (value, start + 1 + i),
+            c => value.push(c),
+        }
+    }
+    (value, text.len())
+}
+
+/// The value of a plain scalar of yaml, by the types of its core schema.
+fn yaml_scalar(scalar: &str) -> NodeValue {
+    match scalar {
+        "" | "~" | "null" | "Null" | "NULL" => NodeValue::Null,
+        "true" | "True" | "TRUE" => NodeValue::Bool(true),
+        "false" | "False" | "FALSE" => NodeValue::Bool(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" => NodeValue::Number(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => NodeValue::Number(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => NodeValue::Number(f64::NAN),
+        _ if scalar.starts_with('*') => NodeValue::Missing,
+        _ => {
+            let number = if let Some(hex) = scalar.strip_prefix("0x") {
+                i64::from_str_radix(hex, 16).ok().map(|n| n as f64)
+            } else if let Some(octal) = scalar.strip_prefix("0o") {
+                i64::from_str_radix(octal, 8).ok().map(|n| n as f64)
+            } else if scalar.starts_with(|c: char| {
+                c.is_ascii_digit() || c == '-' || c == '+' || c == '.'
+            }) {
+                scalar.parse::<f64>().ok()
+            } else {
+                None
+            };
+            number
+                .map(NodeValue::Number)
+                .unwrap_or_else(|| NodeValue::String(scalar.to_string()))
+        }
+    }
+}
+
+/// A line of yaml with its content, which is without the indent and the comment.
+#[derive(Clone, Copy, Debug)]
+struct YamlLine {
+    indent: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Where the comment of the content of a line of yaml starts, which is a `#`
+/// outside of quotes at the start or after a space.
+fn yaml_comment(content: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in content.char_indices() {
+        match (quote, c) {
+            (None, '#') if prev == ' ' || prev == '\t' => return Some(i),
+            (None, '"' | '\'') if prev == ' ' || i == 0 || prev == ':' => {
+                quote = Some(c)
+            }
+            (Some('"'), '"') if prev != '\\' => quote = None,
+            (Some('\''), '\'') => quote = None,
+            _ => {}
+        }
+        prev = c;
+    }
+    None
+}
+
+/// The lines of the first document of the yaml which have content.
+fn yaml_lines(text: &str) -> Vec<YamlLine> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let content = line.trim_start_matches([' ', '\t']);
+        let indent = line.len() - content.len();
+        if content == "---" || content.starts_with("--- ") {
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if content == "..." {
+            break;
+        }
+        if content.starts_with('%') && lines.is_empty() {
+            continue;
+        }
+        let content = match yaml_comment(content) {
+            Some(comment) => &content[..comment],
+            None => content,
+        };
+        let content = content.trim_end();
+        if content.is_empty() {
+            continue;
+        }
+        let start = line_start + indent;
+        lines.push(YamlLine {
+            indent,
+            start,
+            end: start + content.len(),
+        });
+    }
+    lines
+}
+
+/// The key of a line of a yaml mapping, where it is and where its value starts.
+fn yaml_key(text: &str, line: YamlLine) -> Option<(String, Range<usize>, usize)> {
+    let content = &text[line.start..line.end];
+    let (key, key_end) = match content.as_bytes().first()? {
+        b'"' => {
+            let mut parser = FlowParser::new(text, line.start, true);
+            let key = parser.parse_string();
+            (key, parser.pos)
+        }
+        b'\'' => single_quoted(text, line.start),
+        b'[' | b'{' | b'|' | b'>' => return None,
+        _ if is_yaml_item(content) => return None,
+        _ => {
+            let colon = content
+                .find(": ")
+                .or_else(|| content.ends_with(':').then(|| content.len() - 1))?;
+            let key = content[..colon].trim_end();
+            (key.to_string(), line.start + key.len())
+        }
+    };
+    let rest = text.get(key_end..line.end)?.trim_start();
+    let rest = rest.strip_prefix(':')?;
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let value_start = line.end - rest.len();
+    Some((key, line.start..key_end, value_start))
+}
+
+fn is_yaml_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+fn parse_yaml(text: &str) -> Document {
+    let mut parser = YamlParser {
+        text,
+        lines: yaml_lines(text),
+        index: 0,
+        failed: false,
+        errors: Vec::new(),
+    };
+    let root = parser.parse_block(0);
+    if !parser.failed {
+        if let Some(line) = parser.lines.get(parser.index).copied() {
+            parser.error("unexpected content", line.start..line.end);
+        }
+    }
+    Document {
+        root,
+        errors: parser.errors,
+    }
+}
+
+/// The parser of the block collections of yaml, which go by the indents of the
+/// lines.
+struct YamlParser<'a> {
+    text: &'a str,
+    lines: Vec<YamlLine>,
+    index: usize,
+    failed: bool,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> YamlParser<'a> {
+    fn error(&mut self, message: &str, range: Range<usize>) {
+        self.errors.push(SyntaxError {
+            message: message.to_string(),
+            range,
+        });
+        self.failed = true;
+    }
+
+    fn content(&self, line: YamlLine) -> &'a str {
+        &self.text[line.start..line.end]
+    }
+
+    /// The value of the lines from the current one which are indented at least
+    /// as much as the indent.
+    fn parse_block(&mut self, indent: usize) -> Option<Node> {
+        let line = *self.lines.get(self.index)?;
+        if line.indent < indent || self.failed {
+            return None;
+        }
+        let content = self.content(line);
+        if is_yaml_item(content) {
+            Some(self.parse_sequence(line.indent))
+        } else if yaml_key(self.text, line).is_some() {
+            Some(self.parse_mapping(line.indent))
+        } else {
+            self.index += 1;
+            Some(self.inline_value(line.start, line.end, line.indent))
+        }
+    }
+
+    /// The value of the lines after which are indented more than the indent,
+    /// which is null at the end when there are none.
+    fn nested(&mut self, indent: usize, end: usize) -> Node {
+        self.parse_block(indent + 1)
+            .unwrap_or_else(|| Node::new(NodeValue::Null, end..end))
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Node {
+        let start = self.lines[self.index].start;
+        let mut end = start;
+        let mut properties = Vec::new();
+        while let Some(line) = self.lines.get(self.index).copied() {
+            if self.failed || line.indent < indent {
+                break;
+            }
+            if line.indent > indent {
+                self.error("unexpected indentation", line.start..line.end);
+                break;
+            }
+            if is_yaml_item(self.content(line)) {
+                break;
+            }
+            self.index += 1;
+            let Some((key, key_range, value_start)) = yaml_key(self.text, line)
+            else {
+                // A key which is being typed
+                properties.push(Property {
+                    key: self.content(line).to_string(),
+                    key_range: line.start..line.end,
+                    value: Node::new(NodeValue::Missing, line.end..line.end),
+                    separated: false,
+                });
+                end = line.end;
+                self.error("expected `:`", line.end..line.end);
+                break;
+            };
+            let rest = self.text[value_start..line.end].trim_start();
+            let value_start = line.end - rest.len();
+            let value = if rest.is_empty() {
+                let next = self.lines.get(self.index).copied();
+                match next {
+                    Some(next) if next.indent > indent => {
+                        self.nested(indent, line.end)
+                    }
+                    Some(next)
+                        if next.indent == indent
+                            && is_yaml_item(self.content(next)) =>
+                    {
+                        self.parse_sequence(indent)
+                    }
+                    _ => Node::new(NodeValue::Null, line.end..line.end),
+                }
+            } else if rest.starts_with('|') || rest.starts_with('>') {
+                self.block_scalar(value_start, line.end, indent)
+            } else {
+                self.inline_value(value_start, line.end, indent)
+            };
+            end = value.range.end.max(key_range.end);
+            // The merge keys are left out, as what they merge isn't known
+            if key != "<<" {
+                properties.push(Property {
+                    key,
+                    key_range,
+                    value,
+                    separated: true,
+                });
+            }
+        }
+        Node::new(NodeValue::Object(properties), start..end)
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Node {
+        let start = self.lines[self.index].start;
+        let mut end = start;
+        let mut items = Vec::new();
+        while let Some(line) = self.lines.get(self.index).copied() {
+            if self.failed || line.indent < indent {
+                break;
+            }
+            if line.indent > indent {
+                self.error("unexpected indentation", line.start..line.end);
+                break;
+            }
+            let content = self.content(line);
+            if !is_yaml_item(content) {
+                break;
+            }
+            let rest = content[1..].trim_start();
+            let item = if rest.is_empty() {
+                self.index += 1;
+                self.nested(indent, line.end)
+            } else {
+                // The item is like a line of its own, indented to where it starts
+                let item_start = line.end - rest.len();
+                self.lines[self.index] = YamlLine {
+                    indent: indent + (item_start - line.start),
+                    start: item_start,
+                    end: line.end,
+                };
+                let indent = self.lines[self.index].indent;
+                self.parse_block(indent).unwrap_or_else(|| {
+                    Node::new(NodeValue::Null, line.end..line.end)
+                })
+            };
+            end = item.range.end;
+            items.push(item);
+        }
+        Node::new(NodeValue::Array(items), start..end)
+    }
+
+    /// The string of a `|` or `>` scalar, which is the lines after it which are
+    /// indented more than the key.
+    fn block_scalar(&mut self, start: usize, end: usize, indent: usize) -> Node {
+        let folded = self.text[start..].starts_with('>');
+        let mut end = end;
+        let mut lines = Vec::new();
+        while let Some(line) = self.lines.get(self.index).copied() {
+            if line.indent <= indent {
+                break;
+            }
+            self.index += 1;
+            lines.push(self.content(line));
+            end = line.end;
+        }
+        let value = lines.join(if folded { " " } else { "\n" });
+        Node::new(NodeValue::String(value), start..end)
+    }
+
+    fn inline_value(&mut self, start: usize, end: usize, indent: usize) -> Node {
+        let content = &self.text[start..end];
+        // The tags and anchors of the value are skipped
+        if content.starts_with('!') || content.starts_with('&') {
+            return match content.find(' ') {
+                Some(space) => {
+                    let rest = content[space..].trim_start();
+                    let start = end - rest.len();
+                    if rest.starts_with('|') || rest.starts_with('>') {
+                        self.block_scalar(start, end, indent)
+                    } else {
+                        self.inline_value(start, end, indent)
+                    }
+                }
+                None => {
+                    let next = self.lines.get(self.index).copied();
+                    match next {
+                        Some(next) if next.indent > indent => {
+                            self.nested(indent, end)
+                        }
+                        _ => Node::new(NodeValue::Null, end..end),
+                    }
+                }
+            };
+        }
+        match content.as_bytes().first() {
+            Some(b'[' | b'{' | b'"') => {
+                let mut parser = FlowParser::new(self.text, start, true);
+                let node = parser.parse_value();
+                if let Some(error) = parser.errors.into_iter().next() {
+                    self.error(&error.message, error.range);
+                }
+                node
+            }
+            Some(b'\'') => {
+                let (value, value_end) = single_quoted(self.text, start);
+                Node::new(NodeValue::String(value), start..value_end.min(end))
+            }
+            _ => Node::new(yaml_scalar(content), start..end),
+        }
+    }
+}
+
+fn parse_toml(text: &str) -> Document {
+    let mut parser = TomlParser {
+        text,
+        bytes: text.as_bytes(),
+        pos: 0,
+        failed: false,
+        errors: Vec::new(),
+    };
+    let mut root = Node::new(NodeValue::Object(Vec::new()), 0..text.len());
+    parser.parse(&mut root);
+    Document {
+        root: Some(root),
+        errors: parser.errors,
+    }
+}
+
+/// The property of the object with the key, which is added when it doesn't
+/// have it yet.
+fn entry<'a>(
+    properties: &'a mut Vec<Property>,
+    key: &str,
+    key_range: &Range<usize>,
+    value: impl FnOnce() -> NodeValue,
+) -> &'a mut Node {
+    let index = match properties.iter().position(|p| p.key == key) {
+        Some(index) => index,
+        None => {
+            properties.push(Property {
+                key: key.to_string(),
+                key_range: key_range.clone(),
+                value: Node::new(value(), key_range.clone()),
+                separated: true,
+            });
+            properties.len() - 1
+        }
+    };
+    &mut properties[index].value
+}
+
+/// The table of the node at the keys, where an array of tables is its last
+/// table. The tables which aren't there yet are added.
+fn table<'a>(
+    mut node: &'a mut Node,
+    keys: &[(String, Range<usize>)],
+) -> Option<&'a mut Node> {
+    for (key, key_range) in keys {
+        let NodeValue::Object(properties) = &mut node.value else {
+            return None;
+        };
+        node = entry(properties, key, key_range, || NodeValue::Object(Vec::new()));
+        node = last_table(node)?;
+    }
+    matches!(node.value, NodeValue::Object(_)).then_some(node)
+}
+
+/// The last table of the node when it's an array of tables, or else the node.
+fn last_table(node: &mut Node) -> Option<&mut Node> {
+    if matches!(node.value, NodeValue::Array(_)) {
+        match &mut node.value {
+            NodeValue::Array(items) => items.last_mut(),
+            _ => None,
+        }
+    } else {
+        Some(node)
+    }
+}
+
+/// Widen the ranges of the tables at the keys to have the range, as the tables of
+/// toml can be spread out.
+fn widen(mut node: &mut Node, keys: &[String], range: &Range<usize>) {
+    for key in keys {
+        let NodeValue::Object(properties) = &mut node.value else {
+            return;
+        };
+        let Some(property) = properties.iter_mut().find(|p| &p.key == key) else {
+            return;
+        };
+        node = &mut property.value;
+        // Both the array of tables and its last table are widened
+        for _ in 0..2 {
+            node.range.start = node.range.start.min(range.start);
+            node.range.end = node.range.end.max(range.end);
+            let Some(last) = last_table(node) else {
+                return;
+            };
+            node = last;
+        }
+    }
+}
+
+struct TomlParser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    failed: bool,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> TomlParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn error(&mut self, message: &str, range: Range<usize>) {
+        self.errors.push(SyntaxError {
+            message: message.to_string(),
+            range,
+        });
+        self.failed = true;
+    }
+
+    fn line_end(&self) -> usize {
+        self.text[self.pos..]
+            .find('\n')
+            .map(|end| self.pos + end)
+            .unwrap_or(self.text.len())
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip the spaces, the newlines and the comments.
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+                Some(b'#') => self.pos = self.line_end(),
+                _ => return,
+            }
+        }
+    }
+
+    /// Expect the end of the line, with a comment or not.
+    fn expect_line_end(&mut self) {
+        self.skip_spaces();
+        match self.peek() {
+            None | Some(b'\n' | b'\r' | b'#') => self.pos = self.line_end(),
+            Some(_) => {
+                let end = self.line_end();
+                self.error("expected the end of the line", self.pos..end);
+            }
+        }
+    }
+
+    fn parse(&mut self, root: &mut Node) {
+        // The keys of the current table, which are the path of its header
+        let mut current: Vec<(String, Range<usize>)> = Vec::new();
+        let mut section_start = 0;
+        let mut section_end = 0;
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() || self.failed {
+                break;
+            }
+            if self.peek() == Some(b'[') {
+                let header: Vec<String> =
+                    current.iter().map(|(k, _)| k.clone()).collect();
+                widen(root, &header, &(section_start..section_end));
+
+                let start = self.pos;
+                let array = self.bytes.get(self.pos + 1) == Some(&b'[');
+                self.pos += if array { 2 } else { 1 };
+                self.skip_spaces();
+                let keys = self.parse_keys();
+                if self.failed {
+                    break;
+                }
+                self.skip_spaces();
+                let close = if array { "]]" } else { "]" };
+                if !self.text[self.pos..].starts_with(close) {
+                    let end = self.line_end();
+                    self.error(&format!("expected `{close}`"), self.pos..end);
+                    break;
+                }
+                self.pos += close.len();
+                self.expect_line_end();
+                let Some((last, parents)) = keys.split_last() else {
+                    break;
+                };
+                let Some(NodeValue::Object(properties)) =
+                    table(root, parents).map(|node| &mut node.value)
+                else {
+                    self.error("the key isn't a table", last.1.clone());
+                    break;
+                };
+                let node = entry(properties, &last.0, &last.1, || {
+                    if array {
+                        NodeValue::Array(Vec::new())
+                    } else {
+                        NodeValue::Object(Vec::new())
+                    }
+                });
+                node.range.start = node.range.start.min(start);
+                node.range.end = node.range.end.max(self.pos);
+                if array {
+                    match &mut node.value {
+                        NodeValue::Array(items) => items.push(Node::new(
+                            NodeValue::Object(Vec::new()),
+                            start..self.pos,
+                        )),
+                        _ => {
+                            self.error(
+                                "the key isn't an array of tables",
+                                last.1.clone(),
+                            );
+                            break;
+                        }
+                    }
+                }
+                current = keys;
+                section_start = start;
+                section_end = self.pos;
+                continue;
+            }
+
+            let keys = self.parse_keys();
+            if keys.is_empty() {
+                break;
+            }
+            self.skip_spaces();
+            let (last, parents) = keys.split_last().unwrap();
+            let separated = self.peek() == Some(b'=');
+            let value = if separated {
+                self.pos += 1;
+                self.parse_value()
+            } else {
+                Node::new(NodeValue::Missing, self.pos..self.pos)
+            };
+            let end = value.range.end;
+            let path = current.iter().chain(parents).cloned().collect::<Vec<_>>();
+            if let Some(NodeValue::Object(properties)) =
+                table(root, &path).map(|node| &mut node.value)
+            {
+                properties.push(Property {
+                    key: last.0.clone(),
+                    key_range: last.1.clone(),
+                    value,
+                    separated,
+                });
+            }
+            section_end = section_end.max(end);
+            if !separated {
+                self.error("expected `=`", self.pos..self.pos);
+                break;
+            }
+            if self.failed {
+                break;
+            }
+            self.expect_line_end();
+        }
+        if self.failed {
+            section_end = self.text.len();
+        }
+        let header: Vec<String> = current.iter().map(|(k, _)| k.clone()).collect();
+        widen(root, &header, &(section_start..section_end));
+    }
+
+    /// The keys of a dotted key like `a."b".c`.
+    fn parse_keys(&mut self) -> Vec<(String, Range<usize>)> {
+        let mut keys = Vec::new();
+        loop {
+            self.skip_spaces();
+            let start = self.pos;
+            let key = match self.peek() {
+                Some(b'"') => {
+                    let mut parser = FlowParser::new(self.text, start, false);
+                    let key = parser.parse_string();
+                    self.pos = parser.pos;
+                    key
+                }
+                Some(b'\'') => {
+                    let (key, end) = single_quoted(self.text, start);
+                    self.pos = end;
+                    key
+                }
+                _ => {
+                    while matches!(
+                        self.peek(),
+                        Some(b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-')
+                    ) {
+                        self.pos += 1;
+                    }
+                    self.text[start..self.pos].to_string()
+                }
+            };
+            if self.pos == start {
+                let end = self.line_end();
+                self.error("expected a key", start..end);
+                return keys;
+            }
+            keys.push((key, start..self.pos));
+            self.skip_spaces();
+            if self.peek() != Some(b'.') {
+                return keys;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Node {
+        let before = self.pos;
+        self.skip_spaces();
+        let start = self.pos;
+        match self.peek() {
+            None | Some(b'\n' | b'\r' | b'#') => {
+                self.error("expected a value", start..start);
+                Node::new(NodeValue::Missing, before..self.line_end())
+            }
+            Some(b'"') if self.text[start..].starts_with("\"\"\"") => {
+                self.multiline_string("\"\"\"")
+            }
+            Some(b'\'') if self.text[start..].starts_with("'''") => {
+                self.multiline_string("'''")
+            }
+            Some(b'"') => {
+                let mut parser = FlowParser::new(self.text, start, false);
+                let value = parser.parse_string();
+                self.pos = parser.pos;
+                if let Some(error) = parser.errors.into_iter().next() {
+                    self.error(&error.message, error.range);
+                }
+                Node::new(NodeValue::String(value), start..self.pos)
+            }
+            Some(b'\'') => {
+                let (value, end) = single_quoted(self.text, start);
+                self.pos = end;
+                Node::new(NodeValue::String(value), start..end)
+            }
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_inline_table(),
+            Some(_) => {
+                while matches!(
+                    self.peek(),
+                    Some(
+                        b'A'..=b'Z'
+                        | b'a'..=b'z'
+                        | b'0'..=b'9'
+                        | b'_'
+                        | b'-'
+                        | b'+'
+                        | b'.'
+                        | b':',
+                    )
+                ) {
+                    self.pos += 1;
+                }
+                let token = &self.text[start..self.pos];
+                match toml_scalar(token) {
+                    Some(value) => Node::new(value, start..self.pos),
+                    None => {
+                        let end = self.pos.max(start + 1).min(self.text.len());
+                        self.error("expected a value", start..end);
+                        Node::new(NodeValue::Missing, start..self.pos)
+                    }
+                }
+            }
+        }
+    }
+
+    fn multiline_string(&mut self, quotes: &str) -> Node {
+        let start = self.pos;
+        let content_start = start + quotes.len();
+        let value_end = self.text[content_start..]
+            .find(quotes)
+            .map(|end| content_start + end);
+        let Some(value_end) = value_end else {
+            self.pos = self.text.len();
+            self.error("the string isn't closed", start..start + quotes.len());
+            return Node::new(NodeValue::Missing, start..self.pos);
+        };
+        self.pos = value_end + quotes.len();
+        let value = self.text[content_start..value_end]
+            .strip_prefix('\n')
+            .unwrap_or(&self.text[content_start..value_end]);
+        Node::new(NodeValue::String(value.to_string()), start..self.pos)
+    }
+
+    fn parse_array(&mut self) -> Node {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                None => {
+                    self.error("expected `]`", self.pos..self.pos);
+                    break;
+                }
+                _ => {}
+            }
+            items.push(self.parse_value());
+            if self.failed {
+                break;
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {
+                    let end = self.line_end();
+                    self.error("expected `,` or `]`", self.pos..end);
+                    break;
+                }
+            }
+        }
+        let end = if self.failed {
+            self.text.len()
+        } else {
+            self.pos
+        };
+        Node::new(NodeValue::Array(items), start..end)
+    }
+
+    fn parse_inline_table(&mut self) -> Node {
+        let start = self.pos;
+        self.pos += 1;
+        let mut node = Node::new(NodeValue::Object(Vec::new()), start..start);
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                break;
+            }
+            let keys = self.parse_keys();
+            let Some((last, parents)) = keys.split_last() else {
+                break;
+            };
+            self.skip_spaces();
+            let separated = self.peek() == Some(b'=');
+            let value = if separated {
+                self.pos += 1;
+                self.parse_value()
+            } else {
+                Node::new(NodeValue::Missing, self.pos..self.pos)
+            };
+            if let Some(NodeValue::Object(properties)) =
+                table(&mut node, parents).map(|node| &mut node.value)
+            {
+                properties.push(Property {
+                    key: last.0.clone(),
+                    key_range: last.1.clone(),
+                    value,
+                    separated,
+                });
+            }
+            if !separated {
+                self.error("expected `=`", self.pos..self.pos);
+            }
+            if self.failed {
+                break;
+            }
+            self.skip_spaces();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => {
+                    let end = self.line_end();
+                    self.error("expected `,` or `}`", self.pos..end);
+                    break;
+                }
+            }
+        }
+        node.range = start..if self.failed {
+            self.line_end()
+        } else {
+            self.pos
+        };
+        node
+    }
+}
+
+/// The value of a bare value of toml, where dates and times are strings.
+fn toml_scalar(token: &str) -> Option<NodeValue> {
+    match token {
+        "true" => return Some(NodeValue::Bool(true)),
+        "false" => return Some(NodeValue::Bool(false)),
+        "inf" | "+inf" => return Some(NodeValue::Number(f64::INFINITY)),
+        "-inf" => return Some(NodeValue::Number(f64::NEG_INFINITY)),
+        "nan" | "+nan" | "-nan" => return Some(NodeValue::Number(f64::NAN)),
+        _ => {}
+    }
+    let digits = token.replace('_', "");
+    let number = if let Some(hex) = digits.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok().map(|n| n as f64)
+    } else if let Some(octal) = digits.strip_prefix("0o") {
+        i64::from_str_radix(octal, 8).ok().map(|n| n as f64)
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        i64::from_str_radix(binary, 2).ok().map(|n| n as f64)
+    } else {
+        digits.parse::<f64>().ok()
+    };
+    if let Some(number) = number {
+        return Some(NodeValue::Number(number));
+    }
+    let is_date = token.starts_with(|c: char| c.is_ascii_digit())
+        && token.contains(['-', ':']);
+    is_date.then(|| NodeValue::String(token.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(format: Format, text: &str) -> Node {
+        let document = parse(format, text);
+        assert_eq!(document.errors, Vec::new());
+        document.root.unwrap()
+    }
+
+    fn keys(node: &Node) -> Vec<&str> {
+        match &node.value {
+            NodeValue::Object(properties) => {
+                properties.iter().map(|p| p.key.as_str()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn get<'a>(node: &'a Node, key: &str) -> &'a Node {
+        match &node.value {
+            NodeValue::Object(properties) => {
+                &properties.iter().find(|p| p.key == key).unwrap().value
+            }
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let text = r#"{
+  // a comment
+  "name": "foo",
+  "tags": [1, true, null],
+  "nested": {"a": "A"}
+}"#;
+        let node = root(Format::Json, text);
+        assert_eq!(keys(&node), vec!["name", "tags", "nested"]);
+        assert_eq!(
+            get(&node, "name").value,
+            NodeValue::String("foo".to_string())
+        );
+        assert_eq!(
+            get(get(&node, "nested"), "a").value,
+            NodeValue::String("A".to_string())
+        );
+
+        let document = parse(Format::Json, "{\"name\": ");
+        assert_eq!(document.errors.len(), 1);
+        let node = document.root.unwrap();
+        assert_eq!(get(&node, "name").value, NodeValue::Missing);
+        assert_eq!(node.range.end, 9);
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let text = r#"name = "foo"
+version = 1
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+
+[[bin]]
+name = 'a'
+
+[[bin]]
+name = "b"
+"#;
+        let node = root(Format::Toml, text);
+        assert_eq!(keys(&node), vec!["name", "version", "dependencies", "bin"]);
+        let serde = get(get(&node, "dependencies"), "serde");
+        assert_eq!(keys(serde), vec!["version", "features"]);
+        let NodeValue::Array(bins) = &get(&node, "bin").value else {
+            panic!("not an array");
+        };
+        assert_eq!(bins.len(), 2);
+        assert_eq!(get(&bins[1], "name").value, NodeValue::String("b".into()));
+
+        // The offset in the second table is in it rather than the root
+        let offset = text.find("'a'").unwrap();
+        let Some(Location::Value { path, .. }) = locate(&node, offset) else {
+            panic!("not a value");
+        };
+        assert_eq!(
+            path,
+            vec![
+                Segment::Key("bin".to_string()),
+                Segment::Index(0),
+                Segment::Key("name".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_yaml() {
+        let text = "# comment
+name: foo
+on:
+  push:
+    branches: [main, 'dev']
+jobs:
+  - run: echo hi # comment
+    shell: bash
+  - plain
+count: 3
+";
+        let node = root(Format::Yaml, text);
+        assert_eq!(keys(&node), vec!["name", "on", "jobs", "count"]);
+        assert_eq!(get(&node, "count").value, NodeValue::Number(3.0));
+        let branches = get(get(get(&node, "on"), "push"), "branches");
+        let NodeValue::Array(branches) = &branches.value else {
+            panic!("not an array");
+        };
+        assert_eq!(branches[1].value, NodeValue::String("dev".into()));
+        let NodeValue::Array(jobs) = &get(&node, "jobs").value else {
+            panic!("not an array");
+        };
+        assert_eq!(keys(&jobs[0]), vec!["run", "shell"]);
+        assert_eq!(
+            get(&jobs[0], "run").value,
+            NodeValue::String("echo hi".into())
+        );
+        assert_eq!(jobs[1].value, NodeValue::String("plain".into()));
+
+        // A key which is being typed is kept
+        let document = parse(Format::Yaml, "name: foo\nna");
+        let node = document.root.unwrap();
+        let Some(Location::Key { property, .. }) = locate(&node, 12) else {
+            panic!("not a key");
+        };
+        assert_eq!(property.key, "na");
+        assert!(!property.separated);
+    }
+}
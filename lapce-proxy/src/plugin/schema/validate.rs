@@ -0,0 +1,628 @@
+//! Checking the values of a document against a JSON Schema, and what the schema
+//! says about a value for the completions and the hovers.
+
+use std::ops::Range;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use super::document::{Node, NodeValue, Property, Segment};
+
+/// How deep the references and the combinations of schemas are followed, so that
+/// a schema which refers to itself doesn't go on forever.
+const MAX_DEPTH: usize = 16;
+
+static TRUE: Value = Value::Bool(true);
+static FALSE: Value = Value::Bool(false);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub message: String,
+    pub range: Range<usize>,
+}
+
+/// A schema along with the root schema which its references are resolved in.
+#[derive(Clone, Copy)]
+pub struct Schema<'a> {
+    pub value: &'a Value,
+    pub root: &'a Value,
+}
+
+impl<'a> Schema<'a> {
+    pub fn new(root: &'a Value) -> Self {
+        Self { value: root, root }
+    }
+
+    fn with(&self, value: &'a Value) -> Self {
+        Self {
+            value,
+            root: self.root,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.value.as_object()?.get(key)
+    }
+
+    /// The schema with its `$ref` followed, which can only be to the same
+    /// document.
+    pub fn resolve(&self) -> Self {
+        let mut schema = *self;
+        for _ in 0..MAX_DEPTH {
+            let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) else {
+                break;
+            };
+            let Some(pointer) = reference.strip_prefix('#') else {
+                break;
+            };
+            let pointer = pointer.replace("%24", "$");
+            match self.root.pointer(&pointer) {
+                Some(value) => schema = self.with(value),
+                None => break,
+            }
+        }
+        schema
+    }
+
+    /// The schema and the ones which it's combined with, whose properties and
+    /// descriptions all apply.
+    pub fn alternatives(&self) -> Vec<Self> {
+        let mut schemas = Vec::new();
+        self.collect_alternatives(&mut schemas, 0);
+        schemas
+    }
+
+    fn collect_alternatives(&self, schemas: &mut Vec<Self>, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let schema = self.resolve();
+        schemas.push(schema);
+        for key in ["allOf", "anyOf", "oneOf"] {
+            for value in schema
+                .get(key)
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                schema.with(value).collect_alternatives(schemas, depth + 1);
+            }
+        }
+        for key in ["then", "else"] {
+            if let Some(value) = schema.get(key) {
+                schema.with(value).collect_alternatives(schemas, depth + 1);
+            }
+        }
+    }
+
+    /// The schemas of the child of the value at the segment.
+    fn child(&self, segment: &Segment) -> Vec<Self> {
+        let mut children = Vec::new();
+        for schema in self.alternatives() {
+            match segment {
+                Segment::Key(key) => {
+                    if let Some(value) = schema
+                        .get("properties")
+                        .and_then(|p| p.as_object())
+                        .and_then(|p| p.get(key))
+                    {
+                        children.push(schema.with(value));
+                        continue;
+                    }
+                    let mut matched = false;
+                    for (pattern, value) in schema
+                        .get("patternProperties")
+                        .and_then(|p| p.as_object())
+                        .into_iter()
+                        .flatten()
+                    {
+                        if Regex::new(pattern)
+                            .map(|r| r.is_match(key))
+                            .unwrap_or(false)
+                        {
+                            children.push(schema.with(value));
+                            matched = true;
+                        }
+                    }
+                    if !matched {
+                        if let Some(value) = schema
+                            .get("additionalProperties")
+                            .filter(|v| v.is_object())
+                        {
+                            children.push(schema.with(value));
+                        }
+                    }
+                }
+                Segment::Index(index) => {
+                    let tuple = schema
+                        .get("prefixItems")
+                        .or_else(|| schema.get("items").filter(|v| v.is_array()))
+                        .and_then(|v| v.as_array());
+                    if let Some(value) = tuple.and_then(|tuple| tuple.get(*index)) {
+                        children.push(schema.with(value));
+                    } else if let Some(value) = schema
+                        .get("items")
+                        .filter(|v| v.is_object())
+                        .or_else(|| schema.get("additionalItems"))
+                    {
+                        children.push(schema.with(value));
+                    }
+                }
+            }
+        }
+        children
+    }
+
+    /// The schemas of the value at the path.
+    pub fn at_path(&self, path: &[Segment]) -> Vec<Self> {
+        let mut schemas = vec![*self];
+        for segment in path {
+            schemas = schemas.iter().flat_map(|s| s.child(segment)).collect();
+            if schemas.is_empty() {
+                break;
+            }
+        }
+        schemas
+    }
+
+    /// The properties which the schema has, with their schemas.
+    pub fn properties(&self) -> Vec<(&'a str, Self)> {
+        let mut properties: Vec<(&'a str, Self)> = Vec::new();
+        for schema in self.alternatives() {
+            for (key, value) in schema
+                .get("properties")
+                .and_then(|p| p.as_object())
+                .into_iter()
+                .flatten()
+            {
+                if !properties.iter().any(|(k, _)| k == key) {
+                    properties.push((key.as_str(), schema.with(value)));
+                }
+            }
+        }
+        properties
+    }
+
+    /// The values which are suggested for the schema, from its `enum`, `const`,
+    /// `default` and `examples`.
+    pub fn suggested_values(&self) -> Vec<&'a Value> {
+        let mut values: Vec<&'a Value> = Vec::new();
+        let mut push = |value: &'a Value| {
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        };
+        for schema in self.alternatives() {
+            if let Some(value) = schema.get("const") {
+                push(value);
+            }
+            for value in schema
+                .get("enum")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                push(value);
+            }
+            if let Some(value) = schema.get("default") {
+                push(value);
+            }
+            for value in schema
+                .get("examples")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                push(value);
+            }
+            if schema.types().contains(&"boolean") {
+                push(&TRUE);
+                push(&FALSE);
+            }
+        }
+        values
+    }
+
+    /// The description of the schema, as markdown.
+    pub fn description(&self) -> Option<&'a str> {
+        self.alternatives().iter().find_map(|schema| {
+            ["markdownDescription", "description", "title"]
+                .iter()
+                .find_map(|key| schema.get(key).and_then(|v| v.as_str()))
+        })
+    }
+
+    /// The types which the schema allows, which is all of them when it doesn't
+    /// say.
+    pub fn types(&self) -> Vec<&'a str> {
+        match self.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(types)) => {
+                types.iter().filter_map(|t| t.as_str()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn type_name(value: &NodeValue) -> &'static str {
+    match value {
+        NodeValue::Null => "null",
+        NodeValue::Bool(_) => "boolean",
+        NodeValue::Number(n) if n.fract() == 0.0 => "integer",
+        NodeValue::Number(_) => "number",
+        NodeValue::String(_) => "string",
+        NodeValue::Array(_) => "array",
+        NodeValue::Object(_) => "object",
+        NodeValue::Missing => "missing",
+    }
+}
+
+fn matches_type(value: &NodeValue, schema_type: &str) -> bool {
+    match (schema_type, value) {
+        ("number", NodeValue::Number(_)) => true,
+        (t, value) => t == type_name(value),
+    }
+}
+
+/// The value as json, which is how it's compared to `enum` and `const`.
+pub fn to_json(node: &Node) -> Value {
+    match &node.value {
+        NodeValue::Null | NodeValue::Missing => Value::Null,
+        NodeValue::Bool(b) => Value::Bool(*b),
+        NodeValue::Number(n) => serde_json::Number::from_f64(*n)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        NodeValue::String(s) => Value::String(s.clone()),
+        NodeValue::Array(items) => Value::Array(items.iter().map(to_json).collect()),
+        NodeValue::Object(properties) => Value::Object(
+            properties
+                .iter()
+                .map(|p| (p.key.clone(), to_json(&p.value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, v)| b.get(k).map_or(false, |b| json_eq(v, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// Where a problem of the value is shown, which is the start of it for the
+/// objects and arrays so that the whole of them isn't underlined.
+fn problem_range(node: &Node) -> Range<usize> {
+    match node.value {
+        NodeValue::Object(_) | NodeValue::Array(_) => {
+            node.range.start..node.range.start + 1
+        }
+        _ => node.range.clone(),
+    }
+}
+
+/// The problems of the value by the schema.
+pub fn validate(node: &Node, schema: Schema) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    validate_node(node, schema, &mut problems, 0);
+    problems
+}
+
+fn validate_node(
+    node: &Node,
+    schema: Schema,
+    problems: &mut Vec<Problem>,
+    depth: usize,
+) {
+    if depth > MAX_DEPTH || node.value == NodeValue::Missing {
+        return;
+    }
+    let schema = schema.resolve();
+    let mut problem = |message: String| {
+        problems.push(Problem {
+            message,
+            range: problem_range(node),
+        })
+    };
+    let Some(object) = schema.value.as_object() else {
+        if schema.value == &Value::Bool(false) {
+            problem("no value is allowed here".to_string());
+        }
+        return;
+    };
+
+    let types = schema.types();
+    if !types.is_empty() && !types.iter().any(|t| matches_type(&node.value, t)) {
+        problem(format!(
+            "expected {}, found {}",
+            types.join(" or "),
+            type_name(&node.value)
+        ));
+        return;
+    }
+
+    let json = to_json(node);
+    if let Some(values) = object.get("enum").and_then(|v| v.as_array()) {
+        if !values.iter().any(|v| json_eq(v, &json)) {
+            let values = values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            problem(format!("expected one of {values}"));
+        }
+    }
+    if let Some(value) = object.get("const") {
+        if !json_eq(value, &json) {
+            problem(format!("expected {value}"));
+        }
+    }
+
+    match &node.value {
+        NodeValue::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = object.get("minLength").and_then(|v| v.as_u64()) {
+                if len < min {
+                    problem(format!("the string is shorter than {min}"));
+                }
+            }
+            if let Some(max) = object.get("maxLength").and_then(|v| v.as_u64()) {
+                if len > max {
+                    problem(format!("the string is longer than {max}"));
+                }
+            }
+            if let Some(pattern) = object.get("pattern").and_then(|v| v.as_str()) {
+                if let Ok(regex) = Regex::new(pattern) {
+                    if !regex.is_match(s) {
+                        problem(format!("the string doesn't match `{pattern}`"));
+                    }
+                }
+            }
+        }
+        NodeValue::Number(n) => {
+            let n = *n;
+            let bound = |key: &str| object.get(key).and_then(|v| v.as_f64());
+            if let Some(min) = bound("minimum") {
+                if n < min {
+                    problem(format!("the value is less than {min}"));
+                }
+            }
+            if let Some(max) = bound("maximum") {
+                if n > max {
+                    problem(format!("the value is greater than {max}"));
+                }
+            }
+            if let Some(min) = bound("exclusiveMinimum") {
+                if n <= min {
+                    problem(format!("the value must be greater than {min}"));
+                }
+            }
+            if let Some(max) = bound("exclusiveMaximum") {
+                if n >= max {
+                    problem(format!("the value must be less than {max}"));
+                }
+            }
+        }
+        NodeValue::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = object.get("minItems").and_then(|v| v.as_u64()) {
+                if len < min {
+                    problem(format!("the array has fewer than {min} items"));
+                }
+            }
+            if let Some(max) = object.get("maxItems").and_then(|v| v.as_u64()) {
+                if len > max {
+                    problem(format!("the array has more than {max} items"));
+                }
+            }
+            for (index, item) in items.iter().enumerate() {
+                for child in schema.child(&Segment::Index(index)) {
+                    validate_node(item, child, problems, depth + 1);
+                }
+            }
+        }
+        NodeValue::Object(properties) => {
+            validate_object(node, properties, schema, problems, depth);
+        }
+        _ => {}
+    }
+
+    for value in object
+        .get("allOf")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        validate_node(node, schema.with(value), problems, depth + 1);
+    }
+    for key in ["anyOf", "oneOf"] {
+        let Some(branches) = object.get(key).and_then(|v| v.as_array()) else {
+            continue;
+        };
+        let results = branches
+            .iter()
+            .map(|branch| {
+                let mut branch_problems = Vec::new();
+                validate_node(
+                    node,
+                    schema.with(branch),
+                    &mut branch_problems,
+                    depth + 1,
+                );
+                branch_problems
+            })
+            .collect::<Vec<_>>();
+        if results.iter().any(|problems| problems.is_empty()) {
+            continue;
+        }
+        // The branch with the fewest problems is the one which was most likely
+        // meant
+        if let Some(best) = results.into_iter().min_by_key(|problems| problems.len())
+        {
+            problems.extend(best);
+        }
+    }
+    if let Some(condition) = object.get("if") {
+        let mut condition_problems = Vec::new();
+        validate_node(
+            node,
+            schema.with(condition),
+            &mut condition_problems,
+            depth + 1,
+        );
+        let branch = if condition_problems.is_empty() {
+            object.get("then")
+        } else {
+            object.get("else")
+        };
+        if let Some(branch) = branch {
+            validate_node(node, schema.with(branch), problems, depth + 1);
+        }
+    }
+}
+
+fn validate_object(
+    node: &Node,
+    properties: &[Property],
+    schema: Schema,
+    problems: &mut Vec<Problem>,
+    depth: usize,
+) {
+    let Some(object) = schema.value.as_object() else {
+        return;
+    };
+    for required in object
+        .get("required")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+    {
+        if !properties.iter().any(|p| p.key == required) {
+            problems.push(Problem {
+                message: format!("missing the property `{required}`"),
+                range: node.range.start..node.range.start + 1,
+            });
+        }
+    }
+
+    let known = object.get("properties").and_then(|p| p.as_object());
+    let patterns = object
+        .get("patternProperties")
+        .and_then(|p| p.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(k, v)| Some((Regex::new(k).ok()?, v)))
+        .collect::<Vec<_>>();
+    let additional = object.get("additionalProperties");
+    for property in properties {
+        // Only the own properties of the schema, as the ones of its
+        // combinations are checked with them
+        let mut children = known
+            .and_then(|known| known.get(&property.key))
+            .into_iter()
+            .collect::<Vec<_>>();
+        if children.is_empty() {
+            children = patterns
+                .iter()
+                .filter(|(r, _)| r.is_match(&property.key))
+                .map(|(_, v)| *v)
+                .collect();
+        }
+        if children.is_empty() {
+            if additional == Some(&Value::Bool(false)) {
+                problems.push(Problem {
+                    message: format!(
+                        "the property `{}` isn't allowed",
+                        property.key
+                    ),
+                    range: property.key_range.clone(),
+                });
+                continue;
+            }
+            children.extend(additional.filter(|v| v.is_object()));
+        }
+        for child in children {
+            validate_node(&property.value, schema.with(child), problems, depth + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::plugin::schema::document::{parse, Format};
+
+    fn problems(text: &str, schema: Value) -> Vec<String> {
+        let root = parse(Format::Json, text).root.unwrap();
+        validate(&root, Schema::new(&schema))
+            .into_iter()
+            .map(|p| p.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "additionalProperties": false,
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "edition": { "$ref": "#/definitions/edition" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "definitions": {
+                "edition": { "enum": ["2015", "2018", "2021"] }
+            }
+        });
+        assert!(
+            problems(r#"{"name": "a", "edition": "2021"}"#, schema.clone())
+                .is_empty()
+        );
+        assert_eq!(
+            problems(r#"{"edition": "2020", "tags": [1], "x": 1}"#, schema),
+            vec![
+                "missing the property `name`".to_string(),
+                "expected one of \"2015\", \"2018\", \"2021\"".to_string(),
+                "expected string, found integer".to_string(),
+                "the property `x` isn't allowed".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_at_path() {
+        let schema = json!({
+            "properties": {
+                "jobs": {
+                    "additionalProperties": {
+                        "allOf": [{ "properties": { "runs-on": {
+                            "description": "The runner"
+                        } } }]
+                    }
+                }
+            }
+        });
+        let schema = Schema::new(&schema);
+        let path = [
+            Segment::Key("jobs".to_string()),
+            Segment::Key("build".to_string()),
+        ];
+        let schemas = schema.at_path(&path);
+        let properties = schemas[0].properties();
+        assert_eq!(properties[0].0, "runs-on");
+        assert_eq!(properties[0].1.description(), Some("The runner"));
+    }
+}
@@ -29,6 +29,7 @@
 use parking_lot::Mutex;
 use psp_types::{Notification, Request};
 use toml_edit::easy as toml;
+use tracing::warn;
 use wasi_experimental_http_wasmtime::{HttpCtx, HttpState};
 use wasmtime_wasi::WasiCtxBuilder;
 
@@ -36,7 +37,7 @@
     client_capabilities,
     psp::{
         handle_plugin_server_message, PluginHandlerNotification, PluginHostHandler,
-        PluginServerHandler, PluginServerRpc, RpcCallback,
+        PluginServerHandler, PluginServerRpc, ResponseHandler, RpcCallback,
     },
     volt_icon, PluginCatalogRpcHandler,
 };
@@ -94,6 +95,20 @@ fn method_registered(&mut self, method: &'static str) -> bool {
         self.host.method_registered(method)
     }
 
+    fn encode_server_request(
+        &mut self,
+        method: &'static str,
+        params: Params,
+        path: Option<&Path>,
+        rh: ResponseHandler<serde_json::Value, RpcError>,
+    ) -> (Params, ResponseHandler<serde_json::Value, RpcError>) {
+        self.host.encode_server_request(method, params, path, rh)
+    }
+
+    fn handle_server_notification(&mut self, method: &'static str, params: &Params) {
+        self.host.handle_server_notification(method, params);
+    }
+
     fn document_supported(
         &mut self,
         language_id: Option<&str>,
@@ -112,7 +127,9 @@ fn handle_handler_notification(
                 self.initialize();
             }
             InitializeResult(result) => {
-                self.host.server_capabilities = result.capabilities;
+                if let Err(e) = self.host.set_server_capabilities(result) {
+                    warn!("invalid initialize result: {e}");
+                }
             }
             Shutdown => {
                 self.shutdown();
@@ -208,18 +225,16 @@ fn initialize(&mut self) {
             false,
             move |value| {
                 if let Ok(value) = value {
-                    if let Ok(result) = serde_json::from_value(value) {
-                        server_rpc.handle_rpc(PluginServerRpc::Handler(
-                            PluginHandlerNotification::InitializeResult(result),
-                        ));
-                        server_rpc.server_notification(
-                            Initialized::METHOD,
-                            InitializedParams {},
-                            None,
-                            None,
-                            false,
-                        );
-                    }
+                    server_rpc.handle_rpc(PluginServerRpc::Handler(
+                        PluginHandlerNotification::InitializeResult(value),
+                    ));
+                    server_rpc.server_notification(
+                        Initialized::METHOD,
+                        InitializedParams {},
+                        None,
+                        None,
+                        false,
+                    );
                 }
             },
         );
@@ -454,7 +469,7 @@ pub fn start_volt(
     })?;
     linker.func_wrap("lapce", "host_handle_stderr", move || {
         if let Ok(msg) = wasi_read_string(&stderr) {
-            eprintln!("got stderr from plugin: {msg}");
+            warn!("got stderr from plugin: {msg}");
         }
     })?;
     linker.module(&mut store, "", &module)?;
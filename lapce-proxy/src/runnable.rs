@@ -0,0 +1,255 @@
+//! Building the runnables of rust-analyzer, for the binaries which are debugged,
+//! and running the tests of the test explorer.
+
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use lapce_rpc::{dap_types::RunDebugConfig, runnable::Runnable};
+use serde_json::Value;
+
+/// How much of the end of the output of a test is sent back to the app
+const MAX_TEST_OUTPUT: usize = 64 * 1024;
+/// How long a test runs before it's taken as hung and killed
+pub const TEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// The arguments of cargo which build the runnable instead of running it, and
+/// print the artifacts as json.
+fn build_args(runnable: &Runnable) -> Vec<String> {
+    let mut args = runnable.args.cargo_args.clone();
+    match args.first().map(|arg| arg.as_str()) {
+        Some("run") => args[0] = "build".to_string(),
+        Some("test") | Some("bench") => args.push("--no-run".to_string()),
+        _ => {}
+    }
+    args.extend(runnable.args.cargo_extra_args.iter().cloned());
+    args.push("--message-format=json".to_string());
+    args
+}
+
+/// The executables of the artifacts in the json which cargo printed.
+fn executables(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| {
+            message.get("reason").and_then(|r| r.as_str())
+                == Some("compiler-artifact")
+        })
+        .filter_map(|message| {
+            message
+                .get("executable")
+                .and_then(|e| e.as_str())
+                .map(|e| e.to_string())
+        })
+        .collect()
+}
+
+/// Build the runnable with cargo, and return the config which debugs the binary
+/// which it runs.
+pub fn build_runnable(runnable: &Runnable) -> Result<RunDebugConfig> {
+    let cargo = runnable.args.override_cargo.as_deref().unwrap_or("cargo");
+    let mut command = Command::new(cargo);
+    command.args(build_args(runnable));
+    if let Some(cwd) = runnable.cwd() {
+        command.current_dir(cwd);
+    }
+    if let Some(env) = runnable.env() {
+        command.envs(env);
+    }
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let error = stderr
+            .lines()
+            .rev()
+            .find(|line| line.starts_with("error"))
+            .unwrap_or("the build failed");
+        return Err(anyhow!("{error}"));
+    }
+
+    let mut executables = executables(&String::from_utf8_lossy(&output.stdout));
+    let program = match executables.len() {
+        0 => return Err(anyhow!("the build has no binary to debug")),
+        1 => executables.remove(0),
+        _ => return Err(anyhow!("the build has more than one binary to debug")),
+    };
+
+    let mut config = runnable.run_config();
+    config.program = program;
+    config.args = runnable.args.executable_args.clone();
+    config.runnable = None;
+    Ok(config)
+}
+
+/// Run the config of a test, and return whether it passed along with the end of
+/// what it printed to stdout and then stderr. The test is killed when it runs
+/// past the timeout or once `cancelled` says so.
+pub fn run_test(
+    config: &RunDebugConfig,
+    timeout: Duration,
+    cancelled: impl Fn() -> bool,
+) -> Result<(bool, String)> {
+    let mut command = Command::new(&config.program);
+    command.args(&config.args);
+    if let Some(cwd) = config.cwd.as_ref() {
+        command.current_dir(cwd);
+    }
+    if let Some(env) = config.env.as_ref() {
+        command.envs(env);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Both pipes are read while the test runs, so that it doesn't block on a
+    // full one
+    let read = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut output = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut output);
+            }
+            output
+        })
+    };
+    let stdout = read(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = read(child.stderr.take().map(|p| Box::new(p) as _));
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let error = if cancelled() {
+            Some("the test was cancelled".to_string())
+        } else if start.elapsed() >= timeout {
+            Some(format!("the test timed out after {}s", timeout.as_secs()))
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("{error}"));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    let mut text =
+        String::from_utf8_lossy(&stdout.join().unwrap_or_default()).to_string();
+    text.push_str(&String::from_utf8_lossy(&stderr.join().unwrap_or_default()));
+    Ok((status.success(), tail(text, MAX_TEST_OUTPUT)))
+}
+
+/// The last bytes of the text, cut at a char boundary.
+fn tail(text: String, max: usize) -> String {
+    if text.len() <= max {
+        return text;
+    }
+    let mut start = text.len() - max;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    text[start..].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use lapce_rpc::runnable::CargoRunnable;
+
+    use super::*;
+
+    #[test]
+    fn test_build_args() {
+        let runnable = Runnable {
+            label: "test foo".to_string(),
+            location: None,
+            kind: "cargo".to_string(),
+            args: CargoRunnable {
+                cargo_args: vec![
+                    "test".to_string(),
+                    "--package".to_string(),
+                    "foo".to_string(),
+                    "--lib".to_string(),
+                ],
+                executable_args: vec!["tests::foo".to_string()],
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            build_args(&runnable),
+            [
+                "test",
+                "--package",
+                "foo",
+                "--lib",
+                "--no-run",
+                "--message-format=json"
+            ]
+        );
+
+        let output = r#"{"reason":"compiler-artifact","executable":null}
+{"reason":"compiler-artifact","executable":"/t/debug/deps/foo-1"}
+{"reason":"build-finished","success":true}"#;
+        assert_eq!(executables(output), ["/t/debug/deps/foo-1"]);
+    }
+
+    #[cfg(unix)]
+    fn shell_config(script: &str) -> RunDebugConfig {
+        serde_json::from_value(serde_json::json!({
+            "name": "test",
+            "program": "sh",
+            "args": ["-c", script],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_test() {
+        let config = shell_config("echo out; echo err >&2; exit 1");
+        let (success, output) =
+            run_test(&config, Duration::from_secs(10), || false).unwrap();
+        assert!(!success);
+        assert_eq!(output, "out\nerr\n");
+
+        let config = shell_config("echo ok");
+        let (success, output) =
+            run_test(&config, Duration::from_secs(10), || false).unwrap();
+        assert!(success);
+        assert_eq!(output, "ok\n");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_test_killed() {
+        let config = shell_config("sleep 30");
+        let start = Instant::now();
+        let err = run_test(&config, Duration::from_millis(200), || false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("timed out"), "{err}");
+
+        let err = run_test(&config, Duration::from_secs(10), || true)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "the test was cancelled");
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("abc".to_string(), 5), "abc");
+        assert_eq!(tail("abcdef".to_string(), 2), "ef");
+        assert_eq!(tail("aé".to_string(), 1), "");
+    }
+}
@@ -0,0 +1,384 @@
+//! The tasks of a workspace in `.lapce/tasks.toml` or `.lapce/tasks.json`, like
+//! a build, which a debug config runs by their names before it's launched with
+//! `pre_launch_task` and after its session ends with `post_debug_task`. A task
+//! is run by the proxy, and its output is shown in a terminal of the app.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use lapce_rpc::{core::CoreRpcHandler, dap_types::TaskId};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use toml_edit::easy as toml;
+
+use crate::launch::Variables;
+
+/// How long a task runs before it's killed, so that a build which hangs
+/// doesn't keep the session from starting forever
+pub const TASK_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// The lines at the end of the stderr of a task which are kept for the error
+/// when it fails
+const ERROR_LINES: usize = 5;
+
+/// Whether the tasks which are running were cancelled, by their ids
+static RUNNING: Lazy<Mutex<HashMap<TaskId, Arc<AtomicBool>>>> =
+    Lazy::new(Default::default);
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Task {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The folder which the task runs in, which is the workspace when it's left
+    /// out
+    pub cwd: Option<String>,
+    pub env: Option<HashMap<String, String>>,
+}
+
+#[derive(Deserialize)]
+struct TaskFile {
+    #[serde(default)]
+    tasks: Vec<Task>,
+}
+
+/// The file of the tasks in the workspace, where the toml one comes first.
+fn task_file(workspace: &Path) -> Option<PathBuf> {
+    let dir = workspace.join(".lapce");
+    ["tasks.toml", "tasks.json"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The task of the workspace by its name, with its variables substituted.
+pub fn find_task(workspace: Option<&Path>, name: &str) -> Result<Task> {
+    let workspace =
+        workspace.ok_or_else(|| anyhow!("the task {name} needs a workspace"))?;
+    let path = task_file(workspace).ok_or_else(|| {
+        anyhow!("there's no .lapce/tasks.toml for the task {name}")
+    })?;
+    let content = fs::read_to_string(&path)?;
+    let file: TaskFile = if path.extension().and_then(|e| e.to_str()) == Some("json")
+    {
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?
+    } else {
+        toml::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?
+    };
+    let mut task = file
+        .tasks
+        .into_iter()
+        .find(|task| task.name == name)
+        .ok_or_else(|| anyhow!("there's no task {name} in {}", path.display()))?;
+
+    let variables = Variables::new(workspace, None);
+    task.command = variables.substitute(&task.command);
+    for arg in task.args.iter_mut() {
+        *arg = variables.substitute(arg);
+    }
+    task.cwd = Some(
+        task.cwd
+            .map(|cwd| variables.substitute(&cwd))
+            .unwrap_or_else(|| workspace.to_string_lossy().to_string()),
+    );
+    if let Some(env) = task.env.as_mut() {
+        for value in env.values_mut() {
+            *value = variables.substitute(value);
+        }
+    }
+    Ok(task)
+}
+
+/// Send the lines of the output of the task to its terminal, and keep the last
+/// ones in `tail` when there's one.
+fn forward_output(
+    task_id: TaskId,
+    output: impl Read + Send + 'static,
+    core_rpc: CoreRpcHandler,
+    tail: Option<Arc<Mutex<VecDeque<String>>>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            // The terminal doesn't go back to the start of the line by itself
+            core_rpc.task_output(task_id, format!("{line}\r\n").into_bytes());
+            if let Some(tail) = tail.as_ref() {
+                let mut tail = tail.lock();
+                if tail.len() == ERROR_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+        }
+    })
+}
+
+/// Kill the task, which is done once its run checks on it next.
+pub fn cancel_task(task_id: TaskId) {
+    if let Some(cancelled) = RUNNING.lock().get(&task_id) {
+        cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Run the task in a terminal of its own until it exits, which fails unless it
+/// exits with 0, with the end of its stderr in the error. It's killed when it
+/// runs past [`TASK_TIMEOUT`] or its terminal is closed.
+pub fn run_task(task: &Task, core_rpc: &CoreRpcHandler) -> Result<()> {
+    let task_id = TaskId::next();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    RUNNING.lock().insert(task_id, cancelled.clone());
+    core_rpc.task_started(task_id, task.name.clone());
+
+    let result = run_task_until(task, task_id, core_rpc, TASK_TIMEOUT, || {
+        cancelled.load(Ordering::Relaxed)
+    });
+
+    RUNNING.lock().remove(&task_id);
+    core_rpc.task_stopped(task_id);
+    result
+}
+
+fn run_task_until(
+    task: &Task,
+    task_id: TaskId,
+    core_rpc: &CoreRpcHandler,
+    timeout: Duration,
+    cancelled: impl Fn() -> bool,
+) -> Result<()> {
+    let mut command = Command::new(&task.command);
+    command.args(&task.args);
+    if let Some(cwd) = task.cwd.as_ref() {
+        command.current_dir(cwd);
+    }
+    if let Some(env) = task.env.as_ref() {
+        command.envs(env);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to start the task {}", task.name))?;
+
+    let tail = Arc::new(Mutex::new(VecDeque::new()));
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward_output(task_id, stdout, core_rpc.clone(), None));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward_output(
+            task_id,
+            stderr,
+            core_rpc.clone(),
+            Some(tail.clone()),
+        ));
+    }
+
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let error = if cancelled() {
+            Some(format!("the task {} was cancelled", task.name))
+        } else if start.elapsed() >= timeout {
+            Some(format!(
+                "the task {} timed out after {}s",
+                task.name,
+                timeout.as_secs()
+            ))
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            // The output isn't waited for, as what the task started may still
+            // hold its pipes open
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!("{error}"));
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    if status.success() {
+        return Ok(());
+    }
+    let tail = tail.lock();
+    let error = tail
+        .iter()
+        .rev()
+        .find(|line| line.starts_with("error"))
+        .or_else(|| tail.iter().rev().find(|line| !line.trim().is_empty()));
+    match error {
+        Some(error) => Err(anyhow!("the task {} failed: {error}", task.name)),
+        None => Err(anyhow!("the task {} failed with {status}", task.name)),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use lapce_rpc::core::{CoreNotification, CoreRpc};
+
+    use super::*;
+
+    fn workspace(name: &str, file: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("lapce-task-test-{name}-{}", std::process::id()));
+        fs::create_dir_all(dir.join(".lapce")).unwrap();
+        fs::write(dir.join(".lapce").join(file), content).unwrap();
+        dir
+    }
+
+    fn shell(script: &str) -> Task {
+        Task {
+            name: "build".to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            cwd: None,
+            env: None,
+        }
+    }
+
+    #[test]
+    fn test_find_task() {
+        let dir = workspace(
+            "toml",
+            "tasks.toml",
+            r#"
+[[tasks]]
+name = "build"
+command = "cargo"
+args = ["build", "--manifest-path", "${workspaceFolder}/Cargo.toml"]
+env = { TARGET = "${workspaceFolderBasename}" }
+"#,
+        );
+        let task = find_task(Some(&dir), "build").unwrap();
+        let path = dir.to_string_lossy();
+        assert_eq!(task.command, "cargo");
+        assert_eq!(
+            task.args,
+            vec!["build", "--manifest-path", &format!("{path}/Cargo.toml")]
+        );
+        // A task without a cwd runs in the workspace
+        assert_eq!(task.cwd.as_deref(), Some(&*path));
+        let basename = dir.file_name().unwrap().to_string_lossy();
+        assert_eq!(task.env.unwrap()["TARGET"], basename);
+
+        let err = find_task(Some(&dir), "test").unwrap_err();
+        assert!(err.to_string().starts_with("there's no task test"), "{err}");
+        assert!(find_task(None, "build").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_task_json() {
+        let dir = workspace(
+            "json",
+            "tasks.json",
+            r#"{ "tasks": [{ "name": "lint", "command": "make", "cwd": "${workspaceFolder}/sub" }] }"#,
+        );
+        let task = find_task(Some(&dir), "lint").unwrap();
+        assert_eq!(task.command, "make");
+        assert!(task.args.is_empty());
+        assert_eq!(task.cwd, Some(format!("{}/sub", dir.to_string_lossy())));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_task() {
+        let core_rpc = CoreRpcHandler::new();
+        run_task(&shell("echo built"), &core_rpc).unwrap();
+
+        // The output is shown in the terminal of the task, between its start
+        // and its end
+        let notifications: Vec<CoreNotification> = core_rpc
+            .rx()
+            .try_iter()
+            .filter_map(|rpc| match rpc {
+                CoreRpc::Notification(notification) => Some(*notification),
+                _ => None,
+            })
+            .collect();
+        let Some(CoreNotification::TaskStarted { task_id, name }) =
+            notifications.first()
+        else {
+            panic!("the task didn't start");
+        };
+        assert_eq!(name, "build");
+        assert!(notifications.iter().any(|notification| matches!(
+            notification,
+            CoreNotification::TaskOutput { content, .. } if content == b"built\r\n"
+        )));
+        assert!(matches!(
+            notifications.last(),
+            Some(CoreNotification::TaskStopped { .. })
+        ));
+        assert!(!RUNNING.lock().contains_key(task_id));
+    }
+
+    #[test]
+    fn test_run_task_failed() {
+        let core_rpc = CoreRpcHandler::new();
+        // The error is the last line of stderr which starts with error
+        let task =
+            shell("echo 'error: mismatched types' >&2; echo note >&2; exit 1");
+        let err = run_task(&task, &core_rpc).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the task build failed: error: mismatched types"
+        );
+
+        // Or else the last line of it
+        let task = shell("echo first >&2; echo last >&2; exit 2");
+        let err = run_task(&task, &core_rpc).unwrap_err();
+        assert_eq!(err.to_string(), "the task build failed: last");
+
+        let task = shell("exit 3");
+        let err = run_task(&task, &core_rpc).unwrap_err();
+        assert!(err.to_string().starts_with("the task build failed with"));
+    }
+
+    #[test]
+    fn test_run_task_killed() {
+        let core_rpc = CoreRpcHandler::new();
+        let task = shell("sleep 30");
+
+        let start = Instant::now();
+        let err = run_task_until(
+            &task,
+            TaskId::next(),
+            &core_rpc,
+            Duration::from_millis(200),
+            || false,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "the task build timed out after 0s");
+
+        let err =
+            run_task_until(&task, TaskId::next(), &core_rpc, TASK_TIMEOUT, || true)
+                .unwrap_err();
+        assert_eq!(err.to_string(), "the task build was cancelled");
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -13,7 +13,7 @@
     event_loop::Msg,
     tty::{self, setup_env, EventedPty, EventedReadWrite},
 };
-use lapce_rpc::{core::CoreRpcHandler, terminal::TermId};
+use lapce_rpc::{core::CoreRpcHandler, crash::Subsystem, terminal::TermId};
 #[cfg(not(windows))]
 use mio::unix::UnixReady;
 #[allow(deprecated)]
@@ -22,6 +22,8 @@
     Events, PollOpt, Ready,
 };
 
+use crate::crash::supervise;
+
 const READ_BUFFER_SIZE: usize = 0x10_0000;
 
 pub type TermConfig = alacritty_terminal::config::Config;
@@ -135,6 +137,34 @@ pub fn run(&mut self, core_rpc: CoreRpcHandler) {
             .register(&self.poll, &mut tokens, Ready::readable(), poll_opts)
             .unwrap();
 
+        // The shell is kept running when the loop panics, and the loop is
+        // started again for it
+        supervise(Subsystem::Terminal, "", &core_rpc, || {
+            self.event_loop(channel_token, poll_opts, &core_rpc)
+        });
+
+        core_rpc.terminal_process_stopped(self.term_id);
+        let _ = self.poll.deregister(&self.rx);
+        let _ = self.pty.deregister(&self.poll);
+    }
+
+    fn event_loop(
+        &mut self,
+        channel_token: mio::Token,
+        poll_opts: PollOpt,
+        core_rpc: &CoreRpcHandler,
+    ) {
+        // A panic can be before the interest was registered again
+        let _ = self.poll.reregister(
+            &self.rx,
+            channel_token,
+            Ready::readable(),
+            poll_opts,
+        );
+        let _ = self
+            .pty
+            .reregister(&self.poll, Ready::readable(), poll_opts);
+
         let mut buf = [0u8; READ_BUFFER_SIZE];
         let mut events = Events::with_capacity(1024);
         let mut state = State::default();
@@ -196,9 +226,6 @@ pub fn run(&mut self, core_rpc: CoreRpcHandler) {
                 .reregister(&self.poll, interest, poll_opts)
                 .unwrap();
         }
-        core_rpc.terminal_process_stopped(self.term_id);
-        let _ = self.poll.deregister(&self.rx);
-        let _ = self.pty.deregister(&self.poll);
     }
 
     /// Drain the channel.
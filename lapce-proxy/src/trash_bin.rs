@@ -0,0 +1,192 @@
+use std::path::Path;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::{
+    ffi::OsString,
+    fs,
+    io::{ErrorKind, Write},
+};
+
+use anyhow::{anyhow, Result};
+use lapce_rpc::file::TrashedPath;
+
+/// Move the path to the trash. On Linux and the BSDs this is the home trash of
+/// the freedesktop.org trash spec, which works the same when the proxy runs
+/// on a remote machine, and which the path can be restored from. Anything else
+/// goes through the trash of the system, which can't restore it.
+pub fn trash_path(path: &Path) -> Result<Option<TrashedPath>> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    match home_trash().and_then(|trash| trash_to(path, &trash)) {
+        Ok(trashed) => return Ok(Some(trashed)),
+        // The home trash can only take paths on the same file system, the
+        // others go to the trash of their own file system
+        Err(e) if !is_cross_device(&e) => return Err(e),
+        Err(_) => {}
+    }
+
+    trash::delete(path)?;
+    Ok(None)
+}
+
+/// Move a path back from the trash to where it was.
+pub fn restore_path(trashed: &TrashedPath) -> Result<()> {
+    if trashed.path.symlink_metadata().is_ok() {
+        return Err(anyhow!("{} already exists", trashed.path.display()));
+    }
+    if let Some(parent) = trashed.path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&trashed.trash_path, &trashed.path)?;
+    let _ = std::fs::remove_file(&trashed.info_path);
+    Ok(())
+}
+
+/// Delete the path for good.
+pub fn delete_path(path: &Path) -> Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        std::fs::remove_dir_all(path)?;
+    } else {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// The home trash of the trash spec, in the data directory of the user.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn home_trash() -> Result<std::path::PathBuf> {
+    Ok(directories::BaseDirs::new()
+        .ok_or_else(|| anyhow!("can't find the home directory"))?
+        .data_dir()
+        .join("Trash"))
+}
+
+/// Move the path into the `files` of the trash, with its info file in `info`.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn trash_to(path: &Path, trash: &Path) -> Result<TrashedPath> {
+    // The path is kept as an absolute path, but without resolving the path
+    // itself in case it's a symlink
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} can't be trashed", path.display()))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.canonicalize()?,
+        _ => std::env::current_dir()?,
+    };
+    let path = parent.join(name);
+    let encoded_path = encode_path(&path)?;
+
+    let files = trash.join("files");
+    let info = trash.join("info");
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    // The info file is created first, which claims the name in the trash
+    for i in 0.. {
+        let mut trash_name = name.to_os_string();
+        if i > 0 {
+            trash_name.push(format!(".{i}"));
+        }
+        let trash_path = files.join(&trash_name);
+        let info_path = info.join(info_file_name(&trash_name));
+        if trash_path.symlink_metadata().is_ok() {
+            continue;
+        }
+        let mut f = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(f) => f,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let result = write!(
+            f,
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encoded_path,
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+        )
+        .map_err(anyhow::Error::from)
+        .and_then(|_| fs::rename(&path, &trash_path).map_err(anyhow::Error::from));
+        if let Err(e) = result {
+            let _ = fs::remove_file(&info_path);
+            return Err(e);
+        }
+
+        return Ok(TrashedPath {
+            path,
+            trash_path,
+            info_path,
+        });
+    }
+    unreachable!()
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn info_file_name(trash_name: &OsString) -> OsString {
+    let mut name = trash_name.clone();
+    name.push(".trashinfo");
+    name
+}
+
+/// The path as the trash spec has it in the info file, which is escaped like
+/// the path of a URL.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn encode_path(path: &Path) -> Result<String> {
+    let url = url::Url::from_file_path(path)
+        .map_err(|_| anyhow!("{} isn't an absolute path", path.display()))?;
+    Ok(url.path().to_string())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_cross_device(e: &anyhow::Error) -> bool {
+    // EXDEV, which `ErrorKind::CrossesDevices` isn't stable for yet
+    const EXDEV: i32 = 18;
+    e.downcast_ref::<std::io::Error>()
+        .and_then(|e| e.raw_os_error())
+        == Some(EXDEV)
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = std::env::temp_dir()
+            .join(format!("lapce-trash-test-{}", std::process::id()));
+        let trash = dir.join("Trash");
+        let folder = dir.join("folder");
+        fs::create_dir_all(&folder).unwrap();
+        // The folder of a trashed path is canonicalized
+        let path = folder.canonicalize().unwrap().join("file name.txt");
+
+        fs::write(&path, "first").unwrap();
+        let first = trash_to(&path, &trash).unwrap();
+        assert!(!path.exists());
+        assert_eq!(first.path, path);
+        assert_eq!(fs::read_to_string(&first.trash_path).unwrap(), "first");
+        let info = fs::read_to_string(&first.info_path).unwrap();
+        assert!(info.contains("file%20name.txt"), "{info}");
+
+        // Another path of the same name gets a name of its own in the trash
+        fs::write(&path, "second").unwrap();
+        let second = trash_to(&path, &trash).unwrap();
+        assert_ne!(second.trash_path, first.trash_path);
+        assert_ne!(second.info_path, first.info_path);
+
+        restore_path(&second).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert!(!second.trash_path.exists());
+        assert!(!second.info_path.exists());
+
+        // Nothing is restored over the path when it's there again
+        assert!(restore_path(&first).is_err());
+        assert!(first.trash_path.exists());
+        fs::remove_file(&path).unwrap();
+        restore_path(&first).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
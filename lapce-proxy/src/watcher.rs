@@ -1,26 +1,195 @@
 use std::{
     collections::VecDeque,
+    fs,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
-use crossbeam_channel::{unbounded, Receiver};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use lapce_rpc::{core::CoreRpcHandler, crash::Subsystem};
+use lsp_types::{MessageType, ShowMessageParams};
 use notify::{
     event::{ModifyKind, RenameMode},
-    recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode,
-    Watcher,
+    recommended_watcher, Config, ErrorKind, Event, EventKind, PollWatcher,
+    RecommendedWatcher, RecursiveMode, Watcher,
 };
 use parking_lot::Mutex;
+use tracing::{error, warn};
+
+use crate::{crash::spawn_supervised, limits};
 
 /// Wrapper around a `notify::Watcher`. It runs the inner watcher
 /// in a separate thread, and communicates with it via a [crossbeam channel].
 /// [crossbeam channel]: https://docs.rs/crossbeam-channel
 pub struct FileWatcher {
     rx_event: Option<Receiver<Result<Event, notify::Error>>>,
-    inner: RecommendedWatcher,
+    inner: Arc<Mutex<Watchers>>,
     state: Arc<Mutex<WatcherState>>,
 }
 
+/// How often the folders which can't be watched are polled for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The watcher of the system, with the one which polls the folders that it
+/// can't watch since the limit of inotify watches of the user was reached.
+struct Watchers {
+    /// The watcher of the system, which is `None` when it couldn't be started
+    native: Option<RecommendedWatcher>,
+    poller: Option<PollWatcher>,
+    tx_event: Sender<Result<Event, notify::Error>>,
+    /// The folders which are polled
+    polled: Vec<(PathBuf, RecursiveMode)>,
+    core_rpc: Option<CoreRpcHandler>,
+    /// Whether the user was told that the folders are polled
+    limit_reported: bool,
+}
+
+impl Watchers {
+    fn watch(&mut self, path: &Path, mode: RecursiveMode) {
+        let Some(native) = self.native.as_mut() else {
+            self.poll(path, mode);
+            return;
+        };
+        match native.watch(path, mode) {
+            Ok(()) => {}
+            Err(e) if is_watch_limit(&e) => {
+                // The folders of a recursive watch which were watched before
+                // the limit was reached keep their watches, and only the ones
+                // which it didn't get to are polled
+                let unwatched = e
+                    .paths
+                    .first()
+                    .filter(|_| mode == RecursiveMode::Recursive)
+                    .and_then(|failed| unwatched_folders(path, failed));
+                match unwatched {
+                    Some(folders) => {
+                        for folder in folders {
+                            self.poll(&folder, RecursiveMode::Recursive);
+                        }
+                    }
+                    None => {
+                        let _ = native.unwatch(path);
+                        self.poll(path, mode);
+                    }
+                }
+            }
+            Err(e) => warn!("failed to watch {}: {e}", path.display()),
+        }
+    }
+
+    fn unwatch(&mut self, path: &Path) {
+        if let Some(native) = self.native.as_mut() {
+            let _ = native.unwatch(path);
+        }
+        let (removed, polled) = std::mem::take(&mut self.polled)
+            .into_iter()
+            .partition(|(polled, _)| polled.starts_with(path));
+        self.polled = polled;
+        if let Some(poller) = self.poller.as_mut() {
+            for (path, _) in removed {
+                let _ = poller.unwatch(&path);
+            }
+        }
+    }
+
+    /// Poll the folder for changes instead of watching it.
+    fn poll(&mut self, path: &Path, mode: RecursiveMode) {
+        let polled = self.polled.iter().any(|(polled, polled_mode)| {
+            polled == path
+                || (*polled_mode == RecursiveMode::Recursive
+                    && path.starts_with(polled))
+        });
+        if polled {
+            return;
+        }
+        if self.poller.is_none() {
+            let config = Config::default().with_poll_interval(POLL_INTERVAL);
+            match PollWatcher::new(self.tx_event.clone(), config) {
+                Ok(poller) => self.poller = Some(poller),
+                Err(e) => {
+                    error!("failed to start the polling watcher: {e}");
+                    return;
+                }
+            }
+        }
+        let Some(poller) = self.poller.as_mut() else {
+            return;
+        };
+        if let Err(e) = poller.watch(path, mode) {
+            warn!("failed to poll {}: {e}", path.display());
+            return;
+        }
+        self.polled.push((path.to_path_buf(), mode));
+        self.report_limit(path);
+    }
+
+    /// Tell the user once that folders are polled, and how the limit is raised.
+    fn report_limit(&mut self, path: &Path) {
+        if self.limit_reported {
+            return;
+        }
+        let Some(core_rpc) = self.core_rpc.as_ref() else {
+            return;
+        };
+        self.limit_reported = true;
+        let setting = if self.native.is_some() {
+            "max_user_watches"
+        } else {
+            "max_user_instances"
+        };
+        core_rpc.show_message(
+            "File Watcher".to_string(),
+            ShowMessageParams {
+                typ: MessageType::WARNING,
+                message: format!(
+                    "The inotify limit of the user was reached, so {} is polled \
+                     for changes instead, which is slower. The limit is raised \
+                     with `sudo sysctl fs.inotify.{setting}=524288`, and kept \
+                     with `fs.inotify.{setting}=524288` in /etc/sysctl.conf.",
+                    path.display()
+                ),
+            },
+        );
+    }
+}
+
+/// Whether the error is that the inotify watches of the user ran out, which is
+/// ENOSPC when it isn't told apart.
+fn is_watch_limit(e: &notify::Error) -> bool {
+    match &e.kind {
+        ErrorKind::MaxFilesWatch => true,
+        #[cfg(target_os = "linux")]
+        ErrorKind::Io(e) => e.raw_os_error() == Some(libc::ENOSPC),
+        _ => false,
+    }
+}
+
+/// The folders of a recursive watch of `root` which weren't watched when the
+/// watch of `failed` ran out of watches. The watcher walks the folders in the
+/// order of `read_dir`, so they're `failed` and the folders which come after
+/// the ones on the way to it.
+fn unwatched_folders(root: &Path, failed: &Path) -> Option<Vec<PathBuf>> {
+    let rest = failed.strip_prefix(root).ok()?;
+    let mut folders = vec![failed.to_path_buf()];
+    let mut parent = root.to_path_buf();
+    for component in rest.components() {
+        let child = parent.join(component);
+        let entries = fs::read_dir(&parent).ok()?;
+        // Symlinks to folders are followed like the watcher does
+        folders.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .skip_while(|path| path != &child)
+                .skip(1)
+                .filter(|path| path.is_dir()),
+        );
+        parent = child;
+    }
+    Some(folders)
+}
+
 #[derive(Debug, Default)]
 struct WatcherState {
     events: EventQueue,
@@ -34,6 +203,9 @@ struct Watchee {
     recursive: bool,
     token: WatchToken,
     filter: Option<Box<PathFilter>>,
+    /// The folders which the watch takes, which are only counted when there's
+    /// a limit of them, and are 0 for another watch of a path which is watched
+    watches: usize,
 }
 
 /// Token provided to `FileWatcher`, to associate events with
@@ -61,7 +233,19 @@ pub fn new() -> Self {
 
         let state = Arc::new(Mutex::new(WatcherState::default()));
 
-        let inner = recommended_watcher(tx_event).expect("watcher should spawn");
+        // There's no watcher of the system when the inotify instances of the
+        // user ran out, and then everything is polled
+        let native = recommended_watcher(tx_event.clone())
+            .map_err(|e| error!("failed to start the file watcher: {e}"))
+            .ok();
+        let inner = Arc::new(Mutex::new(Watchers {
+            native,
+            poller: None,
+            tx_event,
+            polled: Vec::new(),
+            core_rpc: None,
+            limit_reported: false,
+        }));
 
         FileWatcher {
             rx_event: Some(rx_event),
@@ -70,11 +254,35 @@ pub fn new() -> Self {
         }
     }
 
-    pub fn notify<T: Notify + 'static>(&mut self, peer: T) {
+    /// Send the events to the peer from a thread, which is started again when
+    /// the peer panics, with the panic reported through `core_rpc`.
+    pub fn notify<T: Notify + 'static>(
+        &mut self,
+        peer: T,
+        core_rpc: CoreRpcHandler,
+    ) {
         let rx_event = self.rx_event.take().unwrap();
         let state = self.state.clone();
-        std::thread::spawn(move || {
-            while let Ok(Ok(event)) = rx_event.recv() {
+        let inner = self.inner.clone();
+        inner.lock().core_rpc = Some(core_rpc.clone());
+        spawn_supervised(Subsystem::FileWatcher, "", core_rpc, move || {
+            while let Ok(event) = rx_event.recv() {
+                let event = match event {
+                    Ok(event) => event,
+                    // The folders which were created in a recursive watch after
+                    // the limit was reached are polled
+                    Err(e) if is_watch_limit(&e) => {
+                        let mut inner = inner.lock();
+                        for path in e.paths.iter() {
+                            inner.poll(path, RecursiveMode::Recursive);
+                        }
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("file watcher error: {e}");
+                        continue;
+                    }
+                };
                 let mut events = Vec::new();
                 {
                     let mut state = state.lock();
@@ -136,16 +344,43 @@ fn watch_impl(
 
         let mut state = self.state.lock();
 
-        let w = Watchee {
+        let mut w = Watchee {
             path,
             recursive,
             token,
             filter,
+            watches: 0,
         };
-        let mode = mode_from_bool(w.recursive);
 
         if !state.watchees.iter().any(|w2| w.path == w2.path) {
-            let _ = self.inner.watch(&w.path, mode);
+            w.watches = 1;
+            let max = limits::limits().max_watches;
+            if max > 0 {
+                let watched: usize = state.watchees.iter().map(|w| w.watches).sum();
+                let left = max.saturating_sub(watched);
+                if w.recursive {
+                    w.watches = limits::count_folders(&w.path, left + 1);
+                    if w.watches > left {
+                        // Only the folder itself is watched then
+                        warn!(
+                            "watching {} recursively goes over the limit of {max} watches",
+                            w.path.display()
+                        );
+                        w.recursive = false;
+                        w.watches = 1;
+                    }
+                }
+                if w.watches > left {
+                    warn!(
+                        "watching {} goes over the limit of {max} watches",
+                        w.path.display()
+                    );
+                    return;
+                }
+            }
+            self.inner
+                .lock()
+                .watch(&w.path, mode_from_bool(w.recursive));
         }
 
         state.watchees.push(w);
@@ -164,8 +399,13 @@ pub fn unwatch(&mut self, path: &Path, token: WatchToken) {
 
         if let Some(idx) = idx {
             let removed = state.watchees.remove(idx);
-            if !state.watchees.iter().any(|w| w.path == removed.path) {
-                let _ = self.inner.unwatch(&removed.path);
+            match state.watchees.iter_mut().find(|w| w.path == removed.path) {
+                // The path is still watched, by the watches which were counted
+                // for the removed one
+                Some(w) => w.watches += removed.watches,
+                None => {
+                    self.inner.lock().unwatch(&removed.path);
+                }
             }
             //TODO: Ideally we would be tracking what paths we're watching with
             // some prefix-tree-like structure, which would let us keep track
@@ -188,8 +428,9 @@ pub fn unwatch(&mut self, path: &Path, token: WatchToken) {
                     .map(|w| (w.path.to_owned(), mode_from_bool(w.recursive)))
                     .collect::<Vec<_>>();
 
+                let mut inner = self.inner.lock();
                 for (path, mode) in to_add {
-                    let _ = self.inner.watch(&path, mode);
+                    inner.watch(&path, mode);
                 }
             }
         }
@@ -267,3 +508,51 @@ fn mode_from_bool(is_recursive: bool) -> RecursiveMode {
         RecursiveMode::NonRecursive
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_watch_limit() {
+        assert!(is_watch_limit(&notify::Error::new(
+            ErrorKind::MaxFilesWatch
+        )));
+        #[cfg(target_os = "linux")]
+        assert!(is_watch_limit(&notify::Error::io(
+            std::io::Error::from_raw_os_error(libc::ENOSPC)
+        )));
+        assert!(!is_watch_limit(&notify::Error::path_not_found()));
+    }
+
+    #[test]
+    fn test_unwatched_folders() {
+        let root = std::env::temp_dir()
+            .join(format!("lapce-watcher-test-{}", std::process::id()));
+        for folder in ["a/b/c", "a/d", "e/f", "g"] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+        fs::write(root.join("a/file"), "").unwrap();
+
+        // The folders in the order which the watcher walks them in
+        let walked: Vec<PathBuf> = walkdir::WalkDir::new(&root)
+            .follow_links(true)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_type().is_dir())
+            .map(|entry| entry.into_path())
+            .collect();
+        assert_eq!(walked.len(), 8);
+        for (i, failed) in walked.iter().enumerate() {
+            let unwatched = unwatched_folders(&root, failed).unwrap();
+            // Each folder is either watched or polled, and not both
+            for (j, folder) in walked.iter().enumerate() {
+                let polled = unwatched.iter().any(|path| folder.starts_with(path));
+                assert_eq!(polled, j >= i, "{} failed", failed.display());
+            }
+        }
+        assert_eq!(unwatched_folders(&root, Path::new("/elsewhere")), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -22,3 +22,50 @@ pub struct BufferHeadResponse {
     pub version: String,
     pub content: String,
 }
+
+/// How a file is written when a buffer is saved
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SaveMode {
+    /// Like `Atomic`, but write the file in place when renaming over it would
+    /// break its hard links or change its owner
+    #[default]
+    Auto,
+    /// Write a temporary file next to the file and rename it over the file, so
+    /// that the file is never left half written
+    Atomic,
+    /// Write over the content of the file, which keeps the file itself, for tools
+    /// which watch the file and lose track of it when it's replaced
+    InPlace,
+}
+
+impl SaveMode {
+    pub const ALL: [SaveMode; 3] =
+        [SaveMode::Auto, SaveMode::Atomic, SaveMode::InPlace];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SaveMode::Auto => "auto",
+            SaveMode::Atomic => "atomic",
+            SaveMode::InPlace => "in-place",
+        }
+    }
+
+    /// The mode named in the settings, which falls back to `auto`.
+    pub fn from_name(name: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveOptions {
+    pub mode: SaveMode,
+    /// Flush the file to the disk before the save is done
+    pub fsync: bool,
+    /// Keep the previous content of the file in a backup file next to it, which
+    /// is the file name with a `~` at the end
+    pub backup: bool,
+}
@@ -16,9 +16,15 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    dap_types::{self, DapId, RunDebugConfig, StackFrame, Stopped, ThreadId},
+    coverage::Coverage,
+    crash::CrashReport,
+    dap_types::{
+        self, DapId, RunDebugConfig, StackFrame, Stopped, TaskId, Thread, ThreadId,
+    },
     file::PathObject,
-    plugin::{PluginId, VoltInfo, VoltMetadata},
+    plugin::{
+        PluginId, StatusItemParams, TestItemParams, VoltID, VoltInfo, VoltMetadata,
+    },
     source_control::DiffInfo,
     terminal::TermId,
     RequestId, RpcError, RpcMessage,
@@ -52,8 +58,28 @@ pub enum CoreNotification {
     },
     OpenPaths {
         paths: Vec<PathObject>,
+        /// Open the paths in the active window tab, rather than the one whose
+        /// workspace has them
+        #[serde(default)]
+        reuse_window: bool,
+        /// The paths are two files which are opened side by side to compare
+        /// them
+        #[serde(default)]
+        diff: bool,
+        /// The `lapce` process which sent the paths waits to be told that the
+        /// files are closed
+        #[serde(default)]
+        wait: bool,
+    },
+    /// Open a `lapce://` link which another `lapce` process was given
+    OpenLink {
+        url: String,
     },
     WorkspaceFileChange {},
+    /// The files of the workspace which were changed, created or removed
+    WorkspaceFilesChanged {
+        paths: Vec<PathBuf>,
+    },
     PublishDiagnostics {
         diagnostics: PublishDiagnosticsParams,
     },
@@ -86,6 +112,33 @@ pub enum CoreNotification {
         volt: VoltInfo,
         only_installing: bool,
     },
+    /// The language server of a plugin exited
+    PluginServerStopped {
+        volt_id: VoltID,
+        server: String,
+    },
+    /// A thread of the proxy panicked
+    SubsystemCrashed {
+        report: CrashReport,
+    },
+    /// A plugin shows or updates an item in the status bar
+    SetStatusItem {
+        volt_id: VoltID,
+        item: StatusItemParams,
+    },
+    RemoveStatusItem {
+        volt_id: VoltID,
+        id: String,
+    },
+    /// A plugin replaces the tests which it found
+    SetTests {
+        volt_id: VoltID,
+        tests: Vec<TestItemParams>,
+    },
+    /// The coverage report which is watched changed, or a plugin sent coverage
+    CoverageChanged {
+        coverage: Coverage,
+    },
     DiffInfo {
         diff: DiffInfo,
     },
@@ -103,23 +156,103 @@ pub enum CoreNotification {
     RunInTerminal {
         config: RunDebugConfig,
     },
+    /// A debug session whose debuggee isn't run in a terminal, like one which
+    /// attached to a process or the child session of another
+    DapSessionStarted {
+        config: RunDebugConfig,
+    },
     Log {
         level: String,
         message: String,
+        /// The module of the proxy which the message is from
+        #[serde(default)]
+        target: String,
     },
     DapStopped {
         dap_id: DapId,
         stopped: Stopped,
         stack_frames: HashMap<ThreadId, Vec<StackFrame>>,
     },
+    /// The threads of the debuggee, which are listed again when one starts or
+    /// exits
+    DapThreads {
+        dap_id: DapId,
+        threads: Vec<Thread>,
+    },
     DapContinued {
         dap_id: DapId,
+        /// The thread which was resumed on its own, which is `None` when all
+        /// the threads were
+        #[serde(default)]
+        thread_id: Option<ThreadId>,
+    },
+    /// The debuggee of the session ended, with the exit code which the debugger
+    /// gave for it
+    DapTerminated {
+        dap_id: DapId,
+        exit_code: Option<usize>,
     },
     DapBreakpointsResp {
         dap_id: DapId,
         path: PathBuf,
         breakpoints: Vec<dap_types::Breakpoint>,
     },
+    /// The debugger changed, added or removed a breakpoint by itself, where the
+    /// reason is `changed`, `new` or `removed`
+    DapBreakpointChanged {
+        dap_id: DapId,
+        reason: String,
+        breakpoint: dap_types::Breakpoint,
+    },
+    /// The output of the debugged program, for the debug console
+    DapOutput {
+        dap_id: DapId,
+        output: dap_types::Output,
+    },
+    /// The bytes of the memory at the offset from the reference were changed,
+    /// and have to be read again
+    DapMemory {
+        dap_id: DapId,
+        memory: dap_types::Memory,
+    },
+    /// The value of a watch expression in the frame, or why it couldn't be
+    /// evaluated
+    DapWatchEvaluated {
+        dap_id: DapId,
+        frame_id: usize,
+        expression: String,
+        result: Result<dap_types::EvaluateResponse, String>,
+    },
+    /// What the debugger supports, like the exception filters it can break on
+    DapCapabilities {
+        dap_id: DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    },
+    /// The data breakpoints which the debugger set, in the order they were sent
+    DapDataBreakpointsResp {
+        dap_id: DapId,
+        breakpoints: Vec<dap_types::Breakpoint>,
+    },
+    /// The task of a debug config failed, where the session isn't started when
+    /// it's the one of before the launch
+    DapTaskFailed {
+        config: String,
+        task: String,
+        message: String,
+    },
+    /// A task started, whose output is shown in a terminal of its own
+    TaskStarted {
+        task_id: TaskId,
+        name: String,
+    },
+    TaskOutput {
+        task_id: TaskId,
+        content: Vec<u8>,
+    },
+    /// The task exited, or was killed
+    TaskStopped {
+        task_id: TaskId,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -223,6 +356,10 @@ pub fn workspace_file_change(&self) {
         self.notification(CoreNotification::WorkspaceFileChange {});
     }
 
+    pub fn workspace_files_changed(&self, paths: Vec<PathBuf>) {
+        self.notification(CoreNotification::WorkspaceFilesChanged { paths });
+    }
+
     pub fn diff_info(&self, diff: DiffInfo) {
         self.notification(CoreNotification::DiffInfo { diff });
     }
@@ -278,14 +415,54 @@ pub fn volt_removed(&self, volt: VoltInfo, only_installing: bool) {
         });
     }
 
+    pub fn plugin_server_stopped(&self, volt_id: VoltID, server: String) {
+        self.notification(CoreNotification::PluginServerStopped { volt_id, server });
+    }
+
+    pub fn subsystem_crashed(&self, report: CrashReport) {
+        self.notification(CoreNotification::SubsystemCrashed { report });
+    }
+
+    pub fn set_status_item(&self, volt_id: VoltID, item: StatusItemParams) {
+        self.notification(CoreNotification::SetStatusItem { volt_id, item });
+    }
+
+    pub fn remove_status_item(&self, volt_id: VoltID, id: String) {
+        self.notification(CoreNotification::RemoveStatusItem { volt_id, id });
+    }
+
+    pub fn set_tests(&self, volt_id: VoltID, tests: Vec<TestItemParams>) {
+        self.notification(CoreNotification::SetTests { volt_id, tests });
+    }
+
+    pub fn coverage_changed(&self, coverage: Coverage) {
+        self.notification(CoreNotification::CoverageChanged { coverage });
+    }
+
     pub fn run_in_terminal(&self, config: RunDebugConfig) {
         self.notification(CoreNotification::RunInTerminal { config });
     }
 
+    pub fn dap_session_started(&self, config: RunDebugConfig) {
+        self.notification(CoreNotification::DapSessionStarted { config });
+    }
+
     pub fn log(&self, level: tracing::Level, message: String) {
+        self.forward_log(level, "lapce_proxy".to_string(), message);
+    }
+
+    /// Send an event which was logged in the proxy to the app, which logs it along
+    /// with its own.
+    pub fn forward_log(
+        &self,
+        level: tracing::Level,
+        target: String,
+        message: String,
+    ) {
         self.notification(CoreNotification::Log {
             level: level.as_str().to_string(),
             message,
+            target,
         });
     }
 
@@ -333,8 +510,16 @@ pub fn dap_stopped(
         });
     }
 
-    pub fn dap_continued(&self, dap_id: DapId) {
-        self.notification(CoreNotification::DapContinued { dap_id });
+    pub fn dap_threads(&self, dap_id: DapId, threads: Vec<Thread>) {
+        self.notification(CoreNotification::DapThreads { dap_id, threads });
+    }
+
+    pub fn dap_continued(&self, dap_id: DapId, thread_id: Option<ThreadId>) {
+        self.notification(CoreNotification::DapContinued { dap_id, thread_id });
+    }
+
+    pub fn dap_terminated(&self, dap_id: DapId, exit_code: Option<usize>) {
+        self.notification(CoreNotification::DapTerminated { dap_id, exit_code });
     }
 
     pub fn dap_breakpoints_resp(
@@ -350,6 +535,84 @@ pub fn dap_breakpoints_resp(
         });
     }
 
+    pub fn dap_breakpoint_changed(
+        &self,
+        dap_id: DapId,
+        reason: String,
+        breakpoint: dap_types::Breakpoint,
+    ) {
+        self.notification(CoreNotification::DapBreakpointChanged {
+            dap_id,
+            reason,
+            breakpoint,
+        });
+    }
+
+    pub fn dap_output(&self, dap_id: DapId, output: dap_types::Output) {
+        self.notification(CoreNotification::DapOutput { dap_id, output });
+    }
+
+    pub fn dap_memory(&self, dap_id: DapId, memory: dap_types::Memory) {
+        self.notification(CoreNotification::DapMemory { dap_id, memory });
+    }
+
+    pub fn dap_watch_evaluated(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        expression: String,
+        result: Result<dap_types::EvaluateResponse, String>,
+    ) {
+        self.notification(CoreNotification::DapWatchEvaluated {
+            dap_id,
+            frame_id,
+            expression,
+            result,
+        });
+    }
+
+    pub fn dap_capabilities(
+        &self,
+        dap_id: DapId,
+        capabilities: dap_types::DebuggerCapabilities,
+    ) {
+        self.notification(CoreNotification::DapCapabilities {
+            dap_id,
+            capabilities,
+        });
+    }
+
+    pub fn dap_data_breakpoints_resp(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<dap_types::Breakpoint>,
+    ) {
+        self.notification(CoreNotification::DapDataBreakpointsResp {
+            dap_id,
+            breakpoints,
+        });
+    }
+
+    pub fn dap_task_failed(&self, config: String, task: String, message: String) {
+        self.notification(CoreNotification::DapTaskFailed {
+            config,
+            task,
+            message,
+        });
+    }
+
+    pub fn task_started(&self, task_id: TaskId, name: String) {
+        self.notification(CoreNotification::TaskStarted { task_id, name });
+    }
+
+    pub fn task_output(&self, task_id: TaskId, content: Vec<u8>) {
+        self.notification(CoreNotification::TaskOutput { task_id, content });
+    }
+
+    pub fn task_stopped(&self, task_id: TaskId) {
+        self.notification(CoreNotification::TaskStopped { task_id });
+    }
+
     pub fn home_dir(&self, path: PathBuf) {
         self.notification(CoreNotification::HomeDir { path });
     }
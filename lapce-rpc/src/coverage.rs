@@ -0,0 +1,77 @@
+//! The code coverage of the files of a workspace, which comes from a report of
+//! lcov or cobertura or from a plugin.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How much of a line ran, from the least to the most.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LineCoverage {
+    Uncovered,
+    /// The line ran, but some of its branches didn't
+    Partial,
+    Covered,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCoverage {
+    /// The lines which have code, by line starting at 0
+    pub lines: BTreeMap<u32, LineCoverage>,
+}
+
+impl FileCoverage {
+    /// Add the coverage of a line, where a line which more than one record has
+    /// is as covered as the most covered of them.
+    pub fn add_line(&mut self, line: u32, coverage: LineCoverage) {
+        let entry = self.lines.entry(line).or_insert(coverage);
+        *entry = (*entry).max(coverage);
+    }
+
+    /// The percentage of the lines with code which ran, or `None` when the file
+    /// has none.
+    pub fn percent(&self) -> Option<f64> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        let ran = self
+            .lines
+            .values()
+            .filter(|coverage| **coverage != LineCoverage::Uncovered)
+            .count();
+        Some(ran as f64 * 100.0 / self.lines.len() as f64)
+    }
+}
+
+/// The coverage by file, which a plugin sends as the params of
+/// `lapce/setCoverage`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Coverage {
+    pub files: HashMap<PathBuf, FileCoverage>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileCoverage, LineCoverage};
+
+    #[test]
+    fn test_file_coverage() {
+        let mut file = FileCoverage::default();
+        assert_eq!(file.percent(), None);
+        file.add_line(0, LineCoverage::Uncovered);
+        file.add_line(0, LineCoverage::Partial);
+        file.add_line(1, LineCoverage::Covered);
+        file.add_line(1, LineCoverage::Uncovered);
+        file.add_line(2, LineCoverage::Uncovered);
+        file.add_line(3, LineCoverage::Uncovered);
+        assert_eq!(file.lines[&0], LineCoverage::Partial);
+        assert_eq!(file.lines[&1], LineCoverage::Covered);
+        assert_eq!(file.percent(), Some(50.0));
+    }
+}
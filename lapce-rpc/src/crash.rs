@@ -0,0 +1,50 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A part of the proxy which runs on threads of its own, and is started again
+/// when one of them panics
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Subsystem {
+    LanguageServer,
+    DebugAdapter,
+    Terminal,
+    FileWatcher,
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Subsystem::LanguageServer => "language server",
+            Subsystem::DebugAdapter => "debug adapter",
+            Subsystem::Terminal => "terminal",
+            Subsystem::FileWatcher => "file watcher",
+        })
+    }
+}
+
+/// A panic on a thread of the proxy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrashReport {
+    pub subsystem: Subsystem,
+    /// What the subsystem is running for, like the name of a language server
+    pub name: String,
+    /// The message which the panic was with
+    pub message: String,
+    pub backtrace: String,
+    /// Whether the subsystem was started again, which it isn't after it panicked
+    /// too many times in a row
+    pub restarted: bool,
+}
+
+impl CrashReport {
+    /// The subsystem along with what it runs for, like
+    /// `language server rust-analyzer`.
+    pub fn source(&self) -> String {
+        if self.name.is_empty() {
+            self.subsystem.to_string()
+        } else {
+            format!("{} {}", self.subsystem, self.name)
+        }
+    }
+}
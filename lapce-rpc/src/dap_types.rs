@@ -1,9 +1,9 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::counter::Counter;
+use crate::{counter::Counter, runnable::Runnable};
 
 #[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct DapId(pub u64);
@@ -21,23 +21,186 @@ pub fn next() -> Self {
     }
 }
 
+/// A run of a task of the workspace by the proxy, whose output is shown in a
+/// terminal of the app
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TaskId(pub u64);
+
+impl TaskId {
+    pub fn next() -> Self {
+        static TASK_ID_COUNTER: Counter = Counter::new();
+        Self(TASK_ID_COUNTER.next())
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct DapServer {
+    /// The program of the adapter, which isn't started when it's empty and the
+    /// adapter listens already on its transport
     pub program: String,
     pub args: Vec<String>,
     pub cwd: Option<PathBuf>,
+    pub transport: DapTransport,
+}
+
+/// How the messages of a debug adapter are sent and received
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DapTransport {
+    /// The stdin and stdout of the program of the adapter
+    #[default]
+    Stdio,
+    /// The port which the adapter listens on, on `host` or else this machine,
+    /// which is connected to once its program is started. A port of 0 is a free
+    /// one which is picked, and passed to the program as `${port}` in its args.
+    Tcp { host: Option<String>, port: u16 },
+    /// The unix socket which the adapter listens on, which isn't supported on
+    /// Windows, where a named pipe can't be read and written at once without
+    /// overlapped IO
+    Pipe { path: String },
+}
+
+/// How long the requests to a debug adapter are waited for, in seconds, where 0
+/// waits for as long as it takes.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DapRequestTimeouts {
+    pub default: u64,
+    /// The timeouts of the commands which take another one, like `evaluate`
+    pub commands: HashMap<String, u64>,
+}
+
+impl Default for DapRequestTimeouts {
+    fn default() -> Self {
+        Self {
+            default: 30,
+            commands: HashMap::new(),
+        }
+    }
+}
+
+impl DapRequestTimeouts {
+    pub fn timeout(&self, command: &str) -> Option<Duration> {
+        let secs = self.commands.get(command).copied().unwrap_or(self.default);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+/// The debug adapter which a config is debugged with, instead of the default one
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugAdapter {
+    /// The adapter of the registry of the proxy, like `codelldb`, `debugpy` or
+    /// `js-debug`, which is downloaded when it isn't installed, instead of the
+    /// program
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub transport: DapTransport,
+}
+
+/// Whether the debugger starts the program or attaches to one which runs
+/// already.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RunDebugConfigMode {
+    #[default]
+    Launch,
+    Attach,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct RunDebugConfig {
     pub name: String,
     pub program: String,
+    #[serde(default)]
     pub args: Vec<String>,
     pub cwd: Option<String>,
     pub env: Option<HashMap<String, String>>,
+    /// A file of `NAME=value` lines whose variables are added to `env`, relative
+    /// to the workspace
+    #[serde(alias = "envFile")]
+    pub env_file: Option<String>,
+    /// The task of `.lapce/tasks.toml` which is run before the debuggee is
+    /// launched or attached to, where the session isn't started unless it
+    /// succeeds
+    #[serde(alias = "preLaunchTask")]
+    pub pre_launch_task: Option<String>,
+    /// The task which is run once the debug session ends
+    #[serde(alias = "postDebugTask")]
+    pub post_debug_task: Option<String>,
+    #[serde(default)]
+    pub mode: RunDebugConfigMode,
+    /// The process which is attached to
+    pub pid: Option<u32>,
+    /// The debug port which is attached to, on `host` or else this machine
+    pub port: Option<u16>,
+    pub host: Option<String>,
+    #[serde(default)]
+    pub adapter: Option<DebugAdapter>,
     #[serde(skip)]
     pub debug_command: Option<String>,
     #[serde(skip)]
     pub dap_id: DapId,
+    /// The session which started this one with a `startDebugging` request
+    #[serde(skip)]
+    pub parent: Option<DapId>,
+    /// The arguments of the launch or attach request of a child session, which
+    /// are sent as the adapter gave them instead of the ones of the config
+    #[serde(skip)]
+    pub configuration: Option<Value>,
+    /// Whether the debugger launches the program without debugging it, for a
+    /// run of a config whose adapter knows how to start the program
+    #[serde(skip)]
+    pub no_debug: bool,
+    /// The runnable of the language server which the config runs, which is
+    /// built for the program to debug
+    #[serde(skip)]
+    pub runnable: Option<Runnable>,
+    /// The other keys of the config, like `justMyCode` or `console`, which are
+    /// added to the arguments of the launch or the attach as they are
+    #[serde(flatten)]
+    pub properties: serde_json::Map<String, Value>,
+}
+
+impl RunDebugConfig {
+    /// Add the variables of the content of an env file to the env of the config,
+    /// where the ones which the config has already are kept.
+    pub fn add_env_file(&mut self, content: &str) {
+        let env = self.env.get_or_insert_with(HashMap::new);
+        for (name, value) in parse_env_file(content) {
+            env.entry(name).or_insert(value);
+        }
+    }
+}
+
+/// The variables of an env file, whose lines are `NAME=value`, which can start
+/// with `export` and have the value in quotes. Blank lines and the ones which
+/// start with `#` are skipped.
+pub fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, value) = line.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+                .unwrap_or(value);
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
 }
 
 pub trait Request {
@@ -92,6 +255,9 @@ pub enum DapEvent {
     Process(Process),
     Capabilities(Capabilities),
     Memory(Memory),
+    ProgressStart(ProgressStart),
+    ProgressUpdate(ProgressUpdate),
+    ProgressEnd(ProgressEnd),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -140,6 +306,8 @@ pub struct InitializeParams {
     pub supports_progress_reporting: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supports_invalidated_event: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_start_debugging_request: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -170,7 +338,7 @@ pub struct ExceptionBreakpointsFilter {
     pub condition_description: Option<String>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DebuggerCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -249,6 +417,8 @@ pub struct DebuggerCapabilities {
     pub additional_module_columns: Option<Vec<ColumnDescriptor>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supported_checksum_algorithms: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_single_thread_execution_requests: Option<bool>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -441,17 +611,68 @@ pub struct Process {
     pub pointer_size: Option<usize>,
 }
 
+impl DebuggerCapabilities {
+    /// Take the capabilities which `changed` sets, as a `capabilities` event
+    /// only has the ones which changed.
+    pub fn merge(&mut self, changed: &DebuggerCapabilities) {
+        let (Ok(Value::Object(mut merged)), Ok(Value::Object(changed))) =
+            (serde_json::to_value(&*self), serde_json::to_value(changed))
+        else {
+            return;
+        };
+        merged.extend(changed);
+        if let Ok(merged) = serde_json::from_value(Value::Object(merged)) {
+            *self = merged;
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Capabilities {
     pub capabilities: DebuggerCapabilities,
 }
 
+/// A long operation of the debugger, like loading the symbols, which lasts
+/// until the `progressEnd` event with its id
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressStart {
+    pub progress_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancellable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressUpdate {
+    pub progress_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percentage: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressEnd {
+    pub progress_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Memory {
     pub memory_reference: String,
-    pub offset: usize,
+    pub offset: i64,
     pub count: usize,
 }
 
@@ -463,6 +684,14 @@ impl Request for Launch {
     const COMMAND: &'static str = "launch";
 }
 
+pub enum Attach {}
+
+impl Request for Attach {
+    type Arguments = Value;
+    type Result = Value;
+    const COMMAND: &'static str = "attach";
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunInTerminalResponse {
@@ -495,6 +724,31 @@ impl Request for RunInTerminal {
     const COMMAND: &'static str = "runInTerminal";
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartDebuggingRequestKind {
+    Launch,
+    Attach,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartDebuggingRequestArguments {
+    /// The arguments of the launch or attach request of the new session
+    pub configuration: Value,
+    pub request: StartDebuggingRequestKind,
+}
+
+/// Sent by the adapter to start a child session of the one it's sent in
+#[derive(Debug)]
+pub enum StartDebugging {}
+
+impl Request for StartDebugging {
+    type Arguments = StartDebuggingRequestArguments;
+    type Result = Value;
+    const COMMAND: &'static str = "startDebugging";
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetBreakpointsArguments {
@@ -522,6 +776,102 @@ impl Request for SetBreakpoints {
     const COMMAND: &'static str = "setBreakpoints";
 }
 
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExceptionBreakpointsArguments {
+    /// The ids of the exception filters which are enabled
+    pub filters: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExceptionBreakpointsResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoints: Option<Vec<Breakpoint>>,
+}
+
+#[derive(Debug)]
+pub enum SetExceptionBreakpoints {}
+
+impl Request for SetExceptionBreakpoints {
+    type Arguments = SetExceptionBreakpointsArguments;
+    /// Older debuggers respond without a body
+    type Result = Option<SetExceptionBreakpointsResponse>;
+    const COMMAND: &'static str = "setExceptionBreakpoints";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataBreakpointAccessType {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBreakpointInfoArguments {
+    /// The reference of the container of the variable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<usize>,
+    pub name: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBreakpointInfoResponse {
+    /// The id of the data to break on, which is null when it can't be
+    pub data_id: Option<String>,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_types: Option<Vec<DataBreakpointAccessType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_persist: Option<bool>,
+}
+
+#[derive(Debug)]
+pub enum DataBreakpointInfo {}
+
+impl Request for DataBreakpointInfo {
+    type Arguments = DataBreakpointInfoArguments;
+    type Result = DataBreakpointInfoResponse;
+    const COMMAND: &'static str = "dataBreakpointInfo";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataBreakpoint {
+    pub data_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_type: Option<DataBreakpointAccessType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDataBreakpointsArguments {
+    /// All the data breakpoints, which replace the ones that were set before
+    pub breakpoints: Vec<DataBreakpoint>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDataBreakpointsResponse {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Debug)]
+pub enum SetDataBreakpoints {}
+
+impl Request for SetDataBreakpoints {
+    type Arguments = SetDataBreakpointsArguments;
+    type Result = SetDataBreakpointsResponse;
+    const COMMAND: &'static str = "setDataBreakpoints";
+}
+
 #[derive(Debug)]
 pub enum ConfigurationDone {}
 
@@ -535,6 +885,10 @@ impl Request for ConfigurationDone {
 #[serde(rename_all = "camelCase")]
 pub struct ContinueArguments {
     pub thread_id: ThreadId,
+    /// Whether only the thread is resumed, which an adapter takes when it
+    /// supports single thread execution requests
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub single_thread: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -553,6 +907,42 @@ impl Request for Continue {
     const COMMAND: &'static str = "continue";
 }
 
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReverseContinueArguments {
+    pub thread_id: ThreadId,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub single_thread: bool,
+}
+
+/// Run backwards until a breakpoint is hit, for an adapter which supports
+/// stepping back, like the ones of record and replay debuggers
+#[derive(Debug)]
+pub enum ReverseContinue {}
+
+impl Request for ReverseContinue {
+    type Arguments = ReverseContinueArguments;
+    type Result = ();
+    const COMMAND: &'static str = "reverseContinue";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepBackArguments {
+    pub thread_id: ThreadId,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub single_thread: bool,
+}
+
+#[derive(Debug)]
+pub enum StepBack {}
+
+impl Request for StepBack {
+    type Arguments = StepBackArguments;
+    type Result = ();
+    const COMMAND: &'static str = "stepBack";
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ThreadsResponse {
@@ -648,6 +1038,22 @@ impl Request for Disconnect {
     const COMMAND: &'static str = "disconnect";
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelArguments {
+    pub request_id: Option<u64>,
+    pub progress_id: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Cancel {}
+
+impl Request for Cancel {
+    type Arguments = CancelArguments;
+    type Result = ();
+    const COMMAND: &'static str = "cancel";
+}
+
 #[derive(Debug)]
 pub enum Terminate {}
 
@@ -657,6 +1063,22 @@ impl Request for Terminate {
     const COMMAND: &'static str = "terminate";
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminateThreadsArguments {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_ids: Option<Vec<ThreadId>>,
+}
+
+#[derive(Debug)]
+pub enum TerminateThreads {}
+
+impl Request for TerminateThreads {
+    type Arguments = TerminateThreadsArguments;
+    type Result = ();
+    const COMMAND: &'static str = "terminateThreads";
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PauseArguments {
@@ -671,3 +1093,434 @@ impl Request for Pause {
     type Result = ();
     const COMMAND: &'static str = "pause";
 }
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesArguments {
+    pub frame_id: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+    /// Whether the variables of the scope are expensive to get, so they're only
+    /// requested when asked for
+    pub expensive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesResponse {
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Debug)]
+pub enum Scopes {}
+
+impl Request for Scopes {
+    type Arguments = ScopesArguments;
+    type Result = ScopesResponse;
+    const COMMAND: &'static str = "scopes";
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesArguments {
+    pub variables_reference: usize,
+    /// Either `indexed` or `named`, for only the children of that kind
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluate_name: Option<String>,
+    /// The reference of the children of the variable, which is 0 when the
+    /// variable isn't structured
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesResponse {
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug)]
+pub enum Variables {}
+
+impl Request for Variables {
+    type Arguments = VariablesArguments;
+    type Result = VariablesResponse;
+    const COMMAND: &'static str = "variables";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateArguments {
+    pub expression: String,
+    /// The frame whose scopes the expression is evaluated in, or the global
+    /// scope when it's not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<usize>,
+    /// Where the expression comes from, like `repl` for a debug console
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    pub result: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    pub variables_reference: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Evaluate {}
+
+impl Request for Evaluate {
+    type Arguments = EvaluateArguments;
+    type Result = EvaluateResponse;
+    const COMMAND: &'static str = "evaluate";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVariableArguments {
+    /// The reference of the container of the variable
+    pub variables_reference: usize,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVariableResponse {
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    /// The reference of the children of the new value, which is 0 or not set
+    /// when it isn't structured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum SetVariable {}
+
+impl Request for SetVariable {
+    type Arguments = SetVariableArguments;
+    type Result = SetVariableResponse;
+    const COMMAND: &'static str = "setVariable";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExpressionArguments {
+    /// The expression which is assigned to, like the one of a watch
+    pub expression: String,
+    pub value: String,
+    /// The frame whose scopes the expression is in, or the global scope when
+    /// it's not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetExpressionResponse {
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum SetExpression {}
+
+impl Request for SetExpression {
+    type Arguments = SetExpressionArguments;
+    type Result = SetExpressionResponse;
+    const COMMAND: &'static str = "setExpression";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartFrameArguments {
+    pub frame_id: usize,
+}
+
+#[derive(Debug)]
+pub enum RestartFrame {}
+
+impl Request for RestartFrame {
+    type Arguments = RestartFrameArguments;
+    type Result = ();
+    const COMMAND: &'static str = "restartFrame";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartArguments {
+    /// The arguments of the launch or the attach, which can be new ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+}
+
+#[derive(Debug)]
+pub enum Restart {}
+
+impl Request for Restart {
+    type Arguments = RestartArguments;
+    type Result = ();
+    const COMMAND: &'static str = "restart";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoTargetsArguments {
+    pub source: Source,
+    /// The line which the targets are looked for at, counted from 1
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+/// Where the execution can be moved to with a `goto` request.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoTarget {
+    pub id: usize,
+    pub label: String,
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_pointer_reference: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoTargetsResponse {
+    pub targets: Vec<GotoTarget>,
+}
+
+#[derive(Debug)]
+pub enum GotoTargets {}
+
+impl Request for GotoTargets {
+    type Arguments = GotoTargetsArguments;
+    type Result = GotoTargetsResponse;
+    const COMMAND: &'static str = "gotoTargets";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GotoArguments {
+    pub thread_id: ThreadId,
+    pub target_id: usize,
+}
+
+#[derive(Debug)]
+pub enum Goto {}
+
+impl Request for Goto {
+    type Arguments = GotoArguments;
+    type Result = ();
+    const COMMAND: &'static str = "goto";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryArguments {
+    pub memory_reference: String,
+    /// The offset of the first byte from the reference, which can be negative
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    pub count: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMemoryResponse {
+    /// The address of the first byte which was read
+    pub address: String,
+    /// The number of bytes after the ones which were read that couldn't be
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unreadable_bytes: Option<usize>,
+    /// The bytes which were read, encoded as base64
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ReadMemory {}
+
+impl Request for ReadMemory {
+    type Arguments = ReadMemoryArguments;
+    type Result = ReadMemoryResponse;
+    const COMMAND: &'static str = "readMemory";
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteMemoryArguments {
+    pub memory_reference: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_partial: Option<bool>,
+    /// The bytes to write, encoded as base64
+    pub data: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteMemoryResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_written: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum WriteMemory {}
+
+impl Request for WriteMemory {
+    type Arguments = WriteMemoryArguments;
+    type Result = WriteMemoryResponse;
+    const COMMAND: &'static str = "writeMemory";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let content =
+            "# comment\n\nexport PATH=/bin\nNAME = \"a b\"\nQUOTE='c'\n=x\nnone\n";
+        assert_eq!(
+            parse_env_file(content),
+            vec![
+                ("PATH".to_string(), "/bin".to_string()),
+                ("NAME".to_string(), "a b".to_string()),
+                ("QUOTE".to_string(), "c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_continue_single_thread() {
+        let args = |single_thread| {
+            serde_json::to_string(&ContinueArguments {
+                thread_id: ThreadId(3),
+                single_thread,
+            })
+            .unwrap()
+        };
+        assert_eq!(args(false), r#"{"threadId":3}"#);
+        assert_eq!(args(true), r#"{"threadId":3,"singleThread":true}"#);
+    }
+
+    #[test]
+    fn test_step_back() {
+        let args = StepBackArguments {
+            thread_id: ThreadId(3),
+            single_thread: false,
+        };
+        assert_eq!(serde_json::to_string(&args).unwrap(), r#"{"threadId":3}"#);
+        assert_eq!(StepBack::COMMAND, "stepBack");
+        assert_eq!(ReverseContinue::COMMAND, "reverseContinue");
+    }
+
+    #[test]
+    fn test_merge_capabilities() {
+        let mut capabilities = DebuggerCapabilities {
+            supports_step_back: Some(false),
+            supports_set_variable: Some(true),
+            ..Default::default()
+        };
+        let event: DapEvent = serde_json::from_str(
+            r#"{"event":"capabilities","body":{"capabilities":{"supportsStepBack":true}}}"#,
+        )
+        .unwrap();
+        let DapEvent::Capabilities(changed) = event else {
+            panic!("not a capabilities event");
+        };
+        capabilities.merge(&changed.capabilities);
+        assert_eq!(capabilities.supports_step_back, Some(true));
+        assert_eq!(capabilities.supports_set_variable, Some(true));
+        assert_eq!(capabilities.supports_restart_frame, None);
+    }
+
+    #[test]
+    fn test_request_timeouts() {
+        let mut timeouts = DapRequestTimeouts::default();
+        assert_eq!(timeouts.timeout("threads"), Some(Duration::from_secs(30)));
+
+        timeouts.commands.insert("evaluate".to_string(), 120);
+        timeouts.commands.insert("launch".to_string(), 0);
+        assert_eq!(timeouts.timeout("evaluate"), Some(Duration::from_secs(120)));
+        assert_eq!(timeouts.timeout("launch"), None);
+
+        timeouts.default = 0;
+        assert_eq!(timeouts.timeout("threads"), None);
+        assert_eq!(timeouts.timeout("evaluate"), Some(Duration::from_secs(120)));
+    }
+}
@@ -47,10 +47,34 @@ pub fn from_path(path: PathBuf, is_dir: bool) -> PathObject {
     }
 }
 
+/// A file or directory which was moved to the trash, with where it is in the
+/// trash so that it can be restored
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrashedPath {
+    pub path: PathBuf,
+    pub trash_path: PathBuf,
+    /// The `.trashinfo` file which the trash keeps about it
+    pub info_path: PathBuf,
+}
+
+/// Where the index of the files of the workspace which the proxy keeps is at
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FileIndexStatus {
+    /// The workspace is being walked, and has this many files so far
+    Indexing { files: usize },
+    /// The index has all the files of the workspace, which the file watcher keeps
+    /// it up to date with
+    Ready { files: usize },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FileNodeItem {
     pub path_buf: PathBuf,
+    /// Whether it's a directory, or a symlink to one
     pub is_dir: bool,
+    /// What the symlink points to, as it's written in the link
+    #[serde(default)]
+    pub link_target: Option<PathBuf>,
     pub read: bool,
     pub open: bool,
     pub children: HashMap<PathBuf, FileNodeItem>,
@@ -111,6 +135,7 @@ pub fn sorted_children_mut(&mut self) -> Vec<&mut FileNodeItem> {
     ///     path_buf: PathBuf::from("/pre/fix"),
     ///     // ...
     /// #    is_dir: true,
+    /// #    link_target: None,
     /// #    read: false,
     /// #    open: false,
     /// #    children: HashMap::new(),
@@ -164,6 +189,7 @@ pub fn add_child(&mut self, path: &Path, is_dir: bool) -> Option<()> {
             FileNodeItem {
                 path_buf: PathBuf::from(path),
                 is_dir,
+                link_target: None,
                 read: false,
                 open: false,
                 children: HashMap::new(),
@@ -0,0 +1,463 @@
+//! The requests of `.http` and `.rest` files, which the proxy sends, and the
+//! responses which come back.
+//!
+//! The requests of a file are separated by lines starting with `###`. Each one
+//! is a request line like `POST https://example.com HTTP/1.1`, the headers and
+//! then the body after a blank line. Lines like `@host = example.com` define
+//! the variables which `{{host}}` is replaced with.
+
+use std::{collections::HashMap, ops::Range, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::style::{LineStyle, Style};
+
+const METHODS: [&str; 9] = [
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+];
+
+pub fn is_http_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("http" | "rest")
+    )
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    /// The line of the request line, which starts at 0
+    pub line: usize,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    /// The request with the variables replaced in its url, headers and body.
+    pub fn substitute(&self, variables: &HashMap<String, String>) -> Self {
+        Self {
+            line: self.line,
+            method: self.method.clone(),
+            url: substitute_variables(&self.url, variables),
+            headers: self
+                .headers
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        substitute_variables(name, variables),
+                        substitute_variables(value, variables),
+                    )
+                })
+                .collect(),
+            body: self
+                .body
+                .as_ref()
+                .map(|body| substitute_variables(body, variables)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HttpFile {
+    /// The variables which the file defines
+    pub variables: HashMap<String, String>,
+    pub requests: Vec<HttpRequest>,
+}
+
+impl HttpFile {
+    /// The request whose block has the line.
+    pub fn request_at(&self, line: usize) -> Option<&HttpRequest> {
+        self.requests
+            .iter()
+            .take_while(|request| request.line <= line)
+            .last()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    /// The body, which is formatted when it's json
+    pub body: String,
+    /// How long it took until the whole body came in
+    pub duration_ms: u64,
+}
+
+/// What a line of the file is, by the lines before it in its block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HttpLine {
+    Blank,
+    Separator,
+    Comment,
+    Variable,
+    Request,
+    Header,
+    Body,
+}
+
+fn http_lines<'a>(
+    lines: impl Iterator<Item = &'a str>,
+) -> impl Iterator<Item = (&'a str, HttpLine)> {
+    enum State {
+        Start,
+        Headers,
+        Body,
+    }
+    let mut state = State::Start;
+    lines.map(move |line| {
+        let trimmed = line.trim();
+        if trimmed.starts_with("###") {
+            state = State::Start;
+            return (line, HttpLine::Separator);
+        }
+        let is_comment = trimmed.starts_with('#') || trimmed.starts_with("//");
+        let kind = match state {
+            State::Start if trimmed.is_empty() => HttpLine::Blank,
+            State::Start if is_comment => HttpLine::Comment,
+            State::Start if variable_definition(line).is_some() => {
+                HttpLine::Variable
+            }
+            State::Start => {
+                state = State::Headers;
+                HttpLine::Request
+            }
+            State::Headers if trimmed.is_empty() => {
+                state = State::Body;
+                HttpLine::Blank
+            }
+            State::Headers if is_comment => HttpLine::Comment,
+            State::Headers => HttpLine::Header,
+            State::Body => HttpLine::Body,
+        };
+        (line, kind)
+    })
+}
+
+/// The name and the value of a line like `@name = value`.
+fn variable_definition(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.trim().strip_prefix('@')?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((name, value.trim()))
+}
+
+/// The method, the url and the http version of a request line, where a line
+/// without a method is a `GET`.
+fn request_line(line: &str) -> (Option<&str>, &str, Option<&str>) {
+    let line = line.trim();
+    let (method, target) = match line.split_once(char::is_whitespace) {
+        Some((method, target)) if METHODS.contains(&method) => {
+            (Some(method), target.trim())
+        }
+        _ => (None, line),
+    };
+    match target.rsplit_once(char::is_whitespace) {
+        Some((url, version)) if version.starts_with("HTTP/") => {
+            (method, url.trim(), Some(version))
+        }
+        _ => (method, target, None),
+    }
+}
+
+/// Add the request to the file with the lines of its body, without the blank
+/// ones at the end.
+fn finish_request(
+    file: &mut HttpFile,
+    request: Option<HttpRequest>,
+    body: &mut Vec<&str>,
+) {
+    if let Some(mut request) = request {
+        while body.last().map(|l| l.trim().is_empty()).unwrap_or(false) {
+            body.pop();
+        }
+        if !body.is_empty() {
+            request.body = Some(body.join("\n"));
+        }
+        file.requests.push(request);
+    }
+    body.clear();
+}
+
+pub fn parse_http_file(text: &str) -> HttpFile {
+    let mut file = HttpFile::default();
+    let mut request: Option<HttpRequest> = None;
+    let mut body = Vec::new();
+
+    for (i, (line, kind)) in http_lines(text.lines()).enumerate() {
+        match kind {
+            HttpLine::Separator => {
+                finish_request(&mut file, request.take(), &mut body);
+            }
+            HttpLine::Variable => {
+                if let Some((name, value)) = variable_definition(line) {
+                    file.variables.insert(name.to_string(), value.to_string());
+                }
+            }
+            HttpLine::Request => {
+                let (method, url, _) = request_line(line);
+                request = Some(HttpRequest {
+                    line: i,
+                    method: method.unwrap_or("GET").to_string(),
+                    url: url.to_string(),
+                    headers: Vec::new(),
+                    body: None,
+                });
+            }
+            HttpLine::Header => {
+                if let (Some(request), Some((name, value))) =
+                    (request.as_mut(), line.split_once(':'))
+                {
+                    request
+                        .headers
+                        .push((name.trim().to_string(), value.trim().to_string()));
+                }
+            }
+            HttpLine::Body => body.push(line),
+            HttpLine::Blank | HttpLine::Comment => {}
+        }
+    }
+    finish_request(&mut file, request, &mut body);
+
+    file
+}
+
+/// Replace the `{{name}}` of the text with the values of the variables, where
+/// the ones which aren't known are left as they are.
+pub fn substitute_variables(
+    text: &str,
+    variables: &HashMap<String, String>,
+) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end + 2) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        match variables.get(rest[start + 2..end - 2].trim()) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The variables of the file along with the ones of the environment, where the
+/// ones of the file win. The values can use the other variables.
+pub fn resolve_variables(
+    file: &HashMap<String, String>,
+    environment: HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut variables = environment;
+    variables.extend(file.clone());
+    for _ in 0..variables.len() {
+        let resolved = variables
+            .iter()
+            .map(|(name, value)| {
+                (name.clone(), substitute_variables(value, &variables))
+            })
+            .collect::<HashMap<_, _>>();
+        if resolved == variables {
+            break;
+        }
+        variables = resolved;
+    }
+    variables
+}
+
+/// The body with json pretty printed.
+pub fn format_body(content_type: Option<&str>, body: String) -> String {
+    if !content_type.map(|c| c.contains("json")).unwrap_or(false) {
+        return body;
+    }
+    serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or(body)
+}
+
+/// The syntax styles of the file, which has no tree-sitter grammar, by what
+/// each line is in its block.
+pub fn http_styles(text: &str) -> Vec<LineStyle> {
+    let mut styles = Vec::new();
+    let mut offset = 0;
+    for (line, kind) in http_lines(text.split_inclusive('\n')) {
+        let range = |part: &str| {
+            let start = offset + (part.as_ptr() as usize - line.as_ptr() as usize);
+            start..start + part.len()
+        };
+        match kind {
+            HttpLine::Separator | HttpLine::Comment => {
+                push_style(&mut styles, range(line.trim()), "comment");
+            }
+            HttpLine::Variable => {
+                if let Some((name, value)) = variable_definition(line) {
+                    let at = range(line.trim_start()).start;
+                    push_style(&mut styles, at..range(name).end, "variable");
+                    push_with_variables(&mut styles, range(value), value, "string");
+                }
+            }
+            HttpLine::Request => {
+                let (method, url, version) = request_line(line);
+                if let Some(method) = method {
+                    push_style(&mut styles, range(method), "keyword");
+                }
+                push_with_variables(&mut styles, range(url), url, "text.uri");
+                if let Some(version) = version {
+                    push_style(&mut styles, range(version), "constant");
+                }
+            }
+            HttpLine::Header => {
+                if let Some((name, value)) = line.trim_end().split_once(':') {
+                    let name = name.trim();
+                    push_with_variables(&mut styles, range(name), name, "property");
+                    let value = value.trim();
+                    push_with_variables(&mut styles, range(value), value, "string");
+                }
+            }
+            HttpLine::Body => {
+                push_with_variables(&mut styles, range(line), line, "");
+            }
+            HttpLine::Blank => {}
+        }
+        offset += line.len();
+    }
+    styles
+}
+
+fn push_style(styles: &mut Vec<LineStyle>, range: Range<usize>, style: &str) {
+    if range.is_empty() || style.is_empty() {
+        return;
+    }
+    styles.push(LineStyle {
+        start: range.start,
+        end: range.end,
+        style: Style {
+            fg_color: Some(style.to_string()),
+        },
+    });
+}
+
+/// Push the style of the text, where its `{{name}}`s are variables.
+fn push_with_variables(
+    styles: &mut Vec<LineStyle>,
+    range: Range<usize>,
+    text: &str,
+    style: &str,
+) {
+    let mut start = 0;
+    while let Some(open) = text[start..].find("{{").map(|open| start + open) {
+        let Some(close) = text[open..].find("}}").map(|close| open + close + 2)
+        else {
+            break;
+        };
+        push_style(styles, range.start + start..range.start + open, style);
+        push_style(styles, range.start + open..range.start + close, "variable");
+        start = close;
+    }
+    push_style(styles, range.start + start..range.end, style);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "@host = example.com
+@url = https://{{host}}/api
+
+# The users
+GET {{url}}/users HTTP/1.1
+Accept: application/json
+
+###
+
+POST {{url}}/users
+Content-Type: application/json
+// Not a header
+
+{
+  \"name\": \"{{name}}\"
+}
+
+";
+
+    #[test]
+    fn test_parse_http_file() {
+        let file = parse_http_file(FILE);
+        assert_eq!(file.variables["host"], "example.com");
+        assert_eq!(file.requests.len(), 2);
+
+        let get = &file.requests[0];
+        assert_eq!(get.line, 4);
+        assert_eq!(get.method, "GET");
+        assert_eq!(get.url, "{{url}}/users");
+        assert_eq!(
+            get.headers,
+            [("Accept".to_string(), "application/json".to_string())]
+        );
+        assert_eq!(get.body, None);
+
+        let post = &file.requests[1];
+        assert_eq!(post.line, 9);
+        assert_eq!(post.headers.len(), 1);
+        assert_eq!(post.body.as_deref(), Some("{\n  \"name\": \"{{name}}\"\n}"));
+
+        assert_eq!(file.request_at(3), None);
+        assert_eq!(file.request_at(6).map(|r| r.line), Some(4));
+        assert_eq!(file.request_at(12).map(|r| r.line), Some(9));
+    }
+
+    #[test]
+    fn test_resolve_variables() {
+        let file = parse_http_file(FILE);
+        let environment =
+            HashMap::from([("host".to_string(), "localhost".to_string())]);
+        let variables = resolve_variables(&file.variables, environment);
+        assert_eq!(variables["url"], "https://example.com/api");
+
+        let request = file.requests[1].substitute(&variables);
+        assert_eq!(request.url, "https://example.com/api/users");
+        // An unknown variable stays
+        assert_eq!(
+            request.body.as_deref(),
+            Some("{\n  \"name\": \"{{name}}\"\n}")
+        );
+    }
+
+    #[test]
+    fn test_http_styles() {
+        let text = "POST {{url}}/a HTTP/1.1\nAccept: */*\n";
+        let styles = http_styles(text)
+            .into_iter()
+            .map(|s| (&text[s.start..s.end], s.style.fg_color.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            styles,
+            [
+                ("POST", "keyword".to_string()),
+                ("{{url}}", "variable".to_string()),
+                ("/a", "text.uri".to_string()),
+                ("HTTP/1.1", "constant".to_string()),
+                ("Accept", "property".to_string()),
+                ("*/*", "string".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_body() {
+        assert_eq!(
+            format_body(Some("application/json"), "{\"a\":1}".to_string()),
+            "{\n  \"a\": 1\n}"
+        );
+        assert_eq!(format_body(None, "{\"a\":1}".to_string()), "{\"a\":1}");
+    }
+}
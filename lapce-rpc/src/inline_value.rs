@@ -0,0 +1,128 @@
+//! The inline values of the debugger which a language server gives for the
+//! lines of a file, from the version 3.17 of the protocol which the lsp types
+//! that are used don't have yet.
+
+use lsp_types::{
+    request::Request, Range, TextDocumentIdentifier, WorkDoneProgressParams,
+};
+use serde::{Deserialize, Serialize};
+
+pub enum InlineValueRequest {}
+
+impl Request for InlineValueRequest {
+    type Params = InlineValueParams;
+    type Result = Option<Vec<InlineValue>>;
+    const METHOD: &'static str = "textDocument/inlineValue";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineValueParams {
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    pub text_document: TextDocumentIdentifier,
+    /// The lines of the file which the values are for
+    pub range: Range,
+    pub context: InlineValueContext,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineValueContext {
+    /// The frame of the debugger which the values are evaluated in
+    pub frame_id: i32,
+    /// Where the debuggee stopped, which the values are up to
+    pub stopped_location: Range,
+}
+
+/// A value which is shown as it is.
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+pub struct InlineValueText {
+    pub range: Range,
+    pub text: String,
+}
+
+/// A variable which is looked up in the variables of the frame, by its name or
+/// else by the text of the range.
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineValueVariableLookup {
+    pub range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_name: Option<String>,
+    pub case_sensitive_lookup: bool,
+}
+
+/// An expression which is evaluated in the frame, which is the text of the
+/// range when it isn't given.
+#[derive(Debug, Eq, PartialEq, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineValueEvaluatableExpression {
+    pub range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum InlineValue {
+    Text(InlineValueText),
+    VariableLookup(InlineValueVariableLookup),
+    EvaluatableExpression(InlineValueEvaluatableExpression),
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_inline_values() {
+        let values: Vec<InlineValue> = serde_json::from_value(json!([
+            {
+                "range": {
+                    "start": { "line": 0, "character": 0 },
+                    "end": { "line": 0, "character": 4 }
+                },
+                "text": "one"
+            },
+            {
+                "range": {
+                    "start": { "line": 1, "character": 0 },
+                    "end": { "line": 1, "character": 4 }
+                },
+                "variableName": "a",
+                "caseSensitiveLookup": true
+            },
+            {
+                "range": {
+                    "start": { "line": 2, "character": 0 },
+                    "end": { "line": 2, "character": 4 }
+                }
+            }
+        ]))
+        .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                InlineValue::Text(InlineValueText {
+                    range: Range::new(Position::new(0, 0), Position::new(0, 4)),
+                    text: "one".to_string(),
+                }),
+                InlineValue::VariableLookup(InlineValueVariableLookup {
+                    range: Range::new(Position::new(1, 0), Position::new(1, 4)),
+                    variable_name: Some("a".to_string()),
+                    case_sensitive_lookup: true,
+                }),
+                InlineValue::EvaluatableExpression(
+                    InlineValueEvaluatableExpression {
+                        range: Range::new(Position::new(2, 0), Position::new(2, 4)),
+                        expression: None,
+                    }
+                ),
+            ]
+        );
+    }
+}
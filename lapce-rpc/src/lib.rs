@@ -3,11 +3,17 @@
 pub mod buffer;
 pub mod core;
 pub mod counter;
+pub mod coverage;
+pub mod crash;
 pub mod dap_types;
 pub mod file;
+pub mod http;
+pub mod inline_value;
 mod parse;
 pub mod plugin;
+pub mod protocol;
 pub mod proxy;
+pub mod runnable;
 pub mod source_control;
 pub mod stdio;
 pub mod style;
@@ -30,3 +36,11 @@ pub struct RpcError {
     pub code: i64,
     pub message: String,
 }
+
+impl RpcError {
+    /// The code of the error when a file can't be written for lack of permission
+    pub const PERMISSION_DENIED: i64 = 13;
+    /// The code of the error when a message can't be read, as the peer is of
+    /// another version
+    pub const INVALID_MESSAGE: i64 = -32600;
+}
@@ -140,6 +140,71 @@ fn from(volt: &VoltInfo) -> Self {
     }
 }
 
+/// Which side of the status bar an item is placed on.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusItemAlignment {
+    Left,
+    #[default]
+    Right,
+}
+
+/// The params of `lapce/setStatusItem`, which a plugin sends to show or update an
+/// item in the status bar.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusItemParams {
+    /// The id of the item, which is unique within the plugin
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub alignment: StatusItemAlignment,
+    /// Items with a higher priority are placed further to the left
+    #[serde(default)]
+    pub priority: i32,
+    /// The name of a workbench command which is run when the item is clicked,
+    /// e.g. `open_settings`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// The params of `lapce/removeStatusItem`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveStatusItemParams {
+    pub id: String,
+}
+
+/// A test which a plugin found, which is run with its command and passes when
+/// the command exits with 0.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestItemParams {
+    /// The id of the test, which is unique within the plugin
+    pub id: String,
+    pub label: String,
+    pub path: PathBuf,
+    /// The line of the test, which starts at 0
+    #[serde(default)]
+    pub line: Option<u32>,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
+}
+
+/// The params of `lapce/setTests`, which replace the tests of the plugin
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTestsParams {
+    pub tests: Vec<TestItemParams>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::{VoltID, VoltInfo, VoltMetadata};
@@ -0,0 +1,308 @@
+//! The version of the protocol between the app and the proxy, along with the
+//! features which each side knows, so that an app and a proxy of different
+//! releases leave out the messages which the other can't read.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::CoreNotification,
+    proxy::{ProxyNotification, ProxyRequest},
+};
+
+/// The version of the protocol, which goes up when a message changes in a way
+/// that an older peer can't read
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A feature which came after the first versioned protocol, and which is only
+/// used when the peer knows it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    FileIndex,
+    SaveAsAdmin,
+    RestorePath,
+    LogLevels,
+    CrashReports,
+    StatusItems,
+    PluginServerStopped,
+    GitRemoteFile,
+    Runnables,
+    Tests,
+    Coverage,
+    HttpClient,
+    DebugVariables,
+    DebugConsole,
+    DataBreakpoints,
+    LaunchConfigs,
+    SetVariable,
+    SourceActions,
+    RestartFrame,
+    GotoTargets,
+    ReadMemory,
+    ResourceLimits,
+    DebugTasks,
+    ThreadControl,
+    InlineValues,
+    ReverseDebugging,
+    DebugSessionEnd,
+    CancelTests,
+    DebugSessionStart,
+    TaskTerminals,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 30] = [
+        Capability::FileIndex,
+        Capability::SaveAsAdmin,
+        Capability::RestorePath,
+        Capability::LogLevels,
+        Capability::CrashReports,
+        Capability::StatusItems,
+        Capability::PluginServerStopped,
+        Capability::GitRemoteFile,
+        Capability::Runnables,
+        Capability::Tests,
+        Capability::Coverage,
+        Capability::HttpClient,
+        Capability::DebugVariables,
+        Capability::DebugConsole,
+        Capability::DataBreakpoints,
+        Capability::LaunchConfigs,
+        Capability::SetVariable,
+        Capability::SourceActions,
+        Capability::RestartFrame,
+        Capability::GotoTargets,
+        Capability::ReadMemory,
+        Capability::ResourceLimits,
+        Capability::DebugTasks,
+        Capability::ThreadControl,
+        Capability::InlineValues,
+        Capability::ReverseDebugging,
+        Capability::DebugSessionEnd,
+        Capability::CancelTests,
+        Capability::DebugSessionStart,
+        Capability::TaskTerminals,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::FileIndex => "file_index",
+            Capability::SaveAsAdmin => "save_as_admin",
+            Capability::RestorePath => "restore_path",
+            Capability::LogLevels => "log_levels",
+            Capability::CrashReports => "crash_reports",
+            Capability::StatusItems => "status_items",
+            Capability::PluginServerStopped => "plugin_server_stopped",
+            Capability::GitRemoteFile => "git_remote_file",
+            Capability::Runnables => "runnables",
+            Capability::Tests => "tests",
+            Capability::Coverage => "coverage",
+            Capability::HttpClient => "http_client",
+            Capability::DebugVariables => "debug_variables",
+            Capability::DebugConsole => "debug_console",
+            Capability::DataBreakpoints => "data_breakpoints",
+            Capability::LaunchConfigs => "launch_configs",
+            Capability::SetVariable => "set_variable",
+            Capability::SourceActions => "source_actions",
+            Capability::RestartFrame => "restart_frame",
+            Capability::GotoTargets => "goto_targets",
+            Capability::ReadMemory => "read_memory",
+            Capability::ResourceLimits => "resource_limits",
+            Capability::DebugTasks => "debug_tasks",
+            Capability::ThreadControl => "thread_control",
+            Capability::InlineValues => "inline_values",
+            Capability::ReverseDebugging => "reverse_debugging",
+            Capability::DebugSessionEnd => "debug_session_end",
+            Capability::CancelTests => "cancel_tests",
+            Capability::DebugSessionStart => "debug_session_start",
+            Capability::TaskTerminals => "task_terminals",
+        }
+    }
+}
+
+/// What a side of the protocol knows. The capabilities are kept as names, so
+/// that the ones of a newer peer which this side doesn't know can still be read.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    pub version: u32,
+    pub capabilities: Vec<String>,
+}
+
+impl ProtocolInfo {
+    /// What this build knows.
+    pub fn current() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            capabilities: Capability::ALL
+                .iter()
+                .map(|capability| capability.as_str().to_string())
+                .collect(),
+        }
+    }
+
+    /// A peer from before the protocol was versioned, which knows none of the
+    /// capabilities.
+    pub fn legacy() -> Self {
+        Self {
+            version: 0,
+            capabilities: Vec::new(),
+        }
+    }
+
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities
+            .iter()
+            .any(|name| name == capability.as_str())
+    }
+
+    /// Whether a message which needs the capability can be sent to the peer.
+    pub fn allows(&self, capability: Option<Capability>) -> bool {
+        capability.map(|c| self.supports(c)).unwrap_or(true)
+    }
+}
+
+impl ProxyRequest {
+    /// The capability which the proxy needs to know the request.
+    pub fn capability(&self) -> Option<Capability> {
+        match self {
+            ProxyRequest::GetFileIndexStatus {} => Some(Capability::FileIndex),
+            ProxyRequest::SaveAsAdmin { .. } => Some(Capability::SaveAsAdmin),
+            ProxyRequest::RestorePath { .. } | ProxyRequest::DeletePath { .. } => {
+                Some(Capability::RestorePath)
+            }
+            ProxyRequest::GitGetRemoteFile { .. } => Some(Capability::GitRemoteFile),
+            ProxyRequest::GetRunnables { .. }
+            | ProxyRequest::BuildRunnable { .. } => Some(Capability::Runnables),
+            ProxyRequest::RunTest { .. } => Some(Capability::Tests),
+            ProxyRequest::GetLaunchConfigs { .. } => Some(Capability::LaunchConfigs),
+            ProxyRequest::LoadCoverage { .. } => Some(Capability::Coverage),
+            ProxyRequest::GetInlineValues { .. } => Some(Capability::InlineValues),
+            ProxyRequest::SendHttpRequest { .. } => Some(Capability::HttpClient),
+            ProxyRequest::DapScopes { .. } | ProxyRequest::DapVariables { .. } => {
+                Some(Capability::DebugVariables)
+            }
+            ProxyRequest::DapEvaluate { .. } => Some(Capability::DebugConsole),
+            ProxyRequest::DapDataBreakpointInfo { .. } => {
+                Some(Capability::DataBreakpoints)
+            }
+            ProxyRequest::DapSetVariable { .. }
+            | ProxyRequest::DapSetExpression { .. } => Some(Capability::SetVariable),
+            ProxyRequest::GetSourceActions { .. } => Some(Capability::SourceActions),
+            ProxyRequest::DapRestartFrame { .. } => Some(Capability::RestartFrame),
+            ProxyRequest::DapGotoTargets { .. } | ProxyRequest::DapGoto { .. } => {
+                Some(Capability::GotoTargets)
+            }
+            ProxyRequest::DapReadMemory { .. }
+            | ProxyRequest::DapWriteMemory { .. } => Some(Capability::ReadMemory),
+            _ => None,
+        }
+    }
+}
+
+impl ProxyNotification {
+    /// The capability which the proxy needs to know the notification.
+    pub fn capability(&self) -> Option<Capability> {
+        match self {
+            ProxyNotification::RequeryFileIndex {} => Some(Capability::FileIndex),
+            ProxyNotification::UpdateLogLevels { .. } => Some(Capability::LogLevels),
+            ProxyNotification::UnwatchCoverage {} => Some(Capability::Coverage),
+            ProxyNotification::CancelTests {} => Some(Capability::CancelTests),
+            ProxyNotification::CancelTask { .. } => Some(Capability::TaskTerminals),
+            ProxyNotification::UpdateResourceLimits { .. } => {
+                Some(Capability::ResourceLimits)
+            }
+            ProxyNotification::DapSetDataBreakpoints { .. } => {
+                Some(Capability::DataBreakpoints)
+            }
+            ProxyNotification::DapContinueThread { .. }
+            | ProxyNotification::DapTerminateThreads { .. } => {
+                Some(Capability::ThreadControl)
+            }
+            ProxyNotification::DapReverseContinue { .. }
+            | ProxyNotification::DapStepBack { .. } => {
+                Some(Capability::ReverseDebugging)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl CoreNotification {
+    /// The capability which the app needs to know the notification.
+    pub fn capability(&self) -> Option<Capability> {
+        match self {
+            CoreNotification::SubsystemCrashed { .. } => {
+                Some(Capability::CrashReports)
+            }
+            CoreNotification::SetStatusItem { .. }
+            | CoreNotification::RemoveStatusItem { .. } => {
+                Some(Capability::StatusItems)
+            }
+            CoreNotification::PluginServerStopped { .. } => {
+                Some(Capability::PluginServerStopped)
+            }
+            CoreNotification::SetTests { .. } => Some(Capability::Tests),
+            CoreNotification::CoverageChanged { .. } => Some(Capability::Coverage),
+            CoreNotification::DapOutput { .. } => Some(Capability::DebugConsole),
+            CoreNotification::DapDataBreakpointsResp { .. } => {
+                Some(Capability::DataBreakpoints)
+            }
+            CoreNotification::DapTaskFailed { .. } => Some(Capability::DebugTasks),
+            CoreNotification::DapThreads { .. } => Some(Capability::ThreadControl),
+            CoreNotification::DapTerminated { .. } => {
+                Some(Capability::DebugSessionEnd)
+            }
+            CoreNotification::DapSessionStarted { .. } => {
+                Some(Capability::DebugSessionStart)
+            }
+            CoreNotification::TaskStarted { .. }
+            | CoreNotification::TaskOutput { .. }
+            | CoreNotification::TaskStopped { .. } => {
+                Some(Capability::TaskTerminals)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dap_types::DapId;
+
+    #[test]
+    fn test_unknown_capabilities() {
+        let info: ProtocolInfo = serde_json::from_str(
+            r#"{"version":2,"capabilities":["file_index","from_the_future"]}"#,
+        )
+        .unwrap();
+        assert!(info.supports(Capability::FileIndex));
+        assert!(!info.supports(Capability::LogLevels));
+
+        let legacy = ProtocolInfo::legacy();
+        assert!(!legacy.allows(ProxyNotification::RequeryFileIndex {}.capability()));
+        assert!(legacy.allows(ProxyNotification::GitInit {}.capability()));
+    }
+
+    #[test]
+    fn test_data_breakpoints_capability() {
+        let info = ProxyRequest::DapDataBreakpointInfo {
+            dap_id: DapId::next(),
+            reference: 1,
+            name: "count".to_string(),
+        };
+        let set = ProxyNotification::DapSetDataBreakpoints {
+            dap_id: DapId::next(),
+            breakpoints: Vec::new(),
+        };
+        assert_eq!(info.capability(), Some(Capability::DataBreakpoints));
+        assert_eq!(set.capability(), Some(Capability::DataBreakpoints));
+
+        // A proxy from before data breakpoints isn't asked for them
+        let legacy = ProtocolInfo::legacy();
+        assert!(!legacy.allows(info.capability()));
+        assert!(!legacy.allows(set.capability()));
+        let current = ProtocolInfo::current();
+        assert!(current.allows(info.capability()));
+        assert!(current.allows(set.capability()));
+    }
+}
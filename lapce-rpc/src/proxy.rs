@@ -11,21 +11,31 @@
 use indexmap::IndexMap;
 use lapce_xi_rope::RopeDelta;
 use lsp_types::{
-    request::GotoTypeDefinitionResponse, CodeAction, CodeActionResponse,
-    CompletionItem, Diagnostic, DocumentSymbolResponse, GotoDefinitionResponse,
-    Hover, InlayHint, Location, Position, PrepareRenameResponse, SelectionRange,
-    SymbolInformation, TextDocumentItem, TextEdit, WorkspaceEdit,
+    request::GotoTypeDefinitionResponse, CodeAction, CodeActionKind,
+    CodeActionResponse, CompletionItem, Diagnostic, DocumentSymbolResponse,
+    GotoDefinitionResponse, Hover, InlayHint, Location, Position,
+    PrepareRenameResponse, Range, SelectionRange, SymbolInformation,
+    TextDocumentItem, TextEdit, WorkspaceEdit,
 };
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
 use super::plugin::VoltID;
 use crate::{
-    buffer::BufferId,
-    dap_types::{DapId, RunDebugConfig, SourceBreakpoint, ThreadId},
-    file::{FileNodeItem, PathObject},
+    buffer::{BufferId, SaveOptions},
+    coverage::Coverage,
+    dap_types::{
+        DapId, DapRequestTimeouts, DataBreakpoint, DataBreakpointInfoResponse,
+        EvaluateResponse, GotoTarget, RunDebugConfig, Scope, SetExpressionResponse,
+        SetVariableResponse, SourceBreakpoint, TaskId, ThreadId, Variable,
+    },
+    file::{FileIndexStatus, FileNodeItem, PathObject, TrashedPath},
+    http::HttpResponse,
+    inline_value::InlineValue,
     plugin::{PluginId, VoltInfo, VoltMetadata},
-    source_control::FileDiff,
+    protocol::ProtocolInfo,
+    runnable::Runnable,
+    source_control::{FileDiff, GitRemoteFile},
     style::SemanticStyles,
     terminal::TermId,
     RequestId, RpcError, RpcMessage,
@@ -46,6 +56,24 @@ pub struct SearchMatch {
     pub line_content: String,
 }
 
+/// The limits of what the proxy and the servers which it starts use, for a
+/// machine which is shared with others. A limit which is 0 is no limit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// The niceness which the language servers and the debug adapters are
+    /// started with
+    pub nice: i32,
+    /// The memory of each server, in MB, which it's given in a cgroup
+    pub memory_max: u64,
+    /// The cpu time of each server, in percent of a core, which it's given in
+    /// a cgroup
+    pub cpu_quota: u32,
+    /// The folders which the file watcher watches at most
+    pub max_watches: usize,
+    /// The searches of the workspace which run at the same time at most
+    pub search_threads: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "method", content = "params")]
@@ -63,6 +91,12 @@ pub enum ProxyRequest {
         whole_word: bool,
         is_regex: bool,
     },
+    /// Search the files of the workspace, or the ones of them which are given,
+    /// for the comments which start with one of the tags, like `// TODO: ...`
+    TodoSearch {
+        tags: Vec<String>,
+        paths: Option<Vec<PathBuf>>,
+    },
     CompletionResolve {
         plugin_id: PluginId,
         completion_item: Box<CompletionItem>,
@@ -87,6 +121,109 @@ pub enum ProxyRequest {
     GitGetRemoteFileUrl {
         file: PathBuf,
     },
+    GitGetRemoteFile {
+        path: PathBuf,
+    },
+    GetRunnables {
+        path: PathBuf,
+    },
+    /// Build the runnable for the config which debugs its binary
+    BuildRunnable {
+        runnable: Runnable,
+    },
+    /// Run the config of a test, and give back whether it passed along with
+    /// its output
+    RunTest {
+        config: RunDebugConfig,
+    },
+    /// The debug configs of `.lapce/launch.toml` or `.lapce/launch.json`, whose
+    /// variables like `${file}` are substituted for the active file
+    GetLaunchConfigs {
+        active_file: Option<PathBuf>,
+    },
+    /// Load the coverage report, or the first of the usual ones in the
+    /// workspace without it, and watch it for changes if asked to
+    LoadCoverage {
+        report: Option<PathBuf>,
+        watch: bool,
+    },
+    /// Send the request of the `.http` file whose block has the line, with the
+    /// variables of the environment when there's one
+    SendHttpRequest {
+        path: PathBuf,
+        text: String,
+        line: usize,
+        environment: Option<String>,
+    },
+    DapScopes {
+        dap_id: DapId,
+        frame_id: usize,
+    },
+    /// The children of the scope or the structured variable of the reference
+    DapVariables {
+        dap_id: DapId,
+        reference: usize,
+    },
+    /// Evaluate the expression of the debug console, in the frame when it's set
+    DapEvaluate {
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+    },
+    /// Whether the variable of the name, in the container of the reference, can
+    /// be broken on when its value changes
+    DapDataBreakpointInfo {
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+    },
+    /// Set the value of the variable of the name, in the container of the
+    /// reference
+    DapSetVariable {
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+    },
+    /// Assign the value to the expression, in the frame when it's set
+    DapSetExpression {
+        dap_id: DapId,
+        expression: String,
+        value: String,
+        frame_id: Option<usize>,
+    },
+    /// Run the frame again from its start, which stops the session there
+    DapRestartFrame {
+        dap_id: DapId,
+        frame_id: usize,
+    },
+    /// Get where the execution can be moved to at the line of the file, which
+    /// is counted from 1
+    DapGotoTargets {
+        dap_id: DapId,
+        path: PathBuf,
+        line: usize,
+    },
+    /// Move the execution of the thread to the target, without running the
+    /// code in between
+    DapGoto {
+        dap_id: DapId,
+        thread_id: ThreadId,
+        target_id: usize,
+    },
+    /// Read the bytes of the memory at the offset from the reference
+    DapReadMemory {
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+    },
+    DapWriteMemory {
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        data: Vec<u8>,
+    },
     GetReferences {
         path: PathBuf,
         position: Position,
@@ -104,6 +241,14 @@ pub enum ProxyRequest {
     GetInlayHints {
         path: PathBuf,
     },
+    /// The values which the language server shows for the lines of the range
+    /// while the debugger is stopped in the frame at the location
+    GetInlineValues {
+        path: PathBuf,
+        range: Range,
+        frame_id: usize,
+        stopped_location: Range,
+    },
     GetSemanticTokens {
         path: PathBuf,
     },
@@ -121,6 +266,14 @@ pub enum ProxyRequest {
         position: Position,
         diagnostics: Vec<Diagnostic>,
     },
+    /// Get the code actions of the kinds for the range of the file, like the
+    /// ones which organize its imports
+    GetSourceActions {
+        path: PathBuf,
+        range: Range,
+        kinds: Vec<CodeActionKind>,
+        diagnostics: Vec<Diagnostic>,
+    },
     GetDocumentSymbols {
         path: PathBuf,
     },
@@ -135,12 +288,20 @@ pub enum ProxyRequest {
     GetFiles {
         path: String,
     },
+    GetFileIndexStatus {},
     ReadDir {
         path: PathBuf,
     },
     Save {
         rev: u64,
         path: PathBuf,
+        options: SaveOptions,
+    },
+    /// Save the buffer with the privileges of an administrator, for a file which
+    /// can't be written otherwise
+    SaveAsAdmin {
+        rev: u64,
+        path: PathBuf,
     },
     SaveBufferAs {
         buffer_id: BufferId,
@@ -157,6 +318,14 @@ pub enum ProxyRequest {
     TrashPath {
         path: PathBuf,
     },
+    /// Move a path which was trashed back to where it was
+    RestorePath {
+        trashed: TrashedPath,
+    },
+    /// Delete a path for good, without moving it to the trash
+    DeletePath {
+        path: PathBuf,
+    },
     DuplicatePath {
         existing_path: PathBuf,
         new_path: PathBuf,
@@ -176,6 +345,12 @@ pub enum ProxyNotification {
         plugin_configurations: HashMap<String, HashMap<String, serde_json::Value>>,
         window_id: usize,
         tab_id: usize,
+        /// What the app knows of the protocol, which an app from before it was
+        /// versioned doesn't send
+        #[serde(default = "ProtocolInfo::legacy")]
+        protocol: ProtocolInfo,
+        #[serde(default)]
+        resource_limits: ResourceLimits,
     },
     OpenFileChanged {
         path: PathBuf,
@@ -183,6 +358,9 @@ pub enum ProxyNotification {
     OpenPaths {
         paths: Vec<PathObject>,
     },
+    /// Walk the workspace again for the file index, for when the file watcher
+    /// missed changes
+    RequeryFileIndex {},
     Shutdown {},
     Completion {
         request_id: usize,
@@ -203,6 +381,18 @@ pub enum ProxyNotification {
     UpdatePluginConfigs {
         configs: HashMap<String, HashMap<String, serde_json::Value>>,
     },
+    /// The log levels of the modules of the proxy, like `warn,lapce_proxy::plugin=debug`
+    UpdateLogLevels {
+        directives: String,
+    },
+    /// Stop watching the coverage report
+    UnwatchCoverage {},
+    /// Kill the tests which are running
+    CancelTests {},
+    /// Kill the task, whose terminal was closed
+    CancelTask {
+        task_id: TaskId,
+    },
     NewTerminal {
         term_id: TermId,
         cwd: Option<PathBuf>,
@@ -261,10 +451,30 @@ pub enum ProxyNotification {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    /// Resume only the thread, for a debugger which supports single thread
+    /// execution requests
+    DapContinueThread {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
     DapPause {
         dap_id: DapId,
         thread_id: ThreadId,
     },
+    /// Run backwards until a breakpoint is hit, for a debugger which supports
+    /// stepping back
+    DapReverseContinue {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapStepBack {
+        dap_id: DapId,
+        thread_id: ThreadId,
+    },
+    DapTerminateThreads {
+        dap_id: DapId,
+        thread_ids: Vec<ThreadId>,
+    },
     DapStop {
         dap_id: DapId,
     },
@@ -280,6 +490,34 @@ pub enum ProxyNotification {
         path: PathBuf,
         breakpoints: Vec<SourceBreakpoint>,
     },
+    /// The watch expressions of the workspace, which are evaluated each time a
+    /// debuggee stops
+    DapSetWatches {
+        expressions: Vec<String>,
+    },
+    /// Evaluate the watch expressions again, in another frame
+    DapEvaluateWatches {
+        dap_id: DapId,
+        frame_id: usize,
+    },
+    /// Whether to break on the exception filters, by their ids, where the
+    /// filters which aren't in it are as the debugger defaults them
+    DapSetExceptionFilters {
+        filters: HashMap<String, bool>,
+    },
+    /// The limits for the servers which are started and the searches from now on
+    UpdateResourceLimits {
+        limits: ResourceLimits,
+    },
+    /// How long the requests to the debuggers are waited for
+    DapSetRequestTimeouts {
+        timeouts: DapRequestTimeouts,
+    },
+    /// The data breakpoints of the session, which replace the ones set before
+    DapSetDataBreakpoints {
+        dap_id: DapId,
+        breakpoints: Vec<DataBreakpoint>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -289,8 +527,66 @@ pub enum ProxyResponse {
     GitGetRemoteFileUrl {
         file_url: String,
     },
+    GitGetRemoteFile {
+        file: GitRemoteFile,
+    },
+    GetRunnables {
+        runnables: Vec<Runnable>,
+    },
+    BuildRunnable {
+        config: RunDebugConfig,
+    },
+    RunTest {
+        success: bool,
+        output: String,
+    },
+    GetLaunchConfigs {
+        configs: Vec<RunDebugConfig>,
+    },
+    LoadCoverage {
+        report: PathBuf,
+        coverage: Coverage,
+    },
+    SendHttpRequest {
+        response: HttpResponse,
+    },
+    DapScopes {
+        scopes: Vec<Scope>,
+    },
+    DapVariables {
+        variables: Vec<Variable>,
+    },
+    DapEvaluate {
+        result: EvaluateResponse,
+    },
+    DapDataBreakpointInfo {
+        info: DataBreakpointInfoResponse,
+    },
+    DapSetVariable {
+        result: SetVariableResponse,
+    },
+    DapSetExpression {
+        result: SetExpressionResponse,
+    },
+    DapRestartFrame {},
+    DapGotoTargets {
+        targets: Vec<GotoTarget>,
+    },
+    DapGoto {},
+    DapReadMemory {
+        /// The address of the first of the bytes
+        address: String,
+        data: Vec<u8>,
+        /// The number of bytes after the data which couldn't be read
+        unreadable_bytes: usize,
+    },
+    DapWriteMemory {
+        bytes_written: Option<usize>,
+    },
     NewBufferResponse {
         content: String,
+        /// Whether the file can't be written for lack of permission
+        read_only: bool,
     },
     BufferHeadResponse {
         version: String,
@@ -327,6 +623,9 @@ pub enum ProxyResponse {
     GetFilesResponse {
         items: Vec<PathBuf>,
     },
+    GetFileIndexStatusResponse {
+        status: FileIndexStatus,
+    },
     GetDocumentFormatting {
         edits: Vec<TextEdit>,
     },
@@ -342,6 +641,9 @@ pub enum ProxyResponse {
     GetInlayHints {
         hints: Vec<InlayHint>,
     },
+    GetInlineValues {
+        values: Vec<InlineValue>,
+    },
     GetSemanticTokens {
         styles: SemanticStyles,
     },
@@ -357,6 +659,10 @@ pub enum ProxyResponse {
     GlobalSearchResponse {
         matches: IndexMap<PathBuf, Vec<SearchMatch>>,
     },
+    TrashPathResponse {
+        /// Where the path is in the trash, when the trash can restore it
+        trashed: Option<TrashedPath>,
+    },
     Success {},
     SaveResponse {},
 }
@@ -522,6 +828,7 @@ pub fn initialize(
         plugin_configurations: HashMap<String, HashMap<String, serde_json::Value>>,
         window_id: usize,
         tab_id: usize,
+        resource_limits: ResourceLimits,
     ) {
         self.notification(ProxyNotification::Initialize {
             workspace,
@@ -529,6 +836,8 @@ pub fn initialize(
             plugin_configurations,
             window_id,
             tab_id,
+            protocol: ProtocolInfo::current(),
+            resource_limits,
         });
     }
 
@@ -616,6 +925,18 @@ pub fn trash_path(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
         self.request_async(ProxyRequest::TrashPath { path }, f);
     }
 
+    pub fn restore_path(
+        &self,
+        trashed: TrashedPath,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::RestorePath { trashed }, f);
+    }
+
+    pub fn delete_path(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::DeletePath { path }, f);
+    }
+
     pub fn duplicate_path(
         &self,
         existing_path: PathBuf,
@@ -678,8 +999,32 @@ pub fn global_search(
         );
     }
 
-    pub fn save(&self, rev: u64, path: PathBuf, f: impl ProxyCallback + 'static) {
-        self.request_async(ProxyRequest::Save { rev, path }, f);
+    pub fn todo_search(
+        &self,
+        tags: Vec<String>,
+        paths: Option<Vec<PathBuf>>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::TodoSearch { tags, paths }, f);
+    }
+
+    pub fn save(
+        &self,
+        rev: u64,
+        path: PathBuf,
+        options: SaveOptions,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::Save { rev, path, options }, f);
+    }
+
+    pub fn save_as_admin(
+        &self,
+        rev: u64,
+        path: PathBuf,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::SaveAsAdmin { rev, path }, f);
     }
 
     pub fn get_files(&self, f: impl ProxyCallback + 'static) {
@@ -691,6 +1036,14 @@ pub fn get_files(&self, f: impl ProxyCallback + 'static) {
         );
     }
 
+    pub fn get_file_index_status(&self, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::GetFileIndexStatus {}, f);
+    }
+
+    pub fn requery_file_index(&self) {
+        self.notification(ProxyNotification::RequeryFileIndex {});
+    }
+
     pub fn get_open_files_content(&self) -> Result<ProxyResponse, RpcError> {
         self.request(ProxyRequest::GetOpenFilesContent {})
     }
@@ -806,6 +1159,25 @@ pub fn get_code_actions(
         );
     }
 
+    pub fn get_source_actions(
+        &self,
+        path: PathBuf,
+        range: Range,
+        kinds: Vec<CodeActionKind>,
+        diagnostics: Vec<Diagnostic>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetSourceActions {
+                path,
+                range,
+                kinds,
+                diagnostics,
+            },
+            f,
+        );
+    }
+
     pub fn get_document_formatting(
         &self,
         path: PathBuf,
@@ -855,6 +1227,101 @@ pub fn git_get_remote_file_url(
         self.request_async(ProxyRequest::GitGetRemoteFileUrl { file }, f);
     }
 
+    pub fn git_get_remote_file(
+        &self,
+        path: PathBuf,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GitGetRemoteFile { path }, f);
+    }
+
+    pub fn get_runnables(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::GetRunnables { path }, f);
+    }
+
+    pub fn build_runnable(
+        &self,
+        runnable: Runnable,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::BuildRunnable { runnable }, f);
+    }
+
+    pub fn run_test(&self, config: RunDebugConfig, f: impl ProxyCallback + 'static) {
+        self.request_async(ProxyRequest::RunTest { config }, f);
+    }
+
+    pub fn get_launch_configs(
+        &self,
+        active_file: Option<PathBuf>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::GetLaunchConfigs { active_file }, f);
+    }
+
+    pub fn load_coverage(
+        &self,
+        report: Option<PathBuf>,
+        watch: bool,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::LoadCoverage { report, watch }, f);
+    }
+
+    pub fn send_http_request(
+        &self,
+        path: PathBuf,
+        text: String,
+        line: usize,
+        environment: Option<String>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::SendHttpRequest {
+                path,
+                text,
+                line,
+                environment,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_scopes(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::DapScopes { dap_id, frame_id }, f);
+    }
+
+    pub fn dap_variables(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::DapVariables { dap_id, reference }, f);
+    }
+
+    pub fn dap_evaluate(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        frame_id: Option<usize>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapEvaluate {
+                dap_id,
+                expression,
+                frame_id,
+            },
+            f,
+        );
+    }
+
     pub fn rename(
         &self,
         path: PathBuf,
@@ -876,6 +1343,25 @@ pub fn get_inlay_hints(&self, path: PathBuf, f: impl ProxyCallback + 'static) {
         self.request_async(ProxyRequest::GetInlayHints { path }, f);
     }
 
+    pub fn get_inline_values(
+        &self,
+        path: PathBuf,
+        range: Range,
+        frame_id: usize,
+        stopped_location: Range,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::GetInlineValues {
+                path,
+                range,
+                frame_id,
+                stopped_location,
+            },
+            f,
+        );
+    }
+
     pub fn update(&self, path: PathBuf, delta: RopeDelta, rev: u64) {
         self.notification(ProxyNotification::Update { path, delta, rev });
     }
@@ -887,6 +1373,26 @@ pub fn update_plugin_configs(
         self.notification(ProxyNotification::UpdatePluginConfigs { configs });
     }
 
+    pub fn update_log_levels(&self, directives: String) {
+        self.notification(ProxyNotification::UpdateLogLevels { directives });
+    }
+
+    pub fn update_resource_limits(&self, limits: ResourceLimits) {
+        self.notification(ProxyNotification::UpdateResourceLimits { limits });
+    }
+
+    pub fn unwatch_coverage(&self) {
+        self.notification(ProxyNotification::UnwatchCoverage {});
+    }
+
+    pub fn cancel_tests(&self) {
+        self.notification(ProxyNotification::CancelTests {});
+    }
+
+    pub fn cancel_task(&self, task_id: TaskId) {
+        self.notification(ProxyNotification::CancelTask { task_id });
+    }
+
     pub fn git_discard_files_changes(&self, files: Vec<PathBuf>) {
         self.notification(ProxyNotification::GitDiscardFilesChanges { files });
     }
@@ -943,10 +1449,32 @@ pub fn dap_continue(&self, dap_id: DapId, thread_id: ThreadId) {
         self.notification(ProxyNotification::DapContinue { dap_id, thread_id })
     }
 
+    pub fn dap_continue_thread(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapContinueThread { dap_id, thread_id })
+    }
+
     pub fn dap_pause(&self, dap_id: DapId, thread_id: ThreadId) {
         self.notification(ProxyNotification::DapPause { dap_id, thread_id })
     }
 
+    pub fn dap_reverse_continue(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapReverseContinue {
+            dap_id,
+            thread_id,
+        })
+    }
+
+    pub fn dap_step_back(&self, dap_id: DapId, thread_id: ThreadId) {
+        self.notification(ProxyNotification::DapStepBack { dap_id, thread_id })
+    }
+
+    pub fn dap_terminate_threads(&self, dap_id: DapId, thread_ids: Vec<ThreadId>) {
+        self.notification(ProxyNotification::DapTerminateThreads {
+            dap_id,
+            thread_ids,
+        })
+    }
+
     pub fn dap_stop(&self, dap_id: DapId) {
         self.notification(ProxyNotification::DapStop { dap_id })
     }
@@ -967,6 +1495,162 @@ pub fn dap_set_breakpoints(
             breakpoints,
         })
     }
+
+    pub fn dap_set_watches(&self, expressions: Vec<String>) {
+        self.notification(ProxyNotification::DapSetWatches { expressions })
+    }
+
+    pub fn dap_evaluate_watches(&self, dap_id: DapId, frame_id: usize) {
+        self.notification(ProxyNotification::DapEvaluateWatches { dap_id, frame_id })
+    }
+
+    pub fn dap_set_exception_filters(&self, filters: HashMap<String, bool>) {
+        self.notification(ProxyNotification::DapSetExceptionFilters { filters })
+    }
+
+    pub fn dap_set_request_timeouts(&self, timeouts: DapRequestTimeouts) {
+        self.notification(ProxyNotification::DapSetRequestTimeouts { timeouts })
+    }
+
+    pub fn dap_data_breakpoint_info(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapDataBreakpointInfo {
+                dap_id,
+                reference,
+                name,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_set_variable(
+        &self,
+        dap_id: DapId,
+        reference: usize,
+        name: String,
+        value: String,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapSetVariable {
+                dap_id,
+                reference,
+                name,
+                value,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_restart_frame(
+        &self,
+        dap_id: DapId,
+        frame_id: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::DapRestartFrame { dap_id, frame_id }, f);
+    }
+
+    pub fn dap_goto_targets(
+        &self,
+        dap_id: DapId,
+        path: PathBuf,
+        line: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(ProxyRequest::DapGotoTargets { dap_id, path, line }, f);
+    }
+
+    pub fn dap_goto(
+        &self,
+        dap_id: DapId,
+        thread_id: ThreadId,
+        target_id: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapGoto {
+                dap_id,
+                thread_id,
+                target_id,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_read_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        count: usize,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapReadMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                count,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_write_memory(
+        &self,
+        dap_id: DapId,
+        memory_reference: String,
+        offset: i64,
+        data: Vec<u8>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapWriteMemory {
+                dap_id,
+                memory_reference,
+                offset,
+                data,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_set_expression(
+        &self,
+        dap_id: DapId,
+        expression: String,
+        value: String,
+        frame_id: Option<usize>,
+        f: impl ProxyCallback + 'static,
+    ) {
+        self.request_async(
+            ProxyRequest::DapSetExpression {
+                dap_id,
+                expression,
+                value,
+                frame_id,
+            },
+            f,
+        );
+    }
+
+    pub fn dap_set_data_breakpoints(
+        &self,
+        dap_id: DapId,
+        breakpoints: Vec<DataBreakpoint>,
+    ) {
+        self.notification(ProxyNotification::DapSetDataBreakpoints {
+            dap_id,
+            breakpoints,
+        })
+    }
 }
 
 impl Default for ProxyRpcHandler {
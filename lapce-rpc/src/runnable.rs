@@ -0,0 +1,119 @@
+//! The runnables of rust-analyzer, which are the binaries, tests and benches of
+//! a file that its `experimental/runnables` request gives, along with the cargo
+//! commands which run them.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use lsp_types::{request::Request, LocationLink, Position, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::dap_types::RunDebugConfig;
+
+/// The kind of the runnables which are run with cargo
+pub const CARGO_RUNNABLE: &str = "cargo";
+
+pub enum Runnables {}
+
+impl Request for Runnables {
+    type Params = RunnablesParams;
+    type Result = Vec<Runnable>;
+    const METHOD: &'static str = "experimental/runnables";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnablesParams {
+    pub text_document: TextDocumentIdentifier,
+    /// The position which the runnables are around, or all of the file without
+    /// it
+    pub position: Option<Position>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Runnable {
+    pub label: String,
+    pub location: Option<LocationLink>,
+    /// Only the `cargo` ones are known, and the fields of the args of the other
+    /// kinds are left empty
+    pub kind: String,
+    pub args: CargoRunnable,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CargoRunnable {
+    pub workspace_root: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    /// The command which is run instead of `cargo`
+    pub override_cargo: Option<String>,
+    pub cargo_args: Vec<String>,
+    pub cargo_extra_args: Vec<String>,
+    /// The arguments of the binary or the test, which go after `--`
+    pub executable_args: Vec<String>,
+    /// Whether it's a test of the `expect_test` crate, which updates the
+    /// expectations when it's run with `UPDATE_EXPECT`
+    pub expect_test: Option<bool>,
+    pub environment: Option<HashMap<String, String>>,
+}
+
+impl Runnable {
+    /// The line of the item which is run, such as the name of the test.
+    pub fn line(&self) -> Option<u32> {
+        self.location
+            .as_ref()
+            .map(|location| location.target_selection_range.start.line)
+    }
+
+    /// The folder which cargo is run in.
+    pub fn cwd(&self) -> Option<&PathBuf> {
+        self.args.cwd.as_ref().or(self.args.workspace_root.as_ref())
+    }
+
+    /// The environment of the run, with what the test needs.
+    pub fn env(&self) -> Option<HashMap<String, String>> {
+        let mut env = self.args.environment.clone();
+        if self.args.expect_test == Some(true) {
+            env.get_or_insert_with(HashMap::new)
+                .insert("UPDATE_EXPECT".to_string(), "1".to_string());
+        }
+        env
+    }
+
+    /// The config which runs the runnable with cargo in the terminal. It's
+    /// built first when it's debugged, which the proxy does.
+    pub fn run_config(&self) -> RunDebugConfig {
+        let mut args = self.args.cargo_args.clone();
+        args.extend(self.args.cargo_extra_args.iter().cloned());
+        if !self.args.executable_args.is_empty() {
+            args.push("--".to_string());
+            args.extend(self.args.executable_args.iter().cloned());
+        }
+        RunDebugConfig {
+            name: self.label.clone(),
+            program: self
+                .args
+                .override_cargo
+                .clone()
+                .unwrap_or_else(|| "cargo".to_string()),
+            args,
+            cwd: self.cwd().map(|cwd| cwd.to_string_lossy().to_string()),
+            env: self.env(),
+            env_file: None,
+            pre_launch_task: None,
+            post_debug_task: None,
+            mode: Default::default(),
+            pid: None,
+            port: None,
+            host: None,
+            adapter: None,
+            debug_command: None,
+            dap_id: Default::default(),
+            parent: None,
+            configuration: None,
+            no_debug: false,
+            runnable: Some(self.clone()),
+            properties: Default::default(),
+        }
+    }
+}
@@ -28,3 +28,17 @@ pub fn path(&self) -> &PathBuf {
         }
     }
 }
+
+/// A file of a git repository as it is on the remote of the branch, at the
+/// commit which is checked out.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GitRemoteFile {
+    /// The host of the remote, such as `github.com`
+    pub host: String,
+    /// The path of the repository on the host, such as `lapce/lapce`, which
+    /// can have more parts for the groups of GitLab
+    pub repo: String,
+    pub commit: String,
+    /// The path of the file in the repository, with `/` between its parts
+    pub path: String,
+}
@@ -1,17 +1,19 @@
 use std::{
     io::{self, BufRead, Write},
+    sync::Arc,
     thread,
 };
 
 use anyhow::Result;
 use crossbeam_channel::{Receiver, Sender};
+use parking_lot::Mutex;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{json, Value};
 
 use crate::{RpcError, RpcMessage, RpcObject};
 
 pub fn stdio_transport<W, R, Req1, Notif1, Resp1, Req2, Notif2, Resp2>(
-    mut writer: W,
+    writer: W,
     writer_receiver: Receiver<RpcMessage<Req2, Notif2, Resp2>>,
     mut reader: R,
     reader_sender: Sender<RpcMessage<Req1, Notif1, Resp1>>,
@@ -25,17 +27,56 @@ pub fn stdio_transport<W, R, Req1, Notif1, Resp1, Req2, Notif2, Resp2>(
     Notif2: 'static + Serialize + DeserializeOwned + Send + Sync,
     Resp2: 'static + Serialize + DeserializeOwned + Send + Sync,
 {
-    thread::spawn(move || {
-        for value in writer_receiver {
-            if write_msg(&mut writer, value).is_err() {
-                return;
-            };
-        }
-    });
+    let writer = Arc::new(Mutex::new(writer));
+    {
+        let writer = writer.clone();
+        thread::spawn(move || {
+            for value in writer_receiver {
+                if write_msg(&mut *writer.lock(), value).is_err() {
+                    return;
+                };
+            }
+        });
+    }
     thread::spawn(move || -> Result<()> {
         loop {
-            let msg = read_msg(&mut reader)?;
-            reader_sender.send(msg)?;
+            let object = match read_object(&mut reader) {
+                Ok(object) => object,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    tracing::warn!("skipped a line which isn't a message: {e}");
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let id = object.get_id();
+            let is_response = object.is_response();
+            match parse_msg(object) {
+                Ok(msg) => reader_sender.send(msg)?,
+                // A message of a peer of another version which this side can't
+                // read. The one who waits for an answer to it gets an error
+                // instead, and a notification is left out.
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    let err = RpcError {
+                        code: RpcError::INVALID_MESSAGE,
+                        message: format!("the message can't be read: {e}"),
+                    };
+                    match id {
+                        Some(id) if is_response => {
+                            reader_sender.send(RpcMessage::Error(id, err))?
+                        }
+                        Some(id) => write_msg(
+                            &mut *writer.lock(),
+                            RpcMessage::<Req2, Notif2, Resp2>::Error(id, err),
+                        )?,
+                        None => {
+                            tracing::warn!(
+                                "skipped a notification which can't be read: {e}"
+                            );
+                        }
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
         }
     });
 }
@@ -87,10 +128,24 @@ pub fn read_msg<R, Req, Notif, Resp>(
     Notif: DeserializeOwned,
     Resp: DeserializeOwned,
 {
+    parse_msg(read_object(inp)?)
+}
+
+fn read_object<R: BufRead>(inp: &mut R) -> io::Result<RpcObject> {
     let mut buf = String::new();
     let _s = inp.read_line(&mut buf)?;
     let value: Value = serde_json::from_str(&buf)?;
-    let object = RpcObject(value);
+    Ok(RpcObject(value))
+}
+
+fn parse_msg<Req, Notif, Resp>(
+    object: RpcObject,
+) -> io::Result<RpcMessage<Req, Notif, Resp>>
+where
+    Req: DeserializeOwned,
+    Notif: DeserializeOwned,
+    Resp: DeserializeOwned,
+{
     let is_response = object.is_response();
     let msg = if is_response {
         let id = object.get_id().ok_or(io::ErrorKind::NotFound)?;
@@ -121,3 +176,88 @@ pub fn read_msg<R, Req, Notif, Resp>(
     };
     Ok(msg)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(tag = "method", content = "params", rename_all = "snake_case")]
+    enum Message {
+        Ping { count: u64 },
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Pong {
+        count: u64,
+    }
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_unreadable_messages() {
+        let input = [
+            r#"{"id":1,"method":"from_the_future","params":{}}"#,
+            r#"{"method":"from_the_future","params":{}}"#,
+            r#"{"id":2,"result":{"count":"two"}}"#,
+            r#"not a message"#,
+            r#"{"method":"ping","params":{"count":3}}"#,
+        ]
+        .join("\n")
+            + "\n";
+        let output = Output::default();
+        let (_writer_tx, writer_rx) =
+            crossbeam_channel::unbounded::<RpcMessage<Message, Message, Pong>>();
+        let (reader_tx, reader_rx) =
+            crossbeam_channel::unbounded::<RpcMessage<Message, Message, Pong>>();
+        stdio_transport(
+            output.clone(),
+            writer_rx,
+            io::Cursor::new(input.into_bytes()),
+            reader_tx,
+        );
+
+        // The request which waits for the response which can't be read fails
+        match reader_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+            RpcMessage::Error(id, err) => {
+                assert_eq!(id, 2);
+                assert_eq!(err.code, RpcError::INVALID_MESSAGE);
+            }
+            msg => panic!("unexpected message {msg:?}"),
+        }
+        match reader_rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+            RpcMessage::Notification(Message::Ping { count }) => {
+                assert_eq!(count, 3)
+            }
+            msg => panic!("unexpected message {msg:?}"),
+        }
+        assert!(reader_rx.recv_timeout(Duration::from_secs(5)).is_err());
+
+        // The peer gets an error for its request and nothing for its
+        // notification
+        let output = String::from_utf8(output.0.lock().clone()).unwrap();
+        let replies: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["error"]["code"], RpcError::INVALID_MESSAGE);
+    }
+}